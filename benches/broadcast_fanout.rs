@@ -0,0 +1,196 @@
+use axum::extract::ws::{Message, Utf8Bytes};
+use bytes::Bytes;
+use criterion::{criterion_group, criterion_main, Criterion};
+use signal_fish_server::broadcast::Outbound;
+use signal_fish_server::protocol::ServerMessage;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+const ROOM_SIZE: usize = 64;
+
+/// Counts heap allocations so both fan-out paths can be compared per broadcast.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(new_size, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn game_data() -> ServerMessage {
+    ServerMessage::GameData {
+        from_player: uuid::Uuid::new_v4(),
+        data: serde_json::json!({
+            "tick": 1024,
+            "position": { "x": 12.5, "y": -3.25, "z": 0.0 },
+            "velocity": { "x": 1.0, "y": 0.0, "z": -0.5 },
+            "inputs": ["jump", "fire"],
+        }),
+    }
+}
+
+/// Previous path: the message is queued per player and every send loop
+/// serializes its own copy.
+fn fan_out_through_queues(
+    message: &Arc<ServerMessage>,
+    queues: &mut [(mpsc::Sender<Outbound>, mpsc::Receiver<Outbound>)],
+) {
+    for (sender, _) in queues.iter() {
+        sender.try_send(Arc::clone(message).into()).unwrap();
+    }
+    for (_, receiver) in queues.iter_mut() {
+        let message = receiver.try_recv().unwrap();
+        let frame = Message::Text(serde_json::to_string(&*message).unwrap().into());
+        black_box(frame);
+    }
+}
+
+/// Room frames: serialized once, and the same bytes are queued for every
+/// player, whose send loop writes them as is.
+fn fan_out_as_frames(
+    message: &Arc<ServerMessage>,
+    queues: &mut [(mpsc::Sender<Outbound>, mpsc::Receiver<Outbound>)],
+) {
+    let frame: Arc<[u8]> = Arc::from(serde_json::to_vec(&**message).unwrap());
+    for (sender, _) in queues.iter() {
+        let outbound = Outbound::RoomFrame {
+            message: Arc::clone(message),
+            frame: Arc::clone(&frame),
+        };
+        sender.try_send(outbound).unwrap();
+    }
+    for (_, receiver) in queues.iter_mut() {
+        black_box(text_frame(receiver.try_recv().unwrap()));
+    }
+}
+
+fn text_frame(outbound: Outbound) -> Message {
+    let Outbound::RoomFrame { frame, .. } = outbound else {
+        unreachable!("only room frames are queued");
+    };
+    Message::Text(Utf8Bytes::try_from(Bytes::from_owner(frame)).unwrap())
+}
+
+/// Previous path for a room mixing JSON and MessagePack connections: every
+/// send loop encodes its own copy in its connection's encoding.
+fn fan_out_mixed_through_queues(
+    message: &Arc<ServerMessage>,
+    queues: &mut [(mpsc::Sender<Outbound>, mpsc::Receiver<Outbound>)],
+) {
+    for (sender, _) in queues.iter() {
+        sender.try_send(Arc::clone(message).into()).unwrap();
+    }
+    for (index, (_, receiver)) in queues.iter_mut().enumerate() {
+        let message = receiver.try_recv().unwrap();
+        let frame = if index % 2 == 0 {
+            Message::Text(serde_json::to_string(&*message).unwrap().into())
+        } else {
+            Message::Binary(rmp_serde::to_vec_named(&*message).unwrap().into())
+        };
+        black_box(frame);
+    }
+}
+
+/// Room frames per encoding: serialized once per encoding in the room.
+fn fan_out_mixed_as_frames(
+    message: &Arc<ServerMessage>,
+    queues: &mut [(mpsc::Sender<Outbound>, mpsc::Receiver<Outbound>)],
+) {
+    let json: Arc<[u8]> = Arc::from(serde_json::to_vec(&**message).unwrap());
+    let msgpack: Arc<[u8]> = Arc::from(rmp_serde::to_vec_named(&**message).unwrap());
+    for (index, (sender, _)) in queues.iter().enumerate() {
+        let frame = if index % 2 == 0 { &json } else { &msgpack };
+        let outbound = Outbound::RoomFrame {
+            message: Arc::clone(message),
+            frame: Arc::clone(frame),
+        };
+        sender.try_send(outbound).unwrap();
+    }
+    for (index, (_, receiver)) in queues.iter_mut().enumerate() {
+        let outbound = receiver.try_recv().unwrap();
+        let frame = if index % 2 == 0 {
+            text_frame(outbound)
+        } else {
+            let Outbound::RoomFrame { frame, .. } = outbound else {
+                unreachable!("only room frames are queued");
+            };
+            Message::Binary(Bytes::from_owner(frame))
+        };
+        black_box(frame);
+    }
+}
+
+/// Allocations and bytes allocated by one call of `fan_out`.
+fn measure(mut fan_out: impl FnMut()) -> (usize, usize) {
+    fan_out(); // warm up channel blocks
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let bytes = ALLOCATED_BYTES.load(Ordering::Relaxed);
+    fan_out();
+    (
+        ALLOCATIONS.load(Ordering::Relaxed) - allocations,
+        ALLOCATED_BYTES.load(Ordering::Relaxed) - bytes,
+    )
+}
+
+fn bench_broadcast_fanout(c: &mut Criterion) {
+    let message = Arc::new(game_data());
+    let mut queues: Vec<_> = (0..ROOM_SIZE).map(|_| mpsc::channel(4)).collect();
+
+    let (queue_allocations, queue_bytes) =
+        measure(|| fan_out_through_queues(&message, &mut queues));
+    let (frame_allocations, frame_bytes) = measure(|| fan_out_as_frames(&message, &mut queues));
+    println!(
+        "game_data to {ROOM_SIZE} players: queues {queue_allocations} allocations / {queue_bytes} bytes, \
+         frames {frame_allocations} allocations / {frame_bytes} bytes"
+    );
+
+    c.bench_function("game_data_fanout_queues_64", |b| {
+        b.iter(|| fan_out_through_queues(&message, &mut queues));
+    });
+
+    c.bench_function("game_data_fanout_frames_64", |b| {
+        b.iter(|| fan_out_as_frames(&message, &mut queues));
+    });
+
+    let (queue_allocations, queue_bytes) =
+        measure(|| fan_out_mixed_through_queues(&message, &mut queues));
+    let (frame_allocations, frame_bytes) =
+        measure(|| fan_out_mixed_as_frames(&message, &mut queues));
+    println!(
+        "game_data to {ROOM_SIZE} players, half JSON and half MessagePack: \
+         queues {queue_allocations} allocations / {queue_bytes} bytes, \
+         frames {frame_allocations} allocations / {frame_bytes} bytes"
+    );
+
+    c.bench_function("game_data_fanout_mixed_queues_64", |b| {
+        b.iter(|| fan_out_mixed_through_queues(&message, &mut queues));
+    });
+
+    c.bench_function("game_data_fanout_mixed_frames_64", |b| {
+        b.iter(|| fan_out_mixed_as_frames(&message, &mut queues));
+    });
+}
+
+criterion_group!(broadcast_fanout, bench_broadcast_fanout);
+criterion_main!(broadcast_fanout);
@@ -0,0 +1,66 @@
+//! Create and join latency of the file-backed database against the in-memory one.
+//!
+//! Run with `cargo bench --features persistent --bench database_backends`.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use signal_fish_server::config::{GameOverrideConfig, ProtocolConfig};
+use signal_fish_server::database::{
+    FileDatabase, GameDatabase, InMemoryDatabase, RoomExpiryPolicy,
+};
+use signal_fish_server::protocol::{PlayerInfo, PlayerRole};
+use uuid::Uuid;
+
+/// Create a two-player room and seat the second player.
+async fn create_and_join(db: &dyn GameDatabase) {
+    let room = db
+        .create_room(
+            "bench".to_string(),
+            None,
+            2,
+            true,
+            Uuid::new_v4(),
+            "relay".to_string(),
+            "us-east-1".to_string(),
+            None,
+        )
+        .await
+        .unwrap();
+    let guest = PlayerInfo {
+        id: Uuid::new_v4(),
+        name: "Guest".to_string(),
+        is_authority: false,
+        role: PlayerRole::Member,
+        is_ready: false,
+        connected_at: chrono::Utc::now(),
+        connection_info: None,
+        region_id: "us-east-1".to_string(),
+    };
+    assert!(db.add_player_to_room(&room.id, guest).await.unwrap());
+    db.delete_room(&room.id).await.unwrap();
+}
+
+fn bench_database_backends(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let expiry = RoomExpiryPolicy::uniform(chrono::Duration::hours(1), chrono::Duration::hours(1));
+
+    let in_memory = InMemoryDatabase::new();
+    let file = runtime
+        .block_on(FileDatabase::open(
+            dir.path().join("rooms.json"),
+            ProtocolConfig::default(),
+            GameOverrideConfig::new(),
+            &expiry,
+        ))
+        .unwrap();
+
+    c.bench_function("database_create_join_in_memory", |b| {
+        b.iter(|| runtime.block_on(create_and_join(&in_memory)));
+    });
+    c.bench_function("database_create_join_file", |b| {
+        b.iter(|| runtime.block_on(create_and_join(&file)));
+    });
+}
+
+criterion_group!(benches, bench_database_backends);
+criterion_main!(benches);
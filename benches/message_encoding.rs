@@ -0,0 +1,67 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use signal_fish_server::protocol::{
+    LobbyState, PlayerInfo, PlayerRole, RoomJoinedPayload, ServerMessage,
+};
+use std::hint::black_box;
+
+fn full_room_joined(player_count: usize) -> ServerMessage {
+    let players: Vec<PlayerInfo> = (0..player_count)
+        .map(|index| PlayerInfo {
+            id: uuid::Uuid::new_v4(),
+            name: format!("Player{index}"),
+            is_authority: index == 0,
+            role: PlayerRole::default(),
+            is_ready: index % 2 == 0,
+            connected_at: chrono::Utc::now(),
+            connection_info: None,
+            region_id: String::new(),
+        })
+        .collect();
+    ServerMessage::RoomJoined(Box::new(RoomJoinedPayload {
+        room_id: uuid::Uuid::new_v4(),
+        room_code: "ABC123".to_string(),
+        player_id: players[0].id,
+        player_name: players[0].name.clone(),
+        game_name: "encoding-bench".to_string(),
+        max_players: 16,
+        supports_authority: true,
+        ready_players: players
+            .iter()
+            .filter(|p| p.is_ready)
+            .map(|p| p.id)
+            .collect(),
+        current_players: players,
+        is_authority: true,
+        role: PlayerRole::default(),
+        lobby_state: LobbyState::Lobby,
+        lobby_version: 1,
+        relay_type: "matchbox".to_string(),
+        current_spectators: Vec::new(),
+        reconnection_token: None,
+        relay_session: None,
+        metadata: Default::default(),
+        peer_connection: None,
+    }))
+}
+
+fn bench_message_encoding(c: &mut Criterion) {
+    let message = full_room_joined(16);
+
+    let json_len = serde_json::to_vec(&message).unwrap().len();
+    let msgpack_len = rmp_serde::to_vec_named(&message).unwrap().len();
+    println!(
+        "room_joined (16 players): json {json_len} bytes, msgpack {msgpack_len} bytes ({:.0}% smaller)",
+        100.0 * (1.0 - msgpack_len as f64 / json_len as f64)
+    );
+
+    c.bench_function("room_joined_encode_json", |b| {
+        b.iter(|| black_box(serde_json::to_vec(black_box(&message)).unwrap()));
+    });
+
+    c.bench_function("room_joined_encode_msgpack", |b| {
+        b.iter(|| black_box(rmp_serde::to_vec_named(black_box(&message)).unwrap()));
+    });
+}
+
+criterion_group!(message_encoding, bench_message_encoding);
+criterion_main!(message_encoding);
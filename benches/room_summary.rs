@@ -0,0 +1,186 @@
+use bytes::Bytes;
+use criterion::{criterion_group, criterion_main, Criterion};
+use signal_fish_server::database::{GameDatabase, InMemoryDatabase};
+use signal_fish_server::protocol::{
+    GameDataEncoding, HistoricalMessage, PlayerInfo, PlayerRole, Room, RoomId, SpectatorInfo,
+    DEFAULT_REGION_ID,
+};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+const PLAYERS: usize = 8;
+const SPECTATORS: usize = 16;
+const HISTORY: usize = 50;
+
+/// Counts heap allocations so a full clone and a summary can be compared.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(new_size, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// A busy room: full, watched, with replay history and metadata.
+fn busy_room() -> Room {
+    let mut room = Room::new(
+        "party-game".to_string(),
+        "PARTY1".to_string(),
+        PLAYERS as u8,
+        true,
+        "matchbox".to_string(),
+    );
+    let now = chrono::Utc::now();
+    for index in 0..PLAYERS {
+        let id = uuid::Uuid::new_v4();
+        room.players.insert(
+            id,
+            PlayerInfo {
+                id,
+                name: format!("player-{index}"),
+                is_authority: index == 0,
+                role: PlayerRole::Member,
+                is_ready: false,
+                connected_at: now,
+                connection_info: None,
+                region_id: DEFAULT_REGION_ID.to_string(),
+            },
+        );
+    }
+    for index in 0..SPECTATORS {
+        let id = uuid::Uuid::new_v4();
+        room.spectators.insert(
+            id,
+            SpectatorInfo {
+                id,
+                name: format!("spectator-{index}"),
+                connected_at: now,
+            },
+        );
+    }
+    for tick in 0..HISTORY {
+        room.message_history.push_back(HistoricalMessage {
+            timestamp: now,
+            from_player: uuid::Uuid::new_v4(),
+            encoding: GameDataEncoding::Json,
+            payload: Bytes::from(format!("{{\"tick\":{tick},\"x\":12.5,\"y\":-3.25}}")),
+        });
+    }
+    room.metadata
+        .insert("map".to_string(), "harbour".to_string());
+    room.metadata.insert("mode".to_string(), "ctf".to_string());
+    room
+}
+
+/// Store a copy of `room` in `db` and return its id.
+async fn store_room(db: &InMemoryDatabase, room: &Room) -> RoomId {
+    let stored = db
+        .create_room(
+            room.game_name.clone(),
+            Some(room.code.clone()),
+            room.max_players,
+            room.supports_authority,
+            uuid::Uuid::new_v4(),
+            room.relay_type.clone(),
+            DEFAULT_REGION_ID.to_string(),
+            None,
+        )
+        .await
+        .unwrap();
+    for player in room.players.values().skip(1) {
+        assert!(db
+            .add_player_to_room(&stored.id, player.clone())
+            .await
+            .unwrap());
+    }
+    for spectator in room.spectators.values() {
+        assert!(db
+            .add_spectator_to_room(&stored.id, spectator.clone())
+            .await
+            .unwrap());
+    }
+    for message in &room.message_history {
+        db.append_room_history(&stored.id, message.clone(), HISTORY)
+            .await
+            .unwrap();
+    }
+    stored.id
+}
+
+/// Allocations and bytes allocated by one call of `view`.
+fn measure<T>(mut view: impl FnMut() -> T) -> (usize, usize) {
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let bytes = ALLOCATED_BYTES.load(Ordering::Relaxed);
+    black_box(view());
+    (
+        ALLOCATIONS.load(Ordering::Relaxed) - allocations,
+        ALLOCATED_BYTES.load(Ordering::Relaxed) - bytes,
+    )
+}
+
+fn bench_room_summary(c: &mut Criterion) {
+    let room = busy_room();
+
+    let (clone_allocations, clone_bytes) = measure(|| room.clone());
+    let (summary_allocations, summary_bytes) = measure(|| room.to_summary());
+    println!(
+        "room with {PLAYERS} players, {SPECTATORS} spectators, {HISTORY} history messages: \
+         clone {clone_allocations} allocations / {clone_bytes} bytes, \
+         summary {summary_allocations} allocations / {summary_bytes} bytes"
+    );
+
+    c.bench_function("room_clone", |b| {
+        b.iter(|| black_box(room.clone()));
+    });
+
+    c.bench_function("room_to_summary", |b| {
+        b.iter(|| black_box(room.to_summary()));
+    });
+}
+
+fn bench_database_lookup(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let db = InMemoryDatabase::new();
+    let room_id = runtime.block_on(store_room(&db, &busy_room()));
+
+    let (room_allocations, room_bytes) =
+        measure(|| runtime.block_on(db.get_room_by_id(&room_id)).unwrap());
+    let (summary_allocations, summary_bytes) =
+        measure(|| runtime.block_on(db.get_room_summary(&room_id)).unwrap());
+    println!(
+        "in-memory lookup: get_room_by_id {room_allocations} allocations / {room_bytes} bytes, \
+         get_room_summary {summary_allocations} allocations / {summary_bytes} bytes"
+    );
+
+    c.bench_function("database_get_room_by_id", |b| {
+        b.iter(|| black_box(runtime.block_on(db.get_room_by_id(&room_id)).unwrap()));
+    });
+
+    c.bench_function("database_get_room_summary", |b| {
+        b.iter(|| black_box(runtime.block_on(db.get_room_summary(&room_id)).unwrap()));
+    });
+}
+
+criterion_group!(room_summary, bench_room_summary, bench_database_lookup);
+criterion_main!(room_summary);
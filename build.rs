@@ -1,4 +1,31 @@
+use std::path::Path;
+use std::process::Command;
+
 fn main() {
-    // No build-time dependencies needed for the in-memory server.
-    // This file exists as a placeholder for future build-time codegen.
+    // Reported in metrics as the build commit. Builds without a git checkout
+    // (e.g. Docker) can pass it in through the environment instead.
+    println!("cargo:rerun-if-env-changed=SIGNAL_FISH_GIT_COMMIT");
+    for path in [".git/HEAD", ".git/refs/heads"] {
+        if Path::new(path).exists() {
+            println!("cargo:rerun-if-changed={path}");
+        }
+    }
+
+    let commit = std::env::var("SIGNAL_FISH_GIT_COMMIT")
+        .ok()
+        .filter(|commit| !commit.trim().is_empty())
+        .or_else(git_commit)
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=SIGNAL_FISH_GIT_COMMIT={}", commit.trim());
+}
+
+fn git_commit() -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--short=12", "HEAD"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout).ok()
 }
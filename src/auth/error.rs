@@ -16,6 +16,20 @@ pub enum AuthError {
     RateLimitExceeded,
     #[error("Invalid app ID")]
     InvalidAppId,
+    /// The `X-Api-Key` header did not match any configured API key.
+    #[error("Unauthorized")]
+    Unauthorized,
+    /// The WebSocket upgrade carried no `Authorization: Bearer` token.
+    #[error("Missing bearer token")]
+    MissingToken,
+    /// The bearer token was malformed, used the wrong algorithm, or failed
+    /// signature verification.
+    #[error("Invalid bearer token: {0}")]
+    InvalidToken(String),
+    /// The bearer token's `exp` claim is in the past (beyond clock skew) or its
+    /// `nbf` claim is in the future.
+    #[error("Bearer token expired or not yet valid")]
+    TokenExpired,
     /// Reserved for future use: returned when an app ID has passed its
     /// expiration date in a backend that tracks app lifecycles.
     #[error("App ID expired")]
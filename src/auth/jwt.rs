@@ -0,0 +1,883 @@
+//! JWT bearer-token verification for the WebSocket upgrade.
+//!
+//! Supports compact-serialized HS256 and RS256 tokens, the latter verified
+//! against a single PEM key or a static JWKS document. Only the configured
+//! algorithm is accepted, so `alg: none` and HS/RS confusion tokens are
+//! rejected before any signature check runs.
+
+use super::error::AuthError;
+use crate::config::{JwtAlgorithm, JwtAuthConfig};
+use anyhow::Context;
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use ring::signature::{RsaPublicKeyComponents, UnparsedPublicKey, RSA_PKCS1_2048_8192_SHA256};
+use serde::Deserialize;
+use sha2::Sha256;
+
+/// DER encoding of the `rsaEncryption` OID (1.2.840.113549.1.1.1).
+const RSA_ENCRYPTION_OID: &[u8] = &[
+    0x06, 0x09, 0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x01, 0x01,
+];
+
+/// Claims extracted from a verified token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JwtClaims {
+    /// Application identifier, taken from the `app_id` claim or, if absent, `sub`.
+    pub app_id: String,
+    /// Expiry as a Unix timestamp (seconds).
+    pub expires_at: i64,
+    /// The `sub` claim: the stable identity of the token holder.
+    pub subject: Option<String>,
+    /// Game names the token may create or join rooms for, from the
+    /// `room_scope` claim. `None` means unrestricted.
+    pub room_scope: Option<Vec<String>>,
+}
+
+#[derive(Deserialize)]
+struct JwtHeader {
+    alg: String,
+    #[serde(default)]
+    kid: Option<String>,
+}
+
+/// A claim that may be a single string or an array of strings.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OneOrMany {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl OneOrMany {
+    fn into_vec(self) -> Vec<String> {
+        match self {
+            Self::One(value) => vec![value],
+            Self::Many(values) => values,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct RawClaims {
+    #[serde(default)]
+    app_id: Option<String>,
+    #[serde(default)]
+    sub: Option<String>,
+    #[serde(default)]
+    exp: Option<i64>,
+    #[serde(default)]
+    nbf: Option<i64>,
+    #[serde(default)]
+    iss: Option<String>,
+    #[serde(default)]
+    aud: Option<OneOrMany>,
+    #[serde(default)]
+    room_scope: Option<OneOrMany>,
+}
+
+enum VerificationKey {
+    /// Shared HMAC secret.
+    Hmac(Vec<u8>),
+    /// RSA public keys; more than one only when loaded from a JWKS.
+    Rsa(Vec<RsaKey>),
+}
+
+struct RsaKey {
+    /// The JWK `kid`, matched against the token header's `kid`.
+    kid: Option<String>,
+    material: RsaKeyMaterial,
+}
+
+enum RsaKeyMaterial {
+    /// PKCS#1 `RSAPublicKey` DER.
+    Pkcs1(Vec<u8>),
+    /// Big-endian modulus and exponent from a JWK.
+    Components { n: Vec<u8>, e: Vec<u8> },
+}
+
+#[derive(Deserialize)]
+struct Jwks {
+    keys: Vec<Jwk>,
+}
+
+#[derive(Deserialize)]
+struct Jwk {
+    kty: String,
+    #[serde(default)]
+    kid: Option<String>,
+    #[serde(default, rename = "use")]
+    key_use: Option<String>,
+    #[serde(default)]
+    n: Option<String>,
+    #[serde(default)]
+    e: Option<String>,
+}
+
+/// Verifies bearer tokens against the configured key material and, when set,
+/// the expected issuer and audience.
+pub struct JwtVerifier {
+    key: VerificationKey,
+    clock_skew_secs: u64,
+    issuer: Option<String>,
+    audience: Option<String>,
+}
+
+impl JwtVerifier {
+    /// Build a verifier from the `security.transport.jwt` configuration,
+    /// loading the RS256 public key or JWKS from disk when needed.
+    pub fn from_config(config: &JwtAuthConfig) -> anyhow::Result<Self> {
+        let mut verifier = Self::keys_from_config(config)?;
+        verifier.issuer = config.issuer.clone().filter(|iss| !iss.is_empty());
+        verifier.audience = config.audience.clone().filter(|aud| !aud.is_empty());
+        Ok(verifier)
+    }
+
+    fn keys_from_config(config: &JwtAuthConfig) -> anyhow::Result<Self> {
+        match config.algorithm {
+            JwtAlgorithm::HS256 => {
+                let secret = config
+                    .secret
+                    .as_deref()
+                    .filter(|s| !s.is_empty())
+                    .context("security.transport.jwt.secret is required for HS256")?;
+                Ok(Self::hs256(
+                    secret.as_bytes(),
+                    config.clock_skew_secs.as_secs(),
+                ))
+            }
+            JwtAlgorithm::RS256 => {
+                if let Some(path) = config.jwks_path.as_deref() {
+                    let jwks = std::fs::read_to_string(path)
+                        .with_context(|| format!("failed to read JWKS at {path}"))?;
+                    return Self::rs256_from_jwks(&jwks, config.clock_skew_secs.as_secs())
+                        .with_context(|| format!("invalid JWKS at {path}"));
+                }
+                let path = config
+                    .public_key_path
+                    .as_deref()
+                    .context("security.transport.jwt.public_key_path is required for RS256")?;
+                let pem = std::fs::read_to_string(path)
+                    .with_context(|| format!("failed to read JWT public key at {path}"))?;
+                Self::rs256_from_pem(&pem, config.clock_skew_secs.as_secs())
+                    .with_context(|| format!("invalid JWT public key at {path}"))
+            }
+        }
+    }
+
+    /// Create an HS256 verifier from a shared secret.
+    pub fn hs256(secret: &[u8], clock_skew_secs: u64) -> Self {
+        Self {
+            key: VerificationKey::Hmac(secret.to_vec()),
+            clock_skew_secs,
+            issuer: None,
+            audience: None,
+        }
+    }
+
+    /// Create an RS256 verifier from a PEM `PUBLIC KEY` (SPKI) or
+    /// `RSA PUBLIC KEY` (PKCS#1) block.
+    pub fn rs256_from_pem(pem: &str, clock_skew_secs: u64) -> anyhow::Result<Self> {
+        let der = if let Some(spki) = decode_pem(pem, "PUBLIC KEY") {
+            rsa_key_from_spki(&spki?).context("not an RSA SubjectPublicKeyInfo")?
+        } else if let Some(pkcs1) = decode_pem(pem, "RSA PUBLIC KEY") {
+            pkcs1?
+        } else {
+            anyhow::bail!("expected a PEM \"PUBLIC KEY\" or \"RSA PUBLIC KEY\" block");
+        };
+        Ok(Self {
+            key: VerificationKey::Rsa(vec![RsaKey {
+                kid: None,
+                material: RsaKeyMaterial::Pkcs1(der),
+            }]),
+            clock_skew_secs,
+            issuer: None,
+            audience: None,
+        })
+    }
+
+    /// Create an RS256 verifier from a JWKS document. Non-RSA keys and keys
+    /// not meant for signatures are skipped; at least one must remain.
+    pub fn rs256_from_jwks(jwks: &str, clock_skew_secs: u64) -> anyhow::Result<Self> {
+        let jwks: Jwks = serde_json::from_str(jwks).context("JWKS is not valid JSON")?;
+        let mut keys = Vec::new();
+        for jwk in jwks.keys {
+            if jwk.kty != "RSA" || jwk.key_use.as_deref().is_some_and(|u| u != "sig") {
+                continue;
+            }
+            let (Some(n), Some(e)) = (jwk.n, jwk.e) else {
+                anyhow::bail!("RSA key {:?} is missing n or e", jwk.kid);
+            };
+            let decode = |value: &str| {
+                URL_SAFE_NO_PAD
+                    .decode(value)
+                    .with_context(|| format!("RSA key {:?} is not valid base64url", jwk.kid))
+            };
+            keys.push(RsaKey {
+                material: RsaKeyMaterial::Components {
+                    n: decode(&n)?,
+                    e: decode(&e)?,
+                },
+                kid: jwk.kid,
+            });
+        }
+        anyhow::ensure!(!keys.is_empty(), "JWKS contains no RSA signing keys");
+        Ok(Self {
+            key: VerificationKey::Rsa(keys),
+            clock_skew_secs,
+            issuer: None,
+            audience: None,
+        })
+    }
+
+    /// Require the `iss` claim to equal `issuer`.
+    pub fn with_issuer(mut self, issuer: impl Into<String>) -> Self {
+        self.issuer = Some(issuer.into());
+        self
+    }
+
+    /// Require the `aud` claim to contain `audience`.
+    pub fn with_audience(mut self, audience: impl Into<String>) -> Self {
+        self.audience = Some(audience.into());
+        self
+    }
+
+    /// The algorithm this verifier accepts.
+    pub fn algorithm(&self) -> JwtAlgorithm {
+        match self.key {
+            VerificationKey::Hmac(_) => JwtAlgorithm::HS256,
+            VerificationKey::Rsa(_) => JwtAlgorithm::RS256,
+        }
+    }
+
+    /// Verify a compact JWT against the current time.
+    pub fn verify(&self, token: &str) -> Result<JwtClaims, AuthError> {
+        self.verify_at(token, chrono::Utc::now().timestamp())
+    }
+
+    /// Verify a compact JWT as of `now` (Unix seconds).
+    pub fn verify_at(&self, token: &str, now: i64) -> Result<JwtClaims, AuthError> {
+        let mut parts = token.split('.');
+        let (Some(header_b64), Some(payload_b64), Some(signature_b64), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid("expected three dot-separated segments"));
+        };
+
+        let header: JwtHeader = decode_segment(header_b64, "header")?;
+        let expected_alg = match self.algorithm() {
+            JwtAlgorithm::HS256 => "HS256",
+            JwtAlgorithm::RS256 => "RS256",
+        };
+        if header.alg != expected_alg {
+            return Err(invalid(format!(
+                "algorithm {} is not accepted (expected {expected_alg})",
+                header.alg
+            )));
+        }
+
+        let signature = URL_SAFE_NO_PAD
+            .decode(signature_b64)
+            .map_err(|_| invalid("signature is not valid base64url"))?;
+        let signing_input = &token[..header_b64.len() + 1 + payload_b64.len()];
+        self.verify_signature(signing_input.as_bytes(), &signature, header.kid.as_deref())?;
+
+        let claims: RawClaims = decode_segment(payload_b64, "payload")?;
+        let skew = i64::try_from(self.clock_skew_secs).unwrap_or(i64::MAX);
+        let expires_at = claims.exp.ok_or_else(|| invalid("missing exp claim"))?;
+        if now > expires_at.saturating_add(skew) {
+            return Err(AuthError::TokenExpired);
+        }
+        if claims.nbf.is_some_and(|nbf| now < nbf.saturating_sub(skew)) {
+            return Err(AuthError::TokenExpired);
+        }
+
+        if let Some(issuer) = &self.issuer {
+            if claims.iss.as_ref() != Some(issuer) {
+                return Err(invalid("unexpected iss claim"));
+            }
+        }
+        if let Some(audience) = &self.audience {
+            let audiences = claims.aud.map(OneOrMany::into_vec).unwrap_or_default();
+            if !audiences.contains(audience) {
+                return Err(invalid("unexpected aud claim"));
+            }
+        }
+
+        let subject = claims.sub.filter(|sub| !sub.is_empty());
+        let app_id = claims
+            .app_id
+            .or_else(|| subject.clone())
+            .filter(|id| !id.is_empty())
+            .ok_or_else(|| invalid("missing app_id or sub claim"))?;
+
+        Ok(JwtClaims {
+            app_id,
+            expires_at,
+            subject,
+            room_scope: claims.room_scope.map(OneOrMany::into_vec),
+        })
+    }
+
+    fn verify_signature(
+        &self,
+        message: &[u8],
+        signature: &[u8],
+        kid: Option<&str>,
+    ) -> Result<(), AuthError> {
+        match &self.key {
+            VerificationKey::Hmac(secret) => {
+                let mut mac = Hmac::<Sha256>::new_from_slice(secret)
+                    .map_err(|_| invalid("HMAC key rejected"))?;
+                mac.update(message);
+                mac.verify_slice(signature)
+                    .map_err(|_| invalid("signature mismatch"))
+            }
+            VerificationKey::Rsa(keys) => {
+                // A token naming a key is only checked against that key
+                let mut candidates = keys
+                    .iter()
+                    .filter(|key| kid.is_none() || key.kid.is_none() || key.kid.as_deref() == kid)
+                    .peekable();
+                if candidates.peek().is_none() {
+                    return Err(invalid("unknown kid"));
+                }
+                let verified = candidates.any(|key| match &key.material {
+                    RsaKeyMaterial::Pkcs1(der) => {
+                        UnparsedPublicKey::new(&RSA_PKCS1_2048_8192_SHA256, der)
+                            .verify(message, signature)
+                            .is_ok()
+                    }
+                    RsaKeyMaterial::Components { n, e } => RsaPublicKeyComponents { n, e }
+                        .verify(&RSA_PKCS1_2048_8192_SHA256, message, signature)
+                        .is_ok(),
+                });
+                if verified {
+                    Ok(())
+                } else {
+                    Err(invalid("signature mismatch"))
+                }
+            }
+        }
+    }
+}
+
+fn invalid(reason: impl Into<String>) -> AuthError {
+    AuthError::InvalidToken(reason.into())
+}
+
+fn decode_segment<T: serde::de::DeserializeOwned>(
+    segment: &str,
+    name: &str,
+) -> Result<T, AuthError> {
+    let bytes = URL_SAFE_NO_PAD
+        .decode(segment)
+        .map_err(|_| invalid(format!("{name} is not valid base64url")))?;
+    serde_json::from_slice(&bytes).map_err(|_| invalid(format!("{name} is not valid JSON")))
+}
+
+/// Decode the first PEM block with the given label. Returns `None` when no
+/// such block exists.
+fn decode_pem(pem: &str, label: &str) -> Option<anyhow::Result<Vec<u8>>> {
+    let begin = format!("-----BEGIN {label}-----");
+    let end = format!("-----END {label}-----");
+    let start = pem.find(&begin)? + begin.len();
+    let Some(len) = pem[start..].find(&end) else {
+        return Some(Err(anyhow::anyhow!("unterminated PEM block")));
+    };
+    let body: String = pem[start..start + len]
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    Some(
+        STANDARD
+            .decode(body)
+            .context("PEM body is not valid base64"),
+    )
+}
+
+/// Read one DER TLV, returning `(tag, contents, remainder)`. Lengths must use
+/// the shortest encoding, as DER requires.
+fn read_der(input: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let (&tag, rest) = input.split_first()?;
+    let (&first_len, rest) = rest.split_first()?;
+    let (len, rest) = if first_len < 0x80 {
+        (usize::from(first_len), rest)
+    } else {
+        let octets = usize::from(first_len & 0x7F);
+        if octets == 0 || octets > 4 || rest.len() < octets || rest[0] == 0 {
+            return None;
+        }
+        let len = rest[..octets]
+            .iter()
+            .fold(0usize, |acc, &b| (acc << 8) | usize::from(b));
+        if len < 0x80 {
+            return None;
+        }
+        (len, &rest[octets..])
+    };
+    if rest.len() < len {
+        return None;
+    }
+    Some((tag, &rest[..len], &rest[len..]))
+}
+
+/// Extract the PKCS#1 `RSAPublicKey` from a `SubjectPublicKeyInfo`.
+fn rsa_key_from_spki(spki: &[u8]) -> Option<Vec<u8>> {
+    let (0x30, body, _) = read_der(spki)? else {
+        return None;
+    };
+    let (0x30, algorithm, rest) = read_der(body)? else {
+        return None;
+    };
+    if !algorithm.starts_with(RSA_ENCRYPTION_OID) {
+        return None;
+    }
+    let (0x03, bits, _) = read_der(rest)? else {
+        return None;
+    };
+    // First octet of a BIT STRING is the unused-bit count, always 0 for keys
+    match bits.split_first() {
+        Some((0, key)) => Some(key.to_vec()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ring::rand::SystemRandom;
+    use ring::signature::{RsaKeyPair, RSA_PKCS1_SHA256};
+
+    const NOW: i64 = 1_700_000_000;
+    const SECRET: &[u8] = b"test-signing-secret";
+    const RSA_PRIVATE_PEM: &str = include_str!("../../test-fixtures/jwt/rs256_private.pem");
+    const RSA_PUBLIC_PEM: &str = include_str!("../../test-fixtures/jwt/rs256_public.pem");
+
+    fn encode(value: &serde_json::Value) -> String {
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(value).unwrap())
+    }
+
+    fn hs256_token(claims: &serde_json::Value) -> String {
+        let signing_input = format!(
+            "{}.{}",
+            encode(&serde_json::json!({"alg": "HS256", "typ": "JWT"})),
+            encode(claims)
+        );
+        let mut mac = Hmac::<Sha256>::new_from_slice(SECRET).unwrap();
+        mac.update(signing_input.as_bytes());
+        let signature = URL_SAFE_NO_PAD.encode(mac.finalize().into_bytes());
+        format!("{signing_input}.{signature}")
+    }
+
+    fn rs256_token(claims: &serde_json::Value) -> String {
+        rs256_token_with_header(&serde_json::json!({"alg": "RS256", "typ": "JWT"}), claims)
+    }
+
+    fn rs256_token_with_header(header: &serde_json::Value, claims: &serde_json::Value) -> String {
+        let signing_input = format!("{}.{}", encode(header), encode(claims));
+        let pkcs8 = decode_pem(RSA_PRIVATE_PEM, "PRIVATE KEY").unwrap().unwrap();
+        let key_pair = RsaKeyPair::from_pkcs8(&pkcs8).unwrap();
+        let mut signature = vec![0u8; key_pair.public().modulus_len()];
+        key_pair
+            .sign(
+                &RSA_PKCS1_SHA256,
+                &SystemRandom::new(),
+                signing_input.as_bytes(),
+                &mut signature,
+            )
+            .unwrap();
+        format!("{signing_input}.{}", URL_SAFE_NO_PAD.encode(signature))
+    }
+
+    fn hs256_token_with_header(
+        header: &serde_json::Value,
+        claims: &serde_json::Value,
+        secret: &[u8],
+    ) -> String {
+        let signing_input = format!("{}.{}", encode(header), encode(claims));
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret).unwrap();
+        mac.update(signing_input.as_bytes());
+        let signature = URL_SAFE_NO_PAD.encode(mac.finalize().into_bytes());
+        format!("{signing_input}.{signature}")
+    }
+
+    /// Assert `token` is rejected as invalid for a reason mentioning `reason`.
+    fn assert_rejected(verifier: &JwtVerifier, token: &str, reason: &str) {
+        match verifier.verify_at(token, NOW) {
+            Err(AuthError::InvalidToken(message)) => assert!(
+                message.contains(reason),
+                "token {token:?} was rejected for {message:?}, expected {reason:?}"
+            ),
+            other => panic!("token {token:?} should be rejected, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn hs256_token_yields_app_id_claim() {
+        let verifier = JwtVerifier::hs256(SECRET, 5);
+        let token =
+            hs256_token(&serde_json::json!({"app_id": "game-1", "sub": "user-9", "exp": NOW + 60}));
+        let claims = verifier.verify_at(&token, NOW).unwrap();
+        assert_eq!(claims.app_id, "game-1");
+        assert_eq!(claims.expires_at, NOW + 60);
+    }
+
+    #[test]
+    fn sub_claim_is_used_when_app_id_is_absent() {
+        let verifier = JwtVerifier::hs256(SECRET, 5);
+        let token = hs256_token(&serde_json::json!({"sub": "game-2", "exp": NOW + 60}));
+        assert_eq!(verifier.verify_at(&token, NOW).unwrap().app_id, "game-2");
+    }
+
+    #[test]
+    fn expiry_honours_clock_skew() {
+        let verifier = JwtVerifier::hs256(SECRET, 5);
+        let token = hs256_token(&serde_json::json!({"app_id": "game-1", "exp": NOW}));
+        assert!(verifier.verify_at(&token, NOW + 5).is_ok());
+        assert!(matches!(
+            verifier.verify_at(&token, NOW + 6),
+            Err(AuthError::TokenExpired)
+        ));
+    }
+
+    #[test]
+    fn not_before_in_future_is_rejected() {
+        let verifier = JwtVerifier::hs256(SECRET, 5);
+        let token = hs256_token(
+            &serde_json::json!({"app_id": "game-1", "exp": NOW + 600, "nbf": NOW + 60}),
+        );
+        assert!(matches!(
+            verifier.verify_at(&token, NOW),
+            Err(AuthError::TokenExpired)
+        ));
+    }
+
+    #[test]
+    fn tampered_or_malformed_tokens_are_rejected() {
+        let verifier = JwtVerifier::hs256(SECRET, 5);
+        let token = hs256_token(&serde_json::json!({"app_id": "game-1", "exp": NOW + 60}));
+        let forged_payload = encode(&serde_json::json!({"app_id": "other", "exp": NOW + 60}));
+        let mut parts: Vec<&str> = token.split('.').collect();
+        parts[1] = &forged_payload;
+        let forged = parts.join(".");
+
+        for bad in [forged.as_str(), "not-a-jwt", "a.b.c", ""] {
+            assert!(
+                matches!(
+                    verifier.verify_at(bad, NOW),
+                    Err(AuthError::InvalidToken(_))
+                ),
+                "token {bad:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn missing_exp_is_rejected() {
+        let verifier = JwtVerifier::hs256(SECRET, 5);
+        let token = hs256_token(&serde_json::json!({"app_id": "game-1"}));
+        assert!(matches!(
+            verifier.verify_at(&token, NOW),
+            Err(AuthError::InvalidToken(_))
+        ));
+    }
+
+    #[test]
+    fn rs256_token_verifies_against_spki_public_key() {
+        let verifier = JwtVerifier::rs256_from_pem(RSA_PUBLIC_PEM, 5).unwrap();
+        let token = rs256_token(&serde_json::json!({"app_id": "game-1", "exp": NOW + 60}));
+        assert_eq!(verifier.verify_at(&token, NOW).unwrap().app_id, "game-1");
+    }
+
+    /// The fixture public key as a one-key JWKS with the given `kid`.
+    fn fixture_jwks(kid: &str) -> String {
+        let spki = decode_pem(RSA_PUBLIC_PEM, "PUBLIC KEY").unwrap().unwrap();
+        let pkcs1 = rsa_key_from_spki(&spki).unwrap();
+        let (0x30, body, _) = read_der(&pkcs1).unwrap() else {
+            panic!("RSAPublicKey is a SEQUENCE");
+        };
+        let (0x02, n, rest) = read_der(body).unwrap() else {
+            panic!("modulus is an INTEGER");
+        };
+        let (0x02, e, _) = read_der(rest).unwrap() else {
+            panic!("exponent is an INTEGER");
+        };
+        let n = n.strip_prefix(&[0]).unwrap_or(n);
+        serde_json::json!({"keys": [
+            {"kty": "EC", "kid": "ec-key", "crv": "P-256"},
+            {
+                "kty": "RSA",
+                "kid": kid,
+                "use": "sig",
+                "n": URL_SAFE_NO_PAD.encode(n),
+                "e": URL_SAFE_NO_PAD.encode(e),
+            },
+        ]})
+        .to_string()
+    }
+
+    #[test]
+    fn rs256_token_verifies_against_jwks_key_by_kid() {
+        let verifier = JwtVerifier::rs256_from_jwks(&fixture_jwks("key-1"), 5).unwrap();
+        let claims = serde_json::json!({"sub": "player-7", "exp": NOW + 60});
+
+        let token = rs256_token_with_header(
+            &serde_json::json!({"alg": "RS256", "kid": "key-1"}),
+            &claims,
+        );
+        assert_eq!(
+            verifier.verify_at(&token, NOW).unwrap().subject.as_deref(),
+            Some("player-7")
+        );
+
+        let unknown_kid = rs256_token_with_header(
+            &serde_json::json!({"alg": "RS256", "kid": "key-2"}),
+            &claims,
+        );
+        assert!(matches!(
+            verifier.verify_at(&unknown_kid, NOW),
+            Err(AuthError::InvalidToken(_))
+        ));
+        assert!(JwtVerifier::rs256_from_jwks(r#"{"keys": []}"#, 5).is_err());
+    }
+
+    #[test]
+    fn issuer_and_audience_are_enforced_when_configured() {
+        let verifier = JwtVerifier::hs256(SECRET, 5)
+            .with_issuer("https://auth.example.com")
+            .with_audience("signal-fish");
+        let valid = hs256_token(&serde_json::json!({
+            "sub": "player-1",
+            "iss": "https://auth.example.com",
+            "aud": ["other-service", "signal-fish"],
+            "exp": NOW + 60,
+        }));
+        assert!(verifier.verify_at(&valid, NOW).is_ok());
+
+        for claims in [
+            serde_json::json!({"sub": "player-1", "aud": "signal-fish", "exp": NOW + 60}),
+            serde_json::json!({"sub": "player-1", "iss": "https://evil.example.com", "aud": "signal-fish", "exp": NOW + 60}),
+            serde_json::json!({"sub": "player-1", "iss": "https://auth.example.com", "aud": "other-service", "exp": NOW + 60}),
+        ] {
+            assert!(
+                matches!(
+                    verifier.verify_at(&hs256_token(&claims), NOW),
+                    Err(AuthError::InvalidToken(_))
+                ),
+                "claims {claims} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn room_scope_accepts_a_string_or_an_array() {
+        let verifier = JwtVerifier::hs256(SECRET, 5);
+        let single = hs256_token(
+            &serde_json::json!({"sub": "player-1", "room_scope": "chess", "exp": NOW + 60}),
+        );
+        assert_eq!(
+            verifier.verify_at(&single, NOW).unwrap().room_scope,
+            Some(vec!["chess".to_string()])
+        );
+
+        let many = hs256_token(&serde_json::json!({
+            "sub": "player-1",
+            "room_scope": ["chess", "go"],
+            "exp": NOW + 60,
+        }));
+        assert_eq!(
+            verifier.verify_at(&many, NOW).unwrap().room_scope,
+            Some(vec!["chess".to_string(), "go".to_string()])
+        );
+
+        let unscoped = hs256_token(&serde_json::json!({"sub": "player-1", "exp": NOW + 60}));
+        assert_eq!(verifier.verify_at(&unscoped, NOW).unwrap().room_scope, None);
+    }
+
+    #[test]
+    fn algorithm_mismatch_is_rejected() {
+        let rsa = JwtVerifier::rs256_from_pem(RSA_PUBLIC_PEM, 5).unwrap();
+        let hs_token = hs256_token(&serde_json::json!({"app_id": "game-1", "exp": NOW + 60}));
+        assert!(matches!(
+            rsa.verify_at(&hs_token, NOW),
+            Err(AuthError::InvalidToken(_))
+        ));
+
+        let hmac = JwtVerifier::hs256(SECRET, 5);
+        let rs_token = rs256_token(&serde_json::json!({"app_id": "game-1", "exp": NOW + 60}));
+        assert!(matches!(
+            hmac.verify_at(&rs_token, NOW),
+            Err(AuthError::InvalidToken(_))
+        ));
+    }
+
+    #[test]
+    fn alg_none_tokens_are_rejected() {
+        let claims = serde_json::json!({"app_id": "game-1", "exp": NOW + 60});
+        let verifiers = [
+            JwtVerifier::hs256(SECRET, 5),
+            JwtVerifier::rs256_from_pem(RSA_PUBLIC_PEM, 5).unwrap(),
+        ];
+        for alg in ["none", "None", "NONE", ""] {
+            let unsigned = format!(
+                "{}.{}.",
+                encode(&serde_json::json!({"alg": alg, "typ": "JWT"})),
+                encode(&claims)
+            );
+            for verifier in &verifiers {
+                assert_rejected(verifier, &unsigned, "is not accepted");
+                let (without_signature, _) = unsigned.rsplit_once('.').unwrap();
+                assert_rejected(verifier, without_signature, "three dot-separated segments");
+            }
+        }
+    }
+
+    #[test]
+    fn rsa_public_key_used_as_hmac_secret_is_rejected() {
+        let rsa = JwtVerifier::rs256_from_pem(RSA_PUBLIC_PEM, 5).unwrap();
+        let claims = serde_json::json!({"app_id": "game-1", "exp": NOW + 60});
+        let public_key = RSA_PUBLIC_PEM.as_bytes();
+        let spki = decode_pem(RSA_PUBLIC_PEM, "PUBLIC KEY").unwrap().unwrap();
+
+        for secret in [public_key, spki.as_slice()] {
+            let confused = hs256_token_with_header(
+                &serde_json::json!({"alg": "HS256", "typ": "JWT"}),
+                &claims,
+                secret,
+            );
+            assert_rejected(&rsa, &confused, "algorithm HS256 is not accepted");
+
+            // Relabelling the HMAC token as RS256 leaves an invalid RSA signature
+            let relabelled = hs256_token_with_header(
+                &serde_json::json!({"alg": "RS256", "typ": "JWT"}),
+                &claims,
+                secret,
+            );
+            assert_rejected(&rsa, &relabelled, "signature mismatch");
+        }
+    }
+
+    #[test]
+    fn non_canonical_base64url_is_rejected() {
+        let verifier = JwtVerifier::hs256(SECRET, 5);
+        let token = hs256_token(&serde_json::json!({"app_id": "game-1", "exp": NOW + 60}));
+        let (signing_input, signature) = token.rsplit_once('.').unwrap();
+        let (header, payload) = signing_input.split_once('.').unwrap();
+        assert!(verifier.verify_at(&token, NOW).is_ok());
+
+        // A 32-byte HMAC leaves two unused bits in the last character; setting
+        // one yields the same bytes under a lenient decoder
+        const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+        assert_eq!(signature.len() % 4, 3);
+        let (body, last) = signature.split_at(signature.len() - 1);
+        let index = ALPHABET
+            .iter()
+            .position(|&c| c == last.as_bytes()[0])
+            .unwrap();
+        let trailing_bits = format!("{body}{}", char::from(ALPHABET[index ^ 1]));
+        assert_rejected(
+            &verifier,
+            &format!("{signing_input}.{trailing_bits}"),
+            "signature is not valid base64url",
+        );
+
+        assert_rejected(
+            &verifier,
+            &format!("{signing_input}.{signature}="),
+            "signature is not valid base64url",
+        );
+        let standard_signature = STANDARD.encode(URL_SAFE_NO_PAD.decode(signature).unwrap());
+        assert_rejected(
+            &verifier,
+            &format!("{signing_input}.{standard_signature}"),
+            "signature is not valid base64url",
+        );
+        assert_rejected(
+            &verifier,
+            &format!("{header}=.{payload}.{signature}"),
+            "header is not valid base64url",
+        );
+
+        // The payload is only decoded once its signature checks out
+        let padded_payload = format!("{header}.{payload}=");
+        let mut mac = Hmac::<Sha256>::new_from_slice(SECRET).unwrap();
+        mac.update(padded_payload.as_bytes());
+        let padded_signature = URL_SAFE_NO_PAD.encode(mac.finalize().into_bytes());
+        assert_rejected(
+            &verifier,
+            &format!("{padded_payload}.{padded_signature}"),
+            "payload is not valid base64url",
+        );
+    }
+
+    #[test]
+    fn read_der_rejects_malformed_lengths() {
+        assert_eq!(
+            read_der(&[0x02, 0x01, 0x05, 0xFF]),
+            Some((0x02, &[0x05][..], &[0xFF][..]))
+        );
+        let mut long = vec![0x04, 0x81, 0x80];
+        long.extend([0xAB; 0x80]);
+        assert_eq!(read_der(&long).map(|(_, body, _)| body.len()), Some(0x80));
+
+        for malformed in [
+            &[][..],
+            &[0x30],
+            // Contents shorter than the length
+            &[0x30, 0x03, 0x01, 0x02],
+            &[0x30, 0x82, 0x01],
+            &[0x30, 0x82, 0x01, 0x00, 0x00],
+            // Indefinite length, and more length octets than any key needs
+            &[0x30, 0x80, 0x00, 0x00],
+            &[0x30, 0x85, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00],
+            &[0x30, 0x88, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF],
+            // Long-form lengths that fit in the short form
+            &[0x30, 0x81, 0x01, 0x00],
+            &[0x30, 0x82, 0x00, 0x01, 0x00],
+        ] {
+            assert_eq!(
+                read_der(malformed),
+                None,
+                "{malformed:02X?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn corrupt_spki_public_keys_are_rejected() {
+        let spki = decode_pem(RSA_PUBLIC_PEM, "PUBLIC KEY").unwrap().unwrap();
+        let to_pem = |der: &[u8]| {
+            format!(
+                "-----BEGIN PUBLIC KEY-----\n{}\n-----END PUBLIC KEY-----\n",
+                STANDARD.encode(der)
+            )
+        };
+        assert!(JwtVerifier::rs256_from_pem(&to_pem(&spki), 5).is_ok());
+
+        let mut overlong = spki.clone();
+        overlong[3] = overlong[3].wrapping_add(1);
+        let mut not_rsa = spki.clone();
+        let oid_at = spki
+            .windows(RSA_ENCRYPTION_OID.len())
+            .position(|window| window == RSA_ENCRYPTION_OID)
+            .unwrap();
+        not_rsa[oid_at + RSA_ENCRYPTION_OID.len() - 1] ^= 1;
+        for corrupt in [
+            &spki[..spki.len() - 1],
+            &spki[..16],
+            overlong.as_slice(),
+            not_rsa.as_slice(),
+        ] {
+            assert!(
+                JwtVerifier::rs256_from_pem(&to_pem(corrupt), 5).is_err(),
+                "{corrupt:02X?} should be rejected"
+            );
+        }
+        assert!(JwtVerifier::rs256_from_pem(
+            "-----BEGIN PUBLIC KEY-----\nnot base64!\n-----END PUBLIC KEY-----",
+            5
+        )
+        .is_err());
+    }
+}
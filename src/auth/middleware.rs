@@ -2,16 +2,19 @@
 //!
 //! Validates application credentials against a static configuration loaded at
 //! startup. When auth is disabled the middleware returns a default `AppInfo` for
-//! every request.
+//! every request. An optional [`JwtVerifier`] enables bearer-token auth on the
+//! WebSocket upgrade, and per-app API keys let server-to-server clients
+//! authenticate the upgrade with an `X-Api-Key` header.
 
 use super::error::AuthError;
-use super::rate_limiter::InMemoryRateLimiter;
-use crate::config::AppAuthEntry;
+use super::jwt::JwtVerifier;
+use super::rate_limiter::{AppRateLimitCounts, InMemoryRateLimiter};
+use crate::config::{AppAuthEntry, AppQuotaConfig};
+use crate::security::constant_time_eq;
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
-use subtle::ConstantTimeEq;
 use uuid::Uuid;
 
 /// Per-application rate limit information returned to clients.
@@ -41,9 +44,64 @@ pub struct AppInfo {
     pub name: String,
     pub organization: Option<String>,
     pub max_rooms: Option<u32>,
+    /// Enforced room quota for the application; `None` means unlimited.
+    pub max_rooms_per_app: Option<usize>,
+    /// Enforced quota on players in the application's rooms; `None` means unlimited.
+    pub max_players_per_app: Option<usize>,
+    /// Enforced quota on rooms created per rolling hour; `None` means unlimited.
+    pub max_rooms_per_hour: Option<u32>,
     pub max_players_per_room: Option<u8>,
     pub rate_limit_per_minute: Option<u32>,
     pub rate_limits: RateLimits,
+    /// The bearer token's `sub` claim, when the client authenticated with a JWT.
+    pub subject: Option<String>,
+    /// Game names the client may create or join rooms for; `None` means any.
+    pub room_scope: Option<Vec<String>>,
+}
+
+impl AppInfo {
+    /// The application configured by `entry`.
+    pub fn from_entry(entry: &AppAuthEntry) -> Self {
+        let per_minute = entry
+            .rate_limit_per_minute
+            .unwrap_or(DEFAULT_RATE_LIMIT_PER_MINUTE);
+        Self {
+            // Deterministic UUID derived from the app_id string so that
+            // the same config always produces the same UUID.
+            id: deterministic_uuid(&entry.app_id),
+            name: entry.app_name.clone(),
+            organization: None,
+            max_rooms: entry.max_rooms,
+            max_rooms_per_app: entry.max_rooms_per_app,
+            max_players_per_app: entry.max_players_per_app,
+            max_rooms_per_hour: entry.max_rooms_per_hour,
+            max_players_per_room: entry.max_players_per_room,
+            rate_limit_per_minute: entry.rate_limit_per_minute,
+            rate_limits: RateLimits {
+                per_minute,
+                per_hour: per_minute.saturating_mul(60),
+                per_day: per_minute.saturating_mul(60).saturating_mul(24),
+            },
+            subject: None,
+            room_scope: None,
+        }
+    }
+
+    /// The usage quotas enforced for this application.
+    pub fn quota(&self) -> AppQuotaConfig {
+        AppQuotaConfig {
+            max_rooms_per_app: self.max_rooms_per_app,
+            max_players_per_app: self.max_players_per_app,
+            max_rooms_per_hour: self.max_rooms_per_hour,
+        }
+    }
+
+    /// Whether the client's token scope permits rooms for `game_name`.
+    pub fn allows_game(&self, game_name: &str) -> bool {
+        self.room_scope
+            .as_ref()
+            .is_none_or(|scope| scope.iter().any(|game| game == game_name))
+    }
 }
 
 /// Default rate limits applied when auth is disabled or an application has no
@@ -55,7 +113,7 @@ const DEFAULT_RATE_LIMIT_PER_DAY: u32 = 100_000;
 /// Derive a deterministic UUID from a string key using SHA-256. The first 16
 /// bytes of the hash are used as the UUID value with the version nibble set
 /// to 4 (random) and the variant to RFC 4122.
-fn deterministic_uuid(key: &str) -> Uuid {
+pub(crate) fn deterministic_uuid(key: &str) -> Uuid {
     let hash = Sha256::digest(key.as_bytes());
     let mut bytes = [0u8; 16];
     bytes.copy_from_slice(&hash[..16]);
@@ -68,10 +126,13 @@ fn deterministic_uuid(key: &str) -> Uuid {
 
 /// Constant-time secret comparison to prevent timing attacks.
 fn secrets_match(a: &str, b: &str) -> bool {
-    if a.len() != b.len() {
-        return false;
-    }
-    a.as_bytes().ct_eq(b.as_bytes()).into()
+    constant_time_eq(a.as_bytes(), b.as_bytes())
+}
+
+/// SHA-256 digest of an API key. Keys are compared by digest so the
+/// comparison length is fixed and does not reveal the configured key length.
+fn api_key_digest(key: &str) -> [u8; 32] {
+    Sha256::digest(key.as_bytes()).into()
 }
 
 /// In-memory authentication middleware backed by a `HashMap` of configured
@@ -83,6 +144,12 @@ pub struct AuthMiddleware {
     rate_limiter: Arc<InMemoryRateLimiter>,
     /// Whether authentication is enabled.
     auth_enabled: bool,
+    /// Bearer-token verifier, present when `client_auth` is `jwt`.
+    jwt: Option<JwtVerifier>,
+    /// Whether upgrades without a bearer token are rejected.
+    jwt_required: bool,
+    /// API key digests paired with the app_id each key authenticates as.
+    api_keys: Vec<([u8; 32], String)>,
 }
 
 impl AuthMiddleware {
@@ -94,25 +161,15 @@ impl AuthMiddleware {
         let has_rate_limited_app = entries.iter().any(|e| e.rate_limit_per_minute.is_some());
 
         let mut apps = HashMap::with_capacity(entries.len());
+        let mut api_keys = Vec::new();
         for entry in entries {
-            let per_minute = entry
-                .rate_limit_per_minute
-                .unwrap_or(DEFAULT_RATE_LIMIT_PER_MINUTE);
-            let info = AppInfo {
-                // Deterministic UUID derived from the app_id string so that
-                // the same config always produces the same UUID.
-                id: deterministic_uuid(&entry.app_id),
-                name: entry.app_name.clone(),
-                organization: None,
-                max_rooms: entry.max_rooms,
-                max_players_per_room: entry.max_players_per_room,
-                rate_limit_per_minute: entry.rate_limit_per_minute,
-                rate_limits: RateLimits {
-                    per_minute,
-                    per_hour: per_minute.saturating_mul(60),
-                    per_day: per_minute.saturating_mul(60).saturating_mul(24),
-                },
-            };
+            api_keys.extend(
+                entry
+                    .api_keys
+                    .iter()
+                    .map(|key| (api_key_digest(key), entry.app_id.clone())),
+            );
+            let info = AppInfo::from_entry(&entry);
             apps.insert(entry.app_id, (entry.app_secret, info));
         }
 
@@ -126,6 +183,9 @@ impl AuthMiddleware {
             apps,
             rate_limiter,
             auth_enabled: true,
+            jwt: None,
+            jwt_required: true,
+            api_keys,
         }
     }
 
@@ -136,9 +196,55 @@ impl AuthMiddleware {
             apps: HashMap::new(),
             rate_limiter: Arc::new(InMemoryRateLimiter::new(Duration::from_secs(60))),
             auth_enabled: false,
+            jwt: None,
+            jwt_required: true,
+            api_keys: Vec::new(),
         }
     }
 
+    /// Require JWT bearer tokens on the WebSocket upgrade, verified with `verifier`.
+    pub fn with_jwt(mut self, verifier: JwtVerifier) -> Self {
+        self.jwt = Some(verifier);
+        self
+    }
+
+    /// Let upgrades without a bearer token through to the `Authenticate`
+    /// handshake; presented tokens are still verified.
+    pub fn with_jwt_required(mut self, required: bool) -> Self {
+        self.jwt_required = required;
+        self
+    }
+
+    /// Whether WebSocket upgrades are checked for a bearer token.
+    pub fn jwt_enabled(&self) -> bool {
+        self.jwt.is_some()
+    }
+
+    /// Whether WebSocket upgrades without a bearer token are rejected.
+    pub fn jwt_required(&self) -> bool {
+        self.jwt.is_some() && self.jwt_required
+    }
+
+    /// The configured applications, in no particular order.
+    pub fn configured_apps(&self) -> impl Iterator<Item = &AppInfo> {
+        self.apps.values().map(|(_secret, info)| info)
+    }
+
+    /// The configured application with the given UUID, if any.
+    pub fn configured_app(&self, id: &Uuid) -> Option<&AppInfo> {
+        self.configured_apps().find(|info| info.id == *id)
+    }
+
+    /// Rate-limit checks and rejections per application since startup.
+    pub fn rate_limit_counts(&self) -> Vec<AppRateLimitCounts> {
+        self.rate_limiter.counts()
+    }
+
+    /// Whether any application has API keys configured.
+    pub fn api_keys_enabled(&self) -> bool {
+        !self.api_keys.is_empty()
+    }
+
     /// Validate both app_id and app_secret. Returns `AppInfo` on success.
     ///
     /// This method is `async` for interface compatibility so that future
@@ -161,7 +267,8 @@ impl AuthMiddleware {
 
         // Enforce per-app rate limit if configured.
         if let Some(limit) = info.rate_limit_per_minute {
-            self.rate_limiter.check_rate_limit(app_id, limit)?;
+            self.rate_limiter
+                .check_rate_limit(info.id, "credentials", limit)?;
         }
 
         Ok(info.clone())
@@ -182,12 +289,47 @@ impl AuthMiddleware {
 
         // Enforce per-app rate limit if configured.
         if let Some(limit) = info.rate_limit_per_minute {
-            self.rate_limiter.check_rate_limit(app_id, limit)?;
+            self.rate_limiter
+                .check_rate_limit(info.id, "app_id", limit)?;
         }
 
         Ok(info.clone())
     }
 
+    /// Validate a JWT bearer token and resolve its `app_id` (or `sub`) claim to
+    /// an application.
+    ///
+    /// When authorized apps are configured the claim must name one of them and
+    /// that app's limits apply; otherwise a default `AppInfo` is derived from
+    /// the claim. The token's subject and room scope are attached to the result.
+    pub async fn validate_bearer_token(&self, token: &str) -> Result<AppInfo, AuthError> {
+        let verifier = self.jwt.as_ref().ok_or_else(|| {
+            AuthError::InvalidToken("bearer token auth is not configured".to_string())
+        })?;
+        let claims = verifier.verify(token)?;
+        let mut info = self.validate_app_id(&claims.app_id).await?;
+        info.subject = claims.subject;
+        info.room_scope = claims.room_scope;
+        Ok(info)
+    }
+
+    /// Resolve an `X-Api-Key` header value to the application it is bound to.
+    ///
+    /// Every configured key is compared so the position of a match does not
+    /// show in the timing. Unknown keys return [`AuthError::Unauthorized`];
+    /// the matched app's rate limit applies as with [`Self::validate_app_id`].
+    pub async fn validate_api_key(&self, api_key: &str) -> Result<AppInfo, AuthError> {
+        let presented = api_key_digest(api_key);
+        let mut matched = None;
+        for (digest, app_id) in &self.api_keys {
+            if constant_time_eq(digest, &presented) && matched.is_none() {
+                matched = Some(app_id);
+            }
+        }
+        let app_id = matched.ok_or(AuthError::Unauthorized)?;
+        self.validate_app_id(app_id).await
+    }
+
     /// Build a default `AppInfo` for use when auth is disabled.
     fn default_app_info(&self, app_id: &str) -> AppInfo {
         let id = app_id
@@ -198,6 +340,9 @@ impl AuthMiddleware {
             name: "default".to_string(),
             organization: None,
             max_rooms: None,
+            max_rooms_per_app: None,
+            max_players_per_app: None,
+            max_rooms_per_hour: None,
             max_players_per_room: None,
             rate_limit_per_minute: None,
             rate_limits: RateLimits {
@@ -205,6 +350,8 @@ impl AuthMiddleware {
                 per_hour: DEFAULT_RATE_LIMIT_PER_HOUR,
                 per_day: DEFAULT_RATE_LIMIT_PER_DAY,
             },
+            subject: None,
+            room_scope: None,
         }
     }
 }
@@ -220,16 +367,24 @@ mod tests {
                 app_secret: "secret-1".to_string(),
                 app_name: "Test Game".to_string(),
                 max_rooms: Some(50),
+                max_rooms_per_app: None,
+                max_players_per_app: None,
+                max_rooms_per_hour: None,
                 max_players_per_room: Some(8),
                 rate_limit_per_minute: Some(60),
+                api_keys: vec!["key-1a".to_string(), "key-1b".to_string()],
             },
             AppAuthEntry {
                 app_id: "game-2".to_string(),
                 app_secret: "secret-2".to_string(),
                 app_name: "Another Game".to_string(),
                 max_rooms: None,
+                max_rooms_per_app: None,
+                max_players_per_app: None,
+                max_rooms_per_hour: None,
                 max_players_per_room: None,
                 rate_limit_per_minute: None,
+                api_keys: vec!["key-2".to_string()],
             },
         ]
     }
@@ -292,8 +447,12 @@ mod tests {
             app_secret: "s".to_string(),
             app_name: "Limited App".to_string(),
             max_rooms: None,
+            max_rooms_per_app: None,
+            max_players_per_app: None,
+            max_rooms_per_hour: None,
             max_players_per_room: None,
             rate_limit_per_minute: Some(3),
+            api_keys: Vec::new(),
         }];
         let mw = AuthMiddleware::new(entries);
 
@@ -304,6 +463,11 @@ mod tests {
         // 4th should fail
         let result = mw.validate_app_id("limited").await;
         assert!(matches!(result.unwrap_err(), AuthError::RateLimitExceeded));
+
+        let counts = mw.rate_limit_counts();
+        assert_eq!(counts.len(), 1);
+        assert_eq!(counts[0].app_id, deterministic_uuid("limited"));
+        assert_eq!((counts[0].checks, counts[0].rejections), (4, 1));
     }
 
     #[tokio::test]
@@ -313,8 +477,12 @@ mod tests {
             app_secret: "s".to_string(),
             app_name: "Unlimited App".to_string(),
             max_rooms: None,
+            max_rooms_per_app: None,
+            max_players_per_app: None,
+            max_rooms_per_hour: None,
             max_players_per_room: None,
             rate_limit_per_minute: None,
+            api_keys: Vec::new(),
         }];
         let mw = AuthMiddleware::new(entries);
 
@@ -347,6 +515,135 @@ mod tests {
         assert_eq!(info.id.to_string(), uuid_str);
     }
 
+    fn hs256_token(claims: serde_json::Value) -> String {
+        use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+        use base64::Engine;
+        use hmac::{Hmac, Mac};
+
+        let header = URL_SAFE_NO_PAD.encode(br#"{"alg":"HS256","typ":"JWT"}"#);
+        let payload = URL_SAFE_NO_PAD.encode(serde_json::to_vec(&claims).unwrap());
+        let signing_input = format!("{header}.{payload}");
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"jwt-secret").unwrap();
+        mac.update(signing_input.as_bytes());
+        let signature = URL_SAFE_NO_PAD.encode(mac.finalize().into_bytes());
+        format!("{signing_input}.{signature}")
+    }
+
+    fn future_exp() -> i64 {
+        chrono::Utc::now().timestamp() + 300
+    }
+
+    #[tokio::test]
+    async fn bearer_token_maps_app_id_claim_to_configured_app() {
+        let mw =
+            AuthMiddleware::new(sample_entries()).with_jwt(JwtVerifier::hs256(b"jwt-secret", 5));
+        assert!(mw.jwt_enabled());
+
+        let token = hs256_token(serde_json::json!({"app_id": "game-1", "exp": future_exp()}));
+        let info = mw.validate_bearer_token(&token).await.unwrap();
+        assert_eq!(info.name, "Test Game");
+        assert_eq!(info.id, deterministic_uuid("game-1"));
+    }
+
+    #[tokio::test]
+    async fn bearer_token_for_unknown_app_is_rejected() {
+        let mw =
+            AuthMiddleware::new(sample_entries()).with_jwt(JwtVerifier::hs256(b"jwt-secret", 5));
+        let token = hs256_token(serde_json::json!({"sub": "unknown", "exp": future_exp()}));
+        assert!(matches!(
+            mw.validate_bearer_token(&token).await,
+            Err(AuthError::InvalidAppId)
+        ));
+    }
+
+    #[tokio::test]
+    async fn bearer_token_with_auth_disabled_derives_app_from_sub() {
+        let mw = AuthMiddleware::disabled().with_jwt(JwtVerifier::hs256(b"jwt-secret", 5));
+        let token = hs256_token(serde_json::json!({"sub": "my-game", "exp": future_exp()}));
+        let info = mw.validate_bearer_token(&token).await.unwrap();
+        assert_eq!(info.id, deterministic_uuid("my-game"));
+    }
+
+    #[tokio::test]
+    async fn expired_bearer_token_is_rejected() {
+        let mw =
+            AuthMiddleware::new(sample_entries()).with_jwt(JwtVerifier::hs256(b"jwt-secret", 5));
+        let exp = chrono::Utc::now().timestamp() - 60;
+        let token = hs256_token(serde_json::json!({"app_id": "game-1", "exp": exp}));
+        assert!(matches!(
+            mw.validate_bearer_token(&token).await,
+            Err(AuthError::TokenExpired)
+        ));
+    }
+
+    #[tokio::test]
+    async fn bearer_token_attaches_subject_and_room_scope() {
+        let mw =
+            AuthMiddleware::new(sample_entries()).with_jwt(JwtVerifier::hs256(b"jwt-secret", 5));
+        let token = hs256_token(serde_json::json!({
+            "app_id": "game-1",
+            "sub": "player-42",
+            "room_scope": ["chess"],
+            "exp": future_exp(),
+        }));
+        let info = mw.validate_bearer_token(&token).await.unwrap();
+        assert_eq!(info.subject.as_deref(), Some("player-42"));
+        assert!(info.allows_game("chess"));
+        assert!(!info.allows_game("checkers"));
+
+        let unscoped = mw.validate_app_id("game-1").await.unwrap();
+        assert!(unscoped.allows_game("checkers"));
+    }
+
+    #[tokio::test]
+    async fn jwt_required_can_be_relaxed() {
+        let verifier = || JwtVerifier::hs256(b"jwt-secret", 5);
+        assert!(AuthMiddleware::disabled()
+            .with_jwt(verifier())
+            .jwt_required());
+        let optional = AuthMiddleware::disabled()
+            .with_jwt(verifier())
+            .with_jwt_required(false);
+        assert!(optional.jwt_enabled());
+        assert!(!optional.jwt_required());
+        assert!(!AuthMiddleware::disabled().jwt_required());
+    }
+
+    #[tokio::test]
+    async fn bearer_token_without_jwt_configured_is_rejected() {
+        let mw = AuthMiddleware::new(sample_entries());
+        assert!(!mw.jwt_enabled());
+        let token = hs256_token(serde_json::json!({"app_id": "game-1", "exp": future_exp()}));
+        assert!(matches!(
+            mw.validate_bearer_token(&token).await,
+            Err(AuthError::InvalidToken(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn api_key_maps_to_bound_app() {
+        let mw = AuthMiddleware::new(sample_entries());
+        assert!(mw.api_keys_enabled());
+
+        let first = mw.validate_api_key("key-1b").await.unwrap();
+        assert_eq!(first.name, "Test Game");
+        assert_eq!(first.id, deterministic_uuid("game-1"));
+        let second = mw.validate_api_key("key-2").await.unwrap();
+        assert_eq!(second.id, deterministic_uuid("game-2"));
+    }
+
+    #[tokio::test]
+    async fn unknown_api_key_is_unauthorized() {
+        let mw = AuthMiddleware::new(sample_entries());
+        for key in ["key-3", "", "key-1a ", "secret-1"] {
+            assert!(matches!(
+                mw.validate_api_key(key).await,
+                Err(AuthError::Unauthorized)
+            ));
+        }
+        assert!(!AuthMiddleware::disabled().api_keys_enabled());
+    }
+
     #[tokio::test]
     async fn disabled_non_uuid_app_id_gets_deterministic_id() {
         let mw = AuthMiddleware::disabled();
@@ -1,7 +1,9 @@
 pub mod error;
+pub mod jwt;
 pub mod middleware;
 pub mod rate_limiter;
 
 pub use error::AuthError;
+pub use jwt::{JwtClaims, JwtVerifier};
 pub use middleware::{AppInfo, AuthMiddleware};
-pub use rate_limiter::InMemoryRateLimiter;
+pub use rate_limiter::{AppRateLimitCounts, InMemoryRateLimiter, RateLimitRecord};
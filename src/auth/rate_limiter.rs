@@ -1,19 +1,50 @@
 //! In-memory per-application rate limiter using a sliding-window counter.
 
 use super::error::AuthError;
+use crate::rate_limit::{Quota, RateLimiter};
 use dashmap::DashMap;
 use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// The outcome of one rate-limit check, logged at `DEBUG`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitRecord {
+    pub app_id: Uuid,
+    /// What was being rate limited, e.g. `"app_id"` for app ID validation
+    pub action: &'static str,
+    pub allowed: bool,
+    /// Requests still allowed in the current window
+    pub remaining: u32,
+}
+
+/// Checks and rejections for one application.
+#[derive(Debug, Default)]
+pub struct AppRateLimitCounters {
+    pub checks: AtomicU64,
+    pub rejections: AtomicU64,
+}
+
+/// Point-in-time rate-limit totals for one application.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AppRateLimitCounts {
+    pub app_id: Uuid,
+    pub checks: u64,
+    pub rejections: u64,
+}
 
 /// Sliding-window rate limiter backed by `DashMap`.
 ///
 /// Each application ID maps to a `VecDeque<Instant>` that records the
-/// timestamps of recent requests. When `check_rate_limit` is called the
-/// window is trimmed to the last 60 seconds before comparing the count
-/// against the configured limit.
+/// timestamps of recent requests. Every check first drops timestamps older
+/// than the window and then compares the count against the limit, so no
+/// window-length interval ever admits more than the limit, even across the
+/// edge of a fixed minute.
 pub struct InMemoryRateLimiter {
-    windows: DashMap<String, VecDeque<Instant>>,
+    windows: DashMap<Uuid, VecDeque<Instant>>,
+    counters: DashMap<Uuid, AppRateLimitCounters>,
     cleanup_interval: Duration,
     window_duration: Duration,
 }
@@ -23,6 +54,7 @@ impl InMemoryRateLimiter {
     pub fn new(cleanup_interval: Duration) -> Self {
         Self {
             windows: DashMap::new(),
+            counters: DashMap::new(),
             cleanup_interval,
             window_duration: Duration::from_secs(60),
         }
@@ -40,28 +72,76 @@ impl InMemoryRateLimiter {
     /// last 60 seconds. If the request is allowed, the current timestamp is
     /// recorded and `Ok(())` is returned. Otherwise
     /// `Err(AuthError::RateLimitExceeded)` is returned.
-    pub fn check_rate_limit(&self, app_id: &str, limit_per_minute: u32) -> Result<(), AuthError> {
-        let now = Instant::now();
-        let window = self.window_duration;
+    pub fn check_rate_limit(
+        &self,
+        app_id: Uuid,
+        action: &'static str,
+        limit_per_minute: u32,
+    ) -> Result<(), AuthError> {
+        let quota = Quota::new(limit_per_minute, self.window_duration);
+        self.acquire_at(app_id, action, quota, Instant::now())
+            .map_err(|_| AuthError::RateLimitExceeded)
+    }
+
+    /// Admit one request for `app_id` if fewer than `max_requests` were
+    /// admitted in the last `window`.
+    pub fn try_acquire(&self, app_id: Uuid, max_requests: u32, window: Duration) -> bool {
+        self.acquire_at(
+            app_id,
+            "request",
+            Quota::new(max_requests, window),
+            Instant::now(),
+        )
+        .is_ok()
+    }
 
-        let mut entry = self.windows.entry(app_id.to_owned()).or_default();
-        let timestamps = entry.value_mut();
+    /// Checks and rejections per application since startup.
+    pub fn counts(&self) -> Vec<AppRateLimitCounts> {
+        self.counters
+            .iter()
+            .map(|entry| AppRateLimitCounts {
+                app_id: *entry.key(),
+                checks: entry.checks.load(Ordering::Relaxed),
+                rejections: entry.rejections.load(Ordering::Relaxed),
+            })
+            .collect()
+    }
+
+    fn acquire_at(
+        &self,
+        app_id: Uuid,
+        action: &'static str,
+        quota: Quota,
+        now: Instant,
+    ) -> Result<(), Duration> {
+        let result = {
+            let mut entry = self.windows.entry(app_id).or_default();
+            let timestamps = entry.value_mut();
+            trim_window(timestamps, now, quota.period);
 
-        // Trim expired entries from the front of the deque.
-        while let Some(&front) = timestamps.front() {
-            if now.duration_since(front) > window {
-                timestamps.pop_front();
+            if timestamps.len() >= quota.burst as usize {
+                let oldest = timestamps.front().copied().unwrap_or(now);
+                Err(quota.period.saturating_sub(now.duration_since(oldest)))
             } else {
-                break;
+                timestamps.push_back(now);
+                Ok(timestamps.len())
             }
-        }
+        };
 
-        if timestamps.len() >= limit_per_minute as usize {
-            return Err(AuthError::RateLimitExceeded);
-        }
+        let record = RateLimitRecord {
+            app_id,
+            action,
+            allowed: result.is_ok(),
+            remaining: result.map_or(0, |used| quota.burst.saturating_sub(used as u32)),
+        };
+        tracing::debug!(app_id = %record.app_id, action = record.action, allowed = record.allowed, remaining = record.remaining, "Rate limit checked");
 
-        timestamps.push_back(now);
-        Ok(())
+        let counters = self.counters.entry(app_id).or_default();
+        counters.checks.fetch_add(1, Ordering::Relaxed);
+        if !record.allowed {
+            counters.rejections.fetch_add(1, Ordering::Relaxed);
+        }
+        result.map(|_| ())
     }
 
     /// Spawn a background task that periodically removes stale entries from
@@ -79,22 +159,33 @@ impl InMemoryRateLimiter {
             }
         })
     }
+}
+
+/// Trim expired entries from the front of the deque.
+fn trim_window(timestamps: &mut VecDeque<Instant>, now: Instant, window: Duration) {
+    while let Some(&front) = timestamps.front() {
+        if now.duration_since(front) > window {
+            timestamps.pop_front();
+        } else {
+            break;
+        }
+    }
+}
+
+impl RateLimiter<Uuid> for InMemoryRateLimiter {
+    /// Sliding window: at most `quota.burst` requests in any `quota.period`.
+    fn try_acquire(&self, app_id: &Uuid, quota: Quota) -> Result<(), Duration> {
+        self.acquire_at(*app_id, "request", quota, Instant::now())
+    }
 
     /// Remove entries whose sliding windows are completely empty (all
     /// timestamps have expired).
-    pub(crate) fn cleanup(&self) {
+    fn cleanup(&self) {
         let now = Instant::now();
         let window = self.window_duration;
 
         self.windows.retain(|_key, timestamps| {
-            // Trim expired entries.
-            while let Some(&front) = timestamps.front() {
-                if now.duration_since(front) > window {
-                    timestamps.pop_front();
-                } else {
-                    break;
-                }
-            }
+            trim_window(timestamps, now, window);
             // Keep the entry only if there are remaining timestamps.
             !timestamps.is_empty()
         });
@@ -105,11 +196,14 @@ impl InMemoryRateLimiter {
 mod tests {
     use super::*;
 
+    const APP1: Uuid = Uuid::from_u128(1);
+    const APP2: Uuid = Uuid::from_u128(2);
+
     #[test]
     fn allows_requests_under_limit() {
         let limiter = InMemoryRateLimiter::new(Duration::from_secs(60));
         for _ in 0..5 {
-            assert!(limiter.check_rate_limit("app1", 10).is_ok());
+            assert!(limiter.check_rate_limit(APP1, "test", 10).is_ok());
         }
     }
 
@@ -117,9 +211,9 @@ mod tests {
     fn rejects_requests_over_limit() {
         let limiter = InMemoryRateLimiter::new(Duration::from_secs(60));
         for _ in 0..10 {
-            limiter.check_rate_limit("app1", 10).unwrap();
+            limiter.check_rate_limit(APP1, "test", 10).unwrap();
         }
-        let result = limiter.check_rate_limit("app1", 10);
+        let result = limiter.check_rate_limit(APP1, "test", 10);
         assert!(result.is_err());
         assert!(matches!(result.unwrap_err(), AuthError::RateLimitExceeded));
     }
@@ -128,19 +222,19 @@ mod tests {
     fn independent_limits_per_app() {
         let limiter = InMemoryRateLimiter::new(Duration::from_secs(60));
         for _ in 0..5 {
-            limiter.check_rate_limit("app1", 5).unwrap();
+            limiter.check_rate_limit(APP1, "test", 5).unwrap();
         }
         // app1 is now at limit
-        assert!(limiter.check_rate_limit("app1", 5).is_err());
+        assert!(limiter.check_rate_limit(APP1, "test", 5).is_err());
         // app2 should still be fine
-        assert!(limiter.check_rate_limit("app2", 5).is_ok());
+        assert!(limiter.check_rate_limit(APP2, "test", 5).is_ok());
     }
 
     #[test]
     fn cleanup_removes_empty_entries() {
         let limiter = InMemoryRateLimiter::new(Duration::from_secs(60));
         // Insert an entry then immediately make it empty by not exceeding the window
-        limiter.check_rate_limit("app1", 100).unwrap();
+        limiter.check_rate_limit(APP1, "test", 100).unwrap();
         assert!(!limiter.windows.is_empty());
 
         // After cleanup, entry should still exist (timestamp is recent)
@@ -148,18 +242,27 @@ mod tests {
         assert!(!limiter.windows.is_empty());
     }
 
+    #[test]
+    fn shared_trait_reports_retry_after() {
+        let limiter = InMemoryRateLimiter::new(Duration::from_secs(60));
+        let quota = Quota::new(1, Duration::from_secs(60));
+        assert!(RateLimiter::try_acquire(&limiter, &APP1, quota).is_ok());
+        let retry_after = RateLimiter::try_acquire(&limiter, &APP1, quota).unwrap_err();
+        assert!(retry_after > Duration::ZERO && retry_after <= Duration::from_secs(60));
+    }
+
     #[test]
     fn zero_limit_always_rejects() {
         let limiter = InMemoryRateLimiter::new(Duration::from_secs(60));
-        let result = limiter.check_rate_limit("app1", 0);
+        let result = limiter.check_rate_limit(APP1, "test", 0);
         assert!(matches!(result.unwrap_err(), AuthError::RateLimitExceeded));
     }
 
     #[test]
     fn limit_of_one_allows_single_request() {
         let limiter = InMemoryRateLimiter::new(Duration::from_secs(60));
-        assert!(limiter.check_rate_limit("app1", 1).is_ok());
-        assert!(limiter.check_rate_limit("app1", 1).is_err());
+        assert!(limiter.check_rate_limit(APP1, "test", 1).is_ok());
+        assert!(limiter.check_rate_limit(APP1, "test", 1).is_err());
     }
 
     #[tokio::test]
@@ -167,7 +270,7 @@ mod tests {
         let limiter = InMemoryRateLimiter::new(Duration::from_secs(60))
             .with_window_duration(Duration::from_millis(1));
 
-        limiter.check_rate_limit("app1", 100).unwrap();
+        limiter.check_rate_limit(APP1, "test", 100).unwrap();
         assert!(!limiter.windows.is_empty());
 
         // Wait for the window to expire.
@@ -190,7 +293,7 @@ mod tests {
         for _ in 0..num_tasks {
             let limiter = limiter.clone();
             handles.push(tokio::spawn(async move {
-                limiter.check_rate_limit("contended-app", limit).is_ok()
+                limiter.check_rate_limit(APP1, "test", limit).is_ok()
             }));
         }
 
@@ -206,4 +309,58 @@ mod tests {
             "exactly {limit} requests should have been accepted, but {accepted} were"
         );
     }
+
+    #[test]
+    fn no_window_admits_more_than_the_limit_across_a_boundary() {
+        let limiter = InMemoryRateLimiter::new(Duration::from_secs(60));
+        let quota = Quota::new(3, Duration::from_secs(1));
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+
+        // A burst at the end of one second followed by another at the start
+        // of the next would pass a fixed-window counter.
+        let offsets = [
+            900, 950, 990, 1000, 1010, 1050, 1500, 1900, 1950, 1991, 2000, 2500, 2990, 3000,
+        ];
+        let allowed: Vec<u64> = offsets
+            .into_iter()
+            .filter(|&ms| limiter.acquire_at(APP1, "test", quota, at(ms)).is_ok())
+            .collect();
+        assert_eq!(allowed, [900, 950, 990, 1950, 1991, 2000, 2990, 3000]);
+
+        for window in allowed.windows(quota.burst as usize + 1) {
+            assert!(
+                window[quota.burst as usize] - window[0] > 1000,
+                "more than {} requests admitted within one second: {window:?}",
+                quota.burst
+            );
+        }
+    }
+
+    #[test]
+    fn checks_and_rejections_are_counted_per_app() {
+        let limiter = InMemoryRateLimiter::new(Duration::from_secs(60));
+        for _ in 0..3 {
+            limiter.try_acquire(APP1, 2, Duration::from_secs(60));
+        }
+        assert!(limiter.try_acquire(APP2, 2, Duration::from_secs(60)));
+
+        let mut counts = limiter.counts();
+        counts.sort_by_key(|count| count.app_id);
+        assert_eq!(
+            counts,
+            [
+                AppRateLimitCounts {
+                    app_id: APP1,
+                    checks: 3,
+                    rejections: 1,
+                },
+                AppRateLimitCounts {
+                    app_id: APP2,
+                    checks: 1,
+                    rejections: 0,
+                },
+            ]
+        );
+    }
 }
@@ -7,6 +7,7 @@
 //! - `BroadcastMessage`: Arc-wrapped messages for zero-cost cloning during broadcast
 //! - `PreSerializedMessage`: Pre-serialized message bytes for avoiding per-client serialization
 //! - `SerializationBuffer`: Pooled buffers for message serialization
+//! - `Outbound`: Queue entries that hand every room member the same serialized frame
 
 use bytes::{Bytes, BytesMut};
 use serde::Serialize;
@@ -396,6 +397,56 @@ impl Iterator for PlayerIdIterator<'_> {
     }
 }
 
+/// One entry in a connection's outbound queue. Room broadcasts carry the
+/// frame serialized once for every recipient using the connection's
+/// encoding, so they are written in order with the player's other messages.
+#[derive(Debug, Clone)]
+pub enum Outbound {
+    /// Serialized by the connection in its own encoding
+    Message(Arc<ServerMessage>),
+    /// Written as is; `message` is what `frame` encodes
+    RoomFrame {
+        message: Arc<ServerMessage>,
+        frame: Arc<[u8]>,
+    },
+}
+
+impl Outbound {
+    /// The queued message, whether or not it is already serialized
+    #[inline]
+    pub fn message(&self) -> &Arc<ServerMessage> {
+        match self {
+            Self::Message(message) | Self::RoomFrame { message, .. } => message,
+        }
+    }
+}
+
+impl From<Arc<ServerMessage>> for Outbound {
+    fn from(message: Arc<ServerMessage>) -> Self {
+        Self::Message(message)
+    }
+}
+
+impl From<ServerMessage> for Outbound {
+    fn from(message: ServerMessage) -> Self {
+        Self::Message(Arc::new(message))
+    }
+}
+
+impl std::ops::Deref for Outbound {
+    type Target = ServerMessage;
+
+    fn deref(&self) -> &ServerMessage {
+        self.message()
+    }
+}
+
+impl AsRef<ServerMessage> for Outbound {
+    fn as_ref(&self) -> &ServerMessage {
+        self.message()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -478,4 +529,21 @@ mod tests {
         let bytes2 = pre.get_json_bytes().unwrap();
         assert_eq!(bytes1, bytes2);
     }
+
+    #[test]
+    fn outbound_room_frames_keep_their_message() {
+        let message = Arc::new(ServerMessage::Pong);
+        let frame: Arc<[u8]> = Arc::from(serde_json::to_vec(&*message).unwrap());
+        let outbound = Outbound::RoomFrame {
+            message: Arc::clone(&message),
+            frame,
+        };
+
+        assert!(Arc::ptr_eq(outbound.message(), &message));
+        assert!(matches!(*outbound, ServerMessage::Pong));
+        assert!(matches!(
+            Outbound::from(ServerMessage::RoomLeft).as_ref(),
+            ServerMessage::RoomLeft
+        ));
+    }
 }
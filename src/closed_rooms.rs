@@ -0,0 +1,143 @@
+//! Bounded history of recently closed rooms, for admin tooling.
+//!
+//! Rooms are tracked from creation so that closures reported only by room id,
+//! such as the expired-room sweep, still produce a full summary. Summaries keep
+//! no player names or connection details. Each one takes roughly 250 bytes
+//! with the default 64-character game name limit, so the default history of
+//! 1000 rooms stays around 250 KB; the oldest summary is dropped once
+//! `server.closed_room_history_size` is reached.
+
+use crate::protocol::{Room, RoomId};
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+/// What is known about a room once it has closed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ClosedRoomSummary {
+    pub room_id: RoomId,
+    pub game_name: String,
+    pub room_code: String,
+    pub created_at: DateTime<Utc>,
+    pub closed_at: DateTime<Utc>,
+    pub reason: String,
+    /// Most players seated in the room at once
+    pub peak_player_count: usize,
+    /// Whether every player readied up and the game started
+    pub finalized: bool,
+}
+
+/// A room that is still open, as tracked for its eventual summary.
+#[derive(Debug)]
+struct OpenRoom {
+    game_name: String,
+    room_code: String,
+    created_at: DateTime<Utc>,
+    peak_player_count: usize,
+    finalized: bool,
+}
+
+/// Open rooms created on this instance, and summaries of the most recently
+/// closed ones.
+#[derive(Debug)]
+pub struct ClosedRoomHistory {
+    capacity: usize,
+    open_rooms: DashMap<RoomId, OpenRoom>,
+    closed: Mutex<VecDeque<ClosedRoomSummary>>,
+}
+
+impl ClosedRoomHistory {
+    /// Keep up to `capacity` summaries (0 keeps none; closures are still reported).
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            open_rooms: DashMap::new(),
+            closed: Mutex::new(VecDeque::with_capacity(capacity.min(1024))),
+        }
+    }
+
+    pub fn room_created(&self, room: &Room) {
+        self.open_rooms.insert(
+            room.id,
+            OpenRoom {
+                game_name: room.game_name.clone(),
+                room_code: room.code.clone(),
+                created_at: room.created_at,
+                peak_player_count: room.players.len(),
+                finalized: false,
+            },
+        );
+    }
+
+    /// `room` must already include the new player.
+    pub fn player_joined(&self, room: &Room) {
+        if let Some(mut open) = self.open_rooms.get_mut(&room.id) {
+            open.peak_player_count = open.peak_player_count.max(room.players.len());
+        }
+    }
+
+    pub fn game_started(&self, room_id: &RoomId) {
+        if let Some(mut open) = self.open_rooms.get_mut(room_id) {
+            open.finalized = true;
+        }
+    }
+
+    /// Ids of the rooms that have not been closed yet.
+    pub fn open_room_ids(&self) -> Vec<RoomId> {
+        self.open_rooms.iter().map(|entry| *entry.key()).collect()
+    }
+
+    /// Record the closure and return its summary, or `None` for rooms this
+    /// instance did not create or has already closed.
+    pub fn room_closed(&self, room_id: RoomId, reason: &str) -> Option<ClosedRoomSummary> {
+        let (_, open) = self.open_rooms.remove(&room_id)?;
+        let summary = ClosedRoomSummary {
+            room_id,
+            game_name: open.game_name,
+            room_code: open.room_code,
+            created_at: open.created_at,
+            closed_at: Utc::now(),
+            reason: reason.to_string(),
+            peak_player_count: open.peak_player_count,
+            finalized: open.finalized,
+        };
+        if self.capacity > 0 {
+            let mut closed = self.closed.lock().unwrap_or_else(|e| e.into_inner());
+            if closed.len() == self.capacity {
+                closed.pop_front();
+            }
+            closed.push_back(summary.clone());
+        }
+        Some(summary)
+    }
+
+    /// Most recent closures first, optionally limited to one game and to rooms
+    /// closed at or after `since`.
+    pub fn recent(
+        &self,
+        game_name: Option<&str>,
+        since: Option<DateTime<Utc>>,
+        limit: usize,
+    ) -> Vec<ClosedRoomSummary> {
+        let closed = self.closed.lock().unwrap_or_else(|e| e.into_inner());
+        closed
+            .iter()
+            .rev()
+            .filter(|summary| since.is_none_or(|since| summary.closed_at >= since))
+            .filter(|summary| game_name.is_none_or(|game| summary.game_name == game))
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    /// Number of summaries currently kept.
+    pub fn len(&self) -> usize {
+        self.closed.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
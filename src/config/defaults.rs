@@ -6,6 +6,9 @@
 
 use super::logging::LogFormat;
 use super::security::ClientAuthMode;
+use super::server::TokenBucketConfig;
+use super::types::WebhookEvent;
+use super::units::{ByteSize, ConfigDuration};
 
 // =============================================================================
 // Port & Root Config
@@ -23,34 +26,39 @@ pub const fn default_max_players() -> u8 {
     8
 }
 
-pub const fn default_ping_timeout() -> u64 {
-    30
+pub const fn default_ping_timeout() -> ConfigDuration {
+    ConfigDuration::from_secs(30)
 }
 
 /// Default threshold for heartbeat throttling (seconds).
 /// Controls how frequently heartbeat timestamps are recorded.
-pub const fn default_heartbeat_throttle_secs() -> u64 {
-    30
+pub const fn default_heartbeat_throttle_secs() -> ConfigDuration {
+    ConfigDuration::from_secs(30)
 }
 
-pub const fn default_room_cleanup_interval() -> u64 {
-    60
+pub const fn default_room_cleanup_interval() -> ConfigDuration {
+    ConfigDuration::from_secs(60)
 }
 
 pub const fn default_max_rooms_per_game() -> usize {
     1000
 }
 
-pub const fn default_empty_room_timeout() -> u64 {
-    300 // 5 minutes
+pub const fn default_empty_room_timeout() -> ConfigDuration {
+    ConfigDuration::from_secs(300) // 5 minutes
+}
+
+pub const fn default_inactive_room_timeout() -> ConfigDuration {
+    ConfigDuration::from_secs(3600) // 1 hour
 }
 
-pub const fn default_inactive_room_timeout() -> u64 {
-    3600 // 1 hour
+pub const fn default_reconnection_window() -> ConfigDuration {
+    ConfigDuration::from_secs(300) // 5 minutes
 }
 
-pub const fn default_reconnection_window() -> u64 {
-    300 // 5 minutes
+/// Reconnects faster than this are hidden from the rest of the room.
+pub const fn default_flap_suppression_secs() -> ConfigDuration {
+    ConfigDuration::from_secs(5)
 }
 
 pub const fn default_event_buffer_size() -> usize {
@@ -61,6 +69,102 @@ pub const fn default_enable_reconnection() -> bool {
     true // Enable reconnection by default
 }
 
+pub const fn default_max_token_age_secs() -> u64 {
+    3600 // Reconnection tokens expire after an hour even without rotation
+}
+
+pub const fn default_expiry_warning_secs() -> u64 {
+    120 // Warn players two minutes before their room is cleaned up
+}
+
+/// Default maximum number of players queued on a full room's waiting list.
+/// 0 disables waiting lists so full rooms reject joins immediately.
+pub const fn default_max_waiting_list_size() -> usize {
+    0
+}
+
+/// Default maximum number of spectators queued for promotion per room.
+/// 0 disables promotion requests.
+pub const fn default_max_promotion_queue_size() -> usize {
+    0
+}
+
+/// Default maximum skill-rating spread between players grouped into one match.
+pub const fn default_matchmaking_elo_window() -> f32 {
+    200.0
+}
+
+/// Default interval between matchmaking passes.
+pub const fn default_matchmaking_tick_ms() -> ConfigDuration {
+    ConfigDuration::from_millis(1000)
+}
+
+/// Default deadline for the readiness probe's checks.
+pub const fn default_readiness_check_timeout_ms() -> ConfigDuration {
+    ConfigDuration::from_millis(1000)
+}
+
+/// Retry hint sent to clients turned away while the instance drains.
+pub const fn default_drain_retry_after_secs() -> ConfigDuration {
+    ConfigDuration::from_secs(30)
+}
+
+/// Relayed messages kept per room for history replay.
+pub const fn default_room_history_size() -> usize {
+    50
+}
+
+/// Summaries of recently closed rooms kept for admin tooling.
+pub const fn default_closed_room_history_size() -> usize {
+    1000
+}
+
+/// How long a create-room idempotency key keeps resolving to its room.
+pub const fn default_room_idempotency_window() -> ConfigDuration {
+    ConfigDuration::from_secs(300) // 5 minutes
+}
+
+/// How long a room authority has to answer an overflow `JoinRequest`.
+pub const fn default_join_request_timeout() -> ConfigDuration {
+    ConfigDuration::from_secs(30)
+}
+
+/// How long a seated player may go unseen before cleanup removes them.
+pub const fn default_stale_player_timeout() -> ConfigDuration {
+    ConfigDuration::from_secs(300) // 5 minutes
+}
+
+/// Game data broadcasts per second that move a game onto its own workers
+/// when `isolate_games` contains `"auto"`.
+pub const fn default_isolation_auto_threshold() -> u32 {
+    500
+}
+
+/// Concurrent game data broadcasts shared by games that are not isolated.
+pub const fn default_broadcast_workers() -> usize {
+    256
+}
+
+/// Concurrent game data broadcasts reserved for each isolated game.
+pub const fn default_isolated_broadcast_workers() -> usize {
+    32
+}
+
+/// Time a join interceptor gets to answer before the failure policy applies.
+pub const fn default_join_interceptor_timeout_ms() -> ConfigDuration {
+    ConfigDuration::from_millis(1000)
+}
+
+/// Per-attempt deadline for a webhook delivery, in milliseconds.
+pub const fn default_webhook_timeout_ms() -> u64 {
+    5000
+}
+
+/// Webhooks that do not list their events receive all of them.
+pub fn default_webhook_events() -> Vec<WebhookEvent> {
+    WebhookEvent::LIFECYCLE.to_vec()
+}
+
 // =============================================================================
 // Rate Limit Defaults
 // =============================================================================
@@ -69,14 +173,31 @@ pub const fn default_max_room_creations() -> u32 {
     5
 }
 
-pub const fn default_rate_limit_time_window() -> u64 {
-    60
+pub const fn default_rate_limit_time_window() -> ConfigDuration {
+    ConfigDuration::from_secs(60)
 }
 
 pub const fn default_max_join_attempts() -> u32 {
     20
 }
 
+/// Looser per-IP ceiling applied alongside app-keyed limits.
+pub const fn default_ip_max_room_creations() -> u32 {
+    20
+}
+
+pub const fn default_ip_max_join_attempts() -> u32 {
+    100
+}
+
+/// Bursts of 5 chat lines, then one every two seconds.
+pub const fn default_spectator_chat_bucket() -> TokenBucketConfig {
+    TokenBucketConfig {
+        capacity: 5,
+        refill_per_sec: 0.5,
+    }
+}
+
 // =============================================================================
 // Protocol Defaults
 // =============================================================================
@@ -93,14 +214,26 @@ pub const fn default_max_player_name_length() -> usize {
     32
 }
 
+pub const fn default_max_spectator_chat_length() -> usize {
+    256
+}
+
 pub const fn default_max_players_limit() -> u8 {
     100
 }
 
+pub const fn default_overflow_players_limit() -> u8 {
+    100
+}
+
 pub const fn default_enable_message_pack_game_data() -> bool {
     true
 }
 
+pub const fn default_send_protocol_info() -> bool {
+    true
+}
+
 pub const fn default_sdk_enforce() -> bool {
     true
 }
@@ -169,22 +302,38 @@ pub const fn default_require_auth() -> bool {
     true // Enforce authentication by default; opt-out only for explicit development scenarios
 }
 
-pub const fn default_max_message_size() -> usize {
-    65536 // 64KB
+pub const fn default_max_message_size() -> ByteSize {
+    ByteSize::from_kib(64)
 }
 
 pub const fn default_max_connections_per_ip() -> usize {
     10
 }
 
+pub const fn default_ipv6_connection_prefix() -> u8 {
+    64
+}
+
 pub const fn default_client_auth_mode() -> ClientAuthMode {
     ClientAuthMode::None
 }
 
+pub const fn default_turn_credential_ttl_secs() -> ConfigDuration {
+    ConfigDuration::from_secs(24 * 60 * 60)
+}
+
 pub fn default_token_binding_subprotocol() -> String {
     "signalfish.tokenbinding.v1".to_string()
 }
 
+pub const fn default_jwt_clock_skew_secs() -> ConfigDuration {
+    ConfigDuration::from_secs(5)
+}
+
+pub const fn default_jwt_required() -> bool {
+    true
+}
+
 // =============================================================================
 // Auth Maintenance Defaults
 // =============================================================================
@@ -217,6 +366,61 @@ pub const fn default_dashboard_cache_history_window_secs() -> u64 {
     300
 }
 
+/// Sample one in this many room broadcasts for delivery acknowledgements (0 disables).
+pub const fn default_delivery_ack_sample_rate() -> u32 {
+    1000
+}
+
+pub const fn default_delivery_ack_timeout() -> ConfigDuration {
+    ConfigDuration::from_secs(10)
+}
+
+/// Upper bound on outstanding delivery samples; the oldest is evicted as a failure.
+pub const fn default_delivery_ack_max_pending() -> usize {
+    1024
+}
+
+/// Build version, commit and uptime are reported by the metrics endpoints by default.
+pub const fn default_metrics_include_build_info() -> bool {
+    true
+}
+
+pub fn default_metrics_persistence_path() -> String {
+    "metrics-counters.json".to_string()
+}
+
+pub fn default_storage_path() -> String {
+    "rooms.json".to_string()
+}
+
+pub fn default_session_duration_buckets_secs() -> Vec<f64> {
+    crate::metrics::DEFAULT_SESSION_DURATION_BUCKETS_SECS.to_vec()
+}
+
+pub fn default_histogram_buckets() -> Vec<f64> {
+    crate::metrics::DEFAULT_LATENCY_BUCKETS_MS.to_vec()
+}
+
+/// Persisted counters older than an hour are treated as unrelated to this run.
+pub const fn default_metrics_persistence_max_age() -> ConfigDuration {
+    ConfigDuration::from_secs(3600)
+}
+
+/// Default OTLP/gRPC collector endpoint.
+pub fn default_otlp_endpoint() -> String {
+    "http://localhost:4317".to_string()
+}
+
+/// OTLP export interval, matching the OpenTelemetry SDK default.
+pub const fn default_otlp_interval() -> ConfigDuration {
+    ConfigDuration::from_secs(60)
+}
+
+/// Below about a kilobyte the gzip header and CPU cost outweigh the savings.
+pub const fn default_http_compression_min_size() -> ByteSize {
+    ByteSize::from_kib(1)
+}
+
 pub fn default_dashboard_history_fields() -> Vec<DashboardHistoryField> {
     vec![
         DashboardHistoryField::ActiveRooms,
@@ -226,8 +430,29 @@ pub fn default_dashboard_history_fields() -> Vec<DashboardHistoryField> {
     ]
 }
 
+/// Series sampled for `/metrics/history`: rooms, connected players and the
+/// message and reconnection rates.
+pub fn default_metrics_history_series() -> Vec<DashboardHistoryField> {
+    vec![
+        DashboardHistoryField::ActiveRooms,
+        DashboardHistoryField::ActiveConnections,
+        DashboardHistoryField::MessagesPerSec,
+        DashboardHistoryField::ReconnectRate,
+    ]
+}
+
+/// One history sample per default cleanup tick.
+pub const fn default_metrics_history_sample_interval() -> ConfigDuration {
+    ConfigDuration::from_secs(60)
+}
+
+/// Twelve hours of samples at the default interval.
+pub const fn default_metrics_history_capacity() -> usize {
+    crate::metrics::METRICS_HISTORY_MAX_CAPACITY
+}
+
 /// Dashboard history field (used by metrics config defaults).
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
 pub enum DashboardHistoryField {
     ActiveRooms,
     RoomsByGame,
@@ -235,6 +460,41 @@ pub enum DashboardHistoryField {
     GamePercentiles,
     ActiveConnections,
     RoomsCreated,
+    /// Client messages received per second
+    MessagesPerSec,
+    /// Completed reconnections per second
+    ReconnectRate,
+}
+
+impl DashboardHistoryField {
+    /// Fields that reduce to a single number and can be kept in `/metrics/history`.
+    pub const HISTORY_SERIES: [Self; 4] = [
+        Self::ActiveRooms,
+        Self::ActiveConnections,
+        Self::MessagesPerSec,
+        Self::ReconnectRate,
+    ];
+
+    /// Name used by the `series` parameter of `/metrics/history`.
+    pub const fn series_name(self) -> &'static str {
+        match self {
+            Self::ActiveRooms => "active_rooms",
+            Self::RoomsByGame => "rooms_by_game",
+            Self::PlayerPercentiles => "player_percentiles",
+            Self::GamePercentiles => "game_percentiles",
+            Self::ActiveConnections => "active_connections",
+            Self::RoomsCreated => "rooms_created",
+            Self::MessagesPerSec => "messages_per_sec",
+            Self::ReconnectRate => "reconnect_rate",
+        }
+    }
+
+    /// The history series called `name`, if there is one.
+    pub fn from_series_name(name: &str) -> Option<Self> {
+        Self::HISTORY_SERIES
+            .into_iter()
+            .find(|field| field.series_name() == name)
+    }
 }
 
 // =============================================================================
@@ -265,6 +525,15 @@ pub fn default_relay_type() -> String {
     "matchbox".to_string() // Default to matchbox WebRTC signaling
 }
 
+/// Provisioning attempts per relay session before the failure policy applies.
+pub const fn default_relay_warmup_max_attempts() -> u32 {
+    3
+}
+
+pub const fn default_relay_warmup_retry_delay_ms() -> ConfigDuration {
+    ConfigDuration::from_millis(100)
+}
+
 // =============================================================================
 // WebSocket Defaults
 // =============================================================================
@@ -277,10 +546,42 @@ pub const fn default_batch_size() -> usize {
     10 // Max 10 messages per batch
 }
 
-pub const fn default_batch_interval_ms() -> u64 {
-    16 // One frame at 60fps for minimal latency
+pub const fn default_batch_interval_ms() -> ConfigDuration {
+    ConfigDuration::from_millis(16) // One frame at 60fps for minimal latency
+}
+
+pub const fn default_max_batch_bytes() -> ByteSize {
+    ByteSize::from_bytes(0) // Batches are bounded by count and delay only
+}
+
+pub const fn default_auth_timeout_secs() -> ConfigDuration {
+    ConfigDuration::from_secs(10) // Default auth timeout: 10 seconds
+}
+
+pub const fn default_allow_msgpack() -> bool {
+    false // Opt-in: clients must also offer the signal-fish-msgpack subprotocol
+}
+
+pub const fn default_allow_rkyv() -> bool {
+    false // Opt-in: clients must also offer the signal-fish-rkyv subprotocol
+}
+
+pub const fn default_tcp_keepalive_secs() -> ConfigDuration {
+    ConfigDuration::from_secs(15) // Idle time before the first keepalive probe
+}
+
+pub const fn default_tcp_keepalive_interval_secs() -> ConfigDuration {
+    ConfigDuration::from_secs(5)
+}
+
+pub const fn default_tcp_keepalive_retries() -> u32 {
+    3 // Unanswered probes before the kernel drops the connection
+}
+
+pub const fn default_write_timeout_ms() -> ConfigDuration {
+    ConfigDuration::from_millis(10_000) // A frame flush stalled this long means the peer is gone
 }
 
-pub const fn default_auth_timeout_secs() -> u64 {
-    10 // Default auth timeout: 10 seconds
+pub const fn default_max_pending_send_queue_depth() -> usize {
+    128 // Outbound messages queued per connection before it is shed
 }
@@ -4,18 +4,38 @@ use super::validation::validate_config_security;
 use super::Config;
 use serde_json::Value;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+/// Config file names looked for in a directory, in precedence order. Only the
+/// first one present is read.
+const CONFIG_FILE_NAMES: [&str; 3] = ["config.json", "config.toml", "config.yaml"];
+
+/// Prefix of environment variables that override individual config fields,
+/// e.g. `SIGNALFISH_SERVER__DEFAULT_MAX_PLAYERS=16`.
+pub const ENV_PREFIX: &str = "SIGNALFISH_";
+
+/// Earlier override prefix, still accepted. Where both prefixes set the same
+/// field, [`ENV_PREFIX`] wins.
+const LEGACY_ENV_PREFIX: &str = "SIGNAL_FISH__";
 
 /// Load configuration with the following precedence (highest first):
 /// 1) `SIGNAL_FISH_CONFIG_JSON` env var containing raw JSON
 /// 2) If `SIGNAL_FISH_CONFIG_STDIN=true/1`, read JSON from stdin
 /// 3) File pointed by `SIGNAL_FISH_CONFIG_PATH` env var
-/// 4) config.json in current working directory
-/// 5) config.json next to the executable (application directory)
+/// 4) config.json, config.toml or config.yaml in current working directory
+/// 5) config.json, config.toml or config.yaml next to the executable (application directory)
 /// 6) Defaults compiled into the binary
 ///
-/// Additionally, individual fields can be overridden by environment variables with prefix SIGNAL_FISH
-/// using "__" as a nested separator, e.g. `SIGNAL_FISH__PORT=8080` or `SIGNAL_FISH__LOGGING__LEVEL=debug`.
+/// Files are parsed as JSON, TOML or YAML according to their extension; a file
+/// with any other extension is parsed as whichever of the three it is valid in.
+/// When a directory has more than one of the config files, the first in the
+/// order above is used and a warning names it.
+///
+/// Individual fields can then be overridden by environment variables with prefix [`ENV_PREFIX`]
+/// using "__" as a nested separator, e.g. `SIGNALFISH_PORT=8080` or `SIGNALFISH_LOGGING__LEVEL=debug`.
+/// These are applied last, so they win over every source above. The sources
+/// are merged into one document and read with [`load_from_reader`], so files
+/// and embedded JSON get the same defaults and overrides.
 /// Any errors while reading/parsing are printed to stderr and defaults are used.
 ///
 /// **Note:** Validation errors from [`validate_config_security`] are logged to stderr but are
@@ -24,17 +44,152 @@ use std::path::Path;
 /// on the returned config and handle the error themselves.
 #[must_use]
 pub fn load() -> Config {
+    load_from(None).unwrap_or_else(|e| {
+        eprintln!("{e}; using defaults");
+        Config::default()
+    })
+}
+
+/// Like [`load`], but with `config_file` (the `--config` flag) in place of
+/// `SIGNAL_FISH_CONFIG_PATH` and the config files looked up in steps 4 and 5.
+///
+/// Fails, naming the variable, when an environment override holds a value
+/// its field cannot take; other problems fall back to defaults as in [`load`].
+pub fn load_from(config_file: Option<&Path>) -> anyhow::Result<Config> {
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+    let sources = merge_sources(config_file, &mut errors, &mut warnings);
+    for warning in &warnings {
+        eprintln!("Warning: {warning}");
+    }
+    for error in &errors {
+        eprintln!("{error}");
+    }
+
+    // The merged sources are read as one JSON document, the same way as an
+    // embedded one
+    let document = serde_json::to_vec(&sources)?;
+    let config = match load_from_reader(document.as_slice()) {
+        Ok(cfg) => cfg,
+        Err(e) if e.is::<EnvOverrideError>() => return Err(e),
+        Err(e) => {
+            eprintln!("Failed to deserialize config; using defaults: {e}");
+            Config::default()
+        }
+    };
+
+    // Security validation for sensitive fields — intentional warn-only behaviour;
+    // main.rs calls validate_config_security() again and propagates errors properly.
+    if let Err(e) = validate_config_security(&config) {
+        eprintln!("Configuration validation error: {e}");
+    }
+
+    Ok(config)
+}
+
+/// Load configuration from the same sources as [`load`], but fail instead of
+/// skipping unreadable sources or falling back to defaults, and run
+/// [`validate_config_security`].
+///
+/// Used when reloading a running server, where a fallback would silently
+/// replace the active configuration.
+pub fn try_load() -> anyhow::Result<Config> {
+    try_load_from(None)
+}
+
+/// [`try_load`] with an explicit config file, as for [`load_from`].
+pub fn try_load_from(config_file: Option<&Path>) -> anyhow::Result<Config> {
+    // Stdin was consumed at startup; reading it again would drop that config.
+    if std::env::var("SIGNAL_FISH_CONFIG_STDIN").is_ok_and(|val| env_var_truthy(&val)) {
+        anyhow::bail!("configuration read from stdin cannot be loaded again");
+    }
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+    let sources = merge_sources(config_file, &mut errors, &mut warnings);
+    for warning in &warnings {
+        tracing::warn!("{warning}");
+    }
+    if !errors.is_empty() {
+        anyhow::bail!(errors.join("; "));
+    }
+    let config = load_document(sources)?;
+    validate_config_security(&config)?;
+    Ok(config)
+}
+
+/// Load configuration from a JSON document, e.g. one embedded in a test.
+///
+/// Fields missing from `json` take their defaults, and environment overrides
+/// with prefix [`ENV_PREFIX`] are applied on top as in [`load`]. No other
+/// source is read and [`validate_config_security`] is not run.
+pub fn load_from_str(json: &str) -> anyhow::Result<Config> {
+    let document: Value = serde_json::from_str(json)
+        .map_err(|e| anyhow::anyhow!("Failed to parse config JSON: {e}"))?;
+    load_document(document)
+}
+
+/// [`load_from_str`] for JSON read from `reader`.
+pub fn load_from_reader<R: std::io::Read>(reader: R) -> anyhow::Result<Config> {
+    let document: Value = serde_json::from_reader(reader)
+        .map_err(|e| anyhow::anyhow!("Failed to read config JSON: {e}"))?;
+    load_document(document)
+}
+
+fn load_document(document: Value) -> anyhow::Result<Config> {
+    let mut merged = serde_json::to_value(Config::default())?;
+    merge_values(&mut merged, document);
+    let overrides = apply_env_overrides(&mut merged);
+    deserialize_overridden(merged, &overrides)
+}
+
+/// An environment override holding a value its field cannot take.
+#[derive(Debug)]
+struct EnvOverrideError {
+    var: String,
+    source: serde_path_to_error::Error<serde_json::Error>,
+}
+
+impl std::fmt::Display for EnvOverrideError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Invalid value in environment variable {}: {}",
+            self.var, self.source
+        )
+    }
+}
+
+impl std::error::Error for EnvOverrideError {}
+
+/// Deserialize a merged document, naming the environment variable that set
+/// the offending field when there is one.
+fn deserialize_overridden(value: Value, overrides: &[EnvOverride]) -> anyhow::Result<Config> {
+    deserialize_config(value).map_err(|e| match overriding_var(&e, overrides) {
+        Some(var) => EnvOverrideError {
+            var: var.to_string(),
+            source: e,
+        }
+        .into(),
+        None => e.into(),
+    })
+}
+
+/// Merge every configuration source except environment overrides into one
+/// document. Sources that cannot be read or parsed are skipped and described
+/// in `errors`.
+fn merge_sources(
+    config_file: Option<&Path>,
+    errors: &mut Vec<String>,
+    warnings: &mut Vec<String>,
+) -> Value {
     use std::env;
     use std::io::Read;
-    use std::path::PathBuf;
 
-    let defaults = Config::default();
-    let mut merged =
-        serde_json::to_value(&defaults).unwrap_or_else(|_| Value::Object(serde_json::Map::new()));
+    let mut merged = Value::Object(serde_json::Map::new());
 
     // 1) Inline JSON via env var
     if let Ok(json) = env::var("SIGNAL_FISH_CONFIG_JSON") {
-        if let Some(value) = parse_json_document(&json, "SIGNAL_FISH_CONFIG_JSON") {
+        if let Some(value) = parse_json_document(&json, "SIGNAL_FISH_CONFIG_JSON", errors) {
             merge_values(&mut merged, value);
         }
     }
@@ -44,51 +199,54 @@ pub fn load() -> Config {
         if env_var_truthy(&val) {
             let mut buf = String::new();
             if let Err(e) = std::io::stdin().read_to_string(&mut buf) {
-                eprintln!("Failed to read config from stdin: {e}");
-            } else if let Some(value) = parse_json_document(&buf, "stdin") {
+                errors.push(format!("Failed to read config from stdin: {e}"));
+            } else if let Some(value) = parse_json_document(&buf, "stdin", errors) {
                 merge_values(&mut merged, value);
             }
         }
     }
 
-    // 3) Explicit path via env var
-    if let Ok(path) = env::var("SIGNAL_FISH_CONFIG_PATH") {
-        let path = PathBuf::from(path);
-        merge_file_source(&mut merged, &path);
-    }
-
-    // 4) config.json in CWD
-    merge_file_source(&mut merged, &PathBuf::from("config.json"));
-
-    // 5) config.json next to executable
-    if let Ok(exe_path) = env::current_exe() {
-        if let Some(mut exe_dir) = exe_path.parent().map(std::path::Path::to_path_buf) {
-            exe_dir.push("config.json");
-            merge_file_source(&mut merged, &exe_dir);
+    if let Some(path) = config_file {
+        // 3) Explicit file from the command line, replacing 3-5 below
+        if path.exists() {
+            merge_file_source(&mut merged, path, errors);
+        } else {
+            errors.push(format!("Config file {} does not exist", path.display()));
+        }
+    } else {
+        // 3) Explicit path via env var
+        if let Ok(path) = env::var("SIGNAL_FISH_CONFIG_PATH") {
+            let path = PathBuf::from(path);
+            merge_file_source(&mut merged, &path, errors);
         }
-    }
-
-    // Environment overrides with prefix SIGNAL_FISH and nested separator __
-    apply_env_overrides(&mut merged);
 
-    let config = match serde_json::from_value::<Config>(merged) {
-        Ok(cfg) => cfg,
-        Err(e) => {
-            eprintln!("Failed to deserialize config; using defaults: {e}");
-            defaults
+        // 4) Config file in CWD
+        if let Some(path) = find_config_file(Path::new(""), warnings) {
+            merge_file_source(&mut merged, &path, errors);
         }
-    };
 
-    // Security validation for sensitive fields — intentional warn-only behaviour;
-    // main.rs calls validate_config_security() again and propagates errors properly.
-    if let Err(e) = validate_config_security(&config) {
-        eprintln!("Configuration validation error: {e}");
+        // 5) Config file next to executable
+        if let Ok(exe_path) = env::current_exe() {
+            if let Some(exe_dir) = exe_path.parent() {
+                if let Some(path) = find_config_file(exe_dir, warnings) {
+                    merge_file_source(&mut merged, &path, errors);
+                }
+            }
+        }
     }
 
-    config
+    merged
 }
 
-fn parse_json_document(raw: &str, label: &str) -> Option<Value> {
+/// Deserialize a merged config document, prefixing errors with the path of
+/// the offending field (e.g. `server.ping_timeout: invalid duration ...`).
+pub fn deserialize_config(
+    value: Value,
+) -> Result<Config, serde_path_to_error::Error<serde_json::Error>> {
+    serde_path_to_error::deserialize(value)
+}
+
+fn parse_json_document(raw: &str, label: &str, errors: &mut Vec<String>) -> Option<Value> {
     if raw.trim().is_empty() {
         return None;
     }
@@ -96,26 +254,113 @@ fn parse_json_document(raw: &str, label: &str) -> Option<Value> {
     match serde_json::from_str(raw) {
         Ok(value) => Some(value),
         Err(err) => {
-            eprintln!("Failed to parse config from {label}: {err}");
+            errors.push(format!("Failed to parse config from {label}: {err}"));
             None
         }
     }
 }
 
-fn merge_file_source(target: &mut Value, path: &Path) {
+/// The first of [`CONFIG_FILE_NAMES`] present in `dir`. Any others present
+/// are ignored, with a warning naming the file that was chosen.
+fn find_config_file(dir: &Path, warnings: &mut Vec<String>) -> Option<PathBuf> {
+    let mut present = CONFIG_FILE_NAMES
+        .iter()
+        .map(|name| dir.join(name))
+        .filter(|path| path.exists());
+    let chosen = present.next()?;
+    let ignored: Vec<String> = present.map(|path| path.display().to_string()).collect();
+    if !ignored.is_empty() {
+        warnings.push(format!(
+            "Found several config files; using {} and ignoring {}",
+            chosen.display(),
+            ignored.join(", ")
+        ));
+    }
+    Some(chosen)
+}
+
+/// Syntax of a configuration file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ConfigFormat {
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl ConfigFormat {
+    const ALL: [Self; 3] = [Self::Json, Self::Toml, Self::Yaml];
+
+    /// The format named by the file's extension, if any.
+    fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "json" => Some(Self::Json),
+            "toml" => Some(Self::Toml),
+            "yaml" | "yml" => Some(Self::Yaml),
+            _ => None,
+        }
+    }
+
+    fn parse(self, raw: &str) -> Result<Value, String> {
+        match self {
+            Self::Json => serde_json::from_str(raw).map_err(|e| e.to_string()),
+            Self::Toml => toml::from_str(raw).map_err(|e| e.to_string()),
+            Self::Yaml => serde_norway::from_str(raw).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+/// Parse a config file in the format its extension names, or in the first
+/// format that yields an object when the extension names none.
+fn parse_file_document(
+    raw: &str,
+    path: &Path,
+    label: &str,
+    errors: &mut Vec<String>,
+) -> Option<Value> {
+    if raw.trim().is_empty() {
+        return None;
+    }
+
+    if let Some(format) = ConfigFormat::from_path(path) {
+        return match format.parse(raw) {
+            Ok(value) => Some(value),
+            Err(err) => {
+                errors.push(format!("Failed to parse config from {label}: {err}"));
+                None
+            }
+        };
+    }
+
+    let parsed = ConfigFormat::ALL
+        .into_iter()
+        .find_map(|format| format.parse(raw).ok().filter(Value::is_object));
+    if parsed.is_none() {
+        errors.push(format!(
+            "Failed to parse config from {label}: not a JSON, TOML or YAML object"
+        ));
+    }
+    parsed
+}
+
+fn merge_file_source(target: &mut Value, path: &Path, errors: &mut Vec<String>) {
     if path.as_os_str().is_empty() || !path.exists() {
         return;
     }
 
     match fs::read_to_string(path) {
         Ok(contents) => {
-            if let Some(value) = parse_json_document(&contents, &format!("file {}", path.display()))
-            {
+            let label = format!("file {}", path.display());
+            if let Some(value) = parse_file_document(&contents, path, &label, errors) {
                 merge_values(target, value);
             }
         }
         Err(err) => {
-            eprintln!("Failed to read config from {}: {}", path.display(), err);
+            errors.push(format!(
+                "Failed to read config from {}: {}",
+                path.display(),
+                err
+            ));
         }
     }
 }
@@ -138,25 +383,59 @@ fn merge_values(target: &mut Value, source: Value) {
     }
 }
 
-fn apply_env_overrides(root: &mut Value) {
-    for (key, raw_value) in std::env::vars() {
-        let Some(stripped) = key.strip_prefix("SIGNAL_FISH__") else {
-            continue;
-        };
+/// A config field set from an environment variable.
+struct EnvOverride {
+    var: String,
+    /// Dotted field path, e.g. `server.default_max_players`.
+    path: String,
+}
 
-        let segments: Vec<String> = stripped
-            .split("__")
-            .filter(|segment| !segment.is_empty())
-            .map(str::to_ascii_lowercase)
-            .collect();
+/// Apply environment overrides with prefix [`LEGACY_ENV_PREFIX`], then
+/// [`ENV_PREFIX`], and nested separator `__`. Returns the fields they set.
+fn apply_env_overrides(root: &mut Value) -> Vec<EnvOverride> {
+    let vars: Vec<(String, String)> = std::env::vars().collect();
+    let mut applied = Vec::new();
+    for prefix in [LEGACY_ENV_PREFIX, ENV_PREFIX] {
+        for (key, raw_value) in &vars {
+            let Some(stripped) = key.strip_prefix(prefix) else {
+                continue;
+            };
 
-        if segments.is_empty() {
-            continue;
-        }
+            let segments: Vec<String> = stripped
+                .split("__")
+                .filter(|segment| !segment.is_empty())
+                .map(str::to_ascii_lowercase)
+                .collect();
+
+            if segments.is_empty() {
+                continue;
+            }
 
-        let value = parse_env_value(&raw_value);
-        set_nested_value(root, &segments, value);
+            let value = parse_env_value(raw_value);
+            set_nested_value(root, &segments, value);
+            applied.push(EnvOverride {
+                var: key.clone(),
+                path: segments.join("."),
+            });
+        }
     }
+    applied
+}
+
+/// The environment variable that set the field `err` is about, if any.
+fn overriding_var<'a>(
+    err: &serde_path_to_error::Error<serde_json::Error>,
+    overrides: &'a [EnvOverride],
+) -> Option<&'a str> {
+    let path = err.path().to_string();
+    overrides
+        .iter()
+        .rev()
+        .find(|o| {
+            path.strip_prefix(&o.path)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with(['.', '[']))
+        })
+        .map(|o| o.var.as_str())
 }
 
 fn env_var_truthy(value: &str) -> bool {
@@ -215,3 +494,46 @@ fn ensure_object(value: &mut Value) -> &mut serde_json::Map<String, Value> {
         .as_object_mut()
         .expect("value should be coerced into an object")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_file_lookup_prefers_json_then_toml_and_warns_about_the_rest() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let mut warnings = Vec::new();
+        assert_eq!(find_config_file(dir.path(), &mut warnings), None);
+
+        fs::write(dir.path().join("config.yaml"), "port: 1").expect("write yaml");
+        fs::write(dir.path().join("config.toml"), "port = 2").expect("write toml");
+        let chosen = find_config_file(dir.path(), &mut warnings).expect("a config file");
+        assert_eq!(chosen, dir.path().join("config.toml"));
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("using") && warnings[0].contains("config.toml"));
+        assert!(warnings[0].contains("ignoring") && warnings[0].contains("config.yaml"));
+
+        fs::write(dir.path().join("config.json"), r#"{"port": 3}"#).expect("write json");
+        warnings.clear();
+        let chosen = find_config_file(dir.path(), &mut warnings).expect("a config file");
+        assert_eq!(chosen, dir.path().join("config.json"));
+        assert!(warnings[0].contains("config.toml") && warnings[0].contains("config.yaml"));
+    }
+
+    #[test]
+    fn extensionless_files_use_the_first_format_yielding_an_object() {
+        let path = Path::new("settings");
+        let mut errors = Vec::new();
+        let yaml = parse_file_document("port: 9\n", path, "test", &mut errors);
+        assert_eq!(yaml, Some(serde_json::json!({ "port": 9 })));
+        let toml = parse_file_document("port = 9\n", path, "test", &mut errors);
+        assert_eq!(toml, Some(serde_json::json!({ "port": 9 })));
+        assert!(errors.is_empty());
+
+        assert_eq!(
+            parse_file_document("just text", path, "test", &mut errors),
+            None
+        );
+        assert_eq!(errors.len(), 1);
+    }
+}
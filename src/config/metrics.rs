@@ -2,9 +2,18 @@
 
 use super::defaults::{
     default_dashboard_cache_history_window_secs, default_dashboard_cache_refresh_interval_secs,
-    default_dashboard_cache_ttl_secs, default_dashboard_history_fields, DashboardHistoryField,
+    default_dashboard_cache_ttl_secs, default_dashboard_history_fields,
+    default_delivery_ack_max_pending, default_delivery_ack_sample_rate,
+    default_delivery_ack_timeout, default_histogram_buckets, default_http_compression_min_size,
+    default_metrics_history_capacity, default_metrics_history_sample_interval,
+    default_metrics_history_series, default_metrics_include_build_info,
+    default_metrics_persistence_max_age, default_metrics_persistence_path, default_otlp_endpoint,
+    default_otlp_interval, default_session_duration_buckets_secs, DashboardHistoryField,
 };
+use super::units::{ByteSize, ConfigDuration};
+use crate::metrics::METRICS_HISTORY_MAX_CAPACITY;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Metrics configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,6 +23,35 @@ pub struct MetricsConfig {
     pub dashboard_cache_ttl_secs: u64,
     pub dashboard_cache_history_window_secs: u64,
     pub dashboard_cache_history_fields: Vec<DashboardHistoryField>,
+    /// Series kept for `/metrics/history` (empty disables the history)
+    pub history_series: Vec<DashboardHistoryField>,
+    /// Time between history samples; samples are taken on cleanup ticks, so
+    /// never more often than `server.room_cleanup_interval`
+    pub history_sample_interval: ConfigDuration,
+    /// Samples kept per history series, at most 720
+    pub history_capacity: usize,
+    /// Sample one in N room broadcasts for delivery acknowledgements (0 disables)
+    pub delivery_ack_sample_rate: u32,
+    /// How long a sampled broadcast waits for acknowledgements before counting as failed
+    pub delivery_ack_timeout: ConfigDuration,
+    /// Maximum outstanding delivery samples tracked at once
+    pub delivery_ack_max_pending: usize,
+    /// Report version, commit, start time and uptime from the metrics endpoints
+    pub include_build_info: bool,
+    /// Save counters on graceful shutdown and restore them on the next start
+    pub persist_counters: bool,
+    /// File the counters are written to when `persist_counters` is enabled
+    pub persistence_path: String,
+    /// Persisted counters older than this are ignored on startup
+    pub persistence_max_age: ConfigDuration,
+    /// Upper bounds (seconds) of the session duration histogram buckets
+    pub session_duration_buckets_secs: Vec<f64>,
+    /// Upper bounds (milliseconds) of the room creation, join and query latency histograms
+    pub histogram_buckets: Vec<f64>,
+    /// Push metrics to an OpenTelemetry collector (requires the `otlp` feature)
+    pub otlp: OtlpConfig,
+    /// gzip the metrics and admin JSON responses for clients that accept it
+    pub http_compression: HttpCompressionConfig,
 }
 
 impl Default for MetricsConfig {
@@ -23,6 +61,110 @@ impl Default for MetricsConfig {
             dashboard_cache_ttl_secs: default_dashboard_cache_ttl_secs(),
             dashboard_cache_history_window_secs: default_dashboard_cache_history_window_secs(),
             dashboard_cache_history_fields: default_dashboard_history_fields(),
+            history_series: default_metrics_history_series(),
+            history_sample_interval: default_metrics_history_sample_interval(),
+            history_capacity: default_metrics_history_capacity(),
+            delivery_ack_sample_rate: default_delivery_ack_sample_rate(),
+            delivery_ack_timeout: default_delivery_ack_timeout(),
+            delivery_ack_max_pending: default_delivery_ack_max_pending(),
+            include_build_info: default_metrics_include_build_info(),
+            persist_counters: false,
+            persistence_path: default_metrics_persistence_path(),
+            persistence_max_age: default_metrics_persistence_max_age(),
+            session_duration_buckets_secs: default_session_duration_buckets_secs(),
+            histogram_buckets: default_histogram_buckets(),
+            otlp: OtlpConfig::default(),
+            http_compression: HttpCompressionConfig::default(),
+        }
+    }
+}
+
+/// OTLP/gRPC metrics export.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OtlpConfig {
+    pub enabled: bool,
+    /// Collector gRPC endpoint
+    pub endpoint: String,
+    /// Time between exports
+    pub interval: ConfigDuration,
+    /// Metadata sent with every export, e.g. an API key
+    pub headers: BTreeMap<String, String>,
+}
+
+impl Default for OtlpConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            endpoint: default_otlp_endpoint(),
+            interval: default_otlp_interval(),
+            headers: BTreeMap::new(),
+        }
+    }
+}
+
+impl MetricsConfig {
+    pub fn validate_history(&self) -> anyhow::Result<()> {
+        if let Some(field) = self
+            .history_series
+            .iter()
+            .find(|field| !DashboardHistoryField::HISTORY_SERIES.contains(field))
+        {
+            anyhow::bail!(
+                "metrics.history_series cannot include {field:?}; supported series are \
+                 ActiveRooms, ActiveConnections, MessagesPerSec and ReconnectRate"
+            );
+        }
+        if self.history_capacity == 0 || self.history_capacity > METRICS_HISTORY_MAX_CAPACITY {
+            anyhow::bail!(
+                "metrics.history_capacity must be between 1 and {METRICS_HISTORY_MAX_CAPACITY}"
+            );
+        }
+        if self.history_sample_interval == ConfigDuration::ZERO {
+            anyhow::bail!("metrics.history_sample_interval must be greater than 0");
+        }
+        Ok(())
+    }
+}
+
+impl OtlpConfig {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+        let endpoint = self.endpoint.to_ascii_lowercase();
+        if !(endpoint.starts_with("http://") || endpoint.starts_with("https://")) {
+            anyhow::bail!("metrics.otlp.endpoint must be an http:// or https:// URL");
+        }
+        if self.interval == ConfigDuration::ZERO {
+            anyhow::bail!("metrics.otlp.interval must be greater than 0");
+        }
+        if let Some(name) = self.headers.keys().find(|name| {
+            name.is_empty()
+                || !name
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+        }) {
+            anyhow::bail!("metrics.otlp.headers contains an invalid header name {name:?}");
+        }
+        Ok(())
+    }
+}
+
+/// gzip for the metrics and admin HTTP responses.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpCompressionConfig {
+    pub enabled: bool,
+    /// Responses smaller than this are sent uncompressed
+    pub min_size: ByteSize,
+}
+
+impl Default for HttpCompressionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_size: default_http_compression_min_size(),
         }
     }
 }
@@ -17,9 +17,11 @@
 //! - [`coordination`]: Cross-instance coordination settings
 //! - [`metrics`]: Metrics configuration
 //! - [`websocket`]: WebSocket connection settings
+//! - [`storage`]: Room storage backend
 //! - [`crate::config::loader`]: Configuration loading functions
 //! - [`crate::config::validation`]: Configuration validation functions
 //! - [`crate::config::defaults`]: Default value functions
+//! - [`crate::config::reload`]: Settings applied when a running server reloads
 
 // Submodules
 pub mod coordination;
@@ -29,9 +31,12 @@ pub mod logging;
 pub mod metrics;
 pub mod protocol;
 pub mod relay;
+pub mod reload;
 pub mod security;
 pub mod server;
+pub mod storage;
 pub mod types;
+pub mod units;
 pub mod validation;
 pub mod websocket;
 
@@ -40,27 +45,38 @@ pub use coordination::{CoordinationConfig, DedupCacheConfig};
 
 pub use defaults::DashboardHistoryField;
 
-pub use loader::load;
+pub use loader::{load, load_from, load_from_reader, load_from_str, try_load, try_load_from};
 
 pub use logging::{LogFormat, LogLevel, LoggingConfig};
 
-pub use metrics::MetricsConfig;
+pub use metrics::{HttpCompressionConfig, MetricsConfig, OtlpConfig};
 
 pub use protocol::{
-    PlayerNameValidationConfig, ProtocolConfig, SdkCompatibilityConfig, SdkCompatibilityError,
-    SdkCompatibilityReport,
+    BannedWordMatch, DuplicateNamePolicy, PlayerNameValidationConfig, ProtocolConfig,
+    SdkCompatibilityConfig, SdkCompatibilityError, SdkCompatibilityReport,
 };
 
-pub use relay::RelayTypeConfig;
+pub use relay::{RelayFailurePolicy, RelayTypeConfig, RelayWarmupConfig, RelayWarmupTrigger};
+
+pub use reload::{non_reloadable_changes, RELOADABLE_SETTINGS};
 
 pub use security::{
-    AppAuthEntry, AuthMaintenanceConfig, ClientAuthMode, SecurityConfig, TlsServerConfig,
-    TokenBindingConfig, TransportSecurityConfig,
+    AppAuthEntry, AppQuotaConfig, AuthMaintenanceConfig, ClientAuthMode, CorsConfig, JwtAlgorithm,
+    JwtAuthConfig, SecurityConfig, TlsServerConfig, TokenBindingConfig, TransportSecurityConfig,
+};
+
+pub use server::{
+    JoinInterceptorFailurePolicy, RateLimitConfig, RateLimitKeyBy, RateLimitStrategy, ServerConfig,
+    TokenBucketConfig,
 };
 
-pub use server::{RateLimitConfig, ServerConfig};
+pub use storage::{StorageBackend, StorageConfig};
 
-pub use types::Config;
+pub use types::{
+    Config, GameConfig, GameOverrideConfig, IceServerConfig, WebhookConfig, WebhookEvent,
+};
+
+pub use units::{ByteSize, ConfigDuration, UnitParseError};
 
 pub use validation::{is_production_mode, validate_config_security};
 
@@ -76,14 +92,14 @@ mod tests {
 
         assert_eq!(config.port, 3536);
         assert_eq!(config.server.default_max_players, 8);
-        assert_eq!(config.server.ping_timeout, 30);
-        assert_eq!(config.server.room_cleanup_interval, 60);
+        assert_eq!(config.server.ping_timeout.as_secs(), 30);
+        assert_eq!(config.server.room_cleanup_interval.as_secs(), 60);
         assert_eq!(config.server.max_rooms_per_game, 1000);
-        assert_eq!(config.server.empty_room_timeout, 300);
-        assert_eq!(config.server.inactive_room_timeout, 3600);
+        assert_eq!(config.server.empty_room_timeout.as_secs(), 300);
+        assert_eq!(config.server.inactive_room_timeout.as_secs(), 3600);
 
         assert_eq!(config.rate_limit.max_room_creations, 5);
-        assert_eq!(config.rate_limit.time_window, 60);
+        assert_eq!(config.rate_limit.time_window.as_secs(), 60);
         assert_eq!(config.rate_limit.max_join_attempts, 20);
 
         assert_eq!(config.protocol.max_game_name_length, 64);
@@ -117,6 +133,218 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_webhook_config_defaults_and_validation() {
+        let webhook: WebhookConfig = serde_json::from_str(
+            r#"{"url": "https://hooks.example.com/rooms", "secret": "s3cret"}"#,
+        )
+        .unwrap();
+        assert_eq!(webhook.events, WebhookEvent::LIFECYCLE.to_vec());
+        assert_eq!(webhook.timeout_ms, 5000);
+        assert!(webhook.validate(0).is_ok());
+
+        let invalid = [
+            WebhookConfig {
+                url: "ftp://hooks.example.com".to_string(),
+                ..webhook.clone()
+            },
+            WebhookConfig {
+                secret: String::new(),
+                ..webhook.clone()
+            },
+            WebhookConfig {
+                events: Vec::new(),
+                ..webhook.clone()
+            },
+            WebhookConfig {
+                timeout_ms: 0,
+                ..webhook.clone()
+            },
+        ];
+        for webhook in invalid {
+            assert!(
+                webhook.validate(0).is_err(),
+                "{webhook:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_otlp_config_defaults_and_validation() {
+        let metrics: MetricsConfig =
+            serde_json::from_str(r#"{"otlp": {"enabled": true, "headers": {"x-api-key": "k3y"}}}"#)
+                .unwrap();
+        let otlp = metrics.otlp;
+        assert_eq!(otlp.endpoint, "http://localhost:4317");
+        assert_eq!(otlp.interval, ConfigDuration::from_secs(60));
+        assert!(otlp.validate().is_ok());
+
+        let invalid = [
+            OtlpConfig {
+                endpoint: "localhost:4317".to_string(),
+                ..otlp.clone()
+            },
+            OtlpConfig {
+                interval: ConfigDuration::ZERO,
+                ..otlp.clone()
+            },
+            OtlpConfig {
+                headers: [("bad header".to_string(), "v".to_string())].into(),
+                ..otlp.clone()
+            },
+        ];
+        for otlp in invalid {
+            assert!(otlp.validate().is_err(), "{otlp:?} should be rejected");
+        }
+        assert!(OtlpConfig {
+            enabled: false,
+            interval: ConfigDuration::ZERO,
+            ..otlp
+        }
+        .validate()
+        .is_ok());
+    }
+
+    #[test]
+    fn test_metrics_history_config_defaults_and_validation() {
+        let metrics: MetricsConfig = serde_json::from_str(
+            r#"{"history_series": ["ActiveRooms", "MessagesPerSec"], "history_sample_interval": "30s"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            metrics.history_series,
+            vec![
+                DashboardHistoryField::ActiveRooms,
+                DashboardHistoryField::MessagesPerSec
+            ]
+        );
+        assert_eq!(
+            metrics.history_sample_interval,
+            ConfigDuration::from_secs(30)
+        );
+        assert_eq!(metrics.history_capacity, 720);
+        assert!(metrics.validate_history().is_ok());
+        assert_eq!(
+            DashboardHistoryField::from_series_name("reconnect_rate"),
+            Some(DashboardHistoryField::ReconnectRate)
+        );
+        assert_eq!(
+            DashboardHistoryField::from_series_name("rooms_by_game"),
+            None
+        );
+
+        let invalid = [
+            MetricsConfig {
+                history_series: vec![DashboardHistoryField::RoomsByGame],
+                ..metrics.clone()
+            },
+            MetricsConfig {
+                history_capacity: 721,
+                ..metrics.clone()
+            },
+            MetricsConfig {
+                history_capacity: 0,
+                ..metrics.clone()
+            },
+            MetricsConfig {
+                history_sample_interval: ConfigDuration::ZERO,
+                ..metrics
+            },
+        ];
+        for metrics in invalid {
+            assert!(metrics.validate_history().is_err());
+        }
+    }
+
+    #[test]
+    fn test_ip_filter_config_parsing_and_validation() {
+        let config = load_from_str(
+            r#"{"security": {"require_metrics_auth": false, "ip_allowlist": "10.0.0.0/8", "ip_blocklist": ["10.0.0.7", "10.1.0.0/16"]}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.security.ip_allowlist,
+            Some(vec!["10.0.0.0/8".parse().unwrap()])
+        );
+        assert_eq!(config.security.ip_blocklist.len(), 2);
+        let filter = config.security.ip_filter();
+        assert!(filter.check("10.2.0.1".parse().unwrap()).is_ok());
+        assert!(filter.check("10.1.0.1".parse().unwrap()).is_err());
+        assert!(validate_config_security(&config).is_ok());
+
+        assert!(Config::default().security.ip_filter().is_empty());
+        assert!(load_from_str(r#"{"security": {"ip_blocklist": ["not-an-ip"]}}"#).is_err());
+
+        let mut empty_allowlist = config.clone();
+        empty_allowlist.security.ip_allowlist = Some(Vec::new());
+        assert!(validate_config_security(&empty_allowlist).is_err());
+    }
+
+    #[test]
+    fn test_game_override_validation() {
+        let valid = GameConfig {
+            room_code_length: Some(8),
+            room_code_charset: Some("0123456789".to_string()),
+            room_code_prefix: Some("pin".to_string()),
+            default_max_players: Some(2),
+            max_rooms_per_game: Some(50),
+            empty_room_timeout: Some(ConfigDuration::from_secs(300)),
+            inactive_room_timeout: Some(ConfigDuration::from_secs(7200)),
+        };
+        let protocol = ProtocolConfig::default();
+        assert!(valid.validate("pin-game", &protocol).is_ok());
+        assert!(GameConfig::default().validate("plain", &protocol).is_ok());
+
+        let invalid = [
+            GameConfig {
+                room_code_length: Some(3),
+                ..GameConfig::default()
+            },
+            GameConfig {
+                room_code_length: Some(17),
+                ..GameConfig::default()
+            },
+            GameConfig {
+                room_code_charset: Some("ABAB".to_string()),
+                ..GameConfig::default()
+            },
+            GameConfig {
+                room_code_charset: Some("abcd".to_string()),
+                ..GameConfig::default()
+            },
+            GameConfig {
+                room_code_prefix: Some("LOBBY1".to_string()),
+                ..GameConfig::default()
+            },
+            GameConfig {
+                default_max_players: Some(0),
+                ..GameConfig::default()
+            },
+            GameConfig {
+                default_max_players: Some(protocol.max_players_limit + 1),
+                ..GameConfig::default()
+            },
+            GameConfig {
+                max_rooms_per_game: Some(0),
+                ..GameConfig::default()
+            },
+            GameConfig {
+                empty_room_timeout: Some(ConfigDuration::ZERO),
+                ..GameConfig::default()
+            },
+            GameConfig {
+                inactive_room_timeout: Some(ConfigDuration::ZERO),
+                ..GameConfig::default()
+            },
+        ];
+        for game in invalid {
+            assert!(
+                game.validate("bad", &protocol).is_err(),
+                "{game:?} should be rejected"
+            );
+        }
+    }
+
     #[test]
     fn test_log_level_display() {
         assert_eq!(LogLevel::Trace.to_string(), "trace");
@@ -180,4 +408,20 @@ mod tests {
             Err(SdkCompatibilityError::PlatformUnknown { .. })
         ));
     }
+
+    #[test]
+    fn test_sdk_compatibility_upgrade_hints() {
+        let config = SdkCompatibilityConfig::default();
+        let err = config.evaluate(Some("Unity"), Some("1.5.0")).unwrap_err();
+        assert_eq!(err.platform(), Some("Unity"));
+        assert_eq!(config.minimum_version("Unity"), Some("1.10.0"));
+        assert_eq!(config.recommended_version("Unity"), Some("1.12.0"));
+        assert_eq!(config.recommended_version("godot"), None);
+        assert_eq!(SdkCompatibilityError::PlatformMissing.platform(), None);
+
+        let report = config.evaluate(Some("unity"), Some("1.12.0")).unwrap();
+        assert!(report.has_capability("reconnection"));
+        assert!(report.has_capability("relay-mirror"));
+        assert!(!report.has_capability("delivery-ack"));
+    }
 }
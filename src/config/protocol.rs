@@ -4,8 +4,9 @@ use super::defaults::{
     default_allow_leading_trailing_whitespace, default_allow_spaces_in_player_names,
     default_allow_unicode_player_names, default_allowed_player_name_symbols,
     default_enable_message_pack_game_data, default_max_game_name_length,
-    default_max_player_name_length, default_max_players_limit, default_room_code_length,
-    default_sdk_enforce,
+    default_max_player_name_length, default_max_players_limit, default_max_spectator_chat_length,
+    default_overflow_players_limit, default_room_code_length, default_sdk_enforce,
+    default_send_protocol_info,
 };
 use crate::protocol::GameDataEncoding;
 use serde::{Deserialize, Serialize};
@@ -26,6 +27,13 @@ pub struct ProtocolConfig {
     /// Maximum number of players allowed in a room
     #[serde(default = "default_max_players_limit")]
     pub max_players_limit: u8,
+    /// Maximum number of players in a room that allows overflow joins,
+    /// counting players its authority admitted beyond `max_players`
+    #[serde(default = "default_overflow_players_limit")]
+    pub overflow_players_limit: u8,
+    /// Maximum length of a spectator chat message, in characters
+    #[serde(default = "default_max_spectator_chat_length")]
+    pub max_spectator_chat_length: usize,
     /// Allow MessagePack (binary) payloads for game data transport.
     #[serde(default = "default_enable_message_pack_game_data")]
     pub enable_message_pack_game_data: bool,
@@ -35,6 +43,24 @@ pub struct ProtocolConfig {
     /// Player name validation rules
     #[serde(default)]
     pub player_name_validation: PlayerNameValidationConfig,
+    /// What to do when a joining player's name is already used in the room
+    #[serde(default)]
+    pub on_duplicate_name: DuplicateNamePolicy,
+    /// Send `ProtocolInfo` once per connection, before any join; disable to
+    /// save bandwidth when clients ship with the limits built in
+    #[serde(default = "default_send_protocol_info")]
+    pub send_protocol_info: bool,
+}
+
+/// How a join is handled when the player's name is already taken in the room.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicateNamePolicy {
+    /// Fail the join with `InvalidPlayerName`
+    #[default]
+    Reject,
+    /// Join under the name with the lowest free numeric suffix, e.g. "Alex (2)"
+    Suffix,
 }
 
 impl Default for ProtocolConfig {
@@ -44,9 +70,13 @@ impl Default for ProtocolConfig {
             room_code_length: default_room_code_length(),
             max_player_name_length: default_max_player_name_length(),
             max_players_limit: default_max_players_limit(),
+            overflow_players_limit: default_overflow_players_limit(),
+            max_spectator_chat_length: default_max_spectator_chat_length(),
             enable_message_pack_game_data: default_enable_message_pack_game_data(),
             sdk_compatibility: SdkCompatibilityConfig::default(),
             player_name_validation: PlayerNameValidationConfig::default(),
+            on_duplicate_name: DuplicateNamePolicy::default(),
+            send_protocol_info: default_send_protocol_info(),
         }
     }
 }
@@ -81,6 +111,25 @@ pub struct PlayerNameValidationConfig {
     /// Optional string of additional characters that should be accepted
     #[serde(default)]
     pub additional_allowed_characters: Option<String>,
+    /// Words a player name may not contain. Matching ignores case, full-width
+    /// forms and invisible characters
+    #[serde(default)]
+    pub banned_words: Vec<String>,
+    /// How `banned_words` are matched against a name
+    #[serde(default)]
+    pub banned_word_match: BannedWordMatch,
+}
+
+/// How banned words are matched against player names.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum BannedWordMatch {
+    /// Anywhere in the name, ignoring separators, so `b.a.d` matches `bad`.
+    /// Catches more evasions at the cost of false positives like "Scunthorpe"
+    #[default]
+    Substring,
+    /// Only as whole words, split on anything that is not a letter or digit
+    Word,
 }
 
 impl Default for PlayerNameValidationConfig {
@@ -91,11 +140,27 @@ impl Default for PlayerNameValidationConfig {
             allow_leading_trailing_whitespace: default_allow_leading_trailing_whitespace(),
             allowed_symbols: default_allowed_player_name_symbols(),
             additional_allowed_characters: None,
+            banned_words: Vec::new(),
+            banned_word_match: BannedWordMatch::default(),
         }
     }
 }
 
 impl PlayerNameValidationConfig {
+    /// Reject banned words that could never match a name.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if let Some(word) = self
+            .banned_words
+            .iter()
+            .find(|word| !word.chars().any(char::is_alphanumeric))
+        {
+            anyhow::bail!(
+                "protocol.player_name_validation.banned_words entry {word:?} must contain a letter or digit"
+            );
+        }
+        Ok(())
+    }
+
     pub fn is_allowed_symbol(&self, ch: char) -> bool {
         if self.allowed_symbols.contains(&ch) {
             return true;
@@ -175,6 +240,37 @@ impl Default for SdkCompatibilityConfig {
 }
 
 impl SdkCompatibilityConfig {
+    /// Report for a client that has not identified its SDK: the `_default`
+    /// capabilities and notes only.
+    pub fn default_report(&self) -> SdkCompatibilityReport {
+        SdkCompatibilityReport {
+            platform: None,
+            sdk_version: None,
+            minimum_version: None,
+            recommended_version: None,
+            capabilities: self
+                .capabilities
+                .get("_default")
+                .cloned()
+                .unwrap_or_default(),
+            notes: self.notes.get("_default").cloned(),
+        }
+    }
+
+    /// Minimum SDK version for `platform`, matched case-insensitively.
+    pub fn minimum_version(&self, platform: &str) -> Option<&str> {
+        self.minimum_versions
+            .get(&platform.to_ascii_lowercase())
+            .map(String::as_str)
+    }
+
+    /// Recommended SDK version for `platform`, matched case-insensitively.
+    pub fn recommended_version(&self, platform: &str) -> Option<&str> {
+        self.recommended_versions
+            .get(&platform.to_ascii_lowercase())
+            .map(String::as_str)
+    }
+
     /// Evaluate SDK compatibility for the provided platform/version tuple.
     pub fn evaluate(
         &self,
@@ -289,6 +385,13 @@ pub struct SdkCompatibilityReport {
     pub notes: Option<String>,
 }
 
+impl SdkCompatibilityReport {
+    /// Whether the client's SDK supports the optional feature `capability`.
+    pub fn has_capability(&self, capability: &str) -> bool {
+        self.capabilities.iter().any(|cap| cap == capability)
+    }
+}
+
 /// SDK compatibility error.
 #[derive(Debug, thiserror::Error)]
 pub enum SdkCompatibilityError {
@@ -314,3 +417,16 @@ pub enum SdkCompatibilityError {
         minimum: String,
     },
 }
+
+impl SdkCompatibilityError {
+    /// The platform the client identified as, if any.
+    pub fn platform(&self) -> Option<&str> {
+        match self {
+            Self::PlatformMissing => None,
+            Self::VersionMissing { platform }
+            | Self::PlatformUnknown { platform }
+            | Self::VersionInvalid { platform, .. }
+            | Self::VersionTooLow { platform, .. } => Some(platform),
+        }
+    }
+}
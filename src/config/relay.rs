@@ -1,6 +1,9 @@
 //! Relay type configuration.
 
-use super::defaults::default_relay_type;
+use super::defaults::{
+    default_relay_type, default_relay_warmup_max_attempts, default_relay_warmup_retry_delay_ms,
+};
+use super::units::{self, ConfigDuration};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -13,6 +16,21 @@ pub struct RelayTypeConfig {
     /// Default relay type for games not explicitly configured
     #[serde(default = "default_relay_type")]
     pub default_relay_type: String,
+    /// Map of region ids to relay types (e.g., "eu-west" -> "relay-eu"), used
+    /// when a game has no mapping and the client asks for "auto" or nothing
+    #[serde(default)]
+    pub region_relay_mappings: HashMap<String, String>,
+    /// Relay types clients may request explicitly. When empty, only relay
+    /// types named elsewhere in this config may be requested
+    #[serde(default)]
+    pub allowed_relay_types: Vec<String>,
+    /// Extra relay types clients may request on top of the standard transports
+    /// and the types allowed above, e.g. game-specific P2P libraries
+    #[serde(default)]
+    pub custom_relay_types: Vec<String>,
+    /// Ahead-of-time relay session provisioning
+    #[serde(default)]
+    pub warmup: RelayWarmupConfig,
 }
 
 impl Default for RelayTypeConfig {
@@ -20,6 +38,61 @@ impl Default for RelayTypeConfig {
         Self {
             game_relay_mappings: HashMap::new(),
             default_relay_type: default_relay_type(),
+            region_relay_mappings: HashMap::new(),
+            allowed_relay_types: Vec::new(),
+            custom_relay_types: Vec::new(),
+            warmup: RelayWarmupConfig::default(),
+        }
+    }
+}
+
+/// When a room's relay session is provisioned.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RelayWarmupTrigger {
+    /// As soon as the room is created
+    #[default]
+    RoomCreated,
+    /// When the room fills up and enters the lobby
+    Lobby,
+}
+
+/// What to do when no relay session is available at game start.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RelayFailurePolicy {
+    /// Start the game without relay session data
+    #[default]
+    Proceed,
+    /// Refuse to start the game until provisioning succeeds
+    Block,
+}
+
+/// Relay session warm-up settings.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct RelayWarmupConfig {
+    #[serde(default)]
+    pub trigger: RelayWarmupTrigger,
+    /// Attempts per provisioning run, including the first
+    #[serde(default = "default_relay_warmup_max_attempts")]
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled for each further attempt (milliseconds or e.g. "250ms")
+    #[serde(
+        default = "default_relay_warmup_retry_delay_ms",
+        with = "units::millis"
+    )]
+    pub retry_delay_ms: ConfigDuration,
+    #[serde(default)]
+    pub on_failure: RelayFailurePolicy,
+}
+
+impl Default for RelayWarmupConfig {
+    fn default() -> Self {
+        Self {
+            trigger: RelayWarmupTrigger::default(),
+            max_attempts: default_relay_warmup_max_attempts(),
+            retry_delay_ms: default_relay_warmup_retry_delay_ms(),
+            on_failure: RelayFailurePolicy::default(),
         }
     }
 }
@@ -0,0 +1,101 @@
+//! Which settings a running server can pick up on reload.
+
+use super::Config;
+use serde_json::Value;
+
+/// Settings applied by a reload (`SIGHUP`). A path also covers everything
+/// nested under it.
+pub const RELOADABLE_SETTINGS: &[&str] = &[
+    "rate_limit",
+    "server.ping_timeout",
+    "server.empty_room_timeout",
+    "server.inactive_room_timeout",
+    "server.expiry_warning_secs",
+    "security.cors_origins",
+    "security.cors",
+    "security.ip_allowlist",
+    "security.ip_blocklist",
+];
+
+/// Whether `path` (e.g. `rate_limit.max_join_attempts`) is applied on reload.
+pub fn is_reloadable(path: &str) -> bool {
+    RELOADABLE_SETTINGS.iter().any(|setting| {
+        path.strip_prefix(setting)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+    })
+}
+
+/// Paths of settings that differ between `current` and `reloaded` but only
+/// take effect after a restart, such as `port`. Values are not included, so
+/// the result is safe to log.
+pub fn non_reloadable_changes(current: &Config, reloaded: &Config) -> Vec<String> {
+    let (Ok(current), Ok(reloaded)) = (
+        serde_json::to_value(current),
+        serde_json::to_value(reloaded),
+    ) else {
+        return Vec::new();
+    };
+    let mut changes = Vec::new();
+    collect_changes(&current, &reloaded, String::new(), &mut changes);
+    changes.retain(|path| !is_reloadable(path));
+    changes
+}
+
+fn collect_changes(current: &Value, reloaded: &Value, path: String, changes: &mut Vec<String>) {
+    match (current, reloaded) {
+        (Value::Object(current), Value::Object(reloaded)) => {
+            let mut keys: Vec<&String> = current.keys().chain(reloaded.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let child = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                match (current.get(key), reloaded.get(key)) {
+                    (Some(current), Some(reloaded)) => {
+                        collect_changes(current, reloaded, child, changes);
+                    }
+                    _ => changes.push(child),
+                }
+            }
+        }
+        (current, reloaded) if current != reloaded => changes.push(path),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ConfigDuration;
+
+    #[test]
+    fn only_settings_outside_the_reloadable_set_are_reported() {
+        let current = Config::default();
+        let mut reloaded = Config {
+            port: current.port + 1,
+            ..Config::default()
+        };
+        reloaded.server.region_id = "eu-west".to_string();
+        reloaded.rate_limit.max_join_attempts += 1;
+        reloaded.server.ping_timeout = ConfigDuration::from_secs(5);
+        reloaded.security.cors_origins = "https://example.com".to_string();
+
+        assert_eq!(
+            non_reloadable_changes(&current, &reloaded),
+            ["port", "server.region_id"]
+        );
+        assert!(non_reloadable_changes(&current, &current).is_empty());
+    }
+
+    #[test]
+    fn reloadable_paths_match_whole_segments() {
+        assert!(is_reloadable("rate_limit"));
+        assert!(is_reloadable("rate_limit.room_creation_bucket.capacity"));
+        assert!(is_reloadable("server.ping_timeout"));
+        assert!(!is_reloadable("server.ping_timeout_extra"));
+        assert!(!is_reloadable("server.room_cleanup_interval"));
+    }
+}
@@ -1,10 +1,15 @@
 //! Security and authentication configuration types.
 
 use super::defaults::{
-    default_client_auth_mode, default_cors_origins, default_max_connections_per_ip,
+    default_client_auth_mode, default_cors_origins, default_ipv6_connection_prefix,
+    default_jwt_clock_skew_secs, default_jwt_required, default_max_connections_per_ip,
     default_max_message_size, default_require_auth, default_token_binding_subprotocol,
+    default_turn_credential_ttl_secs,
 };
+use super::units::{ByteSize, ConfigDuration};
+use crate::security::cidr::{deserialize_cidr_list, deserialize_optional_cidr_list};
 use crate::security::token_binding::TokenBindingScheme;
+use crate::security::{IpCidr, IpFilter, TrustedProxies};
 use serde::{Deserialize, Serialize};
 
 /// Security configuration.
@@ -13,6 +18,10 @@ pub struct SecurityConfig {
     /// Allowed CORS origins (comma-separated, or "*" for any)
     #[serde(default = "default_cors_origins")]
     pub cors_origins: String,
+    /// Separate CORS origins for the game and metrics routes; each defaults
+    /// to `cors_origins`
+    #[serde(default)]
+    pub cors: CorsConfig,
     /// Enable authentication for WebSocket connections
     #[serde(default = "default_require_auth")]
     pub require_websocket_auth: bool,
@@ -22,12 +31,32 @@ pub struct SecurityConfig {
     /// Authentication token for metrics endpoint (if required)
     #[serde(default)]
     pub metrics_auth_token: Option<String>,
-    /// Maximum WebSocket message size in bytes
+    /// Bearer token for the `/v2/admin` endpoints. Falls back to
+    /// `metrics_auth_token` when unset; admin endpoints are disabled when neither is set
+    #[serde(default)]
+    pub admin_auth_token: Option<String>,
+    /// Maximum WebSocket message size (bytes, or e.g. "64kb")
     #[serde(default = "default_max_message_size")]
-    pub max_message_size: usize,
+    pub max_message_size: ByteSize,
     /// Maximum connections per IP address
     #[serde(default = "default_max_connections_per_ip")]
     pub max_connections_per_ip: usize,
+    /// Reverse proxies (IP addresses or CIDR ranges) whose `Forwarded` /
+    /// `X-Forwarded-For` headers identify the client. Headers from any other
+    /// peer are ignored
+    #[serde(default)]
+    pub trust_forwarded_for: TrustedProxies,
+    /// IPv6 clients are limited per network of this prefix length rather than
+    /// per address
+    #[serde(default = "default_ipv6_connection_prefix")]
+    pub ipv6_connection_prefix: u8,
+    /// When set, only clients in these IP addresses or CIDR ranges are served;
+    /// everyone else gets 403. Checked after `ip_blocklist`
+    #[serde(default, deserialize_with = "deserialize_optional_cidr_list")]
+    pub ip_allowlist: Option<Vec<IpCidr>>,
+    /// Clients in these IP addresses or CIDR ranges get 403 on every route
+    #[serde(default, deserialize_with = "deserialize_cidr_list")]
+    pub ip_blocklist: Vec<IpCidr>,
     /// Transport-level security configuration (TLS, mTLS, token binding scaffolding)
     #[serde(default)]
     pub transport: TransportSecurityConfig,
@@ -37,23 +66,72 @@ pub struct SecurityConfig {
     /// an app_id matching one of these entries are accepted.
     #[serde(default)]
     pub authorized_apps: Vec<AppAuthEntry>,
+    /// Quotas for the `default` application, which owns rooms created by
+    /// clients that are not authenticated as one of `authorized_apps`
+    /// (every client while `require_websocket_auth` is off).
+    #[serde(default)]
+    pub default_app_quota: AppQuotaConfig,
 }
 
 impl Default for SecurityConfig {
     fn default() -> Self {
         Self {
             cors_origins: default_cors_origins(),
+            cors: CorsConfig::default(),
             require_websocket_auth: default_require_auth(),
             require_metrics_auth: default_require_auth(),
             metrics_auth_token: None,
+            admin_auth_token: None,
             max_message_size: default_max_message_size(),
             max_connections_per_ip: default_max_connections_per_ip(),
+            trust_forwarded_for: TrustedProxies::default(),
+            ipv6_connection_prefix: default_ipv6_connection_prefix(),
+            ip_allowlist: None,
+            ip_blocklist: Vec::new(),
             transport: TransportSecurityConfig::default(),
             authorized_apps: Vec::new(),
+            default_app_quota: AppQuotaConfig::default(),
         }
     }
 }
 
+impl SecurityConfig {
+    /// `ip_allowlist` and `ip_blocklist` as the filter applied to every request.
+    pub fn ip_filter(&self) -> IpFilter {
+        IpFilter::new(self.ip_allowlist.clone(), self.ip_blocklist.clone())
+    }
+
+    /// Origins allowed on the game routes under `/v2`.
+    pub fn game_cors_origins(&self) -> &str {
+        self.cors
+            .game_origins
+            .as_deref()
+            .unwrap_or(&self.cors_origins)
+    }
+
+    /// Origins allowed on the metrics, stats and admin routes.
+    pub fn metrics_cors_origins(&self) -> &str {
+        self.cors
+            .metrics_origins
+            .as_deref()
+            .unwrap_or(&self.cors_origins)
+    }
+}
+
+/// Per-route CORS origin lists, comma-separated or `*` for any origin.
+///
+/// The metrics group covers `/metrics*`, `/v1/metrics*` and the metrics,
+/// stats and admin endpoints under `/v2`; every other route is a game route.
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq)]
+pub struct CorsConfig {
+    /// Origins for the WebSocket, event stream and health routes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub game_origins: Option<String>,
+    /// Origins for the metrics, stats and admin endpoints
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metrics_origins: Option<String>,
+}
+
 /// Transport-level security configuration.
 #[derive(Debug, Deserialize, Serialize, Clone, Default)]
 pub struct TransportSecurityConfig {
@@ -61,10 +139,13 @@ pub struct TransportSecurityConfig {
     pub tls: TlsServerConfig,
     #[serde(default)]
     pub token_binding: TokenBindingConfig,
+    /// Bearer-token settings used when `tls.client_auth` is `jwt`.
+    #[serde(default)]
+    pub jwt: JwtAuthConfig,
 }
 
 /// TLS server configuration.
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Deserialize, Serialize, Clone)]
 pub struct TlsServerConfig {
     /// Enable HTTPS/TLS termination for the HTTP + WebSocket listener.
     #[serde(default)]
@@ -78,9 +159,18 @@ pub struct TlsServerConfig {
     /// Optional path to a PEM bundle of trusted client roots when client auth is enabled.
     #[serde(default)]
     pub client_ca_cert_path: Option<String>,
-    /// Whether client certificates are required.
+    /// Whether client certificates are required, or `jwt` to require an
+    /// `Authorization: Bearer` token on the WebSocket upgrade instead.
     #[serde(default = "default_client_auth_mode")]
     pub client_auth: ClientAuthMode,
+    /// Secret shared with the TURN servers in `server.ice_servers`. When set,
+    /// `turn:` servers without a static credential are handed time-limited
+    /// TURN REST credentials.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub turn_shared_secret: Option<String>,
+    /// How long generated TURN credentials stay valid
+    #[serde(default = "default_turn_credential_ttl_secs")]
+    pub turn_credential_ttl_secs: ConfigDuration,
 }
 
 impl Default for TlsServerConfig {
@@ -91,10 +181,30 @@ impl Default for TlsServerConfig {
             private_key_path: None,
             client_ca_cert_path: None,
             client_auth: default_client_auth_mode(),
+            turn_shared_secret: None,
+            turn_credential_ttl_secs: default_turn_credential_ttl_secs(),
         }
     }
 }
 
+// Hand-written so the TURN secret never reaches the logs.
+impl std::fmt::Debug for TlsServerConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TlsServerConfig")
+            .field("enabled", &self.enabled)
+            .field("certificate_path", &self.certificate_path)
+            .field("private_key_path", &self.private_key_path)
+            .field("client_ca_cert_path", &self.client_ca_cert_path)
+            .field("client_auth", &self.client_auth)
+            .field(
+                "turn_shared_secret",
+                &self.turn_shared_secret.as_ref().map(|_| "<redacted>"),
+            )
+            .field("turn_credential_ttl_secs", &self.turn_credential_ttl_secs)
+            .finish()
+    }
+}
+
 /// Optional token binding / zero-trust enforcement for WebSocket clients.
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct TokenBindingConfig {
@@ -135,6 +245,82 @@ pub enum ClientAuthMode {
     None,
     Optional,
     Require,
+    /// No client certificates; every WebSocket upgrade must carry a signed JWT
+    /// in the `Authorization: Bearer` header (see [`JwtAuthConfig`]).
+    /// Applies whether or not TLS termination is enabled.
+    Jwt,
+}
+
+/// JWT bearer-token verification settings.
+#[derive(Deserialize, Serialize, Clone)]
+pub struct JwtAuthConfig {
+    /// Signing algorithm tokens must use.
+    #[serde(default)]
+    pub algorithm: JwtAlgorithm,
+    /// Shared secret for HS256 tokens.
+    #[serde(default)]
+    pub secret: Option<String>,
+    /// Path to the PEM-encoded RSA public key for RS256 tokens.
+    #[serde(default)]
+    pub public_key_path: Option<String>,
+    /// Path to a JWKS document of RSA keys for RS256 tokens, selected by the
+    /// token's `kid`. Used instead of `public_key_path` when set.
+    #[serde(default)]
+    pub jwks_path: Option<String>,
+    /// Required `iss` claim; unchecked when unset.
+    #[serde(default)]
+    pub issuer: Option<String>,
+    /// Value the `aud` claim must contain; unchecked when unset.
+    #[serde(default)]
+    pub audience: Option<String>,
+    /// Reject upgrades without a token. When false, tokenless clients fall
+    /// back to the `Authenticate` handshake, but a token that is presented
+    /// must still be valid.
+    #[serde(default = "default_jwt_required")]
+    pub required: bool,
+    /// Tolerance applied to `exp`/`nbf` checks
+    #[serde(default = "default_jwt_clock_skew_secs")]
+    pub clock_skew_secs: ConfigDuration,
+}
+
+impl Default for JwtAuthConfig {
+    fn default() -> Self {
+        Self {
+            algorithm: JwtAlgorithm::default(),
+            secret: None,
+            public_key_path: None,
+            jwks_path: None,
+            issuer: None,
+            audience: None,
+            required: default_jwt_required(),
+            clock_skew_secs: default_jwt_clock_skew_secs(),
+        }
+    }
+}
+
+// Hand-written so the shared secret never reaches the logs.
+impl std::fmt::Debug for JwtAuthConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JwtAuthConfig")
+            .field("algorithm", &self.algorithm)
+            .field("secret", &self.secret.as_ref().map(|_| "<redacted>"))
+            .field("public_key_path", &self.public_key_path)
+            .field("jwks_path", &self.jwks_path)
+            .field("issuer", &self.issuer)
+            .field("audience", &self.audience)
+            .field("required", &self.required)
+            .field("clock_skew_secs", &self.clock_skew_secs)
+            .finish()
+    }
+}
+
+/// Supported JWT signing algorithms.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum JwtAlgorithm {
+    #[default]
+    HS256,
+    RS256,
 }
 
 /// A single authorized application entry loaded from configuration.
@@ -152,14 +338,49 @@ pub struct AppAuthEntry {
     /// Human-readable name returned to the client after authentication.
     pub app_name: String,
     /// Optional maximum number of rooms this application may create.
+    ///
+    /// Advisory only; reported to clients but not enforced. See
+    /// `max_rooms_per_app` for the enforced quota.
     #[serde(default)]
     pub max_rooms: Option<u32>,
+    /// Maximum number of rooms owned by this application at any one time.
+    /// Room creation is rejected once the quota is reached. `None` means
+    /// unlimited.
+    #[serde(default)]
+    pub max_rooms_per_app: Option<usize>,
+    /// Maximum players in this application's rooms at any one time. Joins
+    /// and room creation are rejected once the quota is reached. `None` means
+    /// unlimited.
+    #[serde(default)]
+    pub max_players_per_app: Option<usize>,
+    /// Maximum rooms this application may create in any rolling hour. `None`
+    /// means unlimited.
+    #[serde(default)]
+    pub max_rooms_per_hour: Option<u32>,
     /// Optional maximum number of players per room for this application.
     #[serde(default)]
     pub max_players_per_room: Option<u8>,
     /// Optional per-minute request rate limit for this application.
     #[serde(default)]
     pub rate_limit_per_minute: Option<u32>,
+    /// Static API keys that authenticate server-to-server clients as this
+    /// application via the `X-Api-Key` header on the WebSocket upgrade.
+    #[serde(default)]
+    pub api_keys: Vec<String>,
+}
+
+/// Usage quotas for one application; `None` means unlimited.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AppQuotaConfig {
+    /// Rooms owned by the application at any one time
+    #[serde(default)]
+    pub max_rooms_per_app: Option<usize>,
+    /// Players in the application's rooms at any one time
+    #[serde(default)]
+    pub max_players_per_app: Option<usize>,
+    /// Rooms the application may create in any rolling hour
+    #[serde(default)]
+    pub max_rooms_per_hour: Option<u32>,
 }
 
 /// Auth maintenance configuration.
@@ -1,12 +1,22 @@
 //! Server behavior configuration types.
 
 use super::defaults::{
+    default_broadcast_workers, default_closed_room_history_size, default_drain_retry_after_secs,
     default_empty_room_timeout, default_enable_reconnection, default_event_buffer_size,
-    default_heartbeat_throttle_secs, default_inactive_room_timeout, default_max_join_attempts,
-    default_max_players, default_max_room_creations, default_max_rooms_per_game,
-    default_ping_timeout, default_rate_limit_time_window, default_reconnection_window,
-    default_region_id, default_room_cleanup_interval,
+    default_expiry_warning_secs, default_flap_suppression_secs, default_heartbeat_throttle_secs,
+    default_inactive_room_timeout, default_ip_max_join_attempts, default_ip_max_room_creations,
+    default_isolated_broadcast_workers, default_isolation_auto_threshold,
+    default_join_interceptor_timeout_ms, default_join_request_timeout,
+    default_matchmaking_elo_window, default_matchmaking_tick_ms, default_max_join_attempts,
+    default_max_players, default_max_promotion_queue_size, default_max_room_creations,
+    default_max_rooms_per_game, default_max_token_age_secs, default_max_waiting_list_size,
+    default_ping_timeout, default_rate_limit_time_window, default_readiness_check_timeout_ms,
+    default_reconnection_window, default_region_id, default_room_cleanup_interval,
+    default_room_history_size, default_room_idempotency_window, default_spectator_chat_bucket,
+    default_stale_player_timeout,
 };
+use super::types::{IceServerConfig, WebhookConfig};
+use super::units::{self, ConfigDuration};
 use serde::{Deserialize, Serialize};
 
 /// Server configuration for room and player management.
@@ -15,44 +25,132 @@ pub struct ServerConfig {
     /// Default maximum players per room
     #[serde(default = "default_max_players")]
     pub default_max_players: u8,
-    /// Timeout for client ping responses (seconds)
+    /// Timeout for client ping responses (seconds or e.g. "30s")
     #[serde(default = "default_ping_timeout")]
-    pub ping_timeout: u64,
-    /// Interval for room cleanup task (seconds)
+    pub ping_timeout: ConfigDuration,
+    /// Interval for room cleanup task
     #[serde(default = "default_room_cleanup_interval")]
-    pub room_cleanup_interval: u64,
+    pub room_cleanup_interval: ConfigDuration,
     /// Maximum number of rooms per game
     #[serde(default = "default_max_rooms_per_game")]
     pub max_rooms_per_game: usize,
-    /// Time after creation when empty rooms expire (seconds)
+    /// Time after creation when empty rooms expire
     #[serde(default = "default_empty_room_timeout")]
-    pub empty_room_timeout: u64,
-    /// Time after last activity when rooms with players expire (seconds)
+    pub empty_room_timeout: ConfigDuration,
+    /// Time after last activity when rooms with players expire
     #[serde(default = "default_inactive_room_timeout")]
-    pub inactive_room_timeout: u64,
-    /// Time window for reconnection after disconnection (seconds)
+    pub inactive_room_timeout: ConfigDuration,
+    /// Seconds before a room's empty or inactive timeout to send `RoomExpirySoon` (0 disables)
+    #[serde(default = "default_expiry_warning_secs")]
+    pub expiry_warning_secs: u64,
+    /// Time window for reconnection after disconnection
     #[serde(default = "default_reconnection_window")]
-    pub reconnection_window: u64,
+    pub reconnection_window: ConfigDuration,
     /// Number of events to buffer per room for reconnection
     #[serde(default = "default_event_buffer_size")]
     pub event_buffer_size: usize,
     /// Enable player reconnection after disconnection
     #[serde(default = "default_enable_reconnection")]
     pub enable_reconnection: bool,
-    /// Threshold for heartbeat throttling (seconds).
+    /// Hold a disconnected player's seat this long before announcing that
+    /// they left; reconnecting sooner is invisible to the room (0 disables)
+    #[serde(default = "default_flap_suppression_secs")]
+    pub flap_suppression_secs: ConfigDuration,
+    /// Maximum age of a reconnection token in seconds, even if it is never rotated
+    #[serde(default = "default_max_token_age_secs")]
+    pub max_token_age_secs: u64,
+    /// Players awaiting reconnection into one room at once; past this the
+    /// longest-waiting player's slot is reclaimed (0 = unlimited)
+    #[serde(default)]
+    pub max_reconnection_slots_per_room: usize,
+    /// Threshold for heartbeat throttling.
     /// Controls how frequently heartbeat timestamps are recorded.
     /// Set to 0 to disable throttling (update on every heartbeat).
     #[serde(
         default = "default_heartbeat_throttle_secs",
         alias = "heartbeat_db_throttle_secs"
     )]
-    pub heartbeat_throttle_secs: u64,
+    pub heartbeat_throttle_secs: ConfigDuration,
+    /// Change in a player's reported round-trip time (milliseconds) that is
+    /// shared with their room as `PlayerLatencyUpdated`; unset disables
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_update_threshold_ms: Option<u32>,
     /// Identifier for the deployment region (used in player info and room codes).
     #[serde(default = "default_region_id")]
     pub region_id: String,
+    /// Other regions clients may name as their own in a `QuickJoin` hint.
+    #[serde(default)]
+    pub allowed_regions: Vec<String>,
     /// Optional prefix prepended to generated room codes.
     #[serde(default)]
     pub room_code_prefix: Option<String>,
+    /// Maximum number of players queued on a full room's waiting list (0 disables)
+    #[serde(default = "default_max_waiting_list_size")]
+    pub max_waiting_list_size: usize,
+    /// Maximum number of spectators queued for promotion to player per room (0 disables)
+    #[serde(default = "default_max_promotion_queue_size")]
+    pub max_promotion_queue_size: usize,
+    /// Maximum skill-rating spread between players grouped into one match
+    #[serde(default = "default_matchmaking_elo_window")]
+    pub matchmaking_elo_window: f32,
+    /// Interval between matchmaking passes (milliseconds or e.g. "500ms")
+    #[serde(default = "default_matchmaking_tick_ms", with = "units::millis")]
+    pub matchmaking_tick_ms: ConfigDuration,
+    /// Deadline for the readiness probe's checks (milliseconds or e.g. "1s")
+    #[serde(default = "default_readiness_check_timeout_ms", with = "units::millis")]
+    pub readiness_check_timeout_ms: ConfigDuration,
+    /// Retry hint given to joins refused while draining (seconds or e.g. "1m")
+    #[serde(default = "default_drain_retry_after_secs")]
+    pub drain_retry_after_secs: ConfigDuration,
+    /// Games whose game data broadcasts get dedicated workers; `"auto"` also
+    /// isolates any game above `isolation_auto_threshold`
+    #[serde(default)]
+    pub isolate_games: Vec<String>,
+    /// Broadcasts per second that trigger automatic isolation
+    #[serde(default = "default_isolation_auto_threshold")]
+    pub isolation_auto_threshold: u32,
+    /// Concurrent broadcasts shared by games that are not isolated
+    #[serde(default = "default_broadcast_workers")]
+    pub broadcast_workers: usize,
+    /// Concurrent broadcasts reserved for each isolated game
+    #[serde(default = "default_isolated_broadcast_workers")]
+    pub isolated_broadcast_workers: usize,
+    /// Replay recent game data to players after `RoomJoined` and `Reconnected`
+    #[serde(default)]
+    pub enable_history_replay: bool,
+    /// Game data messages kept per room for history replay
+    #[serde(default = "default_room_history_size")]
+    pub room_history_size: usize,
+    /// Deadline for the join interceptor hook (milliseconds or e.g. "500ms")
+    #[serde(
+        default = "default_join_interceptor_timeout_ms",
+        with = "units::millis"
+    )]
+    pub join_interceptor_timeout_ms: ConfigDuration,
+    /// How joins are resolved when the join interceptor fails or times out
+    #[serde(default)]
+    pub join_interceptor_failure_policy: JoinInterceptorFailurePolicy,
+    /// Endpoints notified of room lifecycle events
+    #[serde(default)]
+    pub webhooks: Vec<WebhookConfig>,
+    /// STUN/TURN servers sent to players in `RoomJoined`
+    #[serde(default)]
+    pub ice_servers: Vec<IceServerConfig>,
+    /// Closed room summaries kept for `/admin/rooms/recent-closed` (0 disables)
+    #[serde(default = "default_closed_room_history_size")]
+    pub closed_room_history_size: usize,
+    /// How long a `JoinRoom.idempotency_key` keeps resolving to the room it
+    /// created (0 disables idempotent creation)
+    #[serde(default = "default_room_idempotency_window")]
+    pub room_idempotency_window: ConfigDuration,
+    /// How long a room authority has to answer a `JoinRequest` before the
+    /// overflow join fails (seconds or e.g. "30s")
+    #[serde(default = "default_join_request_timeout")]
+    pub join_request_timeout: ConfigDuration,
+    /// How long a seated player may go without a heartbeat reaching the
+    /// database before cleanup removes them (seconds or e.g. "5m", 0 disables)
+    #[serde(default = "default_stale_player_timeout")]
+    pub stale_player_timeout: ConfigDuration,
 }
 
 impl Default for ServerConfig {
@@ -64,28 +162,138 @@ impl Default for ServerConfig {
             max_rooms_per_game: default_max_rooms_per_game(),
             empty_room_timeout: default_empty_room_timeout(),
             inactive_room_timeout: default_inactive_room_timeout(),
+            expiry_warning_secs: default_expiry_warning_secs(),
             reconnection_window: default_reconnection_window(),
             event_buffer_size: default_event_buffer_size(),
             enable_reconnection: default_enable_reconnection(),
+            flap_suppression_secs: default_flap_suppression_secs(),
+            max_token_age_secs: default_max_token_age_secs(),
+            max_reconnection_slots_per_room: 0,
             heartbeat_throttle_secs: default_heartbeat_throttle_secs(),
+            latency_update_threshold_ms: None,
             region_id: default_region_id(),
+            allowed_regions: Vec::new(),
             room_code_prefix: None,
+            max_waiting_list_size: default_max_waiting_list_size(),
+            max_promotion_queue_size: default_max_promotion_queue_size(),
+            matchmaking_elo_window: default_matchmaking_elo_window(),
+            matchmaking_tick_ms: default_matchmaking_tick_ms(),
+            readiness_check_timeout_ms: default_readiness_check_timeout_ms(),
+            drain_retry_after_secs: default_drain_retry_after_secs(),
+            isolate_games: Vec::new(),
+            isolation_auto_threshold: default_isolation_auto_threshold(),
+            broadcast_workers: default_broadcast_workers(),
+            isolated_broadcast_workers: default_isolated_broadcast_workers(),
+            enable_history_replay: false,
+            room_history_size: default_room_history_size(),
+            join_interceptor_timeout_ms: default_join_interceptor_timeout_ms(),
+            join_interceptor_failure_policy: JoinInterceptorFailurePolicy::default(),
+            webhooks: Vec::new(),
+            ice_servers: Vec::new(),
+            closed_room_history_size: default_closed_room_history_size(),
+            room_idempotency_window: default_room_idempotency_window(),
+            join_request_timeout: default_join_request_timeout(),
+            stale_player_timeout: default_stale_player_timeout(),
         }
     }
 }
 
+/// How a join is resolved when the join interceptor errors or times out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JoinInterceptorFailurePolicy {
+    /// Let the join through
+    FailOpen,
+    /// Refuse the join with `SERVICE_UNAVAILABLE`
+    #[default]
+    FailClosed,
+}
+
+/// Algorithm used to throttle room creation and join attempts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RateLimitStrategy {
+    /// Count requests per `time_window`; allows bursts at window edges
+    FixedWindow,
+    /// Refill tokens continuously; bursts are capped at the bucket capacity
+    #[default]
+    TokenBucket,
+    /// Log join attempts per client IP and admit `max_join_attempts` in any
+    /// trailing `time_window`; room creations keep the fixed window
+    SlidingWindow,
+}
+
+/// What identifies a client for room creation and join limits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RateLimitKeyBy {
+    /// Client IP address
+    #[default]
+    Ip,
+    /// Authenticated application, falling back to the client IP without auth
+    App,
+    /// Authenticated application, plus the looser `ip_max_*` limits per client IP
+    AppThenIp,
+}
+
+/// Token bucket sizing for one kind of request.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct TokenBucketConfig {
+    /// Maximum burst size
+    pub capacity: u32,
+    /// Tokens added per second
+    pub refill_per_sec: f64,
+}
+
 /// Rate limiting configuration.
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct RateLimitConfig {
     /// Maximum number of room creation requests per time window
     #[serde(default = "default_max_room_creations")]
     pub max_room_creations: u32,
-    /// Time window for rate limiting (seconds)
+    /// Time window for rate limiting
     #[serde(default = "default_rate_limit_time_window")]
-    pub time_window: u64,
+    pub time_window: ConfigDuration,
     /// Maximum number of join attempts per time window
     #[serde(default = "default_max_join_attempts")]
     pub max_join_attempts: u32,
+    /// `token_bucket` (uses the buckets below), `fixed_window` (uses the limits above)
+    /// or `sliding_window` (limits join attempts per client IP)
+    #[serde(default)]
+    pub strategy: RateLimitStrategy,
+    /// Room creation bucket when `strategy` is `token_bucket`; when unset, a
+    /// bucket of `max_room_creations` refilled over `time_window`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub room_creation_bucket: Option<TokenBucketConfig>,
+    /// Join attempt bucket when `strategy` is `token_bucket`; when unset, a
+    /// bucket of `max_join_attempts` refilled over `time_window`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub join_attempt_bucket: Option<TokenBucketConfig>,
+    /// Chat messages per spectator, always enforced as a token bucket
+    #[serde(default = "default_spectator_chat_bucket")]
+    pub spectator_chat_bucket: TokenBucketConfig,
+    /// Game data messages relayed per player, always enforced as a token
+    /// bucket; unset means unlimited
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub game_data_bucket: Option<TokenBucketConfig>,
+    /// New WebSocket connections per client IP (per IPv6 network, as for
+    /// `security.max_connections_per_ip`); the capacity is the burst a
+    /// reconnection storm may use. Unset means unlimited
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connection_bucket: Option<TokenBucketConfig>,
+    /// `ip`, `app` or `app_then_ip`
+    #[serde(default)]
+    pub key_by: RateLimitKeyBy,
+    /// Room creations per client IP per time window when `key_by` is `app_then_ip`
+    #[serde(default = "default_ip_max_room_creations")]
+    pub ip_max_room_creations: u32,
+    /// Join attempts per client IP per time window when `key_by` is `app_then_ip`
+    #[serde(default = "default_ip_max_join_attempts")]
+    pub ip_max_join_attempts: u32,
+    /// Rooms a single creator (keyed by `key_by`) may have open at once;
+    /// unset means unlimited
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_active_rooms_per_creator: Option<u32>,
 }
 
 impl Default for RateLimitConfig {
@@ -94,6 +302,16 @@ impl Default for RateLimitConfig {
             max_room_creations: default_max_room_creations(),
             time_window: default_rate_limit_time_window(),
             max_join_attempts: default_max_join_attempts(),
+            strategy: RateLimitStrategy::default(),
+            room_creation_bucket: None,
+            join_attempt_bucket: None,
+            spectator_chat_bucket: default_spectator_chat_bucket(),
+            game_data_bucket: None,
+            connection_bucket: None,
+            key_by: RateLimitKeyBy::default(),
+            ip_max_room_creations: default_ip_max_room_creations(),
+            ip_max_join_attempts: default_ip_max_join_attempts(),
+            max_active_rooms_per_creator: None,
         }
     }
 }
@@ -0,0 +1,51 @@
+//! Room storage backend configuration.
+
+use super::defaults::default_storage_path;
+use serde::{Deserialize, Serialize};
+
+/// Where rooms are stored.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    /// In process memory; rooms are lost on restart
+    #[default]
+    Memory,
+    /// A snapshot file that survives restarts (requires the `persistent` feature)
+    File,
+}
+
+/// Room storage settings.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct StorageConfig {
+    #[serde(default)]
+    pub backend: StorageBackend,
+    /// Snapshot file used by the `file` backend
+    #[serde(default = "default_storage_path")]
+    pub path: String,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            backend: StorageBackend::default(),
+            path: default_storage_path(),
+        }
+    }
+}
+
+impl StorageConfig {
+    /// Check that the selected backend is available in this build.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.backend == StorageBackend::File {
+            if !cfg!(feature = "persistent") {
+                anyhow::bail!(
+                    "storage.backend \"file\" requires a build with the `persistent` feature"
+                );
+            }
+            if self.path.trim().is_empty() {
+                anyhow::bail!("storage.path must be set for the file backend");
+            }
+        }
+        Ok(())
+    }
+}
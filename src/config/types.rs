@@ -1,15 +1,243 @@
 //! Root configuration types.
 
 use super::coordination::CoordinationConfig;
-use super::defaults::default_port;
+use super::defaults::{default_port, default_webhook_events, default_webhook_timeout_ms};
 use super::logging::LoggingConfig;
 use super::metrics::MetricsConfig;
 use super::protocol::ProtocolConfig;
 use super::relay::RelayTypeConfig;
 use super::security::{AuthMaintenanceConfig, SecurityConfig};
 use super::server::{RateLimitConfig, ServerConfig};
+use super::storage::StorageConfig;
+use super::units::ConfigDuration;
 use super::websocket::WebSocketConfig;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Shortest room code a game may configure.
+pub const MIN_GAME_ROOM_CODE_LENGTH: usize = 4;
+/// Longest room code a game may configure.
+pub const MAX_GAME_ROOM_CODE_LENGTH: usize = 16;
+
+/// Per-game overrides, keyed by game name.
+pub type GameOverrideConfig = HashMap<String, GameConfig>;
+
+/// Room code format, limits and timeouts for one game. Unset fields fall back
+/// to the protocol and `server` defaults.
+#[derive(Debug, Default, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct GameConfig {
+    /// Total room code length, including any prefix (4-16)
+    #[serde(default)]
+    pub room_code_length: Option<usize>,
+    /// Characters used for the random part of the code (e.g. "0123456789").
+    /// Must contain at least 4 distinct digits or uppercase letters
+    #[serde(default)]
+    pub room_code_charset: Option<String>,
+    /// Fixed prefix for this game's room codes; replaces `server.room_code_prefix`
+    #[serde(default)]
+    pub room_code_prefix: Option<String>,
+    /// Max players for rooms created without an explicit `max_players`;
+    /// replaces `server.default_max_players`
+    #[serde(default)]
+    pub default_max_players: Option<u8>,
+    /// Room cap for this game; replaces `server.max_rooms_per_game`
+    #[serde(default)]
+    pub max_rooms_per_game: Option<usize>,
+    /// Replaces `server.empty_room_timeout`
+    #[serde(default)]
+    pub empty_room_timeout: Option<ConfigDuration>,
+    /// Replaces `server.inactive_room_timeout`
+    #[serde(default)]
+    pub inactive_room_timeout: Option<ConfigDuration>,
+}
+
+impl GameConfig {
+    /// Validate this game's overrides against the protocol limits.
+    pub fn validate(&self, game_name: &str, protocol: &ProtocolConfig) -> anyhow::Result<()> {
+        let default_length = protocol.room_code_length;
+        if let Some(length) = self.room_code_length {
+            if !(MIN_GAME_ROOM_CODE_LENGTH..=MAX_GAME_ROOM_CODE_LENGTH).contains(&length) {
+                anyhow::bail!(
+                    "game_overrides.{game_name}.room_code_length must be between \
+                     {MIN_GAME_ROOM_CODE_LENGTH} and {MAX_GAME_ROOM_CODE_LENGTH} (configured: {length})"
+                );
+            }
+        }
+        if let Some(charset) = &self.room_code_charset {
+            // Joins uppercase the submitted code, so lowercase characters could never match
+            if !charset
+                .chars()
+                .all(|c| c.is_ascii_digit() || c.is_ascii_uppercase())
+            {
+                anyhow::bail!(
+                    "game_overrides.{game_name}.room_code_charset may only contain digits and uppercase letters"
+                );
+            }
+            let mut distinct: Vec<char> = charset.chars().collect();
+            distinct.sort_unstable();
+            distinct.dedup();
+            if distinct.len() < 4 {
+                anyhow::bail!(
+                    "game_overrides.{game_name}.room_code_charset must contain at least 4 distinct characters"
+                );
+            }
+        }
+        if let Some(prefix) = &self.room_code_prefix {
+            let prefix = prefix.trim();
+            if !prefix.chars().all(|c| c.is_ascii_alphanumeric()) {
+                anyhow::bail!("game_overrides.{game_name}.room_code_prefix must be alphanumeric");
+            }
+            let length = self.room_code_length.unwrap_or(default_length);
+            if prefix.len() >= length {
+                anyhow::bail!(
+                    "game_overrides.{game_name}.room_code_prefix must be shorter than the room code length ({length})"
+                );
+            }
+        }
+        if let Some(max_players) = self.default_max_players {
+            if max_players == 0 || max_players > protocol.max_players_limit {
+                anyhow::bail!(
+                    "game_overrides.{game_name}.default_max_players must be between 1 and {} (configured: {max_players})",
+                    protocol.max_players_limit
+                );
+            }
+        }
+        if self.max_rooms_per_game == Some(0) {
+            anyhow::bail!("game_overrides.{game_name}.max_rooms_per_game must be greater than 0");
+        }
+        if self.empty_room_timeout == Some(ConfigDuration::ZERO) {
+            anyhow::bail!("game_overrides.{game_name}.empty_room_timeout must be greater than 0");
+        }
+        if self.inactive_room_timeout == Some(ConfigDuration::ZERO) {
+            anyhow::bail!(
+                "game_overrides.{game_name}.inactive_room_timeout must be greater than 0"
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Events a webhook can subscribe to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    RoomCreated,
+    RoomClosed,
+    PlayerJoined,
+    PlayerLeft,
+    /// A player report from `ReportPlayer`; only delivered when listed
+    PlayerReported,
+}
+
+impl WebhookEvent {
+    pub const ALL: [Self; 5] = [
+        Self::RoomCreated,
+        Self::RoomClosed,
+        Self::PlayerJoined,
+        Self::PlayerLeft,
+        Self::PlayerReported,
+    ];
+
+    /// Room lifecycle events, delivered when a webhook lists no events.
+    pub const LIFECYCLE: [Self; 4] = [
+        Self::RoomCreated,
+        Self::RoomClosed,
+        Self::PlayerJoined,
+        Self::PlayerLeft,
+    ];
+
+    /// Name used in the payload's `event_type` and the event header.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::RoomCreated => "room_created",
+            Self::RoomClosed => "room_closed",
+            Self::PlayerJoined => "player_joined",
+            Self::PlayerLeft => "player_left",
+            Self::PlayerReported => "player_reported",
+        }
+    }
+}
+
+/// An HTTP endpoint notified of room lifecycle events.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct WebhookConfig {
+    /// `http://` or `https://` URL that receives the `POST`
+    pub url: String,
+    /// Key for the HMAC-SHA256 signature sent in `X-Signal-Fish-Signature`
+    pub secret: String,
+    /// Events to deliver; the room lifecycle events when omitted
+    #[serde(default = "default_webhook_events")]
+    pub events: Vec<WebhookEvent>,
+    /// Deadline for each delivery attempt, in milliseconds
+    #[serde(default = "default_webhook_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+impl WebhookConfig {
+    /// Validate the webhook at position `index` in `server.webhooks`.
+    pub fn validate(&self, index: usize) -> anyhow::Result<()> {
+        let url = self.url.to_ascii_lowercase();
+        if !(url.starts_with("http://") || url.starts_with("https://")) {
+            anyhow::bail!("server.webhooks[{index}].url must be an http:// or https:// URL");
+        }
+        if self.secret.is_empty() {
+            anyhow::bail!("server.webhooks[{index}].secret must not be empty");
+        }
+        if self.events.is_empty() {
+            anyhow::bail!("server.webhooks[{index}].events must list at least one event");
+        }
+        if self.timeout_ms == 0 {
+            anyhow::bail!("server.webhooks[{index}].timeout_ms must be greater than 0");
+        }
+        Ok(())
+    }
+}
+
+/// A STUN or TURN server handed to clients for WebRTC connections.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct IceServerConfig {
+    /// `stun:`, `stuns:`, `turn:` or `turns:` URLs of the server
+    pub urls: Vec<String>,
+    /// Static TURN username; omit to use generated TURN REST credentials
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    /// Static TURN password, set together with `username`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credential: Option<String>,
+}
+
+impl IceServerConfig {
+    /// Whether any of the server's URLs is a TURN URL.
+    pub fn is_turn(&self) -> bool {
+        self.urls.iter().any(|url| {
+            let url = url.to_ascii_lowercase();
+            url.starts_with("turn:") || url.starts_with("turns:")
+        })
+    }
+
+    /// Validate the ICE server at position `index` in `server.ice_servers`.
+    pub fn validate(&self, index: usize) -> anyhow::Result<()> {
+        if self.urls.is_empty() {
+            anyhow::bail!("server.ice_servers[{index}].urls must list at least one URL");
+        }
+        for url in &self.urls {
+            let scheme = url
+                .split_once(':')
+                .map(|(scheme, _)| scheme.to_ascii_lowercase());
+            if !matches!(scheme.as_deref(), Some("stun" | "stuns" | "turn" | "turns")) {
+                anyhow::bail!(
+                    "server.ice_servers[{index}].urls entry \"{url}\" must be a stun:, stuns:, turn: or turns: URL"
+                );
+            }
+        }
+        if self.username.is_some() != self.credential.is_some() {
+            anyhow::bail!(
+                "server.ice_servers[{index}] must set both username and credential, or neither"
+            );
+        }
+        Ok(())
+    }
+}
 
 /// Root configuration struct for Signal Fish.
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -36,6 +264,11 @@ pub struct Config {
     pub relay_types: RelayTypeConfig,
     #[serde(default)]
     pub websocket: WebSocketConfig,
+    #[serde(default)]
+    pub storage: StorageConfig,
+    /// Per-game room code formats
+    #[serde(default)]
+    pub game_overrides: GameOverrideConfig,
 }
 
 impl Default for Config {
@@ -52,6 +285,8 @@ impl Default for Config {
             metrics: MetricsConfig::default(),
             relay_types: RelayTypeConfig::default(),
             websocket: WebSocketConfig::default(),
+            storage: StorageConfig::default(),
+            game_overrides: GameOverrideConfig::new(),
         }
     }
 }
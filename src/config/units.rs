@@ -0,0 +1,488 @@
+//! Typed duration and byte-size configuration values.
+//!
+//! Both types accept either a bare number (interpreted in the field's legacy
+//! unit, so existing config files keep working) or a human-friendly string
+//! such as `"90s"`, `"5m"` or `"512kb"`. They always serialize back to the
+//! canonical numeric form so `--print-config` output can be fed straight back
+//! into the loader.
+
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::time::Duration;
+use thiserror::Error;
+
+/// Accepted duration formats, quoted in parse errors.
+const DURATION_FORMATS: &str =
+    "a number or a string such as \"500ms\", \"90s\", \"5m\", \"2h\", \"1d\" or \"1h30m\"";
+
+/// Accepted byte-size formats, quoted in parse errors.
+const BYTE_SIZE_FORMATS: &str =
+    "a number of bytes or a string such as \"512b\", \"64kb\", \"1mb\" or \"1gb\" (1kb = 1024 bytes)";
+
+/// Error returned when a duration or byte-size string cannot be parsed.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("invalid {kind} {input:?}: expected {expected}")]
+pub struct UnitParseError {
+    kind: &'static str,
+    input: String,
+    expected: &'static str,
+}
+
+impl UnitParseError {
+    fn duration(input: &str) -> Self {
+        Self {
+            kind: "duration",
+            input: input.to_string(),
+            expected: DURATION_FORMATS,
+        }
+    }
+
+    fn byte_size(input: &str) -> Self {
+        Self {
+            kind: "byte size",
+            input: input.to_string(),
+            expected: BYTE_SIZE_FORMATS,
+        }
+    }
+}
+
+// =============================================================================
+// Durations
+// =============================================================================
+
+/// A configuration duration.
+///
+/// Bare numbers are seconds unless the field opts into [`millis`]. Strings are
+/// one or more `<number><unit>` pairs with units `ms`, `s`, `m`, `h` or `d`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ConfigDuration(Duration);
+
+impl ConfigDuration {
+    pub const ZERO: Self = Self(Duration::ZERO);
+
+    pub const fn from_secs(secs: u64) -> Self {
+        Self(Duration::from_secs(secs))
+    }
+
+    pub const fn from_millis(millis: u64) -> Self {
+        Self(Duration::from_millis(millis))
+    }
+
+    pub const fn as_duration(self) -> Duration {
+        self.0
+    }
+
+    pub const fn as_secs(self) -> u64 {
+        self.0.as_secs()
+    }
+
+    /// Whole milliseconds, saturating at `u64::MAX`.
+    pub fn as_millis(self) -> u64 {
+        u64::try_from(self.0.as_millis()).unwrap_or(u64::MAX)
+    }
+
+    /// Parse a human-readable duration; bare numbers are seconds.
+    pub fn parse(input: &str) -> Result<Self, UnitParseError> {
+        parse_duration(input, Duration::from_secs(1))
+    }
+
+    /// Human-readable form, e.g. `"5m"` or `"1h30m"`.
+    pub fn to_human(self) -> String {
+        let mut millis = self.0.as_millis();
+        if millis == 0 {
+            return "0s".to_string();
+        }
+        let mut out = String::new();
+        for (unit, size) in [
+            ("d", 86_400_000u128),
+            ("h", 3_600_000),
+            ("m", 60_000),
+            ("s", 1_000),
+            ("ms", 1),
+        ] {
+            if millis >= size {
+                out.push_str(&format!("{}{unit}", millis / size));
+                millis %= size;
+            }
+        }
+        out
+    }
+}
+
+impl From<ConfigDuration> for Duration {
+    fn from(value: ConfigDuration) -> Self {
+        value.0
+    }
+}
+
+impl From<Duration> for ConfigDuration {
+    fn from(value: Duration) -> Self {
+        Self(value)
+    }
+}
+
+impl fmt::Display for ConfigDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_human())
+    }
+}
+
+impl Serialize for ConfigDuration {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.0.subsec_nanos() == 0 {
+            serializer.serialize_u64(self.0.as_secs())
+        } else {
+            serializer.serialize_f64(self.0.as_secs_f64())
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ConfigDuration {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(DurationVisitor {
+            number_unit: Duration::from_secs(1),
+        })
+    }
+}
+
+/// Serde adapter for duration fields whose bare numbers are milliseconds
+/// (e.g. `batch_interval_ms`). Use with `#[serde(with = "units::millis")]`.
+pub mod millis {
+    use super::{ConfigDuration, DurationVisitor};
+    use serde::{Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(
+        value: &ConfigDuration,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(value.as_millis())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<ConfigDuration, D::Error> {
+        deserializer.deserialize_any(DurationVisitor {
+            number_unit: Duration::from_millis(1),
+        })
+    }
+}
+
+struct DurationVisitor {
+    number_unit: Duration,
+}
+
+impl Visitor<'_> for DurationVisitor {
+    type Value = ConfigDuration;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(DURATION_FORMATS)
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+        u32::try_from(value)
+            .ok()
+            .and_then(|count| self.number_unit.checked_mul(count))
+            .map(ConfigDuration)
+            .ok_or_else(|| E::custom(UnitParseError::duration(&value.to_string())))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Self::Value, E> {
+        match u64::try_from(value) {
+            Ok(value) => self.visit_u64(value),
+            Err(_) => Err(E::custom(UnitParseError::duration(&value.to_string()))),
+        }
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Self::Value, E> {
+        scale(self.number_unit, value)
+            .map(ConfigDuration)
+            .ok_or_else(|| E::custom(UnitParseError::duration(&value.to_string())))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        parse_duration(value, self.number_unit).map_err(E::custom)
+    }
+}
+
+fn scale(unit: Duration, count: f64) -> Option<Duration> {
+    if !count.is_finite() || count < 0.0 {
+        return None;
+    }
+    Duration::try_from_secs_f64(unit.as_secs_f64() * count).ok()
+}
+
+fn parse_duration(input: &str, number_unit: Duration) -> Result<ConfigDuration, UnitParseError> {
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        return Err(UnitParseError::duration(input));
+    }
+    if let Ok(count) = trimmed.parse::<f64>() {
+        return scale(number_unit, count)
+            .map(ConfigDuration)
+            .ok_or_else(|| UnitParseError::duration(input));
+    }
+
+    let mut total = Duration::ZERO;
+    for (count, unit) in split_pairs(trimmed).ok_or_else(|| UnitParseError::duration(input))? {
+        let unit = match unit.to_ascii_lowercase().as_str() {
+            "ms" => Duration::from_millis(1),
+            "s" | "sec" | "secs" => Duration::from_secs(1),
+            "m" | "min" | "mins" => Duration::from_secs(60),
+            "h" | "hr" | "hrs" => Duration::from_secs(3_600),
+            "d" | "day" | "days" => Duration::from_secs(86_400),
+            _ => return Err(UnitParseError::duration(input)),
+        };
+        let part = scale(unit, count).ok_or_else(|| UnitParseError::duration(input))?;
+        total = total
+            .checked_add(part)
+            .ok_or_else(|| UnitParseError::duration(input))?;
+    }
+    Ok(ConfigDuration(total))
+}
+
+/// Split `"1h30m"` / `"1.5 mb"` into `(number, unit)` pairs.
+fn split_pairs(input: &str) -> Option<Vec<(f64, &str)>> {
+    let mut pairs = Vec::new();
+    let mut rest = input.trim_start();
+    while !rest.is_empty() {
+        let number_end = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let count: f64 = rest[..number_end].parse().ok()?;
+        rest = rest[number_end..].trim_start();
+        let unit_end = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        if unit_end == 0 {
+            return None;
+        }
+        pairs.push((count, &rest[..unit_end]));
+        rest = rest[unit_end..].trim_start();
+    }
+    (!pairs.is_empty()).then_some(pairs)
+}
+
+// =============================================================================
+// Byte sizes
+// =============================================================================
+
+/// A configuration size in bytes. Unit suffixes are binary (1kb = 1024 bytes).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ByteSize(u64);
+
+impl ByteSize {
+    pub const fn from_bytes(bytes: u64) -> Self {
+        Self(bytes)
+    }
+
+    pub const fn from_kib(kib: u64) -> Self {
+        Self(kib * 1024)
+    }
+
+    pub const fn as_bytes(self) -> u64 {
+        self.0
+    }
+
+    /// Size as `usize`, saturating on 32-bit targets.
+    pub fn as_usize(self) -> usize {
+        usize::try_from(self.0).unwrap_or(usize::MAX)
+    }
+
+    /// Parse a human-readable size; bare numbers are bytes.
+    pub fn parse(input: &str) -> Result<Self, UnitParseError> {
+        let trimmed = input.trim();
+        if let Ok(bytes) = trimmed.parse::<u64>() {
+            return Ok(Self(bytes));
+        }
+        let pairs = split_pairs(trimmed).ok_or_else(|| UnitParseError::byte_size(input))?;
+        let [(count, unit)] = pairs.as_slice() else {
+            return Err(UnitParseError::byte_size(input));
+        };
+        let multiplier: u64 = match unit.to_ascii_lowercase().as_str() {
+            "b" => 1,
+            "k" | "kb" | "kib" => 1 << 10,
+            "m" | "mb" | "mib" => 1 << 20,
+            "g" | "gb" | "gib" => 1 << 30,
+            _ => return Err(UnitParseError::byte_size(input)),
+        };
+        let bytes = count * multiplier as f64;
+        if !bytes.is_finite() || bytes > u64::MAX as f64 || bytes.fract() != 0.0 {
+            return Err(UnitParseError::byte_size(input));
+        }
+        Ok(Self(bytes as u64))
+    }
+
+    /// Human-readable form, e.g. `"64kb"`, falling back to bytes.
+    pub fn to_human(self) -> String {
+        for (unit, size) in [("gb", 1u64 << 30), ("mb", 1 << 20), ("kb", 1 << 10)] {
+            if self.0 >= size && self.0 % size == 0 {
+                return format!("{}{unit}", self.0 / size);
+            }
+        }
+        format!("{}b", self.0)
+    }
+}
+
+impl fmt::Display for ByteSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_human())
+    }
+}
+
+impl Serialize for ByteSize {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for ByteSize {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ByteSizeVisitor;
+
+        impl Visitor<'_> for ByteSizeVisitor {
+            type Value = ByteSize;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(BYTE_SIZE_FORMATS)
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+                Ok(ByteSize(value))
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<Self::Value, E> {
+                u64::try_from(value)
+                    .map(ByteSize)
+                    .map_err(|_| E::custom(UnitParseError::byte_size(&value.to_string())))
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+                ByteSize::parse(value).map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(ByteSizeVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Deserialize, Serialize)]
+    struct Sample {
+        timeout: ConfigDuration,
+        #[serde(with = "millis")]
+        interval_ms: ConfigDuration,
+        size: ByteSize,
+    }
+
+    fn sample(json: serde_json::Value) -> Result<Sample, serde_json::Error> {
+        serde_json::from_value(json)
+    }
+
+    #[test]
+    fn numbers_keep_legacy_units() {
+        let parsed =
+            sample(serde_json::json!({"timeout": 90, "interval_ms": 16, "size": 65536})).unwrap();
+        assert_eq!(parsed.timeout.as_duration(), Duration::from_secs(90));
+        assert_eq!(parsed.interval_ms.as_duration(), Duration::from_millis(16));
+        assert_eq!(parsed.size.as_bytes(), 65536);
+    }
+
+    #[test]
+    fn human_strings_are_accepted() {
+        let parsed = sample(serde_json::json!({
+            "timeout": "1h30m",
+            "interval_ms": "0.5s",
+            "size": "1mb"
+        }))
+        .unwrap();
+        assert_eq!(parsed.timeout.as_duration(), Duration::from_secs(5_400));
+        assert_eq!(parsed.interval_ms.as_duration(), Duration::from_millis(500));
+        assert_eq!(parsed.size.as_bytes(), 1 << 20);
+
+        for (input, secs) in [
+            ("90s", 90),
+            ("5m", 300),
+            ("2h", 7_200),
+            ("1d", 86_400),
+            ("45", 45),
+        ] {
+            assert_eq!(
+                ConfigDuration::parse(input).unwrap().as_secs(),
+                secs,
+                "{input}"
+            );
+        }
+        for (input, bytes) in [
+            ("512kb", 512 * 1024),
+            ("64KiB", 64 * 1024),
+            ("100b", 100),
+            ("1.5mb", 1_572_864),
+        ] {
+            assert_eq!(ByteSize::parse(input).unwrap().as_bytes(), bytes, "{input}");
+        }
+    }
+
+    #[test]
+    fn invalid_strings_report_accepted_formats() {
+        for input in ["", "soon", "5x", "-3s", "m5"] {
+            let err = ConfigDuration::parse(input).unwrap_err().to_string();
+            assert!(err.contains("\"90s\""), "{input}: {err}");
+        }
+        for input in ["lots", "1tb", "0.5b"] {
+            let err = ByteSize::parse(input).unwrap_err().to_string();
+            assert!(err.contains("\"512b\""), "{input}: {err}");
+        }
+
+        let err = sample(serde_json::json!({"timeout": "5x", "interval_ms": 1, "size": 1}))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("invalid duration \"5x\""), "{err}");
+        let err = sample(serde_json::json!({"timeout": -1, "interval_ms": 1, "size": 1}))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("invalid duration"), "{err}");
+    }
+
+    #[test]
+    fn serialization_round_trips_to_numeric_form() {
+        let parsed = sample(serde_json::json!({
+            "timeout": "5m",
+            "interval_ms": "250ms",
+            "size": "64kb"
+        }))
+        .unwrap();
+        let json = serde_json::to_value(&parsed).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"timeout": 300, "interval_ms": 250, "size": 65536})
+        );
+
+        let reparsed = sample(json).unwrap();
+        assert_eq!(reparsed.timeout, parsed.timeout);
+        assert_eq!(reparsed.interval_ms, parsed.interval_ms);
+        assert_eq!(reparsed.size, parsed.size);
+
+        let fractional = ConfigDuration::parse("1500ms").unwrap();
+        let json = serde_json::to_value(fractional).unwrap();
+        assert_eq!(json, serde_json::json!(1.5));
+        assert_eq!(
+            serde_json::from_value::<ConfigDuration>(json).unwrap(),
+            fractional
+        );
+    }
+
+    #[test]
+    fn human_form_is_compact() {
+        assert_eq!(ConfigDuration::from_secs(5_400).to_human(), "1h30m");
+        assert_eq!(ConfigDuration::from_millis(250).to_human(), "250ms");
+        assert_eq!(ConfigDuration::ZERO.to_human(), "0s");
+        assert_eq!(ByteSize::from_kib(64).to_human(), "64kb");
+        assert_eq!(ByteSize::from_bytes(1000).to_human(), "1000b");
+    }
+}
@@ -1,6 +1,8 @@
 //! Configuration validation functions.
 
-use super::security::ClientAuthMode;
+use super::security::{ClientAuthMode, JwtAlgorithm};
+use super::server::RateLimitStrategy;
+use super::units::ConfigDuration;
 use super::Config;
 use std::path::Path;
 
@@ -54,6 +56,33 @@ pub fn validate_config_security(config: &Config) -> anyhow::Result<()> {
         );
     }
 
+    if config
+        .security
+        .admin_auth_token
+        .as_ref()
+        .is_some_and(|token| token.trim().is_empty())
+    {
+        anyhow::bail!("security.admin_auth_token must not be empty when set");
+    }
+
+    if !(1..=128).contains(&config.security.ipv6_connection_prefix) {
+        anyhow::bail!(
+            "security.ipv6_connection_prefix must be between 1 and 128, got {}",
+            config.security.ipv6_connection_prefix
+        );
+    }
+
+    if config
+        .security
+        .ip_allowlist
+        .as_ref()
+        .is_some_and(Vec::is_empty)
+    {
+        anyhow::bail!(
+            "security.ip_allowlist must not be empty when set; omit it to allow every address"
+        );
+    }
+
     // TLS validation
     if config.security.transport.tls.enabled {
         let tls = &config.security.transport.tls;
@@ -106,6 +135,59 @@ pub fn validate_config_security(config: &Config) -> anyhow::Result<()> {
         }
     }
 
+    // API key validation: keys must be non-empty and bound to a single app
+    let mut seen_api_keys = std::collections::HashSet::new();
+    for app in &config.security.authorized_apps {
+        for key in &app.api_keys {
+            if key.trim().is_empty() {
+                anyhow::bail!(
+                    "security.authorized_apps entry \"{}\" has an empty API key",
+                    app.app_id
+                );
+            }
+            if !seen_api_keys.insert(key.as_str()) {
+                anyhow::bail!(
+                    "security.authorized_apps entry \"{}\" reuses an API key that is already \
+                     bound to another app",
+                    app.app_id
+                );
+            }
+        }
+    }
+
+    // JWT bearer auth validation
+    if config.security.transport.tls.client_auth == ClientAuthMode::Jwt {
+        let jwt = &config.security.transport.jwt;
+        match jwt.algorithm {
+            JwtAlgorithm::HS256 => {
+                let secret_present = jwt.secret.as_deref().is_some_and(|s| !s.is_empty());
+                if !secret_present {
+                    anyhow::bail!(
+                        "security.transport.jwt.secret must be set when client_auth is \"jwt\" \
+                         with algorithm HS256"
+                    );
+                }
+            }
+            JwtAlgorithm::RS256 => {
+                let key_path = jwt
+                    .jwks_path
+                    .as_deref()
+                    .or(jwt.public_key_path.as_deref())
+                    .map(str::trim)
+                    .filter(|p| !p.is_empty())
+                    .ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "security.transport.jwt.public_key_path or jwks_path must be set when \
+                             client_auth is \"jwt\" with algorithm RS256"
+                        )
+                    })?;
+                if !Path::new(key_path).exists() {
+                    anyhow::bail!("JWT public key file not found at {key_path}");
+                }
+            }
+        }
+    }
+
     // Token binding validation
     if config.security.transport.token_binding.enabled {
         let binding = &config.security.transport.token_binding;
@@ -131,9 +213,115 @@ pub fn validate_config_security(config: &Config) -> anyhow::Result<()> {
         }
     }
 
+    if config.server.enable_reconnection && config.server.max_token_age_secs == 0 {
+        anyhow::bail!("server.max_token_age_secs must be greater than zero");
+    }
+    if config.server.enable_reconnection
+        && config.server.flap_suppression_secs > config.server.reconnection_window
+    {
+        anyhow::bail!(
+            "server.flap_suppression_secs ({}) must not exceed server.reconnection_window ({})",
+            config.server.flap_suppression_secs,
+            config.server.reconnection_window
+        );
+    }
+
+    if config.relay_types.warmup.max_attempts == 0 {
+        anyhow::bail!("relay_types.warmup.max_attempts must be greater than zero");
+    }
+
+    let token_bucket = config.rate_limit.strategy == RateLimitStrategy::TokenBucket;
+    for (name, bucket) in [
+        (
+            "room_creation_bucket",
+            config
+                .rate_limit
+                .room_creation_bucket
+                .filter(|_| token_bucket),
+        ),
+        (
+            "join_attempt_bucket",
+            config
+                .rate_limit
+                .join_attempt_bucket
+                .filter(|_| token_bucket),
+        ),
+        ("game_data_bucket", config.rate_limit.game_data_bucket),
+        ("connection_bucket", config.rate_limit.connection_bucket),
+    ] {
+        let Some(bucket) = bucket else {
+            continue;
+        };
+        if bucket.capacity == 0 {
+            anyhow::bail!("rate_limit.{name}.capacity must be greater than zero");
+        }
+        if !(bucket.refill_per_sec.is_finite() && bucket.refill_per_sec > 0.0) {
+            anyhow::bail!("rate_limit.{name}.refill_per_sec must be a positive number");
+        }
+    }
+
+    if config.rate_limit.max_active_rooms_per_creator == Some(0) {
+        anyhow::bail!(
+            "rate_limit.max_active_rooms_per_creator must be greater than zero (omit it for unlimited)"
+        );
+    }
+
+    if config.server.broadcast_workers == 0 || config.server.isolated_broadcast_workers == 0 {
+        anyhow::bail!(
+            "server.broadcast_workers and server.isolated_broadcast_workers must be greater than zero"
+        );
+    }
+
+    validate_histogram_buckets(
+        "metrics.session_duration_buckets_secs",
+        &config.metrics.session_duration_buckets_secs,
+    )?;
+    validate_histogram_buckets(
+        "metrics.histogram_buckets",
+        &config.metrics.histogram_buckets,
+    )?;
+    config.metrics.validate_history()?;
+    config.metrics.otlp.validate()?;
+
     // WebSocket configuration validation
     config.websocket.validate()?;
+    config.storage.validate()?;
+    config.protocol.player_name_validation.validate()?;
+
+    for (game_name, game) in &config.game_overrides {
+        game.validate(game_name, &config.protocol)?;
+    }
 
+    for (index, webhook) in config.server.webhooks.iter().enumerate() {
+        webhook.validate(index)?;
+    }
+
+    for (index, ice_server) in config.server.ice_servers.iter().enumerate() {
+        ice_server.validate(index)?;
+    }
+    if config.security.transport.tls.turn_credential_ttl_secs == ConfigDuration::ZERO {
+        anyhow::bail!("security.transport.tls.turn_credential_ttl_secs must be greater than 0");
+    }
+
+    Ok(())
+}
+
+/// Prometheus bucket bounds must be a non-empty, strictly increasing list of
+/// positive numbers; anything else would render a malformed histogram.
+fn validate_histogram_buckets(name: &str, buckets: &[f64]) -> anyhow::Result<()> {
+    if buckets.is_empty() {
+        anyhow::bail!("{name} must list at least one bucket");
+    }
+    if let Some(bucket) = buckets.iter().find(|b| !(b.is_finite() && **b > 0.0)) {
+        anyhow::bail!("{name} must contain positive numbers (found {bucket})");
+    }
+    if let Some(pair) = buckets.windows(2).find(|pair| pair[0] >= pair[1]) {
+        anyhow::bail!(
+            "{name} must be sorted in strictly increasing order ({} is followed by {})",
+            pair[0],
+            pair[1]
+        );
+    }
     Ok(())
 }
 
@@ -1,9 +1,13 @@
 //! WebSocket configuration types.
 
 use super::defaults::{
-    default_auth_timeout_secs, default_batch_interval_ms, default_batch_size,
-    default_enable_batching,
+    default_allow_msgpack, default_allow_rkyv, default_auth_timeout_secs,
+    default_batch_interval_ms, default_batch_size, default_enable_batching,
+    default_max_batch_bytes, default_max_pending_send_queue_depth,
+    default_tcp_keepalive_interval_secs, default_tcp_keepalive_retries, default_tcp_keepalive_secs,
+    default_write_timeout_ms,
 };
+use super::units::{self, ByteSize, ConfigDuration};
 use serde::{Deserialize, Serialize};
 
 /// WebSocket configuration.
@@ -13,14 +17,43 @@ pub struct WebSocketConfig {
     #[serde(default = "default_enable_batching")]
     pub enable_batching: bool,
     /// Maximum number of messages to batch before flushing
-    #[serde(default = "default_batch_size")]
+    #[serde(default = "default_batch_size", alias = "max_batch_size")]
     pub batch_size: usize,
-    /// Maximum time in milliseconds to wait before flushing batch
-    #[serde(default = "default_batch_interval_ms")]
-    pub batch_interval_ms: u64,
-    /// Authentication timeout in seconds (time allowed for clients to authenticate)
+    /// Maximum time to wait before flushing batch (bare numbers are milliseconds)
+    #[serde(
+        default = "default_batch_interval_ms",
+        alias = "max_batch_delay_ms",
+        with = "units::millis"
+    )]
+    pub batch_interval_ms: ConfigDuration,
+    /// Flush once the batched messages add up to this many bytes (0 disables the limit)
+    #[serde(default = "default_max_batch_bytes")]
+    pub max_batch_bytes: ByteSize,
+    /// Authentication timeout (time allowed for clients to authenticate)
     #[serde(default = "default_auth_timeout_secs")]
-    pub auth_timeout_secs: u64,
+    pub auth_timeout_secs: ConfigDuration,
+    /// Let clients negotiate MessagePack envelopes via the `signal-fish-msgpack` (or `messagepack`) subprotocol
+    #[serde(default = "default_allow_msgpack")]
+    pub allow_msgpack: bool,
+    /// Let clients send rkyv-archived frames via the `signal-fish-rkyv` subprotocol
+    #[serde(default = "default_allow_rkyv")]
+    pub allow_rkyv: bool,
+    /// Idle time before TCP keepalive probes start on accepted connections (0 disables keepalive)
+    #[serde(default = "default_tcp_keepalive_secs")]
+    pub tcp_keepalive_secs: ConfigDuration,
+    /// Time between unanswered TCP keepalive probes
+    #[serde(default = "default_tcp_keepalive_interval_secs")]
+    pub tcp_keepalive_interval_secs: ConfigDuration,
+    /// Unanswered TCP keepalive probes before the connection is dropped
+    #[serde(default = "default_tcp_keepalive_retries")]
+    pub tcp_keepalive_retries: u32,
+    /// Deadline for writing one frame; a stalled write disconnects the client (bare numbers are milliseconds)
+    #[serde(default = "default_write_timeout_ms", with = "units::millis")]
+    pub write_timeout_ms: ConfigDuration,
+    /// Outbound messages queued for one connection before it is closed with
+    /// `BACKPRESSURE_SHED`; also bounds each room's shared broadcast frames
+    #[serde(default = "default_max_pending_send_queue_depth")]
+    pub max_pending_send_queue_depth: usize,
 }
 
 impl Default for WebSocketConfig {
@@ -29,7 +62,15 @@ impl Default for WebSocketConfig {
             enable_batching: default_enable_batching(),
             batch_size: default_batch_size(),
             batch_interval_ms: default_batch_interval_ms(),
+            max_batch_bytes: default_max_batch_bytes(),
             auth_timeout_secs: default_auth_timeout_secs(),
+            allow_msgpack: default_allow_msgpack(),
+            allow_rkyv: default_allow_rkyv(),
+            tcp_keepalive_secs: default_tcp_keepalive_secs(),
+            tcp_keepalive_interval_secs: default_tcp_keepalive_interval_secs(),
+            tcp_keepalive_retries: default_tcp_keepalive_retries(),
+            write_timeout_ms: default_write_timeout_ms(),
+            max_pending_send_queue_depth: default_max_pending_send_queue_depth(),
         }
     }
 }
@@ -38,18 +79,33 @@ impl WebSocketConfig {
     /// Validate WebSocket configuration
     pub fn validate(&self) -> anyhow::Result<()> {
         // Validate auth timeout: must be between 5 and 60 seconds
-        if self.auth_timeout_secs < 5 {
+        if self.auth_timeout_secs < ConfigDuration::from_secs(5) {
             anyhow::bail!(
                 "websocket.auth_timeout_secs must be at least 5 seconds (configured: {})",
                 self.auth_timeout_secs
             );
         }
-        if self.auth_timeout_secs > 60 {
+        if self.auth_timeout_secs > ConfigDuration::from_secs(60) {
             anyhow::bail!(
                 "websocket.auth_timeout_secs must not exceed 60 seconds (configured: {})",
                 self.auth_timeout_secs
             );
         }
+        if self.write_timeout_ms == ConfigDuration::ZERO {
+            anyhow::bail!("websocket.write_timeout_ms must be greater than 0");
+        }
+        if self.max_pending_send_queue_depth == 0 {
+            anyhow::bail!("websocket.max_pending_send_queue_depth must be greater than 0");
+        }
+        if self.tcp_keepalive_secs != ConfigDuration::ZERO
+            && (self.tcp_keepalive_interval_secs == ConfigDuration::ZERO
+                || self.tcp_keepalive_retries == 0)
+        {
+            anyhow::bail!(
+                "websocket.tcp_keepalive_interval_secs and websocket.tcp_keepalive_retries must be \
+                 greater than 0 when TCP keepalive is enabled"
+            );
+        }
         Ok(())
     }
 }
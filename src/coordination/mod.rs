@@ -12,11 +12,15 @@ pub mod room_coordinator;
 
 // Re-export public types
 pub use dedup::DedupCacheSettings;
-pub use room_coordinator::{InMemoryRoomOperationCoordinator, RoomOperationCoordinatorTrait};
+pub use room_coordinator::{
+    InMemoryRoomOperationCoordinator, RoomGuard, RoomOperationCoordinatorTrait,
+};
 
 // MessageCoordinator trait (defined in server.rs as InMemoryMessageCoordinator)
-use crate::protocol::{PlayerId, RoomId, ServerMessage};
+use crate::broadcast::Outbound;
+use crate::protocol::{MessageEncoding, PlayerId, RoomId, ServerMessage};
 use std::sync::Arc;
+use tokio::sync::Notify;
 
 #[async_trait::async_trait]
 pub trait MessageCoordinator: Send + Sync {
@@ -39,15 +43,42 @@ pub trait MessageCoordinator: Send + Sync {
         message: Arc<ServerMessage>,
     ) -> anyhow::Result<()>;
 
+    /// Queue `message` for every client connected to this instance, whether
+    /// or not it is in a room. Returns how many clients it was queued for.
+    async fn broadcast_to_local_clients(
+        &self,
+        message: Arc<ServerMessage>,
+    ) -> anyhow::Result<usize> {
+        let _ = message;
+        Ok(0)
+    }
+
     async fn register_local_client(
         &self,
         player_id: PlayerId,
         room_id: Option<RoomId>,
-        sender: tokio::sync::mpsc::Sender<Arc<ServerMessage>>,
+        sender: tokio::sync::mpsc::Sender<Outbound>,
     ) -> anyhow::Result<()>;
 
     async fn unregister_local_client(&self, player_id: &PlayerId) -> anyhow::Result<()>;
 
+    /// Queue `player_id`'s room broadcasts as [`Outbound::RoomFrame`]s
+    /// serialized once per broadcast in `encoding`, in order with the rest of its
+    /// queue. Returns `false` if the coordinator queues the messages for the
+    /// connection to serialize.
+    async fn subscribe_room_frames(&self, player_id: PlayerId, encoding: MessageEncoding) -> bool {
+        let _ = (player_id, encoding);
+        false
+    }
+
+    /// Notify `shed` whenever a message for `player_id` is dropped because
+    /// its queue is full, so the connection can be closed with
+    /// `BACKPRESSURE_SHED`. Returns `false` if full queues only drop messages.
+    async fn watch_backpressure(&self, player_id: PlayerId, shed: Arc<Notify>) -> bool {
+        let _ = (player_id, shed);
+        false
+    }
+
     async fn should_process_message(
         &self,
         message: &crate::distributed::SequencedMessage,
@@ -63,6 +94,13 @@ pub trait MessageCoordinator: Send + Sync {
         message: crate::distributed::SequencedMessage,
     ) -> anyhow::Result<()>;
 
+    /// Whether the coordinator can take more outgoing messages; used by the
+    /// readiness probe. Coordinators with a shared delivery queue report
+    /// `false` while it is full.
+    async fn is_accepting_messages(&self) -> bool {
+        true
+    }
+
     async fn handle_membership_update(
         &self,
         update: crate::coordination::MembershipUpdate,
@@ -5,21 +5,46 @@
 
 use anyhow::Result;
 use async_trait::async_trait;
-use std::collections::{HashMap, HashSet};
+use dashmap::DashMap;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, OwnedMutexGuard};
 use uuid::Uuid;
 
 use crate::distributed::DistributedLock;
 use crate::protocol::{PlayerId, RoomId};
+use crate::relay_provisioning::RelayWarmup;
 
 use super::MessageCoordinator;
 
+/// Exclusive hold on a room for a multi-step operation, from
+/// [`RoomOperationCoordinatorTrait::lock_room_for_bulk_op`]. The lock is
+/// released when the guard is dropped.
+#[must_use = "the room is unlocked as soon as the guard is dropped"]
+pub struct RoomGuard {
+    room_id: RoomId,
+    _guard: OwnedMutexGuard<()>,
+}
+
+impl RoomGuard {
+    /// The locked room
+    pub fn room_id(&self) -> RoomId {
+        self.room_id
+    }
+}
+
+impl std::fmt::Debug for RoomGuard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RoomGuard")
+            .field("room_id", &self.room_id)
+            .finish_non_exhaustive()
+    }
+}
+
 /// Trait for room operation coordination
 #[async_trait]
 pub trait RoomOperationCoordinatorTrait: Send + Sync {
-    /// Transition a room to lobby state
+    /// Transition a room to lobby state, announcing it if the room transitioned
     async fn transition_room_to_lobby(&self, room_id: &RoomId) -> Result<bool>;
 
     /// Coordinate authority transfer between players
@@ -40,16 +65,21 @@ pub trait RoomOperationCoordinatorTrait: Send + Sync {
         become_authority: bool,
     ) -> Result<(bool, Option<String>)>;
 
-    /// Handle player ready state change
+    /// Handle player ready state change. Returns whether every player is
+    /// ready and the game is starting.
     async fn handle_player_ready(
         &self,
         room_id: &RoomId,
         player_id: &PlayerId,
         app_id: Option<Uuid>,
-    ) -> Result<()>;
+    ) -> Result<bool>;
+
+    /// Hold `room_id` exclusively across several async steps, such as bulk
+    /// player removal. Waits while another guard for the room is alive.
+    async fn lock_room_for_bulk_op(&self, room_id: RoomId) -> Result<RoomGuard>;
 
-    /// Clear ready players for a room
-    async fn clear_ready_players(&self, room_id: &RoomId) -> Result<()>;
+    /// Drop per-room coordination state once `room_id` is deleted.
+    fn forget_room(&self, room_id: &RoomId);
 }
 
 /// In-memory room operation coordinator
@@ -57,8 +87,10 @@ pub struct InMemoryRoomOperationCoordinator {
     coordinator: Arc<dyn MessageCoordinator>,
     distributed_lock: Arc<dyn DistributedLock>,
     database: Arc<dyn crate::database::GameDatabase>,
-    /// Track ready players per room for in-memory coordinator
-    ready_players: Arc<RwLock<HashMap<RoomId, HashSet<PlayerId>>>>,
+    /// Supplies relay session data when a game starts
+    relay_warmup: Option<Arc<RelayWarmup>>,
+    /// Bulk operation locks, created on first use
+    room_locks: DashMap<RoomId, Arc<Mutex<()>>>,
 }
 
 impl InMemoryRoomOperationCoordinator {
@@ -72,33 +104,147 @@ impl InMemoryRoomOperationCoordinator {
             coordinator,
             distributed_lock,
             database,
-            ready_players: Arc::new(RwLock::new(HashMap::new())),
+            relay_warmup: None,
+            room_locks: DashMap::new(),
         }
     }
+
+    /// Attach relay session data to `GameStarting`, provisioning it if warm-up failed.
+    pub fn with_relay_warmup(mut self, relay_warmup: Arc<RelayWarmup>) -> Self {
+        self.relay_warmup = Some(relay_warmup);
+        self
+    }
+
+    /// Toggle readiness and announce it, starting the game once everyone is
+    /// ready. Called with the room's ready-state lock held.
+    async fn toggle_player_ready(&self, room_id: &RoomId, player_id: &PlayerId) -> Result<bool> {
+        // Get current room state to check if it has enough players for lobby actions
+        let room = match self.database.get_room_by_id(room_id).await {
+            Ok(Some(room)) => room,
+            Ok(None) => {
+                return Err(anyhow::anyhow!("Room not found"));
+            }
+            Err(e) => {
+                return Err(anyhow::anyhow!("Failed to get room: {e}"));
+            }
+        };
+
+        // Toggle under the database lock so the update gets its own lobby version
+        let Some(readiness) = self
+            .database
+            .toggle_player_ready(room_id, player_id)
+            .await?
+        else {
+            return Err(anyhow::anyhow!("Player ready failed: room may not be in lobby state. Current state: {:?}, player count: {}/{}", room.lobby_state, room.players.len(), room.max_players));
+        };
+        let ready = readiness.ready_players.contains(player_id);
+
+        // Resolve the relay session before announcing anything, so a blocked
+        // start leaves the lobby exactly as it was
+        let relay_session = if readiness.all_ready {
+            let session = match &self.relay_warmup {
+                Some(relay_warmup) => relay_warmup.session_for_start(&room).await,
+                None => Ok(room.relay_session.clone()),
+            };
+            match session {
+                Ok(session) => session,
+                Err(e) => {
+                    let _ = self.database.toggle_player_ready(room_id, player_id).await;
+                    return Err(e);
+                }
+            }
+        } else {
+            None
+        };
+
+        // Broadcast lobby state change
+        let message = crate::protocol::ServerMessage::LobbyStateChanged {
+            lobby_state: readiness.lobby_state,
+            ready_players: readiness.ready_players,
+            all_ready: readiness.all_ready,
+            lobby_version: readiness.lobby_version,
+        };
+
+        self.coordinator
+            .broadcast_to_room(room_id, Arc::new(message))
+            .await?;
+
+        // If all players are ready, finalize and start the game
+        let mut game_started = false;
+        if readiness.all_ready {
+            if let Some(lobby_version) = self.database.finalize_room_game(room_id).await? {
+                // Use P2P connection info from players (no relay server support in signal-fish-server)
+                let room_players = match self.database.get_room_players(room_id).await {
+                    Ok(players) => players,
+                    Err(e) => {
+                        tracing::error!("Failed to get room players: {}", e);
+                        Vec::new()
+                    }
+                };
+                let peer_connections: Vec<crate::protocol::PeerConnectionInfo> = room_players
+                    .into_iter()
+                    .map(|player| crate::protocol::PeerConnectionInfo {
+                        player_id: player.id,
+                        player_name: player.name,
+                        is_authority: player.is_authority,
+                        relay_type: room.relay_type.clone(),
+                        connection_info: player.connection_info,
+                        ice_servers: Vec::new(),
+                    })
+                    .collect();
+
+                let game_start_message = Arc::new(crate::protocol::ServerMessage::GameStarting {
+                    peer_connections,
+                    relay_session,
+                    lobby_version,
+                });
+
+                self.coordinator
+                    .broadcast_to_room(room_id, game_start_message)
+                    .await?;
+                game_started = true;
+            }
+        }
+
+        tracing::info!(%room_id, %player_id, ready, "Player ready state toggled (in-memory)");
+        Ok(game_started)
+    }
 }
 
 #[async_trait]
 impl RoomOperationCoordinatorTrait for InMemoryRoomOperationCoordinator {
     async fn transition_room_to_lobby(&self, room_id: &RoomId) -> Result<bool> {
-        // For in-memory implementation, just simulate the operation
         let lock_key = format!("room_lobby_transition:{room_id}");
-        let _lock_handle = self
+        let lock_handle = self
             .distributed_lock
             .acquire(&lock_key, Duration::from_secs(10))
             .await?;
 
-        // Simulate success and broadcast lobby state change
-        let message = crate::protocol::ServerMessage::LobbyStateChanged {
-            lobby_state: crate::protocol::LobbyState::Lobby,
-            ready_players: Vec::new(),
-            all_ready: false,
+        let transition = self.database.transition_room_to_lobby(room_id).await;
+        let broadcast_result = match transition {
+            Ok(Some(lobby_version)) => {
+                let message = crate::protocol::ServerMessage::LobbyStateChanged {
+                    lobby_state: crate::protocol::LobbyState::Lobby,
+                    ready_players: Vec::new(),
+                    all_ready: false,
+                    lobby_version,
+                };
+                self.coordinator
+                    .broadcast_to_room(room_id, Arc::new(message))
+                    .await
+                    .map(|()| true)
+            }
+            Ok(None) => Ok(false),
+            Err(e) => Err(e),
         };
-
-        self.coordinator
-            .broadcast_to_room(room_id, Arc::new(message))
-            .await?;
-        tracing::info!(%room_id, "Room transitioned to lobby state (in-memory)");
-        Ok(true)
+        // Release eagerly so a room that refills (e.g. from its waiting list)
+        // can transition again without waiting for the lock TTL.
+        let _ = self.distributed_lock.release(&lock_handle).await;
+        let transitioned = broadcast_result?;
+        if transitioned {
+            tracing::info!(%room_id, "Room transitioned to lobby state (in-memory)");
+        }
+        Ok(transitioned)
     }
 
     async fn coordinate_authority_transfer(
@@ -289,102 +435,92 @@ impl RoomOperationCoordinatorTrait for InMemoryRoomOperationCoordinator {
         room_id: &RoomId,
         player_id: &PlayerId,
         _app_id: Option<Uuid>,
-    ) -> Result<()> {
-        // For in-memory implementation, simulate player ready toggle
+    ) -> Result<bool> {
         let lock_key = format!("room_ready_state:{room_id}");
-        let _lock_handle = self
+        let lock_handle = self
             .distributed_lock
             .acquire(&lock_key, Duration::from_secs(5))
             .await?;
+        let result = self.toggle_player_ready(room_id, player_id).await;
+        // Release eagerly so quick successive toggles don't wait for the lock TTL
+        let _ = self.distributed_lock.release(&lock_handle).await;
+        result
+    }
 
-        // Get current room state to check if it has enough players for lobby actions
-        let room = match self.database.get_room_by_id(room_id).await {
-            Ok(Some(room)) => room,
-            Ok(None) => {
-                return Err(anyhow::anyhow!("Room not found"));
-            }
-            Err(e) => {
-                return Err(anyhow::anyhow!("Failed to get room: {e}"));
-            }
-        };
-
-        // Check if room has enough players for lobby state - prevent ready actions if room is no longer full
-        if !room.should_enter_lobby() && room.lobby_state != crate::protocol::LobbyState::Lobby {
-            return Err(anyhow::anyhow!("Player ready failed: room may not be in lobby state. Current state: {:?}, player count: {}/{}", room.lobby_state, room.players.len(), room.max_players));
-        }
-
-        // Toggle player ready state in ready_players map
-        let mut ready_map = self.ready_players.write().await;
-        let room_ready_players = ready_map.entry(*room_id).or_insert_with(HashSet::new);
-
-        let was_ready = room_ready_players.contains(player_id);
-        if was_ready {
-            room_ready_players.remove(player_id);
-        } else {
-            room_ready_players.insert(*player_id);
-        }
-
-        let ready_players_vec: Vec<PlayerId> = room_ready_players.iter().copied().collect();
+    async fn lock_room_for_bulk_op(&self, room_id: RoomId) -> Result<RoomGuard> {
+        // Clone out of the map so no shard lock is held while waiting
+        let lock = self.room_locks.entry(room_id).or_default().clone();
+        Ok(RoomGuard {
+            room_id,
+            _guard: lock.lock_owned().await,
+        })
+    }
 
-        // Get total players in room to check if all are ready
-        let room_players = match self.database.get_room_players(room_id).await {
-            Ok(players) => players,
-            Err(e) => {
-                tracing::error!("Failed to get room players: {}", e);
-                Vec::new()
-            }
-        };
+    fn forget_room(&self, room_id: &RoomId) {
+        // A guard still held keeps its mutex alive; the room is gone either way
+        self.room_locks.remove(room_id);
+    }
+}
 
-        let all_ready = !room_players.is_empty() && ready_players_vec.len() == room_players.len();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::InMemoryDatabase;
+    use crate::distributed::InMemoryDistributedLock;
+    use crate::server::InMemoryMessageCoordinator;
+    use tokio::time::timeout;
+
+    fn coordinator() -> InMemoryRoomOperationCoordinator {
+        InMemoryRoomOperationCoordinator::new(
+            Arc::new(InMemoryMessageCoordinator::new()),
+            Arc::new(InMemoryDistributedLock::new()),
+            Arc::new(InMemoryDatabase::new()),
+        )
+    }
 
-        drop(ready_map); // Release write lock
+    #[tokio::test]
+    async fn bulk_op_lock_blocks_until_the_guard_is_dropped() {
+        let coordinator = Arc::new(coordinator());
+        let room_id = Uuid::new_v4();
+        let guard = coordinator.lock_room_for_bulk_op(room_id).await.unwrap();
+        assert_eq!(guard.room_id(), room_id);
 
-        // Broadcast lobby state change
-        let message = crate::protocol::ServerMessage::LobbyStateChanged {
-            lobby_state: crate::protocol::LobbyState::Lobby,
-            ready_players: ready_players_vec.clone(),
-            all_ready,
+        let contender = {
+            let coordinator = Arc::clone(&coordinator);
+            tokio::spawn(async move { coordinator.lock_room_for_bulk_op(room_id).await })
         };
-
-        self.coordinator
-            .broadcast_to_room(room_id, Arc::new(message))
-            .await?;
-
-        // If all players are ready, transition to game starting
-        if all_ready {
-            // Use P2P connection info from players (no relay server support in signal-fish-server)
-            let peer_connections: Vec<crate::protocol::PeerConnectionInfo> = room_players
-                .into_iter()
-                .map(|player| crate::protocol::PeerConnectionInfo {
-                    player_id: player.id,
-                    player_name: player.name,
-                    is_authority: player.is_authority,
-                    relay_type: room.relay_type.clone(),
-                    connection_info: player.connection_info,
-                })
-                .collect();
-
-            let game_start_message =
-                Arc::new(crate::protocol::ServerMessage::GameStarting { peer_connections });
-
-            self.coordinator
-                .broadcast_to_room(room_id, game_start_message)
-                .await?;
-
-            // Clear ready players for this room since game is starting
-            let mut ready_map = self.ready_players.write().await;
-            ready_map.remove(room_id);
-        }
-
-        tracing::info!(%room_id, %player_id, ready = !was_ready, "Player ready state toggled (in-memory)");
-        Ok(())
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(
+            !contender.is_finished(),
+            "second lock must wait for the guard"
+        );
+
+        // Other rooms are unaffected
+        let other = timeout(
+            Duration::from_secs(1),
+            coordinator.lock_room_for_bulk_op(Uuid::new_v4()),
+        )
+        .await
+        .expect("other room locks immediately");
+        drop(other);
+
+        drop(guard);
+        let second = timeout(Duration::from_secs(1), contender)
+            .await
+            .expect("lock acquired once the guard is dropped")
+            .expect("task completes")
+            .unwrap();
+        assert_eq!(second.room_id(), room_id);
     }
 
-    async fn clear_ready_players(&self, room_id: &RoomId) -> Result<()> {
-        // Clear ready players from the in-memory coordinator map
-        let mut ready_map = self.ready_players.write().await;
-        ready_map.remove(room_id);
-        tracing::info!(%room_id, "Cleared ready players from coordinator (in-memory)");
-        Ok(())
+    #[tokio::test]
+    async fn forgetting_a_room_drops_its_lock_entry() {
+        let coordinator = coordinator();
+        let room_id = Uuid::new_v4();
+        drop(coordinator.lock_room_for_bulk_op(room_id).await.unwrap());
+        assert!(coordinator.room_locks.contains_key(&room_id));
+
+        coordinator.forget_room(&room_id);
+        assert!(!coordinator.room_locks.contains_key(&room_id));
     }
 }
@@ -0,0 +1,991 @@
+//! Behavior every [`GameDatabase`](super::GameDatabase) backend must share.
+//!
+//! `database_conformance_tests!()` expands to a `conformance` test module in
+//! the calling module, which must provide
+//! `async fn new_database() -> (impl GameDatabase + 'static, Guard)`. The
+//! guard (e.g. a temporary directory) is kept alive for the whole test.
+
+macro_rules! database_conformance_tests {
+    () => {
+        mod conformance {
+            use super::new_database;
+            use crate::database::*;
+            use std::collections::HashSet;
+            use std::sync::Arc;
+
+            /// Helper: create a room with the given game name and room code using sensible defaults.
+            async fn create_test_room(
+                db: &dyn GameDatabase,
+                game_name: &str,
+                room_code: &str,
+            ) -> Result<Room> {
+                db.create_room(
+                    game_name.to_string(),
+                    Some(room_code.to_string()),
+                    4,
+                    true,
+                    Uuid::new_v4(),
+                    "relay".to_string(),
+                    "us-east-1".to_string(),
+                    None,
+                )
+                .await
+            }
+
+            #[tokio::test]
+            async fn test_create_room_generates_unique_ids() {
+                let (db, _guard) = new_database().await;
+                let mut ids = HashSet::new();
+                let count = 100;
+
+                for i in 0..count {
+                    let room_code = format!("ROOM{i:03}");
+                    let room = create_test_room(&db, "uniqueness_game", &room_code)
+                        .await
+                        .expect("room creation should succeed");
+                    ids.insert(room.id);
+                }
+
+                assert_eq!(
+                    ids.len(),
+                    count,
+                    "all {count} room IDs must be distinct, but only {} unique IDs found",
+                    ids.len()
+                );
+            }
+
+            #[tokio::test]
+            async fn test_create_room_id_is_retrievable_by_id() {
+                let (db, _guard) = new_database().await;
+                let room = create_test_room(&db, "lookup_game", "LOOK01")
+                    .await
+                    .expect("room creation should succeed");
+
+                let fetched = db
+                    .get_room_by_id(&room.id)
+                    .await
+                    .expect("get_room_by_id should not error")
+                    .expect("room should exist in the rooms map");
+
+                assert_eq!(fetched.id, room.id);
+                assert_eq!(fetched.code, room.code);
+                assert_eq!(fetched.game_name, room.game_name);
+            }
+
+            #[tokio::test]
+            async fn test_room_summary_matches_the_stored_room() {
+                let (db, _guard) = new_database().await;
+                let room = create_test_room(&db, "summary_game", "SUMM01")
+                    .await
+                    .expect("room creation should succeed");
+                let spectator = SpectatorInfo {
+                    id: Uuid::new_v4(),
+                    name: "Watcher".to_string(),
+                    connected_at: chrono::Utc::now(),
+                };
+                assert!(db.add_spectator_to_room(&room.id, spectator).await.unwrap());
+
+                let stored = db.get_room_by_id(&room.id).await.unwrap().unwrap();
+                let summary = db
+                    .get_room_summary(&room.id)
+                    .await
+                    .expect("get_room_summary should not error")
+                    .expect("room should have a summary");
+                assert_eq!(summary, stored.to_summary());
+                assert_eq!(summary.current_players, 1);
+                assert_eq!(summary.spectator_count, 1);
+                assert_eq!(summary.authority_player, stored.authority_player);
+
+                db.delete_room(&room.id).await.unwrap();
+                assert!(db.get_room_summary(&room.id).await.unwrap().is_none());
+            }
+
+            #[tokio::test]
+            async fn test_create_room_room_code_collision_rejected() {
+                let (db, _guard) = new_database().await;
+
+                create_test_room(&db, "game1", "TEST01")
+                    .await
+                    .expect("first room creation should succeed");
+
+                let result = create_test_room(&db, "game1", "TEST01").await;
+                assert!(
+                    result.is_err(),
+                    "duplicate room code for the same game must be rejected"
+                );
+
+                let err_msg = result.unwrap_err().to_string();
+                assert!(
+                    err_msg.contains("already exists"),
+                    "error message should contain 'already exists', got: {err_msg}"
+                );
+            }
+
+            #[tokio::test]
+            async fn test_create_room_same_code_different_game_allowed() {
+                let (db, _guard) = new_database().await;
+
+                let room1 = create_test_room(&db, "game1", "TEST01")
+                    .await
+                    .expect("room creation for game1 should succeed");
+
+                let room2 = create_test_room(&db, "game2", "TEST01")
+                    .await
+                    .expect("room creation for game2 with same code should succeed");
+
+                assert_ne!(
+                    room1.id, room2.id,
+                    "rooms for different games must have different IDs"
+                );
+                assert_eq!(room1.code, room2.code);
+                assert_ne!(room1.game_name, room2.game_name);
+            }
+
+            #[tokio::test]
+            async fn test_create_room_concurrent_unique_ids() {
+                let (db, _guard) = new_database().await;
+                let db = Arc::new(db);
+                let task_count = 50;
+                let barrier = Arc::new(tokio::sync::Barrier::new(task_count));
+
+                let mut handles = Vec::with_capacity(task_count);
+                for i in 0..task_count {
+                    let db = Arc::clone(&db);
+                    let barrier = Arc::clone(&barrier);
+                    handles.push(tokio::spawn(async move {
+                        barrier.wait().await;
+                        let room_code = format!("CONC{i:03}");
+                        db.create_room(
+                            "concurrent_game".to_string(),
+                            Some(room_code),
+                            4,
+                            true,
+                            Uuid::new_v4(),
+                            "relay".to_string(),
+                            "us-east-1".to_string(),
+                            None,
+                        )
+                        .await
+                    }));
+                }
+
+                let mut ids = HashSet::new();
+                for handle in handles {
+                    let room = handle
+                        .await
+                        .expect("task should not panic")
+                        .expect("room creation should succeed");
+                    ids.insert(room.id);
+                }
+
+                assert_eq!(
+                    ids.len(),
+                    task_count,
+                    "all {task_count} concurrently created rooms must have unique IDs"
+                );
+            }
+
+            #[tokio::test(flavor = "multi_thread")]
+            async fn test_create_room_if_under_limit_never_overshoots() {
+                let (db, _guard) = new_database().await;
+                let db = Arc::new(db);
+                let task_count = 100;
+                let barrier = Arc::new(tokio::sync::Barrier::new(task_count));
+
+                let mut handles = Vec::with_capacity(task_count);
+                for i in 0..task_count {
+                    let db = Arc::clone(&db);
+                    let barrier = Arc::clone(&barrier);
+                    handles.push(tokio::spawn(async move {
+                        barrier.wait().await;
+                        db.create_room_if_under_limit(
+                            "capped_game".to_string(),
+                            Some(format!("CAP{i:03}")),
+                            4,
+                            true,
+                            Uuid::new_v4(),
+                            "relay".to_string(),
+                            "us-east-1".to_string(),
+                            None,
+                            10,
+                        )
+                        .await
+                    }));
+                }
+
+                let mut created = 0;
+                for handle in handles {
+                    let room = handle
+                        .await
+                        .expect("task should not panic")
+                        .expect("room creation should not error");
+                    if room.is_some() {
+                        created += 1;
+                    }
+                }
+
+                assert_eq!(created, 10);
+                assert_eq!(db.get_game_room_count("capped_game").await.unwrap(), 10);
+                // Other games are not affected by the cap
+                assert!(create_test_room(&*db, "other_game", "FREE01").await.is_ok());
+            }
+
+            #[tokio::test]
+            async fn test_create_room_concurrent_same_code_only_one_succeeds() {
+                let (db, _guard) = new_database().await;
+                let db = Arc::new(db);
+                let task_count = 10;
+                let barrier = Arc::new(tokio::sync::Barrier::new(task_count));
+
+                let mut handles = Vec::with_capacity(task_count);
+                for _ in 0..task_count {
+                    let db = Arc::clone(&db);
+                    let barrier = Arc::clone(&barrier);
+                    handles.push(tokio::spawn(async move {
+                        barrier.wait().await;
+                        db.create_room(
+                            "game1".to_string(),
+                            Some("RACE01".to_string()),
+                            4,
+                            true,
+                            Uuid::new_v4(),
+                            "relay".to_string(),
+                            "us-east-1".to_string(),
+                            None,
+                        )
+                        .await
+                    }));
+                }
+
+                let mut successes = 0usize;
+                let mut failures = 0usize;
+                for handle in handles {
+                    match handle.await.expect("task should not panic") {
+                        Ok(_) => successes += 1,
+                        Err(e) => {
+                            assert!(
+                                e.to_string().contains("already exists"),
+                                "failure reason should be 'already exists', got: {e}"
+                            );
+                            failures += 1;
+                        }
+                    }
+                }
+
+                assert_eq!(successes, 1, "exactly one task should win the race");
+                assert_eq!(
+                    failures,
+                    task_count - 1,
+                    "all other tasks should fail with 'already exists'"
+                );
+
+                // Verify only one room exists in the database for this game+code
+                let room = db
+                    .get_room("game1", "RACE01")
+                    .await
+                    .expect("get_room should not error")
+                    .expect("the winning room should be findable");
+                assert_eq!(room.code, "RACE01");
+            }
+
+            #[tokio::test]
+            async fn test_create_room_atomic_consistency() {
+                let (db, _guard) = new_database().await;
+                let room = create_test_room(&db, "atomic_game", "ATOM01")
+                    .await
+                    .expect("room creation should succeed");
+
+                // Lookup via room ID
+                let by_id = db
+                    .get_room_by_id(&room.id)
+                    .await
+                    .expect("get_room_by_id should not error")
+                    .expect("room should be in the rooms map");
+
+                // Lookup via game name + room code
+                let by_code = db
+                    .get_room("atomic_game", "ATOM01")
+                    .await
+                    .expect("get_room should not error")
+                    .expect("room should be in the room_codes map");
+
+                assert_eq!(by_id.id, room.id);
+                assert_eq!(by_code.id, room.id);
+                assert_eq!(
+                    by_id.id, by_code.id,
+                    "both lookups must resolve to the same room"
+                );
+            }
+
+            #[tokio::test]
+            async fn test_delete_room_frees_room_code() {
+                let (db, _guard) = new_database().await;
+
+                let room = create_test_room(&db, "reuse_game", "REUSE1")
+                    .await
+                    .expect("initial room creation should succeed");
+
+                let deleted = db
+                    .delete_room(&room.id)
+                    .await
+                    .expect("delete_room should not error");
+                assert!(
+                    deleted,
+                    "delete_room should return true for an existing room"
+                );
+
+                // The room code is now free; re-creating with the same code should work.
+                let room2 = create_test_room(&db, "reuse_game", "REUSE1")
+                    .await
+                    .expect("re-creating room with freed code should succeed");
+
+                assert_ne!(
+                    room.id, room2.id,
+                    "the new room must have a different ID than the deleted one"
+                );
+                assert_eq!(room2.code, "REUSE1");
+            }
+
+            #[tokio::test]
+            async fn test_create_room_preserves_all_fields() {
+                let (db, _guard) = new_database().await;
+                let creator_id = Uuid::new_v4();
+                let app_id = Uuid::new_v4();
+
+                let room = db
+                    .create_room(
+                        "my_game".to_string(),
+                        Some("FIELD1".to_string()),
+                        8,
+                        true,
+                        creator_id,
+                        "webrtc".to_string(),
+                        "eu-west-1".to_string(),
+                        Some(app_id),
+                    )
+                    .await
+                    .expect("room creation should succeed");
+
+                assert_eq!(room.game_name, "my_game");
+                assert_eq!(room.code, "FIELD1");
+                assert_eq!(room.max_players, 8);
+                assert!(room.supports_authority);
+                assert_eq!(room.relay_type, "webrtc");
+                assert_eq!(room.region_id, "eu-west-1");
+                assert_eq!(room.application_id, Some(app_id));
+
+                // Creator should be in the players map
+                assert!(
+                    room.players.contains_key(&creator_id),
+                    "creator must appear in the players map"
+                );
+                let creator = &room.players[&creator_id];
+                assert_eq!(creator.id, creator_id);
+                assert!(
+                    creator.is_authority,
+                    "creator should be marked as authority when supports_authority is true"
+                );
+
+                // Authority player should be set to creator
+                assert_eq!(room.authority_player, Some(creator_id));
+            }
+
+            #[tokio::test]
+            async fn test_waiting_list_fifo_cap_and_removal() {
+                let (db, _guard) = new_database().await;
+                let room = create_test_room(&db, "queue_game", "QUEUE1")
+                    .await
+                    .expect("room creation should succeed");
+                let (first, second, third) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+
+                assert_eq!(
+                    db.add_to_waiting_list(&room.id, &first, 2).await.unwrap(),
+                    Some(1)
+                );
+                assert_eq!(
+                    db.add_to_waiting_list(&room.id, &second, 2).await.unwrap(),
+                    Some(2)
+                );
+                assert_eq!(
+                    db.add_to_waiting_list(&room.id, &first, 2).await.unwrap(),
+                    Some(1),
+                    "re-queueing keeps the existing position"
+                );
+                assert_eq!(
+                    db.add_to_waiting_list(&room.id, &third, 2).await.unwrap(),
+                    None,
+                    "waiting list should be capped at max_size"
+                );
+
+                assert!(db.remove_from_waiting_list(&room.id, &first).await.unwrap());
+                assert!(!db.remove_from_waiting_list(&room.id, &first).await.unwrap());
+                assert_eq!(db.pop_waiting_list(&room.id).await.unwrap(), Some(second));
+                assert_eq!(db.pop_waiting_list(&room.id).await.unwrap(), None);
+            }
+
+            #[tokio::test]
+            async fn test_room_bans_are_capped_and_reversible() {
+                let (db, _guard) = new_database().await;
+                let room = create_test_room(&db, "ban_game", "BAN001")
+                    .await
+                    .expect("room creation should succeed");
+                let first_banned = Uuid::new_v4();
+                let ip: IpAddr = "203.0.113.7".parse().unwrap();
+
+                assert!(db
+                    .ban_player_from_room(&room.id, &first_banned, Some(ip))
+                    .await
+                    .unwrap());
+                for _ in 0..crate::protocol::MAX_ROOM_BANS {
+                    db.ban_player_from_room(&room.id, &Uuid::new_v4(), None)
+                        .await
+                        .unwrap();
+                }
+
+                let stored = db.get_room_by_id(&room.id).await.unwrap().unwrap();
+                assert_eq!(stored.bans.len(), crate::protocol::MAX_ROOM_BANS);
+                assert!(
+                    !stored.is_banned(&first_banned, Some(ip)),
+                    "oldest ban should be evicted once the cap is reached"
+                );
+
+                let latest = stored.bans.back().unwrap().player_id;
+                assert!(db.unban_player_from_room(&room.id, &latest).await.unwrap());
+                assert!(!db.unban_player_from_room(&room.id, &latest).await.unwrap());
+                assert!(!db
+                    .ban_player_from_room(&Uuid::new_v4(), &latest, None)
+                    .await
+                    .unwrap());
+            }
+
+            #[tokio::test]
+            async fn test_app_room_count_only_counts_matching_application() {
+                let (db, _guard) = new_database().await;
+                let app = Uuid::new_v4();
+                let other_app = Uuid::new_v4();
+
+                for (code, application_id) in [
+                    ("APP001", Some(app)),
+                    ("APP002", Some(app)),
+                    ("APP003", Some(other_app)),
+                    ("APP004", None),
+                ] {
+                    db.create_room(
+                        "quota_game".to_string(),
+                        Some(code.to_string()),
+                        4,
+                        true,
+                        Uuid::new_v4(),
+                        "relay".to_string(),
+                        "us-east-1".to_string(),
+                        application_id,
+                    )
+                    .await
+                    .expect("room creation should succeed");
+                }
+
+                assert_eq!(db.get_app_room_count(&app).await.unwrap(), 2);
+                assert_eq!(db.get_app_room_count(&other_app).await.unwrap(), 1);
+                assert_eq!(db.get_app_room_count(&Uuid::new_v4()).await.unwrap(), 0);
+            }
+
+            #[tokio::test]
+            async fn test_players_by_application_sums_players_in_its_rooms() {
+                let (db, _guard) = new_database().await;
+                let app = Uuid::new_v4();
+                let other_app = Uuid::new_v4();
+
+                let mut rooms = Vec::new();
+                for (code, application_id) in
+                    [("PLY001", app), ("PLY002", app), ("PLY003", other_app)]
+                {
+                    let room = db
+                        .create_room(
+                            "quota_game".to_string(),
+                            Some(code.to_string()),
+                            4,
+                            true,
+                            Uuid::new_v4(),
+                            "relay".to_string(),
+                            "us-east-1".to_string(),
+                            Some(application_id),
+                        )
+                        .await
+                        .expect("room creation should succeed");
+                    rooms.push(room);
+                }
+                let guest = PlayerInfo {
+                    id: Uuid::new_v4(),
+                    name: "Guest".to_string(),
+                    is_authority: false,
+                    role: PlayerRole::Member,
+                    is_ready: false,
+                    connected_at: chrono::Utc::now(),
+                    connection_info: None,
+                    region_id: "us-east-1".to_string(),
+                };
+                assert!(db.add_player_to_room(&rooms[0].id, guest).await.unwrap());
+
+                assert_eq!(db.count_players_by_application(&app).await.unwrap(), 3);
+                assert_eq!(
+                    db.count_players_by_application(&other_app).await.unwrap(),
+                    1
+                );
+                assert_eq!(
+                    db.count_players_by_application(&Uuid::new_v4())
+                        .await
+                        .unwrap(),
+                    0
+                );
+            }
+
+            #[tokio::test]
+            async fn test_rooms_by_creator_are_counted_until_deleted() {
+                let (db, _guard) = new_database().await;
+                let mut rooms = Vec::new();
+                for code in ["OWN001", "OWN002"] {
+                    let room = db
+                        .create_room(
+                            "owner_game".to_string(),
+                            Some(code.to_string()),
+                            4,
+                            true,
+                            Uuid::new_v4(),
+                            "relay".to_string(),
+                            "us-east-1".to_string(),
+                            None,
+                        )
+                        .await
+                        .expect("room creation should succeed");
+                    rooms.push(room.id);
+                }
+                db.set_room_creator(&rooms[0], "ip:203.0.113.7")
+                    .await
+                    .unwrap();
+                db.set_room_creator(&rooms[1], "ip:203.0.113.7")
+                    .await
+                    .unwrap();
+
+                assert_eq!(
+                    db.count_rooms_by_creator("ip:203.0.113.7").await.unwrap(),
+                    2
+                );
+                assert_eq!(
+                    db.count_rooms_by_creator("ip:198.51.100.1").await.unwrap(),
+                    0
+                );
+
+                assert!(db.delete_room(&rooms[0]).await.unwrap());
+                assert_eq!(
+                    db.count_rooms_by_creator("ip:203.0.113.7").await.unwrap(),
+                    1
+                );
+            }
+
+            #[tokio::test]
+            async fn test_cleanup_expired_rooms_uses_per_game_timeouts() {
+                let (db, _guard) = new_database().await;
+                let quick = create_test_room(&db, "quick_game", "QCK001")
+                    .await
+                    .expect("room creation should succeed");
+                let slow = create_test_room(&db, "slow_game", "SLW001")
+                    .await
+                    .expect("room creation should succeed");
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+
+                let policy = RoomExpiryPolicy::uniform(
+                    chrono::Duration::hours(1),
+                    chrono::Duration::hours(1),
+                )
+                .with_game(
+                    "quick_game",
+                    RoomTimeouts {
+                        empty: chrono::Duration::zero(),
+                        inactive: chrono::Duration::zero(),
+                    },
+                );
+                let outcome = db
+                    .cleanup_expired_rooms(&policy)
+                    .await
+                    .expect("cleanup should succeed");
+
+                assert_eq!(outcome.inactive_rooms_cleaned, 1);
+                assert!(db.get_room_by_id(&quick.id).await.unwrap().is_none());
+                assert!(db.get_room_by_id(&slow.id).await.unwrap().is_some());
+            }
+
+            #[tokio::test]
+            async fn test_list_rooms_near_expiry_warns_once_per_cycle() {
+                let (db, _guard) = new_database().await;
+                let room = create_test_room(&db, "warn_game", "WRN001")
+                    .await
+                    .expect("room creation should succeed");
+                let policy = RoomExpiryPolicy::uniform(
+                    chrono::Duration::minutes(1),
+                    chrono::Duration::minutes(1),
+                );
+
+                let far = db
+                    .list_rooms_near_expiry(&policy, chrono::Duration::seconds(30))
+                    .await
+                    .unwrap();
+                assert!(far.is_empty());
+
+                let near = db
+                    .list_rooms_near_expiry(&policy, chrono::Duration::minutes(2))
+                    .await
+                    .unwrap();
+                assert_eq!(near.len(), 1);
+                assert_eq!(near[0].room_id, room.id);
+                assert!(near[0].expires_in <= chrono::Duration::minutes(1));
+
+                // Already warned until the room sees activity again
+                let repeat = db
+                    .list_rooms_near_expiry(&policy, chrono::Duration::minutes(2))
+                    .await
+                    .unwrap();
+                assert!(repeat.is_empty());
+
+                db.update_room_activity(&room.id).await.unwrap();
+                let rearmed = db
+                    .list_rooms_near_expiry(&policy, chrono::Duration::minutes(2))
+                    .await
+                    .unwrap();
+                assert_eq!(rearmed.len(), 1);
+            }
+
+            #[tokio::test]
+            async fn test_find_joinable_room_prefers_the_players_region() {
+                let (db, _guard) = new_database().await;
+                let mut rooms = Vec::new();
+                for (code, region) in [("REGN01", "us-east-1"), ("REGN02", "eu-west-1")] {
+                    let room = db
+                        .create_room(
+                            "region_game".to_string(),
+                            Some(code.to_string()),
+                            4,
+                            true,
+                            Uuid::new_v4(),
+                            "relay".to_string(),
+                            region.to_string(),
+                            None,
+                        )
+                        .await
+                        .unwrap();
+                    rooms.push(room);
+                }
+                let guest = PlayerInfo {
+                    id: Uuid::new_v4(),
+                    name: "Guest".to_string(),
+                    is_authority: false,
+                    role: PlayerRole::Member,
+                    is_ready: false,
+                    connected_at: chrono::Utc::now(),
+                    connection_info: None,
+                    region_id: "us-east-1".to_string(),
+                };
+                assert!(db.add_player_to_room(&rooms[0].id, guest).await.unwrap());
+
+                // A room in the player's region beats a fuller one elsewhere
+                assert_eq!(
+                    db.find_joinable_room("region_game", Some("eu-west-1"))
+                        .await
+                        .unwrap(),
+                    Some(rooms[1].id)
+                );
+                // With no room in the region, any open room will do
+                assert_eq!(
+                    db.find_joinable_room("region_game", Some("ap-south-1"))
+                        .await
+                        .unwrap(),
+                    Some(rooms[0].id)
+                );
+                assert_eq!(
+                    db.find_joinable_room("region_game", None).await.unwrap(),
+                    Some(rooms[0].id)
+                );
+            }
+
+            #[tokio::test]
+            async fn test_find_joinable_room_prefers_the_fullest_open_room() {
+                let (db, _guard) = new_database().await;
+                assert_eq!(
+                    db.find_joinable_room("quick_game", None).await.unwrap(),
+                    None
+                );
+
+                let add_guest = |room_id: RoomId, name: &str| {
+                    let guest = PlayerInfo {
+                        id: Uuid::new_v4(),
+                        name: name.to_string(),
+                        is_authority: false,
+                        role: PlayerRole::Member,
+                        is_ready: false,
+                        connected_at: chrono::Utc::now(),
+                        connection_info: None,
+                        region_id: "us-east-1".to_string(),
+                    };
+                    let db = &db;
+                    async move { db.add_player_to_room(&room_id, guest).await.unwrap() }
+                };
+
+                let quiet = create_test_room(&db, "quick_game", "QUICK1").await.unwrap();
+                let busy = create_test_room(&db, "quick_game", "QUICK2").await.unwrap();
+                create_test_room(&db, "other_game", "QUICK3").await.unwrap();
+                assert!(add_guest(busy.id, "Guest1").await);
+                assert_eq!(
+                    db.find_joinable_room("quick_game", None).await.unwrap(),
+                    Some(busy.id)
+                );
+
+                // Full and finalized rooms are skipped
+                assert!(add_guest(busy.id, "Guest2").await);
+                assert!(add_guest(busy.id, "Guest3").await);
+                assert_eq!(
+                    db.find_joinable_room("quick_game", None).await.unwrap(),
+                    Some(quiet.id)
+                );
+
+                // A finalized game stays closed even after a player leaves
+                db.delete_room(&quiet.id).await.unwrap();
+                let creator_id = Uuid::new_v4();
+                let started = db
+                    .create_room(
+                        "quick_game".to_string(),
+                        Some("QUICK4".to_string()),
+                        2,
+                        true,
+                        creator_id,
+                        "relay".to_string(),
+                        "us-east-1".to_string(),
+                        None,
+                    )
+                    .await
+                    .unwrap();
+                let guest_id = Uuid::new_v4();
+                let guest = PlayerInfo {
+                    id: guest_id,
+                    name: "Guest".to_string(),
+                    is_authority: false,
+                    role: PlayerRole::Member,
+                    is_ready: false,
+                    connected_at: chrono::Utc::now(),
+                    connection_info: None,
+                    region_id: "us-east-1".to_string(),
+                };
+                assert!(db.add_player_to_room(&started.id, guest).await.unwrap());
+                db.transition_room_to_lobby(&started.id).await.unwrap();
+                for player_id in [creator_id, guest_id] {
+                    db.toggle_player_ready(&started.id, &player_id)
+                        .await
+                        .unwrap();
+                }
+                assert!(db.finalize_room_game(&started.id).await.unwrap().is_some());
+                db.remove_player_from_room(&started.id, &guest_id)
+                    .await
+                    .unwrap();
+                assert_eq!(
+                    db.find_joinable_room("quick_game", None).await.unwrap(),
+                    None
+                );
+            }
+
+            #[tokio::test]
+            async fn test_room_metadata_survives_lobby_transitions() {
+                let (db, _guard) = new_database().await;
+                let room = db
+                    .create_room(
+                        "meta_game".to_string(),
+                        Some("MTA001".to_string()),
+                        2,
+                        true,
+                        Uuid::new_v4(),
+                        "relay".to_string(),
+                        "us-east-1".to_string(),
+                        None,
+                    )
+                    .await
+                    .expect("room creation should succeed");
+                let entries = HashMap::from([("map".to_string(), "dust".to_string())]);
+
+                let update = db.set_room_metadata(&room.id, &entries).await.unwrap();
+                assert_eq!(update, RoomMetadataUpdate::Applied(entries.clone()));
+
+                let guest_id = Uuid::new_v4();
+                let guest = PlayerInfo {
+                    id: guest_id,
+                    name: "Guest".to_string(),
+                    is_authority: false,
+                    role: PlayerRole::Member,
+                    is_ready: false,
+                    connected_at: chrono::Utc::now(),
+                    connection_info: None,
+                    region_id: "us-east-1".to_string(),
+                };
+                assert!(db.add_player_to_room(&room.id, guest).await.unwrap());
+                db.transition_room_to_lobby(&room.id).await.unwrap();
+                let lobby = db.get_room_by_id(&room.id).await.unwrap().unwrap();
+                assert_eq!(lobby.lobby_state, crate::protocol::LobbyState::Lobby);
+                assert_eq!(lobby.metadata, entries);
+
+                db.remove_player_from_room(&room.id, &guest_id)
+                    .await
+                    .unwrap();
+                db.transition_room_to_waiting(&room.id).await.unwrap();
+                let waiting = db.get_room_by_id(&room.id).await.unwrap().unwrap();
+                assert_eq!(waiting.lobby_state, crate::protocol::LobbyState::Waiting);
+                assert_eq!(waiting.metadata, entries);
+
+                assert!(db.delete_room(&room.id).await.unwrap());
+                assert_eq!(
+                    db.set_room_metadata(&room.id, &entries).await.unwrap(),
+                    RoomMetadataUpdate::RoomNotFound
+                );
+            }
+
+            #[tokio::test]
+            async fn test_room_idempotency_keys_resolve_concurrent_claims_to_one_room() {
+                let (db, _guard) = new_database().await;
+                let db = Arc::new(db);
+                let app_id = Uuid::new_v4();
+                let ttl = chrono::Duration::minutes(5);
+                let task_count = 10;
+                let barrier = Arc::new(tokio::sync::Barrier::new(task_count));
+
+                let mut handles = Vec::with_capacity(task_count);
+                for i in 0..task_count {
+                    let db = Arc::clone(&db);
+                    let barrier = Arc::clone(&barrier);
+                    handles.push(tokio::spawn(async move {
+                        let room = IdempotentRoom {
+                            game_name: "game1".to_string(),
+                            room_code: format!("IDEM{i:02}"),
+                        };
+                        barrier.wait().await;
+                        let claimed = db
+                            .claim_room_idempotency_key(&app_id, "retry-1", room.clone(), ttl)
+                            .await
+                            .expect("claim should not error");
+                        claimed.unwrap_or(room)
+                    }));
+                }
+                let mut rooms = HashSet::new();
+                for handle in handles {
+                    rooms.insert(handle.await.expect("task should not panic").room_code);
+                }
+                assert_eq!(rooms.len(), 1, "every claim resolves to the first room");
+
+                // Keys are scoped to their application
+                let other = IdempotentRoom {
+                    game_name: "game1".to_string(),
+                    room_code: "OTHER1".to_string(),
+                };
+                assert_eq!(
+                    db.claim_room_idempotency_key(&Uuid::new_v4(), "retry-1", other, ttl)
+                        .await
+                        .unwrap(),
+                    None
+                );
+
+                // An expired key can be claimed again and is cleaned up
+                let fresh = IdempotentRoom {
+                    game_name: "game1".to_string(),
+                    room_code: "FRESH1".to_string(),
+                };
+                let expired = chrono::Duration::zero();
+                assert_eq!(
+                    db.claim_room_idempotency_key(&app_id, "short", fresh.clone(), expired)
+                        .await
+                        .unwrap(),
+                    None
+                );
+                assert_eq!(
+                    db.claim_room_idempotency_key(&app_id, "short", fresh.clone(), expired)
+                        .await
+                        .unwrap(),
+                    None
+                );
+                assert_eq!(db.cleanup_expired_room_idempotency_keys().await.unwrap(), 1);
+            }
+
+            #[tokio::test]
+            async fn test_overflow_players_fill_rooms_up_to_the_limit() {
+                let (db, _guard) = new_database().await;
+                let room = create_test_room(&db, "game1", "OVRFL1")
+                    .await
+                    .expect("room creation should succeed");
+                db.set_allow_overflow_joins(&room.id, true).await.unwrap();
+                let player = |name: &str| PlayerInfo {
+                    id: Uuid::new_v4(),
+                    name: name.to_string(),
+                    is_authority: false,
+                    role: PlayerRole::Member,
+                    is_ready: false,
+                    connected_at: chrono::Utc::now(),
+                    connection_info: None,
+                    region_id: "us-east-1".to_string(),
+                };
+                for name in ["P2", "P3", "P4"] {
+                    assert!(db.add_player_to_room(&room.id, player(name)).await.unwrap());
+                }
+                assert!(!db.add_player_to_room(&room.id, player("P5")).await.unwrap());
+
+                assert!(db
+                    .add_overflow_player_to_room(&room.id, player("P5"), 5)
+                    .await
+                    .unwrap());
+                assert!(!db
+                    .add_overflow_player_to_room(&room.id, player("P6"), 5)
+                    .await
+                    .unwrap());
+
+                let stored = db.get_room_by_id(&room.id).await.unwrap().unwrap();
+                assert!(stored.allow_overflow_joins);
+                assert_eq!(stored.players.len(), 5);
+                assert_eq!(stored.max_players, 4);
+            }
+
+            #[tokio::test]
+            async fn test_stale_players_count_from_their_last_heartbeat() {
+                let (db, _guard) = new_database().await;
+                let room = create_test_room(&db, "game1", "STALE1")
+                    .await
+                    .expect("room creation should succeed");
+                let stale_player = PlayerInfo {
+                    id: Uuid::new_v4(),
+                    name: "Idle".to_string(),
+                    is_authority: false,
+                    role: PlayerRole::Member,
+                    is_ready: false,
+                    connected_at: chrono::Utc::now() - chrono::Duration::hours(1),
+                    connection_info: None,
+                    region_id: "us-east-1".to_string(),
+                };
+                let player_id = stale_player.id;
+                assert!(db.add_player_to_room(&room.id, stale_player).await.unwrap());
+
+                let threshold = chrono::Duration::minutes(5);
+                assert_eq!(
+                    db.get_stale_players(threshold).await.unwrap(),
+                    vec![(room.id, player_id)],
+                    "only the player unseen since joining an hour ago is stale"
+                );
+
+                db.update_player_last_seen(&player_id).await.unwrap();
+                assert!(db.get_stale_players(threshold).await.unwrap().is_empty());
+
+                db.remove_player_from_room(&room.id, &player_id)
+                    .await
+                    .unwrap();
+                assert!(db
+                    .get_stale_players(chrono::Duration::zero())
+                    .await
+                    .unwrap()
+                    .iter()
+                    .all(|(_, stale)| *stale != player_id));
+            }
+        }
+    };
+}
+
+pub(crate) use database_conformance_tests;
@@ -0,0 +1,959 @@
+//! File-backed room storage (`persistent` feature).
+//!
+//! Rooms live in an [`InMemoryDatabase`] exactly as without persistence, so
+//! every operation keeps its in-memory semantics and latency. Each change
+//! wakes a background writer that snapshots all rooms and claimed cleanup
+//! events to a JSON file, at most once per [`WRITE_INTERVAL`]. Snapshots are
+//! written to a temporary file that is then renamed over the previous one, so
+//! a crash leaves either the old or the new snapshot, never a torn one.
+//! [`GameDatabase::flush`] writes synchronously and is called on graceful
+//! shutdown.
+//!
+//! On startup the snapshot is read back and rooms that have been inactive for
+//! longer than their game's inactive timeout are dropped.
+
+use super::*;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Mutex, Notify, RwLock};
+
+/// Minimum time between two background snapshot writes.
+pub const WRITE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Everything the file backend persists.
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    saved_at: chrono::DateTime<chrono::Utc>,
+    rooms: Vec<Room>,
+    cleanup_events: HashMap<String, CleanupEventEntry>,
+}
+
+/// Rooms stored in memory and snapshotted to a file.
+pub struct FileDatabase {
+    inner: InMemoryDatabase,
+    store: Arc<SnapshotStore>,
+    writer: tokio::task::JoinHandle<()>,
+}
+
+/// The state shared with the background writer.
+struct SnapshotStore {
+    path: PathBuf,
+    rooms: Arc<RwLock<HashMap<RoomId, Room>>>,
+    cleanup_events: Arc<RwLock<HashMap<String, CleanupEventEntry>>>,
+    changed: Notify,
+    /// Keeps the writer and `flush` from renaming over each other
+    write_lock: Mutex<()>,
+}
+
+impl FileDatabase {
+    /// Open the snapshot at `path`, or start empty if it does not exist yet.
+    ///
+    /// Rooms inactive for longer than `expiry` allows are dropped. Fails if
+    /// the snapshot cannot be parsed or the file cannot be written, rather
+    /// than starting empty and overwriting it.
+    pub async fn open(
+        path: impl Into<PathBuf>,
+        protocol_config: ProtocolConfig,
+        game_overrides: GameOverrideConfig,
+        expiry: &RoomExpiryPolicy,
+    ) -> Result<Self> {
+        let path = path.into();
+        let (saved_rooms, cleanup_events) = read_snapshot(&path)?
+            .map(|snapshot| (snapshot.rooms, snapshot.cleanup_events))
+            .unwrap_or_default();
+
+        let now = chrono::Utc::now();
+        let mut rooms = HashMap::new();
+        let mut room_codes = HashMap::new();
+        let mut expired = 0usize;
+        for mut room in saved_rooms {
+            if now - room.last_activity > expiry.timeouts_for(&room.game_name).inactive {
+                expired += 1;
+                continue;
+            }
+            // Player regions are not serialized; players share their room's region
+            for player in room.players.values_mut() {
+                player.region_id.clone_from(&room.region_id);
+            }
+            room_codes.insert((room.game_name.clone(), room.code.clone()), room.id);
+            rooms.insert(room.id, room);
+        }
+        tracing::info!(
+            path = %path.display(),
+            restored = rooms.len(),
+            expired,
+            "Opened room snapshot"
+        );
+
+        let mut inner = InMemoryDatabase::with_room_code_config(protocol_config, game_overrides);
+        inner.rooms = Arc::new(RwLock::new(rooms));
+        inner.room_codes = Arc::new(RwLock::new(room_codes));
+        inner.cleanup_events = Arc::new(RwLock::new(cleanup_events));
+
+        let store = Arc::new(SnapshotStore {
+            path,
+            rooms: Arc::clone(&inner.rooms),
+            cleanup_events: Arc::clone(&inner.cleanup_events),
+            changed: Notify::new(),
+            write_lock: Mutex::new(()),
+        });
+        // Persist the reconciled state now so an unwritable path fails startup
+        store
+            .write()
+            .await
+            .with_context(|| format!("failed to write room snapshot {}", store.path.display()))?;
+        let writer = tokio::spawn(Arc::clone(&store).run_writer());
+
+        Ok(Self {
+            inner,
+            store,
+            writer,
+        })
+    }
+
+    /// The snapshot file this database writes to.
+    pub fn path(&self) -> &Path {
+        &self.store.path
+    }
+
+    /// Schedule a snapshot write after a change.
+    fn changed(&self) {
+        self.store.changed.notify_one();
+    }
+}
+
+impl Drop for FileDatabase {
+    fn drop(&mut self) {
+        self.writer.abort();
+    }
+}
+
+impl SnapshotStore {
+    async fn run_writer(self: Arc<Self>) {
+        loop {
+            // Changes made while a write is in flight leave a permit behind,
+            // so they are picked up by the next iteration
+            self.changed.notified().await;
+            if let Err(err) = self.write().await {
+                tracing::warn!(path = %self.path.display(), error = %err, "Failed to write room snapshot");
+            }
+            tokio::time::sleep(WRITE_INTERVAL).await;
+        }
+    }
+
+    async fn write(&self) -> io::Result<()> {
+        let _write = self.write_lock.lock().await;
+        // Taken one after the other, never together, to stay clear of the
+        // inner database's lock ordering
+        let rooms = self.rooms.read().await.values().cloned().collect();
+        let cleanup_events = self.cleanup_events.read().await.clone();
+        let snapshot = Snapshot {
+            saved_at: chrono::Utc::now(),
+            rooms,
+            cleanup_events,
+        };
+        let path = self.path.clone();
+        tokio::task::spawn_blocking(move || write_snapshot(&path, &snapshot))
+            .await
+            .map_err(io::Error::other)?
+    }
+}
+
+fn read_snapshot(path: &Path) -> Result<Option<Snapshot>> {
+    let contents = match fs::read(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => {
+            return Err(err)
+                .with_context(|| format!("failed to read room snapshot {}", path.display()))
+        }
+    };
+    serde_json::from_slice(&contents)
+        .map(Some)
+        .with_context(|| format!("failed to parse room snapshot {}", path.display()))
+}
+
+fn write_snapshot(path: &Path, snapshot: &Snapshot) -> io::Result<()> {
+    let json = serde_json::to_vec(snapshot).map_err(io::Error::other)?;
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    let temp_path = path.with_file_name(name);
+
+    let mut file = File::create(&temp_path)?;
+    file.write_all(&json)?;
+    file.sync_all()?;
+    drop(file);
+    fs::rename(&temp_path, path)
+}
+
+#[async_trait]
+impl GameDatabase for FileDatabase {
+    async fn initialize(&self) -> Result<()> {
+        self.inner.initialize().await
+    }
+
+    async fn create_room(
+        &self,
+        game_name: String,
+        room_code: Option<String>,
+        max_players: u8,
+        supports_authority: bool,
+        creator_id: PlayerId,
+        relay_type: String,
+        region_id: String,
+        application_id: Option<Uuid>,
+    ) -> Result<Room> {
+        let room = self
+            .inner
+            .create_room(
+                game_name,
+                room_code,
+                max_players,
+                supports_authority,
+                creator_id,
+                relay_type,
+                region_id,
+                application_id,
+            )
+            .await?;
+        self.changed();
+        Ok(room)
+    }
+
+    async fn create_room_if_under_limit(
+        &self,
+        game_name: String,
+        room_code: Option<String>,
+        max_players: u8,
+        supports_authority: bool,
+        creator_id: PlayerId,
+        relay_type: String,
+        region_id: String,
+        application_id: Option<Uuid>,
+        max_rooms: usize,
+    ) -> Result<Option<Room>> {
+        let room = self
+            .inner
+            .create_room_if_under_limit(
+                game_name,
+                room_code,
+                max_players,
+                supports_authority,
+                creator_id,
+                relay_type,
+                region_id,
+                application_id,
+                max_rooms,
+            )
+            .await?;
+        if room.is_some() {
+            self.changed();
+        }
+        Ok(room)
+    }
+
+    async fn set_room_application_id(&self, room_id: &RoomId, application_id: Uuid) -> Result<()> {
+        self.inner
+            .set_room_application_id(room_id, application_id)
+            .await?;
+        self.changed();
+        Ok(())
+    }
+
+    async fn clear_room_application_id(&self, room_id: &RoomId) -> Result<()> {
+        self.inner.clear_room_application_id(room_id).await?;
+        self.changed();
+        Ok(())
+    }
+
+    async fn set_spectator_chat_mode(
+        &self,
+        room_id: &RoomId,
+        mode: SpectatorChatMode,
+    ) -> Result<()> {
+        self.inner.set_spectator_chat_mode(room_id, mode).await?;
+        self.changed();
+        Ok(())
+    }
+
+    async fn set_allow_overflow_joins(&self, room_id: &RoomId, allow: bool) -> Result<()> {
+        self.inner.set_allow_overflow_joins(room_id, allow).await?;
+        self.changed();
+        Ok(())
+    }
+
+    async fn set_invites_authority_only(
+        &self,
+        room_id: &RoomId,
+        authority_only: bool,
+    ) -> Result<()> {
+        self.inner
+            .set_invites_authority_only(room_id, authority_only)
+            .await?;
+        self.changed();
+        Ok(())
+    }
+
+    async fn get_room(&self, game_name: &str, room_code: &str) -> Result<Option<Room>> {
+        self.inner.get_room(game_name, room_code).await
+    }
+
+    async fn get_room_by_id(&self, room_id: &RoomId) -> Result<Option<Room>> {
+        self.inner.get_room_by_id(room_id).await
+    }
+
+    async fn get_room_summary(&self, room_id: &RoomId) -> Result<Option<RoomSummary>> {
+        self.inner.get_room_summary(room_id).await
+    }
+
+    async fn add_player_to_room(&self, room_id: &RoomId, player: PlayerInfo) -> Result<bool> {
+        let added = self.inner.add_player_to_room(room_id, player).await?;
+        if added {
+            self.changed();
+        }
+        Ok(added)
+    }
+
+    async fn add_overflow_player_to_room(
+        &self,
+        room_id: &RoomId,
+        player: PlayerInfo,
+        player_limit: u8,
+    ) -> Result<bool> {
+        let added = self
+            .inner
+            .add_overflow_player_to_room(room_id, player, player_limit)
+            .await?;
+        if added {
+            self.changed();
+        }
+        Ok(added)
+    }
+
+    async fn remove_player_from_room(
+        &self,
+        room_id: &RoomId,
+        player_id: &PlayerId,
+    ) -> Result<Option<PlayerInfo>> {
+        let removed = self
+            .inner
+            .remove_player_from_room(room_id, player_id)
+            .await?;
+        if removed.is_some() {
+            self.changed();
+        }
+        Ok(removed)
+    }
+
+    async fn update_room_authority(
+        &self,
+        room_id: &RoomId,
+        authority_player: Option<PlayerId>,
+    ) -> Result<bool> {
+        let updated = self
+            .inner
+            .update_room_authority(room_id, authority_player)
+            .await?;
+        if updated {
+            self.changed();
+        }
+        Ok(updated)
+    }
+
+    async fn request_room_authority(
+        &self,
+        room_id: &RoomId,
+        player_id: &PlayerId,
+        become_authority: bool,
+    ) -> Result<(bool, Option<String>)> {
+        let outcome = self
+            .inner
+            .request_room_authority(room_id, player_id, become_authority)
+            .await?;
+        if outcome.0 {
+            self.changed();
+        }
+        Ok(outcome)
+    }
+
+    async fn update_player_name(
+        &self,
+        room_id: &RoomId,
+        player_id: &PlayerId,
+        name: &str,
+    ) -> Result<bool> {
+        let updated = self
+            .inner
+            .update_player_name(room_id, player_id, name)
+            .await?;
+        if updated {
+            self.changed();
+        }
+        Ok(updated)
+    }
+
+    async fn set_player_role(
+        &self,
+        room_id: &RoomId,
+        player_id: &PlayerId,
+        role: PlayerRole,
+    ) -> Result<bool> {
+        let updated = self.inner.set_player_role(room_id, player_id, role).await?;
+        if updated {
+            self.changed();
+        }
+        Ok(updated)
+    }
+
+    async fn set_room_relay_session(
+        &self,
+        room_id: &RoomId,
+        session: Option<serde_json::Value>,
+    ) -> Result<bool> {
+        let updated = self.inner.set_room_relay_session(room_id, session).await?;
+        if updated {
+            self.changed();
+        }
+        Ok(updated)
+    }
+
+    async fn set_room_metadata(
+        &self,
+        room_id: &RoomId,
+        entries: &HashMap<String, String>,
+    ) -> Result<RoomMetadataUpdate> {
+        let update = self.inner.set_room_metadata(room_id, entries).await?;
+        if matches!(update, RoomMetadataUpdate::Applied(_)) {
+            self.changed();
+        }
+        Ok(update)
+    }
+
+    async fn update_room_settings(
+        &self,
+        room_id: &RoomId,
+        max_players: Option<u8>,
+        max_spectators: Option<u8>,
+    ) -> Result<RoomSettingsUpdate> {
+        let update = self
+            .inner
+            .update_room_settings(room_id, max_players, max_spectators)
+            .await?;
+        if matches!(update, RoomSettingsUpdate::Applied { .. }) {
+            self.changed();
+        }
+        Ok(update)
+    }
+
+    async fn update_player_connection_info(
+        &self,
+        room_id: &RoomId,
+        player_id: &PlayerId,
+        connection_info: ConnectionInfo,
+    ) -> Result<bool> {
+        let updated = self
+            .inner
+            .update_player_connection_info(room_id, player_id, connection_info)
+            .await?;
+        if updated {
+            self.changed();
+        }
+        Ok(updated)
+    }
+
+    async fn get_room_players(&self, room_id: &RoomId) -> Result<Vec<PlayerInfo>> {
+        self.inner.get_room_players(room_id).await
+    }
+
+    async fn cleanup_empty_rooms(&self, policy: &RoomExpiryPolicy) -> Result<Vec<RoomId>> {
+        let removed = self.inner.cleanup_empty_rooms(policy).await?;
+        if !removed.is_empty() {
+            self.changed();
+        }
+        Ok(removed)
+    }
+
+    async fn cleanup_expired_rooms(&self, policy: &RoomExpiryPolicy) -> Result<RoomCleanupOutcome> {
+        let outcome = self.inner.cleanup_expired_rooms(policy).await?;
+        if !outcome.is_empty() {
+            self.changed();
+        }
+        Ok(outcome)
+    }
+
+    async fn list_rooms_near_expiry(
+        &self,
+        policy: &RoomExpiryPolicy,
+        warn_window: chrono::Duration,
+    ) -> Result<Vec<ExpiringRoom>> {
+        let rooms = self
+            .inner
+            .list_rooms_near_expiry(policy, warn_window)
+            .await?;
+        if !rooms.is_empty() {
+            self.changed();
+        }
+        Ok(rooms)
+    }
+
+    async fn update_room_activity(&self, room_id: &RoomId) -> Result<()> {
+        self.inner.update_room_activity(room_id).await?;
+        self.changed();
+        Ok(())
+    }
+
+    async fn delete_room(&self, room_id: &RoomId) -> Result<bool> {
+        let deleted = self.inner.delete_room(room_id).await?;
+        if deleted {
+            self.changed();
+        }
+        Ok(deleted)
+    }
+
+    async fn get_game_room_count(&self, game_name: &str) -> Result<usize> {
+        self.inner.get_game_room_count(game_name).await
+    }
+
+    async fn find_joinable_room(
+        &self,
+        game_name: &str,
+        region_id: Option<&str>,
+    ) -> Result<Option<RoomId>> {
+        self.inner.find_joinable_room(game_name, region_id).await
+    }
+
+    async fn get_app_room_count(&self, application_id: &Uuid) -> Result<usize> {
+        self.inner.get_app_room_count(application_id).await
+    }
+
+    async fn count_players_by_application(&self, application_id: &Uuid) -> Result<usize> {
+        self.inner
+            .count_players_by_application(application_id)
+            .await
+    }
+
+    async fn set_room_creator(&self, room_id: &RoomId, creator_key: &str) -> Result<()> {
+        self.inner.set_room_creator(room_id, creator_key).await?;
+        self.changed();
+        Ok(())
+    }
+
+    async fn count_rooms_by_creator(&self, creator_key: &str) -> Result<usize> {
+        self.inner.count_rooms_by_creator(creator_key).await
+    }
+
+    async fn health_check(&self) -> bool {
+        self.inner.health_check().await
+    }
+
+    async fn update_player_last_seen(&self, player_id: &PlayerId) -> Result<()> {
+        self.inner.update_player_last_seen(player_id).await
+    }
+
+    async fn get_stale_players(
+        &self,
+        stale_threshold: chrono::Duration,
+    ) -> Result<Vec<(RoomId, PlayerId)>> {
+        self.inner.get_stale_players(stale_threshold).await
+    }
+
+    async fn get_rooms_by_game(&self) -> Result<HashMap<String, usize>> {
+        self.inner.get_rooms_by_game().await
+    }
+
+    async fn get_players_by_game(&self) -> Result<HashMap<String, usize>> {
+        self.inner.get_players_by_game().await
+    }
+
+    async fn get_player_count_percentiles(&self) -> Result<HashMap<String, f64>> {
+        self.inner.get_player_count_percentiles().await
+    }
+
+    async fn get_game_player_percentiles(&self) -> Result<HashMap<String, HashMap<String, f64>>> {
+        self.inner.get_game_player_percentiles().await
+    }
+
+    async fn transition_room_to_lobby(&self, room_id: &RoomId) -> Result<Option<u64>> {
+        let version = self.inner.transition_room_to_lobby(room_id).await?;
+        if version.is_some() {
+            self.changed();
+        }
+        Ok(version)
+    }
+
+    async fn transition_room_to_waiting(&self, room_id: &RoomId) -> Result<Option<u64>> {
+        let version = self.inner.transition_room_to_waiting(room_id).await?;
+        if version.is_some() {
+            self.changed();
+        }
+        Ok(version)
+    }
+
+    async fn toggle_player_ready(
+        &self,
+        room_id: &RoomId,
+        player_id: &PlayerId,
+    ) -> Result<Option<LobbyReadiness>> {
+        let readiness = self.inner.toggle_player_ready(room_id, player_id).await?;
+        if readiness.is_some() {
+            self.changed();
+        }
+        Ok(readiness)
+    }
+
+    async fn finalize_room_game(&self, room_id: &RoomId) -> Result<Option<u64>> {
+        let version = self.inner.finalize_room_game(room_id).await?;
+        if version.is_some() {
+            self.changed();
+        }
+        Ok(version)
+    }
+
+    async fn add_spectator_to_room(
+        &self,
+        room_id: &RoomId,
+        spectator: SpectatorInfo,
+    ) -> Result<bool> {
+        let added = self.inner.add_spectator_to_room(room_id, spectator).await?;
+        if added {
+            self.changed();
+        }
+        Ok(added)
+    }
+
+    async fn remove_spectator_from_room(
+        &self,
+        room_id: &RoomId,
+        spectator_id: &PlayerId,
+    ) -> Result<Option<SpectatorInfo>> {
+        let removed = self
+            .inner
+            .remove_spectator_from_room(room_id, spectator_id)
+            .await?;
+        if removed.is_some() {
+            self.changed();
+        }
+        Ok(removed)
+    }
+
+    async fn get_room_spectators(&self, room_id: &RoomId) -> Result<Vec<SpectatorInfo>> {
+        self.inner.get_room_spectators(room_id).await
+    }
+
+    async fn add_to_waiting_list(
+        &self,
+        room_id: &RoomId,
+        player_id: &PlayerId,
+        max_size: usize,
+    ) -> Result<Option<usize>> {
+        let position = self
+            .inner
+            .add_to_waiting_list(room_id, player_id, max_size)
+            .await?;
+        if position.is_some() {
+            self.changed();
+        }
+        Ok(position)
+    }
+
+    async fn pop_waiting_list(&self, room_id: &RoomId) -> Result<Option<PlayerId>> {
+        let next = self.inner.pop_waiting_list(room_id).await?;
+        if next.is_some() {
+            self.changed();
+        }
+        Ok(next)
+    }
+
+    async fn ban_player_from_room(
+        &self,
+        room_id: &RoomId,
+        player_id: &PlayerId,
+        client_ip: Option<IpAddr>,
+    ) -> Result<bool> {
+        let banned = self
+            .inner
+            .ban_player_from_room(room_id, player_id, client_ip)
+            .await?;
+        if banned {
+            self.changed();
+        }
+        Ok(banned)
+    }
+
+    async fn unban_player_from_room(&self, room_id: &RoomId, player_id: &PlayerId) -> Result<bool> {
+        let unbanned = self
+            .inner
+            .unban_player_from_room(room_id, player_id)
+            .await?;
+        if unbanned {
+            self.changed();
+        }
+        Ok(unbanned)
+    }
+
+    async fn append_room_history(
+        &self,
+        room_id: &RoomId,
+        message: HistoricalMessage,
+        capacity: usize,
+    ) -> Result<bool> {
+        let appended = self
+            .inner
+            .append_room_history(room_id, message, capacity)
+            .await?;
+        if appended {
+            self.changed();
+        }
+        Ok(appended)
+    }
+
+    async fn get_room_history(&self, room_id: &RoomId) -> Result<Vec<HistoricalMessage>> {
+        self.inner.get_room_history(room_id).await
+    }
+
+    async fn remove_from_waiting_list(
+        &self,
+        room_id: &RoomId,
+        player_id: &PlayerId,
+    ) -> Result<bool> {
+        let removed = self
+            .inner
+            .remove_from_waiting_list(room_id, player_id)
+            .await?;
+        if removed {
+            self.changed();
+        }
+        Ok(removed)
+    }
+
+    async fn try_claim_room_cleanup(
+        &self,
+        room_id: &RoomId,
+        cleanup_type: &str,
+        instance_id: &uuid::Uuid,
+    ) -> Result<bool> {
+        let claimed = self
+            .inner
+            .try_claim_room_cleanup(room_id, cleanup_type, instance_id)
+            .await?;
+        if claimed {
+            self.changed();
+        }
+        Ok(claimed)
+    }
+
+    async fn cleanup_old_room_cleanup_events(&self) -> Result<u64> {
+        let removed = self.inner.cleanup_old_room_cleanup_events().await?;
+        if removed > 0 {
+            self.changed();
+        }
+        Ok(removed)
+    }
+
+    // Idempotency keys only guard against retries within minutes, so they
+    // are not part of the snapshot
+    async fn claim_room_idempotency_key(
+        &self,
+        app_id: &Uuid,
+        key: &str,
+        room: IdempotentRoom,
+        ttl: chrono::Duration,
+    ) -> Result<Option<IdempotentRoom>> {
+        self.inner
+            .claim_room_idempotency_key(app_id, key, room, ttl)
+            .await
+    }
+
+    async fn cleanup_expired_room_idempotency_keys(&self) -> Result<u64> {
+        self.inner.cleanup_expired_room_idempotency_keys().await
+    }
+
+    fn as_any(&self) -> &(dyn Any + Send + Sync) {
+        self
+    }
+
+    async fn flush(&self) -> Result<()> {
+        self.store
+            .write()
+            .await
+            .with_context(|| format!("failed to write room snapshot {}", self.path().display()))
+    }
+
+    async fn admin_user_exists(&self, email: &str) -> Result<bool> {
+        self.inner.admin_user_exists(email).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn new_database() -> (FileDatabase, tempfile::TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        let db = open(dir.path(), &keep_all()).await.unwrap();
+        (db, dir)
+    }
+
+    async fn open(dir: &Path, expiry: &RoomExpiryPolicy) -> Result<FileDatabase> {
+        FileDatabase::open(
+            dir.join("rooms.json"),
+            ProtocolConfig::default(),
+            GameOverrideConfig::new(),
+            expiry,
+        )
+        .await
+    }
+
+    fn keep_all() -> RoomExpiryPolicy {
+        RoomExpiryPolicy::uniform(chrono::Duration::hours(1), chrono::Duration::hours(1))
+    }
+
+    super::super::conformance_tests::database_conformance_tests!();
+
+    async fn create_room(db: &FileDatabase, game_name: &str, code: &str) -> Room {
+        db.create_room(
+            game_name.to_string(),
+            Some(code.to_string()),
+            4,
+            true,
+            Uuid::new_v4(),
+            "relay".to_string(),
+            "eu-west".to_string(),
+            None,
+        )
+        .await
+        .expect("room creation should succeed")
+    }
+
+    #[tokio::test]
+    async fn rooms_players_and_spectators_survive_a_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = open(dir.path(), &keep_all()).await.unwrap();
+        let room = create_room(&db, "chess", "SAVE01").await;
+        let guest = PlayerInfo {
+            id: Uuid::new_v4(),
+            name: "Guest".to_string(),
+            is_authority: false,
+            role: PlayerRole::Member,
+            is_ready: false,
+            connected_at: chrono::Utc::now(),
+            connection_info: None,
+            region_id: "eu-west".to_string(),
+        };
+        let spectator = SpectatorInfo {
+            id: Uuid::new_v4(),
+            name: "Watcher".to_string(),
+            connected_at: chrono::Utc::now(),
+        };
+        assert!(db
+            .add_player_to_room(&room.id, guest.clone())
+            .await
+            .unwrap());
+        assert!(db
+            .add_spectator_to_room(&room.id, spectator.clone())
+            .await
+            .unwrap());
+        assert!(db
+            .try_claim_room_cleanup(&room.id, "empty", &Uuid::new_v4())
+            .await
+            .unwrap());
+        db.flush().await.unwrap();
+        drop(db);
+
+        let db = open(dir.path(), &keep_all()).await.unwrap();
+        let restored = db
+            .get_room("chess", "SAVE01")
+            .await
+            .unwrap()
+            .expect("room should be restored");
+        assert_eq!(restored.id, room.id);
+        assert_eq!(restored.players.len(), 2);
+        assert_eq!(restored.players[&guest.id].name, "Guest");
+        assert_eq!(restored.players[&guest.id].region_id, "eu-west");
+        assert_eq!(restored.authority_player, room.authority_player);
+        assert!(restored.spectators.contains_key(&spectator.id));
+        assert!(
+            !db.try_claim_room_cleanup(&room.id, "empty", &Uuid::new_v4())
+                .await
+                .unwrap(),
+            "claimed cleanups should survive the restart"
+        );
+        // The room code is still taken after the restart
+        assert!(db
+            .create_room(
+                "chess".to_string(),
+                Some("SAVE01".to_string()),
+                4,
+                true,
+                Uuid::new_v4(),
+                "relay".to_string(),
+                "eu-west".to_string(),
+                None,
+            )
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn changes_are_written_in_the_background() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = open(dir.path(), &keep_all()).await.unwrap();
+        let room = create_room(&db, "chess", "BACK01").await;
+
+        let path = db.path().to_path_buf();
+        let written = async {
+            loop {
+                let snapshot = read_snapshot(&path).unwrap().unwrap();
+                if snapshot.rooms.iter().any(|r| r.id == room.id) {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        };
+        tokio::time::timeout(Duration::from_secs(5), written)
+            .await
+            .expect("the room should be written without an explicit flush");
+    }
+
+    #[tokio::test]
+    async fn restart_drops_rooms_past_their_inactive_timeout() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = open(dir.path(), &keep_all()).await.unwrap();
+        let stale = create_room(&db, "quick_game", "OLD001").await;
+        let fresh = create_room(&db, "slow_game", "NEW001").await;
+        db.flush().await.unwrap();
+        drop(db);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let expiry = keep_all().with_game(
+            "quick_game",
+            RoomTimeouts {
+                empty: chrono::Duration::zero(),
+                inactive: chrono::Duration::zero(),
+            },
+        );
+        let db = open(dir.path(), &expiry).await.unwrap();
+        assert!(db.get_room_by_id(&stale.id).await.unwrap().is_none());
+        assert!(db.get_room("quick_game", "OLD001").await.unwrap().is_none());
+        assert!(db.get_room_by_id(&fresh.id).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn corrupt_snapshots_fail_to_open() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("rooms.json"), b"{ not json").unwrap();
+
+        let err = open(dir.path(), &keep_all()).await.err().unwrap();
+        assert!(err.to_string().contains("failed to parse"), "{err}");
+        // The unreadable snapshot is left for the operator to inspect
+        assert_eq!(
+            fs::read(dir.path().join("rooms.json")).unwrap(),
+            b"{ not json"
+        );
+    }
+}
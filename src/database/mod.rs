@@ -1,15 +1,39 @@
-use crate::protocol::{ConnectionInfo, PlayerId, PlayerInfo, Room, RoomId, SpectatorInfo};
+use crate::config::{GameOverrideConfig, ProtocolConfig};
+use crate::protocol::room_codes::generate_room_code_for_game;
+use crate::protocol::validation;
+use crate::protocol::{
+    ConnectionInfo, HistoricalMessage, PlayerId, PlayerInfo, PlayerRole, Room, RoomId, RoomSummary,
+    SpectatorChatMode, SpectatorInfo,
+};
 use anyhow::Result;
 use async_trait::async_trait;
 use std::any::Any;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::net::IpAddr;
+use thiserror::Error;
 use uuid::Uuid;
 
-/// Summary describing how many rooms were removed by the cleanup routine.
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg(feature = "persistent")]
+mod file;
+#[cfg(feature = "persistent")]
+pub use file::FileDatabase;
+
+/// Returned by [`GameDatabase::add_player_to_room`] when another player in the
+/// room already uses the joiner's name, compared case-insensitively.
+#[derive(Debug, Default, Error)]
+#[error("Player name already exists in this room")]
+pub struct PlayerNameTakenError {
+    /// A free name to offer the player instead; the database leaves it unset
+    pub suggested: Option<String>,
+}
+
+/// Summary describing which rooms were removed by the cleanup routine.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct RoomCleanupOutcome {
     pub empty_rooms_cleaned: usize,
     pub inactive_rooms_cleaned: usize,
+    pub empty_room_ids: Vec<RoomId>,
+    pub inactive_room_ids: Vec<RoomId>,
 }
 
 impl RoomCleanupOutcome {
@@ -23,6 +47,87 @@ impl RoomCleanupOutcome {
     }
 }
 
+/// A room about to be cleaned up whose players have not been warned yet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExpiringRoom {
+    pub room_id: RoomId,
+    /// Time left until the room is eligible for cleanup
+    pub expires_in: chrono::Duration,
+}
+
+/// Lobby readiness after [`GameDatabase::toggle_player_ready`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LobbyReadiness {
+    pub lobby_state: crate::protocol::LobbyState,
+    pub ready_players: Vec<PlayerId>,
+    pub all_ready: bool,
+    /// Room lobby version after the toggle
+    pub lobby_version: u64,
+}
+
+/// Outcome of [`GameDatabase::set_room_metadata`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RoomMetadataUpdate {
+    /// The room's metadata after the update
+    Applied(HashMap<String, String>),
+    /// The update would break a metadata limit; the room is unchanged
+    Rejected(String),
+    RoomNotFound,
+}
+
+/// Outcome of [`GameDatabase::update_room_settings`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RoomSettingsUpdate {
+    Applied {
+        max_players: u8,
+        max_spectators: Option<u8>,
+        /// Lobby version after a lobby that is no longer full returned to Waiting
+        reopened_lobby_version: Option<u64>,
+    },
+    /// More players or spectators are present than the requested capacity;
+    /// the room is unchanged
+    BelowOccupancy(String),
+    RoomNotFound,
+}
+
+/// Empty and inactive room timeouts for one game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RoomTimeouts {
+    pub empty: chrono::Duration,
+    pub inactive: chrono::Duration,
+}
+
+/// Room timeouts used by the cleanup routines, with optional per-game overrides.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoomExpiryPolicy {
+    default: RoomTimeouts,
+    per_game: HashMap<String, RoomTimeouts>,
+}
+
+impl RoomExpiryPolicy {
+    /// Apply the same timeouts to every game.
+    pub fn uniform(empty: chrono::Duration, inactive: chrono::Duration) -> Self {
+        Self {
+            default: RoomTimeouts { empty, inactive },
+            per_game: HashMap::new(),
+        }
+    }
+
+    /// Override the timeouts for `game_name`.
+    pub fn with_game(mut self, game_name: impl Into<String>, timeouts: RoomTimeouts) -> Self {
+        self.per_game.insert(game_name.into(), timeouts);
+        self
+    }
+
+    /// Timeouts that apply to rooms of `game_name`.
+    pub fn timeouts_for(&self, game_name: &str) -> RoomTimeouts {
+        self.per_game
+            .get(game_name)
+            .copied()
+            .unwrap_or(self.default)
+    }
+}
+
 /// Database abstraction trait for game server storage
 #[async_trait]
 pub trait GameDatabase: Send + Sync {
@@ -43,6 +148,24 @@ pub trait GameDatabase: Send + Sync {
         region_id: String,
         application_id: Option<Uuid>,
     ) -> Result<Room>;
+
+    /// Create a room unless `game_name` already has `max_rooms` rooms.
+    /// The count and the insert are atomic, so concurrent creates cannot
+    /// overshoot the limit. Returns `None` when the game is at its limit.
+    #[allow(clippy::too_many_arguments)]
+    async fn create_room_if_under_limit(
+        &self,
+        game_name: String,
+        room_code: Option<String>,
+        max_players: u8,
+        supports_authority: bool,
+        creator_id: PlayerId,
+        relay_type: String,
+        region_id: String,
+        application_id: Option<Uuid>,
+        max_rooms: usize,
+    ) -> Result<Option<Room>>;
+
     async fn set_room_application_id(
         &self,
         _room_id: &RoomId,
@@ -55,15 +178,58 @@ pub trait GameDatabase: Send + Sync {
         Ok(())
     }
 
+    /// Set who receives spectator chat in a room
+    async fn set_spectator_chat_mode(
+        &self,
+        _room_id: &RoomId,
+        _mode: SpectatorChatMode,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    /// Set whether joins to the full room go to its authority for approval
+    async fn set_allow_overflow_joins(&self, _room_id: &RoomId, _allow: bool) -> Result<()> {
+        Ok(())
+    }
+
+    /// Set whether only the room authority may create invites
+    async fn set_invites_authority_only(
+        &self,
+        _room_id: &RoomId,
+        _authority_only: bool,
+    ) -> Result<()> {
+        Ok(())
+    }
+
     /// Get room by game name and room code
     async fn get_room(&self, game_name: &str, room_code: &str) -> Result<Option<Room>>;
 
     /// Get room by ID
     async fn get_room_by_id(&self, room_id: &RoomId) -> Result<Option<Room>>;
 
+    /// Get a room's counts and state by ID without copying its players,
+    /// spectators or history. Prefer it over [`Self::get_room_by_id`] when
+    /// those are not needed.
+    async fn get_room_summary(&self, room_id: &RoomId) -> Result<Option<RoomSummary>>;
+
     /// Add player to room (atomic operation)
+    ///
+    /// Returns `Ok(false)` if the room is full and fails with
+    /// [`PlayerNameTakenError`] if another player already has the name.
     async fn add_player_to_room(&self, room_id: &RoomId, player: PlayerInfo) -> Result<bool>;
 
+    /// Add a player the room authority admitted beyond `max_players`
+    /// (atomic operation)
+    ///
+    /// Like [`Self::add_player_to_room`], with the room full only once it
+    /// holds `player_limit` players.
+    async fn add_overflow_player_to_room(
+        &self,
+        room_id: &RoomId,
+        player: PlayerInfo,
+        player_limit: u8,
+    ) -> Result<bool>;
+
     /// Remove player from room
     async fn remove_player_from_room(
         &self,
@@ -95,6 +261,38 @@ pub trait GameDatabase: Send + Sync {
         name: &str,
     ) -> Result<bool>;
 
+    /// Set a player's room role; returns false if the player is not in the room
+    async fn set_player_role(
+        &self,
+        room_id: &RoomId,
+        player_id: &PlayerId,
+        role: PlayerRole,
+    ) -> Result<bool>;
+
+    /// Store relay session data on a room. Returns `false` if the room no longer exists.
+    async fn set_room_relay_session(
+        &self,
+        room_id: &RoomId,
+        session: Option<serde_json::Value>,
+    ) -> Result<bool>;
+
+    /// Atomically merge metadata entries into a room; an empty value removes its key.
+    async fn set_room_metadata(
+        &self,
+        room_id: &RoomId,
+        entries: &HashMap<String, String>,
+    ) -> Result<RoomMetadataUpdate>;
+
+    /// Atomically change a room's player and spectator capacity; `None`
+    /// leaves a setting unchanged. A lobby that is no longer full returns to
+    /// Waiting with readiness cleared.
+    async fn update_room_settings(
+        &self,
+        room_id: &RoomId,
+        max_players: Option<u8>,
+        max_spectators: Option<u8>,
+    ) -> Result<RoomSettingsUpdate>;
+
     /// Update player connection info for P2P establishment
     async fn update_player_connection_info(
         &self,
@@ -107,14 +305,18 @@ pub trait GameDatabase: Send + Sync {
     async fn get_room_players(&self, room_id: &RoomId) -> Result<Vec<PlayerInfo>>;
 
     /// Delete empty rooms and return their IDs for relay cleanup
-    async fn cleanup_empty_rooms(&self, empty_timeout: chrono::Duration) -> Result<Vec<RoomId>>;
+    async fn cleanup_empty_rooms(&self, policy: &RoomExpiryPolicy) -> Result<Vec<RoomId>>;
+
+    /// Delete expired rooms based on each game's timeouts and return a summary of what was removed.
+    async fn cleanup_expired_rooms(&self, policy: &RoomExpiryPolicy) -> Result<RoomCleanupOutcome>;
 
-    /// Delete expired rooms based on timeouts and return a summary of what was removed.
-    async fn cleanup_expired_rooms(
+    /// Rooms that expire within `warn_window` and have not been warned this expiry cycle.
+    /// Returned rooms are marked as warned until their next activity.
+    async fn list_rooms_near_expiry(
         &self,
-        empty_timeout: chrono::Duration,
-        inactive_timeout: chrono::Duration,
-    ) -> Result<RoomCleanupOutcome>;
+        policy: &RoomExpiryPolicy,
+        warn_window: chrono::Duration,
+    ) -> Result<Vec<ExpiringRoom>>;
 
     /// Update room activity timestamp
     async fn update_room_activity(&self, room_id: &RoomId) -> Result<()>;
@@ -126,37 +328,70 @@ pub trait GameDatabase: Send + Sync {
     /// Get room count for a specific game (for rate limiting)
     async fn get_game_room_count(&self, game_name: &str) -> Result<usize>;
 
+    /// An open, non-finalized room of `game_name` with a free player slot.
+    /// Rooms in `region_id` come first when given; among those the fullest
+    /// (then oldest) room wins so players are packed together
+    async fn find_joinable_room(
+        &self,
+        game_name: &str,
+        region_id: Option<&str>,
+    ) -> Result<Option<RoomId>>;
+
+    /// Get room count for a specific application (for per-app quotas)
+    async fn get_app_room_count(&self, application_id: &Uuid) -> Result<usize>;
+
+    /// Count players seated in rooms owned by a specific application
+    async fn count_players_by_application(&self, application_id: &Uuid) -> Result<usize>;
+
+    /// Record the identity a room's creator is rate limited by
+    async fn set_room_creator(&self, room_id: &RoomId, creator_key: &str) -> Result<()>;
+
+    /// Count rooms recorded against `creator_key` (for per-creator room caps)
+    async fn count_rooms_by_creator(&self, creator_key: &str) -> Result<usize>;
+
     /// Health check
     async fn health_check(&self) -> bool;
 
     /// Update player's last_seen (heartbeat) for cross-instance liveness
     async fn update_player_last_seen(&self, player_id: &PlayerId) -> Result<()>;
 
+    /// Seated players not seen for longer than `stale_threshold`. A player
+    /// never seen since joining counts from `connected_at`.
+    async fn get_stale_players(
+        &self,
+        stale_threshold: chrono::Duration,
+    ) -> Result<Vec<(RoomId, PlayerId)>>;
+
     /// Get room counts by game name for metrics
     async fn get_rooms_by_game(&self) -> Result<HashMap<String, usize>>;
 
+    /// Get player counts by game name for metrics
+    async fn get_players_by_game(&self) -> Result<HashMap<String, usize>>;
+
     /// Get player count statistics for metrics
     async fn get_player_count_percentiles(&self) -> Result<HashMap<String, f64>>;
 
     /// Get player count statistics by game for metrics
     async fn get_game_player_percentiles(&self) -> Result<HashMap<String, HashMap<String, f64>>>;
 
-    /// Transition room to lobby state when full
-    async fn transition_room_to_lobby(&self, room_id: &RoomId) -> Result<()>;
+    /// Transition room to lobby state when full.
+    /// Returns the new lobby version, or `None` if the room did not transition.
+    async fn transition_room_to_lobby(&self, room_id: &RoomId) -> Result<Option<u64>>;
 
-    /// Transition room back to waiting state when no longer full
-    async fn transition_room_to_waiting(&self, room_id: &RoomId) -> Result<()>;
+    /// Transition room back to waiting state when no longer full.
+    /// Returns the new lobby version, or `None` if the room did not transition.
+    async fn transition_room_to_waiting(&self, room_id: &RoomId) -> Result<Option<u64>>;
 
-    /// Toggle player ready state and return lobby information if successful
-    /// Returns (lobby_state, ready_players, all_ready) if in lobby state
+    /// Toggle player ready state; `None` unless the room is in lobby state
     async fn toggle_player_ready(
         &self,
         room_id: &RoomId,
         player_id: &PlayerId,
-    ) -> Result<Option<(crate::protocol::LobbyState, Vec<PlayerId>, bool)>>;
+    ) -> Result<Option<LobbyReadiness>>;
 
-    /// Finalize room game when all players are ready
-    async fn finalize_room_game(&self, room_id: &RoomId) -> Result<()>;
+    /// Finalize room game when all players are ready.
+    /// Returns the new lobby version, or `None` if the room was not finalized.
+    async fn finalize_room_game(&self, room_id: &RoomId) -> Result<Option<u64>>;
 
     /// Add spectator to room (atomic operation)
     /// Returns true if successfully added, false if room is full or doesn't exist
@@ -177,6 +412,50 @@ pub trait GameDatabase: Send + Sync {
     /// Get all spectators in a room
     async fn get_room_spectators(&self, room_id: &RoomId) -> Result<Vec<SpectatorInfo>>;
 
+    /// Queue a player on a full room's waiting list.
+    /// Returns the 1-based position, or None if the waiting list is at `max_size`.
+    async fn add_to_waiting_list(
+        &self,
+        room_id: &RoomId,
+        player_id: &PlayerId,
+        max_size: usize,
+    ) -> Result<Option<usize>>;
+
+    /// Pop the next queued player off a room's waiting list
+    async fn pop_waiting_list(&self, room_id: &RoomId) -> Result<Option<PlayerId>>;
+
+    /// Ban a player from a room so later joins and spectator joins are rejected.
+    /// Returns false if the room does not exist.
+    async fn ban_player_from_room(
+        &self,
+        room_id: &RoomId,
+        player_id: &PlayerId,
+        client_ip: Option<IpAddr>,
+    ) -> Result<bool>;
+
+    /// Lift a room ban. Returns true if the player was banned.
+    async fn unban_player_from_room(&self, room_id: &RoomId, player_id: &PlayerId) -> Result<bool>;
+
+    /// Append a relayed message to a room's history, keeping the newest `capacity` entries.
+    /// Returns false if the room does not exist.
+    async fn append_room_history(
+        &self,
+        room_id: &RoomId,
+        message: HistoricalMessage,
+        capacity: usize,
+    ) -> Result<bool>;
+
+    /// A room's message history, oldest first (empty if the room does not exist)
+    async fn get_room_history(&self, room_id: &RoomId) -> Result<Vec<HistoricalMessage>>;
+
+    /// Remove a player from a room's waiting list (cancellation or disconnect)
+    /// Returns true if the player was queued
+    async fn remove_from_waiting_list(
+        &self,
+        room_id: &RoomId,
+        player_id: &PlayerId,
+    ) -> Result<bool>;
+
     /// Try to claim a room cleanup operation for idempotency.
     /// Returns true if this instance should process the cleanup (we claimed it),
     /// false if another instance already processed it.
@@ -193,9 +472,33 @@ pub trait GameDatabase: Send + Sync {
     /// Cleanup old room cleanup events (called periodically)
     async fn cleanup_old_room_cleanup_events(&self) -> Result<u64>;
 
+    /// Claim a create-room idempotency key of `app_id` for the room about to
+    /// be created as `room`. Returns the room claimed earlier with the same key
+    /// while that claim is younger than `ttl`; otherwise records this claim and
+    /// returns `None`.
+    ///
+    /// The claim is made before the room exists, so a concurrent retry gets
+    /// the same room code and waits on the same room join lock.
+    async fn claim_room_idempotency_key(
+        &self,
+        app_id: &Uuid,
+        key: &str,
+        room: IdempotentRoom,
+        ttl: chrono::Duration,
+    ) -> Result<Option<IdempotentRoom>>;
+
+    /// Forget expired create-room idempotency keys (called periodically)
+    async fn cleanup_expired_room_idempotency_keys(&self) -> Result<u64>;
+
     /// Downcast helper to access backend-specific implementations
     fn as_any(&self) -> &(dyn Any + Send + Sync);
 
+    /// Write any changes that are still buffered to durable storage.
+    /// Called during graceful shutdown; a no-op for in-memory storage.
+    async fn flush(&self) -> Result<()> {
+        Ok(())
+    }
+
     /// Check if an admin user exists. Always returns false for in-memory backend.
     /// Placeholder for future auth integration.
     async fn admin_user_exists(&self, email: &str) -> Result<bool> {
@@ -222,11 +525,14 @@ impl<T: GameDatabase + ?Sized> MetricsStore for T {}
 pub trait AdminDirectory: GameDatabase {}
 impl<T: GameDatabase + ?Sized> AdminDirectory for T {}
 
-/// Database configuration — in-memory only for signal-fish-server.
+/// Database configuration for signal-fish-server.
 #[derive(Debug, Clone, Default)]
 pub enum DatabaseConfig {
     #[default]
     InMemory,
+    /// Rooms kept in memory and snapshotted to `path`, restored on startup
+    #[cfg(feature = "persistent")]
+    File { path: std::path::PathBuf },
 }
 
 impl DatabaseConfig {
@@ -237,17 +543,54 @@ impl DatabaseConfig {
 }
 
 /// Create database instance based on configuration
-pub async fn create_database(config: DatabaseConfig) -> Result<Box<dyn GameDatabase>> {
+///
+/// Persistent backends drop restored rooms that `expiry` considers inactive.
+pub async fn create_database(
+    config: DatabaseConfig,
+    protocol_config: &ProtocolConfig,
+    game_overrides: &GameOverrideConfig,
+    expiry: &RoomExpiryPolicy,
+) -> Result<Box<dyn GameDatabase>> {
     match config {
         DatabaseConfig::InMemory => {
-            let db = InMemoryDatabase::new();
+            // Nothing to restore, so nothing to expire
+            let _ = expiry;
+            let db = InMemoryDatabase::with_room_code_config(
+                protocol_config.clone(),
+                game_overrides.clone(),
+            );
+            Ok(Box::new(db))
+        }
+        #[cfg(feature = "persistent")]
+        DatabaseConfig::File { path } => {
+            let db = FileDatabase::open(
+                path,
+                protocol_config.clone(),
+                game_overrides.clone(),
+                expiry,
+            )
+            .await?;
             Ok(Box::new(db))
         }
     }
 }
 
-/// Entry tracking a claimed room cleanup operation for idempotency
+/// The room a create-room idempotency key resolves to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdempotentRoom {
+    pub game_name: String,
+    pub room_code: String,
+}
+
+/// Entry mapping a create-room idempotency key to its room until it expires
 #[derive(Debug, Clone)]
+struct IdempotencyKeyEntry {
+    room: IdempotentRoom,
+    expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Entry tracking a claimed room cleanup operation for idempotency
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 struct CleanupEventEntry {
     #[allow(dead_code)]
     instance_id: uuid::Uuid,
@@ -261,14 +604,61 @@ pub struct InMemoryDatabase {
     room_codes: std::sync::Arc<tokio::sync::RwLock<HashMap<(String, String), RoomId>>>,
     /// Tracks claimed cleanup operations for idempotency (cleanup_id -> entry)
     cleanup_events: std::sync::Arc<tokio::sync::RwLock<HashMap<String, CleanupEventEntry>>>,
+    /// Create-room idempotency keys ((app_id, key) -> entry)
+    room_idempotency_keys:
+        std::sync::Arc<tokio::sync::RwLock<HashMap<(Uuid, String), IdempotencyKeyEntry>>>,
+    /// Last heartbeat of each player, for stale player detection
+    player_last_seen:
+        std::sync::Arc<tokio::sync::RwLock<HashMap<PlayerId, chrono::DateTime<chrono::Utc>>>>,
+    /// Room code settings used when `create_room` is called without a code
+    protocol_config: ProtocolConfig,
+    game_overrides: GameOverrideConfig,
 }
 
 impl InMemoryDatabase {
     pub fn new() -> Self {
+        Self::with_room_code_config(ProtocolConfig::default(), GameOverrideConfig::new())
+    }
+
+    /// Create a database that generates room codes with the given per-game formats.
+    pub fn with_room_code_config(
+        protocol_config: ProtocolConfig,
+        game_overrides: GameOverrideConfig,
+    ) -> Self {
         Self {
             rooms: std::sync::Arc::new(tokio::sync::RwLock::new(HashMap::new())),
             room_codes: std::sync::Arc::new(tokio::sync::RwLock::new(HashMap::new())),
             cleanup_events: std::sync::Arc::new(tokio::sync::RwLock::new(HashMap::new())),
+            room_idempotency_keys: std::sync::Arc::new(tokio::sync::RwLock::new(HashMap::new())),
+            player_last_seen: std::sync::Arc::new(tokio::sync::RwLock::new(HashMap::new())),
+            protocol_config,
+            game_overrides,
+        }
+    }
+
+    /// Seat `player` while the room holds fewer than `player_limit` players,
+    /// or fewer than `max_players` without a limit.
+    async fn insert_player(
+        &self,
+        room_id: &RoomId,
+        player: PlayerInfo,
+        player_limit: Option<u8>,
+    ) -> Result<bool> {
+        let mut rooms = self.rooms.write().await;
+        if let Some(room) = rooms.get_mut(room_id) {
+            let capacity = player_limit.unwrap_or(room.max_players);
+            if room.players.len() >= capacity as usize {
+                return Ok(false); // Room is full
+            }
+            if !room.players.contains_key(&player.id)
+                && validation::validate_player_name_uniqueness(&player.name, &room.players).is_err()
+            {
+                return Err(PlayerNameTakenError::default().into());
+            }
+            room.players.insert(player.id, player);
+            Ok(true)
+        } else {
+            anyhow::bail!("Room not found")
         }
     }
 }
@@ -296,14 +686,39 @@ impl GameDatabase for InMemoryDatabase {
         region_id: String,
         application_id: Option<Uuid>,
     ) -> Result<Room> {
-        let room_code =
-            room_code.unwrap_or_else(crate::protocol::room_codes::generate_clean_room_code);
+        self.create_room_if_under_limit(
+            game_name,
+            room_code,
+            max_players,
+            supports_authority,
+            creator_id,
+            relay_type,
+            region_id,
+            application_id,
+            usize::MAX,
+        )
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("room limit reached"))
+    }
 
+    async fn create_room_if_under_limit(
+        &self,
+        game_name: String,
+        room_code: Option<String>,
+        max_players: u8,
+        supports_authority: bool,
+        creator_id: PlayerId,
+        relay_type: String,
+        region_id: String,
+        application_id: Option<Uuid>,
+        max_rooms: usize,
+    ) -> Result<Option<Room>> {
         // Create creator player info before acquiring locks
         let creator_info = PlayerInfo {
             id: creator_id,
             name: "Creator".to_string(), // This will be updated later when we have the actual name
             is_authority: true,
+            role: PlayerRole::Creator,
             is_ready: false,
             connected_at: chrono::Utc::now(),
             connection_info: None,
@@ -320,11 +735,44 @@ impl GameDatabase for InMemoryDatabase {
         let mut rooms = self.rooms.write().await;
         let mut room_codes = self.room_codes.write().await;
 
+        // Count the game's rooms under the same write lock as the insert
+        let game_rooms = rooms
+            .values()
+            .filter(|room| room.game_name == game_name)
+            .count();
+        if game_rooms >= max_rooms {
+            return Ok(None);
+        }
+
         // Check room code uniqueness under the write lock (no TOCTOU gap)
+        let room_code = match room_code {
+            Some(room_code) => {
+                if room_codes.contains_key(&(game_name.clone(), room_code.clone())) {
+                    anyhow::bail!("Room code {room_code} already exists for game {game_name}");
+                }
+                room_code
+            }
+            None => {
+                let mut attempts = 0u8;
+                loop {
+                    let candidate = generate_room_code_for_game(
+                        &game_name,
+                        &self.protocol_config,
+                        &self.game_overrides,
+                    );
+                    if !room_codes.contains_key(&(game_name.clone(), candidate.clone())) {
+                        break candidate;
+                    }
+                    attempts += 1;
+                    if attempts >= 16 {
+                        anyhow::bail!(
+                            "Failed to generate unique room code for game {game_name} after {attempts} attempts"
+                        );
+                    }
+                }
+            }
+        };
         let game_room_key = (game_name.clone(), room_code.clone());
-        if room_codes.contains_key(&game_room_key) {
-            anyhow::bail!("Room code {room_code} already exists for game {game_name}");
-        }
 
         // Generate a unique room ID
         let room_id = {
@@ -355,22 +803,33 @@ impl GameDatabase for InMemoryDatabase {
             },
             lobby_state: crate::protocol::LobbyState::Waiting,
             ready_players: Vec::new(),
+            lobby_version: 0,
             lobby_started_at: None,
             game_finalized_at: None,
             relay_type,
             region_id,
             application_id,
+            creator_key: None,
             created_at: now,
             last_activity: now,
             spectators: HashMap::new(),
             max_spectators: None,
+            waiting_list: VecDeque::new(),
+            bans: VecDeque::new(),
+            expiry_warned: false,
+            relay_session: None,
+            metadata: HashMap::new(),
+            spectator_chat_mode: SpectatorChatMode::default(),
+            allow_overflow_joins: false,
+            invites_authority_only: false,
+            message_history: VecDeque::new(),
         };
 
         // Insert into both maps atomically while holding both locks
         rooms.insert(room_id, room.clone());
         room_codes.insert(game_room_key, room_id);
 
-        Ok(room)
+        Ok(Some(room))
     }
 
     async fn get_room(&self, game_name: &str, room_code: &str) -> Result<Option<Room>> {
@@ -391,18 +850,23 @@ impl GameDatabase for InMemoryDatabase {
         Ok(rooms.get(room_id).cloned())
     }
 
+    async fn get_room_summary(&self, room_id: &RoomId) -> Result<Option<RoomSummary>> {
+        let rooms = self.rooms.read().await;
+        Ok(rooms.get(room_id).map(Room::to_summary))
+    }
+
     async fn add_player_to_room(&self, room_id: &RoomId, player: PlayerInfo) -> Result<bool> {
-        let mut rooms = self.rooms.write().await;
-        if let Some(room) = rooms.get_mut(room_id) {
-            if room.players.len() < room.max_players as usize {
-                room.players.insert(player.id, player);
-                Ok(true)
-            } else {
-                Ok(false) // Room is full
-            }
-        } else {
-            anyhow::bail!("Room not found")
-        }
+        self.insert_player(room_id, player, None).await
+    }
+
+    async fn add_overflow_player_to_room(
+        &self,
+        room_id: &RoomId,
+        player: PlayerInfo,
+        player_limit: u8,
+    ) -> Result<bool> {
+        self.insert_player(room_id, player, Some(player_limit))
+            .await
     }
 
     async fn remove_player_from_room(
@@ -413,6 +877,9 @@ impl GameDatabase for InMemoryDatabase {
         let mut rooms = self.rooms.write().await;
         if let Some(room) = rooms.get_mut(room_id) {
             let removed_player = room.players.remove(player_id);
+            if removed_player.is_some() {
+                self.player_last_seen.write().await.remove(player_id);
+            }
 
             // If removed player was authority, CLEAR authority (don't auto-reassign per protocol)
             if room.authority_player == Some(*player_id) {
@@ -542,6 +1009,74 @@ impl GameDatabase for InMemoryDatabase {
         }
     }
 
+    async fn set_player_role(
+        &self,
+        room_id: &RoomId,
+        player_id: &PlayerId,
+        role: PlayerRole,
+    ) -> Result<bool> {
+        let mut rooms = self.rooms.write().await;
+        match rooms
+            .get_mut(room_id)
+            .and_then(|room| room.players.get_mut(player_id))
+        {
+            Some(player) => {
+                player.role = role;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    async fn set_room_relay_session(
+        &self,
+        room_id: &RoomId,
+        session: Option<serde_json::Value>,
+    ) -> Result<bool> {
+        let mut rooms = self.rooms.write().await;
+        Ok(rooms.get_mut(room_id).is_some_and(|room| {
+            room.relay_session = session;
+            true
+        }))
+    }
+
+    async fn set_room_metadata(
+        &self,
+        room_id: &RoomId,
+        entries: &HashMap<String, String>,
+    ) -> Result<RoomMetadataUpdate> {
+        let mut rooms = self.rooms.write().await;
+        let Some(room) = rooms.get_mut(room_id) else {
+            return Ok(RoomMetadataUpdate::RoomNotFound);
+        };
+        Ok(
+            match validation::apply_room_metadata_entries(&mut room.metadata, entries) {
+                Ok(()) => RoomMetadataUpdate::Applied(room.metadata.clone()),
+                Err(reason) => RoomMetadataUpdate::Rejected(reason),
+            },
+        )
+    }
+
+    async fn update_room_settings(
+        &self,
+        room_id: &RoomId,
+        max_players: Option<u8>,
+        max_spectators: Option<u8>,
+    ) -> Result<RoomSettingsUpdate> {
+        let mut rooms = self.rooms.write().await;
+        let Some(room) = rooms.get_mut(room_id) else {
+            return Ok(RoomSettingsUpdate::RoomNotFound);
+        };
+        Ok(match room.resize(max_players, max_spectators) {
+            Ok(reopened_lobby_version) => RoomSettingsUpdate::Applied {
+                max_players: room.max_players,
+                max_spectators: room.max_spectators,
+                reopened_lobby_version,
+            },
+            Err(reason) => RoomSettingsUpdate::BelowOccupancy(reason),
+        })
+    }
+
     async fn update_player_connection_info(
         &self,
         room_id: &RoomId,
@@ -570,20 +1105,18 @@ impl GameDatabase for InMemoryDatabase {
         }
     }
 
-    async fn cleanup_empty_rooms(&self, empty_timeout: chrono::Duration) -> Result<Vec<RoomId>> {
+    async fn cleanup_empty_rooms(&self, policy: &RoomExpiryPolicy) -> Result<Vec<RoomId>> {
         let mut rooms = self.rooms.write().await;
         let mut room_codes = self.room_codes.write().await;
 
-        let effective_timeout = if empty_timeout <= chrono::Duration::zero() {
-            chrono::Duration::zero()
-        } else {
-            empty_timeout
-        };
-        let cutoff = chrono::Utc::now() - effective_timeout;
-
+        let now = chrono::Utc::now();
         let mut to_remove = Vec::new();
         for (room_id, room) in rooms.iter() {
-            if room.players.is_empty() && room.last_activity <= cutoff {
+            let empty_timeout = policy
+                .timeouts_for(&room.game_name)
+                .empty
+                .max(chrono::Duration::zero());
+            if room.players.is_empty() && room.last_activity <= now - empty_timeout {
                 to_remove.push((*room_id, room.game_name.clone(), room.code.clone()));
             }
         }
@@ -598,17 +1131,14 @@ impl GameDatabase for InMemoryDatabase {
         Ok(deleted_ids)
     }
 
-    async fn cleanup_expired_rooms(
-        &self,
-        empty_timeout: chrono::Duration,
-        inactive_timeout: chrono::Duration,
-    ) -> Result<RoomCleanupOutcome> {
+    async fn cleanup_expired_rooms(&self, policy: &RoomExpiryPolicy) -> Result<RoomCleanupOutcome> {
         let mut rooms = self.rooms.write().await;
         let mut room_codes = self.room_codes.write().await;
 
         let mut to_remove = Vec::new();
         for (room_id, room) in rooms.iter() {
-            if room.is_expired(empty_timeout, inactive_timeout) {
+            let timeouts = policy.timeouts_for(&room.game_name);
+            if room.is_expired(timeouts.empty, timeouts.inactive) {
                 let was_empty = room.players.is_empty();
                 to_remove.push((
                     *room_id,
@@ -626,18 +1156,58 @@ impl GameDatabase for InMemoryDatabase {
 
             if was_empty {
                 outcome.empty_rooms_cleaned += 1;
+                outcome.empty_room_ids.push(room_id);
             } else {
                 outcome.inactive_rooms_cleaned += 1;
+                outcome.inactive_room_ids.push(room_id);
             }
         }
 
         Ok(outcome)
     }
 
+    async fn list_rooms_near_expiry(
+        &self,
+        policy: &RoomExpiryPolicy,
+        warn_window: chrono::Duration,
+    ) -> Result<Vec<ExpiringRoom>> {
+        let now = chrono::Utc::now();
+        let expiring: Vec<ExpiringRoom> = {
+            let rooms = self.rooms.read().await;
+            rooms
+                .values()
+                .filter(|room| !room.expiry_warned)
+                .filter_map(|room| {
+                    let timeouts = policy.timeouts_for(&room.game_name);
+                    let expires_in = room.expires_at(timeouts.empty, timeouts.inactive) - now;
+                    (expires_in > chrono::Duration::zero() && expires_in <= warn_window).then_some(
+                        ExpiringRoom {
+                            room_id: room.id,
+                            expires_in,
+                        },
+                    )
+                })
+                .collect()
+        };
+
+        // Only take the write lock when there is something to mark
+        if !expiring.is_empty() {
+            let mut rooms = self.rooms.write().await;
+            for entry in &expiring {
+                if let Some(room) = rooms.get_mut(&entry.room_id) {
+                    room.expiry_warned = true;
+                }
+            }
+        }
+
+        Ok(expiring)
+    }
+
     async fn update_room_activity(&self, room_id: &RoomId) -> Result<()> {
         let mut rooms = self.rooms.write().await;
         if let Some(room) = rooms.get_mut(room_id) {
             room.last_activity = chrono::Utc::now();
+            room.expiry_warned = false;
         }
         Ok(())
     }
@@ -664,15 +1234,106 @@ impl GameDatabase for InMemoryDatabase {
         Ok(count)
     }
 
+    async fn find_joinable_room(
+        &self,
+        game_name: &str,
+        region_id: Option<&str>,
+    ) -> Result<Option<RoomId>> {
+        let rooms = self.rooms.read().await;
+        let in_region = |room: &Room| region_id.is_some_and(|region| room.region_id == region);
+        let room_id = rooms
+            .values()
+            .filter(|room| {
+                room.game_name == game_name
+                    && room.lobby_state != crate::protocol::LobbyState::Finalized
+                    && room.players.len() < room.max_players as usize
+            })
+            .max_by(|a, b| {
+                in_region(a)
+                    .cmp(&in_region(b))
+                    .then_with(|| a.players.len().cmp(&b.players.len()))
+                    .then_with(|| b.created_at.cmp(&a.created_at))
+            })
+            .map(|room| room.id);
+        Ok(room_id)
+    }
+
+    async fn get_app_room_count(&self, application_id: &Uuid) -> Result<usize> {
+        let rooms = self.rooms.read().await;
+        let count = rooms
+            .values()
+            .filter(|room| room.application_id.as_ref() == Some(application_id))
+            .count();
+        Ok(count)
+    }
+
+    async fn count_players_by_application(&self, application_id: &Uuid) -> Result<usize> {
+        let rooms = self.rooms.read().await;
+        let count = rooms
+            .values()
+            .filter(|room| room.application_id.as_ref() == Some(application_id))
+            .map(|room| room.players.len())
+            .sum();
+        Ok(count)
+    }
+
+    async fn set_room_creator(&self, room_id: &RoomId, creator_key: &str) -> Result<()> {
+        let mut rooms = self.rooms.write().await;
+        if let Some(room) = rooms.get_mut(room_id) {
+            room.creator_key = Some(creator_key.to_string());
+        }
+        Ok(())
+    }
+
+    async fn count_rooms_by_creator(&self, creator_key: &str) -> Result<usize> {
+        let rooms = self.rooms.read().await;
+        let count = rooms
+            .values()
+            .filter(|room| room.creator_key.as_deref() == Some(creator_key))
+            .count();
+        Ok(count)
+    }
+
     async fn health_check(&self) -> bool {
         true
     }
 
-    async fn update_player_last_seen(&self, _player_id: &PlayerId) -> Result<()> {
-        // In-memory DB has no per-player last_seen tracking; no-op
+    async fn update_player_last_seen(&self, player_id: &PlayerId) -> Result<()> {
+        self.player_last_seen
+            .write()
+            .await
+            .insert(*player_id, chrono::Utc::now());
         Ok(())
     }
 
+    async fn get_stale_players(
+        &self,
+        stale_threshold: chrono::Duration,
+    ) -> Result<Vec<(RoomId, PlayerId)>> {
+        let cutoff = chrono::Utc::now() - stale_threshold.max(chrono::Duration::zero());
+        let rooms = self.rooms.read().await;
+        let mut last_seen = self.player_last_seen.write().await;
+
+        let mut seated = std::collections::HashSet::new();
+        let mut stale = Vec::new();
+        for room in rooms.values() {
+            for player in room.players.values() {
+                seated.insert(player.id);
+                let seen = last_seen
+                    .get(&player.id)
+                    .copied()
+                    .unwrap_or(player.connected_at);
+                if seen < cutoff {
+                    stale.push((room.id, player.id));
+                }
+            }
+        }
+
+        // Forget players whose rooms were deleted with them still seated
+        last_seen.retain(|player_id, _| seated.contains(player_id));
+        Ok(stale)
+    }
+
     async fn get_rooms_by_game(&self) -> Result<HashMap<String, usize>> {
         let rooms = self.rooms.read().await;
         let mut game_counts = HashMap::new();
@@ -684,6 +1345,17 @@ impl GameDatabase for InMemoryDatabase {
         Ok(game_counts)
     }
 
+    async fn get_players_by_game(&self) -> Result<HashMap<String, usize>> {
+        let rooms = self.rooms.read().await;
+        let mut player_counts = HashMap::new();
+
+        for room in rooms.values() {
+            *player_counts.entry(room.game_name.clone()).or_insert(0) += room.players.len();
+        }
+
+        Ok(player_counts)
+    }
+
     async fn get_player_count_percentiles(&self) -> Result<HashMap<String, f64>> {
         let rooms = self.rooms.read().await;
         let mut player_counts: Vec<usize> = rooms.values().map(|room| room.players.len()).collect();
@@ -745,67 +1417,49 @@ impl GameDatabase for InMemoryDatabase {
         Ok(result)
     }
 
-    async fn transition_room_to_lobby(&self, room_id: &RoomId) -> Result<()> {
+    async fn transition_room_to_lobby(&self, room_id: &RoomId) -> Result<Option<u64>> {
         let mut rooms = self.rooms.write().await;
-        if let Some(room) = rooms.get_mut(room_id) {
-            if room.should_enter_lobby() {
-                room.enter_lobby();
-            }
-        }
-        Ok(())
+        Ok(rooms
+            .get_mut(room_id)
+            .and_then(|room| room.enter_lobby().then_some(room.lobby_version)))
     }
 
-    async fn transition_room_to_waiting(&self, room_id: &RoomId) -> Result<()> {
+    async fn transition_room_to_waiting(&self, room_id: &RoomId) -> Result<Option<u64>> {
         let mut rooms = self.rooms.write().await;
-        if let Some(room) = rooms.get_mut(room_id) {
-            // Only transition if room is currently in lobby but no longer meets lobby requirements
-            if room.lobby_state == crate::protocol::LobbyState::Lobby && !room.should_enter_lobby()
-            {
-                room.lobby_state = crate::protocol::LobbyState::Waiting;
-                room.lobby_started_at = None;
-                room.ready_players.clear();
-                // Clear ready status for all players
-                for player in room.players.values_mut() {
-                    player.is_ready = false;
-                }
-            }
-        }
-        Ok(())
+        Ok(rooms
+            .get_mut(room_id)
+            .and_then(|room| room.return_to_waiting().then_some(room.lobby_version)))
     }
 
     async fn toggle_player_ready(
         &self,
         room_id: &RoomId,
         player_id: &PlayerId,
-    ) -> Result<Option<(crate::protocol::LobbyState, Vec<PlayerId>, bool)>> {
+    ) -> Result<Option<LobbyReadiness>> {
         let mut rooms = self.rooms.write().await;
-        if let Some(room) = rooms.get_mut(room_id) {
-            if room.lobby_state == crate::protocol::LobbyState::Lobby {
-                // Toggle player ready state
-                let current_ready = room
-                    .players
-                    .get(player_id)
-                    .map(|p| p.is_ready)
-                    .unwrap_or(false);
-                room.set_player_ready(player_id, !current_ready);
-
-                let all_ready = room.all_players_ready();
-                return Ok(Some((
-                    room.lobby_state.clone(),
-                    room.ready_players.clone(),
-                    all_ready,
-                )));
-            }
+        let Some(room) = rooms.get_mut(room_id) else {
+            return Ok(None);
+        };
+        let current_ready = room
+            .players
+            .get(player_id)
+            .is_some_and(|player| player.is_ready);
+        if !room.set_player_ready(player_id, !current_ready) {
+            return Ok(None);
         }
-        Ok(None)
+        Ok(Some(LobbyReadiness {
+            lobby_state: room.lobby_state.clone(),
+            ready_players: room.ready_players.clone(),
+            all_ready: room.all_players_ready(),
+            lobby_version: room.lobby_version,
+        }))
     }
 
-    async fn finalize_room_game(&self, room_id: &RoomId) -> Result<()> {
+    async fn finalize_room_game(&self, room_id: &RoomId) -> Result<Option<u64>> {
         let mut rooms = self.rooms.write().await;
-        if let Some(room) = rooms.get_mut(room_id) {
-            room.finalize_game();
-        }
-        Ok(())
+        Ok(rooms
+            .get_mut(room_id)
+            .and_then(|room| room.finalize_game().then_some(room.lobby_version)))
     }
 
     async fn add_spectator_to_room(
@@ -843,6 +1497,79 @@ impl GameDatabase for InMemoryDatabase {
         }
     }
 
+    async fn add_to_waiting_list(
+        &self,
+        room_id: &RoomId,
+        player_id: &PlayerId,
+        max_size: usize,
+    ) -> Result<Option<usize>> {
+        let mut rooms = self.rooms.write().await;
+        if let Some(room) = rooms.get_mut(room_id) {
+            Ok(room.enqueue_waiting(*player_id, max_size))
+        } else {
+            anyhow::bail!("Room not found")
+        }
+    }
+
+    async fn pop_waiting_list(&self, room_id: &RoomId) -> Result<Option<PlayerId>> {
+        let mut rooms = self.rooms.write().await;
+        Ok(rooms.get_mut(room_id).and_then(Room::pop_waiting))
+    }
+
+    async fn ban_player_from_room(
+        &self,
+        room_id: &RoomId,
+        player_id: &PlayerId,
+        client_ip: Option<IpAddr>,
+    ) -> Result<bool> {
+        let mut rooms = self.rooms.write().await;
+        if let Some(room) = rooms.get_mut(room_id) {
+            room.ban_player(*player_id, client_ip);
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    async fn unban_player_from_room(&self, room_id: &RoomId, player_id: &PlayerId) -> Result<bool> {
+        let mut rooms = self.rooms.write().await;
+        Ok(rooms
+            .get_mut(room_id)
+            .is_some_and(|room| room.unban_player(player_id)))
+    }
+
+    async fn append_room_history(
+        &self,
+        room_id: &RoomId,
+        message: HistoricalMessage,
+        capacity: usize,
+    ) -> Result<bool> {
+        let mut rooms = self.rooms.write().await;
+        Ok(rooms.get_mut(room_id).is_some_and(|room| {
+            room.record_history(message, capacity);
+            true
+        }))
+    }
+
+    async fn get_room_history(&self, room_id: &RoomId) -> Result<Vec<HistoricalMessage>> {
+        let rooms = self.rooms.read().await;
+        Ok(rooms
+            .get(room_id)
+            .map(|room| room.message_history.iter().cloned().collect())
+            .unwrap_or_default())
+    }
+
+    async fn remove_from_waiting_list(
+        &self,
+        room_id: &RoomId,
+        player_id: &PlayerId,
+    ) -> Result<bool> {
+        let mut rooms = self.rooms.write().await;
+        Ok(rooms
+            .get_mut(room_id)
+            .is_some_and(|room| room.remove_waiting(player_id)))
+    }
+
     async fn set_room_application_id(&self, room_id: &RoomId, application_id: Uuid) -> Result<()> {
         let mut rooms = self.rooms.write().await;
         if let Some(room) = rooms.get_mut(room_id) {
@@ -859,6 +1586,38 @@ impl GameDatabase for InMemoryDatabase {
         Ok(())
     }
 
+    async fn set_spectator_chat_mode(
+        &self,
+        room_id: &RoomId,
+        mode: SpectatorChatMode,
+    ) -> Result<()> {
+        let mut rooms = self.rooms.write().await;
+        if let Some(room) = rooms.get_mut(room_id) {
+            room.spectator_chat_mode = mode;
+        }
+        Ok(())
+    }
+
+    async fn set_allow_overflow_joins(&self, room_id: &RoomId, allow: bool) -> Result<()> {
+        let mut rooms = self.rooms.write().await;
+        if let Some(room) = rooms.get_mut(room_id) {
+            room.allow_overflow_joins = allow;
+        }
+        Ok(())
+    }
+
+    async fn set_invites_authority_only(
+        &self,
+        room_id: &RoomId,
+        authority_only: bool,
+    ) -> Result<()> {
+        let mut rooms = self.rooms.write().await;
+        if let Some(room) = rooms.get_mut(room_id) {
+            room.invites_authority_only = authority_only;
+        }
+        Ok(())
+    }
+
     async fn try_claim_room_cleanup(
         &self,
         room_id: &RoomId,
@@ -897,6 +1656,42 @@ impl GameDatabase for InMemoryDatabase {
         Ok(deleted_count as u64)
     }
 
+    async fn claim_room_idempotency_key(
+        &self,
+        app_id: &Uuid,
+        key: &str,
+        room: IdempotentRoom,
+        ttl: chrono::Duration,
+    ) -> Result<Option<IdempotentRoom>> {
+        let mut keys = self.room_idempotency_keys.write().await;
+        let now = chrono::Utc::now();
+
+        // Checked and claimed under one write lock, so concurrent claims of
+        // a key cannot both win
+        let key = (*app_id, key.to_string());
+        if let Some(claimed) = keys.get(&key).filter(|entry| entry.expires_at > now) {
+            return Ok(Some(claimed.room.clone()));
+        }
+        keys.insert(
+            key,
+            IdempotencyKeyEntry {
+                room,
+                expires_at: now + ttl,
+            },
+        );
+        Ok(None)
+    }
+
+    async fn cleanup_expired_room_idempotency_keys(&self) -> Result<u64> {
+        let mut keys = self.room_idempotency_keys.write().await;
+        let now = chrono::Utc::now();
+
+        let initial_count = keys.len();
+        keys.retain(|_, entry| entry.expires_at > now);
+
+        Ok((initial_count - keys.len()) as u64)
+    }
+
     fn as_any(&self) -> &(dyn Any + Send + Sync) {
         self
     }
@@ -915,311 +1710,61 @@ fn percentile(sorted_values: &[usize], p: f64) -> f64 {
     sorted_values[index.min(sorted_values.len() - 1)] as f64
 }
 
+#[cfg(test)]
+mod conformance_tests;
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::collections::HashSet;
-    use std::sync::Arc;
-
-    /// Helper: create a room with the given game name and room code using sensible defaults.
-    async fn create_test_room(
-        db: &InMemoryDatabase,
-        game_name: &str,
-        room_code: &str,
-    ) -> Result<Room> {
-        db.create_room(
-            game_name.to_string(),
-            Some(room_code.to_string()),
-            4,
-            true,
-            Uuid::new_v4(),
-            "relay".to_string(),
-            "us-east-1".to_string(),
-            None,
-        )
-        .await
-    }
 
-    #[tokio::test]
-    async fn test_create_room_generates_unique_ids() {
-        let db = InMemoryDatabase::new();
-        let mut ids = HashSet::new();
-        let count = 100;
-
-        for i in 0..count {
-            let room_code = format!("ROOM{i:03}");
-            let room = create_test_room(&db, "uniqueness_game", &room_code)
-                .await
-                .expect("room creation should succeed");
-            ids.insert(room.id);
-        }
-
-        assert_eq!(
-            ids.len(),
-            count,
-            "all {count} room IDs must be distinct, but only {} unique IDs found",
-            ids.len()
-        );
+    async fn new_database() -> (InMemoryDatabase, ()) {
+        (InMemoryDatabase::new(), ())
     }
 
-    #[tokio::test]
-    async fn test_create_room_id_is_retrievable_by_id() {
-        let db = InMemoryDatabase::new();
-        let room = create_test_room(&db, "lookup_game", "LOOK01")
-            .await
-            .expect("room creation should succeed");
-
-        let fetched = db
-            .get_room_by_id(&room.id)
-            .await
-            .expect("get_room_by_id should not error")
-            .expect("room should exist in the rooms map");
-
-        assert_eq!(fetched.id, room.id);
-        assert_eq!(fetched.code, room.code);
-        assert_eq!(fetched.game_name, room.game_name);
-    }
-
-    #[tokio::test]
-    async fn test_create_room_room_code_collision_rejected() {
-        let db = InMemoryDatabase::new();
-
-        create_test_room(&db, "game1", "TEST01")
-            .await
-            .expect("first room creation should succeed");
-
-        let result = create_test_room(&db, "game1", "TEST01").await;
-        assert!(
-            result.is_err(),
-            "duplicate room code for the same game must be rejected"
-        );
-
-        let err_msg = result.unwrap_err().to_string();
-        assert!(
-            err_msg.contains("already exists"),
-            "error message should contain 'already exists', got: {err_msg}"
-        );
-    }
-
-    #[tokio::test]
-    async fn test_create_room_same_code_different_game_allowed() {
-        let db = InMemoryDatabase::new();
-
-        let room1 = create_test_room(&db, "game1", "TEST01")
-            .await
-            .expect("room creation for game1 should succeed");
-
-        let room2 = create_test_room(&db, "game2", "TEST01")
-            .await
-            .expect("room creation for game2 with same code should succeed");
-
-        assert_ne!(
-            room1.id, room2.id,
-            "rooms for different games must have different IDs"
-        );
-        assert_eq!(room1.code, room2.code);
-        assert_ne!(room1.game_name, room2.game_name);
-    }
-
-    #[tokio::test]
-    async fn test_create_room_concurrent_unique_ids() {
-        let db = Arc::new(InMemoryDatabase::new());
-        let task_count = 50;
-        let barrier = Arc::new(tokio::sync::Barrier::new(task_count));
-
-        let mut handles = Vec::with_capacity(task_count);
-        for i in 0..task_count {
-            let db = Arc::clone(&db);
-            let barrier = Arc::clone(&barrier);
-            handles.push(tokio::spawn(async move {
-                barrier.wait().await;
-                let room_code = format!("CONC{i:03}");
-                db.create_room(
-                    "concurrent_game".to_string(),
-                    Some(room_code),
-                    4,
-                    true,
-                    Uuid::new_v4(),
-                    "relay".to_string(),
-                    "us-east-1".to_string(),
-                    None,
-                )
-                .await
-            }));
-        }
-
-        let mut ids = HashSet::new();
-        for handle in handles {
-            let room = handle
-                .await
-                .expect("task should not panic")
-                .expect("room creation should succeed");
-            ids.insert(room.id);
-        }
-
-        assert_eq!(
-            ids.len(),
-            task_count,
-            "all {task_count} concurrently created rooms must have unique IDs"
-        );
-    }
-
-    #[tokio::test]
-    async fn test_create_room_concurrent_same_code_only_one_succeeds() {
-        let db = Arc::new(InMemoryDatabase::new());
-        let task_count = 10;
-        let barrier = Arc::new(tokio::sync::Barrier::new(task_count));
-
-        let mut handles = Vec::with_capacity(task_count);
-        for _ in 0..task_count {
-            let db = Arc::clone(&db);
-            let barrier = Arc::clone(&barrier);
-            handles.push(tokio::spawn(async move {
-                barrier.wait().await;
-                db.create_room(
-                    "game1".to_string(),
-                    Some("RACE01".to_string()),
-                    4,
-                    true,
-                    Uuid::new_v4(),
-                    "relay".to_string(),
-                    "us-east-1".to_string(),
-                    None,
-                )
-                .await
-            }));
-        }
-
-        let mut successes = 0usize;
-        let mut failures = 0usize;
-        for handle in handles {
-            match handle.await.expect("task should not panic") {
-                Ok(_) => successes += 1,
-                Err(e) => {
-                    assert!(
-                        e.to_string().contains("already exists"),
-                        "failure reason should be 'already exists', got: {e}"
-                    );
-                    failures += 1;
-                }
+    super::conformance_tests::database_conformance_tests!();
+
+    proptest::proptest! {
+        #![proptest_config(proptest::prelude::ProptestConfig::with_cases(32))]
+
+        /// Even with a tiny code space, generated codes never collide within a game.
+        #[test]
+        fn generated_codes_are_unique_for_custom_charsets(
+            charset in proptest::sample::subsequence(b"0123456789ABCDEF".to_vec(), 4..=6),
+        ) {
+            let charset = String::from_utf8(charset).unwrap();
+            let overrides = GameOverrideConfig::from([(
+                "pin-game".to_string(),
+                crate::config::GameConfig {
+                    room_code_length: Some(4),
+                    room_code_charset: Some(charset.clone()),
+                    room_code_prefix: None,
+                    ..Default::default()
+                },
+            )]);
+            let db = InMemoryDatabase::with_room_code_config(ProtocolConfig::default(), overrides);
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .build()
+                .unwrap();
+
+            let mut codes = HashSet::new();
+            for _ in 0..24 {
+                let room = runtime
+                    .block_on(db.create_room(
+                        "pin-game".to_string(),
+                        None,
+                        4,
+                        true,
+                        Uuid::new_v4(),
+                        "relay".to_string(),
+                        "us-east-1".to_string(),
+                        None,
+                    ))
+                    .expect("room creation should succeed");
+                proptest::prop_assert_eq!(room.code.len(), 4);
+                proptest::prop_assert!(room.code.chars().all(|c| charset.contains(c)));
+                proptest::prop_assert!(codes.insert(room.code));
             }
         }
-
-        assert_eq!(successes, 1, "exactly one task should win the race");
-        assert_eq!(
-            failures,
-            task_count - 1,
-            "all other tasks should fail with 'already exists'"
-        );
-
-        // Verify only one room exists in the database for this game+code
-        let room = db
-            .get_room("game1", "RACE01")
-            .await
-            .expect("get_room should not error")
-            .expect("the winning room should be findable");
-        assert_eq!(room.code, "RACE01");
-    }
-
-    #[tokio::test]
-    async fn test_create_room_atomic_consistency() {
-        let db = InMemoryDatabase::new();
-        let room = create_test_room(&db, "atomic_game", "ATOM01")
-            .await
-            .expect("room creation should succeed");
-
-        // Lookup via room ID
-        let by_id = db
-            .get_room_by_id(&room.id)
-            .await
-            .expect("get_room_by_id should not error")
-            .expect("room should be in the rooms map");
-
-        // Lookup via game name + room code
-        let by_code = db
-            .get_room("atomic_game", "ATOM01")
-            .await
-            .expect("get_room should not error")
-            .expect("room should be in the room_codes map");
-
-        assert_eq!(by_id.id, room.id);
-        assert_eq!(by_code.id, room.id);
-        assert_eq!(
-            by_id.id, by_code.id,
-            "both lookups must resolve to the same room"
-        );
-    }
-
-    #[tokio::test]
-    async fn test_delete_room_frees_room_code() {
-        let db = InMemoryDatabase::new();
-
-        let room = create_test_room(&db, "reuse_game", "REUSE1")
-            .await
-            .expect("initial room creation should succeed");
-
-        let deleted = db
-            .delete_room(&room.id)
-            .await
-            .expect("delete_room should not error");
-        assert!(
-            deleted,
-            "delete_room should return true for an existing room"
-        );
-
-        // The room code is now free; re-creating with the same code should work.
-        let room2 = create_test_room(&db, "reuse_game", "REUSE1")
-            .await
-            .expect("re-creating room with freed code should succeed");
-
-        assert_ne!(
-            room.id, room2.id,
-            "the new room must have a different ID than the deleted one"
-        );
-        assert_eq!(room2.code, "REUSE1");
-    }
-
-    #[tokio::test]
-    async fn test_create_room_preserves_all_fields() {
-        let db = InMemoryDatabase::new();
-        let creator_id = Uuid::new_v4();
-        let app_id = Uuid::new_v4();
-
-        let room = db
-            .create_room(
-                "my_game".to_string(),
-                Some("FIELD1".to_string()),
-                8,
-                true,
-                creator_id,
-                "webrtc".to_string(),
-                "eu-west-1".to_string(),
-                Some(app_id),
-            )
-            .await
-            .expect("room creation should succeed");
-
-        assert_eq!(room.game_name, "my_game");
-        assert_eq!(room.code, "FIELD1");
-        assert_eq!(room.max_players, 8);
-        assert!(room.supports_authority);
-        assert_eq!(room.relay_type, "webrtc");
-        assert_eq!(room.region_id, "eu-west-1");
-        assert_eq!(room.application_id, Some(app_id));
-
-        // Creator should be in the players map
-        assert!(
-            room.players.contains_key(&creator_id),
-            "creator must appear in the players map"
-        );
-        let creator = &room.players[&creator_id];
-        assert_eq!(creator.id, creator_id);
-        assert!(
-            creator.is_authority,
-            "creator should be marked as authority when supports_authority is true"
-        );
-
-        // Authority player should be set to creator
-        assert_eq!(room.authority_player, Some(creator_id));
     }
 }
@@ -0,0 +1,291 @@
+//! Invite tokens that let players join a room without knowing its code.
+//!
+//! A token is `<lookup id>.<secret>`. The lookup id finds the invite and the
+//! 128-bit secret is then compared in constant time, so tokens can be neither
+//! guessed nor probed by timing. Invites are kept by the instance that created
+//! them until they expire, even once used up, so that redeeming them reports
+//! why they no longer work; deleting the room drops them at once.
+
+use crate::protocol::RoomId;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use rand::RngExt;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+/// Most joins a single invite may allow.
+pub const MAX_INVITE_USES: u32 = 100;
+/// Longest an invite may stay valid, in seconds (one week).
+pub const MAX_INVITE_TTL_SECS: u64 = 7 * 24 * 60 * 60;
+/// Most unexpired invites a room may have at once.
+pub const MAX_INVITES_PER_ROOM: usize = 32;
+
+/// A room invite and its remaining uses.
+#[derive(Debug)]
+pub struct Invite {
+    pub room_id: RoomId,
+    pub game_name: String,
+    pub room_code: String,
+    pub max_uses: u32,
+    pub expires_at: DateTime<Utc>,
+    secret: String,
+    remaining: AtomicU32,
+}
+
+impl Invite {
+    /// Joins this invite still allows.
+    pub fn remaining_uses(&self) -> u32 {
+        self.remaining.load(Ordering::Acquire)
+    }
+
+    /// Give back a use taken by [`InviteStore::redeem`] for a join that did
+    /// not seat the player.
+    pub fn refund(&self) {
+        self.remaining.fetch_add(1, Ordering::AcqRel);
+    }
+}
+
+/// Why an invite token cannot be redeemed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InviteError {
+    /// Never issued, malformed, or its room was deleted
+    NotFound,
+    Expired,
+    /// Every use has been taken
+    Exhausted,
+}
+
+/// Why an invite could not be created.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CreateInviteError {
+    /// The room already has [`MAX_INVITES_PER_ROOM`] unexpired invites
+    TooManyInvites,
+}
+
+/// Invites issued on this instance, keyed by lookup id.
+#[derive(Debug, Default)]
+pub struct InviteStore {
+    invites: DashMap<String, Arc<Invite>>,
+}
+
+impl InviteStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Issue an invite to the room allowing `max_uses` joins until
+    /// `expires_at`. Returns the token and the stored invite.
+    pub fn create(
+        &self,
+        room_id: RoomId,
+        game_name: String,
+        room_code: String,
+        max_uses: u32,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(String, Arc<Invite>), CreateInviteError> {
+        let now = Utc::now();
+        let room_invites = self
+            .invites
+            .iter()
+            .filter(|entry| entry.room_id == room_id && entry.expires_at > now)
+            .count();
+        if room_invites >= MAX_INVITES_PER_ROOM {
+            return Err(CreateInviteError::TooManyInvites);
+        }
+
+        let mut rng = rand::rng();
+        let secret = URL_SAFE_NO_PAD.encode(rng.random::<[u8; 16]>());
+        let invite = Arc::new(Invite {
+            room_id,
+            game_name,
+            room_code,
+            max_uses,
+            expires_at,
+            secret: secret.clone(),
+            remaining: AtomicU32::new(max_uses),
+        });
+        loop {
+            let lookup_id = URL_SAFE_NO_PAD.encode(rng.random::<[u8; 8]>());
+            if let dashmap::Entry::Vacant(entry) = self.invites.entry(lookup_id.clone()) {
+                entry.insert(Arc::clone(&invite));
+                return Ok((format!("{lookup_id}.{secret}"), invite));
+            }
+        }
+    }
+
+    /// Take one use of the invite behind `token`. The use is gone even if
+    /// the join then fails, unless the caller refunds it.
+    pub fn redeem(&self, token: &str, now: DateTime<Utc>) -> Result<Arc<Invite>, InviteError> {
+        let (lookup_id, secret) = token.split_once('.').ok_or(InviteError::NotFound)?;
+        let invite = self
+            .invites
+            .get(lookup_id)
+            .map(|entry| Arc::clone(entry.value()))
+            .ok_or(InviteError::NotFound)?;
+        if !crate::security::constant_time_eq(secret.as_bytes(), invite.secret.as_bytes()) {
+            return Err(InviteError::NotFound);
+        }
+        if invite.expires_at <= now {
+            return Err(InviteError::Expired);
+        }
+        invite
+            .remaining
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |remaining| {
+                remaining.checked_sub(1)
+            })
+            .map_err(|_| InviteError::Exhausted)?;
+        Ok(invite)
+    }
+
+    /// Drop every invite to a deleted room. Returns how many were dropped.
+    pub fn forget_room(&self, room_id: &RoomId) -> usize {
+        let before = self.invites.len();
+        self.invites.retain(|_, invite| invite.room_id != *room_id);
+        before.saturating_sub(self.invites.len())
+    }
+
+    /// Drop invites that expired by `now`. Returns how many were dropped.
+    pub fn purge_expired(&self, now: DateTime<Utc>) -> usize {
+        let before = self.invites.len();
+        self.invites.retain(|_, invite| invite.expires_at > now);
+        before.saturating_sub(self.invites.len())
+    }
+
+    /// Number of invites held, including used up ones.
+    pub fn len(&self) -> usize {
+        self.invites.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.invites.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn create(store: &InviteStore, room_id: RoomId, max_uses: u32) -> String {
+        store
+            .create(
+                room_id,
+                "game".to_string(),
+                "ABC123".to_string(),
+                max_uses,
+                Utc::now() + chrono::Duration::minutes(5),
+            )
+            .expect("invite is created")
+            .0
+    }
+
+    #[test]
+    fn tokens_are_random_and_carry_a_128_bit_secret() {
+        let store = InviteStore::new();
+        let room_id = Uuid::new_v4();
+        let first = create(&store, room_id, 1);
+        let second = create(&store, room_id, 1);
+        assert_ne!(first, second);
+
+        let (_, secret) = first.split_once('.').expect("token has two parts");
+        let decoded = URL_SAFE_NO_PAD.decode(secret).expect("secret is base64");
+        assert_eq!(decoded.len(), 16);
+    }
+
+    #[test]
+    fn uses_run_out_and_wrong_secrets_are_not_found() {
+        let store = InviteStore::new();
+        let token = create(&store, Uuid::new_v4(), 2);
+        let now = Utc::now();
+
+        let (lookup_id, _) = token.split_once('.').unwrap();
+        let forged = format!("{lookup_id}.AAAAAAAAAAAAAAAAAAAAAA");
+        assert_eq!(
+            store.redeem(&forged, now).unwrap_err(),
+            InviteError::NotFound
+        );
+        assert_eq!(
+            store.redeem("garbage", now).unwrap_err(),
+            InviteError::NotFound
+        );
+
+        let invite = store.redeem(&token, now).expect("first use");
+        assert_eq!(invite.remaining_uses(), 1);
+        store.redeem(&token, now).expect("second use");
+        assert_eq!(
+            store.redeem(&token, now).unwrap_err(),
+            InviteError::Exhausted
+        );
+
+        // A refunded use can be taken again
+        invite.refund();
+        store.redeem(&token, now).expect("refunded use");
+    }
+
+    #[test]
+    fn concurrent_redemptions_never_exceed_max_uses() {
+        let store = Arc::new(InviteStore::new());
+        let token = create(&store, Uuid::new_v4(), 10);
+        let now = Utc::now();
+        let handles: Vec<_> = (0..32)
+            .map(|_| {
+                let store = Arc::clone(&store);
+                let token = token.clone();
+                std::thread::spawn(move || store.redeem(&token, now).is_ok())
+            })
+            .collect();
+        let redeemed = handles
+            .into_iter()
+            .map(|handle| handle.join().expect("redeeming thread finishes"))
+            .filter(|redeemed| *redeemed)
+            .count();
+        assert_eq!(redeemed, 10);
+    }
+
+    #[test]
+    fn expired_and_deleted_room_invites_are_dropped() {
+        let store = InviteStore::new();
+        let room_id = Uuid::new_v4();
+        let token = create(&store, room_id, 1);
+        let other = create(&store, Uuid::new_v4(), 1);
+
+        let later = Utc::now() + chrono::Duration::minutes(10);
+        assert_eq!(
+            store.redeem(&token, later).unwrap_err(),
+            InviteError::Expired
+        );
+        assert_eq!(store.purge_expired(Utc::now()), 0);
+
+        assert_eq!(store.forget_room(&room_id), 1);
+        assert_eq!(
+            store.redeem(&token, Utc::now()).unwrap_err(),
+            InviteError::NotFound
+        );
+        assert_eq!(store.purge_expired(later), 1);
+        assert_eq!(
+            store.redeem(&other, Utc::now()).unwrap_err(),
+            InviteError::NotFound
+        );
+        assert!(store.is_empty());
+    }
+
+    #[test]
+    fn rooms_have_a_cap_on_unexpired_invites() {
+        let store = InviteStore::new();
+        let room_id = Uuid::new_v4();
+        for _ in 0..MAX_INVITES_PER_ROOM {
+            create(&store, room_id, 1);
+        }
+        let result = store.create(
+            room_id,
+            "game".to_string(),
+            "ABC123".to_string(),
+            1,
+            Utc::now() + chrono::Duration::minutes(5),
+        );
+        assert_eq!(result.unwrap_err(), CreateInviteError::TooManyInvites);
+        create(&store, Uuid::new_v4(), 1);
+    }
+}
@@ -0,0 +1,156 @@
+//! Room join precondition hooks.
+//!
+//! Embedders implement [`JoinInterceptor`] and install it with
+//! [`EnhancedGameServer::set_join_interceptor`](crate::server::EnhancedGameServer::set_join_interceptor)
+//! to apply their own join rules (ban lists, entitlement checks, team
+//! balancing). The hook runs after the server's own validation and before the
+//! player is added to the room. Calls are bounded by
+//! `server.join_interceptor_timeout_ms`; errors and timeouts are resolved by
+//! `server.join_interceptor_failure_policy`.
+
+use crate::auth::AppInfo;
+use crate::protocol::{ErrorCode, LobbyState, PlayerId, PlayerRole, Room, RoomId};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+
+/// The room as it stood when the join was attempted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JoinRoomSummary {
+    pub room_id: RoomId,
+    pub player_count: usize,
+    pub max_players: u8,
+    pub spectator_count: usize,
+    pub lobby_state: LobbyState,
+    pub metadata: HashMap<String, String>,
+}
+
+impl JoinRoomSummary {
+    pub fn for_room(room: &Room) -> Self {
+        Self {
+            room_id: room.id,
+            player_count: room.players.len(),
+            max_players: room.max_players,
+            spectator_count: room.spectators.len(),
+            lobby_state: room.lobby_state.clone(),
+            metadata: room.metadata.clone(),
+        }
+    }
+}
+
+/// Details handed to [`JoinInterceptor::authorize_join`].
+#[derive(Debug, Clone)]
+pub struct JoinContext {
+    pub game_name: String,
+    pub room_code: String,
+    /// `None` when the join creates the room
+    pub room: Option<JoinRoomSummary>,
+    pub player_id: PlayerId,
+    /// Requested player or spectator name
+    pub player_name: String,
+    /// Authenticated application of the connection, if any
+    pub app_info: Option<AppInfo>,
+    pub client_ip: Option<IpAddr>,
+    /// Whether the connection is joining as a spectator
+    pub spectator: bool,
+}
+
+/// Changes applied to a player admitted with [`JoinDecision::AllowWithModifications`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JoinModifications {
+    /// Role to give the player instead of the default
+    pub role: Option<PlayerRole>,
+    /// Entries merged into the room's metadata; an empty value removes its key
+    pub metadata: HashMap<String, String>,
+}
+
+/// Outcome of a join hook.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JoinDecision {
+    Allow,
+    /// Refuse the join; `code` and `message` are sent to the client
+    Deny {
+        code: ErrorCode,
+        message: String,
+    },
+    /// Admit the player with changes. Spectator joins treat this as `Allow`.
+    AllowWithModifications(JoinModifications),
+}
+
+/// Hook consulted before a player or spectator is added to a room.
+#[async_trait]
+pub trait JoinInterceptor: Send + Sync {
+    /// Decide whether the join may go ahead. An error is handled by the
+    /// configured failure policy, as is a call that overruns the timeout.
+    async fn authorize_join(&self, ctx: JoinContext) -> anyhow::Result<JoinDecision>;
+}
+
+/// Default interceptor: every join is allowed.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoopJoinInterceptor;
+
+#[async_trait]
+impl JoinInterceptor for NoopJoinInterceptor {
+    async fn authorize_join(&self, _ctx: JoinContext) -> anyhow::Result<JoinDecision> {
+        Ok(JoinDecision::Allow)
+    }
+}
+
+/// Interceptor for tests. Returns a fixed decision (or fails) after an
+/// optional delay and records the context of every call.
+#[derive(Debug)]
+pub struct FakeJoinInterceptor {
+    decision: Option<JoinDecision>,
+    delay: Duration,
+    calls: Mutex<Vec<JoinContext>>,
+}
+
+impl FakeJoinInterceptor {
+    pub fn new(decision: JoinDecision) -> Self {
+        Self {
+            decision: Some(decision),
+            delay: Duration::ZERO,
+            calls: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Fail every call.
+    pub fn failing() -> Self {
+        Self {
+            decision: None,
+            ..Self::new(JoinDecision::Allow)
+        }
+    }
+
+    /// Wait `delay` before answering.
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Contexts of every call so far, oldest first.
+    pub fn calls(&self) -> Vec<JoinContext> {
+        self.calls
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+}
+
+#[async_trait]
+impl JoinInterceptor for FakeJoinInterceptor {
+    async fn authorize_join(&self, ctx: JoinContext) -> anyhow::Result<JoinDecision> {
+        self.calls
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(ctx);
+        if !self.delay.is_zero() {
+            tokio::time::sleep(self.delay).await;
+        }
+        self.decision
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("fake join interceptor failure"))
+    }
+}
@@ -22,6 +22,9 @@ pub mod auth;
 /// Optimized broadcast message handling
 pub mod broadcast;
 
+/// Recently closed room summaries for admin tooling
+pub mod closed_rooms;
+
 /// Server configuration and environment variables
 pub mod config;
 
@@ -34,9 +37,18 @@ pub mod database;
 /// Distributed locking (in-memory implementation)
 pub mod distributed;
 
+/// Invite tokens for joining rooms without their code
+pub mod invites;
+
+/// Room join precondition hooks
+pub mod join_interceptor;
+
 /// Structured logging configuration
 pub mod logging;
 
+/// Skill-based matchmaking queue
+pub mod matchmaking;
+
 /// Metrics collection and reporting
 pub mod metrics;
 
@@ -49,17 +61,29 @@ pub mod rate_limit;
 /// Reconnection token and state management
 pub mod reconnection;
 
+/// Ahead-of-time relay session provisioning hooks
+pub mod relay_provisioning;
+
+/// Player reports routed to operators for review
+pub mod reports;
+
 /// Retry logic utilities
 pub mod retry;
 
 /// Zero-copy serialization utilities
 pub mod rkyv_utils;
 
+/// Room lifecycle events streamed to observers
+pub mod room_events;
+
 /// TLS and crypto utilities
 pub mod security;
 
 /// Main server orchestration
 pub mod server;
 
+/// Room lifecycle webhooks
+pub mod webhooks;
+
 /// WebSocket connection handling
 pub mod websocket;
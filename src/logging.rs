@@ -2,6 +2,10 @@ use tracing_subscriber::{fmt::time::UtcTime, layer::Identity, prelude::*};
 
 use crate::config::{LogFormat, LoggingConfig};
 
+pub mod events;
+
+pub use events::{log_ws_event, WebSocketEvent};
+
 /// Initialize logging: console + rolling file appender (buffered), configurable via config file.
 /// Notes:
 /// - If logging.level is provided in config, it is used; otherwise RUST_LOG env var is used; fallback is "info".
@@ -0,0 +1,177 @@
+//! Structured events for WebSocket message processing.
+//!
+//! Each event is logged with its name in `event` and every field as its own
+//! key, so `logging.format = "json"` output can be filtered without parsing
+//! the message text.
+
+use crate::protocol::{PlayerId, RoomId};
+use std::net::IpAddr;
+
+/// A WebSocket connection or message lifecycle event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WebSocketEvent {
+    MessageReceived {
+        message_type: &'static str,
+        player_id: PlayerId,
+        room_id: Option<RoomId>,
+    },
+    MessageSent {
+        message_type: &'static str,
+        player_id: PlayerId,
+    },
+    ConnectionOpened {
+        player_id: PlayerId,
+        ip: IpAddr,
+    },
+    ConnectionClosed {
+        player_id: PlayerId,
+        reason: &'static str,
+    },
+}
+
+impl WebSocketEvent {
+    /// Value of the `event` field.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::MessageReceived { .. } => "message_received",
+            Self::MessageSent { .. } => "message_sent",
+            Self::ConnectionOpened { .. } => "connection_opened",
+            Self::ConnectionClosed { .. } => "connection_closed",
+        }
+    }
+}
+
+/// Emit `event` at info level with all of its fields as key-value pairs.
+pub fn log_ws_event(event: &WebSocketEvent) {
+    let name = event.name();
+    match event {
+        WebSocketEvent::MessageReceived {
+            message_type,
+            player_id,
+            room_id,
+        } => tracing::info!(
+            event = name,
+            message_type,
+            %player_id,
+            room_id = room_id.as_ref().map(tracing::field::display),
+            "WebSocket message received"
+        ),
+        WebSocketEvent::MessageSent {
+            message_type,
+            player_id,
+        } => tracing::info!(
+            event = name,
+            message_type,
+            %player_id,
+            "WebSocket message sent"
+        ),
+        WebSocketEvent::ConnectionOpened { player_id, ip } => tracing::info!(
+            event = name,
+            %player_id,
+            %ip,
+            "WebSocket connection established"
+        ),
+        WebSocketEvent::ConnectionClosed { player_id, reason } => tracing::info!(
+            event = name,
+            %player_id,
+            reason,
+            "WebSocket connection closed"
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+    use uuid::Uuid;
+
+    #[derive(Clone, Default)]
+    struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Log `events` through the JSON formatter and return each line's fields.
+    fn capture_json_fields(events: &[WebSocketEvent]) -> Vec<serde_json::Value> {
+        let logs = CapturedLogs::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .json()
+            .with_writer(move || writer.clone())
+            .finish();
+        tracing::subscriber::with_default(subscriber, || {
+            for event in events {
+                log_ws_event(event);
+            }
+        });
+
+        let output = logs.0.lock().unwrap().clone();
+        String::from_utf8(output)
+            .expect("logs are UTF-8")
+            .lines()
+            .map(|line| {
+                let mut record: serde_json::Value =
+                    serde_json::from_str(line).expect("each line is a JSON record");
+                record["fields"].take()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn events_are_logged_as_structured_json() {
+        let player_id = Uuid::new_v4();
+        let room_id = Uuid::new_v4();
+        let lines = capture_json_fields(&[
+            WebSocketEvent::ConnectionOpened {
+                player_id,
+                ip: "203.0.113.7".parse().unwrap(),
+            },
+            WebSocketEvent::MessageReceived {
+                message_type: "JoinRoom",
+                player_id,
+                room_id: Some(room_id),
+            },
+            WebSocketEvent::MessageReceived {
+                message_type: "Ping",
+                player_id,
+                room_id: None,
+            },
+            WebSocketEvent::MessageSent {
+                message_type: "Authenticated",
+                player_id,
+            },
+            WebSocketEvent::ConnectionClosed {
+                player_id,
+                reason: "client_closed",
+            },
+        ]);
+        assert_eq!(lines.len(), 5);
+        let player = player_id.to_string();
+
+        assert_eq!(lines[0]["event"], "connection_opened");
+        assert_eq!(lines[0]["player_id"], player);
+        assert_eq!(lines[0]["ip"], "203.0.113.7");
+
+        assert_eq!(lines[1]["event"], "message_received");
+        assert_eq!(lines[1]["message_type"], "JoinRoom");
+        assert_eq!(lines[1]["player_id"], player);
+        assert_eq!(lines[1]["room_id"], room_id.to_string());
+        assert!(lines[2].get("room_id").is_none());
+
+        assert_eq!(lines[3]["event"], "message_sent");
+        assert_eq!(lines[3]["message_type"], "Authenticated");
+
+        assert_eq!(lines[4]["event"], "connection_closed");
+        assert_eq!(lines[4]["reason"], "client_closed");
+    }
+}
@@ -1,20 +1,12 @@
 #![cfg_attr(not(test), deny(clippy::panic))]
 
-use axum::extract::Request;
-use axum::http::HeaderMap;
-use axum::middleware::{self, Next};
-use axum::response::Response;
-use axum::Router;
 use clap::Parser;
 use signal_fish_server::config;
 use signal_fish_server::database::DatabaseConfig;
 use signal_fish_server::logging;
-use signal_fish_server::security::{
-    ClientCertificateFingerprint, CLIENT_FINGERPRINT_HEADER_CANDIDATES,
-};
 use signal_fish_server::server::{EnhancedGameServer, ServerConfig};
 use signal_fish_server::websocket;
-use std::{convert::Infallible, net::SocketAddr, sync::Arc};
+use std::{net::SocketAddr, path::PathBuf, sync::Arc};
 
 /// Signal Fish -- lightweight WebSocket signaling server for P2P game networking
 #[derive(Parser, Debug)]
@@ -31,14 +23,19 @@ struct Cli {
     /// Useful for debugging configuration loading from multiple sources.
     #[arg(long, conflicts_with = "validate_config")]
     print_config: bool,
+
+    /// Load configuration from this file instead of looking for config.json,
+    /// config.toml or config.yaml. The format follows the file extension.
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
 }
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
 
-    // Load configuration from config.json if present; otherwise use code defaults.
-    let cfg = Arc::new(config::load());
+    // Load configuration from --config or a config file if present; otherwise use code defaults.
+    let cfg = Arc::new(config::load_from(cli.config.as_deref())?);
 
     // Handle --print-config: output the loaded configuration as JSON
     if cli.print_config {
@@ -64,6 +61,10 @@ async fn main() -> anyhow::Result<()> {
                 println!("  Port: {}", cfg.port);
                 println!("  Storage backend: InMemory");
                 println!("  TLS enabled: {}", cfg.security.transport.tls.enabled);
+                println!(
+                    "  Client auth mode: {:?}",
+                    cfg.security.transport.tls.client_auth
+                );
                 println!(
                     "  Metrics auth required: {}",
                     cfg.security.require_metrics_auth
@@ -91,35 +92,9 @@ async fn main() -> anyhow::Result<()> {
 
     tracing::info!(%addr, "Starting Signal Fish server");
 
-    // Create server configuration from loaded config
-    let server_config = ServerConfig {
-        default_max_players: cfg.server.default_max_players,
-        ping_timeout: tokio::time::Duration::from_secs(cfg.server.ping_timeout),
-        room_cleanup_interval: tokio::time::Duration::from_secs(cfg.server.room_cleanup_interval),
-        max_rooms_per_game: cfg.server.max_rooms_per_game,
-        rate_limit_config: signal_fish_server::rate_limit::RateLimitConfig {
-            max_room_creations: cfg.rate_limit.max_room_creations,
-            time_window: tokio::time::Duration::from_secs(cfg.rate_limit.time_window),
-            max_join_attempts: cfg.rate_limit.max_join_attempts,
-        },
-        empty_room_timeout: tokio::time::Duration::from_secs(cfg.server.empty_room_timeout),
-        inactive_room_timeout: tokio::time::Duration::from_secs(cfg.server.inactive_room_timeout),
-        max_message_size: cfg.security.max_message_size,
-        max_connections_per_ip: cfg.security.max_connections_per_ip,
-        require_metrics_auth: cfg.security.require_metrics_auth,
-        metrics_auth_token: cfg.security.metrics_auth_token.clone(),
-        reconnection_window: tokio::time::Duration::from_secs(cfg.server.reconnection_window),
-        event_buffer_size: cfg.server.event_buffer_size,
-        enable_reconnection: cfg.server.enable_reconnection,
-        websocket_config: cfg.websocket.clone(),
-        auth_enabled: cfg.security.require_websocket_auth,
-        heartbeat_throttle: tokio::time::Duration::from_secs(cfg.server.heartbeat_throttle_secs),
-        region_id: cfg.server.region_id.clone(),
-        room_code_prefix: cfg.server.room_code_prefix.clone(),
-    };
+    let server_config = server_config(&cfg);
 
-    // Always use in-memory storage
-    let database_config = DatabaseConfig::InMemory;
+    let database_config = database_config(&cfg.storage)?;
 
     // Create the enhanced game server
     let game_server = EnhancedGameServer::new(
@@ -141,46 +116,8 @@ async fn main() -> anyhow::Result<()> {
         cleanup_server.cleanup_task().await;
     });
 
-    // Create enhanced protocol router with CORS configuration
-    let enhanced_router =
-        websocket::create_router(&cfg.security.cors_origins).with_state(game_server.clone());
-
-    // Parse CORS origins for top-level router
-    use tower_http::cors::{Any, CorsLayer};
-
-    let cors = if cfg.security.cors_origins == "*" {
-        CorsLayer::permissive()
-    } else {
-        let origins: Vec<_> = cfg
-            .security
-            .cors_origins
-            .split(',')
-            .filter_map(|s| s.trim().parse::<axum::http::HeaderValue>().ok())
-            .collect();
-
-        if origins.is_empty() {
-            tracing::warn!("No valid CORS origins configured, using permissive CORS");
-            CorsLayer::permissive()
-        } else {
-            CorsLayer::new()
-                .allow_origin(origins)
-                .allow_methods(Any)
-                .allow_headers(Any)
-        }
-    };
-
-    use axum::routing::get;
-
-    // Build base router with metrics endpoints
-    #[allow(unused_mut)]
-    let mut combined_router = Router::new()
-        .route("/v1/metrics", get(websocket::metrics_handler))
-        .route("/metrics", get(websocket::metrics_handler))
-        .route(
-            "/v1/metrics/prom",
-            get(websocket::prometheus_metrics_handler),
-        )
-        .route("/metrics/prom", get(websocket::prometheus_metrics_handler));
+    #[cfg(unix)]
+    spawn_reload_on_sighup(game_server.clone(), cfg.clone(), cli.config.clone());
 
     // Spawn legacy full-mesh signaling on a separate port if enabled
     #[cfg(feature = "legacy-fullmesh")]
@@ -203,76 +140,138 @@ async fn main() -> anyhow::Result<()> {
         );
     }
 
-    // Complete the router
-    let combined_router = combined_router
-        .nest("/v2", enhanced_router) // Enhanced protocol under /v2
-        .fallback(|| async {
-            "Signal Fish Server. Use /v2/ws for WebSocket protocol, /v1/metrics for metrics, /metrics/prom for Prometheus."
-        })
-        .layer(middleware::from_fn(capture_client_fingerprint))
-        .with_state(game_server)
-        .layer(cors);
-
-    let make_service = combined_router.into_make_service_with_connect_info::<SocketAddr>();
-
-    #[cfg(feature = "tls")]
-    if cfg.security.transport.tls.enabled {
-        let tls_config =
-            signal_fish_server::security::build_rustls_config(&cfg.security.transport.tls)
-                .map_err(|err| anyhow::anyhow!("failed to initialize TLS configuration: {err}"))?;
-
-        tracing::info!(
-            %addr,
-            client_auth = ?cfg.security.transport.tls.client_auth,
-            "Server started over HTTPS with TLS enabled - Enhanced protocol: /v2/ws, Metrics: /v1/metrics"
-        );
-
-        axum_server::bind_rustls(addr, tls_config)
-            .serve(make_service)
-            .await?;
-
-        return Ok(());
-    }
-
-    // Start the server over plain TCP (typically behind a reverse proxy).
-    let listener = tokio::net::TcpListener::bind(addr).await?;
-    tracing::info!(
-        %addr,
-        cors_origins = %cfg.security.cors_origins,
-        "Server started over HTTP - Enhanced protocol: /v2/ws, Metrics: /v1/metrics"
-    );
-
-    axum::serve(listener, make_service).await?;
+    let router = websocket::build_router(&cfg, game_server.clone());
+    websocket::serve(router, &cfg, &game_server).await?;
 
+    game_server.persist_metrics();
+    game_server.flush_database().await;
+    game_server.shutdown_metrics_export().await;
     Ok(())
 }
 
-async fn capture_client_fingerprint(mut req: Request, next: Next) -> Result<Response, Infallible> {
-    if let Some(fingerprint) = extract_client_fingerprint(req.headers()) {
-        req.extensions_mut().insert(fingerprint);
+/// Room storage backend selected by the `storage` config section.
+fn database_config(storage: &config::StorageConfig) -> anyhow::Result<DatabaseConfig> {
+    match storage.backend {
+        config::StorageBackend::Memory => Ok(DatabaseConfig::InMemory),
+        #[cfg(feature = "persistent")]
+        config::StorageBackend::File => Ok(DatabaseConfig::File {
+            path: storage.path.clone().into(),
+        }),
+        #[cfg(not(feature = "persistent"))]
+        config::StorageBackend::File => {
+            anyhow::bail!("storage.backend \"file\" requires the `persistent` feature")
+        }
     }
+}
 
-    Ok(next.run(req).await)
+/// Runtime server settings derived from the loaded configuration.
+fn server_config(cfg: &config::Config) -> ServerConfig {
+    ServerConfig {
+        default_max_players: cfg.server.default_max_players,
+        ping_timeout: cfg.server.ping_timeout.into(),
+        room_cleanup_interval: cfg.server.room_cleanup_interval.into(),
+        max_rooms_per_game: cfg.server.max_rooms_per_game,
+        rate_limit_config: signal_fish_server::rate_limit::RateLimitConfig {
+            max_room_creations: cfg.rate_limit.max_room_creations,
+            time_window: cfg.rate_limit.time_window.into(),
+            max_join_attempts: cfg.rate_limit.max_join_attempts,
+            strategy: cfg.rate_limit.strategy,
+            room_creation_bucket: cfg.rate_limit.room_creation_bucket,
+            join_attempt_bucket: cfg.rate_limit.join_attempt_bucket,
+            spectator_chat_bucket: cfg.rate_limit.spectator_chat_bucket,
+            game_data_bucket: cfg.rate_limit.game_data_bucket,
+            connection_bucket: cfg.rate_limit.connection_bucket,
+            key_by: cfg.rate_limit.key_by,
+            ip_max_room_creations: cfg.rate_limit.ip_max_room_creations,
+            ip_max_join_attempts: cfg.rate_limit.ip_max_join_attempts,
+            max_active_rooms_per_creator: cfg.rate_limit.max_active_rooms_per_creator,
+        },
+        empty_room_timeout: cfg.server.empty_room_timeout.into(),
+        inactive_room_timeout: cfg.server.inactive_room_timeout.into(),
+        expiry_warning: std::time::Duration::from_secs(cfg.server.expiry_warning_secs),
+        max_message_size: cfg.security.max_message_size.as_usize(),
+        max_connections_per_ip: cfg.security.max_connections_per_ip,
+        trusted_proxies: cfg.security.trust_forwarded_for.clone(),
+        ipv6_connection_prefix: cfg.security.ipv6_connection_prefix,
+        ip_filter: cfg.security.ip_filter(),
+        require_metrics_auth: cfg.security.require_metrics_auth,
+        metrics_auth_token: cfg.security.metrics_auth_token.clone(),
+        admin_auth_token: cfg.security.admin_auth_token.clone(),
+        reconnection_window: cfg.server.reconnection_window.into(),
+        event_buffer_size: cfg.server.event_buffer_size,
+        enable_reconnection: cfg.server.enable_reconnection,
+        flap_suppression: cfg.server.flap_suppression_secs.into(),
+        max_token_age: std::time::Duration::from_secs(cfg.server.max_token_age_secs),
+        max_reconnection_slots_per_room: cfg.server.max_reconnection_slots_per_room,
+        websocket_config: cfg.websocket.clone(),
+        auth_enabled: cfg.security.require_websocket_auth,
+        heartbeat_throttle: cfg.server.heartbeat_throttle_secs.into(),
+        latency_update_threshold_ms: cfg.server.latency_update_threshold_ms,
+        region_id: cfg.server.region_id.clone(),
+        allowed_regions: cfg.server.allowed_regions.clone(),
+        room_code_prefix: cfg.server.room_code_prefix.clone(),
+        max_waiting_list_size: cfg.server.max_waiting_list_size,
+        max_promotion_queue_size: cfg.server.max_promotion_queue_size,
+        matchmaking_elo_window: cfg.server.matchmaking_elo_window,
+        matchmaking_tick: cfg.server.matchmaking_tick_ms.into(),
+        readiness_check_timeout: cfg.server.readiness_check_timeout_ms.into(),
+        drain_retry_after: cfg.server.drain_retry_after_secs.into(),
+        isolate_games: cfg.server.isolate_games.clone(),
+        isolation_auto_threshold: cfg.server.isolation_auto_threshold,
+        broadcast_workers: cfg.server.broadcast_workers,
+        isolated_broadcast_workers: cfg.server.isolated_broadcast_workers,
+        enable_history_replay: cfg.server.enable_history_replay,
+        room_history_size: cfg.server.room_history_size,
+        join_interceptor_timeout: cfg.server.join_interceptor_timeout_ms.into(),
+        join_interceptor_failure_policy: cfg.server.join_interceptor_failure_policy,
+        webhooks: cfg.server.webhooks.clone(),
+        ice_servers: cfg.server.ice_servers.clone(),
+        closed_room_history_size: cfg.server.closed_room_history_size,
+        room_idempotency_window: cfg.server.room_idempotency_window.into(),
+        join_request_timeout: cfg.server.join_request_timeout.into(),
+        stale_player_timeout: cfg.server.stale_player_timeout.into(),
+        cors_origins: cfg.security.game_cors_origins().to_string(),
+        metrics_cors_origins: cfg.security.metrics_cors_origins().to_string(),
+        game_overrides: cfg.game_overrides.clone(),
+        default_app_quota: cfg.security.default_app_quota,
+    }
 }
 
-fn extract_client_fingerprint(headers: &HeaderMap) -> Option<ClientCertificateFingerprint> {
-    for header_name in CLIENT_FINGERPRINT_HEADER_CANDIDATES {
-        if let Some(value) = headers
-            .get(*header_name)
-            .and_then(|value| value.to_str().ok())
-        {
-            let trimmed = value.trim();
-            if trimmed.is_empty() {
-                continue;
+/// Reload configuration on every SIGHUP and apply its reloadable subset to
+/// `server`. A configuration that fails to load or validate is rejected and
+/// the active one stays in force. Settings that need a restart are reported
+/// and ignored.
+#[cfg(unix)]
+fn spawn_reload_on_sighup(
+    server: Arc<EnhancedGameServer>,
+    startup: Arc<config::Config>,
+    config_file: Option<PathBuf>,
+) {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangup = match signal(SignalKind::hangup()) {
+        Ok(hangup) => hangup,
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to install SIGHUP handler; configuration reload disabled");
+            return;
+        }
+    };
+    tokio::spawn(async move {
+        while hangup.recv().await.is_some() {
+            tracing::info!("Received SIGHUP, reloading configuration");
+            let reloaded = match config::try_load_from(config_file.as_deref()) {
+                Ok(reloaded) => reloaded,
+                Err(e) => {
+                    tracing::error!(error = %e, "Rejected reloaded configuration; keeping the active one");
+                    continue;
+                }
+            };
+            for setting in config::non_reloadable_changes(&startup, &reloaded) {
+                tracing::warn!(%setting, "Setting changed but requires a restart; ignoring");
             }
-            return Some(ClientCertificateFingerprint {
-                fingerprint: Arc::<str>::from(trimmed.to_owned()),
-                source_header: header_name,
-            });
+            server.reload_config(&server_config(&reloaded));
         }
-    }
-
-    None
+    });
 }
 
 #[cfg(test)]
@@ -285,6 +284,21 @@ mod cli_tests {
         let cli = Cli::try_parse_from(["signal-fish-server"]).unwrap();
         assert!(!cli.validate_config);
         assert!(!cli.print_config);
+        assert!(cli.config.is_none());
+    }
+
+    #[test]
+    fn test_cli_config_path() {
+        let cli = Cli::try_parse_from([
+            "signal-fish-server",
+            "--config",
+            "/etc/signal-fish/server.toml",
+        ])
+        .unwrap();
+        assert_eq!(
+            cli.config.as_deref(),
+            Some(std::path::Path::new("/etc/signal-fish/server.toml"))
+        );
     }
 
     #[test]
@@ -327,6 +341,7 @@ mod cli_tests {
         let help_text = err.to_string();
         assert!(help_text.contains("--validate-config"));
         assert!(help_text.contains("--print-config"));
+        assert!(help_text.contains("--config <PATH>"));
         assert!(help_text.contains("-c"));
     }
 
@@ -0,0 +1,301 @@
+//! Skill-based matchmaking queue.
+//!
+//! Players queue per game, region and desired room size. Each matching pass
+//! sorts a bucket by skill rating and greedily takes runs of
+//! `desired_max_players` whose ratings all fall within the ELO window,
+//! oldest requests breaking ties.
+
+use crate::protocol::PlayerId;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// A player's request to be matched into a new room.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchmakingRequest {
+    pub player_id: PlayerId,
+    pub player_name: String,
+    pub game_name: String,
+    pub skill_rating: f32,
+    pub region_id: String,
+    pub desired_max_players: u8,
+}
+
+/// Players grouped by a matching pass; the first request becomes the room host.
+#[derive(Debug, Clone)]
+pub struct MatchGroup {
+    pub game_name: String,
+    pub region_id: String,
+    pub max_players: u8,
+    pub players: Vec<MatchmakingRequest>,
+}
+
+/// Queue depth for one game and region, as reported by `/v2/matchmaking/stats`.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchmakingQueueStats {
+    pub game_name: String,
+    pub region_id: String,
+    pub queued: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct BucketKey {
+    game_name: String,
+    region_id: String,
+    desired_max_players: u8,
+}
+
+impl BucketKey {
+    fn for_request(request: &MatchmakingRequest) -> Self {
+        Self {
+            game_name: request.game_name.clone(),
+            region_id: request.region_id.clone(),
+            desired_max_players: request.desired_max_players,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct QueuedRequest {
+    request: MatchmakingRequest,
+    enqueued_at: Instant,
+}
+
+#[derive(Debug, Default)]
+struct QueueState {
+    buckets: HashMap<BucketKey, Vec<QueuedRequest>>,
+    players: HashMap<PlayerId, BucketKey>,
+}
+
+impl QueueState {
+    fn remove(&mut self, player_id: &PlayerId) -> Option<MatchmakingRequest> {
+        let key = self.players.remove(player_id)?;
+        let bucket = self.buckets.get_mut(&key)?;
+        let index = bucket
+            .iter()
+            .position(|queued| queued.request.player_id == *player_id)?;
+        let removed = bucket.remove(index);
+        if bucket.is_empty() {
+            self.buckets.remove(&key);
+        }
+        Some(removed.request)
+    }
+}
+
+/// Pending matchmaking requests, at most one per player.
+#[derive(Debug, Default)]
+pub struct MatchmakingQueue {
+    state: Mutex<QueueState>,
+}
+
+impl MatchmakingQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue a request, replacing any earlier request from the same player.
+    pub fn enqueue(&self, request: MatchmakingRequest) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.remove(&request.player_id);
+        let key = BucketKey::for_request(&request);
+        state.players.insert(request.player_id, key.clone());
+        state.buckets.entry(key).or_default().push(QueuedRequest {
+            request,
+            enqueued_at: Instant::now(),
+        });
+    }
+
+    /// Cancel a player's request. Returns `false` if they were not queued.
+    pub fn dequeue(&self, player_id: &PlayerId) -> bool {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.remove(player_id).is_some()
+    }
+
+    pub fn is_queued(&self, player_id: &PlayerId) -> bool {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.players.contains_key(player_id)
+    }
+
+    pub fn len(&self) -> usize {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.players.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remove and return every group that can be formed within `elo_window`.
+    pub fn find_matches(&self, elo_window: f32) -> Vec<MatchGroup> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let QueueState { buckets, players } = &mut *state;
+        let mut groups = Vec::new();
+
+        buckets.retain(|key, bucket| {
+            let group_size = usize::from(key.desired_max_players.max(1));
+            if bucket.len() < group_size {
+                return true;
+            }
+
+            bucket.sort_by(|a, b| {
+                a.request
+                    .skill_rating
+                    .total_cmp(&b.request.skill_rating)
+                    .then(a.enqueued_at.cmp(&b.enqueued_at))
+            });
+
+            let mut remaining = Vec::with_capacity(bucket.len());
+            let queued = std::mem::take(bucket);
+            let mut window: Vec<QueuedRequest> = Vec::with_capacity(group_size);
+            for next in queued {
+                window.push(next);
+                if window.len() < group_size {
+                    continue;
+                }
+                let spread =
+                    window[group_size - 1].request.skill_rating - window[0].request.skill_rating;
+                if spread <= elo_window {
+                    let mut members: Vec<QueuedRequest> = std::mem::take(&mut window);
+                    members.sort_by_key(|member| member.enqueued_at);
+                    for member in &members {
+                        players.remove(&member.request.player_id);
+                    }
+                    groups.push(MatchGroup {
+                        game_name: key.game_name.clone(),
+                        region_id: key.region_id.clone(),
+                        max_players: key.desired_max_players,
+                        players: members.into_iter().map(|member| member.request).collect(),
+                    });
+                } else {
+                    // The lowest rating can't be matched with its neighbours; move on.
+                    remaining.push(window.remove(0));
+                }
+            }
+            remaining.extend(window);
+            remaining.sort_by_key(|queued| queued.enqueued_at);
+            *bucket = remaining;
+            !bucket.is_empty()
+        });
+
+        groups
+    }
+
+    /// Queue depth per game and region, summed across room sizes.
+    pub fn stats(&self) -> Vec<MatchmakingQueueStats> {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let mut depth: BTreeMap<(String, String), usize> = BTreeMap::new();
+        for (key, bucket) in &state.buckets {
+            *depth
+                .entry((key.game_name.clone(), key.region_id.clone()))
+                .or_default() += bucket.len();
+        }
+        depth
+            .into_iter()
+            .map(|((game_name, region_id), queued)| MatchmakingQueueStats {
+                game_name,
+                region_id,
+                queued,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn request(game_name: &str, skill_rating: f32, desired_max_players: u8) -> MatchmakingRequest {
+        MatchmakingRequest {
+            player_id: Uuid::new_v4(),
+            player_name: format!("Player{skill_rating}"),
+            game_name: game_name.to_string(),
+            skill_rating,
+            region_id: "eu".to_string(),
+            desired_max_players,
+        }
+    }
+
+    #[test]
+    fn groups_players_within_elo_window() {
+        let queue = MatchmakingQueue::new();
+        let low = request("chess", 1000.0, 2);
+        let high = request("chess", 1500.0, 2);
+        let near_low = request("chess", 1080.0, 2);
+        queue.enqueue(low.clone());
+        queue.enqueue(high.clone());
+        queue.enqueue(near_low.clone());
+
+        let groups = queue.find_matches(100.0);
+
+        assert_eq!(groups.len(), 1);
+        let ids: Vec<_> = groups[0].players.iter().map(|p| p.player_id).collect();
+        assert_eq!(ids, vec![low.player_id, near_low.player_id]);
+        assert!(queue.is_queued(&high.player_id));
+        assert_eq!(queue.len(), 1);
+    }
+
+    #[test]
+    fn waits_until_enough_players_are_queued() {
+        let queue = MatchmakingQueue::new();
+        queue.enqueue(request("chess", 1000.0, 3));
+        queue.enqueue(request("chess", 1010.0, 3));
+        assert!(queue.find_matches(100.0).is_empty());
+
+        queue.enqueue(request("chess", 1020.0, 3));
+        let groups = queue.find_matches(100.0);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].players.len(), 3);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn buckets_are_separated_by_game_region_and_size() {
+        let queue = MatchmakingQueue::new();
+        queue.enqueue(request("chess", 1000.0, 2));
+        queue.enqueue(request("go", 1000.0, 2));
+        queue.enqueue(request("chess", 1000.0, 4));
+        let mut other_region = request("chess", 1000.0, 2);
+        other_region.region_id = "us".to_string();
+        queue.enqueue(other_region);
+
+        assert!(queue.find_matches(100.0).is_empty());
+        assert_eq!(
+            queue.stats(),
+            vec![
+                MatchmakingQueueStats {
+                    game_name: "chess".to_string(),
+                    region_id: "eu".to_string(),
+                    queued: 2,
+                },
+                MatchmakingQueueStats {
+                    game_name: "chess".to_string(),
+                    region_id: "us".to_string(),
+                    queued: 1,
+                },
+                MatchmakingQueueStats {
+                    game_name: "go".to_string(),
+                    region_id: "eu".to_string(),
+                    queued: 1,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn requeue_replaces_and_dequeue_cancels() {
+        let queue = MatchmakingQueue::new();
+        let mut first = request("chess", 1000.0, 2);
+        queue.enqueue(first.clone());
+        first.game_name = "go".to_string();
+        queue.enqueue(first.clone());
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue.stats()[0].game_name, "go");
+
+        assert!(queue.dequeue(&first.player_id));
+        assert!(!queue.dequeue(&first.player_id));
+        assert!(queue.stats().is_empty());
+    }
+}
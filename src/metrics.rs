@@ -1,11 +1,30 @@
+use dashmap::DashMap;
 use hdrhistogram::Histogram;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 
+mod histogram;
+pub mod history;
+#[cfg(feature = "otlp")]
+pub mod otlp;
+pub mod persistence;
+
+pub use histogram::{DurationHistogram, DurationHistogramSnapshot};
+pub use history::{HistoryPoint, HistoryReading, MetricsHistory, METRICS_HISTORY_MAX_CAPACITY};
+
+/// Default session duration buckets in seconds: 1s, 10s, 1m, 5m, 30m and 1h.
+pub const DEFAULT_SESSION_DURATION_BUCKETS_SECS: [f64; 6] =
+    [1.0, 10.0, 60.0, 300.0, 1800.0, 3600.0];
+
+/// Default latency histogram buckets in milliseconds, from 1ms to 5s.
+pub const DEFAULT_LATENCY_BUCKETS_MS: [f64; 11] = [
+    1.0, 5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1000.0, 2500.0, 5000.0,
+];
+
 /// Comprehensive metrics collection for in-memory signaling server
 #[derive(Debug)]
 pub struct ServerMetrics {
@@ -15,6 +34,30 @@ pub struct ServerMetrics {
     pub disconnections: AtomicU64,
     pub connection_errors: AtomicU64,
     pub websocket_messages_dropped: AtomicU64,
+    /// Client messages received, including binary game data
+    pub websocket_messages_received: AtomicU64,
+    /// Serialized payload bytes written to WebSocket frames
+    pub websocket_bytes_sent: AtomicU64,
+    /// Connections lost because the read half ended or errored
+    pub disconnects_detected_on_read: AtomicU64,
+    /// Connections lost because a write failed or stalled past the write timeout
+    pub disconnects_detected_on_write: AtomicU64,
+    /// WebSocket upgrades refused because the client IP was at its connection limit
+    pub connections_rejected_ip_limit: AtomicU64,
+    /// WebSocket upgrades refused because the client IP opened connections too quickly
+    pub connections_rejected_rate_limit: AtomicU64,
+    /// HTTP requests refused because the client IP was not in `security.ip_allowlist`
+    pub connections_blocked_allowlist: AtomicU64,
+    /// HTTP requests refused because the client IP was in `security.ip_blocklist`
+    pub connections_blocked_blocklist: AtomicU64,
+    /// Connections closed with `BACKPRESSURE_SHED` because their outbound queue filled up
+    pub connections_closed_backpressure: AtomicU64,
+    /// Message batches written by connections with `websocket.enable_batching`
+    pub websocket_batches_flushed: AtomicU64,
+    /// Messages and room frames written as part of those batches
+    pub websocket_batched_messages: AtomicU64,
+    /// `ServerAnnouncement` messages queued to players, one per recipient
+    pub server_announcements_sent: AtomicU64,
 
     // Room operation metrics
     pub rooms_created: AtomicU64,
@@ -25,6 +68,19 @@ pub struct ServerMetrics {
     pub room_cap_lock_acquisitions: AtomicU64,
     pub room_cap_lock_failures: AtomicU64,
     pub room_cap_denials: AtomicU64,
+    pub rooms_closed_by_admin: AtomicU64,
+    /// Joins refused by the join interceptor
+    pub join_interceptor_denials: AtomicU64,
+    /// Join interceptor calls that errored or timed out
+    pub join_interceptor_failures: AtomicU64,
+    /// Webhook deliveries abandoned after exhausting their retries
+    pub webhook_delivery_failed: AtomicU64,
+    /// Invite tokens issued with `CreateInvite`
+    pub invites_created: AtomicU64,
+    /// Joins that used an invite token
+    pub invites_redeemed: AtomicU64,
+    /// Invite tokens dropped by the cleanup task after their TTL
+    pub invites_expired: AtomicU64,
 
     // Race condition and retry metrics
     pub room_capacity_conflicts: AtomicU64,
@@ -77,6 +133,8 @@ pub struct ServerMetrics {
     pub players_left: AtomicU64,
     pub authority_transfers: AtomicU64,
     pub game_data_messages: AtomicU64,
+    /// Player reports accepted from `ReportPlayer`
+    pub player_reports: AtomicU64,
 
     // Heartbeat throttling metrics
     /// Updates performed for player last_seen timestamps
@@ -90,6 +148,9 @@ pub struct ServerMetrics {
     pub reconnection_validations_failed: AtomicU64,
     pub reconnection_completions: AtomicU64,
     pub reconnection_events_buffered: AtomicU64,
+    pub token_replays_detected: AtomicU64,
+    /// Reconnects inside `flap_suppression_secs` that other players never saw
+    pub flaps_suppressed: AtomicU64,
 
     // Distributed lock metrics
     pub distributed_lock_release_failures: AtomicU64,
@@ -106,11 +167,127 @@ pub struct ServerMetrics {
     pub empty_rooms_cleaned: AtomicU64,
     pub inactive_rooms_cleaned: AtomicU64,
     pub expired_players_cleaned: AtomicU64,
+    pub stale_players_removed: AtomicU64,
 
     // Relay health metrics
     pub relay_client_id_reuse_events: AtomicU64,
     pub relay_client_id_exhaustion_events: AtomicU64,
     pub relay_session_timeouts: AtomicU64,
+
+    // Delivery acknowledgement sampling, keyed by (game name, region id)
+    pub delivery_acks: DashMap<(String, String), DeliveryAckCounters>,
+
+    // Game data broadcast dispatch, keyed by game name
+    pub broadcast_queues: DashMap<String, BroadcastQueueCounters>,
+
+    // Reconnection lifecycle, keyed by game name
+    pub reconnection_by_game: DashMap<String, ReconnectionGameCounters>,
+
+    // Disconnect-to-reconnect times, keyed by game name
+    pub time_to_reconnect: Arc<RwLock<ResponseTimeTracker>>,
+
+    // Rooms created, keyed by relay type
+    pub rooms_created_by_relay_type: DashMap<String, AtomicU64>,
+
+    // Protocol messages, keyed by direction and message type
+    pub messages_by_type: DashMap<(MessageDirection, &'static str), AtomicU64>,
+
+    // Heartbeat round-trip times, keyed by region id
+    pub connection_rtt: Arc<RwLock<ResponseTimeTracker>>,
+
+    // Time from WebSocket accept to close
+    pub session_duration: DurationHistogram,
+
+    // Upper bounds (ms) reported for the latency histograms
+    latency_buckets_ms: Vec<f64>,
+
+    // Process start, for uptime reporting
+    started_at: chrono::DateTime<chrono::Utc>,
+    started: Instant,
+}
+
+/// Crate version reported by the metrics endpoints.
+pub const BUILD_VERSION: &str = env!("CARGO_PKG_VERSION");
+/// Git commit the binary was built from (`unknown` outside a checkout).
+pub const BUILD_COMMIT: &str = env!("SIGNAL_FISH_GIT_COMMIT");
+
+/// Build and process information for correlating metrics with deploys.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildInfo {
+    pub version: String,
+    pub commit: String,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub uptime_seconds: f64,
+}
+
+/// Sampled delivery counters for one game/region pair.
+#[derive(Debug, Default)]
+pub struct DeliveryAckCounters {
+    pub expected: AtomicU64,
+    pub acked: AtomicU64,
+    pub failed: AtomicU64,
+    pub latency_total_us: AtomicU64,
+    pub latency_max_us: AtomicU64,
+}
+
+/// Broadcast dispatch counters for one game.
+#[derive(Debug, Default)]
+pub struct BroadcastQueueCounters {
+    /// Whether the game has its own broadcast workers
+    pub isolated: AtomicBool,
+    /// Broadcasts currently waiting for a worker
+    pub queued: AtomicU64,
+    pub dispatched: AtomicU64,
+    pub wait_total_us: AtomicU64,
+    pub wait_max_us: AtomicU64,
+}
+
+/// Reconnection counters for one game.
+#[derive(Debug, Default)]
+pub struct ReconnectionGameCounters {
+    pub tokens_issued: AtomicU64,
+    pub attempted: AtomicU64,
+    pub succeeded: AtomicU64,
+    pub failed_expired: AtomicU64,
+    pub failed_invalid: AtomicU64,
+    pub failed_room_gone: AtomicU64,
+    pub failed_slot_reclaimed: AtomicU64,
+    /// Unexpired tokens, refreshed by the cleanup task
+    pub outstanding_tokens: AtomicU64,
+    /// Joins into a full room seated by reclaiming an expired ghost slot
+    pub ghost_slots_reclaimed: AtomicU64,
+}
+
+/// Why a reconnection attempt was turned away.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReconnectFailureReason {
+    /// The token or the reconnection window expired
+    Expired,
+    /// The token was forged, superseded, or matched no pending disconnection
+    Invalid,
+    /// The room was removed while the player was away
+    RoomGone,
+    /// The slot was reclaimed to keep the room within its reconnection slot limit
+    SlotReclaimed,
+}
+
+/// Whether a counted protocol message came from a client or went to one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageDirection {
+    Inbound,
+    Outbound,
+}
+
+impl MessageDirection {
+    /// Label value for the metrics endpoints.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Inbound => "inbound",
+            Self::Outbound => "outbound",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -154,6 +331,16 @@ pub struct MetricsSnapshot {
     pub reconnection: ReconnectionMetrics,
     pub distributed_lock: DistributedLockMetrics,
     pub relay_health: RelayHealthMetrics,
+    #[serde(default)]
+    pub delivery_acks: Vec<DeliveryAckMetrics>,
+    #[serde(default)]
+    pub broadcast_queues: Vec<BroadcastQueueMetrics>,
+    #[serde(default)]
+    pub connection_rtt: Vec<ConnectionRttMetrics>,
+    #[serde(default)]
+    pub reconnection_by_game: Vec<ReconnectionGameMetrics>,
+    #[serde(default)]
+    pub messages_by_type: Vec<MessageTypeMetrics>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -163,11 +350,42 @@ pub struct ConnectionMetrics {
     pub disconnections: u64,
     pub connection_errors: u64,
     pub websocket_messages_dropped: u64,
+    #[serde(default)]
+    pub websocket_messages_received: u64,
+    #[serde(default)]
+    pub websocket_bytes_sent: u64,
+    #[serde(default)]
+    pub disconnects_detected_on_read: u64,
+    #[serde(default)]
+    pub disconnects_detected_on_write: u64,
+    #[serde(default)]
+    pub connections_rejected_ip_limit: u64,
+    #[serde(default)]
+    pub connections_rejected_rate_limit: u64,
+    #[serde(default)]
+    pub connections_blocked_allowlist: u64,
+    #[serde(default)]
+    pub connections_blocked_blocklist: u64,
+    #[serde(default)]
+    pub connections_closed_backpressure: u64,
+    #[serde(default)]
+    pub websocket_batches_flushed: u64,
+    #[serde(default)]
+    pub websocket_batched_messages: u64,
+    #[serde(default)]
+    pub server_announcements_sent: u64,
+    /// Messages per flushed batch, for tuning `websocket.batch_size`
+    #[serde(default)]
+    pub websocket_average_batch_size: Option<f64>,
+    #[serde(default)]
+    pub session_duration: DurationHistogramSnapshot,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct RoomMetrics {
     pub rooms_created: u64,
+    #[serde(default)]
+    pub rooms_created_by_relay_type: BTreeMap<String, u64>,
     pub rooms_joined: u64,
     pub room_creation_failures: u64,
     pub room_join_failures: u64,
@@ -175,6 +393,20 @@ pub struct RoomMetrics {
     pub room_cap_lock_acquisitions: u64,
     pub room_cap_lock_failures: u64,
     pub room_cap_denials: u64,
+    #[serde(default)]
+    pub rooms_closed_by_admin: u64,
+    #[serde(default)]
+    pub join_interceptor_denials: u64,
+    #[serde(default)]
+    pub join_interceptor_failures: u64,
+    #[serde(default)]
+    pub webhook_delivery_failed: u64,
+    #[serde(default)]
+    pub invites_created: u64,
+    #[serde(default)]
+    pub invites_redeemed: u64,
+    #[serde(default)]
+    pub invites_expired: u64,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -212,10 +444,15 @@ pub struct PerformanceMetrics {
     pub room_creation_latency: OperationLatencyMetrics,
     pub room_join_latency: OperationLatencyMetrics,
     pub query_latency: OperationLatencyMetrics,
+    #[serde(default)]
+    pub join_interceptor_latency: OperationLatencyMetrics,
+    /// Time from receiving a `LatencyPing` to queueing its `LatencyPong`
+    #[serde(default)]
+    pub latency_ping_processing: OperationLatencyMetrics,
     pub latency_histogram_clamped_samples: u64,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct OperationLatencyMetrics {
     pub average_ms: Option<f64>,
     pub p50_ms: Option<f64>,
@@ -224,6 +461,9 @@ pub struct OperationLatencyMetrics {
     pub min_ms: Option<f64>,
     pub max_ms: Option<f64>,
     pub sample_count: u64,
+    /// `(upper bound in milliseconds, cumulative count)` for each histogram bucket
+    #[serde(default)]
+    pub buckets: Vec<(f64, u64)>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -252,6 +492,7 @@ pub struct PlayerMetrics {
     pub players_left: u64,
     pub authority_transfers: u64,
     pub game_data_messages: u64,
+    pub player_reports: u64,
     /// Updates performed for player last_seen timestamps
     pub heartbeat_updates: u64,
     /// Updates skipped due to threshold-based throttling
@@ -265,6 +506,10 @@ pub struct ReconnectionMetrics {
     pub validations_failed: u64,
     pub completions: u64,
     pub events_buffered: u64,
+    #[serde(default)]
+    pub token_replays_detected: u64,
+    #[serde(default)]
+    pub flaps_suppressed: u64,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -282,6 +527,64 @@ pub struct RelayHealthMetrics {
     pub session_timeouts: u64,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeliveryAckMetrics {
+    pub game_name: String,
+    pub region_id: String,
+    /// Acknowledgements requested from capable clients
+    pub expected: u64,
+    pub acked: u64,
+    /// Samples that timed out (or were evicted) without an acknowledgement
+    pub failed: u64,
+    /// Share of resolved samples that were acknowledged
+    pub ack_rate: f64,
+    pub average_latency_ms: Option<f64>,
+    pub max_latency_ms: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BroadcastQueueMetrics {
+    pub game_name: String,
+    /// Whether the game has its own broadcast workers
+    pub isolated: bool,
+    /// Broadcasts waiting for a worker when the snapshot was taken
+    pub queue_depth: u64,
+    pub dispatched: u64,
+    pub average_wait_ms: Option<f64>,
+    pub max_wait_ms: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReconnectionGameMetrics {
+    pub game_name: String,
+    pub tokens_issued: u64,
+    pub attempted: u64,
+    pub succeeded: u64,
+    pub failed_expired: u64,
+    pub failed_invalid: u64,
+    pub failed_room_gone: u64,
+    pub failed_slot_reclaimed: u64,
+    /// Unexpired tokens as of the last cleanup run
+    pub outstanding_tokens: u64,
+    pub ghost_slots_reclaimed: u64,
+    pub median_time_to_reconnect_ms: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct MessageTypeMetrics {
+    pub direction: MessageDirection,
+    /// The message's `type` tag
+    pub message_type: String,
+    pub count: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ConnectionRttMetrics {
+    pub region_id: String,
+    /// Round trips measured from client heartbeats
+    pub rtt: OperationLatencyMetrics,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ErrorMetrics {
     pub validation_errors: u64,
@@ -295,8 +598,173 @@ pub struct CleanupMetrics {
     pub empty_rooms_cleaned: u64,
     pub inactive_rooms_cleaned: u64,
     pub expired_players_cleaned: u64,
+    /// Seated players removed after `server.stale_player_timeout` without a heartbeat
+    #[serde(default)]
+    pub stale_players_removed: u64,
 }
 
+/// A counter carried across restarts, by its persisted name.
+type PersistedCounter = (&'static str, fn(&ServerMetrics) -> &AtomicU64);
+
+/// Monotonic counters carried across restarts by [`persistence`].
+/// Gauges, latency histograms and delivery-ack samples always start fresh.
+const PERSISTED_COUNTERS: &[PersistedCounter] = &[
+    ("total_connections", |m| &m.total_connections),
+    ("disconnections", |m| &m.disconnections),
+    ("connection_errors", |m| &m.connection_errors),
+    ("websocket_messages_dropped", |m| {
+        &m.websocket_messages_dropped
+    }),
+    ("websocket_messages_received", |m| {
+        &m.websocket_messages_received
+    }),
+    ("websocket_bytes_sent", |m| &m.websocket_bytes_sent),
+    ("disconnects_detected_on_read", |m| {
+        &m.disconnects_detected_on_read
+    }),
+    ("disconnects_detected_on_write", |m| {
+        &m.disconnects_detected_on_write
+    }),
+    ("connections_rejected_ip_limit", |m| {
+        &m.connections_rejected_ip_limit
+    }),
+    ("connections_rejected_rate_limit", |m| {
+        &m.connections_rejected_rate_limit
+    }),
+    ("connections_blocked_allowlist", |m| {
+        &m.connections_blocked_allowlist
+    }),
+    ("connections_blocked_blocklist", |m| {
+        &m.connections_blocked_blocklist
+    }),
+    ("connections_closed_backpressure", |m| {
+        &m.connections_closed_backpressure
+    }),
+    ("websocket_batches_flushed", |m| {
+        &m.websocket_batches_flushed
+    }),
+    ("websocket_batched_messages", |m| {
+        &m.websocket_batched_messages
+    }),
+    ("server_announcements_sent", |m| {
+        &m.server_announcements_sent
+    }),
+    ("rooms_created", |m| &m.rooms_created),
+    ("rooms_joined", |m| &m.rooms_joined),
+    ("room_creation_failures", |m| &m.room_creation_failures),
+    ("room_join_failures", |m| &m.room_join_failures),
+    ("rooms_deleted", |m| &m.rooms_deleted),
+    ("room_cap_lock_acquisitions", |m| {
+        &m.room_cap_lock_acquisitions
+    }),
+    ("room_cap_lock_failures", |m| &m.room_cap_lock_failures),
+    ("room_cap_denials", |m| &m.room_cap_denials),
+    ("rooms_closed_by_admin", |m| &m.rooms_closed_by_admin),
+    ("join_interceptor_denials", |m| &m.join_interceptor_denials),
+    ("join_interceptor_failures", |m| {
+        &m.join_interceptor_failures
+    }),
+    ("webhook_delivery_failed", |m| &m.webhook_delivery_failed),
+    ("invites_created", |m| &m.invites_created),
+    ("invites_redeemed", |m| &m.invites_redeemed),
+    ("invites_expired", |m| &m.invites_expired),
+    ("room_capacity_conflicts", |m| &m.room_capacity_conflicts),
+    ("room_code_collisions", |m| &m.room_code_collisions),
+    ("authority_transfer_conflicts", |m| {
+        &m.authority_transfer_conflicts
+    }),
+    ("retry_attempts", |m| &m.retry_attempts),
+    ("retry_successes", |m| &m.retry_successes),
+    ("cross_instance_messages", |m| &m.cross_instance_messages),
+    ("dedup_cache_hits", |m| &m.dedup_cache_hits),
+    ("dedup_cache_misses", |m| &m.dedup_cache_misses),
+    ("dedup_cache_evictions", |m| &m.dedup_cache_evictions),
+    ("membership_cache_hits", |m| &m.membership_cache_hits),
+    ("membership_cache_misses", |m| &m.membership_cache_misses),
+    ("remote_membership_updates_published", |m| {
+        &m.remote_membership_updates_published
+    }),
+    ("remote_membership_updates_received", |m| {
+        &m.remote_membership_updates_received
+    }),
+    ("remote_membership_known_broadcasts", |m| {
+        &m.remote_membership_known_broadcasts
+    }),
+    ("remote_membership_forced_broadcasts", |m| {
+        &m.remote_membership_forced_broadcasts
+    }),
+    ("remote_membership_skipped_broadcasts", |m| {
+        &m.remote_membership_skipped_broadcasts
+    }),
+    ("query_count", |m| &m.query_count),
+    ("dashboard_cache_refresh_failures", |m| {
+        &m.dashboard_cache_refresh_failures
+    }),
+    ("latency_histogram_clamped_samples", |m| {
+        &m.latency_histogram_clamped_samples
+    }),
+    ("rate_limit_rejections", |m| &m.rate_limit_rejections),
+    ("rate_limit_resets", |m| &m.rate_limit_resets),
+    ("rate_limit_minute_checks", |m| &m.rate_limit_minute_checks),
+    ("rate_limit_hour_checks", |m| &m.rate_limit_hour_checks),
+    ("rate_limit_day_checks", |m| &m.rate_limit_day_checks),
+    ("rate_limit_minute_rejections", |m| {
+        &m.rate_limit_minute_rejections
+    }),
+    ("rate_limit_hour_rejections", |m| {
+        &m.rate_limit_hour_rejections
+    }),
+    ("rate_limit_day_rejections", |m| {
+        &m.rate_limit_day_rejections
+    }),
+    ("rate_limit_cache_purged", |m| &m.rate_limit_cache_purged),
+    ("players_joined", |m| &m.players_joined),
+    ("players_left", |m| &m.players_left),
+    ("authority_transfers", |m| &m.authority_transfers),
+    ("game_data_messages", |m| &m.game_data_messages),
+    ("player_reports", |m| &m.player_reports),
+    ("heartbeat_updates", |m| &m.heartbeat_updates),
+    ("heartbeat_skipped", |m| &m.heartbeat_skipped),
+    ("reconnection_tokens_issued", |m| {
+        &m.reconnection_tokens_issued
+    }),
+    ("reconnection_validations_failed", |m| {
+        &m.reconnection_validations_failed
+    }),
+    ("reconnection_completions", |m| &m.reconnection_completions),
+    ("reconnection_events_buffered", |m| {
+        &m.reconnection_events_buffered
+    }),
+    ("token_replays_detected", |m| &m.token_replays_detected),
+    ("flaps_suppressed", |m| &m.flaps_suppressed),
+    ("distributed_lock_release_failures", |m| {
+        &m.distributed_lock_release_failures
+    }),
+    ("distributed_lock_extend_failures", |m| {
+        &m.distributed_lock_extend_failures
+    }),
+    ("distributed_lock_cleanup_runs", |m| {
+        &m.distributed_lock_cleanup_runs
+    }),
+    ("distributed_lock_cleanup_removed", |m| {
+        &m.distributed_lock_cleanup_removed
+    }),
+    ("validation_errors", |m| &m.validation_errors),
+    ("internal_errors", |m| &m.internal_errors),
+    ("websocket_errors", |m| &m.websocket_errors),
+    ("empty_rooms_cleaned", |m| &m.empty_rooms_cleaned),
+    ("inactive_rooms_cleaned", |m| &m.inactive_rooms_cleaned),
+    ("expired_players_cleaned", |m| &m.expired_players_cleaned),
+    ("stale_players_removed", |m| &m.stale_players_removed),
+    ("relay_client_id_reuse_events", |m| {
+        &m.relay_client_id_reuse_events
+    }),
+    ("relay_client_id_exhaustion_events", |m| {
+        &m.relay_client_id_exhaustion_events
+    }),
+    ("relay_session_timeouts", |m| &m.relay_session_timeouts),
+];
+
 impl Default for ServerMetrics {
     fn default() -> Self {
         Self::new()
@@ -304,6 +772,29 @@ impl Default for ServerMetrics {
 }
 
 impl ServerMetrics {
+    /// Current value of every persisted counter, by name.
+    pub fn counter_values(&self) -> BTreeMap<String, u64> {
+        PERSISTED_COUNTERS
+            .iter()
+            .map(|(name, counter)| ((*name).to_string(), counter(self).load(Ordering::Relaxed)))
+            .collect()
+    }
+
+    /// Add previously persisted values onto the matching counters.
+    ///
+    /// Unknown names are skipped so files from other versions still load.
+    /// Returns how many counters were restored.
+    pub fn restore_counters(&self, values: &BTreeMap<String, u64>) -> usize {
+        PERSISTED_COUNTERS
+            .iter()
+            .filter_map(|(name, counter)| {
+                let value = values.get(*name)?;
+                counter(self).fetch_add(*value, Ordering::Relaxed);
+                Some(())
+            })
+            .count()
+    }
+
     pub fn new() -> Self {
         Self {
             total_connections: AtomicU64::new(0),
@@ -311,6 +802,18 @@ impl ServerMetrics {
             disconnections: AtomicU64::new(0),
             connection_errors: AtomicU64::new(0),
             websocket_messages_dropped: AtomicU64::new(0),
+            websocket_messages_received: AtomicU64::new(0),
+            websocket_bytes_sent: AtomicU64::new(0),
+            disconnects_detected_on_read: AtomicU64::new(0),
+            disconnects_detected_on_write: AtomicU64::new(0),
+            connections_rejected_ip_limit: AtomicU64::new(0),
+            connections_rejected_rate_limit: AtomicU64::new(0),
+            connections_blocked_allowlist: AtomicU64::new(0),
+            connections_blocked_blocklist: AtomicU64::new(0),
+            connections_closed_backpressure: AtomicU64::new(0),
+            server_announcements_sent: AtomicU64::new(0),
+            websocket_batches_flushed: AtomicU64::new(0),
+            websocket_batched_messages: AtomicU64::new(0),
             rooms_created: AtomicU64::new(0),
             rooms_joined: AtomicU64::new(0),
             room_creation_failures: AtomicU64::new(0),
@@ -319,6 +822,13 @@ impl ServerMetrics {
             room_cap_lock_acquisitions: AtomicU64::new(0),
             room_cap_lock_failures: AtomicU64::new(0),
             room_cap_denials: AtomicU64::new(0),
+            rooms_closed_by_admin: AtomicU64::new(0),
+            join_interceptor_denials: AtomicU64::new(0),
+            join_interceptor_failures: AtomicU64::new(0),
+            webhook_delivery_failed: AtomicU64::new(0),
+            invites_created: AtomicU64::new(0),
+            invites_redeemed: AtomicU64::new(0),
+            invites_expired: AtomicU64::new(0),
             room_capacity_conflicts: AtomicU64::new(0),
             room_code_collisions: AtomicU64::new(0),
             authority_transfer_conflicts: AtomicU64::new(0),
@@ -361,6 +871,7 @@ impl ServerMetrics {
             players_left: AtomicU64::new(0),
             authority_transfers: AtomicU64::new(0),
             game_data_messages: AtomicU64::new(0),
+            player_reports: AtomicU64::new(0),
             heartbeat_updates: AtomicU64::new(0),
             heartbeat_skipped: AtomicU64::new(0),
             reconnection_tokens_issued: AtomicU64::new(0),
@@ -368,6 +879,8 @@ impl ServerMetrics {
             reconnection_validations_failed: AtomicU64::new(0),
             reconnection_completions: AtomicU64::new(0),
             reconnection_events_buffered: AtomicU64::new(0),
+            token_replays_detected: AtomicU64::new(0),
+            flaps_suppressed: AtomicU64::new(0),
             distributed_lock_release_failures: AtomicU64::new(0),
             distributed_lock_extend_failures: AtomicU64::new(0),
             distributed_lock_cleanup_runs: AtomicU64::new(0),
@@ -378,9 +891,52 @@ impl ServerMetrics {
             empty_rooms_cleaned: AtomicU64::new(0),
             inactive_rooms_cleaned: AtomicU64::new(0),
             expired_players_cleaned: AtomicU64::new(0),
+            stale_players_removed: AtomicU64::new(0),
             relay_client_id_reuse_events: AtomicU64::new(0),
             relay_client_id_exhaustion_events: AtomicU64::new(0),
             relay_session_timeouts: AtomicU64::new(0),
+            delivery_acks: DashMap::new(),
+            broadcast_queues: DashMap::new(),
+            reconnection_by_game: DashMap::new(),
+            time_to_reconnect: Arc::new(RwLock::new(ResponseTimeTracker::new())),
+            rooms_created_by_relay_type: DashMap::new(),
+            messages_by_type: DashMap::new(),
+            connection_rtt: Arc::new(RwLock::new(ResponseTimeTracker::new())),
+            session_duration: DurationHistogram::new(&DEFAULT_SESSION_DURATION_BUCKETS_SECS),
+            latency_buckets_ms: DEFAULT_LATENCY_BUCKETS_MS.to_vec(),
+            started_at: chrono::Utc::now(),
+            started: Instant::now(),
+        }
+    }
+
+    /// Metrics with custom session duration buckets (upper bounds in seconds).
+    pub fn with_session_duration_buckets(bounds_secs: &[f64]) -> Self {
+        Self {
+            session_duration: DurationHistogram::new(bounds_secs),
+            ..Self::new()
+        }
+    }
+
+    /// Report the latency histograms with custom buckets (upper bounds in milliseconds).
+    pub fn with_latency_buckets(mut self, bounds_ms: &[f64]) -> Self {
+        let mut bounds: Vec<f64> = bounds_ms
+            .iter()
+            .copied()
+            .filter(|bound| bound.is_finite() && *bound > 0.0)
+            .collect();
+        bounds.sort_by(f64::total_cmp);
+        bounds.dedup();
+        self.latency_buckets_ms = bounds;
+        self
+    }
+
+    /// Version, commit, start time and uptime of this process.
+    pub fn build_info(&self) -> BuildInfo {
+        BuildInfo {
+            version: BUILD_VERSION.to_string(),
+            commit: BUILD_COMMIT.to_string(),
+            started_at: self.started_at,
+            uptime_seconds: self.started.elapsed().as_secs_f64(),
         }
     }
 
@@ -404,6 +960,11 @@ impl ServerMetrics {
         self.disconnections.fetch_add(1, Ordering::Relaxed);
     }
 
+    /// Record how long a WebSocket session lasted, however it ended.
+    pub fn record_session_duration(&self, duration: Duration) {
+        self.session_duration.observe(duration);
+    }
+
     #[allow(dead_code)]
     pub fn increment_connection_errors(&self) {
         self.connection_errors.fetch_add(1, Ordering::Relaxed);
@@ -414,11 +975,122 @@ impl ServerMetrics {
             .fetch_add(1, Ordering::Relaxed);
     }
 
+    pub fn increment_websocket_messages_received(&self) {
+        self.websocket_messages_received
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn increment_disconnects_detected_on_read(&self) {
+        self.disconnects_detected_on_read
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn increment_disconnects_detected_on_write(&self) {
+        self.disconnects_detected_on_write
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn increment_connections_rejected_ip_limit(&self) {
+        self.connections_rejected_ip_limit
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn increment_connections_rejected_rate_limit(&self) {
+        self.connections_rejected_rate_limit
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn increment_connections_blocked_allowlist(&self) {
+        self.connections_blocked_allowlist
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn increment_connections_blocked_blocklist(&self) {
+        self.connections_blocked_blocklist
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn increment_connections_closed_backpressure(&self) {
+        self.connections_closed_backpressure
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record `recipients` players sent a `ServerAnnouncement`.
+    pub fn record_server_announcements_sent(&self, recipients: u64) {
+        self.server_announcements_sent
+            .fetch_add(recipients, Ordering::Relaxed);
+    }
+
+    /// Record one flushed message batch of `messages` messages.
+    pub fn record_websocket_batch(&self, messages: usize) {
+        self.websocket_batches_flushed
+            .fetch_add(1, Ordering::Relaxed);
+        self.websocket_batched_messages
+            .fetch_add(messages as u64, Ordering::Relaxed);
+    }
+
+    /// Count a received client message of `message_type`.
+    pub fn record_message_received(&self, message_type: &'static str) {
+        self.record_messages(MessageDirection::Inbound, message_type, 1);
+    }
+
+    /// Count `count` server messages of `message_type` sent to clients.
+    pub fn record_messages_sent(&self, message_type: &'static str, count: u64) {
+        self.record_messages(MessageDirection::Outbound, message_type, count);
+    }
+
+    fn record_messages(&self, direction: MessageDirection, message_type: &'static str, count: u64) {
+        let key = (direction, message_type);
+        if let Some(counter) = self.messages_by_type.get(&key) {
+            counter.fetch_add(count, Ordering::Relaxed);
+            return;
+        }
+        self.messages_by_type
+            .entry(key)
+            .or_default()
+            .fetch_add(count, Ordering::Relaxed);
+    }
+
+    fn messages_by_type_snapshot(&self) -> Vec<MessageTypeMetrics> {
+        let mut messages: Vec<MessageTypeMetrics> = self
+            .messages_by_type
+            .iter()
+            .map(|entry| {
+                let (direction, message_type) = *entry.key();
+                MessageTypeMetrics {
+                    direction,
+                    message_type: message_type.to_string(),
+                    count: entry.value().load(Ordering::Relaxed),
+                }
+            })
+            .collect();
+        messages
+            .sort_by(|a, b| (a.direction, &a.message_type).cmp(&(b.direction, &b.message_type)));
+        messages
+    }
+
+    /// Record the payload size of one outbound frame.
+    pub fn record_websocket_bytes_sent(&self, bytes: usize) {
+        self.websocket_bytes_sent
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
     // Room operation metrics
     pub fn increment_rooms_created(&self) {
         self.rooms_created.fetch_add(1, Ordering::Relaxed);
     }
 
+    pub fn increment_rooms_created_for_relay_type(&self, relay_type: &str) {
+        if let Some(counter) = self.rooms_created_by_relay_type.get(relay_type) {
+            counter.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        self.rooms_created_by_relay_type
+            .entry(relay_type.to_string())
+            .or_default()
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
     pub fn increment_rooms_joined(&self) {
         self.rooms_joined.fetch_add(1, Ordering::Relaxed);
     }
@@ -451,6 +1123,36 @@ impl ServerMetrics {
         self.room_cap_denials.fetch_add(1, Ordering::Relaxed);
     }
 
+    pub fn increment_rooms_closed_by_admin(&self) {
+        self.rooms_closed_by_admin.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn increment_join_interceptor_denials(&self) {
+        self.join_interceptor_denials
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn increment_join_interceptor_failures(&self) {
+        self.join_interceptor_failures
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn increment_webhook_delivery_failed(&self) {
+        self.webhook_delivery_failed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn increment_invites_created(&self) {
+        self.invites_created.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn increment_invites_redeemed(&self) {
+        self.invites_redeemed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn add_invites_expired(&self, count: u64) {
+        self.invites_expired.fetch_add(count, Ordering::Relaxed);
+    }
+
     // Race condition metrics
     #[allow(dead_code)]
     pub fn increment_room_capacity_conflicts(&self) {
@@ -668,6 +1370,10 @@ impl ServerMetrics {
         self.game_data_messages.fetch_add(1, Ordering::Relaxed);
     }
 
+    pub fn increment_player_reports(&self) {
+        self.player_reports.fetch_add(1, Ordering::Relaxed);
+    }
+
     // Heartbeat throttling metrics
     pub fn increment_heartbeat_updates(&self) {
         self.heartbeat_updates.fetch_add(1, Ordering::Relaxed);
@@ -726,6 +1432,14 @@ impl ServerMetrics {
         }
     }
 
+    pub fn increment_token_replay_detected(&self) {
+        self.token_replays_detected.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn increment_flaps_suppressed(&self) {
+        self.flaps_suppressed.fetch_add(1, Ordering::Relaxed);
+    }
+
     // Distributed lock metrics
     pub fn increment_distributed_lock_release_failures(&self) {
         self.distributed_lock_release_failures
@@ -779,6 +1493,11 @@ impl ServerMetrics {
             .fetch_add(count, Ordering::Relaxed);
     }
 
+    pub fn add_stale_players_removed(&self, count: u64) {
+        self.stale_players_removed
+            .fetch_add(count, Ordering::Relaxed);
+    }
+
     // Relay health metrics
     pub fn increment_relay_client_id_reuse(&self) {
         self.relay_client_id_reuse_events
@@ -798,14 +1517,300 @@ impl ServerMetrics {
             .fetch_add(count, Ordering::Relaxed);
     }
 
+    // Delivery acknowledgement sampling
+    fn delivery_ack_counters(
+        &self,
+        game_name: &str,
+        region_id: &str,
+    ) -> dashmap::mapref::one::Ref<'_, (String, String), DeliveryAckCounters> {
+        let key = (game_name.to_string(), region_id.to_string());
+        if let Some(counters) = self.delivery_acks.get(&key) {
+            return counters;
+        }
+        self.delivery_acks.entry(key).or_default().downgrade()
+    }
+
+    pub fn record_delivery_ack_expected(&self, game_name: &str, region_id: &str, count: u64) {
+        self.delivery_ack_counters(game_name, region_id)
+            .expected
+            .fetch_add(count, Ordering::Relaxed);
+    }
+
+    pub fn record_delivery_ack(&self, game_name: &str, region_id: &str, latency: Duration) {
+        let latency_us = u64::try_from(latency.as_micros()).unwrap_or(u64::MAX);
+        let counters = self.delivery_ack_counters(game_name, region_id);
+        counters.acked.fetch_add(1, Ordering::Relaxed);
+        counters
+            .latency_total_us
+            .fetch_add(latency_us, Ordering::Relaxed);
+        counters
+            .latency_max_us
+            .fetch_max(latency_us, Ordering::Relaxed);
+    }
+
+    pub fn record_delivery_ack_failures(&self, game_name: &str, region_id: &str, count: u64) {
+        if count == 0 {
+            return;
+        }
+        self.delivery_ack_counters(game_name, region_id)
+            .failed
+            .fetch_add(count, Ordering::Relaxed);
+    }
+
+    fn delivery_ack_snapshot(&self) -> Vec<DeliveryAckMetrics> {
+        let mut groups: Vec<DeliveryAckMetrics> = self
+            .delivery_acks
+            .iter()
+            .map(|entry| {
+                let (game_name, region_id) = entry.key().clone();
+                let counters = entry.value();
+                let acked = counters.acked.load(Ordering::Relaxed);
+                let failed = counters.failed.load(Ordering::Relaxed);
+                let resolved = acked + failed;
+                let ack_rate = if resolved > 0 {
+                    (acked as f64) / (resolved as f64)
+                } else {
+                    1.0
+                };
+                let (average_latency_ms, max_latency_ms) = if acked > 0 {
+                    let total_us = counters.latency_total_us.load(Ordering::Relaxed);
+                    let max_us = counters.latency_max_us.load(Ordering::Relaxed);
+                    (
+                        Some((total_us as f64) / (acked as f64) / 1000.0),
+                        Some((max_us as f64) / 1000.0),
+                    )
+                } else {
+                    (None, None)
+                };
+                DeliveryAckMetrics {
+                    game_name,
+                    region_id,
+                    expected: counters.expected.load(Ordering::Relaxed),
+                    acked,
+                    failed,
+                    ack_rate,
+                    average_latency_ms,
+                    max_latency_ms,
+                }
+            })
+            .collect();
+        groups.sort_by(|a, b| {
+            (a.game_name.as_str(), a.region_id.as_str())
+                .cmp(&(b.game_name.as_str(), b.region_id.as_str()))
+        });
+        groups
+    }
+
+    // Game data broadcast dispatch
+    fn broadcast_queue_counters(
+        &self,
+        game_name: &str,
+    ) -> dashmap::mapref::one::Ref<'_, String, BroadcastQueueCounters> {
+        if let Some(counters) = self.broadcast_queues.get(game_name) {
+            return counters;
+        }
+        self.broadcast_queues
+            .entry(game_name.to_string())
+            .or_default()
+            .downgrade()
+    }
+
+    pub fn mark_broadcast_isolated(&self, game_name: &str) {
+        self.broadcast_queue_counters(game_name)
+            .isolated
+            .store(true, Ordering::Relaxed);
+    }
+
+    pub fn increment_broadcast_queued(&self, game_name: &str) {
+        self.broadcast_queue_counters(game_name)
+            .queued
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn decrement_broadcast_queued(&self, game_name: &str) {
+        let _ = self
+            .broadcast_queue_counters(game_name)
+            .queued
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |queued| {
+                queued.checked_sub(1)
+            });
+    }
+
+    pub fn record_broadcast_dispatched(&self, game_name: &str, wait: Duration) {
+        let wait_us = u64::try_from(wait.as_micros()).unwrap_or(u64::MAX);
+        let counters = self.broadcast_queue_counters(game_name);
+        counters.dispatched.fetch_add(1, Ordering::Relaxed);
+        counters.wait_total_us.fetch_add(wait_us, Ordering::Relaxed);
+        counters.wait_max_us.fetch_max(wait_us, Ordering::Relaxed);
+    }
+
+    fn broadcast_queue_snapshot(&self) -> Vec<BroadcastQueueMetrics> {
+        let mut games: Vec<BroadcastQueueMetrics> = self
+            .broadcast_queues
+            .iter()
+            .map(|entry| {
+                let counters = entry.value();
+                let dispatched = counters.dispatched.load(Ordering::Relaxed);
+                let (average_wait_ms, max_wait_ms) = if dispatched > 0 {
+                    let total_us = counters.wait_total_us.load(Ordering::Relaxed);
+                    let max_us = counters.wait_max_us.load(Ordering::Relaxed);
+                    (
+                        Some((total_us as f64) / (dispatched as f64) / 1000.0),
+                        Some((max_us as f64) / 1000.0),
+                    )
+                } else {
+                    (None, None)
+                };
+                BroadcastQueueMetrics {
+                    game_name: entry.key().clone(),
+                    isolated: counters.isolated.load(Ordering::Relaxed),
+                    queue_depth: counters.queued.load(Ordering::Relaxed),
+                    dispatched,
+                    average_wait_ms,
+                    max_wait_ms,
+                }
+            })
+            .collect();
+        games.sort_by(|a, b| a.game_name.cmp(&b.game_name));
+        games
+    }
+
+    // Reconnection lifecycle by game
+    fn reconnection_game_counters(
+        &self,
+        game_name: &str,
+    ) -> dashmap::mapref::one::Ref<'_, String, ReconnectionGameCounters> {
+        if let Some(counters) = self.reconnection_by_game.get(game_name) {
+            return counters;
+        }
+        self.reconnection_by_game
+            .entry(game_name.to_string())
+            .or_default()
+            .downgrade()
+    }
+
+    pub fn record_reconnection_token_issued(&self, game_name: &str) {
+        self.increment_reconnection_tokens_issued();
+        self.reconnection_game_counters(game_name)
+            .tokens_issued
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_reconnection_attempt(&self, game_name: &str) {
+        self.reconnection_game_counters(game_name)
+            .attempted
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub async fn record_reconnection_succeeded(&self, game_name: &str, elapsed: Duration) {
+        self.reconnection_game_counters(game_name)
+            .succeeded
+            .fetch_add(1, Ordering::Relaxed);
+        let mut tracker = self.time_to_reconnect.write().await;
+        let clamped = tracker.add_sample(game_name, elapsed);
+        drop(tracker);
+        if clamped {
+            self.increment_latency_histogram_clamps();
+        }
+    }
+
+    pub fn record_reconnection_failed(&self, game_name: &str, reason: ReconnectFailureReason) {
+        let counters = self.reconnection_game_counters(game_name);
+        let counter = match reason {
+            ReconnectFailureReason::Expired => &counters.failed_expired,
+            ReconnectFailureReason::Invalid => &counters.failed_invalid,
+            ReconnectFailureReason::RoomGone => &counters.failed_room_gone,
+            ReconnectFailureReason::SlotReclaimed => &counters.failed_slot_reclaimed,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Replace the outstanding token gauges; games missing from `outstanding` drop to zero.
+    pub fn set_reconnection_outstanding_tokens(&self, outstanding: &HashMap<String, u64>) {
+        for entry in self.reconnection_by_game.iter() {
+            let value = outstanding.get(entry.key()).copied().unwrap_or(0);
+            entry.outstanding_tokens.store(value, Ordering::Relaxed);
+        }
+        for (game_name, value) in outstanding {
+            self.reconnection_game_counters(game_name)
+                .outstanding_tokens
+                .store(*value, Ordering::Relaxed);
+        }
+    }
+
+    pub fn increment_ghost_slots_reclaimed(&self, game_name: &str) {
+        self.reconnection_game_counters(game_name)
+            .ghost_slots_reclaimed
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    async fn reconnection_by_game_snapshot(&self) -> Vec<ReconnectionGameMetrics> {
+        let tracker = self.time_to_reconnect.read().await;
+        let mut games: Vec<ReconnectionGameMetrics> = self
+            .reconnection_by_game
+            .iter()
+            .map(|entry| {
+                let counters = entry.value();
+                ReconnectionGameMetrics {
+                    game_name: entry.key().clone(),
+                    tokens_issued: counters.tokens_issued.load(Ordering::Relaxed),
+                    attempted: counters.attempted.load(Ordering::Relaxed),
+                    succeeded: counters.succeeded.load(Ordering::Relaxed),
+                    failed_expired: counters.failed_expired.load(Ordering::Relaxed),
+                    failed_invalid: counters.failed_invalid.load(Ordering::Relaxed),
+                    failed_room_gone: counters.failed_room_gone.load(Ordering::Relaxed),
+                    failed_slot_reclaimed: counters.failed_slot_reclaimed.load(Ordering::Relaxed),
+                    outstanding_tokens: counters.outstanding_tokens.load(Ordering::Relaxed),
+                    ghost_slots_reclaimed: counters.ghost_slots_reclaimed.load(Ordering::Relaxed),
+                    median_time_to_reconnect_ms: tracker
+                        .get_latency_metrics(entry.key())
+                        .and_then(|times| times.p50_ms),
+                }
+            })
+            .collect();
+        drop(tracker);
+        games.sort_by(|a, b| a.game_name.cmp(&b.game_name));
+        games
+    }
+
+    // Heartbeat round-trip times
+    pub async fn record_connection_rtt(&self, region_id: &str, rtt: Duration) {
+        let mut tracker = self.connection_rtt.write().await;
+        let clamped = tracker.add_sample(region_id, rtt);
+        drop(tracker);
+        if clamped {
+            self.increment_latency_histogram_clamps();
+        }
+    }
+
+    async fn connection_rtt_snapshot(&self) -> Vec<ConnectionRttMetrics> {
+        let tracker = self.connection_rtt.read().await;
+        let mut regions: Vec<ConnectionRttMetrics> = tracker
+            .operations
+            .keys()
+            .filter_map(|region_id| {
+                Some(ConnectionRttMetrics {
+                    region_id: region_id.clone(),
+                    rtt: tracker.get_latency_metrics(region_id)?,
+                })
+            })
+            .collect();
+        regions.sort_by(|a, b| a.region_id.cmp(&b.region_id));
+        regions
+    }
+
     // Snapshot generation
     pub async fn snapshot(&self) -> MetricsSnapshot {
         let tracker = self.average_response_times.read().await;
-        let room_creation_latency = tracker
-            .get_latency_metrics("room_creation")
-            .unwrap_or_default();
-        let room_join_latency = tracker.get_latency_metrics("room_join").unwrap_or_default();
-        let query_latency = tracker.get_latency_metrics("query").unwrap_or_default();
+        let room_creation_latency =
+            tracker.get_latency_histogram("room_creation", &self.latency_buckets_ms);
+        let room_join_latency =
+            tracker.get_latency_histogram("room_join", &self.latency_buckets_ms);
+        let query_latency = tracker.get_latency_histogram("query", &self.latency_buckets_ms);
+        let join_interceptor_latency =
+            tracker.get_latency_histogram("join_interceptor", &self.latency_buckets_ms);
+        let latency_ping_processing =
+            tracker.get_latency_histogram("latency_ping", &self.latency_buckets_ms);
 
         let retry_attempts = self.retry_attempts.load(Ordering::Relaxed);
         let retry_successes = self.retry_successes.load(Ordering::Relaxed);
@@ -819,6 +1824,8 @@ impl ServerMetrics {
         let internal_errors = self.internal_errors.load(Ordering::Relaxed);
         let websocket_errors = self.websocket_errors.load(Ordering::Relaxed);
         let total_errors = validation_errors + internal_errors + websocket_errors;
+        let batches_flushed = self.websocket_batches_flushed.load(Ordering::Relaxed);
+        let batched_messages = self.websocket_batched_messages.load(Ordering::Relaxed);
 
         MetricsSnapshot {
             timestamp: chrono::Utc::now(),
@@ -828,9 +1835,45 @@ impl ServerMetrics {
                 disconnections: self.disconnections.load(Ordering::Relaxed),
                 connection_errors: self.connection_errors.load(Ordering::Relaxed),
                 websocket_messages_dropped: self.websocket_messages_dropped.load(Ordering::Relaxed),
+                websocket_messages_received: self
+                    .websocket_messages_received
+                    .load(Ordering::Relaxed),
+                websocket_bytes_sent: self.websocket_bytes_sent.load(Ordering::Relaxed),
+                disconnects_detected_on_read: self
+                    .disconnects_detected_on_read
+                    .load(Ordering::Relaxed),
+                disconnects_detected_on_write: self
+                    .disconnects_detected_on_write
+                    .load(Ordering::Relaxed),
+                connections_rejected_ip_limit: self
+                    .connections_rejected_ip_limit
+                    .load(Ordering::Relaxed),
+                connections_rejected_rate_limit: self
+                    .connections_rejected_rate_limit
+                    .load(Ordering::Relaxed),
+                connections_blocked_allowlist: self
+                    .connections_blocked_allowlist
+                    .load(Ordering::Relaxed),
+                connections_blocked_blocklist: self
+                    .connections_blocked_blocklist
+                    .load(Ordering::Relaxed),
+                connections_closed_backpressure: self
+                    .connections_closed_backpressure
+                    .load(Ordering::Relaxed),
+                websocket_batches_flushed: batches_flushed,
+                websocket_batched_messages: batched_messages,
+                server_announcements_sent: self.server_announcements_sent.load(Ordering::Relaxed),
+                websocket_average_batch_size: (batches_flushed > 0)
+                    .then(|| batched_messages as f64 / batches_flushed as f64),
+                session_duration: self.session_duration.snapshot(),
             },
             rooms: RoomMetrics {
                 rooms_created: self.rooms_created.load(Ordering::Relaxed),
+                rooms_created_by_relay_type: self
+                    .rooms_created_by_relay_type
+                    .iter()
+                    .map(|entry| (entry.key().clone(), entry.value().load(Ordering::Relaxed)))
+                    .collect(),
                 rooms_joined: self.rooms_joined.load(Ordering::Relaxed),
                 room_creation_failures: self.room_creation_failures.load(Ordering::Relaxed),
                 room_join_failures: self.room_join_failures.load(Ordering::Relaxed),
@@ -838,6 +1881,13 @@ impl ServerMetrics {
                 room_cap_lock_acquisitions: self.room_cap_lock_acquisitions.load(Ordering::Relaxed),
                 room_cap_lock_failures: self.room_cap_lock_failures.load(Ordering::Relaxed),
                 room_cap_denials: self.room_cap_denials.load(Ordering::Relaxed),
+                rooms_closed_by_admin: self.rooms_closed_by_admin.load(Ordering::Relaxed),
+                join_interceptor_denials: self.join_interceptor_denials.load(Ordering::Relaxed),
+                join_interceptor_failures: self.join_interceptor_failures.load(Ordering::Relaxed),
+                webhook_delivery_failed: self.webhook_delivery_failed.load(Ordering::Relaxed),
+                invites_created: self.invites_created.load(Ordering::Relaxed),
+                invites_redeemed: self.invites_redeemed.load(Ordering::Relaxed),
+                invites_expired: self.invites_expired.load(Ordering::Relaxed),
             },
             race_conditions: RaceConditionMetrics {
                 room_capacity_conflicts: self.room_capacity_conflicts.load(Ordering::Relaxed),
@@ -881,6 +1931,8 @@ impl ServerMetrics {
                 room_creation_latency,
                 room_join_latency,
                 query_latency,
+                join_interceptor_latency,
+                latency_ping_processing,
                 latency_histogram_clamped_samples: self
                     .latency_histogram_clamped_samples
                     .load(Ordering::Relaxed),
@@ -918,6 +1970,7 @@ impl ServerMetrics {
                 players_left: self.players_left.load(Ordering::Relaxed),
                 authority_transfers: self.authority_transfers.load(Ordering::Relaxed),
                 game_data_messages: self.game_data_messages.load(Ordering::Relaxed),
+                player_reports: self.player_reports.load(Ordering::Relaxed),
                 heartbeat_updates: self.heartbeat_updates.load(Ordering::Relaxed),
                 heartbeat_skipped: self.heartbeat_skipped.load(Ordering::Relaxed),
             },
@@ -931,6 +1984,7 @@ impl ServerMetrics {
                 empty_rooms_cleaned: self.empty_rooms_cleaned.load(Ordering::Relaxed),
                 inactive_rooms_cleaned: self.inactive_rooms_cleaned.load(Ordering::Relaxed),
                 expired_players_cleaned: self.expired_players_cleaned.load(Ordering::Relaxed),
+                stale_players_removed: self.stale_players_removed.load(Ordering::Relaxed),
             },
             reconnection: ReconnectionMetrics {
                 tokens_issued: self.reconnection_tokens_issued.load(Ordering::Relaxed),
@@ -938,6 +1992,8 @@ impl ServerMetrics {
                 validations_failed: self.reconnection_validations_failed.load(Ordering::Relaxed),
                 completions: self.reconnection_completions.load(Ordering::Relaxed),
                 events_buffered: self.reconnection_events_buffered.load(Ordering::Relaxed),
+                token_replays_detected: self.token_replays_detected.load(Ordering::Relaxed),
+                flaps_suppressed: self.flaps_suppressed.load(Ordering::Relaxed),
             },
             distributed_lock: DistributedLockMetrics {
                 release_failures: self
@@ -958,6 +2014,11 @@ impl ServerMetrics {
                     .load(Ordering::Relaxed),
                 session_timeouts: self.relay_session_timeouts.load(Ordering::Relaxed),
             },
+            delivery_acks: self.delivery_ack_snapshot(),
+            broadcast_queues: self.broadcast_queue_snapshot(),
+            connection_rtt: self.connection_rtt_snapshot().await,
+            reconnection_by_game: self.reconnection_by_game_snapshot().await,
+            messages_by_type: self.messages_by_type_snapshot(),
         }
     }
 
@@ -1082,6 +2143,28 @@ impl ResponseTimeTracker {
         let histogram = self.operations.get(operation)?;
         histogram.metrics()
     }
+
+    /// Latency metrics plus cumulative counts for the given bucket bounds (ms).
+    ///
+    /// Operations without samples report zero in every bucket.
+    pub fn get_latency_histogram(
+        &self,
+        operation: &str,
+        bounds_ms: &[f64],
+    ) -> OperationLatencyMetrics {
+        let histogram = self.operations.get(operation);
+        let mut metrics = histogram
+            .and_then(OperationLatencyHistogram::metrics)
+            .unwrap_or_default();
+        metrics.buckets = bounds_ms
+            .iter()
+            .map(|bound| {
+                let count = histogram.map_or(0, |h| h.count_at_or_below(*bound));
+                (*bound, count)
+            })
+            .collect();
+        metrics
+    }
 }
 
 impl OperationLatencyHistogram {
@@ -1157,9 +2240,17 @@ impl OperationLatencyHistogram {
             min_ms: Some(histogram.min() as f64 / MICROS_PER_MS),
             max_ms: Some(histogram.max() as f64 / MICROS_PER_MS),
             sample_count: histogram.len(),
+            buckets: Vec::new(),
         })
     }
 
+    fn count_at_or_below(&self, bound_ms: f64) -> u64 {
+        let bound_micros = (bound_ms * MICROS_PER_MS) as u64;
+        self.histogram
+            .as_ref()
+            .map_or(0, |h| h.count_between(0, bound_micros))
+    }
+
     fn percentile(&self, percentile: f64) -> f64 {
         self.histogram
             .as_ref()
@@ -1278,6 +2369,33 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_messages_are_counted_by_direction_and_type() {
+        let metrics = ServerMetrics::new();
+        metrics.record_messages_sent("PlayerJoined", 2);
+        metrics.record_message_received("JoinRoom");
+        metrics.record_messages_sent("PlayerJoined", 1);
+        metrics.record_message_received("GameData");
+        metrics.record_messages_sent("GameData", 4);
+
+        let counted: Vec<(MessageDirection, String, u64)> = metrics
+            .snapshot()
+            .await
+            .messages_by_type
+            .into_iter()
+            .map(|m| (m.direction, m.message_type, m.count))
+            .collect();
+        assert_eq!(
+            counted,
+            vec![
+                (MessageDirection::Inbound, "GameData".to_string(), 1),
+                (MessageDirection::Inbound, "JoinRoom".to_string(), 1),
+                (MessageDirection::Outbound, "GameData".to_string(), 4),
+                (MessageDirection::Outbound, "PlayerJoined".to_string(), 3),
+            ]
+        );
+    }
+
     /// E21: Decrement reconnection_sessions_active from 0 stays at 0.
     #[tokio::test]
     async fn test_decrement_reconnection_sessions_no_underflow() {
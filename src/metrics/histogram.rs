@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Fixed-bucket histogram for durations, exported in Prometheus histogram form.
+///
+/// Unlike the HDR latency histograms this is lock-free and cheap enough to
+/// update on every disconnect.
+#[derive(Debug)]
+pub struct DurationHistogram {
+    /// Upper bounds in seconds, strictly increasing
+    bounds: Vec<f64>,
+    /// Per-bucket (non-cumulative) counts; the last slot is the `+Inf` bucket
+    counts: Vec<AtomicU64>,
+    sum_micros: AtomicU64,
+}
+
+/// Point-in-time view of a [`DurationHistogram`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct DurationHistogramSnapshot {
+    /// `(upper bound in seconds, cumulative count)` for each finite bucket
+    pub buckets: Vec<(f64, u64)>,
+    pub count: u64,
+    pub sum_seconds: f64,
+}
+
+impl DurationHistogram {
+    /// Build a histogram with the given bucket upper bounds in seconds.
+    ///
+    /// Non-finite and non-positive bounds are dropped, and the rest are
+    /// sorted and deduplicated.
+    pub fn new(bounds_secs: &[f64]) -> Self {
+        let mut bounds: Vec<f64> = bounds_secs
+            .iter()
+            .copied()
+            .filter(|bound| bound.is_finite() && *bound > 0.0)
+            .collect();
+        bounds.sort_by(f64::total_cmp);
+        bounds.dedup();
+        let counts = (0..=bounds.len()).map(|_| AtomicU64::new(0)).collect();
+        Self {
+            bounds,
+            counts,
+            sum_micros: AtomicU64::new(0),
+        }
+    }
+
+    pub fn observe(&self, duration: Duration) {
+        let secs = duration.as_secs_f64();
+        let index = self.bounds.partition_point(|bound| *bound < secs);
+        self.counts[index].fetch_add(1, Ordering::Relaxed);
+        self.sum_micros.fetch_add(
+            u64::try_from(duration.as_micros()).unwrap_or(u64::MAX),
+            Ordering::Relaxed,
+        );
+    }
+
+    pub fn snapshot(&self) -> DurationHistogramSnapshot {
+        let mut cumulative = 0;
+        let buckets = self
+            .bounds
+            .iter()
+            .zip(&self.counts)
+            .map(|(bound, count)| {
+                cumulative += count.load(Ordering::Relaxed);
+                (*bound, cumulative)
+            })
+            .collect();
+        let overflow = self.counts.last().map_or(0, |c| c.load(Ordering::Relaxed));
+        DurationHistogramSnapshot {
+            buckets,
+            count: cumulative + overflow,
+            sum_seconds: self.sum_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn observations_land_in_cumulative_buckets() {
+        let histogram = DurationHistogram::new(&[60.0, 1.0, 10.0, 10.0, -5.0, f64::NAN]);
+        histogram.observe(Duration::from_millis(500));
+        histogram.observe(Duration::from_secs(1));
+        histogram.observe(Duration::from_secs(30));
+        histogram.observe(Duration::from_secs(3600));
+
+        let snapshot = histogram.snapshot();
+        assert_eq!(snapshot.buckets, vec![(1.0, 2), (10.0, 2), (60.0, 3)]);
+        assert_eq!(snapshot.count, 4);
+        assert!((snapshot.sum_seconds - 3631.5).abs() < 1e-9);
+    }
+}
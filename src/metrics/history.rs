@@ -0,0 +1,278 @@
+//! Bounded in-memory history of a few headline series for dashboard sparklines.
+//!
+//! Samples are recorded by the cleanup task, so no task runs per series. Each
+//! series keeps at most `capacity` points and drops its oldest point first,
+//! which bounds memory at `capacity` points per configured series.
+
+use std::collections::VecDeque;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::config::DashboardHistoryField;
+
+/// Most samples kept per series, whatever `metrics.history_capacity` says.
+pub const METRICS_HISTORY_MAX_CAPACITY: usize = 720;
+
+/// Values read from the server when a sample is taken.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HistoryReading {
+    pub active_rooms: u64,
+    pub active_connections: u64,
+    /// Monotonic; turned into a per-second rate between samples
+    pub messages_received: u64,
+    /// Monotonic; turned into a per-second rate between samples
+    pub reconnection_completions: u64,
+}
+
+/// One point of a history series.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct HistoryPoint {
+    pub timestamp: DateTime<Utc>,
+    pub value: f64,
+}
+
+/// Ring buffers for the series named in `metrics.history_series`.
+#[derive(Debug)]
+pub struct MetricsHistory {
+    series: Vec<DashboardHistoryField>,
+    sample_interval: Duration,
+    capacity: usize,
+    state: Mutex<HistoryState>,
+}
+
+#[derive(Debug)]
+struct HistoryState {
+    previous: Option<(Instant, HistoryReading)>,
+    /// One buffer per entry of `series`, in the same order
+    points: Vec<VecDeque<HistoryPoint>>,
+}
+
+impl MetricsHistory {
+    /// Fields that are not single-number series and repeated fields are ignored.
+    pub fn new(
+        series: &[DashboardHistoryField],
+        sample_interval: Duration,
+        capacity: usize,
+    ) -> Self {
+        let mut selected: Vec<DashboardHistoryField> = Vec::new();
+        for field in series {
+            if DashboardHistoryField::HISTORY_SERIES.contains(field) && !selected.contains(field) {
+                selected.push(*field);
+            }
+        }
+        let capacity = capacity.clamp(1, METRICS_HISTORY_MAX_CAPACITY);
+        let points = selected
+            .iter()
+            .map(|_| VecDeque::with_capacity(capacity))
+            .collect();
+
+        Self {
+            series: selected,
+            sample_interval,
+            capacity,
+            state: Mutex::new(HistoryState {
+                previous: None,
+                points,
+            }),
+        }
+    }
+
+    /// Series being recorded, in configuration order.
+    pub fn series(&self) -> &[DashboardHistoryField] {
+        &self.series
+    }
+
+    pub fn tracks(&self, field: DashboardHistoryField) -> bool {
+        self.series.contains(&field)
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn sample_interval(&self) -> Duration {
+        self.sample_interval
+    }
+
+    /// Whether `sample_interval` has passed since the last sample.
+    pub fn sample_due(&self, now: Instant) -> bool {
+        if self.series.is_empty() {
+            return false;
+        }
+        let state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state
+            .previous
+            .is_none_or(|(at, _)| now.saturating_duration_since(at) >= self.sample_interval)
+    }
+
+    /// Append a sample. Rate series get their first point on the second sample.
+    pub fn record(&self, now: Instant, timestamp: DateTime<Utc>, reading: HistoryReading) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let previous = state.previous.replace((now, reading));
+
+        for (field, points) in self.series.iter().zip(state.points.iter_mut()) {
+            let Some(value) = series_value(*field, reading, previous, now) else {
+                continue;
+            };
+            if points.len() == self.capacity {
+                points.pop_front();
+            }
+            points.push_back(HistoryPoint { timestamp, value });
+        }
+    }
+
+    /// Points of `field` taken at or after `since`, oldest first, or `None`
+    /// if the series is not recorded.
+    pub fn points(
+        &self,
+        field: DashboardHistoryField,
+        since: Option<DateTime<Utc>>,
+    ) -> Option<Vec<HistoryPoint>> {
+        let index = self.series.iter().position(|tracked| *tracked == field)?;
+        let state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        Some(
+            state.points[index]
+                .iter()
+                .filter(|point| since.is_none_or(|since| point.timestamp >= since))
+                .copied()
+                .collect(),
+        )
+    }
+}
+
+fn series_value(
+    field: DashboardHistoryField,
+    reading: HistoryReading,
+    previous: Option<(Instant, HistoryReading)>,
+    now: Instant,
+) -> Option<f64> {
+    let rate = |counter: fn(&HistoryReading) -> u64| {
+        let (at, before) = previous?;
+        let elapsed = now.saturating_duration_since(at).as_secs_f64();
+        if elapsed <= 0.0 {
+            return None;
+        }
+        Some(counter(&reading).saturating_sub(counter(&before)) as f64 / elapsed)
+    };
+
+    match field {
+        DashboardHistoryField::ActiveRooms => Some(reading.active_rooms as f64),
+        DashboardHistoryField::ActiveConnections => Some(reading.active_connections as f64),
+        DashboardHistoryField::MessagesPerSec => rate(|r| r.messages_received),
+        DashboardHistoryField::ReconnectRate => rate(|r| r.reconnection_completions),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reading(active_rooms: u64, messages_received: u64) -> HistoryReading {
+        HistoryReading {
+            active_rooms,
+            messages_received,
+            ..HistoryReading::default()
+        }
+    }
+
+    #[test]
+    fn series_are_capped_at_capacity() {
+        let history = MetricsHistory::new(
+            &[DashboardHistoryField::ActiveRooms],
+            Duration::from_secs(1),
+            3,
+        );
+        let start = Instant::now();
+        let t0 = Utc::now();
+        for i in 0..5u64 {
+            history.record(
+                start + Duration::from_secs(i),
+                t0 + chrono::Duration::seconds(i as i64),
+                reading(i, 0),
+            );
+        }
+
+        let values: Vec<f64> = history
+            .points(DashboardHistoryField::ActiveRooms, None)
+            .expect("active rooms are tracked")
+            .iter()
+            .map(|point| point.value)
+            .collect();
+        assert_eq!(values, vec![2.0, 3.0, 4.0]);
+    }
+
+    #[test]
+    fn capacity_never_exceeds_the_maximum() {
+        let history = MetricsHistory::new(
+            &[DashboardHistoryField::ActiveRooms],
+            Duration::from_secs(1),
+            METRICS_HISTORY_MAX_CAPACITY * 10,
+        );
+        assert_eq!(history.capacity(), METRICS_HISTORY_MAX_CAPACITY);
+    }
+
+    #[test]
+    fn counters_become_per_second_rates() {
+        let history = MetricsHistory::new(
+            &[DashboardHistoryField::MessagesPerSec],
+            Duration::from_secs(10),
+            10,
+        );
+        let start = Instant::now();
+        let t0 = Utc::now();
+        history.record(start, t0, reading(0, 100));
+        assert_eq!(
+            history.points(DashboardHistoryField::MessagesPerSec, None),
+            Some(Vec::new()),
+            "the first sample has nothing to compare against"
+        );
+
+        history.record(
+            start + Duration::from_secs(10),
+            t0 + chrono::Duration::seconds(10),
+            reading(0, 350),
+        );
+        let points = history
+            .points(DashboardHistoryField::MessagesPerSec, None)
+            .expect("messages per second are tracked");
+        assert_eq!(points.len(), 1);
+        assert!((points[0].value - 25.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn samples_wait_for_the_interval_and_windows_filter_points() {
+        let history = MetricsHistory::new(
+            &[DashboardHistoryField::ActiveRooms],
+            Duration::from_secs(60),
+            10,
+        );
+        let start = Instant::now();
+        assert!(history.sample_due(start));
+
+        let t0 = Utc::now();
+        history.record(start, t0, reading(1, 0));
+        assert!(!history.sample_due(start + Duration::from_secs(30)));
+        assert!(history.sample_due(start + Duration::from_secs(60)));
+        history.record(
+            start + Duration::from_secs(60),
+            t0 + chrono::Duration::seconds(60),
+            reading(2, 0),
+        );
+
+        let recent = history
+            .points(
+                DashboardHistoryField::ActiveRooms,
+                Some(t0 + chrono::Duration::seconds(30)),
+            )
+            .expect("active rooms are tracked");
+        assert_eq!(recent.len(), 1);
+        assert!((recent[0].value - 2.0).abs() < f64::EPSILON);
+        assert!(history
+            .points(DashboardHistoryField::ReconnectRate, None)
+            .is_none());
+    }
+}
@@ -0,0 +1,277 @@
+//! OTLP/gRPC push export, enabled with the `otlp` cargo feature.
+//!
+//! The exporter mirrors the Prometheus endpoint rather than keeping a second
+//! list of instruments: each refresh parses the rendered exposition text and
+//! every counter and gauge family becomes an observable OpenTelemetry
+//! instrument of the same name (counters drop their `_total` suffix, which
+//! OTLP-to-Prometheus bridges add back). Histograms are only exported through
+//! their `_p50_ms`-style gauges.
+
+use crate::config::OtlpConfig;
+use axum::http::{HeaderMap, HeaderName, HeaderValue};
+use opentelemetry::metrics::{Meter, MeterProvider as _};
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::tonic_types::metadata::MetadataMap;
+use opentelemetry_otlp::{MetricExporter, WithExportConfig, WithTonicConfig};
+use opentelemetry_sdk::metrics::{PeriodicReader, SdkMeterProvider};
+use opentelemetry_sdk::Resource;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, RwLock};
+use uuid::Uuid;
+
+/// Latest observed samples, keyed by instrument name.
+type Observations = Arc<RwLock<HashMap<String, Vec<(Vec<KeyValue>, f64)>>>>;
+
+/// Pushes the server's counters and gauges to an OTLP collector.
+pub struct OtlpExporter {
+    provider: SdkMeterProvider,
+    meter: Meter,
+    observations: Observations,
+    /// Instruments already registered with the meter
+    registered: Mutex<HashSet<String>>,
+}
+
+impl OtlpExporter {
+    /// Build the exporter. Must be called from within a Tokio runtime.
+    ///
+    /// Exports carry `service.name`, `service.instance.id` and `region_id`
+    /// as resource attributes.
+    pub fn new(config: &OtlpConfig, instance_id: Uuid, region_id: &str) -> anyhow::Result<Self> {
+        let mut headers = HeaderMap::new();
+        for (name, value) in &config.headers {
+            let invalid = |e: &dyn std::fmt::Display| {
+                anyhow::anyhow!("invalid metrics.otlp.headers entry {name:?}: {e}")
+            };
+            let name = HeaderName::from_bytes(name.as_bytes()).map_err(|e| invalid(&e))?;
+            let value = HeaderValue::from_str(value).map_err(|e| invalid(&e))?;
+            headers.insert(name, value);
+        }
+        let exporter = MetricExporter::builder()
+            .with_tonic()
+            .with_endpoint(config.endpoint.clone())
+            .with_metadata(MetadataMap::from_headers(headers))
+            .build()?;
+        let reader = PeriodicReader::builder(exporter)
+            .with_interval(config.interval.into())
+            .build();
+        let provider = SdkMeterProvider::builder()
+            .with_reader(reader)
+            .with_resource(
+                Resource::builder()
+                    .with_service_name(env!("CARGO_PKG_NAME"))
+                    .with_attributes([
+                        KeyValue::new("service.instance.id", instance_id.to_string()),
+                        KeyValue::new("region_id", region_id.to_string()),
+                    ])
+                    .build(),
+            )
+            .build();
+        let meter = provider.meter(env!("CARGO_PKG_NAME"));
+        Ok(Self {
+            provider,
+            meter,
+            observations: Arc::default(),
+            registered: Mutex::default(),
+        })
+    }
+
+    /// Replace the observed values with those in a Prometheus exposition.
+    pub fn update(&self, exposition: &str) {
+        let families = parse_exposition(exposition);
+        let mut observations = HashMap::with_capacity(families.len());
+        for family in families {
+            self.register(&family);
+            observations.insert(family.instrument_name().to_string(), family.samples);
+        }
+        if let Ok(mut current) = self.observations.write() {
+            *current = observations;
+        }
+    }
+
+    /// Export once more and stop. Blocks until the collector answers or times out.
+    pub fn shutdown(&self) {
+        if let Err(err) = self.provider.shutdown() {
+            tracing::warn!(error = %err, "Failed to flush OTLP metrics on shutdown");
+        }
+    }
+
+    fn register(&self, family: &Family) {
+        let name = family.instrument_name().to_string();
+        let Ok(mut registered) = self.registered.lock() else {
+            return;
+        };
+        if !registered.insert(name.clone()) {
+            return;
+        }
+        let observations = Arc::clone(&self.observations);
+        let key = name.clone();
+        let observe = move |observer: &dyn opentelemetry::metrics::AsyncInstrument<f64>| {
+            let Ok(observations) = observations.read() else {
+                return;
+            };
+            for (attributes, value) in observations.get(&key).into_iter().flatten() {
+                observer.observe(*value, attributes);
+            }
+        };
+        // Observable instruments stay registered with the meter once built
+        match family.kind {
+            FamilyKind::Counter => {
+                self.meter
+                    .f64_observable_counter(name)
+                    .with_description(family.help.clone())
+                    .with_callback(observe)
+                    .build();
+            }
+            FamilyKind::Gauge => {
+                self.meter
+                    .f64_observable_gauge(name)
+                    .with_description(family.help.clone())
+                    .with_callback(observe)
+                    .build();
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FamilyKind {
+    Counter,
+    Gauge,
+}
+
+/// A counter or gauge family from the Prometheus exposition format.
+#[derive(Debug, Clone, PartialEq)]
+struct Family {
+    name: String,
+    help: String,
+    kind: FamilyKind,
+    samples: Vec<(Vec<KeyValue>, f64)>,
+}
+
+impl Family {
+    fn instrument_name(&self) -> &str {
+        match self.kind {
+            FamilyKind::Counter => self.name.strip_suffix("_total").unwrap_or(&self.name),
+            FamilyKind::Gauge => &self.name,
+        }
+    }
+}
+
+/// Collect the counter and gauge families of a Prometheus text exposition.
+fn parse_exposition(text: &str) -> Vec<Family> {
+    let mut families: Vec<Family> = Vec::new();
+    let mut help: HashMap<&str, &str> = HashMap::new();
+    for line in text.lines() {
+        if let Some(rest) = line.strip_prefix("# HELP ") {
+            if let Some((name, text)) = rest.split_once(' ') {
+                help.insert(name, text);
+            }
+        } else if let Some(rest) = line.strip_prefix("# TYPE ") {
+            let kind = match rest.split_once(' ') {
+                Some((name, "counter")) => Some((name, FamilyKind::Counter)),
+                Some((name, "gauge")) => Some((name, FamilyKind::Gauge)),
+                _ => None,
+            };
+            if let Some((name, kind)) = kind {
+                families.push(Family {
+                    name: name.to_string(),
+                    help: help.get(name).copied().unwrap_or_default().to_string(),
+                    kind,
+                    samples: Vec::new(),
+                });
+            }
+        } else if !line.starts_with('#') {
+            let Some((name, labels, value)) = parse_sample(line) else {
+                continue;
+            };
+            if let Some(family) = families.last_mut().filter(|f| f.name == name) {
+                family.samples.push((labels, value));
+            }
+        }
+    }
+    families.retain(|family| !family.samples.is_empty());
+    families
+}
+
+/// Split `name{label="value",...} 1.5` into its parts.
+fn parse_sample(line: &str) -> Option<(&str, Vec<KeyValue>, f64)> {
+    let (series, value) = line.rsplit_once(' ')?;
+    let value = value.parse().ok()?;
+    let Some((name, labels)) = series.split_once('{') else {
+        return Some((series, Vec::new(), value));
+    };
+    let mut attributes = Vec::new();
+    let mut rest = labels.strip_suffix('}')?;
+    while !rest.is_empty() {
+        let (key, after) = rest.split_once("=\"")?;
+        let mut label = String::new();
+        let mut chars = after.char_indices();
+        let end = loop {
+            match chars.next()? {
+                (_, '\\') => match chars.next()?.1 {
+                    'n' => label.push('\n'),
+                    other => label.push(other),
+                },
+                (index, '"') => break index,
+                (_, other) => label.push(other),
+            }
+        };
+        attributes.push(KeyValue::new(key.to_string(), label));
+        rest = after[end + 1..].trim_start_matches(',');
+    }
+    Some((name, attributes, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXPOSITION: &str = "\
+# HELP signal_fish_connections_total Total connections
+# TYPE signal_fish_connections_total counter
+signal_fish_connections_total 7
+# HELP signal_fish_broadcast_queue_depth Game data broadcasts waiting for a worker
+# TYPE signal_fish_broadcast_queue_depth gauge
+signal_fish_broadcast_queue_depth{game=\"chess\"} 2
+signal_fish_broadcast_queue_depth{game=\"say \\\"hi\\\"\",region=\"eu\"} 0.5
+# HELP signal_fish_session_duration_seconds Session lengths
+# TYPE signal_fish_session_duration_seconds histogram
+signal_fish_session_duration_seconds_bucket{le=\"1\"} 3
+signal_fish_session_duration_seconds_count 3
+# HELP signal_fish_empty_total Nothing recorded yet
+# TYPE signal_fish_empty_total counter
+";
+
+    #[test]
+    fn counters_and_gauges_are_mirrored_with_labels() {
+        let families = parse_exposition(EXPOSITION);
+        assert_eq!(
+            families.len(),
+            2,
+            "histograms and empty families are skipped"
+        );
+
+        let connections = &families[0];
+        assert_eq!(connections.instrument_name(), "signal_fish_connections");
+        assert_eq!(connections.kind, FamilyKind::Counter);
+        assert_eq!(connections.help, "Total connections");
+        assert_eq!(connections.samples, vec![(Vec::new(), 7.0)]);
+
+        let depth = &families[1];
+        assert_eq!(depth.instrument_name(), "signal_fish_broadcast_queue_depth");
+        assert_eq!(depth.kind, FamilyKind::Gauge);
+        assert_eq!(
+            depth.samples,
+            vec![
+                (vec![KeyValue::new("game", "chess")], 2.0),
+                (
+                    vec![
+                        KeyValue::new("game", "say \"hi\""),
+                        KeyValue::new("region", "eu")
+                    ],
+                    0.5
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use super::ServerMetrics;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Counter values written on graceful shutdown and read back on startup, so
+/// Prometheus sees a continuation instead of a reset after each deploy.
+#[derive(Debug, Serialize, Deserialize)]
+struct PersistedCounters {
+    saved_at: chrono::DateTime<chrono::Utc>,
+    counters: BTreeMap<String, u64>,
+}
+
+/// Write the persisted counters to `path`.
+///
+/// The values go to a temporary file next to `path` that is then renamed over
+/// it, so a crash mid-write never leaves a truncated file behind.
+pub fn save_counters(metrics: &ServerMetrics, path: &Path) -> io::Result<()> {
+    let snapshot = PersistedCounters {
+        saved_at: chrono::Utc::now(),
+        counters: metrics.counter_values(),
+    };
+    let json = serde_json::to_vec_pretty(&snapshot).map_err(io::Error::other)?;
+
+    let temp_path = temp_path_for(path);
+    let mut file = File::create(&temp_path)?;
+    file.write_all(&json)?;
+    file.sync_all()?;
+    drop(file);
+    fs::rename(&temp_path, path)
+}
+
+/// Add the counters saved at `path` onto `metrics`, returning how many were restored.
+///
+/// Missing, unreadable, corrupt or older-than-`max_age` files are ignored
+/// (with a warning for anything but a missing file). A restored file is
+/// removed so that a later crash cannot replay the same values twice.
+pub fn restore_counters(metrics: &ServerMetrics, path: &Path, max_age: Duration) -> usize {
+    let contents = match fs::read(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return 0,
+        Err(err) => {
+            warn!(path = %path.display(), error = %err, "Failed to read persisted metrics; starting from zero");
+            return 0;
+        }
+    };
+    let snapshot: PersistedCounters = match serde_json::from_slice(&contents) {
+        Ok(snapshot) => snapshot,
+        Err(err) => {
+            warn!(path = %path.display(), error = %err, "Ignoring corrupt persisted metrics file");
+            return 0;
+        }
+    };
+
+    let age = chrono::Utc::now()
+        .signed_duration_since(snapshot.saved_at)
+        .to_std()
+        .unwrap_or_default();
+    if age > max_age {
+        warn!(
+            path = %path.display(),
+            age_secs = age.as_secs(),
+            max_age_secs = max_age.as_secs(),
+            "Ignoring stale persisted metrics file"
+        );
+        return 0;
+    }
+
+    let restored = metrics.restore_counters(&snapshot.counters);
+    if let Err(err) = fs::remove_file(path) {
+        warn!(path = %path.display(), error = %err, "Failed to remove restored metrics file");
+    }
+    info!(path = %path.display(), restored, "Restored persisted metrics counters");
+    restored
+}
+
+fn temp_path_for(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::Ordering;
+
+    #[test]
+    fn counters_continue_across_simulated_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("metrics.json");
+
+        let before = ServerMetrics::new();
+        before.increment_rooms_created();
+        before.increment_rooms_created();
+        before.increment_players_joined();
+        before.active_connections.store(7, Ordering::Relaxed);
+        save_counters(&before, &path).unwrap();
+        assert!(!temp_path_for(&path).exists());
+
+        let after = ServerMetrics::new();
+        after.increment_rooms_created();
+        let restored = restore_counters(&after, &path, Duration::from_secs(60));
+
+        assert_eq!(restored, before.counter_values().len());
+        assert_eq!(after.rooms_created.load(Ordering::Relaxed), 3);
+        assert_eq!(after.players_joined.load(Ordering::Relaxed), 1);
+        // Gauges are not persisted
+        assert_eq!(after.active_connections.load(Ordering::Relaxed), 0);
+        // The file is consumed so a crash cannot replay it
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn corrupt_or_stale_files_are_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("metrics.json");
+        let metrics = ServerMetrics::new();
+
+        fs::write(&path, b"{ not json").unwrap();
+        assert_eq!(
+            restore_counters(&metrics, &path, Duration::from_secs(60)),
+            0
+        );
+        assert_eq!(metrics.rooms_created.load(Ordering::Relaxed), 0);
+
+        let stale = PersistedCounters {
+            saved_at: chrono::Utc::now() - chrono::Duration::hours(2),
+            counters: BTreeMap::from([("rooms_created".to_string(), 5)]),
+        };
+        fs::write(&path, serde_json::to_vec(&stale).unwrap()).unwrap();
+        assert_eq!(
+            restore_counters(&metrics, &path, Duration::from_secs(3600)),
+            0
+        );
+        assert_eq!(metrics.rooms_created.load(Ordering::Relaxed), 0);
+
+        let missing = dir.path().join("missing.json");
+        assert_eq!(restore_counters(&metrics, &missing, Duration::MAX), 0);
+    }
+}
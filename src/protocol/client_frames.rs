@@ -0,0 +1,158 @@
+//! Binary client frames for the `signal-fish-rkyv` subprotocol.
+//!
+//! Each binary frame is an rkyv archive of [`ClientFrame`]. The archive is
+//! validated with bytecheck before any field is read, so a crafted buffer is
+//! rejected instead of being trusted. Relay payloads are then forwarded as a
+//! slice of the received frame without being copied or decoded.
+
+use bytes::Bytes;
+use rkyv::{Archive, Deserialize as RkyvDeserialize, Serialize as RkyvSerialize};
+use thiserror::Error;
+
+use super::messages::ClientMessage;
+use super::types::{ArchivedGameDataEncoding, GameDataEncoding};
+use crate::rkyv_utils::{zero_copy_access, AlignedBytes, RkyvError, RkyvSerializer};
+
+/// One client frame on a `signal-fish-rkyv` connection.
+#[derive(Debug, Clone, PartialEq, Eq, Archive, RkyvSerialize, RkyvDeserialize)]
+pub enum ClientFrame {
+    /// A client message, MessagePack-encoded with named fields as on
+    /// `signal-fish-msgpack` connections.
+    Message(Vec<u8>),
+    /// A relay payload forwarded to the room without being decoded.
+    GameData {
+        encoding: GameDataEncoding,
+        payload: Vec<u8>,
+    },
+}
+
+impl ClientFrame {
+    /// Wrap `message` in a [`ClientFrame::Message`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the message cannot be MessagePack-encoded.
+    pub fn message(message: &ClientMessage) -> Result<Self, rmp_serde::encode::Error> {
+        rmp_serde::to_vec_named(message).map(Self::Message)
+    }
+
+    /// Archive the frame into the bytes of one binary WebSocket frame.
+    ///
+    /// # Errors
+    ///
+    /// Returns `RkyvError::Serialization` if archiving fails.
+    pub fn encode(&self) -> Result<Bytes, RkyvError> {
+        RkyvSerializer::new().serialize(self)
+    }
+}
+
+/// A validated [`ClientFrame`], ready for the server to handle.
+#[derive(Debug)]
+pub enum DecodedClientFrame {
+    Message(ClientMessage),
+    /// `payload` shares the received frame's buffer when it was aligned.
+    GameData {
+        encoding: GameDataEncoding,
+        payload: Bytes,
+    },
+}
+
+/// Why a binary frame was rejected.
+#[derive(Debug, Error)]
+pub enum ClientFrameError {
+    #[error("invalid rkyv frame: {0}")]
+    Archive(#[from] RkyvError),
+    #[error("invalid MessagePack message: {0}")]
+    Message(#[from] rmp_serde::decode::Error),
+}
+
+/// Validate and decode one binary frame.
+///
+/// # Errors
+///
+/// Returns [`ClientFrameError::Archive`] if the bytes are not a valid
+/// archived [`ClientFrame`], or [`ClientFrameError::Message`] if the
+/// embedded client message does not decode.
+pub fn decode_client_frame(frame: Bytes) -> Result<DecodedClientFrame, ClientFrameError> {
+    let buffer = AlignedBytes::new::<ArchivedClientFrame>(frame);
+    match zero_copy_access::<ArchivedClientFrame>(buffer.as_slice())? {
+        ArchivedClientFrame::Message(message) => Ok(DecodedClientFrame::Message(
+            rmp_serde::from_slice(message.as_slice())?,
+        )),
+        ArchivedClientFrame::GameData { encoding, payload } => Ok(DecodedClientFrame::GameData {
+            encoding: match encoding {
+                ArchivedGameDataEncoding::Json => GameDataEncoding::Json,
+                ArchivedGameDataEncoding::MessagePack => GameDataEncoding::MessagePack,
+                ArchivedGameDataEncoding::Rkyv => GameDataEncoding::Rkyv,
+            },
+            payload: buffer.slice_ref(payload.as_slice()),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_frame_round_trip() {
+        let frame = ClientFrame::message(&ClientMessage::LeaveRoom)
+            .unwrap()
+            .encode()
+            .unwrap();
+        let decoded = decode_client_frame(frame).unwrap();
+        assert!(matches!(
+            decoded,
+            DecodedClientFrame::Message(ClientMessage::LeaveRoom)
+        ));
+    }
+
+    #[test]
+    fn test_game_data_payload_shares_the_frame() {
+        let frame = ClientFrame::GameData {
+            encoding: GameDataEncoding::Rkyv,
+            payload: b"position:12,4".to_vec(),
+        }
+        .encode()
+        .unwrap();
+        let frame_range = frame.as_ptr_range();
+
+        let DecodedClientFrame::GameData { encoding, payload } =
+            decode_client_frame(frame.clone()).unwrap()
+        else {
+            panic!("expected game data");
+        };
+        assert_eq!(encoding, GameDataEncoding::Rkyv);
+        assert_eq!(payload, Bytes::from_static(b"position:12,4"));
+        assert!(frame_range.contains(&payload.as_ptr()));
+    }
+
+    #[test]
+    fn test_malformed_frames_are_rejected() {
+        let valid = ClientFrame::GameData {
+            encoding: GameDataEncoding::Json,
+            payload: vec![7; 32],
+        }
+        .encode()
+        .unwrap();
+
+        // Truncated archive, random bytes, and an out-of-range enum tag
+        let truncated = valid.slice(..valid.len() - 4);
+        let garbage = Bytes::from_static(&[0xff; 24]);
+        let mut bad_tag = valid.to_vec();
+        let tag = bad_tag.len() - std::mem::size_of::<ArchivedClientFrame>();
+        bad_tag[tag] = 0xee;
+        for frame in [truncated, garbage, Bytes::new(), Bytes::from(bad_tag)] {
+            assert!(matches!(
+                decode_client_frame(frame),
+                Err(ClientFrameError::Archive(_))
+            ));
+        }
+
+        let not_msgpack = ClientFrame::Message(vec![0xc1]).encode().unwrap();
+        assert!(matches!(
+            decode_client_frame(not_msgpack),
+            Err(ClientFrameError::Message(_))
+        ));
+    }
+}
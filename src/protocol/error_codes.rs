@@ -3,6 +3,8 @@ use serde::{Deserialize, Serialize};
 use std::fmt;
 
 /// Error codes for structured error handling
+///
+/// Serialized in SCREAMING_SNAKE_CASE, the form released SDKs match on.
 #[derive(
     Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Archive, RkyvSerialize, RkyvDeserialize,
 )]
@@ -29,6 +31,8 @@ pub enum ErrorCode {
     InvalidPlayerName,
     InvalidMaxPlayers,
     MessageTooLarge,
+    RoomMetadataLimitExceeded,
+    PlayerNameNotAllowed,
 
     // Room errors (3xxx)
     RoomNotFound,
@@ -38,6 +42,14 @@ pub enum ErrorCode {
     RoomCreationFailed,
     MaxRoomsPerGameExceeded,
     InvalidRoomState,
+    WaitingListFull,
+    Banned,
+    CapacityBelowOccupancy,
+    JoinRequestRejected,
+    JoinRequestTimedOut,
+    InviteNotFound,
+    InviteExpired,
+    InviteExhausted,
 
     // Authority errors (4xxx)
     AuthorityNotSupported,
@@ -47,23 +59,32 @@ pub enum ErrorCode {
     // Rate limiting (5xxx)
     RateLimitExceeded,
     TooManyConnections,
+    AppRoomQuotaExceeded,
+    TooManyActiveRooms,
+    AppQuotaExceeded,
+    BackpressureShed,
 
     // Reconnection errors (6xxx)
     ReconnectionFailed,
     ReconnectionTokenInvalid,
     ReconnectionExpired,
     PlayerAlreadyConnected,
+    TokenReplayed,
 
     // Spectator errors (7xxx)
     SpectatorNotAllowed,
     TooManySpectators,
     NotASpectator,
     SpectatorJoinFailed,
+    PromotionQueueFull,
+    SpectatorChatDisabled,
 
     // Server errors (9xxx)
     InternalError,
     StorageError,
     ServiceUnavailable,
+    ServerDraining,
+    ServerShuttingDown,
 }
 
 impl ErrorCode {
@@ -127,6 +148,12 @@ impl ErrorCode {
             Self::MessageTooLarge => {
                 "The message size exceeds the maximum allowed limit. Please send a smaller message."
             }
+            Self::RoomMetadataLimitExceeded => {
+                "The room metadata update exceeds the allowed number of keys or the key or value length limits."
+            }
+            Self::PlayerNameNotAllowed => {
+                "The player name contains a word that is not allowed on this server."
+            }
 
             // Room errors (3xxx)
             Self::RoomNotFound => {
@@ -150,6 +177,30 @@ impl ErrorCode {
             Self::InvalidRoomState => {
                 "The room is in an invalid state for this operation. Try refreshing or rejoining the room."
             }
+            Self::WaitingListFull => {
+                "The room is full and its waiting list has no free slots. Try again later or join a different room."
+            }
+            Self::Banned => {
+                "You have been banned from this room by its authority and cannot rejoin or spectate it."
+            }
+            Self::CapacityBelowOccupancy => {
+                "The requested room capacity is below the number of players or spectators already in the room."
+            }
+            Self::JoinRequestRejected => {
+                "The room authority declined your request to join the full room, or left before answering."
+            }
+            Self::JoinRequestTimedOut => {
+                "The room authority did not answer your request to join the full room in time. Try again later."
+            }
+            Self::InviteNotFound => {
+                "The invite token is not valid, or its room has closed. Ask for a new invite."
+            }
+            Self::InviteExpired => {
+                "The invite token has expired. Ask a player in the room for a new invite."
+            }
+            Self::InviteExhausted => {
+                "The invite token has already been used as many times as allowed. Ask for a new invite."
+            }
 
             // Authority errors (4xxx)
             Self::AuthorityNotSupported => {
@@ -169,6 +220,18 @@ impl ErrorCode {
             Self::TooManyConnections => {
                 "You have too many active connections. Close some connections before opening new ones."
             }
+            Self::AppRoomQuotaExceeded => {
+                "Your application has reached its room limit. Close an existing room or try again later."
+            }
+            Self::TooManyActiveRooms => {
+                "You already have the maximum number of active rooms. Close one of them before creating another."
+            }
+            Self::AppQuotaExceeded => {
+                "Your application has reached one of its usage quotas. Try again once usage drops."
+            }
+            Self::BackpressureShed => {
+                "Your connection fell too far behind reading server messages and was closed. Reconnect to resume."
+            }
 
             // Reconnection errors (6xxx)
             Self::ReconnectionFailed => {
@@ -183,6 +246,9 @@ impl ErrorCode {
             Self::PlayerAlreadyConnected => {
                 "This player is already connected to the room from another session."
             }
+            Self::TokenReplayed => {
+                "This reconnection token has already been replaced by a newer one. Use the most recent token from RoomJoined or Reconnected."
+            }
 
             // Spectator errors (7xxx)
             Self::SpectatorNotAllowed => {
@@ -197,6 +263,12 @@ impl ErrorCode {
             Self::SpectatorJoinFailed => {
                 "Failed to join as a spectator. The room may be full or spectating may be disabled."
             }
+            Self::PromotionQueueFull => {
+                "The room's spectator promotion queue is full. Try again once queued spectators have been seated."
+            }
+            Self::SpectatorChatDisabled => {
+                "Spectator chat is disabled for this room."
+            }
 
             // Server errors (9xxx)
             Self::InternalError => {
@@ -208,6 +280,355 @@ impl ErrorCode {
             Self::ServiceUnavailable => {
                 "The service is temporarily unavailable. Please try again in a few moments."
             }
+            Self::ServerDraining => {
+                "This server is draining for maintenance and is not accepting new rooms or joins. Please retry shortly; you may be routed to another instance."
+            }
+            Self::ServerShuttingDown => {
+                "This server is shutting down. Reconnect to be routed to another instance."
+            }
+        }
+    }
+
+    /// WebSocket close code (4000-4999) sent when the server closes a
+    /// connection because of this error.
+    ///
+    /// The second digit follows the error category (41xx authentication,
+    /// 45xx rate limiting, ...). SDKs use these to decide whether to
+    /// reconnect, so existing values must never change.
+    pub const fn close_code(&self) -> u16 {
+        match self {
+            Self::Unauthorized => 4100,
+            Self::InvalidToken => 4101,
+            Self::AuthenticationRequired => 4102,
+            Self::InvalidAppId => 4103,
+            Self::AppIdExpired => 4104,
+            Self::AppIdRevoked => 4105,
+            Self::AppIdSuspended => 4106,
+            Self::MissingAppId => 4107,
+            Self::AuthenticationTimeout => 4108,
+            Self::SdkVersionUnsupported => 4109,
+            Self::UnsupportedGameDataFormat => 4110,
+            Self::InvalidInput => 4200,
+            Self::InvalidGameName => 4201,
+            Self::InvalidRoomCode => 4202,
+            Self::InvalidPlayerName => 4203,
+            Self::InvalidMaxPlayers => 4204,
+            Self::MessageTooLarge => 4205,
+            Self::RoomMetadataLimitExceeded => 4206,
+            Self::PlayerNameNotAllowed => 4207,
+            Self::RoomNotFound => 4300,
+            Self::RoomFull => 4301,
+            Self::AlreadyInRoom => 4302,
+            Self::NotInRoom => 4303,
+            Self::RoomCreationFailed => 4304,
+            Self::MaxRoomsPerGameExceeded => 4305,
+            Self::InvalidRoomState => 4306,
+            Self::WaitingListFull => 4307,
+            Self::Banned => 4308,
+            Self::CapacityBelowOccupancy => 4311,
+            Self::JoinRequestRejected => 4312,
+            Self::JoinRequestTimedOut => 4313,
+            Self::InviteNotFound => 4314,
+            Self::InviteExpired => 4315,
+            Self::InviteExhausted => 4316,
+            Self::AuthorityNotSupported => 4400,
+            Self::AuthorityConflict => 4401,
+            Self::AuthorityDenied => 4402,
+            Self::RateLimitExceeded => 4500,
+            Self::TooManyConnections => 4501,
+            Self::AppRoomQuotaExceeded => 4502,
+            Self::TooManyActiveRooms => 4503,
+            Self::AppQuotaExceeded => 4504,
+            Self::BackpressureShed => 4505,
+            Self::ReconnectionFailed => 4600,
+            Self::ReconnectionTokenInvalid => 4601,
+            Self::ReconnectionExpired => 4602,
+            Self::PlayerAlreadyConnected => 4603,
+            Self::TokenReplayed => 4604,
+            Self::SpectatorNotAllowed => 4700,
+            Self::TooManySpectators => 4701,
+            Self::NotASpectator => 4702,
+            Self::SpectatorJoinFailed => 4703,
+            Self::PromotionQueueFull => 4704,
+            Self::SpectatorChatDisabled => 4705,
+            Self::InternalError => 4900,
+            Self::StorageError => 4901,
+            Self::ServiceUnavailable => 4902,
+            Self::ServerDraining => 4903,
+            Self::ServerShuttingDown => 4904,
+        }
+    }
+
+    /// Close frame reason: the code's wire name, which fits the 123-byte limit.
+    pub const fn close_reason(&self) -> &'static str {
+        match self {
+            Self::Unauthorized => "UNAUTHORIZED",
+            Self::InvalidToken => "INVALID_TOKEN",
+            Self::AuthenticationRequired => "AUTHENTICATION_REQUIRED",
+            Self::InvalidAppId => "INVALID_APP_ID",
+            Self::AppIdExpired => "APP_ID_EXPIRED",
+            Self::AppIdRevoked => "APP_ID_REVOKED",
+            Self::AppIdSuspended => "APP_ID_SUSPENDED",
+            Self::MissingAppId => "MISSING_APP_ID",
+            Self::AuthenticationTimeout => "AUTHENTICATION_TIMEOUT",
+            Self::SdkVersionUnsupported => "SDK_VERSION_UNSUPPORTED",
+            Self::UnsupportedGameDataFormat => "UNSUPPORTED_GAME_DATA_FORMAT",
+            Self::InvalidInput => "INVALID_INPUT",
+            Self::InvalidGameName => "INVALID_GAME_NAME",
+            Self::InvalidRoomCode => "INVALID_ROOM_CODE",
+            Self::InvalidPlayerName => "INVALID_PLAYER_NAME",
+            Self::InvalidMaxPlayers => "INVALID_MAX_PLAYERS",
+            Self::MessageTooLarge => "MESSAGE_TOO_LARGE",
+            Self::RoomMetadataLimitExceeded => "ROOM_METADATA_LIMIT_EXCEEDED",
+            Self::PlayerNameNotAllowed => "PLAYER_NAME_NOT_ALLOWED",
+            Self::RoomNotFound => "ROOM_NOT_FOUND",
+            Self::RoomFull => "ROOM_FULL",
+            Self::AlreadyInRoom => "ALREADY_IN_ROOM",
+            Self::NotInRoom => "NOT_IN_ROOM",
+            Self::RoomCreationFailed => "ROOM_CREATION_FAILED",
+            Self::MaxRoomsPerGameExceeded => "MAX_ROOMS_PER_GAME_EXCEEDED",
+            Self::InvalidRoomState => "INVALID_ROOM_STATE",
+            Self::WaitingListFull => "WAITING_LIST_FULL",
+            Self::Banned => "BANNED",
+            Self::CapacityBelowOccupancy => "CAPACITY_BELOW_OCCUPANCY",
+            Self::JoinRequestRejected => "JOIN_REQUEST_REJECTED",
+            Self::JoinRequestTimedOut => "JOIN_REQUEST_TIMED_OUT",
+            Self::InviteNotFound => "INVITE_NOT_FOUND",
+            Self::InviteExpired => "INVITE_EXPIRED",
+            Self::InviteExhausted => "INVITE_EXHAUSTED",
+            Self::AuthorityNotSupported => "AUTHORITY_NOT_SUPPORTED",
+            Self::AuthorityConflict => "AUTHORITY_CONFLICT",
+            Self::AuthorityDenied => "AUTHORITY_DENIED",
+            Self::RateLimitExceeded => "RATE_LIMIT_EXCEEDED",
+            Self::TooManyConnections => "TOO_MANY_CONNECTIONS",
+            Self::AppRoomQuotaExceeded => "APP_ROOM_QUOTA_EXCEEDED",
+            Self::TooManyActiveRooms => "TOO_MANY_ACTIVE_ROOMS",
+            Self::AppQuotaExceeded => "APP_QUOTA_EXCEEDED",
+            Self::BackpressureShed => "BACKPRESSURE_SHED",
+            Self::ReconnectionFailed => "RECONNECTION_FAILED",
+            Self::ReconnectionTokenInvalid => "RECONNECTION_TOKEN_INVALID",
+            Self::ReconnectionExpired => "RECONNECTION_EXPIRED",
+            Self::PlayerAlreadyConnected => "PLAYER_ALREADY_CONNECTED",
+            Self::TokenReplayed => "TOKEN_REPLAYED",
+            Self::SpectatorNotAllowed => "SPECTATOR_NOT_ALLOWED",
+            Self::TooManySpectators => "TOO_MANY_SPECTATORS",
+            Self::NotASpectator => "NOT_A_SPECTATOR",
+            Self::SpectatorJoinFailed => "SPECTATOR_JOIN_FAILED",
+            Self::PromotionQueueFull => "PROMOTION_QUEUE_FULL",
+            Self::SpectatorChatDisabled => "SPECTATOR_CHAT_DISABLED",
+            Self::InternalError => "INTERNAL_ERROR",
+            Self::StorageError => "STORAGE_ERROR",
+            Self::ServiceUnavailable => "SERVICE_UNAVAILABLE",
+            Self::ServerDraining => "SERVER_DRAINING",
+            Self::ServerShuttingDown => "SERVER_SHUTTING_DOWN",
+        }
+    }
+}
+
+/// Typed, machine-readable details sent as `details` on error payloads, so
+/// clients need not parse the human-readable message.
+///
+/// Serialized as an object tagged by `kind`. Each [`ErrorCode`] has at most
+/// one shape, given by [`ErrorCode::details_kind`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ErrorDetails {
+    /// The request was throttled
+    RateLimited {
+        /// Whole seconds until the request can succeed (at least 1)
+        retry_after_secs: u64,
+        /// Requests allowed per window, or the token bucket's burst size
+        limit: u32,
+        /// Requests still allowed right now
+        remaining: u32,
+    },
+    /// A request field broke a validation rule
+    ValidationFailed {
+        /// The offending field, e.g. `player_name`
+        field: String,
+        constraint: ValidationConstraint,
+        /// The limit the field exceeded, for length and range constraints
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max: Option<u64>,
+    },
+    /// A message was over the size limit, in bytes
+    MessageTooLarge { size: usize, max_size: usize },
+    /// The room has no free player slots
+    RoomFull { max_players: u8 },
+    /// Another player in the room already uses the name
+    NameTaken {
+        /// A free name the player could join under instead
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        suggested: Option<String>,
+    },
+    /// A room or usage cap was reached
+    QuotaExceeded {
+        current: usize,
+        limit: usize,
+        /// Which cap was reached
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        quota: Option<QuotaKind>,
+    },
+}
+
+/// The cap behind an [`ErrorDetails::QuotaExceeded`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotaKind {
+    /// Rooms per game on this server
+    RoomsPerGame,
+    /// Active rooms per creator
+    RoomsPerCreator,
+    /// Rooms owned by the application at once
+    AppRooms,
+    /// Players in the application's rooms at once
+    AppPlayers,
+    /// Rooms the application created in the last hour
+    AppRoomsPerHour,
+}
+
+impl QuotaKind {
+    /// The name used on the wire and in metrics labels.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::RoomsPerGame => "rooms_per_game",
+            Self::RoomsPerCreator => "rooms_per_creator",
+            Self::AppRooms => "app_rooms",
+            Self::AppPlayers => "app_players",
+            Self::AppRoomsPerHour => "app_rooms_per_hour",
+        }
+    }
+}
+
+impl fmt::Display for QuotaKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The rule a field broke, for [`ErrorDetails::ValidationFailed`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidationConstraint {
+    /// The field was empty or blank
+    Required,
+    /// Longer than `max`
+    MaxLength,
+    /// Not exactly `max` long
+    ExactLength,
+    /// Outside 1 to `max`
+    Range,
+    /// Contains characters that are not allowed
+    AllowedCharacters,
+    /// Contains a banned word
+    BannedWord,
+    /// Not one of the values the server accepts
+    AllowedValues,
+    /// Over a count or size limit across several entries
+    Limits,
+}
+
+/// The shape of an [`ErrorDetails`], without its data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorDetailsKind {
+    RateLimited,
+    ValidationFailed,
+    MessageTooLarge,
+    RoomFull,
+    NameTaken,
+    QuotaExceeded,
+}
+
+impl ErrorDetails {
+    pub const fn kind(&self) -> ErrorDetailsKind {
+        match self {
+            Self::RateLimited { .. } => ErrorDetailsKind::RateLimited,
+            Self::ValidationFailed { .. } => ErrorDetailsKind::ValidationFailed,
+            Self::MessageTooLarge { .. } => ErrorDetailsKind::MessageTooLarge,
+            Self::RoomFull { .. } => ErrorDetailsKind::RoomFull,
+            Self::NameTaken { .. } => ErrorDetailsKind::NameTaken,
+            Self::QuotaExceeded { .. } => ErrorDetailsKind::QuotaExceeded,
+        }
+    }
+
+    /// Whether these details are the shape `code` carries.
+    pub fn fits(&self, code: &ErrorCode) -> bool {
+        code.details_kind() == Some(self.kind())
+    }
+
+    pub fn validation(field: &str, constraint: ValidationConstraint, max: Option<u64>) -> Self {
+        Self::ValidationFailed {
+            field: field.to_string(),
+            constraint,
+            max,
+        }
+    }
+}
+
+impl ErrorCode {
+    /// The [`ErrorDetails`] shape sent with this code, if any.
+    pub const fn details_kind(&self) -> Option<ErrorDetailsKind> {
+        match self {
+            Self::RateLimitExceeded => Some(ErrorDetailsKind::RateLimited),
+            Self::InvalidInput
+            | Self::InvalidGameName
+            | Self::InvalidRoomCode
+            | Self::InvalidMaxPlayers
+            | Self::RoomMetadataLimitExceeded
+            | Self::PlayerNameNotAllowed => Some(ErrorDetailsKind::ValidationFailed),
+            Self::MessageTooLarge => Some(ErrorDetailsKind::MessageTooLarge),
+            Self::RoomFull => Some(ErrorDetailsKind::RoomFull),
+            Self::InvalidPlayerName => Some(ErrorDetailsKind::NameTaken),
+            Self::MaxRoomsPerGameExceeded
+            | Self::AppRoomQuotaExceeded
+            | Self::TooManyActiveRooms
+            | Self::AppQuotaExceeded => Some(ErrorDetailsKind::QuotaExceeded),
+            Self::Unauthorized
+            | Self::InvalidToken
+            | Self::AuthenticationRequired
+            | Self::InvalidAppId
+            | Self::AppIdExpired
+            | Self::AppIdRevoked
+            | Self::AppIdSuspended
+            | Self::MissingAppId
+            | Self::AuthenticationTimeout
+            | Self::SdkVersionUnsupported
+            | Self::UnsupportedGameDataFormat
+            | Self::RoomNotFound
+            | Self::AlreadyInRoom
+            | Self::NotInRoom
+            | Self::RoomCreationFailed
+            | Self::InvalidRoomState
+            | Self::WaitingListFull
+            | Self::Banned
+            | Self::CapacityBelowOccupancy
+            | Self::JoinRequestRejected
+            | Self::JoinRequestTimedOut
+            | Self::InviteNotFound
+            | Self::InviteExpired
+            | Self::InviteExhausted
+            | Self::AuthorityNotSupported
+            | Self::AuthorityConflict
+            | Self::AuthorityDenied
+            | Self::TooManyConnections
+            | Self::BackpressureShed
+            | Self::ReconnectionFailed
+            | Self::ReconnectionTokenInvalid
+            | Self::ReconnectionExpired
+            | Self::PlayerAlreadyConnected
+            | Self::TokenReplayed
+            | Self::SpectatorNotAllowed
+            | Self::TooManySpectators
+            | Self::NotASpectator
+            | Self::SpectatorJoinFailed
+            | Self::PromotionQueueFull
+            | Self::SpectatorChatDisabled
+            | Self::InternalError
+            | Self::StorageError
+            | Self::ServiceUnavailable
+            | Self::ServerDraining
+            | Self::ServerShuttingDown => None,
         }
     }
 }
@@ -222,10 +643,9 @@ impl fmt::Display for ErrorCode {
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_all_error_codes_have_descriptions() {
-        // Ensure all error codes have non-empty descriptions
-        let error_codes = [
+    /// Every variant, in declaration order.
+    fn all_error_codes() -> Vec<ErrorCode> {
+        vec![
             ErrorCode::Unauthorized,
             ErrorCode::InvalidToken,
             ErrorCode::AuthenticationRequired,
@@ -243,6 +663,8 @@ mod tests {
             ErrorCode::InvalidPlayerName,
             ErrorCode::InvalidMaxPlayers,
             ErrorCode::MessageTooLarge,
+            ErrorCode::RoomMetadataLimitExceeded,
+            ErrorCode::PlayerNameNotAllowed,
             ErrorCode::RoomNotFound,
             ErrorCode::RoomFull,
             ErrorCode::AlreadyInRoom,
@@ -250,23 +672,113 @@ mod tests {
             ErrorCode::RoomCreationFailed,
             ErrorCode::MaxRoomsPerGameExceeded,
             ErrorCode::InvalidRoomState,
+            ErrorCode::WaitingListFull,
+            ErrorCode::Banned,
+            ErrorCode::CapacityBelowOccupancy,
+            ErrorCode::JoinRequestRejected,
+            ErrorCode::JoinRequestTimedOut,
+            ErrorCode::InviteNotFound,
+            ErrorCode::InviteExpired,
+            ErrorCode::InviteExhausted,
             ErrorCode::AuthorityNotSupported,
             ErrorCode::AuthorityConflict,
             ErrorCode::AuthorityDenied,
             ErrorCode::RateLimitExceeded,
             ErrorCode::TooManyConnections,
+            ErrorCode::AppRoomQuotaExceeded,
+            ErrorCode::TooManyActiveRooms,
+            ErrorCode::AppQuotaExceeded,
+            ErrorCode::BackpressureShed,
             ErrorCode::ReconnectionFailed,
             ErrorCode::ReconnectionTokenInvalid,
             ErrorCode::ReconnectionExpired,
             ErrorCode::PlayerAlreadyConnected,
+            ErrorCode::TokenReplayed,
             ErrorCode::SpectatorNotAllowed,
             ErrorCode::TooManySpectators,
             ErrorCode::NotASpectator,
             ErrorCode::SpectatorJoinFailed,
+            ErrorCode::PromotionQueueFull,
+            ErrorCode::SpectatorChatDisabled,
             ErrorCode::InternalError,
             ErrorCode::StorageError,
             ErrorCode::ServiceUnavailable,
-        ];
+            ErrorCode::ServerDraining,
+            ErrorCode::ServerShuttingDown,
+        ]
+    }
+
+    /// Stops compiling when a variant is added, as a reminder to list it in
+    /// [`all_error_codes`].
+    #[allow(dead_code)]
+    const fn is_listed(code: &ErrorCode) -> bool {
+        match code {
+            ErrorCode::Unauthorized
+            | ErrorCode::InvalidToken
+            | ErrorCode::AuthenticationRequired
+            | ErrorCode::InvalidAppId
+            | ErrorCode::AppIdExpired
+            | ErrorCode::AppIdRevoked
+            | ErrorCode::AppIdSuspended
+            | ErrorCode::MissingAppId
+            | ErrorCode::AuthenticationTimeout
+            | ErrorCode::SdkVersionUnsupported
+            | ErrorCode::UnsupportedGameDataFormat
+            | ErrorCode::InvalidInput
+            | ErrorCode::InvalidGameName
+            | ErrorCode::InvalidRoomCode
+            | ErrorCode::InvalidPlayerName
+            | ErrorCode::InvalidMaxPlayers
+            | ErrorCode::MessageTooLarge
+            | ErrorCode::RoomMetadataLimitExceeded
+            | ErrorCode::PlayerNameNotAllowed
+            | ErrorCode::RoomNotFound
+            | ErrorCode::RoomFull
+            | ErrorCode::AlreadyInRoom
+            | ErrorCode::NotInRoom
+            | ErrorCode::RoomCreationFailed
+            | ErrorCode::MaxRoomsPerGameExceeded
+            | ErrorCode::InvalidRoomState
+            | ErrorCode::WaitingListFull
+            | ErrorCode::Banned
+            | ErrorCode::CapacityBelowOccupancy
+            | ErrorCode::JoinRequestRejected
+            | ErrorCode::JoinRequestTimedOut
+            | ErrorCode::InviteNotFound
+            | ErrorCode::InviteExpired
+            | ErrorCode::InviteExhausted
+            | ErrorCode::AuthorityNotSupported
+            | ErrorCode::AuthorityConflict
+            | ErrorCode::AuthorityDenied
+            | ErrorCode::RateLimitExceeded
+            | ErrorCode::TooManyConnections
+            | ErrorCode::AppRoomQuotaExceeded
+            | ErrorCode::TooManyActiveRooms
+            | ErrorCode::AppQuotaExceeded
+            | ErrorCode::BackpressureShed
+            | ErrorCode::ReconnectionFailed
+            | ErrorCode::ReconnectionTokenInvalid
+            | ErrorCode::ReconnectionExpired
+            | ErrorCode::PlayerAlreadyConnected
+            | ErrorCode::TokenReplayed
+            | ErrorCode::SpectatorNotAllowed
+            | ErrorCode::TooManySpectators
+            | ErrorCode::NotASpectator
+            | ErrorCode::SpectatorJoinFailed
+            | ErrorCode::PromotionQueueFull
+            | ErrorCode::SpectatorChatDisabled
+            | ErrorCode::InternalError
+            | ErrorCode::StorageError
+            | ErrorCode::ServiceUnavailable
+            | ErrorCode::ServerDraining
+            | ErrorCode::ServerShuttingDown => true,
+        }
+    }
+
+    #[test]
+    fn test_all_error_codes_have_descriptions() {
+        // Ensure all error codes have non-empty descriptions
+        let error_codes = all_error_codes();
 
         for error_code in &error_codes {
             let description = error_code.description();
@@ -311,4 +823,130 @@ mod tests {
         let json = serde_json::to_string(&error).unwrap();
         assert_eq!(json, "\"ROOM_NOT_FOUND\"");
     }
+
+    #[test]
+    fn test_every_error_code_has_a_unique_close_code() {
+        let mut seen = std::collections::HashMap::new();
+        for error_code in all_error_codes() {
+            let close_code = error_code.close_code();
+            assert!(
+                (4000..=4999).contains(&close_code),
+                "ErrorCode::{error_code:?} maps to {close_code}, outside 4000-4999"
+            );
+            if let Some(previous) = seen.insert(close_code, error_code.clone()) {
+                panic!("ErrorCode::{error_code:?} and ErrorCode::{previous:?} share close code {close_code}");
+            }
+
+            let reason = error_code.close_reason();
+            assert!(reason.len() <= 123, "close reason too long: {reason}");
+            assert_eq!(
+                serde_json::to_string(&error_code).unwrap(),
+                format!("\"{reason}\"")
+            );
+        }
+    }
+
+    #[test]
+    fn test_close_codes_are_stable() {
+        // SDKs decide whether to reconnect from these values
+        assert_eq!(ErrorCode::Unauthorized.close_code(), 4100);
+        assert_eq!(ErrorCode::MissingAppId.close_code(), 4107);
+        assert_eq!(ErrorCode::MessageTooLarge.close_code(), 4205);
+        assert_eq!(ErrorCode::RateLimitExceeded.close_code(), 4500);
+        assert_eq!(ErrorCode::TooManyConnections.close_code(), 4501);
+        assert_eq!(ErrorCode::BackpressureShed.close_code(), 4505);
+        assert_eq!(ErrorCode::ServerDraining.close_code(), 4903);
+    }
+
+    /// One value of every details shape.
+    fn one_of_each_details() -> Vec<ErrorDetails> {
+        vec![
+            ErrorDetails::RateLimited {
+                retry_after_secs: 3,
+                limit: 10,
+                remaining: 0,
+            },
+            ErrorDetails::validation("player_name", ValidationConstraint::MaxLength, Some(32)),
+            ErrorDetails::MessageTooLarge {
+                size: 70_000,
+                max_size: 65_536,
+            },
+            ErrorDetails::RoomFull { max_players: 4 },
+            ErrorDetails::NameTaken {
+                suggested: Some("Alex (2)".to_string()),
+            },
+            ErrorDetails::QuotaExceeded {
+                current: 5,
+                limit: 5,
+                quota: Some(QuotaKind::AppPlayers),
+            },
+        ]
+    }
+
+    /// Stops compiling when a details shape is added, as a reminder to
+    /// sample it in [`one_of_each_details`].
+    #[allow(dead_code)]
+    const fn is_sampled(kind: ErrorDetailsKind) -> bool {
+        match kind {
+            ErrorDetailsKind::RateLimited
+            | ErrorDetailsKind::ValidationFailed
+            | ErrorDetailsKind::MessageTooLarge
+            | ErrorDetailsKind::RoomFull
+            | ErrorDetailsKind::NameTaken
+            | ErrorDetailsKind::QuotaExceeded => true,
+        }
+    }
+
+    #[test]
+    fn test_each_error_code_fits_at_most_one_details_shape() {
+        let samples = one_of_each_details();
+        for error_code in all_error_codes() {
+            let fitting: Vec<_> = samples
+                .iter()
+                .filter(|details| details.fits(&error_code))
+                .collect();
+            assert!(
+                fitting.len() <= 1,
+                "ErrorCode::{error_code:?} fits {fitting:?}"
+            );
+            assert_eq!(
+                fitting.first().map(|details| details.kind()),
+                error_code.details_kind()
+            );
+        }
+
+        // Every shape is sent with some code
+        for details in &samples {
+            assert!(
+                all_error_codes().iter().any(|code| details.fits(code)),
+                "no error code carries {details:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_error_details_are_tagged_by_kind() {
+        for details in one_of_each_details() {
+            let json = serde_json::to_value(&details).unwrap();
+            assert_eq!(json["kind"], serde_json::to_value(details.kind()).unwrap());
+            let decoded: ErrorDetails = serde_json::from_value(json).unwrap();
+            assert_eq!(decoded, details);
+        }
+
+        let json = serde_json::to_value(ErrorDetails::validation(
+            "max_players",
+            ValidationConstraint::Range,
+            Some(8),
+        ))
+        .unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "kind": "validation_failed",
+                "field": "max_players",
+                "constraint": "range",
+                "max": 8
+            })
+        );
+    }
 }
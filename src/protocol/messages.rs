@@ -1,12 +1,16 @@
 use bytes::Bytes;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
 
-use super::error_codes::ErrorCode;
-use super::room_state::LobbyState;
+use super::error_codes::{ErrorCode, ErrorDetails};
+use super::permissions::PlayerRole;
+use super::room_state::{HistoricalMessage, LobbyState};
 use super::types::{
-    ConnectionInfo, GameDataEncoding, PeerConnectionInfo, PlayerId, PlayerInfo,
-    ProtocolInfoPayload, RateLimitInfo, RelayTransport, RoomId, SpectatorInfo,
-    SpectatorStateChangeReason,
+    AnnouncementSeverity, ConnectionInfo, GameDataEncoding, HeartbeatEcho, ParticipantRole,
+    PeerConnectionInfo, PlayerId, PlayerInfo, ProtocolInfoPayload, RateLimitInfo, RelayTransport,
+    ReportReason, RoomId, SpectatorChatMode, SpectatorInfo, SpectatorStateChangeReason,
 };
 
 /// Message types sent from client to server
@@ -27,6 +31,9 @@ pub enum ClientMessage {
         /// Preferred game data encoding (defaults to JSON text frames)
         #[serde(skip_serializing_if = "Option::is_none")]
         game_data_format: Option<GameDataEncoding>,
+        /// Optional client-side capabilities (e.g., "delivery-ack")
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        capabilities: Vec<String>,
     },
     /// Join or create a room for a specific game
     JoinRoom {
@@ -39,6 +46,31 @@ pub enum ClientMessage {
         /// If not specified, defaults to Auto
         #[serde(default)]
         relay_transport: Option<RelayTransport>,
+        /// Relay type for a newly created room. "auto" or omitted lets the
+        /// server choose based on game and region
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        relay_type: Option<String>,
+        /// Who receives spectator chat in a newly created room (defaults to `none`)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        spectator_chat_mode: Option<SpectatorChatMode>,
+        /// Client-chosen key that makes room creation safe to retry: a repeat
+        /// create with the same key joins the room the first one created.
+        /// Ignored when `room_code` is set
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        idempotency_key: Option<String>,
+        /// Let joins to the full room through once its authority accepts them
+        /// (see `JoinRequest`). Only applies when the room is created
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        allow_overflow_joins: Option<bool>,
+    },
+    /// Join the fullest open room of a game, or create one if none has space
+    QuickJoin {
+        game_name: String,
+        player_name: String,
+        /// Region the player is in; rooms there are preferred. Must be the
+        /// server's region or one of its `allowed_regions`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        region_id: Option<String>,
     },
     /// Leave the current room
     LeaveRoom,
@@ -52,6 +84,30 @@ pub enum ClientMessage {
     ProvideConnectionInfo { connection_info: ConnectionInfo },
     /// Heartbeat to maintain connection
     Ping,
+    /// Latency probe, answered with `LatencyPong` (at most one per second)
+    LatencyPing {
+        /// Client-chosen probe id, echoed in the pong
+        id: u64,
+        /// Client clock in milliseconds, echoed in the pong
+        sent_at_ms: u64,
+        /// Round trip the client measured for its previous probe; shared with
+        /// the room as `PlayerLatencyUpdated` when it changes enough
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        last_rtt_ms: Option<u32>,
+    },
+    /// Timed heartbeat, answered with `HeartbeatAck` unless throttled
+    Heartbeat {
+        /// Client clock in milliseconds, echoed back in the ack
+        client_timestamp: u64,
+        /// Echo of the most recent ack, used to measure round-trip time
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        last_ack: Option<HeartbeatEcho>,
+    },
+    /// Clock synchronization request, answered immediately with `TimeSyncResult`
+    TimeSync {
+        /// Client clock in milliseconds, echoed in the result
+        client_sent_at_ms: u64,
+    },
     /// Reconnect to a room after disconnection
     Reconnect {
         player_id: PlayerId,
@@ -67,6 +123,125 @@ pub enum ClientMessage {
     },
     /// Leave spectator mode
     LeaveSpectator,
+    /// Send a chat line to the room being spectated (spectators only)
+    SpectatorChat { text: String },
+    /// Ask to be seated as a player in the room being spectated; queued until a slot opens
+    RequestPromotion,
+    /// Queue for a slot in a full room; promoted automatically when a player leaves
+    JoinWaitingList {
+        game_name: String,
+        room_code: String,
+        player_name: String,
+    },
+    /// Remove a player from the room (requires the kick permission), optionally banning them
+    KickPlayer {
+        player_id: PlayerId,
+        /// Prevent the player from rejoining or spectating this room
+        #[serde(default)]
+        ban: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        reason: Option<String>,
+    },
+    /// Lift a room ban (requires the ban management permission)
+    UnbanPlayer { player_id: PlayerId },
+    /// Report a player or spectator in your room to the server operators,
+    /// answered with `ReportReceived`. The server takes no action itself
+    ReportPlayer {
+        target_player_id: PlayerId,
+        reason: ReportReason,
+        /// Free-text description, up to 1000 characters
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        details: Option<String>,
+    },
+    /// Change another player's role (creator or authority only)
+    AssignRole {
+        player_id: PlayerId,
+        role: PlayerRole,
+    },
+    /// Seat a spectator as a player or move a player to the spectators (authority only)
+    RequestRoleChange {
+        target: PlayerId,
+        new_role: ParticipantRole,
+    },
+    /// Set or remove room metadata entries (authority only); an empty value removes the key
+    SetRoomMetadata { entries: HashMap<String, String> },
+    /// Change the room's settings (authority only); omitted settings stay as they are
+    UpdateRoomSettings {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_players: Option<u8>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_spectators: Option<u8>,
+        /// Only let the room authority create invites
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        invites_authority_only: Option<bool>,
+    },
+    /// Answer a `JoinRequest` (authority only); accepted players are seated
+    /// even if the room is full
+    RespondToJoinRequest { player_id: PlayerId, accept: bool },
+    /// Confirm receipt of a broadcast marked with `ack_sample`
+    /// (only sent by clients that negotiated the `delivery-ack` capability)
+    DeliveryAck { ack_sample: u64 },
+    /// Queue for skill-based matchmaking; a room is created once enough players match
+    EnqueueMatchmaking {
+        game_name: String,
+        player_name: String,
+        skill_rating: f32,
+        /// Region to match within (defaults to the server's region)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        region_id: Option<String>,
+        desired_max_players: u8,
+    },
+    /// Cancel a pending matchmaking request
+    DequeueMatchmaking,
+    /// Create an invite token for your room, answered with `InviteCreated`.
+    /// Any player may send it unless the room allows only its authority
+    CreateInvite {
+        /// Joins the token allows, from 1 to 100
+        max_uses: u32,
+        /// Seconds the token stays valid, up to one week
+        ttl_secs: u64,
+    },
+    /// Join the room an invite token was created for
+    JoinByInvite { token: String, player_name: String },
+}
+
+impl ClientMessage {
+    /// The message's `type` tag, for logs.
+    pub const fn message_type(&self) -> &'static str {
+        match self {
+            Self::Authenticate { .. } => "Authenticate",
+            Self::JoinRoom { .. } => "JoinRoom",
+            Self::QuickJoin { .. } => "QuickJoin",
+            Self::LeaveRoom => "LeaveRoom",
+            Self::GameData { .. } => "GameData",
+            Self::AuthorityRequest { .. } => "AuthorityRequest",
+            Self::PlayerReady => "PlayerReady",
+            Self::ProvideConnectionInfo { .. } => "ProvideConnectionInfo",
+            Self::Ping => "Ping",
+            Self::LatencyPing { .. } => "LatencyPing",
+            Self::Heartbeat { .. } => "Heartbeat",
+            Self::TimeSync { .. } => "TimeSync",
+            Self::Reconnect { .. } => "Reconnect",
+            Self::JoinAsSpectator { .. } => "JoinAsSpectator",
+            Self::LeaveSpectator => "LeaveSpectator",
+            Self::SpectatorChat { .. } => "SpectatorChat",
+            Self::RequestPromotion => "RequestPromotion",
+            Self::JoinWaitingList { .. } => "JoinWaitingList",
+            Self::KickPlayer { .. } => "KickPlayer",
+            Self::UnbanPlayer { .. } => "UnbanPlayer",
+            Self::ReportPlayer { .. } => "ReportPlayer",
+            Self::AssignRole { .. } => "AssignRole",
+            Self::RequestRoleChange { .. } => "RequestRoleChange",
+            Self::SetRoomMetadata { .. } => "SetRoomMetadata",
+            Self::UpdateRoomSettings { .. } => "UpdateRoomSettings",
+            Self::RespondToJoinRequest { .. } => "RespondToJoinRequest",
+            Self::DeliveryAck { .. } => "DeliveryAck",
+            Self::EnqueueMatchmaking { .. } => "EnqueueMatchmaking",
+            Self::DequeueMatchmaking => "DequeueMatchmaking",
+            Self::CreateInvite { .. } => "CreateInvite",
+            Self::JoinByInvite { .. } => "JoinByInvite",
+        }
+    }
 }
 
 /// Payload for the RoomJoined server message.
@@ -76,17 +251,40 @@ pub struct RoomJoinedPayload {
     pub room_id: RoomId,
     pub room_code: String,
     pub player_id: PlayerId,
+    /// Your name in the room, which may differ from the requested name when
+    /// the server adds a suffix to keep it unique
+    #[serde(default)]
+    pub player_name: String,
     pub game_name: String,
     pub max_players: u8,
     pub supports_authority: bool,
     pub current_players: Vec<PlayerInfo>,
     pub is_authority: bool,
+    /// Your role in the room
+    #[serde(default)]
+    pub role: PlayerRole,
     pub lobby_state: LobbyState,
     pub ready_players: Vec<PlayerId>,
+    /// Lobby version of this snapshot; later lobby updates carry greater versions
+    #[serde(default)]
+    pub lobby_version: u64,
     pub relay_type: String,
     /// List of spectators currently watching (if any)
     #[serde(default)]
     pub current_spectators: Vec<SpectatorInfo>,
+    /// Token for `Reconnect` if this connection drops (absent when reconnection is disabled)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reconnection_token: Option<String>,
+    /// Relay session data, if one has been provisioned for the room
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relay_session: Option<serde_json::Value>,
+    /// Key-value metadata set by the room authority
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+    /// Your own peer connection entry carrying the ICE servers to use, present
+    /// when the server has `ice_servers` configured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peer_connection: Option<PeerConnectionInfo>,
 }
 
 /// Payload for the Reconnected server message.
@@ -103,12 +301,18 @@ pub struct ReconnectedPayload {
     pub is_authority: bool,
     pub lobby_state: LobbyState,
     pub ready_players: Vec<PlayerId>,
+    /// Lobby version of this snapshot; replayed `missed_events` keep their own
+    #[serde(default)]
+    pub lobby_version: u64,
     pub relay_type: String,
     /// List of spectators currently watching (if any)
     #[serde(default)]
     pub current_spectators: Vec<SpectatorInfo>,
     /// Events that occurred while disconnected
     pub missed_events: Vec<ServerMessage>,
+    /// Replacement reconnection token; the token used for this reconnect is no longer valid
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reconnection_token: Option<String>,
 }
 
 /// Payload for the SpectatorJoined server message.
@@ -124,6 +328,72 @@ pub struct SpectatorJoinedPayload {
     pub lobby_state: LobbyState,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reason: Option<SpectatorStateChangeReason>,
+    /// Who receives `SpectatorChat` in this room
+    #[serde(default)]
+    pub spectator_chat_mode: SpectatorChatMode,
+}
+
+/// Payload for the Error server message.
+///
+/// `code` goes on the wire as `error_code`, the same key the other failure
+/// messages use.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ErrorDetail {
+    /// Machine-readable error code
+    #[serde(rename = "error_code")]
+    pub code: ErrorCode,
+    /// Human-readable description, not meant for matching
+    pub message: String,
+    /// Milliseconds to wait before the same request can succeed. Only set for
+    /// codes whose failure clears with time (see the error code reference);
+    /// absent means retrying unchanged will keep failing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_after_ms: Option<u64>,
+    /// Code-specific details, e.g. the offending size for `MESSAGE_TOO_LARGE`.
+    /// Superseded by [`Self::details`] but still sent where it was before
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<serde_json::Value>,
+    /// Typed details for the code, in the shape given by
+    /// [`ErrorCode::details_kind`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<ErrorDetails>,
+}
+
+impl ErrorDetail {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            retry_after_ms: None,
+            context: None,
+            details: None,
+        }
+    }
+
+    /// Set [`Self::retry_after_ms`], rounding up to whole milliseconds.
+    #[must_use]
+    pub fn with_retry_after(mut self, retry_after: std::time::Duration) -> Self {
+        let millis = retry_after.as_micros().div_ceil(1000);
+        self.retry_after_ms = Some(u64::try_from(millis).unwrap_or(u64::MAX));
+        self
+    }
+
+    #[must_use]
+    pub fn with_context(mut self, context: serde_json::Value) -> Self {
+        self.context = Some(context);
+        self
+    }
+
+    #[must_use]
+    pub fn with_details(mut self, details: ErrorDetails) -> Self {
+        debug_assert!(
+            details.fits(&self.code),
+            "{details:?} is not the details shape for {:?}",
+            self.code
+        );
+        self.details = Some(details);
+        self
+    }
 }
 
 /// Message types sent from server to client
@@ -149,6 +419,17 @@ pub enum ServerMessage {
         /// Error code for programmatic handling
         error_code: ErrorCode,
     },
+    /// The client's SDK failed the compatibility check; the connection is
+    /// closed with `SDK_VERSION_UNSUPPORTED` right after
+    IncompatibleClient {
+        reason: String,
+        /// Oldest SDK version the server accepts for the client's platform
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        min_version: Option<String>,
+        /// Version to upgrade to, when one is recommended for the platform
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        recommended_version: Option<String>,
+    },
     /// Successfully joined a room (boxed to reduce enum size)
     RoomJoined(Box<RoomJoinedPayload>),
     /// Failed to join room
@@ -156,6 +437,12 @@ pub enum ServerMessage {
         reason: String,
         #[serde(skip_serializing_if = "Option::is_none")]
         error_code: Option<ErrorCode>,
+        /// Seconds to wait before retrying, when the failure is temporary
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        retry_after_secs: Option<u64>,
+        /// Typed details for `error_code`, as on [`ErrorDetail::details`]
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        details: Option<ErrorDetails>,
     },
     /// Successfully left room
     RoomLeft,
@@ -163,6 +450,9 @@ pub enum ServerMessage {
     PlayerJoined { player: PlayerInfo },
     /// Another player left the room
     PlayerLeft { player_id: PlayerId },
+    /// A player went unseen for longer than `server.stale_player_timeout`
+    /// and is being removed; their `PlayerLeft` follows
+    PlayerTimedOut { player_id: PlayerId },
     /// Game data from another player
     GameData {
         from_player: PlayerId,
@@ -193,13 +483,50 @@ pub enum ServerMessage {
         lobby_state: LobbyState,
         ready_players: Vec<PlayerId>,
         all_ready: bool,
+        /// Apply only if greater than the last lobby version seen for the room
+        #[serde(default)]
+        lobby_version: u64,
     },
     /// Game is starting with peer connection information
     GameStarting {
         peer_connections: Vec<PeerConnectionInfo>,
+        /// Relay session data provisioned for the room, if any
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        relay_session: Option<serde_json::Value>,
+        /// Lobby version of the finalization
+        #[serde(default)]
+        lobby_version: u64,
     },
     /// Pong response to ping
     Pong,
+    /// Response to a `LatencyPing`
+    LatencyPong {
+        /// The ping's `id`
+        id: u64,
+        /// The ping's `sent_at_ms`
+        echo_sent_at_ms: u64,
+        /// Server clock in milliseconds since the Unix epoch when the ping arrived
+        server_received_at_ms: u64,
+    },
+    /// A player in the room reported a round-trip time that differs from the
+    /// last one shared by at least `server.latency_update_threshold_ms`
+    PlayerLatencyUpdated { player_id: PlayerId, rtt_ms: u32 },
+    /// Response to a processed `Heartbeat`
+    HeartbeatAck {
+        /// The heartbeat's `client_timestamp`
+        client_timestamp: u64,
+        /// Server clock in milliseconds since the Unix epoch
+        server_timestamp: u64,
+    },
+    /// Response to a `TimeSync`, for an NTP-style offset and round-trip estimate
+    TimeSyncResult {
+        /// The request's `client_sent_at_ms`
+        client_sent_at_ms: u64,
+        /// Server clock in milliseconds since the Unix epoch when the request arrived
+        server_received_at_ms: u64,
+        /// Server clock in milliseconds since the Unix epoch when the result was queued
+        server_sent_at_ms: u64,
+    },
     /// Reconnection successful (boxed to reduce enum size)
     Reconnected(Box<ReconnectedPayload>),
     /// Reconnection failed
@@ -244,19 +571,234 @@ pub enum ServerMessage {
         #[serde(default)]
         current_spectators: Vec<SpectatorInfo>,
     },
+    /// Chat from a spectator, relayed per the room's spectator chat mode
+    SpectatorChatRelay {
+        spectator_id: PlayerId,
+        name: String,
+        text: String,
+    },
     /// Error message
-    Error {
-        message: String,
+    Error(ErrorDetail),
+    /// Current 1-based position on a full room's waiting list
+    WaitingListPosition { position: usize },
+    /// A slot opened up and the player was moved from the waiting list into the room.
+    /// Followed by the regular `RoomJoined` message.
+    WaitingListPromoted { room_id: RoomId, room_code: String },
+    /// A player asked to join your full room, which accepts overflow joins
+    /// (sent to the room authority). Answer with `RespondToJoinRequest`
+    /// before `server.join_request_timeout`
+    JoinRequest { player_id: PlayerId, name: String },
+    /// Current 1-based position in the room's spectator promotion queue.
+    /// Promotion is announced with `SpectatorLeft` (reason `promoted`) followed
+    /// by the regular `RoomJoined` message.
+    PromotionQueuePosition { position: usize },
+    /// You were removed from the room by its authority.
+    /// Followed by the regular `RoomLeft` message.
+    Kicked {
+        room_id: RoomId,
+        banned: bool,
         #[serde(skip_serializing_if = "Option::is_none")]
-        error_code: Option<ErrorCode>,
+        reason: Option<String>,
+    },
+    /// A room ban was lifted (sent to the player that requested it)
+    PlayerUnbanned { player_id: PlayerId },
+    /// Response to `ReportPlayer`: the report was recorded for review under `report_id`
+    ReportReceived {
+        report_id: Uuid,
+        target_player_id: PlayerId,
+    },
+    /// A player's role changed (broadcast to the room)
+    PlayerRoleChanged {
+        player_id: PlayerId,
+        role: PlayerRole,
+    },
+    /// A participant moved between players and spectators (broadcast to the room).
+    /// Sent after the regular leave and join messages for the move.
+    RoleChanged {
+        player_id: PlayerId,
+        new_role: ParticipantRole,
+    },
+    /// The room's metadata changed (broadcast to the room with the full metadata)
+    RoomMetadataUpdated {
+        metadata: HashMap<String, String>,
+        updated_by: PlayerId,
+    },
+    /// The room's settings changed (broadcast to the room with the full settings)
+    RoomSettingsUpdated {
+        max_players: u8,
+        /// `None` means unlimited spectators
+        max_spectators: Option<u8>,
+        /// Only the room authority may create invites
+        #[serde(default)]
+        invites_authority_only: bool,
+        updated_by: PlayerId,
+    },
+    /// Matchmaking grouped you with other players into a new room.
+    /// Followed by the regular `RoomJoined` message.
+    MatchFound { room_code: String, room_id: RoomId },
+    /// The room will be cleaned up for emptiness or inactivity unless there is
+    /// activity first. Sent at most once per expiry cycle.
+    RoomExpirySoon { seconds_remaining: u32 },
+    /// Recent game data relayed in the room, oldest first. Sent right after
+    /// `RoomJoined` or `Reconnected` when history replay is enabled and the
+    /// room has history.
+    HistoryReplay { messages: Vec<HistoricalMessage> },
+    /// The room was shut down by the server (e.g. `"closed_by_admin"`).
+    /// Recipients are no longer in the room and cannot reconnect to it.
+    RoomClosed { reason: String },
+    /// Response to `CreateInvite`. Share `token` with players who should
+    /// join with `JoinByInvite`
+    InviteCreated {
+        token: String,
+        room_code: String,
+        max_uses: u32,
+        /// When the token stops working, in milliseconds since the Unix epoch
+        expires_at_ms: u64,
+    },
+    /// An operator notice for every connected player, such as an upcoming
+    /// maintenance window. Players connecting before it expires get it too.
+    ServerAnnouncement {
+        message: String,
+        severity: AnnouncementSeverity,
+        /// When the notice stops applying, in milliseconds since the Unix epoch
+        expires_at_ms: u64,
     },
+    /// A broadcast selected for delivery sampling. Serialized as the wrapped
+    /// message with an extra top-level `ack_sample` field.
+    #[serde(untagged)]
+    AckSampled(AckSampledMessage),
+}
+
+impl ServerMessage {
+    /// The message's `type` tag, for logs and metrics.
+    pub const fn message_type(&self) -> &'static str {
+        match self {
+            Self::Authenticated { .. } => "Authenticated",
+            Self::ProtocolInfo(_) => "ProtocolInfo",
+            Self::AuthenticationError { .. } => "AuthenticationError",
+            Self::IncompatibleClient { .. } => "IncompatibleClient",
+            Self::RoomJoined(_) => "RoomJoined",
+            Self::RoomJoinFailed { .. } => "RoomJoinFailed",
+            Self::RoomLeft => "RoomLeft",
+            Self::PlayerJoined { .. } => "PlayerJoined",
+            Self::PlayerLeft { .. } => "PlayerLeft",
+            Self::PlayerTimedOut { .. } => "PlayerTimedOut",
+            Self::GameData { .. } => "GameData",
+            Self::GameDataBinary { .. } => "GameDataBinary",
+            Self::AuthorityChanged { .. } => "AuthorityChanged",
+            Self::AuthorityResponse { .. } => "AuthorityResponse",
+            Self::LobbyStateChanged { .. } => "LobbyStateChanged",
+            Self::GameStarting { .. } => "GameStarting",
+            Self::Pong => "Pong",
+            Self::LatencyPong { .. } => "LatencyPong",
+            Self::PlayerLatencyUpdated { .. } => "PlayerLatencyUpdated",
+            Self::HeartbeatAck { .. } => "HeartbeatAck",
+            Self::TimeSyncResult { .. } => "TimeSyncResult",
+            Self::Reconnected(_) => "Reconnected",
+            Self::ReconnectionFailed { .. } => "ReconnectionFailed",
+            Self::PlayerReconnected { .. } => "PlayerReconnected",
+            Self::SpectatorJoined(_) => "SpectatorJoined",
+            Self::SpectatorJoinFailed { .. } => "SpectatorJoinFailed",
+            Self::SpectatorLeft { .. } => "SpectatorLeft",
+            Self::NewSpectatorJoined { .. } => "NewSpectatorJoined",
+            Self::SpectatorDisconnected { .. } => "SpectatorDisconnected",
+            Self::SpectatorChatRelay { .. } => "SpectatorChatRelay",
+            Self::Error(_) => "Error",
+            Self::WaitingListPosition { .. } => "WaitingListPosition",
+            Self::WaitingListPromoted { .. } => "WaitingListPromoted",
+            Self::JoinRequest { .. } => "JoinRequest",
+            Self::PromotionQueuePosition { .. } => "PromotionQueuePosition",
+            Self::Kicked { .. } => "Kicked",
+            Self::PlayerUnbanned { .. } => "PlayerUnbanned",
+            Self::ReportReceived { .. } => "ReportReceived",
+            Self::PlayerRoleChanged { .. } => "PlayerRoleChanged",
+            Self::RoleChanged { .. } => "RoleChanged",
+            Self::RoomMetadataUpdated { .. } => "RoomMetadataUpdated",
+            Self::RoomSettingsUpdated { .. } => "RoomSettingsUpdated",
+            Self::MatchFound { .. } => "MatchFound",
+            Self::RoomExpirySoon { .. } => "RoomExpirySoon",
+            Self::HistoryReplay { .. } => "HistoryReplay",
+            Self::RoomClosed { .. } => "RoomClosed",
+            Self::InviteCreated { .. } => "InviteCreated",
+            Self::ServerAnnouncement { .. } => "ServerAnnouncement",
+            Self::AckSampled(_) => "AckSampled",
+        }
+    }
+}
+
+/// Broadcast message carrying a delivery acknowledgement sample id.
+#[derive(Debug, Clone)]
+pub struct AckSampledMessage {
+    pub ack_sample: u64,
+    pub message: Arc<ServerMessage>,
+}
+
+impl Serialize for AckSampledMessage {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut value =
+            serde_json::to_value(self.message.as_ref()).map_err(serde::ser::Error::custom)?;
+        if let serde_json::Value::Object(ref mut fields) = value {
+            fields.insert("ack_sample".to_string(), self.ack_sample.into());
+        }
+        value.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for AckSampledMessage {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut value = serde_json::Value::deserialize(deserializer)?;
+        let ack_sample = value
+            .as_object_mut()
+            .and_then(|fields| fields.remove("ack_sample"))
+            .and_then(|sample| sample.as_u64())
+            .ok_or_else(|| serde::de::Error::missing_field("ack_sample"))?;
+        let message = ServerMessage::deserialize(value).map_err(serde::de::Error::custom)?;
+        Ok(Self {
+            ack_sample,
+            message: Arc::new(message),
+        })
+    }
+}
+
+/// Envelope encoding for every message on a connection, chosen during the
+/// WebSocket upgrade.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum MessageEncoding {
+    /// JSON envelopes over text frames.
+    #[default]
+    Json,
+    /// MessagePack envelopes (named fields) over binary frames.
+    MsgPack,
+    /// Client frames are archived [`ClientFrame`](super::ClientFrame)s over
+    /// binary frames; server messages go out as MessagePack.
+    Rkyv,
+}
+
+impl MessageEncoding {
+    /// `Sec-WebSocket-Protocol` value a client offers to receive MessagePack.
+    pub const MSGPACK_SUBPROTOCOL: &'static str = "signal-fish-msgpack";
+    /// Generic name also accepted for [`Self::MSGPACK_SUBPROTOCOL`].
+    pub const MESSAGEPACK_SUBPROTOCOL: &'static str = "messagepack";
+    /// `Sec-WebSocket-Protocol` value a client offers to send rkyv frames.
+    pub const RKYV_SUBPROTOCOL: &'static str = "signal-fish-rkyv";
+
+    /// Whether server messages go out as binary MessagePack frames.
+    pub fn is_binary(self) -> bool {
+        matches!(self, Self::MsgPack | Self::Rkyv)
+    }
 }
 
 /// Custom serde module for `bytes::Bytes` serialization
 ///
 /// This provides efficient serialization that works with both JSON (base64-like)
 /// and binary formats (direct bytes).
-mod bytes_serde {
+pub(super) mod bytes_serde {
     use bytes::Bytes;
     use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
@@ -1,7 +1,9 @@
 // Protocol module: Message types, validation, and room state management
 
+pub mod client_frames;
 pub mod error_codes;
 pub mod messages;
+pub mod permissions;
 pub mod room_codes;
 pub mod room_state;
 pub mod types;
@@ -10,13 +12,17 @@ pub mod validation;
 // Re-export everything for backward compatibility
 // This allows external code to use `use crate::protocol::*`
 
+// From client_frames
+pub use client_frames::{decode_client_frame, ClientFrame, ClientFrameError, DecodedClientFrame};
+
 // From error_codes
-pub use error_codes::ErrorCode;
+pub use error_codes::{ErrorCode, ErrorDetails, ErrorDetailsKind, QuotaKind, ValidationConstraint};
 
 // From types
 pub use types::{
-    ConnectionInfo, GameDataEncoding, PeerConnectionInfo, PlayerId, PlayerInfo,
-    PlayerNameRulesPayload, ProtocolInfoPayload, RateLimitInfo, RelayTransport, RoomId,
+    AnnouncementSeverity, ConnectionInfo, GameDataEncoding, HeartbeatEcho, IceServer,
+    ParticipantRole, PeerConnectionInfo, PlayerId, PlayerInfo, PlayerNameRulesPayload,
+    ProtocolInfoPayload, RateLimitInfo, RelayTransport, ReportReason, RoomId, SpectatorChatMode,
     SpectatorInfo, SpectatorStateChangeReason, DEFAULT_MAX_GAME_NAME_LENGTH,
     DEFAULT_MAX_PLAYERS_LIMIT, DEFAULT_MAX_PLAYER_NAME_LENGTH, DEFAULT_REGION_ID,
     DEFAULT_ROOM_CODE_LENGTH,
@@ -24,21 +30,25 @@ pub use types::{
 
 // From messages
 pub use messages::{
-    ClientMessage, ReconnectedPayload, RoomJoinedPayload, ServerMessage, SpectatorJoinedPayload,
+    AckSampledMessage, ClientMessage, ErrorDetail, MessageEncoding, ReconnectedPayload,
+    RoomJoinedPayload, ServerMessage, SpectatorJoinedPayload,
 };
 
+// From permissions
+pub use permissions::{Permissions, PlayerRole, PrivilegedAction};
+
 // From room_state
-pub use room_state::{LobbyState, Room};
+pub use room_state::{HistoricalMessage, LobbyState, Room, RoomBan, RoomSummary, MAX_ROOM_BANS};
 
 #[cfg(test)]
 mod tests {
     use super::room_codes;
     use super::validation::{
         validate_game_name_with_config, validate_player_name_with_config,
-        validate_room_code_with_config,
+        validate_room_code_for_game, validate_room_code_with_config,
     };
     use super::*;
-    use crate::config::ProtocolConfig;
+    use crate::config::{GameConfig, GameOverrideConfig, ProtocolConfig};
     use proptest::prelude::*;
     use uuid::Uuid;
 
@@ -59,6 +69,47 @@ mod tests {
         assert!(room.can_join());
     }
 
+    #[test]
+    fn test_room_summary() {
+        let mut room = Room::new(
+            "test_game".to_string(),
+            "ABC123".to_string(),
+            4,
+            true,
+            "matchbox".to_string(),
+        );
+        let player_id = Uuid::new_v4();
+        assert!(room.add_player(PlayerInfo {
+            id: player_id,
+            name: "Player1".to_string(),
+            is_authority: false,
+            role: PlayerRole::Member,
+            is_ready: false,
+            connected_at: chrono::Utc::now(),
+            connection_info: None,
+            region_id: types::DEFAULT_REGION_ID.to_string(),
+        }));
+        assert!(room.add_spectator(SpectatorInfo {
+            id: Uuid::new_v4(),
+            name: "Watcher".to_string(),
+            connected_at: chrono::Utc::now(),
+        }));
+
+        let summary = room.to_summary();
+        assert_eq!(summary.id, room.id);
+        assert_eq!(summary.game_name, "test_game");
+        assert_eq!(summary.code, "ABC123");
+        assert_eq!(summary.current_players, 1);
+        assert_eq!(summary.max_players, 4);
+        assert_eq!(summary.spectator_count, 1);
+        assert_eq!(summary.lobby_state, LobbyState::Waiting);
+        assert_eq!(summary.authority_player, room.authority_player);
+        assert_eq!(summary.region_id, room.region_id);
+        assert_eq!(summary.relay_type, "matchbox");
+        assert_eq!(summary.created_at, room.created_at);
+        assert_eq!(summary.last_activity, room.last_activity);
+    }
+
     #[test]
     fn test_player_management() {
         let mut room = Room::new(
@@ -73,6 +124,7 @@ mod tests {
             id: Uuid::new_v4(),
             name: "Player1".to_string(),
             is_authority: false,
+            role: PlayerRole::Member,
             is_ready: false,
             connected_at: chrono::Utc::now(),
             connection_info: None,
@@ -83,6 +135,7 @@ mod tests {
             id: Uuid::new_v4(),
             name: "Player2".to_string(),
             is_authority: false,
+            role: PlayerRole::Member,
             is_ready: false,
             connected_at: chrono::Utc::now(),
             connection_info: None,
@@ -97,6 +150,7 @@ mod tests {
             id: Uuid::new_v4(),
             name: "Player3".to_string(),
             is_authority: false,
+            role: PlayerRole::Member,
             is_ready: false,
             connected_at: chrono::Utc::now(),
             connection_info: None,
@@ -121,6 +175,7 @@ mod tests {
             id: player_id,
             name: "Authority Player".to_string(),
             is_authority: false,
+            role: PlayerRole::Member,
             is_ready: false,
             connected_at: chrono::Utc::now(),
             connection_info: None,
@@ -148,6 +203,7 @@ mod tests {
             id: player_id,
             name: "Authority Player".to_string(),
             is_authority: false,
+            role: PlayerRole::Member,
             is_ready: false,
             connected_at: chrono::Utc::now(),
             connection_info: None,
@@ -189,6 +245,69 @@ mod tests {
         assert!(validate_max_players(101).is_err());
     }
 
+    #[test]
+    fn application_ids_must_name_an_authorized_app() {
+        use crate::config::AppAuthEntry;
+        use validation::{validate_application_id, ValidationError};
+
+        let apps = vec![AppAuthEntry {
+            app_id: "known-game".to_string(),
+            app_secret: "secret".to_string(),
+            app_name: "Known Game".to_string(),
+            max_rooms: None,
+            max_rooms_per_app: None,
+            max_players_per_app: None,
+            max_rooms_per_hour: None,
+            max_players_per_room: Some(6),
+            rate_limit_per_minute: Some(120),
+            api_keys: Vec::new(),
+        }];
+        let known = crate::auth::AppInfo::from_entry(&apps[0]);
+
+        assert!(validate_application_id(None, &apps).unwrap().is_none());
+        let info = validate_application_id(Some(known.id), &apps)
+            .unwrap()
+            .expect("the configured app is found");
+        assert_eq!(info.id, known.id);
+        assert_eq!(info.name, "Known Game");
+        assert_eq!(info.max_players_per_room, Some(6));
+        assert_eq!(info.rate_limit_per_minute, Some(120));
+        assert_eq!(
+            validate_application_id(Some(Uuid::new_v4()), &apps).unwrap_err(),
+            ValidationError::UnknownApplicationId
+        );
+        assert_eq!(
+            validate_application_id(Some(known.id), &[]).unwrap_err(),
+            ValidationError::UnknownApplicationId
+        );
+    }
+
+    #[test]
+    fn message_type_names_match_the_wire_tag() {
+        let server_messages = [
+            ServerMessage::RoomLeft,
+            ServerMessage::PlayerLeft {
+                player_id: Uuid::new_v4(),
+            },
+            ServerMessage::Error(ErrorDetail::new(ErrorCode::InvalidInput, "bad input")),
+        ];
+        for message in &server_messages {
+            let wire = serde_json::to_value(message).unwrap();
+            assert_eq!(wire["type"], message.message_type());
+        }
+
+        let client_messages = [
+            ClientMessage::Ping,
+            ClientMessage::TimeSync {
+                client_sent_at_ms: 1,
+            },
+        ];
+        for message in &client_messages {
+            let wire = serde_json::to_value(message).unwrap();
+            assert_eq!(wire["type"], message.message_type());
+        }
+    }
+
     #[test]
     fn player_name_validation_obeys_config_overrides() {
         use validation::validate_player_name_with_config;
@@ -205,6 +324,115 @@ mod tests {
         assert!(validate_player_name_with_config("Alert!", &config).is_ok());
     }
 
+    #[test]
+    fn relay_type_validation_accepts_standard_and_registered_types() {
+        use crate::config::RelayTypeConfig;
+        use validation::validate_relay_type;
+
+        let mut config = RelayTypeConfig::default();
+        for standard in ["tcp", "UDP", "websocket", "auto"] {
+            assert!(validate_relay_type(standard, &config).is_ok(), "{standard}");
+        }
+        assert!(validate_relay_type(&config.default_relay_type.clone(), &config).is_ok());
+        assert!(validate_relay_type("steam-p2p", &config).is_err());
+
+        config.custom_relay_types = vec!["steam-p2p".to_string()];
+        assert!(validate_relay_type("steam-p2p", &config).is_ok());
+        assert!(validate_relay_type("Steam-P2P", &config).is_err());
+
+        // An explicit allow list narrows the configured types, but neither the
+        // standard transports nor registered custom types
+        config.allowed_relay_types = vec!["matchbox".to_string()];
+        assert!(validate_relay_type("udp", &config).is_ok());
+        assert!(validate_relay_type("steam-p2p", &config).is_ok());
+        assert!(validate_relay_type("matchbox", &config).is_ok());
+        let err = validate_relay_type("webrtc-mesh", &config).unwrap_err();
+        assert!(err.contains("webrtc-mesh"), "{err}");
+    }
+
+    #[test]
+    fn player_name_banned_words_match_by_substring_or_word() {
+        use crate::config::BannedWordMatch;
+        use validation::{validate_player_name_with_config, PlayerNameError};
+
+        let mut config = ProtocolConfig::default();
+        config.player_name_validation.banned_words =
+            vec!["Cunt".to_string(), "bad guy".to_string()];
+        let check =
+            |name: &str, config: &ProtocolConfig| validate_player_name_with_config(name, config);
+
+        // Substring matching ignores case, separators and full-width forms
+        assert_eq!(check("CUNTfan", &config), Err(PlayerNameError::NotAllowed));
+        assert_eq!(check("c-u-n-t", &config), Err(PlayerNameError::NotAllowed));
+        assert_eq!(check("ｃｕｎｔ", &config), Err(PlayerNameError::NotAllowed));
+        assert_eq!(
+            check("Scunthorpe", &config),
+            Err(PlayerNameError::NotAllowed)
+        );
+        assert_eq!(
+            check("TheBadGuy", &config),
+            Err(PlayerNameError::NotAllowed)
+        );
+        assert!(check("Goodie", &config).is_ok());
+
+        // Word matching spares names that only contain a banned word
+        config.player_name_validation.banned_word_match = BannedWordMatch::Word;
+        assert!(check("Scunthorpe", &config).is_ok());
+        assert!(check("TheBadGuy", &config).is_ok());
+        assert_eq!(check("cunt_99", &config), Err(PlayerNameError::NotAllowed));
+        assert_eq!(
+            check("The Bad Guy", &config),
+            Err(PlayerNameError::NotAllowed)
+        );
+        assert_eq!(check("ｃｕｎｔ", &config), Err(PlayerNameError::NotAllowed));
+
+        // Format errors keep their own variant and the caller's error code
+        let invalid = check("", &config).unwrap_err();
+        assert!(matches!(invalid, PlayerNameError::Invalid(_)));
+        assert_eq!(
+            invalid.error_code(ErrorCode::InvalidInput),
+            ErrorCode::InvalidInput
+        );
+        assert_eq!(
+            PlayerNameError::NotAllowed.error_code(ErrorCode::InvalidInput),
+            ErrorCode::PlayerNameNotAllowed
+        );
+    }
+
+    #[test]
+    fn room_metadata_entries_apply_within_limits() {
+        use std::collections::HashMap;
+        use validation::{apply_room_metadata_entries, MAX_ROOM_METADATA_KEYS};
+
+        let mut metadata = HashMap::from([("map".to_string(), "dust".to_string())]);
+        let update = HashMap::from([
+            ("map".to_string(), String::new()),
+            ("motd".to_string(), "welcome".to_string()),
+        ]);
+        assert!(apply_room_metadata_entries(&mut metadata, &update).is_ok());
+        assert_eq!(
+            metadata,
+            HashMap::from([("motd".to_string(), "welcome".to_string())])
+        );
+
+        let too_many: HashMap<String, String> = (0..MAX_ROOM_METADATA_KEYS)
+            .map(|i| (format!("k{i}"), "v".to_string()))
+            .collect();
+        assert!(apply_room_metadata_entries(&mut metadata, &too_many).is_err());
+        assert_eq!(
+            metadata.len(),
+            1,
+            "rejected updates leave metadata untouched"
+        );
+
+        let blank_key = HashMap::from([(" ".to_string(), "v".to_string())]);
+        assert!(apply_room_metadata_entries(&mut metadata, &blank_key).is_err());
+        let long_value = HashMap::from([("motd".to_string(), "é".repeat(257))]);
+        assert!(apply_room_metadata_entries(&mut metadata, &long_value).is_err());
+        let max_value = HashMap::from([("motd".to_string(), "é".repeat(256))]);
+        assert!(apply_room_metadata_entries(&mut metadata, &max_value).is_ok());
+    }
+
     #[test]
     fn test_player_name_uniqueness() {
         use std::collections::HashMap;
@@ -219,6 +447,7 @@ mod tests {
                 id: Uuid::new_v4(),
                 name: "Player1".to_string(),
                 is_authority: false,
+                role: PlayerRole::Member,
                 is_ready: false,
                 connected_at: chrono::Utc::now(),
                 connection_info: None,
@@ -237,6 +466,45 @@ mod tests {
         assert!(validate_player_name_uniqueness("PLAYER1", &players).is_err());
     }
 
+    #[test]
+    fn duplicate_player_names_get_lowest_free_suffix() {
+        use std::collections::HashMap;
+        use validation::suffix_duplicate_player_name;
+
+        let players: HashMap<PlayerId, PlayerInfo> = ["Alex", "alex (2)", "Alex (4)"]
+            .into_iter()
+            .map(|name| {
+                let id = Uuid::new_v4();
+                let player = PlayerInfo {
+                    id,
+                    name: name.to_string(),
+                    is_authority: false,
+                    role: PlayerRole::Member,
+                    is_ready: false,
+                    connected_at: chrono::Utc::now(),
+                    connection_info: None,
+                    region_id: types::DEFAULT_REGION_ID.to_string(),
+                };
+                (id, player)
+            })
+            .collect();
+
+        assert_eq!(
+            suffix_duplicate_player_name("Alex", &players, 32).as_deref(),
+            Some("Alex (3)")
+        );
+        // The base is truncated, without leaving trailing spaces, to fit the limit
+        assert_eq!(
+            suffix_duplicate_player_name("Alex Jones", &players, 9).as_deref(),
+            Some("Alex (3)")
+        );
+        assert_eq!(
+            suffix_duplicate_player_name("Zoë", &players, 7).as_deref(),
+            Some("Zo (2)")
+        );
+        assert_eq!(suffix_duplicate_player_name("Alex", &players, 4), None);
+    }
+
     #[test]
     fn test_room_code_generation() {
         use room_codes::*;
@@ -281,6 +549,7 @@ mod tests {
             id: player1_id,
             name: "Player1".to_string(),
             is_authority: true, // First player should get authority
+            role: PlayerRole::Member,
             is_ready: false,
             connected_at: chrono::Utc::now(),
             connection_info: None,
@@ -299,6 +568,7 @@ mod tests {
             id: player2_id,
             name: "Player2".to_string(),
             is_authority: false,
+            role: PlayerRole::Member,
             is_ready: false,
             connected_at: chrono::Utc::now(),
             connection_info: None,
@@ -330,6 +600,7 @@ mod tests {
             id: player1_id,
             name: "Player1".to_string(),
             is_authority: true,
+            role: PlayerRole::Member,
             is_ready: false,
             connected_at: chrono::Utc::now(),
             connection_info: None,
@@ -339,6 +610,7 @@ mod tests {
             id: player2_id,
             name: "Player2".to_string(),
             is_authority: false,
+            role: PlayerRole::Member,
             is_ready: false,
             connected_at: chrono::Utc::now(),
             connection_info: None,
@@ -385,6 +657,7 @@ mod tests {
             id: player1_id,
             name: "AuthorityPlayer".to_string(),
             is_authority: true,
+            role: PlayerRole::Member,
             is_ready: false,
             connected_at: chrono::Utc::now(),
             connection_info: None,
@@ -394,6 +667,7 @@ mod tests {
             id: player2_id,
             name: "RegularPlayer".to_string(),
             is_authority: false,
+            role: PlayerRole::Member,
             is_ready: false,
             connected_at: chrono::Utc::now(),
             connection_info: None,
@@ -431,6 +705,7 @@ mod tests {
             id: player_id,
             name: "Player".to_string(),
             is_authority: false,
+            role: PlayerRole::Member,
             is_ready: false,
             connected_at: chrono::Utc::now(),
             connection_info: None,
@@ -458,6 +733,7 @@ mod tests {
             id: player2_id,
             name: "Player2".to_string(),
             is_authority: false,
+            role: PlayerRole::Member,
             is_ready: false,
             connected_at: chrono::Utc::now(),
             connection_info: None,
@@ -493,6 +769,7 @@ mod tests {
             id: player1_id,
             name: "Player1".to_string(),
             is_authority: true,
+            role: PlayerRole::Member,
             is_ready: false,
             connected_at: chrono::Utc::now(),
             connection_info: None,
@@ -511,6 +788,7 @@ mod tests {
             id: player2_id,
             name: "Player2".to_string(),
             is_authority: false,
+            role: PlayerRole::Member,
             is_ready: false,
             connected_at: chrono::Utc::now(),
             connection_info: None,
@@ -542,6 +820,152 @@ mod tests {
         assert!(room.is_finalized());
     }
 
+    #[test]
+    fn test_error_detail_wire_format() {
+        let limited = ServerMessage::Error(
+            ErrorDetail::new(ErrorCode::RateLimitExceeded, "Slow down")
+                .with_retry_after(std::time::Duration::from_micros(1_500_001)),
+        );
+        let json = serde_json::to_value(&limited).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "type": "Error",
+                "data": {
+                    "error_code": "RATE_LIMIT_EXCEEDED",
+                    "message": "Slow down",
+                    "retry_after_ms": 1501,
+                }
+            })
+        );
+
+        let detail = ErrorDetail::new(ErrorCode::MessageTooLarge, "Too big")
+            .with_context(serde_json::json!({ "size": 70000, "max_size": 65536 }))
+            .with_details(ErrorDetails::MessageTooLarge {
+                size: 70000,
+                max_size: 65536,
+            });
+        let round_trip: ServerMessage = serde_json::from_value(
+            serde_json::to_value(ServerMessage::Error(detail.clone())).unwrap(),
+        )
+        .unwrap();
+        match round_trip {
+            ServerMessage::Error(parsed) => assert_eq!(parsed, detail),
+            other => panic!("expected Error, got {other:?}"),
+        }
+
+        // Payloads from servers that predate `details` still parse
+        let legacy: ServerMessage = serde_json::from_value(serde_json::json!({
+            "type": "RoomJoinFailed",
+            "data": { "reason": "Room is full", "error_code": "ROOM_FULL" }
+        }))
+        .unwrap();
+        assert!(matches!(
+            legacy,
+            ServerMessage::RoomJoinFailed { details: None, .. }
+        ));
+    }
+
+    #[test]
+    fn test_room_join_failed_carries_typed_details() {
+        let failed = ServerMessage::RoomJoinFailed {
+            reason: "Join attempt rate limit exceeded. Try again in 3 seconds.".to_string(),
+            error_code: Some(ErrorCode::RateLimitExceeded),
+            retry_after_secs: Some(3),
+            details: Some(ErrorDetails::RateLimited {
+                retry_after_secs: 3,
+                limit: 30,
+                remaining: 0,
+            }),
+        };
+        let json = serde_json::to_value(&failed).unwrap();
+        assert_eq!(
+            json["data"]["details"],
+            serde_json::json!({
+                "kind": "rate_limited",
+                "retry_after_secs": 3,
+                "limit": 30,
+                "remaining": 0
+            })
+        );
+    }
+
+    #[test]
+    fn test_validation_details_name_the_broken_rule() {
+        let config = ProtocolConfig::default();
+        assert_eq!(
+            validation::text_field_details("game_name", "", config.max_game_name_length),
+            ErrorDetails::validation("game_name", ValidationConstraint::Required, None)
+        );
+        assert_eq!(
+            validation::text_field_details("game_name", &"g".repeat(65), 64),
+            ErrorDetails::validation("game_name", ValidationConstraint::MaxLength, Some(64))
+        );
+        assert_eq!(
+            validation::text_field_details("game_name", "bad!", 64),
+            ErrorDetails::validation("game_name", ValidationConstraint::AllowedCharacters, None)
+        );
+        assert_eq!(
+            validation::room_code_details("ABC", 6),
+            ErrorDetails::validation("room_code", ValidationConstraint::ExactLength, Some(6))
+        );
+        assert_eq!(
+            validation::room_code_details("ABC-12", 6),
+            ErrorDetails::validation("room_code", ValidationConstraint::AllowedCharacters, None)
+        );
+    }
+
+    #[test]
+    fn test_lobby_version_increases_with_every_lobby_update() {
+        let mut room = Room::new(
+            "version_game".to_string(),
+            "VER001".to_string(),
+            2,
+            true,
+            "matchbox".to_string(),
+        );
+        let player_ids = [Uuid::new_v4(), Uuid::new_v4()];
+        for id in player_ids {
+            room.add_player(PlayerInfo {
+                id,
+                name: id.to_string(),
+                is_authority: false,
+                role: PlayerRole::Member,
+                is_ready: false,
+                connected_at: chrono::Utc::now(),
+                connection_info: None,
+                region_id: types::DEFAULT_REGION_ID.to_string(),
+            });
+        }
+        assert_eq!(room.lobby_version, 0);
+
+        let mut versions = Vec::new();
+        assert!(room.enter_lobby());
+        versions.push(room.lobby_version);
+        assert!(room.set_player_ready(&player_ids[0], true));
+        versions.push(room.lobby_version);
+        assert!(room.return_to_waiting());
+        assert!(room.ready_players.is_empty());
+        assert!(!room.players[&player_ids[0]].is_ready);
+        versions.push(room.lobby_version);
+        assert!(room.enter_lobby());
+        versions.push(room.lobby_version);
+        for id in &player_ids {
+            assert!(room.set_player_ready(id, true));
+            versions.push(room.lobby_version);
+        }
+        assert!(room.finalize_game());
+        versions.push(room.lobby_version);
+        assert!(versions.windows(2).all(|pair| pair[0] < pair[1]));
+
+        // Rejected transitions leave the version alone
+        let finalized = room.lobby_version;
+        assert!(!room.return_to_waiting());
+        assert!(!room.enter_lobby());
+        assert!(!room.set_player_ready(&player_ids[0], false));
+        assert_eq!(room.lobby_version, finalized);
+    }
+
     #[test]
     fn test_lobby_ready_state_changes() {
         let mut room = Room::new(
@@ -562,6 +986,7 @@ mod tests {
                 id,
                 name: name.to_string(),
                 is_authority: id == player1_id,
+                role: PlayerRole::Member,
                 is_ready: false,
                 connected_at: chrono::Utc::now(),
                 connection_info: None,
@@ -606,6 +1031,7 @@ mod tests {
             id: player1_id,
             name: "Authority".to_string(),
             is_authority: true,
+            role: PlayerRole::Member,
             is_ready: false,
             connected_at: chrono::Utc::now(),
             connection_info: None,
@@ -616,6 +1042,7 @@ mod tests {
             id: player2_id,
             name: "Player".to_string(),
             is_authority: false,
+            role: PlayerRole::Member,
             is_ready: false,
             connected_at: chrono::Utc::now(),
             connection_info: None,
@@ -654,6 +1081,7 @@ mod tests {
                 id,
                 name: format!("Player{id}"),
                 is_authority: false,
+                role: PlayerRole::Member,
                 is_ready: false,
                 connected_at: chrono::Utc::now(),
                 connection_info: None,
@@ -803,4 +1231,122 @@ mod tests {
         let code = room_codes::generate_region_room_code(&config, Some("LONGPREFIX"));
         assert_eq!(code.len(), 4);
     }
+
+    #[test]
+    fn privileged_messages_require_role_permissions() {
+        use super::permissions::{effective_permissions, PERMISSION_TABLE};
+
+        let target = Uuid::new_v4();
+        let messages = [
+            ClientMessage::KickPlayer {
+                player_id: target,
+                ban: true,
+                reason: None,
+            },
+            ClientMessage::UnbanPlayer { player_id: target },
+            ClientMessage::AssignRole {
+                player_id: target,
+                role: PlayerRole::Moderator,
+            },
+            ClientMessage::GameData {
+                data: serde_json::json!({}),
+            },
+            ClientMessage::AuthorityRequest {
+                become_authority: true,
+            },
+            ClientMessage::SetRoomMetadata {
+                entries: std::collections::HashMap::new(),
+            },
+        ];
+        // Allowed per message above, for each role without room authority
+        let matrix = [
+            (PlayerRole::Creator, [false, false, true, true, true, false]),
+            (PlayerRole::Authority, [true, true, true, true, true, true]),
+            (
+                PlayerRole::Moderator,
+                [true, true, false, true, true, false],
+            ),
+            (PlayerRole::Member, [false, false, false, true, true, false]),
+        ];
+
+        for (role, expected) in matrix {
+            for (message, allowed) in messages.iter().zip(expected) {
+                let permitted = message.privileged_action().is_none_or(|action| {
+                    effective_permissions(role, false).contains(action.required_permission())
+                });
+                assert_eq!(permitted, allowed, "{role:?} sending {message:?}");
+                // Holding room authority grants every privileged action
+                assert!(message.privileged_action().is_none_or(|action| {
+                    effective_permissions(role, true).contains(action.required_permission())
+                }));
+            }
+        }
+
+        for (index, (action, permission)) in PERMISSION_TABLE.iter().enumerate() {
+            assert_eq!(*action as usize, index);
+            assert_eq!(action.required_permission(), *permission);
+        }
+    }
+
+    fn numeric_overrides() -> GameOverrideConfig {
+        GameOverrideConfig::from([(
+            "pin-game".to_string(),
+            GameConfig {
+                room_code_length: Some(8),
+                room_code_charset: Some("0123456789".to_string()),
+                room_code_prefix: None,
+                ..GameConfig::default()
+            },
+        )])
+    }
+
+    #[test]
+    fn game_room_code_uses_defaults_without_override() {
+        let config = ProtocolConfig::default();
+        let code = room_codes::generate_room_code_for_game("other", &config, &numeric_overrides());
+        assert_eq!(code.len(), config.room_code_length);
+        assert!(validate_room_code_with_config(&code, &config).is_ok());
+    }
+
+    #[test]
+    fn game_room_code_validation_uses_override_length() {
+        let config = ProtocolConfig::default();
+        let overrides = numeric_overrides();
+        assert!(validate_room_code_for_game("12345678", "pin-game", &config, &overrides).is_ok());
+        assert!(validate_room_code_for_game("ABC123", "pin-game", &config, &overrides).is_err());
+        assert!(validate_room_code_for_game("ABC123", "other", &config, &overrides).is_ok());
+    }
+
+    fn custom_game_config() -> impl Strategy<Value = GameConfig> {
+        (
+            proptest::sample::subsequence(b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ".to_vec(), 4..=36),
+            4usize..=16,
+            proptest::option::of("[A-Z0-9]{1,3}"),
+        )
+            .prop_map(|(charset, length, prefix)| GameConfig {
+                room_code_length: Some(length),
+                room_code_charset: Some(String::from_utf8(charset).unwrap()),
+                room_code_prefix: prefix,
+                ..GameConfig::default()
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn game_room_code_matches_configured_format(game in custom_game_config()) {
+            let config = ProtocolConfig::default();
+            let overrides = GameOverrideConfig::from([("custom".to_string(), game.clone())]);
+            let length = game.room_code_length.unwrap();
+            let charset = game.room_code_charset.clone().unwrap();
+            let prefix = game.room_code_prefix.clone().unwrap_or_default();
+
+            let code = room_codes::generate_room_code_for_game("custom", &config, &overrides);
+
+            prop_assert!(game.validate("custom", &config).is_ok());
+            prop_assert_eq!(code.len(), length);
+            prop_assert!(code.starts_with(&prefix));
+            prop_assert!(code[prefix.len()..].chars().all(|c| charset.contains(c)));
+            prop_assert!(validate_room_code_for_game(&code, "custom", &config, &overrides).is_ok());
+        }
+    }
 }
@@ -0,0 +1,172 @@
+//! Player roles and the permissions required by room-altering client messages.
+
+use super::messages::ClientMessage;
+use serde::{Deserialize, Serialize};
+use std::ops::BitOr;
+
+/// Set of room permissions held by a player.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Permissions(u32);
+
+impl Permissions {
+    pub const NONE: Self = Self(0);
+    /// Remove players from the room (`KickPlayer`).
+    pub const KICK_PLAYERS: Self = Self(1 << 0);
+    /// Lift room bans (`UnbanPlayer`).
+    pub const MANAGE_BANS: Self = Self(1 << 1);
+    /// Change other players' roles (`AssignRole`).
+    pub const ASSIGN_ROLES: Self = Self(1 << 2);
+    /// Edit the room's metadata (`SetRoomMetadata`).
+    pub const MANAGE_METADATA: Self = Self(1 << 3);
+
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for Permissions {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        self.union(rhs)
+    }
+}
+
+/// A player's role within a room.
+///
+/// The room authority always holds [`PlayerRole::Authority`] permissions on
+/// top of its own role, so handing authority over does not require a role change.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlayerRole {
+    /// The player who created the room
+    Creator,
+    /// Full moderation rights without owning the simulation
+    Authority,
+    /// May kick and unban players
+    Moderator,
+    #[default]
+    Member,
+}
+
+impl PlayerRole {
+    /// Permissions granted by this role alone.
+    pub const fn permissions(self) -> Permissions {
+        match self {
+            Self::Creator => Permissions::ASSIGN_ROLES,
+            Self::Authority => Permissions::KICK_PLAYERS
+                .union(Permissions::MANAGE_BANS)
+                .union(Permissions::ASSIGN_ROLES)
+                .union(Permissions::MANAGE_METADATA),
+            Self::Moderator => Permissions::KICK_PLAYERS.union(Permissions::MANAGE_BANS),
+            Self::Member => Permissions::NONE,
+        }
+    }
+
+    /// Whether this role may be handed out with `AssignRole`.
+    pub const fn is_assignable(self) -> bool {
+        !matches!(self, Self::Creator)
+    }
+}
+
+/// Effective permissions for a player with `role`, including authority rights if `is_authority`.
+pub const fn effective_permissions(role: PlayerRole, is_authority: bool) -> Permissions {
+    if is_authority {
+        role.permissions()
+            .union(PlayerRole::Authority.permissions())
+    } else {
+        role.permissions()
+    }
+}
+
+/// Client messages that alter the room on behalf of other players.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PrivilegedAction {
+    KickPlayer,
+    UnbanPlayer,
+    AssignRole,
+    SetRoomMetadata,
+}
+
+/// Permission required for each privileged action, in declaration order.
+pub const PERMISSION_TABLE: [(PrivilegedAction, Permissions); 4] = [
+    (PrivilegedAction::KickPlayer, Permissions::KICK_PLAYERS),
+    (PrivilegedAction::UnbanPlayer, Permissions::MANAGE_BANS),
+    (PrivilegedAction::AssignRole, Permissions::ASSIGN_ROLES),
+    (
+        PrivilegedAction::SetRoomMetadata,
+        Permissions::MANAGE_METADATA,
+    ),
+];
+
+// Lookups index the table by discriminant, so reject a misordered table at compile time
+const _: () = {
+    let mut i = 0;
+    while i < PERMISSION_TABLE.len() {
+        assert!(PERMISSION_TABLE[i].0 as usize == i);
+        i += 1;
+    }
+};
+
+impl PrivilegedAction {
+    /// Permission a player needs to perform this action.
+    pub const fn required_permission(self) -> Permissions {
+        PERMISSION_TABLE[self as usize].1
+    }
+
+    /// Message type name used in logs and error messages.
+    pub const fn message_type(self) -> &'static str {
+        match self {
+            Self::KickPlayer => "KickPlayer",
+            Self::UnbanPlayer => "UnbanPlayer",
+            Self::AssignRole => "AssignRole",
+            Self::SetRoomMetadata => "SetRoomMetadata",
+        }
+    }
+}
+
+impl ClientMessage {
+    /// The privileged action this message performs, if any.
+    ///
+    /// Deliberately lists every variant so that adding a message forces a
+    /// decision about whether it needs a permission.
+    pub const fn privileged_action(&self) -> Option<PrivilegedAction> {
+        match self {
+            Self::KickPlayer { .. } => Some(PrivilegedAction::KickPlayer),
+            Self::UnbanPlayer { .. } => Some(PrivilegedAction::UnbanPlayer),
+            Self::AssignRole { .. } => Some(PrivilegedAction::AssignRole),
+            Self::SetRoomMetadata { .. } => Some(PrivilegedAction::SetRoomMetadata),
+            Self::Authenticate { .. }
+            | Self::JoinRoom { .. }
+            | Self::QuickJoin { .. }
+            | Self::LeaveRoom
+            | Self::GameData { .. }
+            | Self::AuthorityRequest { .. }
+            | Self::PlayerReady
+            | Self::ProvideConnectionInfo { .. }
+            | Self::Ping
+            | Self::LatencyPing { .. }
+            | Self::Heartbeat { .. }
+            | Self::TimeSync { .. }
+            | Self::Reconnect { .. }
+            | Self::JoinAsSpectator { .. }
+            | Self::LeaveSpectator
+            | Self::SpectatorChat { .. }
+            | Self::RequestPromotion
+            | Self::RequestRoleChange { .. }
+            | Self::UpdateRoomSettings { .. }
+            | Self::RespondToJoinRequest { .. }
+            | Self::JoinWaitingList { .. }
+            | Self::ReportPlayer { .. }
+            | Self::DeliveryAck { .. }
+            | Self::EnqueueMatchmaking { .. }
+            | Self::DequeueMatchmaking
+            | Self::CreateInvite { .. }
+            | Self::JoinByInvite { .. } => None,
+        }
+    }
+}
@@ -1,5 +1,9 @@
-use crate::config::ProtocolConfig;
+use crate::config::{GameConfig, ProtocolConfig};
 use rand::RngExt;
+use std::collections::HashMap;
+
+/// Characters used by clean room codes: no 0, O, I or 1.
+const CLEAN_CHARS: &[u8] = b"23456789ABCDEFGHJKLMNPQRSTUVWXYZ";
 
 /// Generate alphanumeric room code with configurable length
 /// Uses uppercase letters and numbers for easy communication
@@ -21,7 +25,6 @@ pub fn generate_clean_room_code_with_config(config: &ProtocolConfig) -> String {
 
 /// Generate a clean room code of the requested length.
 pub fn generate_clean_room_code_of_length(length: usize) -> String {
-    const CLEAN_CHARS: &[u8] = b"23456789ABCDEFGHJKLMNPQRSTUVWXYZ";
     if length == 0 {
         return String::new();
     }
@@ -59,6 +62,39 @@ pub fn generate_region_room_code(config: &ProtocolConfig, region_prefix: Option<
     }
 }
 
+/// Generate a room code using the game's configured length, charset and prefix.
+///
+/// Games without an entry in `game_overrides` get a clean code of the
+/// protocol's default length. A prefix that would fill the whole code is
+/// ignored, as in [`generate_region_room_code`].
+pub fn generate_room_code_for_game(
+    game_name: &str,
+    config: &ProtocolConfig,
+    game_overrides: &HashMap<String, GameConfig>,
+) -> String {
+    let Some(game) = game_overrides.get(game_name) else {
+        return generate_clean_room_code_with_config(config);
+    };
+
+    let length = game.room_code_length.unwrap_or(config.room_code_length);
+    let charset: Vec<char> = match game.room_code_charset.as_deref() {
+        Some(charset) if !charset.is_empty() => charset.chars().collect(),
+        _ => CLEAN_CHARS.iter().map(|&c| c as char).collect(),
+    };
+    let prefix = game
+        .room_code_prefix
+        .as_deref()
+        .map(|p| p.trim().to_ascii_uppercase())
+        .filter(|p| !p.is_empty() && p.chars().count() < length)
+        .unwrap_or_default();
+
+    let mut rng = rand::rng();
+    let random_len = length - prefix.chars().count();
+    let mut code = prefix;
+    code.extend((0..random_len).map(|_| charset[rng.random_range(0..charset.len())]));
+    code
+}
+
 /// Generate a 6-character alphanumeric room code (legacy)
 /// Uses uppercase letters and numbers for easy communication
 #[allow(dead_code)]
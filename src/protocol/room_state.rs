@@ -1,10 +1,13 @@
+use bytes::Bytes;
 use rkyv::{Archive, Deserialize as RkyvDeserialize, Serialize as RkyvSerialize};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::net::IpAddr;
 use uuid::Uuid;
 
 use super::types::{
-    PeerConnectionInfo, PlayerId, PlayerInfo, RoomId, SpectatorInfo, DEFAULT_REGION_ID,
+    GameDataEncoding, PeerConnectionInfo, PlayerId, PlayerInfo, RoomId, SpectatorChatMode,
+    SpectatorInfo, DEFAULT_REGION_ID,
 };
 
 // ============================================================================
@@ -20,7 +23,7 @@ use super::types::{
 // ```text
 // [*] --> Waiting: Room Created
 //
-// Waiting --> Lobby: Room Full (players == max_players)
+// Waiting --> Lobby: Room Full (players >= max_players)
 // Lobby --> Waiting: Player Leaves (players < max_players)
 // Lobby --> Finalized: All Players Ready
 //
@@ -44,10 +47,12 @@ use super::types::{
 // - **Description**: Room is full and players are coordinating readiness to
 //   start the game.
 // - **Characteristics**:
-//   - Room has exactly `max_players` players
+//   - Room has at least `max_players` players (more when the authority
+//     admits overflow joins)
 //   - Players can mark themselves ready/unready via `PlayerReady` messages
 //   - `ready_players` list tracks who is ready
-//   - Cannot accept new players (room is full)
+//   - Cannot accept new players (room is full) unless the room allows
+//     overflow joins and its authority accepts them
 //   - Transitions to Finalized when all players ready
 //   - Broadcasts `LobbyStateChanged` when ready state changes
 //
@@ -129,6 +134,22 @@ use super::types::{
 // - **Stale Finalization**: Ready state version tracking prevents multiple
 //   server instances from finalizing the same room (distributed lock protection).
 //
+// ## Lobby Versions
+//
+// Every room carries a `lobby_version` that starts at 0 and is bumped by each
+// lobby update: entering the lobby, a ready toggle, returning to Waiting and
+// finalizing. The bump happens under the same storage lock as the change, so
+// no two updates share a version. `LobbyStateChanged` and `GameStarting`
+// carry the version of the update they announce, and `RoomJoined` and
+// `Reconnected` carry the version of the snapshot they describe.
+//
+// Client contract: keep the highest version seen for the room and apply a
+// lobby update only if its version is greater; anything at or below it is
+// stale (delivered late, or older than a snapshot) and must be discarded.
+// Versions only increase for the life of a room but may skip values. Events
+// replayed in `Reconnected.missed_events` keep the versions they were sent
+// with, so the same rule applies to them.
+//
 // ## Timestamps and Activity Tracking
 //
 // Rooms track several timestamps for lifecycle management:
@@ -176,8 +197,51 @@ pub enum LobbyState {
     Finalized,
 }
 
+/// Maximum number of bans tracked per room; the oldest ban is evicted beyond this.
+pub const MAX_ROOM_BANS: usize = 64;
+
+/// A player banned from rejoining a room
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoomBan {
+    pub player_id: PlayerId,
+    /// Client IP at ban time, so a fresh connection (new player ID) is still rejected
+    pub client_ip: Option<IpAddr>,
+}
+
+/// A relayed game data message kept for replay to late joiners and reconnects
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoricalMessage {
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub from_player: PlayerId,
+    /// Format of `payload`; JSON game data is stored as serialized JSON
+    pub encoding: GameDataEncoding,
+    #[serde(with = "super::messages::bytes_serde")]
+    pub payload: Bytes,
+}
+
+/// Counts and identifiers of a room, without its player, spectator or
+/// history collections. Cheap to build and clone; see [`Room::to_summary`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoomSummary {
+    pub id: RoomId,
+    pub game_name: String,
+    pub code: String,
+    pub current_players: u8,
+    pub max_players: u8,
+    pub spectator_count: u8,
+    pub lobby_state: LobbyState,
+    pub authority_player: Option<PlayerId>,
+    pub region_id: String,
+    pub relay_type: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub last_activity: chrono::DateTime<chrono::Utc>,
+}
+
 /// Room configuration and state
-#[derive(Debug, Clone)]
+///
+/// Serialized by the file-backed database only; clients see rooms through
+/// the protocol messages instead.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[allow(dead_code)]
 pub struct Room {
     pub id: RoomId,
@@ -189,6 +253,8 @@ pub struct Room {
     pub authority_player: Option<PlayerId>,
     pub lobby_state: LobbyState,
     pub ready_players: Vec<PlayerId>,
+    /// Bumped by every lobby update; see "Lobby Versions" above
+    pub lobby_version: u64,
     pub lobby_started_at: Option<chrono::DateTime<chrono::Utc>>,
     pub game_finalized_at: Option<chrono::DateTime<chrono::Utc>>,
     pub relay_type: String,
@@ -196,12 +262,32 @@ pub struct Room {
     pub region_id: String,
     /// Owning application for per-app rate limiting and access control.
     pub application_id: Option<Uuid>,
+    /// Identity the creator is rate limited by, for per-creator active room caps
+    pub creator_key: Option<String>,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub last_activity: chrono::DateTime<chrono::Utc>,
     /// Spectators watching the room (read-only observers)
     pub spectators: HashMap<PlayerId, SpectatorInfo>,
     /// Maximum number of spectators allowed (None = unlimited)
     pub max_spectators: Option<u8>,
+    /// Players queued for a slot while the room is full (front = next promoted)
+    pub waiting_list: VecDeque<PlayerId>,
+    /// Players banned from joining or spectating (oldest first, capped at `MAX_ROOM_BANS`)
+    pub bans: VecDeque<RoomBan>,
+    /// Players were sent `RoomExpirySoon`; cleared by the next room activity
+    pub expiry_warned: bool,
+    /// Opaque relay session data from the relay provisioner, once provisioned
+    pub relay_session: Option<serde_json::Value>,
+    /// Key-value metadata set by the room authority; kept until the room is deleted
+    pub metadata: HashMap<String, String>,
+    /// Who receives spectator chat; chosen when the room is created
+    pub spectator_chat_mode: SpectatorChatMode,
+    /// Joins to the full room go to the authority for approval instead of failing
+    pub allow_overflow_joins: bool,
+    /// Only the authority may create invites; set with `UpdateRoomSettings`
+    pub invites_authority_only: bool,
+    /// Most recent relayed game data, oldest first (only kept with history replay enabled)
+    pub message_history: VecDeque<HistoricalMessage>,
 }
 
 impl Room {
@@ -224,15 +310,45 @@ impl Room {
             authority_player: None,
             lobby_state: LobbyState::Waiting,
             ready_players: Vec::new(),
+            lobby_version: 0,
             lobby_started_at: None,
             game_finalized_at: None,
             relay_type,
             region_id: DEFAULT_REGION_ID.to_string(),
             application_id: None,
+            creator_key: None,
             created_at: now,
             last_activity: now,
             spectators: HashMap::new(),
             max_spectators: None, // Unlimited spectators by default
+            waiting_list: VecDeque::new(),
+            bans: VecDeque::new(),
+            expiry_warned: false,
+            relay_session: None,
+            metadata: HashMap::new(),
+            spectator_chat_mode: SpectatorChatMode::default(),
+            allow_overflow_joins: false,
+            invites_authority_only: false,
+            message_history: VecDeque::new(),
+        }
+    }
+
+    /// Lightweight view of the room for listings and metrics. Counts saturate
+    /// at `u8::MAX`.
+    pub fn to_summary(&self) -> RoomSummary {
+        RoomSummary {
+            id: self.id,
+            game_name: self.game_name.clone(),
+            code: self.code.clone(),
+            current_players: u8::try_from(self.players.len()).unwrap_or(u8::MAX),
+            max_players: self.max_players,
+            spectator_count: u8::try_from(self.spectators.len()).unwrap_or(u8::MAX),
+            lobby_state: self.lobby_state.clone(),
+            authority_player: self.authority_player,
+            region_id: self.region_id.clone(),
+            relay_type: self.relay_type.clone(),
+            created_at: self.created_at,
+            last_activity: self.last_activity,
         }
     }
 
@@ -249,14 +365,21 @@ impl Room {
         empty_timeout: chrono::Duration,
         inactive_timeout: chrono::Duration,
     ) -> bool {
-        let now = chrono::Utc::now();
+        chrono::Utc::now() > self.expires_at(empty_timeout, inactive_timeout)
+    }
 
+    /// When the room becomes eligible for cleanup under the given timeouts
+    pub fn expires_at(
+        &self,
+        empty_timeout: chrono::Duration,
+        inactive_timeout: chrono::Duration,
+    ) -> chrono::DateTime<chrono::Utc> {
         if self.players.is_empty() {
             // Empty room - check against creation time
-            now.signed_duration_since(self.created_at) > empty_timeout
+            self.created_at + empty_timeout
         } else {
             // Room has players - check against last activity
-            now.signed_duration_since(self.last_activity) > inactive_timeout
+            self.last_activity + inactive_timeout
         }
     }
 
@@ -328,11 +451,12 @@ impl Room {
         self.set_authority(None)
     }
 
-    /// Check if room should transition to lobby state
+    /// Check if room should transition to lobby state. Players admitted
+    /// beyond `max_players` count like any other.
     #[allow(dead_code)]
     pub fn should_enter_lobby(&self) -> bool {
         self.lobby_state == LobbyState::Waiting
-            && self.players.len() == self.max_players as usize
+            && self.players.len() >= self.max_players as usize
             && self.max_players > 1
     }
 
@@ -343,12 +467,28 @@ impl Room {
             self.lobby_state = LobbyState::Lobby;
             self.lobby_started_at = Some(chrono::Utc::now());
             self.ready_players.clear();
+            self.lobby_version += 1;
             true
         } else {
             false
         }
     }
 
+    /// Revert the lobby to Waiting after a player left, clearing readiness
+    pub fn return_to_waiting(&mut self) -> bool {
+        if self.lobby_state != LobbyState::Lobby {
+            return false;
+        }
+        self.lobby_state = LobbyState::Waiting;
+        self.lobby_started_at = None;
+        self.ready_players.clear();
+        for player in self.players.values_mut() {
+            player.is_ready = false;
+        }
+        self.lobby_version += 1;
+        true
+    }
+
     /// Mark a player as ready in lobby
     #[allow(dead_code)]
     pub fn set_player_ready(&mut self, player_id: &PlayerId, ready: bool) -> bool {
@@ -367,6 +507,7 @@ impl Room {
             player.is_ready = ready;
         }
 
+        self.lobby_version += 1;
         true
     }
 
@@ -385,6 +526,7 @@ impl Room {
         if self.lobby_state == LobbyState::Lobby && self.all_players_ready() {
             self.lobby_state = LobbyState::Finalized;
             self.game_finalized_at = Some(chrono::Utc::now());
+            self.lobby_version += 1;
             true
         } else {
             false
@@ -402,6 +544,7 @@ impl Room {
                 is_authority: player.is_authority,
                 relay_type: self.relay_type.clone(),
                 connection_info: player.connection_info.clone(),
+                ice_servers: Vec::new(),
             })
             .collect()
     }
@@ -422,6 +565,44 @@ impl Room {
         }
     }
 
+    /// Change the player and spectator capacity; `None` leaves a setting as it
+    /// is. Fails without changing anything when more players or spectators are
+    /// present than the new capacity allows. A lobby that is no longer full
+    /// returns to Waiting, and its new lobby version is returned.
+    pub fn resize(
+        &mut self,
+        max_players: Option<u8>,
+        max_spectators: Option<u8>,
+    ) -> Result<Option<u64>, String> {
+        if let Some(max_players) = max_players {
+            if self.players.len() > max_players as usize {
+                return Err(format!(
+                    "Room has {} players, more than the requested {max_players}",
+                    self.players.len()
+                ));
+            }
+        }
+        if let Some(max_spectators) = max_spectators {
+            if self.spectators.len() > max_spectators as usize {
+                return Err(format!(
+                    "Room has {} spectators, more than the requested {max_spectators}",
+                    self.spectators.len()
+                ));
+            }
+        }
+
+        if let Some(max_players) = max_players {
+            self.max_players = max_players;
+        }
+        if max_spectators.is_some() {
+            self.max_spectators = max_spectators;
+        }
+        let reopened = self.lobby_state == LobbyState::Lobby
+            && self.players.len() < self.max_players as usize
+            && self.return_to_waiting();
+        Ok(reopened.then_some(self.lobby_version))
+    }
+
     /// Add a spectator to the room
     #[allow(dead_code)]
     pub fn add_spectator(&mut self, spectator: SpectatorInfo) -> bool {
@@ -444,4 +625,74 @@ impl Room {
     pub fn get_spectators(&self) -> Vec<SpectatorInfo> {
         self.spectators.values().cloned().collect()
     }
+
+    /// Queue a player on the waiting list.
+    /// Returns the 1-based position, or None if the list already holds `max_size` entries.
+    pub fn enqueue_waiting(&mut self, player_id: PlayerId, max_size: usize) -> Option<usize> {
+        if let Some(position) = self.waiting_position(&player_id) {
+            return Some(position);
+        }
+        if self.waiting_list.len() >= max_size {
+            return None;
+        }
+        self.waiting_list.push_back(player_id);
+        Some(self.waiting_list.len())
+    }
+
+    /// Pop the next player off the waiting list
+    pub fn pop_waiting(&mut self) -> Option<PlayerId> {
+        self.waiting_list.pop_front()
+    }
+
+    /// Remove a player from the waiting list, returning whether they were queued
+    pub fn remove_waiting(&mut self, player_id: &PlayerId) -> bool {
+        let before = self.waiting_list.len();
+        self.waiting_list.retain(|queued| queued != player_id);
+        self.waiting_list.len() != before
+    }
+
+    /// 1-based waiting list position for a player, if queued
+    pub fn waiting_position(&self, player_id: &PlayerId) -> Option<usize> {
+        self.waiting_list
+            .iter()
+            .position(|queued| queued == player_id)
+            .map(|index| index + 1)
+    }
+
+    /// Ban a player from the room, evicting the oldest ban once `MAX_ROOM_BANS` is reached
+    pub fn ban_player(&mut self, player_id: PlayerId, client_ip: Option<IpAddr>) {
+        self.bans.retain(|ban| ban.player_id != player_id);
+        while self.bans.len() >= MAX_ROOM_BANS {
+            self.bans.pop_front();
+        }
+        self.bans.push_back(RoomBan {
+            player_id,
+            client_ip,
+        });
+    }
+
+    /// Append to the message history, evicting the oldest entries beyond `capacity`
+    pub fn record_history(&mut self, message: HistoricalMessage, capacity: usize) {
+        if capacity == 0 {
+            return;
+        }
+        while self.message_history.len() >= capacity {
+            self.message_history.pop_front();
+        }
+        self.message_history.push_back(message);
+    }
+
+    /// Lift a ban, returning whether the player was banned
+    pub fn unban_player(&mut self, player_id: &PlayerId) -> bool {
+        let before = self.bans.len();
+        self.bans.retain(|ban| &ban.player_id != player_id);
+        self.bans.len() != before
+    }
+
+    /// Whether a player (or their client IP) is banned from this room
+    pub fn is_banned(&self, player_id: &PlayerId, client_ip: Option<IpAddr>) -> bool {
+        self.bans.iter().any(|ban| {
+            &ban.player_id == player_id || (client_ip.is_some() && ban.client_ip == client_ip)
+        })
+    }
 }
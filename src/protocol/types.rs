@@ -2,6 +2,8 @@ use rkyv::{Archive, Deserialize as RkyvDeserialize, Serialize as RkyvSerialize};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use super::permissions::PlayerRole;
+
 /// Default constants for validation (can be overridden by config)
 /// These are used when no config is available
 #[allow(dead_code)]
@@ -52,6 +54,25 @@ pub enum RelayTransport {
     Auto,
 }
 
+impl RelayTransport {
+    /// Parse a transport by its wire name, ignoring case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        [Self::Tcp, Self::Udp, Self::Websocket, Self::Auto]
+            .into_iter()
+            .find(|transport| transport.name().eq_ignore_ascii_case(name))
+    }
+
+    /// The wire name of this transport.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Tcp => "tcp",
+            Self::Udp => "udp",
+            Self::Websocket => "websocket",
+            Self::Auto => "auto",
+        }
+    }
+}
+
 /// Encoding format for sequenced game data payloads.
 #[derive(
     Debug,
@@ -130,6 +151,9 @@ pub struct PlayerInfo {
     pub id: PlayerId,
     pub name: String,
     pub is_authority: bool,
+    /// Room role; see [`PlayerRole`] for the permissions each role grants
+    #[serde(default)]
+    pub role: PlayerRole,
     pub is_ready: bool,
     pub connected_at: chrono::DateTime<chrono::Utc>,
     /// Connection info for P2P establishment (provided when player is ready)
@@ -170,6 +194,62 @@ pub enum SpectatorStateChangeReason {
     Disconnected,
     Removed,
     RoomClosed,
+    Promoted,
+    Demoted,
+}
+
+/// Whether someone takes part in a room as a player or watches as a spectator.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ParticipantRole {
+    Player,
+    Spectator,
+}
+
+/// Why a player is being reported with `ReportPlayer`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportReason {
+    Cheating,
+    Harassment,
+    OffensiveName,
+    Spam,
+    Other,
+}
+
+impl ReportReason {
+    /// Name used on the wire and in report logs.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Cheating => "cheating",
+            Self::Harassment => "harassment",
+            Self::OffensiveName => "offensive_name",
+            Self::Spam => "spam",
+            Self::Other => "other",
+        }
+    }
+}
+
+/// Who receives spectator chat in a room.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SpectatorChatMode {
+    /// Spectator chat is disabled
+    #[default]
+    None,
+    /// Only other spectators receive it
+    SpectatorsOnly,
+    /// Spectators and players receive it
+    Everyone,
+}
+
+/// How urgent a `ServerAnnouncement` is, for clients choosing how to show it.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AnnouncementSeverity {
+    Info,
+    Warning,
+    Critical,
 }
 
 /// Peer connection information for game start
@@ -182,6 +262,30 @@ pub struct PeerConnectionInfo {
     /// Connection info provided by the peer for P2P establishment
     #[serde(skip_serializing_if = "Option::is_none")]
     pub connection_info: Option<ConnectionInfo>,
+    /// STUN/TURN servers this peer should gather ICE candidates from. Only
+    /// set in a player's own entry, since TURN credentials are per player
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ice_servers: Vec<IceServer>,
+}
+
+/// A STUN or TURN server, in the shape of WebRTC's `RTCIceServer`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IceServer {
+    /// `stun:`, `stuns:`, `turn:` or `turns:` URLs of the server
+    pub urls: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credential: Option<String>,
+}
+
+/// Echo of the most recent `HeartbeatAck`, sent with the next heartbeat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HeartbeatEcho {
+    /// `server_timestamp` from that ack
+    pub server_timestamp: u64,
+    /// Milliseconds between receiving that ack and sending this heartbeat
+    pub held_ms: u64,
 }
 
 /// Rate limit information for an application
@@ -214,6 +318,12 @@ pub struct ProtocolInfoPayload {
     pub game_data_formats: Vec<GameDataEncoding>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub player_name_rules: Option<PlayerNameRulesPayload>,
+    /// Length of room codes
+    #[serde(default)]
+    pub room_code_length: usize,
+    /// Largest `max_players` a room may be created with
+    #[serde(default)]
+    pub max_players: u8,
 }
 
 /// Describes the characters your deployment allows inside `player_name`.
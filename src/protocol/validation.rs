@@ -1,7 +1,15 @@
-use crate::config::ProtocolConfig;
+use crate::auth::middleware::deterministic_uuid;
+use crate::auth::AppInfo;
+use crate::config::{
+    AppAuthEntry, BannedWordMatch, GameConfig, PlayerNameValidationConfig, ProtocolConfig,
+    RelayTypeConfig,
+};
 use std::collections::HashMap;
+use thiserror::Error;
+use uuid::Uuid;
 
-use super::types::{PlayerId, PlayerInfo};
+use super::error_codes::{ErrorCode, ErrorDetails, ValidationConstraint};
+use super::types::{PlayerId, PlayerInfo, RelayTransport};
 
 pub fn validate_game_name_with_config(name: &str, config: &ProtocolConfig) -> Result<(), String> {
     if name.is_empty() {
@@ -23,14 +31,55 @@ pub fn validate_game_name_with_config(name: &str, config: &ProtocolConfig) -> Re
 }
 
 pub fn validate_room_code_with_config(code: &str, config: &ProtocolConfig) -> Result<(), String> {
+    validate_room_code_of_length(code, config.room_code_length)
+}
+
+/// Validate a room code against the length configured for `game_name`.
+pub fn validate_room_code_for_game(
+    code: &str,
+    game_name: &str,
+    config: &ProtocolConfig,
+    game_overrides: &HashMap<String, GameConfig>,
+) -> Result<(), String> {
+    validate_room_code_of_length(
+        code,
+        room_code_length_for_game(game_name, config, game_overrides),
+    )
+}
+
+/// The room code length configured for `game_name`.
+pub fn room_code_length_for_game(
+    game_name: &str,
+    config: &ProtocolConfig,
+    game_overrides: &HashMap<String, GameConfig>,
+) -> usize {
+    game_overrides
+        .get(game_name)
+        .and_then(|game| game.room_code_length)
+        .unwrap_or(config.room_code_length)
+}
+
+/// Typed details for a room code rejected by [`validate_room_code_for_game`].
+pub fn room_code_details(code: &str, length: usize) -> ErrorDetails {
+    if code.is_empty() {
+        ErrorDetails::validation("room_code", ValidationConstraint::Required, None)
+    } else if code.len() != length {
+        ErrorDetails::validation(
+            "room_code",
+            ValidationConstraint::ExactLength,
+            Some(length as u64),
+        )
+    } else {
+        ErrorDetails::validation("room_code", ValidationConstraint::AllowedCharacters, None)
+    }
+}
+
+fn validate_room_code_of_length(code: &str, length: usize) -> Result<(), String> {
     if code.is_empty() {
         return Err("Room code cannot be empty".to_string());
     }
-    if code.len() != config.room_code_length {
-        return Err(format!(
-            "Room code must be exactly {} characters",
-            config.room_code_length
-        ));
+    if code.len() != length {
+        return Err(format!("Room code must be exactly {length} characters"));
     }
     if !code.chars().all(|c| c.is_ascii_alphanumeric()) {
         return Err("Room code must be alphanumeric".to_string());
@@ -38,7 +87,115 @@ pub fn validate_room_code_with_config(code: &str, config: &ProtocolConfig) -> Re
     Ok(())
 }
 
-pub fn validate_player_name_with_config(name: &str, config: &ProtocolConfig) -> Result<(), String> {
+/// Validate a relay type a client requested explicitly.
+///
+/// The standard transports (`tcp`, `udp`, `websocket`, `auto`) are always
+/// accepted, as are the config's `custom_relay_types`. Beyond those, only
+/// `allowed_relay_types` may be requested or, when that list is empty, the
+/// relay types named in the game, region and default mappings.
+pub fn validate_relay_type(relay_type: &str, config: &RelayTypeConfig) -> Result<(), String> {
+    let known = |candidate: &String| candidate == relay_type;
+    let allowed = RelayTransport::from_name(relay_type).is_some()
+        || config.custom_relay_types.iter().any(known)
+        || if config.allowed_relay_types.is_empty() {
+            relay_type == config.default_relay_type
+                || config.game_relay_mappings.values().any(known)
+                || config.region_relay_mappings.values().any(known)
+        } else {
+            config.allowed_relay_types.iter().any(known)
+        };
+    if allowed {
+        Ok(())
+    } else {
+        Err(format!("Relay type '{relay_type}' is not allowed"))
+    }
+}
+
+/// Why a room's application was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum ValidationError {
+    /// The application id is not one of `security.authorized_apps`
+    #[error("Unknown application id")]
+    UnknownApplicationId,
+}
+
+/// Resolve the application a room is created for. `None` for an open room
+/// without one; otherwise `app_id` must belong to one of `authorized_apps`.
+pub fn validate_application_id(
+    app_id: Option<Uuid>,
+    authorized_apps: &[AppAuthEntry],
+) -> Result<Option<AppInfo>, ValidationError> {
+    let Some(app_id) = app_id else {
+        return Ok(None);
+    };
+    authorized_apps
+        .iter()
+        .find(|entry| deterministic_uuid(&entry.app_id) == app_id)
+        .map(|entry| Some(AppInfo::from_entry(entry)))
+        .ok_or(ValidationError::UnknownApplicationId)
+}
+
+/// Why a player name was rejected.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum PlayerNameError {
+    /// The name breaks the length or character rules
+    #[error("{0}")]
+    Invalid(String),
+    /// The name contains one of the configured banned words
+    #[error("Player name contains a word that is not allowed")]
+    NotAllowed,
+}
+
+impl PlayerNameError {
+    /// Error code to report; `invalid` is used for length and character errors.
+    pub fn error_code(&self, invalid: ErrorCode) -> ErrorCode {
+        match self {
+            Self::Invalid(_) => invalid,
+            Self::NotAllowed => ErrorCode::PlayerNameNotAllowed,
+        }
+    }
+
+    /// Typed details for the rule `name` broke.
+    pub fn details(&self, name: &str, config: &ProtocolConfig) -> ErrorDetails {
+        match self {
+            Self::Invalid(_) => {
+                text_field_details("player_name", name, config.max_player_name_length)
+            }
+            Self::NotAllowed => {
+                ErrorDetails::validation("player_name", ValidationConstraint::BannedWord, None)
+            }
+        }
+    }
+}
+
+/// Typed details for a text field that failed a length-limited check: blank,
+/// longer than `max_length` bytes, or otherwise disallowed characters.
+pub fn text_field_details(field: &str, value: &str, max_length: usize) -> ErrorDetails {
+    if value.trim().is_empty() && value.len() <= max_length {
+        ErrorDetails::validation(field, ValidationConstraint::Required, None)
+    } else if value.len() > max_length {
+        ErrorDetails::validation(
+            field,
+            ValidationConstraint::MaxLength,
+            Some(max_length as u64),
+        )
+    } else {
+        ErrorDetails::validation(field, ValidationConstraint::AllowedCharacters, None)
+    }
+}
+
+pub fn validate_player_name_with_config(
+    name: &str,
+    config: &ProtocolConfig,
+) -> Result<(), PlayerNameError> {
+    validate_player_name_format(name, config).map_err(PlayerNameError::Invalid)?;
+    if contains_banned_word(name, &config.player_name_validation) {
+        return Err(PlayerNameError::NotAllowed);
+    }
+    Ok(())
+}
+
+fn validate_player_name_format(name: &str, config: &ProtocolConfig) -> Result<(), String> {
     if name.is_empty() {
         return Err("Player name cannot be empty".to_string());
     }
@@ -87,6 +244,72 @@ pub fn validate_player_name_with_config(name: &str, config: &ProtocolConfig) ->
     Ok(())
 }
 
+fn contains_banned_word(name: &str, rules: &PlayerNameValidationConfig) -> bool {
+    if rules.banned_words.is_empty() {
+        return false;
+    }
+    match rules.banned_word_match {
+        BannedWordMatch::Substring => {
+            let name = fold_letters(name);
+            rules.banned_words.iter().any(|banned| {
+                let banned = fold_letters(banned);
+                !banned.is_empty() && name.contains(&banned)
+            })
+        }
+        BannedWordMatch::Word => {
+            let words = fold_words(name);
+            rules.banned_words.iter().any(|banned| {
+                let banned = fold_words(banned);
+                !banned.is_empty() && words.windows(banned.len()).any(|run| run == banned)
+            })
+        }
+    }
+}
+
+/// Lowercase `text` for banned word matching, folding full-width forms to
+/// ASCII and dropping combining marks and invisible characters.
+fn fold_for_matching(text: &str) -> impl Iterator<Item = char> + '_ {
+    text.chars()
+        .filter(|ch| !is_combining_or_invisible(*ch))
+        .map(|ch| match ch {
+            '\u{FF01}'..='\u{FF5E}' => char::from_u32(ch as u32 - 0xFEE0).unwrap_or(ch),
+            _ => ch,
+        })
+        .flat_map(char::to_lowercase)
+}
+
+/// The folded letters and digits of `text`, without separators.
+fn fold_letters(text: &str) -> String {
+    fold_for_matching(text)
+        .filter(|ch| ch.is_alphanumeric())
+        .collect()
+}
+
+/// The folded words of `text`, split on anything that is not a letter or digit.
+fn fold_words(text: &str) -> Vec<String> {
+    fold_for_matching(text)
+        .collect::<String>()
+        .split(|ch: char| !ch.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn is_combining_or_invisible(ch: char) -> bool {
+    matches!(
+        ch,
+        '\u{0300}'..='\u{036F}'
+            | '\u{1AB0}'..='\u{1AFF}'
+            | '\u{1DC0}'..='\u{1DFF}'
+            | '\u{20D0}'..='\u{20FF}'
+            | '\u{FE20}'..='\u{FE2F}'
+            | '\u{00AD}'
+            | '\u{200B}'..='\u{200F}'
+            | '\u{2060}'..='\u{2064}'
+            | '\u{FEFF}'
+    )
+}
+
 pub fn validate_player_name_uniqueness(
     name: &str,
     existing_players: &HashMap<PlayerId, PlayerInfo>,
@@ -100,6 +323,34 @@ pub fn validate_player_name_uniqueness(
     Ok(())
 }
 
+/// The lowest `"name (n)"` (n >= 2) not yet used in the room, compared
+/// case-insensitively like [`validate_player_name_uniqueness`].
+///
+/// The base is truncated so the result fits in `max_length` bytes. Returns
+/// `None` if no base is left once the suffix is appended.
+pub fn suffix_duplicate_player_name(
+    name: &str,
+    existing_players: &HashMap<PlayerId, PlayerInfo>,
+    max_length: usize,
+) -> Option<String> {
+    // Among len + 1 candidates at least one is free
+    (2..=existing_players.len() + 2).find_map(|n| {
+        let suffix = format!(" ({n})");
+        let mut end = name.len().min(max_length.checked_sub(suffix.len())?);
+        while !name.is_char_boundary(end) {
+            end -= 1;
+        }
+        let base = name[..end].trim_end();
+        if base.is_empty() {
+            return None;
+        }
+        let candidate = format!("{base}{suffix}");
+        validate_player_name_uniqueness(&candidate, existing_players)
+            .is_ok()
+            .then_some(candidate)
+    })
+}
+
 pub fn validate_max_players_with_config(
     max_players: u8,
     config: &ProtocolConfig,
@@ -116,6 +367,103 @@ pub fn validate_max_players_with_config(
     Ok(())
 }
 
+/// Maximum number of keys in a room's metadata.
+pub const MAX_ROOM_METADATA_KEYS: usize = 16;
+/// Maximum length of a room metadata key, in characters.
+pub const MAX_ROOM_METADATA_KEY_LENGTH: usize = 32;
+/// Maximum length of a room metadata value, in characters.
+pub const MAX_ROOM_METADATA_VALUE_LENGTH: usize = 256;
+
+/// Check each entry of a `SetRoomMetadata` request against the key and value limits.
+pub fn validate_room_metadata_entries(entries: &HashMap<String, String>) -> Result<(), String> {
+    for (key, value) in entries {
+        if key.trim().is_empty() {
+            return Err("Room metadata keys cannot be empty".to_string());
+        }
+        if key.chars().count() > MAX_ROOM_METADATA_KEY_LENGTH {
+            return Err(format!(
+                "Room metadata keys cannot exceed {MAX_ROOM_METADATA_KEY_LENGTH} characters"
+            ));
+        }
+        if value.chars().count() > MAX_ROOM_METADATA_VALUE_LENGTH {
+            return Err(format!(
+                "Room metadata values cannot exceed {MAX_ROOM_METADATA_VALUE_LENGTH} characters"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Details for a `SetRoomMetadata` request over the key or length limits.
+pub fn room_metadata_details() -> ErrorDetails {
+    ErrorDetails::validation("metadata", ValidationConstraint::Limits, None)
+}
+
+/// Merge `entries` into `metadata`; an empty value removes its key.
+///
+/// Leaves `metadata` untouched if any entry is invalid or the merged result
+/// would exceed [`MAX_ROOM_METADATA_KEYS`].
+pub fn apply_room_metadata_entries(
+    metadata: &mut HashMap<String, String>,
+    entries: &HashMap<String, String>,
+) -> Result<(), String> {
+    validate_room_metadata_entries(entries)?;
+
+    let added = entries
+        .iter()
+        .filter(|(key, value)| !value.is_empty() && !metadata.contains_key(*key))
+        .count();
+    let removed = entries
+        .iter()
+        .filter(|(key, value)| value.is_empty() && metadata.contains_key(*key))
+        .count();
+    if metadata.len() + added - removed > MAX_ROOM_METADATA_KEYS {
+        return Err(format!(
+            "Rooms cannot hold more than {MAX_ROOM_METADATA_KEYS} metadata keys"
+        ));
+    }
+
+    for (key, value) in entries {
+        if value.is_empty() {
+            metadata.remove(key);
+        } else {
+            metadata.insert(key.clone(), value.clone());
+        }
+    }
+    Ok(())
+}
+
+/// Maximum length of a `JoinRoom` idempotency key, in bytes.
+pub const MAX_IDEMPOTENCY_KEY_LENGTH: usize = 64;
+
+/// Check a create-room idempotency key: not blank and at most
+/// [`MAX_IDEMPOTENCY_KEY_LENGTH`] bytes.
+pub fn validate_idempotency_key(key: &str) -> Result<(), String> {
+    if key.trim().is_empty() {
+        return Err("Idempotency key cannot be empty".to_string());
+    }
+    if key.len() > MAX_IDEMPOTENCY_KEY_LENGTH {
+        return Err(format!(
+            "Idempotency key too long (max {MAX_IDEMPOTENCY_KEY_LENGTH} bytes)"
+        ));
+    }
+    Ok(())
+}
+
+/// Check a `SpectatorChat` line against the configured length limit.
+pub fn validate_spectator_chat_text(text: &str, config: &ProtocolConfig) -> Result<(), String> {
+    if text.trim().is_empty() {
+        return Err("Chat message cannot be empty".to_string());
+    }
+    if text.chars().count() > config.max_spectator_chat_length {
+        return Err(format!(
+            "Chat message too long (max {} characters)",
+            config.max_spectator_chat_length
+        ));
+    }
+    Ok(())
+}
+
 // Legacy validation functions using default constants for backward compatibility
 #[allow(dead_code)]
 pub fn validate_game_name(name: &str) -> Result<(), &'static str> {
@@ -1,5 +1,13 @@
-use std::collections::HashMap;
-use std::sync::Arc;
+use crate::config::{RateLimitKeyBy, RateLimitStrategy, TokenBucketConfig};
+use crate::protocol::ErrorDetails;
+use crate::security::ConnectionKey;
+use dashmap::DashMap;
+use std::borrow::Borrow;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::hash::Hash;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex, PoisonError};
 use tokio::sync::RwLock;
 use tokio::time::{Duration, Instant};
 use uuid::Uuid;
@@ -13,6 +21,29 @@ pub struct RateLimitConfig {
     pub time_window: Duration,
     /// Maximum number of join attempts per time window (including existing rooms)
     pub max_join_attempts: u32,
+    /// Which algorithm enforces the limits
+    pub strategy: RateLimitStrategy,
+    /// Room creation bucket, used with [`RateLimitStrategy::TokenBucket`];
+    /// `None` allows `max_room_creations` per `time_window`
+    pub room_creation_bucket: Option<TokenBucketConfig>,
+    /// Join attempt bucket, used with [`RateLimitStrategy::TokenBucket`];
+    /// `None` allows `max_join_attempts` per `time_window`
+    pub join_attempt_bucket: Option<TokenBucketConfig>,
+    /// Chat messages per spectator, regardless of strategy
+    pub spectator_chat_bucket: TokenBucketConfig,
+    /// Game data messages relayed per player, regardless of strategy; `None` is unlimited
+    pub game_data_bucket: Option<TokenBucketConfig>,
+    /// New connections per client IP, regardless of strategy; `None` is unlimited
+    pub connection_bucket: Option<TokenBucketConfig>,
+    /// What the limits above are counted against
+    pub key_by: RateLimitKeyBy,
+    /// Per-IP room creations per time window under [`RateLimitKeyBy::AppThenIp`]
+    pub ip_max_room_creations: u32,
+    /// Per-IP join attempts per time window under [`RateLimitKeyBy::AppThenIp`]
+    pub ip_max_join_attempts: u32,
+    /// Rooms one creator may have open at once, keyed like the limits above;
+    /// `None` is unlimited
+    pub max_active_rooms_per_creator: Option<u32>,
 }
 
 impl Default for RateLimitConfig {
@@ -21,10 +52,304 @@ impl Default for RateLimitConfig {
             max_room_creations: 5, // 5 room creations per minute
             time_window: Duration::from_secs(60),
             max_join_attempts: 20, // 20 join attempts per minute
+            strategy: RateLimitStrategy::TokenBucket,
+            room_creation_bucket: None,
+            join_attempt_bucket: None,
+            spectator_chat_bucket: crate::config::defaults::default_spectator_chat_bucket(),
+            game_data_bucket: None,
+            connection_bucket: None,
+            key_by: RateLimitKeyBy::Ip,
+            ip_max_room_creations: 20,
+            ip_max_join_attempts: 100,
+            max_active_rooms_per_creator: None,
         }
     }
 }
 
+impl RateLimitConfig {
+    /// Room creations allowed per key under [`RateLimitStrategy::TokenBucket`].
+    pub fn room_creation_quota(&self) -> Quota {
+        self.room_creation_bucket.map_or(
+            Quota::new(self.max_room_creations, self.time_window),
+            Quota::from,
+        )
+    }
+
+    /// Join attempts allowed per key under [`RateLimitStrategy::TokenBucket`].
+    pub fn join_attempt_quota(&self) -> Quota {
+        self.join_attempt_bucket.map_or(
+            Quota::new(self.max_join_attempts, self.time_window),
+            Quota::from,
+        )
+    }
+}
+
+/// Requests allowed per key: up to `burst` at once, replenished evenly over `period`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quota {
+    pub burst: u32,
+    pub period: Duration,
+}
+
+impl Quota {
+    pub const fn new(burst: u32, period: Duration) -> Self {
+        Self { burst, period }
+    }
+}
+
+impl From<TokenBucketConfig> for Quota {
+    fn from(bucket: TokenBucketConfig) -> Self {
+        Self::new(
+            bucket.capacity,
+            saturating_secs(f64::from(bucket.capacity) / bucket.refill_per_sec),
+        )
+    }
+}
+
+/// Latency pings answered per connection: one per second.
+pub const LATENCY_PING_QUOTA: Quota = Quota::new(1, Duration::from_secs(1));
+
+/// Time sync requests answered per connection: bursts of three, ten per minute.
+pub const TIME_SYNC_QUOTA: Quota = Quota::new(3, Duration::from_secs(18));
+
+/// Player reports accepted per connection: bursts of three, one every five minutes.
+pub const REPORT_QUOTA: Quota = Quota::new(3, Duration::from_secs(300));
+
+/// Per-key admission control, shared by room operations and per-app authentication.
+pub trait RateLimiter<K: ?Sized>: Send + Sync {
+    /// Admit one request for `key` under `quota`, or return how long to wait before retrying.
+    fn try_acquire(&self, key: &K, quota: Quota) -> Result<(), Duration>;
+
+    /// Drop state for keys that are back to their full allowance.
+    fn cleanup(&self);
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+    /// When the bucket will be full again, after which its state can be dropped
+    full_at: Option<Instant>,
+}
+
+/// Convert seconds to a `Duration`, saturating instead of panicking on overflow.
+fn saturating_secs(secs: f64) -> Duration {
+    Duration::try_from_secs_f64(secs).unwrap_or(Duration::MAX)
+}
+
+/// Token-bucket limiter: each key holds up to `burst` tokens that refill
+/// continuously, so throughput stays smooth instead of resetting at window edges.
+pub struct TokenBucketLimiter<K: Eq + Hash> {
+    buckets: DashMap<K, Bucket>,
+}
+
+impl<K: Eq + Hash> Default for TokenBucketLimiter<K> {
+    fn default() -> Self {
+        Self {
+            buckets: DashMap::new(),
+        }
+    }
+}
+
+impl<K: Eq + Hash> TokenBucketLimiter<K> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of keys currently tracked.
+    pub fn tracked_keys(&self) -> usize {
+        self.buckets.len()
+    }
+
+    /// Whole tokens `key` could spend right now under `quota`, without spending any.
+    pub fn remaining<Q>(&self, key: &Q, quota: Quota) -> u32
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.remaining_at(key, quota, Instant::now())
+    }
+
+    fn remaining_at<Q>(&self, key: &Q, quota: Quota, now: Instant) -> u32
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        if quota.burst == 0 || quota.period.is_zero() {
+            return quota.burst;
+        }
+        let Some(bucket) = self.buckets.get(key).map(|bucket| *bucket) else {
+            return quota.burst;
+        };
+        let burst = f64::from(quota.burst);
+        let per_token = quota.period.as_secs_f64() / burst;
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        // Truncation is intended: only whole tokens can be spent
+        (bucket.tokens + elapsed / per_token).min(burst).floor() as u32
+    }
+}
+
+impl<K, Q> RateLimiter<Q> for TokenBucketLimiter<K>
+where
+    K: Eq + Hash + Borrow<Q> + Send + Sync,
+    Q: Eq + Hash + ToOwned<Owned = K> + ?Sized,
+{
+    fn try_acquire(&self, key: &Q, quota: Quota) -> Result<(), Duration> {
+        self.try_acquire_at(key, quota, Instant::now())
+    }
+
+    fn cleanup(&self) {
+        let now = Instant::now();
+        self.buckets
+            .retain(|_, bucket| bucket.full_at.is_none_or(|full_at| full_at > now));
+    }
+}
+
+impl<K: Eq + Hash> TokenBucketLimiter<K> {
+    /// [`RateLimiter::try_acquire`] evaluated at `now`.
+    fn try_acquire_at<Q>(&self, key: &Q, quota: Quota, now: Instant) -> Result<(), Duration>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ToOwned<Owned = K> + ?Sized,
+    {
+        if quota.burst == 0 {
+            return Err(quota.period);
+        }
+        if quota.period.is_zero() {
+            return Ok(());
+        }
+
+        let burst = f64::from(quota.burst);
+        let per_token = quota.period.as_secs_f64() / burst;
+        let mut bucket = match self.buckets.get_mut(key) {
+            Some(bucket) => bucket,
+            None => self.buckets.entry(key.to_owned()).or_insert(Bucket {
+                tokens: burst,
+                updated: now,
+                full_at: None,
+            }),
+        };
+
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        let tokens = (bucket.tokens + elapsed / per_token).min(burst);
+        bucket.updated = now;
+        if tokens < 1.0 {
+            bucket.tokens = tokens;
+            return Err(saturating_secs((1.0 - tokens) * per_token));
+        }
+
+        bucket.tokens = tokens - 1.0;
+        bucket.full_at = now.checked_add(saturating_secs((burst - bucket.tokens) * per_token));
+        Ok(())
+    }
+}
+
+/// Details of a throttled request, so clients know when to back off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitInfo {
+    /// Requests allowed per window
+    pub limit: u32,
+    pub window: Duration,
+    /// Whole seconds until the oldest request leaves the window (at least 1)
+    pub retry_after_secs: u64,
+}
+
+/// Sliding-window log limiter for join attempts per key.
+///
+/// Keeps the timestamp of every admitted attempt inside the window, so the
+/// allowance frees up one attempt at a time instead of all at once when a
+/// fixed window resets. Stale keys are pruned during checks at most once per
+/// window, bounding memory without relying on the cleanup timer.
+pub struct SlidingWindowLimiter<K: Eq + Hash> {
+    /// Attempts allowed per window, and the window
+    limits: Mutex<(u32, Duration)>,
+    logs: DashMap<K, VecDeque<Instant>>,
+    last_sweep: Mutex<Instant>,
+}
+
+impl<K: Eq + Hash> SlidingWindowLimiter<K> {
+    pub fn new(max_attempts: u32, time_window: Duration) -> Self {
+        Self {
+            limits: Mutex::new((max_attempts, time_window)),
+            logs: DashMap::new(),
+            last_sweep: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Change the allowance; attempts already logged still count.
+    pub fn set_limits(&self, max_attempts: u32, time_window: Duration) {
+        *self.limits.lock().unwrap_or_else(PoisonError::into_inner) = (max_attempts, time_window);
+    }
+
+    fn limits(&self) -> (u32, Duration) {
+        *self.limits.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Admit one join attempt for `key`, or report when it may retry.
+    pub fn check(&self, key: K) -> Result<(), RateLimitInfo> {
+        self.check_at(key, Instant::now())
+    }
+
+    /// Number of keys currently tracked.
+    pub fn tracked_keys(&self) -> usize {
+        self.logs.len()
+    }
+
+    /// Drop keys with no attempts left inside the window.
+    pub fn cleanup(&self) {
+        self.prune_stale(Instant::now());
+    }
+
+    fn check_at(&self, key: K, now: Instant) -> Result<(), RateLimitInfo> {
+        self.maybe_sweep(now);
+        let (max_attempts, time_window) = self.limits();
+
+        let mut log = self.logs.entry(key).or_default();
+        while log
+            .front()
+            .is_some_and(|at| now.saturating_duration_since(*at) >= time_window)
+        {
+            log.pop_front();
+        }
+
+        if log.len() < max_attempts as usize {
+            log.push_back(now);
+            return Ok(());
+        }
+
+        let retry_after = log.front().map_or(time_window, |oldest| {
+            time_window.saturating_sub(now.saturating_duration_since(*oldest))
+        });
+        Err(RateLimitInfo {
+            limit: max_attempts,
+            window: time_window,
+            retry_after_secs: retry_after.as_secs_f64().ceil().max(1.0) as u64,
+        })
+    }
+
+    fn maybe_sweep(&self, now: Instant) {
+        {
+            let mut last_sweep = self
+                .last_sweep
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            if now.saturating_duration_since(*last_sweep) < self.limits().1 {
+                return;
+            }
+            *last_sweep = now;
+        }
+        self.prune_stale(now);
+    }
+
+    fn prune_stale(&self, now: Instant) {
+        let (_, time_window) = self.limits();
+        self.logs.retain(|_, log| {
+            log.back()
+                .is_some_and(|newest| now.saturating_duration_since(*newest) < time_window)
+        });
+    }
+}
+
 /// Rate limiter entry for tracking requests
 #[derive(Debug, Clone)]
 struct RateLimitEntry {
@@ -37,26 +362,26 @@ struct RateLimitEntry {
 }
 
 impl RateLimitEntry {
-    fn new() -> Self {
+    fn new(now: Instant) -> Self {
         Self {
             room_creations: 0,
             join_attempts: 0,
-            window_start: Instant::now(),
+            window_start: now,
         }
     }
 
     /// Reset the rate limit window if enough time has passed
-    fn maybe_reset_window(&mut self, config: &RateLimitConfig) {
-        if self.window_start.elapsed() >= config.time_window {
+    fn maybe_reset_window(&mut self, config: &RateLimitConfig, now: Instant) {
+        if now.saturating_duration_since(self.window_start) >= config.time_window {
             self.room_creations = 0;
             self.join_attempts = 0;
-            self.window_start = Instant::now();
+            self.window_start = now;
         }
     }
 
     /// Check if room creation is allowed and increment counter
-    fn try_room_creation(&mut self, config: &RateLimitConfig) -> bool {
-        self.maybe_reset_window(config);
+    fn try_room_creation(&mut self, config: &RateLimitConfig, now: Instant) -> bool {
+        self.maybe_reset_window(config, now);
         if self.room_creations < config.max_room_creations {
             self.room_creations += 1;
             self.join_attempts += 1;
@@ -67,8 +392,8 @@ impl RateLimitEntry {
     }
 
     /// Check if join attempt is allowed and increment counter
-    fn try_join_attempt(&mut self, config: &RateLimitConfig) -> bool {
-        self.maybe_reset_window(config);
+    fn try_join_attempt(&mut self, config: &RateLimitConfig, now: Instant) -> bool {
+        self.maybe_reset_window(config, now);
         if self.join_attempts < config.max_join_attempts {
             self.join_attempts += 1;
             true
@@ -85,51 +410,372 @@ impl RateLimitEntry {
     }
 }
 
+/// Identity that room creations and join attempts are counted against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RateLimitKey {
+    App(Uuid),
+    Ip(IpAddr),
+    Player(Uuid),
+}
+
+impl fmt::Display for RateLimitKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::App(app_id) => write!(f, "app:{app_id}"),
+            Self::Ip(ip) => write!(f, "ip:{ip}"),
+            Self::Player(player_id) => write!(f, "player:{player_id}"),
+        }
+    }
+}
+
+/// Where a room request came from, as far as the connection knows.
+#[derive(Debug, Clone, Copy)]
+pub struct RequestOrigin {
+    pub player_id: Uuid,
+    pub client_ip: Option<IpAddr>,
+    /// Authenticated application, if the connection authenticated
+    pub app_id: Option<Uuid>,
+}
+
+impl RequestOrigin {
+    /// Key for the primary limits: the app when `key_by` asks for it and one is
+    /// known, otherwise the client IP, otherwise the player.
+    pub fn key(&self, key_by: RateLimitKeyBy) -> RateLimitKey {
+        match (key_by, self.app_id, self.client_ip) {
+            (RateLimitKeyBy::App | RateLimitKeyBy::AppThenIp, Some(app_id), _) => {
+                RateLimitKey::App(app_id)
+            }
+            (_, _, Some(ip)) => RateLimitKey::Ip(ip),
+            (_, _, None) => RateLimitKey::Player(self.player_id),
+        }
+    }
+}
+
 /// Rate limiter for room operations
 pub struct RoomRateLimiter {
+    limits: std::sync::RwLock<Arc<Limits>>,
+    /// Fixed-window entries by rate-limit key
+    entries: Arc<RwLock<HashMap<RateLimitKey, RateLimitEntry>>>,
+    /// Room creation buckets, used with [`RateLimitStrategy::TokenBucket`]
+    room_creation_buckets: TokenBucketLimiter<RateLimitKey>,
+    /// Join attempt buckets, used with [`RateLimitStrategy::TokenBucket`]
+    join_attempt_buckets: TokenBucketLimiter<RateLimitKey>,
+    /// Chat buckets per spectator
+    spectator_chat_buckets: TokenBucketLimiter<Uuid>,
+    /// Game data buckets per player
+    game_data_buckets: TokenBucketLimiter<Uuid>,
+    /// Latency ping buckets per player
+    latency_ping_buckets: TokenBucketLimiter<Uuid>,
+    /// Time sync buckets per player
+    time_sync_buckets: TokenBucketLimiter<Uuid>,
+    /// Player report buckets per reporter
+    report_buckets: TokenBucketLimiter<Uuid>,
+    /// New connection buckets per client IP or IPv6 network
+    connection_buckets: TokenBucketLimiter<ConnectionKey>,
+    /// Join attempt logs, used with [`RateLimitStrategy::SlidingWindow`]
+    join_log: SlidingWindowLimiter<RateLimitKey>,
+    /// Safety-net entries by client IP
+    ip_entries: RwLock<HashMap<IpAddr, RateLimitEntry>>,
+}
+
+/// Limits in force, replaced as a whole by [`RoomRateLimiter::reconfigure`].
+#[derive(Debug)]
+struct Limits {
     config: RateLimitConfig,
-    /// Rate limit entries by player ID
-    entries: Arc<RwLock<HashMap<Uuid, RateLimitEntry>>>,
+    /// Fixed-window limits for the per-IP safety net under [`RateLimitKeyBy::AppThenIp`]
+    ip_limits: RateLimitConfig,
+}
+
+impl Limits {
+    fn new(config: RateLimitConfig) -> Self {
+        let ip_limits = RateLimitConfig {
+            max_room_creations: config.ip_max_room_creations,
+            max_join_attempts: config.ip_max_join_attempts,
+            ..config.clone()
+        };
+        Self { config, ip_limits }
+    }
 }
 
 impl RoomRateLimiter {
     pub fn new(config: RateLimitConfig) -> Self {
         Self {
-            config,
+            join_log: SlidingWindowLimiter::new(config.max_join_attempts, config.time_window),
+            limits: std::sync::RwLock::new(Arc::new(Limits::new(config))),
             entries: Arc::new(RwLock::new(HashMap::new())),
+            room_creation_buckets: TokenBucketLimiter::new(),
+            join_attempt_buckets: TokenBucketLimiter::new(),
+            spectator_chat_buckets: TokenBucketLimiter::new(),
+            game_data_buckets: TokenBucketLimiter::new(),
+            latency_ping_buckets: TokenBucketLimiter::new(),
+            time_sync_buckets: TokenBucketLimiter::new(),
+            report_buckets: TokenBucketLimiter::new(),
+            connection_buckets: TokenBucketLimiter::new(),
+            ip_entries: RwLock::new(HashMap::new()),
         }
     }
 
+    /// Apply new limits to subsequent checks. Requests already counted in the
+    /// current window stay counted.
+    pub fn reconfigure(&self, config: RateLimitConfig) {
+        self.join_log
+            .set_limits(config.max_join_attempts, config.time_window);
+        *self.limits.write().unwrap_or_else(PoisonError::into_inner) =
+            Arc::new(Limits::new(config));
+    }
+
+    /// The configuration currently in force.
+    pub fn config(&self) -> RateLimitConfig {
+        self.limits().config.clone()
+    }
+
+    fn limits(&self) -> Arc<Limits> {
+        Arc::clone(&self.limits.read().unwrap_or_else(PoisonError::into_inner))
+    }
+
     /// Check if a room creation request is allowed for the given player
     pub async fn check_room_creation(&self, player_id: &Uuid) -> Result<(), RateLimitError> {
+        self.check_room_creation_by(RateLimitKey::Player(*player_id))
+            .await
+    }
+
+    /// Check if a join attempt is allowed for the given player
+    pub async fn check_join_attempt(&self, player_id: &Uuid) -> Result<(), RateLimitError> {
+        self.check_join_attempt_by(RateLimitKey::Player(*player_id))
+            .await
+    }
+
+    /// Check if a player may relay another game data message
+    pub fn check_game_data(&self, player_id: &Uuid) -> Result<(), RateLimitError> {
+        let Some(bucket) = self.limits().config.game_data_bucket else {
+            return Ok(());
+        };
+        self.game_data_buckets
+            .try_acquire(player_id, bucket.into())
+            .map_err(|retry_after| RateLimitError::GameDataLimitExceeded {
+                retry_after,
+                limit: bucket.capacity,
+            })
+    }
+
+    /// Check if a client may open another connection from `key`
+    pub fn check_connection(&self, key: &ConnectionKey) -> Result<(), RateLimitError> {
+        let Some(bucket) = self.limits().config.connection_bucket else {
+            return Ok(());
+        };
+        self.connection_buckets
+            .try_acquire(key, bucket.into())
+            .map_err(|retry_after| RateLimitError::ConnectionLimitExceeded {
+                retry_after,
+                limit: bucket.capacity,
+            })
+    }
+
+    /// Check if a player may send another latency ping
+    pub fn check_latency_ping(&self, player_id: &Uuid) -> Result<(), RateLimitError> {
+        self.latency_ping_buckets
+            .try_acquire(player_id, LATENCY_PING_QUOTA)
+            .map_err(|retry_after| RateLimitError::LatencyPingLimitExceeded {
+                retry_after,
+                limit: LATENCY_PING_QUOTA.burst,
+            })
+    }
+
+    /// Check if a player may send another time sync request
+    pub fn check_time_sync(&self, player_id: &Uuid) -> Result<(), RateLimitError> {
+        self.time_sync_buckets
+            .try_acquire(player_id, TIME_SYNC_QUOTA)
+            .map_err(|retry_after| RateLimitError::TimeSyncLimitExceeded {
+                retry_after,
+                limit: TIME_SYNC_QUOTA.burst,
+            })
+    }
+
+    /// Room creations the origin could make right now. `None` unless the
+    /// strategy is [`RateLimitStrategy::TokenBucket`].
+    pub fn remaining_room_creations(&self, origin: &RequestOrigin) -> Option<u32> {
+        let limits = self.limits();
+        (limits.config.strategy == RateLimitStrategy::TokenBucket).then(|| {
+            self.room_creation_buckets.remaining(
+                &origin.key(limits.config.key_by),
+                limits.config.room_creation_quota(),
+            )
+        })
+    }
+
+    /// Join attempts the origin could make right now. `None` unless the
+    /// strategy is [`RateLimitStrategy::TokenBucket`].
+    pub fn remaining_join_attempts(&self, origin: &RequestOrigin) -> Option<u32> {
+        let limits = self.limits();
+        (limits.config.strategy == RateLimitStrategy::TokenBucket).then(|| {
+            self.join_attempt_buckets.remaining(
+                &origin.key(limits.config.key_by),
+                limits.config.join_attempt_quota(),
+            )
+        })
+    }
+
+    /// Check if a spectator may send another chat message
+    pub fn check_spectator_chat(&self, spectator_id: &Uuid) -> Result<(), RateLimitError> {
+        let bucket = self.limits().config.spectator_chat_bucket;
+        self.spectator_chat_buckets
+            .try_acquire(spectator_id, bucket.into())
+            .map_err(|retry_after| RateLimitError::SpectatorChatLimitExceeded {
+                retry_after,
+                limit: bucket.capacity,
+            })
+    }
+
+    /// Check if a player may file another player report
+    pub fn check_report(&self, player_id: &Uuid) -> Result<(), RateLimitError> {
+        self.report_buckets
+            .try_acquire(player_id, REPORT_QUOTA)
+            .map_err(|retry_after| RateLimitError::ReportLimitExceeded {
+                retry_after,
+                limit: REPORT_QUOTA.burst,
+            })
+    }
+
+    /// Check a room creation request against the key chosen by `key_by`,
+    /// and against the per-IP safety net when it applies.
+    pub async fn check_room_creation_for(
+        &self,
+        origin: &RequestOrigin,
+    ) -> Result<(), RateLimitError> {
+        self.check_room_creation_by(origin.key(self.limits().config.key_by))
+            .await?;
+        match self.safety_net_ip(origin) {
+            Some(ip) => self.check_ip_safety_net(ip, true).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Check a join attempt against the key chosen by `key_by`, and against
+    /// the per-IP safety net when it applies.
+    pub async fn check_join_attempt_for(
+        &self,
+        origin: &RequestOrigin,
+    ) -> Result<(), RateLimitError> {
+        self.check_join_attempt_by(origin.key(self.limits().config.key_by))
+            .await?;
+        match self.safety_net_ip(origin) {
+            Some(ip) => self.check_ip_safety_net(ip, false).await,
+            None => Ok(()),
+        }
+    }
+
+    async fn check_room_creation_by(&self, key: RateLimitKey) -> Result<(), RateLimitError> {
+        let limits = self.limits();
+        if limits.config.strategy == RateLimitStrategy::TokenBucket {
+            let quota = limits.config.room_creation_quota();
+            self.room_creation_buckets
+                .try_acquire(&key, quota)
+                .map_err(|retry_after| RateLimitError::RoomCreationLimitExceeded {
+                    retry_after,
+                    limit: quota.burst,
+                })?;
+            // A creation is also a join attempt, as with the fixed window
+            let _ = self
+                .join_attempt_buckets
+                .try_acquire(&key, limits.config.join_attempt_quota());
+            return Ok(());
+        }
+
         let mut entries = self.entries.write().await;
         let entry = entries
-            .entry(*player_id)
-            .or_insert_with(RateLimitEntry::new);
+            .entry(key)
+            .or_insert_with(|| RateLimitEntry::new(Instant::now()));
 
-        if entry.try_room_creation(&self.config) {
-            Ok(())
-        } else {
-            let reset_time = entry.time_until_reset(&self.config);
-            Err(RateLimitError::RoomCreationLimitExceeded {
+        if !entry.try_room_creation(&limits.config, Instant::now()) {
+            let reset_time = entry.time_until_reset(&limits.config);
+            return Err(RateLimitError::RoomCreationLimitExceeded {
                 retry_after: reset_time,
-            })
+                limit: limits.config.max_room_creations,
+            });
+        }
+        drop(entries);
+
+        if limits.config.strategy == RateLimitStrategy::SlidingWindow {
+            let _ = self.join_log.check(key);
         }
+        Ok(())
     }
 
-    /// Check if a join attempt is allowed for the given player
-    pub async fn check_join_attempt(&self, player_id: &Uuid) -> Result<(), RateLimitError> {
+    async fn check_join_attempt_by(&self, key: RateLimitKey) -> Result<(), RateLimitError> {
+        let limits = self.limits();
+        match limits.config.strategy {
+            RateLimitStrategy::TokenBucket => {
+                let quota = limits.config.join_attempt_quota();
+                return self
+                    .join_attempt_buckets
+                    .try_acquire(&key, quota)
+                    .map_err(|retry_after| RateLimitError::JoinLimitExceeded {
+                        retry_after,
+                        limit: quota.burst,
+                    });
+            }
+            RateLimitStrategy::SlidingWindow => {
+                return self.join_log.check(key).map_err(|info| {
+                    RateLimitError::JoinLimitExceeded {
+                        retry_after: Duration::from_secs(info.retry_after_secs),
+                        limit: info.limit,
+                    }
+                });
+            }
+            RateLimitStrategy::FixedWindow => {}
+        }
+
         let mut entries = self.entries.write().await;
         let entry = entries
-            .entry(*player_id)
-            .or_insert_with(RateLimitEntry::new);
+            .entry(key)
+            .or_insert_with(|| RateLimitEntry::new(Instant::now()));
 
-        if entry.try_join_attempt(&self.config) {
+        if entry.try_join_attempt(&limits.config, Instant::now()) {
             Ok(())
         } else {
-            let reset_time = entry.time_until_reset(&self.config);
+            let reset_time = entry.time_until_reset(&limits.config);
             Err(RateLimitError::JoinLimitExceeded {
                 retry_after: reset_time,
+                limit: limits.config.max_join_attempts,
+            })
+        }
+    }
+
+    /// The client IP to hold to the safety net, when requests are keyed by app.
+    fn safety_net_ip(&self, origin: &RequestOrigin) -> Option<IpAddr> {
+        match (self.limits().config.key_by, origin.app_id) {
+            (RateLimitKeyBy::AppThenIp, Some(_)) => origin.client_ip,
+            _ => None,
+        }
+    }
+
+    async fn check_ip_safety_net(
+        &self,
+        ip: IpAddr,
+        is_creation: bool,
+    ) -> Result<(), RateLimitError> {
+        let limits = self.limits();
+        let now = Instant::now();
+        let mut entries = self.ip_entries.write().await;
+        let entry = entries
+            .entry(ip)
+            .or_insert_with(|| RateLimitEntry::new(now));
+
+        if is_creation {
+            if entry.try_room_creation(&limits.ip_limits, now) {
+                return Ok(());
+            }
+            Err(RateLimitError::RoomCreationLimitExceeded {
+                retry_after: entry.time_until_reset(&limits.ip_limits),
+                limit: limits.ip_limits.max_room_creations,
+            })
+        } else {
+            if entry.try_join_attempt(&limits.ip_limits, now) {
+                return Ok(());
+            }
+            Err(RateLimitError::JoinLimitExceeded {
+                retry_after: entry.time_until_reset(&limits.ip_limits),
+                limit: limits.ip_limits.max_join_attempts,
             })
         }
     }
@@ -140,59 +786,129 @@ impl RoomRateLimiter {
         let now = Instant::now();
 
         // Remove entries that haven't been used for 2x the time window
-        let cleanup_threshold = self.config.time_window * 2;
+        let cleanup_threshold = self.limits().config.time_window * 2;
         entries.retain(|_, entry| now.duration_since(entry.window_start) < cleanup_threshold);
+        drop(entries);
+        self.ip_entries
+            .write()
+            .await
+            .retain(|_, entry| now.duration_since(entry.window_start) < cleanup_threshold);
+
+        RateLimiter::<RateLimitKey>::cleanup(&self.room_creation_buckets);
+        RateLimiter::<RateLimitKey>::cleanup(&self.join_attempt_buckets);
+        RateLimiter::<Uuid>::cleanup(&self.spectator_chat_buckets);
+        RateLimiter::<Uuid>::cleanup(&self.game_data_buckets);
+        RateLimiter::<Uuid>::cleanup(&self.latency_ping_buckets);
+        RateLimiter::<Uuid>::cleanup(&self.time_sync_buckets);
+        RateLimiter::<Uuid>::cleanup(&self.report_buckets);
+        RateLimiter::<ConnectionKey>::cleanup(&self.connection_buckets);
+        self.join_log.cleanup();
     }
 
     /// Start a background task to periodically clean up old entries
     pub fn start_cleanup_task(self: Arc<Self>) {
         let rate_limiter = Arc::clone(&self);
         tokio::spawn(async move {
-            let mut interval = tokio::time::interval(rate_limiter.config.time_window);
+            // Re-read the window each pass so reconfigured limits take effect
             loop {
-                interval.tick().await;
+                tokio::time::sleep(rate_limiter.limits().config.time_window).await;
                 rate_limiter.cleanup_old_entries().await;
             }
         });
     }
 
-    /// Get current stats for a player (for debugging/monitoring)
+    /// Get current stats for a player (for debugging/monitoring).
+    /// Only fixed-window counters for player-keyed requests are reported.
     pub async fn get_player_stats(&self, player_id: &Uuid) -> Option<PlayerRateStats> {
         let entries = self.entries.read().await;
-        entries.get(player_id).map(|entry| PlayerRateStats {
-            room_creations: entry.room_creations,
-            join_attempts: entry.join_attempts,
-            time_until_reset: entry.time_until_reset(&self.config),
-        })
+        entries
+            .get(&RateLimitKey::Player(*player_id))
+            .map(|entry| PlayerRateStats {
+                room_creations: entry.room_creations,
+                join_attempts: entry.join_attempts,
+                time_until_reset: entry.time_until_reset(&self.limits().config),
+            })
     }
 }
 
 /// Rate limiting errors
+///
+/// `limit` is the allowance that was exhausted: requests per window, or the
+/// token bucket's burst size.
 #[derive(Debug, Clone)]
 pub enum RateLimitError {
-    RoomCreationLimitExceeded { retry_after: Duration },
-    JoinLimitExceeded { retry_after: Duration },
+    RoomCreationLimitExceeded { retry_after: Duration, limit: u32 },
+    JoinLimitExceeded { retry_after: Duration, limit: u32 },
+    SpectatorChatLimitExceeded { retry_after: Duration, limit: u32 },
+    GameDataLimitExceeded { retry_after: Duration, limit: u32 },
+    LatencyPingLimitExceeded { retry_after: Duration, limit: u32 },
+    TimeSyncLimitExceeded { retry_after: Duration, limit: u32 },
+    ReportLimitExceeded { retry_after: Duration, limit: u32 },
+    ConnectionLimitExceeded { retry_after: Duration, limit: u32 },
 }
 
-impl std::fmt::Display for RateLimitError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl RateLimitError {
+    /// How long the client should wait before retrying.
+    pub fn retry_after(&self) -> Duration {
         match self {
-            Self::RoomCreationLimitExceeded { retry_after } => {
-                write!(
-                    f,
-                    "Room creation rate limit exceeded. Try again in {} seconds.",
-                    retry_after.as_secs()
-                )
-            }
-            Self::JoinLimitExceeded { retry_after } => {
-                write!(
-                    f,
-                    "Join attempt rate limit exceeded. Try again in {} seconds.",
-                    retry_after.as_secs()
-                )
-            }
+            Self::RoomCreationLimitExceeded { retry_after, .. }
+            | Self::JoinLimitExceeded { retry_after, .. }
+            | Self::SpectatorChatLimitExceeded { retry_after, .. }
+            | Self::GameDataLimitExceeded { retry_after, .. }
+            | Self::LatencyPingLimitExceeded { retry_after, .. }
+            | Self::TimeSyncLimitExceeded { retry_after, .. }
+            | Self::ReportLimitExceeded { retry_after, .. }
+            | Self::ConnectionLimitExceeded { retry_after, .. } => *retry_after,
+        }
+    }
+
+    /// [`Self::retry_after`] rounded up to whole seconds, and at least 1.
+    pub fn retry_after_secs(&self) -> u64 {
+        self.retry_after().as_secs_f64().ceil().max(1.0) as u64
+    }
+
+    pub fn limit(&self) -> u32 {
+        match self {
+            Self::RoomCreationLimitExceeded { limit, .. }
+            | Self::JoinLimitExceeded { limit, .. }
+            | Self::SpectatorChatLimitExceeded { limit, .. }
+            | Self::GameDataLimitExceeded { limit, .. }
+            | Self::LatencyPingLimitExceeded { limit, .. }
+            | Self::TimeSyncLimitExceeded { limit, .. }
+            | Self::ReportLimitExceeded { limit, .. }
+            | Self::ConnectionLimitExceeded { limit, .. } => *limit,
         }
     }
+
+    /// Details to send with `RATE_LIMIT_EXCEEDED`. Nothing is left of the
+    /// allowance until `retry_after_secs` has passed.
+    pub fn details(&self) -> ErrorDetails {
+        ErrorDetails::RateLimited {
+            retry_after_secs: self.retry_after_secs(),
+            limit: self.limit(),
+            remaining: 0,
+        }
+    }
+}
+
+impl std::fmt::Display for RateLimitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let what = match self {
+            Self::RoomCreationLimitExceeded { .. } => "Room creation",
+            Self::JoinLimitExceeded { .. } => "Join attempt",
+            Self::SpectatorChatLimitExceeded { .. } => "Spectator chat",
+            Self::GameDataLimitExceeded { .. } => "Game data",
+            Self::LatencyPingLimitExceeded { .. } => "Latency ping",
+            Self::TimeSyncLimitExceeded { .. } => "Time sync",
+            Self::ReportLimitExceeded { .. } => "Player report",
+            Self::ConnectionLimitExceeded { .. } => "Connection",
+        };
+        write!(
+            f,
+            "{what} rate limit exceeded. Try again in {} seconds.",
+            self.retry_after_secs()
+        )
+    }
 }
 
 impl std::error::Error for RateLimitError {}
@@ -208,12 +924,15 @@ pub struct PlayerRateStats {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     fn create_test_config() -> RateLimitConfig {
         RateLimitConfig {
             max_room_creations: 2,
             time_window: Duration::from_millis(100),
             max_join_attempts: 3,
+            strategy: RateLimitStrategy::FixedWindow,
+            ..Default::default()
         }
     }
 
@@ -288,6 +1007,8 @@ mod tests {
             max_room_creations: 1,
             time_window: Duration::from_millis(50),
             max_join_attempts: 1,
+            strategy: RateLimitStrategy::FixedWindow,
+            ..Default::default()
         };
         let limiter = RoomRateLimiter::new(config);
         let player_id = Uuid::new_v4();
@@ -324,4 +1045,449 @@ mod tests {
         assert_eq!(stats.room_creations, 1);
         assert_eq!(stats.join_attempts, 2); // Room creation counts as join attempt too
     }
+
+    #[test]
+    fn token_bucket_allows_burst_then_refills() {
+        let limiter = TokenBucketLimiter::<Uuid>::new();
+        let player_id = Uuid::new_v4();
+        let quota = Quota::new(3, Duration::from_secs(3));
+        let start = Instant::now();
+
+        for _ in 0..3 {
+            assert!(limiter.try_acquire_at(&player_id, quota, start).is_ok());
+        }
+        let retry_after = limiter
+            .try_acquire_at(&player_id, quota, start)
+            .unwrap_err();
+        assert_eq!(retry_after, Duration::from_secs(1));
+
+        // One token per second comes back
+        let later = start + Duration::from_secs(1);
+        assert!(limiter.try_acquire_at(&player_id, quota, later).is_ok());
+        assert!(limiter.try_acquire_at(&player_id, quota, later).is_err());
+    }
+
+    #[tokio::test]
+    async fn token_bucket_strategy_limits_room_creation_and_joins() {
+        let config = RateLimitConfig {
+            strategy: RateLimitStrategy::TokenBucket,
+            room_creation_bucket: Some(TokenBucketConfig {
+                capacity: 2,
+                refill_per_sec: 0.01,
+            }),
+            join_attempt_bucket: Some(TokenBucketConfig {
+                capacity: 3,
+                refill_per_sec: 0.01,
+            }),
+            ..create_test_config()
+        };
+        let limiter = RoomRateLimiter::new(config);
+        let player_id = Uuid::new_v4();
+
+        assert!(limiter.check_room_creation(&player_id).await.is_ok());
+        assert!(limiter.check_room_creation(&player_id).await.is_ok());
+        assert!(matches!(
+            limiter.check_room_creation(&player_id).await,
+            Err(RateLimitError::RoomCreationLimitExceeded { .. })
+        ));
+
+        // Both creations also drew from the join bucket
+        assert!(limiter.check_join_attempt(&player_id).await.is_ok());
+        assert!(matches!(
+            limiter.check_join_attempt(&player_id).await,
+            Err(RateLimitError::JoinLimitExceeded { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn token_bucket_falls_back_to_legacy_limits() {
+        let limiter = RoomRateLimiter::new(RateLimitConfig {
+            max_room_creations: 2,
+            time_window: Duration::from_secs(60),
+            max_join_attempts: 3,
+            ..Default::default()
+        });
+        let origin = origin(None, None);
+        assert_eq!(limiter.remaining_room_creations(&origin), Some(2));
+        assert_eq!(limiter.remaining_join_attempts(&origin), Some(3));
+
+        assert!(limiter.check_room_creation_for(&origin).await.is_ok());
+        assert_eq!(limiter.remaining_room_creations(&origin), Some(1));
+        assert_eq!(limiter.remaining_join_attempts(&origin), Some(2));
+        assert!(limiter.check_room_creation_for(&origin).await.is_ok());
+
+        // Two per minute refills one token every 30 seconds
+        let err = limiter.check_room_creation_for(&origin).await.unwrap_err();
+        assert!(matches!(
+            err,
+            RateLimitError::RoomCreationLimitExceeded { .. }
+        ));
+        assert!(err.retry_after() <= Duration::from_secs(30));
+        assert_eq!(err.retry_after_secs(), 30);
+    }
+
+    #[tokio::test]
+    async fn remaining_is_only_reported_for_token_buckets() {
+        let limiter = RoomRateLimiter::new(create_test_config());
+        let origin = origin(None, None);
+        assert_eq!(limiter.remaining_room_creations(&origin), None);
+        assert_eq!(limiter.remaining_join_attempts(&origin), None);
+    }
+
+    #[test]
+    fn game_data_is_unlimited_unless_configured() {
+        let player_id = Uuid::new_v4();
+        let unlimited = RoomRateLimiter::new(RateLimitConfig::default());
+        for _ in 0..100 {
+            assert!(unlimited.check_game_data(&player_id).is_ok());
+        }
+
+        let limited = RoomRateLimiter::new(RateLimitConfig {
+            game_data_bucket: Some(TokenBucketConfig {
+                capacity: 2,
+                refill_per_sec: 0.01,
+            }),
+            ..Default::default()
+        });
+        assert!(limited.check_game_data(&player_id).is_ok());
+        assert!(limited.check_game_data(&player_id).is_ok());
+        assert!(matches!(
+            limited.check_game_data(&player_id),
+            Err(RateLimitError::GameDataLimitExceeded { .. })
+        ));
+        assert!(limited.check_game_data(&Uuid::new_v4()).is_ok());
+    }
+
+    #[test]
+    fn connections_are_limited_per_ip_with_a_burst_allowance() {
+        let key = ConnectionKey::new("203.0.113.7".parse().unwrap(), 64);
+        let unlimited = RoomRateLimiter::new(RateLimitConfig::default());
+        for _ in 0..100 {
+            assert!(unlimited.check_connection(&key).is_ok());
+        }
+
+        let limited = RoomRateLimiter::new(RateLimitConfig {
+            connection_bucket: Some(TokenBucketConfig {
+                capacity: 3,
+                refill_per_sec: 0.1,
+            }),
+            ..Default::default()
+        });
+        // A reconnection storm up to the burst is admitted at once
+        for _ in 0..3 {
+            assert!(limited.check_connection(&key).is_ok());
+        }
+        let err = limited.check_connection(&key).unwrap_err();
+        assert!(matches!(
+            err,
+            RateLimitError::ConnectionLimitExceeded { limit: 3, .. }
+        ));
+        assert_eq!(err.retry_after_secs(), 10);
+
+        // Other addresses have their own allowance; an IPv6 network shares one
+        let other = ConnectionKey::new("203.0.113.8".parse().unwrap(), 64);
+        assert!(limited.check_connection(&other).is_ok());
+        let network = |ip: &str| ConnectionKey::new(ip.parse().unwrap(), 64);
+        for _ in 0..3 {
+            assert!(limited.check_connection(&network("2001:db8::1")).is_ok());
+        }
+        assert!(limited.check_connection(&network("2001:db8::2")).is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn token_bucket_never_exceeds_capacity_plus_refill(
+            capacity in 1u32..20,
+            refill_per_sec in 0.5f64..50.0,
+            gaps_ms in proptest::collection::vec(0u64..500, 1..200),
+        ) {
+            let limiter = TokenBucketLimiter::<u8>::new();
+            let quota = Quota::from(TokenBucketConfig { capacity, refill_per_sec });
+            let start = Instant::now();
+            let mut now = start;
+            let mut admitted = Vec::new();
+            for gap in gaps_ms {
+                now += Duration::from_millis(gap);
+                if limiter.try_acquire_at(&0, quota, now).is_ok() {
+                    admitted.push(now - start);
+                }
+            }
+
+            // Every interval between two admissions holds at most capacity + rate * t of them
+            for (i, first) in admitted.iter().enumerate() {
+                for (j, last) in admitted.iter().enumerate().skip(i) {
+                    let count = (j - i + 1) as f64;
+                    let bound = f64::from(capacity) + refill_per_sec * (*last - *first).as_secs_f64();
+                    prop_assert!(count <= bound + 1e-6, "{count} admitted within {:?}", *last - *first);
+                }
+            }
+        }
+    }
+
+    /// Drive a request every 10ms for five seconds and record when each was admitted.
+    fn admitted_under_sustained_load(mut admit: impl FnMut(Instant) -> bool) -> Vec<Duration> {
+        let start = Instant::now();
+        (0..500)
+            .map(|tick| Duration::from_millis(tick * 10))
+            .filter(|offset| admit(start + *offset))
+            .collect()
+    }
+
+    #[test]
+    fn token_bucket_throttles_more_smoothly_than_fixed_window() {
+        let config = RateLimitConfig {
+            time_window: Duration::from_secs(1),
+            max_join_attempts: 10,
+            ..Default::default()
+        };
+
+        let mut entry = RateLimitEntry::new(Instant::now());
+        let fixed = admitted_under_sustained_load(|now| entry.try_join_attempt(&config, now));
+
+        let buckets = TokenBucketLimiter::<Uuid>::new();
+        let player_id = Uuid::new_v4();
+        let quota = Quota::new(config.max_join_attempts, config.time_window);
+        let bucket = admitted_under_sustained_load(|now| {
+            buckets.try_acquire_at(&player_id, quota, now).is_ok()
+        });
+
+        // Same long-run rate of ten per second; the bucket only adds its initial burst
+        assert_eq!(fixed.len(), 50);
+        assert!((50..=60).contains(&bucket.len()), "{}", bucket.len());
+
+        // Once the initial burst is spent, the bucket admits at an even pace
+        // while the fixed window admits a full burst at every reset
+        let busiest_span = |admitted: &[Duration]| {
+            admitted
+                .iter()
+                .filter(|at| **at >= Duration::from_secs(1))
+                .map(|from| {
+                    admitted
+                        .iter()
+                        .filter(|at| **at >= *from && **at < *from + Duration::from_millis(200))
+                        .count()
+                })
+                .max()
+                .unwrap_or(0)
+        };
+        let longest_gap = |admitted: &[Duration]| {
+            admitted
+                .windows(2)
+                .map(|pair| pair[1] - pair[0])
+                .max()
+                .unwrap_or_default()
+        };
+
+        assert_eq!(busiest_span(&fixed), 10);
+        assert!(busiest_span(&bucket) <= 3, "{}", busiest_span(&bucket));
+        assert!(longest_gap(&fixed) >= Duration::from_millis(900));
+        assert!(longest_gap(&bucket) <= Duration::from_millis(110));
+    }
+
+    #[test]
+    fn sliding_window_frees_one_attempt_at_a_time() {
+        let limiter = SlidingWindowLimiter::new(3, Duration::from_secs(10));
+        let ip: IpAddr = "203.0.113.7".parse().unwrap();
+        let start = Instant::now();
+
+        for offset in [0, 2, 4] {
+            assert!(limiter
+                .check_at(ip, start + Duration::from_secs(offset))
+                .is_ok());
+        }
+        let info = limiter
+            .check_at(ip, start + Duration::from_secs(5))
+            .unwrap_err();
+        assert_eq!(
+            info,
+            RateLimitInfo {
+                limit: 3,
+                window: Duration::from_secs(10),
+                retry_after_secs: 5,
+            }
+        );
+
+        // Only the oldest attempt has left the window
+        let later = start + Duration::from_secs(10);
+        assert!(limiter.check_at(ip, later).is_ok());
+        let info = limiter.check_at(ip, later).unwrap_err();
+        assert_eq!(info.retry_after_secs, 2);
+    }
+
+    #[test]
+    fn sliding_window_prunes_stale_ips_during_checks() {
+        let window = Duration::from_secs(10);
+        let limiter = SlidingWindowLimiter::new(5, window);
+        let start = Instant::now();
+        for last_octet in 0..100u8 {
+            let ip = IpAddr::from([198, 51, 100, last_octet]);
+            assert!(limiter.check_at(ip, start).is_ok());
+        }
+        assert_eq!(limiter.tracked_keys(), 100);
+
+        let fresh: IpAddr = "192.0.2.1".parse().unwrap();
+        assert!(limiter.check_at(fresh, start + window).is_ok());
+        assert_eq!(limiter.tracked_keys(), 1);
+    }
+
+    fn origin(client_ip: Option<IpAddr>, app_id: Option<Uuid>) -> RequestOrigin {
+        RequestOrigin {
+            player_id: Uuid::new_v4(),
+            client_ip,
+            app_id,
+        }
+    }
+
+    #[tokio::test]
+    async fn sliding_window_strategy_limits_joins_per_ip() {
+        let config = RateLimitConfig {
+            strategy: RateLimitStrategy::SlidingWindow,
+            max_join_attempts: 2,
+            ..create_test_config()
+        };
+        let limiter = RoomRateLimiter::new(config);
+        let shared_ip: Option<IpAddr> = Some("203.0.113.9".parse().unwrap());
+
+        // A creation counts as a join from its IP
+        assert!(limiter
+            .check_room_creation_for(&origin(shared_ip, None))
+            .await
+            .is_ok());
+        assert!(limiter
+            .check_join_attempt_for(&origin(shared_ip, None))
+            .await
+            .is_ok());
+        let err = limiter
+            .check_join_attempt_for(&origin(shared_ip, None))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            RateLimitError::JoinLimitExceeded { retry_after, .. } if retry_after >= Duration::from_secs(1)
+        ));
+
+        let other_ip = Some("203.0.113.10".parse().unwrap());
+        assert!(limiter
+            .check_join_attempt_for(&origin(other_ip, None))
+            .await
+            .is_ok());
+        // Without a known IP the player is the key
+        assert!(limiter
+            .check_join_attempt_for(&origin(None, None))
+            .await
+            .is_ok());
+    }
+
+    #[test]
+    fn request_origin_key_falls_back_from_app_to_ip_to_player() {
+        let ip: IpAddr = "198.51.100.4".parse().unwrap();
+        let app_id = Uuid::new_v4();
+        let authed = origin(Some(ip), Some(app_id));
+        let anonymous = origin(Some(ip), None);
+        let unknown = origin(None, None);
+
+        assert_eq!(authed.key(RateLimitKeyBy::Ip), RateLimitKey::Ip(ip));
+        assert_eq!(authed.key(RateLimitKeyBy::App), RateLimitKey::App(app_id));
+        assert_eq!(
+            authed.key(RateLimitKeyBy::AppThenIp),
+            RateLimitKey::App(app_id)
+        );
+        assert_eq!(anonymous.key(RateLimitKeyBy::App), RateLimitKey::Ip(ip));
+        assert_eq!(
+            unknown.key(RateLimitKeyBy::AppThenIp),
+            RateLimitKey::Player(unknown.player_id)
+        );
+    }
+
+    #[tokio::test]
+    async fn app_keyed_limits_are_shared_across_ips() {
+        let limiter = RoomRateLimiter::new(RateLimitConfig {
+            key_by: RateLimitKeyBy::App,
+            ..create_test_config()
+        });
+        let app_id = Some(Uuid::new_v4());
+
+        for last_octet in 1..=3u8 {
+            let ip = Some(IpAddr::from([192, 0, 2, last_octet]));
+            assert!(limiter
+                .check_join_attempt_for(&origin(ip, app_id))
+                .await
+                .is_ok());
+        }
+        let fresh_ip = Some(IpAddr::from([192, 0, 2, 4]));
+        assert!(limiter
+            .check_join_attempt_for(&origin(fresh_ip, app_id))
+            .await
+            .is_err());
+
+        // Another app, and unauthenticated clients, keep their own allowance
+        assert!(limiter
+            .check_join_attempt_for(&origin(fresh_ip, Some(Uuid::new_v4())))
+            .await
+            .is_ok());
+        assert!(limiter
+            .check_join_attempt_for(&origin(fresh_ip, None))
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn app_then_ip_enforces_both_limits() {
+        let limiter = RoomRateLimiter::new(RateLimitConfig {
+            key_by: RateLimitKeyBy::AppThenIp,
+            max_join_attempts: 3,
+            ip_max_join_attempts: 4,
+            time_window: Duration::from_secs(60),
+            ..create_test_config()
+        });
+        let busy_app = Some(Uuid::new_v4());
+        let shared_ip = Some("203.0.113.20".parse().unwrap());
+
+        // The app limit binds first
+        for _ in 0..3 {
+            assert!(limiter
+                .check_join_attempt_for(&origin(shared_ip, busy_app))
+                .await
+                .is_ok());
+        }
+        assert!(limiter
+            .check_join_attempt_for(&origin(shared_ip, busy_app))
+            .await
+            .is_err());
+
+        // The looser IP net still caps one address across apps
+        let other_app = Some(Uuid::new_v4());
+        assert!(limiter
+            .check_join_attempt_for(&origin(shared_ip, other_app))
+            .await
+            .is_ok());
+        assert!(matches!(
+            limiter
+                .check_join_attempt_for(&origin(shared_ip, other_app))
+                .await,
+            Err(RateLimitError::JoinLimitExceeded { .. })
+        ));
+        let other_ip = Some("203.0.113.21".parse().unwrap());
+        assert!(limiter
+            .check_join_attempt_for(&origin(other_ip, other_app))
+            .await
+            .is_ok());
+    }
+
+    #[test]
+    fn token_bucket_cleanup_drops_refilled_buckets() {
+        let limiter = TokenBucketLimiter::<Uuid>::new();
+        let quota = Quota::new(1, Duration::from_millis(1));
+        let idle = Uuid::new_v4();
+        assert!(limiter.try_acquire(&idle, quota).is_ok());
+
+        std::thread::sleep(Duration::from_millis(5));
+        let busy = Uuid::new_v4();
+        assert!(limiter
+            .try_acquire(&busy, Quota::new(1, Duration::from_secs(60)))
+            .is_ok());
+        limiter.cleanup();
+
+        assert_eq!(limiter.tracked_keys(), 1);
+    }
 }
@@ -1,27 +1,51 @@
 /// Reconnection support module
 ///
 /// Handles player reconnection after network disruptions including:
-/// - Authentication token generation and validation
+/// - Authentication token generation, rotation and validation
 /// - Event buffering for missed messages
 /// - Player disconnection tracking
 /// - Reconnection window management
-use crate::metrics::ServerMetrics;
-use crate::protocol::{PlayerId, RoomId, ServerMessage};
+use crate::metrics::{ReconnectFailureReason, ServerMetrics};
+use crate::protocol::{ErrorCode, PlayerId, RoomId, ServerMessage};
+use crate::security::crypto::EncryptionError;
+use crate::security::EnvelopeEncryptor;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
 use chrono::{DateTime, Duration, Utc};
-use std::collections::{HashMap, VecDeque};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
+use thiserror::Error;
 use tokio::sync::RwLock;
-use uuid::Uuid;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// SDK capability required to receive reconnection tokens. Clients without it
+/// are removed from their room on disconnect, as if reconnection were disabled.
+pub const RECONNECTION_CAPABILITY: &str = "reconnection";
+
+/// Game label used for rooms that have not been labelled yet.
+const UNKNOWN_GAME: &str = "unknown";
+
+/// Key-derivation context for the token signing key.
+const TOKEN_KEY_CONTEXT: &str = "signal-fish/reconnection-token";
 
 /// Authentication token for reconnection
+///
+/// The token string is `<version>.<issued_at>.<signature>`, where the signature
+/// is an HMAC over the player, room, version and issue time keyed by a secret
+/// unique to the issuing server instance.
 #[derive(Debug, Clone)]
 pub struct ReconnectionToken {
-    /// Token value (UUID)
+    /// Signed token value
     pub token: String,
     /// Player ID this token is for
     pub player_id: PlayerId,
     /// Room ID this token is for
     pub room_id: RoomId,
+    /// Rotation counter; every token issued to a player gets the next version
+    pub version: u32,
     /// When the token was created
     pub created_at: DateTime<Utc>,
     /// When the token expires
@@ -29,18 +53,6 @@ pub struct ReconnectionToken {
 }
 
 impl ReconnectionToken {
-    /// Create a new reconnection token
-    pub fn new(player_id: PlayerId, room_id: RoomId, validity_seconds: i64) -> Self {
-        let now = Utc::now();
-        Self {
-            token: Uuid::new_v4().to_string(),
-            player_id,
-            room_id,
-            created_at: now,
-            expires_at: now + Duration::seconds(validity_seconds),
-        }
-    }
-
     /// Check if token is expired
     pub fn is_expired(&self) -> bool {
         Utc::now() > self.expires_at
@@ -120,6 +132,48 @@ impl EventBuffer {
     }
 }
 
+/// Why a reconnection attempt was rejected
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ReconnectionError {
+    #[error("No disconnection record found")]
+    NoDisconnectionRecord,
+    #[error("Invalid reconnection token")]
+    InvalidToken,
+    #[error("Reconnection token has been superseded by a newer token")]
+    TokenReplayed,
+    #[error("Reconnection token has expired")]
+    TokenExpired,
+    #[error("Reconnection window has expired")]
+    WindowExpired,
+    #[error("Reconnection slot was reclaimed for a new player")]
+    SlotReclaimed,
+}
+
+impl ReconnectionError {
+    /// Protocol error code reported to the client.
+    pub fn error_code(&self) -> ErrorCode {
+        match self {
+            Self::NoDisconnectionRecord => ErrorCode::ReconnectionFailed,
+            Self::InvalidToken => ErrorCode::ReconnectionTokenInvalid,
+            Self::TokenReplayed => ErrorCode::TokenReplayed,
+            Self::TokenExpired | Self::WindowExpired | Self::SlotReclaimed => {
+                ErrorCode::ReconnectionExpired
+            }
+        }
+    }
+
+    /// Failure reason reported in the per-game reconnection metrics.
+    pub fn failure_reason(&self) -> ReconnectFailureReason {
+        match self {
+            Self::TokenExpired | Self::WindowExpired => ReconnectFailureReason::Expired,
+            Self::NoDisconnectionRecord | Self::InvalidToken | Self::TokenReplayed => {
+                ReconnectFailureReason::Invalid
+            }
+            Self::SlotReclaimed => ReconnectFailureReason::SlotReclaimed,
+        }
+    }
+}
+
 /// Disconnected player information
 #[derive(Debug, Clone)]
 pub struct DisconnectedPlayer {
@@ -135,6 +189,8 @@ pub struct DisconnectedPlayer {
     pub last_sequence: u64,
     /// Was player authority?
     pub was_authority: bool,
+    /// Whether the slot was given up to keep the room within its slot limit
+    pub reclaimed: bool,
 }
 
 impl DisconnectedPlayer {
@@ -143,16 +199,31 @@ impl DisconnectedPlayer {
         let expiry = self.disconnected_at + Duration::seconds(window_seconds);
         Utc::now() > expiry
     }
+
+    /// Whether the player could still redeem their token.
+    fn is_redeemable(&self, window_seconds: i64) -> bool {
+        !self.reclaimed && !self.is_expired(window_seconds)
+    }
 }
 
 /// Reconnection manager
 pub struct ReconnectionManager {
     /// Disconnected players awaiting reconnection
     disconnected_players: RwLock<HashMap<PlayerId, DisconnectedPlayer>>,
+    /// Latest token issued to each player; anything older is a replay
+    issued_tokens: RwLock<HashMap<PlayerId, ReconnectionToken>>,
     /// Event buffers per room
     event_buffers: RwLock<HashMap<RoomId, EventBuffer>>,
+    /// Game name of each room, used to label metrics
+    room_games: RwLock<HashMap<RoomId, String>>,
+    /// Pending reconnections allowed per room; 0 means unlimited
+    max_slots_per_room: usize,
     /// Reconnection window in seconds
     reconnection_window: i64,
+    /// Maximum token age in seconds, regardless of rotation
+    max_token_age: i64,
+    /// Per-instance token signing key
+    token_mac: HmacSha256,
     /// Event buffer size per room
     event_buffer_size: usize,
     /// Next sequence number for events
@@ -163,29 +234,161 @@ pub struct ReconnectionManager {
 
 impl ReconnectionManager {
     /// Create a new reconnection manager
+    ///
+    /// Tokens are signed with a key derived from `token_encryptor`, so only
+    /// managers sharing the same master key accept each other's tokens.
     pub fn new(
         reconnection_window: u64,
         event_buffer_size: usize,
+        max_token_age_secs: u64,
+        token_encryptor: &EnvelopeEncryptor,
         metrics: Arc<ServerMetrics>,
-    ) -> Self {
-        Self {
+    ) -> Result<Self, EncryptionError> {
+        let signing_key = token_encryptor.derive_key(TOKEN_KEY_CONTEXT)?;
+        let token_mac = HmacSha256::new_from_slice(&signing_key)
+            .map_err(|_| EncryptionError::InvalidKeyLength(signing_key.len()))?;
+
+        Ok(Self {
             disconnected_players: RwLock::new(HashMap::new()),
+            issued_tokens: RwLock::new(HashMap::new()),
             event_buffers: RwLock::new(HashMap::new()),
+            room_games: RwLock::new(HashMap::new()),
+            max_slots_per_room: 0,
             reconnection_window: reconnection_window as i64,
+            max_token_age: i64::try_from(max_token_age_secs).unwrap_or(i64::MAX),
+            token_mac,
             event_buffer_size,
             next_sequence: RwLock::new(0),
             metrics,
+        })
+    }
+
+    /// Limit how many players can await reconnection into one room at once.
+    ///
+    /// Past the limit the longest-waiting player's slot is reclaimed and their
+    /// token stops working. `0` leaves the number unlimited.
+    pub fn with_max_slots_per_room(mut self, max_slots_per_room: usize) -> Self {
+        self.max_slots_per_room = max_slots_per_room;
+        self
+    }
+
+    /// Attach the game name used to label metrics for this room.
+    pub async fn label_room(&self, room_id: RoomId, game_name: &str) {
+        self.room_games
+            .write()
+            .await
+            .entry(room_id)
+            .or_insert_with(|| game_name.to_string());
+    }
+
+    async fn game_label(&self, room_id: &RoomId) -> String {
+        self.room_games
+            .read()
+            .await
+            .get(room_id)
+            .cloned()
+            .unwrap_or_else(|| UNKNOWN_GAME.to_string())
+    }
+
+    /// Issue a fresh token for a player, superseding any earlier token.
+    pub async fn issue_token(&self, player_id: PlayerId, room_id: RoomId) -> String {
+        let game_name = self.game_label(&room_id).await;
+        let mut issued = self.issued_tokens.write().await;
+        self.issue_token_locked(&mut issued, player_id, room_id, &game_name)
+            .token
+    }
+
+    fn issue_token_locked(
+        &self,
+        issued: &mut HashMap<PlayerId, ReconnectionToken>,
+        player_id: PlayerId,
+        room_id: RoomId,
+        game_name: &str,
+    ) -> ReconnectionToken {
+        let version = issued
+            .get(&player_id)
+            .map_or(1, |previous| previous.version.wrapping_add(1));
+        let token = self.mint_token(player_id, room_id, version);
+        issued.insert(player_id, token.clone());
+        self.metrics.record_reconnection_token_issued(game_name);
+        token
+    }
+
+    fn mint_token(&self, player_id: PlayerId, room_id: RoomId, version: u32) -> ReconnectionToken {
+        self.mint_token_at(player_id, room_id, version, Utc::now())
+    }
+
+    fn mint_token_at(
+        &self,
+        player_id: PlayerId,
+        room_id: RoomId,
+        version: u32,
+        now: DateTime<Utc>,
+    ) -> ReconnectionToken {
+        // Second precision so the issue time round-trips through the token string
+        let created_at = DateTime::from_timestamp(now.timestamp(), 0).unwrap_or(now);
+        let issued_at = created_at.timestamp();
+        let signature =
+            URL_SAFE_NO_PAD.encode(self.token_signature(&player_id, &room_id, version, issued_at));
+
+        ReconnectionToken {
+            token: format!("{version}.{issued_at}.{signature}"),
+            player_id,
+            room_id,
+            version,
+            created_at,
+            expires_at: created_at + Duration::seconds(self.max_token_age),
         }
     }
 
+    fn token_signature(
+        &self,
+        player_id: &PlayerId,
+        room_id: &RoomId,
+        version: u32,
+        issued_at: i64,
+    ) -> Vec<u8> {
+        let mut mac = self.token_mac.clone();
+        mac.update(format!("{player_id}:{room_id}:{version}:{issued_at}").as_bytes());
+        mac.finalize().into_bytes().to_vec()
+    }
+
+    /// Version and issue time of a token whose signature checks out for this player and room.
+    fn verify_token(
+        &self,
+        player_id: &PlayerId,
+        room_id: &RoomId,
+        token: &str,
+    ) -> Option<(u32, i64)> {
+        let mut parts = token.splitn(3, '.');
+        let version: u32 = parts.next()?.parse().ok()?;
+        let issued_at: i64 = parts.next()?.parse().ok()?;
+        let signature = URL_SAFE_NO_PAD.decode(parts.next()?).ok()?;
+
+        let mut mac = self.token_mac.clone();
+        mac.update(format!("{player_id}:{room_id}:{version}:{issued_at}").as_bytes());
+        mac.verify_slice(&signature).ok()?;
+        Some((version, issued_at))
+    }
+
     /// Register a player disconnection
+    ///
+    /// The player's current token stays valid; a new one is only issued if they
+    /// have none for this room or it has expired. If the room is already at its
+    /// slot limit, the slot of the player who has waited longest is reclaimed.
     pub async fn register_disconnection(
         &self,
         player_id: PlayerId,
         room_id: RoomId,
         was_authority: bool,
     ) -> String {
-        let token = ReconnectionToken::new(player_id, room_id, self.reconnection_window);
+        let game_name = self.game_label(&room_id).await;
+        let mut issued = self.issued_tokens.write().await;
+        let token = match issued.get(&player_id) {
+            Some(current) if current.room_id == room_id && !current.is_expired() => current.clone(),
+            _ => self.issue_token_locked(&mut issued, player_id, room_id, &game_name),
+        };
+        drop(issued);
         let token_string = token.token.clone();
 
         let last_sequence = *self.next_sequence.read().await;
@@ -197,16 +400,25 @@ impl ReconnectionManager {
             token,
             last_sequence,
             was_authority,
+            reclaimed: false,
         };
 
         let mut players = self.disconnected_players.write().await;
         let previous = players.insert(player_id, disconnected);
+        let reclaimed = self.reclaim_excess_slots(&mut players, &room_id);
         drop(players);
 
-        self.metrics.increment_reconnection_tokens_issued();
-        if previous.is_none() {
+        if previous.is_none_or(|previous| previous.reclaimed) {
             self.metrics.increment_reconnection_sessions_active();
         }
+        for reclaimed_id in reclaimed {
+            self.metrics.decrement_reconnection_sessions_active();
+            tracing::info!(
+                player_id = %reclaimed_id,
+                %room_id,
+                "Reconnection slot reclaimed; room is at its slot limit"
+            );
+        }
 
         tracing::info!(
             %player_id,
@@ -217,33 +429,105 @@ impl ReconnectionManager {
         token_string
     }
 
+    /// Mark the longest-waiting slots in `room_id` as reclaimed until the room
+    /// is back within `max_slots_per_room`. Returns the players affected.
+    fn reclaim_excess_slots(
+        &self,
+        players: &mut HashMap<PlayerId, DisconnectedPlayer>,
+        room_id: &RoomId,
+    ) -> Vec<PlayerId> {
+        if self.max_slots_per_room == 0 {
+            return Vec::new();
+        }
+        let mut pending: Vec<&mut DisconnectedPlayer> = players
+            .values_mut()
+            .filter(|player| player.room_id == *room_id && !player.reclaimed)
+            .collect();
+        let excess = pending.len().saturating_sub(self.max_slots_per_room);
+        pending.sort_by_key(|player| player.disconnected_at);
+        pending
+            .into_iter()
+            .take(excess)
+            .map(|player| {
+                player.reclaimed = true;
+                player.player_id
+            })
+            .collect()
+    }
+
     /// Validate reconnection attempt
+    ///
+    /// A correctly signed token with an older version than the player's latest
+    /// token is reported as [`ReconnectionError::TokenReplayed`].
     pub async fn validate_reconnection(
         &self,
         player_id: &PlayerId,
         room_id: &RoomId,
         token: &str,
-    ) -> Result<DisconnectedPlayer, String> {
-        let disconnected = self.disconnected_players.read().await;
+    ) -> Result<DisconnectedPlayer, ReconnectionError> {
+        let game_name = self.game_label(room_id).await;
+        self.metrics.record_reconnection_attempt(&game_name);
+        let result = self.check_reconnection(player_id, room_id, token).await;
+        if let Err(error) = &result {
+            self.metrics
+                .record_reconnection_failed(&game_name, error.failure_reason());
+        }
+        match &result {
+            Err(ReconnectionError::TokenReplayed) => {
+                self.metrics.increment_reconnection_validation_failure();
+                self.metrics.increment_token_replay_detected();
+                tracing::warn!(
+                    %player_id,
+                    %room_id,
+                    "Superseded reconnection token presented; possible token theft"
+                );
+            }
+            Err(_) => self.metrics.increment_reconnection_validation_failure(),
+            Ok(_) => {}
+        }
+        result
+    }
 
+    async fn check_reconnection(
+        &self,
+        player_id: &PlayerId,
+        room_id: &RoomId,
+        token: &str,
+    ) -> Result<DisconnectedPlayer, ReconnectionError> {
+        let Some((version, issued_at)) = self.verify_token(player_id, room_id, token) else {
+            return Err(ReconnectionError::InvalidToken);
+        };
+
+        let latest_version = self
+            .issued_tokens
+            .read()
+            .await
+            .get(player_id)
+            .filter(|latest| latest.room_id == *room_id)
+            .map(|latest| latest.version);
+        if latest_version.is_some_and(|latest| version < latest) {
+            return Err(ReconnectionError::TokenReplayed);
+        }
+
+        if Utc::now().timestamp() > issued_at.saturating_add(self.max_token_age) {
+            return Err(ReconnectionError::TokenExpired);
+        }
+
+        let disconnected = self.disconnected_players.read().await;
         let Some(player) = disconnected.get(player_id) else {
-            self.metrics.increment_reconnection_validation_failure();
-            return Err("No disconnection record found".to_string());
+            return Err(ReconnectionError::NoDisconnectionRecord);
         };
 
-        if player.token.token != token {
-            self.metrics.increment_reconnection_validation_failure();
-            return Err("Invalid reconnection token".to_string());
+        if player.token.token != token || !player.token.is_valid(player_id, room_id) {
+            return Err(ReconnectionError::InvalidToken);
         }
 
-        if !player.token.is_valid(player_id, room_id) {
-            self.metrics.increment_reconnection_validation_failure();
-            return Err("Reconnection token is invalid or expired".to_string());
+        if player.reclaimed {
+            return Err(ReconnectionError::SlotReclaimed);
         }
 
         if player.is_expired(self.reconnection_window) {
-            self.metrics.increment_reconnection_validation_failure();
-            return Err("Reconnection window has expired".to_string());
+            return Err(ReconnectionError::WindowExpired);
         }
 
         Ok(player.clone())
@@ -266,9 +550,18 @@ impl ReconnectionManager {
         });
         drop(players);
 
-        if removed.is_some() {
-            self.metrics.decrement_reconnection_sessions_active();
+        if let Some(player) = &removed {
+            if !player.reclaimed {
+                self.metrics.decrement_reconnection_sessions_active();
+            }
             self.metrics.increment_reconnection_completions();
+            let elapsed = (Utc::now() - player.disconnected_at)
+                .to_std()
+                .unwrap_or_default();
+            let game_name = self.game_label(&player.room_id).await;
+            self.metrics
+                .record_reconnection_succeeded(&game_name, elapsed)
+                .await;
         }
 
         if let Some(room_id) = room_to_clear {
@@ -279,6 +572,13 @@ impl ReconnectionManager {
         tracing::info!(%player_id, "Player reconnection completed");
     }
 
+    /// Record a validated reconnection that failed because its room was removed.
+    pub async fn record_room_gone(&self, room_id: &RoomId) {
+        let game_name = self.game_label(room_id).await;
+        self.metrics
+            .record_reconnection_failed(&game_name, ReconnectFailureReason::RoomGone);
+    }
+
     /// Get missed events for a reconnecting player
     pub async fn get_missed_events(
         &self,
@@ -316,7 +616,40 @@ impl ReconnectionManager {
         tracing::debug!(%room_id, "Event buffer cleared for room");
     }
 
-    /// Clean up expired disconnections
+    /// Revoke every pending reconnection into a room and drop its event buffer.
+    ///
+    /// Returns the number of players whose tokens were invalidated.
+    pub async fn revoke_room(&self, room_id: &RoomId) -> usize {
+        let mut players = self.disconnected_players.write().await;
+        let before = players.len();
+        let mut active_revoked = 0;
+        players.retain(|_, player| {
+            let keep = player.room_id != *room_id;
+            if !keep && !player.reclaimed {
+                active_revoked += 1;
+            }
+            keep
+        });
+        let revoked = before - players.len();
+        drop(players);
+        self.issued_tokens
+            .write()
+            .await
+            .retain(|_, token| token.room_id != *room_id);
+
+        for _ in 0..active_revoked {
+            self.metrics.decrement_reconnection_sessions_active();
+        }
+        self.clear_room_buffer(room_id).await;
+        self.room_games.write().await.remove(room_id);
+
+        if revoked > 0 {
+            tracing::info!(%room_id, count = revoked, "Reconnection tokens revoked for room");
+        }
+        revoked
+    }
+
+    /// Clean up expired disconnections and refresh the reconnection gauges
     pub async fn cleanup_expired(&self) -> usize {
         let mut disconnected = self.disconnected_players.write().await;
         let initial_count = disconnected.len();
@@ -331,7 +664,29 @@ impl ReconnectionManager {
             !expired
         });
         let removed = initial_count - disconnected.len();
+        let sessions_active = disconnected.values().filter(|p| !p.reclaimed).count();
+        let mut live_rooms: HashSet<RoomId> = disconnected.values().map(|p| p.room_id).collect();
         drop(disconnected);
+
+        let mut issued = self.issued_tokens.write().await;
+        issued.retain(|_, token| !token.is_expired());
+        live_rooms.extend(issued.values().map(|token| token.room_id));
+        let mut room_games = self.room_games.write().await;
+        room_games.retain(|room_id, _| live_rooms.contains(room_id));
+        let mut outstanding: HashMap<String, u64> = HashMap::new();
+        for token in issued.values() {
+            let game_name = room_games
+                .get(&token.room_id)
+                .map_or(UNKNOWN_GAME, String::as_str);
+            *outstanding.entry(game_name.to_string()).or_default() += 1;
+        }
+        drop(room_games);
+        drop(issued);
+
+        self.metrics
+            .set_reconnection_sessions_active(sessions_active as u64);
+        self.metrics
+            .set_reconnection_outstanding_tokens(&outstanding);
         if removed > 0 {
             tracing::info!(count = removed, "Cleaned up expired reconnection records");
         }
@@ -344,7 +699,18 @@ impl ReconnectionManager {
         self.disconnected_players
             .read()
             .await
-            .contains_key(player_id)
+            .get(player_id)
+            .is_some_and(|player| !player.reclaimed)
+    }
+
+    /// Check if a player could still redeem their token: their slot has been
+    /// neither reclaimed nor left to expire.
+    pub async fn has_redeemable_reconnection(&self, player_id: &PlayerId) -> bool {
+        self.disconnected_players
+            .read()
+            .await
+            .get(player_id)
+            .is_some_and(|player| player.is_redeemable(self.reconnection_window))
     }
 
     /// Get all disconnected players for a room
@@ -353,7 +719,7 @@ impl ReconnectionManager {
             .read()
             .await
             .values()
-            .filter(|p| p.room_id == *room_id)
+            .filter(|p| p.room_id == *room_id && !p.reclaimed)
             .map(|p| p.player_id)
             .collect()
     }
@@ -364,27 +730,44 @@ mod tests {
     use super::*;
     use crate::metrics::ServerMetrics;
     use std::sync::Arc;
+    use uuid::Uuid;
+
+    fn create_manager(max_token_age_secs: u64) -> ReconnectionManager {
+        let encryptor = EnvelopeEncryptor::generate("test-instance").expect("entropy");
+        ReconnectionManager::new(
+            300,
+            100,
+            max_token_age_secs,
+            &encryptor,
+            Arc::new(ServerMetrics::new()),
+        )
+        .expect("token key derivation")
+    }
 
     #[test]
     fn test_reconnection_token_creation() {
+        let manager = create_manager(3600);
         let player_id = Uuid::new_v4();
         let room_id = Uuid::new_v4();
-        let token = ReconnectionToken::new(player_id, room_id, 300);
+        let token = manager.mint_token(player_id, room_id, 1);
 
         assert_eq!(token.player_id, player_id);
         assert_eq!(token.room_id, room_id);
+        assert_eq!(token.version, 1);
+        assert!(token.token.starts_with("1."));
         assert!(!token.is_expired());
         assert!(token.is_valid(&player_id, &room_id));
     }
 
     #[test]
     fn test_reconnection_token_validation() {
+        let manager = create_manager(3600);
         let player_id = Uuid::new_v4();
         let room_id = Uuid::new_v4();
         let other_player = Uuid::new_v4();
         let other_room = Uuid::new_v4();
 
-        let token = ReconnectionToken::new(player_id, room_id, 300);
+        let token = manager.mint_token(player_id, room_id, 1);
 
         // Should be valid for correct player and room
         assert!(token.is_valid(&player_id, &room_id));
@@ -432,8 +815,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_reconnection_manager_flow() {
-        let metrics = Arc::new(ServerMetrics::new());
-        let manager = ReconnectionManager::new(300, 100, metrics);
+        let manager = create_manager(3600);
         let player_id = Uuid::new_v4();
         let room_id = Uuid::new_v4();
 
@@ -457,8 +839,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_event_buffering() {
-        let metrics = Arc::new(ServerMetrics::new());
-        let manager = ReconnectionManager::new(300, 100, metrics);
+        let manager = create_manager(3600);
         let room_id = Uuid::new_v4();
 
         use crate::protocol::ServerMessage;
@@ -472,4 +853,230 @@ mod tests {
         let events = manager.get_missed_events(&room_id, 0).await;
         assert_eq!(events.len(), 3);
     }
+
+    #[tokio::test]
+    async fn test_replayed_lobby_updates_keep_their_versions() {
+        let manager = create_manager(3600);
+        let room_id = Uuid::new_v4();
+
+        use crate::protocol::{LobbyState, ServerMessage};
+
+        for lobby_version in [4, 5, 7] {
+            manager
+                .buffer_event(
+                    &room_id,
+                    ServerMessage::LobbyStateChanged {
+                        lobby_state: LobbyState::Lobby,
+                        ready_players: Vec::new(),
+                        all_ready: false,
+                        lobby_version,
+                    },
+                )
+                .await;
+        }
+
+        let versions: Vec<u64> = manager
+            .get_missed_events(&room_id, 0)
+            .await
+            .iter()
+            .filter_map(|event| match event {
+                ServerMessage::LobbyStateChanged { lobby_version, .. } => Some(*lobby_version),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(versions, [4, 5, 7]);
+    }
+
+    #[tokio::test]
+    async fn test_revoke_room_invalidates_tokens() {
+        let manager = create_manager(3600);
+        let room_id = Uuid::new_v4();
+        let other_room = Uuid::new_v4();
+        let player_id = Uuid::new_v4();
+        let other_player = Uuid::new_v4();
+
+        let token = manager
+            .register_disconnection(player_id, room_id, false)
+            .await;
+        manager
+            .register_disconnection(other_player, other_room, false)
+            .await;
+        manager
+            .buffer_event(&room_id, crate::protocol::ServerMessage::Pong)
+            .await;
+
+        assert_eq!(manager.revoke_room(&room_id).await, 1);
+        assert!(manager
+            .validate_reconnection(&player_id, &room_id, &token)
+            .await
+            .is_err());
+        assert!(manager.get_missed_events(&room_id, 0).await.is_empty());
+        assert!(manager.has_pending_reconnection(&other_player).await);
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_rotation_detects_replayed_token() {
+        let manager = create_manager(3600);
+        let player_id = Uuid::new_v4();
+        let room_id = Uuid::new_v4();
+
+        let first = manager.issue_token(player_id, room_id).await;
+        let disconnect_token = manager
+            .register_disconnection(player_id, room_id, false)
+            .await;
+        assert_eq!(
+            disconnect_token, first,
+            "disconnect keeps the token the client holds"
+        );
+        manager
+            .validate_reconnection(&player_id, &room_id, &first)
+            .await
+            .expect("current token is accepted");
+        manager.complete_reconnection(&player_id).await;
+        let rotated = manager.issue_token(player_id, room_id).await;
+        assert!(rotated.starts_with("2."));
+
+        manager
+            .register_disconnection(player_id, room_id, false)
+            .await;
+        assert_eq!(
+            manager
+                .validate_reconnection(&player_id, &room_id, &first)
+                .await
+                .unwrap_err(),
+            ReconnectionError::TokenReplayed
+        );
+        assert_eq!(
+            manager
+                .metrics
+                .token_replays_detected
+                .load(std::sync::atomic::Ordering::Relaxed),
+            1
+        );
+        assert!(manager
+            .validate_reconnection(&player_id, &room_id, &rotated)
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_tokens_are_bound_to_issuing_instance() {
+        let issuer = create_manager(3600);
+        let other_instance = create_manager(3600);
+        let player_id = Uuid::new_v4();
+        let room_id = Uuid::new_v4();
+
+        let token = issuer.issue_token(player_id, room_id).await;
+        other_instance
+            .register_disconnection(player_id, room_id, false)
+            .await;
+        assert_eq!(
+            other_instance
+                .validate_reconnection(&player_id, &room_id, &token)
+                .await
+                .unwrap_err(),
+            ReconnectionError::InvalidToken
+        );
+
+        let (version, issued_at) = issuer
+            .verify_token(&player_id, &room_id, &token)
+            .expect("issuer accepts its own token");
+        assert_eq!(version, 1);
+        let forged = format!(
+            "{}.{issued_at}.{}",
+            version + 1,
+            token.rsplit('.').next().unwrap()
+        );
+        assert!(issuer.verify_token(&player_id, &room_id, &forged).is_none());
+        assert!(issuer
+            .verify_token(&player_id, &Uuid::new_v4(), &token)
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_token_expires_after_max_age() {
+        let manager = create_manager(60);
+        let player_id = Uuid::new_v4();
+        let room_id = Uuid::new_v4();
+
+        let token = manager
+            .mint_token_at(player_id, room_id, 1, Utc::now() - Duration::seconds(120))
+            .token;
+        assert_eq!(
+            manager
+                .validate_reconnection(&player_id, &room_id, &token)
+                .await
+                .unwrap_err(),
+            ReconnectionError::TokenExpired
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reconnection_metrics_are_labelled_by_game() {
+        let manager = create_manager(3600);
+        let player_id = Uuid::new_v4();
+        let room_id = Uuid::new_v4();
+        manager.label_room(room_id, "racer").await;
+
+        let token = manager.issue_token(player_id, room_id).await;
+        manager
+            .register_disconnection(player_id, room_id, false)
+            .await;
+        assert!(manager
+            .validate_reconnection(&player_id, &room_id, "1.0.forged")
+            .await
+            .is_err());
+        manager
+            .validate_reconnection(&player_id, &room_id, &token)
+            .await
+            .expect("current token is accepted");
+        manager.complete_reconnection(&player_id).await;
+        manager.issue_token(player_id, room_id).await;
+        manager.cleanup_expired().await;
+
+        let snapshot = manager.metrics.snapshot().await;
+        let racer = snapshot
+            .reconnection_by_game
+            .iter()
+            .find(|game| game.game_name == "racer")
+            .expect("racer metrics");
+        assert_eq!(racer.tokens_issued, 2);
+        assert_eq!(racer.attempted, 2);
+        assert_eq!(racer.succeeded, 1);
+        assert_eq!(racer.failed_invalid, 1);
+        assert_eq!(racer.outstanding_tokens, 1);
+        assert!(racer.median_time_to_reconnect_ms.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_slot_limit_reclaims_longest_waiting_player() {
+        let manager = create_manager(3600).with_max_slots_per_room(1);
+        let room_id = Uuid::new_v4();
+        let first = Uuid::new_v4();
+        let second = Uuid::new_v4();
+
+        let first_token = manager.register_disconnection(first, room_id, false).await;
+        manager.register_disconnection(second, room_id, false).await;
+
+        assert!(!manager.has_pending_reconnection(&first).await);
+        assert!(manager.has_pending_reconnection(&second).await);
+        assert_eq!(
+            manager.get_disconnected_players_in_room(&room_id).await,
+            vec![second]
+        );
+        assert_eq!(
+            manager
+                .validate_reconnection(&first, &room_id, &first_token)
+                .await
+                .unwrap_err(),
+            ReconnectionError::SlotReclaimed
+        );
+        assert_eq!(
+            manager
+                .metrics
+                .reconnection_sessions_active
+                .load(std::sync::atomic::Ordering::Relaxed),
+            1
+        );
+    }
 }
@@ -0,0 +1,300 @@
+//! Ahead-of-time relay session provisioning.
+//!
+//! Relay infrastructure (SFU or TURN allocations) often needs a moment to set
+//! up per room. Embedders implement [`RelayProvisioner`] and install it with
+//! [`EnhancedGameServer::set_relay_provisioner`](crate::server::EnhancedGameServer::set_relay_provisioner)
+//! so the session is ready before the game starts. The session data is opaque
+//! to the server and is passed through to clients in `RoomJoined` and
+//! `GameStarting`.
+
+use crate::config::{RelayFailurePolicy, RelayWarmupConfig};
+use crate::database::GameDatabase;
+use crate::protocol::{Room, RoomId};
+use crate::retry::{RetryConfig, RetryExecutor};
+use async_trait::async_trait;
+use dashmap::mapref::entry::Entry;
+use dashmap::DashMap;
+use serde_json::Value;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, PoisonError, RwLock};
+use thiserror::Error;
+
+/// Room details handed to [`RelayProvisioner::provision`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelayProvisionRequest {
+    pub room_id: RoomId,
+    pub game_name: String,
+    pub relay_type: String,
+    pub region_id: String,
+    /// Number of players the room is sized for
+    pub expected_players: u8,
+}
+
+impl RelayProvisionRequest {
+    pub fn for_room(room: &Room) -> Self {
+        Self {
+            room_id: room.id,
+            game_name: room.game_name.clone(),
+            relay_type: room.relay_type.clone(),
+            region_id: room.region_id.clone(),
+            expected_players: room.max_players,
+        }
+    }
+}
+
+/// Hook for allocating and releasing per-room relay sessions.
+#[async_trait]
+pub trait RelayProvisioner: Send + Sync {
+    /// Allocate relay resources for a room. `Ok(None)` means the room needs no relay session.
+    async fn provision(&self, request: &RelayProvisionRequest) -> anyhow::Result<Option<Value>>;
+
+    /// Release a session returned by [`Self::provision`] once its room has closed.
+    async fn teardown(&self, room_id: &RoomId, session: &Value) -> anyhow::Result<()>;
+}
+
+/// Default provisioner: rooms get no relay session.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoopRelayProvisioner;
+
+#[async_trait]
+impl RelayProvisioner for NoopRelayProvisioner {
+    async fn provision(&self, _request: &RelayProvisionRequest) -> anyhow::Result<Option<Value>> {
+        Ok(None)
+    }
+
+    async fn teardown(&self, _room_id: &RoomId, _session: &Value) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+/// Provisioner for tests. Fails a set number of times, then returns a session
+/// describing the request. Records every provision and teardown call.
+#[derive(Debug, Default)]
+pub struct FakeRelayProvisioner {
+    failures_remaining: AtomicU32,
+    provision_calls: AtomicU32,
+    torn_down: DashMap<RoomId, Value>,
+}
+
+impl FakeRelayProvisioner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fail the first `times` provision calls.
+    pub fn failing(times: u32) -> Self {
+        Self {
+            failures_remaining: AtomicU32::new(times),
+            ..Self::default()
+        }
+    }
+
+    /// Fail every provision call.
+    pub fn always_failing() -> Self {
+        Self::failing(u32::MAX)
+    }
+
+    pub fn provision_calls(&self) -> u32 {
+        self.provision_calls.load(Ordering::Relaxed)
+    }
+
+    /// Rooms whose sessions were torn down.
+    pub fn torn_down(&self) -> Vec<RoomId> {
+        self.torn_down.iter().map(|entry| *entry.key()).collect()
+    }
+}
+
+#[async_trait]
+impl RelayProvisioner for FakeRelayProvisioner {
+    async fn provision(&self, request: &RelayProvisionRequest) -> anyhow::Result<Option<Value>> {
+        self.provision_calls.fetch_add(1, Ordering::Relaxed);
+        let failed = self
+            .failures_remaining
+            .fetch_update(
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+                |remaining| match remaining {
+                    0 => None,
+                    u32::MAX => Some(u32::MAX),
+                    n => Some(n - 1),
+                },
+            )
+            .is_ok();
+        if failed {
+            anyhow::bail!("fake relay provisioning failure");
+        }
+
+        Ok(Some(serde_json::json!({
+            "session_id": format!("fake-{}", request.room_id),
+            "relay_type": request.relay_type,
+            "region_id": request.region_id,
+            "expected_players": request.expected_players,
+        })))
+    }
+
+    async fn teardown(&self, room_id: &RoomId, session: &Value) -> anyhow::Result<()> {
+        self.torn_down.insert(*room_id, session.clone());
+        Ok(())
+    }
+}
+
+/// The room's relay session could not be provisioned and the failure policy is `block`.
+#[derive(Debug, Error)]
+#[error("relay session unavailable for room {room_id}")]
+pub struct RelaySessionUnavailable {
+    pub room_id: RoomId,
+}
+
+/// Runs the installed [`RelayProvisioner`] with retries and tracks live
+/// sessions so they are torn down when their rooms close.
+pub struct RelayWarmup {
+    provisioner: RwLock<Arc<dyn RelayProvisioner>>,
+    config: RelayWarmupConfig,
+    database: Arc<dyn GameDatabase>,
+    sessions: DashMap<RoomId, Value>,
+}
+
+impl RelayWarmup {
+    pub fn new(config: RelayWarmupConfig, database: Arc<dyn GameDatabase>) -> Self {
+        Self {
+            provisioner: RwLock::new(Arc::new(NoopRelayProvisioner)),
+            config,
+            database,
+            sessions: DashMap::new(),
+        }
+    }
+
+    pub fn config(&self) -> &RelayWarmupConfig {
+        &self.config
+    }
+
+    pub fn set_provisioner(&self, provisioner: Arc<dyn RelayProvisioner>) {
+        *self
+            .provisioner
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = provisioner;
+    }
+
+    fn provisioner(&self) -> Arc<dyn RelayProvisioner> {
+        self.provisioner
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    fn retry_config(&self) -> RetryConfig {
+        let defaults = RetryConfig::default();
+        let initial_delay = self.config.retry_delay_ms.into();
+        RetryConfig {
+            max_attempts: self.config.max_attempts.max(1),
+            initial_delay,
+            max_delay: defaults.max_delay.max(initial_delay),
+            ..defaults
+        }
+    }
+
+    /// Provision the room's relay session in the background.
+    pub fn spawn_provision(self: &Arc<Self>, room: &Room) {
+        let warmup = Arc::clone(self);
+        let request = RelayProvisionRequest::for_room(room);
+        tokio::spawn(async move {
+            if let Err(e) = warmup.provision(&request).await {
+                tracing::warn!(room_id = %request.room_id, error = %e, "Relay warm-up failed");
+            }
+        });
+    }
+
+    /// Provision a session for the room, retrying per config, and store it on the room.
+    pub async fn provision(
+        &self,
+        request: &RelayProvisionRequest,
+    ) -> anyhow::Result<Option<Value>> {
+        if let Some(existing) = self.sessions.get(&request.room_id) {
+            return Ok(Some(existing.clone()));
+        }
+
+        let provisioner = self.provisioner();
+        let session = RetryExecutor::new(self.retry_config())
+            .execute_with_condition(
+                "relay_provision",
+                || provisioner.provision(request),
+                |_| true,
+            )
+            .await?;
+        let Some(session) = session else {
+            return Ok(None);
+        };
+
+        // A concurrent run (warm-up racing game start) may have finished first
+        match self.sessions.entry(request.room_id) {
+            Entry::Occupied(existing) => {
+                let existing = existing.get().clone();
+                if let Err(e) = provisioner.teardown(&request.room_id, &session).await {
+                    tracing::warn!(room_id = %request.room_id, error = %e, "Failed to tear down duplicate relay session");
+                }
+                return Ok(Some(existing));
+            }
+            Entry::Vacant(slot) => {
+                slot.insert(session.clone());
+            }
+        }
+
+        if !self
+            .database
+            .set_room_relay_session(&request.room_id, Some(session.clone()))
+            .await?
+        {
+            // The room closed while provisioning was in flight
+            self.release(&request.room_id).await;
+            return Ok(None);
+        }
+
+        tracing::debug!(room_id = %request.room_id, "Relay session provisioned");
+        Ok(Some(session))
+    }
+
+    /// Session to send with `GameStarting`, provisioning again if warm-up has not succeeded.
+    ///
+    /// Fails with [`RelaySessionUnavailable`] only under the `block` failure policy.
+    pub async fn session_for_start(&self, room: &Room) -> anyhow::Result<Option<Value>> {
+        if let Some(session) = &room.relay_session {
+            return Ok(Some(session.clone()));
+        }
+
+        match self.provision(&RelayProvisionRequest::for_room(room)).await {
+            Ok(session) => Ok(session),
+            Err(e) => match self.config.on_failure {
+                RelayFailurePolicy::Proceed => {
+                    tracing::warn!(room_id = %room.id, error = %e, "Starting game without a relay session");
+                    Ok(None)
+                }
+                RelayFailurePolicy::Block => {
+                    tracing::warn!(room_id = %room.id, error = %e, "Blocking game start until a relay session is available");
+                    Err(anyhow::anyhow!(RelaySessionUnavailable {
+                        room_id: room.id
+                    }))
+                }
+            },
+        }
+    }
+
+    /// Tear down the room's relay session, if it has one.
+    pub async fn release(&self, room_id: &RoomId) {
+        let Some((_, session)) = self.sessions.remove(room_id) else {
+            return;
+        };
+        if let Err(e) = self.provisioner().teardown(room_id, &session).await {
+            tracing::warn!(%room_id, error = %e, "Relay session teardown failed");
+        }
+    }
+
+    /// Tear down sessions whose rooms no longer exist.
+    pub async fn release_closed_rooms(&self) {
+        let room_ids: Vec<RoomId> = self.sessions.iter().map(|entry| *entry.key()).collect();
+        for room_id in room_ids {
+            if matches!(self.database.get_room_summary(&room_id).await, Ok(None)) {
+                self.release(&room_id).await;
+            }
+        }
+    }
+}
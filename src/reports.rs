@@ -0,0 +1,60 @@
+//! Player reports routed to operators for review.
+//!
+//! The server never acts on a report. Each accepted `ReportPlayer` is logged
+//! at info level under the [`REPORTS_TARGET`] target, so operators can route
+//! reports to their own sink with a filter such as
+//! `signalfish::reports=info`, and is posted to webhooks subscribed to
+//! `player_reported`.
+
+use crate::protocol::{PlayerId, ReportReason, RoomId};
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Tracing target of report log events.
+pub const REPORTS_TARGET: &str = "signalfish::reports";
+/// Longest `details` a report may carry, in characters.
+pub const MAX_REPORT_DETAILS_LENGTH: usize = 1000;
+
+/// A report of one room participant by another.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerReport {
+    pub report_id: Uuid,
+    pub reported_at: DateTime<Utc>,
+    pub room_id: RoomId,
+    pub room_code: String,
+    pub game_name: String,
+    pub reporter_id: PlayerId,
+    pub reporter_name: String,
+    /// Client certificate fingerprint captured on the reporter's upgrade
+    pub reporter_fingerprint: Option<Arc<str>>,
+    pub target_id: PlayerId,
+    pub target_name: String,
+    /// Client certificate fingerprint captured on the target's upgrade
+    pub target_fingerprint: Option<Arc<str>>,
+    pub reason: ReportReason,
+    pub details: Option<String>,
+}
+
+/// Emit `report` under [`REPORTS_TARGET`] with every field as its own key.
+/// Fingerprints and details are left out when absent.
+pub fn log_player_report(report: &PlayerReport) {
+    tracing::info!(
+        target: REPORTS_TARGET,
+        event = "player_report",
+        report_id = %report.report_id,
+        reported_at = %report.reported_at.to_rfc3339(),
+        room_id = %report.room_id,
+        room_code = %report.room_code,
+        game_name = %report.game_name,
+        reporter_id = %report.reporter_id,
+        reporter_name = %report.reporter_name,
+        reporter_fingerprint = report.reporter_fingerprint.as_deref(),
+        target_id = %report.target_id,
+        target_name = %report.target_name,
+        target_fingerprint = report.target_fingerprint.as_deref(),
+        reason = report.reason.as_str(),
+        details = report.details.as_deref(),
+        "Player reported"
+    );
+}
@@ -198,6 +198,52 @@ pub fn validate_alignment<T>(bytes: &[u8]) -> Result<(), RkyvError> {
     Ok(())
 }
 
+/// A received buffer prepared for [`zero_copy_access`].
+///
+/// Network buffers carry no alignment guarantee, so a buffer that does not
+/// start at `T`'s alignment is copied into an [`AlignedVec`] once. Aligned
+/// buffers are shared as-is.
+#[derive(Debug)]
+pub enum AlignedBytes {
+    /// The original buffer, already aligned
+    Shared(Bytes),
+    /// An aligned copy of a misaligned buffer
+    Copied(AlignedVec),
+}
+
+impl AlignedBytes {
+    /// Wrap `bytes`, copying them only if they are misaligned for `T`.
+    #[must_use]
+    pub fn new<T>(bytes: Bytes) -> Self {
+        if is_aligned::<T>(&bytes) {
+            Self::Shared(bytes)
+        } else {
+            let mut aligned = AlignedVec::with_capacity(bytes.len());
+            aligned.extend_from_slice(&bytes);
+            Self::Copied(aligned)
+        }
+    }
+
+    /// The buffer contents.
+    #[must_use]
+    pub fn as_slice(&self) -> &[u8] {
+        match self {
+            Self::Shared(bytes) => bytes,
+            Self::Copied(aligned) => aligned.as_slice(),
+        }
+    }
+
+    /// `Bytes` for `part`, which must be a subslice of [`Self::as_slice`].
+    /// Shares the original allocation unless the buffer had to be copied.
+    #[must_use]
+    pub fn slice_ref(&self, part: &[u8]) -> Bytes {
+        match self {
+            Self::Shared(bytes) => bytes.slice_ref(part),
+            Self::Copied(_) => Bytes::copy_from_slice(part),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -298,6 +344,36 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_aligned_bytes_copies_only_misaligned_buffers() {
+        let original = TestStruct {
+            id: 7,
+            name: "shifted".to_string(),
+            active: true,
+        };
+        let bytes = RkyvSerializer::new()
+            .serialize(&original)
+            .expect("serialization failed");
+
+        let shared = AlignedBytes::new::<ArchivedTestStruct>(bytes.clone());
+        assert!(matches!(shared, AlignedBytes::Shared(_)));
+
+        // Shift the archive one byte into a larger buffer
+        let mut padded = vec![0u8; bytes.len() + 1];
+        padded[1..].copy_from_slice(&bytes);
+        let shifted = Bytes::from(padded).slice(1..);
+        let copied = AlignedBytes::new::<ArchivedTestStruct>(shifted);
+        assert!(matches!(copied, AlignedBytes::Copied(_)));
+
+        let archived =
+            zero_copy_access::<ArchivedTestStruct>(copied.as_slice()).expect("access failed");
+        assert_eq!(archived.name.as_str(), "shifted");
+        assert_eq!(
+            copied.slice_ref(archived.name.as_bytes()),
+            Bytes::from_static(b"shifted")
+        );
+    }
+
     #[test]
     fn test_serialize_aligned() {
         let original = TestStruct {
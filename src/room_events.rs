@@ -0,0 +1,93 @@
+//! Server-wide room lifecycle events for observers.
+//!
+//! The server publishes an event at each point where it tells a room about a
+//! lifecycle change. Observers (see `/v2/events`) subscribe to a bounded
+//! broadcast channel: a subscriber that falls behind loses the oldest events
+//! instead of slowing the server down, and learns how many it missed from
+//! [`tokio::sync::broadcast::error::RecvError::Lagged`].
+
+use crate::protocol::{PlayerId, RoomId};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+/// Events buffered per observer before the oldest are dropped.
+pub const ROOM_EVENT_BUFFER: usize = 1024;
+
+/// A room lifecycle event, serialized with its kind's fields flattened in.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RoomEvent {
+    pub room_id: RoomId,
+    pub game_name: String,
+    pub timestamp: DateTime<Utc>,
+    #[serde(flatten)]
+    pub kind: RoomEventKind,
+}
+
+/// What happened to the room, serialized as `type` plus event fields.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RoomEventKind {
+    RoomCreated {
+        room_code: String,
+        max_players: u8,
+        created_by: PlayerId,
+    },
+    RoomDeleted {
+        room_code: String,
+        reason: String,
+    },
+    PlayerJoined {
+        player_id: PlayerId,
+        player_name: String,
+        player_count: usize,
+    },
+    PlayerLeft {
+        player_id: PlayerId,
+        player_name: String,
+        player_count: usize,
+    },
+    LobbyEntered,
+    GameFinalized,
+}
+
+/// Publishing side of the room event channel, owned by the server.
+pub struct RoomEventStream {
+    sender: broadcast::Sender<Arc<RoomEvent>>,
+}
+
+impl RoomEventStream {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self { sender }
+    }
+
+    /// Whether anyone is listening. Callers skip building events otherwise.
+    pub fn has_observers(&self) -> bool {
+        self.sender.receiver_count() > 0
+    }
+
+    pub fn publish(&self, room_id: RoomId, game_name: &str, kind: RoomEventKind) {
+        if !self.has_observers() {
+            return;
+        }
+        // Only fails when the last observer left in the meantime.
+        let _ = self.sender.send(Arc::new(RoomEvent {
+            room_id,
+            game_name: game_name.to_string(),
+            timestamp: Utc::now(),
+            kind,
+        }));
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<RoomEvent>> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for RoomEventStream {
+    fn default() -> Self {
+        Self::new(ROOM_EVENT_BUFFER)
+    }
+}
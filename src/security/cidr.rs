@@ -0,0 +1,152 @@
+//! IP address ranges as written in configuration.
+//!
+//! Used by `security.trusted_proxies`, `security.ip_allowlist` and
+//! `security.ip_blocklist`. IPv4-mapped IPv6 addresses match IPv4 ranges.
+
+use ipnet::IpNet;
+use serde::de::{self, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::net::IpAddr;
+use std::str::FromStr;
+
+/// An IP address range. Parses from CIDR notation; a bare address is a single
+/// host (`/32` or `/128`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IpCidr(IpNet);
+
+impl IpCidr {
+    pub fn new(net: IpNet) -> Self {
+        Self(net)
+    }
+
+    /// Whether `ip` falls within this range.
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        self.0.contains(&ip.to_canonical())
+    }
+}
+
+impl FromStr for IpCidr {
+    type Err = String;
+
+    fn from_str(entry: &str) -> Result<Self, Self::Err> {
+        let entry = entry.trim();
+        entry
+            .parse::<IpNet>()
+            .or_else(|_| entry.parse::<IpAddr>().map(IpNet::from))
+            .map(Self)
+            .map_err(|_| {
+                format!("invalid IP range `{entry}`: expected an IP address or CIDR range")
+            })
+    }
+}
+
+impl fmt::Display for IpCidr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl Serialize for IpCidr {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for IpCidr {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let entry = String::deserialize(deserializer)?;
+        entry.parse().map_err(de::Error::custom)
+    }
+}
+
+/// Deserialize a list of ranges from a list or a comma-separated string, so a
+/// single range set through an environment variable parses too.
+pub fn deserialize_cidr_list<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<IpCidr>, D::Error> {
+    deserializer.deserialize_any(CidrListVisitor)
+}
+
+/// Like [`deserialize_cidr_list`], with `null` meaning no list at all.
+pub fn deserialize_optional_cidr_list<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Vec<IpCidr>>, D::Error> {
+    deserializer.deserialize_option(OptionalCidrListVisitor)
+}
+
+struct CidrListVisitor;
+
+impl<'de> Visitor<'de> for CidrListVisitor {
+    type Value = Vec<IpCidr>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a list or comma-separated string of IP addresses and CIDR ranges")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(IpCidr::from_str)
+            .collect::<Result<_, _>>()
+            .map_err(E::custom)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut ranges = Vec::new();
+        while let Some(range) = seq.next_element::<IpCidr>()? {
+            ranges.push(range);
+        }
+        Ok(ranges)
+    }
+}
+
+struct OptionalCidrListVisitor;
+
+impl<'de> Visitor<'de> for OptionalCidrListVisitor {
+    type Value = Option<Vec<IpCidr>>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        CidrListVisitor.expecting(f)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(None)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserialize_cidr_list(deserializer).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ranges(value: serde_json::Value) -> Vec<IpCidr> {
+        deserialize_cidr_list(value).unwrap()
+    }
+
+    #[test]
+    fn ranges_parse_bare_addresses_and_reject_garbage() {
+        let parsed = ranges(serde_json::json!(["198.51.100.7", "::1"]));
+        assert_eq!(
+            serde_json::to_value(&parsed).unwrap(),
+            serde_json::json!(["198.51.100.7/32", "::1/128"])
+        );
+
+        let err = deserialize_cidr_list(serde_json::json!(["10.0.0.0/33"])).unwrap_err();
+        assert!(err.to_string().contains("10.0.0.0/33"), "{err}");
+
+        assert_eq!(
+            deserialize_optional_cidr_list(serde_json::Value::Null).unwrap(),
+            None
+        );
+    }
+}
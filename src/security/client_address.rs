@@ -0,0 +1,245 @@
+//! Client address resolution for per-IP connection limiting.
+//!
+//! Behind a reverse proxy every connection arrives from the proxy's address.
+//! When the socket peer is one of the configured trusted proxies, the client
+//! address is read from the `Forwarded` (RFC 7239) or `X-Forwarded-For`
+//! header instead. Hops are walked right to left and the first one that is not
+//! itself a trusted proxy is the client, so entries a client prepends to the
+//! header are never reached. Headers from untrusted peers are ignored.
+
+use super::cidr::{deserialize_cidr_list, IpCidr};
+use axum::http::HeaderMap;
+use ipnet::{IpNet, Ipv6Net};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+
+const FORWARDED_HEADER: &str = "forwarded";
+const X_FORWARDED_FOR_HEADER: &str = "x-forwarded-for";
+
+/// Proxies whose forwarding headers are trusted.
+///
+/// Deserializes from a list or a comma-separated string of CIDR ranges; a
+/// bare address is a single host (`/32` or `/128`).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct TrustedProxies(Vec<IpCidr>);
+
+impl TrustedProxies {
+    pub fn new(ranges: Vec<IpCidr>) -> Self {
+        Self(ranges)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Whether `ip` belongs to a trusted proxy.
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        self.0.iter().any(|range| range.contains(ip))
+    }
+
+    /// Resolve the client address of a connection accepted from `peer`.
+    ///
+    /// Returns `peer` unchanged unless it is a trusted proxy. The port is
+    /// always the peer's, since forwarded ports are not reliable.
+    pub fn client_addr(&self, peer: SocketAddr, headers: &HeaderMap) -> SocketAddr {
+        if !self.contains(peer.ip()) {
+            return peer;
+        }
+        let mut client = peer.ip();
+        for hop in forwarded_hops(headers).into_iter().rev() {
+            if !self.contains(client) {
+                break;
+            }
+            // An obfuscated or malformed hop ends the chain at the last proxy.
+            let Some(ip) = hop else {
+                break;
+            };
+            client = ip;
+        }
+        SocketAddr::new(client.to_canonical(), peer.port())
+    }
+}
+
+impl<'de> Deserialize<'de> for TrustedProxies {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_cidr_list(deserializer).map(Self)
+    }
+}
+
+/// Forwarding hops from the `Forwarded` header, or `X-Forwarded-For` when it
+/// is absent, in order from the original client to the nearest proxy.
+/// Hops without a usable address are `None`.
+fn forwarded_hops(headers: &HeaderMap) -> Vec<Option<IpAddr>> {
+    let forwarded: Vec<&str> = headers
+        .get_all(FORWARDED_HEADER)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .collect();
+    if !forwarded.is_empty() {
+        return forwarded
+            .iter()
+            .flat_map(|value| value.split(','))
+            .map(|element| {
+                element.split(';').find_map(|pair| {
+                    let (name, node) = pair.split_once('=')?;
+                    name.trim()
+                        .eq_ignore_ascii_case("for")
+                        .then(|| parse_node(node))
+                })?
+            })
+            .collect();
+    }
+    headers
+        .get_all(X_FORWARDED_FOR_HEADER)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(parse_node)
+        .collect()
+}
+
+/// Parse a forwarded node: an address, optionally bracketed, quoted or with a port.
+fn parse_node(node: &str) -> Option<IpAddr> {
+    let node = node.trim().trim_matches('"');
+    node.parse::<IpAddr>()
+        .ok()
+        .or_else(|| node.parse::<SocketAddr>().ok().map(|addr| addr.ip()))
+        .or_else(|| node.strip_prefix('[')?.strip_suffix(']')?.parse().ok())
+}
+
+/// Key the per-IP connection limit is counted under: an IPv4 address, or the
+/// IPv6 network of the configured prefix length so a subnet shares one budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ConnectionKey(IpNet);
+
+impl ConnectionKey {
+    pub fn new(ip: IpAddr, ipv6_prefix_len: u8) -> Self {
+        match ip.to_canonical() {
+            IpAddr::V4(ip) => Self(IpNet::from(IpAddr::V4(ip))),
+            IpAddr::V6(ip) => Self(IpNet::V6(
+                Ipv6Net::new_assert(ip, ipv6_prefix_len.min(128)).trunc(),
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ConnectionKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            IpNet::V6(net) if net.prefix_len() < 128 => net.fmt(f),
+            net => net.addr().fmt(f),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn proxies(ranges: &str) -> TrustedProxies {
+        serde_json::from_value(serde_json::json!(ranges)).unwrap()
+    }
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.append(*name, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    fn peer(ip: &str) -> SocketAddr {
+        SocketAddr::new(ip.parse().unwrap(), 40000)
+    }
+
+    #[test]
+    fn test_forwarded_headers_from_untrusted_peers_are_ignored() {
+        let trusted = proxies("10.0.0.0/8");
+        let spoofed = headers(&[
+            ("x-forwarded-for", "198.51.100.1"),
+            ("forwarded", "for=198.51.100.2"),
+        ]);
+
+        let resolved = trusted.client_addr(peer("203.0.113.9"), &spoofed);
+        assert_eq!(resolved, peer("203.0.113.9"));
+
+        let unconfigured = TrustedProxies::default().client_addr(peer("10.0.0.1"), &spoofed);
+        assert_eq!(unconfigured, peer("10.0.0.1"));
+    }
+
+    #[test]
+    fn test_trusted_chain_resolves_to_first_untrusted_hop() {
+        let trusted = proxies("10.0.0.0/8, 192.168.1.1");
+        // The client prepended a fake hop; the real client is the one the
+        // outermost trusted proxy saw.
+        let forwarded = headers(&[("x-forwarded-for", "1.1.1.1, 203.0.113.9, 192.168.1.1")]);
+        assert_eq!(
+            trusted.client_addr(peer("10.1.2.3"), &forwarded).ip(),
+            "203.0.113.9".parse::<IpAddr>().unwrap()
+        );
+
+        // Every hop trusted: the leftmost is the client
+        let internal = headers(&[("x-forwarded-for", "10.9.9.9, 192.168.1.1")]);
+        assert_eq!(
+            trusted.client_addr(peer("10.1.2.3"), &internal).ip(),
+            "10.9.9.9".parse::<IpAddr>().unwrap()
+        );
+
+        // A malformed hop stops the walk at the last trusted proxy
+        let garbled = headers(&[("x-forwarded-for", "203.0.113.9, not-an-ip")]);
+        assert_eq!(
+            trusted.client_addr(peer("10.1.2.3"), &garbled).ip(),
+            "10.1.2.3".parse::<IpAddr>().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_forwarded_header_takes_precedence() {
+        let trusted = proxies("10.0.0.1");
+        let forwarded = headers(&[
+            ("x-forwarded-for", "198.51.100.1"),
+            (
+                "forwarded",
+                "for=unknown, proto=https;For=\"[2001:db8:cafe::17]:4711\"",
+            ),
+        ]);
+        assert_eq!(
+            trusted.client_addr(peer("10.0.0.1"), &forwarded).ip(),
+            "2001:db8:cafe::17".parse::<IpAddr>().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_ipv6_addresses_share_a_key_within_the_prefix() {
+        let key = |ip: &str, prefix| ConnectionKey::new(ip.parse().unwrap(), prefix);
+
+        assert_eq!(key("2001:db8:1:2::1", 64), key("2001:db8:1:2:ffff::9", 64));
+        assert_ne!(key("2001:db8:1:2::1", 64), key("2001:db8:1:3::1", 64));
+        assert_eq!(key("2001:db8:1:2::1", 48), key("2001:db8:1:3::1", 48));
+        assert_ne!(key("2001:db8::1", 128), key("2001:db8::2", 128));
+        assert_eq!(key("::ffff:192.0.2.7", 64), key("192.0.2.7", 64));
+
+        assert_eq!(key("2001:db8:1:2::1", 64).to_string(), "2001:db8:1:2::/64");
+        assert_eq!(key("192.0.2.7", 64).to_string(), "192.0.2.7");
+    }
+
+    #[test]
+    fn test_trusted_proxies_parse_lists_and_reject_garbage() {
+        let parsed: TrustedProxies =
+            serde_json::from_value(serde_json::json!(["10.0.0.0/8", "::1"])).unwrap();
+        assert!(parsed.contains("10.20.30.40".parse().unwrap()));
+        assert!(parsed.contains("::1".parse().unwrap()));
+        assert!(!parsed.contains("11.0.0.1".parse().unwrap()));
+        assert_eq!(
+            serde_json::to_value(&parsed).unwrap(),
+            serde_json::json!(["10.0.0.0/8", "::1/128"])
+        );
+
+        let err = serde_json::from_value::<TrustedProxies>(serde_json::json!(["10.0.0.0/33"]))
+            .unwrap_err();
+        assert!(err.to_string().contains("10.0.0.0/33"), "{err}");
+    }
+}
@@ -4,9 +4,12 @@ use base64::engine::general_purpose::STANDARD as BASE64;
 use base64::Engine;
 use chrono::{DateTime, Utc};
 use getrandom::fill as fill_random;
+use ring::hmac;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::convert::TryInto;
 use std::fmt;
+use subtle::ConstantTimeEq;
 use thiserror::Error;
 
 /// Size of the AES-GCM nonce in bytes.
@@ -14,6 +17,28 @@ const NONCE_SIZE: usize = 12;
 /// Size of the AES-256 key in bytes.
 const KEY_SIZE: usize = 32;
 
+/// Compare two secrets in constant time.
+///
+/// Only a length mismatch returns early; callers that must not reveal the
+/// secret's length should compare fixed-size digests instead.
+#[must_use]
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.ct_eq(b).into()
+}
+
+/// HMAC-SHA256 of `message` under `key`, as lowercase hex.
+#[must_use]
+pub fn hmac_sha256_hex(key: &[u8], message: &[u8]) -> String {
+    let tag = hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, key), message);
+    tag.as_ref()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
 /// Encrypted secret payload for secure storage.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct EncryptedSecret {
@@ -85,12 +110,43 @@ impl EnvelopeEncryptor {
         let key_bytes: [u8; KEY_SIZE] = decoded
             .try_into()
             .map_err(|_| EncryptionError::InvalidKeyLength(decoded_len))?;
+        Ok(Self::from_key_bytes(key_id, key_bytes))
+    }
+
+    /// Construct an encryptor around a freshly generated random key.
+    ///
+    /// The key never leaves the process, so anything sealed or derived from it is
+    /// only usable by this instance.
+    pub fn generate(key_id: impl Into<String>) -> Result<Self, EncryptionError> {
+        let mut key_bytes = [0u8; KEY_SIZE];
+        fill_random(&mut key_bytes).map_err(|_| EncryptionError::EntropyUnavailable)?;
+        Ok(Self::from_key_bytes(key_id, key_bytes))
+    }
+
+    fn from_key_bytes(key_id: impl Into<String>, key_bytes: [u8; KEY_SIZE]) -> Self {
         let key = Key::<Aes256Gcm>::from(key_bytes);
-        let cipher = Aes256Gcm::new(&key);
-        Ok(Self {
+        Self {
             key_id: key_id.into(),
-            cipher,
-        })
+            cipher: Aes256Gcm::new(&key),
+        }
+    }
+
+    /// Derive a 256-bit subkey for `context` from the master key.
+    ///
+    /// The result is deterministic for a given master key and context, and
+    /// different contexts yield unrelated keys. The master key itself is never exposed.
+    pub fn derive_key(&self, context: &str) -> Result<[u8; KEY_SIZE], EncryptionError> {
+        let digest = Sha256::digest(context.as_bytes());
+        let mut nonce_bytes = [0u8; NONCE_SIZE];
+        nonce_bytes.copy_from_slice(&digest[..NONCE_SIZE]);
+        let keystream = self
+            .cipher
+            .encrypt(&Nonce::from(nonce_bytes), [0u8; KEY_SIZE].as_ref())
+            .map_err(|_| EncryptionError::EncryptionFailure)?;
+
+        let mut derived = [0u8; KEY_SIZE];
+        derived.copy_from_slice(&keystream[..KEY_SIZE]);
+        Ok(derived)
     }
 
     /// The identifier of the master key backing this encryptor.
@@ -165,6 +221,15 @@ mod tests {
     use super::*;
     use base64::Engine;
 
+    #[test]
+    fn hmac_sha256_hex_matches_rfc_4231() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            hmac_sha256_hex(b"Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
     fn sample_key() -> String {
         // 32 bytes key (all 1s) encoded in base64.
         BASE64.encode([1u8; KEY_SIZE])
@@ -194,4 +259,33 @@ mod tests {
         let err = other.decrypt(&bundle).expect_err("should fail");
         matches!(err, EncryptionError::KeyMismatch { .. });
     }
+
+    #[test]
+    fn derived_keys_depend_on_master_key_and_context() {
+        let encryptor =
+            EnvelopeEncryptor::new_from_base64_key("test-key", &sample_key()).expect("key");
+        let same_master =
+            EnvelopeEncryptor::new_from_base64_key("other-id", &sample_key()).expect("key");
+        let generated = EnvelopeEncryptor::generate("instance").expect("entropy");
+
+        let derived = encryptor.derive_key("reconnection").expect("derive");
+        assert_eq!(
+            derived,
+            same_master.derive_key("reconnection").expect("derive")
+        );
+        assert_ne!(derived, encryptor.derive_key("other").expect("derive"));
+        assert_ne!(
+            derived,
+            generated.derive_key("reconnection").expect("derive")
+        );
+        assert_ne!(derived, [1u8; KEY_SIZE]);
+    }
+
+    #[test]
+    fn constant_time_eq_compares_contents_and_length() {
+        assert!(constant_time_eq(b"api-key", b"api-key"));
+        assert!(!constant_time_eq(b"api-key", b"api-kex"));
+        assert!(!constant_time_eq(b"api-key", b"api-key-2"));
+        assert!(constant_time_eq(b"", b""));
+    }
 }
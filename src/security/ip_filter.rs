@@ -0,0 +1,104 @@
+//! Client IP allow/blocklists checked before any route runs.
+//!
+//! The blocklist wins: an address in both lists is refused. When an allowlist
+//! is configured, addresses outside it are refused as well. IPv4-mapped IPv6
+//! addresses match IPv4 ranges.
+
+use super::cidr::IpCidr;
+use std::net::IpAddr;
+
+/// Why [`IpFilter::check`] refused an address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IpFilterRejection {
+    Blocklisted,
+    NotAllowlisted,
+}
+
+/// The `security.ip_allowlist` and `security.ip_blocklist` settings.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IpFilter {
+    allowlist: Option<Vec<IpCidr>>,
+    blocklist: Vec<IpCidr>,
+}
+
+impl IpFilter {
+    pub fn new(allowlist: Option<Vec<IpCidr>>, blocklist: Vec<IpCidr>) -> Self {
+        Self {
+            allowlist,
+            blocklist,
+        }
+    }
+
+    /// Whether every address is let through.
+    pub fn is_empty(&self) -> bool {
+        self.allowlist.is_none() && self.blocklist.is_empty()
+    }
+
+    /// Check `ip` against the blocklist, then the allowlist.
+    pub fn check(&self, ip: IpAddr) -> Result<(), IpFilterRejection> {
+        if self.blocklist.iter().any(|range| range.contains(ip)) {
+            return Err(IpFilterRejection::Blocklisted);
+        }
+        match &self.allowlist {
+            Some(allowlist) if !allowlist.iter().any(|range| range.contains(ip)) => {
+                Err(IpFilterRejection::NotAllowlisted)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::cidr::deserialize_cidr_list;
+
+    fn ranges(value: serde_json::Value) -> Vec<IpCidr> {
+        deserialize_cidr_list(value).unwrap()
+    }
+
+    fn ip(value: &str) -> IpAddr {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn blocklisted_ranges_are_refused() {
+        let filter = IpFilter::new(None, ranges(serde_json::json!(["192.168.0.0/24"])));
+
+        assert_eq!(
+            filter.check(ip("192.168.0.1")),
+            Err(IpFilterRejection::Blocklisted)
+        );
+        assert_eq!(
+            filter.check(ip("::ffff:192.168.0.1")),
+            Err(IpFilterRejection::Blocklisted)
+        );
+        assert_eq!(filter.check(ip("10.0.0.1")), Ok(()));
+    }
+
+    #[test]
+    fn allowlist_applies_after_the_blocklist() {
+        let filter = IpFilter::new(
+            Some(ranges(serde_json::json!("10.0.0.0/8, 2001:db8::/32"))),
+            ranges(serde_json::json!("10.0.0.13")),
+        );
+
+        assert_eq!(filter.check(ip("10.1.2.3")), Ok(()));
+        assert_eq!(filter.check(ip("2001:db8::7")), Ok(()));
+        assert_eq!(
+            filter.check(ip("10.0.0.13")),
+            Err(IpFilterRejection::Blocklisted)
+        );
+        assert_eq!(
+            filter.check(ip("203.0.113.9")),
+            Err(IpFilterRejection::NotAllowlisted)
+        );
+
+        let deny_all = IpFilter::new(Some(Vec::new()), Vec::new());
+        assert_eq!(
+            deny_all.check(ip("10.1.2.3")),
+            Err(IpFilterRejection::NotAllowlisted)
+        );
+        assert!(IpFilter::default().is_empty());
+    }
+}
@@ -4,17 +4,29 @@
 /// - TLS/mTLS support (gated behind `tls` feature)
 /// - Envelope encryption (AES-GCM)
 /// - Token binding and channel security
+/// - Client address resolution behind trusted proxies
+/// - Client IP allow/blocklists
+/// - IP ranges shared by the proxy and IP filter settings
+/// - Time-limited TURN credentials (TURN REST API)
+pub mod cidr;
+pub mod client_address;
 pub mod crypto;
+pub mod ip_filter;
 pub mod tls;
 pub mod token_binding; // Always include tls module (ClientCertificateFingerprint is always needed)
+pub mod turn;
 
-pub use crypto::EnvelopeEncryptor;
+pub use cidr::IpCidr;
+pub use client_address::{ConnectionKey, TrustedProxies};
+pub use crypto::{constant_time_eq, hmac_sha256_hex, EnvelopeEncryptor};
+pub use ip_filter::{IpFilter, IpFilterRejection};
 pub use token_binding::{
     derive_session_secret, ActiveTokenBinding, TokenBindingError, TokenBindingProof,
 };
+pub use turn::{turn_rest_credentials, turn_rest_password, TurnCredentials};
 
 // ClientCertificateFingerprint and CLIENT_FINGERPRINT_HEADER_CANDIDATES are always available
 pub use tls::{ClientCertificateFingerprint, CLIENT_FINGERPRINT_HEADER_CANDIDATES};
 
 #[cfg(feature = "tls")]
-pub use tls::build_rustls_config;
+pub use tls::{build_client_cert_verifier, build_rustls_config};
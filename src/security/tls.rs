@@ -49,7 +49,7 @@ pub fn build_rustls_config(tls: &TlsServerConfig) -> Result<RustlsConfig> {
 fn build_server_config(tls: &TlsServerConfig) -> Result<RustlsServerConfig> {
     let cert_chain = load_cert_chain(tls)?;
     let private_key = load_private_key(tls)?;
-    let verifier = build_client_verifier(tls)?;
+    let verifier = build_client_cert_verifier(tls)?;
 
     let mut config = RustlsServerConfig::builder()
         .with_client_cert_verifier(verifier)
@@ -112,8 +112,13 @@ fn load_private_key(tls: &TlsServerConfig) -> Result<PrivateKeyDer<'static>> {
 }
 
 #[cfg(feature = "tls")]
-fn build_client_verifier(tls: &TlsServerConfig) -> Result<Arc<dyn ClientCertVerifier>> {
-    if matches!(tls.client_auth, ClientAuthMode::None) {
+/// Build the client certificate verifier for `tls.client_auth`.
+///
+/// `None` and `Jwt` disable certificate requests entirely. `Optional` and
+/// `Require` verify presented certificates against `client_ca_cert_path`;
+/// only `Require` rejects handshakes that present none.
+pub fn build_client_cert_verifier(tls: &TlsServerConfig) -> Result<Arc<dyn ClientCertVerifier>> {
+    if matches!(tls.client_auth, ClientAuthMode::None | ClientAuthMode::Jwt) {
         return Ok(WebPkiClientVerifier::no_client_auth());
     }
 
@@ -156,3 +161,71 @@ fn build_client_verifier(tls: &TlsServerConfig) -> Result<Arc<dyn ClientCertVeri
 
     Ok(verifier)
 }
+
+#[cfg(all(test, feature = "tls"))]
+mod tests {
+    use super::*;
+
+    const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/test-fixtures/tls");
+
+    fn tls_config(client_auth: ClientAuthMode) -> TlsServerConfig {
+        TlsServerConfig {
+            enabled: true,
+            certificate_path: Some(format!("{FIXTURES}/server.pem")),
+            private_key_path: Some(format!("{FIXTURES}/server-key.pem")),
+            client_ca_cert_path: Some(format!("{FIXTURES}/ca.pem")),
+            client_auth,
+            ..TlsServerConfig::default()
+        }
+    }
+
+    #[test]
+    fn client_cert_verifier_matches_auth_mode() {
+        let cases = [
+            (ClientAuthMode::None, false, false),
+            (ClientAuthMode::Jwt, false, false),
+            (ClientAuthMode::Optional, true, false),
+            (ClientAuthMode::Require, true, true),
+        ];
+        for (mode, offered, mandatory) in cases {
+            let verifier = build_client_cert_verifier(&tls_config(mode)).unwrap();
+            assert_eq!(verifier.offer_client_auth(), offered, "{mode:?}");
+            assert_eq!(verifier.client_auth_mandatory(), mandatory, "{mode:?}");
+            if offered {
+                assert!(!verifier.root_hint_subjects().is_empty(), "{mode:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn server_config_builds_for_every_auth_mode() {
+        for mode in [
+            ClientAuthMode::None,
+            ClientAuthMode::Jwt,
+            ClientAuthMode::Optional,
+            ClientAuthMode::Require,
+        ] {
+            let config = build_server_config(&tls_config(mode)).unwrap();
+            assert_eq!(
+                config.alpn_protocols,
+                vec![b"h2".to_vec(), b"http/1.1".to_vec()],
+                "{mode:?}"
+            );
+            assert!(build_rustls_config(&tls_config(mode)).is_ok(), "{mode:?}");
+        }
+    }
+
+    #[test]
+    fn client_auth_without_ca_bundle_is_rejected() {
+        for mode in [ClientAuthMode::Optional, ClientAuthMode::Require] {
+            let mut tls = tls_config(mode);
+            tls.client_ca_cert_path = None;
+            let err = build_client_cert_verifier(&tls).err().unwrap();
+            assert!(err.to_string().contains("client_ca_cert_path"), "{err}");
+        }
+
+        let mut tls = tls_config(ClientAuthMode::None);
+        tls.client_ca_cert_path = None;
+        assert!(build_client_cert_verifier(&tls).is_ok());
+    }
+}
@@ -0,0 +1,123 @@
+//! Time-limited TURN credentials in the TURN REST API format.
+//!
+//! A TURN server configured with the same shared secret (coturn's
+//! `use-auth-secret`) accepts the username `<expiry unix seconds>:<user id>`
+//! with the password `base64(HMAC-SHA1(secret, username))` until the expiry
+//! passes, so clients get credentials without accounts being provisioned.
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use ring::hmac;
+use std::time::Duration;
+
+/// A TURN username and password valid until `expires_at`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TurnCredentials {
+    pub username: String,
+    pub credential: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Credentials for `user_id` that stop working `ttl` after `now`.
+#[must_use]
+pub fn turn_rest_credentials(
+    secret: &str,
+    user_id: &str,
+    ttl: Duration,
+    now: DateTime<Utc>,
+) -> TurnCredentials {
+    let ttl = chrono::Duration::from_std(ttl).unwrap_or(chrono::Duration::MAX);
+    let expires_at = now
+        .checked_add_signed(ttl)
+        .unwrap_or(DateTime::<Utc>::MAX_UTC);
+    let username = format!("{}:{user_id}", expires_at.timestamp());
+    let credential = turn_rest_password(secret, &username);
+    TurnCredentials {
+        username,
+        credential,
+        expires_at,
+    }
+}
+
+/// The password a TURN server derives for `username` from the shared secret.
+#[must_use]
+pub fn turn_rest_password(secret: &str, username: &str) -> String {
+    BASE64.encode(hmac_sha1(secret.as_bytes(), username.as_bytes()))
+}
+
+/// HMAC-SHA1, the message integrity algorithm of STUN (RFC 5389).
+fn hmac_sha1(key: &[u8], message: &[u8]) -> hmac::Tag {
+    hmac::sign(
+        &hmac::Key::new(hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY, key),
+        message,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+
+    #[test]
+    fn hmac_sha1_matches_the_rfc_5769_stun_request_vector() {
+        // RFC 5769 section 2.1: a Binding request whose MESSAGE-INTEGRITY is
+        // keyed with the short-term password "VOkJxbRl1RmTxUk/WvJxBt". The
+        // HMAC covers the message up to that attribute, with the header
+        // length counting through it (0x50) rather than the FINGERPRINT.
+        let message: [u8; 80] = [
+            0x00, 0x01, 0x00, 0x50, 0x21, 0x12, 0xa4, 0x42, 0xb7, 0xe7, 0xa7, 0x01, 0xbc, 0x34,
+            0xd6, 0x86, 0xfa, 0x87, 0xdf, 0xae, 0x80, 0x22, 0x00, 0x10, 0x53, 0x54, 0x55, 0x4e,
+            0x20, 0x74, 0x65, 0x73, 0x74, 0x20, 0x63, 0x6c, 0x69, 0x65, 0x6e, 0x74, 0x00, 0x24,
+            0x00, 0x04, 0x6e, 0x00, 0x01, 0xff, 0x80, 0x29, 0x00, 0x08, 0x93, 0x2f, 0xf9, 0xb1,
+            0x51, 0x26, 0x3b, 0x36, 0x00, 0x06, 0x00, 0x09, 0x65, 0x76, 0x74, 0x6a, 0x3a, 0x68,
+            0x36, 0x76, 0x59, 0x20, 0x20, 0x20, 0x00, 0x08, 0x00, 0x14,
+        ];
+        // The attribute header itself is not covered
+        let covered = &message[..76];
+        let tag = hmac_sha1(b"VOkJxbRl1RmTxUk/WvJxBt", covered);
+        assert_eq!(
+            hex(tag.as_ref()),
+            "9aeaa70cbfd8cb56781ef2b5b2d3f249c1b571a2"
+        );
+    }
+
+    #[test]
+    fn hmac_sha1_matches_rfc_2202() {
+        let tag = hmac_sha1(&[0x0b; 20], b"Hi There");
+        assert_eq!(
+            hex(tag.as_ref()),
+            "b617318655057264e28bc0b6fb378c8ef146be00"
+        );
+    }
+
+    #[test]
+    fn credentials_follow_the_turn_rest_format() {
+        let now = DateTime::from_timestamp(1_718_000_000, 0).unwrap();
+        let credentials = turn_rest_credentials(
+            "turn-shared-secret",
+            "4a3ed0c1-5ad6-4f7c-8a55-2f4b1a7f7b10",
+            Duration::from_secs(3600),
+            now,
+        );
+        assert_eq!(
+            credentials.username,
+            "1718003600:4a3ed0c1-5ad6-4f7c-8a55-2f4b1a7f7b10"
+        );
+        assert_eq!(credentials.credential, "rZ/glmJo86b80fz5QYolLDkIPnw=");
+        assert_eq!(credentials.expires_at.timestamp(), 1_718_003_600);
+
+        // The TURN server recomputes the password from the username alone
+        assert_eq!(
+            turn_rest_password("turn-shared-secret", &credentials.username),
+            credentials.credential
+        );
+        assert_ne!(
+            turn_rest_password("other-secret", &credentials.username),
+            credentials.credential
+        );
+    }
+}
@@ -1,12 +1,14 @@
 use crate::auth::AppInfo;
-use crate::config::AppAuthEntry;
+use crate::broadcast::Outbound;
+use crate::config::{AppAuthEntry, SdkCompatibilityReport};
 use crate::coordination::{
     InMemoryRoomOperationCoordinator, MessageCoordinator, RoomOperationCoordinatorTrait,
 };
 use crate::database::{create_database, DatabaseConfig, GameDatabase};
 use crate::distributed::{DistributedLock, InMemoryDistributedLock};
 use crate::protocol::{
-    room_codes, GameDataEncoding, PlayerId, RoomId, ServerMessage, SpectatorStateChangeReason,
+    room_codes, GameDataEncoding, MessageEncoding, PlayerId, RoomId, ServerMessage,
+    SpectatorStateChangeReason,
 };
 use crate::rate_limit::{RateLimitConfig, RoomRateLimiter};
 use anyhow::Result;
@@ -24,30 +26,127 @@ fn chrono_duration_from_std(duration: Duration) -> chrono::Duration {
 }
 
 mod admin;
+#[cfg(test)]
+mod admin_tests;
+mod announcements;
+#[cfg(test)]
+mod announcements_tests;
+mod app_quotas;
+#[cfg(test)]
+mod app_quotas_tests;
 mod authority;
+mod broadcast_isolation;
+#[cfg(test)]
+mod closed_rooms_tests;
+mod config_reload;
+#[cfg(test)]
+mod config_reload_tests;
 mod connection_manager;
 mod dashboard_cache;
+mod delivery_ack;
+#[cfg(test)]
+mod delivery_ack_tests;
+mod draining;
+#[cfg(test)]
+mod draining_tests;
+mod flap_damping;
+#[cfg(test)]
+mod flap_damping_tests;
 mod game_data;
 mod heartbeat;
+mod history_replay;
+#[cfg(test)]
+mod history_replay_tests;
+mod ice_servers;
+#[cfg(test)]
+mod ice_servers_tests;
+mod invites;
+#[cfg(test)]
+mod invites_tests;
+mod join_interception;
+#[cfg(test)]
+mod join_interception_tests;
 mod maintenance;
+mod matchmaking_service;
+#[cfg(test)]
+mod matchmaking_tests;
 mod message_router;
 #[cfg(test)]
 mod message_router_tests;
 mod messaging;
+mod moderation;
+#[cfg(test)]
+mod moderation_tests;
+#[cfg(feature = "otlp")]
+mod otlp_export;
+mod overflow_joins;
+#[cfg(test)]
+mod overflow_joins_tests;
+mod quick_join;
+#[cfg(test)]
+mod quick_join_tests;
+mod readiness;
+#[cfg(test)]
+mod readiness_tests;
 mod ready_state;
 #[cfg(test)]
 mod ready_state_tests;
 mod reconnection_service;
 mod relay_policy;
+#[cfg(test)]
+mod relay_policy_tests;
+#[cfg(test)]
+mod relay_warmup_tests;
+mod reports;
+#[cfg(test)]
+mod reports_tests;
+mod role_change;
+#[cfg(test)]
+mod role_change_tests;
+#[cfg(test)]
+mod room_events_tests;
+mod room_frames;
+#[cfg(test)]
+mod room_frames_tests;
+mod room_metadata;
+#[cfg(test)]
+mod room_metadata_tests;
+mod room_ordering;
+#[cfg(test)]
+mod room_ordering_tests;
 mod room_service;
 #[cfg(test)]
 mod room_service_tests;
+mod room_settings;
+#[cfg(test)]
+mod room_settings_tests;
 mod spectator_handlers;
+#[cfg(test)]
+mod spectator_handlers_tests;
 mod spectator_service;
+mod stats;
+#[cfg(test)]
+mod stats_tests;
+mod waiting_list;
+#[cfg(test)]
+mod waiting_list_tests;
+#[cfg(test)]
+mod webhooks_tests;
 
+pub use admin::{AdminRoomClosure, ADMIN_CLOSE_REASON};
+pub use app_quotas::{default_app_id, AppQuotaUsage, DEFAULT_APP_NAME};
+pub use broadcast_isolation::{BroadcastScheduler, AUTO_ISOLATE};
 use connection_manager::ConnectionManager;
 use dashboard_cache::{DashboardMetricsCache, DashboardMetricsView};
+pub use delivery_ack::{DeliveryAckSampler, DELIVERY_ACK_CAPABILITY};
+pub use flap_damping::PlayerFlaps;
+pub use maintenance::{EMPTY_CLEANUP_REASON, EXPIRED_CLOSE_REASON, INACTIVE_CLEANUP_REASON};
+use overflow_joins::PendingJoinRequest;
+use room_frames::{BroadcastFrames, RoomFrameRoutes};
+pub use spectator_handlers::SPECTATOR_MODE_CAPABILITY;
 use spectator_service::SpectatorService;
+pub use stats::ServerStats;
+use waiting_list::WaitingListEntry;
 
 // Removed unused imports
 
@@ -58,7 +157,8 @@ pub struct EnhancedGameServer {
     /// Connection management (clients, IP accounting)
     connection_manager: ConnectionManager,
     /// Server configuration
-    config: ServerConfig,
+    /// Replaced as a whole when the configuration is reloaded
+    config: std::sync::RwLock<Arc<ServerConfig>>,
     /// Protocol configuration for validation
     protocol_config: crate::config::ProtocolConfig,
     /// Relay type configuration for game-specific networking
@@ -79,6 +179,8 @@ pub struct EnhancedGameServer {
     reconnection_manager: Option<Arc<crate::reconnection::ReconnectionManager>>,
     /// Authentication middleware for App ID validation
     pub(crate) auth_middleware: Arc<crate::auth::AuthMiddleware>,
+    /// Applications from `security.authorized_apps`, which rooms may belong to
+    authorized_apps: Vec<AppAuthEntry>,
     /// Mapping from room IDs to owning application IDs (for relay policies)
     room_applications: Arc<DashMap<RoomId, Uuid>>,
     /// Spectator lifecycle manager
@@ -87,6 +189,51 @@ pub struct EnhancedGameServer {
     transport_security: crate::config::TransportSecurityConfig,
     /// Cached metrics used by the admin dashboard
     dashboard_metrics_cache: Arc<DashboardMetricsCache>,
+    /// Sparkline series served by `/metrics/history`, sampled by the cleanup task
+    metrics_history: crate::metrics::MetricsHistory,
+    /// Players queued on a full room's waiting list, keyed by player
+    waiting_players: DashMap<PlayerId, WaitingListEntry>,
+    /// Joins to full rooms waiting for the room authority's answer, keyed by player
+    join_requests: DashMap<PlayerId, PendingJoinRequest>,
+    /// Sampled broadcast delivery acknowledgements
+    delivery_acks: Arc<DeliveryAckSampler>,
+    /// Shared and per-game worker budgets for game data broadcasts
+    broadcast_scheduler: BroadcastScheduler,
+    /// Players waiting for skill-based matchmaking
+    matchmaking: crate::matchmaking::MatchmakingQueue,
+    flap_damping: flap_damping::FlapDamping,
+    /// Last heartbeat-driven activity refresh per room
+    room_activity: heartbeat::RoomActivityThrottle,
+    /// Report build version and uptime from the metrics endpoints
+    include_build_info: bool,
+    /// Embedder hook consulted before players and spectators join a room
+    join_interceptor: std::sync::RwLock<Arc<dyn crate::join_interceptor::JoinInterceptor>>,
+    /// Ahead-of-time relay session provisioning
+    relay_warmup: Arc<crate::relay_provisioning::RelayWarmup>,
+    /// Room lifecycle notifications for `server.webhooks`
+    webhooks: crate::webhooks::WebhookDispatcher,
+    /// Rooms created here, and summaries of recently closed ones
+    closed_rooms: crate::closed_rooms::ClosedRoomHistory,
+    /// Invite tokens issued for rooms, dropped when they expire or the room closes
+    invites: crate::invites::InviteStore,
+    /// Room lifecycle events for `/v2/events` observers
+    room_events: crate::room_events::RoomEventStream,
+    /// Cleanup task liveness, checked by the readiness probe
+    cleanup_heartbeat: readiness::CleanupHeartbeat,
+    /// Cleanup task tick count and time, for `get_server_stats`
+    cleanup_runs: stats::CleanupRuns,
+    /// Rooms each application created in the last hour, for `max_rooms_per_hour`
+    app_room_creations: app_quotas::AppRoomCreations,
+    /// Set while the instance refuses new rooms and joins (see `draining`)
+    draining: std::sync::atomic::AtomicBool,
+    /// Flipped once when the process starts shutting down (see `draining`)
+    shutting_down: tokio::sync::watch::Sender<bool>,
+    /// The latest operator announcement, kept for late joiners until it expires
+    announcement: Arc<std::sync::RwLock<Option<announcements::ActiveAnnouncement>>>,
+    /// Where counters are saved on graceful shutdown (None when disabled)
+    metrics_persistence_path: Option<std::path::PathBuf>,
+    #[cfg(feature = "otlp")]
+    otlp_exporter: Option<Arc<crate::metrics::otlp::OtlpExporter>>,
 }
 
 #[derive(Debug, Error)]
@@ -95,6 +242,32 @@ pub enum RegisterClientError {
     IpLimitExceeded { current: usize, limit: usize },
 }
 
+/// A per-IP connection slot taken before a WebSocket upgrade is accepted.
+///
+/// Dropping it without registering a client, e.g. when the upgrade never
+/// completes, gives the slot back.
+pub struct ConnectionSlot {
+    server: Option<Arc<EnhancedGameServer>>,
+    client_addr: SocketAddr,
+}
+
+impl ConnectionSlot {
+    pub fn client_addr(&self) -> SocketAddr {
+        self.client_addr
+    }
+}
+
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) {
+        if let Some(server) = self.server.take() {
+            let key = server
+                .connection_manager
+                .connection_key(self.client_addr.ip());
+            server.connection_manager.release_reserved_ip_slot(key);
+        }
+    }
+}
+
 #[derive(Debug, Error)]
 #[error("Game `{game_name}` already has {current} rooms (limit {limit})")]
 pub struct MaxRoomsPerGameExceededError {
@@ -103,6 +276,42 @@ pub struct MaxRoomsPerGameExceededError {
     pub limit: usize,
 }
 
+#[derive(Debug, Error)]
+#[error("Application already has {current} rooms (limit {limit})")]
+pub struct MaxRoomsPerAppExceededError {
+    pub application_id: Uuid,
+    pub current: usize,
+    pub limit: usize,
+}
+
+#[derive(Debug, Error)]
+#[error("Application {quota} quota reached: {current} of {limit}")]
+pub struct AppQuotaExceededError {
+    pub application_id: Uuid,
+    pub quota: crate::protocol::QuotaKind,
+    pub current: usize,
+    pub limit: usize,
+}
+
+#[derive(Debug, Error)]
+#[error("Creator already has {current} active rooms (limit {limit})")]
+pub struct MaxActiveRoomsPerCreatorError {
+    pub creator: String,
+    pub current: usize,
+    pub limit: usize,
+}
+
+#[derive(Debug, Error)]
+#[error("Room is full")]
+pub struct RoomFullError {
+    pub room_id: RoomId,
+    pub max_players: u8,
+}
+
+#[derive(Debug, Error)]
+#[error("You are banned from this room")]
+pub struct PlayerBannedError;
+
 #[derive(Debug, Clone)]
 pub struct ServerConfig {
     pub default_max_players: u8,
@@ -112,23 +321,97 @@ pub struct ServerConfig {
     pub rate_limit_config: RateLimitConfig,
     pub empty_room_timeout: Duration,
     pub inactive_room_timeout: Duration,
+    /// How long before a room expires to warn its players (zero disables).
+    pub expiry_warning: Duration,
     pub max_message_size: usize,
     pub max_connections_per_ip: usize,
+    /// Proxies whose forwarding headers identify the client for connection limits.
+    pub trusted_proxies: crate::security::TrustedProxies,
+    /// IPv6 clients share a connection limit per network of this prefix length.
+    pub ipv6_connection_prefix: u8,
+    /// Client IP allow/blocklists applied to every HTTP request.
+    pub ip_filter: crate::security::IpFilter,
     pub require_metrics_auth: bool,
     pub metrics_auth_token: Option<String>,
+    /// Bearer token for admin endpoints; `metrics_auth_token` is used when unset.
+    pub admin_auth_token: Option<String>,
     pub reconnection_window: Duration,
     pub event_buffer_size: usize,
     pub enable_reconnection: bool,
+    /// How long a disconnected player's seat is held before `PlayerLeft` is
+    /// broadcast; reconnecting sooner is hidden from the room.
+    pub flap_suppression: Duration,
+    /// Maximum age of a reconnection token, regardless of rotation.
+    pub max_token_age: Duration,
+    /// Players awaiting reconnection into one room at once (0 = unlimited).
+    pub max_reconnection_slots_per_room: usize,
     pub websocket_config: crate::config::WebSocketConfig,
     pub auth_enabled: bool,
     /// Threshold for heartbeat update throttling.
     /// Only update `last_seen` if this duration has passed since the last update.
     /// Set to Duration::ZERO to disable throttling (update on every heartbeat).
     pub heartbeat_throttle: Duration,
+    /// Change in reported round-trip time, in milliseconds, that is broadcast
+    /// to the room as `PlayerLatencyUpdated` (`None` disables).
+    pub latency_update_threshold_ms: Option<u32>,
     /// Identifier for the deployment region (used in player info and room codes).
     pub region_id: String,
+    /// Other regions clients may name as their own.
+    pub allowed_regions: Vec<String>,
     /// Optional prefix prepended to generated room codes.
     pub room_code_prefix: Option<String>,
+    /// Maximum number of players queued on a full room's waiting list (0 disables).
+    pub max_waiting_list_size: usize,
+    /// Maximum number of spectators queued for promotion per room (0 disables).
+    pub max_promotion_queue_size: usize,
+    /// Maximum skill-rating spread between players grouped into one match.
+    pub matchmaking_elo_window: f32,
+    /// Interval between matchmaking passes.
+    pub matchmaking_tick: Duration,
+    /// Deadline for the readiness probe's checks.
+    pub readiness_check_timeout: Duration,
+    /// Retry hint given to joins refused while the instance drains.
+    pub drain_retry_after: Duration,
+    /// Games whose game data broadcasts get dedicated workers (`"auto"` for
+    /// automatic isolation above `isolation_auto_threshold`).
+    pub isolate_games: Vec<String>,
+    /// Broadcasts per second that trigger automatic isolation.
+    pub isolation_auto_threshold: u32,
+    /// Concurrent broadcasts shared by games that are not isolated.
+    pub broadcast_workers: usize,
+    /// Concurrent broadcasts reserved for each isolated game.
+    pub isolated_broadcast_workers: usize,
+    /// Replay recent game data to players after `RoomJoined` and `Reconnected`.
+    pub enable_history_replay: bool,
+    /// Game data messages kept per room for history replay.
+    pub room_history_size: usize,
+    /// Deadline for the join interceptor hook.
+    pub join_interceptor_timeout: Duration,
+    /// How joins are resolved when the join interceptor fails or times out.
+    pub join_interceptor_failure_policy: crate::config::JoinInterceptorFailurePolicy,
+    /// Endpoints notified of room lifecycle events.
+    pub webhooks: Vec<crate::config::WebhookConfig>,
+    /// STUN/TURN servers sent to players in `RoomJoined`.
+    pub ice_servers: Vec<crate::config::IceServerConfig>,
+    /// Closed room summaries kept for the admin API.
+    pub closed_room_history_size: usize,
+    /// How long a create-room idempotency key resolves to its room (zero disables).
+    pub room_idempotency_window: Duration,
+    /// How long a room authority has to answer an overflow `JoinRequest`.
+    pub join_request_timeout: Duration,
+    /// How long a seated player may go unseen before cleanup removes them
+    /// (zero disables).
+    pub stale_player_timeout: Duration,
+    /// Comma-separated origins allowed by CORS on the game routes, or `*`
+    /// for any origin.
+    pub cors_origins: String,
+    /// Comma-separated origins allowed by CORS on the metrics, stats and
+    /// admin routes, or `*` for any origin.
+    pub metrics_cors_origins: String,
+    /// Per-game room code formats, limits and timeouts.
+    pub game_overrides: crate::config::GameOverrideConfig,
+    /// Quotas for rooms created without an authenticated application.
+    pub default_app_quota: crate::config::AppQuotaConfig,
 }
 
 impl Default for ServerConfig {
@@ -141,22 +424,64 @@ impl Default for ServerConfig {
             rate_limit_config: RateLimitConfig::default(),
             empty_room_timeout: Duration::from_secs(300),
             inactive_room_timeout: Duration::from_secs(3600),
+            expiry_warning: Duration::from_secs(120),
             max_message_size: 65536, // 64KB
             max_connections_per_ip: 10,
+            trusted_proxies: crate::security::TrustedProxies::default(),
+            ipv6_connection_prefix: 64,
+            ip_filter: crate::security::IpFilter::default(),
             require_metrics_auth: true,
             metrics_auth_token: None,
+            admin_auth_token: None,
             reconnection_window: Duration::from_secs(300), // 5 minutes
             event_buffer_size: 100,
             enable_reconnection: true,
+            flap_suppression: Duration::from_secs(5),
+            max_token_age: Duration::from_secs(3600),
+            max_reconnection_slots_per_room: 0, // Unlimited by default
             websocket_config: crate::config::WebSocketConfig::default(),
             auth_enabled: false, // Disabled by default for backward compatibility
             heartbeat_throttle: Duration::from_secs(30), // 30 second update throttle by default
+            latency_update_threshold_ms: None, // Latency updates disabled by default
             region_id: "default".to_string(),
+            allowed_regions: Vec::new(),
             room_code_prefix: None,
+            max_waiting_list_size: 0,    // Waiting lists disabled by default
+            max_promotion_queue_size: 0, // Spectator promotion disabled by default
+            matchmaking_elo_window: 200.0,
+            matchmaking_tick: Duration::from_millis(1000),
+            readiness_check_timeout: Duration::from_millis(1000),
+            drain_retry_after: Duration::from_secs(30),
+            isolate_games: Vec::new(), // Every game shares the broadcast workers by default
+            isolation_auto_threshold: 500,
+            broadcast_workers: 256,
+            isolated_broadcast_workers: 32,
+            enable_history_replay: false, // History replay disabled by default
+            room_history_size: 50,
+            join_interceptor_timeout: Duration::from_millis(1000),
+            join_interceptor_failure_policy:
+                crate::config::JoinInterceptorFailurePolicy::FailClosed,
+            webhooks: Vec::new(), // No webhooks by default
+            ice_servers: Vec::new(),
+            closed_room_history_size: 1000,
+            room_idempotency_window: Duration::from_secs(300),
+            join_request_timeout: Duration::from_secs(30),
+            stale_player_timeout: Duration::from_secs(300),
+            cors_origins: crate::config::defaults::default_cors_origins(),
+            metrics_cors_origins: crate::config::defaults::default_cors_origins(),
+            game_overrides: crate::config::GameOverrideConfig::new(),
+            default_app_quota: crate::config::AppQuotaConfig::default(),
         }
     }
 }
 
+impl ServerConfig {
+    /// Outbound messages buffered per connection, and per room broadcast group.
+    pub fn client_queue_capacity(&self) -> usize {
+        self.websocket_config.max_pending_send_queue_depth.max(1)
+    }
+}
+
 impl EnhancedGameServer {
     #[allow(clippy::too_many_arguments, clippy::type_complexity)]
     pub async fn new(
@@ -170,8 +495,15 @@ impl EnhancedGameServer {
         transport_security: crate::config::TransportSecurityConfig,
         authorized_apps: Vec<AppAuthEntry>,
     ) -> anyhow::Result<Arc<Self>> {
-        let database: Arc<dyn GameDatabase> =
-            Arc::from(create_database(database_config.clone()).await?);
+        let database: Arc<dyn GameDatabase> = Arc::from(
+            create_database(
+                database_config.clone(),
+                &protocol_config,
+                &config.game_overrides,
+                &maintenance::room_expiry_policy_for(&config),
+            )
+            .await?,
+        );
         database.initialize().await?;
 
         let instance_id = Uuid::new_v4();
@@ -179,7 +511,22 @@ impl EnhancedGameServer {
         let rate_limiter = Arc::new(RoomRateLimiter::new(config.rate_limit_config.clone()));
         rate_limiter.clone().start_cleanup_task();
 
-        let metrics = Arc::new(crate::metrics::ServerMetrics::new());
+        let metrics = Arc::new(
+            crate::metrics::ServerMetrics::with_session_duration_buckets(
+                &metrics_config.session_duration_buckets_secs,
+            )
+            .with_latency_buckets(&metrics_config.histogram_buckets),
+        );
+        let metrics_persistence_path = metrics_config
+            .persist_counters
+            .then(|| std::path::PathBuf::from(&metrics_config.persistence_path));
+        if let Some(path) = &metrics_persistence_path {
+            crate::metrics::persistence::restore_counters(
+                &metrics,
+                path,
+                metrics_config.persistence_max_age.into(),
+            );
+        }
 
         let cache_refresh_interval =
             Duration::from_secs(metrics_config.dashboard_cache_refresh_interval_secs.max(1));
@@ -197,30 +544,78 @@ impl EnhancedGameServer {
         ));
         dashboard_metrics_cache.spawn(database.clone());
 
+        let history_sample_interval: Duration = metrics_config.history_sample_interval.into();
+        if !metrics_config.history_series.is_empty()
+            && history_sample_interval < config.room_cleanup_interval
+        {
+            tracing::warn!(
+                sample_interval = %metrics_config.history_sample_interval,
+                cleanup_interval = ?config.room_cleanup_interval,
+                "metrics.history_sample_interval is shorter than server.room_cleanup_interval; \
+                 history is sampled once per cleanup tick"
+            );
+        }
+        let metrics_history = crate::metrics::MetricsHistory::new(
+            &metrics_config.history_series,
+            history_sample_interval,
+            metrics_config.history_capacity,
+        );
+
+        let delivery_acks = Arc::new(DeliveryAckSampler::new(
+            metrics_config.delivery_ack_sample_rate,
+            metrics_config.delivery_ack_timeout.into(),
+            metrics_config.delivery_ack_max_pending,
+            config.region_id.clone(),
+            metrics.clone(),
+        ));
+        delivery_acks.clone().start_sweeper();
+
+        let broadcast_scheduler = BroadcastScheduler::new(&config, metrics.clone());
+
         // Setup distributed coordination - in-memory only
         let distributed_lock = Arc::new(InMemoryDistributedLock::new());
-        let message_coordinator = Arc::new(InMemoryMessageCoordinator::new());
+        let message_coordinator = Arc::new(
+            InMemoryMessageCoordinator::new()
+                .with_delivery_acks(delivery_acks.clone())
+                .with_room_frames(),
+        );
 
         let connection_manager = ConnectionManager::new(
             config.max_connections_per_ip,
+            config.ipv6_connection_prefix,
             metrics.clone(),
             message_coordinator.clone(),
         );
 
-        let room_coordinator: Arc<dyn RoomOperationCoordinatorTrait> =
-            Arc::new(InMemoryRoomOperationCoordinator::new(
+        let relay_warmup = Arc::new(crate::relay_provisioning::RelayWarmup::new(
+            relay_type_config.warmup.clone(),
+            database.clone(),
+        ));
+        let room_coordinator: Arc<dyn RoomOperationCoordinatorTrait> = Arc::new(
+            InMemoryRoomOperationCoordinator::new(
                 message_coordinator.clone(),
                 distributed_lock.clone(),
                 database.clone(),
-            ));
+            )
+            .with_relay_warmup(relay_warmup.clone()),
+        );
 
-        // Initialize reconnection manager if enabled (in-memory only)
+        // Initialize reconnection manager if enabled (in-memory only). Tokens are
+        // signed with a key generated for this instance, so they cannot be
+        // redeemed on another instance.
         let reconnection_manager = if config.enable_reconnection {
-            Some(Arc::new(crate::reconnection::ReconnectionManager::new(
-                config.reconnection_window.as_secs(),
-                config.event_buffer_size,
-                metrics.clone(),
-            )))
+            let token_encryptor =
+                crate::security::EnvelopeEncryptor::generate(instance_id.to_string())?;
+            Some(Arc::new(
+                crate::reconnection::ReconnectionManager::new(
+                    config.reconnection_window.as_secs(),
+                    config.event_buffer_size,
+                    config.max_token_age.as_secs(),
+                    &token_encryptor,
+                    metrics.clone(),
+                )?
+                .with_max_slots_per_room(config.max_reconnection_slots_per_room),
+            ))
         } else {
             None
         };
@@ -238,10 +633,58 @@ impl EnhancedGameServer {
                     "Auth enabled with configured applications"
                 );
             }
-            Arc::new(crate::auth::AuthMiddleware::new(authorized_apps))
+            crate::auth::AuthMiddleware::new(authorized_apps.clone())
+        } else {
+            crate::auth::AuthMiddleware::disabled()
+        };
+        let auth_middleware =
+            if transport_security.tls.client_auth == crate::config::ClientAuthMode::Jwt {
+                let jwt = &transport_security.jwt;
+                let verifier = crate::auth::JwtVerifier::from_config(jwt)?;
+                tracing::info!(
+                    algorithm = ?verifier.algorithm(),
+                    required = jwt.required,
+                    issuer = jwt.issuer.as_deref(),
+                    audience = jwt.audience.as_deref(),
+                    "JWT bearer auth enabled on WebSocket upgrade"
+                );
+                Arc::new(
+                    auth_middleware
+                        .with_jwt(verifier)
+                        .with_jwt_required(jwt.required),
+                )
+            } else {
+                Arc::new(auth_middleware)
+            };
+
+        let webhooks =
+            crate::webhooks::WebhookDispatcher::new(config.webhooks.clone(), metrics.clone());
+        let closed_rooms =
+            crate::closed_rooms::ClosedRoomHistory::new(config.closed_room_history_size);
+
+        #[cfg(feature = "otlp")]
+        let otlp_exporter = if metrics_config.otlp.enabled {
+            let exporter = crate::metrics::otlp::OtlpExporter::new(
+                &metrics_config.otlp,
+                instance_id,
+                &config.region_id,
+            )?;
+            tracing::info!(
+                endpoint = %metrics_config.otlp.endpoint,
+                interval = %metrics_config.otlp.interval,
+                "Exporting metrics over OTLP"
+            );
+            Some(Arc::new(exporter))
         } else {
-            Arc::new(crate::auth::AuthMiddleware::disabled())
+            None
         };
+        #[cfg(not(feature = "otlp"))]
+        if metrics_config.otlp.enabled {
+            tracing::warn!(
+                "metrics.otlp.enabled is set, but this build does not include the otlp feature; \
+                 metrics will only be served from the Prometheus endpoint"
+            );
+        }
 
         let room_applications = Arc::new(DashMap::new());
         let spectator_service = SpectatorService::new(
@@ -254,7 +697,7 @@ impl EnhancedGameServer {
         let server = Arc::new(Self {
             database,
             connection_manager,
-            config,
+            config: std::sync::RwLock::new(Arc::new(config)),
             protocol_config,
             relay_type_config,
             rate_limiter,
@@ -265,11 +708,43 @@ impl EnhancedGameServer {
             instance_id,
             reconnection_manager,
             auth_middleware,
+            authorized_apps,
             room_applications,
             spectator_service,
             transport_security,
             dashboard_metrics_cache: dashboard_metrics_cache.clone(),
+            metrics_history,
+            include_build_info: metrics_config.include_build_info,
+            join_interceptor: std::sync::RwLock::new(Arc::new(
+                crate::join_interceptor::NoopJoinInterceptor,
+            )),
+            relay_warmup,
+            webhooks,
+            closed_rooms,
+            invites: crate::invites::InviteStore::new(),
+            room_events: crate::room_events::RoomEventStream::default(),
+            cleanup_heartbeat: readiness::CleanupHeartbeat::new(),
+            cleanup_runs: stats::CleanupRuns::default(),
+            app_room_creations: app_quotas::AppRoomCreations::default(),
+            draining: std::sync::atomic::AtomicBool::new(false),
+            shutting_down: tokio::sync::watch::Sender::new(false),
+            announcement: Arc::new(std::sync::RwLock::new(None)),
+            metrics_persistence_path,
+            #[cfg(feature = "otlp")]
+            otlp_exporter,
+            waiting_players: DashMap::new(),
+            join_requests: DashMap::new(),
+            delivery_acks,
+            broadcast_scheduler,
+            matchmaking: crate::matchmaking::MatchmakingQueue::new(),
+            flap_damping: flap_damping::FlapDamping::default(),
+            room_activity: heartbeat::RoomActivityThrottle::default(),
         });
+        Self::start_matchmaking(&server);
+        Self::start_flap_damping(&server);
+        Self::start_join_request_expiry(&server);
+        #[cfg(feature = "otlp")]
+        Self::start_otlp_export(&server, metrics_config.otlp.interval.into());
 
         Ok(server)
     }
@@ -278,27 +753,77 @@ impl EnhancedGameServer {
         self.dashboard_metrics_cache.view().await
     }
 
+    /// Recorded sparkline series for `/metrics/history`.
+    pub fn metrics_history(&self) -> &crate::metrics::MetricsHistory {
+        &self.metrics_history
+    }
+
+    /// Build version and uptime for the metrics endpoints, unless disabled in config.
+    pub fn build_info(&self) -> Option<crate::metrics::BuildInfo> {
+        self.include_build_info.then(|| self.metrics.build_info())
+    }
+
     /// Identifier for the current deployment region.
-    pub fn region_id(&self) -> &str {
-        &self.config.region_id
+    pub fn region_id(&self) -> String {
+        self.config().region_id.clone()
+    }
+
+    /// Whether clients may name `region_id` as their region: this server's
+    /// own region or one of `allowed_regions`.
+    pub fn is_allowed_region(&self, region_id: &str) -> bool {
+        let config = self.config();
+        config.region_id == region_id || config.allowed_regions.iter().any(|r| r == region_id)
+    }
+
+    /// The region `player_id` said it is in, or this server's region.
+    pub fn client_region(&self, player_id: &PlayerId) -> String {
+        self.connection_manager
+            .client_region(player_id)
+            .map_or_else(|| self.region_id(), |region| region.to_string())
     }
 
     /// Optional room-code prefix configured for this deployment.
-    pub fn room_code_prefix(&self) -> Option<&str> {
-        self.config.room_code_prefix.as_deref()
+    pub fn room_code_prefix(&self) -> Option<String> {
+        self.config().room_code_prefix.clone()
+    }
+
+    /// Max players for a room of `game_name` created without an explicit limit.
+    fn default_max_players_for(&self, game_name: &str) -> u8 {
+        self.config()
+            .game_overrides
+            .get(game_name)
+            .and_then(|game| game.default_max_players)
+            .unwrap_or(self.config().default_max_players)
+    }
+
+    /// Room cap for `game_name`, honouring its override.
+    fn max_rooms_for_game(&self, game_name: &str) -> usize {
+        self.config()
+            .game_overrides
+            .get(game_name)
+            .and_then(|game| game.max_rooms_per_game)
+            .unwrap_or(self.config().max_rooms_per_game)
     }
 
-    fn generate_region_room_code(&self) -> String {
+    /// Generate a room code for `game_name`, honouring its overrides before the region prefix.
+    fn generate_room_code(&self, game_name: &str) -> String {
+        if self.config().game_overrides.contains_key(game_name) {
+            return room_codes::generate_room_code_for_game(
+                game_name,
+                &self.protocol_config,
+                &self.config().game_overrides,
+            );
+        }
         room_codes::generate_region_room_code(
             &self.protocol_config,
-            self.config.room_code_prefix.as_deref(),
+            self.config().room_code_prefix.as_deref(),
         )
     }
 
     /// Register a new client connection
     pub async fn register_client(
         &self,
-        sender: mpsc::Sender<Arc<ServerMessage>>,
+        sender: mpsc::Sender<Outbound>,
         client_addr: SocketAddr,
     ) -> Result<PlayerId, RegisterClientError> {
         self.connection_manager
@@ -306,6 +831,47 @@ impl EnhancedGameServer {
             .await
     }
 
+    /// Admit a new connection from `client_addr`'s IP under
+    /// `rate_limit.connection_bucket`. IPv6 addresses share an allowance per
+    /// `ipv6_connection_prefix` network, as for the concurrent limit.
+    pub fn check_connection_rate(
+        &self,
+        client_addr: SocketAddr,
+    ) -> Result<(), crate::rate_limit::RateLimitError> {
+        let key = self.connection_manager.connection_key(client_addr.ip());
+        self.rate_limiter.check_connection(&key).inspect_err(|_| {
+            self.metrics.increment_connections_rejected_rate_limit();
+        })
+    }
+
+    /// Take a connection slot for `client_addr`'s IP ahead of a WebSocket
+    /// upgrade, failing when the IP is at `max_connections_per_ip`. IPv6
+    /// addresses share slots per `ipv6_connection_prefix` network.
+    pub fn reserve_connection_slot(
+        self: &Arc<Self>,
+        client_addr: SocketAddr,
+    ) -> Result<ConnectionSlot, RegisterClientError> {
+        let key = self.connection_manager.connection_key(client_addr.ip());
+        self.connection_manager.reserve_ip_slot(key)?;
+        Ok(ConnectionSlot {
+            server: Some(Arc::clone(self)),
+            client_addr,
+        })
+    }
+
+    /// Register a client into a slot from [`Self::reserve_connection_slot`].
+    pub async fn register_reserved_client(
+        &self,
+        sender: mpsc::Sender<Outbound>,
+        mut slot: ConnectionSlot,
+    ) -> PlayerId {
+        // The registered client owns the slot now; unregistering releases it.
+        slot.server = None;
+        self.connection_manager
+            .register_reserved_client(sender, slot.client_addr, self.instance_id)
+            .await
+    }
+
     /// Update a client's preferred game data encoding.
     pub fn set_client_game_data_format(&self, player_id: &PlayerId, format: GameDataEncoding) {
         self.connection_manager
@@ -327,6 +893,48 @@ impl EnhancedGameServer {
         self.connection_manager.app_info(player_id)
     }
 
+    /// Record the client certificate fingerprint captured on the upgrade.
+    pub fn set_client_fingerprint(
+        &self,
+        player_id: &PlayerId,
+        fingerprint: &crate::security::ClientCertificateFingerprint,
+    ) {
+        self.connection_manager
+            .set_client_fingerprint(player_id, Arc::clone(&fingerprint.fingerprint));
+    }
+
+    /// Client certificate fingerprint of a connected client, if one was captured.
+    pub fn client_fingerprint(&self, player_id: &PlayerId) -> Option<Arc<str>> {
+        self.connection_manager.client_fingerprint(player_id)
+    }
+
+    /// Record the SDK capabilities found when checking `player_id`'s SDK.
+    pub fn set_client_sdk_report(&self, player_id: &PlayerId, report: &SdkCompatibilityReport) {
+        self.connection_manager
+            .set_sdk_capabilities(player_id, report.capabilities.clone());
+    }
+
+    /// Whether `player_id`'s SDK supports the optional feature `capability`.
+    /// Clients that have not identified their SDK get the `_default` set.
+    pub fn client_has_capability(&self, player_id: &PlayerId, capability: &str) -> bool {
+        self.connection_manager
+            .sdk_capability(player_id, capability)
+            .unwrap_or_else(|| {
+                self.protocol_config
+                    .sdk_compatibility
+                    .capabilities
+                    .get("_default")
+                    .is_some_and(|caps| caps.iter().any(|cap| cap == capability))
+            })
+    }
+
+    /// Whether `player_id`'s bearer token scope lets them create or join rooms
+    /// for `game_name`. Clients without a scoped token may use any game.
+    pub fn client_allows_game(&self, player_id: &PlayerId, game_name: &str) -> bool {
+        self.client_app_info(player_id)
+            .is_none_or(|info| info.allows_game(game_name))
+    }
+
     /// Fetch just the application UUID for a connected client.
     pub fn client_app_id(&self, player_id: &PlayerId) -> Option<Uuid> {
         self.connection_manager.app_id(player_id)
@@ -371,11 +979,7 @@ impl EnhancedGameServer {
     }
 
     /// Connect a client with a specific player ID (used for testing)
-    pub async fn connect_client(
-        &self,
-        player_id: PlayerId,
-        sender: mpsc::Sender<Arc<ServerMessage>>,
-    ) {
+    pub async fn connect_client(&self, player_id: PlayerId, sender: mpsc::Sender<Outbound>) {
         let addr = "127.0.0.1:0".parse().unwrap();
         self.connection_manager
             .connect_test_client(player_id, sender, addr)
@@ -401,7 +1005,7 @@ impl EnhancedGameServer {
         let (room_id_opt, was_authority) = {
             let room_id = self.get_client_room(player_id).await;
             let was_authority = if let Some(ref room_id) = room_id {
-                if let Ok(Some(room)) = self.database.get_room_by_id(room_id).await {
+                if let Ok(Some(room)) = self.database.get_room_summary(room_id).await {
                     room.authority_player == Some(*player_id)
                 } else {
                     false
@@ -418,16 +1022,24 @@ impl EnhancedGameServer {
             .detach(player_id, SpectatorStateChangeReason::Disconnected)
             .await;
 
+        // Drop any pending waiting-list entry or overflow join request
+        self.cancel_waiting_list(player_id).await;
+        self.cancel_join_request(player_id);
+
         // Register disconnection for potential reconnection (before removing from room)
         if let Some(room_id) = room_id_opt {
             self.register_disconnection_for_reconnect(player_id, room_id, was_authority)
                 .await;
         }
 
-        // Remove from room if joined
+        // Remove from room if joined, unless the seat is held in case this is a flap
         if let Some(room_id) = room_id_opt {
-            tracing::info!(%player_id, %room_id, "Removing player from room during unregister");
-            self.leave_room(player_id).await;
+            if self.hold_seat_for_flap(player_id, room_id) {
+                tracing::info!(%player_id, %room_id, "Holding seat for disconnected player");
+            } else {
+                tracing::info!(%player_id, %room_id, "Removing player from room during unregister");
+                self.leave_room(player_id).await;
+            }
             // Note: We previously had a sleep here, but it's been removed to eliminate sleeps from production code
             // Tests should properly handle the asynchronous nature of message delivery
         }
@@ -436,6 +1048,8 @@ impl EnhancedGameServer {
         if self.connection_manager.remove_client(player_id).is_some() {
             self.metrics.decrement_active_connections();
         }
+        self.delivery_acks.forget_player(player_id);
+        self.matchmaking.dequeue(player_id);
 
         // Unregister from message coordinator
         if let Err(e) = self
@@ -461,9 +1075,15 @@ impl EnhancedGameServer {
         self.instance_id
     }
 
-    /// Get server configuration
-    pub fn config(&self) -> &ServerConfig {
-        &self.config
+    /// Get server configuration. The snapshot does not change if the
+    /// configuration is reloaded while it is held.
+    pub fn config(&self) -> Arc<ServerConfig> {
+        Arc::clone(
+            &self
+                .config
+                .read()
+                .unwrap_or_else(std::sync::PoisonError::into_inner),
+        )
     }
 
     /// Access zero-trust / token binding settings.
@@ -480,6 +1100,43 @@ impl EnhancedGameServer {
         self.metrics.clone()
     }
 
+    /// Save metrics counters for the next start, if persistence is enabled.
+    ///
+    /// Called once the listener has drained during graceful shutdown.
+    pub fn persist_metrics(&self) {
+        let Some(path) = &self.metrics_persistence_path else {
+            return;
+        };
+        match crate::metrics::persistence::save_counters(&self.metrics, path) {
+            Ok(()) => tracing::info!(path = %path.display(), "Persisted metrics counters"),
+            Err(err) => {
+                tracing::warn!(path = %path.display(), error = %err, "Failed to persist metrics counters");
+            }
+        }
+    }
+
+    /// Write rooms still buffered by a persistent database backend.
+    ///
+    /// Called once the listener has drained during graceful shutdown.
+    pub async fn flush_database(&self) {
+        if let Err(err) = self.database.flush().await {
+            tracing::warn!(error = %err, "Failed to flush room storage");
+        }
+    }
+
+    /// Push a final OTLP export and stop the exporter, if OTLP export is enabled.
+    ///
+    /// Called once the listener has drained during graceful shutdown.
+    pub async fn shutdown_metrics_export(&self) {
+        #[cfg(feature = "otlp")]
+        if let Some(exporter) = &self.otlp_exporter {
+            exporter.update(&crate::websocket::render_server_metrics(self).await);
+            // The final export blocks on the collector round trip
+            let exporter = Arc::clone(exporter);
+            let _ = tokio::task::spawn_blocking(move || exporter.shutdown()).await;
+        }
+    }
+
     /// Access the reconnection manager for integration tests or admin tooling.
     pub fn reconnection_manager(&self) -> Option<Arc<crate::reconnection::ReconnectionManager>> {
         self.reconnection_manager.clone()
@@ -488,10 +1145,15 @@ impl EnhancedGameServer {
 
 /// In-memory message coordinator for testing
 pub struct InMemoryMessageCoordinator {
-    local_clients: Arc<RwLock<HashMap<PlayerId, mpsc::Sender<Arc<ServerMessage>>>>>,
+    local_clients: Arc<RwLock<HashMap<PlayerId, mpsc::Sender<Outbound>>>>,
     room_players: Arc<RwLock<HashMap<RoomId, HashSet<PlayerId>>>>,
     #[allow(dead_code)]
     instance_id: Uuid,
+    delivery_acks: Option<Arc<DeliveryAckSampler>>,
+    /// Connections that take room broadcasts as shared frames
+    room_frames: Option<RoomFrameRoutes>,
+    /// Connections to shed when their queue overflows
+    backpressure: DashMap<PlayerId, Arc<tokio::sync::Notify>>,
 }
 
 use std::collections::HashSet;
@@ -502,8 +1164,118 @@ impl InMemoryMessageCoordinator {
             local_clients: Arc::new(RwLock::new(HashMap::new())),
             room_players: Arc::new(RwLock::new(HashMap::new())),
             instance_id: Uuid::new_v4(),
+            delivery_acks: None,
+            room_frames: None,
+            backpressure: DashMap::new(),
+        }
+    }
+
+    /// Serialize room broadcasts once for connections that subscribe with
+    /// [`MessageCoordinator::subscribe_room_frames`].
+    pub fn with_room_frames(mut self) -> Self {
+        self.room_frames = Some(RoomFrameRoutes::new());
+        self
+    }
+
+    /// Mark sampled room broadcasts for delivery acknowledgement.
+    pub fn with_delivery_acks(mut self, sampler: Arc<DeliveryAckSampler>) -> Self {
+        self.delivery_acks = Some(sampler);
+        self
+    }
+
+    /// Queue `message` for a local client. When the queue is full the message
+    /// is dropped and the connection, if it watches for backpressure, is shed.
+    fn enqueue(
+        &self,
+        player_id: &PlayerId,
+        sender: &mpsc::Sender<Outbound>,
+        message: Outbound,
+    ) -> bool {
+        match sender.try_send(message) {
+            Ok(()) => true,
+            Err(mpsc::error::TrySendError::Full(_)) => {
+                if let Some(shed) = self.backpressure.get(player_id) {
+                    shed.notify_one();
+                }
+                false
+            }
+            Err(mpsc::error::TrySendError::Closed(_)) => false,
         }
     }
+
+    fn sample_broadcast<'a>(
+        &self,
+        room_id: &RoomId,
+        message: &Arc<ServerMessage>,
+        recipients: impl IntoIterator<Item = &'a PlayerId>,
+    ) -> Option<delivery_ack::SampledBroadcast> {
+        self.delivery_acks
+            .as_ref()
+            .and_then(|sampler| sampler.sample(room_id, message, recipients))
+    }
+}
+
+impl InMemoryMessageCoordinator {
+    /// Queue `message` for the room's players except `except`. Subscribed
+    /// connections get one shared frame per encoding, the rest the message.
+    async fn broadcast(
+        &self,
+        room_id: &RoomId,
+        except: Option<&PlayerId>,
+        message: Arc<ServerMessage>,
+    ) {
+        // Lock ordering: room_players, then local_clients
+        let room_players = self.room_players.read().await;
+        let clients = self.local_clients.read().await;
+        let Some(players) = room_players.get(room_id) else {
+            return;
+        };
+        let sample = self.sample_broadcast(
+            room_id,
+            &message,
+            players
+                .iter()
+                .filter(|player_id| Some(*player_id) != except),
+        );
+
+        let mut frames = BroadcastFrames::default();
+        for player_id in players {
+            if Some(player_id) == except {
+                continue;
+            }
+            let Some(sender) = clients.get(player_id) else {
+                continue;
+            };
+            // Sampled broadcasts are marked per recipient, so they skip the shared frames
+            let frame = self
+                .room_frames
+                .as_ref()
+                .filter(|_| sample.is_none())
+                .and_then(|routes| routes.subscribed_encoding(player_id))
+                .and_then(|encoding| frames.get(&message, encoding));
+            let outgoing = match frame {
+                Some(frame) => Outbound::RoomFrame {
+                    message: Arc::clone(&message),
+                    frame,
+                },
+                None => Arc::clone(outgoing_message(sample.as_ref(), player_id, &message)).into(),
+            };
+            if !self.enqueue(player_id, sender, outgoing) {
+                tracing::warn!(%player_id, "Failed to broadcast message to player in room");
+            }
+        }
+    }
+}
+
+fn outgoing_message<'a>(
+    sample: Option<&'a delivery_ack::SampledBroadcast>,
+    player_id: &PlayerId,
+    message: &'a Arc<ServerMessage>,
+) -> &'a Arc<ServerMessage> {
+    match sample {
+        Some(sample) if sample.recipients.contains(player_id) => &sample.message,
+        _ => message,
+    }
 }
 
 #[async_trait::async_trait]
@@ -515,7 +1287,7 @@ impl MessageCoordinator for InMemoryMessageCoordinator {
     ) -> anyhow::Result<()> {
         let clients = self.local_clients.read().await;
         if let Some(sender) = clients.get(player_id) {
-            if sender.try_send(Arc::clone(&message)).is_err() {
+            if !self.enqueue(player_id, sender, Arc::clone(&message).into()) {
                 tracing::warn!(%player_id, "Failed to send message to local client");
             }
             tracing::info!(%player_id, ?message, "Message sent to player");
@@ -530,18 +1302,7 @@ impl MessageCoordinator for InMemoryMessageCoordinator {
         room_id: &RoomId,
         message: Arc<ServerMessage>,
     ) -> anyhow::Result<()> {
-        let room_players = self.room_players.read().await;
-        let clients = self.local_clients.read().await;
-
-        if let Some(players) = room_players.get(room_id) {
-            for player_id in players {
-                if let Some(sender) = clients.get(player_id) {
-                    if sender.try_send(Arc::clone(&message)).is_err() {
-                        tracing::warn!(%player_id, "Failed to broadcast message to player in room");
-                    }
-                }
-            }
-        }
+        self.broadcast(room_id, None, message).await;
         Ok(())
     }
 
@@ -551,64 +1312,98 @@ impl MessageCoordinator for InMemoryMessageCoordinator {
         except_player: &PlayerId,
         message: Arc<ServerMessage>,
     ) -> anyhow::Result<()> {
-        let room_players = self.room_players.read().await;
-        let clients = self.local_clients.read().await;
-
-        if let Some(players) = room_players.get(room_id) {
-            for player_id in players {
-                if player_id != except_player {
-                    if let Some(sender) = clients.get(player_id) {
-                        if sender.try_send(Arc::clone(&message)).is_err() {
-                            tracing::warn!(%player_id, "Failed to broadcast message to player in room");
-                        }
-                    }
-                }
-            }
-        }
+        self.broadcast(room_id, Some(except_player), message).await;
         Ok(())
     }
 
+    async fn broadcast_to_local_clients(
+        &self,
+        message: Arc<ServerMessage>,
+    ) -> anyhow::Result<usize> {
+        let clients = self.local_clients.read().await;
+        let delivered = clients
+            .iter()
+            .filter(|(player_id, sender)| {
+                self.enqueue(player_id, sender, Arc::clone(&message).into())
+            })
+            .count();
+        Ok(delivered)
+    }
+
     async fn register_local_client(
         &self,
         player_id: PlayerId,
         room_id: Option<RoomId>,
-        sender: mpsc::Sender<Arc<ServerMessage>>,
+        sender: mpsc::Sender<Outbound>,
     ) -> anyhow::Result<()> {
         // Lock ordering: room_players first, then local_clients
-        // (consistent with broadcast_to_room / broadcast_to_room_except read paths
-        //  to prevent ABBA deadlocks)
-        if let Some(room_id) = room_id {
+        // (consistent with the broadcast read path to prevent ABBA deadlocks)
+        let _room_players = if let Some(room_id) = room_id {
             let mut room_players = self.room_players.write().await;
             room_players
                 .entry(room_id)
                 .or_insert_with(HashSet::new)
                 .insert(player_id);
-            let mut clients = self.local_clients.write().await;
-            clients.insert(player_id, sender);
+            Some(room_players)
         } else {
             // No room_players lock needed when room_id is None
-            let mut clients = self.local_clients.write().await;
-            clients.insert(player_id, sender);
-        }
+            None
+        };
+        let mut clients = self.local_clients.write().await;
+        clients.insert(player_id, sender);
         Ok(())
     }
 
     async fn unregister_local_client(&self, player_id: &PlayerId) -> anyhow::Result<()> {
         // Lock ordering: room_players first, then local_clients
-        // (consistent with broadcast_to_room / broadcast_to_room_except read paths
-        //  to prevent ABBA deadlocks)
+        // (consistent with the broadcast read path to prevent ABBA deadlocks)
         let mut room_players = self.room_players.write().await;
-        room_players.retain(|_, players| {
+        room_players.retain(|room_id, players| {
             players.remove(player_id);
-            !players.is_empty()
+            if players.is_empty() {
+                if let Some(sampler) = &self.delivery_acks {
+                    sampler.forget_room(room_id);
+                }
+                return false;
+            }
+            true
         });
 
         let mut clients = self.local_clients.write().await;
         clients.remove(player_id);
+        self.backpressure.remove(player_id);
 
+        if let Some(frames) = &self.room_frames {
+            frames.remove_subscriber(player_id);
+        }
         Ok(())
     }
 
+    async fn subscribe_room_frames(&self, player_id: PlayerId, encoding: MessageEncoding) -> bool {
+        let Some(frames) = &self.room_frames else {
+            return false;
+        };
+        frames.add_subscriber(player_id, encoding);
+        true
+    }
+
+    async fn watch_backpressure(
+        &self,
+        player_id: PlayerId,
+        shed: Arc<tokio::sync::Notify>,
+    ) -> bool {
+        self.backpressure.insert(player_id, shed);
+        true
+    }
+
+    async fn is_accepting_messages(&self) -> bool {
+        // Each client has its own bounded channel and there is no shared queue
+        // to fill up, so this only confirms routing is not wedged behind a lock
+        let _room_players = self.room_players.read().await;
+        let _clients = self.local_clients.read().await;
+        true
+    }
+
     async fn should_process_message(
         &self,
         _message: &crate::distributed::SequencedMessage,
@@ -1,5 +1,27 @@
 use super::EnhancedGameServer;
+use crate::protocol::{PlayerId, RoomId, ServerMessage};
 use anyhow::Result;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// Reason sent in `RoomClosed` when support staff tear a room down.
+pub const ADMIN_CLOSE_REASON: &str = "closed_by_admin";
+
+/// Summary of a room force-closed through the admin API.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminRoomClosure {
+    pub room_id: RoomId,
+    pub game_name: String,
+    pub room_code: String,
+    /// Players seated in the room. Players awaiting reconnection are
+    /// counted in `reconnection_tokens_revoked` instead.
+    pub players_disconnected: usize,
+    pub spectators_disconnected: usize,
+    pub waiting_players_removed: usize,
+    pub reconnection_tokens_revoked: usize,
+}
 
 impl EnhancedGameServer {
     pub async fn admin_user_exists(&self, email: &str) -> Result<bool> {
@@ -9,4 +31,111 @@ impl EnhancedGameServer {
     pub async fn health_check(&self) -> bool {
         self.database.health_check().await
     }
+
+    /// Recently closed rooms, newest first, optionally for one game and
+    /// closed at or after `since`.
+    pub fn recent_closed_rooms(
+        &self,
+        game_name: Option<&str>,
+        since: Option<chrono::DateTime<chrono::Utc>>,
+        limit: usize,
+    ) -> Vec<crate::closed_rooms::ClosedRoomSummary> {
+        self.closed_rooms.recent(game_name, since, limit)
+    }
+
+    /// Subscribe to room lifecycle events from now on.
+    pub fn subscribe_room_events(
+        &self,
+    ) -> tokio::sync::broadcast::Receiver<Arc<crate::room_events::RoomEvent>> {
+        self.room_events.subscribe()
+    }
+
+    /// Whether any observer is currently subscribed to room lifecycle events.
+    pub fn has_room_event_observers(&self) -> bool {
+        self.room_events.has_observers()
+    }
+
+    /// Force-close a room: notify everyone in it, revoke reconnection tokens and delete it.
+    ///
+    /// Returns `Ok(None)` if no room with that code exists for the game.
+    pub async fn admin_close_room(
+        &self,
+        game_name: &str,
+        room_code: &str,
+    ) -> Result<Option<AdminRoomClosure>> {
+        let room_code = room_code.to_uppercase();
+        let Some(room) = self.database.get_room(game_name, &room_code).await? else {
+            return Ok(None);
+        };
+
+        let spectators = self.spectator_service.release_room(&room.id);
+        let waiting: Vec<PlayerId> = self
+            .waiting_players
+            .iter()
+            .filter(|entry| entry.value().room_id == room.id)
+            .map(|entry| *entry.key())
+            .collect();
+        for player_id in &waiting {
+            self.waiting_players.remove(player_id);
+        }
+
+        let recipients: HashSet<PlayerId> = room
+            .players
+            .keys()
+            .chain(&spectators)
+            .chain(&waiting)
+            .copied()
+            .collect();
+        let closed = Arc::new(ServerMessage::RoomClosed {
+            reason: ADMIN_CLOSE_REASON.to_string(),
+        });
+        for player_id in &recipients {
+            let _ = self
+                .message_coordinator
+                .send_to_player(player_id, Arc::clone(&closed))
+                .await;
+        }
+
+        // Detach players so they can join another room on the same connection
+        for player_id in room.players.keys() {
+            if let Some(sender) = self.connection_manager.clear_room_assignment(player_id) {
+                let _ = self
+                    .message_coordinator
+                    .register_local_client(*player_id, None, sender)
+                    .await;
+            }
+        }
+
+        let reconnection_tokens_revoked = match &self.reconnection_manager {
+            Some(manager) => manager.revoke_room(&room.id).await,
+            None => 0,
+        };
+
+        self.database.delete_room(&room.id).await?;
+        self.relay_warmup.release(&room.id).await;
+        self.clear_room_application(&room.id).await;
+        self.publish_room_closed(room.id, ADMIN_CLOSE_REASON);
+        self.metrics.increment_rooms_closed_by_admin();
+
+        let closure = AdminRoomClosure {
+            room_id: room.id,
+            game_name: room.game_name,
+            room_code: room.code,
+            players_disconnected: room.players.len(),
+            spectators_disconnected: spectators.len(),
+            waiting_players_removed: waiting.len(),
+            reconnection_tokens_revoked,
+        };
+        tracing::warn!(
+            room_id = %closure.room_id,
+            game_name = %closure.game_name,
+            room_code = %closure.room_code,
+            players = closure.players_disconnected,
+            spectators = closure.spectators_disconnected,
+            waiting = closure.waiting_players_removed,
+            reconnection_tokens_revoked,
+            "Room closed by admin"
+        );
+        Ok(Some(closure))
+    }
 }
@@ -0,0 +1,120 @@
+use super::*;
+use crate::config::{
+    AuthMaintenanceConfig, CoordinationConfig, MetricsConfig, ProtocolConfig, RelayTypeConfig,
+    TransportSecurityConfig,
+};
+use crate::database::DatabaseConfig;
+use crate::protocol::ServerMessage;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::time::{timeout, Duration};
+
+async fn create_test_server() -> Arc<EnhancedGameServer> {
+    EnhancedGameServer::new(
+        ServerConfig {
+            // Disconnects leave immediately rather than holding the seat
+            flap_suppression: Duration::ZERO,
+            ..ServerConfig::default()
+        },
+        ProtocolConfig::default(),
+        RelayTypeConfig::default(),
+        DatabaseConfig::InMemory,
+        MetricsConfig::default(),
+        AuthMaintenanceConfig::default(),
+        CoordinationConfig::default(),
+        TransportSecurityConfig::default(),
+        Vec::new(),
+    )
+    .await
+    .expect("failed to construct test server")
+}
+
+async fn register(server: &EnhancedGameServer, addr: &str) -> (PlayerId, mpsc::Receiver<Outbound>) {
+    let (sender, receiver) = mpsc::channel(32);
+    let addr: SocketAddr = addr.parse().unwrap();
+    let player_id = server
+        .connection_manager
+        .register_client(sender, addr, server.instance_id)
+        .await
+        .expect("client registration succeeds");
+    (player_id, receiver)
+}
+
+async fn join(server: &EnhancedGameServer, player_id: &PlayerId, name: &str) {
+    server
+        .handle_join_room(
+            player_id,
+            "admin-game".to_string(),
+            Some("CLOSE1".to_string()),
+            name.to_string(),
+            Some(4),
+            Some(false),
+            None,
+            None,
+            None,
+        )
+        .await;
+}
+
+async fn expect_room_closed(receiver: &mut mpsc::Receiver<Outbound>) {
+    while let Ok(Some(message)) = timeout(Duration::from_secs(1), receiver.recv()).await {
+        if let ServerMessage::RoomClosed { reason } = message.as_ref() {
+            assert_eq!(reason, ADMIN_CLOSE_REASON);
+            return;
+        }
+    }
+    panic!("expected RoomClosed");
+}
+
+#[tokio::test]
+async fn admin_close_removes_everyone_and_revokes_reconnection() {
+    let server = create_test_server().await;
+    let (host, mut host_rx) = register(&server, "127.0.0.1:48600").await;
+    let (dropped, _dropped_rx) = register(&server, "127.0.0.2:48601").await;
+    let (spectator, mut spectator_rx) = register(&server, "127.0.0.3:48602").await;
+
+    join(&server, &host, "Host").await;
+    join(&server, &dropped, "Dropped").await;
+    server
+        .handle_join_as_spectator(
+            &spectator,
+            "admin-game".to_string(),
+            "CLOSE1".to_string(),
+            "Watcher".to_string(),
+        )
+        .await;
+    // Leaves a pending reconnection token behind
+    server.unregister_client(&dropped).await;
+
+    let closure = server
+        .admin_close_room("admin-game", "close1")
+        .await
+        .unwrap()
+        .expect("room exists");
+
+    assert_eq!(closure.room_code, "CLOSE1");
+    // The dropped player already left the room; only their token remained
+    assert_eq!(closure.players_disconnected, 1);
+    assert_eq!(closure.spectators_disconnected, 1);
+    assert_eq!(closure.reconnection_tokens_revoked, 1);
+    expect_room_closed(&mut host_rx).await;
+    expect_room_closed(&mut spectator_rx).await;
+
+    assert!(server.get_client_room(&host).await.is_none());
+    assert!(server
+        .database
+        .get_room("admin-game", "CLOSE1")
+        .await
+        .unwrap()
+        .is_none());
+    assert!(server
+        .admin_close_room("admin-game", "CLOSE1")
+        .await
+        .unwrap()
+        .is_none());
+
+    // The host's connection is free to join a fresh room
+    join(&server, &host, "Host").await;
+    assert!(server.get_client_room(&host).await.is_some());
+}
@@ -0,0 +1,92 @@
+//! Operator announcements pushed to every connected player.
+//!
+//! `POST /v2/admin/announce` queues a `ServerAnnouncement` for each client on
+//! this instance and keeps it until `expires_at`, so players who connect in the
+//! meantime receive it after their handshake. A newer announcement replaces
+//! the active one.
+
+use chrono::{DateTime, Utc};
+use std::sync::{Arc, PoisonError};
+
+use super::EnhancedGameServer;
+use crate::protocol::{AnnouncementSeverity, PlayerId, ServerMessage};
+
+/// The announcement new connections still receive.
+#[derive(Debug, Clone)]
+pub struct ActiveAnnouncement {
+    pub message: Arc<ServerMessage>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl EnhancedGameServer {
+    /// Send `message` to every connected player and keep it for players who
+    /// connect before `expires_at`. Returns how many players it was queued for.
+    pub async fn broadcast_server_announcement(
+        &self,
+        message: String,
+        severity: AnnouncementSeverity,
+        expires_at: DateTime<Utc>,
+    ) -> usize {
+        let announcement = Arc::new(ServerMessage::ServerAnnouncement {
+            message,
+            severity,
+            expires_at_ms: u64::try_from(expires_at.timestamp_millis()).unwrap_or(0),
+        });
+        *self
+            .announcement
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = Some(ActiveAnnouncement {
+            message: Arc::clone(&announcement),
+            expires_at,
+        });
+
+        let recipients = match self
+            .message_coordinator
+            .broadcast_to_local_clients(announcement)
+            .await
+        {
+            Ok(recipients) => recipients,
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to broadcast server announcement");
+                0
+            }
+        };
+        self.metrics
+            .record_server_announcements_sent(recipients as u64);
+        tracing::info!(recipients, ?severity, %expires_at, "Server announcement sent");
+        recipients
+    }
+
+    /// The announcement a player connecting now should receive, if one has
+    /// not expired yet.
+    pub fn active_announcement(&self) -> Option<Arc<ServerMessage>> {
+        let mut active = self
+            .announcement
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        if active
+            .as_ref()
+            .is_some_and(|announcement| announcement.expires_at <= Utc::now())
+        {
+            *active = None;
+        }
+        active
+            .as_ref()
+            .map(|announcement| Arc::clone(&announcement.message))
+    }
+
+    /// Queue the active announcement for a newly connected player.
+    pub async fn send_active_announcement(&self, player_id: &PlayerId) {
+        let Some(announcement) = self.active_announcement() else {
+            return;
+        };
+        if self
+            .message_coordinator
+            .send_to_player(player_id, announcement)
+            .await
+            .is_ok()
+        {
+            self.metrics.record_server_announcements_sent(1);
+        }
+    }
+}
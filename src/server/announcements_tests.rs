@@ -0,0 +1,103 @@
+use super::*;
+use crate::config::{
+    AuthMaintenanceConfig, CoordinationConfig, MetricsConfig, ProtocolConfig, RelayTypeConfig,
+    TransportSecurityConfig,
+};
+use crate::database::DatabaseConfig;
+use crate::protocol::{AnnouncementSeverity, ServerMessage};
+use std::net::SocketAddr;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+async fn create_test_server() -> Arc<EnhancedGameServer> {
+    EnhancedGameServer::new(
+        ServerConfig::default(),
+        ProtocolConfig::default(),
+        RelayTypeConfig::default(),
+        DatabaseConfig::InMemory,
+        MetricsConfig::default(),
+        AuthMaintenanceConfig::default(),
+        CoordinationConfig::default(),
+        TransportSecurityConfig::default(),
+        Vec::new(),
+    )
+    .await
+    .expect("failed to construct test server")
+}
+
+async fn register(server: &EnhancedGameServer, index: u8) -> (PlayerId, mpsc::Receiver<Outbound>) {
+    let (sender, receiver) = mpsc::channel(8);
+    let addr: SocketAddr = format!("10.3.0.{}:4000", index + 1).parse().unwrap();
+    let player_id = server
+        .connection_manager
+        .register_client(sender, addr, server.instance_id)
+        .await
+        .expect("client registration succeeds");
+    (player_id, receiver)
+}
+
+fn announced_text(receiver: &mut mpsc::Receiver<Outbound>) -> Option<String> {
+    match &*receiver.try_recv().ok()? {
+        ServerMessage::ServerAnnouncement { message, .. } => Some(message.clone()),
+        other => panic!("expected ServerAnnouncement, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn announcements_reach_every_player_and_late_joiners_until_they_expire() {
+    let server = create_test_server().await;
+    let (_, mut lobby_rx) = register(&server, 0).await;
+    let (_, mut other_rx) = register(&server, 1).await;
+
+    let expires_at = chrono::Utc::now() + chrono::Duration::minutes(10);
+    let recipients = server
+        .broadcast_server_announcement(
+            "Restarting soon".to_string(),
+            AnnouncementSeverity::Critical,
+            expires_at,
+        )
+        .await;
+    assert_eq!(recipients, 2);
+    assert_eq!(
+        announced_text(&mut lobby_rx).as_deref(),
+        Some("Restarting soon")
+    );
+    assert_eq!(
+        announced_text(&mut other_rx).as_deref(),
+        Some("Restarting soon")
+    );
+
+    // A later connection is sent the active announcement on connect
+    let (late, mut late_rx) = register(&server, 2).await;
+    server.send_active_announcement(&late).await;
+    let announcement = late_rx.try_recv().expect("late joiner is told");
+    assert!(matches!(
+        &*announcement,
+        ServerMessage::ServerAnnouncement {
+            severity: AnnouncementSeverity::Critical,
+            expires_at_ms,
+            ..
+        } if *expires_at_ms == expires_at.timestamp_millis() as u64
+    ));
+    assert_eq!(
+        server
+            .metrics
+            .server_announcements_sent
+            .load(Ordering::Relaxed),
+        3
+    );
+
+    // Once expired it is no longer handed out
+    server
+        .broadcast_server_announcement(
+            "Already over".to_string(),
+            AnnouncementSeverity::Info,
+            chrono::Utc::now(),
+        )
+        .await;
+    assert!(server.active_announcement().is_none());
+    let (expired, mut expired_rx) = register(&server, 3).await;
+    server.send_active_announcement(&expired).await;
+    assert_eq!(announced_text(&mut expired_rx), None);
+}
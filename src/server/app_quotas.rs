@@ -0,0 +1,235 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use dashmap::DashMap;
+use serde::Serialize;
+use uuid::Uuid;
+
+use super::{AppQuotaExceededError, EnhancedGameServer, MaxRoomsPerAppExceededError};
+use crate::auth::middleware::deterministic_uuid;
+use crate::auth::AppInfo;
+use crate::config::AppQuotaConfig;
+use crate::distributed::LockHandle;
+use crate::protocol::validation::{validate_application_id, ValidationError};
+use crate::protocol::{PlayerId, QuotaKind};
+
+/// Name of the application that owns rooms created by unauthenticated clients.
+pub const DEFAULT_APP_NAME: &str = "default";
+
+/// Window for `max_rooms_per_hour`.
+const ROOM_CREATION_WINDOW: Duration = Duration::from_secs(60 * 60);
+
+/// The application a client's rooms and players are counted against.
+#[derive(Debug, Clone)]
+pub(super) struct QuotaApp {
+    pub id: Uuid,
+    pub name: String,
+    pub quota: AppQuotaConfig,
+}
+
+/// One application's usage next to its quotas, for the metrics endpoints.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppQuotaUsage {
+    /// The configured `app_name`, or `default`
+    pub app_name: String,
+    pub rooms: usize,
+    pub max_rooms: Option<usize>,
+    pub players: usize,
+    pub max_players: Option<usize>,
+    pub rooms_created_last_hour: usize,
+    pub max_rooms_per_hour: Option<u32>,
+}
+
+/// Room creation times per application over the last hour.
+#[derive(Default)]
+pub(super) struct AppRoomCreations {
+    windows: DashMap<Uuid, VecDeque<Instant>>,
+}
+
+impl AppRoomCreations {
+    pub(super) fn record(&self, app_id: Uuid) {
+        let now = Instant::now();
+        let mut window = self.windows.entry(app_id).or_default();
+        evict_expired(&mut window, now);
+        window.push_back(now);
+    }
+
+    pub(super) fn count(&self, app_id: &Uuid) -> usize {
+        self.windows.get_mut(app_id).map_or(0, |mut window| {
+            evict_expired(&mut window, Instant::now());
+            window.len()
+        })
+    }
+}
+
+fn evict_expired(window: &mut VecDeque<Instant>, now: Instant) {
+    while window
+        .front()
+        .is_some_and(|created| now.duration_since(*created) >= ROOM_CREATION_WINDOW)
+    {
+        window.pop_front();
+    }
+}
+
+/// UUID of the default application. Derived like configured app ids, from a
+/// key no configured `app_id` is expected to use.
+pub fn default_app_id() -> Uuid {
+    deterministic_uuid("signal-fish:default-application")
+}
+
+impl EnhancedGameServer {
+    /// The application `player_id`'s rooms are counted against: its
+    /// authenticated app, or the default application when auth is off or the
+    /// client has not authenticated.
+    pub(super) fn quota_app(&self, player_id: &PlayerId) -> QuotaApp {
+        let app_info = self
+            .config()
+            .auth_enabled
+            .then(|| self.client_app_info(player_id))
+            .flatten();
+        self.quota_app_for(app_info.as_ref())
+    }
+
+    /// The application counted for `app`, or the default application.
+    pub(super) fn quota_app_for(&self, app: Option<&AppInfo>) -> QuotaApp {
+        match app {
+            Some(info) => QuotaApp {
+                id: info.id,
+                quota: info.quota(),
+                name: info.name.clone(),
+            },
+            None => self.default_quota_app(),
+        }
+    }
+
+    /// The configured application a room created by `player_id` belongs to.
+    /// `None` when auth is off, for an open room counted against the default
+    /// application; an authenticated app that is not in
+    /// `security.authorized_apps` is rejected.
+    pub(super) fn room_creation_app(
+        &self,
+        player_id: &PlayerId,
+    ) -> Result<Option<AppInfo>, ValidationError> {
+        let app_id = self
+            .config()
+            .auth_enabled
+            .then(|| self.client_app_id(player_id))
+            .flatten();
+        validate_application_id(app_id, &self.authorized_apps)
+    }
+
+    fn default_quota_app(&self) -> QuotaApp {
+        QuotaApp {
+            id: default_app_id(),
+            name: DEFAULT_APP_NAME.to_string(),
+            quota: self.config().default_app_quota,
+        }
+    }
+
+    /// Quotas of the application with `app_id`; unknown applications are unlimited.
+    pub(super) fn app_quota(&self, app_id: &Uuid) -> AppQuotaConfig {
+        if *app_id == default_app_id() {
+            return self.config().default_app_quota;
+        }
+        self.auth_middleware
+            .configured_app(app_id)
+            .map(|info| info.quota())
+            .unwrap_or_default()
+    }
+
+    /// Serialize quota checks for one application across instances; quotas are
+    /// still checked when the lock cannot be taken.
+    pub(super) async fn acquire_app_quota_lock(&self, app_id: &Uuid) -> Option<LockHandle> {
+        match self
+            .distributed_lock
+            .acquire(&format!("app_quota:{app_id}"), Duration::from_secs(10))
+            .await
+        {
+            Ok(lock) => Some(lock),
+            Err(err) => {
+                tracing::error!("Failed to acquire app quota lock: {}", err);
+                None
+            }
+        }
+    }
+
+    /// Check that `app` may create a room with its creator seated.
+    pub(super) async fn check_app_room_creation(&self, app: &QuotaApp) -> Result<()> {
+        if let Some(limit) = app.quota.max_rooms_per_app {
+            let current = self.database.get_app_room_count(&app.id).await?;
+            if current >= limit {
+                return Err(MaxRoomsPerAppExceededError {
+                    application_id: app.id,
+                    current,
+                    limit,
+                }
+                .into());
+            }
+        }
+        if let Some(limit) = app.quota.max_rooms_per_hour {
+            let current = self.app_room_creations.count(&app.id);
+            if current >= limit as usize {
+                return Err(AppQuotaExceededError {
+                    application_id: app.id,
+                    quota: QuotaKind::AppRoomsPerHour,
+                    current,
+                    limit: limit as usize,
+                }
+                .into());
+            }
+        }
+        self.check_app_player_quota(&app.id, &app.quota).await
+    }
+
+    /// Check that the application with `app_id` has room for one more player.
+    pub(super) async fn check_app_player_quota(
+        &self,
+        app_id: &Uuid,
+        quota: &AppQuotaConfig,
+    ) -> Result<()> {
+        if let Some(limit) = quota.max_players_per_app {
+            let current = self.database.count_players_by_application(app_id).await?;
+            if current >= limit {
+                return Err(AppQuotaExceededError {
+                    application_id: *app_id,
+                    quota: QuotaKind::AppPlayers,
+                    current,
+                    limit,
+                }
+                .into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Usage against quota for every configured application and the default one.
+    pub async fn app_quota_usage(&self) -> Result<Vec<AppQuotaUsage>> {
+        let mut apps: Vec<QuotaApp> = self
+            .auth_middleware
+            .configured_apps()
+            .map(|info| QuotaApp {
+                id: info.id,
+                name: info.name.clone(),
+                quota: info.quota(),
+            })
+            .collect();
+        apps.sort_by(|a, b| a.name.cmp(&b.name));
+        apps.push(self.default_quota_app());
+
+        let mut usage = Vec::with_capacity(apps.len());
+        for app in apps {
+            usage.push(AppQuotaUsage {
+                rooms: self.database.get_app_room_count(&app.id).await?,
+                max_rooms: app.quota.max_rooms_per_app,
+                players: self.database.count_players_by_application(&app.id).await?,
+                max_players: app.quota.max_players_per_app,
+                rooms_created_last_hour: self.app_room_creations.count(&app.id),
+                max_rooms_per_hour: app.quota.max_rooms_per_hour,
+                app_name: app.name,
+            });
+        }
+        Ok(usage)
+    }
+}
@@ -0,0 +1,304 @@
+use super::*;
+use crate::auth::AppInfo;
+use crate::config::{
+    AppAuthEntry, AppQuotaConfig, AuthMaintenanceConfig, CoordinationConfig, MetricsConfig,
+    ProtocolConfig, RelayTypeConfig, TransportSecurityConfig,
+};
+use crate::database::DatabaseConfig;
+use crate::protocol::{ErrorCode, ErrorDetails, QuotaKind, ServerMessage};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::time::{timeout, Duration};
+
+fn app_entry(app_id: &str, quota: AppQuotaConfig) -> AppAuthEntry {
+    AppAuthEntry {
+        app_id: app_id.to_string(),
+        app_secret: "secret".to_string(),
+        app_name: app_id.to_string(),
+        max_rooms: None,
+        max_rooms_per_app: quota.max_rooms_per_app,
+        max_players_per_app: quota.max_players_per_app,
+        max_rooms_per_hour: quota.max_rooms_per_hour,
+        max_players_per_room: None,
+        rate_limit_per_minute: None,
+        api_keys: Vec::new(),
+    }
+}
+
+async fn create_test_server(
+    config: ServerConfig,
+    apps: Vec<AppAuthEntry>,
+) -> Arc<EnhancedGameServer> {
+    EnhancedGameServer::new(
+        config,
+        ProtocolConfig::default(),
+        RelayTypeConfig::default(),
+        DatabaseConfig::InMemory,
+        MetricsConfig::default(),
+        AuthMaintenanceConfig::default(),
+        CoordinationConfig::default(),
+        TransportSecurityConfig::default(),
+        apps,
+    )
+    .await
+    .expect("failed to construct test server")
+}
+
+async fn create_auth_server(apps: Vec<AppAuthEntry>) -> Arc<EnhancedGameServer> {
+    let config = ServerConfig {
+        auth_enabled: true,
+        ..ServerConfig::default()
+    };
+    create_test_server(config, apps).await
+}
+
+async fn register(
+    server: &EnhancedGameServer,
+    app_info: Option<&AppInfo>,
+    port: u16,
+) -> (PlayerId, mpsc::Receiver<Outbound>) {
+    let (sender, receiver) = mpsc::channel(16);
+    let addr: SocketAddr = format!("127.0.0.1:{port}").parse().unwrap();
+    let player_id = server
+        .connection_manager
+        .register_client(sender, addr, server.instance_id)
+        .await
+        .expect("client registration succeeds");
+    if let Some(app_info) = app_info {
+        server.set_client_app_info(&player_id, app_info.clone());
+    }
+    (player_id, receiver)
+}
+
+async fn join(
+    server: &EnhancedGameServer,
+    player_id: &PlayerId,
+    room_code: &str,
+    receiver: &mut mpsc::Receiver<Outbound>,
+) -> Outbound {
+    server
+        .handle_join_room(
+            player_id,
+            "quota-game".to_string(),
+            Some(room_code.to_string()),
+            format!("player-{}", &player_id.to_string()[..8]),
+            Some(8),
+            Some(false),
+            None,
+            None,
+            None,
+        )
+        .await;
+    timeout(Duration::from_secs(1), receiver.recv())
+        .await
+        .expect("join response arrives in time")
+        .expect("channel still open")
+}
+
+fn assert_app_quota_exceeded(message: &ServerMessage, quota: QuotaKind, limit: usize) {
+    match message {
+        ServerMessage::RoomJoinFailed {
+            error_code,
+            details,
+            ..
+        } => {
+            assert_eq!(*error_code, Some(ErrorCode::AppQuotaExceeded));
+            assert_eq!(
+                *details,
+                Some(ErrorDetails::QuotaExceeded {
+                    current: limit,
+                    limit,
+                    quota: Some(quota),
+                })
+            );
+        }
+        other => panic!("expected RoomJoinFailed, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn joins_are_rejected_once_the_app_player_quota_is_reached() {
+    let quota = AppQuotaConfig {
+        max_players_per_app: Some(2),
+        ..AppQuotaConfig::default()
+    };
+    let server = create_auth_server(vec![app_entry("players", quota)]).await;
+    let app = server
+        .auth_middleware
+        .validate_app_id("players")
+        .await
+        .unwrap();
+
+    let (host, mut host_rx) = register(&server, Some(&app), 48600).await;
+    let joined = join(&server, &host, "PLAY01", &mut host_rx).await;
+    assert!(matches!(*joined, ServerMessage::RoomJoined(_)));
+    let (guest, mut guest_rx) = register(&server, Some(&app), 48601).await;
+    let joined = join(&server, &guest, "PLAY01", &mut guest_rx).await;
+    assert!(matches!(*joined, ServerMessage::RoomJoined(_)));
+
+    // Both joining an existing room and creating a new one need a free seat
+    let (late, mut late_rx) = register(&server, Some(&app), 48602).await;
+    let rejected = join(&server, &late, "PLAY01", &mut late_rx).await;
+    assert_app_quota_exceeded(&rejected, QuotaKind::AppPlayers, 2);
+    let rejected = join(&server, &late, "PLAY02", &mut late_rx).await;
+    assert_app_quota_exceeded(&rejected, QuotaKind::AppPlayers, 2);
+    assert_eq!(
+        server
+            .database
+            .count_players_by_application(&app.id)
+            .await
+            .unwrap(),
+        2
+    );
+}
+
+#[tokio::test]
+async fn rooms_per_hour_counts_rooms_that_already_closed() {
+    let quota = AppQuotaConfig {
+        max_rooms_per_hour: Some(1),
+        ..AppQuotaConfig::default()
+    };
+    let server = create_auth_server(vec![app_entry("hourly", quota)]).await;
+    let app = server
+        .auth_middleware
+        .validate_app_id("hourly")
+        .await
+        .unwrap();
+
+    let (player, mut rx) = register(&server, Some(&app), 48610).await;
+    let joined = join(&server, &player, "HOUR01", &mut rx).await;
+    let ServerMessage::RoomJoined(payload) = &*joined else {
+        panic!("expected RoomJoined, got {joined:?}");
+    };
+    server.database.delete_room(&payload.room_id).await.unwrap();
+    assert_eq!(
+        server.database.get_app_room_count(&app.id).await.unwrap(),
+        0
+    );
+
+    let (player, mut rx) = register(&server, Some(&app), 48611).await;
+    let rejected = join(&server, &player, "HOUR02", &mut rx).await;
+    assert_app_quota_exceeded(&rejected, QuotaKind::AppRoomsPerHour, 1);
+}
+
+#[tokio::test]
+async fn unauthenticated_rooms_count_against_the_default_app() {
+    let config = ServerConfig {
+        default_app_quota: AppQuotaConfig {
+            max_rooms_per_app: Some(1),
+            ..AppQuotaConfig::default()
+        },
+        ..ServerConfig::default()
+    };
+    let server = create_test_server(config, Vec::new()).await;
+
+    let (first, mut first_rx) = register(&server, None, 48620).await;
+    let joined = join(&server, &first, "DFLT01", &mut first_rx).await;
+    let ServerMessage::RoomJoined(payload) = &*joined else {
+        panic!("expected RoomJoined, got {joined:?}");
+    };
+    assert_eq!(
+        server.room_application_id(&payload.room_id),
+        Some(default_app_id())
+    );
+
+    let (second, mut second_rx) = register(&server, None, 48621).await;
+    let rejected = join(&server, &second, "DFLT02", &mut second_rx).await;
+    match &*rejected {
+        ServerMessage::RoomJoinFailed {
+            error_code,
+            details,
+            ..
+        } => {
+            assert_eq!(*error_code, Some(ErrorCode::AppRoomQuotaExceeded));
+            assert_eq!(
+                *details,
+                Some(ErrorDetails::QuotaExceeded {
+                    current: 1,
+                    limit: 1,
+                    quota: Some(QuotaKind::AppRooms),
+                })
+            );
+        }
+        other => panic!("expected RoomJoinFailed, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn quota_usage_lists_configured_apps_and_the_default_app() {
+    let quota = AppQuotaConfig {
+        max_rooms_per_app: Some(5),
+        max_players_per_app: Some(20),
+        max_rooms_per_hour: Some(10),
+    };
+    let server = create_auth_server(vec![
+        app_entry("zeta", AppQuotaConfig::default()),
+        app_entry("alpha", quota),
+    ])
+    .await;
+    let alpha = server
+        .auth_middleware
+        .validate_app_id("alpha")
+        .await
+        .unwrap();
+    let (host, mut host_rx) = register(&server, Some(&alpha), 48630).await;
+    join(&server, &host, "USAGE1", &mut host_rx).await;
+    let (guest, mut guest_rx) = register(&server, None, 48631).await;
+    join(&server, &guest, "USAGE1", &mut guest_rx).await;
+
+    let usage = server.app_quota_usage().await.expect("usage");
+    let names: Vec<&str> = usage.iter().map(|app| app.app_name.as_str()).collect();
+    assert_eq!(names, ["alpha", "zeta", DEFAULT_APP_NAME]);
+    assert_eq!(
+        usage[0],
+        AppQuotaUsage {
+            app_name: "alpha".to_string(),
+            rooms: 1,
+            max_rooms: Some(5),
+            players: 2,
+            max_players: Some(20),
+            rooms_created_last_hour: 1,
+            max_rooms_per_hour: Some(10),
+        }
+    );
+    assert_eq!(usage[1].rooms, 0);
+    assert_eq!(usage[1].max_rooms, None);
+    assert_eq!(usage[2].rooms, 0);
+}
+
+#[tokio::test]
+async fn rooms_are_only_created_for_configured_apps() {
+    let mut limited = app_entry("limited", AppQuotaConfig::default());
+    limited.max_players_per_room = Some(4);
+    let server = create_auth_server(vec![limited]).await;
+
+    // An app that is no longer configured cannot own new rooms
+    let removed = AppInfo::from_entry(&app_entry("removed", AppQuotaConfig::default()));
+    let (player, mut rx) = register(&server, Some(&removed), 48640).await;
+    let rejected = join(&server, &player, "APPS01", &mut rx).await;
+    assert!(
+        matches!(
+            &*rejected,
+            ServerMessage::RoomJoinFailed {
+                error_code: Some(ErrorCode::InvalidAppId),
+                ..
+            }
+        ),
+        "expected InvalidAppId, got {rejected:?}"
+    );
+
+    // A configured app's room is capped at its max_players_per_room
+    let app = server
+        .auth_middleware
+        .validate_app_id("limited")
+        .await
+        .unwrap();
+    let (player, mut rx) = register(&server, Some(&app), 48641).await;
+    let joined = join(&server, &player, "APPS02", &mut rx).await;
+    let ServerMessage::RoomJoined(payload) = &*joined else {
+        panic!("expected RoomJoined, got {joined:?}");
+    };
+    assert_eq!(payload.max_players, 4);
+    assert_eq!(server.room_application_id(&payload.room_id), Some(app.id));
+}
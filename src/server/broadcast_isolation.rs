@@ -0,0 +1,288 @@
+//! Per-game worker budgets for game data broadcasts.
+//!
+//! Every game data fan-out holds a permit while it runs. Games listed in
+//! `isolate_games` (or promoted automatically when it contains `"auto"`) draw
+//! permits from their own semaphore; everything else shares one. A single
+//! busy game can then exhaust only its own budget instead of delaying every
+//! other game on the instance.
+//!
+//! Permits are handed out in FIFO order and a room always belongs to one
+//! game, so broadcasts from a room are dispatched in the order they queued.
+
+use crate::metrics::ServerMetrics;
+use dashmap::DashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+
+use super::ServerConfig;
+
+/// Entry in `isolate_games` that turns on automatic isolation.
+pub const AUTO_ISOLATE: &str = "auto";
+
+/// Window over which broadcast rates are measured for automatic isolation.
+const TRAFFIC_WINDOW: Duration = Duration::from_secs(1);
+
+struct TrafficWindow {
+    started: Instant,
+    broadcasts: u32,
+}
+
+/// Dispatches game data broadcasts onto shared or per-game worker budgets.
+pub struct BroadcastScheduler {
+    shared: Arc<Semaphore>,
+    isolated: DashMap<String, Arc<Semaphore>>,
+    isolated_workers: usize,
+    /// Broadcasts per second that isolate a game (None unless `"auto"` is listed)
+    auto_threshold: Option<u32>,
+    traffic: DashMap<String, TrafficWindow>,
+    metrics: Arc<ServerMetrics>,
+}
+
+impl BroadcastScheduler {
+    pub fn new(config: &ServerConfig, metrics: Arc<ServerMetrics>) -> Self {
+        let scheduler = Self {
+            shared: Arc::new(Semaphore::new(config.broadcast_workers.max(1))),
+            isolated: DashMap::new(),
+            isolated_workers: config.isolated_broadcast_workers.max(1),
+            auto_threshold: config
+                .isolate_games
+                .iter()
+                .any(|game| game == AUTO_ISOLATE)
+                .then_some(config.isolation_auto_threshold),
+            traffic: DashMap::new(),
+            metrics,
+        };
+        for game_name in &config.isolate_games {
+            if game_name != AUTO_ISOLATE {
+                scheduler.isolate(game_name);
+            }
+        }
+        scheduler
+    }
+
+    /// Whether broadcasts for `game_name` currently run on dedicated workers.
+    pub fn is_isolated(&self, game_name: &str) -> bool {
+        self.isolated.contains_key(game_name)
+    }
+
+    /// Run `broadcast` once a worker for `game_name` is free.
+    pub async fn dispatch<F: Future>(&self, game_name: &str, broadcast: F) -> F::Output {
+        let semaphore = self.semaphore_for(game_name);
+        let queued_at = Instant::now();
+        let _permit = {
+            let _queued = QueuedBroadcast::new(&self.metrics, game_name);
+            // The semaphores are never closed, so acquiring cannot fail
+            semaphore.acquire().await.ok()
+        };
+        self.metrics
+            .record_broadcast_dispatched(game_name, queued_at.elapsed());
+        broadcast.await
+    }
+
+    fn semaphore_for(&self, game_name: &str) -> Arc<Semaphore> {
+        if let Some(semaphore) = self.isolated.get(game_name) {
+            return Arc::clone(&semaphore);
+        }
+        if self.exceeds_auto_threshold(game_name) {
+            tracing::info!(
+                game_name,
+                threshold = self.auto_threshold,
+                "Isolating game data broadcasts for a busy game"
+            );
+            return self.isolate(game_name);
+        }
+        Arc::clone(&self.shared)
+    }
+
+    fn isolate(&self, game_name: &str) -> Arc<Semaphore> {
+        self.traffic.remove(game_name);
+        self.metrics.mark_broadcast_isolated(game_name);
+        let semaphore = self
+            .isolated
+            .entry(game_name.to_string())
+            .or_insert_with(|| Arc::new(Semaphore::new(self.isolated_workers)));
+        Arc::clone(&semaphore)
+    }
+
+    fn exceeds_auto_threshold(&self, game_name: &str) -> bool {
+        let Some(threshold) = self.auto_threshold else {
+            return false;
+        };
+        let now = Instant::now();
+        let mut window = self
+            .traffic
+            .entry(game_name.to_string())
+            .or_insert_with(|| TrafficWindow {
+                started: now,
+                broadcasts: 0,
+            });
+        if now.duration_since(window.started) >= TRAFFIC_WINDOW {
+            window.started = now;
+            window.broadcasts = 0;
+        }
+        window.broadcasts = window.broadcasts.saturating_add(1);
+        window.broadcasts > threshold
+    }
+}
+
+/// Counts a broadcast in its game's queue depth until it gets a worker or is dropped.
+struct QueuedBroadcast<'a> {
+    metrics: &'a ServerMetrics,
+    game_name: &'a str,
+}
+
+impl<'a> QueuedBroadcast<'a> {
+    fn new(metrics: &'a ServerMetrics, game_name: &'a str) -> Self {
+        metrics.increment_broadcast_queued(game_name);
+        Self { metrics, game_name }
+    }
+}
+
+impl Drop for QueuedBroadcast<'_> {
+    fn drop(&mut self) {
+        self.metrics.decrement_broadcast_queued(self.game_name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::time::sleep;
+
+    const SLOW_FANOUT: Duration = Duration::from_millis(20);
+
+    fn scheduler(isolate_games: &[&str], workers: usize) -> Arc<BroadcastScheduler> {
+        let config = ServerConfig {
+            isolate_games: isolate_games.iter().map(ToString::to_string).collect(),
+            isolation_auto_threshold: 10,
+            broadcast_workers: workers,
+            isolated_broadcast_workers: workers,
+            ..ServerConfig::default()
+        };
+        Arc::new(BroadcastScheduler::new(
+            &config,
+            Arc::new(ServerMetrics::new()),
+        ))
+    }
+
+    /// Queue a burst of slow broadcasts for `heavy`, then time one for `light`.
+    async fn light_game_latency(scheduler: Arc<BroadcastScheduler>) -> Duration {
+        let heavy: Vec<_> = (0..40)
+            .map(|_| {
+                let scheduler = Arc::clone(&scheduler);
+                tokio::spawn(async move {
+                    // The fan-out only starts its clock once it has a worker
+                    let fanout = async { sleep(SLOW_FANOUT).await };
+                    scheduler.dispatch("heavy", fanout).await;
+                })
+            })
+            .collect();
+        sleep(Duration::from_millis(5)).await;
+
+        let started = Instant::now();
+        scheduler.dispatch("light", async {}).await;
+        let latency = started.elapsed();
+
+        for task in heavy {
+            task.await.unwrap();
+        }
+        latency
+    }
+
+    #[tokio::test]
+    async fn isolated_heavy_game_does_not_delay_other_games() {
+        let bound = Duration::from_millis(100);
+
+        let shared = light_game_latency(scheduler(&[], 2)).await;
+        assert!(
+            shared > bound,
+            "without isolation the light game should queue behind the heavy one ({shared:?})"
+        );
+
+        let isolated_scheduler = scheduler(&["heavy"], 2);
+        let isolated = light_game_latency(Arc::clone(&isolated_scheduler)).await;
+        assert!(
+            isolated < bound,
+            "light game broadcast took {isolated:?} with the heavy game isolated"
+        );
+
+        let queues = isolated_scheduler.metrics.snapshot().await.broadcast_queues;
+        let heavy = queues.iter().find(|q| q.game_name == "heavy").unwrap();
+        assert!(heavy.isolated);
+        assert_eq!(heavy.dispatched, 40);
+        assert_eq!(heavy.queue_depth, 0);
+        assert!(heavy.max_wait_ms.unwrap() >= SLOW_FANOUT.as_millis() as f64);
+        let light = queues.iter().find(|q| q.game_name == "light").unwrap();
+        assert!(!light.isolated);
+    }
+
+    #[tokio::test]
+    async fn auto_isolates_games_above_the_threshold() {
+        let scheduler = scheduler(&[AUTO_ISOLATE], 2);
+        for _ in 0..10 {
+            scheduler.dispatch("busy", async {}).await;
+        }
+        assert!(!scheduler.is_isolated("busy"));
+
+        scheduler.dispatch("busy", async {}).await;
+        assert!(scheduler.is_isolated("busy"));
+        assert!(!scheduler.is_isolated(AUTO_ISOLATE));
+    }
+
+    #[tokio::test]
+    async fn queued_broadcasts_run_in_arrival_order() {
+        let scheduler = scheduler(&["ordered"], 1);
+        let (release, released) = tokio::sync::oneshot::channel::<()>();
+        let blocker = {
+            let scheduler = Arc::clone(&scheduler);
+            tokio::spawn(async move {
+                scheduler
+                    .dispatch("ordered", async move {
+                        let _ = released.await;
+                    })
+                    .await;
+            })
+        };
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut tasks = vec![blocker];
+        for seq in 0..20u64 {
+            let scheduler_for_task = Arc::clone(&scheduler);
+            let tx = tx.clone();
+            tasks.push(tokio::spawn(async move {
+                scheduler_for_task
+                    .dispatch("ordered", async move {
+                        let _ = tx.send(seq);
+                    })
+                    .await;
+            }));
+            // Wait for this broadcast to queue before sending the next one
+            while queue_depth(&scheduler, "ordered") <= seq {
+                tokio::task::yield_now().await;
+            }
+        }
+        drop(tx);
+        let _ = release.send(());
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        let mut received = Vec::new();
+        while let Some(seq) = rx.recv().await {
+            received.push(seq);
+        }
+        assert_eq!(received, (0..20).collect::<Vec<_>>());
+    }
+
+    fn queue_depth(scheduler: &BroadcastScheduler, game_name: &str) -> u64 {
+        scheduler
+            .metrics
+            .broadcast_queues
+            .get(game_name)
+            .map_or(0, |counters| {
+                counters.queued.load(std::sync::atomic::Ordering::Relaxed)
+            })
+    }
+}
@@ -0,0 +1,156 @@
+use super::*;
+use crate::closed_rooms::ClosedRoomSummary;
+use crate::config::{
+    AuthMaintenanceConfig, CoordinationConfig, MetricsConfig, ProtocolConfig, RelayTypeConfig,
+    TransportSecurityConfig,
+};
+use crate::database::DatabaseConfig;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::time::{sleep, timeout, Duration};
+
+const GAME: &str = "history-game";
+
+async fn create_test_server(config: ServerConfig) -> Arc<EnhancedGameServer> {
+    EnhancedGameServer::new(
+        config,
+        ProtocolConfig::default(),
+        RelayTypeConfig::default(),
+        DatabaseConfig::InMemory,
+        MetricsConfig::default(),
+        AuthMaintenanceConfig::default(),
+        CoordinationConfig::default(),
+        TransportSecurityConfig::default(),
+        Vec::new(),
+    )
+    .await
+    .expect("failed to construct test server")
+}
+
+async fn join(
+    server: &EnhancedGameServer,
+    port: u16,
+    room_code: &str,
+    name: &str,
+) -> (PlayerId, mpsc::Receiver<Outbound>) {
+    let (sender, receiver) = mpsc::channel(32);
+    let addr: SocketAddr = format!("127.0.0.1:{port}").parse().unwrap();
+    let player_id = server
+        .register_client(sender, addr)
+        .await
+        .expect("client registration succeeds");
+    server
+        .handle_join_room(
+            &player_id,
+            GAME.to_string(),
+            Some(room_code.to_string()),
+            name.to_string(),
+            Some(4),
+            Some(false),
+            None,
+            None,
+            None,
+        )
+        .await;
+    (player_id, receiver)
+}
+
+fn closed(server: &EnhancedGameServer, room_code: &str) -> Option<ClosedRoomSummary> {
+    server
+        .recent_closed_rooms(None, None, usize::MAX)
+        .into_iter()
+        .find(|summary| summary.room_code == room_code)
+}
+
+#[tokio::test]
+async fn every_close_path_is_recorded_with_its_reason() {
+    let server = create_test_server(ServerConfig {
+        room_cleanup_interval: Duration::from_millis(20),
+        empty_room_timeout: Duration::ZERO,
+        inactive_room_timeout: Duration::from_millis(200),
+        ..ServerConfig::default()
+    })
+    .await;
+    let cleanup_server = Arc::clone(&server);
+    let cleanup = tokio::spawn(async move { cleanup_server.cleanup_task().await });
+
+    let (_host, _host_rx) = join(&server, 49100, "ADMIN1", "Host").await;
+    let (_guest, _guest_rx) = join(&server, 49101, "ADMIN1", "Guest").await;
+    server
+        .admin_close_room(GAME, "ADMIN1")
+        .await
+        .unwrap()
+        .expect("room exists");
+
+    let (leaver, _leaver_rx) = join(&server, 49102, "EMPTY1", "Leaver").await;
+    server.leave_room(&leaver).await;
+
+    let (_idler, _idler_rx) = join(&server, 49103, "IDLE01", "Idler").await;
+
+    let recorded = timeout(Duration::from_secs(3), async {
+        while server.closed_rooms.len() < 3 {
+            sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await;
+    cleanup.abort();
+    assert!(recorded.is_ok(), "all three closures are recorded");
+
+    let admin = closed(&server, "ADMIN1").expect("admin close recorded");
+    assert_eq!(admin.reason, ADMIN_CLOSE_REASON);
+    assert_eq!(admin.game_name, GAME);
+    assert_eq!(admin.peak_player_count, 2);
+    assert!(!admin.finalized);
+    assert!(admin.created_at <= admin.closed_at);
+
+    let empty = closed(&server, "EMPTY1").expect("empty cleanup recorded");
+    assert_eq!(empty.reason, EMPTY_CLEANUP_REASON);
+    assert_eq!(empty.peak_player_count, 1);
+
+    let idle = closed(&server, "IDLE01").expect("inactive cleanup recorded");
+    assert_eq!(idle.reason, INACTIVE_CLEANUP_REASON);
+    assert_eq!(idle.peak_player_count, 1);
+
+    let json = serde_json::to_string(&server.recent_closed_rooms(None, None, 10)).unwrap();
+    for name in ["Host", "Guest", "Leaver", "Idler", "127.0.0.1"] {
+        assert!(!json.contains(name), "summaries do not retain {name}");
+    }
+}
+
+#[tokio::test]
+async fn history_keeps_only_the_most_recent_closures() {
+    let server = create_test_server(ServerConfig {
+        closed_room_history_size: 2,
+        ..ServerConfig::default()
+    })
+    .await;
+
+    let mut receivers = Vec::new();
+    for (port, code) in [(49110, "CAP001"), (49111, "CAP002"), (49112, "CAP003")] {
+        let (_player, rx) = join(&server, port, code, "Player").await;
+        receivers.push(rx);
+        server
+            .admin_close_room(GAME, code)
+            .await
+            .unwrap()
+            .expect("room exists");
+    }
+
+    let codes = |rooms: Vec<ClosedRoomSummary>| -> Vec<String> {
+        rooms.into_iter().map(|summary| summary.room_code).collect()
+    };
+    assert_eq!(server.closed_rooms.len(), 2);
+    assert_eq!(
+        codes(server.recent_closed_rooms(None, None, 10)),
+        ["CAP003", "CAP002"]
+    );
+    assert_eq!(codes(server.recent_closed_rooms(None, None, 1)), ["CAP003"]);
+    assert!(server
+        .recent_closed_rooms(Some("other-game"), None, 10)
+        .is_empty());
+    let future = chrono::Utc::now() + chrono::Duration::seconds(1);
+    assert!(server
+        .recent_closed_rooms(None, Some(future), 10)
+        .is_empty());
+}
@@ -0,0 +1,28 @@
+use super::{EnhancedGameServer, ServerConfig};
+use std::sync::{Arc, PoisonError};
+
+impl EnhancedGameServer {
+    /// Apply the reloadable subset of `config` to the running server: rate
+    /// limits, ping and room timeouts, the expiry warning, CORS origins and
+    /// the client IP allow/blocklists.
+    /// Every other field keeps its startup value.
+    ///
+    /// `config` is expected to have passed validation already.
+    pub fn reload_config(&self, config: &ServerConfig) {
+        let mut current = self.config.write().unwrap_or_else(PoisonError::into_inner);
+        let mut updated = ServerConfig::clone(&current);
+        updated.rate_limit_config = config.rate_limit_config.clone();
+        updated.ping_timeout = config.ping_timeout;
+        updated.empty_room_timeout = config.empty_room_timeout;
+        updated.inactive_room_timeout = config.inactive_room_timeout;
+        updated.expiry_warning = config.expiry_warning;
+        updated.cors_origins = config.cors_origins.clone();
+        updated.metrics_cors_origins = config.metrics_cors_origins.clone();
+        updated.ip_filter = config.ip_filter.clone();
+
+        self.rate_limiter
+            .reconfigure(updated.rate_limit_config.clone());
+        *current = Arc::new(updated);
+        tracing::info!("Applied reloaded configuration");
+    }
+}
@@ -0,0 +1,86 @@
+use super::*;
+use crate::config::{
+    AuthMaintenanceConfig, CoordinationConfig, MetricsConfig, ProtocolConfig, RelayTypeConfig,
+    TransportSecurityConfig,
+};
+use crate::database::DatabaseConfig;
+use crate::rate_limit::RateLimitConfig;
+use std::sync::Arc;
+use tokio::time::Duration;
+
+async fn create_test_server(config: ServerConfig) -> Arc<EnhancedGameServer> {
+    EnhancedGameServer::new(
+        config,
+        ProtocolConfig::default(),
+        RelayTypeConfig::default(),
+        DatabaseConfig::InMemory,
+        MetricsConfig::default(),
+        AuthMaintenanceConfig::default(),
+        CoordinationConfig::default(),
+        TransportSecurityConfig::default(),
+        Vec::new(),
+    )
+    .await
+    .expect("failed to construct test server")
+}
+
+#[tokio::test]
+async fn reload_applies_only_the_reloadable_settings() {
+    let server = create_test_server(ServerConfig {
+        region_id: "us-east".to_string(),
+        ..ServerConfig::default()
+    })
+    .await;
+
+    server.reload_config(&ServerConfig {
+        rate_limit_config: RateLimitConfig {
+            max_join_attempts: 2,
+            ..RateLimitConfig::default()
+        },
+        ping_timeout: Duration::from_secs(5),
+        empty_room_timeout: Duration::from_secs(10),
+        inactive_room_timeout: Duration::from_secs(20),
+        expiry_warning: Duration::from_secs(3),
+        cors_origins: "https://example.com".to_string(),
+        metrics_cors_origins: "https://ops.example.com".to_string(),
+        ip_filter: crate::security::IpFilter::new(None, vec!["192.0.2.1".parse().unwrap()]),
+        region_id: "eu-west".to_string(),
+        max_rooms_per_game: 1,
+        ..ServerConfig::default()
+    });
+
+    let config = server.config();
+    assert_eq!(config.ping_timeout, Duration::from_secs(5));
+    assert_eq!(config.empty_room_timeout, Duration::from_secs(10));
+    assert_eq!(config.inactive_room_timeout, Duration::from_secs(20));
+    assert_eq!(config.expiry_warning, Duration::from_secs(3));
+    assert_eq!(config.cors_origins, "https://example.com");
+    assert_eq!(config.metrics_cors_origins, "https://ops.example.com");
+    assert!(config
+        .ip_filter
+        .check("192.0.2.1".parse().unwrap())
+        .is_err());
+    assert_eq!(config.rate_limit_config.max_join_attempts, 2);
+    assert_eq!(config.region_id, "us-east");
+    assert_eq!(
+        config.max_rooms_per_game,
+        ServerConfig::default().max_rooms_per_game
+    );
+
+    let player_id = uuid::Uuid::new_v4();
+    assert!(server
+        .rate_limiter
+        .check_join_attempt(&player_id)
+        .await
+        .is_ok());
+    assert!(server
+        .rate_limiter
+        .check_join_attempt(&player_id)
+        .await
+        .is_ok());
+    assert!(server
+        .rate_limiter
+        .check_join_attempt(&player_id)
+        .await
+        .is_err());
+}
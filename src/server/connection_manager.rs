@@ -1,6 +1,6 @@
 use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use dashmap::DashMap;
 use tokio::sync::mpsc;
@@ -8,9 +8,11 @@ use tracing::{info, warn};
 use uuid::Uuid;
 
 use crate::auth::AppInfo;
+use crate::broadcast::Outbound;
 use crate::coordination::MessageCoordinator;
 use crate::metrics::ServerMetrics;
-use crate::protocol::{GameDataEncoding, PlayerId, RoomId, ServerMessage};
+use crate::protocol::{GameDataEncoding, HeartbeatEcho, PlayerId, RoomId};
+use crate::security::ConnectionKey;
 
 use super::RegisterClientError;
 
@@ -22,23 +24,53 @@ pub(crate) struct ClientConnection {
     /// Used to throttle heartbeat updates - we only record if this is older
     /// than the configured threshold (default 30 seconds).
     pub last_heartbeat_update: Option<Instant>,
-    pub sender: mpsc::Sender<Arc<ServerMessage>>,
+    pub sender: mpsc::Sender<Outbound>,
     pub client_addr: SocketAddr,
     pub game_data_format: GameDataEncoding,
     pub app_info: Option<AppInfo>,
+    /// Client certificate fingerprint forwarded by the TLS terminator, if any
+    pub client_fingerprint: Option<Arc<str>>,
+    /// Game of the room the client is in, used to schedule its broadcasts
+    pub game_name: Option<Arc<str>>,
+    /// Region the client named as its own, if accepted
+    pub region_id: Option<Arc<str>>,
+    pub heartbeat: HeartbeatTiming,
+    /// Round-trip time last shared with the client's room
+    pub shared_rtt_ms: Option<u32>,
+    /// Capabilities of the SDK the client identified, once checked
+    pub sdk_capabilities: Option<Vec<String>>,
+}
+
+/// Round-trip state for timed heartbeats.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct HeartbeatTiming {
+    /// The last ack sent: its `server_timestamp` and when it was sent
+    last_ack: Option<(u64, Instant)>,
+    /// Smoothed round-trip time (RFC 6298 SRTT)
+    smoothed_rtt: Option<Duration>,
+}
+
+/// Result of accepting a heartbeat for acknowledgement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct HeartbeatAck {
+    pub server_timestamp: u64,
+    /// Round trip measured from the client's echo of the previous ack
+    pub rtt_sample: Option<Duration>,
 }
 
 pub(crate) struct ConnectionManager {
     clients: DashMap<PlayerId, ClientConnection>,
-    connections_per_ip: DashMap<IpAddr, usize>,
+    connections_per_ip: DashMap<ConnectionKey, usize>,
     metrics: Arc<ServerMetrics>,
     message_coordinator: Arc<dyn MessageCoordinator>,
     max_connections_per_ip: usize,
+    ipv6_connection_prefix: u8,
 }
 
 impl ConnectionManager {
     pub fn new(
         max_connections_per_ip: usize,
+        ipv6_connection_prefix: u8,
         metrics: Arc<ServerMetrics>,
         message_coordinator: Arc<dyn MessageCoordinator>,
     ) -> Self {
@@ -48,29 +80,57 @@ impl ConnectionManager {
             metrics,
             message_coordinator,
             max_connections_per_ip,
+            ipv6_connection_prefix,
         }
     }
 
+    /// Key `ip`'s connections are limited under.
+    pub fn connection_key(&self, ip: IpAddr) -> ConnectionKey {
+        ConnectionKey::new(ip, self.ipv6_connection_prefix)
+    }
+
     pub async fn register_client(
         &self,
-        sender: mpsc::Sender<Arc<ServerMessage>>,
+        sender: mpsc::Sender<Outbound>,
         client_addr: SocketAddr,
         instance_id: Uuid,
     ) -> Result<PlayerId, RegisterClientError> {
-        let ip = client_addr.ip();
-        if let Err(current) = self.try_reserve_ip_slot(ip) {
+        self.reserve_ip_slot(self.connection_key(client_addr.ip()))?;
+        Ok(self
+            .register_reserved_client(sender, client_addr, instance_id)
+            .await)
+    }
+
+    /// Take one of `key`'s connection slots, failing once it is at the limit.
+    pub fn reserve_ip_slot(&self, key: ConnectionKey) -> Result<(), RegisterClientError> {
+        if let Err(current) = self.try_reserve_ip_slot(key) {
             warn!(
-                %ip,
+                ip = %key,
                 current,
                 max = self.max_connections_per_ip,
                 "IP connection limit exceeded"
             );
+            self.metrics.increment_connections_rejected_ip_limit();
             return Err(RegisterClientError::IpLimitExceeded {
                 current,
                 limit: self.max_connections_per_ip,
             });
         }
+        Ok(())
+    }
+
+    /// Give back a slot from [`Self::reserve_ip_slot`] that no client registered into.
+    pub fn release_reserved_ip_slot(&self, key: ConnectionKey) {
+        self.release_ip_slot(key);
+    }
 
+    /// Register a client whose IP slot was taken with [`Self::reserve_ip_slot`].
+    pub async fn register_reserved_client(
+        &self,
+        sender: mpsc::Sender<Outbound>,
+        client_addr: SocketAddr,
+        instance_id: Uuid,
+    ) -> PlayerId {
         let player_id = Uuid::new_v4();
         let connection = ClientConnection {
             room_id: None,
@@ -80,6 +140,12 @@ impl ConnectionManager {
             client_addr,
             game_data_format: GameDataEncoding::Json,
             app_info: None,
+            client_fingerprint: None,
+            game_name: None,
+            region_id: None,
+            heartbeat: HeartbeatTiming::default(),
+            shared_rtt_ms: None,
+            sdk_capabilities: None,
         };
 
         self.clients.insert(player_id, connection);
@@ -94,13 +160,13 @@ impl ConnectionManager {
         }
 
         info!(%player_id, instance_id = %instance_id, client_addr = %client_addr, "Client registered");
-        Ok(player_id)
+        player_id
     }
 
     pub async fn connect_test_client(
         &self,
         player_id: PlayerId,
-        sender: mpsc::Sender<Arc<ServerMessage>>,
+        sender: mpsc::Sender<Outbound>,
         client_addr: SocketAddr,
     ) {
         let connection = ClientConnection {
@@ -111,9 +177,15 @@ impl ConnectionManager {
             client_addr,
             game_data_format: GameDataEncoding::Json,
             app_info: None,
+            client_fingerprint: None,
+            game_name: None,
+            region_id: None,
+            heartbeat: HeartbeatTiming::default(),
+            shared_rtt_ms: None,
+            sdk_capabilities: None,
         };
 
-        self.increment_ip_slot_unbounded(client_addr.ip());
+        self.increment_ip_slot_unbounded(self.connection_key(client_addr.ip()));
         self.clients.insert(player_id, connection);
         self.metrics.increment_connections();
 
@@ -175,16 +247,39 @@ impl ConnectionManager {
             .and_then(|conn| conn.app_info.clone())
     }
 
+    pub fn set_client_fingerprint(&self, player_id: &PlayerId, fingerprint: Arc<str>) {
+        if let Some(mut connection) = self.clients.get_mut(player_id) {
+            connection.client_fingerprint = Some(fingerprint);
+        }
+    }
+
+    pub fn client_fingerprint(&self, player_id: &PlayerId) -> Option<Arc<str>> {
+        self.clients
+            .get(player_id)
+            .and_then(|conn| conn.client_fingerprint.clone())
+    }
+
+    pub fn set_sdk_capabilities(&self, player_id: &PlayerId, capabilities: Vec<String>) {
+        if let Some(mut connection) = self.clients.get_mut(player_id) {
+            connection.sdk_capabilities = Some(capabilities);
+        }
+    }
+
+    /// Whether the client's SDK has `capability`; `None` until its SDK is checked.
+    pub fn sdk_capability(&self, player_id: &PlayerId, capability: &str) -> Option<bool> {
+        let connection = self.clients.get(player_id)?;
+        let capabilities = connection.sdk_capabilities.as_ref()?;
+        Some(capabilities.iter().any(|cap| cap == capability))
+    }
+
     pub fn app_id(&self, player_id: &PlayerId) -> Option<Uuid> {
         self.app_info(player_id).map(|info| info.id)
     }
 
-    pub fn clear_room_assignment(
-        &self,
-        player_id: &PlayerId,
-    ) -> Option<mpsc::Sender<Arc<ServerMessage>>> {
+    pub fn clear_room_assignment(&self, player_id: &PlayerId) -> Option<mpsc::Sender<Outbound>> {
         self.clients.get_mut(player_id).map(|mut client| {
             client.room_id = None;
+            client.game_name = None;
             client.sender.clone()
         })
     }
@@ -224,16 +319,114 @@ impl ConnectionManager {
         }
     }
 
+    /// Accept a heartbeat for acknowledgement, at most once per `threshold`.
+    ///
+    /// Returns `None` while throttled. When `echo` refers to the previous ack,
+    /// the time since that ack was sent minus the client's hold time is one
+    /// round trip; it is folded into the connection's smoothed RTT.
+    pub fn acknowledge_heartbeat(
+        &self,
+        player_id: &PlayerId,
+        threshold: Duration,
+        echo: Option<HeartbeatEcho>,
+        server_timestamp: u64,
+    ) -> Option<HeartbeatAck> {
+        let mut client = self.clients.get_mut(player_id)?;
+        let timing = &mut client.heartbeat;
+        let now = Instant::now();
+        if let Some((_, sent_at)) = timing.last_ack {
+            if now.duration_since(sent_at) < threshold {
+                return None;
+            }
+        }
+
+        let rtt_sample = match (timing.last_ack, echo) {
+            (Some((timestamp, sent_at)), Some(echo)) if echo.server_timestamp == timestamp => now
+                .duration_since(sent_at)
+                .checked_sub(Duration::from_millis(echo.held_ms)),
+            _ => None,
+        };
+        if let Some(sample) = rtt_sample {
+            timing.smoothed_rtt = Some(match timing.smoothed_rtt {
+                Some(smoothed) => (smoothed * 7 + sample) / 8,
+                None => sample,
+            });
+        }
+        timing.last_ack = Some((server_timestamp, now));
+        Some(HeartbeatAck {
+            server_timestamp,
+            rtt_sample,
+        })
+    }
+
+    /// Record a round-trip time reported by the client. Returns true when it
+    /// differs from the last shared value by at least `threshold_ms` (or none
+    /// was shared yet), in which case it becomes the shared value.
+    pub fn update_shared_rtt(&self, player_id: &PlayerId, rtt_ms: u32, threshold_ms: u32) -> bool {
+        let Some(mut client) = self.clients.get_mut(player_id) else {
+            return false;
+        };
+        let changed = client
+            .shared_rtt_ms
+            .is_none_or(|shared| shared.abs_diff(rtt_ms) >= threshold_ms);
+        if changed {
+            client.shared_rtt_ms = Some(rtt_ms);
+        }
+        changed
+    }
+
+    /// Smoothed round-trip time measured from the client's heartbeats.
+    pub fn connection_rtt(&self, player_id: &PlayerId) -> Option<Duration> {
+        self.clients
+            .get(player_id)
+            .and_then(|client| client.heartbeat.smoothed_rtt)
+    }
+
+    pub fn set_client_game(&self, player_id: &PlayerId, game_name: &str) {
+        if let Some(mut client) = self.clients.get_mut(player_id) {
+            client.game_name = Some(Arc::from(game_name));
+        }
+    }
+
+    pub fn client_game(&self, player_id: &PlayerId) -> Option<Arc<str>> {
+        self.clients
+            .get(player_id)
+            .and_then(|client| client.game_name.clone())
+    }
+
+    pub fn set_client_region(&self, player_id: &PlayerId, region_id: &str) {
+        if let Some(mut client) = self.clients.get_mut(player_id) {
+            client.region_id = Some(Arc::from(region_id));
+        }
+    }
+
+    pub fn client_region(&self, player_id: &PlayerId) -> Option<Arc<str>> {
+        self.clients
+            .get(player_id)
+            .and_then(|client| client.region_id.clone())
+    }
+
     pub fn get_client_room(&self, player_id: &PlayerId) -> Option<RoomId> {
         self.clients
             .get(player_id)
             .and_then(|client| client.room_id)
     }
 
+    /// Number of registered clients.
+    pub fn client_count(&self) -> usize {
+        self.clients.len()
+    }
+
     pub fn has_client(&self, player_id: &PlayerId) -> bool {
         self.clients.contains_key(player_id)
     }
 
+    pub fn client_ip(&self, player_id: &PlayerId) -> Option<IpAddr> {
+        self.clients
+            .get(player_id)
+            .map(|client| client.client_addr.ip())
+    }
+
     pub fn reassign_connection(
         &self,
         current_player_id: &PlayerId,
@@ -250,6 +443,12 @@ impl ConnectionManager {
                 client_addr: old_connection.client_addr,
                 game_data_format: old_connection.game_data_format,
                 app_info: old_connection.app_info,
+                client_fingerprint: old_connection.client_fingerprint,
+                game_name: None,
+                region_id: old_connection.region_id,
+                heartbeat: old_connection.heartbeat,
+                shared_rtt_ms: old_connection.shared_rtt_ms,
+                sdk_capabilities: old_connection.sdk_capabilities,
             };
 
             // IP slot is already reserved from the old entry -- no need to
@@ -263,7 +462,7 @@ impl ConnectionManager {
 
     pub fn remove_client(&self, player_id: &PlayerId) -> Option<ClientConnection> {
         self.clients.remove(player_id).map(|(_, connection)| {
-            self.release_ip_slot(connection.client_addr.ip());
+            self.release_ip_slot(self.connection_key(connection.client_addr.ip()));
             connection
         })
     }
@@ -282,8 +481,8 @@ impl ConnectionManager {
             .collect()
     }
 
-    fn try_reserve_ip_slot(&self, ip: IpAddr) -> Result<usize, usize> {
-        match self.connections_per_ip.entry(ip) {
+    fn try_reserve_ip_slot(&self, key: ConnectionKey) -> Result<usize, usize> {
+        match self.connections_per_ip.entry(key) {
             dashmap::mapref::entry::Entry::Occupied(mut entry) => {
                 let current = *entry.get();
                 if current >= self.max_connections_per_ip {
@@ -305,10 +504,10 @@ impl ConnectionManager {
         }
     }
 
-    fn increment_ip_slot_unbounded(&self, ip: IpAddr) -> usize {
+    fn increment_ip_slot_unbounded(&self, key: ConnectionKey) -> usize {
         // Use entry API for atomicity: prevents TOCTOU race where two threads
         // both see the key as absent and both insert 1 instead of 2
-        match self.connections_per_ip.entry(ip) {
+        match self.connections_per_ip.entry(key) {
             dashmap::mapref::entry::Entry::Occupied(mut entry) => {
                 *entry.get_mut() += 1;
                 *entry.get()
@@ -320,12 +519,12 @@ impl ConnectionManager {
         }
     }
 
-    fn release_ip_slot(&self, ip: IpAddr) {
+    fn release_ip_slot(&self, key: ConnectionKey) {
         // Use entry API for atomicity: prevents TOCTOU race where the count
         // is read as 1, the ref is dropped, another thread increments to 2,
         // then this thread removes the entry (losing the increment)
         if let dashmap::mapref::entry::Entry::Occupied(mut entry) =
-            self.connections_per_ip.entry(ip)
+            self.connections_per_ip.entry(key)
         {
             if *entry.get() > 1 {
                 *entry.get_mut() -= 1;
@@ -341,6 +540,7 @@ mod tests {
     use super::*;
     use crate::coordination::{MembershipUpdate, MessageCoordinator};
     use crate::distributed::SequencedMessage;
+    use crate::protocol::ServerMessage;
     use anyhow::Result;
     use async_trait::async_trait;
     use std::net::SocketAddr;
@@ -383,7 +583,7 @@ mod tests {
             &self,
             player_id: PlayerId,
             room_id: Option<RoomId>,
-            _sender: mpsc::Sender<Arc<ServerMessage>>,
+            _sender: mpsc::Sender<Outbound>,
         ) -> Result<()> {
             self.registrations.lock().await.push((player_id, room_id));
             Ok(())
@@ -414,13 +614,10 @@ mod tests {
     fn make_manager(max_connections_per_ip: usize) -> ConnectionManager {
         let metrics = Arc::new(ServerMetrics::new());
         let coordinator: Arc<dyn MessageCoordinator> = Arc::new(TestCoordinator::default());
-        ConnectionManager::new(max_connections_per_ip, metrics, coordinator)
+        ConnectionManager::new(max_connections_per_ip, 64, metrics, coordinator)
     }
 
-    fn channel() -> (
-        mpsc::Sender<Arc<ServerMessage>>,
-        mpsc::Receiver<Arc<ServerMessage>>,
-    ) {
+    fn channel() -> (mpsc::Sender<Outbound>, mpsc::Receiver<Outbound>) {
         mpsc::channel(4)
     }
 
@@ -456,12 +653,83 @@ mod tests {
             .expect("registrations resume after slot release");
     }
 
+    #[tokio::test]
+    async fn reserved_ip_slots_count_against_the_limit_until_released() {
+        let metrics = Arc::new(ServerMetrics::new());
+        let coordinator = Arc::new(TestCoordinator::default());
+        let manager = ConnectionManager::new(
+            2,
+            64,
+            metrics.clone(),
+            coordinator as Arc<dyn MessageCoordinator>,
+        );
+        let addr: SocketAddr = "127.0.0.1:5000".parse().unwrap();
+        let key = manager.connection_key(addr.ip());
+
+        manager.reserve_ip_slot(key).expect("first slot");
+        manager.reserve_ip_slot(key).expect("second slot");
+        assert!(manager.reserve_ip_slot(key).is_err());
+        let (tx, _rx) = channel();
+        assert!(manager
+            .register_client(tx, addr, Uuid::new_v4())
+            .await
+            .is_err());
+        assert_eq!(
+            metrics
+                .connections_rejected_ip_limit
+                .load(std::sync::atomic::Ordering::Relaxed),
+            2
+        );
+
+        // An abandoned upgrade gives its slot back; a registered client keeps it.
+        manager.release_reserved_ip_slot(key);
+        manager.reserve_ip_slot(key).expect("released slot is free");
+        let (tx, _rx) = channel();
+        manager
+            .register_reserved_client(tx, addr, Uuid::new_v4())
+            .await;
+        assert!(manager.reserve_ip_slot(key).is_err());
+    }
+
+    #[tokio::test]
+    async fn ipv6_clients_share_a_limit_per_prefix() {
+        let manager = make_manager(2);
+        for addr in ["[2001:db8:1:2::1]:5000", "[2001:db8:1:2:ffff::9]:5001"] {
+            let (tx, _rx) = channel();
+            manager
+                .register_client(tx, addr.parse().unwrap(), Uuid::new_v4())
+                .await
+                .expect("slot free within the /64");
+        }
+
+        // Rotating within the /64 does not earn another slot; another /64 does.
+        let (tx, _rx) = channel();
+        assert!(manager
+            .register_client(
+                tx,
+                "[2001:db8:1:2::abcd]:5002".parse().unwrap(),
+                Uuid::new_v4()
+            )
+            .await
+            .is_err());
+        let (tx, _rx) = channel();
+        assert!(manager
+            .register_client(
+                tx,
+                "[2001:db8:1:3::1]:5003".parse().unwrap(),
+                Uuid::new_v4()
+            )
+            .await
+            .is_ok());
+    }
+
     #[tokio::test]
     async fn assign_client_to_room_updates_coordinator_membership() {
         let metrics = Arc::new(ServerMetrics::new());
         let coordinator = Arc::new(TestCoordinator::default());
         let manager = ConnectionManager::new(
             4,
+            64,
             metrics.clone(),
             coordinator.clone() as Arc<dyn MessageCoordinator>,
         );
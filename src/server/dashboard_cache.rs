@@ -245,8 +245,11 @@ impl HistoryFields {
                     settings.player_percentiles = true;
                 }
                 DashboardHistoryField::GamePercentiles => settings.game_percentiles = true,
-                // Minimal stub variants don't track history
-                DashboardHistoryField::ActiveConnections | DashboardHistoryField::RoomsCreated => {}
+                // Scalar series are kept by the metrics history instead
+                DashboardHistoryField::ActiveConnections
+                | DashboardHistoryField::RoomsCreated
+                | DashboardHistoryField::MessagesPerSec
+                | DashboardHistoryField::ReconnectRate => {}
             }
         }
 
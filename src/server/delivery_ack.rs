@@ -0,0 +1,299 @@
+//! Sampled delivery acknowledgements for room broadcasts.
+//!
+//! One in every `delivery_ack_sample_rate` broadcasts is marked with an
+//! `ack_sample` id for recipients that negotiated the `delivery-ack`
+//! capability. Their acknowledgements (or the lack of them) feed per
+//! game/region ack-rate and latency metrics, which reflect what actually
+//! reached clients rather than what was queued.
+
+use crate::metrics::ServerMetrics;
+use crate::protocol::{AckSampledMessage, PlayerId, RoomId, ServerMessage};
+use dashmap::{DashMap, DashSet};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use super::EnhancedGameServer;
+
+/// Capability a client lists in `Authenticate` to receive sampled broadcasts.
+pub const DELIVERY_ACK_CAPABILITY: &str = "delivery-ack";
+
+/// Game label used when a sampled room has not been labelled yet.
+const UNKNOWN_GAME: &str = "unknown";
+
+/// Lower bound on the sweep interval so tiny timeouts don't spin the task.
+const MIN_SWEEP_INTERVAL: Duration = Duration::from_millis(50);
+
+struct PendingSample {
+    sent_at: Instant,
+    game_name: String,
+    awaiting: HashSet<PlayerId>,
+}
+
+/// A broadcast selected for sampling: the marked message and who should ack it.
+pub(crate) struct SampledBroadcast {
+    pub(crate) message: Arc<ServerMessage>,
+    pub(crate) recipients: HashSet<PlayerId>,
+}
+
+/// Tracks sampled broadcasts awaiting acknowledgement.
+///
+/// Only capable clients are marked and counted, so clients without the
+/// capability never enter the ack-rate denominator. Outstanding samples are
+/// bounded by `max_pending`; stale ones are swept after `timeout` and counted
+/// as failures.
+pub struct DeliveryAckSampler {
+    sample_rate: u64,
+    timeout: Duration,
+    max_pending: usize,
+    region_id: String,
+    broadcasts: AtomicU64,
+    next_sample_id: AtomicU64,
+    capable_players: DashSet<PlayerId>,
+    room_games: DashMap<RoomId, String>,
+    pending: DashMap<u64, PendingSample>,
+    metrics: Arc<ServerMetrics>,
+}
+
+impl DeliveryAckSampler {
+    pub fn new(
+        sample_rate: u32,
+        timeout: Duration,
+        max_pending: usize,
+        region_id: String,
+        metrics: Arc<ServerMetrics>,
+    ) -> Self {
+        Self {
+            sample_rate: u64::from(sample_rate),
+            timeout,
+            max_pending: max_pending.max(1),
+            region_id,
+            broadcasts: AtomicU64::new(0),
+            next_sample_id: AtomicU64::new(1),
+            capable_players: DashSet::new(),
+            room_games: DashMap::new(),
+            pending: DashMap::new(),
+            metrics,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.sample_rate > 0
+    }
+
+    /// Record that a client negotiated the delivery-ack capability.
+    pub fn enable_for(&self, player_id: PlayerId) {
+        if self.is_enabled() {
+            self.capable_players.insert(player_id);
+        }
+    }
+
+    pub fn is_capable(&self, player_id: &PlayerId) -> bool {
+        self.capable_players.contains(player_id)
+    }
+
+    pub fn forget_player(&self, player_id: &PlayerId) {
+        self.capable_players.remove(player_id);
+    }
+
+    /// Attach the game name used to label metrics for samples from this room.
+    pub fn label_room(&self, room_id: RoomId, game_name: &str) {
+        if self.is_enabled() && !self.room_games.contains_key(&room_id) {
+            self.room_games.insert(room_id, game_name.to_string());
+        }
+    }
+
+    pub fn forget_room(&self, room_id: &RoomId) {
+        self.room_games.remove(room_id);
+    }
+
+    /// Number of samples still waiting on at least one acknowledgement.
+    pub fn pending_samples(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Decide whether this broadcast is sampled. Called once per broadcast.
+    pub(crate) fn sample<'a>(
+        &self,
+        room_id: &RoomId,
+        message: &Arc<ServerMessage>,
+        recipients: impl IntoIterator<Item = &'a PlayerId>,
+    ) -> Option<SampledBroadcast> {
+        if !self.is_enabled() {
+            return None;
+        }
+        // Binary frames bypass JSON serialization, so they can't carry the mark.
+        if matches!(
+            message.as_ref(),
+            ServerMessage::GameDataBinary { .. } | ServerMessage::AckSampled(_)
+        ) {
+            return None;
+        }
+        let broadcast = self.broadcasts.fetch_add(1, Ordering::Relaxed);
+        if broadcast % self.sample_rate != 0 {
+            return None;
+        }
+
+        let awaiting: HashSet<PlayerId> = recipients
+            .into_iter()
+            .filter(|player_id| self.capable_players.contains(*player_id))
+            .copied()
+            .collect();
+        if awaiting.is_empty() {
+            return None;
+        }
+
+        self.expire_stale();
+        while self.pending.len() >= self.max_pending {
+            if !self.evict_oldest() {
+                break;
+            }
+        }
+
+        let ack_sample = self.next_sample_id.fetch_add(1, Ordering::Relaxed);
+        let game_name = self
+            .room_games
+            .get(room_id)
+            .map(|game| game.clone())
+            .unwrap_or_else(|| UNKNOWN_GAME.to_string());
+        self.metrics.record_delivery_ack_expected(
+            &game_name,
+            &self.region_id,
+            awaiting.len() as u64,
+        );
+        self.pending.insert(
+            ack_sample,
+            PendingSample {
+                sent_at: Instant::now(),
+                game_name,
+                awaiting: awaiting.clone(),
+            },
+        );
+
+        Some(SampledBroadcast {
+            message: Arc::new(ServerMessage::AckSampled(AckSampledMessage {
+                ack_sample,
+                message: Arc::clone(message),
+            })),
+            recipients: awaiting,
+        })
+    }
+
+    /// Record an acknowledgement. Returns `false` for unknown, expired or duplicate acks.
+    pub fn acknowledge(&self, player_id: &PlayerId, ack_sample: u64) -> bool {
+        let Some(mut sample) = self.pending.get_mut(&ack_sample) else {
+            return false;
+        };
+        if !sample.awaiting.remove(player_id) {
+            return false;
+        }
+        let latency = sample.sent_at.elapsed();
+        let game_name = sample.game_name.clone();
+        let complete = sample.awaiting.is_empty();
+        drop(sample);
+
+        if complete {
+            self.pending
+                .remove_if(&ack_sample, |_, sample| sample.awaiting.is_empty());
+        }
+        self.metrics
+            .record_delivery_ack(&game_name, &self.region_id, latency);
+        true
+    }
+
+    /// Drop samples older than the timeout, counting missing acks as failures.
+    /// Returns the number of failures recorded.
+    pub fn expire_stale(&self) -> usize {
+        let expired: Vec<u64> = self
+            .pending
+            .iter()
+            .filter(|entry| entry.sent_at.elapsed() >= self.timeout)
+            .map(|entry| *entry.key())
+            .collect();
+
+        expired
+            .into_iter()
+            .filter_map(|ack_sample| self.pending.remove(&ack_sample))
+            .map(|(_, sample)| self.record_failures(&sample))
+            .sum()
+    }
+
+    fn evict_oldest(&self) -> bool {
+        let oldest = self
+            .pending
+            .iter()
+            .min_by_key(|entry| entry.sent_at)
+            .map(|entry| *entry.key());
+        match oldest.and_then(|ack_sample| self.pending.remove(&ack_sample)) {
+            Some((_, sample)) => {
+                self.record_failures(&sample);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn record_failures(&self, sample: &PendingSample) -> usize {
+        let failures = sample.awaiting.len();
+        self.metrics.record_delivery_ack_failures(
+            &sample.game_name,
+            &self.region_id,
+            failures as u64,
+        );
+        failures
+    }
+
+    /// Periodically time out unacknowledged samples.
+    pub fn start_sweeper(self: Arc<Self>) {
+        if !self.is_enabled() {
+            return;
+        }
+        let sampler = Arc::clone(&self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval((sampler.timeout / 2).max(MIN_SWEEP_INTERVAL));
+            loop {
+                interval.tick().await;
+                let failures = sampler.expire_stale();
+                if failures > 0 {
+                    tracing::debug!(failures, "Delivery ack samples timed out");
+                }
+            }
+        });
+    }
+}
+
+impl EnhancedGameServer {
+    /// Apply capabilities a client listed in `Authenticate`.
+    pub fn negotiate_client_capabilities(&self, player_id: &PlayerId, capabilities: &[String]) {
+        if capabilities
+            .iter()
+            .any(|capability| capability == DELIVERY_ACK_CAPABILITY)
+        {
+            self.delivery_acks.enable_for(*player_id);
+        }
+    }
+
+    /// Capabilities advertised in `ProtocolInfo`, including server-side features.
+    pub fn advertised_capabilities(&self, sdk_capabilities: &[String]) -> Vec<String> {
+        let mut capabilities = sdk_capabilities.to_vec();
+        if self.delivery_acks.is_enabled()
+            && !capabilities
+                .iter()
+                .any(|capability| capability == DELIVERY_ACK_CAPABILITY)
+        {
+            capabilities.push(DELIVERY_ACK_CAPABILITY.to_string());
+        }
+        capabilities
+    }
+
+    pub fn handle_delivery_ack(&self, player_id: &PlayerId, ack_sample: u64) {
+        if !self.delivery_acks.acknowledge(player_id, ack_sample) {
+            tracing::debug!(%player_id, ack_sample, "Ignoring unknown or late delivery ack");
+        }
+    }
+
+    pub fn delivery_ack_sampler(&self) -> &Arc<DeliveryAckSampler> {
+        &self.delivery_acks
+    }
+}
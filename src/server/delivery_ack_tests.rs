@@ -0,0 +1,238 @@
+use super::*;
+use crate::config::{
+    AuthMaintenanceConfig, ConfigDuration, CoordinationConfig, MetricsConfig, ProtocolConfig,
+    RelayTypeConfig, TransportSecurityConfig,
+};
+use crate::database::DatabaseConfig;
+use crate::metrics::DeliveryAckMetrics;
+use crate::protocol::ClientMessage;
+use std::net::SocketAddr;
+use tokio::sync::mpsc;
+use tokio::time::{timeout, Duration};
+
+async fn create_test_server(sample_rate: u32, ack_timeout_ms: u64) -> Arc<EnhancedGameServer> {
+    EnhancedGameServer::new(
+        ServerConfig::default(),
+        ProtocolConfig::default(),
+        RelayTypeConfig::default(),
+        DatabaseConfig::InMemory,
+        MetricsConfig {
+            delivery_ack_sample_rate: sample_rate,
+            delivery_ack_timeout: ConfigDuration::from_millis(ack_timeout_ms),
+            ..MetricsConfig::default()
+        },
+        AuthMaintenanceConfig::default(),
+        CoordinationConfig::default(),
+        TransportSecurityConfig::default(),
+        Vec::new(),
+    )
+    .await
+    .expect("failed to construct test server")
+}
+
+struct TestClient {
+    id: PlayerId,
+    rx: mpsc::Receiver<Outbound>,
+}
+
+/// Two clients that negotiated `delivery-ack` and one that did not, all in one room.
+async fn setup_room(server: &EnhancedGameServer) -> (RoomId, [TestClient; 3]) {
+    let mut clients = Vec::new();
+    for (index, addr) in ["127.0.0.1:48300", "127.0.0.2:48301", "127.0.0.3:48302"]
+        .iter()
+        .enumerate()
+    {
+        let (sender, rx) = mpsc::channel(32);
+        let addr: SocketAddr = addr.parse().unwrap();
+        let id = server
+            .connection_manager
+            .register_client(sender, addr, server.instance_id)
+            .await
+            .expect("client registration succeeds");
+        server
+            .handle_join_room(
+                &id,
+                "ack-game".to_string(),
+                Some("ACKS01".to_string()),
+                format!("Player{index}"),
+                Some(4),
+                Some(false),
+                None,
+                None,
+                None,
+            )
+            .await;
+        clients.push(TestClient { id, rx });
+    }
+
+    let capabilities = vec![DELIVERY_ACK_CAPABILITY.to_string()];
+    server.negotiate_client_capabilities(&clients[0].id, &capabilities);
+    server.negotiate_client_capabilities(&clients[1].id, &capabilities);
+
+    for client in &mut clients {
+        while client.rx.try_recv().is_ok() {}
+    }
+    let room_id = server
+        .get_client_room(&clients[0].id)
+        .await
+        .expect("host is in a room");
+    let clients: [TestClient; 3] = clients.try_into().ok().expect("three clients");
+    (room_id, clients)
+}
+
+async fn broadcast(server: &EnhancedGameServer, room_id: &RoomId, tick: u64) {
+    server
+        .message_coordinator
+        .broadcast_to_room(
+            room_id,
+            Arc::new(ServerMessage::GameData {
+                from_player: Uuid::new_v4(),
+                data: serde_json::json!({ "tick": tick }),
+            }),
+        )
+        .await
+        .expect("broadcast succeeds");
+}
+
+/// Returns the `ack_sample` field of the next message, if it was marked.
+async fn next_ack_sample(client: &mut TestClient) -> Option<u64> {
+    let message = timeout(Duration::from_secs(1), client.rx.recv())
+        .await
+        .expect("message arrives in time")
+        .expect("channel still open");
+    let value = serde_json::to_value(message.as_ref()).expect("message serializes");
+    assert_eq!(value["type"], "GameData", "unexpected message {value}");
+    value.get("ack_sample").and_then(serde_json::Value::as_u64)
+}
+
+async fn ack_metrics(server: &EnhancedGameServer) -> DeliveryAckMetrics {
+    server
+        .metrics
+        .snapshot()
+        .await
+        .delivery_acks
+        .into_iter()
+        .find(|group| group.game_name == "ack-game" && group.region_id == "default")
+        .expect("delivery ack metrics recorded for game and region")
+}
+
+#[tokio::test]
+async fn sampled_broadcast_marks_only_capable_clients() {
+    let server = create_test_server(1, 10_000).await;
+    let (room_id, [mut first, mut second, mut legacy]) = setup_room(&server).await;
+
+    broadcast(&server, &room_id, 1).await;
+
+    let first_sample = next_ack_sample(&mut first).await;
+    let second_sample = next_ack_sample(&mut second).await;
+    assert!(first_sample.is_some(), "capable client should be marked");
+    assert_eq!(first_sample, second_sample, "one sample id per broadcast");
+    assert_eq!(
+        next_ack_sample(&mut legacy).await,
+        None,
+        "client without the capability receives the plain message"
+    );
+    assert_eq!(server.delivery_ack_sampler().pending_samples(), 1);
+}
+
+#[tokio::test]
+async fn acknowledgements_are_counted_per_game_and_region() {
+    let server = create_test_server(1, 10_000).await;
+    let (room_id, [mut first, mut second, legacy]) = setup_room(&server).await;
+
+    broadcast(&server, &room_id, 1).await;
+    let sample = next_ack_sample(&mut first).await.expect("sampled");
+    next_ack_sample(&mut second).await.expect("sampled");
+
+    server
+        .handle_client_message(&first.id, ClientMessage::DeliveryAck { ack_sample: sample })
+        .await;
+    // Duplicate and uninvited acks are ignored
+    assert!(!server.delivery_ack_sampler().acknowledge(&first.id, sample));
+    assert!(!server
+        .delivery_ack_sampler()
+        .acknowledge(&legacy.id, sample));
+    server
+        .handle_client_message(
+            &second.id,
+            ClientMessage::DeliveryAck { ack_sample: sample },
+        )
+        .await;
+
+    let metrics = ack_metrics(&server).await;
+    assert_eq!(metrics.expected, 2);
+    assert_eq!(metrics.acked, 2);
+    assert_eq!(metrics.failed, 0);
+    assert!((metrics.ack_rate - 1.0).abs() < f64::EPSILON);
+    assert!(metrics.average_latency_ms.is_some());
+    assert_eq!(server.delivery_ack_sampler().pending_samples(), 0);
+}
+
+#[tokio::test]
+async fn unacknowledged_samples_time_out_as_failures() {
+    let server = create_test_server(1, 100).await;
+    let (room_id, [mut first, mut second, _legacy]) = setup_room(&server).await;
+
+    broadcast(&server, &room_id, 1).await;
+    let sample = next_ack_sample(&mut first).await.expect("sampled");
+    next_ack_sample(&mut second).await.expect("sampled");
+    server.handle_delivery_ack(&first.id, sample);
+
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    server.delivery_ack_sampler().expire_stale();
+
+    let metrics = ack_metrics(&server).await;
+    // The legacy client never enters the denominator
+    assert_eq!(metrics.expected, 2);
+    assert_eq!(metrics.acked, 1);
+    assert_eq!(metrics.failed, 1);
+    assert!((metrics.ack_rate - 0.5).abs() < f64::EPSILON);
+    assert_eq!(server.delivery_ack_sampler().pending_samples(), 0);
+
+    // A late ack for an expired sample is not counted
+    server.handle_delivery_ack(&second.id, sample);
+    assert_eq!(ack_metrics(&server).await.acked, 1);
+}
+
+#[tokio::test]
+async fn sample_rate_selects_one_in_n_broadcasts() {
+    let server = create_test_server(3, 10_000).await;
+    let (room_id, [mut first, mut second, _legacy]) = setup_room(&server).await;
+
+    let mut sampled = 0;
+    for tick in 0..6 {
+        broadcast(&server, &room_id, tick).await;
+        let first_sample = next_ack_sample(&mut first).await;
+        assert_eq!(first_sample, next_ack_sample(&mut second).await);
+        sampled += usize::from(first_sample.is_some());
+    }
+
+    assert_eq!(sampled, 2);
+    assert_eq!(ack_metrics(&server).await.expected, 4);
+}
+
+#[tokio::test]
+async fn pending_samples_are_bounded() {
+    let metrics = Arc::new(crate::metrics::ServerMetrics::new());
+    let sampler = DeliveryAckSampler::new(
+        1,
+        Duration::from_secs(60),
+        2,
+        "default".to_string(),
+        metrics.clone(),
+    );
+    let room_id = Uuid::new_v4();
+    let player_id = Uuid::new_v4();
+    sampler.enable_for(player_id);
+    sampler.label_room(room_id, "ack-game");
+
+    let message = Arc::new(ServerMessage::Pong);
+    for _ in 0..3 {
+        assert!(sampler.sample(&room_id, &message, [&player_id]).is_some());
+    }
+
+    assert_eq!(sampler.pending_samples(), 2);
+    let snapshot = metrics.snapshot().await;
+    assert_eq!(snapshot.delivery_acks[0].expected, 3);
+    assert_eq!(snapshot.delivery_acks[0].failed, 1);
+}
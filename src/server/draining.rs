@@ -0,0 +1,71 @@
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use super::EnhancedGameServer;
+use crate::protocol::{ErrorCode, PlayerId, ServerMessage};
+
+impl EnhancedGameServer {
+    /// Whether the instance is refusing new rooms and joins ahead of a deploy.
+    pub fn is_draining(&self) -> bool {
+        self.draining.load(Ordering::Relaxed)
+    }
+
+    /// Start or stop draining, returning the previous state.
+    ///
+    /// While draining, `JoinRoom` and matchmaking requests are refused with
+    /// [`ErrorCode::ServerDraining`] and the readiness probe fails so load
+    /// balancers stop routing here. Reconnects and traffic in existing rooms
+    /// are unaffected.
+    pub fn set_draining(&self, draining: bool) -> bool {
+        let previous = self.draining.swap(draining, Ordering::Relaxed);
+        if previous != draining {
+            tracing::info!(
+                instance_id = %self.instance_id,
+                draining,
+                "Instance drain state changed"
+            );
+        }
+        previous
+    }
+
+    /// Refuse a new join while draining. Returns true when the player was turned away.
+    pub(super) async fn reject_if_draining(&self, player_id: &PlayerId) -> bool {
+        if !self.is_draining() {
+            return false;
+        }
+
+        let retry_after_secs = self.config().drain_retry_after.as_secs().max(1);
+        let _ = self
+            .message_coordinator
+            .send_to_player(
+                player_id,
+                Arc::new(ServerMessage::RoomJoinFailed {
+                    reason: format!(
+                        "Server is draining and not accepting new rooms or joins. Try again in {retry_after_secs} seconds."
+                    ),
+                    error_code: Some(ErrorCode::ServerDraining),
+                    retry_after_secs: Some(retry_after_secs),
+                    details: None,
+                }),
+            )
+            .await;
+        true
+    }
+
+    /// Close every WebSocket connection with [`ErrorCode::ServerShuttingDown`]
+    /// so clients reconnect elsewhere instead of waiting on a dead socket.
+    /// Connections opened afterwards are closed as soon as they are set up.
+    pub fn begin_shutdown(&self) {
+        if !self.shutting_down.send_replace(true) {
+            tracing::info!(
+                instance_id = %self.instance_id,
+                "Closing WebSocket connections for shutdown"
+            );
+        }
+    }
+
+    /// Watch for [`Self::begin_shutdown`].
+    pub(crate) fn shutdown_watch(&self) -> tokio::sync::watch::Receiver<bool> {
+        self.shutting_down.subscribe()
+    }
+}
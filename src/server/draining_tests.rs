@@ -0,0 +1,160 @@
+use super::*;
+use crate::config::{
+    AuthMaintenanceConfig, CoordinationConfig, MetricsConfig, ProtocolConfig, RelayTypeConfig,
+    TransportSecurityConfig,
+};
+use crate::database::DatabaseConfig;
+use crate::protocol::{ClientMessage, ErrorCode, ServerMessage};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::time::{timeout, Duration};
+
+const GAME: &str = "drain-game";
+
+async fn create_test_server() -> Arc<EnhancedGameServer> {
+    EnhancedGameServer::new(
+        ServerConfig {
+            drain_retry_after: Duration::from_secs(45),
+            ..ServerConfig::default()
+        },
+        ProtocolConfig::default(),
+        RelayTypeConfig::default(),
+        DatabaseConfig::InMemory,
+        MetricsConfig::default(),
+        AuthMaintenanceConfig::default(),
+        CoordinationConfig::default(),
+        TransportSecurityConfig::default(),
+        Vec::new(),
+    )
+    .await
+    .expect("failed to construct test server")
+}
+
+async fn register(server: &EnhancedGameServer, addr: &str) -> (PlayerId, mpsc::Receiver<Outbound>) {
+    let (sender, receiver) = mpsc::channel(32);
+    let addr: SocketAddr = addr.parse().unwrap();
+    let player_id = server
+        .connection_manager
+        .register_client(sender, addr, server.instance_id)
+        .await
+        .expect("client registration succeeds");
+    (player_id, receiver)
+}
+
+async fn join(
+    server: &EnhancedGameServer,
+    player_id: &PlayerId,
+    name: &str,
+    room_code: Option<&str>,
+) {
+    server
+        .handle_join_room(
+            player_id,
+            GAME.to_string(),
+            room_code.map(str::to_string),
+            name.to_string(),
+            Some(4),
+            Some(false),
+            None,
+            None,
+            None,
+        )
+        .await;
+}
+
+async fn next_message(receiver: &mut mpsc::Receiver<Outbound>) -> Outbound {
+    timeout(Duration::from_secs(1), receiver.recv())
+        .await
+        .expect("message arrives in time")
+        .expect("channel still open")
+}
+
+fn drain_rejection(message: &ServerMessage) -> Option<u64> {
+    match message {
+        ServerMessage::RoomJoinFailed {
+            error_code: Some(ErrorCode::ServerDraining),
+            retry_after_secs,
+            ..
+        } => *retry_after_secs,
+        _ => None,
+    }
+}
+
+#[tokio::test]
+async fn draining_refuses_new_rooms_and_joins_with_retry_hint() {
+    let server = create_test_server().await;
+    let (host, mut host_rx) = register(&server, "127.0.0.1:48800").await;
+    join(&server, &host, "Host", Some("DRAIN1")).await;
+    assert!(matches!(
+        &*next_message(&mut host_rx).await,
+        ServerMessage::RoomJoined(_)
+    ));
+
+    assert!(!server.set_draining(true));
+    assert!(server.set_draining(true));
+
+    let (creator, mut creator_rx) = register(&server, "127.0.0.2:48801").await;
+    join(&server, &creator, "Creator", None).await;
+    assert_eq!(
+        drain_rejection(&*next_message(&mut creator_rx).await),
+        Some(45)
+    );
+
+    let (joiner, mut joiner_rx) = register(&server, "127.0.0.3:48802").await;
+    join(&server, &joiner, "Joiner", Some("DRAIN1")).await;
+    assert_eq!(
+        drain_rejection(&*next_message(&mut joiner_rx).await),
+        Some(45)
+    );
+
+    server
+        .handle_client_message(
+            &joiner,
+            ClientMessage::EnqueueMatchmaking {
+                game_name: GAME.to_string(),
+                player_name: "Queued".to_string(),
+                skill_rating: 1000.0,
+                region_id: None,
+                desired_max_players: 2,
+            },
+        )
+        .await;
+    assert_eq!(
+        drain_rejection(&*next_message(&mut joiner_rx).await),
+        Some(45)
+    );
+
+    assert!(server.set_draining(false));
+    join(&server, &joiner, "Joiner", Some("DRAIN1")).await;
+    assert!(matches!(
+        &*next_message(&mut joiner_rx).await,
+        ServerMessage::RoomJoined(_)
+    ));
+}
+
+#[tokio::test]
+async fn draining_keeps_existing_rooms_running() {
+    let server = create_test_server().await;
+    let (host, _host_rx) = register(&server, "127.0.0.1:48810").await;
+    let (guest, mut guest_rx) = register(&server, "127.0.0.2:48811").await;
+    join(&server, &host, "Host", Some("DRAIN2")).await;
+    join(&server, &guest, "Guest", Some("DRAIN2")).await;
+    while guest_rx.try_recv().is_ok() {}
+
+    server.set_draining(true);
+    server
+        .handle_client_message(
+            &host,
+            ClientMessage::GameData {
+                data: serde_json::json!({ "tick": 1 }),
+            },
+        )
+        .await;
+
+    let message = next_message(&mut guest_rx).await;
+    assert!(
+        matches!(&*message, ServerMessage::GameData { from_player, .. } if *from_player == host),
+        "expected relayed game data, got {message:?}"
+    );
+}
@@ -0,0 +1,196 @@
+//! Flap damping: hide rapid disconnect/reconnect cycles from the rest of a room.
+//!
+//! A seated player who drops keeps their seat for `flap_suppression`. If they
+//! reconnect inside that window the session resumes without `PlayerLeft` or
+//! `PlayerReconnected` reaching anyone else; only their flap count moves.
+//! Room broadcasts missed in between are still buffered for their replay.
+//! Once the window lapses the seat is vacated exactly like a normal leave.
+
+use crate::protocol::{PlayerId, RoomId};
+use dashmap::DashMap;
+use serde::Serialize;
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use tokio::time::Instant;
+
+use super::EnhancedGameServer;
+
+/// Number of suppressed flaps recorded for a seated player.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PlayerFlaps {
+    pub player_id: PlayerId,
+    pub room_id: RoomId,
+    pub flaps: u32,
+}
+
+#[derive(Default)]
+pub(super) struct FlapDamping {
+    /// Seats held for disconnected players, with the room and lapse deadline
+    held_seats: DashMap<PlayerId, (RoomId, Instant)>,
+    /// Suppressed flaps per seated player, forgotten once they leave the room
+    flaps: DashMap<PlayerId, (RoomId, u32)>,
+}
+
+impl EnhancedGameServer {
+    /// Hold a disconnecting player's seat instead of vacating it. Returns
+    /// `false` when flap suppression is off or the player's SDK cannot
+    /// reconnect, and the player should leave now.
+    pub(super) fn hold_seat_for_flap(&self, player_id: &PlayerId, room_id: RoomId) -> bool {
+        let window = self.config().flap_suppression;
+        if window.is_zero() || self.reconnection_manager_for(player_id).is_none() {
+            return false;
+        }
+        self.flap_damping
+            .held_seats
+            .insert(*player_id, (room_id, Instant::now() + window));
+        tracing::debug!(%player_id, %room_id, ?window, "Holding seat for a possible flap");
+        true
+    }
+
+    /// Resume a seat held by [`Self::hold_seat_for_flap`], counting the flap.
+    /// Returns `false` if no seat was held, e.g. because the window lapsed.
+    pub(super) fn resume_held_seat(&self, player_id: &PlayerId, room_id: &RoomId) -> bool {
+        let held = self
+            .flap_damping
+            .held_seats
+            .remove_if(player_id, |_, (held_room, _)| held_room == room_id);
+        if held.is_none() {
+            return false;
+        }
+        let mut entry = self
+            .flap_damping
+            .flaps
+            .entry(*player_id)
+            .or_insert((*room_id, 0));
+        if entry.0 != *room_id {
+            *entry = (*room_id, 0);
+        }
+        entry.1 += 1;
+        drop(entry);
+        self.metrics.increment_flaps_suppressed();
+        true
+    }
+
+    /// Forget a player's flap count once they are no longer seated.
+    pub(super) fn forget_flaps(&self, player_id: &PlayerId) {
+        self.flap_damping.flaps.remove(player_id);
+    }
+
+    /// Drop held seats and flap counts for a room that no longer exists.
+    pub(super) fn forget_room_flaps(&self, room_id: &RoomId) {
+        self.flap_damping
+            .held_seats
+            .retain(|_, (held_room, _)| held_room != room_id);
+        self.flap_damping
+            .flaps
+            .retain(|_, (flap_room, _)| flap_room != room_id);
+    }
+
+    /// Vacate every held seat whose window has lapsed, broadcasting `PlayerLeft`.
+    pub(super) async fn release_lapsed_seats(&self) {
+        let now = Instant::now();
+        let lapsed: Vec<PlayerId> = self
+            .flap_damping
+            .held_seats
+            .iter()
+            .filter(|entry| entry.value().1 <= now)
+            .map(|entry| *entry.key())
+            .collect();
+        for player_id in lapsed {
+            // A reconnect may have claimed the seat since the scan.
+            let Some((_, (room_id, _))) = self
+                .flap_damping
+                .held_seats
+                .remove_if(&player_id, |_, (_, deadline)| *deadline <= now)
+            else {
+                continue;
+            };
+            tracing::info!(%player_id, %room_id, "Flap window lapsed; player left room");
+            if self.vacate_seat(&player_id, &room_id).await {
+                self.promote_queued_spectators(&room_id).await;
+                self.promote_from_waiting_list(&room_id).await;
+            }
+        }
+    }
+
+    /// Vacate a seat in `room_id` held for a player who can no longer reconnect,
+    /// because their window lapsed or their reconnection slot was reclaimed.
+    /// Returns the player whose seat was freed.
+    pub(super) async fn reclaim_ghost_seat(&self, room_id: &RoomId) -> Option<PlayerId> {
+        let manager = self.reconnection_manager.as_ref()?;
+        let now = Instant::now();
+        let held: Vec<(PlayerId, Instant)> = self
+            .flap_damping
+            .held_seats
+            .iter()
+            .filter(|entry| entry.value().0 == *room_id)
+            .map(|entry| (*entry.key(), entry.value().1))
+            .collect();
+        for (player_id, deadline) in held {
+            if deadline > now && manager.has_redeemable_reconnection(&player_id).await {
+                continue;
+            }
+            // A reconnect may have claimed the seat since the scan.
+            if self
+                .flap_damping
+                .held_seats
+                .remove_if(&player_id, |_, (held_room, _)| held_room == room_id)
+                .is_none()
+            {
+                continue;
+            }
+            tracing::info!(%player_id, %room_id, "Reclaimed ghost seat for a joining player");
+            if self.vacate_seat(&player_id, room_id).await {
+                return Some(player_id);
+            }
+        }
+        None
+    }
+
+    /// Players with suppressed flaps, most first.
+    pub fn flapping_players(&self, limit: usize) -> Vec<PlayerFlaps> {
+        let mut players: Vec<PlayerFlaps> = self
+            .flap_damping
+            .flaps
+            .iter()
+            .map(|entry| PlayerFlaps {
+                player_id: *entry.key(),
+                room_id: entry.value().0,
+                flaps: entry.value().1,
+            })
+            .collect();
+        players.sort_by(|a, b| b.flaps.cmp(&a.flaps));
+        players.truncate(limit);
+        players
+    }
+
+    /// Suppressed flaps recorded for a seated player.
+    pub fn player_flap_count(&self, player_id: &PlayerId) -> u32 {
+        self.flap_damping
+            .flaps
+            .get(player_id)
+            .map_or(0, |entry| entry.1)
+    }
+
+    /// Spawn the loop that releases lapsed seats. It stops once the server is dropped.
+    pub(super) fn start_flap_damping(server: &Arc<Self>) {
+        let window = server.config().flap_suppression;
+        if window.is_zero() {
+            return;
+        }
+        let weak: Weak<Self> = Arc::downgrade(server);
+        let tick = (window / 10).clamp(Duration::from_millis(10), Duration::from_millis(250));
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(tick);
+            loop {
+                interval.tick().await;
+                let Some(server) = weak.upgrade() else {
+                    break;
+                };
+                if !server.flap_damping.held_seats.is_empty() {
+                    server.release_lapsed_seats().await;
+                }
+            }
+        });
+    }
+}
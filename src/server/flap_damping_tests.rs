@@ -0,0 +1,308 @@
+use super::*;
+use crate::config::{
+    AuthMaintenanceConfig, CoordinationConfig, MetricsConfig, ProtocolConfig, RelayTypeConfig,
+    TransportSecurityConfig,
+};
+use crate::database::DatabaseConfig;
+use crate::protocol::ReconnectedPayload;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::time::{sleep, timeout, Duration};
+
+const GAME: &str = "flap-game";
+const ROOM: &str = "FLAP01";
+const WINDOW: Duration = Duration::from_millis(200);
+
+async fn create_test_server() -> Arc<EnhancedGameServer> {
+    create_test_server_with_config(ServerConfig {
+        flap_suppression: WINDOW,
+        ..ServerConfig::default()
+    })
+    .await
+}
+
+async fn create_test_server_with_config(config: ServerConfig) -> Arc<EnhancedGameServer> {
+    EnhancedGameServer::new(
+        config,
+        ProtocolConfig::default(),
+        RelayTypeConfig::default(),
+        DatabaseConfig::InMemory,
+        MetricsConfig::default(),
+        AuthMaintenanceConfig::default(),
+        CoordinationConfig::default(),
+        TransportSecurityConfig::default(),
+        Vec::new(),
+    )
+    .await
+    .expect("failed to construct test server")
+}
+
+async fn connect(server: &EnhancedGameServer, port: u16) -> (PlayerId, mpsc::Receiver<Outbound>) {
+    let (sender, receiver) = mpsc::channel(32);
+    let addr: SocketAddr = format!("127.0.0.1:{port}").parse().unwrap();
+    let player_id = server
+        .register_client(sender, addr)
+        .await
+        .expect("client registration succeeds");
+    (player_id, receiver)
+}
+
+/// Join the test room, returning the room id and reconnection token.
+async fn join(
+    server: &EnhancedGameServer,
+    port: u16,
+    name: &str,
+) -> (PlayerId, mpsc::Receiver<Outbound>, RoomId, String) {
+    let (player_id, mut receiver) = connect(server, port).await;
+    server
+        .handle_join_room(
+            &player_id,
+            GAME.to_string(),
+            Some(ROOM.to_string()),
+            name.to_string(),
+            Some(4),
+            Some(false),
+            None,
+            None,
+            None,
+        )
+        .await;
+    let joined = timeout(Duration::from_secs(1), async {
+        loop {
+            let message = receiver.recv().await.expect("channel open");
+            if let ServerMessage::RoomJoined(payload) = &*message {
+                return payload.clone();
+            }
+        }
+    })
+    .await
+    .expect("player joins the room");
+    let token = joined.reconnection_token.expect("reconnection is enabled");
+    (player_id, receiver, joined.room_id, token)
+}
+
+fn drain(receiver: &mut mpsc::Receiver<Outbound>) -> Vec<Outbound> {
+    std::iter::from_fn(|| receiver.try_recv().ok()).collect()
+}
+
+fn hears_departure_or_return(messages: &[Outbound]) -> bool {
+    messages.iter().any(|message| {
+        matches!(
+            &**message,
+            ServerMessage::PlayerLeft { .. } | ServerMessage::PlayerReconnected { .. }
+        )
+    })
+}
+
+/// Reconnect `player` on a fresh connection, returning its `Reconnected` payload.
+async fn reconnect(
+    server: &EnhancedGameServer,
+    port: u16,
+    player: &PlayerId,
+    room_id: &RoomId,
+    token: &str,
+) -> (Box<ReconnectedPayload>, mpsc::Receiver<Outbound>) {
+    let (current, mut receiver) = connect(server, port).await;
+    server
+        .handle_reconnect(&current, player, room_id, token)
+        .await;
+    let message = timeout(Duration::from_secs(1), receiver.recv())
+        .await
+        .expect("reconnect is answered")
+        .expect("channel open");
+    let ServerMessage::Reconnected(payload) = &*message else {
+        panic!("expected Reconnected, got {message:?}");
+    };
+    (payload.clone(), receiver)
+}
+
+#[tokio::test]
+async fn reconnect_within_window_is_hidden_from_the_room() {
+    let server = create_test_server().await;
+    let (_host, mut host_rx, _, _) = join(&server, 49300, "Host").await;
+    let (player, _player_rx, room_id, token) = join(&server, 49301, "Flapper").await;
+    sleep(Duration::from_millis(20)).await;
+    drain(&mut host_rx);
+
+    server.unregister_client(&player).await;
+    let (payload, _rx) = reconnect(&server, 49302, &player, &room_id, &token).await;
+
+    assert!(payload.current_players.iter().any(|info| info.id == player));
+    sleep(WINDOW * 2).await;
+    assert!(!hears_departure_or_return(&drain(&mut host_rx)));
+    assert_eq!(server.player_flap_count(&player), 1);
+    assert_eq!(
+        server.flapping_players(10),
+        vec![PlayerFlaps {
+            player_id: player,
+            room_id,
+            flaps: 1,
+        }]
+    );
+    let snapshot = server.metrics().snapshot().await;
+    assert_eq!(snapshot.reconnection.flaps_suppressed, 1);
+}
+
+#[tokio::test]
+async fn lapsed_window_vacates_the_seat() {
+    let server = create_test_server().await;
+    let (_host, mut host_rx, _, _) = join(&server, 49310, "Host").await;
+    let (player, _player_rx, room_id, _token) = join(&server, 49311, "Dropper").await;
+    sleep(Duration::from_millis(20)).await;
+    drain(&mut host_rx);
+
+    server.unregister_client(&player).await;
+    assert!(!hears_departure_or_return(&drain(&mut host_rx)));
+
+    let left = timeout(WINDOW * 5, async {
+        loop {
+            let message = host_rx.recv().await.expect("host channel open");
+            if let ServerMessage::PlayerLeft { player_id } = &*message {
+                return *player_id;
+            }
+        }
+    })
+    .await
+    .expect("host hears PlayerLeft once the window lapses");
+    assert_eq!(left, player);
+
+    let room = server
+        .database
+        .get_room_by_id(&room_id)
+        .await
+        .unwrap()
+        .expect("host keeps the room open");
+    assert!(!room.players.contains_key(&player));
+    assert_eq!(server.player_flap_count(&player), 0);
+}
+
+#[tokio::test]
+async fn flap_count_accumulates_until_the_player_leaves() {
+    let server = create_test_server().await;
+    let (_host, _host_rx, _, _) = join(&server, 49320, "Host").await;
+    let (player, _player_rx, room_id, token) = join(&server, 49321, "Flapper").await;
+
+    server.unregister_client(&player).await;
+    let (payload, _rx) = reconnect(&server, 49322, &player, &room_id, &token).await;
+    let token = payload.reconnection_token.clone().expect("token rotated");
+    server.unregister_client(&player).await;
+    let _ = reconnect(&server, 49323, &player, &room_id, &token).await;
+
+    assert_eq!(server.player_flap_count(&player), 2);
+
+    server.leave_room(&player).await;
+    assert_eq!(server.player_flap_count(&player), 0);
+    assert!(server.flapping_players(10).is_empty());
+}
+
+#[tokio::test]
+async fn full_room_reclaims_a_ghost_seat_past_the_slot_limit() {
+    let server = create_test_server_with_config(ServerConfig {
+        flap_suppression: Duration::from_secs(30),
+        max_reconnection_slots_per_room: 1,
+        ..ServerConfig::default()
+    })
+    .await;
+    let (_host, _host_rx, _, _) = join(&server, 49330, "Host").await;
+    let (first, _first_rx, room_id, first_token) = join(&server, 49331, "First").await;
+    let (second, _second_rx, _, _) = join(&server, 49332, "Second").await;
+    let (_third, _third_rx, _, _) = join(&server, 49333, "Third").await;
+
+    // Both seats stay held, but only the latest drop keeps its reconnection slot
+    server.unregister_client(&first).await;
+    server.unregister_client(&second).await;
+    let manager = server.reconnection_manager().expect("reconnection enabled");
+    assert!(!manager.has_redeemable_reconnection(&first).await);
+    assert!(manager.has_redeemable_reconnection(&second).await);
+
+    let (newcomer, _newcomer_rx, _, _) = join(&server, 49334, "Newcomer").await;
+    let room = server
+        .database
+        .get_room_by_id(&room_id)
+        .await
+        .unwrap()
+        .expect("room exists");
+    assert!(room.players.contains_key(&newcomer));
+    assert!(!room.players.contains_key(&first));
+    assert!(room.players.contains_key(&second));
+
+    let (current, mut receiver) = connect(&server, 49335).await;
+    server
+        .handle_reconnect(&current, &first, &room_id, &first_token)
+        .await;
+    let message = timeout(Duration::from_secs(1), receiver.recv())
+        .await
+        .expect("reconnect is answered")
+        .expect("channel open");
+    assert!(matches!(
+        &*message,
+        ServerMessage::ReconnectionFailed { error_code, .. }
+            if *error_code == crate::protocol::ErrorCode::ReconnectionExpired
+    ));
+
+    let snapshot = server.metrics().snapshot().await;
+    let game = snapshot
+        .reconnection_by_game
+        .iter()
+        .find(|game| game.game_name == GAME)
+        .expect("reconnection metrics are labelled by game");
+    assert_eq!(game.ghost_slots_reclaimed, 1);
+    assert_eq!(game.failed_slot_reclaimed, 1);
+    assert_eq!(game.tokens_issued, 5);
+}
+
+#[tokio::test]
+async fn clients_without_the_reconnection_capability_get_no_token_or_held_seat() {
+    let server = create_test_server().await;
+    let (_host, mut host_rx, _, _) = join(&server, 49330, "Host").await;
+
+    let mut old_sdk_report = server
+        .protocol_config
+        .sdk_compatibility
+        .evaluate(Some("unity"), Some("1.12.0"))
+        .unwrap();
+    old_sdk_report
+        .capabilities
+        .retain(|cap| cap != crate::reconnection::RECONNECTION_CAPABILITY);
+    let (player, mut player_rx) = connect(&server, 49331).await;
+    server.set_client_sdk_report(&player, &old_sdk_report);
+    server
+        .handle_join_room(
+            &player,
+            GAME.to_string(),
+            Some(ROOM.to_string()),
+            "OldSdk".to_string(),
+            Some(4),
+            Some(false),
+            None,
+            None,
+            None,
+        )
+        .await;
+    let joined = timeout(Duration::from_secs(1), async {
+        loop {
+            let message = player_rx.recv().await.expect("channel open");
+            if let ServerMessage::RoomJoined(payload) = &*message {
+                return payload.clone();
+            }
+        }
+    })
+    .await
+    .expect("player joins the room");
+    assert!(joined.reconnection_token.is_none());
+    sleep(Duration::from_millis(20)).await;
+    drain(&mut host_rx);
+
+    server.unregister_client(&player).await;
+    let messages = drain(&mut host_rx);
+    assert!(
+        messages.iter().any(|message| matches!(
+            &**message,
+            ServerMessage::PlayerLeft { player_id } if *player_id == player
+        )),
+        "the seat is vacated at once instead of being held"
+    );
+    let snapshot = server.metrics().snapshot().await;
+    assert_eq!(snapshot.reconnection.tokens_issued, 1);
+}
@@ -1,4 +1,6 @@
-use crate::protocol::{ErrorCode, GameDataEncoding, PlayerId, RoomId, ServerMessage};
+use crate::protocol::{
+    ErrorCode, ErrorDetail, ErrorDetails, GameDataEncoding, PlayerId, RoomId, ServerMessage,
+};
 use bytes::Bytes;
 use std::sync::Arc;
 
@@ -16,10 +18,10 @@ impl EnhancedGameServer {
                 .message_coordinator
                 .send_to_player(
                     player_id,
-                    Arc::new(ServerMessage::Error {
-                        message: "Not in a room".to_string(),
-                        error_code: Some(ErrorCode::NotInRoom),
-                    }),
+                    Arc::new(ServerMessage::Error(ErrorDetail::new(
+                        ErrorCode::NotInRoom,
+                        "Not in a room",
+                    ))),
                 )
                 .await;
             return;
@@ -37,10 +39,10 @@ impl EnhancedGameServer {
                 .message_coordinator
                 .send_to_player(
                     player_id,
-                    Arc::new(ServerMessage::Error {
-                        message: "Failed to store connection info".to_string(),
-                        error_code: Some(ErrorCode::InternalError),
-                    }),
+                    Arc::new(ServerMessage::Error(ErrorDetail::new(
+                        ErrorCode::InternalError,
+                        "Failed to store connection info",
+                    ))),
                 )
                 .await;
         }
@@ -69,21 +71,31 @@ impl EnhancedGameServer {
         encoding: GameDataEncoding,
         payload: Bytes,
     ) {
-        if payload.len() > self.config.max_message_size {
+        if payload.len() > self.config().max_message_size {
             tracing::warn!(
                 %player_id,
                 payload_size = payload.len(),
-                max = self.config.max_message_size,
+                max = self.config().max_message_size,
                 "Binary game data payload exceeds maximum message size"
             );
             let _ = self
                 .send_error_to_player(
                     player_id,
-                    format!(
-                        "Binary payload exceeded maximum size ({} bytes)",
-                        self.config.max_message_size
-                    ),
-                    Some(ErrorCode::MessageTooLarge),
+                    ErrorDetail::new(
+                        ErrorCode::MessageTooLarge,
+                        format!(
+                            "Binary payload exceeded maximum size ({} bytes)",
+                            self.config().max_message_size
+                        ),
+                    )
+                    .with_context(serde_json::json!({
+                        "size": payload.len(),
+                        "max_size": self.config().max_message_size,
+                    }))
+                    .with_details(ErrorDetails::MessageTooLarge {
+                        size: payload.len(),
+                        max_size: self.config().max_message_size,
+                    }),
                 )
                 .await;
             return;
@@ -109,14 +121,37 @@ impl EnhancedGameServer {
         room_id: &RoomId,
         message: ServerMessage,
     ) {
+        if let Err(err) = self.rate_limiter.check_game_data(player_id) {
+            let _ = self
+                .send_error_to_player(
+                    player_id,
+                    ErrorDetail::new(ErrorCode::RateLimitExceeded, err.to_string())
+                        .with_retry_after(err.retry_after())
+                        .with_details(err.details()),
+                )
+                .await;
+            return;
+        }
+
         // Update last_seen with throttling (same mechanism as heartbeat)
         self.maybe_update_last_seen(player_id).await;
+        self.record_room_history(room_id, &message).await;
 
-        if let Err(e) = self
-            .message_coordinator
-            .broadcast_to_room_except(room_id, player_id, Arc::new(message))
-            .await
-        {
+        let broadcast = self.message_coordinator.broadcast_to_room_except(
+            room_id,
+            player_id,
+            Arc::new(message),
+        );
+        // Clients placed in a room without joining (test hydration) have no game
+        let result = match self.connection_manager.client_game(player_id) {
+            Some(game_name) => {
+                self.broadcast_scheduler
+                    .dispatch(&game_name, broadcast)
+                    .await
+            }
+            None => broadcast.await,
+        };
+        if let Err(e) = result {
             tracing::error!(
                 %player_id,
                 %room_id,
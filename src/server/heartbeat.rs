@@ -1,19 +1,76 @@
-use crate::protocol::{PlayerId, ServerMessage};
+use crate::protocol::{ErrorCode, ErrorDetail, HeartbeatEcho, PlayerId, RoomId, ServerMessage};
+use dashmap::DashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use super::EnhancedGameServer;
 
+/// When each room's activity was last refreshed by a heartbeat.
+///
+/// Heartbeats from every player in a room race to refresh it; the one that
+/// moves the room's timestamp forward wins and the rest are skipped without
+/// touching the database.
+pub(super) struct RoomActivityThrottle {
+    epoch: Instant,
+    /// Milliseconds since `epoch` of the last refresh, plus one so zero means never
+    last_update_ms: DashMap<RoomId, AtomicU64>,
+}
+
+impl Default for RoomActivityThrottle {
+    fn default() -> Self {
+        Self {
+            epoch: Instant::now(),
+            last_update_ms: DashMap::new(),
+        }
+    }
+}
+
+impl RoomActivityThrottle {
+    /// Claim the room's refresh if none happened in the last `threshold`.
+    pub(super) fn should_update(&self, room_id: &RoomId, threshold: Duration) -> bool {
+        let now = u64::try_from(self.epoch.elapsed().as_millis())
+            .unwrap_or(u64::MAX)
+            .saturating_add(1);
+        let threshold_ms = u64::try_from(threshold.as_millis()).unwrap_or(u64::MAX);
+        let Some(last_update) = self.last_update_ms.get(room_id) else {
+            // First heartbeat for the room; a concurrent one may have inserted it
+            return match self.last_update_ms.entry(*room_id) {
+                dashmap::Entry::Vacant(entry) => {
+                    entry.insert(AtomicU64::new(now));
+                    true
+                }
+                dashmap::Entry::Occupied(_) => false,
+            };
+        };
+        let last = last_update.load(Ordering::Acquire);
+        if now.saturating_sub(last) < threshold_ms {
+            return false;
+        }
+        last_update
+            .compare_exchange(last, now, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok()
+    }
+
+    /// Forget a room that no longer exists.
+    pub(super) fn forget(&self, room_id: &RoomId) {
+        self.last_update_ms.remove(room_id);
+    }
+}
+
 impl EnhancedGameServer {
     /// Handle ping with coordination.
     ///
-    /// Updates the in-memory ping timestamp (always) and the `last_seen` timestamp
-    /// (throttled based on `heartbeat_throttle` configuration to reduce processing overhead).
+    /// Updates the in-memory ping timestamp (always), and the `last_seen`
+    /// timestamp and room activity (throttled based on `heartbeat_throttle`
+    /// configuration to reduce processing overhead).
     pub async fn handle_ping(&self, player_id: &PlayerId) {
         // Always record the ping in memory for disconnect detection
         self.connection_manager.record_ping(player_id);
 
         // Only update last_seen if enough time has passed since last update (throttled)
         self.maybe_update_last_seen(player_id).await;
+        self.maybe_update_room_activity(player_id).await;
 
         let _ = self
             .message_coordinator
@@ -21,10 +78,175 @@ impl EnhancedGameServer {
             .await;
     }
 
+    /// Handle a timed heartbeat.
+    ///
+    /// Liveness is recorded exactly as for `Ping`. The `HeartbeatAck` is
+    /// throttled by `heartbeat_throttle` like `last_seen`, so a flood of
+    /// heartbeats yields at most one ack per window. An echo of the previous
+    /// ack becomes a round-trip sample for this connection and its region.
+    pub async fn handle_heartbeat(
+        &self,
+        player_id: &PlayerId,
+        client_timestamp: u64,
+        last_ack: Option<HeartbeatEcho>,
+    ) {
+        self.connection_manager.record_ping(player_id);
+        self.maybe_update_last_seen(player_id).await;
+        self.maybe_update_room_activity(player_id).await;
+
+        let server_timestamp = u64::try_from(chrono::Utc::now().timestamp_millis()).unwrap_or(0);
+        let Some(ack) = self.connection_manager.acknowledge_heartbeat(
+            player_id,
+            self.config().heartbeat_throttle,
+            last_ack,
+            server_timestamp,
+        ) else {
+            tracing::trace!(%player_id, "Skipped heartbeat ack (throttled)");
+            return;
+        };
+        if let Some(rtt) = ack.rtt_sample {
+            self.metrics
+                .record_connection_rtt(&self.config().region_id, rtt)
+                .await;
+        }
+
+        let _ = self
+            .message_coordinator
+            .send_to_player(
+                player_id,
+                Arc::new(ServerMessage::HeartbeatAck {
+                    client_timestamp,
+                    server_timestamp: ack.server_timestamp,
+                }),
+            )
+            .await;
+    }
+
+    /// Handle a client latency probe.
+    ///
+    /// Liveness is recorded as for `Ping`. Probes are limited to one per
+    /// second per connection; the `LatencyPong` echoes the probe and stamps
+    /// the server's receive time. A reported `last_rtt_ms` that moved by at
+    /// least `latency_update_threshold_ms` is shared with the player's room.
+    pub async fn handle_latency_ping(
+        &self,
+        player_id: &PlayerId,
+        id: u64,
+        sent_at_ms: u64,
+        last_rtt_ms: Option<u32>,
+    ) {
+        let received = Instant::now();
+        let server_received_at_ms =
+            u64::try_from(chrono::Utc::now().timestamp_millis()).unwrap_or(0);
+        self.connection_manager.record_ping(player_id);
+
+        if let Err(err) = self.rate_limiter.check_latency_ping(player_id) {
+            let _ = self
+                .send_error_to_player(
+                    player_id,
+                    ErrorDetail::new(ErrorCode::RateLimitExceeded, err.to_string())
+                        .with_retry_after(err.retry_after())
+                        .with_details(err.details()),
+                )
+                .await;
+            return;
+        }
+
+        let _ = self
+            .message_coordinator
+            .send_to_player(
+                player_id,
+                Arc::new(ServerMessage::LatencyPong {
+                    id,
+                    echo_sent_at_ms: sent_at_ms,
+                    server_received_at_ms,
+                }),
+            )
+            .await;
+        self.metrics
+            .record_response_time("latency_ping", received.elapsed())
+            .await;
+
+        if let (Some(rtt_ms), Some(threshold_ms)) =
+            (last_rtt_ms, self.config().latency_update_threshold_ms)
+        {
+            self.share_player_latency(player_id, rtt_ms, threshold_ms)
+                .await;
+        }
+    }
+
+    /// Handle a clock synchronization request.
+    ///
+    /// Answered at once with the server's receive and send times, limited by
+    /// [`TIME_SYNC_QUOTA`](crate::rate_limit::TIME_SYNC_QUOTA) per connection.
+    /// Unlike the other probes this is not liveness: it neither refreshes the
+    /// ping timeout nor `last_seen`, so a client that only syncs its clock
+    /// does not keep itself or its room alive. The send time is derived from
+    /// a monotonic clock, so it never precedes the receive time.
+    pub async fn handle_time_sync(&self, player_id: &PlayerId, client_sent_at_ms: u64) {
+        let received = Instant::now();
+        let server_received_at_ms =
+            u64::try_from(chrono::Utc::now().timestamp_millis()).unwrap_or(0);
+
+        if let Err(err) = self.rate_limiter.check_time_sync(player_id) {
+            let _ = self
+                .send_error_to_player(
+                    player_id,
+                    ErrorDetail::new(ErrorCode::RateLimitExceeded, err.to_string())
+                        .with_retry_after(err.retry_after())
+                        .with_details(err.details()),
+                )
+                .await;
+            return;
+        }
+
+        let handling_ms = u64::try_from(received.elapsed().as_millis()).unwrap_or(u64::MAX);
+        let _ = self
+            .message_coordinator
+            .send_to_player(
+                player_id,
+                Arc::new(ServerMessage::TimeSyncResult {
+                    client_sent_at_ms,
+                    server_received_at_ms,
+                    server_sent_at_ms: server_received_at_ms.saturating_add(handling_ms),
+                }),
+            )
+            .await;
+    }
+
+    /// Broadcast `PlayerLatencyUpdated` if `rtt_ms` moved far enough from the
+    /// value last shared for the player.
+    async fn share_player_latency(&self, player_id: &PlayerId, rtt_ms: u32, threshold_ms: u32) {
+        let Some(room_id) = self.get_client_room(player_id).await else {
+            return;
+        };
+        if !self
+            .connection_manager
+            .update_shared_rtt(player_id, rtt_ms, threshold_ms)
+        {
+            return;
+        }
+        let _ = self
+            .message_coordinator
+            .broadcast_to_room(
+                &room_id,
+                Arc::new(ServerMessage::PlayerLatencyUpdated {
+                    player_id: *player_id,
+                    rtt_ms,
+                }),
+            )
+            .await;
+    }
+
+    /// Smoothed heartbeat round-trip time for a connected player.
+    pub fn connection_rtt(&self, player_id: &PlayerId) -> Option<std::time::Duration> {
+        self.connection_manager.connection_rtt(player_id)
+    }
+
     /// Conditionally updates `last_seen` if the throttle threshold has elapsed.
     /// This reduces update overhead while maintaining cross-instance staleness accuracy.
     pub(super) async fn maybe_update_last_seen(&self, player_id: &PlayerId) {
-        let threshold = self.config.heartbeat_throttle;
+        let threshold = self.config().heartbeat_throttle;
 
         // If throttle is disabled (Duration::ZERO), always update
         let should_update = threshold.is_zero()
@@ -42,17 +264,37 @@ impl EnhancedGameServer {
             tracing::trace!(%player_id, "Skipped last_seen update (throttled)");
         }
     }
+
+    /// Refresh the activity of the player's room at most once per
+    /// `heartbeat_throttle` across all of its players, so heartbeats from a
+    /// full room do not queue on the rooms write lock.
+    pub(super) async fn maybe_update_room_activity(&self, player_id: &PlayerId) {
+        let Some(room_id) = self.connection_manager.get_client_room(player_id) else {
+            return;
+        };
+        let threshold = self.config().heartbeat_throttle;
+        if !threshold.is_zero() && !self.room_activity.should_update(&room_id, threshold) {
+            tracing::trace!(%player_id, %room_id, "Skipped room activity update (throttled)");
+            return;
+        }
+        if let Err(e) = self.database.update_room_activity(&room_id).await {
+            tracing::warn!(%room_id, "Failed to update room activity: {}", e);
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::broadcast::Outbound;
     use crate::config::{
         AuthMaintenanceConfig, CoordinationConfig, MetricsConfig, ProtocolConfig, RelayTypeConfig,
         TransportSecurityConfig,
     };
     use crate::database::DatabaseConfig;
-    use crate::protocol::ServerMessage;
+    use crate::protocol::{ErrorCode, HeartbeatEcho, ServerMessage};
     use crate::server::{EnhancedGameServer, ServerConfig};
+
+    use super::RoomActivityThrottle;
     use std::net::SocketAddr;
     use std::sync::Arc;
     use std::time::Duration as StdDuration;
@@ -60,11 +302,23 @@ mod tests {
     use tokio::time::{sleep, timeout, Duration};
 
     async fn create_test_server() -> Arc<EnhancedGameServer> {
+        create_test_server_with_throttle(ServerConfig::default().heartbeat_throttle).await
+    }
+
+    async fn create_test_server_with_throttle(
+        heartbeat_throttle: StdDuration,
+    ) -> Arc<EnhancedGameServer> {
+        create_test_server_with_config(ServerConfig {
+            max_connections_per_ip: 32,
+            heartbeat_throttle,
+            ..ServerConfig::default()
+        })
+        .await
+    }
+
+    async fn create_test_server_with_config(config: ServerConfig) -> Arc<EnhancedGameServer> {
         EnhancedGameServer::new(
-            ServerConfig {
-                max_connections_per_ip: 32,
-                ..ServerConfig::default()
-            },
+            config,
             ProtocolConfig::default(),
             RelayTypeConfig::default(),
             DatabaseConfig::InMemory,
@@ -119,4 +373,305 @@ mod tests {
             "ping refresh should remove player from expired set"
         );
     }
+
+    fn heartbeat_ack(message: &ServerMessage) -> (u64, u64) {
+        match message {
+            ServerMessage::HeartbeatAck {
+                client_timestamp,
+                server_timestamp,
+            } => (*client_timestamp, *server_timestamp),
+            other => panic!("expected HeartbeatAck, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn heartbeat_acks_are_throttled_and_echoes_measure_rtt() {
+        let throttle = StdDuration::from_millis(100);
+        let server = create_test_server_with_throttle(throttle).await;
+        let (sender, mut receiver) = mpsc::channel(64);
+        let addr: SocketAddr = "127.0.0.1:45001".parse().unwrap();
+        let player_id = server.register_client(sender, addr).await.unwrap();
+
+        server.handle_heartbeat(&player_id, 1_000, None).await;
+        let first = receiver.recv().await.expect("first heartbeat is acked");
+        let (client_timestamp, server_timestamp) = heartbeat_ack(&first);
+        assert_eq!(client_timestamp, 1_000);
+        assert!(server_timestamp > 0);
+
+        for offset in 1..=20 {
+            server
+                .handle_heartbeat(&player_id, 1_000 + offset, None)
+                .await;
+        }
+        sleep(Duration::from_millis(20)).await;
+        assert!(
+            receiver.try_recv().is_err(),
+            "heartbeats inside the throttle window are not acked"
+        );
+        assert_eq!(server.connection_rtt(&player_id), None);
+
+        sleep(throttle).await;
+        let echo = HeartbeatEcho {
+            server_timestamp,
+            held_ms: 100,
+        };
+        let started = std::time::Instant::now();
+        server.handle_heartbeat(&player_id, 2_000, Some(echo)).await;
+        let second = receiver
+            .recv()
+            .await
+            .expect("heartbeat after the window is acked");
+        assert_eq!(heartbeat_ack(&second).0, 2_000);
+
+        let rtt = server
+            .connection_rtt(&player_id)
+            .expect("echo of the previous ack yields an RTT sample");
+        assert!(rtt <= StdDuration::from_millis(100) + started.elapsed());
+        let snapshot = server.metrics().snapshot().await;
+        assert_eq!(snapshot.connection_rtt.len(), 1);
+        assert_eq!(snapshot.connection_rtt[0].region_id, server.region_id());
+        assert_eq!(snapshot.connection_rtt[0].rtt.sample_count, 1);
+    }
+
+    #[tokio::test]
+    async fn echo_of_an_unknown_ack_is_not_measured() {
+        let server = create_test_server_with_throttle(StdDuration::ZERO).await;
+        let (sender, mut receiver) = mpsc::channel(4);
+        let addr: SocketAddr = "127.0.0.1:45002".parse().unwrap();
+        let player_id = server.register_client(sender, addr).await.unwrap();
+
+        server.handle_heartbeat(&player_id, 1, None).await;
+        let (_, server_timestamp) = heartbeat_ack(&receiver.recv().await.unwrap());
+        let forged = HeartbeatEcho {
+            server_timestamp: server_timestamp + 1,
+            held_ms: 0,
+        };
+        server.handle_heartbeat(&player_id, 2, Some(forged)).await;
+        heartbeat_ack(&receiver.recv().await.unwrap());
+
+        assert_eq!(server.connection_rtt(&player_id), None);
+        assert!(server.metrics().snapshot().await.connection_rtt.is_empty());
+    }
+
+    #[test]
+    fn room_activity_is_claimed_once_per_window() {
+        let throttle = RoomActivityThrottle::default();
+        let room_id = uuid::Uuid::new_v4();
+        let window = StdDuration::from_millis(50);
+
+        assert!(throttle.should_update(&room_id, window));
+        assert!(!throttle.should_update(&room_id, window));
+        std::thread::sleep(window);
+        assert!(throttle.should_update(&room_id, window));
+
+        throttle.forget(&room_id);
+        assert!(throttle.should_update(&room_id, window));
+    }
+
+    async fn room_last_activity(
+        server: &EnhancedGameServer,
+        room_id: &crate::protocol::RoomId,
+    ) -> chrono::DateTime<chrono::Utc> {
+        server
+            .database
+            .get_room_by_id(room_id)
+            .await
+            .unwrap()
+            .expect("room exists")
+            .last_activity
+    }
+
+    #[tokio::test]
+    async fn heartbeats_refresh_room_activity_once_per_throttle_window() {
+        let throttle = StdDuration::from_millis(200);
+        let server = create_test_server_with_throttle(throttle).await;
+        let addr: SocketAddr = "127.0.0.1:45006".parse().unwrap();
+        let mut players = Vec::new();
+        for name in ["Host", "Guest"] {
+            let (sender, receiver) = mpsc::channel(32);
+            let player_id = server.register_client(sender, addr).await.unwrap();
+            server
+                .handle_join_room(
+                    &player_id,
+                    "activity-game".to_string(),
+                    Some("ACTVTY".to_string()),
+                    name.to_string(),
+                    Some(4),
+                    Some(false),
+                    None,
+                    None,
+                    None,
+                )
+                .await;
+            players.push((player_id, receiver));
+        }
+        let room_id = server
+            .get_client_room(&players[0].0)
+            .await
+            .expect("host is in a room");
+        let created = room_last_activity(&server, &room_id).await;
+
+        sleep(Duration::from_millis(20)).await;
+        for (player_id, _) in &players {
+            server.handle_heartbeat(player_id, 1, None).await;
+        }
+        let refreshed = room_last_activity(&server, &room_id).await;
+        assert!(
+            refreshed > created,
+            "the first heartbeat refreshes the room"
+        );
+
+        sleep(Duration::from_millis(20)).await;
+        for (player_id, _) in &players {
+            server.handle_ping(player_id).await;
+        }
+        assert_eq!(
+            room_last_activity(&server, &room_id).await,
+            refreshed,
+            "heartbeats inside the window leave the room alone"
+        );
+
+        sleep(throttle).await;
+        server.handle_heartbeat(&players[1].0, 2, None).await;
+        assert!(room_last_activity(&server, &room_id).await > refreshed);
+    }
+
+    async fn next_message(receiver: &mut mpsc::Receiver<Outbound>) -> Outbound {
+        timeout(Duration::from_secs(1), receiver.recv())
+            .await
+            .expect("message arrives in time")
+            .expect("channel still open")
+    }
+
+    #[tokio::test]
+    async fn latency_ping_is_echoed_and_limited_to_one_per_second() {
+        let server = create_test_server().await;
+        let (sender, mut receiver) = mpsc::channel(8);
+        let addr: SocketAddr = "127.0.0.1:45003".parse().unwrap();
+        let player_id = server.register_client(sender, addr).await.unwrap();
+
+        server.handle_latency_ping(&player_id, 7, 1_000, None).await;
+        match &*next_message(&mut receiver).await {
+            ServerMessage::LatencyPong {
+                id,
+                echo_sent_at_ms,
+                server_received_at_ms,
+            } => {
+                assert_eq!(*id, 7);
+                assert_eq!(*echo_sent_at_ms, 1_000);
+                assert!(*server_received_at_ms > 0);
+            }
+            other => panic!("expected LatencyPong, got {other:?}"),
+        }
+
+        server.handle_latency_ping(&player_id, 8, 1_001, None).await;
+        match &*next_message(&mut receiver).await {
+            ServerMessage::Error(error) => assert_eq!(error.code, ErrorCode::RateLimitExceeded),
+            other => panic!("expected a rate limit error, got {other:?}"),
+        }
+
+        let snapshot = server.metrics().snapshot().await;
+        assert_eq!(snapshot.performance.latency_ping_processing.sample_count, 1);
+    }
+
+    #[tokio::test]
+    async fn time_sync_timestamps_are_monotonic_and_requests_are_throttled() {
+        let server = create_test_server().await;
+        let (sender, mut receiver) = mpsc::channel(8);
+        let addr: SocketAddr = "127.0.0.1:45005".parse().unwrap();
+        let player_id = server.register_client(sender, addr).await.unwrap();
+        sleep(Duration::from_millis(10)).await;
+
+        for _ in 0..crate::rate_limit::TIME_SYNC_QUOTA.burst {
+            let client_sent_at_ms = u64::try_from(chrono::Utc::now().timestamp_millis()).unwrap();
+            server.handle_time_sync(&player_id, client_sent_at_ms).await;
+            match &*next_message(&mut receiver).await {
+                ServerMessage::TimeSyncResult {
+                    client_sent_at_ms: echoed,
+                    server_received_at_ms,
+                    server_sent_at_ms,
+                } => {
+                    assert_eq!(*echoed, client_sent_at_ms);
+                    assert!(client_sent_at_ms <= *server_received_at_ms);
+                    assert!(server_received_at_ms <= server_sent_at_ms);
+                }
+                other => panic!("expected TimeSyncResult, got {other:?}"),
+            }
+        }
+
+        server.handle_time_sync(&player_id, 0).await;
+        match &*next_message(&mut receiver).await {
+            ServerMessage::Error(error) => {
+                assert_eq!(error.code, ErrorCode::RateLimitExceeded);
+                assert!(error.retry_after_ms.is_some());
+            }
+            other => panic!("expected a rate limit error, got {other:?}"),
+        }
+        assert_eq!(
+            server
+                .connection_manager
+                .collect_expired_clients(StdDuration::from_millis(5)),
+            vec![player_id],
+            "time sync is not a heartbeat"
+        );
+    }
+
+    #[tokio::test]
+    async fn reported_latency_is_shared_with_the_room_past_the_threshold() {
+        let server = create_test_server_with_config(ServerConfig {
+            max_connections_per_ip: 32,
+            latency_update_threshold_ms: Some(20),
+            ..ServerConfig::default()
+        })
+        .await;
+        let addr: SocketAddr = "127.0.0.1:45004".parse().unwrap();
+        let (host_sender, mut host_rx) = mpsc::channel(32);
+        let host = server.register_client(host_sender, addr).await.unwrap();
+        let (guest_sender, mut guest_rx) = mpsc::channel(32);
+        let guest = server.register_client(guest_sender, addr).await.unwrap();
+
+        for (player_id, name) in [(&host, "Host"), (&guest, "Guest")] {
+            server
+                .handle_join_room(
+                    player_id,
+                    "latency-game".to_string(),
+                    Some("LATNCY".to_string()),
+                    name.to_string(),
+                    Some(4),
+                    Some(false),
+                    None,
+                    None,
+                    None,
+                )
+                .await;
+        }
+        sleep(Duration::from_millis(50)).await;
+        while host_rx.try_recv().is_ok() {}
+        while guest_rx.try_recv().is_ok() {}
+
+        for (rtt_ms, shared) in [(50, true), (60, false), (80, true)] {
+            server
+                .handle_latency_ping(&guest, rtt_ms.into(), 0, Some(rtt_ms))
+                .await;
+            assert!(matches!(
+                *next_message(&mut guest_rx).await,
+                ServerMessage::LatencyPong { .. }
+            ));
+            sleep(Duration::from_millis(50)).await;
+            let update = host_rx.try_recv().ok();
+            match update.as_deref() {
+                Some(ServerMessage::PlayerLatencyUpdated {
+                    player_id,
+                    rtt_ms: reported,
+                }) if shared => {
+                    assert_eq!(*player_id, guest);
+                    assert_eq!(*reported, rtt_ms);
+                }
+                None if !shared => {}
+                other => panic!("rtt {rtt_ms}: unexpected {other:?}"),
+            }
+            while guest_rx.try_recv().is_ok() {}
+            sleep(Duration::from_millis(1_000)).await;
+        }
+    }
 }
@@ -0,0 +1,74 @@
+//! Per-room game data history, replayed to players who join or reconnect
+//! mid-game so they can catch up on recent events.
+
+use crate::protocol::{GameDataEncoding, HistoricalMessage, PlayerId, RoomId, ServerMessage};
+use bytes::Bytes;
+use std::sync::Arc;
+
+use super::EnhancedGameServer;
+
+impl EnhancedGameServer {
+    /// Store a relayed game data message in the room's history, if replay is enabled.
+    pub(super) async fn record_room_history(&self, room_id: &RoomId, message: &ServerMessage) {
+        if !self.config().enable_history_replay {
+            return;
+        }
+        let Some(entry) = historical_message(message) else {
+            return;
+        };
+        if let Err(err) = self
+            .database
+            .append_room_history(room_id, entry, self.config().room_history_size)
+            .await
+        {
+            tracing::warn!(%room_id, error = %err, "Failed to record room message history");
+        }
+    }
+
+    /// Send the room's history to a player that just received `RoomJoined` or
+    /// `Reconnected`. Nothing is sent when replay is disabled or the history is empty.
+    pub(super) async fn replay_room_history(&self, player_id: &PlayerId, room_id: &RoomId) {
+        if !self.config().enable_history_replay {
+            return;
+        }
+        let messages = match self.database.get_room_history(room_id).await {
+            Ok(messages) => messages,
+            Err(err) => {
+                tracing::warn!(%player_id, %room_id, error = %err, "Failed to load room message history");
+                return;
+            }
+        };
+        if messages.is_empty() {
+            return;
+        }
+        let _ = self
+            .message_coordinator
+            .send_to_player(
+                player_id,
+                Arc::new(ServerMessage::HistoryReplay { messages }),
+            )
+            .await;
+    }
+}
+
+fn historical_message(message: &ServerMessage) -> Option<HistoricalMessage> {
+    let (from_player, encoding, payload) = match message {
+        ServerMessage::GameData { from_player, data } => (
+            *from_player,
+            GameDataEncoding::Json,
+            Bytes::from(serde_json::to_vec(data).ok()?),
+        ),
+        ServerMessage::GameDataBinary {
+            from_player,
+            encoding,
+            payload,
+        } => (*from_player, *encoding, payload.clone()),
+        _ => return None,
+    };
+    Some(HistoricalMessage {
+        timestamp: chrono::Utc::now(),
+        from_player,
+        encoding,
+        payload,
+    })
+}
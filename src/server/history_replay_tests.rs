@@ -0,0 +1,133 @@
+use super::*;
+use crate::config::{
+    AuthMaintenanceConfig, CoordinationConfig, MetricsConfig, ProtocolConfig, RelayTypeConfig,
+    TransportSecurityConfig,
+};
+use crate::database::DatabaseConfig;
+use crate::protocol::{HistoricalMessage, ServerMessage};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::time::{sleep, timeout, Duration};
+
+const GAME: &str = "history-game";
+const ROOM_CODE: &str = "HIST01";
+
+async fn create_test_server(enable_history_replay: bool) -> Arc<EnhancedGameServer> {
+    EnhancedGameServer::new(
+        ServerConfig {
+            enable_history_replay,
+            room_history_size: 3,
+            ..ServerConfig::default()
+        },
+        ProtocolConfig::default(),
+        RelayTypeConfig::default(),
+        DatabaseConfig::InMemory,
+        MetricsConfig::default(),
+        AuthMaintenanceConfig::default(),
+        CoordinationConfig::default(),
+        TransportSecurityConfig::default(),
+        Vec::new(),
+    )
+    .await
+    .expect("failed to construct test server")
+}
+
+async fn join(
+    server: &EnhancedGameServer,
+    port: u16,
+    name: &str,
+) -> (PlayerId, mpsc::Receiver<Outbound>) {
+    let (sender, mut receiver) = mpsc::channel(32);
+    let addr: SocketAddr = format!("127.0.0.1:{port}").parse().unwrap();
+    let player_id = server
+        .connection_manager
+        .register_client(sender, addr, server.instance_id)
+        .await
+        .expect("client registration succeeds");
+    server
+        .handle_join_room(
+            &player_id,
+            GAME.to_string(),
+            Some(ROOM_CODE.to_string()),
+            name.to_string(),
+            Some(4),
+            Some(false),
+            None,
+            None,
+            None,
+        )
+        .await;
+    let joined = timeout(Duration::from_secs(1), receiver.recv())
+        .await
+        .expect("RoomJoined arrives in time")
+        .expect("channel still open");
+    assert!(matches!(&*joined, ServerMessage::RoomJoined(_)));
+    (player_id, receiver)
+}
+
+fn replayed(receiver: &mut mpsc::Receiver<Outbound>) -> Option<Vec<HistoricalMessage>> {
+    std::iter::from_fn(|| receiver.try_recv().ok()).find_map(|msg| match &*msg {
+        ServerMessage::HistoryReplay { messages } => Some(messages.clone()),
+        _ => None,
+    })
+}
+
+#[tokio::test]
+async fn late_joiner_receives_the_most_recent_history_after_room_joined() {
+    let server = create_test_server(true).await;
+    let (host, _host_rx) = join(&server, 48800, "Host").await;
+
+    for turn in 0..5 {
+        server
+            .handle_game_data(&host, serde_json::json!({ "turn": turn }))
+            .await;
+    }
+    server
+        .handle_game_data_binary(&host, GameDataEncoding::MessagePack, vec![1, 2, 3].into())
+        .await;
+
+    let (_guest, mut guest_rx) = join(&server, 48801, "Guest").await;
+    sleep(Duration::from_millis(20)).await;
+    let history = replayed(&mut guest_rx).expect("guest receives HistoryReplay");
+
+    assert_eq!(history.len(), 3, "history is capped at room_history_size");
+    assert!(history.iter().all(|entry| entry.from_player == host));
+    let turns: Vec<serde_json::Value> = history[..2]
+        .iter()
+        .map(|entry| {
+            assert_eq!(entry.encoding, GameDataEncoding::Json);
+            serde_json::from_slice(&entry.payload).unwrap()
+        })
+        .collect();
+    assert_eq!(
+        turns,
+        vec![
+            serde_json::json!({ "turn": 3 }),
+            serde_json::json!({ "turn": 4 })
+        ]
+    );
+    assert_eq!(history[2].encoding, GameDataEncoding::MessagePack);
+    assert_eq!(history[2].payload.as_ref(), &[1, 2, 3]);
+    assert!(history[0].timestamp <= history[2].timestamp);
+}
+
+#[tokio::test]
+async fn history_is_neither_kept_nor_replayed_when_disabled() {
+    let server = create_test_server(false).await;
+    let (host, _host_rx) = join(&server, 48810, "Host").await;
+    server
+        .handle_game_data(&host, serde_json::json!({ "turn": 1 }))
+        .await;
+
+    let (_guest, mut guest_rx) = join(&server, 48811, "Guest").await;
+    sleep(Duration::from_millis(20)).await;
+    assert!(replayed(&mut guest_rx).is_none());
+
+    let room_id = server
+        .get_client_room(&host)
+        .await
+        .expect("host is in a room");
+    let history = server.database().get_room_history(&room_id).await.unwrap();
+    assert!(history.is_empty());
+}
@@ -0,0 +1,59 @@
+//! ICE servers handed to players in `RoomJoined`, with TURN REST credentials
+//! generated per player when a shared secret is configured.
+
+use chrono::Utc;
+
+use super::EnhancedGameServer;
+use crate::protocol::{IceServer, PlayerId};
+use crate::security::turn_rest_credentials;
+
+impl EnhancedGameServer {
+    /// The configured ICE servers as sent to `player_id`.
+    ///
+    /// Servers with a static username keep it. TURN servers without one get
+    /// credentials derived from `security.transport.tls.turn_shared_secret`
+    /// for this player, or none when no secret is set. STUN servers never
+    /// carry credentials.
+    pub(crate) fn ice_servers_for(&self, player_id: &PlayerId) -> Vec<IceServer> {
+        let config = self.config();
+        if config.ice_servers.is_empty() {
+            return Vec::new();
+        }
+        let tls = &self.transport_security.tls;
+        let mut generated = None;
+        config
+            .ice_servers
+            .iter()
+            .map(|server| {
+                let (username, credential) = match (&server.username, &server.credential) {
+                    (Some(username), Some(credential)) => {
+                        (Some(username.clone()), Some(credential.clone()))
+                    }
+                    _ => match tls.turn_shared_secret.as_deref() {
+                        Some(secret) if server.is_turn() => {
+                            // One set of credentials covers every TURN server sharing the secret
+                            let credentials = generated.get_or_insert_with(|| {
+                                turn_rest_credentials(
+                                    secret,
+                                    &player_id.to_string(),
+                                    tls.turn_credential_ttl_secs.as_duration(),
+                                    Utc::now(),
+                                )
+                            });
+                            (
+                                Some(credentials.username.clone()),
+                                Some(credentials.credential.clone()),
+                            )
+                        }
+                        _ => (None, None),
+                    },
+                };
+                IceServer {
+                    urls: server.urls.clone(),
+                    username,
+                    credential,
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,164 @@
+use super::*;
+use crate::config::{
+    AuthMaintenanceConfig, ConfigDuration, CoordinationConfig, IceServerConfig, MetricsConfig,
+    ProtocolConfig, RelayTypeConfig, TlsServerConfig, TransportSecurityConfig,
+};
+use crate::database::DatabaseConfig;
+use crate::protocol::{RoomJoinedPayload, ServerMessage};
+use crate::security::turn_rest_password;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+const SECRET: &str = "turn-shared-secret";
+
+fn ice_server(url: &str) -> IceServerConfig {
+    IceServerConfig {
+        urls: vec![url.to_string()],
+        username: None,
+        credential: None,
+    }
+}
+
+async fn create_test_server(
+    ice_servers: Vec<IceServerConfig>,
+    turn_shared_secret: Option<&str>,
+) -> Arc<EnhancedGameServer> {
+    let transport_security = TransportSecurityConfig {
+        tls: TlsServerConfig {
+            turn_shared_secret: turn_shared_secret.map(str::to_string),
+            turn_credential_ttl_secs: ConfigDuration::from_secs(600),
+            ..TlsServerConfig::default()
+        },
+        ..TransportSecurityConfig::default()
+    };
+    EnhancedGameServer::new(
+        ServerConfig {
+            ice_servers,
+            ..ServerConfig::default()
+        },
+        ProtocolConfig::default(),
+        RelayTypeConfig::default(),
+        DatabaseConfig::InMemory,
+        MetricsConfig::default(),
+        AuthMaintenanceConfig::default(),
+        CoordinationConfig::default(),
+        transport_security,
+        Vec::new(),
+    )
+    .await
+    .expect("failed to construct test server")
+}
+
+async fn join(server: &EnhancedGameServer, index: u8) -> (PlayerId, RoomJoinedPayload) {
+    let (sender, mut receiver) = mpsc::channel(64);
+    let addr: SocketAddr = format!("10.5.0.{}:4000", index + 1).parse().unwrap();
+    let player_id = server
+        .connection_manager
+        .register_client(sender, addr, server.instance_id)
+        .await
+        .expect("client registration succeeds");
+    server
+        .handle_join_room(
+            &player_id,
+            "ice-game".to_string(),
+            Some("ICE001".to_string()),
+            format!("Player{index}"),
+            Some(4),
+            Some(true),
+            None,
+            None,
+            None,
+        )
+        .await;
+    while let Ok(message) = receiver.try_recv() {
+        if let ServerMessage::RoomJoined(payload) = &*message {
+            return (player_id, (**payload).clone());
+        }
+    }
+    panic!("no RoomJoined was sent");
+}
+
+#[tokio::test]
+async fn turn_servers_get_per_player_rest_credentials() {
+    let server = create_test_server(
+        vec![
+            ice_server("stun:stun.example.com:3478"),
+            IceServerConfig {
+                urls: vec![
+                    "turn:turn.example.com:3478".to_string(),
+                    "turns:turn.example.com:5349".to_string(),
+                ],
+                username: None,
+                credential: None,
+            },
+        ],
+        Some(SECRET),
+    )
+    .await;
+
+    let (host, joined) = join(&server, 0).await;
+    let entry = joined.peer_connection.expect("ICE servers are sent");
+    assert_eq!(entry.player_id, host);
+    assert_eq!(entry.player_name, "Player0");
+    assert!(entry.is_authority);
+
+    let [stun, turn] = entry.ice_servers.as_slice() else {
+        panic!("expected two ICE servers, got {:?}", entry.ice_servers);
+    };
+    assert_eq!(stun.urls, vec!["stun:stun.example.com:3478".to_string()]);
+    assert_eq!(stun.username, None);
+    assert_eq!(stun.credential, None);
+
+    let username = turn.username.as_deref().expect("TURN username");
+    let (expiry, user) = username.split_once(':').expect("expiry:user");
+    assert_eq!(user, host.to_string());
+    let expires_in = expiry.parse::<i64>().unwrap() - chrono::Utc::now().timestamp();
+    assert!(
+        (590..=600).contains(&expires_in),
+        "expires in {expires_in}s"
+    );
+    assert_eq!(
+        turn.credential.as_deref(),
+        Some(turn_rest_password(SECRET, username).as_str())
+    );
+
+    // Each player gets credentials of their own
+    let (guest, joined) = join(&server, 1).await;
+    let entry = joined.peer_connection.expect("ICE servers are sent");
+    let guest_username = entry.ice_servers[1].username.as_deref().unwrap();
+    assert!(guest_username.ends_with(&format!(":{guest}")));
+}
+
+#[tokio::test]
+async fn static_credentials_are_kept_and_missing_secrets_send_none() {
+    let server = create_test_server(
+        vec![
+            IceServerConfig {
+                urls: vec!["turn:static.example.com".to_string()],
+                username: Some("user".to_string()),
+                credential: Some("pass".to_string()),
+            },
+            ice_server("turn:rest.example.com"),
+        ],
+        None,
+    )
+    .await;
+
+    let (_, joined) = join(&server, 0).await;
+    let servers = joined
+        .peer_connection
+        .expect("ICE servers are sent")
+        .ice_servers;
+    assert_eq!(servers[0].username.as_deref(), Some("user"));
+    assert_eq!(servers[0].credential.as_deref(), Some("pass"));
+    assert_eq!(servers[1].username, None);
+    assert_eq!(servers[1].credential, None);
+}
+
+#[tokio::test]
+async fn room_joined_has_no_peer_connection_without_ice_servers() {
+    let server = create_test_server(Vec::new(), Some(SECRET)).await;
+    let (_, joined) = join(&server, 0).await;
+    assert!(joined.peer_connection.is_none());
+}
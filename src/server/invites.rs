@@ -0,0 +1,202 @@
+//! Room invites: `CreateInvite` issues a token for the sender's room and
+//! `JoinByInvite` redeems it to join that room without its code.
+
+use chrono::Utc;
+use std::sync::Arc;
+
+use super::EnhancedGameServer;
+use crate::invites::{
+    CreateInviteError, InviteError, MAX_INVITES_PER_ROOM, MAX_INVITE_TTL_SECS, MAX_INVITE_USES,
+};
+use crate::protocol::{
+    ErrorCode, ErrorDetail, ErrorDetails, PlayerId, ServerMessage, ValidationConstraint,
+};
+
+impl EnhancedGameServer {
+    /// Handle a player asking for an invite to their room.
+    ///
+    /// Any player in the room may create invites unless the room is set to
+    /// `invites_authority_only`. The token is sent back in `InviteCreated`.
+    pub async fn handle_create_invite(
+        &self,
+        requester_id: &PlayerId,
+        max_uses: u32,
+        ttl_secs: u64,
+    ) {
+        let invalid = if !(1..=MAX_INVITE_USES).contains(&max_uses) {
+            Some((
+                "max_uses",
+                format!("max_uses must be between 1 and {MAX_INVITE_USES}"),
+                u64::from(MAX_INVITE_USES),
+            ))
+        } else if !(1..=MAX_INVITE_TTL_SECS).contains(&ttl_secs) {
+            Some((
+                "ttl_secs",
+                format!("ttl_secs must be between 1 and {MAX_INVITE_TTL_SECS}"),
+                MAX_INVITE_TTL_SECS,
+            ))
+        } else {
+            None
+        };
+        if let Some((field, reason, max)) = invalid {
+            let _ = self
+                .send_error_to_player(
+                    requester_id,
+                    ErrorDetail::new(ErrorCode::InvalidInput, reason).with_details(
+                        ErrorDetails::validation(field, ValidationConstraint::Range, Some(max)),
+                    ),
+                )
+                .await;
+            return;
+        }
+
+        let Some(room) = self.requester_room(requester_id).await else {
+            return;
+        };
+        if !room.players.contains_key(requester_id) {
+            let _ = self
+                .send_error_to_player(
+                    requester_id,
+                    ErrorDetail::new(ErrorCode::NotInRoom, "Only players can create invites"),
+                )
+                .await;
+            return;
+        }
+        if room.invites_authority_only && room.authority_player != Some(*requester_id) {
+            let _ = self
+                .send_error_to_player(
+                    requester_id,
+                    ErrorDetail::new(
+                        ErrorCode::AuthorityDenied,
+                        "Only the room authority can create invites for this room",
+                    ),
+                )
+                .await;
+            return;
+        }
+
+        let expires_at = Utc::now() + chrono::Duration::seconds(ttl_secs as i64);
+        let token = match self.invites.create(
+            room.id,
+            room.game_name.clone(),
+            room.code.clone(),
+            max_uses,
+            expires_at,
+        ) {
+            Ok((token, _)) => token,
+            Err(CreateInviteError::TooManyInvites) => {
+                let _ = self
+                    .send_error_to_player(
+                        requester_id,
+                        ErrorDetail::new(
+                            ErrorCode::InvalidInput,
+                            format!(
+                                "A room can have at most {MAX_INVITES_PER_ROOM} active invites"
+                            ),
+                        )
+                        .with_details(ErrorDetails::validation(
+                            "invites",
+                            ValidationConstraint::Limits,
+                            Some(MAX_INVITES_PER_ROOM as u64),
+                        )),
+                    )
+                    .await;
+                return;
+            }
+        };
+        self.metrics.increment_invites_created();
+        tracing::info!(room_id = %room.id, requester = %requester_id, max_uses, ttl_secs, "Invite created");
+
+        let _ = self
+            .message_coordinator
+            .send_to_player(
+                requester_id,
+                Arc::new(ServerMessage::InviteCreated {
+                    token,
+                    room_code: room.code,
+                    max_uses,
+                    expires_at_ms: u64::try_from(expires_at.timestamp_millis()).unwrap_or(0),
+                }),
+            )
+            .await;
+    }
+
+    /// Handle a player joining the room behind an invite token.
+    ///
+    /// One use is taken before the join and given back if the join does not
+    /// seat the player, e.g. because the room is full.
+    pub async fn handle_join_by_invite(
+        &self,
+        player_id: &PlayerId,
+        token: &str,
+        player_name: String,
+    ) {
+        if self.get_client_room(player_id).await.is_some() {
+            self.send_invite_join_failed(player_id, ErrorCode::AlreadyInRoom, "Already in a room")
+                .await;
+            return;
+        }
+
+        let invite = match self.invites.redeem(token, Utc::now()) {
+            Ok(invite) => invite,
+            Err(error) => {
+                let (code, reason) = match error {
+                    InviteError::NotFound => (ErrorCode::InviteNotFound, "Invite not found"),
+                    InviteError::Expired => (ErrorCode::InviteExpired, "Invite has expired"),
+                    InviteError::Exhausted => {
+                        (ErrorCode::InviteExhausted, "Invite has no uses left")
+                    }
+                };
+                self.send_invite_join_failed(player_id, code, reason).await;
+                return;
+            }
+        };
+
+        self.join_room_request(
+            player_id,
+            invite.game_name.clone(),
+            Some(invite.room_code.clone()),
+            player_name,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            false,
+        )
+        .await;
+
+        if self.get_client_room(player_id).await == Some(invite.room_id) {
+            self.metrics.increment_invites_redeemed();
+            tracing::info!(%player_id, room_id = %invite.room_id, remaining_uses = invite.remaining_uses(), "Invite redeemed");
+        } else {
+            invite.refund();
+        }
+    }
+
+    /// Drop invites past their TTL; run by the cleanup task.
+    pub(crate) fn purge_expired_invites(&self) {
+        let expired = self.invites.purge_expired(Utc::now());
+        if expired > 0 {
+            self.metrics.add_invites_expired(expired as u64);
+            tracing::debug!(expired, "Purged expired invites");
+        }
+    }
+
+    async fn send_invite_join_failed(&self, player_id: &PlayerId, code: ErrorCode, reason: &str) {
+        let _ = self
+            .message_coordinator
+            .send_to_player(
+                player_id,
+                Arc::new(ServerMessage::RoomJoinFailed {
+                    reason: reason.to_string(),
+                    error_code: Some(code),
+                    retry_after_secs: None,
+                    details: None,
+                }),
+            )
+            .await;
+    }
+}
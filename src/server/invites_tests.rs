@@ -0,0 +1,249 @@
+use super::*;
+use crate::config::{
+    AuthMaintenanceConfig, CoordinationConfig, MetricsConfig, ProtocolConfig, RelayTypeConfig,
+    TransportSecurityConfig,
+};
+use crate::database::DatabaseConfig;
+use crate::protocol::{ErrorCode, ErrorDetail, ServerMessage};
+use std::net::SocketAddr;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+const GAME: &str = "invite-game";
+const ROOM: &str = "INVT01";
+
+async fn create_test_server() -> Arc<EnhancedGameServer> {
+    EnhancedGameServer::new(
+        ServerConfig::default(),
+        ProtocolConfig::default(),
+        RelayTypeConfig::default(),
+        DatabaseConfig::InMemory,
+        MetricsConfig::default(),
+        AuthMaintenanceConfig::default(),
+        CoordinationConfig::default(),
+        TransportSecurityConfig::default(),
+        Vec::new(),
+    )
+    .await
+    .expect("failed to construct test server")
+}
+
+async fn register(server: &EnhancedGameServer, index: u8) -> (PlayerId, mpsc::Receiver<Outbound>) {
+    let (sender, receiver) = mpsc::channel(64);
+    let addr: SocketAddr = format!("10.4.0.{}:4000", index + 1).parse().unwrap();
+    let player_id = server
+        .connection_manager
+        .register_client(sender, addr, server.instance_id)
+        .await
+        .expect("client registration succeeds");
+    (player_id, receiver)
+}
+
+async fn host_room(
+    server: &EnhancedGameServer,
+    max_players: u8,
+) -> (PlayerId, mpsc::Receiver<Outbound>) {
+    let (host, mut host_rx) = register(server, 0).await;
+    server
+        .handle_join_room(
+            &host,
+            GAME.to_string(),
+            Some(ROOM.to_string()),
+            "Host".to_string(),
+            Some(max_players),
+            Some(true),
+            None,
+            None,
+            None,
+        )
+        .await;
+    drain(&mut host_rx);
+    (host, host_rx)
+}
+
+fn drain(receiver: &mut mpsc::Receiver<Outbound>) {
+    while receiver.try_recv().is_ok() {}
+}
+
+async fn create_invite(
+    server: &EnhancedGameServer,
+    player_id: &PlayerId,
+    receiver: &mut mpsc::Receiver<Outbound>,
+    max_uses: u32,
+) -> String {
+    server.handle_create_invite(player_id, max_uses, 300).await;
+    match &*receiver.try_recv().expect("invite response") {
+        ServerMessage::InviteCreated {
+            token,
+            room_code,
+            max_uses: granted,
+            ..
+        } => {
+            assert_eq!(room_code, ROOM);
+            assert_eq!(*granted, max_uses);
+            token.clone()
+        }
+        other => panic!("expected InviteCreated, got {other:?}"),
+    }
+}
+
+fn join_outcome(receiver: &mut mpsc::Receiver<Outbound>) -> Result<(), ErrorCode> {
+    while let Ok(message) = receiver.try_recv() {
+        match &*message {
+            ServerMessage::RoomJoined(_) => return Ok(()),
+            ServerMessage::RoomJoinFailed { error_code, .. } => {
+                return Err(error_code.clone().expect("join failures carry a code"));
+            }
+            _ => {}
+        }
+    }
+    panic!("no join outcome was sent");
+}
+
+#[tokio::test]
+async fn invites_seat_players_until_their_uses_run_out() {
+    let server = create_test_server().await;
+    let (host, mut host_rx) = host_room(&server, 8).await;
+    let token = create_invite(&server, &host, &mut host_rx, 2).await;
+
+    for index in 1..=2 {
+        let (guest, mut guest_rx) = register(&server, index).await;
+        server
+            .handle_join_by_invite(&guest, &token, format!("Guest{index}"))
+            .await;
+        assert_eq!(join_outcome(&mut guest_rx), Ok(()));
+    }
+    let (late, mut late_rx) = register(&server, 3).await;
+    server
+        .handle_join_by_invite(&late, &token, "Late".to_string())
+        .await;
+    assert_eq!(join_outcome(&mut late_rx), Err(ErrorCode::InviteExhausted));
+
+    server
+        .handle_join_by_invite(&late, "not.atoken", "Late".to_string())
+        .await;
+    assert_eq!(join_outcome(&mut late_rx), Err(ErrorCode::InviteNotFound));
+
+    assert_eq!(server.metrics.invites_created.load(Ordering::Relaxed), 1);
+    assert_eq!(server.metrics.invites_redeemed.load(Ordering::Relaxed), 2);
+}
+
+#[tokio::test]
+async fn failed_joins_do_not_use_up_the_invite() {
+    let server = create_test_server().await;
+    let (host, mut host_rx) = host_room(&server, 2).await;
+    let token = create_invite(&server, &host, &mut host_rx, 1).await;
+
+    let (seated, _seated_rx) = register(&server, 1).await;
+    server
+        .handle_join_room(
+            &seated,
+            GAME.to_string(),
+            Some(ROOM.to_string()),
+            "Seated".to_string(),
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .await;
+    let (guest, mut guest_rx) = register(&server, 2).await;
+    server
+        .handle_join_by_invite(&guest, &token, "Guest".to_string())
+        .await;
+    assert_eq!(join_outcome(&mut guest_rx), Err(ErrorCode::RoomFull));
+
+    // Once a seat frees up, the same single-use invite still works
+    server.leave_room(&seated).await;
+    server
+        .handle_join_by_invite(&guest, &token, "Guest".to_string())
+        .await;
+    assert_eq!(join_outcome(&mut guest_rx), Ok(()));
+    assert_eq!(server.metrics.invites_redeemed.load(Ordering::Relaxed), 1);
+}
+
+#[tokio::test]
+async fn authority_only_rooms_refuse_invites_from_other_players() {
+    let server = create_test_server().await;
+    let (host, mut host_rx) = host_room(&server, 4).await;
+    let (guest, mut guest_rx) = register(&server, 1).await;
+    let token = create_invite(&server, &host, &mut host_rx, 1).await;
+    server
+        .handle_join_by_invite(&guest, &token, "Guest".to_string())
+        .await;
+    assert_eq!(join_outcome(&mut guest_rx), Ok(()));
+    drain(&mut host_rx);
+    drain(&mut guest_rx);
+
+    server
+        .handle_update_room_settings(&host, None, None, Some(true))
+        .await;
+    let update = guest_rx.try_recv().expect("settings are broadcast");
+    assert!(
+        matches!(
+            &*update,
+            ServerMessage::RoomSettingsUpdated {
+                invites_authority_only: true,
+                ..
+            }
+        ),
+        "expected RoomSettingsUpdated, got {update:?}"
+    );
+
+    server.handle_create_invite(&guest, 1, 300).await;
+    let refused = guest_rx.try_recv().expect("the guest is refused");
+    assert!(
+        matches!(
+            &*refused,
+            ServerMessage::Error(ErrorDetail {
+                code: ErrorCode::AuthorityDenied,
+                ..
+            })
+        ),
+        "expected AUTHORITY_DENIED, got {refused:?}"
+    );
+    drain(&mut host_rx);
+    create_invite(&server, &host, &mut host_rx, 1).await;
+
+    server.handle_create_invite(&host, 0, 300).await;
+    let invalid = host_rx.try_recv().expect("invalid uses are rejected");
+    assert!(matches!(
+        &*invalid,
+        ServerMessage::Error(ErrorDetail {
+            code: ErrorCode::InvalidInput,
+            ..
+        })
+    ));
+}
+
+#[tokio::test]
+async fn invites_stop_working_when_the_room_is_deleted_or_they_expire() {
+    let server = create_test_server().await;
+    let (host, mut host_rx) = host_room(&server, 4).await;
+    let token = create_invite(&server, &host, &mut host_rx, 5).await;
+    server
+        .invites
+        .create(
+            Uuid::new_v4(),
+            GAME.to_string(),
+            "GONE01".to_string(),
+            1,
+            chrono::Utc::now() - chrono::Duration::seconds(1),
+        )
+        .expect("invite is created");
+    server.purge_expired_invites();
+    assert_eq!(server.metrics.invites_expired.load(Ordering::Relaxed), 1);
+
+    server
+        .admin_close_room(GAME, ROOM)
+        .await
+        .expect("room closes");
+    assert!(server.invites.is_empty());
+    let (guest, mut guest_rx) = register(&server, 1).await;
+    server
+        .handle_join_by_invite(&guest, &token, "Guest".to_string())
+        .await;
+    assert_eq!(join_outcome(&mut guest_rx), Err(ErrorCode::InviteNotFound));
+}
@@ -0,0 +1,172 @@
+//! Runs the embedder's [`JoinInterceptor`] before a player or spectator is
+//! added to a room.
+
+use crate::config::JoinInterceptorFailurePolicy;
+use crate::database::RoomMetadataUpdate;
+use crate::join_interceptor::{
+    JoinContext, JoinDecision, JoinInterceptor, JoinModifications, JoinRoomSummary,
+};
+use crate::protocol::{validation, ErrorCode, PlayerId, Room, ServerMessage};
+use std::sync::{Arc, PoisonError};
+use std::time::Instant;
+
+use super::EnhancedGameServer;
+
+impl EnhancedGameServer {
+    /// Install the hook consulted before players and spectators join a room.
+    ///
+    /// Call before serving traffic; joins already waiting on the previous hook
+    /// finish with its decision.
+    pub fn set_join_interceptor(&self, interceptor: Arc<dyn JoinInterceptor>) {
+        *self
+            .join_interceptor
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = interceptor;
+    }
+
+    fn join_interceptor(&self) -> Arc<dyn JoinInterceptor> {
+        self.join_interceptor
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Ask the join interceptor whether a join may go ahead.
+    ///
+    /// Errors and timeouts are resolved by `join_interceptor_failure_policy`,
+    /// so the result is always `Allow`, `Deny` or `AllowWithModifications`.
+    pub(super) async fn intercept_join(
+        &self,
+        player_id: &PlayerId,
+        game_name: &str,
+        room_code: &str,
+        player_name: &str,
+        spectator: bool,
+    ) -> JoinDecision {
+        let room = match self.database.get_room(game_name, room_code).await {
+            Ok(room) => room.as_ref().map(JoinRoomSummary::for_room),
+            Err(err) => {
+                tracing::warn!(%player_id, game_name, room_code, error = %err, "Failed to load room for join interceptor");
+                None
+            }
+        };
+        let ctx = JoinContext {
+            game_name: game_name.to_string(),
+            room_code: room_code.to_string(),
+            room,
+            player_id: *player_id,
+            player_name: player_name.to_string(),
+            app_info: self.client_app_info(player_id),
+            client_ip: self.connection_manager.client_ip(player_id),
+            spectator,
+        };
+
+        let interceptor = self.join_interceptor();
+        let started = Instant::now();
+        let outcome = tokio::time::timeout(
+            self.config().join_interceptor_timeout,
+            interceptor.authorize_join(ctx),
+        )
+        .await;
+        self.metrics
+            .record_response_time("join_interceptor", started.elapsed())
+            .await;
+
+        let decision = match outcome {
+            Ok(Ok(decision)) => decision,
+            Ok(Err(err)) => {
+                tracing::warn!(%player_id, game_name, error = %err, "Join interceptor failed");
+                self.join_interceptor_failure()
+            }
+            Err(_) => {
+                tracing::warn!(
+                    %player_id,
+                    game_name,
+                    timeout_ms = self.config().join_interceptor_timeout.as_millis() as u64,
+                    "Join interceptor timed out"
+                );
+                self.join_interceptor_failure()
+            }
+        };
+        if matches!(decision, JoinDecision::Deny { .. }) {
+            self.metrics.increment_join_interceptor_denials();
+        }
+        decision
+    }
+
+    fn join_interceptor_failure(&self) -> JoinDecision {
+        self.metrics.increment_join_interceptor_failures();
+        match self.config().join_interceptor_failure_policy {
+            JoinInterceptorFailurePolicy::FailOpen => JoinDecision::Allow,
+            JoinInterceptorFailurePolicy::FailClosed => JoinDecision::Deny {
+                code: ErrorCode::ServiceUnavailable,
+                message: "Join could not be authorized, please retry".to_string(),
+            },
+        }
+    }
+
+    /// Apply the interceptor's changes to a player that has just been added to `room`.
+    ///
+    /// Metadata entries that break the room's metadata limits are dropped with a
+    /// warning; the join itself is never undone.
+    pub(super) async fn apply_join_modifications(
+        &self,
+        player_id: &PlayerId,
+        room: &mut Room,
+        modifications: JoinModifications,
+    ) {
+        if let Some(role) = modifications.role {
+            match self
+                .database
+                .set_player_role(&room.id, player_id, role)
+                .await
+            {
+                Ok(true) => {
+                    if let Some(player) = room.players.get_mut(player_id) {
+                        player.role = role;
+                    }
+                }
+                Ok(false) => {}
+                Err(err) => {
+                    tracing::warn!(%player_id, room_id = %room.id, error = %err, "Failed to apply join interceptor role");
+                }
+            }
+        }
+
+        if modifications.metadata.is_empty() {
+            return;
+        }
+        if let Err(reason) = validation::validate_room_metadata_entries(&modifications.metadata) {
+            tracing::warn!(%player_id, room_id = %room.id, %reason, "Ignoring join interceptor metadata");
+            return;
+        }
+        match self
+            .database
+            .set_room_metadata(&room.id, &modifications.metadata)
+            .await
+        {
+            Ok(RoomMetadataUpdate::Applied(metadata)) => {
+                room.metadata = metadata.clone();
+                // The joiner sees the new metadata in `RoomJoined`
+                let _ = self
+                    .message_coordinator
+                    .broadcast_to_room_except(
+                        &room.id,
+                        player_id,
+                        Arc::new(ServerMessage::RoomMetadataUpdated {
+                            metadata,
+                            updated_by: *player_id,
+                        }),
+                    )
+                    .await;
+            }
+            Ok(RoomMetadataUpdate::Rejected(reason)) => {
+                tracing::warn!(%player_id, room_id = %room.id, %reason, "Ignoring join interceptor metadata");
+            }
+            Ok(RoomMetadataUpdate::RoomNotFound) => {}
+            Err(err) => {
+                tracing::warn!(%player_id, room_id = %room.id, error = %err, "Failed to apply join interceptor metadata");
+            }
+        }
+    }
+}
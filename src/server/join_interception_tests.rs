@@ -0,0 +1,197 @@
+use super::*;
+use crate::config::{
+    AuthMaintenanceConfig, CoordinationConfig, JoinInterceptorFailurePolicy, MetricsConfig,
+    ProtocolConfig, RelayTypeConfig, TransportSecurityConfig,
+};
+use crate::database::DatabaseConfig;
+use crate::join_interceptor::{FakeJoinInterceptor, JoinDecision, JoinModifications};
+use crate::protocol::{ErrorCode, PlayerRole, ServerMessage};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::time::{timeout, Duration};
+
+const GAME: &str = "hooked-game";
+const ROOM_CODE: &str = "HOOK01";
+
+async fn create_test_server(
+    interceptor: Arc<FakeJoinInterceptor>,
+    failure_policy: JoinInterceptorFailurePolicy,
+) -> Arc<EnhancedGameServer> {
+    let server = EnhancedGameServer::new(
+        ServerConfig {
+            join_interceptor_timeout: Duration::from_millis(50),
+            join_interceptor_failure_policy: failure_policy,
+            ..ServerConfig::default()
+        },
+        ProtocolConfig::default(),
+        RelayTypeConfig::default(),
+        DatabaseConfig::InMemory,
+        MetricsConfig::default(),
+        AuthMaintenanceConfig::default(),
+        CoordinationConfig::default(),
+        TransportSecurityConfig::default(),
+        Vec::new(),
+    )
+    .await
+    .expect("failed to construct test server");
+    server.set_join_interceptor(interceptor);
+    server
+}
+
+async fn join(server: &EnhancedGameServer, port: u16, name: &str) -> (PlayerId, Outbound) {
+    let (sender, mut receiver) = mpsc::channel(32);
+    let addr: SocketAddr = format!("127.0.0.1:{port}").parse().unwrap();
+    let player_id = server
+        .connection_manager
+        .register_client(sender, addr, server.instance_id)
+        .await
+        .expect("client registration succeeds");
+    server
+        .handle_join_room(
+            &player_id,
+            GAME.to_string(),
+            Some(ROOM_CODE.to_string()),
+            name.to_string(),
+            Some(4),
+            Some(false),
+            None,
+            None,
+            None,
+        )
+        .await;
+    let reply = timeout(Duration::from_secs(1), receiver.recv())
+        .await
+        .expect("join reply arrives in time")
+        .expect("channel still open");
+    (player_id, reply)
+}
+
+fn join_failure(reply: &ServerMessage) -> Option<ErrorCode> {
+    match reply {
+        ServerMessage::RoomJoinFailed { error_code, .. } => error_code.clone(),
+        _ => None,
+    }
+}
+
+#[tokio::test]
+async fn denied_join_reports_the_hook_code_and_never_reaches_the_room() {
+    let interceptor = Arc::new(FakeJoinInterceptor::new(JoinDecision::Deny {
+        code: ErrorCode::Banned,
+        message: "Banned by the game service".to_string(),
+    }));
+    let server = create_test_server(
+        Arc::clone(&interceptor),
+        JoinInterceptorFailurePolicy::FailOpen,
+    )
+    .await;
+
+    let (player_id, reply) = join(&server, 48900, "Host").await;
+    match &*reply {
+        ServerMessage::RoomJoinFailed {
+            reason, error_code, ..
+        } => {
+            assert_eq!(reason, "Banned by the game service");
+            assert_eq!(error_code, &Some(ErrorCode::Banned));
+        }
+        other => panic!("expected RoomJoinFailed, got {other:?}"),
+    }
+    assert!(server.get_client_room(&player_id).await.is_none());
+    assert!(server
+        .database()
+        .get_room(GAME, ROOM_CODE)
+        .await
+        .unwrap()
+        .is_none());
+
+    let calls = interceptor.calls();
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].player_id, player_id);
+    assert_eq!(calls[0].game_name, GAME);
+    assert_eq!(calls[0].room_code, ROOM_CODE);
+    assert!(
+        calls[0].room.is_none(),
+        "the join would have created the room"
+    );
+    assert!(!calls[0].spectator);
+    let snapshot = server.metrics.snapshot().await;
+    assert_eq!(snapshot.rooms.join_interceptor_denials, 1);
+}
+
+#[tokio::test]
+async fn modifications_override_role_and_merge_room_metadata() {
+    let interceptor = Arc::new(FakeJoinInterceptor::new(
+        JoinDecision::AllowWithModifications(JoinModifications {
+            role: Some(PlayerRole::Moderator),
+            metadata: [("team".to_string(), "red".to_string())].into(),
+        }),
+    ));
+    let server = create_test_server(
+        Arc::clone(&interceptor),
+        JoinInterceptorFailurePolicy::FailClosed,
+    )
+    .await;
+
+    join(&server, 48910, "Host").await;
+    let (guest, reply) = join(&server, 48911, "Guest").await;
+    let ServerMessage::RoomJoined(payload) = &*reply else {
+        panic!("expected RoomJoined, got {reply:?}");
+    };
+    assert_eq!(payload.role, PlayerRole::Moderator);
+    assert_eq!(
+        payload.metadata.get("team").map(String::as_str),
+        Some("red")
+    );
+
+    let room = server
+        .database()
+        .get_room(GAME, ROOM_CODE)
+        .await
+        .unwrap()
+        .expect("room exists");
+    assert_eq!(room.players[&guest].role, PlayerRole::Moderator);
+
+    let summary = interceptor.calls()[1]
+        .room
+        .clone()
+        .expect("second join sees the room");
+    assert_eq!(summary.player_count, 1);
+    assert_eq!(summary.max_players, 4);
+}
+
+#[tokio::test]
+async fn timed_out_hook_fails_closed_and_records_latency() {
+    let interceptor = Arc::new(
+        FakeJoinInterceptor::new(JoinDecision::Allow).with_delay(Duration::from_millis(500)),
+    );
+    let server = create_test_server(interceptor, JoinInterceptorFailurePolicy::FailClosed).await;
+
+    let (player_id, reply) = join(&server, 48920, "Host").await;
+    assert_eq!(join_failure(&reply), Some(ErrorCode::ServiceUnavailable));
+    assert!(server.get_client_room(&player_id).await.is_none());
+
+    let snapshot = server.metrics.snapshot().await;
+    assert_eq!(snapshot.rooms.join_interceptor_failures, 1);
+    let latency = &snapshot.performance.join_interceptor_latency;
+    assert_eq!(latency.sample_count, 1);
+    let max_ms = latency.max_ms.expect("latency recorded");
+    assert!(
+        (50.0..500.0).contains(&max_ms),
+        "hook latency should reflect the timeout ({max_ms}ms)"
+    );
+}
+
+#[tokio::test]
+async fn failing_hook_lets_joins_through_when_failing_open() {
+    let server = create_test_server(
+        Arc::new(FakeJoinInterceptor::failing()),
+        JoinInterceptorFailurePolicy::FailOpen,
+    )
+    .await;
+
+    let (_player_id, reply) = join(&server, 48930, "Host").await;
+    assert!(matches!(&*reply, ServerMessage::RoomJoined(_)));
+    let snapshot = server.metrics.snapshot().await;
+    assert_eq!(snapshot.rooms.join_interceptor_failures, 1);
+    assert_eq!(snapshot.rooms.join_interceptor_denials, 0);
+}
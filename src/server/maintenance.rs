@@ -1,11 +1,170 @@
-use crate::protocol::RoomId;
+use crate::config::DashboardHistoryField;
+use crate::database::{RoomExpiryPolicy, RoomTimeouts};
+use crate::metrics::HistoryReading;
+use crate::protocol::{RoomId, ServerMessage};
+use crate::room_events::RoomEventKind;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
 
-use super::{chrono_duration_from_std, EnhancedGameServer};
+use super::{chrono_duration_from_std, EnhancedGameServer, ServerConfig};
+
+/// Close reason for empty rooms removed by the cleanup task.
+pub const EMPTY_CLEANUP_REASON: &str = "empty_cleanup";
+/// Close reason for rooms removed after `inactive_room_timeout` without activity.
+pub const INACTIVE_CLEANUP_REASON: &str = "inactive_cleanup";
+/// Close reason for rooms that disappeared without a close event, such as
+/// those removed by another instance.
+pub const EXPIRED_CLOSE_REASON: &str = "expired";
 
 impl EnhancedGameServer {
-    /// Log that a room has been closed during cleanup.
+    /// Record a room's closure and notify webhooks and observers.
     pub(crate) fn publish_room_closed(&self, room_id: RoomId, reason: &str) {
         tracing::debug!(%room_id, %reason, "Room closed");
+        self.forget_room_flaps(&room_id);
+        self.close_room_join_requests(&room_id);
+        self.room_coordinator.forget_room(&room_id);
+        self.invites.forget_room(&room_id);
+        self.room_activity.forget(&room_id);
+        if let Some(summary) = self.closed_rooms.room_closed(room_id, reason) {
+            self.webhooks.room_closed(&summary);
+            self.room_events.publish(
+                room_id,
+                &summary.game_name,
+                RoomEventKind::RoomDeleted {
+                    room_code: summary.room_code,
+                    reason: summary.reason,
+                },
+            );
+        }
+    }
+
+    /// Report rooms created here that are gone from storage without a close event.
+    pub(crate) async fn close_missing_rooms(&self) {
+        for room_id in self.closed_rooms.open_room_ids() {
+            if matches!(self.database.get_room_summary(&room_id).await, Ok(None)) {
+                self.publish_room_closed(room_id, EXPIRED_CLOSE_REASON);
+            }
+        }
+    }
+
+    /// Remove seated players whose heartbeats stopped reaching the database
+    /// for longer than `stale_player_timeout`, such as those left behind by
+    /// an instance that went away. The room hears `PlayerTimedOut` before the
+    /// usual `PlayerLeft`.
+    pub(crate) async fn remove_stale_players(&self) {
+        let stale_player_timeout = self.config().stale_player_timeout;
+        if stale_player_timeout.is_zero() {
+            return;
+        }
+
+        let stale_players = match self
+            .database
+            .get_stale_players(chrono_duration_from_std(stale_player_timeout))
+            .await
+        {
+            Ok(stale_players) => stale_players,
+            Err(e) => {
+                tracing::error!("Failed to find stale players: {}", e);
+                return;
+            }
+        };
+
+        let mut removed = 0;
+        for (room_id, player_id) in stale_players {
+            tracing::info!(%player_id, %room_id, instance_id = %self.instance_id, "Removing stale player");
+            let _ = self
+                .message_coordinator
+                .broadcast_to_room(
+                    &room_id,
+                    Arc::new(ServerMessage::PlayerTimedOut { player_id }),
+                )
+                .await;
+            if self.vacate_seat(&player_id, &room_id).await {
+                removed += 1;
+            }
+        }
+        if removed > 0 {
+            self.metrics.add_stale_players_removed(removed);
+        }
+    }
+
+    /// Room timeouts from the server config, including per-game overrides.
+    pub(crate) fn room_expiry_policy(&self) -> RoomExpiryPolicy {
+        room_expiry_policy_for(&self.config())
+    }
+
+    /// Send `RoomExpirySoon` to rooms within `expiry_warning` of their timeout.
+    pub(crate) async fn warn_rooms_near_expiry(&self, policy: &RoomExpiryPolicy) {
+        if self.config().expiry_warning.is_zero() {
+            return;
+        }
+
+        let warn_window = chrono_duration_from_std(self.config().expiry_warning);
+        let expiring = match self
+            .database
+            .list_rooms_near_expiry(policy, warn_window)
+            .await
+        {
+            Ok(expiring) => expiring,
+            Err(e) => {
+                tracing::error!("Failed to list rooms near expiry: {}", e);
+                return;
+            }
+        };
+
+        for room in expiring {
+            // Round up so clients never see zero before the room is gone
+            let millis = room.expires_in.num_milliseconds().max(0).unsigned_abs();
+            let seconds_remaining = u32::try_from(millis.div_ceil(1000)).unwrap_or(u32::MAX);
+            tracing::debug!(room_id = %room.room_id, seconds_remaining, "Room expiring soon");
+            if let Err(e) = self
+                .message_coordinator
+                .broadcast_to_room(
+                    &room.room_id,
+                    Arc::new(ServerMessage::RoomExpirySoon { seconds_remaining }),
+                )
+                .await
+            {
+                tracing::warn!(room_id = %room.room_id, error = %e, "Failed to send expiry warning");
+            }
+        }
+    }
+
+    /// Record a `/metrics/history` sample if one is due at `tick`.
+    pub(crate) async fn sample_metrics_history(&self, tick: std::time::Instant) {
+        let history = &self.metrics_history;
+        if !history.sample_due(tick) {
+            return;
+        }
+
+        let active_rooms = if history.tracks(DashboardHistoryField::ActiveRooms) {
+            match self.database.get_rooms_by_game().await {
+                Ok(rooms_by_game) => rooms_by_game.values().sum::<usize>() as u64,
+                Err(e) => {
+                    tracing::warn!(error = %e, "Failed to count rooms for metrics history");
+                    return;
+                }
+            }
+        } else {
+            0
+        };
+
+        history.record(
+            tick,
+            chrono::Utc::now(),
+            HistoryReading {
+                active_rooms,
+                active_connections: self.connection_manager.client_count() as u64,
+                messages_received: self
+                    .metrics
+                    .websocket_messages_received
+                    .load(Ordering::Relaxed),
+                reconnection_completions: self
+                    .metrics
+                    .reconnection_completions
+                    .load(Ordering::Relaxed),
+            },
+        );
     }
 
     /// Enhanced cleanup task with distributed coordination and idempotency
@@ -15,17 +174,24 @@ impl EnhancedGameServer {
     /// application mapping cleanup) only happen once per room, even if multiple
     /// instances attempt cleanup simultaneously.
     pub async fn cleanup_task(&self) {
-        let mut interval = tokio::time::interval(self.config.room_cleanup_interval);
-        let empty_timeout = chrono_duration_from_std(self.config.empty_room_timeout);
-        let inactive_timeout = chrono_duration_from_std(self.config.inactive_room_timeout);
+        let mut interval = tokio::time::interval(self.config().room_cleanup_interval);
+        let heartbeat = self.cleanup_heartbeat.claim();
+        if heartbeat.is_none() {
+            tracing::warn!("Cleanup task already running; readiness tracks the first one only");
+        }
 
         loop {
-            interval.tick().await;
+            let tick = interval.tick().await;
+            if let Some(heartbeat) = &heartbeat {
+                heartbeat.send_replace(tokio::time::Instant::now());
+            }
+            self.cleanup_runs.record();
+            self.sample_metrics_history(tick.into_std()).await;
 
             // Cleanup expired clients
             let expired_clients = self
                 .connection_manager
-                .collect_expired_clients(self.config.ping_timeout);
+                .collect_expired_clients(self.config().ping_timeout);
 
             let expired_client_count = expired_clients.len() as u64;
             if expired_client_count > 0 {
@@ -37,9 +203,14 @@ impl EnhancedGameServer {
                 tracing::info!(%player_id, instance_id = %self.instance_id, "Removing expired client");
                 self.unregister_client(&player_id).await;
             }
+            self.remove_stale_players().await;
+
+            // Rebuilt every tick so reloaded timeouts apply
+            let expiry_policy = self.room_expiry_policy();
+            self.warn_rooms_near_expiry(&expiry_policy).await;
 
             // Cleanup empty rooms with idempotency
-            match self.database.cleanup_empty_rooms(empty_timeout).await {
+            match self.database.cleanup_empty_rooms(&expiry_policy).await {
                 Ok(deleted_room_ids) => {
                     let count = deleted_room_ids.len();
                     if count > 0 {
@@ -56,7 +227,7 @@ impl EnhancedGameServer {
                             // Only proceed with post-cleanup if we successfully claimed it
                             let should_process = self
                                 .database
-                                .try_claim_room_cleanup(room_id, "empty_cleanup", &self.instance_id)
+                                .try_claim_room_cleanup(room_id, EMPTY_CLEANUP_REASON, &self.instance_id)
                                 .await
                                 .unwrap_or_else(|e| {
                                     tracing::warn!(
@@ -68,7 +239,8 @@ impl EnhancedGameServer {
                                 });
 
                             if should_process {
-                                self.publish_room_closed(*room_id, "empty_cleanup");
+                                self.publish_room_closed(*room_id, EMPTY_CLEANUP_REASON);
+                                self.relay_warmup.release(room_id).await;
                                 // Relay server removed in signal-fish-server
                                 self.clear_room_application(room_id).await;
                             } else {
@@ -86,11 +258,7 @@ impl EnhancedGameServer {
                 }
             }
 
-            match self
-                .database
-                .cleanup_expired_rooms(empty_timeout, inactive_timeout)
-                .await
-            {
+            match self.database.cleanup_expired_rooms(&expiry_policy).await {
                 Ok(outcome) if !outcome.is_empty() => {
                     let total = outcome.total_cleaned();
                     tracing::info!(
@@ -109,6 +277,12 @@ impl EnhancedGameServer {
                         self.metrics
                             .add_inactive_rooms_cleaned(outcome.inactive_rooms_cleaned as u64);
                     }
+                    for room_id in &outcome.empty_room_ids {
+                        self.publish_room_closed(*room_id, EMPTY_CLEANUP_REASON);
+                    }
+                    for room_id in &outcome.inactive_room_ids {
+                        self.publish_room_closed(*room_id, INACTIVE_CLEANUP_REASON);
+                    }
                 }
                 Ok(_) => {}
                 Err(e) => {
@@ -116,6 +290,17 @@ impl EnhancedGameServer {
                 }
             }
 
+            // Drop lapsed reconnection records and refresh the outstanding token gauges
+            if let Some(reconnection_manager) = &self.reconnection_manager {
+                reconnection_manager.cleanup_expired().await;
+            }
+
+            // Reap relay sessions of rooms removed by expired-room cleanup, and report
+            // rooms that were removed without a close event
+            self.relay_warmup.release_closed_rooms().await;
+            self.close_missing_rooms().await;
+            self.purge_expired_invites();
+
             // Cleanup expired distributed locks
             match self.distributed_lock.cleanup_expired_locks().await {
                 Ok(count) => {
@@ -139,6 +324,39 @@ impl EnhancedGameServer {
                     tracing::error!("Failed to cleanup old room cleanup events: {}", e);
                 }
             }
+
+            match self.database.cleanup_expired_room_idempotency_keys().await {
+                Ok(count) => {
+                    if count > 0 {
+                        tracing::debug!(count, instance_id = %self.instance_id, "Cleaned up expired room idempotency keys");
+                    }
+                }
+                Err(e) => {
+                    tracing::error!("Failed to cleanup room idempotency keys: {}", e);
+                }
+            }
+        }
+    }
+}
+
+/// Room timeouts from `config`, including per-game overrides.
+pub(crate) fn room_expiry_policy_for(config: &ServerConfig) -> RoomExpiryPolicy {
+    let empty = chrono_duration_from_std(config.empty_room_timeout);
+    let inactive = chrono_duration_from_std(config.inactive_room_timeout);
+    let mut policy = RoomExpiryPolicy::uniform(empty, inactive);
+    for (game_name, game) in &config.game_overrides {
+        if game.empty_room_timeout.is_none() && game.inactive_room_timeout.is_none() {
+            continue;
         }
+        let timeouts = RoomTimeouts {
+            empty: game
+                .empty_room_timeout
+                .map_or(empty, |t| chrono_duration_from_std(t.into())),
+            inactive: game
+                .inactive_room_timeout
+                .map_or(inactive, |t| chrono_duration_from_std(t.into())),
+        };
+        policy = policy.with_game(game_name.clone(), timeouts);
     }
+    policy
 }
@@ -0,0 +1,304 @@
+use super::room_ordering::RoomTurn;
+use super::EnhancedGameServer;
+use crate::matchmaking::{MatchGroup, MatchmakingQueueStats, MatchmakingRequest};
+use crate::protocol::validation;
+use crate::protocol::{ErrorCode, PlayerId, Room, ServerMessage};
+use std::sync::{Arc, Weak};
+
+/// Longest region id accepted on a matchmaking request.
+const MAX_REGION_ID_LENGTH: usize = 64;
+
+impl EnhancedGameServer {
+    /// Validate and queue a matchmaking request from a connected player.
+    pub async fn handle_enqueue_matchmaking(
+        &self,
+        player_id: &PlayerId,
+        game_name: String,
+        player_name: String,
+        skill_rating: f32,
+        region_id: Option<String>,
+        desired_max_players: u8,
+    ) {
+        if self.reject_if_draining(player_id).await {
+            return;
+        }
+        if let Err(reason) =
+            validation::validate_game_name_with_config(&game_name, &self.protocol_config)
+        {
+            let details = validation::text_field_details(
+                "game_name",
+                &game_name,
+                self.protocol_config.max_game_name_length,
+            );
+            self.send_join_failed_with_details(
+                player_id,
+                reason,
+                ErrorCode::InvalidGameName,
+                Some(details),
+            )
+            .await;
+            return;
+        }
+        if !self.client_allows_game(player_id, &game_name) {
+            self.send_join_failed(
+                player_id,
+                format!("Token does not permit rooms for game '{game_name}'"),
+                ErrorCode::Unauthorized,
+            )
+            .await;
+            return;
+        }
+        if let Err(reason) =
+            validation::validate_player_name_with_config(&player_name, &self.protocol_config)
+        {
+            let code = reason.error_code(ErrorCode::InvalidInput);
+            let details = reason.details(&player_name, &self.protocol_config);
+            self.send_join_failed_with_details(player_id, reason.to_string(), code, Some(details))
+                .await;
+            return;
+        }
+        if let Err(reason) =
+            validation::validate_max_players_with_config(desired_max_players, &self.protocol_config)
+        {
+            self.send_join_failed_with_details(
+                player_id,
+                reason,
+                ErrorCode::InvalidInput,
+                Some(self.max_players_range_details()),
+            )
+            .await;
+            return;
+        }
+        if desired_max_players < 2 {
+            self.send_join_failed_with_details(
+                player_id,
+                "Matchmaking requires at least 2 players".to_string(),
+                ErrorCode::InvalidInput,
+                Some(self.max_players_range_details()),
+            )
+            .await;
+            return;
+        }
+        if !skill_rating.is_finite() {
+            self.send_join_failed(
+                player_id,
+                "Skill rating must be a finite number".to_string(),
+                ErrorCode::InvalidInput,
+            )
+            .await;
+            return;
+        }
+        let region_id = region_id.unwrap_or_else(|| self.client_region(player_id));
+        if region_id.trim().is_empty() || region_id.len() > MAX_REGION_ID_LENGTH {
+            let details =
+                validation::text_field_details("region_id", &region_id, MAX_REGION_ID_LENGTH);
+            self.send_join_failed_with_details(
+                player_id,
+                format!("Region id must be 1-{MAX_REGION_ID_LENGTH} characters"),
+                ErrorCode::InvalidInput,
+                Some(details),
+            )
+            .await;
+            return;
+        }
+        if self.get_client_room(player_id).await.is_some() {
+            self.send_join_failed(
+                player_id,
+                "Already in a room".to_string(),
+                ErrorCode::AlreadyInRoom,
+            )
+            .await;
+            return;
+        }
+
+        tracing::info!(
+            %player_id,
+            %game_name,
+            %region_id,
+            skill_rating,
+            desired_max_players,
+            "Player queued for matchmaking"
+        );
+        self.matchmaking.enqueue(MatchmakingRequest {
+            player_id: *player_id,
+            player_name,
+            game_name,
+            skill_rating,
+            region_id,
+            desired_max_players,
+        });
+    }
+
+    /// Cancel a player's matchmaking request. Cancelling when not queued is a no-op.
+    pub fn handle_dequeue_matchmaking(&self, player_id: &PlayerId) {
+        if self.matchmaking.dequeue(player_id) {
+            tracing::info!(%player_id, "Player left matchmaking queue");
+        }
+    }
+
+    /// Queue depth per game and region.
+    pub fn matchmaking_stats(&self) -> Vec<MatchmakingQueueStats> {
+        self.matchmaking.stats()
+    }
+
+    /// Create a new room with `host_id` as its first player and authority.
+    /// Returns the room's turn, to be held until the host's join is announced.
+    pub(super) async fn create_room(
+        &self,
+        host_id: &PlayerId,
+        game_name: &str,
+        player_name: &str,
+        max_players: u8,
+    ) -> anyhow::Result<(Room, RoomTurn)> {
+        let room_code = self.generate_room_code(game_name);
+        self.join_room_with_coordination(
+            host_id,
+            game_name,
+            &room_code,
+            player_name,
+            max_players,
+            true,
+            None,
+            None,
+            false,
+        )
+        .await
+    }
+
+    /// Run one matching pass and seat every group that was formed.
+    pub async fn run_matchmaking_tick(&self) {
+        let groups = self
+            .matchmaking
+            .find_matches(self.config().matchmaking_elo_window);
+        for group in groups {
+            self.seat_match_group(group).await;
+        }
+    }
+
+    async fn seat_match_group(&self, group: MatchGroup) {
+        // Players may have disconnected or joined a room since the pass started.
+        let group_size = group.players.len();
+        let mut available = Vec::with_capacity(group_size);
+        for request in group.players {
+            if self.connection_manager.has_client(&request.player_id)
+                && self.get_client_room(&request.player_id).await.is_none()
+            {
+                available.push(request);
+            }
+        }
+        if available.len() < group_size {
+            for request in available {
+                self.matchmaking.enqueue(request);
+            }
+            return;
+        }
+
+        let mut players = available.into_iter();
+        let Some(host) = players.next() else {
+            return;
+        };
+
+        let (room, turn) = match self
+            .create_room(
+                &host.player_id,
+                &group.game_name,
+                &host.player_name,
+                group.max_players,
+            )
+            .await
+        {
+            Ok(room) => room,
+            Err(e) => {
+                tracing::warn!(
+                    game_name = %group.game_name,
+                    region_id = %group.region_id,
+                    error = %e,
+                    "Failed to create matchmaking room; requeueing players"
+                );
+                self.matchmaking.enqueue(host);
+                for request in players {
+                    self.matchmaking.enqueue(request);
+                }
+                return;
+            }
+        };
+
+        tracing::info!(
+            room_id = %room.id,
+            room_code = %room.code,
+            game_name = %group.game_name,
+            region_id = %group.region_id,
+            players = group.max_players,
+            "Matchmaking created room"
+        );
+        self.announce_match(&host.player_id, &room).await;
+        self.announce_room_join(&host.player_id, &room, host.player_name, turn)
+            .await;
+
+        for request in players {
+            let joined = self
+                .join_room_with_coordination(
+                    &request.player_id,
+                    &room.game_name,
+                    &room.code,
+                    &request.player_name,
+                    room.max_players,
+                    room.supports_authority,
+                    None,
+                    None,
+                    false,
+                )
+                .await;
+            match joined {
+                Ok((room, turn)) => {
+                    self.announce_match(&request.player_id, &room).await;
+                    self.announce_room_join(&request.player_id, &room, request.player_name, turn)
+                        .await;
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        player_id = %request.player_id,
+                        room_id = %room.id,
+                        error = %e,
+                        "Matched player could not join room; requeueing"
+                    );
+                    self.matchmaking.enqueue(request);
+                }
+            }
+        }
+    }
+
+    async fn announce_match(&self, player_id: &PlayerId, room: &Room) {
+        let _ = self
+            .message_coordinator
+            .send_to_player(
+                player_id,
+                Arc::new(ServerMessage::MatchFound {
+                    room_code: room.code.clone(),
+                    room_id: room.id,
+                }),
+            )
+            .await;
+    }
+
+    /// Spawn the matching loop. It stops once the server is dropped.
+    pub(super) fn start_matchmaking(server: &Arc<Self>) {
+        let weak: Weak<Self> = Arc::downgrade(server);
+        let tick = server
+            .config()
+            .matchmaking_tick
+            .max(std::time::Duration::from_millis(1));
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(tick);
+            loop {
+                interval.tick().await;
+                let Some(server) = weak.upgrade() else {
+                    break;
+                };
+                if !server.matchmaking.is_empty() {
+                    server.run_matchmaking_tick().await;
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,195 @@
+use super::*;
+use crate::config::{
+    AuthMaintenanceConfig, CoordinationConfig, MetricsConfig, ProtocolConfig, RelayTypeConfig,
+    TransportSecurityConfig,
+};
+use crate::database::DatabaseConfig;
+use crate::protocol::{ClientMessage, ErrorCode};
+use std::net::SocketAddr;
+use tokio::sync::mpsc;
+use tokio::time::{timeout, Duration};
+
+async fn create_test_server(matchmaking_tick: Duration) -> Arc<EnhancedGameServer> {
+    EnhancedGameServer::new(
+        ServerConfig {
+            matchmaking_elo_window: 100.0,
+            matchmaking_tick,
+            ..ServerConfig::default()
+        },
+        ProtocolConfig::default(),
+        RelayTypeConfig::default(),
+        DatabaseConfig::InMemory,
+        MetricsConfig::default(),
+        AuthMaintenanceConfig::default(),
+        CoordinationConfig::default(),
+        TransportSecurityConfig::default(),
+        Vec::new(),
+    )
+    .await
+    .expect("failed to construct test server")
+}
+
+/// Server whose background loop never fires during a test, so ticks are explicit.
+async fn create_manual_server() -> Arc<EnhancedGameServer> {
+    create_test_server(Duration::from_secs(3600)).await
+}
+
+async fn register(server: &EnhancedGameServer, addr: &str) -> (PlayerId, mpsc::Receiver<Outbound>) {
+    let (sender, receiver) = mpsc::channel(32);
+    let addr: SocketAddr = addr.parse().unwrap();
+    let player_id = server
+        .connection_manager
+        .register_client(sender, addr, server.instance_id)
+        .await
+        .expect("client registration succeeds");
+    (player_id, receiver)
+}
+
+async fn enqueue(server: &EnhancedGameServer, player_id: &PlayerId, name: &str, skill: f32) {
+    server
+        .handle_enqueue_matchmaking(
+            player_id,
+            "ranked".to_string(),
+            name.to_string(),
+            skill,
+            None,
+            2,
+        )
+        .await;
+}
+
+async fn next_message(receiver: &mut mpsc::Receiver<Outbound>) -> Outbound {
+    timeout(Duration::from_secs(1), receiver.recv())
+        .await
+        .expect("message arrives in time")
+        .expect("channel still open")
+}
+
+#[tokio::test]
+async fn players_within_elo_window_are_seated_together() {
+    let server = create_manual_server().await;
+    let (first, mut first_rx) = register(&server, "127.0.0.1:48400").await;
+    let (second, mut second_rx) = register(&server, "127.0.0.2:48401").await;
+    let (outlier, mut outlier_rx) = register(&server, "127.0.0.3:48402").await;
+
+    enqueue(&server, &first, "First", 1200.0).await;
+    enqueue(&server, &outlier, "Outlier", 1900.0).await;
+    enqueue(&server, &second, "Second", 1250.0).await;
+    server.run_matchmaking_tick().await;
+
+    let found = next_message(&mut first_rx).await;
+    let ServerMessage::MatchFound {
+        room_id,
+        ref room_code,
+    } = *found
+    else {
+        panic!("expected MatchFound, got {found:?}");
+    };
+    let joined = next_message(&mut first_rx).await;
+    assert!(
+        matches!(*joined, ServerMessage::RoomJoined(ref payload) if payload.room_id == room_id && payload.is_authority),
+        "expected host RoomJoined, got {joined:?}"
+    );
+
+    let found = next_message(&mut second_rx).await;
+    assert!(
+        matches!(*found, ServerMessage::MatchFound { room_id: id, room_code: ref code } if id == room_id && code == room_code),
+        "expected matching MatchFound, got {found:?}"
+    );
+    let joined = next_message(&mut second_rx).await;
+    assert!(matches!(*joined, ServerMessage::RoomJoined(_)));
+
+    assert_eq!(server.get_client_room(&first).await, Some(room_id));
+    assert_eq!(server.get_client_room(&second).await, Some(room_id));
+    assert!(server.get_client_room(&outlier).await.is_none());
+    assert!(outlier_rx.try_recv().is_err());
+    assert_eq!(
+        server.matchmaking_stats(),
+        vec![crate::matchmaking::MatchmakingQueueStats {
+            game_name: "ranked".to_string(),
+            region_id: "default".to_string(),
+            queued: 1,
+        }]
+    );
+}
+
+#[tokio::test]
+async fn dequeue_and_disconnect_cancel_requests() {
+    let server = create_manual_server().await;
+    let (first, mut first_rx) = register(&server, "127.0.0.1:48410").await;
+    let (second, _second_rx) = register(&server, "127.0.0.2:48411").await;
+    let (third, _third_rx) = register(&server, "127.0.0.3:48412").await;
+
+    enqueue(&server, &first, "First", 1000.0).await;
+    enqueue(&server, &second, "Second", 1000.0).await;
+    server
+        .handle_client_message(&second, ClientMessage::DequeueMatchmaking)
+        .await;
+    enqueue(&server, &third, "Third", 1000.0).await;
+    server.unregister_client(&third).await;
+
+    server.run_matchmaking_tick().await;
+
+    assert!(first_rx.try_recv().is_err(), "nobody left to match with");
+    assert_eq!(server.matchmaking_stats()[0].queued, 1);
+    assert!(server.get_client_room(&first).await.is_none());
+}
+
+#[tokio::test]
+async fn invalid_requests_are_rejected() {
+    let server = create_manual_server().await;
+    let (player, mut rx) = register(&server, "127.0.0.1:48420").await;
+
+    enqueue(&server, &player, "Player", f32::NAN).await;
+    let rejected = next_message(&mut rx).await;
+    assert!(
+        matches!(
+            *rejected,
+            ServerMessage::RoomJoinFailed {
+                error_code: Some(ErrorCode::InvalidInput),
+                ..
+            }
+        ),
+        "expected InvalidInput, got {rejected:?}"
+    );
+    assert!(server.matchmaking_stats().is_empty());
+}
+
+#[tokio::test]
+async fn background_loop_forms_matches() {
+    let server = create_test_server(Duration::from_millis(10)).await;
+    let (first, mut first_rx) = register(&server, "127.0.0.1:48430").await;
+    let (second, _second_rx) = register(&server, "127.0.0.2:48431").await;
+
+    server
+        .handle_client_message(
+            &first,
+            ClientMessage::EnqueueMatchmaking {
+                game_name: "ranked".to_string(),
+                player_name: "First".to_string(),
+                skill_rating: 1500.0,
+                region_id: Some("eu-west".to_string()),
+                desired_max_players: 2,
+            },
+        )
+        .await;
+    server
+        .handle_client_message(
+            &second,
+            ClientMessage::EnqueueMatchmaking {
+                game_name: "ranked".to_string(),
+                player_name: "Second".to_string(),
+                skill_rating: 1550.0,
+                region_id: Some("eu-west".to_string()),
+                desired_max_players: 2,
+            },
+        )
+        .await;
+
+    let found = next_message(&mut first_rx).await;
+    assert!(
+        matches!(*found, ServerMessage::MatchFound { .. }),
+        "expected MatchFound, got {found:?}"
+    );
+    assert!(server.matchmaking_stats().is_empty());
+}
@@ -21,8 +21,12 @@ impl EnhancedGameServer {
                 max_players,
                 supports_authority,
                 relay_transport,
+                relay_type,
+                spectator_chat_mode,
+                idempotency_key,
+                allow_overflow_joins,
             } => {
-                self.handle_join_room(
+                self.join_room_request(
                     player_id,
                     game_name,
                     room_code,
@@ -30,9 +34,22 @@ impl EnhancedGameServer {
                     max_players,
                     supports_authority,
                     relay_transport,
+                    relay_type,
+                    spectator_chat_mode,
+                    allow_overflow_joins,
+                    idempotency_key,
+                    false,
                 )
                 .await;
             }
+            ClientMessage::QuickJoin {
+                game_name,
+                player_name,
+                region_id,
+            } => {
+                self.handle_quick_join(player_id, game_name, player_name, region_id)
+                    .await;
+            }
             ClientMessage::LeaveRoom => {
                 self.leave_room(player_id).await;
             }
@@ -53,6 +70,24 @@ impl EnhancedGameServer {
             ClientMessage::Ping => {
                 self.handle_ping(player_id).await;
             }
+            ClientMessage::LatencyPing {
+                id,
+                sent_at_ms,
+                last_rtt_ms,
+            } => {
+                self.handle_latency_ping(player_id, id, sent_at_ms, last_rtt_ms)
+                    .await;
+            }
+            ClientMessage::Heartbeat {
+                client_timestamp,
+                last_ack,
+            } => {
+                self.handle_heartbeat(player_id, client_timestamp, last_ack)
+                    .await;
+            }
+            ClientMessage::TimeSync { client_sent_at_ms } => {
+                self.handle_time_sync(player_id, client_sent_at_ms).await;
+            }
             ClientMessage::Reconnect {
                 player_id: reconnect_player_id,
                 room_id,
@@ -72,6 +107,105 @@ impl EnhancedGameServer {
             ClientMessage::LeaveSpectator => {
                 self.handle_leave_spectator(player_id).await;
             }
+            ClientMessage::SpectatorChat { text } => {
+                self.handle_spectator_chat(player_id, text).await;
+            }
+            ClientMessage::RequestPromotion => {
+                self.handle_request_promotion(player_id).await;
+            }
+            ClientMessage::JoinWaitingList {
+                game_name,
+                room_code,
+                player_name,
+            } => {
+                self.handle_join_waiting_list(player_id, game_name, room_code, player_name)
+                    .await;
+            }
+            ClientMessage::KickPlayer {
+                player_id: target_player_id,
+                ban,
+                reason,
+            } => {
+                self.handle_kick_player(player_id, &target_player_id, ban, reason)
+                    .await;
+            }
+            ClientMessage::UnbanPlayer {
+                player_id: target_player_id,
+            } => {
+                self.handle_unban_player(player_id, &target_player_id).await;
+            }
+            ClientMessage::ReportPlayer {
+                target_player_id,
+                reason,
+                details,
+            } => {
+                self.handle_report_player(player_id, &target_player_id, reason, details)
+                    .await;
+            }
+            ClientMessage::AssignRole {
+                player_id: target_player_id,
+                role,
+            } => {
+                self.handle_assign_role(player_id, &target_player_id, role)
+                    .await;
+            }
+            ClientMessage::RequestRoleChange { target, new_role } => {
+                self.handle_role_change(player_id, &target, new_role).await;
+            }
+            ClientMessage::SetRoomMetadata { entries } => {
+                self.handle_set_room_metadata(player_id, entries).await;
+            }
+            ClientMessage::UpdateRoomSettings {
+                max_players,
+                max_spectators,
+                invites_authority_only,
+            } => {
+                self.handle_update_room_settings(
+                    player_id,
+                    max_players,
+                    max_spectators,
+                    invites_authority_only,
+                )
+                .await;
+            }
+            ClientMessage::RespondToJoinRequest {
+                player_id: requester_id,
+                accept,
+            } => {
+                self.handle_respond_to_join_request(player_id, &requester_id, accept)
+                    .await;
+            }
+            ClientMessage::DeliveryAck { ack_sample } => {
+                self.handle_delivery_ack(player_id, ack_sample);
+            }
+            ClientMessage::EnqueueMatchmaking {
+                game_name,
+                player_name,
+                skill_rating,
+                region_id,
+                desired_max_players,
+            } => {
+                self.handle_enqueue_matchmaking(
+                    player_id,
+                    game_name,
+                    player_name,
+                    skill_rating,
+                    region_id,
+                    desired_max_players,
+                )
+                .await;
+            }
+            ClientMessage::DequeueMatchmaking => {
+                self.handle_dequeue_matchmaking(player_id);
+            }
+            ClientMessage::CreateInvite { max_uses, ttl_secs } => {
+                self.handle_create_invite(player_id, max_uses, ttl_secs)
+                    .await;
+            }
+            ClientMessage::JoinByInvite { token, player_name } => {
+                self.handle_join_by_invite(player_id, &token, player_name)
+                    .await;
+            }
         }
     }
 }
@@ -45,6 +45,7 @@ async fn delayed_authenticate_is_rejected_with_warning_only() {
                 sdk_version: None,
                 platform: None,
                 game_data_format: None,
+                capabilities: Vec::new(),
             },
         )
         .await;
@@ -79,6 +80,10 @@ async fn join_room_request_is_forwarded_to_room_service() {
                 max_players: Some(2),
                 supports_authority: Some(true),
                 relay_transport: None,
+                relay_type: None,
+                spectator_chat_mode: None,
+                idempotency_key: None,
+                allow_overflow_joins: None,
             },
         )
         .await;
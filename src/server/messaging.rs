@@ -1,23 +1,37 @@
 use super::EnhancedGameServer;
-use crate::protocol::{ErrorCode, PlayerId, ServerMessage};
+use crate::protocol::{ErrorDetail, MessageEncoding, PlayerId, ServerMessage};
 use std::sync::Arc;
+use tokio::sync::Notify;
 
 impl EnhancedGameServer {
     /// Send an error message to a specific player, tracking back-pressure metrics.
     pub async fn send_error_to_player(
         &self,
         player_id: &PlayerId,
-        message: String,
-        error_code: Option<ErrorCode>,
+        error: ErrorDetail,
     ) -> anyhow::Result<()> {
         self.message_coordinator
-            .send_to_player(
-                player_id,
-                Arc::new(ServerMessage::Error {
-                    message,
-                    error_code,
-                }),
-            )
+            .send_to_player(player_id, Arc::new(ServerMessage::Error(error)))
+            .await
+    }
+
+    /// Queue the player's room broadcasts as shared frames serialized in
+    /// `encoding`. Returns `false` if the connection serializes them itself.
+    pub async fn subscribe_room_frames(
+        &self,
+        player_id: PlayerId,
+        encoding: MessageEncoding,
+    ) -> bool {
+        self.message_coordinator
+            .subscribe_room_frames(player_id, encoding)
+            .await
+    }
+
+    /// Notify `shed` when the player's queue overflows. Returns `false` if
+    /// overflowing messages are only dropped.
+    pub async fn watch_backpressure(&self, player_id: PlayerId, shed: Arc<Notify>) -> bool {
+        self.message_coordinator
+            .watch_backpressure(player_id, shed)
             .await
     }
 }
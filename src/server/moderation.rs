@@ -0,0 +1,295 @@
+use crate::protocol::permissions::effective_permissions;
+use crate::protocol::{
+    ErrorCode, ErrorDetail, Permissions, PlayerId, PlayerRole, PrivilegedAction, Room,
+    ServerMessage,
+};
+use std::sync::Arc;
+
+use super::EnhancedGameServer;
+
+/// Effective permissions of `player_id` in `room`; players not in the room have none.
+pub(crate) fn player_permissions(room: &Room, player_id: &PlayerId) -> Permissions {
+    room.players
+        .get(player_id)
+        .map_or(Permissions::NONE, |player| {
+            effective_permissions(player.role, room.authority_player == Some(*player_id))
+        })
+}
+
+impl EnhancedGameServer {
+    /// Load the requester's room and check that they may perform `action` in it.
+    ///
+    /// Every privileged handler goes through here. On failure the requester is
+    /// sent an error and `None` is returned.
+    pub(crate) async fn authorize_privileged(
+        &self,
+        requester_id: &PlayerId,
+        action: PrivilegedAction,
+    ) -> Option<Room> {
+        let room = self.requester_room(requester_id).await?;
+
+        if !player_permissions(&room, requester_id).contains(action.required_permission()) {
+            tracing::debug!(%requester_id, room_id = %room.id, action = action.message_type(), "Privileged action denied");
+            let _ = self
+                .send_error_to_player(
+                    requester_id,
+                    ErrorDetail::new(
+                        ErrorCode::AuthorityDenied,
+                        format!(
+                            "Your role does not permit {} in this room",
+                            action.message_type()
+                        ),
+                    ),
+                )
+                .await;
+            return None;
+        }
+
+        Some(room)
+    }
+
+    /// Load the room the requester is in. On failure the requester is sent an
+    /// error and `None` is returned.
+    pub(crate) async fn requester_room(&self, requester_id: &PlayerId) -> Option<Room> {
+        let Some(room_id) = self.get_client_room(requester_id).await else {
+            let _ = self
+                .send_error_to_player(
+                    requester_id,
+                    ErrorDetail::new(ErrorCode::NotInRoom, "Not in a room"),
+                )
+                .await;
+            return None;
+        };
+
+        match self.database.get_room_by_id(&room_id).await {
+            Ok(Some(room)) => Some(room),
+            Ok(None) => {
+                let _ = self
+                    .send_error_to_player(
+                        requester_id,
+                        ErrorDetail::new(ErrorCode::RoomNotFound, "Room not found"),
+                    )
+                    .await;
+                None
+            }
+            Err(e) => {
+                tracing::error!(%requester_id, %room_id, error = %e, "Failed to load room for requester");
+                let _ = self
+                    .send_error_to_player(
+                        requester_id,
+                        ErrorDetail::new(ErrorCode::StorageError, "Failed to load room"),
+                    )
+                    .await;
+                None
+            }
+        }
+    }
+
+    /// Handle a request to remove a player, optionally banning them.
+    ///
+    /// Bans match both the player id and the client IP of the kicked connection,
+    /// so reconnecting with a fresh session does not bypass them.
+    pub async fn handle_kick_player(
+        &self,
+        requester_id: &PlayerId,
+        target_id: &PlayerId,
+        ban: bool,
+        reason: Option<String>,
+    ) {
+        let Some(room) = self
+            .authorize_privileged(requester_id, PrivilegedAction::KickPlayer)
+            .await
+        else {
+            return;
+        };
+        let room_id = room.id;
+
+        if target_id == requester_id || !room.players.contains_key(target_id) {
+            let _ = self
+                .send_error_to_player(
+                    requester_id,
+                    ErrorDetail::new(ErrorCode::InvalidInput, "Target player is not in this room"),
+                )
+                .await;
+            return;
+        }
+
+        // Moderators cannot remove players who hold rights they lack
+        if !player_permissions(&room, requester_id).contains(player_permissions(&room, target_id)) {
+            let _ = self
+                .send_error_to_player(
+                    requester_id,
+                    ErrorDetail::new(
+                        ErrorCode::AuthorityDenied,
+                        "Cannot kick a player with more permissions than you",
+                    ),
+                )
+                .await;
+            return;
+        }
+
+        if ban {
+            let client_ip = self.connection_manager.client_ip(target_id);
+            if let Err(e) = self
+                .database
+                .ban_player_from_room(&room_id, target_id, client_ip)
+                .await
+            {
+                tracing::error!(%room_id, player_id = %target_id, error = %e, "Failed to record room ban");
+                let _ = self
+                    .send_error_to_player(
+                        requester_id,
+                        ErrorDetail::new(ErrorCode::StorageError, "Failed to ban player"),
+                    )
+                    .await;
+                return;
+            }
+        }
+
+        tracing::info!(
+            %room_id,
+            requester = %requester_id,
+            player_id = %target_id,
+            banned = ban,
+            "Kicking player from room"
+        );
+
+        let _ = self
+            .message_coordinator
+            .send_to_player(
+                target_id,
+                Arc::new(ServerMessage::Kicked {
+                    room_id,
+                    banned: ban,
+                    reason,
+                }),
+            )
+            .await;
+
+        self.leave_room(target_id).await;
+    }
+
+    /// Handle a request to lift a ban.
+    pub async fn handle_unban_player(&self, requester_id: &PlayerId, target_id: &PlayerId) {
+        let Some(room) = self
+            .authorize_privileged(requester_id, PrivilegedAction::UnbanPlayer)
+            .await
+        else {
+            return;
+        };
+        let room_id = room.id;
+
+        match self
+            .database
+            .unban_player_from_room(&room_id, target_id)
+            .await
+        {
+            Ok(true) => {
+                tracing::info!(%room_id, requester = %requester_id, player_id = %target_id, "Room ban lifted");
+                let _ = self
+                    .message_coordinator
+                    .send_to_player(
+                        requester_id,
+                        Arc::new(ServerMessage::PlayerUnbanned {
+                            player_id: *target_id,
+                        }),
+                    )
+                    .await;
+            }
+            Ok(false) => {
+                let _ = self
+                    .send_error_to_player(
+                        requester_id,
+                        ErrorDetail::new(
+                            ErrorCode::InvalidInput,
+                            "Player is not banned from this room",
+                        ),
+                    )
+                    .await;
+            }
+            Err(e) => {
+                tracing::error!(%room_id, player_id = %target_id, error = %e, "Failed to lift room ban");
+                let _ = self
+                    .send_error_to_player(
+                        requester_id,
+                        ErrorDetail::new(ErrorCode::StorageError, "Failed to unban player"),
+                    )
+                    .await;
+            }
+        }
+    }
+
+    /// Handle a request to change another player's role and broadcast the change.
+    pub async fn handle_assign_role(
+        &self,
+        requester_id: &PlayerId,
+        target_id: &PlayerId,
+        role: PlayerRole,
+    ) {
+        let Some(room) = self
+            .authorize_privileged(requester_id, PrivilegedAction::AssignRole)
+            .await
+        else {
+            return;
+        };
+
+        let rejection = match room.players.get(target_id) {
+            _ if target_id == requester_id => Some("Cannot change your own role"),
+            None => Some("Target player is not in this room"),
+            Some(target) if target.role == PlayerRole::Creator => {
+                Some("The room creator's role cannot be changed")
+            }
+            Some(_) if !role.is_assignable() => Some("That role cannot be assigned"),
+            Some(_) => None,
+        };
+        if let Some(reason) = rejection {
+            let _ = self
+                .send_error_to_player(
+                    requester_id,
+                    ErrorDetail::new(ErrorCode::InvalidInput, reason.to_string()),
+                )
+                .await;
+            return;
+        }
+
+        match self
+            .database
+            .set_player_role(&room.id, target_id, role)
+            .await
+        {
+            Ok(true) => {
+                tracing::info!(room_id = %room.id, requester = %requester_id, player_id = %target_id, ?role, "Player role changed");
+                let _ = self
+                    .message_coordinator
+                    .broadcast_to_room(
+                        &room.id,
+                        Arc::new(ServerMessage::PlayerRoleChanged {
+                            player_id: *target_id,
+                            role,
+                        }),
+                    )
+                    .await;
+            }
+            Ok(false) => {
+                let _ = self
+                    .send_error_to_player(
+                        requester_id,
+                        ErrorDetail::new(
+                            ErrorCode::InvalidInput,
+                            "Target player is not in this room",
+                        ),
+                    )
+                    .await;
+            }
+            Err(e) => {
+                tracing::error!(room_id = %room.id, player_id = %target_id, error = %e, "Failed to change player role");
+                let _ = self
+                    .send_error_to_player(
+                        requester_id,
+                        ErrorDetail::new(ErrorCode::StorageError, "Failed to change role"),
+                    )
+                    .await;
+            }
+        }
+    }
+}
@@ -0,0 +1,385 @@
+use super::*;
+use crate::config::{
+    AuthMaintenanceConfig, CoordinationConfig, MetricsConfig, ProtocolConfig, RelayTypeConfig,
+    TransportSecurityConfig,
+};
+use crate::database::DatabaseConfig;
+use crate::protocol::{ErrorCode, ErrorDetail, PlayerRole, ServerMessage};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::time::{timeout, Duration};
+
+async fn create_test_server() -> Arc<EnhancedGameServer> {
+    EnhancedGameServer::new(
+        ServerConfig::default(),
+        ProtocolConfig::default(),
+        RelayTypeConfig::default(),
+        DatabaseConfig::InMemory,
+        MetricsConfig::default(),
+        AuthMaintenanceConfig::default(),
+        CoordinationConfig::default(),
+        TransportSecurityConfig::default(),
+        Vec::new(),
+    )
+    .await
+    .expect("failed to construct test server")
+}
+
+async fn register(server: &EnhancedGameServer, addr: &str) -> (PlayerId, mpsc::Receiver<Outbound>) {
+    let (sender, receiver) = mpsc::channel(32);
+    let addr: SocketAddr = addr.parse().unwrap();
+    let player_id = server
+        .connection_manager
+        .register_client(sender, addr, server.instance_id)
+        .await
+        .expect("client registration succeeds");
+    (player_id, receiver)
+}
+
+async fn join(server: &EnhancedGameServer, player_id: &PlayerId, name: &str) {
+    server
+        .handle_join_room(
+            player_id,
+            "ban-game".to_string(),
+            Some("BANS01".to_string()),
+            name.to_string(),
+            Some(4),
+            Some(true),
+            None,
+            None,
+            None,
+        )
+        .await;
+}
+
+async fn next_message(receiver: &mut mpsc::Receiver<Outbound>) -> Outbound {
+    timeout(Duration::from_secs(1), receiver.recv())
+        .await
+        .expect("message arrives in time")
+        .expect("channel still open")
+}
+
+fn drain(receiver: &mut mpsc::Receiver<Outbound>) {
+    while receiver.try_recv().is_ok() {}
+}
+
+#[tokio::test]
+async fn banned_player_cannot_rejoin_until_unbanned() {
+    let server = create_test_server().await;
+    let (host, mut host_rx) = register(&server, "127.0.0.1:48200").await;
+    let (guest, mut guest_rx) = register(&server, "127.0.0.2:48201").await;
+
+    join(&server, &host, "Host").await;
+    join(&server, &guest, "Guest").await;
+    drain(&mut host_rx);
+    drain(&mut guest_rx);
+
+    server
+        .handle_kick_player(&host, &guest, true, Some("griefing".to_string()))
+        .await;
+
+    let kicked = next_message(&mut guest_rx).await;
+    assert!(
+        matches!(*kicked, ServerMessage::Kicked { banned: true, ref reason, .. } if reason.as_deref() == Some("griefing")),
+        "expected Kicked with ban, got {kicked:?}"
+    );
+    assert!(server.get_client_room(&guest).await.is_none());
+    drain(&mut guest_rx);
+
+    join(&server, &guest, "Guest").await;
+    let rejected = next_message(&mut guest_rx).await;
+    assert!(
+        matches!(
+            *rejected,
+            ServerMessage::RoomJoinFailed {
+                error_code: Some(ErrorCode::Banned),
+                ..
+            }
+        ),
+        "expected Banned rejection, got {rejected:?}"
+    );
+
+    // A fresh session from the same address is still banned
+    let (rejoiner, mut rejoiner_rx) = register(&server, "127.0.0.2:48202").await;
+    join(&server, &rejoiner, "Guest Again").await;
+    let rejected = next_message(&mut rejoiner_rx).await;
+    assert!(matches!(
+        *rejected,
+        ServerMessage::RoomJoinFailed {
+            error_code: Some(ErrorCode::Banned),
+            ..
+        }
+    ));
+
+    drain(&mut host_rx);
+    server.handle_unban_player(&host, &guest).await;
+    let unbanned = next_message(&mut host_rx).await;
+    assert!(
+        matches!(*unbanned, ServerMessage::PlayerUnbanned { player_id } if player_id == guest),
+        "expected PlayerUnbanned, got {unbanned:?}"
+    );
+
+    join(&server, &guest, "Guest").await;
+    let joined = next_message(&mut guest_rx).await;
+    assert!(
+        matches!(*joined, ServerMessage::RoomJoined(_)),
+        "expected RoomJoined after unban, got {joined:?}"
+    );
+}
+
+#[tokio::test]
+async fn kick_without_ban_allows_rejoin() {
+    let server = create_test_server().await;
+    let (host, _host_rx) = register(&server, "127.0.0.1:48210").await;
+    let (guest, mut guest_rx) = register(&server, "127.0.0.2:48211").await;
+
+    join(&server, &host, "Host").await;
+    join(&server, &guest, "Guest").await;
+    drain(&mut guest_rx);
+
+    server.handle_kick_player(&host, &guest, false, None).await;
+    let kicked = next_message(&mut guest_rx).await;
+    assert!(matches!(
+        *kicked,
+        ServerMessage::Kicked {
+            banned: false,
+            reason: None,
+            ..
+        }
+    ));
+    drain(&mut guest_rx);
+
+    join(&server, &guest, "Guest").await;
+    let joined = next_message(&mut guest_rx).await;
+    assert!(
+        matches!(*joined, ServerMessage::RoomJoined(_)),
+        "expected RoomJoined, got {joined:?}"
+    );
+}
+
+#[tokio::test]
+async fn banned_player_cannot_spectate() {
+    let server = create_test_server().await;
+    let (host, _host_rx) = register(&server, "127.0.0.1:48220").await;
+    let (guest, mut guest_rx) = register(&server, "127.0.0.2:48221").await;
+
+    join(&server, &host, "Host").await;
+    join(&server, &guest, "Guest").await;
+    server.handle_kick_player(&host, &guest, true, None).await;
+    drain(&mut guest_rx);
+
+    server
+        .handle_join_as_spectator(
+            &guest,
+            "ban-game".to_string(),
+            "BANS01".to_string(),
+            "Watcher".to_string(),
+        )
+        .await;
+
+    let rejected = next_message(&mut guest_rx).await;
+    assert!(
+        matches!(
+            *rejected,
+            ServerMessage::Error(ErrorDetail {
+                code: ErrorCode::Banned,
+                ..
+            })
+        ),
+        "expected Banned spectator rejection, got {rejected:?}"
+    );
+}
+
+#[tokio::test]
+async fn non_authority_cannot_kick() {
+    let server = create_test_server().await;
+    let (host, _host_rx) = register(&server, "127.0.0.1:48230").await;
+    let (guest, mut guest_rx) = register(&server, "127.0.0.2:48231").await;
+
+    join(&server, &host, "Host").await;
+    join(&server, &guest, "Guest").await;
+    drain(&mut guest_rx);
+
+    server.handle_kick_player(&guest, &host, true, None).await;
+
+    let denied = next_message(&mut guest_rx).await;
+    assert!(
+        matches!(
+            *denied,
+            ServerMessage::Error(ErrorDetail {
+                code: ErrorCode::AuthorityDenied,
+                ..
+            })
+        ),
+        "expected AuthorityDenied, got {denied:?}"
+    );
+    assert!(server.get_client_room(&host).await.is_some());
+}
+
+/// Room with a creator, an actor holding `role` and a victim. A `Creator` actor
+/// is the room creator after it releases authority.
+async fn room_with_actor_role(
+    role: PlayerRole,
+) -> (
+    Arc<EnhancedGameServer>,
+    PlayerId,
+    mpsc::Receiver<Outbound>,
+    PlayerId,
+    mpsc::Receiver<Outbound>,
+) {
+    let server = create_test_server().await;
+    let (host, mut host_rx) = register(&server, "127.0.0.1:48240").await;
+    let (other, other_rx) = register(&server, "127.0.0.2:48241").await;
+    let (victim, mut victim_rx) = register(&server, "127.0.0.3:48242").await;
+    join(&server, &host, "Host").await;
+    join(&server, &other, "Other").await;
+    join(&server, &victim, "Victim").await;
+
+    let (actor, mut actor_rx) = match role {
+        PlayerRole::Creator => {
+            server.handle_authority_request(&host, false).await;
+            (host, host_rx)
+        }
+        PlayerRole::Member => (other, other_rx),
+        _ => {
+            server.handle_assign_role(&host, &other, role).await;
+            drain(&mut host_rx);
+            (other, other_rx)
+        }
+    };
+    drain(&mut actor_rx);
+    drain(&mut victim_rx);
+    (server, actor, actor_rx, victim, victim_rx)
+}
+
+fn is_denied(message: &ServerMessage) -> bool {
+    matches!(
+        message,
+        ServerMessage::Error(ErrorDetail {
+            code: ErrorCode::AuthorityDenied,
+            ..
+        })
+    )
+}
+
+#[tokio::test]
+async fn privileged_actions_follow_role_permissions() {
+    // (role, may kick, may unban, may assign roles)
+    let matrix = [
+        (PlayerRole::Creator, false, false, true),
+        (PlayerRole::Authority, true, true, true),
+        (PlayerRole::Moderator, true, true, false),
+        (PlayerRole::Member, false, false, false),
+    ];
+
+    for (role, may_kick, may_unban, may_assign) in matrix {
+        let (server, actor, mut actor_rx, victim, mut victim_rx) = room_with_actor_role(role).await;
+        server
+            .handle_kick_player(&actor, &victim, false, None)
+            .await;
+        if may_kick {
+            let kicked = next_message(&mut victim_rx).await;
+            assert!(
+                matches!(*kicked, ServerMessage::Kicked { .. }),
+                "{role:?} kick: {kicked:?}"
+            );
+        } else {
+            let denied = next_message(&mut actor_rx).await;
+            assert!(is_denied(&denied), "{role:?} kick: {denied:?}");
+        }
+
+        // Unbanning a player who was never banned is rejected only after the permission check
+        let (server, actor, mut actor_rx, _, _) = room_with_actor_role(role).await;
+        server
+            .handle_unban_player(&actor, &uuid::Uuid::new_v4())
+            .await;
+        let response = next_message(&mut actor_rx).await;
+        assert_eq!(
+            !is_denied(&response),
+            may_unban,
+            "{role:?} unban: {response:?}"
+        );
+
+        let (server, actor, mut actor_rx, victim, _) = room_with_actor_role(role).await;
+        server
+            .handle_assign_role(&actor, &victim, PlayerRole::Moderator)
+            .await;
+        let response = next_message(&mut actor_rx).await;
+        if may_assign {
+            assert!(
+                matches!(
+                    *response,
+                    ServerMessage::PlayerRoleChanged {
+                        player_id,
+                        role: PlayerRole::Moderator,
+                    } if player_id == victim
+                ),
+                "{role:?} assign: {response:?}"
+            );
+        } else {
+            assert!(is_denied(&response), "{role:?} assign: {response:?}");
+        }
+    }
+}
+
+#[tokio::test]
+async fn roles_appear_in_room_joined_and_creator_role_is_fixed() {
+    let server = create_test_server().await;
+    let (host, mut host_rx) = register(&server, "127.0.0.1:48250").await;
+    let (guest, mut guest_rx) = register(&server, "127.0.0.2:48251").await;
+
+    join(&server, &host, "Host").await;
+    match &*next_message(&mut host_rx).await {
+        ServerMessage::RoomJoined(payload) => assert_eq!(payload.role, PlayerRole::Creator),
+        other => panic!("expected RoomJoined, got {other:?}"),
+    }
+    join(&server, &guest, "Guest").await;
+    match &*next_message(&mut guest_rx).await {
+        ServerMessage::RoomJoined(payload) => {
+            assert_eq!(payload.role, PlayerRole::Member);
+            let host_info = payload
+                .current_players
+                .iter()
+                .find(|player| player.id == host)
+                .expect("host listed");
+            assert_eq!(host_info.role, PlayerRole::Creator);
+        }
+        other => panic!("expected RoomJoined, got {other:?}"),
+    }
+    drain(&mut host_rx);
+
+    server
+        .handle_assign_role(&host, &guest, PlayerRole::Authority)
+        .await;
+    assert!(matches!(
+        *next_message(&mut guest_rx).await,
+        ServerMessage::PlayerRoleChanged {
+            role: PlayerRole::Authority,
+            ..
+        }
+    ));
+    drain(&mut host_rx);
+
+    // The assigned authority role cannot touch the creator or hand out the creator role
+    server
+        .handle_assign_role(&guest, &host, PlayerRole::Member)
+        .await;
+    assert!(matches!(
+        *next_message(&mut guest_rx).await,
+        ServerMessage::Error(ErrorDetail {
+            code: ErrorCode::InvalidInput,
+            ..
+        })
+    ));
+    server
+        .handle_assign_role(&host, &guest, PlayerRole::Creator)
+        .await;
+    assert!(matches!(
+        *next_message(&mut host_rx).await,
+        ServerMessage::Error(ErrorDetail {
+            code: ErrorCode::InvalidInput,
+            ..
+        })
+    ));
+}
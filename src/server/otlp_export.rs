@@ -0,0 +1,29 @@
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+
+use super::EnhancedGameServer;
+
+impl EnhancedGameServer {
+    /// Refresh the OTLP mirror of the Prometheus metrics once per export
+    /// interval. The exporter pushes on its own schedule, so values are at
+    /// most one interval old when they leave.
+    pub(super) fn start_otlp_export(server: &Arc<Self>, interval: Duration) {
+        if server.otlp_exporter.is_none() {
+            return;
+        }
+        let weak: Weak<Self> = Arc::downgrade(server);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                let Some(server) = weak.upgrade() else {
+                    break;
+                };
+                let exposition = crate::websocket::render_server_metrics(&server).await;
+                if let Some(exporter) = &server.otlp_exporter {
+                    exporter.update(&exposition);
+                }
+            }
+        });
+    }
+}
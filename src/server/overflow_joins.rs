@@ -0,0 +1,357 @@
+//! Overflow joins: a full room created with `allow_overflow_joins` asks its
+//! authority before turning a player away.
+//!
+//! The join is parked and the authority receives `JoinRequest`. Accepting it
+//! with `RespondToJoinRequest` seats the player beyond `max_players`, up to
+//! `protocol.overflow_players_limit`. The join fails when the authority
+//! declines, does not answer within `server.join_request_timeout`, or leaves,
+//! or when the room closes first. Admitted players count toward the lobby
+//! like everyone else.
+
+use crate::protocol::{
+    ErrorCode, ErrorDetail, PlayerId, PlayerInfo, PlayerRole, Room, RoomId, ServerMessage,
+};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use tokio::time::Instant;
+
+use super::room_ordering::RoomTurn;
+use super::room_service::join_error_code_and_details;
+use super::{EnhancedGameServer, PlayerBannedError, RoomFullError};
+
+/// A join to a full room waiting for the authority's answer.
+#[derive(Debug, Clone)]
+pub(super) struct PendingJoinRequest {
+    pub(super) room_id: RoomId,
+    pub(super) player_name: String,
+    /// When the request times out; brought forward when the room closes
+    pub(super) deadline: Instant,
+    pub(super) room_closed: bool,
+}
+
+impl EnhancedGameServer {
+    /// Park a join refused because `room_id` is full and ask the room's
+    /// authority to admit the player. Returns `false` when the room does not
+    /// allow overflow joins, has no authority, or is at its overflow limit,
+    /// and the join should fail as usual.
+    pub(super) async fn request_overflow_join(
+        &self,
+        player_id: &PlayerId,
+        room_id: &RoomId,
+        player_name: &str,
+    ) -> bool {
+        let room = match self.database.get_room_by_id(room_id).await {
+            Ok(Some(room)) if room.allow_overflow_joins => room,
+            _ => return false,
+        };
+        let Some(authority_id) = room.authority_player else {
+            return false;
+        };
+        if room.players.len() >= self.overflow_limit(&room) as usize {
+            return false;
+        }
+
+        let timeout = self.config().join_request_timeout;
+        self.join_requests.insert(
+            *player_id,
+            PendingJoinRequest {
+                room_id: room.id,
+                player_name: player_name.to_string(),
+                deadline: Instant::now() + timeout,
+                room_closed: false,
+            },
+        );
+        let _ = self
+            .message_coordinator
+            .send_to_player(
+                &authority_id,
+                Arc::new(ServerMessage::JoinRequest {
+                    player_id: *player_id,
+                    name: player_name.to_string(),
+                }),
+            )
+            .await;
+        tracing::info!(%player_id, room_id = %room.id, %authority_id, "Asked room authority to admit overflow join");
+        true
+    }
+
+    /// Handle the room authority's answer to a `JoinRequest`.
+    pub async fn handle_respond_to_join_request(
+        &self,
+        authority_id: &PlayerId,
+        requester_id: &PlayerId,
+        accept: bool,
+    ) {
+        let Some(room) = self.requester_room(authority_id).await else {
+            return;
+        };
+        if room.authority_player != Some(*authority_id) {
+            let _ = self
+                .send_error_to_player(
+                    authority_id,
+                    ErrorDetail::new(
+                        ErrorCode::AuthorityDenied,
+                        "Only the room authority can answer join requests",
+                    ),
+                )
+                .await;
+            return;
+        }
+        let Some((_, request)) = self
+            .join_requests
+            .remove_if(requester_id, |_, request| request.room_id == room.id)
+        else {
+            let _ = self
+                .send_error_to_player(
+                    authority_id,
+                    ErrorDetail::new(
+                        ErrorCode::InvalidInput,
+                        "No pending join request from that player",
+                    ),
+                )
+                .await;
+            return;
+        };
+
+        if !accept {
+            tracing::info!(player_id = %requester_id, room_id = %room.id, "Room authority declined overflow join");
+            self.send_join_failed(
+                requester_id,
+                "The room authority declined your join request".to_string(),
+                ErrorCode::JoinRequestRejected,
+            )
+            .await;
+            return;
+        }
+        self.admit_overflow_player(requester_id, &room, request.player_name)
+            .await;
+    }
+
+    /// Seat an accepted requester, even beyond `max_players`.
+    async fn admit_overflow_player(&self, player_id: &PlayerId, room: &Room, player_name: String) {
+        // The requester may have joined elsewhere while waiting
+        if !self.connection_manager.has_client(player_id)
+            || self.get_client_room(player_id).await.is_some()
+        {
+            return;
+        }
+
+        let lock_key = format!("room_join:{}:{}", room.game_name, room.code);
+        let lock_handle = match self
+            .distributed_lock
+            .acquire(&lock_key, Duration::from_secs(10))
+            .await
+        {
+            Ok(handle) => handle,
+            Err(e) => {
+                tracing::warn!(room_id = %room.id, error = %e, "Failed to lock room for overflow join");
+                self.send_join_failed(player_id, e.to_string(), ErrorCode::InternalError)
+                    .await;
+                return;
+            }
+        };
+        let seated = self
+            .seat_overflow_player(player_id, &room.id, player_name)
+            .await;
+        let _ = self.distributed_lock.release(&lock_handle).await;
+
+        match seated {
+            Ok(Some((room, turn))) => {
+                self.metrics.increment_rooms_joined();
+                self.metrics.increment_players_joined();
+                let player_name = room
+                    .players
+                    .get(player_id)
+                    .map(|player| player.name.clone())
+                    .unwrap_or_default();
+                self.announce_room_join(player_id, &room, player_name, turn)
+                    .await;
+                tracing::info!(
+                    %player_id,
+                    room_id = %room.id,
+                    players = room.players.len(),
+                    max_players = room.max_players,
+                    "Admitted overflow join"
+                );
+            }
+            Ok(None) => {
+                self.send_join_failed(
+                    player_id,
+                    "Room not found".to_string(),
+                    ErrorCode::RoomNotFound,
+                )
+                .await;
+            }
+            Err(e) => {
+                let (error_code, details) = join_error_code_and_details(&e);
+                self.send_join_failed_with_details(player_id, e.to_string(), error_code, details)
+                    .await;
+            }
+        }
+    }
+
+    /// Add the player to the room, which must be locked for joins. Returns
+    /// the updated room and the turn the player was seated in, or `None` if
+    /// the room no longer exists.
+    async fn seat_overflow_player(
+        &self,
+        player_id: &PlayerId,
+        room_id: &RoomId,
+        player_name: String,
+    ) -> anyhow::Result<Option<(Room, RoomTurn)>> {
+        let Some(mut room) = self.database.get_room_by_id(room_id).await? else {
+            return Ok(None);
+        };
+        if room.is_banned(player_id, self.connection_manager.client_ip(player_id)) {
+            return Err(anyhow::anyhow!(PlayerBannedError));
+        }
+
+        // Seat the player only while the room's application has space
+        let room_app_id = room
+            .application_id
+            .or_else(|| self.room_application_id(&room.id));
+        let mut app_quota_lock = None;
+        if let Some(app_id) = room_app_id {
+            let quota = self.app_quota(&app_id);
+            if quota.max_players_per_app.is_some() {
+                app_quota_lock = self.acquire_app_quota_lock(&app_id).await;
+                if let Err(e) = self.check_app_player_quota(&app_id, &quota).await {
+                    if let Some(lock) = &app_quota_lock {
+                        let _ = self.distributed_lock.release(lock).await;
+                    }
+                    return Err(e);
+                }
+            }
+        }
+
+        let mut player_info = PlayerInfo {
+            id: *player_id,
+            name: player_name,
+            is_authority: false,
+            role: PlayerRole::Member,
+            is_ready: false,
+            connected_at: chrono::Utc::now(),
+            connection_info: None,
+            region_id: room.region_id.clone(),
+        };
+        let added = self
+            .add_player_with_name_policy(&room, &mut player_info, Some(self.overflow_limit(&room)))
+            .await;
+        if let Some(lock) = &app_quota_lock {
+            let _ = self.distributed_lock.release(lock).await;
+        }
+        let Some(turn) = added? else {
+            return Err(anyhow::anyhow!(RoomFullError {
+                room_id: room.id,
+                max_players: room.max_players,
+            }));
+        };
+        room.players.insert(*player_id, player_info);
+        Ok(Some((room, turn)))
+    }
+
+    /// Most players `room` may hold once its authority admits overflow joins.
+    fn overflow_limit(&self, room: &Room) -> u8 {
+        self.protocol_config
+            .overflow_players_limit
+            .max(room.max_players)
+    }
+
+    /// Drop a player's pending join request, e.g. because they disconnected.
+    /// Returns true if the player had one.
+    pub(super) fn cancel_join_request(&self, player_id: &PlayerId) -> bool {
+        self.join_requests.remove(player_id).is_some()
+    }
+
+    /// Fail every join request waiting on `room_id`'s authority.
+    pub(super) async fn reject_room_join_requests(&self, room_id: &RoomId, reason: &str) {
+        let requesters: Vec<PlayerId> = self
+            .join_requests
+            .iter()
+            .filter(|entry| entry.value().room_id == *room_id)
+            .map(|entry| *entry.key())
+            .collect();
+        for player_id in requesters {
+            if self
+                .join_requests
+                .remove_if(&player_id, |_, request| request.room_id == *room_id)
+                .is_some()
+            {
+                self.send_join_failed(
+                    &player_id,
+                    reason.to_string(),
+                    ErrorCode::JoinRequestRejected,
+                )
+                .await;
+            }
+        }
+    }
+
+    /// Fail the join requests for a room that no longer exists on the next
+    /// expiry pass.
+    pub(super) fn close_room_join_requests(&self, room_id: &RoomId) {
+        let now = Instant::now();
+        for mut entry in self.join_requests.iter_mut() {
+            if entry.room_id == *room_id {
+                entry.deadline = now;
+                entry.room_closed = true;
+            }
+        }
+    }
+
+    /// Fail every join request whose deadline has passed.
+    pub(super) async fn expire_join_requests(&self) {
+        let now = Instant::now();
+        let lapsed: Vec<PlayerId> = self
+            .join_requests
+            .iter()
+            .filter(|entry| entry.value().deadline <= now)
+            .map(|entry| *entry.key())
+            .collect();
+        for player_id in lapsed {
+            // The authority may have answered since the scan.
+            let Some((_, request)) = self
+                .join_requests
+                .remove_if(&player_id, |_, request| request.deadline <= now)
+            else {
+                continue;
+            };
+            if request.room_closed {
+                self.send_join_failed(
+                    &player_id,
+                    "Room closed before the authority answered".to_string(),
+                    ErrorCode::RoomNotFound,
+                )
+                .await;
+            } else {
+                tracing::info!(%player_id, room_id = %request.room_id, "Overflow join request timed out");
+                self.send_join_failed(
+                    &player_id,
+                    "The room authority did not answer your join request in time".to_string(),
+                    ErrorCode::JoinRequestTimedOut,
+                )
+                .await;
+            }
+        }
+    }
+
+    /// Spawn the loop that fails lapsed join requests. It stops once the
+    /// server is dropped.
+    pub(super) fn start_join_request_expiry(server: &Arc<Self>) {
+        let weak: Weak<Self> = Arc::downgrade(server);
+        let tick = (server.config().join_request_timeout / 10)
+            .clamp(Duration::from_millis(10), Duration::from_millis(250));
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(tick);
+            loop {
+                interval.tick().await;
+                let Some(server) = weak.upgrade() else {
+                    break;
+                };
+                if !server.join_requests.is_empty() {
+                    server.expire_join_requests().await;
+                }
+            }
+        });
+    }
+}
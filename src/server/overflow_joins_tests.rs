@@ -0,0 +1,266 @@
+use super::*;
+use crate::config::{
+    AuthMaintenanceConfig, CoordinationConfig, MetricsConfig, ProtocolConfig, RelayTypeConfig,
+    TransportSecurityConfig,
+};
+use crate::database::DatabaseConfig;
+use crate::protocol::{ErrorCode, ErrorDetail, LobbyState, ServerMessage};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::time::{timeout, Duration};
+
+const GAME: &str = "overflow-game";
+const CODE: &str = "OVER01";
+
+async fn create_test_server(join_request_timeout: Duration) -> Arc<EnhancedGameServer> {
+    EnhancedGameServer::new(
+        ServerConfig {
+            join_request_timeout,
+            ..ServerConfig::default()
+        },
+        ProtocolConfig::default(),
+        RelayTypeConfig::default(),
+        DatabaseConfig::InMemory,
+        MetricsConfig::default(),
+        AuthMaintenanceConfig::default(),
+        CoordinationConfig::default(),
+        TransportSecurityConfig::default(),
+        Vec::new(),
+    )
+    .await
+    .expect("failed to construct test server")
+}
+
+async fn register(server: &EnhancedGameServer, port: u16) -> (PlayerId, mpsc::Receiver<Outbound>) {
+    let (sender, receiver) = mpsc::channel(32);
+    let addr: SocketAddr = format!("127.0.0.{}:{port}", port % 250 + 1)
+        .parse()
+        .unwrap();
+    let player_id = server
+        .connection_manager
+        .register_client(sender, addr, server.instance_id)
+        .await
+        .expect("client registration succeeds");
+    (player_id, receiver)
+}
+
+/// Join the two-player test room, creating it with overflow joins allowed.
+async fn join(server: &EnhancedGameServer, player_id: &PlayerId, name: &str) {
+    server
+        .join_room_request(
+            player_id,
+            GAME.to_string(),
+            Some(CODE.to_string()),
+            name.to_string(),
+            Some(2),
+            Some(true),
+            None,
+            None,
+            None,
+            Some(true),
+            None,
+            false,
+        )
+        .await;
+}
+
+async fn next_message(receiver: &mut mpsc::Receiver<Outbound>) -> Outbound {
+    timeout(Duration::from_secs(1), receiver.recv())
+        .await
+        .expect("message arrives in time")
+        .expect("channel still open")
+}
+
+fn drain(receiver: &mut mpsc::Receiver<Outbound>) {
+    while receiver.try_recv().is_ok() {}
+}
+
+/// Wait for the requester's `RoomJoinFailed` and return its error code.
+async fn join_failure(receiver: &mut mpsc::Receiver<Outbound>) -> Option<ErrorCode> {
+    loop {
+        if let ServerMessage::RoomJoinFailed { error_code, .. } = &*next_message(receiver).await {
+            return error_code.clone();
+        }
+    }
+}
+
+/// Fill the room with a host and a guest, then have `requester` ask to join.
+async fn fill_and_request(
+    server: &EnhancedGameServer,
+    base_port: u16,
+) -> (
+    (PlayerId, mpsc::Receiver<Outbound>),
+    (PlayerId, mpsc::Receiver<Outbound>),
+) {
+    let (host, mut host_rx) = register(server, base_port).await;
+    let (guest, _) = register(server, base_port + 1).await;
+    let (requester, requester_rx) = register(server, base_port + 2).await;
+
+    join(server, &host, "Host").await;
+    join(server, &guest, "Guest").await;
+    drain(&mut host_rx);
+
+    join(server, &requester, "Late").await;
+    let request = next_message(&mut host_rx).await;
+    assert!(
+        matches!(*request, ServerMessage::JoinRequest { player_id, ref name } if player_id == requester && name == "Late"),
+        "expected JoinRequest for the requester, got {request:?}"
+    );
+    ((host, host_rx), (requester, requester_rx))
+}
+
+#[tokio::test]
+async fn accepted_request_seats_player_beyond_max_players() {
+    let server = create_test_server(Duration::from_secs(30)).await;
+    let ((host, mut host_rx), (requester, mut requester_rx)) =
+        fill_and_request(&server, 48400).await;
+
+    server
+        .handle_respond_to_join_request(&host, &requester, true)
+        .await;
+
+    let joined = next_message(&mut requester_rx).await;
+    let ServerMessage::RoomJoined(payload) = &*joined else {
+        panic!("expected RoomJoined, got {joined:?}");
+    };
+    assert_eq!(payload.current_players.len(), 3);
+    assert_eq!(payload.lobby_state, LobbyState::Lobby);
+
+    let room_id = server
+        .get_client_room(&requester)
+        .await
+        .expect("admitted player is assigned to the room");
+    let room = server
+        .database
+        .get_room_by_id(&room_id)
+        .await
+        .unwrap()
+        .expect("room exists");
+    assert_eq!(room.players.len(), 3);
+    assert_eq!(room.max_players, 2);
+    assert_eq!(room.lobby_state, LobbyState::Lobby);
+    assert!(server.join_requests.is_empty());
+
+    let mut announced = false;
+    while let Ok(message) = host_rx.try_recv() {
+        announced |=
+            matches!(&*message, ServerMessage::PlayerJoined { player } if player.id == requester);
+    }
+    assert!(announced, "the authority is told about the admitted player");
+}
+
+#[tokio::test]
+async fn declined_request_fails_the_join() {
+    let server = create_test_server(Duration::from_secs(30)).await;
+    let ((host, _host_rx), (requester, mut requester_rx)) = fill_and_request(&server, 48410).await;
+
+    server
+        .handle_respond_to_join_request(&host, &requester, false)
+        .await;
+
+    assert_eq!(
+        join_failure(&mut requester_rx).await,
+        Some(ErrorCode::JoinRequestRejected)
+    );
+    assert!(server.get_client_room(&requester).await.is_none());
+}
+
+#[tokio::test]
+async fn unanswered_request_times_out() {
+    let server = create_test_server(Duration::from_millis(100)).await;
+    let (_host, (requester, mut requester_rx)) = fill_and_request(&server, 48420).await;
+
+    assert_eq!(
+        join_failure(&mut requester_rx).await,
+        Some(ErrorCode::JoinRequestTimedOut)
+    );
+    assert!(server.join_requests.is_empty());
+    assert!(server.get_client_room(&requester).await.is_none());
+}
+
+#[tokio::test]
+async fn only_the_authority_can_answer() {
+    let server = create_test_server(Duration::from_secs(30)).await;
+    let (host, mut host_rx) = register(&server, 48430).await;
+    let (guest, mut guest_rx) = register(&server, 48431).await;
+    let (requester, _) = register(&server, 48432).await;
+
+    join(&server, &host, "Host").await;
+    join(&server, &guest, "Guest").await;
+    join(&server, &requester, "Late").await;
+    drain(&mut host_rx);
+    drain(&mut guest_rx);
+
+    server
+        .handle_respond_to_join_request(&guest, &requester, true)
+        .await;
+
+    let denied = next_message(&mut guest_rx).await;
+    assert!(
+        matches!(
+            &*denied,
+            ServerMessage::Error(ErrorDetail {
+                code: ErrorCode::AuthorityDenied,
+                ..
+            })
+        ),
+        "expected AuthorityDenied, got {denied:?}"
+    );
+    assert!(server.join_requests.contains_key(&requester));
+}
+
+#[tokio::test]
+async fn requests_are_dropped_when_the_requester_disconnects() {
+    let server = create_test_server(Duration::from_secs(30)).await;
+    let ((host, mut host_rx), (requester, _)) = fill_and_request(&server, 48440).await;
+
+    server.unregister_client(&requester).await;
+    assert!(server.join_requests.is_empty());
+
+    server
+        .handle_respond_to_join_request(&host, &requester, true)
+        .await;
+    let error = next_message(&mut host_rx).await;
+    assert!(
+        matches!(
+            &*error,
+            ServerMessage::Error(ErrorDetail {
+                code: ErrorCode::InvalidInput,
+                ..
+            })
+        ),
+        "expected InvalidInput for a withdrawn request, got {error:?}"
+    );
+}
+
+#[tokio::test]
+async fn requests_fail_when_the_authority_leaves() {
+    let server = create_test_server(Duration::from_secs(30)).await;
+    let ((host, _host_rx), (_requester, mut requester_rx)) = fill_and_request(&server, 48450).await;
+
+    server.leave_room(&host).await;
+
+    assert_eq!(
+        join_failure(&mut requester_rx).await,
+        Some(ErrorCode::JoinRequestRejected)
+    );
+    assert!(server.join_requests.is_empty());
+}
+
+#[tokio::test]
+async fn requests_fail_when_the_room_closes() {
+    let server = create_test_server(Duration::from_secs(30)).await;
+    let (_host, (_requester, mut requester_rx)) = fill_and_request(&server, 48460).await;
+
+    server
+        .admin_close_room(GAME, CODE)
+        .await
+        .expect("room closes");
+
+    assert_eq!(
+        join_failure(&mut requester_rx).await,
+        Some(ErrorCode::RoomNotFound)
+    );
+    assert!(server.join_requests.is_empty());
+}
@@ -0,0 +1,100 @@
+use super::room_service::JoinAttempt;
+use super::EnhancedGameServer;
+use crate::protocol::{ErrorCode, ErrorDetails, PlayerId, RoomId, ValidationConstraint};
+
+/// Existing rooms a quick join tries before creating one.
+const QUICK_JOIN_ATTEMPTS: usize = 3;
+
+impl EnhancedGameServer {
+    /// Join the fullest open room of `game_name`, or create a room if none
+    /// has space. Rooms in the player's region are tried first; a `region_id`
+    /// hint is remembered for the connection. A room that fills (or turns the
+    /// player away) between the lookup and the join is skipped for the next
+    /// candidate.
+    pub async fn handle_quick_join(
+        &self,
+        player_id: &PlayerId,
+        game_name: String,
+        player_name: String,
+        region_id: Option<String>,
+    ) {
+        if let Some(region_id) = region_id {
+            if !self.is_allowed_region(&region_id) {
+                let details = ErrorDetails::ValidationFailed {
+                    field: "region_id".to_string(),
+                    constraint: ValidationConstraint::AllowedValues,
+                    max: None,
+                };
+                self.send_join_failed_with_details(
+                    player_id,
+                    format!("Region '{region_id}' is not served here"),
+                    ErrorCode::InvalidInput,
+                    Some(details),
+                )
+                .await;
+                return;
+            }
+            self.connection_manager
+                .set_client_region(player_id, &region_id);
+        }
+        let region_id = self.client_region(player_id);
+
+        let mut tried: Vec<RoomId> = Vec::with_capacity(QUICK_JOIN_ATTEMPTS);
+        while tried.len() < QUICK_JOIN_ATTEMPTS {
+            let room = match self
+                .database
+                .find_joinable_room(&game_name, Some(&region_id))
+                .await
+            {
+                Ok(Some(room_id)) if !tried.contains(&room_id) => {
+                    tried.push(room_id);
+                    self.database.get_room_by_id(&room_id).await
+                }
+                // Only a room that already turned this player away is left
+                Ok(_) => break,
+                Err(e) => {
+                    tracing::warn!(%player_id, %game_name, "Quick join lookup failed: {}", e);
+                    break;
+                }
+            };
+            // Closed between the lookup and the fetch
+            let Ok(Some(room)) = room else {
+                continue;
+            };
+
+            let attempt = self
+                .join_room_request(
+                    player_id,
+                    game_name.clone(),
+                    Some(room.code),
+                    player_name.clone(),
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    true,
+                )
+                .await;
+            if attempt == JoinAttempt::Handled {
+                return;
+            }
+            tracing::debug!(%player_id, room_id = %room.id, "Quick join candidate unavailable, trying the next");
+        }
+
+        self.handle_join_room(
+            player_id,
+            game_name,
+            None,
+            player_name,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .await;
+    }
+}
@@ -0,0 +1,260 @@
+use super::*;
+use crate::config::{
+    AuthMaintenanceConfig, CoordinationConfig, DuplicateNamePolicy, MetricsConfig, ProtocolConfig,
+    RelayTypeConfig, TransportSecurityConfig,
+};
+use crate::database::DatabaseConfig;
+use crate::protocol::{ClientMessage, ErrorCode, ErrorDetails, RoomJoinedPayload, ServerMessage};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::time::{timeout, Duration};
+
+const GAME: &str = "quick-game";
+const OTHER_REGION: &str = "eu-west";
+
+async fn create_test_server() -> Arc<EnhancedGameServer> {
+    EnhancedGameServer::new(
+        ServerConfig {
+            allowed_regions: vec![OTHER_REGION.to_string()],
+            ..ServerConfig::default()
+        },
+        ProtocolConfig {
+            on_duplicate_name: DuplicateNamePolicy::Reject,
+            ..ProtocolConfig::default()
+        },
+        RelayTypeConfig::default(),
+        DatabaseConfig::InMemory,
+        MetricsConfig::default(),
+        AuthMaintenanceConfig::default(),
+        CoordinationConfig::default(),
+        TransportSecurityConfig::default(),
+        Vec::new(),
+    )
+    .await
+    .expect("failed to construct test server")
+}
+
+async fn register(server: &EnhancedGameServer, addr: &str) -> (PlayerId, mpsc::Receiver<Outbound>) {
+    let (sender, receiver) = mpsc::channel(32);
+    let addr: SocketAddr = addr.parse().unwrap();
+    let player_id = server
+        .connection_manager
+        .register_client(sender, addr, server.instance_id)
+        .await
+        .expect("client registration succeeds");
+    (player_id, receiver)
+}
+
+async fn host_room(server: &EnhancedGameServer, addr: &str, name: &str, code: &str) -> RoomId {
+    let (host, mut host_rx) = register(server, addr).await;
+    server
+        .handle_join_room(
+            &host,
+            GAME.to_string(),
+            Some(code.to_string()),
+            name.to_string(),
+            Some(3),
+            Some(false),
+            None,
+            None,
+            None,
+        )
+        .await;
+    joined(&mut host_rx).await.room_id
+}
+
+async fn quick_join(server: &EnhancedGameServer, player_id: &PlayerId, name: &str) {
+    quick_join_in(server, player_id, name, None).await;
+}
+
+async fn quick_join_in(
+    server: &EnhancedGameServer,
+    player_id: &PlayerId,
+    name: &str,
+    region_id: Option<&str>,
+) {
+    server
+        .handle_client_message(
+            player_id,
+            ClientMessage::QuickJoin {
+                game_name: GAME.to_string(),
+                player_name: name.to_string(),
+                region_id: region_id.map(str::to_string),
+            },
+        )
+        .await;
+}
+
+/// Skip room notifications until the player's own join result arrives.
+async fn joined(receiver: &mut mpsc::Receiver<Outbound>) -> RoomJoinedPayload {
+    loop {
+        let message = timeout(Duration::from_secs(1), receiver.recv())
+            .await
+            .expect("message arrives in time")
+            .expect("channel still open");
+        match &*message {
+            ServerMessage::RoomJoined(payload) => return (**payload).clone(),
+            ServerMessage::RoomJoinFailed { reason, .. } => panic!("join failed: {reason}"),
+            _ => {}
+        }
+    }
+}
+
+#[tokio::test]
+async fn quick_join_packs_players_into_the_fullest_room() {
+    let server = create_test_server().await;
+    host_room(&server, "127.0.0.1:49100", "Quiet", "QUIET1").await;
+    let busy = host_room(&server, "127.0.0.2:49101", "Busy", "BUSY01").await;
+    let (guest, mut guest_rx) = register(&server, "127.0.0.3:49102").await;
+    server
+        .handle_join_room(
+            &guest,
+            GAME.to_string(),
+            Some("BUSY01".to_string()),
+            "Guest".to_string(),
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .await;
+    joined(&mut guest_rx).await;
+
+    let (player, mut player_rx) = register(&server, "127.0.0.4:49103").await;
+    quick_join(&server, &player, "Quick").await;
+    let payload = joined(&mut player_rx).await;
+    assert_eq!(payload.room_id, busy);
+    assert_eq!(payload.current_players.len(), 3);
+}
+
+#[tokio::test]
+async fn quick_join_creates_a_room_when_none_has_space() {
+    let server = create_test_server().await;
+    let (player, mut player_rx) = register(&server, "127.0.0.1:49110").await;
+    quick_join(&server, &player, "First").await;
+    let payload = joined(&mut player_rx).await;
+    assert_eq!(payload.game_name, GAME);
+    assert_eq!(payload.current_players.len(), 1);
+}
+
+#[tokio::test]
+async fn quick_join_skips_a_room_that_refuses_the_player() {
+    let server = create_test_server().await;
+    let taken = host_room(&server, "127.0.0.1:49120", "Sam", "TAKEN1").await;
+
+    let (player, mut player_rx) = register(&server, "127.0.0.2:49121").await;
+    quick_join(&server, &player, "sam").await;
+    let payload = joined(&mut player_rx).await;
+    assert_ne!(payload.room_id, taken);
+    assert_eq!(payload.player_name, "sam");
+}
+
+#[tokio::test]
+async fn concurrent_quick_joins_never_fail_when_the_room_fills() {
+    let server = create_test_server().await;
+    let room = host_room(&server, "127.0.0.1:49130", "Host", "RACE01").await;
+
+    let mut joins = Vec::new();
+    for i in 0..6 {
+        let server = Arc::clone(&server);
+        let (player, mut player_rx) = register(&server, &format!("127.0.1.{i}:49131")).await;
+        joins.push(tokio::spawn(async move {
+            quick_join(&server, &player, &format!("Racer{i}")).await;
+            joined(&mut player_rx).await
+        }));
+    }
+    for join in joins {
+        join.await.expect("quick join task completes");
+    }
+
+    let room = server
+        .database
+        .get_room_by_id(&room)
+        .await
+        .unwrap()
+        .expect("room still exists");
+    assert_eq!(room.players.len(), usize::from(room.max_players));
+    assert!(server.waiting_players.is_empty());
+}
+
+#[tokio::test]
+async fn quick_join_prefers_rooms_in_the_players_region() {
+    let server = create_test_server().await;
+    let local = host_room(&server, "127.0.0.1:49140", "Local", "LOCAL1").await;
+    let (guest, mut guest_rx) = register(&server, "127.0.0.2:49141").await;
+    server
+        .handle_join_room(
+            &guest,
+            GAME.to_string(),
+            Some("LOCAL1".to_string()),
+            "Guest".to_string(),
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .await;
+    joined(&mut guest_rx).await;
+
+    // A room created by a player in the other region is stamped with it
+    let (remote_host, mut remote_host_rx) = register(&server, "127.0.0.3:49142").await;
+    server
+        .connection_manager
+        .set_client_region(&remote_host, OTHER_REGION);
+    server
+        .handle_join_room(
+            &remote_host,
+            GAME.to_string(),
+            Some("EURO01".to_string()),
+            "Euro1".to_string(),
+            Some(3),
+            Some(false),
+            None,
+            None,
+            None,
+        )
+        .await;
+    let remote = joined(&mut remote_host_rx).await.room_id;
+    let room = server.database.get_room_by_id(&remote).await.unwrap();
+    assert_eq!(room.expect("room exists").region_id, OTHER_REGION);
+
+    // A less full room in the player's region beats the fuller local room
+    let (second, mut second_rx) = register(&server, "127.0.0.4:49143").await;
+    quick_join_in(&server, &second, "Euro2", Some(OTHER_REGION)).await;
+    assert_eq!(joined(&mut second_rx).await.room_id, remote);
+
+    // Players without a hint are in the server's region
+    let (third, mut third_rx) = register(&server, "127.0.0.5:49144").await;
+    quick_join(&server, &third, "Home").await;
+    assert_eq!(joined(&mut third_rx).await.room_id, local);
+}
+
+#[tokio::test]
+async fn quick_join_rejects_regions_the_server_does_not_allow() {
+    let server = create_test_server().await;
+    let (player, mut player_rx) = register(&server, "127.0.0.1:49150").await;
+    quick_join_in(&server, &player, "Lost", Some("mars-north")).await;
+
+    let message = timeout(Duration::from_secs(1), player_rx.recv())
+        .await
+        .expect("message arrives in time")
+        .expect("channel still open");
+    match &*message {
+        ServerMessage::RoomJoinFailed {
+            error_code,
+            details,
+            ..
+        } => {
+            assert_eq!(*error_code, Some(ErrorCode::InvalidInput));
+            assert!(matches!(
+                details,
+                Some(ErrorDetails::ValidationFailed { field, .. }) if field == "region_id"
+            ));
+        }
+        other => panic!("expected RoomJoinFailed, got {other:?}"),
+    }
+    assert!(server.connection_manager.client_region(&player).is_none());
+}
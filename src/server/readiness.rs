@@ -0,0 +1,88 @@
+use tokio::sync::watch;
+use tokio::time::Instant;
+
+use super::EnhancedGameServer;
+
+/// A cleanup task that has not ticked for this many intervals is considered stalled.
+const CLEANUP_STALL_INTERVALS: u32 = 3;
+
+/// Liveness signal between the cleanup task and the readiness probe.
+///
+/// The running cleanup task takes the sender and publishes a timestamp every
+/// tick. If the task exits or panics the sender is dropped, which the probe
+/// sees as a closed channel.
+pub(super) struct CleanupHeartbeat {
+    sender: std::sync::Mutex<Option<watch::Sender<Instant>>>,
+    receiver: watch::Receiver<Instant>,
+}
+
+impl CleanupHeartbeat {
+    pub(super) fn new() -> Self {
+        let (sender, receiver) = watch::channel(Instant::now());
+        Self {
+            sender: std::sync::Mutex::new(Some(sender)),
+            receiver,
+        }
+    }
+
+    /// Claim the sender for a cleanup task; `None` if another task already holds it.
+    pub(super) fn claim(&self) -> Option<watch::Sender<Instant>> {
+        self.sender
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .take()
+    }
+
+    fn is_unclaimed(&self) -> bool {
+        self.sender
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .is_some()
+    }
+}
+
+impl EnhancedGameServer {
+    /// Whether this instance should receive traffic. See [`Self::readiness`].
+    pub async fn is_ready(&self) -> bool {
+        self.readiness().await.is_ok()
+    }
+
+    /// Check the drain flag, the database, the cleanup task and the message
+    /// coordinator, returning the first failure. All checks share `readiness_check_timeout`.
+    pub async fn readiness(&self) -> Result<(), &'static str> {
+        tokio::time::timeout(
+            self.config().readiness_check_timeout,
+            self.check_readiness(),
+        )
+        .await
+        .unwrap_or(Err("readiness checks timed out"))
+    }
+
+    async fn check_readiness(&self) -> Result<(), &'static str> {
+        if self.is_draining() {
+            return Err("instance is draining");
+        }
+        if !self.database.health_check().await {
+            return Err("database health check failed");
+        }
+        self.check_cleanup_task()?;
+        if !self.message_coordinator.is_accepting_messages().await {
+            return Err("message coordinator is not accepting messages");
+        }
+        Ok(())
+    }
+
+    fn check_cleanup_task(&self) -> Result<(), &'static str> {
+        if self.cleanup_heartbeat.is_unclaimed() {
+            return Err("cleanup task is not running");
+        }
+        if self.cleanup_heartbeat.receiver.has_changed().is_err() {
+            return Err("cleanup task stopped");
+        }
+        let last_tick = *self.cleanup_heartbeat.receiver.borrow();
+        if last_tick.elapsed() > self.config().room_cleanup_interval * CLEANUP_STALL_INTERVALS {
+            return Err("cleanup task stalled");
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,60 @@
+use super::*;
+use crate::config::{
+    AuthMaintenanceConfig, CoordinationConfig, MetricsConfig, ProtocolConfig, RelayTypeConfig,
+    TransportSecurityConfig,
+};
+use crate::database::DatabaseConfig;
+use std::sync::Arc;
+use tokio::time::{sleep, Duration, Instant};
+
+async fn create_test_server(room_cleanup_interval: Duration) -> Arc<EnhancedGameServer> {
+    EnhancedGameServer::new(
+        ServerConfig {
+            room_cleanup_interval,
+            ..ServerConfig::default()
+        },
+        ProtocolConfig::default(),
+        RelayTypeConfig::default(),
+        DatabaseConfig::InMemory,
+        MetricsConfig::default(),
+        AuthMaintenanceConfig::default(),
+        CoordinationConfig::default(),
+        TransportSecurityConfig::default(),
+        Vec::new(),
+    )
+    .await
+    .expect("failed to construct test server")
+}
+
+#[tokio::test]
+async fn ready_only_while_cleanup_task_runs() {
+    let server = create_test_server(Duration::from_secs(60)).await;
+    assert_eq!(server.readiness().await, Err("cleanup task is not running"));
+    assert!(!server.is_ready().await);
+
+    let cleanup_server = Arc::clone(&server);
+    let cleanup = tokio::spawn(async move { cleanup_server.cleanup_task().await });
+    sleep(Duration::from_millis(20)).await;
+    assert_eq!(server.readiness().await, Ok(()));
+    assert!(server.is_ready().await);
+
+    cleanup.abort();
+    let _ = cleanup.await;
+    assert_eq!(server.readiness().await, Err("cleanup task stopped"));
+}
+
+#[tokio::test]
+async fn stalled_cleanup_task_is_not_ready() {
+    let server = create_test_server(Duration::from_millis(10)).await;
+    let heartbeat = server
+        .cleanup_heartbeat
+        .claim()
+        .expect("heartbeat is unclaimed");
+    assert!(server.cleanup_heartbeat.claim().is_none());
+
+    sleep(Duration::from_millis(50)).await;
+    assert_eq!(server.readiness().await, Err("cleanup task stalled"));
+
+    heartbeat.send_replace(Instant::now());
+    assert_eq!(server.readiness().await, Ok(()));
+}
@@ -1,4 +1,6 @@
-use crate::protocol::{ErrorCode, PlayerId, ServerMessage};
+use crate::protocol::{ErrorCode, ErrorDetail, PlayerId, ServerMessage};
+use crate::relay_provisioning::RelaySessionUnavailable;
+use crate::room_events::RoomEventKind;
 use std::sync::Arc;
 
 use super::EnhancedGameServer;
@@ -11,41 +13,71 @@ impl EnhancedGameServer {
                 .message_coordinator
                 .send_to_player(
                     player_id,
-                    Arc::new(ServerMessage::Error {
-                        message: "Not in a room".to_string(),
-                        error_code: Some(ErrorCode::NotInRoom),
-                    }),
+                    Arc::new(ServerMessage::Error(ErrorDetail::new(
+                        ErrorCode::NotInRoom,
+                        "Not in a room",
+                    ))),
                 )
                 .await;
             return;
         };
 
-        if let Err(e) = self
+        match self
             .room_coordinator
             .handle_player_ready(&room_id, player_id, self.client_app_id(player_id))
             .await
         {
-            tracing::debug!(
-                "Player {:?} attempted to change ready status: {}",
-                player_id,
-                e
-            );
-            let error_message = if e.to_string().contains("room may not be in lobby state") {
-                "Cannot change ready status. Room must be in lobby state (full with all players joined)."
-                    .to_string()
-            } else {
-                "Failed to update ready state".to_string()
-            };
-            let _ = self
-                .message_coordinator
-                .send_to_player(
+            Ok(true) => {
+                self.closed_rooms.game_started(&room_id);
+                if self.room_events.has_observers() {
+                    if let Ok(Some(room)) = self.database.get_room_summary(&room_id).await {
+                        self.room_events.publish(
+                            room_id,
+                            &room.game_name,
+                            RoomEventKind::GameFinalized,
+                        );
+                    }
+                }
+            }
+            Ok(false) => {}
+            Err(e) => {
+                tracing::debug!(
+                    "Player {:?} attempted to change ready status: {}",
                     player_id,
-                    Arc::new(ServerMessage::Error {
-                        message: error_message,
-                        error_code: Some(ErrorCode::InvalidRoomState),
-                    }),
-                )
-                .await;
+                    e
+                );
+                let (error_message, error_code) = if e
+                    .to_string()
+                    .contains("room may not be in lobby state")
+                {
+                    (
+                        "Cannot change ready status. Room must be in lobby state (full with all players joined)."
+                            .to_string(),
+                        ErrorCode::InvalidRoomState,
+                    )
+                } else if e.downcast_ref::<RelaySessionUnavailable>().is_some() {
+                    (
+                        "Game cannot start until a relay session is available. Try again shortly."
+                            .to_string(),
+                        ErrorCode::ServiceUnavailable,
+                    )
+                } else {
+                    (
+                        "Failed to update ready state".to_string(),
+                        ErrorCode::InvalidRoomState,
+                    )
+                };
+                let _ = self
+                    .message_coordinator
+                    .send_to_player(
+                        player_id,
+                        Arc::new(ServerMessage::Error(ErrorDetail::new(
+                            error_code,
+                            error_message,
+                        ))),
+                    )
+                    .await;
+            }
         }
     }
 }
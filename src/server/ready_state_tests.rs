@@ -45,10 +45,10 @@ async fn handle_player_ready_without_room_returns_not_in_room_error() {
         .expect("message present");
 
     match response.as_ref() {
-        ServerMessage::Error { error_code, .. } => {
+        ServerMessage::Error(error) => {
             assert_eq!(
-                *error_code,
-                Some(ErrorCode::NotInRoom),
+                error.code,
+                ErrorCode::NotInRoom,
                 "ready handler should emit NotInRoom error when player lacks assignment"
             );
         }
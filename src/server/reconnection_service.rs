@@ -1,16 +1,30 @@
-use crate::protocol::{ErrorCode, PlayerId, PlayerInfo, ReconnectedPayload, RoomId, ServerMessage};
+use crate::protocol::{
+    ErrorCode, ErrorDetail, PlayerId, PlayerInfo, ReconnectedPayload, RoomId, ServerMessage,
+};
+use crate::reconnection::{ReconnectionError, ReconnectionManager, RECONNECTION_CAPABILITY};
 use std::sync::Arc;
 
 use super::EnhancedGameServer;
 
 impl EnhancedGameServer {
+    /// The reconnection manager, if reconnection is enabled and `player_id`'s
+    /// SDK supports it.
+    pub(crate) fn reconnection_manager_for(
+        &self,
+        player_id: &PlayerId,
+    ) -> Option<&Arc<ReconnectionManager>> {
+        self.reconnection_manager
+            .as_ref()
+            .filter(|_| self.client_has_capability(player_id, RECONNECTION_CAPABILITY))
+    }
+
     pub(crate) async fn register_disconnection_for_reconnect(
         &self,
         player_id: &PlayerId,
         room_id: RoomId,
         was_authority: bool,
     ) {
-        let Some(reconnection_manager) = &self.reconnection_manager else {
+        let Some(reconnection_manager) = self.reconnection_manager_for(player_id) else {
             return;
         };
 
@@ -57,26 +71,31 @@ impl EnhancedGameServer {
             .await
         {
             Ok(d) => d,
-            Err(reason) => {
+            // Theft indicator: answered with a plain error rather than a reconnect failure
+            Err(error @ ReconnectionError::TokenReplayed) => {
+                let _ = self
+                    .send_error_to_player(
+                        current_player_id,
+                        ErrorDetail::new(error.error_code(), error.to_string()),
+                    )
+                    .await;
+                return;
+            }
+            Err(error) => {
                 tracing::warn!(
                     %reconnect_player_id,
                     %room_id,
-                    %reason,
+                    reason = %error,
                     "Reconnection validation failed"
                 );
-                let error_code = if reason.contains("expired") {
-                    ErrorCode::ReconnectionExpired
-                } else if reason.contains("token") {
-                    ErrorCode::ReconnectionTokenInvalid
-                } else {
-                    ErrorCode::ReconnectionFailed
-                };
-
                 let _ = self
                     .message_coordinator
                     .send_to_player(
                         current_player_id,
-                        Arc::new(ServerMessage::ReconnectionFailed { reason, error_code }),
+                        Arc::new(ServerMessage::ReconnectionFailed {
+                            reason: error.to_string(),
+                            error_code: error.error_code(),
+                        }),
                     )
                     .await;
                 return;
@@ -102,6 +121,7 @@ impl EnhancedGameServer {
         let room = match self.database.get_room_by_id(room_id).await {
             Ok(Some(room)) => room,
             Ok(None) => {
+                reconnection_manager.record_room_gone(room_id).await;
                 let _ = self
                     .message_coordinator
                     .send_to_player(
@@ -130,17 +150,31 @@ impl EnhancedGameServer {
             }
         };
 
+        // Claim a seat held since the disconnect before its window lapses
+        let flap_resumed = self.resume_held_seat(reconnect_player_id, room_id);
+
         // Get missed events
         let missed_events = reconnection_manager
             .get_missed_events(room_id, disconnected.last_sequence)
             .await;
 
-        // Update client connection to use new sender
-        self.connection_manager.reassign_connection(
+        // Update client connection to use new sender, and route the reconnected
+        // player's messages to it
+        if self.connection_manager.reassign_connection(
             current_player_id,
             reconnect_player_id,
             *room_id,
-        );
+        ) {
+            let _ = self
+                .message_coordinator
+                .unregister_local_client(current_player_id)
+                .await;
+            self.connection_manager
+                .assign_client_to_room(reconnect_player_id, *room_id)
+                .await;
+            self.connection_manager
+                .set_client_game(reconnect_player_id, &room.game_name);
+        }
 
         // Update database last_seen
         if let Err(e) = self
@@ -155,10 +189,13 @@ impl EnhancedGameServer {
             );
         }
 
-        // Complete reconnection in manager
+        // Complete reconnection in manager and rotate the token, invalidating the one just used
         reconnection_manager
             .complete_reconnection(reconnect_player_id)
             .await;
+        let reconnection_token = reconnection_manager
+            .issue_token(*reconnect_player_id, *room_id)
+            .await;
 
         // Prepare room state
         let current_players: Vec<PlayerInfo> = room.players.values().cloned().collect();
@@ -180,28 +217,40 @@ impl EnhancedGameServer {
                     is_authority,
                     lobby_state: room.lobby_state.clone(),
                     ready_players: room.ready_players.clone(),
+                    lobby_version: room.lobby_version,
                     relay_type: room.relay_type.clone(),
                     current_spectators: room.get_spectators(),
                     missed_events,
+                    reconnection_token: Some(reconnection_token),
                 }))),
             )
             .await;
+        self.replay_room_history(reconnect_player_id, room_id).await;
 
-        // Notify other players
-        let notification = Arc::new(ServerMessage::PlayerReconnected {
-            player_id: *reconnect_player_id,
-        });
+        // Notify other players, unless the drop was short enough to hide from them
+        if flap_resumed {
+            tracing::info!(
+                %reconnect_player_id,
+                %room_id,
+                flaps = self.player_flap_count(reconnect_player_id),
+                "Reconnected within the flap window; room not notified"
+            );
+        } else {
+            let notification = Arc::new(ServerMessage::PlayerReconnected {
+                player_id: *reconnect_player_id,
+            });
 
-        for other_player_id in room.players.keys() {
-            if other_player_id != reconnect_player_id {
-                let _ = self
-                    .message_coordinator
-                    .send_to_player(other_player_id, Arc::clone(&notification))
-                    .await;
+            for other_player_id in room.players.keys() {
+                if other_player_id != reconnect_player_id {
+                    let _ = self
+                        .message_coordinator
+                        .send_to_player(other_player_id, Arc::clone(&notification))
+                        .await;
+                }
             }
+            self.metrics.increment_players_joined();
         }
 
-        self.metrics.increment_players_joined();
         tracing::info!(
             %reconnect_player_id,
             %room_id,
@@ -1,8 +1,27 @@
 use crate::auth::AppInfo;
+use crate::config::RelayWarmupTrigger;
+use crate::protocol::{validation, RelayTransport, Room};
+use crate::relay_provisioning::RelayProvisioner;
+use std::sync::Arc;
 
 use super::EnhancedGameServer;
 
 impl EnhancedGameServer {
+    /// Install the hook that provisions relay sessions ahead of game start.
+    ///
+    /// Call before serving traffic; rooms provisioned by the previous hook are
+    /// still torn down through the new one.
+    pub fn set_relay_provisioner(&self, provisioner: Arc<dyn RelayProvisioner>) {
+        self.relay_warmup.set_provisioner(provisioner);
+    }
+
+    /// Start provisioning the room's relay session if `reached` is the configured trigger.
+    pub(crate) fn warm_up_relay(&self, room: &Room, reached: RelayWarmupTrigger) {
+        if self.relay_warmup.config().trigger == reached {
+            self.relay_warmup.spawn_provision(room);
+        }
+    }
+
     /// Determine whether the client should use relay based on configuration.
     /// Note: signal-fish-server does not include relay servers - this always returns false.
     pub fn should_use_relay(&self, _relay_type: &str) -> bool {
@@ -15,13 +34,34 @@ impl EnhancedGameServer {
         // No-op: no relay server in signal-fish-server
     }
 
-    /// Resolve the relay type for a game based on configuration.
+    /// Resolve the relay type for a new room.
     /// This is used for protocol labeling even without a relay server.
-    pub(crate) fn resolve_relay_type(&self, game_name: &str) -> String {
-        self.relay_type_config
+    ///
+    /// An explicit request wins; otherwise the game mapping, then this
+    /// server's region mapping, then the default applies. Explicit requests
+    /// must already have passed [`Self::relay_type_allowed`].
+    pub(crate) fn resolve_relay_type(&self, game_name: &str, requested: Option<&str>) -> String {
+        if let Some(requested) = requested.filter(|relay| !is_auto_relay(relay)) {
+            return requested.to_string();
+        }
+        let config = &self.relay_type_config;
+        config
             .game_relay_mappings
             .get(game_name)
+            .or_else(|| config.region_relay_mappings.get(&self.region_id()))
             .cloned()
-            .unwrap_or_else(|| self.relay_type_config.default_relay_type.clone())
+            .unwrap_or_else(|| config.default_relay_type.clone())
     }
+
+    /// Whether a client may explicitly request `relay_type`.
+    ///
+    /// See [`validation::validate_relay_type`].
+    pub(crate) fn relay_type_allowed(&self, relay_type: &str) -> Result<(), String> {
+        validation::validate_relay_type(relay_type, &self.relay_type_config)
+    }
+}
+
+/// "auto" (any case) asks the server to choose the relay.
+fn is_auto_relay(relay_type: &str) -> bool {
+    RelayTransport::from_name(relay_type) == Some(RelayTransport::Auto)
 }
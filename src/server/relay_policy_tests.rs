@@ -0,0 +1,151 @@
+use super::*;
+use crate::config::{
+    AuthMaintenanceConfig, CoordinationConfig, MetricsConfig, ProtocolConfig, RelayTypeConfig,
+    TransportSecurityConfig,
+};
+use crate::database::DatabaseConfig;
+use crate::protocol::{ErrorCode, ServerMessage};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::time::{timeout, Duration};
+
+fn regional_relays() -> RelayTypeConfig {
+    RelayTypeConfig {
+        game_relay_mappings: HashMap::from([("chess".to_string(), "unity_netcode".to_string())]),
+        default_relay_type: "matchbox".to_string(),
+        region_relay_mappings: HashMap::from([
+            ("na".to_string(), "relay-na".to_string()),
+            ("eu".to_string(), "relay-eu".to_string()),
+        ]),
+        allowed_relay_types: Vec::new(),
+        ..RelayTypeConfig::default()
+    }
+}
+
+async fn create_test_server(region_id: &str, relays: RelayTypeConfig) -> Arc<EnhancedGameServer> {
+    EnhancedGameServer::new(
+        ServerConfig {
+            region_id: region_id.to_string(),
+            ..ServerConfig::default()
+        },
+        ProtocolConfig::default(),
+        relays,
+        DatabaseConfig::InMemory,
+        MetricsConfig::default(),
+        AuthMaintenanceConfig::default(),
+        CoordinationConfig::default(),
+        TransportSecurityConfig::default(),
+        Vec::new(),
+    )
+    .await
+    .expect("failed to construct test server")
+}
+
+/// Create a room as a fresh client and return the first response it receives.
+async fn create_room(
+    server: &EnhancedGameServer,
+    game_name: &str,
+    relay_type: Option<&str>,
+) -> Outbound {
+    let (sender, mut receiver) = mpsc::channel(8);
+    let addr: SocketAddr = "127.0.0.1:48500".parse().unwrap();
+    let player_id = server
+        .connection_manager
+        .register_client(sender, addr, server.instance_id)
+        .await
+        .expect("client registration succeeds");
+    server
+        .handle_join_room(
+            &player_id,
+            game_name.to_string(),
+            None,
+            "Host".to_string(),
+            Some(4),
+            None,
+            None,
+            relay_type.map(str::to_string),
+            None,
+        )
+        .await;
+    timeout(Duration::from_secs(1), receiver.recv())
+        .await
+        .expect("response arrives in time")
+        .expect("channel still open")
+}
+
+async fn created_relay(
+    server: &EnhancedGameServer,
+    game_name: &str,
+    relay_type: Option<&str>,
+) -> String {
+    let response = create_room(server, game_name, relay_type).await;
+    match response.as_ref() {
+        ServerMessage::RoomJoined(payload) => payload.relay_type.clone(),
+        other => panic!("expected RoomJoined, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn auto_relay_follows_server_region() {
+    let na = create_test_server("na", regional_relays()).await;
+    assert_eq!(created_relay(&na, "racing", Some("auto")).await, "relay-na");
+
+    let eu = create_test_server("eu", regional_relays()).await;
+    assert_eq!(created_relay(&eu, "racing", Some("AUTO")).await, "relay-eu");
+    assert_eq!(created_relay(&eu, "racing", None).await, "relay-eu");
+}
+
+#[tokio::test]
+async fn game_mapping_and_default_bracket_region_mapping() {
+    let eu = create_test_server("eu", regional_relays()).await;
+    assert_eq!(created_relay(&eu, "chess", None).await, "unity_netcode");
+
+    let apac = create_test_server("apac", regional_relays()).await;
+    assert_eq!(created_relay(&apac, "racing", None).await, "matchbox");
+}
+
+#[tokio::test]
+async fn explicit_relay_overrides_region() {
+    let na = create_test_server("na", regional_relays()).await;
+    assert_eq!(
+        created_relay(&na, "racing", Some("relay-eu")).await,
+        "relay-eu"
+    );
+}
+
+#[tokio::test]
+async fn explicit_relay_must_be_allowed() {
+    let na = create_test_server("na", regional_relays()).await;
+    let response = create_room(&na, "racing", Some("rogue-relay")).await;
+    assert!(
+        matches!(
+            response.as_ref(),
+            ServerMessage::RoomJoinFailed {
+                error_code: Some(ErrorCode::InvalidInput),
+                ..
+            }
+        ),
+        "expected InvalidInput, got {response:?}"
+    );
+
+    let allowlisted = create_test_server(
+        "na",
+        RelayTypeConfig {
+            allowed_relay_types: vec!["rogue-relay".to_string()],
+            ..regional_relays()
+        },
+    )
+    .await;
+    assert_eq!(
+        created_relay(&allowlisted, "racing", Some("rogue-relay")).await,
+        "rogue-relay"
+    );
+    // An explicit allowlist replaces the implicit one
+    let response = create_room(&allowlisted, "racing", Some("relay-eu")).await;
+    assert!(matches!(
+        response.as_ref(),
+        ServerMessage::RoomJoinFailed { .. }
+    ));
+}
@@ -0,0 +1,259 @@
+use super::*;
+use crate::config::{
+    AuthMaintenanceConfig, ConfigDuration, CoordinationConfig, MetricsConfig, ProtocolConfig,
+    RelayFailurePolicy, RelayTypeConfig, RelayWarmupConfig, RelayWarmupTrigger,
+    TransportSecurityConfig,
+};
+use crate::database::DatabaseConfig;
+use crate::protocol::{ErrorCode, ErrorDetail, PlayerId, RoomJoinedPayload, ServerMessage};
+use crate::relay_provisioning::FakeRelayProvisioner;
+use serde_json::Value;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::time::{sleep, timeout, Duration};
+
+const GAME: &str = "relay-game";
+
+fn warmup_config(trigger: RelayWarmupTrigger, on_failure: RelayFailurePolicy) -> RelayWarmupConfig {
+    RelayWarmupConfig {
+        trigger,
+        max_attempts: 3,
+        retry_delay_ms: ConfigDuration::from_millis(1),
+        on_failure,
+    }
+}
+
+async fn create_test_server(
+    warmup: RelayWarmupConfig,
+    provisioner: Arc<FakeRelayProvisioner>,
+) -> Arc<EnhancedGameServer> {
+    let server = EnhancedGameServer::new(
+        ServerConfig::default(),
+        ProtocolConfig::default(),
+        RelayTypeConfig {
+            warmup,
+            ..RelayTypeConfig::default()
+        },
+        DatabaseConfig::InMemory,
+        MetricsConfig::default(),
+        AuthMaintenanceConfig::default(),
+        CoordinationConfig::default(),
+        TransportSecurityConfig::default(),
+        Vec::new(),
+    )
+    .await
+    .expect("failed to construct test server");
+    server.set_relay_provisioner(provisioner);
+    server
+}
+
+struct TestClient {
+    id: PlayerId,
+    receiver: mpsc::Receiver<Outbound>,
+}
+
+impl TestClient {
+    async fn connect(server: &EnhancedGameServer, port: u16) -> Self {
+        let (sender, receiver) = mpsc::channel(32);
+        let addr: SocketAddr = format!("127.0.0.1:{port}").parse().unwrap();
+        let id = server
+            .connection_manager
+            .register_client(sender, addr, server.instance_id)
+            .await
+            .expect("client registration succeeds");
+        Self { id, receiver }
+    }
+
+    async fn join(&mut self, server: &EnhancedGameServer, name: &str) -> RoomJoinedPayload {
+        server
+            .handle_join_room(
+                &self.id,
+                GAME.to_string(),
+                Some("RELAY1".to_string()),
+                name.to_string(),
+                Some(2),
+                Some(true),
+                None,
+                None,
+                None,
+            )
+            .await;
+        match &*self
+            .next_matching(|msg| matches!(msg, ServerMessage::RoomJoined(_)))
+            .await
+        {
+            ServerMessage::RoomJoined(payload) => (**payload).clone(),
+            _ => unreachable!(),
+        }
+    }
+
+    async fn next_matching(&mut self, predicate: impl Fn(&ServerMessage) -> bool) -> Outbound {
+        timeout(Duration::from_secs(2), async {
+            loop {
+                let msg = self.receiver.recv().await.expect("channel still open");
+                if predicate(&msg) {
+                    return msg;
+                }
+            }
+        })
+        .await
+        .expect("expected message arrives in time")
+    }
+
+    fn drain(&mut self) -> Vec<Outbound> {
+        std::iter::from_fn(|| self.receiver.try_recv().ok()).collect()
+    }
+}
+
+async fn stored_session(server: &EnhancedGameServer) -> Option<Value> {
+    server
+        .database()
+        .get_room(GAME, "RELAY1")
+        .await
+        .expect("room lookup succeeds")
+        .and_then(|room| room.relay_session)
+}
+
+async fn wait_for_session(server: &EnhancedGameServer) -> Value {
+    timeout(Duration::from_secs(2), async {
+        loop {
+            if let Some(session) = stored_session(server).await {
+                return session;
+            }
+            sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("relay session is provisioned in time")
+}
+
+fn game_starting_session(msg: &ServerMessage) -> Option<Value> {
+    match msg {
+        ServerMessage::GameStarting { relay_session, .. } => relay_session.clone(),
+        other => panic!("expected GameStarting, got {other:?}"),
+    }
+}
+
+fn is_game_starting(msg: &ServerMessage) -> bool {
+    matches!(msg, ServerMessage::GameStarting { .. })
+}
+
+#[tokio::test]
+async fn warm_session_reaches_joiners_and_game_start_and_is_torn_down_on_close() {
+    let provisioner = Arc::new(FakeRelayProvisioner::new());
+    let server = create_test_server(
+        warmup_config(RelayWarmupTrigger::RoomCreated, RelayFailurePolicy::Proceed),
+        Arc::clone(&provisioner),
+    )
+    .await;
+
+    let mut host = TestClient::connect(&server, 48700).await;
+    host.join(&server, "Host").await;
+    let session = wait_for_session(&server).await;
+
+    let mut guest = TestClient::connect(&server, 48701).await;
+    let joined = guest.join(&server, "Guest").await;
+    assert_eq!(joined.relay_session.as_ref(), Some(&session));
+
+    server.handle_player_ready(&host.id).await;
+    server.handle_player_ready(&guest.id).await;
+    let starting = host.next_matching(is_game_starting).await;
+    assert_eq!(game_starting_session(&starting), Some(session));
+    assert_eq!(provisioner.provision_calls(), 1);
+
+    let closure = server
+        .admin_close_room(GAME, "RELAY1")
+        .await
+        .expect("admin close succeeds")
+        .expect("room exists");
+    assert_eq!(provisioner.torn_down(), vec![closure.room_id]);
+}
+
+#[tokio::test]
+async fn provisioning_retries_transient_failures() {
+    let provisioner = Arc::new(FakeRelayProvisioner::failing(2));
+    let server = create_test_server(
+        warmup_config(RelayWarmupTrigger::RoomCreated, RelayFailurePolicy::Block),
+        Arc::clone(&provisioner),
+    )
+    .await;
+
+    let mut host = TestClient::connect(&server, 48710).await;
+    host.join(&server, "Host").await;
+    wait_for_session(&server).await;
+    assert_eq!(provisioner.provision_calls(), 3);
+}
+
+#[tokio::test]
+async fn lobby_trigger_waits_for_the_room_to_fill() {
+    let provisioner = Arc::new(FakeRelayProvisioner::new());
+    let server = create_test_server(
+        warmup_config(RelayWarmupTrigger::Lobby, RelayFailurePolicy::Proceed),
+        Arc::clone(&provisioner),
+    )
+    .await;
+
+    let mut host = TestClient::connect(&server, 48720).await;
+    host.join(&server, "Host").await;
+    sleep(Duration::from_millis(50)).await;
+    assert_eq!(provisioner.provision_calls(), 0);
+    assert!(stored_session(&server).await.is_none());
+
+    let mut guest = TestClient::connect(&server, 48721).await;
+    guest.join(&server, "Guest").await;
+    wait_for_session(&server).await;
+    assert_eq!(provisioner.provision_calls(), 1);
+}
+
+#[tokio::test]
+async fn block_policy_holds_game_start_when_provisioning_fails() {
+    let provisioner = Arc::new(FakeRelayProvisioner::always_failing());
+    let server = create_test_server(
+        warmup_config(RelayWarmupTrigger::RoomCreated, RelayFailurePolicy::Block),
+        Arc::clone(&provisioner),
+    )
+    .await;
+
+    let mut host = TestClient::connect(&server, 48730).await;
+    host.join(&server, "Host").await;
+    let mut guest = TestClient::connect(&server, 48731).await;
+    guest.join(&server, "Guest").await;
+
+    server.handle_player_ready(&host.id).await;
+    server.handle_player_ready(&guest.id).await;
+    let error = guest
+        .next_matching(|msg| matches!(msg, ServerMessage::Error(_)))
+        .await;
+    assert!(matches!(
+        &*error,
+        ServerMessage::Error(ErrorDetail {
+            code: ErrorCode::ServiceUnavailable,
+            ..
+        })
+    ));
+
+    sleep(Duration::from_millis(20)).await;
+    assert!(!host.drain().iter().any(|msg| is_game_starting(msg)));
+    assert!(!guest.drain().iter().any(|msg| is_game_starting(msg)));
+}
+
+#[tokio::test]
+async fn proceed_policy_starts_without_a_session() {
+    let provisioner = Arc::new(FakeRelayProvisioner::always_failing());
+    let server = create_test_server(
+        warmup_config(RelayWarmupTrigger::RoomCreated, RelayFailurePolicy::Proceed),
+        Arc::clone(&provisioner),
+    )
+    .await;
+
+    let mut host = TestClient::connect(&server, 48740).await;
+    host.join(&server, "Host").await;
+    let mut guest = TestClient::connect(&server, 48741).await;
+    guest.join(&server, "Guest").await;
+
+    server.handle_player_ready(&host.id).await;
+    server.handle_player_ready(&guest.id).await;
+    let starting = guest.next_matching(is_game_starting).await;
+    assert_eq!(game_starting_session(&starting), None);
+}
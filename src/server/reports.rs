@@ -0,0 +1,132 @@
+//! Player reports: `ReportPlayer` records a report about another participant
+//! in the sender's room for operators to review.
+
+use chrono::Utc;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use super::EnhancedGameServer;
+use crate::protocol::{
+    ErrorCode, ErrorDetail, ErrorDetails, PlayerId, ReportReason, ServerMessage,
+    ValidationConstraint,
+};
+use crate::reports::{log_player_report, PlayerReport, MAX_REPORT_DETAILS_LENGTH};
+
+impl EnhancedGameServer {
+    /// Handle a player reporting a player or spectator in their room.
+    ///
+    /// Every request takes one use of
+    /// [`REPORT_QUOTA`](crate::rate_limit::REPORT_QUOTA), including ones
+    /// that are then rejected. Accepted reports are logged under
+    /// [`REPORTS_TARGET`](crate::reports::REPORTS_TARGET), sent to
+    /// `player_reported` webhooks and acknowledged with `ReportReceived`;
+    /// nothing happens to the target.
+    pub async fn handle_report_player(
+        &self,
+        reporter_id: &PlayerId,
+        target_id: &PlayerId,
+        reason: ReportReason,
+        details: Option<String>,
+    ) {
+        if let Err(err) = self.rate_limiter.check_report(reporter_id) {
+            tracing::debug!(%reporter_id, %target_id, "Player report rate limited");
+            let _ = self
+                .send_error_to_player(
+                    reporter_id,
+                    ErrorDetail::new(ErrorCode::RateLimitExceeded, err.to_string())
+                        .with_retry_after(err.retry_after())
+                        .with_details(err.details()),
+                )
+                .await;
+            return;
+        }
+
+        let details = details
+            .map(|details| details.trim().to_string())
+            .filter(|details| !details.is_empty());
+        if details
+            .as_ref()
+            .is_some_and(|details| details.chars().count() > MAX_REPORT_DETAILS_LENGTH)
+        {
+            let _ = self
+                .send_error_to_player(
+                    reporter_id,
+                    ErrorDetail::new(
+                        ErrorCode::InvalidInput,
+                        format!("details must be at most {MAX_REPORT_DETAILS_LENGTH} characters"),
+                    )
+                    .with_details(ErrorDetails::validation(
+                        "details",
+                        ValidationConstraint::MaxLength,
+                        Some(MAX_REPORT_DETAILS_LENGTH as u64),
+                    )),
+                )
+                .await;
+            return;
+        }
+
+        let Some(room) = self.requester_room(reporter_id).await else {
+            return;
+        };
+        let Some(reporter) = room.players.get(reporter_id) else {
+            let _ = self
+                .send_error_to_player(
+                    reporter_id,
+                    ErrorDetail::new(ErrorCode::NotInRoom, "Only players can report"),
+                )
+                .await;
+            return;
+        };
+        let target_name = if target_id == reporter_id {
+            None
+        } else {
+            room.players
+                .get(target_id)
+                .map(|player| player.name.clone())
+                .or_else(|| {
+                    room.spectators
+                        .get(target_id)
+                        .map(|spectator| spectator.name.clone())
+                })
+        };
+        let Some(target_name) = target_name else {
+            let _ = self
+                .send_error_to_player(
+                    reporter_id,
+                    ErrorDetail::new(ErrorCode::InvalidInput, "Target player is not in this room"),
+                )
+                .await;
+            return;
+        };
+
+        let report = PlayerReport {
+            report_id: Uuid::new_v4(),
+            reported_at: Utc::now(),
+            room_id: room.id,
+            room_code: room.code.clone(),
+            game_name: room.game_name.clone(),
+            reporter_id: *reporter_id,
+            reporter_name: reporter.name.clone(),
+            reporter_fingerprint: self.client_fingerprint(reporter_id),
+            target_id: *target_id,
+            target_name,
+            target_fingerprint: self.client_fingerprint(target_id),
+            reason,
+            details,
+        };
+        log_player_report(&report);
+        self.webhooks.player_reported(&report);
+        self.metrics.increment_player_reports();
+
+        let _ = self
+            .message_coordinator
+            .send_to_player(
+                reporter_id,
+                Arc::new(ServerMessage::ReportReceived {
+                    report_id: report.report_id,
+                    target_player_id: *target_id,
+                }),
+            )
+            .await;
+    }
+}
@@ -0,0 +1,249 @@
+use super::*;
+use crate::config::{
+    AuthMaintenanceConfig, CoordinationConfig, MetricsConfig, ProtocolConfig, RelayTypeConfig,
+    TransportSecurityConfig,
+};
+use crate::database::DatabaseConfig;
+use crate::protocol::{ErrorCode, ErrorDetail, ReportReason, ServerMessage};
+use crate::rate_limit::REPORT_QUOTA;
+use crate::reports::{MAX_REPORT_DETAILS_LENGTH, REPORTS_TARGET};
+use crate::security::ClientCertificateFingerprint;
+use std::io::Write;
+use std::net::SocketAddr;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+
+const GAME: &str = "report-game";
+const ROOM: &str = "RPRT01";
+
+#[derive(Clone, Default)]
+struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+impl Write for CapturedLogs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl CapturedLogs {
+    /// Fields of every JSON record logged under the reports target.
+    fn reports(&self) -> Vec<serde_json::Value> {
+        let output = self.0.lock().unwrap().clone();
+        String::from_utf8(output)
+            .expect("logs are UTF-8")
+            .lines()
+            .filter_map(|line| {
+                let mut record: serde_json::Value =
+                    serde_json::from_str(line).expect("each line is a JSON record");
+                (record["target"] == REPORTS_TARGET).then(|| record["fields"].take())
+            })
+            .collect()
+    }
+}
+
+async fn create_test_server() -> Arc<EnhancedGameServer> {
+    EnhancedGameServer::new(
+        ServerConfig::default(),
+        ProtocolConfig::default(),
+        RelayTypeConfig::default(),
+        DatabaseConfig::InMemory,
+        MetricsConfig::default(),
+        AuthMaintenanceConfig::default(),
+        CoordinationConfig::default(),
+        TransportSecurityConfig::default(),
+        Vec::new(),
+    )
+    .await
+    .expect("failed to construct test server")
+}
+
+async fn join(
+    server: &EnhancedGameServer,
+    index: u8,
+    name: &str,
+) -> (PlayerId, mpsc::Receiver<Outbound>) {
+    let (sender, mut receiver) = mpsc::channel(64);
+    let addr: SocketAddr = format!("10.6.0.{}:4000", index + 1).parse().unwrap();
+    let player_id = server
+        .connection_manager
+        .register_client(sender, addr, server.instance_id)
+        .await
+        .expect("client registration succeeds");
+    server
+        .handle_join_room(
+            &player_id,
+            GAME.to_string(),
+            Some(ROOM.to_string()),
+            name.to_string(),
+            Some(4),
+            Some(true),
+            None,
+            None,
+            None,
+        )
+        .await;
+    while receiver.try_recv().is_ok() {}
+    (player_id, receiver)
+}
+
+/// The next `ReportReceived` or error, skipping room broadcasts.
+fn next_message(receiver: &mut mpsc::Receiver<Outbound>) -> Outbound {
+    loop {
+        let message = receiver.try_recv().expect("a reply was sent");
+        if matches!(
+            &*message,
+            ServerMessage::ReportReceived { .. } | ServerMessage::Error { .. }
+        ) {
+            return message;
+        }
+    }
+}
+
+fn error_code(message: &ServerMessage) -> Option<ErrorCode> {
+    match message {
+        ServerMessage::Error(ErrorDetail { code, .. }) => Some(code.clone()),
+        _ => None,
+    }
+}
+
+#[tokio::test]
+async fn reports_are_logged_with_reporter_target_room_and_fingerprints() {
+    let server = create_test_server().await;
+    let (reporter, mut reporter_rx) = join(&server, 0, "Reporter").await;
+    let (target, _target_rx) = join(&server, 1, "Target").await;
+    server.set_client_fingerprint(
+        &target,
+        &ClientCertificateFingerprint {
+            fingerprint: Arc::from("ab:cd:ef"),
+            source_header: "x-client-cert-fingerprint",
+        },
+    );
+
+    let logs = CapturedLogs::default();
+    let writer = logs.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .json()
+        .with_writer(move || writer.clone())
+        .finish();
+    let guard = tracing::subscriber::set_default(subscriber);
+    server
+        .handle_report_player(
+            &reporter,
+            &target,
+            ReportReason::Cheating,
+            Some("  Speed hacking  ".to_string()),
+        )
+        .await;
+    drop(guard);
+
+    let report_id = match &*next_message(&mut reporter_rx) {
+        ServerMessage::ReportReceived {
+            report_id,
+            target_player_id,
+        } => {
+            assert_eq!(*target_player_id, target);
+            *report_id
+        }
+        other => panic!("expected ReportReceived, got {other:?}"),
+    };
+
+    let reports = logs.reports();
+    assert_eq!(reports.len(), 1);
+    let report = &reports[0];
+    let room_id = server.get_client_room(&reporter).await.unwrap();
+    assert_eq!(report["event"], "player_report");
+    assert_eq!(report["report_id"], report_id.to_string());
+    assert_eq!(report["room_id"], room_id.to_string());
+    assert_eq!(report["room_code"], ROOM);
+    assert_eq!(report["game_name"], GAME);
+    assert_eq!(report["reporter_id"], reporter.to_string());
+    assert_eq!(report["reporter_name"], "Reporter");
+    assert_eq!(report["target_id"], target.to_string());
+    assert_eq!(report["target_name"], "Target");
+    assert_eq!(report["target_fingerprint"], "ab:cd:ef");
+    assert!(report.get("reporter_fingerprint").is_none());
+    assert_eq!(report["reason"], "cheating");
+    assert_eq!(report["details"], "Speed hacking");
+    assert!(report["reported_at"].is_string());
+    assert_eq!(server.metrics.player_reports.load(Ordering::Relaxed), 1);
+}
+
+#[tokio::test]
+async fn report_spam_is_rate_limited() {
+    let server = create_test_server().await;
+    let (reporter, mut reporter_rx) = join(&server, 0, "Reporter").await;
+    let (target, mut target_rx) = join(&server, 1, "Target").await;
+
+    for _ in 0..REPORT_QUOTA.burst {
+        server
+            .handle_report_player(&reporter, &target, ReportReason::Spam, None)
+            .await;
+        assert!(matches!(
+            &*next_message(&mut reporter_rx),
+            ServerMessage::ReportReceived { .. }
+        ));
+    }
+
+    server
+        .handle_report_player(&reporter, &target, ReportReason::Spam, None)
+        .await;
+    match &*next_message(&mut reporter_rx) {
+        ServerMessage::Error(error) => {
+            assert_eq!(error.code, ErrorCode::RateLimitExceeded);
+            assert!(error.retry_after_ms.is_some_and(|ms| ms > 0));
+        }
+        other => panic!("expected RATE_LIMIT_EXCEEDED, got {other:?}"),
+    }
+    assert_eq!(
+        server.metrics.player_reports.load(Ordering::Relaxed),
+        u64::from(REPORT_QUOTA.burst)
+    );
+
+    // The limit is per reporter
+    server
+        .handle_report_player(&target, &reporter, ReportReason::Other, None)
+        .await;
+    assert!(matches!(
+        &*next_message(&mut target_rx),
+        ServerMessage::ReportReceived { .. }
+    ));
+}
+
+#[tokio::test]
+async fn invalid_reports_are_rejected() {
+    let server = create_test_server().await;
+    let (reporter, mut reporter_rx) = join(&server, 0, "Reporter").await;
+    let (target, _target_rx) = join(&server, 1, "Target").await;
+
+    server
+        .handle_report_player(&reporter, &reporter, ReportReason::Other, None)
+        .await;
+    assert_eq!(
+        error_code(&next_message(&mut reporter_rx)),
+        Some(ErrorCode::InvalidInput)
+    );
+
+    server
+        .handle_report_player(&reporter, &Uuid::new_v4(), ReportReason::Other, None)
+        .await;
+    assert_eq!(
+        error_code(&next_message(&mut reporter_rx)),
+        Some(ErrorCode::InvalidInput)
+    );
+
+    let details = "x".repeat(MAX_REPORT_DETAILS_LENGTH + 1);
+    server
+        .handle_report_player(&reporter, &target, ReportReason::Other, Some(details))
+        .await;
+    assert_eq!(
+        error_code(&next_message(&mut reporter_rx)),
+        Some(ErrorCode::InvalidInput)
+    );
+    assert_eq!(server.metrics.player_reports.load(Ordering::Relaxed), 0);
+}
@@ -0,0 +1,198 @@
+use crate::protocol::validation;
+use crate::protocol::{
+    ErrorCode, ErrorDetail, ParticipantRole, PlayerId, Room, RoomId, ServerMessage,
+    SpectatorStateChangeReason,
+};
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::EnhancedGameServer;
+
+/// Why a role change was refused, as sent to the requester.
+type RoleChangeError = (ErrorCode, &'static str);
+
+impl EnhancedGameServer {
+    /// Handle the room authority seating a spectator as a player, or moving a
+    /// player (including themselves) to the spectators.
+    ///
+    /// The move happens under the room join lock, so no join can take the slot
+    /// in between. Once it is complete everyone in the room receives
+    /// `RoleChanged`.
+    pub async fn handle_role_change(
+        &self,
+        requester_id: &PlayerId,
+        target_id: &PlayerId,
+        new_role: ParticipantRole,
+    ) {
+        let Some(room) = self.requester_room(requester_id).await else {
+            return;
+        };
+        if room.authority_player != Some(*requester_id) {
+            let _ = self
+                .send_error_to_player(
+                    requester_id,
+                    ErrorDetail::new(
+                        ErrorCode::AuthorityDenied,
+                        "Only the room authority can change roles",
+                    ),
+                )
+                .await;
+            return;
+        }
+
+        let lock_key = format!("room_join:{}:{}", room.game_name, room.code);
+        let lock_handle = match self
+            .distributed_lock
+            .acquire(&lock_key, Duration::from_secs(10))
+            .await
+        {
+            Ok(handle) => handle,
+            Err(e) => {
+                tracing::warn!(room_id = %room.id, error = %e, "Failed to lock room for role change");
+                let _ = self
+                    .send_error_to_player(
+                        requester_id,
+                        ErrorDetail::new(ErrorCode::InternalError, "Failed to change role"),
+                    )
+                    .await;
+                return;
+            }
+        };
+        let result = match new_role {
+            ParticipantRole::Player => self.seat_as_player(&room.id, target_id).await,
+            ParticipantRole::Spectator => self.move_to_spectators(&room.id, target_id).await,
+        };
+        let _ = self.distributed_lock.release(&lock_handle).await;
+
+        if let Err((code, reason)) = result {
+            let _ = self
+                .send_error_to_player(requester_id, ErrorDetail::new(code, reason.to_string()))
+                .await;
+            return;
+        }
+
+        tracing::info!(room_id = %room.id, requester = %requester_id, player_id = %target_id, ?new_role, "Participant role changed");
+        let _ = self
+            .message_coordinator
+            .broadcast_to_room(
+                &room.id,
+                Arc::new(ServerMessage::RoleChanged {
+                    player_id: *target_id,
+                    new_role,
+                }),
+            )
+            .await;
+
+        if new_role == ParticipantRole::Spectator {
+            self.promote_queued_spectators(&room.id).await;
+            self.promote_from_waiting_list(&room.id).await;
+        }
+    }
+
+    /// Seat a spectator of the room as a player. Must be called with the room
+    /// join lock held.
+    async fn seat_as_player(
+        &self,
+        room_id: &RoomId,
+        target_id: &PlayerId,
+    ) -> Result<(), RoleChangeError> {
+        let mut room = self.load_room_for_role_change(room_id).await?;
+        let Some(spectator) = room.spectators.get(target_id).cloned() else {
+            return Err(if room.players.contains_key(target_id) {
+                (ErrorCode::InvalidInput, "Target is already a player")
+            } else {
+                (
+                    ErrorCode::InvalidInput,
+                    "Target is not spectating this room",
+                )
+            });
+        };
+        if !room.can_join() {
+            return Err((ErrorCode::RoomFull, "Room is full"));
+        }
+        if validation::validate_player_name_uniqueness(&spectator.name, &room.players).is_err() {
+            return Err((
+                ErrorCode::InvalidPlayerName,
+                "A player with the spectator's name is already in the room",
+            ));
+        }
+
+        match self.seat_spectator(&mut room, spectator).await {
+            Ok(true) => Ok(()),
+            Ok(false) => Err((ErrorCode::RoomFull, "Room is full")),
+            Err(e) => {
+                tracing::warn!(%room_id, player_id = %target_id, error = %e, "Failed to seat spectator");
+                Err((ErrorCode::StorageError, "Failed to change role"))
+            }
+        }
+    }
+
+    /// Move a player of the room to its spectators, releasing authority first
+    /// if they hold it. Must be called with the room join lock held.
+    async fn move_to_spectators(
+        &self,
+        room_id: &RoomId,
+        target_id: &PlayerId,
+    ) -> Result<(), RoleChangeError> {
+        let room = self.load_room_for_role_change(room_id).await?;
+        let Some(player) = room.players.get(target_id).cloned() else {
+            return Err(if room.spectators.contains_key(target_id) {
+                (ErrorCode::InvalidInput, "Target is already a spectator")
+            } else {
+                (ErrorCode::InvalidInput, "Target player is not in this room")
+            });
+        };
+        if !room.can_spectate() {
+            return Err((ErrorCode::TooManySpectators, "Spectator limit reached"));
+        }
+
+        if room.authority_player == Some(*target_id) {
+            match self
+                .room_coordinator
+                .handle_authority_request(room_id, target_id, false)
+                .await
+            {
+                Ok((true, _)) => {}
+                Ok((false, reason)) => {
+                    tracing::warn!(%room_id, player_id = %target_id, ?reason, "Authority release refused before demotion");
+                    return Err((ErrorCode::AuthorityConflict, "Failed to release authority"));
+                }
+                Err(e) => {
+                    tracing::warn!(%room_id, player_id = %target_id, error = %e, "Failed to release authority before demotion");
+                    return Err((ErrorCode::InternalError, "Failed to release authority"));
+                }
+            }
+        }
+
+        if !self.vacate_seat(target_id, room_id).await {
+            return Err((ErrorCode::InvalidInput, "Target player is not in this room"));
+        }
+        let room = self.load_room_for_role_change(room_id).await?;
+        self.spectator_service
+            .join_validated(
+                target_id,
+                room,
+                player.name,
+                SpectatorStateChangeReason::Demoted,
+            )
+            .await
+            .map_err(|err| {
+                tracing::warn!(%room_id, player_id = %target_id, error = %err.message, "Failed to add demoted player as spectator");
+                (
+                    err.code,
+                    "Failed to move player to spectators",
+                )
+            })
+    }
+
+    async fn load_room_for_role_change(&self, room_id: &RoomId) -> Result<Room, RoleChangeError> {
+        match self.database.get_room_by_id(room_id).await {
+            Ok(Some(room)) => Ok(room),
+            Ok(None) => Err((ErrorCode::RoomNotFound, "Room not found")),
+            Err(e) => {
+                tracing::error!(%room_id, error = %e, "Failed to load room for role change");
+                Err((ErrorCode::StorageError, "Failed to load room"))
+            }
+        }
+    }
+}
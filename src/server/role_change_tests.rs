@@ -0,0 +1,236 @@
+use super::*;
+use crate::config::{
+    AuthMaintenanceConfig, CoordinationConfig, MetricsConfig, ProtocolConfig, RelayTypeConfig,
+    TransportSecurityConfig,
+};
+use crate::database::DatabaseConfig;
+use crate::protocol::{ErrorCode, ErrorDetail, ParticipantRole, Room, ServerMessage};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::time::{timeout, Duration};
+
+const GAME: &str = "role-game";
+const ROOM_CODE: &str = "ROLE01";
+
+async fn create_test_server() -> Arc<EnhancedGameServer> {
+    EnhancedGameServer::new(
+        ServerConfig::default(),
+        ProtocolConfig::default(),
+        RelayTypeConfig::default(),
+        DatabaseConfig::InMemory,
+        MetricsConfig::default(),
+        AuthMaintenanceConfig::default(),
+        CoordinationConfig::default(),
+        TransportSecurityConfig::default(),
+        Vec::new(),
+    )
+    .await
+    .expect("failed to construct test server")
+}
+
+async fn register(server: &EnhancedGameServer, port: u16) -> (PlayerId, mpsc::Receiver<Outbound>) {
+    let (sender, receiver) = mpsc::channel(64);
+    let addr: SocketAddr = format!("127.0.0.{}:{port}", port % 250 + 1)
+        .parse()
+        .unwrap();
+    let player_id = server
+        .connection_manager
+        .register_client(sender, addr, server.instance_id)
+        .await
+        .expect("client registration succeeds");
+    (player_id, receiver)
+}
+
+/// Join as a player; the first player creates the room and holds authority.
+async fn join(server: &EnhancedGameServer, player_id: &PlayerId, name: &str, max_players: u8) {
+    server
+        .handle_join_room(
+            player_id,
+            GAME.to_string(),
+            Some(ROOM_CODE.to_string()),
+            name.to_string(),
+            Some(max_players),
+            Some(true),
+            None,
+            None,
+            None,
+        )
+        .await;
+}
+
+async fn spectate(server: &EnhancedGameServer, player_id: &PlayerId, name: &str) {
+    server
+        .handle_join_as_spectator(
+            player_id,
+            GAME.to_string(),
+            ROOM_CODE.to_string(),
+            name.to_string(),
+        )
+        .await;
+}
+
+async fn room(server: &EnhancedGameServer) -> Room {
+    server
+        .database
+        .get_room(GAME, ROOM_CODE)
+        .await
+        .unwrap()
+        .expect("room exists")
+}
+
+/// Skip messages until one matches `predicate`.
+async fn expect_message(
+    receiver: &mut mpsc::Receiver<Outbound>,
+    description: &str,
+    predicate: impl Fn(&ServerMessage) -> bool,
+) {
+    let found = timeout(Duration::from_secs(1), async {
+        while let Some(message) = receiver.recv().await {
+            if predicate(&message) {
+                return true;
+            }
+        }
+        false
+    })
+    .await;
+    assert!(matches!(found, Ok(true)), "expected {description}");
+}
+
+fn drain(receiver: &mut mpsc::Receiver<Outbound>) {
+    while receiver.try_recv().is_ok() {}
+}
+
+fn is_role_changed(player_id: PlayerId, role: ParticipantRole) -> impl Fn(&ServerMessage) -> bool {
+    move |message| matches!(message, ServerMessage::RoleChanged { player_id: id, new_role } if *id == player_id && *new_role == role)
+}
+
+fn is_error(code: ErrorCode) -> impl Fn(&ServerMessage) -> bool {
+    move |message| matches!(message, ServerMessage::Error(ErrorDetail { code: c, .. }) if *c == code)
+}
+
+#[tokio::test]
+async fn authority_moves_a_participant_between_spectators_and_players() {
+    let server = create_test_server().await;
+    let (host, mut host_rx) = register(&server, 49200).await;
+    let (watcher, mut watcher_rx) = register(&server, 49201).await;
+    join(&server, &host, "Host", 4).await;
+    spectate(&server, &watcher, "Watcher").await;
+    drain(&mut host_rx);
+    drain(&mut watcher_rx);
+
+    server
+        .handle_role_change(&host, &watcher, ParticipantRole::Player)
+        .await;
+    expect_message(&mut watcher_rx, "RoomJoined after promotion", |message| {
+        matches!(message, ServerMessage::RoomJoined(_))
+    })
+    .await;
+    expect_message(
+        &mut host_rx,
+        "RoleChanged to player",
+        is_role_changed(watcher, ParticipantRole::Player),
+    )
+    .await;
+    let promoted = room(&server).await;
+    assert!(promoted.players.contains_key(&watcher));
+    assert!(!promoted.spectators.contains_key(&watcher));
+
+    server
+        .handle_role_change(&host, &watcher, ParticipantRole::Spectator)
+        .await;
+    expect_message(&mut watcher_rx, "SpectatorJoined after demotion", |message| {
+        matches!(message, ServerMessage::SpectatorJoined(payload) if payload.reason == Some(SpectatorStateChangeReason::Demoted))
+    })
+    .await;
+    expect_message(
+        &mut host_rx,
+        "RoleChanged to spectator",
+        is_role_changed(watcher, ParticipantRole::Spectator),
+    )
+    .await;
+    let demoted = room(&server).await;
+    assert!(!demoted.players.contains_key(&watcher));
+    assert!(demoted.spectators.contains_key(&watcher));
+    assert_eq!(server.get_client_room(&watcher).await, None);
+}
+
+#[tokio::test]
+async fn promoting_into_a_full_room_is_rejected() {
+    let server = create_test_server().await;
+    let (host, mut host_rx) = register(&server, 49210).await;
+    let (guest, _guest_rx) = register(&server, 49211).await;
+    let (watcher, _watcher_rx) = register(&server, 49212).await;
+    join(&server, &host, "Host", 2).await;
+    join(&server, &guest, "Guest", 2).await;
+    spectate(&server, &watcher, "Watcher").await;
+    drain(&mut host_rx);
+
+    server
+        .handle_role_change(&host, &watcher, ParticipantRole::Player)
+        .await;
+    expect_message(
+        &mut host_rx,
+        "RoomFull error",
+        is_error(ErrorCode::RoomFull),
+    )
+    .await;
+    let room = room(&server).await;
+    assert_eq!(room.players.len(), 2);
+    assert!(room.spectators.contains_key(&watcher));
+}
+
+#[tokio::test]
+async fn only_the_authority_can_change_roles() {
+    let server = create_test_server().await;
+    let (host, _host_rx) = register(&server, 49220).await;
+    let (guest, mut guest_rx) = register(&server, 49221).await;
+    join(&server, &host, "Host", 4).await;
+    join(&server, &guest, "Guest", 4).await;
+    drain(&mut guest_rx);
+
+    server
+        .handle_role_change(&guest, &guest, ParticipantRole::Spectator)
+        .await;
+    expect_message(
+        &mut guest_rx,
+        "AuthorityDenied error",
+        is_error(ErrorCode::AuthorityDenied),
+    )
+    .await;
+    assert!(room(&server).await.players.contains_key(&guest));
+}
+
+#[tokio::test]
+async fn authority_demoting_themselves_releases_authority_first() {
+    let server = create_test_server().await;
+    let (host, _host_rx) = register(&server, 49230).await;
+    let (guest, mut guest_rx) = register(&server, 49231).await;
+    join(&server, &host, "Host", 4).await;
+    join(&server, &guest, "Guest", 4).await;
+    drain(&mut guest_rx);
+
+    server
+        .handle_role_change(&host, &host, ParticipantRole::Spectator)
+        .await;
+    expect_message(&mut guest_rx, "AuthorityChanged to nobody", |message| {
+        matches!(
+            message,
+            ServerMessage::AuthorityChanged {
+                authority_player: None,
+                ..
+            }
+        )
+    })
+    .await;
+    expect_message(
+        &mut guest_rx,
+        "RoleChanged to spectator",
+        is_role_changed(host, ParticipantRole::Spectator),
+    )
+    .await;
+    let room = room(&server).await;
+    assert_eq!(room.authority_player, None);
+    assert!(room.spectators.contains_key(&host));
+    assert!(!room.players.contains_key(&host));
+}
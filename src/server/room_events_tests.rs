@@ -0,0 +1,138 @@
+use super::*;
+use crate::config::{
+    AuthMaintenanceConfig, CoordinationConfig, MetricsConfig, ProtocolConfig, RelayTypeConfig,
+    TransportSecurityConfig,
+};
+use crate::database::DatabaseConfig;
+use crate::room_events::{RoomEvent, RoomEventKind, RoomEventStream};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::broadcast::error::{RecvError, TryRecvError};
+use tokio::sync::mpsc;
+
+const GAME: &str = "events-game";
+
+async fn create_test_server() -> Arc<EnhancedGameServer> {
+    EnhancedGameServer::new(
+        ServerConfig::default(),
+        ProtocolConfig::default(),
+        RelayTypeConfig::default(),
+        DatabaseConfig::InMemory,
+        MetricsConfig::default(),
+        AuthMaintenanceConfig::default(),
+        CoordinationConfig::default(),
+        TransportSecurityConfig::default(),
+        Vec::new(),
+    )
+    .await
+    .expect("failed to construct test server")
+}
+
+async fn join(
+    server: &EnhancedGameServer,
+    port: u16,
+    name: &str,
+) -> (PlayerId, mpsc::Receiver<Outbound>) {
+    let (sender, receiver) = mpsc::channel(32);
+    let addr: SocketAddr = format!("127.0.0.1:{port}").parse().unwrap();
+    let player_id = server
+        .register_client(sender, addr)
+        .await
+        .expect("client registration succeeds");
+    server
+        .handle_join_room(
+            &player_id,
+            GAME.to_string(),
+            Some("EVENTS".to_string()),
+            name.to_string(),
+            Some(2),
+            Some(false),
+            None,
+            None,
+            None,
+        )
+        .await;
+    (player_id, receiver)
+}
+
+fn drain(events: &mut tokio::sync::broadcast::Receiver<Arc<RoomEvent>>) -> Vec<RoomEventKind> {
+    let mut kinds = Vec::new();
+    loop {
+        match events.try_recv() {
+            Ok(event) => {
+                assert_eq!(event.game_name, GAME);
+                kinds.push(event.kind.clone());
+            }
+            Err(TryRecvError::Empty) => return kinds,
+            Err(other) => panic!("unexpected receive error: {other:?}"),
+        }
+    }
+}
+
+#[tokio::test]
+async fn room_lifecycle_is_published_in_order() {
+    let server = create_test_server().await;
+    let mut events = server.subscribe_room_events();
+
+    let (host, _host_rx) = join(&server, 49200, "Host").await;
+    let (guest, _guest_rx) = join(&server, 49201, "Guest").await;
+    server.leave_room(&guest).await;
+    server.leave_room(&host).await;
+
+    let kinds = drain(&mut events);
+    assert!(matches!(
+        kinds.first(),
+        Some(RoomEventKind::RoomCreated { room_code, max_players: 2, created_by })
+            if room_code == "EVENTS" && *created_by == host
+    ));
+    assert!(kinds.contains(&RoomEventKind::PlayerJoined {
+        player_id: guest,
+        player_name: "Guest".to_string(),
+        player_count: 2,
+    }));
+    assert!(kinds.contains(&RoomEventKind::LobbyEntered));
+    assert!(kinds.contains(&RoomEventKind::PlayerLeft {
+        player_id: guest,
+        player_name: "Guest".to_string(),
+        player_count: 1,
+    }));
+    let joined = kinds
+        .iter()
+        .position(|kind| matches!(kind, RoomEventKind::PlayerJoined { .. }))
+        .unwrap();
+    let left = kinds
+        .iter()
+        .position(|kind| matches!(kind, RoomEventKind::PlayerLeft { .. }))
+        .unwrap();
+    assert!(joined < left, "join is published before leave: {kinds:?}");
+}
+
+#[tokio::test]
+async fn nothing_is_buffered_without_observers() {
+    let server = create_test_server().await;
+    let (_host, _host_rx) = join(&server, 49210, "Host").await;
+
+    let mut events = server.subscribe_room_events();
+    assert!(drain(&mut events).is_empty());
+}
+
+#[tokio::test]
+async fn slow_observers_lose_the_oldest_events() {
+    let stream = RoomEventStream::new(2);
+    let mut events = stream.subscribe();
+    let room_id = RoomId::new_v4();
+    for _ in 0..5 {
+        stream.publish(room_id, GAME, RoomEventKind::LobbyEntered);
+    }
+    stream.publish(room_id, GAME, RoomEventKind::GameFinalized);
+
+    assert!(matches!(events.recv().await, Err(RecvError::Lagged(4))));
+    assert_eq!(
+        events.recv().await.unwrap().kind,
+        RoomEventKind::LobbyEntered
+    );
+    assert_eq!(
+        events.recv().await.unwrap().kind,
+        RoomEventKind::GameFinalized
+    );
+}
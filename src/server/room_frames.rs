@@ -0,0 +1,87 @@
+//! Which connections take room broadcasts as shared, pre-serialized frames.
+//!
+//! A broadcast is serialized at most once per encoding its recipients use,
+//! and the same frame is pushed onto each recipient's queue as an
+//! [`Outbound::RoomFrame`](crate::broadcast::Outbound::RoomFrame).
+
+use dashmap::DashMap;
+use std::sync::Arc;
+
+use crate::protocol::{MessageEncoding, PlayerId, ServerMessage};
+
+/// Frame encoding of each connection that takes room frames.
+#[derive(Default)]
+pub(super) struct RoomFrameRoutes {
+    subscribers: DashMap<PlayerId, MessageEncoding>,
+}
+
+impl RoomFrameRoutes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue room broadcasts for `player_id` as frames encoded as `encoding`.
+    pub fn add_subscriber(&self, player_id: PlayerId, encoding: MessageEncoding) {
+        self.subscribers.insert(player_id, frame_encoding(encoding));
+    }
+
+    pub fn remove_subscriber(&self, player_id: &PlayerId) {
+        self.subscribers.remove(player_id);
+    }
+
+    /// The frame encoding `player_id` receives room broadcasts in, if it
+    /// takes them as frames.
+    pub fn subscribed_encoding(&self, player_id: &PlayerId) -> Option<MessageEncoding> {
+        self.subscribers.get(player_id).map(|encoding| *encoding)
+    }
+}
+
+/// One broadcast's frames, each serialized the first time a recipient needs it.
+#[derive(Default)]
+pub(super) struct BroadcastFrames {
+    json: Option<Option<Arc<[u8]>>>,
+    msgpack: Option<Option<Arc<[u8]>>>,
+}
+
+impl BroadcastFrames {
+    /// `message` as a frame in `encoding`, shared by every call for that encoding.
+    pub fn get(&mut self, message: &ServerMessage, encoding: MessageEncoding) -> Option<Arc<[u8]>> {
+        let frame = if encoding.is_binary() {
+            &mut self.msgpack
+        } else {
+            &mut self.json
+        };
+        frame
+            .get_or_insert_with(|| room_frame(message, encoding))
+            .clone()
+    }
+}
+
+/// rkyv connections receive MessagePack, so they share its frames.
+fn frame_encoding(encoding: MessageEncoding) -> MessageEncoding {
+    if encoding.is_binary() {
+        MessageEncoding::MsgPack
+    } else {
+        MessageEncoding::Json
+    }
+}
+
+/// Serialize `message` once for every recipient using `encoding`. Binary game
+/// data is encoded per recipient on JSON connections, so it is queued as a
+/// message for them.
+fn room_frame(message: &ServerMessage, encoding: MessageEncoding) -> Option<Arc<[u8]>> {
+    let encoded = if encoding.is_binary() {
+        rmp_serde::to_vec_named(message).map_err(|err| err.to_string())
+    } else if matches!(message, ServerMessage::GameDataBinary { .. }) {
+        return None;
+    } else {
+        serde_json::to_vec(message).map_err(|err| err.to_string())
+    };
+    match encoded {
+        Ok(bytes) => Some(Arc::from(bytes)),
+        Err(err) => {
+            tracing::error!(?encoding, error = %err, "Failed to serialize room broadcast frame");
+            None
+        }
+    }
+}
@@ -0,0 +1,214 @@
+use super::*;
+use crate::protocol::GameDataEncoding;
+use bytes::Bytes;
+
+async fn queue_client(
+    coordinator: &InMemoryMessageCoordinator,
+    room_id: RoomId,
+) -> (PlayerId, mpsc::Receiver<Outbound>) {
+    let player_id = Uuid::new_v4();
+    let (sender, receiver) = mpsc::channel(8);
+    coordinator
+        .register_local_client(player_id, Some(room_id), sender)
+        .await
+        .unwrap();
+    (player_id, receiver)
+}
+
+/// A connection taking shared JSON frames, joined to `room_id`.
+async fn frame_client(
+    coordinator: &InMemoryMessageCoordinator,
+    room_id: RoomId,
+) -> (PlayerId, mpsc::Receiver<Outbound>) {
+    encoded_frame_client(coordinator, room_id, MessageEncoding::Json).await
+}
+
+/// Like [`frame_client`], for a connection using `encoding`.
+async fn encoded_frame_client(
+    coordinator: &InMemoryMessageCoordinator,
+    room_id: RoomId,
+    encoding: MessageEncoding,
+) -> (PlayerId, mpsc::Receiver<Outbound>) {
+    let player_id = Uuid::new_v4();
+    let (sender, receiver) = mpsc::channel(8);
+    coordinator
+        .register_local_client(player_id, None, sender.clone())
+        .await
+        .unwrap();
+    assert!(coordinator.subscribe_room_frames(player_id, encoding).await);
+    coordinator
+        .register_local_client(player_id, Some(room_id), sender)
+        .await
+        .unwrap();
+    (player_id, receiver)
+}
+
+fn next_frame(queue: &mut mpsc::Receiver<Outbound>) -> Arc<[u8]> {
+    match queue.try_recv().expect("a frame was queued") {
+        Outbound::RoomFrame { frame, .. } => frame,
+        Outbound::Message(message) => panic!("expected a room frame, got {message:?}"),
+    }
+}
+
+#[tokio::test]
+async fn room_broadcasts_share_one_frame_between_subscribers() {
+    let coordinator = InMemoryMessageCoordinator::new().with_room_frames();
+    let room_id = Uuid::new_v4();
+    let (sender_id, mut sender_queue) = frame_client(&coordinator, room_id).await;
+    let (_, mut other_queue) = frame_client(&coordinator, room_id).await;
+    let (_, mut queue_only) = queue_client(&coordinator, room_id).await;
+
+    let message = Arc::new(ServerMessage::PlayerLeft {
+        player_id: Uuid::new_v4(),
+    });
+    coordinator
+        .broadcast_to_room_except(&room_id, &sender_id, Arc::clone(&message))
+        .await
+        .unwrap();
+
+    let frame = next_frame(&mut other_queue);
+    assert_eq!(&*frame, serde_json::to_vec(&*message).unwrap().as_slice());
+    assert!(matches!(
+        queue_only.try_recv().unwrap(),
+        Outbound::Message(message) if matches!(*message, ServerMessage::PlayerLeft { .. })
+    ));
+    assert!(sender_queue.try_recv().is_err(), "the sender is skipped");
+
+    coordinator
+        .broadcast_to_room(&room_id, Arc::new(ServerMessage::RoomLeft))
+        .await
+        .unwrap();
+    let frame = next_frame(&mut sender_queue);
+    assert!(
+        Arc::ptr_eq(&frame, &next_frame(&mut other_queue)),
+        "every subscriber gets the same frame"
+    );
+    assert_eq!(
+        &*frame,
+        serde_json::to_vec(&ServerMessage::RoomLeft)
+            .unwrap()
+            .as_slice()
+    );
+}
+
+#[tokio::test]
+async fn room_frames_stay_in_order_with_direct_messages() {
+    let coordinator = InMemoryMessageCoordinator::new().with_room_frames();
+    let room_id = Uuid::new_v4();
+    let (player_id, mut queue) = frame_client(&coordinator, room_id).await;
+
+    coordinator
+        .send_to_player(&player_id, Arc::new(ServerMessage::Pong))
+        .await
+        .unwrap();
+    coordinator
+        .broadcast_to_room(&room_id, Arc::new(ServerMessage::RoomLeft))
+        .await
+        .unwrap();
+    coordinator
+        .send_to_player(&player_id, Arc::new(ServerMessage::Pong))
+        .await
+        .unwrap();
+
+    let received: Vec<_> = std::iter::from_fn(|| queue.try_recv().ok()).collect();
+    assert!(matches!(
+        received.as_slice(),
+        [
+            Outbound::Message(_),
+            Outbound::RoomFrame { .. },
+            Outbound::Message(_)
+        ]
+    ));
+    assert!(matches!(*received[1], ServerMessage::RoomLeft));
+}
+
+#[tokio::test]
+async fn room_broadcasts_are_serialized_once_per_encoding() {
+    let coordinator = InMemoryMessageCoordinator::new().with_room_frames();
+    let room_id = Uuid::new_v4();
+    let (_, mut json) = frame_client(&coordinator, room_id).await;
+    let (_, mut msgpack) =
+        encoded_frame_client(&coordinator, room_id, MessageEncoding::MsgPack).await;
+    let (_, mut rkyv) = encoded_frame_client(&coordinator, room_id, MessageEncoding::Rkyv).await;
+
+    let message = Arc::new(ServerMessage::PlayerLeft {
+        player_id: Uuid::new_v4(),
+    });
+    coordinator
+        .broadcast_to_room(&room_id, Arc::clone(&message))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        &*next_frame(&mut json),
+        serde_json::to_vec(&*message).unwrap().as_slice()
+    );
+    let msgpack_frame = next_frame(&mut msgpack);
+    assert!(
+        Arc::ptr_eq(&msgpack_frame, &next_frame(&mut rkyv)),
+        "rkyv connections share the MessagePack frame"
+    );
+    let decoded: ServerMessage = rmp_serde::from_slice(&msgpack_frame).unwrap();
+    assert!(matches!(decoded, ServerMessage::PlayerLeft { .. }));
+
+    // MessagePack envelopes carry binary game data as is, so it is framed too
+    coordinator
+        .broadcast_to_room(
+            &room_id,
+            Arc::new(ServerMessage::GameDataBinary {
+                from_player: Uuid::new_v4(),
+                encoding: GameDataEncoding::MessagePack,
+                payload: Bytes::from_static(&[1, 2, 3]),
+            }),
+        )
+        .await
+        .unwrap();
+    next_frame(&mut msgpack);
+}
+
+#[tokio::test]
+async fn per_recipient_messages_are_queued_unserialized() {
+    let coordinator = InMemoryMessageCoordinator::new().with_room_frames();
+    let room_id = Uuid::new_v4();
+    let (_, mut queue) = frame_client(&coordinator, room_id).await;
+
+    coordinator
+        .broadcast_to_room(
+            &room_id,
+            Arc::new(ServerMessage::GameDataBinary {
+                from_player: Uuid::new_v4(),
+                encoding: GameDataEncoding::MessagePack,
+                payload: Bytes::from_static(&[1, 2, 3]),
+            }),
+        )
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        queue.try_recv().unwrap(),
+        Outbound::Message(message) if matches!(*message, ServerMessage::GameDataBinary { .. })
+    ));
+}
+
+#[tokio::test]
+async fn unregistered_connections_stop_taking_frames() {
+    let coordinator = InMemoryMessageCoordinator::new().with_room_frames();
+    let room_id = Uuid::new_v4();
+    let (player_id, _queue) = frame_client(&coordinator, room_id).await;
+
+    coordinator
+        .unregister_local_client(&player_id)
+        .await
+        .unwrap();
+    let (sender, mut queue) = mpsc::channel(8);
+    coordinator
+        .register_local_client(player_id, Some(room_id), sender)
+        .await
+        .unwrap();
+    coordinator
+        .broadcast_to_room(&room_id, Arc::new(ServerMessage::RoomLeft))
+        .await
+        .unwrap();
+
+    assert!(matches!(queue.try_recv().unwrap(), Outbound::Message(_)));
+}
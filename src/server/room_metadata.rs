@@ -0,0 +1,77 @@
+use crate::database::RoomMetadataUpdate;
+use crate::protocol::{
+    validation, ErrorCode, ErrorDetail, PlayerId, PrivilegedAction, ServerMessage,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::EnhancedGameServer;
+
+impl EnhancedGameServer {
+    /// Handle a request to set or remove room metadata entries and broadcast the result.
+    pub async fn handle_set_room_metadata(
+        &self,
+        requester_id: &PlayerId,
+        entries: HashMap<String, String>,
+    ) {
+        let Some(room) = self
+            .authorize_privileged(requester_id, PrivilegedAction::SetRoomMetadata)
+            .await
+        else {
+            return;
+        };
+
+        if let Err(reason) = validation::validate_room_metadata_entries(&entries) {
+            let _ = self
+                .send_error_to_player(
+                    requester_id,
+                    ErrorDetail::new(ErrorCode::RoomMetadataLimitExceeded, reason)
+                        .with_details(validation::room_metadata_details()),
+                )
+                .await;
+            return;
+        }
+
+        match self.database.set_room_metadata(&room.id, &entries).await {
+            Ok(RoomMetadataUpdate::Applied(metadata)) => {
+                tracing::debug!(room_id = %room.id, requester = %requester_id, keys = metadata.len(), "Room metadata updated");
+                let _ = self
+                    .message_coordinator
+                    .broadcast_to_room(
+                        &room.id,
+                        Arc::new(ServerMessage::RoomMetadataUpdated {
+                            metadata,
+                            updated_by: *requester_id,
+                        }),
+                    )
+                    .await;
+            }
+            Ok(RoomMetadataUpdate::Rejected(reason)) => {
+                let _ = self
+                    .send_error_to_player(
+                        requester_id,
+                        ErrorDetail::new(ErrorCode::RoomMetadataLimitExceeded, reason)
+                            .with_details(validation::room_metadata_details()),
+                    )
+                    .await;
+            }
+            Ok(RoomMetadataUpdate::RoomNotFound) => {
+                let _ = self
+                    .send_error_to_player(
+                        requester_id,
+                        ErrorDetail::new(ErrorCode::RoomNotFound, "Room not found"),
+                    )
+                    .await;
+            }
+            Err(e) => {
+                tracing::error!(room_id = %room.id, error = %e, "Failed to update room metadata");
+                let _ = self
+                    .send_error_to_player(
+                        requester_id,
+                        ErrorDetail::new(ErrorCode::StorageError, "Failed to update room metadata"),
+                    )
+                    .await;
+            }
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use super::*;
+use crate::config::{
+    AuthMaintenanceConfig, CoordinationConfig, MetricsConfig, ProtocolConfig, RelayTypeConfig,
+    TransportSecurityConfig,
+};
+use crate::database::DatabaseConfig;
+use crate::protocol::validation::MAX_ROOM_METADATA_KEYS;
+use crate::protocol::{ErrorCode, ErrorDetail, ServerMessage};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::time::{timeout, Duration};
+
+async fn create_test_server() -> Arc<EnhancedGameServer> {
+    EnhancedGameServer::new(
+        ServerConfig::default(),
+        ProtocolConfig::default(),
+        RelayTypeConfig::default(),
+        DatabaseConfig::InMemory,
+        MetricsConfig::default(),
+        AuthMaintenanceConfig::default(),
+        CoordinationConfig::default(),
+        TransportSecurityConfig::default(),
+        Vec::new(),
+    )
+    .await
+    .expect("failed to construct test server")
+}
+
+async fn register(server: &EnhancedGameServer, addr: &str) -> (PlayerId, mpsc::Receiver<Outbound>) {
+    let (sender, receiver) = mpsc::channel(32);
+    let addr: SocketAddr = addr.parse().unwrap();
+    let player_id = server
+        .connection_manager
+        .register_client(sender, addr, server.instance_id)
+        .await
+        .expect("client registration succeeds");
+    (player_id, receiver)
+}
+
+async fn join(server: &EnhancedGameServer, player_id: &PlayerId, name: &str) {
+    server
+        .handle_join_room(
+            player_id,
+            "meta-game".to_string(),
+            Some("META01".to_string()),
+            name.to_string(),
+            Some(3),
+            Some(true),
+            None,
+            None,
+            None,
+        )
+        .await;
+}
+
+async fn next_message(receiver: &mut mpsc::Receiver<Outbound>) -> Outbound {
+    timeout(Duration::from_secs(1), receiver.recv())
+        .await
+        .expect("message arrives in time")
+        .expect("channel still open")
+}
+
+fn drain(receiver: &mut mpsc::Receiver<Outbound>) {
+    while receiver.try_recv().is_ok() {}
+}
+
+fn entries(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs
+        .iter()
+        .map(|(key, value)| ((*key).to_string(), (*value).to_string()))
+        .collect()
+}
+
+async fn stored_metadata(server: &EnhancedGameServer) -> HashMap<String, String> {
+    server
+        .database()
+        .get_room("meta-game", "META01")
+        .await
+        .expect("room lookup succeeds")
+        .expect("room exists")
+        .metadata
+}
+
+#[tokio::test]
+async fn authority_metadata_is_broadcast_and_seen_by_new_joiners() {
+    let server = create_test_server().await;
+    let (host, mut host_rx) = register(&server, "127.0.0.1:48300").await;
+    let (guest, mut guest_rx) = register(&server, "127.0.0.2:48301").await;
+
+    join(&server, &host, "Host").await;
+    join(&server, &guest, "Guest").await;
+    drain(&mut host_rx);
+    drain(&mut guest_rx);
+
+    server
+        .handle_set_room_metadata(&host, entries(&[("map", "dust"), ("mode", "ctf")]))
+        .await;
+    let expected = entries(&[("map", "dust"), ("mode", "ctf")]);
+    for receiver in [&mut host_rx, &mut guest_rx] {
+        let update = next_message(receiver).await;
+        assert!(
+            matches!(&*update, ServerMessage::RoomMetadataUpdated { metadata, updated_by } if *metadata == expected && *updated_by == host),
+            "expected RoomMetadataUpdated, got {update:?}"
+        );
+    }
+
+    // Non-authority players cannot change metadata
+    server
+        .handle_set_room_metadata(&guest, entries(&[("map", "nuke")]))
+        .await;
+    let denied = next_message(&mut guest_rx).await;
+    assert!(matches!(
+        *denied,
+        ServerMessage::Error(ErrorDetail {
+            code: ErrorCode::AuthorityDenied,
+            ..
+        })
+    ));
+
+    let (late, mut late_rx) = register(&server, "127.0.0.3:48302").await;
+    join(&server, &late, "Late").await;
+    let joined = next_message(&mut late_rx).await;
+    match &*joined {
+        ServerMessage::RoomJoined(payload) => assert_eq!(payload.metadata, expected),
+        other => panic!("expected RoomJoined, got {other:?}"),
+    }
+
+    // Filling the room moves it to the lobby without touching the metadata
+    assert_eq!(stored_metadata(&server).await, expected);
+}
+
+#[tokio::test]
+async fn metadata_limits_reject_without_partial_updates() {
+    let server = create_test_server().await;
+    let (host, mut host_rx) = register(&server, "127.0.0.1:48310").await;
+    join(&server, &host, "Host").await;
+    drain(&mut host_rx);
+
+    let full: HashMap<String, String> = (0..MAX_ROOM_METADATA_KEYS)
+        .map(|i| (format!("key{i}"), "value".to_string()))
+        .collect();
+    server.handle_set_room_metadata(&host, full.clone()).await;
+    assert!(matches!(
+        *next_message(&mut host_rx).await,
+        ServerMessage::RoomMetadataUpdated { .. }
+    ));
+
+    let rejected_updates = [
+        entries(&[("extra", "value")]),
+        entries(&[(&"k".repeat(33), "value")]),
+        entries(&[("key0", &"v".repeat(257))]),
+    ];
+    for update in rejected_updates {
+        server.handle_set_room_metadata(&host, update).await;
+        let rejected = next_message(&mut host_rx).await;
+        assert!(
+            matches!(
+                *rejected,
+                ServerMessage::Error(ErrorDetail {
+                    code: ErrorCode::RoomMetadataLimitExceeded,
+                    ..
+                })
+            ),
+            "expected RoomMetadataLimitExceeded, got {rejected:?}"
+        );
+    }
+    assert_eq!(stored_metadata(&server).await, full);
+
+    // Removing a key with an empty value makes room for a new one in the same update
+    server
+        .handle_set_room_metadata(&host, entries(&[("key0", ""), ("extra", "value")]))
+        .await;
+    assert!(matches!(
+        *next_message(&mut host_rx).await,
+        ServerMessage::RoomMetadataUpdated { .. }
+    ));
+    let metadata = stored_metadata(&server).await;
+    assert_eq!(metadata.len(), MAX_ROOM_METADATA_KEYS);
+    assert!(!metadata.contains_key("key0"));
+    assert_eq!(metadata.get("extra").map(String::as_str), Some("value"));
+}
@@ -0,0 +1,39 @@
+//! Room membership changes reach every member in commit order.
+//!
+//! Joins and leaves take their room's [`RoomGuard`] from the room coordinator
+//! before touching the database. A leave keeps it until `RoomLeft` and
+//! `PlayerLeft` are queued. A join returns its [`RoomTurn`] with the seated
+//! room, and the caller passes it to `announce_room_join`, which keeps it
+//! until `RoomJoined` and `PlayerJoined` are queued, so no later join or
+//! leave can slip between the commit and the announcement. A join that is
+//! never announced drops its turn with the value that carried it. Each player
+//! has a single queue, so the order messages are queued in is the order they
+//! arrive in.
+
+use crate::coordination::RoomGuard;
+use crate::protocol::RoomId;
+
+use super::EnhancedGameServer;
+
+/// A room's turn to change its membership. The room is released when this
+/// is dropped.
+#[must_use = "the room is released as soon as its turn is dropped"]
+pub(super) struct RoomTurn {
+    _guard: Option<RoomGuard>,
+}
+
+impl EnhancedGameServer {
+    /// Wait for `room_id`'s turn to change its membership. If the room could
+    /// not be locked the turn holds nothing and the change is not ordered.
+    pub(super) async fn room_turn(&self, room_id: &RoomId) -> RoomTurn {
+        match self.room_coordinator.lock_room_for_bulk_op(*room_id).await {
+            Ok(guard) => RoomTurn {
+                _guard: Some(guard),
+            },
+            Err(err) => {
+                tracing::warn!(%room_id, error = %err, "Failed to lock room for a membership change");
+                RoomTurn { _guard: None }
+            }
+        }
+    }
+}
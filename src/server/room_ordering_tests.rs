@@ -0,0 +1,266 @@
+use super::*;
+use crate::config::{
+    AuthMaintenanceConfig, CoordinationConfig, MetricsConfig, ProtocolConfig, RelayTypeConfig,
+    TransportSecurityConfig,
+};
+use crate::database::DatabaseConfig;
+use crate::protocol::ServerMessage;
+use std::collections::BTreeSet;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+const GAME: &str = "ordering-game";
+const CODE: &str = "ORDR01";
+
+/// A membership change as one player observed it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Change {
+    Joined(PlayerId),
+    Left(PlayerId),
+}
+
+/// What one player saw of the room: the players listed in its `RoomJoined`,
+/// then every change after it, up to its own `RoomLeft`.
+struct Observation {
+    snapshot: BTreeSet<PlayerId>,
+    changes: Vec<Change>,
+    left: bool,
+}
+
+async fn create_test_server() -> Arc<EnhancedGameServer> {
+    EnhancedGameServer::new(
+        ServerConfig::default(),
+        ProtocolConfig::default(),
+        RelayTypeConfig::default(),
+        DatabaseConfig::InMemory,
+        MetricsConfig::default(),
+        AuthMaintenanceConfig::default(),
+        CoordinationConfig::default(),
+        TransportSecurityConfig::default(),
+        Vec::new(),
+    )
+    .await
+    .expect("failed to construct test server")
+}
+
+async fn register(server: &EnhancedGameServer, index: u8) -> (PlayerId, mpsc::Receiver<Outbound>) {
+    let (sender, receiver) = mpsc::channel(256);
+    // Distinct addresses keep connection and rate limits out of the way
+    let addr: SocketAddr = format!("10.2.0.{}:4000", index + 1).parse().unwrap();
+    let player_id = server
+        .connection_manager
+        .register_client(sender, addr, server.instance_id)
+        .await
+        .expect("client registration succeeds");
+    (player_id, receiver)
+}
+
+async fn join(server: &EnhancedGameServer, player_id: &PlayerId) {
+    server
+        .join_room_request(
+            player_id,
+            GAME.to_string(),
+            Some(CODE.to_string()),
+            format!("player-{}", &player_id.to_string()[..8]),
+            Some(64),
+            Some(true),
+            None,
+            None,
+            None,
+            None,
+            None,
+            false,
+        )
+        .await;
+}
+
+fn observe(player_id: PlayerId, receiver: &mut mpsc::Receiver<Outbound>) -> Observation {
+    let mut snapshot = None;
+    let mut changes = Vec::new();
+    while let Ok(message) = receiver.try_recv() {
+        let change = match &*message {
+            ServerMessage::RoomJoined(payload) => {
+                assert!(snapshot.is_none(), "{player_id} joined twice");
+                snapshot = Some(
+                    payload
+                        .current_players
+                        .iter()
+                        .map(|player| player.id)
+                        .collect(),
+                );
+                continue;
+            }
+            ServerMessage::RoomLeft => {
+                return Observation {
+                    snapshot: snapshot.expect("left after joining"),
+                    changes,
+                    left: true,
+                };
+            }
+            ServerMessage::PlayerJoined { player } => Change::Joined(player.id),
+            ServerMessage::PlayerLeft { player_id } => Change::Left(*player_id),
+            _ => continue,
+        };
+        assert!(
+            snapshot.is_some(),
+            "{player_id} saw {change:?} before its own RoomJoined"
+        );
+        changes.push(change);
+    }
+    Observation {
+        snapshot: snapshot.expect("every player joined"),
+        changes,
+        left: false,
+    }
+}
+
+/// Apply `change` to `members`, failing on a join or leave that contradicts them.
+fn apply(members: &mut BTreeSet<PlayerId>, change: Change) {
+    match change {
+        Change::Joined(player_id) => assert!(
+            members.insert(player_id),
+            "{player_id} joined while already listed"
+        ),
+        Change::Left(player_id) => assert!(
+            members.remove(&player_id),
+            "{player_id} left without being listed"
+        ),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+async fn concurrent_joins_and_leaves_arrive_in_commit_order() {
+    let server = create_test_server().await;
+    let (host, mut host_rx) = register(&server, 0).await;
+    join(&server, &host).await;
+
+    let mut clients = Vec::new();
+    for index in 1..=24u8 {
+        clients.push(register(&server, index).await);
+    }
+    let tasks: Vec<_> = clients
+        .iter()
+        .enumerate()
+        .map(|(index, (player_id, _))| {
+            let server = Arc::clone(&server);
+            let player_id = *player_id;
+            tokio::spawn(async move {
+                join(&server, &player_id).await;
+                if index % 2 == 1 {
+                    server.leave_room(&player_id).await;
+                }
+            })
+        })
+        .collect();
+    for task in tasks {
+        task.await.expect("join task completes");
+    }
+
+    // The host saw every change, in the order the room committed them
+    let host_view = observe(host, &mut host_rx);
+    assert_eq!(host_view.snapshot, BTreeSet::from([host]));
+    let mut members = host_view.snapshot.clone();
+    let mut history = Vec::new();
+    for change in &host_view.changes {
+        apply(&mut members, *change);
+        history.push((*change, members.clone()));
+    }
+    let room = server
+        .database
+        .get_room(GAME, CODE)
+        .await
+        .unwrap()
+        .expect("room exists");
+    let committed: BTreeSet<PlayerId> = room.players.keys().copied().collect();
+    assert_eq!(members, committed);
+    assert_eq!(committed.len(), 13);
+
+    // Everyone else saw the same changes from the moment they joined
+    for (player_id, receiver) in &mut clients {
+        let view = observe(*player_id, receiver);
+        let joined_at = history
+            .iter()
+            .position(|(change, _)| *change == Change::Joined(*player_id))
+            .expect("the host saw every join");
+        assert_eq!(
+            view.snapshot, history[joined_at].1,
+            "{player_id}'s RoomJoined lists the room as of its own join"
+        );
+
+        let later = &history[joined_at + 1..];
+        assert!(view.changes.len() <= later.len());
+        let seen: Vec<Change> = later[..view.changes.len()]
+            .iter()
+            .map(|(change, _)| *change)
+            .collect();
+        assert_eq!(view.changes, seen, "{player_id} saw changes out of order");
+        if view.left {
+            assert_eq!(
+                later.get(view.changes.len()).map(|(change, _)| *change),
+                Some(Change::Left(*player_id))
+            );
+        } else {
+            assert_eq!(view.changes.len(), later.len());
+        }
+
+        let mut members = view.snapshot.clone();
+        for change in &view.changes {
+            apply(&mut members, *change);
+        }
+    }
+}
+
+#[tokio::test]
+async fn joins_wait_for_the_room_lock() {
+    let server = create_test_server().await;
+    let (host, _host_rx) = register(&server, 0).await;
+    join(&server, &host).await;
+    let room = server
+        .database
+        .get_room(GAME, CODE)
+        .await
+        .unwrap()
+        .expect("room exists");
+    let (joiner, _joiner_rx) = register(&server, 1).await;
+
+    let guard = server
+        .room_coordinator
+        .lock_room_for_bulk_op(room.id)
+        .await
+        .expect("room lock");
+    let task = {
+        let server = Arc::clone(&server);
+        tokio::spawn(async move { join(&server, &joiner).await })
+    };
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    assert!(!task.is_finished(), "the join waits for the room's turn");
+
+    drop(guard);
+    task.await.expect("join task completes");
+    let room = server.database.get_room(GAME, CODE).await.unwrap().unwrap();
+    assert!(room.players.contains_key(&joiner));
+}
+
+#[tokio::test]
+async fn joins_that_are_never_announced_release_the_room() {
+    let server = create_test_server().await;
+    let (host, _host_rx) = register(&server, 0).await;
+    join(&server, &host).await;
+    let (joiner, _joiner_rx) = register(&server, 1).await;
+
+    // Seat the player, then bail out before the announcement
+    let seated = server
+        .join_room_with_coordination(&joiner, GAME, CODE, "joiner", 64, true, None, None, false)
+        .await;
+    let (room, _) = seated.expect("the player is seated");
+    assert!(server.get_client_room(&joiner).await.is_none());
+
+    let guard = tokio::time::timeout(
+        std::time::Duration::from_secs(1),
+        server.room_coordinator.lock_room_for_bulk_op(room.id),
+    )
+    .await
+    .expect("the unannounced join released the room's turn");
+    assert!(guard.is_ok());
+}
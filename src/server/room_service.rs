@@ -1,12 +1,33 @@
-use super::{EnhancedGameServer, MaxRoomsPerGameExceededError};
+use super::room_ordering::RoomTurn;
+use super::{
+    chrono_duration_from_std, AppQuotaExceededError, EnhancedGameServer,
+    MaxActiveRoomsPerCreatorError, MaxRoomsPerAppExceededError, MaxRoomsPerGameExceededError,
+    PlayerBannedError, RoomFullError,
+};
+use crate::config::{AppQuotaConfig, DuplicateNamePolicy, RelayWarmupTrigger};
+use crate::database::{IdempotentRoom, PlayerNameTakenError};
 use crate::distributed::LockHandle;
+use crate::join_interceptor::JoinDecision;
 use crate::protocol::validation;
 use crate::protocol::{
-    LobbyState, PlayerId, PlayerInfo, RelayTransport, Room, RoomJoinedPayload, ServerMessage,
+    ErrorCode, ErrorDetails, LobbyState, PeerConnectionInfo, PlayerId, PlayerInfo, PlayerRole,
+    QuotaKind, RelayTransport, Room, RoomId, RoomJoinedPayload, ServerMessage, SpectatorChatMode,
+    ValidationConstraint,
 };
+use crate::rate_limit::RequestOrigin;
+use crate::room_events::RoomEventKind;
 use std::sync::Arc;
 use std::time::Duration;
 
+/// Outcome of a join request, for callers that can try another room.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum JoinAttempt {
+    /// The player joined, or was told why not
+    Handled,
+    /// The room was full, or refused this player, and nothing was sent
+    RoomUnavailable,
+}
+
 impl EnhancedGameServer {
     /// Enhanced room joining with distributed coordination
     #[allow(clippy::too_many_arguments)]
@@ -18,8 +39,46 @@ impl EnhancedGameServer {
         player_name: String,
         max_players: Option<u8>,
         supports_authority: Option<bool>,
-        _relay_transport: Option<RelayTransport>, // Reserved for future transport selection
+        relay_transport: Option<RelayTransport>,
+        relay_type: Option<String>,
+        spectator_chat_mode: Option<SpectatorChatMode>,
     ) {
+        self.join_room_request(
+            player_id,
+            game_name,
+            room_code,
+            player_name,
+            max_players,
+            supports_authority,
+            relay_transport,
+            relay_type,
+            spectator_chat_mode,
+            None,
+            None,
+            false,
+        )
+        .await;
+    }
+
+    /// Handle a join request. With `try_elsewhere`, a room that is full or
+    /// refuses the player (ban, name clash) is reported to the caller instead
+    /// of the player, and the waiting list is not offered.
+    #[allow(clippy::too_many_arguments)]
+    pub(super) async fn join_room_request(
+        &self,
+        player_id: &PlayerId,
+        game_name: String,
+        room_code: Option<String>,
+        player_name: String,
+        max_players: Option<u8>,
+        supports_authority: Option<bool>,
+        _relay_transport: Option<RelayTransport>, // Reserved for future transport selection
+        relay_type: Option<String>,
+        spectator_chat_mode: Option<SpectatorChatMode>,
+        allow_overflow_joins: Option<bool>,
+        idempotency_key: Option<String>,
+        try_elsewhere: bool,
+    ) -> JoinAttempt {
         let requested_room_code = room_code.clone();
         let room_join_span = tracing::info_span!(
             "room.join",
@@ -35,12 +94,17 @@ impl EnhancedGameServer {
         );
         let _span_guard = room_join_span.enter();
 
+        if self.reject_if_draining(player_id).await {
+            return JoinAttempt::Handled;
+        }
+
         // Rate limiting check
         let is_room_creation = room_code.is_none();
+        let origin = self.request_origin(player_id);
         let rate_limit_result = if is_room_creation {
-            self.rate_limiter.check_room_creation(player_id).await
+            self.rate_limiter.check_room_creation_for(&origin).await
         } else {
-            self.rate_limiter.check_join_attempt(player_id).await
+            self.rate_limiter.check_join_attempt_for(&origin).await
         };
 
         if let Err(rate_limit_error) = rate_limit_result {
@@ -51,19 +115,26 @@ impl EnhancedGameServer {
                     Arc::new(ServerMessage::RoomJoinFailed {
                         reason: rate_limit_error.to_string(),
                         error_code: Some(crate::protocol::ErrorCode::RateLimitExceeded),
+                        retry_after_secs: Some(rate_limit_error.retry_after_secs()),
+                        details: Some(rate_limit_error.details()),
                     }),
                 )
                 .await
             {
                 tracing::error!(%player_id, "Failed to send rate limit error: {}", e);
             }
-            return;
+            return JoinAttempt::Handled;
         }
 
         // Validate inputs
         if let Err(reason) =
             validation::validate_game_name_with_config(&game_name, &self.protocol_config)
         {
+            let details = validation::text_field_details(
+                "game_name",
+                &game_name,
+                self.protocol_config.max_game_name_length,
+            );
             let _ = self
                 .message_coordinator
                 .send_to_player(
@@ -71,29 +142,65 @@ impl EnhancedGameServer {
                     Arc::new(ServerMessage::RoomJoinFailed {
                         reason,
                         error_code: Some(crate::protocol::ErrorCode::InvalidGameName),
+                        retry_after_secs: None,
+                        details: Some(details),
                     }),
                 )
                 .await;
-            return;
+            return JoinAttempt::Handled;
+        }
+
+        if !self.client_allows_game(player_id, &game_name) {
+            tracing::warn!(%player_id, %game_name, "Join rejected: game outside token room scope");
+            self.send_join_failed(
+                player_id,
+                format!("Token does not permit rooms for game '{game_name}'"),
+                ErrorCode::Unauthorized,
+            )
+            .await;
+            return JoinAttempt::Handled;
         }
 
         if let Err(reason) =
             validation::validate_player_name_with_config(&player_name, &self.protocol_config)
         {
+            let details = reason.details(&player_name, &self.protocol_config);
             let _ = self
                 .message_coordinator
                 .send_to_player(
                     player_id,
                     Arc::new(ServerMessage::RoomJoinFailed {
-                        reason,
-                        error_code: Some(crate::protocol::ErrorCode::InvalidInput),
+                        reason: reason.to_string(),
+                        error_code: Some(
+                            reason.error_code(crate::protocol::ErrorCode::InvalidInput),
+                        ),
+                        retry_after_secs: None,
+                        details: Some(details),
                     }),
                 )
                 .await;
-            return;
+            return JoinAttempt::Handled;
+        }
+
+        if let Some(key) = &idempotency_key {
+            if let Err(reason) = validation::validate_idempotency_key(key) {
+                let details = validation::text_field_details(
+                    "idempotency_key",
+                    key,
+                    validation::MAX_IDEMPOTENCY_KEY_LENGTH,
+                );
+                self.send_join_failed_with_details(
+                    player_id,
+                    reason,
+                    ErrorCode::InvalidInput,
+                    Some(details),
+                )
+                .await;
+                return JoinAttempt::Handled;
+            }
         }
 
-        let max_players = max_players.unwrap_or(self.config.default_max_players);
+        let max_players = max_players.unwrap_or_else(|| self.default_max_players_for(&game_name));
         if let Err(reason) =
             validation::validate_max_players_with_config(max_players, &self.protocol_config)
         {
@@ -104,14 +211,38 @@ impl EnhancedGameServer {
                     Arc::new(ServerMessage::RoomJoinFailed {
                         reason,
                         error_code: Some(crate::protocol::ErrorCode::InvalidInput),
+                        retry_after_secs: None,
+                        details: Some(self.max_players_range_details()),
                     }),
                 )
                 .await;
-            return;
+            return JoinAttempt::Handled;
         }
 
         let supports_authority = supports_authority.unwrap_or(true);
 
+        if let Some(relay) = relay_type.as_deref() {
+            if let Err(reason) = self.relay_type_allowed(relay) {
+                let _ = self
+                    .message_coordinator
+                    .send_to_player(
+                        player_id,
+                        Arc::new(ServerMessage::RoomJoinFailed {
+                            reason,
+                            error_code: Some(crate::protocol::ErrorCode::InvalidInput),
+                            retry_after_secs: None,
+                            details: Some(ErrorDetails::validation(
+                                "relay_type",
+                                ValidationConstraint::AllowedValues,
+                                None,
+                            )),
+                        }),
+                    )
+                    .await;
+                return JoinAttempt::Handled;
+            }
+        }
+
         // Check if player is already in a room
         if self.get_client_room(player_id).await.is_some() {
             let _ = self
@@ -121,17 +252,28 @@ impl EnhancedGameServer {
                     Arc::new(ServerMessage::RoomJoinFailed {
                         reason: "Already in a room".to_string(),
                         error_code: Some(crate::protocol::ErrorCode::AlreadyInRoom),
+                        retry_after_secs: None,
+                        details: None,
                     }),
                 )
                 .await;
-            return;
+            return JoinAttempt::Handled;
         }
 
         let room_code = match room_code {
             Some(code) => {
-                if let Err(reason) =
-                    validation::validate_room_code_with_config(&code, &self.protocol_config)
-                {
+                if let Err(reason) = validation::validate_room_code_for_game(
+                    &code,
+                    &game_name,
+                    &self.protocol_config,
+                    &self.config().game_overrides,
+                ) {
+                    let length = validation::room_code_length_for_game(
+                        &game_name,
+                        &self.protocol_config,
+                        &self.config().game_overrides,
+                    );
+                    let details = validation::room_code_details(&code, length);
                     let _ = self
                         .message_coordinator
                         .send_to_player(
@@ -139,17 +281,49 @@ impl EnhancedGameServer {
                             Arc::new(ServerMessage::RoomJoinFailed {
                                 reason,
                                 error_code: Some(crate::protocol::ErrorCode::InvalidRoomCode),
+                                retry_after_secs: None,
+                                details: Some(details),
                             }),
                         )
                         .await;
-                    return;
+                    return JoinAttempt::Handled;
                 }
                 code.to_uppercase()
             }
-            None => self.generate_region_room_code(),
+            None => self.generate_room_code(&game_name),
+        };
+        let room_code = match idempotency_key.filter(|_| is_room_creation) {
+            Some(key) => {
+                self.claim_idempotent_room_code(player_id, &game_name, &key, room_code)
+                    .await
+            }
+            None => room_code,
         };
         room_join_span.record("room_code", tracing::field::display(&room_code));
 
+        let modifications = match self
+            .intercept_join(player_id, &game_name, &room_code, &player_name, false)
+            .await
+        {
+            JoinDecision::Allow => None,
+            JoinDecision::AllowWithModifications(modifications) => Some(modifications),
+            JoinDecision::Deny { code, message } => {
+                let _ = self
+                    .message_coordinator
+                    .send_to_player(
+                        player_id,
+                        Arc::new(ServerMessage::RoomJoinFailed {
+                            reason: message,
+                            error_code: Some(code),
+                            retry_after_secs: None,
+                            details: None,
+                        }),
+                    )
+                    .await;
+                return JoinAttempt::Handled;
+            }
+        };
+
         // Use distributed coordination for room operations
         let room_join_result = self
             .join_room_with_coordination(
@@ -159,79 +333,26 @@ impl EnhancedGameServer {
                 &player_name,
                 max_players,
                 supports_authority,
+                relay_type.as_deref(),
+                spectator_chat_mode,
+                allow_overflow_joins.unwrap_or(false),
             )
             .await;
 
         match room_join_result {
-            Ok(room) => {
+            Ok((mut room, turn)) => {
                 room_join_span.record("room_id", tracing::field::display(room.id));
-                self.connection_manager
-                    .assign_client_to_room(player_id, room.id)
-                    .await;
-
-                // Get current players from database
-                let current_players = match self.database.get_room_players(&room.id).await {
-                    Ok(players) => players,
-                    Err(e) => {
-                        tracing::error!("Failed to get room players: {}", e);
-                        Vec::new()
-                    }
-                };
-
-                // Send success response
-                let is_authority = room.authority_player == Some(*player_id);
-                let _ = self
-                    .message_coordinator
-                    .send_to_player(
-                        player_id,
-                        Arc::new(ServerMessage::RoomJoined(Box::new(RoomJoinedPayload {
-                            room_id: room.id,
-                            room_code: room.code.clone(),
-                            player_id: *player_id,
-                            game_name: room.game_name.clone(),
-                            max_players: room.max_players,
-                            supports_authority: room.supports_authority,
-                            current_players: current_players.clone(),
-                            is_authority,
-                            lobby_state: room.lobby_state.clone(),
-                            ready_players: room.ready_players.clone(),
-                            relay_type: room.relay_type.clone(),
-                            current_spectators: room.get_spectators(),
-                        }))),
-                    )
-                    .await;
-
-                // Notify other players
-                let player_info = PlayerInfo {
-                    id: *player_id,
-                    name: player_name,
-                    is_authority,
-                    is_ready: false,
-                    connected_at: chrono::Utc::now(),
-                    connection_info: None,
-                    region_id: self.region_id().to_string(),
-                };
-                let _ = self
-                    .message_coordinator
-                    .broadcast_to_room_except(
-                        &room.id,
-                        player_id,
-                        Arc::new(ServerMessage::PlayerJoined {
-                            player: player_info,
-                        }),
-                    )
-                    .await;
-
-                // Check if room should transition to lobby state
-                if room.should_enter_lobby() {
-                    if let Err(e) = self
-                        .room_coordinator
-                        .transition_room_to_lobby(&room.id)
-                        .await
-                    {
-                        tracing::error!("Failed to transition room to lobby: {}", e);
-                    }
+                // The name may have been suffixed to keep it unique
+                let player_name = room
+                    .players
+                    .get(player_id)
+                    .map_or(player_name, |player| player.name.clone());
+                if let Some(modifications) = modifications {
+                    self.apply_join_modifications(player_id, &mut room, modifications)
+                        .await;
                 }
+                self.announce_room_join(player_id, &room, player_name, turn)
+                    .await;
 
                 tracing::info!(
                     %player_id,
@@ -243,21 +364,180 @@ impl EnhancedGameServer {
                 );
             }
             Err(e) => {
+                if try_elsewhere
+                    && (e.downcast_ref::<RoomFullError>().is_some()
+                        || e.downcast_ref::<PlayerBannedError>().is_some()
+                        || e.downcast_ref::<PlayerNameTakenError>().is_some())
+                {
+                    return JoinAttempt::RoomUnavailable;
+                }
+                if let Some(full) = e.downcast_ref::<RoomFullError>() {
+                    if self
+                        .request_overflow_join(player_id, &full.room_id, &player_name)
+                        .await
+                    {
+                        return JoinAttempt::Handled;
+                    }
+                    if self.config().max_waiting_list_size > 0 {
+                        self.enqueue_waiting_player(player_id, full.room_id, player_name)
+                            .await;
+                        return JoinAttempt::Handled;
+                    }
+                }
                 let reason = e.to_string();
-                let error_code = if e.downcast_ref::<MaxRoomsPerGameExceededError>().is_some() {
-                    Some(crate::protocol::ErrorCode::MaxRoomsPerGameExceeded)
-                } else {
-                    Some(crate::protocol::ErrorCode::RoomCreationFailed)
-                };
+                let (error_code, details) = join_error_code_and_details(&e);
                 let _ = self
                     .message_coordinator
                     .send_to_player(
                         player_id,
-                        Arc::new(ServerMessage::RoomJoinFailed { reason, error_code }),
+                        Arc::new(ServerMessage::RoomJoinFailed {
+                            reason,
+                            error_code: Some(error_code),
+                            retry_after_secs: None,
+                            details,
+                        }),
                     )
                     .await;
             }
         }
+        JoinAttempt::Handled
+    }
+
+    /// Assign a freshly joined player to the room, send `RoomJoined`, notify the
+    /// other players, and move the room into the lobby once it fills up.
+    ///
+    /// `turn` is the room's turn the join was committed in; later joins and
+    /// leaves wait until it is released at the end of the announcement.
+    pub(super) async fn announce_room_join(
+        &self,
+        player_id: &PlayerId,
+        room: &Room,
+        player_name: String,
+        _turn: RoomTurn,
+    ) {
+        self.connection_manager
+            .assign_client_to_room(player_id, room.id)
+            .await;
+        self.connection_manager
+            .set_client_game(player_id, &room.game_name);
+        self.delivery_acks.label_room(room.id, &room.game_name);
+        self.matchmaking.dequeue(player_id);
+
+        // Get current players from database
+        let current_players = match self.database.get_room_players(&room.id).await {
+            Ok(players) => players,
+            Err(e) => {
+                tracing::error!("Failed to get room players: {}", e);
+                Vec::new()
+            }
+        };
+
+        // Send success response
+        let is_authority = room.authority_player == Some(*player_id);
+        let role = room
+            .players
+            .get(player_id)
+            .map(|player| player.role)
+            .unwrap_or_default();
+        let reconnection_token = match self.reconnection_manager_for(player_id) {
+            Some(manager) => {
+                manager.label_room(room.id, &room.game_name).await;
+                Some(manager.issue_token(*player_id, room.id).await)
+            }
+            None => None,
+        };
+        let ice_servers = self.ice_servers_for(player_id);
+        let peer_connection = (!ice_servers.is_empty()).then(|| PeerConnectionInfo {
+            player_id: *player_id,
+            player_name: player_name.clone(),
+            is_authority,
+            relay_type: room.relay_type.clone(),
+            connection_info: None,
+            ice_servers,
+        });
+        let _ = self
+            .message_coordinator
+            .send_to_player(
+                player_id,
+                Arc::new(ServerMessage::RoomJoined(Box::new(RoomJoinedPayload {
+                    room_id: room.id,
+                    room_code: room.code.clone(),
+                    player_id: *player_id,
+                    player_name: player_name.clone(),
+                    game_name: room.game_name.clone(),
+                    max_players: room.max_players,
+                    supports_authority: room.supports_authority,
+                    current_players: current_players.clone(),
+                    is_authority,
+                    role,
+                    lobby_state: room.lobby_state.clone(),
+                    ready_players: room.ready_players.clone(),
+                    lobby_version: room.lobby_version,
+                    relay_type: room.relay_type.clone(),
+                    current_spectators: room.get_spectators(),
+                    reconnection_token,
+                    relay_session: room.relay_session.clone(),
+                    metadata: room.metadata.clone(),
+                    peer_connection,
+                }))),
+            )
+            .await;
+        self.replay_room_history(player_id, &room.id).await;
+        self.closed_rooms.player_joined(room);
+        self.webhooks.player_joined(room, *player_id, &player_name);
+        self.room_events.publish(
+            room.id,
+            &room.game_name,
+            RoomEventKind::PlayerJoined {
+                player_id: *player_id,
+                player_name: player_name.clone(),
+                player_count: room.players.len(),
+            },
+        );
+
+        // Notify other players
+        let player_info = PlayerInfo {
+            id: *player_id,
+            name: player_name,
+            is_authority,
+            role,
+            is_ready: false,
+            connected_at: chrono::Utc::now(),
+            connection_info: None,
+            region_id: self.region_id().to_string(),
+        };
+        let _ = self
+            .message_coordinator
+            .broadcast_to_room_except(
+                &room.id,
+                player_id,
+                Arc::new(ServerMessage::PlayerJoined {
+                    player: player_info,
+                }),
+            )
+            .await;
+
+        self.enter_lobby_if_full(room).await;
+    }
+
+    /// Move a room that has just filled up into the lobby.
+    pub(super) async fn enter_lobby_if_full(&self, room: &Room) {
+        if !room.should_enter_lobby() {
+            return;
+        }
+        match self
+            .room_coordinator
+            .transition_room_to_lobby(&room.id)
+            .await
+        {
+            Ok(true) => {
+                self.room_events
+                    .publish(room.id, &room.game_name, RoomEventKind::LobbyEntered);
+                self.warm_up_relay(room, RelayWarmupTrigger::Lobby);
+            }
+            Ok(false) => {}
+            Err(e) => tracing::error!("Failed to transition room to lobby: {}", e),
+        }
     }
 
     /// Leave room with coordination
@@ -271,29 +551,68 @@ impl EnhancedGameServer {
         );
         let _span_guard = leave_span.enter();
         let Some(room_id) = self.get_client_room(player_id).await else {
+            // Leaving while queued or awaiting the authority cancels the request
+            let cancelled_join_request = self.cancel_join_request(player_id);
+            if self.cancel_waiting_list(player_id).await || cancelled_join_request {
+                let _ = self
+                    .message_coordinator
+                    .send_to_player(player_id, Arc::new(ServerMessage::RoomLeft))
+                    .await;
+            }
             return;
         };
         leave_span.record("room_id", tracing::field::display(room_id));
 
+        if !self.vacate_seat(player_id, &room_id).await {
+            return;
+        }
+
+        // A slot just opened up; spectators already watching the room are seated
+        // first, then players from the waiting list
+        self.promote_queued_spectators(&room_id).await;
+        self.promote_from_waiting_list(&room_id).await;
+
+        tracing::info!(
+            %player_id,
+            %room_id,
+            instance_id = %self.instance_id,
+            "Player left room with distributed coordination"
+        );
+    }
+
+    /// Remove a seated player from `room_id` and notify them and the room,
+    /// without filling the freed slot. Returns `false` if the player was not
+    /// seated there.
+    ///
+    /// Records `room_code` on the current span.
+    pub(super) async fn vacate_seat(&self, player_id: &PlayerId, room_id: &RoomId) -> bool {
+        let room_id = *room_id;
+        // Held until the room has been told, so later changes queue up behind
+        let _turn = self.room_turn(&room_id).await;
+
         // Remove player from room in database
-        let player_removed = match self
+        let removed_player = match self
             .database
             .remove_player_from_room(&room_id, player_id)
             .await
         {
-            Ok(Some(_)) => true,
-            Ok(None) => false,
+            Ok(player) => player,
             Err(e) => {
                 tracing::error!("Failed to remove player from room: {}", e);
-                false
+                None
             }
         };
 
-        if !player_removed {
-            return;
-        }
+        let Some(removed_player) = removed_player else {
+            return false;
+        };
 
         self.metrics.increment_players_left();
+        self.forget_flaps(player_id);
+        if removed_player.is_authority {
+            self.reject_room_join_requests(&room_id, "The room authority left")
+                .await;
+        }
 
         // Update client connection and coordinator
         let existing_sender = self.connection_manager.clear_room_assignment(player_id);
@@ -303,7 +622,7 @@ impl EnhancedGameServer {
                 .message_coordinator
                 .register_local_client(*player_id, None, sender)
                 .await;
-        } else {
+        } else if self.connection_manager.has_client(player_id) {
             tracing::warn!(%player_id, "Could not find existing sender for player when leaving room");
         }
 
@@ -326,38 +645,128 @@ impl EnhancedGameServer {
             .await;
 
         // Check if room should transition out of lobby state after player left
-        let mut latest_room_code: Option<String> = None;
         if let Ok(Some(room)) = self.database.get_room_by_id(&room_id).await {
-            latest_room_code = Some(room.code.clone());
+            tracing::Span::current().record("room_code", tracing::field::display(&room.code));
+            self.webhooks
+                .player_left(&room, *player_id, &removed_player.name);
+            self.room_events.publish(
+                room_id,
+                &room.game_name,
+                RoomEventKind::PlayerLeft {
+                    player_id: *player_id,
+                    player_name: removed_player.name.clone(),
+                    player_count: room.players.len(),
+                },
+            );
             if room.lobby_state == LobbyState::Lobby && !room.should_enter_lobby() {
-                if let Err(e) = self.database.transition_room_to_waiting(&room_id).await {
-                    tracing::warn!("Failed to transition room back to waiting state: {}", e);
-                } else {
-                    tracing::info!(
-                        %room_id,
-                        "Room transitioned from lobby back to waiting state after player left"
-                    );
-
-                    if let Err(e) = self.room_coordinator.clear_ready_players(&room_id).await {
-                        tracing::warn!("Failed to clear ready players from coordinator: {}", e);
+                match self.database.transition_room_to_waiting(&room_id).await {
+                    Ok(Some(lobby_version)) => {
+                        tracing::info!(
+                            %room_id,
+                            "Room transitioned from lobby back to waiting state after player left"
+                        );
+                        let _ = self
+                            .message_coordinator
+                            .broadcast_to_room(
+                                &room_id,
+                                Arc::new(ServerMessage::LobbyStateChanged {
+                                    lobby_state: LobbyState::Waiting,
+                                    ready_players: Vec::new(),
+                                    all_ready: false,
+                                    lobby_version,
+                                }),
+                            )
+                            .await;
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        tracing::warn!("Failed to transition room back to waiting state: {}", e);
                     }
                 }
             }
         }
-        if let Some(code) = &latest_room_code {
-            leave_span.record("room_code", tracing::field::display(code));
+        true
+    }
+
+    /// Add `player_info` to `room`, applying `on_duplicate_name` if the name
+    /// is taken. `player_info.name` is updated to the name actually used.
+    ///
+    /// With `overflow_limit`, the room is full once it holds that many
+    /// players instead of `max_players`. Returns the turn the player was
+    /// seated in, or `None` if the room is full.
+    pub(super) async fn add_player_with_name_policy(
+        &self,
+        room: &Room,
+        player_info: &mut PlayerInfo,
+        overflow_limit: Option<u8>,
+    ) -> anyhow::Result<Option<RoomTurn>> {
+        let result = self
+            .seat_player(&room.id, player_info.clone(), overflow_limit)
+            .await;
+        let name_taken =
+            matches!(&result, Err(e) if e.downcast_ref::<PlayerNameTakenError>().is_some());
+        if !name_taken {
+            return result;
         }
+        // `room` was read under the join lock, so its names are current
+        let Some(name) = validation::suffix_duplicate_player_name(
+            &player_info.name,
+            &room.players,
+            self.protocol_config.max_player_name_length,
+        ) else {
+            return result;
+        };
+        if self.protocol_config.on_duplicate_name == DuplicateNamePolicy::Reject {
+            // Offer the name `suffix` would have picked
+            return Err(PlayerNameTakenError {
+                suggested: Some(name),
+            }
+            .into());
+        }
+        player_info.name = name;
+        self.seat_player(&room.id, player_info.clone(), overflow_limit)
+            .await
+    }
 
-        tracing::info!(
-            %player_id,
-            %room_id,
-            room_code = latest_room_code.as_deref().unwrap_or("unknown"),
-            instance_id = %self.instance_id,
-            "Player left room with distributed coordination"
-        );
+    /// Add a player to the room in its turn. Returns the turn, to be held
+    /// until the join is announced, or `None` if the room is full.
+    pub(super) async fn seat_player(
+        &self,
+        room_id: &RoomId,
+        player_info: PlayerInfo,
+        overflow_limit: Option<u8>,
+    ) -> anyhow::Result<Option<RoomTurn>> {
+        let turn = self.room_turn(room_id).await;
+        let added = match overflow_limit {
+            Some(limit) => {
+                self.database
+                    .add_overflow_player_to_room(room_id, player_info, limit)
+                    .await?
+            }
+            None => {
+                self.database
+                    .add_player_to_room(room_id, player_info)
+                    .await?
+            }
+        };
+        Ok(added.then_some(turn))
     }
 
-    /// Join room with distributed coordination
+    /// Details for a `max_players` outside 1 to `max_players_limit`.
+    pub(super) fn max_players_range_details(&self) -> ErrorDetails {
+        ErrorDetails::validation(
+            "max_players",
+            ValidationConstraint::Range,
+            Some(u64::from(self.protocol_config.max_players_limit)),
+        )
+    }
+
+    /// Join room with distributed coordination.
+    ///
+    /// `relay_type`, `spectator_chat_mode` and `allow_overflow_joins` only
+    /// apply when the room is created; joining an existing room keeps its
+    /// settings.
+    #[allow(clippy::too_many_arguments)]
     pub(super) async fn join_room_with_coordination(
         &self,
         player_id: &PlayerId,
@@ -366,7 +775,10 @@ impl EnhancedGameServer {
         player_name: &str,
         max_players: u8,
         supports_authority: bool,
-    ) -> anyhow::Result<Room> {
+        relay_type: Option<&str>,
+        spectator_chat_mode: Option<SpectatorChatMode>,
+        allow_overflow_joins: bool,
+    ) -> anyhow::Result<(Room, RoomTurn)> {
         let lock_key = format!("room_join:{game_name}:{room_code}");
         let lock_handle = self
             .distributed_lock
@@ -377,33 +789,69 @@ impl EnhancedGameServer {
         // Try to join existing room or create new one
         let result = match self.database.get_room(game_name, room_code).await {
             Ok(Some(mut room)) => {
-                let client_app_id = self.client_app_id(player_id);
-                // Validate player name uniqueness
-                if let Err(reason) =
-                    validation::validate_player_name_uniqueness(player_name, &room.players)
-                {
+                if room.is_banned(player_id, self.connection_manager.client_ip(player_id)) {
                     let _ = self.distributed_lock.release(&lock_handle).await;
-                    return Err(anyhow::anyhow!(reason));
+                    return Err(anyhow::anyhow!(PlayerBannedError));
                 }
-
-                let player_info = PlayerInfo {
+                let client_app_id = self.client_app_id(player_id);
+                // Seat the player only while the room's application has space
+                let room_app_id = room
+                    .application_id
+                    .or_else(|| self.room_application_id(&room.id));
+                let app_quota_lock = match room_app_id {
+                    Some(app_id) => {
+                        let quota = self.app_quota(&app_id);
+                        if quota.max_players_per_app.is_some() {
+                            let lock = self.acquire_app_quota_lock(&app_id).await;
+                            if let Err(e) = self.check_app_player_quota(&app_id, &quota).await {
+                                for lock in lock.iter().chain(std::iter::once(&lock_handle)) {
+                                    let _ = self.distributed_lock.release(lock).await;
+                                }
+                                return Err(e);
+                            }
+                            lock
+                        } else {
+                            None
+                        }
+                    }
+                    None => None,
+                };
+                // Name uniqueness is checked by the database as the player is added
+                let mut player_info = PlayerInfo {
                     id: *player_id,
                     name: player_name.to_string(),
                     is_authority: false,
+                    role: PlayerRole::Member,
                     is_ready: false,
                     connected_at: chrono::Utc::now(),
                     connection_info: None,
                     region_id: room.region_id.clone(),
                 };
 
-                match self
-                    .database
-                    .add_player_to_room(&room.id, player_info.clone())
-                    .await
-                {
-                    Ok(true) => {
+                let mut added = self
+                    .add_player_with_name_policy(&room, &mut player_info, None)
+                    .await;
+                // A full room may still have a seat held for someone who can no longer reconnect
+                let mut reclaimed_ghost = false;
+                if matches!(added, Ok(None)) {
+                    if let Some(ghost_id) = self.reclaim_ghost_seat(&room.id).await {
+                        room.players.remove(&ghost_id);
+                        reclaimed_ghost = true;
+                        added = self
+                            .add_player_with_name_policy(&room, &mut player_info, None)
+                            .await;
+                    }
+                }
+                if let Some(lock) = &app_quota_lock {
+                    let _ = self.distributed_lock.release(lock).await;
+                }
+                match added {
+                    Ok(Some(turn)) => {
                         self.metrics.increment_rooms_joined();
                         self.metrics.increment_players_joined();
+                        if reclaimed_ghost {
+                            self.metrics.increment_ghost_slots_reclaimed(game_name);
+                        }
                         room.players.insert(*player_id, player_info);
                         if self.room_application_id(&room.id).is_none() {
                             if let Some(persisted_app) = room.application_id {
@@ -412,13 +860,31 @@ impl EnhancedGameServer {
                                 self.record_room_application(&room.id, app_id).await;
                             }
                         }
-                        Ok(room)
+                        Ok((room, turn))
                     }
-                    Ok(false) => Err(anyhow::anyhow!("Room is full")),
+                    Ok(None) => Err(anyhow::anyhow!(RoomFullError {
+                        room_id: room.id,
+                        max_players: room.max_players,
+                    })),
                     Err(e) => Err(e),
                 }
             }
             Ok(None) => {
+                // The room belongs to the creator's configured application, if
+                // any, and gets that application's limits
+                let room_app = match self.room_creation_app(player_id) {
+                    Ok(app) => app,
+                    Err(e) => {
+                        tracing::warn!(%player_id, app_id = ?self.client_app_id(player_id), "Room creation rejected: {}", e);
+                        let _ = self.distributed_lock.release(&lock_handle).await;
+                        return Err(anyhow::anyhow!(e));
+                    }
+                };
+                let max_players = room_app
+                    .as_ref()
+                    .and_then(|app| app.max_players_per_room)
+                    .map_or(max_players, |limit| max_players.min(limit));
+
                 // Enforce per-game room cap before creating a new room
                 let cap_lock_key = format!("game_room_cap:{game_name}");
                 match self
@@ -436,25 +902,67 @@ impl EnhancedGameServer {
                     }
                 }
 
-                let current_room_count = self.database.get_game_room_count(game_name).await?;
-                if current_room_count >= self.config.max_rooms_per_game {
-                    self.metrics.increment_room_cap_denials();
-                    if let Some(lock) = &game_cap_lock {
-                        let _ = self.distributed_lock.release(lock).await;
+                // Checked by the database as the room is inserted, so the cap
+                // holds even when the lock above could not be taken
+                let room_limit = self.max_rooms_for_game(game_name);
+
+                // Enforce the quotas of the application the room is counted against
+                let app = self.quota_app_for(room_app.as_ref());
+                let mut app_cap_lock = None;
+                if app.quota != AppQuotaConfig::default() {
+                    app_cap_lock = self.acquire_app_quota_lock(&app.id).await;
+                    if let Err(e) = self.check_app_room_creation(&app).await {
+                        self.metrics.increment_room_cap_denials();
+                        for lock in app_cap_lock.iter().chain(&game_cap_lock) {
+                            let _ = self.distributed_lock.release(lock).await;
+                        }
+                        let _ = self.distributed_lock.release(&lock_handle).await;
+                        return Err(e);
                     }
-                    return Err(anyhow::anyhow!(MaxRoomsPerGameExceededError {
-                        game_name: game_name.to_string(),
-                        current: current_room_count,
-                        limit: self.config.max_rooms_per_game,
-                    }));
                 }
 
-                let relay_type = self.resolve_relay_type(game_name);
-                let client_app_id = self.client_app_id(player_id);
-                let region_id = self.region_id().to_string();
+                // Enforce the per-creator active room cap, keyed like rate limits
+                let rate_limits = &self.config().rate_limit_config;
+                let creator_key = self
+                    .request_origin(player_id)
+                    .key(rate_limits.key_by)
+                    .to_string();
+                let mut creator_cap_lock = None;
+                if let Some(limit) = rate_limits.max_active_rooms_per_creator {
+                    let creator_lock_key = format!("creator_room_cap:{creator_key}");
+                    match self
+                        .distributed_lock
+                        .acquire(&creator_lock_key, Duration::from_secs(10))
+                        .await
+                    {
+                        Ok(lock) => creator_cap_lock = Some(lock),
+                        Err(err) => tracing::error!("Failed to acquire creator cap lock: {}", err),
+                    }
+
+                    let current = self.database.count_rooms_by_creator(&creator_key).await?;
+                    if current >= limit as usize {
+                        self.metrics.increment_room_cap_denials();
+                        for lock in creator_cap_lock
+                            .iter()
+                            .chain(&app_cap_lock)
+                            .chain(&game_cap_lock)
+                        {
+                            let _ = self.distributed_lock.release(lock).await;
+                        }
+                        let _ = self.distributed_lock.release(&lock_handle).await;
+                        return Err(anyhow::anyhow!(MaxActiveRoomsPerCreatorError {
+                            creator: creator_key,
+                            current,
+                            limit: limit as usize,
+                        }));
+                    }
+                }
+
+                let relay_type = self.resolve_relay_type(game_name, relay_type);
+                let region_id = self.client_region(player_id);
                 let created_room = self
                     .database
-                    .create_room(
+                    .create_room_if_under_limit(
                         game_name.to_string(),
                         Some(room_code.to_string()),
                         max_players,
@@ -462,20 +970,66 @@ impl EnhancedGameServer {
                         *player_id,
                         relay_type,
                         region_id.clone(),
-                        client_app_id,
+                        Some(app.id),
+                        room_limit,
                     )
                     .await;
 
-                if let Some(lock) = &game_cap_lock {
+                // Record the creator before releasing its cap lock so the next
+                // create from the same creator counts this room
+                if let Ok(Some(room)) = &created_room {
+                    if let Err(e) = self.database.set_room_creator(&room.id, &creator_key).await {
+                        tracing::warn!(room_id = %room.id, "Failed to record room creator: {}", e);
+                    }
+                    self.app_room_creations.record(app.id);
+                }
+                for lock in creator_cap_lock
+                    .iter()
+                    .chain(&app_cap_lock)
+                    .chain(&game_cap_lock)
+                {
                     let _ = self.distributed_lock.release(lock).await;
                 }
 
                 match created_room {
-                    Ok(mut room) => {
+                    Ok(Some(mut room)) => {
+                        // Nobody else can join before the join lock is released
+                        let turn = self.room_turn(&room.id).await;
+                        room.creator_key = Some(creator_key);
                         self.metrics.increment_rooms_created();
+                        self.metrics
+                            .increment_rooms_created_for_relay_type(&room.relay_type);
+                        self.closed_rooms.room_created(&room);
+                        self.webhooks.room_created(&room, *player_id);
+                        self.room_events.publish(
+                            room.id,
+                            &room.game_name,
+                            RoomEventKind::RoomCreated {
+                                room_code: room.code.clone(),
+                                max_players: room.max_players,
+                                created_by: *player_id,
+                            },
+                        );
+                        self.warm_up_relay(&room, RelayWarmupTrigger::RoomCreated);
                         self.metrics.increment_players_joined();
-                        if let Some(app_id) = client_app_id {
-                            self.record_room_application(&room.id, app_id).await;
+                        self.record_room_application(&room.id, app.id).await;
+                        if let Some(mode) = spectator_chat_mode {
+                            if let Err(e) =
+                                self.database.set_spectator_chat_mode(&room.id, mode).await
+                            {
+                                tracing::warn!(room_id = %room.id, "Failed to set spectator chat mode: {}", e);
+                            } else {
+                                room.spectator_chat_mode = mode;
+                            }
+                        }
+                        if allow_overflow_joins {
+                            if let Err(e) =
+                                self.database.set_allow_overflow_joins(&room.id, true).await
+                            {
+                                tracing::warn!(room_id = %room.id, "Failed to allow overflow joins: {}", e);
+                            } else {
+                                room.allow_overflow_joins = true;
+                            }
                         }
                         if let Err(e) = self
                             .database
@@ -486,7 +1040,15 @@ impl EnhancedGameServer {
                         } else if let Some(creator_info) = room.players.get_mut(player_id) {
                             creator_info.name = player_name.to_string();
                         }
-                        Ok(room)
+                        Ok((room, turn))
+                    }
+                    Ok(None) => {
+                        self.metrics.increment_room_cap_denials();
+                        Err(anyhow::anyhow!(MaxRoomsPerGameExceededError {
+                            game_name: game_name.to_string(),
+                            current: room_limit,
+                            limit: room_limit,
+                        }))
                     }
                     Err(e) => Err(anyhow::anyhow!(e)),
                 }
@@ -497,4 +1059,119 @@ impl EnhancedGameServer {
         let _ = self.distributed_lock.release(&lock_handle).await;
         result
     }
+
+    /// The room code a create with `key` should use: the code claimed by an
+    /// earlier create with the same key while it is within
+    /// `room_idempotency_window`, otherwise `room_code`, which is claimed for
+    /// the key. A retry then joins the first create's room, and concurrent
+    /// creates with one key queue on the same room join lock.
+    async fn claim_idempotent_room_code(
+        &self,
+        player_id: &PlayerId,
+        game_name: &str,
+        key: &str,
+        room_code: String,
+    ) -> String {
+        let window = self.config().room_idempotency_window;
+        if window.is_zero() {
+            return room_code;
+        }
+        let app_id = self.quota_app(player_id).id;
+        let claim = IdempotentRoom {
+            game_name: game_name.to_string(),
+            room_code,
+        };
+        match self
+            .database
+            .claim_room_idempotency_key(
+                &app_id,
+                key,
+                claim.clone(),
+                chrono_duration_from_std(window),
+            )
+            .await
+        {
+            Ok(Some(existing)) if existing.game_name == game_name => {
+                tracing::info!(
+                    %player_id,
+                    %game_name,
+                    room_code = %existing.room_code,
+                    "Replayed idempotent room creation"
+                );
+                existing.room_code
+            }
+            Ok(Some(existing)) => {
+                tracing::warn!(
+                    %player_id,
+                    %game_name,
+                    claimed_game = %existing.game_name,
+                    "Idempotency key already used for another game; creating a new room"
+                );
+                claim.room_code
+            }
+            Ok(None) => claim.room_code,
+            Err(e) => {
+                tracing::warn!(%player_id, "Failed to claim room idempotency key: {}", e);
+                claim.room_code
+            }
+        }
+    }
+
+    /// Where `player_id`'s requests come from, for rate limits and creator caps.
+    fn request_origin(&self, player_id: &PlayerId) -> RequestOrigin {
+        RequestOrigin {
+            player_id: *player_id,
+            client_ip: self.connection_manager.client_ip(player_id),
+            app_id: self.connection_manager.app_id(player_id),
+        }
+    }
+}
+
+/// The error code and typed details to report for a failed join.
+pub(super) fn join_error_code_and_details(e: &anyhow::Error) -> (ErrorCode, Option<ErrorDetails>) {
+    if let Some(err) = e.downcast_ref::<MaxRoomsPerGameExceededError>() {
+        let details = ErrorDetails::QuotaExceeded {
+            current: err.current,
+            limit: err.limit,
+            quota: Some(QuotaKind::RoomsPerGame),
+        };
+        (ErrorCode::MaxRoomsPerGameExceeded, Some(details))
+    } else if let Some(err) = e.downcast_ref::<MaxRoomsPerAppExceededError>() {
+        let details = ErrorDetails::QuotaExceeded {
+            current: err.current,
+            limit: err.limit,
+            quota: Some(QuotaKind::AppRooms),
+        };
+        (ErrorCode::AppRoomQuotaExceeded, Some(details))
+    } else if let Some(err) = e.downcast_ref::<AppQuotaExceededError>() {
+        let details = ErrorDetails::QuotaExceeded {
+            current: err.current,
+            limit: err.limit,
+            quota: Some(err.quota),
+        };
+        (ErrorCode::AppQuotaExceeded, Some(details))
+    } else if let Some(err) = e.downcast_ref::<MaxActiveRoomsPerCreatorError>() {
+        let details = ErrorDetails::QuotaExceeded {
+            current: err.current,
+            limit: err.limit,
+            quota: Some(QuotaKind::RoomsPerCreator),
+        };
+        (ErrorCode::TooManyActiveRooms, Some(details))
+    } else if let Some(err) = e.downcast_ref::<RoomFullError>() {
+        let details = ErrorDetails::RoomFull {
+            max_players: err.max_players,
+        };
+        (ErrorCode::RoomFull, Some(details))
+    } else if e.downcast_ref::<PlayerBannedError>().is_some() {
+        (ErrorCode::Banned, None)
+    } else if e.downcast_ref::<validation::ValidationError>().is_some() {
+        (ErrorCode::InvalidAppId, None)
+    } else if let Some(err) = e.downcast_ref::<PlayerNameTakenError>() {
+        let details = ErrorDetails::NameTaken {
+            suggested: err.suggested.clone(),
+        };
+        (ErrorCode::InvalidPlayerName, Some(details))
+    } else {
+        (ErrorCode::RoomCreationFailed, None)
+    }
 }
@@ -84,3 +84,593 @@ async fn leave_room_sends_confirmation_and_clears_membership() {
         "player should be removed from room state"
     );
 }
+
+async fn register_app_client(
+    server: &EnhancedGameServer,
+    app_info: &crate::auth::AppInfo,
+    port: u16,
+) -> (PlayerId, mpsc::Receiver<Outbound>) {
+    let (sender, receiver) = mpsc::channel(16);
+    let addr: SocketAddr = format!("127.0.0.1:{port}").parse().unwrap();
+    let player_id = server
+        .connection_manager
+        .register_client(sender, addr, server.instance_id)
+        .await
+        .expect("client registration succeeds");
+    server.set_client_app_info(&player_id, app_info.clone());
+    (player_id, receiver)
+}
+
+async fn create_room_as(server: &EnhancedGameServer, player_id: &PlayerId, game_name: &str) {
+    server
+        .handle_join_room(
+            player_id,
+            game_name.to_string(),
+            None,
+            "creator".to_string(),
+            Some(4),
+            Some(false),
+            None,
+            None,
+            None,
+        )
+        .await;
+}
+
+#[tokio::test]
+async fn token_room_scope_limits_games_a_client_may_join() {
+    let server = create_test_server().await;
+    let mut scoped = server
+        .auth_middleware
+        .validate_app_id("scoped-app")
+        .await
+        .unwrap();
+    scoped.subject = Some("player-1".to_string());
+    scoped.room_scope = Some(vec!["chess".to_string()]);
+
+    let (player_id, mut rx) = register_app_client(&server, &scoped, 48110).await;
+    create_room_as(&server, &player_id, "checkers").await;
+    let rejected = timeout(Duration::from_secs(1), rx.recv())
+        .await
+        .expect("channel still open")
+        .expect("join response present");
+    assert!(matches!(
+        &*rejected,
+        ServerMessage::RoomJoinFailed {
+            error_code: Some(crate::protocol::ErrorCode::Unauthorized),
+            ..
+        }
+    ));
+    assert!(server.get_client_room(&player_id).await.is_none());
+
+    create_room_as(&server, &player_id, "chess").await;
+    let joined = timeout(Duration::from_secs(1), rx.recv())
+        .await
+        .expect("channel still open")
+        .expect("join response present");
+    assert!(matches!(*joined, ServerMessage::RoomJoined(_)));
+}
+
+#[tokio::test]
+async fn create_room_rejected_once_app_room_quota_is_reached() {
+    let apps = vec![
+        crate::config::AppAuthEntry {
+            app_id: "limited".to_string(),
+            app_secret: "secret".to_string(),
+            app_name: "Limited".to_string(),
+            max_rooms: None,
+            max_rooms_per_app: Some(1),
+            max_players_per_app: None,
+            max_rooms_per_hour: None,
+            max_players_per_room: None,
+            rate_limit_per_minute: None,
+            api_keys: Vec::new(),
+        },
+        crate::config::AppAuthEntry {
+            app_id: "unlimited".to_string(),
+            app_secret: "secret".to_string(),
+            app_name: "Unlimited".to_string(),
+            max_rooms: None,
+            max_rooms_per_app: None,
+            max_players_per_app: None,
+            max_rooms_per_hour: None,
+            max_players_per_room: None,
+            rate_limit_per_minute: None,
+            api_keys: Vec::new(),
+        },
+    ];
+    let server = EnhancedGameServer::new(
+        ServerConfig {
+            auth_enabled: true,
+            ..ServerConfig::default()
+        },
+        ProtocolConfig::default(),
+        RelayTypeConfig::default(),
+        DatabaseConfig::InMemory,
+        MetricsConfig::default(),
+        AuthMaintenanceConfig::default(),
+        CoordinationConfig::default(),
+        TransportSecurityConfig::default(),
+        apps,
+    )
+    .await
+    .expect("failed to construct test server");
+    let limited = server
+        .auth_middleware
+        .validate_app_id("limited")
+        .await
+        .unwrap();
+    let unlimited = server
+        .auth_middleware
+        .validate_app_id("unlimited")
+        .await
+        .unwrap();
+
+    let (first, mut first_rx) = register_app_client(&server, &limited, 48100).await;
+    create_room_as(&server, &first, "quota-game").await;
+    let joined = timeout(Duration::from_secs(1), first_rx.recv())
+        .await
+        .expect("channel still open")
+        .expect("join response present");
+    assert!(matches!(*joined, ServerMessage::RoomJoined(_)));
+
+    // The quota spans games: a second room for the same app is rejected anywhere
+    let (second, mut second_rx) = register_app_client(&server, &limited, 48101).await;
+    create_room_as(&server, &second, "other-game").await;
+    let rejected = timeout(Duration::from_secs(1), second_rx.recv())
+        .await
+        .expect("channel still open")
+        .expect("join response present");
+    match &*rejected {
+        ServerMessage::RoomJoinFailed {
+            error_code,
+            details,
+            ..
+        } => {
+            assert_eq!(
+                *error_code,
+                Some(crate::protocol::ErrorCode::AppRoomQuotaExceeded)
+            );
+            assert_eq!(
+                *details,
+                Some(crate::protocol::ErrorDetails::QuotaExceeded {
+                    current: 1,
+                    limit: 1,
+                    quota: Some(crate::protocol::QuotaKind::AppRooms),
+                })
+            );
+        }
+        other => panic!("expected RoomJoinFailed, got {other:?}"),
+    }
+    assert_eq!(
+        server
+            .database
+            .get_app_room_count(&limited.id)
+            .await
+            .unwrap(),
+        1
+    );
+
+    // Apps without a quota are unlimited
+    for port in 48102..48105 {
+        let (player, mut rx) = register_app_client(&server, &unlimited, port).await;
+        create_room_as(&server, &player, "quota-game").await;
+        let joined = timeout(Duration::from_secs(1), rx.recv())
+            .await
+            .expect("channel still open")
+            .expect("join response present");
+        assert!(matches!(*joined, ServerMessage::RoomJoined(_)));
+    }
+    assert_eq!(
+        server
+            .database
+            .get_app_room_count(&unlimited.id)
+            .await
+            .unwrap(),
+        3
+    );
+}
+
+#[tokio::test]
+async fn game_overrides_replace_default_max_players_and_room_cap() {
+    let mut config = ServerConfig::default();
+    config.game_overrides.insert(
+        "duel".to_string(),
+        crate::config::GameConfig {
+            default_max_players: Some(2),
+            max_rooms_per_game: Some(1),
+            ..Default::default()
+        },
+    );
+    let server = EnhancedGameServer::new(
+        config,
+        ProtocolConfig::default(),
+        RelayTypeConfig::default(),
+        DatabaseConfig::InMemory,
+        MetricsConfig::default(),
+        AuthMaintenanceConfig::default(),
+        CoordinationConfig::default(),
+        TransportSecurityConfig::default(),
+        Vec::new(),
+    )
+    .await
+    .expect("failed to construct test server");
+
+    let mut responses = Vec::new();
+    for (port, game_name) in [(48200, "duel"), (48201, "duel"), (48202, "arena")] {
+        let (sender, mut receiver) = mpsc::channel(16);
+        let addr: SocketAddr = format!("127.0.0.1:{port}").parse().unwrap();
+        let player_id = server
+            .connection_manager
+            .register_client(sender, addr, server.instance_id)
+            .await
+            .expect("client registration succeeds");
+        server
+            .handle_join_room(
+                &player_id,
+                game_name.to_string(),
+                None,
+                "creator".to_string(),
+                None,
+                Some(false),
+                None,
+                None,
+                None,
+            )
+            .await;
+        let response = timeout(Duration::from_secs(1), receiver.recv())
+            .await
+            .expect("channel still open")
+            .expect("join response present");
+        responses.push(response);
+    }
+
+    match &*responses[0] {
+        ServerMessage::RoomJoined(payload) => assert_eq!(payload.max_players, 2),
+        other => panic!("expected RoomJoined, got {other:?}"),
+    }
+    match &*responses[1] {
+        ServerMessage::RoomJoinFailed { error_code, .. } => assert_eq!(
+            *error_code,
+            Some(crate::protocol::ErrorCode::MaxRoomsPerGameExceeded)
+        ),
+        other => panic!("expected RoomJoinFailed, got {other:?}"),
+    }
+    match &*responses[2] {
+        ServerMessage::RoomJoined(payload) => {
+            assert_eq!(payload.max_players, server.config().default_max_players);
+        }
+        other => panic!("expected RoomJoined, got {other:?}"),
+    }
+}
+
+async fn response(receiver: &mut mpsc::Receiver<Outbound>) -> Outbound {
+    timeout(Duration::from_secs(1), receiver.recv())
+        .await
+        .expect("channel still open")
+        .expect("join response present")
+}
+
+#[tokio::test]
+async fn concurrent_creates_stop_at_the_per_creator_room_cap() {
+    let server = EnhancedGameServer::new(
+        ServerConfig {
+            rate_limit_config: crate::rate_limit::RateLimitConfig {
+                max_active_rooms_per_creator: Some(2),
+                ..Default::default()
+            },
+            ..ServerConfig::default()
+        },
+        ProtocolConfig::default(),
+        RelayTypeConfig::default(),
+        DatabaseConfig::InMemory,
+        MetricsConfig::default(),
+        AuthMaintenanceConfig::default(),
+        CoordinationConfig::default(),
+        TransportSecurityConfig::default(),
+        Vec::new(),
+    )
+    .await
+    .expect("failed to construct test server");
+
+    // Every client connects from 127.0.0.1, so they share one creator key
+    let mut clients = Vec::new();
+    for port in 48300..48304 {
+        let (sender, receiver) = mpsc::channel(16);
+        let addr: SocketAddr = format!("127.0.0.1:{port}").parse().unwrap();
+        let player_id = server
+            .connection_manager
+            .register_client(sender, addr, server.instance_id)
+            .await
+            .expect("client registration succeeds");
+        clients.push((player_id, receiver));
+    }
+
+    create_room_as(&server, &clients[0].0, "cap-game").await;
+    assert!(matches!(
+        *response(&mut clients[0].1).await,
+        ServerMessage::RoomJoined(_)
+    ));
+
+    // Two creates race for the last slot; exactly one of them gets it
+    tokio::join!(
+        create_room_as(&server, &clients[1].0, "cap-game"),
+        create_room_as(&server, &clients[2].0, "other-game"),
+    );
+    let mut joined = None;
+    let mut rejections = 0;
+    for (_, receiver) in &mut clients[1..3] {
+        match &*response(receiver).await {
+            ServerMessage::RoomJoined(payload) => joined = Some(payload.room_id),
+            ServerMessage::RoomJoinFailed { error_code, .. } => {
+                assert_eq!(
+                    *error_code,
+                    Some(crate::protocol::ErrorCode::TooManyActiveRooms)
+                );
+                rejections += 1;
+            }
+            other => panic!("expected a join response, got {other:?}"),
+        }
+    }
+    assert_eq!(rejections, 1);
+    let creator_key = "ip:127.0.0.1";
+    assert_eq!(
+        server
+            .database
+            .count_rooms_by_creator(creator_key)
+            .await
+            .unwrap(),
+        2
+    );
+
+    // Closing a room frees its slot
+    let room_id = joined.expect("one create succeeded");
+    assert!(server.database.delete_room(&room_id).await.unwrap());
+    create_room_as(&server, &clients[3].0, "cap-game").await;
+    assert!(matches!(
+        *response(&mut clients[3].1).await,
+        ServerMessage::RoomJoined(_)
+    ));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+async fn concurrent_creates_stop_at_the_per_game_room_cap() {
+    let server = EnhancedGameServer::new(
+        ServerConfig {
+            max_rooms_per_game: 10,
+            ..ServerConfig::default()
+        },
+        ProtocolConfig::default(),
+        RelayTypeConfig::default(),
+        DatabaseConfig::InMemory,
+        MetricsConfig::default(),
+        AuthMaintenanceConfig::default(),
+        CoordinationConfig::default(),
+        TransportSecurityConfig::default(),
+        Vec::new(),
+    )
+    .await
+    .expect("failed to construct test server");
+
+    // Distinct addresses keep connection and rate limits out of the way
+    let mut clients = Vec::new();
+    for index in 0..100u8 {
+        let (sender, receiver) = mpsc::channel(16);
+        let addr: SocketAddr = format!("10.1.0.{}:4000", index + 1).parse().unwrap();
+        let player_id = server
+            .connection_manager
+            .register_client(sender, addr, server.instance_id)
+            .await
+            .expect("client registration succeeds");
+        clients.push((player_id, receiver));
+    }
+
+    let creates: Vec<_> = clients
+        .iter()
+        .map(|(player_id, _)| {
+            let server = Arc::clone(&server);
+            let player_id = *player_id;
+            tokio::spawn(async move { create_room_as(&server, &player_id, "crowded-game").await })
+        })
+        .collect();
+    for create in creates {
+        create.await.expect("create task completes");
+    }
+
+    let mut created = 0;
+    for (_, receiver) in &mut clients {
+        match &*response(receiver).await {
+            ServerMessage::RoomJoined(_) => created += 1,
+            ServerMessage::RoomJoinFailed { error_code, .. } => assert_eq!(
+                *error_code,
+                Some(crate::protocol::ErrorCode::MaxRoomsPerGameExceeded)
+            ),
+            other => panic!("expected a join response, got {other:?}"),
+        }
+    }
+    assert_eq!(created, 10);
+    assert_eq!(
+        server
+            .database
+            .get_game_room_count("crowded-game")
+            .await
+            .unwrap(),
+        10
+    );
+}
+
+async fn create_room_with_key(server: &EnhancedGameServer, player_id: &PlayerId, key: &str) {
+    server
+        .join_room_request(
+            player_id,
+            "retry-game".to_string(),
+            None,
+            format!("player-{}", &player_id.to_string()[..8]),
+            Some(8),
+            Some(false),
+            None,
+            None,
+            None,
+            None,
+            Some(key.to_string()),
+            false,
+        )
+        .await;
+}
+
+#[tokio::test]
+async fn concurrent_creates_with_one_idempotency_key_share_a_room() {
+    let server = create_test_server().await;
+    // Separate addresses keep the creates under the per-IP rate limit
+    let mut clients = Vec::new();
+    for host in 1..=5 {
+        let (sender, receiver) = mpsc::channel(32);
+        let addr: SocketAddr = format!("127.0.1.{host}:48300").parse().unwrap();
+        let player_id = server
+            .connection_manager
+            .register_client(sender, addr, server.instance_id)
+            .await
+            .expect("client registration succeeds");
+        clients.push((player_id, receiver));
+    }
+
+    let creates: Vec<_> = clients
+        .iter()
+        .map(|(player_id, _)| {
+            let server = Arc::clone(&server);
+            let player_id = *player_id;
+            tokio::spawn(async move { create_room_with_key(&server, &player_id, "retry-1").await })
+        })
+        .collect();
+    for create in creates {
+        create.await.expect("create task completes");
+    }
+
+    let mut room_ids = std::collections::HashSet::new();
+    for (_, receiver) in &mut clients {
+        match &*response(receiver).await {
+            ServerMessage::RoomJoined(payload) => {
+                room_ids.insert(payload.room_id);
+            }
+            other => panic!("expected RoomJoined, got {other:?}"),
+        }
+    }
+    assert_eq!(room_ids.len(), 1, "every create joined the same room");
+    assert_eq!(
+        server
+            .database
+            .get_game_room_count("retry-game")
+            .await
+            .unwrap(),
+        1
+    );
+
+    // A different key creates its own room
+    let (sender, mut receiver) = mpsc::channel(8);
+    let addr: SocketAddr = "127.0.2.1:48300".parse().unwrap();
+    let player_id = server
+        .connection_manager
+        .register_client(sender, addr, server.instance_id)
+        .await
+        .expect("client registration succeeds");
+    create_room_with_key(&server, &player_id, "retry-2").await;
+    match &*response(&mut receiver).await {
+        ServerMessage::RoomJoined(payload) => assert!(!room_ids.contains(&payload.room_id)),
+        other => panic!("expected RoomJoined, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn oversized_idempotency_keys_are_rejected() {
+    let server = create_test_server().await;
+    let (sender, mut receiver) = mpsc::channel(8);
+    let addr: SocketAddr = "127.0.0.1:48320".parse().unwrap();
+    let player_id = server
+        .connection_manager
+        .register_client(sender, addr, server.instance_id)
+        .await
+        .expect("client registration succeeds");
+
+    let key = "k".repeat(crate::protocol::validation::MAX_IDEMPOTENCY_KEY_LENGTH + 1);
+    create_room_with_key(&server, &player_id, &key).await;
+    match &*response(&mut receiver).await {
+        ServerMessage::RoomJoinFailed { error_code, .. } => {
+            assert_eq!(*error_code, Some(crate::protocol::ErrorCode::InvalidInput));
+        }
+        other => panic!("expected RoomJoinFailed, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn stale_players_time_out_before_leaving() {
+    let server = create_test_server().await;
+    let (sender, mut receiver) = mpsc::channel(16);
+    let addr: SocketAddr = "127.0.0.1:48340".parse().unwrap();
+    let host = server
+        .connection_manager
+        .register_client(sender, addr, server.instance_id)
+        .await
+        .expect("client registration succeeds");
+    create_room_as(&server, &host, "stale-game").await;
+    let room_id = match &*response(&mut receiver).await {
+        ServerMessage::RoomJoined(payload) => payload.room_id,
+        other => panic!("expected RoomJoined, got {other:?}"),
+    };
+
+    // A player seated by an instance that went away, never seen since
+    let orphan = PlayerId::new_v4();
+    let seated = server
+        .database
+        .add_player_to_room(
+            &room_id,
+            crate::protocol::PlayerInfo {
+                id: orphan,
+                name: "orphan".to_string(),
+                is_authority: false,
+                role: crate::protocol::PlayerRole::Member,
+                is_ready: false,
+                connected_at: chrono::Utc::now() - chrono::Duration::hours(1),
+                connection_info: None,
+                region_id: "default".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+    assert!(seated);
+
+    server.remove_stale_players().await;
+
+    let mut timed_out = false;
+    loop {
+        match &*response(&mut receiver).await {
+            ServerMessage::PlayerTimedOut { player_id } => {
+                assert_eq!(*player_id, orphan);
+                timed_out = true;
+            }
+            ServerMessage::PlayerLeft { player_id } => {
+                assert_eq!(*player_id, orphan);
+                break;
+            }
+            _ => {}
+        }
+    }
+    assert!(timed_out, "PlayerTimedOut arrives before PlayerLeft");
+
+    let room = server
+        .database
+        .get_room_by_id(&room_id)
+        .await
+        .unwrap()
+        .expect("room still exists");
+    assert_eq!(room.players.len(), 1);
+    assert!(room.players.contains_key(&host));
+    assert_eq!(
+        server
+            .metrics
+            .snapshot()
+            .await
+            .cleanup
+            .stale_players_removed,
+        1
+    );
+}
@@ -0,0 +1,160 @@
+use crate::database::RoomSettingsUpdate;
+use crate::protocol::{validation, ErrorCode, ErrorDetail, LobbyState, PlayerId, ServerMessage};
+use std::sync::Arc;
+
+use super::EnhancedGameServer;
+
+impl EnhancedGameServer {
+    /// Handle the room authority resizing the room or restricting invites.
+    ///
+    /// The room's players receive `RoomSettingsUpdated`. A lobby that is no
+    /// longer full returns to Waiting, and new slots are offered to queued
+    /// spectators and then the waiting list. A room shrunk to exactly its
+    /// player count enters the lobby.
+    pub async fn handle_update_room_settings(
+        &self,
+        requester_id: &PlayerId,
+        max_players: Option<u8>,
+        max_spectators: Option<u8>,
+        invites_authority_only: Option<bool>,
+    ) {
+        let Some(room) = self.requester_room(requester_id).await else {
+            return;
+        };
+        if room.authority_player != Some(*requester_id) {
+            let _ = self
+                .send_error_to_player(
+                    requester_id,
+                    ErrorDetail::new(
+                        ErrorCode::AuthorityDenied,
+                        "Only the room authority can change room settings",
+                    ),
+                )
+                .await;
+            return;
+        }
+        if max_players.is_none() && max_spectators.is_none() && invites_authority_only.is_none() {
+            let _ = self
+                .send_error_to_player(
+                    requester_id,
+                    ErrorDetail::new(ErrorCode::InvalidInput, "No room settings to update"),
+                )
+                .await;
+            return;
+        }
+        if let Some(max_players) = max_players {
+            if let Err(reason) =
+                validation::validate_max_players_with_config(max_players, &self.protocol_config)
+            {
+                let _ = self
+                    .send_error_to_player(
+                        requester_id,
+                        ErrorDetail::new(ErrorCode::InvalidMaxPlayers, reason)
+                            .with_details(self.max_players_range_details()),
+                    )
+                    .await;
+                return;
+            }
+        }
+
+        let (max_players, max_spectators, reopened_lobby_version) = match self
+            .database
+            .update_room_settings(&room.id, max_players, max_spectators)
+            .await
+        {
+            Ok(RoomSettingsUpdate::Applied {
+                max_players,
+                max_spectators,
+                reopened_lobby_version,
+            }) => (max_players, max_spectators, reopened_lobby_version),
+            Ok(RoomSettingsUpdate::BelowOccupancy(reason)) => {
+                let _ = self
+                    .send_error_to_player(
+                        requester_id,
+                        ErrorDetail::new(ErrorCode::CapacityBelowOccupancy, reason),
+                    )
+                    .await;
+                return;
+            }
+            Ok(RoomSettingsUpdate::RoomNotFound) => {
+                let _ = self
+                    .send_error_to_player(
+                        requester_id,
+                        ErrorDetail::new(ErrorCode::RoomNotFound, "Room not found"),
+                    )
+                    .await;
+                return;
+            }
+            Err(e) => {
+                tracing::error!(room_id = %room.id, error = %e, "Failed to update room settings");
+                let _ = self
+                    .send_error_to_player(
+                        requester_id,
+                        ErrorDetail::new(ErrorCode::StorageError, "Failed to update room settings"),
+                    )
+                    .await;
+                return;
+            }
+        };
+
+        let invites_authority_only = match invites_authority_only {
+            Some(authority_only) if authority_only != room.invites_authority_only => {
+                if let Err(e) = self
+                    .database
+                    .set_invites_authority_only(&room.id, authority_only)
+                    .await
+                {
+                    tracing::error!(room_id = %room.id, error = %e, "Failed to update invite setting");
+                    let _ = self
+                        .send_error_to_player(
+                            requester_id,
+                            ErrorDetail::new(
+                                ErrorCode::StorageError,
+                                "Failed to update room settings",
+                            ),
+                        )
+                        .await;
+                    return;
+                }
+                authority_only
+            }
+            _ => room.invites_authority_only,
+        };
+
+        tracing::info!(room_id = %room.id, requester = %requester_id, max_players, ?max_spectators, invites_authority_only, "Room settings updated");
+        let _ = self
+            .message_coordinator
+            .broadcast_to_room(
+                &room.id,
+                Arc::new(ServerMessage::RoomSettingsUpdated {
+                    max_players,
+                    max_spectators,
+                    invites_authority_only,
+                    updated_by: *requester_id,
+                }),
+            )
+            .await;
+        if let Some(lobby_version) = reopened_lobby_version {
+            let _ = self
+                .message_coordinator
+                .broadcast_to_room(
+                    &room.id,
+                    Arc::new(ServerMessage::LobbyStateChanged {
+                        lobby_state: LobbyState::Waiting,
+                        ready_players: Vec::new(),
+                        all_ready: false,
+                        lobby_version,
+                    }),
+                )
+                .await;
+        }
+
+        if max_players > room.max_players {
+            self.promote_queued_spectators(&room.id).await;
+            self.promote_from_waiting_list(&room.id).await;
+        }
+        if let Ok(Some(room)) = self.database.get_room_by_id(&room.id).await {
+            self.enter_lobby_if_full(&room).await;
+        }
+    }
+}
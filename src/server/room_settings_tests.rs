@@ -0,0 +1,236 @@
+use super::*;
+use crate::config::{
+    AuthMaintenanceConfig, CoordinationConfig, MetricsConfig, ProtocolConfig, RelayTypeConfig,
+    TransportSecurityConfig,
+};
+use crate::database::DatabaseConfig;
+use crate::protocol::{ErrorCode, ErrorDetail, LobbyState, Room, ServerMessage};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::time::{timeout, Duration};
+
+const GAME: &str = "settings-game";
+const ROOM: &str = "SIZE01";
+
+async fn create_test_server() -> Arc<EnhancedGameServer> {
+    EnhancedGameServer::new(
+        ServerConfig::default(),
+        ProtocolConfig::default(),
+        RelayTypeConfig::default(),
+        DatabaseConfig::InMemory,
+        MetricsConfig::default(),
+        AuthMaintenanceConfig::default(),
+        CoordinationConfig::default(),
+        TransportSecurityConfig::default(),
+        Vec::new(),
+    )
+    .await
+    .expect("failed to construct test server")
+}
+
+async fn register(server: &EnhancedGameServer, addr: &str) -> (PlayerId, mpsc::Receiver<Outbound>) {
+    let (sender, receiver) = mpsc::channel(32);
+    let addr: SocketAddr = addr.parse().unwrap();
+    let player_id = server
+        .register_client(sender, addr)
+        .await
+        .expect("client registration succeeds");
+    (player_id, receiver)
+}
+
+async fn join(server: &EnhancedGameServer, player_id: &PlayerId, name: &str, max_players: u8) {
+    server
+        .handle_join_room(
+            player_id,
+            GAME.to_string(),
+            Some(ROOM.to_string()),
+            name.to_string(),
+            Some(max_players),
+            Some(true),
+            None,
+            None,
+            None,
+        )
+        .await;
+}
+
+async fn next_message(receiver: &mut mpsc::Receiver<Outbound>) -> Outbound {
+    timeout(Duration::from_secs(1), receiver.recv())
+        .await
+        .expect("message arrives in time")
+        .expect("channel still open")
+}
+
+fn drain(receiver: &mut mpsc::Receiver<Outbound>) {
+    while receiver.try_recv().is_ok() {}
+}
+
+async fn stored_room(server: &EnhancedGameServer) -> Room {
+    server
+        .database()
+        .get_room(GAME, ROOM)
+        .await
+        .expect("room lookup succeeds")
+        .expect("room exists")
+}
+
+async fn expect_error(receiver: &mut mpsc::Receiver<Outbound>, expected: ErrorCode) {
+    let message = next_message(receiver).await;
+    assert!(
+        matches!(&*message, ServerMessage::Error(ErrorDetail { code, .. }) if *code == expected),
+        "expected {expected:?}, got {message:?}"
+    );
+}
+
+#[tokio::test]
+async fn growing_a_full_lobby_reopens_it_and_clears_readiness() {
+    let server = create_test_server().await;
+    let (host, mut host_rx) = register(&server, "127.0.0.1:48400").await;
+    let (guest, mut guest_rx) = register(&server, "127.0.0.2:48401").await;
+    join(&server, &host, "Host", 2).await;
+    join(&server, &guest, "Guest", 2).await;
+    server.handle_player_ready(&guest).await;
+    assert_eq!(stored_room(&server).await.lobby_state, LobbyState::Lobby);
+    drain(&mut host_rx);
+    drain(&mut guest_rx);
+
+    server
+        .handle_update_room_settings(&host, Some(3), Some(5), None)
+        .await;
+
+    for receiver in [&mut host_rx, &mut guest_rx] {
+        let update = next_message(receiver).await;
+        assert!(
+            matches!(
+                &*update,
+                ServerMessage::RoomSettingsUpdated {
+                    max_players: 3,
+                    max_spectators: Some(5),
+                    invites_authority_only: false,
+                    updated_by,
+                } if *updated_by == host
+            ),
+            "expected RoomSettingsUpdated, got {update:?}"
+        );
+        let reopened = next_message(receiver).await;
+        assert!(
+            matches!(
+                &*reopened,
+                ServerMessage::LobbyStateChanged {
+                    lobby_state: LobbyState::Waiting,
+                    ..
+                }
+            ),
+            "expected LobbyStateChanged, got {reopened:?}"
+        );
+    }
+    let room = stored_room(&server).await;
+    assert_eq!(room.max_players, 3);
+    assert_eq!(room.max_spectators, Some(5));
+    assert_eq!(room.lobby_state, LobbyState::Waiting);
+    assert!(room.ready_players.is_empty());
+    assert!(room.players.values().all(|player| !player.is_ready));
+
+    // The new slot fills the room again, which enters the lobby as usual
+    let (third, _third_rx) = register(&server, "127.0.0.3:48402").await;
+    join(&server, &third, "Third", 3).await;
+    assert_eq!(stored_room(&server).await.lobby_state, LobbyState::Lobby);
+}
+
+#[tokio::test]
+async fn shrinking_to_the_player_count_enters_the_lobby_but_not_below() {
+    let server = create_test_server().await;
+    let (host, mut host_rx) = register(&server, "127.0.0.1:48410").await;
+    let (guest, _guest_rx) = register(&server, "127.0.0.2:48411").await;
+    join(&server, &host, "Host", 4).await;
+    join(&server, &guest, "Guest", 4).await;
+    drain(&mut host_rx);
+
+    server
+        .handle_update_room_settings(&host, Some(1), None, None)
+        .await;
+    expect_error(&mut host_rx, ErrorCode::CapacityBelowOccupancy).await;
+    assert_eq!(stored_room(&server).await.max_players, 4);
+
+    server
+        .handle_update_room_settings(&host, Some(2), None, None)
+        .await;
+    let update = next_message(&mut host_rx).await;
+    assert!(
+        matches!(
+            &*update,
+            ServerMessage::RoomSettingsUpdated {
+                max_players: 2,
+                max_spectators: None,
+                ..
+            }
+        ),
+        "expected RoomSettingsUpdated, got {update:?}"
+    );
+    let lobby = next_message(&mut host_rx).await;
+    assert!(
+        matches!(
+            &*lobby,
+            ServerMessage::LobbyStateChanged {
+                lobby_state: LobbyState::Lobby,
+                ..
+            }
+        ),
+        "expected LobbyStateChanged, got {lobby:?}"
+    );
+    assert_eq!(stored_room(&server).await.lobby_state, LobbyState::Lobby);
+}
+
+#[tokio::test]
+async fn invalid_or_unauthorized_updates_are_rejected() {
+    let server = create_test_server().await;
+    let (host, mut host_rx) = register(&server, "127.0.0.1:48420").await;
+    let (guest, mut guest_rx) = register(&server, "127.0.0.2:48421").await;
+    let (watcher, _watcher_rx) = register(&server, "127.0.0.3:48422").await;
+    join(&server, &host, "Host", 4).await;
+    join(&server, &guest, "Guest", 4).await;
+    server
+        .handle_join_as_spectator(
+            &watcher,
+            GAME.to_string(),
+            ROOM.to_string(),
+            "Watcher".to_string(),
+        )
+        .await;
+    drain(&mut host_rx);
+    drain(&mut guest_rx);
+
+    server
+        .handle_update_room_settings(&guest, Some(6), None, None)
+        .await;
+    expect_error(&mut guest_rx, ErrorCode::AuthorityDenied).await;
+
+    server
+        .handle_update_room_settings(&host, None, None, None)
+        .await;
+    expect_error(&mut host_rx, ErrorCode::InvalidInput).await;
+
+    server
+        .handle_update_room_settings(&host, Some(0), None, None)
+        .await;
+    expect_error(&mut host_rx, ErrorCode::InvalidMaxPlayers).await;
+
+    let over_limit = ProtocolConfig::default().max_players_limit + 1;
+    server
+        .handle_update_room_settings(&host, Some(over_limit), None, None)
+        .await;
+    expect_error(&mut host_rx, ErrorCode::InvalidMaxPlayers).await;
+
+    // Capacity is checked as a whole: the valid player count is not applied either
+    server
+        .handle_update_room_settings(&host, Some(6), Some(0), None)
+        .await;
+    expect_error(&mut host_rx, ErrorCode::CapacityBelowOccupancy).await;
+
+    let room = stored_room(&server).await;
+    assert_eq!(room.max_players, 4);
+    assert_eq!(room.max_spectators, None);
+    assert!(host_rx.try_recv().is_err());
+    assert!(guest_rx.try_recv().is_err());
+}
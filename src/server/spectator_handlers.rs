@@ -1,5 +1,15 @@
 use super::EnhancedGameServer;
-use crate::protocol::PlayerId;
+use crate::join_interceptor::JoinDecision;
+use crate::protocol::validation;
+use crate::protocol::{
+    ErrorCode, ErrorDetail, ErrorDetails, PlayerId, PlayerInfo, PlayerRole, Room, RoomId,
+    ServerMessage, SpectatorInfo, SpectatorStateChangeReason,
+};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// SDK capability required to join rooms as a spectator.
+pub const SPECTATOR_MODE_CAPABILITY: &str = "spectator-mode";
 
 impl EnhancedGameServer {
     /// Handle joining a room as spectator, surfacing validation errors back to the client.
@@ -10,13 +20,55 @@ impl EnhancedGameServer {
         room_code: String,
         spectator_name: String,
     ) {
-        if let Err(err) = self
+        if !self.client_has_capability(player_id, SPECTATOR_MODE_CAPABILITY) {
+            let _ = self
+                .send_error_to_player(
+                    player_id,
+                    ErrorDetail::new(
+                        ErrorCode::SpectatorNotAllowed,
+                        "Spectator mode is not supported by this SDK version",
+                    ),
+                )
+                .await;
+            return;
+        }
+
+        let admitted = match self
             .spectator_service
-            .join(player_id, game_name, room_code, spectator_name)
+            .validate_join(
+                player_id,
+                &game_name,
+                &room_code,
+                &spectator_name,
+                self.connection_manager.client_ip(player_id),
+            )
             .await
         {
+            Ok(room) => {
+                if let JoinDecision::Deny { code, message } = self
+                    .intercept_join(player_id, &game_name, &room_code, &spectator_name, true)
+                    .await
+                {
+                    let _ = self
+                        .send_error_to_player(player_id, ErrorDetail::new(code, message))
+                        .await;
+                    return;
+                }
+                self.spectator_service
+                    .join_validated(
+                        player_id,
+                        room,
+                        spectator_name,
+                        SpectatorStateChangeReason::Joined,
+                    )
+                    .await
+            }
+            Err(err) => Err(err),
+        };
+
+        if let Err(err) = admitted {
             let _ = self
-                .send_error_to_player(player_id, err.message, err.code)
+                .send_error_to_player(player_id, ErrorDetail::new(err.code, err.message))
                 .await;
         }
     }
@@ -27,9 +79,254 @@ impl EnhancedGameServer {
             Ok(()) => tracing::info!(%player_id, "Spectator left room"),
             Err(err) => {
                 let _ = self
-                    .send_error_to_player(player_id, err.message, err.code)
+                    .send_error_to_player(player_id, ErrorDetail::new(err.code, err.message))
+                    .await;
+            }
+        }
+    }
+
+    /// Handle a chat line from a spectator.
+    ///
+    /// The sender is always the connection's own spectator identity, so players
+    /// (who are not in the spectator map) cannot send or spoof spectator chat.
+    pub async fn handle_spectator_chat(&self, player_id: &PlayerId, text: String) {
+        if let Err(reason) = validation::validate_spectator_chat_text(&text, &self.protocol_config)
+        {
+            let details = validation::text_field_details(
+                "text",
+                &text,
+                self.protocol_config.max_spectator_chat_length,
+            );
+            let _ = self
+                .send_error_to_player(
+                    player_id,
+                    ErrorDetail::new(ErrorCode::InvalidInput, reason).with_details(details),
+                )
+                .await;
+            return;
+        }
+
+        let room = match self.spectator_service.chat_room(player_id).await {
+            Ok(room) => room,
+            Err(err) => {
+                let _ = self
+                    .send_error_to_player(player_id, ErrorDetail::new(err.code, err.message))
                     .await;
+                return;
             }
+        };
+
+        if let Err(err) = self.rate_limiter.check_spectator_chat(player_id) {
+            let _ = self
+                .send_error_to_player(
+                    player_id,
+                    ErrorDetail::new(ErrorCode::RateLimitExceeded, err.to_string())
+                        .with_retry_after(err.retry_after())
+                        .with_details(err.details()),
+                )
+                .await;
+            return;
+        }
+
+        self.spectator_service
+            .relay_chat(&room, player_id, text)
+            .await;
+    }
+
+    /// Handle a spectator asking to be seated as a player.
+    ///
+    /// The spectator is queued (bounded by `max_promotion_queue_size`) and seated
+    /// right away if the room has a free slot.
+    pub async fn handle_request_promotion(&self, player_id: &PlayerId) {
+        let max_queue_size = self.config().max_promotion_queue_size;
+        if max_queue_size == 0 {
+            let _ = self
+                .send_error_to_player(
+                    player_id,
+                    ErrorDetail::new(
+                        ErrorCode::PromotionQueueFull,
+                        "Spectator promotion is disabled",
+                    ),
+                )
+                .await;
+            return;
         }
+
+        match self
+            .spectator_service
+            .request_promotion(player_id, max_queue_size)
+        {
+            Ok((room_id, position)) => {
+                let _ = self
+                    .message_coordinator
+                    .send_to_player(
+                        player_id,
+                        Arc::new(ServerMessage::PromotionQueuePosition { position }),
+                    )
+                    .await;
+                tracing::info!(%player_id, %room_id, position, "Spectator queued for promotion");
+                self.promote_queued_spectators(&room_id).await;
+            }
+            Err(err) => {
+                let _ = self
+                    .send_error_to_player(player_id, ErrorDetail::new(err.code, err.message))
+                    .await;
+            }
+        }
+    }
+
+    /// Seat queued spectators as players until the room is full or the queue is empty.
+    pub(super) async fn promote_queued_spectators(&self, room_id: &RoomId) {
+        let mut promoted_any = false;
+
+        loop {
+            let Ok(Some(room)) = self.database.get_room_by_id(room_id).await else {
+                return;
+            };
+            if !room.can_join() {
+                break;
+            }
+
+            let lock_key = format!("room_join:{}:{}", room.game_name, room.code);
+            let lock_handle = match self
+                .distributed_lock
+                .acquire(&lock_key, Duration::from_secs(10))
+                .await
+            {
+                Ok(handle) => handle,
+                Err(e) => {
+                    tracing::warn!(%room_id, error = %e, "Failed to lock room for spectator promotion");
+                    break;
+                }
+            };
+
+            let promoted = self.promote_next_spectator(room_id).await;
+            let _ = self.distributed_lock.release(&lock_handle).await;
+
+            match promoted {
+                Some(true) => promoted_any = true,
+                Some(false) => {}
+                None => break,
+            }
+        }
+
+        if promoted_any {
+            for (index, queued) in self
+                .spectator_service
+                .promotion_queue(room_id)
+                .iter()
+                .enumerate()
+            {
+                let _ = self
+                    .message_coordinator
+                    .send_to_player(
+                        queued,
+                        Arc::new(ServerMessage::PromotionQueuePosition {
+                            position: index + 1,
+                        }),
+                    )
+                    .await;
+            }
+        }
+    }
+
+    /// Pop and seat the next queued spectator. Must be called with the room join lock held.
+    ///
+    /// Returns `None` when nothing more can be promoted, `Some(true)` when a spectator
+    /// was seated, and `Some(false)` when the spectator could not be seated.
+    async fn promote_next_spectator(&self, room_id: &RoomId) -> Option<bool> {
+        let mut room = match self.database.get_room_by_id(room_id).await {
+            Ok(Some(room)) if room.can_join() => room,
+            _ => return None,
+        };
+        let next_player = self.spectator_service.pop_promotion(room_id)?;
+        let Some(spectator) = room.spectators.get(&next_player).cloned() else {
+            return Some(false);
+        };
+
+        if let Err(reason) =
+            validation::validate_player_name_uniqueness(&spectator.name, &room.players)
+        {
+            let suggested = validation::suffix_duplicate_player_name(
+                &spectator.name,
+                &room.players,
+                self.protocol_config.max_player_name_length,
+            );
+            let _ = self
+                .send_error_to_player(
+                    &next_player,
+                    ErrorDetail::new(ErrorCode::InvalidPlayerName, reason)
+                        .with_details(ErrorDetails::NameTaken { suggested }),
+                )
+                .await;
+            return Some(false);
+        }
+
+        match self.seat_spectator(&mut room, spectator).await {
+            Ok(true) => Some(true),
+            Ok(false) => {
+                self.send_join_failed_with_details(
+                    &next_player,
+                    "Room is full".to_string(),
+                    ErrorCode::RoomFull,
+                    Some(ErrorDetails::RoomFull {
+                        max_players: room.max_players,
+                    }),
+                )
+                .await;
+                None
+            }
+            Err(e) => {
+                tracing::warn!(%room_id, player_id = %next_player, error = %e, "Failed to seat promoted spectator");
+                None
+            }
+        }
+    }
+
+    /// Move a spectator of `room` into a player slot and announce the join.
+    /// Must be called with the room join lock held.
+    ///
+    /// Returns `Ok(false)` if the room filled up first; the spectator has
+    /// already left spectator mode by then.
+    pub(super) async fn seat_spectator(
+        &self,
+        room: &mut Room,
+        spectator: SpectatorInfo,
+    ) -> anyhow::Result<bool> {
+        self.spectator_service
+            .detach(&spectator.id, SpectatorStateChangeReason::Promoted)
+            .await;
+
+        let player_info = PlayerInfo {
+            id: spectator.id,
+            name: spectator.name.clone(),
+            is_authority: false,
+            role: PlayerRole::Member,
+            is_ready: false,
+            connected_at: chrono::Utc::now(),
+            connection_info: None,
+            region_id: room.region_id.clone(),
+        };
+        let Some(turn) = self
+            .seat_player(&room.id, player_info.clone(), None)
+            .await?
+        else {
+            return Ok(false);
+        };
+
+        self.metrics.increment_rooms_joined();
+        self.metrics.increment_players_joined();
+        room.players.insert(spectator.id, player_info);
+        room.spectators.remove(&spectator.id);
+        self.announce_room_join(&spectator.id, room, spectator.name, turn)
+            .await;
+
+        tracing::info!(
+            player_id = %spectator.id,
+            room_id = %room.id,
+            room_code = %room.code,
+            "Promoted spectator to player"
+        );
+        Ok(true)
     }
 }
@@ -0,0 +1,397 @@
+use super::*;
+use crate::config::{
+    AuthMaintenanceConfig, CoordinationConfig, MetricsConfig, ProtocolConfig, RelayTypeConfig,
+    TransportSecurityConfig,
+};
+use crate::database::DatabaseConfig;
+use crate::protocol::{ErrorCode, ErrorDetail, ServerMessage, SpectatorChatMode};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::time::{timeout, Duration};
+
+async fn create_test_server(max_promotion_queue_size: usize) -> Arc<EnhancedGameServer> {
+    EnhancedGameServer::new(
+        ServerConfig {
+            max_promotion_queue_size,
+            ..ServerConfig::default()
+        },
+        ProtocolConfig::default(),
+        RelayTypeConfig::default(),
+        DatabaseConfig::InMemory,
+        MetricsConfig::default(),
+        AuthMaintenanceConfig::default(),
+        CoordinationConfig::default(),
+        TransportSecurityConfig::default(),
+        Vec::new(),
+    )
+    .await
+    .expect("failed to construct test server")
+}
+
+async fn register(server: &EnhancedGameServer, port: u16) -> (PlayerId, mpsc::Receiver<Outbound>) {
+    let (sender, receiver) = mpsc::channel(32);
+    let addr: SocketAddr = format!("127.0.0.{}:{port}", port % 250 + 1)
+        .parse()
+        .unwrap();
+    let player_id = server
+        .connection_manager
+        .register_client(sender, addr, server.instance_id)
+        .await
+        .expect("client registration succeeds");
+    (player_id, receiver)
+}
+
+async fn join(server: &EnhancedGameServer, player_id: &PlayerId, name: &str) {
+    server
+        .handle_join_room(
+            player_id,
+            "promo-game".to_string(),
+            Some("PROMO1".to_string()),
+            name.to_string(),
+            Some(2),
+            Some(true),
+            None,
+            None,
+            None,
+        )
+        .await;
+}
+
+async fn spectate(server: &EnhancedGameServer, player_id: &PlayerId, name: &str) {
+    server
+        .handle_join_as_spectator(
+            player_id,
+            "promo-game".to_string(),
+            "PROMO1".to_string(),
+            name.to_string(),
+        )
+        .await;
+}
+
+async fn next_message(receiver: &mut mpsc::Receiver<Outbound>) -> Outbound {
+    timeout(Duration::from_secs(1), receiver.recv())
+        .await
+        .expect("message arrives in time")
+        .expect("channel still open")
+}
+
+fn drain(receiver: &mut mpsc::Receiver<Outbound>) {
+    while receiver.try_recv().is_ok() {}
+}
+
+fn assert_queue_position(message: &ServerMessage, expected: usize) {
+    assert!(
+        matches!(message, ServerMessage::PromotionQueuePosition { position } if *position == expected),
+        "expected PromotionQueuePosition {expected}, got {message:?}"
+    );
+}
+
+#[tokio::test]
+async fn promotion_queue_fills_to_cap_and_frees_space_as_promotions_complete() {
+    let server = create_test_server(2).await;
+    let (host, _host_rx) = register(&server, 49100).await;
+    let (guest, _guest_rx) = register(&server, 49101).await;
+    join(&server, &host, "Host").await;
+    join(&server, &guest, "Guest").await;
+
+    let mut spectators = Vec::new();
+    for (index, port) in (49110..49113).enumerate() {
+        let (spectator, mut rx) = register(&server, port).await;
+        spectate(&server, &spectator, &format!("Watcher{index}")).await;
+        drain(&mut rx);
+        spectators.push((spectator, rx));
+    }
+
+    for (expected, (spectator, rx)) in spectators.iter_mut().take(2).enumerate() {
+        server.handle_request_promotion(spectator).await;
+        assert_queue_position(&*next_message(rx).await, expected + 1);
+    }
+
+    let (third, third_rx) = &mut spectators[2];
+    server.handle_request_promotion(third).await;
+    let rejected = next_message(third_rx).await;
+    assert!(
+        matches!(
+            *rejected,
+            ServerMessage::Error(ErrorDetail {
+                code: ErrorCode::PromotionQueueFull,
+                ..
+            })
+        ),
+        "expected PromotionQueueFull, got {rejected:?}"
+    );
+
+    // A player leaving seats the head of the queue and frees a queue slot
+    server.leave_room(&guest).await;
+    let (first, first_rx) = &mut spectators[0];
+    let left = next_message(first_rx).await;
+    assert!(
+        matches!(
+            *left,
+            ServerMessage::SpectatorLeft {
+                reason: Some(SpectatorStateChangeReason::Promoted),
+                ..
+            }
+        ),
+        "expected SpectatorLeft with promoted reason, got {left:?}"
+    );
+    let joined = next_message(first_rx).await;
+    assert!(
+        matches!(*joined, ServerMessage::RoomJoined(_)),
+        "expected RoomJoined after promotion, got {joined:?}"
+    );
+    let room_id = server
+        .get_client_room(first)
+        .await
+        .expect("promoted spectator is assigned to the room");
+    let room = server
+        .database
+        .get_room_by_id(&room_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(room.players.contains_key(first));
+    assert!(!room.spectators.contains_key(first));
+
+    let (second, second_rx) = &mut spectators[1];
+    drain(second_rx);
+    assert_eq!(
+        server.spectator_service.promotion_queue(&room_id),
+        vec![*second]
+    );
+
+    let (third, third_rx) = &mut spectators[2];
+    server.handle_request_promotion(third).await;
+    assert_queue_position(&*next_message(third_rx).await, 2);
+}
+
+#[tokio::test]
+async fn promotion_request_is_rejected_when_disabled_or_not_spectating() {
+    let server = create_test_server(0).await;
+    let (host, _host_rx) = register(&server, 49200).await;
+    join(&server, &host, "Host").await;
+
+    let (spectator, mut spectator_rx) = register(&server, 49201).await;
+    spectate(&server, &spectator, "Watcher").await;
+    drain(&mut spectator_rx);
+    server.handle_request_promotion(&spectator).await;
+    let disabled = next_message(&mut spectator_rx).await;
+    assert!(
+        matches!(
+            *disabled,
+            ServerMessage::Error(ErrorDetail {
+                code: ErrorCode::PromotionQueueFull,
+                ..
+            })
+        ),
+        "expected PromotionQueueFull, got {disabled:?}"
+    );
+
+    let server = create_test_server(2).await;
+    let (outsider, mut outsider_rx) = register(&server, 49202).await;
+    server.handle_request_promotion(&outsider).await;
+    let not_spectating = next_message(&mut outsider_rx).await;
+    assert!(
+        matches!(
+            *not_spectating,
+            ServerMessage::Error(ErrorDetail {
+                code: ErrorCode::NotASpectator,
+                ..
+            })
+        ),
+        "expected NotASpectator, got {not_spectating:?}"
+    );
+}
+
+async fn create_chat_room(server: &EnhancedGameServer, host: &PlayerId, mode: SpectatorChatMode) {
+    server
+        .handle_join_room(
+            host,
+            "chat-game".to_string(),
+            Some("CHAT01".to_string()),
+            "Host".to_string(),
+            Some(4),
+            Some(true),
+            None,
+            None,
+            Some(mode),
+        )
+        .await;
+}
+
+async fn spectate_chat_room(server: &EnhancedGameServer, player_id: &PlayerId, name: &str) {
+    server
+        .handle_join_as_spectator(
+            player_id,
+            "chat-game".to_string(),
+            "CHAT01".to_string(),
+            name.to_string(),
+        )
+        .await;
+}
+
+fn chat_relays(receiver: &mut mpsc::Receiver<Outbound>) -> Vec<(PlayerId, String, String)> {
+    std::iter::from_fn(|| receiver.try_recv().ok())
+        .filter_map(|message| match &*message {
+            ServerMessage::SpectatorChatRelay {
+                spectator_id,
+                name,
+                text,
+            } => Some((*spectator_id, name.clone(), text.clone())),
+            _ => None,
+        })
+        .collect()
+}
+
+fn assert_error_code(message: &ServerMessage, expected: ErrorCode) {
+    assert!(
+        matches!(message, ServerMessage::Error(ErrorDetail { code, .. }) if *code == expected),
+        "expected {expected:?} error, got {message:?}"
+    );
+}
+
+#[tokio::test]
+async fn spectators_only_chat_reaches_spectators_but_not_players() {
+    let server = create_test_server(0).await;
+    let (host, mut host_rx) = register(&server, 49200).await;
+    let (alice, mut alice_rx) = register(&server, 49201).await;
+    let (bob, mut bob_rx) = register(&server, 49202).await;
+    create_chat_room(&server, &host, SpectatorChatMode::SpectatorsOnly).await;
+    spectate_chat_room(&server, &alice, "Alice").await;
+    spectate_chat_room(&server, &bob, "Bob").await;
+    drain(&mut host_rx);
+    drain(&mut alice_rx);
+    drain(&mut bob_rx);
+
+    server
+        .handle_spectator_chat(&alice, "nice play".to_string())
+        .await;
+    tokio::time::sleep(Duration::from_millis(20)).await;
+
+    let expected = vec![(alice, "Alice".to_string(), "nice play".to_string())];
+    assert_eq!(chat_relays(&mut alice_rx), expected);
+    assert_eq!(chat_relays(&mut bob_rx), expected);
+    assert!(chat_relays(&mut host_rx).is_empty());
+}
+
+#[tokio::test]
+async fn everyone_chat_reaches_players_and_players_cannot_send_it() {
+    let server = create_test_server(0).await;
+    let (host, mut host_rx) = register(&server, 49210).await;
+    let (alice, mut alice_rx) = register(&server, 49211).await;
+    create_chat_room(&server, &host, SpectatorChatMode::Everyone).await;
+    spectate_chat_room(&server, &alice, "Alice").await;
+    drain(&mut host_rx);
+    drain(&mut alice_rx);
+
+    server.handle_spectator_chat(&alice, "gg".to_string()).await;
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert_eq!(
+        chat_relays(&mut host_rx),
+        vec![(alice, "Alice".to_string(), "gg".to_string())]
+    );
+    drain(&mut alice_rx);
+
+    // A player cannot speak as a spectator
+    server
+        .handle_spectator_chat(&host, "spoof".to_string())
+        .await;
+    assert_error_code(&*next_message(&mut host_rx).await, ErrorCode::NotASpectator);
+    assert!(chat_relays(&mut alice_rx).is_empty());
+
+    // Spectators still cannot send game data to the players
+    server
+        .handle_game_data(&alice, serde_json::json!({ "move": 1 }))
+        .await;
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert!(!std::iter::from_fn(|| host_rx.try_recv().ok())
+        .any(|message| matches!(*message, ServerMessage::GameData { .. })));
+}
+
+#[tokio::test]
+async fn spectator_chat_is_validated_and_rate_limited() {
+    let server = create_test_server(0).await;
+    let (host, _host_rx) = register(&server, 49220).await;
+    let (alice, mut alice_rx) = register(&server, 49221).await;
+    create_chat_room(&server, &host, SpectatorChatMode::SpectatorsOnly).await;
+    spectate_chat_room(&server, &alice, "Alice").await;
+    drain(&mut alice_rx);
+
+    let too_long = "x".repeat(server.protocol_config.max_spectator_chat_length + 1);
+    server.handle_spectator_chat(&alice, too_long).await;
+    assert_error_code(&*next_message(&mut alice_rx).await, ErrorCode::InvalidInput);
+
+    let burst = server
+        .config()
+        .rate_limit_config
+        .spectator_chat_bucket
+        .capacity;
+    for _ in 0..burst {
+        server.handle_spectator_chat(&alice, "hi".to_string()).await;
+    }
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert_eq!(chat_relays(&mut alice_rx).len(), burst as usize);
+
+    server.handle_spectator_chat(&alice, "hi".to_string()).await;
+    let limited = next_message(&mut alice_rx).await;
+    assert_error_code(&limited, ErrorCode::RateLimitExceeded);
+    let ServerMessage::Error(error) = &*limited else {
+        unreachable!()
+    };
+    assert!(
+        error.retry_after_ms.is_some_and(|ms| ms > 0),
+        "rate limit errors say when to retry: {error:?}"
+    );
+}
+
+#[tokio::test]
+async fn spectator_chat_is_disabled_by_default() {
+    let server = create_test_server(0).await;
+    let (host, _host_rx) = register(&server, 49230).await;
+    let (alice, mut alice_rx) = register(&server, 49231).await;
+    join(&server, &host, "Host").await;
+    spectate(&server, &alice, "Alice").await;
+    drain(&mut alice_rx);
+
+    server
+        .handle_spectator_chat(&alice, "hello".to_string())
+        .await;
+    assert_error_code(
+        &*next_message(&mut alice_rx).await,
+        ErrorCode::SpectatorChatDisabled,
+    );
+}
+
+#[tokio::test]
+async fn spectating_requires_the_sdk_spectator_capability() {
+    let server = create_test_server(0).await;
+    let (host, mut host_rx) = register(&server, 48300).await;
+    join(&server, &host, "host").await;
+    drain(&mut host_rx);
+
+    let sdk = &server.protocol_config.sdk_compatibility;
+    let mut without_spectating = sdk.evaluate(Some("unity"), Some("1.12.0")).unwrap();
+    without_spectating
+        .capabilities
+        .retain(|cap| cap != SPECTATOR_MODE_CAPABILITY);
+    let (old_sdk, mut old_rx) = register(&server, 48301).await;
+    server.set_client_sdk_report(&old_sdk, &without_spectating);
+    spectate(&server, &old_sdk, "old-sdk").await;
+    assert!(matches!(
+        &*next_message(&mut old_rx).await,
+        ServerMessage::Error(ErrorDetail {
+            code: ErrorCode::SpectatorNotAllowed,
+            ..
+        })
+    ));
+
+    // Clients that never identified their SDK get the `_default` capabilities
+    let (unidentified, mut unidentified_rx) = register(&server, 48302).await;
+    assert!(server.client_has_capability(&unidentified, SPECTATOR_MODE_CAPABILITY));
+    spectate(&server, &unidentified, "watcher").await;
+    assert!(!matches!(
+        &*next_message(&mut unidentified_rx).await,
+        ServerMessage::Error(_)
+    ));
+}
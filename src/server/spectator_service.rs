@@ -1,3 +1,5 @@
+use std::collections::VecDeque;
+use std::net::IpAddr;
 use std::sync::Arc;
 
 use dashmap::DashMap;
@@ -8,15 +10,14 @@ use crate::config::ProtocolConfig;
 use crate::coordination::MessageCoordinator;
 use crate::database::GameDatabase;
 use crate::protocol::{
-    validation, ErrorCode, PlayerId, PlayerInfo, RoomId, ServerMessage, SpectatorInfo,
-    SpectatorJoinedPayload, SpectatorStateChangeReason,
+    validation, ErrorCode, PlayerId, PlayerInfo, Room, RoomId, ServerMessage, SpectatorChatMode,
+    SpectatorInfo, SpectatorJoinedPayload, SpectatorStateChangeReason,
 };
 
-#[cfg(test)]
-use crate::protocol::Room;
-
 pub(crate) struct SpectatorService {
     spectator_rooms: DashMap<PlayerId, RoomId>,
+    /// Spectators waiting to be seated as players, per room (front = next promoted)
+    promotion_queues: DashMap<RoomId, VecDeque<PlayerId>>,
     database: Arc<dyn GameDatabase>,
     message_coordinator: Arc<dyn MessageCoordinator>,
     room_applications: Arc<DashMap<RoomId, Uuid>>,
@@ -26,11 +27,11 @@ pub(crate) struct SpectatorService {
 #[derive(Debug)]
 pub(crate) struct SpectatorError {
     pub message: String,
-    pub code: Option<ErrorCode>,
+    pub code: ErrorCode,
 }
 
 impl SpectatorError {
-    fn new(message: impl Into<String>, code: Option<ErrorCode>) -> Self {
+    fn new(message: impl Into<String>, code: ErrorCode) -> Self {
         Self {
             message: message.into(),
             code,
@@ -47,6 +48,7 @@ impl SpectatorService {
     ) -> Self {
         Self {
             spectator_rooms: DashMap::new(),
+            promotion_queues: DashMap::new(),
             database,
             message_coordinator,
             room_applications,
@@ -54,43 +56,93 @@ impl SpectatorService {
         }
     }
 
+    /// Validate and join in one step, without consulting the join interceptor.
+    #[cfg(test)]
     pub(crate) async fn join(
         &self,
         player_id: &PlayerId,
         game_name: String,
         room_code: String,
         spectator_name: String,
+        client_ip: Option<IpAddr>,
     ) -> Result<(), SpectatorError> {
+        let room = self
+            .validate_join(
+                player_id,
+                &game_name,
+                &room_code,
+                &spectator_name,
+                client_ip,
+            )
+            .await?;
+        self.join_validated(
+            player_id,
+            room,
+            spectator_name,
+            SpectatorStateChangeReason::Joined,
+        )
+        .await
+    }
+
+    /// Check that the connection may spectate the room, returning the room.
+    pub(crate) async fn validate_join(
+        &self,
+        player_id: &PlayerId,
+        game_name: &str,
+        room_code: &str,
+        spectator_name: &str,
+        client_ip: Option<IpAddr>,
+    ) -> Result<Room, SpectatorError> {
         if let Err(err) =
-            validation::validate_player_name_with_config(&spectator_name, &self.protocol_config)
+            validation::validate_player_name_with_config(spectator_name, &self.protocol_config)
         {
-            return Err(SpectatorError::new(err, Some(ErrorCode::InvalidPlayerName)));
+            let code = err.error_code(ErrorCode::InvalidPlayerName);
+            return Err(SpectatorError::new(err.to_string(), code));
         }
 
-        let room = match self.database.get_room(&game_name, &room_code).await {
+        let room = match self.database.get_room(game_name, room_code).await {
             Ok(Some(room)) => room,
             Ok(None) => {
                 return Err(SpectatorError::new(
                     "Room not found",
-                    Some(ErrorCode::RoomNotFound),
+                    ErrorCode::RoomNotFound,
                 ))
             }
             Err(err) => {
                 warn!("Failed to fetch room for spectator: {err}");
                 return Err(SpectatorError::new(
                     "Storage error",
-                    Some(ErrorCode::StorageError),
+                    ErrorCode::StorageError,
                 ));
             }
         };
 
+        if room.is_banned(player_id, client_ip) {
+            return Err(SpectatorError::new(
+                "You are banned from this room",
+                ErrorCode::Banned,
+            ));
+        }
+
         if !room.can_spectate() {
             return Err(SpectatorError::new(
                 "Spectator limit reached",
-                Some(ErrorCode::TooManySpectators),
+                ErrorCode::TooManySpectators,
             ));
         }
 
+        Ok(room)
+    }
+
+    /// Add the spectator to a room that passed [`Self::validate_join`].
+    /// `join_reason` is reported to the spectator and the room.
+    pub(crate) async fn join_validated(
+        &self,
+        player_id: &PlayerId,
+        room: Room,
+        spectator_name: String,
+        join_reason: SpectatorStateChangeReason,
+    ) -> Result<(), SpectatorError> {
         let spectator = SpectatorInfo {
             id: *player_id,
             name: spectator_name.clone(),
@@ -119,8 +171,6 @@ impl SpectatorService {
                     );
                 }
 
-                let join_reason = SpectatorStateChangeReason::Joined;
-
                 let _ = self
                     .message_coordinator
                     .send_to_player(
@@ -135,6 +185,7 @@ impl SpectatorService {
                                 current_spectators: spectator_snapshot.clone(),
                                 lobby_state: room.lobby_state.clone(),
                                 reason: Some(join_reason.clone()),
+                                spectator_chat_mode: room.spectator_chat_mode,
                             },
                         ))),
                     )
@@ -154,7 +205,7 @@ impl SpectatorService {
                 info!(
                     %player_id,
                     spectator_name,
-                    room_code,
+                    room_code = %room.code,
                     "Spectator joined room"
                 );
 
@@ -162,13 +213,13 @@ impl SpectatorService {
             }
             Ok(false) => Err(SpectatorError::new(
                 "Failed to join as spectator",
-                Some(ErrorCode::SpectatorJoinFailed),
+                ErrorCode::SpectatorJoinFailed,
             )),
             Err(err) => {
                 warn!("Storage error adding spectator: {err}");
                 Err(SpectatorError::new(
                     "Storage error",
-                    Some(ErrorCode::StorageError),
+                    ErrorCode::StorageError,
                 ))
             }
         }
@@ -183,7 +234,7 @@ impl SpectatorService {
         } else {
             Err(SpectatorError::new(
                 "You are not currently spectating a room",
-                Some(ErrorCode::NotASpectator),
+                ErrorCode::NotASpectator,
             ))
         }
     }
@@ -196,6 +247,7 @@ impl SpectatorService {
         let Some((_, room_id)) = self.spectator_rooms.remove(player_id) else {
             return false;
         };
+        self.cancel_promotion(&room_id, player_id);
 
         if let Err(err) = self
             .database
@@ -263,6 +315,152 @@ impl SpectatorService {
         true
     }
 
+    /// Forget every spectator of a room that is being torn down, returning their ids.
+    pub(crate) fn release_room(&self, room_id: &RoomId) -> Vec<PlayerId> {
+        let spectators: Vec<PlayerId> = self
+            .spectator_rooms
+            .iter()
+            .filter(|entry| entry.value() == room_id)
+            .map(|entry| *entry.key())
+            .collect();
+        for spectator_id in &spectators {
+            self.spectator_rooms
+                .remove_if(spectator_id, |_, current| current == room_id);
+        }
+        self.promotion_queues.remove(room_id);
+        spectators
+    }
+
+    /// Queue a spectator for a player slot in the room they are watching.
+    ///
+    /// Returns the room and the spectator's 1-based queue position. Spectators
+    /// already queued keep their place; new requests beyond `max_queue_size`
+    /// are rejected instead of queued.
+    pub(crate) fn request_promotion(
+        &self,
+        player_id: &PlayerId,
+        max_queue_size: usize,
+    ) -> Result<(RoomId, usize), SpectatorError> {
+        let Some(room_id) = self
+            .spectator_rooms
+            .get(player_id)
+            .map(|entry| *entry.value())
+        else {
+            return Err(SpectatorError::new(
+                "You are not currently spectating a room",
+                ErrorCode::NotASpectator,
+            ));
+        };
+
+        let mut queue = self.promotion_queues.entry(room_id).or_default();
+        if let Some(index) = queue.iter().position(|queued| queued == player_id) {
+            return Ok((room_id, index + 1));
+        }
+        if queue.len() >= max_queue_size {
+            return Err(SpectatorError::new(
+                format!("Promotion queue is full ({max_queue_size} spectators)"),
+                ErrorCode::PromotionQueueFull,
+            ));
+        }
+        queue.push_back(*player_id);
+        Ok((room_id, queue.len()))
+    }
+
+    /// Pop the next queued spectator that is still watching `room_id`.
+    pub(crate) fn pop_promotion(&self, room_id: &RoomId) -> Option<PlayerId> {
+        let mut queue = self.promotion_queues.get_mut(room_id)?;
+        while let Some(next) = queue.pop_front() {
+            let still_watching = self
+                .spectator_rooms
+                .get(&next)
+                .is_some_and(|entry| entry.value() == room_id);
+            if still_watching {
+                return Some(next);
+            }
+        }
+        drop(queue);
+        self.promotion_queues
+            .remove_if(room_id, |_, queue| queue.is_empty());
+        None
+    }
+
+    /// Spectators currently queued for promotion in `room_id`, in order.
+    pub(crate) fn promotion_queue(&self, room_id: &RoomId) -> Vec<PlayerId> {
+        self.promotion_queues
+            .get(room_id)
+            .map(|queue| queue.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// The room a spectator is watching, if its spectator chat mode lets them chat.
+    pub(crate) async fn chat_room(&self, player_id: &PlayerId) -> Result<Room, SpectatorError> {
+        let not_spectating = || {
+            SpectatorError::new(
+                "You are not currently spectating a room",
+                ErrorCode::NotASpectator,
+            )
+        };
+        let room_id = self
+            .spectator_rooms
+            .get(player_id)
+            .map(|entry| *entry.value())
+            .ok_or_else(not_spectating)?;
+
+        let room = match self.database.get_room_by_id(&room_id).await {
+            Ok(Some(room)) if room.spectators.contains_key(player_id) => room,
+            Ok(_) => return Err(not_spectating()),
+            Err(err) => {
+                warn!(%room_id, error = %err, "Failed to fetch room for spectator chat");
+                return Err(SpectatorError::new(
+                    "Storage error",
+                    ErrorCode::StorageError,
+                ));
+            }
+        };
+
+        if room.spectator_chat_mode == SpectatorChatMode::None {
+            return Err(SpectatorError::new(
+                "Spectator chat is disabled for this room",
+                ErrorCode::SpectatorChatDisabled,
+            ));
+        }
+        Ok(room)
+    }
+
+    /// Relay a chat line to the room's spectators, and to its players when the
+    /// room's mode is `everyone`.
+    pub(crate) async fn relay_chat(&self, room: &Room, spectator_id: &PlayerId, text: String) {
+        let Some(spectator) = room.spectators.get(spectator_id) else {
+            return;
+        };
+        let message = Arc::new(ServerMessage::SpectatorChatRelay {
+            spectator_id: *spectator_id,
+            name: spectator.name.clone(),
+            text,
+        });
+
+        for watcher in room.spectators.keys() {
+            let _ = self
+                .message_coordinator
+                .send_to_player(watcher, Arc::clone(&message))
+                .await;
+        }
+        if room.spectator_chat_mode == SpectatorChatMode::Everyone {
+            let _ = self
+                .message_coordinator
+                .broadcast_to_room(&room.id, message)
+                .await;
+        }
+    }
+
+    fn cancel_promotion(&self, room_id: &RoomId, player_id: &PlayerId) {
+        if let Some(mut queue) = self.promotion_queues.get_mut(room_id) {
+            queue.retain(|queued| queued != player_id);
+        }
+        self.promotion_queues
+            .remove_if(room_id, |_, queue| queue.is_empty());
+    }
+
     #[allow(dead_code)]
     fn room_app_id(&self, room_id: &RoomId) -> Option<Uuid> {
         self.room_applications
@@ -346,7 +544,7 @@ mod tests {
             &self,
             _player_id: PlayerId,
             _room_id: Option<RoomId>,
-            _sender: mpsc::Sender<Arc<ServerMessage>>,
+            _sender: mpsc::Sender<crate::broadcast::Outbound>,
         ) -> Result<()> {
             Ok(())
         }
@@ -417,6 +615,7 @@ mod tests {
                 room.game_name.clone(),
                 room.code.clone(),
                 "Spectator One".to_string(),
+                None,
             )
             .await
             .expect("spectator join succeeds");
@@ -469,6 +668,7 @@ mod tests {
                 room.game_name.clone(),
                 room.code.clone(),
                 "Spectator One".to_string(),
+                None,
             )
             .await
             .expect("spectator join succeeds");
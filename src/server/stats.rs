@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use super::EnhancedGameServer;
+use crate::protocol::{PlayerInfo, Room};
+
+/// Overall health numbers for one instance, served by `/v2/stats`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerStats {
+    pub uptime_secs: u64,
+    pub total_active_connections: usize,
+    pub total_rooms: usize,
+    pub total_players: usize,
+    pub rooms_by_game: HashMap<String, usize>,
+    pub players_by_game: HashMap<String, usize>,
+    /// Cleanup task ticks since startup
+    pub cleanup_runs: u64,
+    pub last_cleanup_at: Option<DateTime<Utc>>,
+    /// Rough size of room and player state; ignores heap data such as names and metadata
+    pub memory_estimate_bytes: usize,
+}
+
+/// How often, and when last, the cleanup task ran.
+#[derive(Default)]
+pub(super) struct CleanupRuns {
+    runs: AtomicU64,
+    last_run_at: std::sync::Mutex<Option<DateTime<Utc>>>,
+}
+
+impl CleanupRuns {
+    pub(super) fn record(&self) {
+        self.runs.fetch_add(1, Ordering::Relaxed);
+        *self
+            .last_run_at
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = Some(Utc::now());
+    }
+
+    fn runs(&self) -> u64 {
+        self.runs.load(Ordering::Relaxed)
+    }
+
+    fn last_run_at(&self) -> Option<DateTime<Utc>> {
+        *self
+            .last_run_at
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+impl EnhancedGameServer {
+    /// Connection, room and player totals plus cleanup activity for this instance.
+    pub async fn get_server_stats(&self) -> Result<ServerStats> {
+        let rooms_by_game = self.database.get_rooms_by_game().await?;
+        let players_by_game = self.database.get_players_by_game().await?;
+        let total_rooms: usize = rooms_by_game.values().sum();
+        let total_players: usize = players_by_game.values().sum();
+
+        Ok(ServerStats {
+            uptime_secs: self.metrics.build_info().uptime_seconds as u64,
+            total_active_connections: self.connection_manager.client_count(),
+            total_rooms,
+            total_players,
+            rooms_by_game,
+            players_by_game,
+            cleanup_runs: self.cleanup_runs.runs(),
+            last_cleanup_at: self.cleanup_runs.last_run_at(),
+            memory_estimate_bytes: total_rooms * std::mem::size_of::<Room>()
+                + total_players * std::mem::size_of::<PlayerInfo>(),
+        })
+    }
+}
@@ -0,0 +1,122 @@
+use super::*;
+use crate::config::{
+    AuthMaintenanceConfig, CoordinationConfig, MetricsConfig, ProtocolConfig, RelayTypeConfig,
+    TransportSecurityConfig,
+};
+use crate::database::DatabaseConfig;
+use crate::protocol::Room;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::time::{sleep, Duration};
+
+const MAX_PLAYERS: u8 = 3;
+
+async fn create_test_server(room_cleanup_interval: Duration) -> Arc<EnhancedGameServer> {
+    EnhancedGameServer::new(
+        ServerConfig {
+            room_cleanup_interval,
+            ..ServerConfig::default()
+        },
+        ProtocolConfig::default(),
+        RelayTypeConfig::default(),
+        DatabaseConfig::InMemory,
+        MetricsConfig::default(),
+        AuthMaintenanceConfig::default(),
+        CoordinationConfig::default(),
+        TransportSecurityConfig::default(),
+        Vec::new(),
+    )
+    .await
+    .expect("failed to construct test server")
+}
+
+async fn join(
+    server: &EnhancedGameServer,
+    port: u16,
+    game_name: &str,
+    room_code: Option<&str>,
+) -> mpsc::Receiver<Outbound> {
+    let (sender, receiver) = mpsc::channel(16);
+    let addr: SocketAddr = format!("127.0.0.1:{port}").parse().unwrap();
+    let player_id = server
+        .connection_manager
+        .register_client(sender, addr, server.instance_id)
+        .await
+        .expect("client registration succeeds");
+    server
+        .handle_join_room(
+            &player_id,
+            game_name.to_string(),
+            room_code.map(str::to_string),
+            format!("player-{port}"),
+            Some(MAX_PLAYERS),
+            Some(false),
+            None,
+            None,
+            None,
+        )
+        .await;
+    receiver
+}
+
+#[tokio::test]
+async fn stats_count_rooms_players_and_connections_per_game() {
+    let server = create_test_server(Duration::from_secs(60)).await;
+    let mut receivers = vec![
+        join(&server, 49001, "chess", Some("CHESS1")).await,
+        join(&server, 49002, "chess", Some("CHESS1")).await,
+        join(&server, 49003, "chess", Some("CHESS2")).await,
+        join(&server, 49004, "go", None).await,
+    ];
+    let (sender, receiver) = mpsc::channel(16);
+    server
+        .connection_manager
+        .register_client(
+            sender,
+            "127.0.0.1:49005".parse().unwrap(),
+            server.instance_id,
+        )
+        .await
+        .expect("client registration succeeds");
+    receivers.push(receiver);
+
+    let stats = server.get_server_stats().await.expect("stats");
+    assert_eq!(stats.total_active_connections, 5);
+    assert_eq!(stats.total_rooms, 3);
+    assert_eq!(stats.total_players, 4);
+    assert_eq!(stats.rooms_by_game.get("chess"), Some(&2));
+    assert_eq!(stats.rooms_by_game.get("go"), Some(&1));
+    assert_eq!(stats.players_by_game.get("chess"), Some(&3));
+    assert_eq!(stats.players_by_game.get("go"), Some(&1));
+    assert_eq!(
+        stats.total_rooms,
+        stats.rooms_by_game.values().sum::<usize>()
+    );
+    assert_eq!(
+        stats.total_players,
+        stats.players_by_game.values().sum::<usize>()
+    );
+    assert!(stats.total_players <= stats.total_rooms * usize::from(MAX_PLAYERS));
+    assert!(stats.memory_estimate_bytes >= stats.total_rooms * std::mem::size_of::<Room>());
+}
+
+#[tokio::test]
+async fn stats_track_cleanup_runs() {
+    let server = create_test_server(Duration::from_millis(10)).await;
+    let stats = server.get_server_stats().await.expect("stats");
+    assert_eq!(stats.total_rooms, 0);
+    assert_eq!(stats.total_players, 0);
+    assert_eq!(stats.memory_estimate_bytes, 0);
+    assert_eq!(stats.cleanup_runs, 0);
+    assert!(stats.last_cleanup_at.is_none());
+
+    let cleanup_server = Arc::clone(&server);
+    let cleanup = tokio::spawn(async move { cleanup_server.cleanup_task().await });
+    sleep(Duration::from_millis(50)).await;
+    cleanup.abort();
+    let _ = cleanup.await;
+
+    let stats = server.get_server_stats().await.expect("stats");
+    assert!(stats.cleanup_runs > 0);
+    assert!(stats.last_cleanup_at.is_some());
+}
@@ -0,0 +1,418 @@
+use super::EnhancedGameServer;
+use crate::protocol::validation;
+use crate::protocol::{
+    ErrorCode, ErrorDetails, PlayerId, PlayerInfo, PlayerRole, RoomId, ServerMessage,
+};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A player queued for a slot in a full room.
+#[derive(Debug, Clone)]
+pub(super) struct WaitingListEntry {
+    pub(super) room_id: RoomId,
+    pub(super) player_name: String,
+}
+
+impl EnhancedGameServer {
+    /// Handle an explicit request to queue for a full room.
+    ///
+    /// If the room still has a free slot the player simply joins it.
+    pub async fn handle_join_waiting_list(
+        &self,
+        player_id: &PlayerId,
+        game_name: String,
+        room_code: String,
+        player_name: String,
+    ) {
+        if let Err(reason) =
+            validation::validate_game_name_with_config(&game_name, &self.protocol_config)
+        {
+            let details = validation::text_field_details(
+                "game_name",
+                &game_name,
+                self.protocol_config.max_game_name_length,
+            );
+            self.send_join_failed_with_details(
+                player_id,
+                reason,
+                ErrorCode::InvalidGameName,
+                Some(details),
+            )
+            .await;
+            return;
+        }
+        if let Err(reason) =
+            validation::validate_player_name_with_config(&player_name, &self.protocol_config)
+        {
+            let code = reason.error_code(ErrorCode::InvalidInput);
+            let details = reason.details(&player_name, &self.protocol_config);
+            self.send_join_failed_with_details(player_id, reason.to_string(), code, Some(details))
+                .await;
+            return;
+        }
+        if let Err(reason) = validation::validate_room_code_for_game(
+            &room_code,
+            &game_name,
+            &self.protocol_config,
+            &self.config().game_overrides,
+        ) {
+            let length = validation::room_code_length_for_game(
+                &game_name,
+                &self.protocol_config,
+                &self.config().game_overrides,
+            );
+            let details = validation::room_code_details(&room_code, length);
+            self.send_join_failed_with_details(
+                player_id,
+                reason,
+                ErrorCode::InvalidRoomCode,
+                Some(details),
+            )
+            .await;
+            return;
+        }
+        if self.get_client_room(player_id).await.is_some() {
+            self.send_join_failed(
+                player_id,
+                "Already in a room".to_string(),
+                ErrorCode::AlreadyInRoom,
+            )
+            .await;
+            return;
+        }
+
+        let room_code = room_code.to_uppercase();
+        let room = match self.database.get_room(&game_name, &room_code).await {
+            Ok(Some(room)) => room,
+            Ok(None) => {
+                self.send_join_failed(
+                    player_id,
+                    "Room not found".to_string(),
+                    ErrorCode::RoomNotFound,
+                )
+                .await;
+                return;
+            }
+            Err(e) => {
+                tracing::error!(%player_id, error = %e, "Failed to look up room for waiting list");
+                self.send_join_failed(player_id, e.to_string(), ErrorCode::StorageError)
+                    .await;
+                return;
+            }
+        };
+
+        if room.is_banned(player_id, self.connection_manager.client_ip(player_id)) {
+            self.send_join_failed(
+                player_id,
+                "You are banned from this room".to_string(),
+                ErrorCode::Banned,
+            )
+            .await;
+            return;
+        }
+
+        if room.can_join() {
+            self.handle_join_room(
+                player_id,
+                game_name,
+                Some(room_code),
+                player_name,
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
+            .await;
+            return;
+        }
+
+        self.enqueue_waiting_player(player_id, room.id, player_name)
+            .await;
+    }
+
+    /// Queue a player on a full room's waiting list and report their position.
+    pub(super) async fn enqueue_waiting_player(
+        &self,
+        player_id: &PlayerId,
+        room_id: RoomId,
+        player_name: String,
+    ) {
+        let max_size = self.config().max_waiting_list_size;
+        if max_size == 0 {
+            self.send_join_failed(
+                player_id,
+                "Room is full and waiting lists are disabled".to_string(),
+                ErrorCode::RoomFull,
+            )
+            .await;
+            return;
+        }
+
+        // A player can only wait for one room at a time
+        let previous_room = self
+            .waiting_players
+            .get(player_id)
+            .map(|entry| entry.room_id);
+        if previous_room.is_some_and(|previous| previous != room_id) {
+            self.cancel_waiting_list(player_id).await;
+        }
+
+        match self
+            .database
+            .add_to_waiting_list(&room_id, player_id, max_size)
+            .await
+        {
+            Ok(Some(position)) => {
+                self.waiting_players.insert(
+                    *player_id,
+                    WaitingListEntry {
+                        room_id,
+                        player_name,
+                    },
+                );
+                let _ = self
+                    .message_coordinator
+                    .send_to_player(
+                        player_id,
+                        Arc::new(ServerMessage::WaitingListPosition { position }),
+                    )
+                    .await;
+                tracing::info!(%player_id, %room_id, position, "Player queued on room waiting list");
+            }
+            Ok(None) => {
+                self.send_join_failed(
+                    player_id,
+                    format!("Room is full and its waiting list is full ({max_size} players)"),
+                    ErrorCode::WaitingListFull,
+                )
+                .await;
+            }
+            Err(e) => {
+                self.send_join_failed(player_id, e.to_string(), ErrorCode::RoomNotFound)
+                    .await;
+            }
+        }
+    }
+
+    /// Remove a player from whichever waiting list they are queued on.
+    /// Returns true if the player was waiting.
+    pub(super) async fn cancel_waiting_list(&self, player_id: &PlayerId) -> bool {
+        let Some((_, entry)) = self.waiting_players.remove(player_id) else {
+            return false;
+        };
+
+        match self
+            .database
+            .remove_from_waiting_list(&entry.room_id, player_id)
+            .await
+        {
+            Ok(true) => {
+                tracing::info!(%player_id, room_id = %entry.room_id, "Player removed from waiting list");
+                self.send_waiting_list_positions(&entry.room_id).await;
+            }
+            Ok(false) => {}
+            Err(e) => {
+                tracing::warn!(%player_id, room_id = %entry.room_id, error = %e, "Failed to remove player from waiting list");
+            }
+        }
+        true
+    }
+
+    /// Move queued players into the room until it is full or the waiting list is empty.
+    pub(super) async fn promote_from_waiting_list(&self, room_id: &RoomId) {
+        let mut promoted_any = false;
+
+        loop {
+            let Ok(Some(room)) = self.database.get_room_by_id(room_id).await else {
+                return;
+            };
+            if room.waiting_list.is_empty() || !room.can_join() {
+                break;
+            }
+
+            let lock_key = format!("room_join:{}:{}", room.game_name, room.code);
+            let lock_handle = match self
+                .distributed_lock
+                .acquire(&lock_key, Duration::from_secs(10))
+                .await
+            {
+                Ok(handle) => handle,
+                Err(e) => {
+                    tracing::warn!(%room_id, error = %e, "Failed to lock room for waiting-list promotion");
+                    break;
+                }
+            };
+
+            let promoted = self.promote_next_waiting_player(room_id).await;
+            let _ = self.distributed_lock.release(&lock_handle).await;
+
+            match promoted {
+                Some(true) => promoted_any = true,
+                Some(false) => {}
+                None => break,
+            }
+        }
+
+        if promoted_any {
+            self.send_waiting_list_positions(room_id).await;
+        }
+    }
+
+    /// Pop and seat the next queued player. Must be called with the room join lock held.
+    ///
+    /// Returns `None` when nothing more can be promoted, `Some(true)` when a player
+    /// was seated, and `Some(false)` when a stale entry was skipped.
+    async fn promote_next_waiting_player(&self, room_id: &RoomId) -> Option<bool> {
+        let mut room = match self.database.get_room_by_id(room_id).await {
+            Ok(Some(room)) if room.can_join() => room,
+            _ => return None,
+        };
+
+        let next_player = match self.database.pop_waiting_list(room_id).await {
+            Ok(Some(player_id)) => player_id,
+            Ok(None) => return None,
+            Err(e) => {
+                tracing::warn!(%room_id, error = %e, "Failed to pop waiting list");
+                return None;
+            }
+        };
+
+        let Some((_, entry)) = self.waiting_players.remove(&next_player) else {
+            return Some(false);
+        };
+        if !self.connection_manager.has_client(&next_player)
+            || self.get_client_room(&next_player).await.is_some()
+            || room.is_banned(
+                &next_player,
+                self.connection_manager.client_ip(&next_player),
+            )
+        {
+            return Some(false);
+        }
+
+        if let Err(reason) =
+            validation::validate_player_name_uniqueness(&entry.player_name, &room.players)
+        {
+            let suggested = validation::suffix_duplicate_player_name(
+                &entry.player_name,
+                &room.players,
+                self.protocol_config.max_player_name_length,
+            );
+            self.send_join_failed_with_details(
+                &next_player,
+                reason,
+                ErrorCode::InvalidPlayerName,
+                Some(ErrorDetails::NameTaken { suggested }),
+            )
+            .await;
+            return Some(false);
+        }
+
+        let player_info = PlayerInfo {
+            id: next_player,
+            name: entry.player_name.clone(),
+            is_authority: false,
+            role: PlayerRole::Member,
+            is_ready: false,
+            connected_at: chrono::Utc::now(),
+            connection_info: None,
+            region_id: room.region_id.clone(),
+        };
+
+        let turn = match self.seat_player(room_id, player_info.clone(), None).await {
+            Ok(Some(turn)) => turn,
+            Ok(None) => {
+                self.send_join_failed_with_details(
+                    &next_player,
+                    "Room is full".to_string(),
+                    ErrorCode::RoomFull,
+                    Some(ErrorDetails::RoomFull {
+                        max_players: room.max_players,
+                    }),
+                )
+                .await;
+                return None;
+            }
+            Err(e) => {
+                tracing::warn!(%room_id, player_id = %next_player, error = %e, "Failed to seat waiting player");
+                return None;
+            }
+        };
+
+        self.metrics.increment_rooms_joined();
+        self.metrics.increment_players_joined();
+        room.players.insert(next_player, player_info);
+
+        let _ = self
+            .message_coordinator
+            .send_to_player(
+                &next_player,
+                Arc::new(ServerMessage::WaitingListPromoted {
+                    room_id: room.id,
+                    room_code: room.code.clone(),
+                }),
+            )
+            .await;
+        self.announce_room_join(&next_player, &room, entry.player_name, turn)
+            .await;
+
+        tracing::info!(
+            player_id = %next_player,
+            %room_id,
+            room_code = %room.code,
+            "Promoted player from waiting list"
+        );
+        Some(true)
+    }
+
+    /// Send every queued player their current waiting-list position.
+    async fn send_waiting_list_positions(&self, room_id: &RoomId) {
+        let Ok(Some(room)) = self.database.get_room_by_id(room_id).await else {
+            return;
+        };
+        for (index, queued_player) in room.waiting_list.iter().enumerate() {
+            let _ = self
+                .message_coordinator
+                .send_to_player(
+                    queued_player,
+                    Arc::new(ServerMessage::WaitingListPosition {
+                        position: index + 1,
+                    }),
+                )
+                .await;
+        }
+    }
+
+    pub(super) async fn send_join_failed(
+        &self,
+        player_id: &PlayerId,
+        reason: String,
+        error_code: ErrorCode,
+    ) {
+        self.send_join_failed_with_details(player_id, reason, error_code, None)
+            .await;
+    }
+
+    /// [`Self::send_join_failed`] with typed details for `error_code`.
+    pub(super) async fn send_join_failed_with_details(
+        &self,
+        player_id: &PlayerId,
+        reason: String,
+        error_code: ErrorCode,
+        details: Option<ErrorDetails>,
+    ) {
+        let _ = self
+            .message_coordinator
+            .send_to_player(
+                player_id,
+                Arc::new(ServerMessage::RoomJoinFailed {
+                    reason,
+                    error_code: Some(error_code),
+                    retry_after_secs: None,
+                    details,
+                }),
+            )
+            .await;
+    }
+}
@@ -0,0 +1,231 @@
+use super::*;
+use crate::config::{
+    AuthMaintenanceConfig, CoordinationConfig, MetricsConfig, ProtocolConfig, RelayTypeConfig,
+    TransportSecurityConfig,
+};
+use crate::database::DatabaseConfig;
+use crate::protocol::{ErrorCode, ServerMessage};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::time::{timeout, Duration};
+
+async fn create_test_server(max_waiting_list_size: usize) -> Arc<EnhancedGameServer> {
+    EnhancedGameServer::new(
+        ServerConfig {
+            max_waiting_list_size,
+            ..ServerConfig::default()
+        },
+        ProtocolConfig::default(),
+        RelayTypeConfig::default(),
+        DatabaseConfig::InMemory,
+        MetricsConfig::default(),
+        AuthMaintenanceConfig::default(),
+        CoordinationConfig::default(),
+        TransportSecurityConfig::default(),
+        Vec::new(),
+    )
+    .await
+    .expect("failed to construct test server")
+}
+
+async fn register(server: &EnhancedGameServer, port: u16) -> (PlayerId, mpsc::Receiver<Outbound>) {
+    let (sender, receiver) = mpsc::channel(32);
+    let addr: SocketAddr = format!("127.0.0.{}:{port}", port % 250 + 1)
+        .parse()
+        .unwrap();
+    let player_id = server
+        .connection_manager
+        .register_client(sender, addr, server.instance_id)
+        .await
+        .expect("client registration succeeds");
+    (player_id, receiver)
+}
+
+async fn join(server: &EnhancedGameServer, player_id: &PlayerId, name: &str) {
+    server
+        .handle_join_room(
+            player_id,
+            "queue-game".to_string(),
+            Some("QUEUE1".to_string()),
+            name.to_string(),
+            Some(2),
+            Some(true),
+            None,
+            None,
+            None,
+        )
+        .await;
+}
+
+async fn next_message(receiver: &mut mpsc::Receiver<Outbound>) -> Outbound {
+    timeout(Duration::from_secs(1), receiver.recv())
+        .await
+        .expect("message arrives in time")
+        .expect("channel still open")
+}
+
+fn drain(receiver: &mut mpsc::Receiver<Outbound>) {
+    while receiver.try_recv().is_ok() {}
+}
+
+#[tokio::test]
+async fn full_room_queues_player_and_promotes_on_leave() {
+    let server = create_test_server(4).await;
+    let (host, mut host_rx) = register(&server, 48100).await;
+    let (guest, mut guest_rx) = register(&server, 48101).await;
+    let (waiter, mut waiter_rx) = register(&server, 48102).await;
+
+    join(&server, &host, "Host").await;
+    join(&server, &guest, "Guest").await;
+    drain(&mut host_rx);
+    drain(&mut guest_rx);
+
+    join(&server, &waiter, "Waiter").await;
+    let queued = next_message(&mut waiter_rx).await;
+    assert!(
+        matches!(*queued, ServerMessage::WaitingListPosition { position: 1 }),
+        "expected waiting list position 1, got {queued:?}"
+    );
+
+    server.leave_room(&guest).await;
+
+    let promoted = next_message(&mut waiter_rx).await;
+    assert!(
+        matches!(*promoted, ServerMessage::WaitingListPromoted { ref room_code, .. } if room_code == "QUEUE1"),
+        "expected WaitingListPromoted, got {promoted:?}"
+    );
+    let joined = next_message(&mut waiter_rx).await;
+    assert!(
+        matches!(*joined, ServerMessage::RoomJoined(_)),
+        "expected RoomJoined after promotion, got {joined:?}"
+    );
+
+    let room_id = server
+        .get_client_room(&waiter)
+        .await
+        .expect("promoted player is assigned to the room");
+    let room = server
+        .database
+        .get_room_by_id(&room_id)
+        .await
+        .unwrap()
+        .expect("room exists");
+    assert!(room.players.contains_key(&waiter));
+    assert!(room.waiting_list.is_empty());
+}
+
+#[tokio::test]
+async fn waiting_list_rejects_players_beyond_cap() {
+    let server = create_test_server(1).await;
+    let (host, _host_rx) = register(&server, 48110).await;
+    let (guest, _guest_rx) = register(&server, 48111).await;
+    let (first_waiter, _first_rx) = register(&server, 48112).await;
+    let (second_waiter, mut second_rx) = register(&server, 48113).await;
+
+    join(&server, &host, "Host").await;
+    join(&server, &guest, "Guest").await;
+    join(&server, &first_waiter, "First").await;
+    join(&server, &second_waiter, "Second").await;
+
+    let rejection = next_message(&mut second_rx).await;
+    assert!(
+        matches!(
+            *rejection,
+            ServerMessage::RoomJoinFailed {
+                error_code: Some(ErrorCode::WaitingListFull),
+                ..
+            }
+        ),
+        "expected WaitingListFull rejection, got {rejection:?}"
+    );
+}
+
+#[tokio::test]
+async fn disabled_waiting_list_rejects_full_room() {
+    let server = create_test_server(0).await;
+    let (host, _host_rx) = register(&server, 48120).await;
+    let (guest, _guest_rx) = register(&server, 48121).await;
+    let (late, mut late_rx) = register(&server, 48122).await;
+
+    join(&server, &host, "Host").await;
+    join(&server, &guest, "Guest").await;
+    join(&server, &late, "Late").await;
+
+    let rejection = next_message(&mut late_rx).await;
+    assert!(
+        matches!(
+            *rejection,
+            ServerMessage::RoomJoinFailed {
+                error_code: Some(ErrorCode::RoomFull),
+                ..
+            }
+        ),
+        "expected RoomFull rejection, got {rejection:?}"
+    );
+}
+
+#[tokio::test]
+async fn disconnecting_waiter_is_removed_and_positions_update() {
+    let server = create_test_server(4).await;
+    let (host, _host_rx) = register(&server, 48130).await;
+    let (guest, _guest_rx) = register(&server, 48131).await;
+    let (first_waiter, _first_rx) = register(&server, 48132).await;
+    let (second_waiter, mut second_rx) = register(&server, 48133).await;
+
+    join(&server, &host, "Host").await;
+    join(&server, &guest, "Guest").await;
+    join(&server, &first_waiter, "First").await;
+    join(&server, &second_waiter, "Second").await;
+
+    let queued = next_message(&mut second_rx).await;
+    assert!(matches!(
+        *queued,
+        ServerMessage::WaitingListPosition { position: 2 }
+    ));
+
+    server.unregister_client(&first_waiter).await;
+
+    let moved_up = next_message(&mut second_rx).await;
+    assert!(
+        matches!(
+            *moved_up,
+            ServerMessage::WaitingListPosition { position: 1 }
+        ),
+        "remaining waiter should move up, got {moved_up:?}"
+    );
+
+    let room = server
+        .database
+        .get_room("queue-game", "QUEUE1")
+        .await
+        .unwrap()
+        .expect("room exists");
+    assert_eq!(room.waiting_list.len(), 1);
+    assert_eq!(room.waiting_list.front(), Some(&second_waiter));
+}
+
+#[tokio::test]
+async fn leave_room_cancels_waiting_list_entry() {
+    let server = create_test_server(4).await;
+    let (host, _host_rx) = register(&server, 48140).await;
+    let (guest, _guest_rx) = register(&server, 48141).await;
+    let (waiter, mut waiter_rx) = register(&server, 48142).await;
+
+    join(&server, &host, "Host").await;
+    join(&server, &guest, "Guest").await;
+    join(&server, &waiter, "Waiter").await;
+    drain(&mut waiter_rx);
+
+    server.leave_room(&waiter).await;
+
+    let left = next_message(&mut waiter_rx).await;
+    assert!(matches!(*left, ServerMessage::RoomLeft));
+    let room = server
+        .database
+        .get_room("queue-game", "QUEUE1")
+        .await
+        .unwrap()
+        .expect("room exists");
+    assert!(room.waiting_list.is_empty());
+}
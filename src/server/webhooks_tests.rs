@@ -0,0 +1,262 @@
+use super::*;
+use crate::config::{
+    AuthMaintenanceConfig, CoordinationConfig, MetricsConfig, ProtocolConfig, RelayTypeConfig,
+    TransportSecurityConfig, WebhookConfig, WebhookEvent,
+};
+use crate::database::DatabaseConfig;
+use crate::protocol::{ReportReason, ServerMessage};
+use crate::retry::RetryConfig;
+use crate::webhooks::{signature, EVENT_HEADER, SIGNATURE_HEADER};
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::post;
+use axum::Router;
+use bytes::Bytes;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::time::{sleep, timeout, Duration};
+
+const GAME: &str = "webhook-game";
+const ROOM_CODE: &str = "HOOKED";
+const SECRET: &str = "webhook-secret";
+
+struct Delivery {
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+impl Delivery {
+    fn json(&self) -> serde_json::Value {
+        serde_json::from_slice(&self.body).expect("webhook body is JSON")
+    }
+}
+
+/// Start an HTTP endpoint that answers every `POST` with `status` and hands
+/// the request to the returned receiver.
+async fn start_receiver(status: StatusCode) -> (String, mpsc::UnboundedReceiver<Delivery>) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let app = Router::new().route(
+        "/hooks",
+        post(move |headers: HeaderMap, body: Bytes| {
+            let tx = tx.clone();
+            async move {
+                let _ = tx.send(Delivery { headers, body });
+                status
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let _ = axum::serve(listener, app).await;
+    });
+    (format!("http://{addr}/hooks"), rx)
+}
+
+fn webhook(url: String, events: Vec<WebhookEvent>) -> WebhookConfig {
+    WebhookConfig {
+        url,
+        secret: SECRET.to_string(),
+        events,
+        timeout_ms: 1000,
+    }
+}
+
+async fn create_test_server(webhooks: Vec<WebhookConfig>) -> Arc<EnhancedGameServer> {
+    EnhancedGameServer::new(
+        ServerConfig {
+            webhooks,
+            ..ServerConfig::default()
+        },
+        ProtocolConfig::default(),
+        RelayTypeConfig::default(),
+        DatabaseConfig::InMemory,
+        MetricsConfig::default(),
+        AuthMaintenanceConfig::default(),
+        CoordinationConfig::default(),
+        TransportSecurityConfig::default(),
+        Vec::new(),
+    )
+    .await
+    .expect("failed to construct test server")
+}
+
+async fn join(server: &EnhancedGameServer, port: u16, name: &str) -> PlayerId {
+    let (sender, mut receiver) = mpsc::channel(32);
+    let addr: SocketAddr = format!("127.0.0.1:{port}").parse().unwrap();
+    let player_id = server
+        .connection_manager
+        .register_client(sender, addr, server.instance_id)
+        .await
+        .expect("client registration succeeds");
+    server
+        .handle_join_room(
+            &player_id,
+            GAME.to_string(),
+            Some(ROOM_CODE.to_string()),
+            name.to_string(),
+            Some(4),
+            Some(false),
+            None,
+            None,
+            None,
+        )
+        .await;
+    let joined = timeout(Duration::from_secs(1), receiver.recv())
+        .await
+        .expect("RoomJoined arrives in time")
+        .expect("channel still open");
+    assert!(matches!(&*joined, ServerMessage::RoomJoined(_)));
+    player_id
+}
+
+async fn next_delivery(rx: &mut mpsc::UnboundedReceiver<Delivery>) -> Delivery {
+    timeout(Duration::from_secs(2), rx.recv())
+        .await
+        .expect("webhook delivered in time")
+        .expect("receiver still running")
+}
+
+#[tokio::test]
+async fn lifecycle_events_are_posted_with_a_valid_signature() {
+    let (url, mut rx) = start_receiver(StatusCode::OK).await;
+    let server = create_test_server(vec![webhook(url, WebhookEvent::ALL.to_vec())]).await;
+
+    let host = join(&server, 49000, "Host").await;
+    // Each delivery runs on its own task, so the two may arrive in either order
+    let mut first = [next_delivery(&mut rx).await, next_delivery(&mut rx).await];
+    first.sort_by_key(|delivery| delivery.headers[EVENT_HEADER] != "room_created");
+    let [created, joined] = first;
+
+    let expected = signature(SECRET, &created.body);
+    assert_eq!(
+        created.headers[SIGNATURE_HEADER].to_str().unwrap(),
+        expected
+    );
+    assert_eq!(created.headers[EVENT_HEADER], "room_created");
+    let created = created.json();
+    assert_eq!(created["event_type"], "room_created");
+    assert_eq!(created["game_name"], GAME);
+    assert_eq!(created["room_code"], ROOM_CODE);
+    assert_eq!(created["created_by"], host.to_string());
+    assert!(created["timestamp"].is_string());
+    let room_id = created["room_id"].clone();
+
+    let joined = joined.json();
+    assert_eq!(joined["event_type"], "player_joined");
+    assert_eq!(joined["room_id"], room_id);
+    assert_eq!(joined["player_name"], "Host");
+    assert_eq!(joined["player_count"], 1);
+
+    let guest = join(&server, 49001, "Guest").await;
+    assert_eq!(next_delivery(&mut rx).await.json()["player_count"], 2);
+    server.leave_room(&guest).await;
+    let left = next_delivery(&mut rx).await.json();
+    assert_eq!(left["event_type"], "player_left");
+    assert_eq!(left["player_id"], guest.to_string());
+    assert_eq!(left["player_name"], "Guest");
+    assert_eq!(left["player_count"], 1);
+
+    server
+        .admin_close_room(GAME, ROOM_CODE)
+        .await
+        .unwrap()
+        .expect("room exists");
+    let closed = next_delivery(&mut rx).await.json();
+    assert_eq!(closed["event_type"], "room_closed");
+    assert_eq!(closed["room_id"], room_id);
+    assert_eq!(closed["game_name"], GAME);
+    assert_eq!(closed["reason"], ADMIN_CLOSE_REASON);
+}
+
+#[tokio::test]
+async fn only_subscribed_events_are_delivered() {
+    let (url, mut rx) = start_receiver(StatusCode::OK).await;
+    let server = create_test_server(vec![webhook(url, vec![WebhookEvent::PlayerLeft])]).await;
+
+    join(&server, 49010, "Host").await;
+    let guest = join(&server, 49011, "Guest").await;
+    server.leave_room(&guest).await;
+
+    let delivery = next_delivery(&mut rx).await.json();
+    assert_eq!(delivery["event_type"], "player_left");
+    sleep(Duration::from_millis(50)).await;
+    assert!(rx.try_recv().is_err(), "no other events are delivered");
+}
+
+#[tokio::test]
+async fn rejected_deliveries_are_retried_then_counted_as_failed() {
+    let (url, mut rx) = start_receiver(StatusCode::INTERNAL_SERVER_ERROR).await;
+    let server = create_test_server(vec![webhook(url, vec![WebhookEvent::RoomCreated])]).await;
+
+    join(&server, 49020, "Host").await;
+    let attempts = RetryConfig::default().max_attempts;
+    for _ in 0..attempts {
+        next_delivery(&mut rx).await;
+    }
+
+    let counted = timeout(Duration::from_secs(1), async {
+        while server
+            .metrics
+            .snapshot()
+            .await
+            .rooms
+            .webhook_delivery_failed
+            == 0
+        {
+            sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await;
+    assert!(counted.is_ok(), "failure is counted after the last attempt");
+    assert!(
+        rx.try_recv().is_err(),
+        "no attempts beyond the retry budget"
+    );
+}
+
+#[tokio::test]
+async fn player_reports_are_delivered_only_when_subscribed() {
+    let (url, mut rx) = start_receiver(StatusCode::OK).await;
+    let (lifecycle_url, mut lifecycle_rx) = start_receiver(StatusCode::OK).await;
+    let server = create_test_server(vec![
+        webhook(url, vec![WebhookEvent::PlayerReported]),
+        webhook(lifecycle_url, WebhookEvent::LIFECYCLE.to_vec()),
+    ])
+    .await;
+
+    let host = join(&server, 49030, "Host").await;
+    let guest = join(&server, 49031, "Guest").await;
+    for _ in 0..3 {
+        next_delivery(&mut lifecycle_rx).await;
+    }
+    server
+        .handle_report_player(
+            &host,
+            &guest,
+            ReportReason::Harassment,
+            Some("Spamming the chat".to_string()),
+        )
+        .await;
+
+    let delivery = next_delivery(&mut rx).await;
+    assert_eq!(delivery.headers[EVENT_HEADER], "player_reported");
+    let report = delivery.json();
+    assert_eq!(report["event_type"], "player_reported");
+    assert_eq!(report["game_name"], GAME);
+    assert_eq!(report["room_code"], ROOM_CODE);
+    assert_eq!(report["reporter_id"], host.to_string());
+    assert_eq!(report["reporter_name"], "Host");
+    assert_eq!(report["target_id"], guest.to_string());
+    assert_eq!(report["target_name"], "Guest");
+    assert_eq!(report["reason"], "harassment");
+    assert_eq!(report["details"], "Spamming the chat");
+    assert!(report["report_id"].is_string());
+    assert!(report.get("reporter_fingerprint").is_none());
+
+    sleep(Duration::from_millis(50)).await;
+    assert!(
+        lifecycle_rx.try_recv().is_err(),
+        "reports are not sent to lifecycle webhooks"
+    );
+}
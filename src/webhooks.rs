@@ -0,0 +1,272 @@
+//! Room lifecycle and player report webhooks.
+//!
+//! Every endpoint in `server.webhooks` receives a JSON `POST` for the events it
+//! subscribes to. The body is signed with HMAC-SHA256 under the endpoint's
+//! secret and the hex digest is sent as `X-Signal-Fish-Signature: sha256=<hex>`.
+//! Deliveries run on their own tasks so room operations never wait on them;
+//! failed attempts are retried with backoff and deliveries that still fail are
+//! counted in `webhook_delivery_failed_total`.
+
+use crate::closed_rooms::ClosedRoomSummary;
+use crate::config::{WebhookConfig, WebhookEvent};
+use crate::metrics::ServerMetrics;
+use crate::protocol::{PlayerId, ReportReason, Room, RoomId};
+use crate::reports::PlayerReport;
+use crate::retry::{RetryConfig, RetryExecutor};
+use crate::security::hmac_sha256_hex;
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Header carrying `sha256=<hex digest of the body>`.
+pub const SIGNATURE_HEADER: &str = "X-Signal-Fish-Signature";
+/// Header carrying the payload's `event_type`.
+pub const EVENT_HEADER: &str = "X-Signal-Fish-Event";
+
+/// Body of a webhook delivery.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WebhookPayload {
+    pub event_type: WebhookEvent,
+    pub room_id: RoomId,
+    pub game_name: String,
+    pub timestamp: DateTime<Utc>,
+    #[serde(flatten)]
+    pub details: WebhookDetails,
+}
+
+/// Event-specific fields, flattened into the payload.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum WebhookDetails {
+    RoomCreated {
+        room_code: String,
+        max_players: u8,
+        created_by: PlayerId,
+    },
+    RoomClosed {
+        room_code: String,
+        reason: String,
+        created_at: DateTime<Utc>,
+        peak_player_count: usize,
+        finalized: bool,
+    },
+    PlayerJoined {
+        player_id: PlayerId,
+        player_name: String,
+        player_count: usize,
+    },
+    PlayerLeft {
+        player_id: PlayerId,
+        player_name: String,
+        player_count: usize,
+    },
+    PlayerReported {
+        report_id: Uuid,
+        room_code: String,
+        reporter_id: PlayerId,
+        reporter_name: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        reporter_fingerprint: Option<String>,
+        target_id: PlayerId,
+        target_name: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        target_fingerprint: Option<String>,
+        reason: ReportReason,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        details: Option<String>,
+    },
+}
+
+impl WebhookDetails {
+    pub const fn event(&self) -> WebhookEvent {
+        match self {
+            Self::RoomCreated { .. } => WebhookEvent::RoomCreated,
+            Self::RoomClosed { .. } => WebhookEvent::RoomClosed,
+            Self::PlayerJoined { .. } => WebhookEvent::PlayerJoined,
+            Self::PlayerLeft { .. } => WebhookEvent::PlayerLeft,
+            Self::PlayerReported { .. } => WebhookEvent::PlayerReported,
+        }
+    }
+}
+
+/// Value of [`SIGNATURE_HEADER`] for `body` signed with `secret`.
+pub fn signature(secret: &str, body: &[u8]) -> String {
+    format!("sha256={}", hmac_sha256_hex(secret.as_bytes(), body))
+}
+
+/// Sends room lifecycle events and player reports to the configured webhooks.
+pub struct WebhookDispatcher {
+    endpoints: Vec<WebhookConfig>,
+    client: reqwest::Client,
+    retry: RetryConfig,
+    metrics: Arc<ServerMetrics>,
+}
+
+impl WebhookDispatcher {
+    pub fn new(endpoints: Vec<WebhookConfig>, metrics: Arc<ServerMetrics>) -> Self {
+        Self {
+            endpoints,
+            client: reqwest::Client::new(),
+            retry: RetryConfig::default(),
+            metrics,
+        }
+    }
+
+    fn subscribed(&self, event: WebhookEvent) -> bool {
+        self.endpoints
+            .iter()
+            .any(|endpoint| endpoint.events.contains(&event))
+    }
+
+    pub fn room_created(&self, room: &Room, created_by: PlayerId) {
+        self.notify(
+            room.id,
+            &room.game_name,
+            WebhookDetails::RoomCreated {
+                room_code: room.code.clone(),
+                max_players: room.max_players,
+                created_by,
+            },
+        );
+    }
+
+    pub fn room_closed(&self, summary: &ClosedRoomSummary) {
+        self.notify(
+            summary.room_id,
+            &summary.game_name,
+            WebhookDetails::RoomClosed {
+                room_code: summary.room_code.clone(),
+                reason: summary.reason.clone(),
+                created_at: summary.created_at,
+                peak_player_count: summary.peak_player_count,
+                finalized: summary.finalized,
+            },
+        );
+    }
+
+    /// `room` must already include the new player.
+    pub fn player_joined(&self, room: &Room, player_id: PlayerId, player_name: &str) {
+        self.notify(
+            room.id,
+            &room.game_name,
+            WebhookDetails::PlayerJoined {
+                player_id,
+                player_name: player_name.to_string(),
+                player_count: room.players.len(),
+            },
+        );
+    }
+
+    /// `room` must no longer include the departed player.
+    pub fn player_left(&self, room: &Room, player_id: PlayerId, player_name: &str) {
+        self.notify(
+            room.id,
+            &room.game_name,
+            WebhookDetails::PlayerLeft {
+                player_id,
+                player_name: player_name.to_string(),
+                player_count: room.players.len(),
+            },
+        );
+    }
+
+    pub fn player_reported(&self, report: &PlayerReport) {
+        self.notify(
+            report.room_id,
+            &report.game_name,
+            WebhookDetails::PlayerReported {
+                report_id: report.report_id,
+                room_code: report.room_code.clone(),
+                reporter_id: report.reporter_id,
+                reporter_name: report.reporter_name.clone(),
+                reporter_fingerprint: report.reporter_fingerprint.as_deref().map(str::to_string),
+                target_id: report.target_id,
+                target_name: report.target_name.clone(),
+                target_fingerprint: report.target_fingerprint.as_deref().map(str::to_string),
+                reason: report.reason,
+                details: report.details.clone(),
+            },
+        );
+    }
+
+    fn notify(&self, room_id: RoomId, game_name: &str, details: WebhookDetails) {
+        let event = details.event();
+        if !self.subscribed(event) {
+            return;
+        }
+        let payload = WebhookPayload {
+            event_type: event,
+            room_id,
+            game_name: game_name.to_string(),
+            timestamp: Utc::now(),
+            details,
+        };
+        let body = match serde_json::to_vec(&payload) {
+            Ok(body) => Bytes::from(body),
+            Err(e) => {
+                tracing::error!(%room_id, event = event.as_str(), error = %e, "Failed to serialize webhook payload");
+                return;
+            }
+        };
+
+        for endpoint in &self.endpoints {
+            if !endpoint.events.contains(&event) {
+                continue;
+            }
+            let delivery = Delivery {
+                client: self.client.clone(),
+                url: endpoint.url.clone(),
+                signature: signature(&endpoint.secret, &body),
+                timeout: Duration::from_millis(endpoint.timeout_ms),
+                event,
+                body: body.clone(),
+            };
+            let retry = self.retry.clone();
+            let metrics = Arc::clone(&self.metrics);
+            tokio::spawn(async move {
+                let result = RetryExecutor::new(retry)
+                    .execute_with_condition("webhook_delivery", || delivery.send(), |_| true)
+                    .await;
+                if let Err(e) = result {
+                    metrics.increment_webhook_delivery_failed();
+                    tracing::warn!(
+                        %room_id,
+                        url = %delivery.url,
+                        event = event.as_str(),
+                        error = %e,
+                        "Webhook delivery failed"
+                    );
+                }
+            });
+        }
+    }
+}
+
+/// One signed request, sent once per attempt.
+struct Delivery {
+    client: reqwest::Client,
+    url: String,
+    signature: String,
+    timeout: Duration,
+    event: WebhookEvent,
+    body: Bytes,
+}
+
+impl Delivery {
+    async fn send(&self) -> reqwest::Result<()> {
+        self.client
+            .post(&self.url)
+            .timeout(self.timeout)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(SIGNATURE_HEADER, &self.signature)
+            .header(EVENT_HEADER, self.event.as_str())
+            .body(self.body.clone())
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,221 @@
+use crate::protocol::AnnouncementSeverity;
+use crate::security::constant_time_eq;
+use crate::server::EnhancedGameServer;
+use axum::extract::{Path, Query, State};
+use axum::http::header::AUTHORIZATION;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Json, Response};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::sync::Arc;
+
+/// Summaries returned by `recent-closed` when no `limit` is given.
+const DEFAULT_RECENT_CLOSED_LIMIT: usize = 100;
+
+/// Players returned by `players/flapping` when no `limit` is given.
+const DEFAULT_FLAPPING_PLAYERS_LIMIT: usize = 100;
+
+pub(super) fn admin_error(status: StatusCode, error: &str, message: impl Into<String>) -> Response {
+    let body = serde_json::json!({
+        "error": error,
+        "message": message.into(),
+    });
+    (status, Json(body)).into_response()
+}
+
+/// Status, error code and message for a rejected admin request.
+type AdminRejection = (StatusCode, &'static str, &'static str);
+
+/// Admin endpoints always require a bearer token, independent of `require_metrics_auth`.
+pub(super) fn enforce_admin_auth(
+    headers: &HeaderMap,
+    server: &EnhancedGameServer,
+) -> Result<(), AdminRejection> {
+    let config = server.config();
+    let Some(expected) = config
+        .admin_auth_token
+        .as_deref()
+        .or(config.metrics_auth_token.as_deref())
+        .filter(|token| !token.is_empty())
+    else {
+        tracing::warn!("Admin API request rejected: no admin token configured");
+        return Err((
+            StatusCode::FORBIDDEN,
+            "admin_disabled",
+            "No admin token is configured on this server",
+        ));
+    };
+
+    let Some(token) = headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+    else {
+        tracing::warn!(
+            "Unauthorized admin access attempt: missing or invalid Authorization header"
+        );
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            "unauthorized",
+            "A bearer token is required",
+        ));
+    };
+
+    if constant_time_eq(token.as_bytes(), expected.as_bytes()) {
+        Ok(())
+    } else {
+        tracing::warn!("Unauthorized admin access attempt: token rejected");
+        Err((StatusCode::FORBIDDEN, "forbidden", "Bearer token rejected"))
+    }
+}
+
+/// `DELETE /admin/rooms/{game_name}/{room_code}`: force-close a room
+pub async fn admin_close_room_handler(
+    headers: HeaderMap,
+    State(server): State<Arc<EnhancedGameServer>>,
+    Path((game_name, room_code)): Path<(String, String)>,
+) -> Response {
+    if let Err((status, error, message)) = enforce_admin_auth(&headers, server.as_ref()) {
+        return admin_error(status, error, message);
+    }
+
+    match server.admin_close_room(&game_name, &room_code).await {
+        Ok(Some(closure)) => Json(closure).into_response(),
+        Ok(None) => admin_error(
+            StatusCode::NOT_FOUND,
+            "room_not_found",
+            format!("No room {room_code} for game {game_name}"),
+        ),
+        Err(e) => {
+            tracing::error!(%game_name, %room_code, error = %e, "Admin room closure failed");
+            admin_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "internal_error",
+                "Failed to close room",
+            )
+        }
+    }
+}
+
+/// Filters for `GET /admin/rooms/recent-closed`.
+#[derive(Debug, Deserialize)]
+pub struct RecentClosedQuery {
+    game: Option<String>,
+    /// RFC 3339 timestamp; only rooms closed at or after it are returned
+    since: Option<DateTime<Utc>>,
+    limit: Option<usize>,
+}
+
+/// `GET /admin/rooms/recent-closed`: summaries of recently closed rooms, newest first
+pub async fn admin_recent_closed_rooms_handler(
+    headers: HeaderMap,
+    State(server): State<Arc<EnhancedGameServer>>,
+    Query(query): Query<RecentClosedQuery>,
+) -> Response {
+    if let Err((status, error, message)) = enforce_admin_auth(&headers, server.as_ref()) {
+        return admin_error(status, error, message);
+    }
+
+    let rooms = server.recent_closed_rooms(
+        query.game.as_deref(),
+        query.since,
+        query.limit.unwrap_or(DEFAULT_RECENT_CLOSED_LIMIT),
+    );
+    Json(serde_json::json!({ "rooms": rooms })).into_response()
+}
+
+/// Filters for `GET /admin/players/flapping`.
+#[derive(Debug, Deserialize)]
+pub struct FlappingPlayersQuery {
+    limit: Option<usize>,
+}
+
+/// `GET /admin/players/flapping`: seated players whose reconnects were hidden
+/// by flap suppression, most flaps first
+pub async fn admin_flapping_players_handler(
+    headers: HeaderMap,
+    State(server): State<Arc<EnhancedGameServer>>,
+    Query(query): Query<FlappingPlayersQuery>,
+) -> Response {
+    if let Err((status, error, message)) = enforce_admin_auth(&headers, server.as_ref()) {
+        return admin_error(status, error, message);
+    }
+
+    let players = server.flapping_players(query.limit.unwrap_or(DEFAULT_FLAPPING_PLAYERS_LIMIT));
+    Json(serde_json::json!({ "players": players })).into_response()
+}
+
+/// Body of `POST /admin/announce`.
+#[derive(Debug, Deserialize)]
+pub struct AnnounceRequest {
+    message: String,
+    severity: AnnouncementSeverity,
+    /// Unix timestamp in seconds after which new connections no longer get it
+    expires_at: i64,
+}
+
+/// `POST /admin/announce`: push a notice to every connected player
+pub async fn admin_announce_handler(
+    headers: HeaderMap,
+    State(server): State<Arc<EnhancedGameServer>>,
+    Json(request): Json<AnnounceRequest>,
+) -> Response {
+    if let Err((status, error, message)) = enforce_admin_auth(&headers, server.as_ref()) {
+        return admin_error(status, error, message);
+    }
+
+    if request.message.trim().is_empty() {
+        return admin_error(
+            StatusCode::BAD_REQUEST,
+            "invalid_request",
+            "Announcement message must not be empty",
+        );
+    }
+    let Some(expires_at) = DateTime::from_timestamp(request.expires_at, 0)
+        .filter(|expires_at| *expires_at > Utc::now())
+    else {
+        return admin_error(
+            StatusCode::BAD_REQUEST,
+            "invalid_request",
+            "expires_at must be a Unix timestamp in the future",
+        );
+    };
+
+    let recipients = server
+        .broadcast_server_announcement(request.message, request.severity, expires_at)
+        .await;
+    Json(serde_json::json!({
+        "recipients": recipients,
+        "expiresAt": expires_at,
+    }))
+    .into_response()
+}
+
+/// `POST /admin/drain`: stop accepting new rooms and joins ahead of a deploy
+pub async fn admin_drain_handler(
+    headers: HeaderMap,
+    State(server): State<Arc<EnhancedGameServer>>,
+) -> Response {
+    set_drain_state(&headers, &server, true)
+}
+
+/// `POST /admin/undrain`: resume accepting new rooms and joins
+pub async fn admin_undrain_handler(
+    headers: HeaderMap,
+    State(server): State<Arc<EnhancedGameServer>>,
+) -> Response {
+    set_drain_state(&headers, &server, false)
+}
+
+fn set_drain_state(headers: &HeaderMap, server: &EnhancedGameServer, draining: bool) -> Response {
+    if let Err((status, error, message)) = enforce_admin_auth(headers, server) {
+        return admin_error(status, error, message);
+    }
+
+    let previous = server.set_draining(draining);
+    Json(serde_json::json!({
+        "draining": draining,
+        "changed": previous != draining,
+    }))
+    .into_response()
+}
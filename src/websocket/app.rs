@@ -0,0 +1,276 @@
+//! Full application router and listener, reusable by crates that embed the server.
+//!
+//! `main.rs` is a thin wrapper around [`build_router`] and [`serve`]. Embedders
+//! call the same two functions and can merge their own routes in between:
+//!
+//! ```no_run
+//! use axum::{routing::get, Extension, Router};
+//! use signal_fish_server::config::Config;
+//! use signal_fish_server::server::EnhancedGameServer;
+//! use signal_fish_server::websocket::{build_router, serve};
+//! use std::sync::Arc;
+//!
+//! struct HookState {
+//!     greeting: String,
+//! }
+//!
+//! async fn hook(Extension(state): Extension<Arc<HookState>>) -> String {
+//!     state.greeting.clone()
+//! }
+//!
+//! async fn run(config: Config, server: Arc<EnhancedGameServer>) -> anyhow::Result<()> {
+//!     let custom = Router::new()
+//!         .route("/my/hook", get(hook))
+//!         .layer(Extension(Arc::new(HookState {
+//!             greeting: "hello".to_string(),
+//!         })));
+//!
+//!     // CORS and client fingerprint capture are applied by `serve`, so they
+//!     // cover `/my/hook` as well as `/v2/ws`.
+//!     let router = build_router(&config, server.clone()).merge(custom);
+//!     serve(router, &config, &server).await
+//! }
+//! ```
+
+use crate::config::Config;
+use crate::security::{
+    ClientCertificateFingerprint, IpFilterRejection, CLIENT_FINGERPRINT_HEADER_CANDIDATES,
+};
+use crate::server::EnhancedGameServer;
+use axum::extract::{ConnectInfo, Request, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::Response;
+use axum::routing::get;
+use axum::serve::ListenerExt;
+use axum::Router;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use super::admin::admin_error;
+use super::http_compression::compress_responses;
+use super::keepalive::{apply_tcp_keepalive, tcp_keepalive};
+use super::metrics::{metrics_handler, prometheus_metrics_handler};
+use super::routes::{
+    connection_routes, reloadable_cors_layer, reporting_routes, warn_invalid_cors_origins,
+    CorsScope,
+};
+
+/// Assemble the server's routes: the enhanced protocol under `/v2`, the
+/// top-level metrics endpoints and a fallback. Metrics and admin responses are
+/// gzipped for clients that accept it, per `metrics.http_compression`.
+///
+/// The router already carries its state, so routes merged into it must be
+/// `Router<()>` and pass their own state through extensions. Merged routers
+/// must not set a fallback. Middleware is added by [`with_middleware`].
+pub fn build_router(config: &Config, server: Arc<EnhancedGameServer>) -> Router {
+    let compression = &config.metrics.http_compression;
+    let enhanced_router = connection_routes()
+        .merge(compress_responses(reporting_routes(), compression))
+        .layer(tower_http::trace::TraceLayer::new_for_http())
+        .with_state(server.clone());
+    let metrics_router = Router::new()
+        .route("/v1/metrics", get(metrics_handler))
+        .route("/metrics", get(metrics_handler))
+        .route("/v1/metrics/prom", get(prometheus_metrics_handler))
+        .route("/metrics/prom", get(prometheus_metrics_handler));
+
+    compress_responses(metrics_router, compression)
+        .nest("/v2", enhanced_router) // Enhanced protocol under /v2
+        .fallback(|| async {
+            "Signal Fish Server. Use /v2/ws for WebSocket protocol, /v1/metrics for metrics, /metrics/prom for Prometheus."
+        })
+        .with_state(server)
+}
+
+/// Apply the client IP filter, client fingerprint capture and CORS to every
+/// route in `router`, including routes merged in after [`build_router`].
+/// Allowed origins and IP lists follow `server`'s current configuration;
+/// metrics, stats and admin routes use the metrics CORS origins and every
+/// other route the game origins.
+pub fn with_middleware(router: Router, server: &Arc<EnhancedGameServer>) -> Router {
+    router
+        .layer(middleware::from_fn(capture_client_fingerprint))
+        .layer(reloadable_cors_layer(Arc::clone(server)))
+        .layer(middleware::from_fn_with_state(
+            Arc::clone(server),
+            filter_client_ip,
+        ))
+}
+
+/// Serve `router` on `config.port`, over TLS when enabled, with middleware applied.
+///
+/// Handlers can extract `ConnectInfo<SocketAddr>`. Returns once Ctrl+C or
+/// SIGTERM has been received and the listener has shut down gracefully.
+pub async fn serve(
+    router: Router,
+    config: &Config,
+    server: &Arc<EnhancedGameServer>,
+) -> anyhow::Result<()> {
+    let addr = SocketAddr::from(([0, 0, 0, 0], config.port));
+    warn_invalid_cors_origins(CorsScope::Game, config.security.game_cors_origins());
+    warn_invalid_cors_origins(CorsScope::Metrics, config.security.metrics_cors_origins());
+    let make_service =
+        with_middleware(router, server).into_make_service_with_connect_info::<SocketAddr>();
+    let keepalive = tcp_keepalive(&config.websocket);
+
+    #[cfg(feature = "tls")]
+    if config.security.transport.tls.enabled {
+        let tls_config = crate::security::build_rustls_config(&config.security.transport.tls)
+            .map_err(|err| anyhow::anyhow!("failed to initialize TLS configuration: {err}"))?;
+
+        tracing::info!(
+            %addr,
+            client_auth = ?config.security.transport.tls.client_auth,
+            "Server started over HTTPS with TLS enabled - Enhanced protocol: /v2/ws, Metrics: /v1/metrics"
+        );
+
+        let handle = axum_server::Handle::new();
+        let shutdown_handle = handle.clone();
+        let server = Arc::clone(server);
+        tokio::spawn(async move {
+            shutdown_signal().await;
+            server.begin_shutdown();
+            shutdown_handle.graceful_shutdown(Some(std::time::Duration::from_secs(10)));
+        });
+
+        axum_server::bind_rustls(addr, tls_config)
+            .map(|acceptor| acceptor.acceptor(super::keepalive::KeepaliveAcceptor(keepalive)))
+            .handle(handle)
+            .serve(make_service)
+            .await?;
+
+        return Ok(());
+    }
+
+    // Start the server over plain TCP (typically behind a reverse proxy).
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await?
+        .tap_io(move |stream| {
+            if let Some(keepalive) = &keepalive {
+                apply_tcp_keepalive(stream, keepalive);
+            }
+        });
+    tracing::info!(
+        %addr,
+        cors_origins = %config.security.game_cors_origins(),
+        metrics_cors_origins = %config.security.metrics_cors_origins(),
+        "Server started over HTTP - Enhanced protocol: /v2/ws, Metrics: /v1/metrics"
+    );
+
+    let server = Arc::clone(server);
+    axum::serve(listener, make_service)
+        .with_graceful_shutdown(async move {
+            shutdown_signal().await;
+            server.begin_shutdown();
+        })
+        .await?;
+
+    Ok(())
+}
+
+/// Resolve on Ctrl+C or, on Unix, SIGTERM (as sent by container orchestrators).
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(err) = tokio::signal::ctrl_c().await {
+            tracing::warn!(error = %err, "Failed to listen for Ctrl+C");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(err) => {
+                tracing::warn!(error = %err, "Failed to listen for SIGTERM");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        () = ctrl_c => {},
+        () = terminate => {},
+    }
+
+    tracing::info!("Shutdown signal received, draining connections");
+}
+
+/// Refuse requests from clients in `security.ip_blocklist`, or outside
+/// `security.ip_allowlist` when one is set, with 403. The client is resolved
+/// through trusted proxies the same way as for connection limits.
+async fn filter_client_ip(
+    State(server): State<Arc<EnhancedGameServer>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let config = server.config();
+    if config.ip_filter.is_empty() {
+        return next.run(req).await;
+    }
+    // Without connect info (e.g. an in-process test transport) there is no
+    // client address to check.
+    let Some(&ConnectInfo(peer)) = req.extensions().get::<ConnectInfo<SocketAddr>>() else {
+        return next.run(req).await;
+    };
+
+    let client_ip = config.trusted_proxies.client_addr(peer, req.headers()).ip();
+    match config.ip_filter.check(client_ip) {
+        Ok(()) => next.run(req).await,
+        Err(rejection) => {
+            let metrics = server.metrics();
+            let message = match rejection {
+                IpFilterRejection::Blocklisted => {
+                    metrics.increment_connections_blocked_blocklist();
+                    "Client address is blocked"
+                }
+                IpFilterRejection::NotAllowlisted => {
+                    metrics.increment_connections_blocked_allowlist();
+                    "Client address is not allowed"
+                }
+            };
+            tracing::debug!(
+                %client_ip,
+                ?rejection,
+                path = %req.uri().path(),
+                "Refused request from filtered client IP"
+            );
+            admin_error(StatusCode::FORBIDDEN, "ip_forbidden", message)
+        }
+    }
+}
+
+async fn capture_client_fingerprint(mut req: Request, next: Next) -> Result<Response, Infallible> {
+    if let Some(fingerprint) = extract_client_fingerprint(req.headers()) {
+        req.extensions_mut().insert(fingerprint);
+    }
+
+    Ok(next.run(req).await)
+}
+
+fn extract_client_fingerprint(headers: &HeaderMap) -> Option<ClientCertificateFingerprint> {
+    for header_name in CLIENT_FINGERPRINT_HEADER_CANDIDATES {
+        if let Some(value) = headers
+            .get(*header_name)
+            .and_then(|value| value.to_str().ok())
+        {
+            let trimmed = value.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+            return Some(ClientCertificateFingerprint {
+                fingerprint: Arc::<str>::from(trimmed.to_owned()),
+                source_header: header_name,
+            });
+        }
+    }
+
+    None
+}
@@ -1,47 +1,70 @@
-use crate::protocol::{PlayerId, ServerMessage};
-use axum::extract::ws::{Message, WebSocket};
+use crate::broadcast::Outbound;
+use crate::protocol::{MessageEncoding, PlayerId, ServerMessage};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::time::Instant;
 
 use crate::server::EnhancedGameServer;
 
-use super::sending::send_single_message;
+use super::sending::{send_outbound, FrameSink};
 
 /// Message batcher for WebSocket connections
 /// Batches multiple messages together to reduce syscall overhead
 pub(super) struct MessageBatcher {
-    pending: Vec<Arc<ServerMessage>>,
+    pending: Vec<Outbound>,
     batch_size: usize,
     batch_interval: Duration,
+    /// Byte limit per batch; `None` when batches are bounded by count and delay only
+    max_bytes: Option<usize>,
+    pending_bytes: usize,
     last_flush: Instant,
 }
 
 impl MessageBatcher {
-    pub(super) fn new(batch_size: usize, batch_interval_ms: u64) -> Self {
+    pub(super) fn new(batch_size: usize, batch_interval: Duration) -> Self {
         Self {
             pending: Vec::with_capacity(batch_size),
             batch_size,
-            batch_interval: Duration::from_millis(batch_interval_ms),
+            batch_interval,
+            max_bytes: None,
+            pending_bytes: 0,
             last_flush: Instant::now(),
         }
     }
 
-    /// Queue a message for batching
-    pub(super) fn queue(&mut self, message: Arc<ServerMessage>) {
-        self.pending.push(message);
+    /// Also flush once the pending messages add up to `max_bytes` (0 disables
+    /// the limit). Messages are measured by their JSON size.
+    pub(super) fn with_max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = (max_bytes > 0).then_some(max_bytes);
+        self
+    }
+
+    /// Queue a message or room frame for batching
+    pub(super) fn queue(&mut self, outbound: Outbound) {
+        match &outbound {
+            Outbound::Message(message) if self.max_bytes.is_some() => {
+                self.pending_bytes += encoded_len(message);
+            }
+            Outbound::Message(_) => {}
+            Outbound::RoomFrame { frame, .. } => self.pending_bytes += frame.len(),
+        }
+        self.pending.push(outbound);
     }
 
     /// Check if batch should be flushed
     pub(super) fn should_flush(&self) -> bool {
-        // Flush if batch is full or time threshold exceeded
+        // Flush on whichever limit is hit first: count, bytes or elapsed time
         self.pending.len() >= self.batch_size
+            || self
+                .max_bytes
+                .is_some_and(|max_bytes| self.pending_bytes >= max_bytes)
             || (!self.pending.is_empty() && self.last_flush.elapsed() >= self.batch_interval)
     }
 
     /// Flush all pending messages
-    pub(super) fn flush(&mut self) -> Vec<Arc<ServerMessage>> {
+    pub(super) fn flush(&mut self) -> Vec<Outbound> {
         self.last_flush = Instant::now();
+        self.pending_bytes = 0;
         std::mem::take(&mut self.pending)
     }
 
@@ -57,10 +80,45 @@ impl MessageBatcher {
     }
 }
 
+/// Messages that should reach the client without waiting for the batch to
+/// fill: errors, authority changes and time sync results, whose timestamps
+/// go stale while they wait. Queuing one flushes the batch, so
+/// earlier messages still go out first.
+pub(super) fn bypasses_batching(message: &ServerMessage) -> bool {
+    matches!(
+        message,
+        ServerMessage::Error(_)
+            | ServerMessage::AuthorityChanged { .. }
+            | ServerMessage::AuthorityResponse { .. }
+            | ServerMessage::TimeSyncResult { .. }
+    )
+}
+
+/// JSON size of `message`, counted without buffering the encoding.
+fn encoded_len(message: &ServerMessage) -> usize {
+    struct ByteCounter(usize);
+
+    impl std::io::Write for ByteCounter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0 += buf.len();
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut counter = ByteCounter(0);
+    let _ = serde_json::to_writer(&mut counter, message);
+    counter.0
+}
+
 /// Helper function to send a batch of messages
 pub(super) async fn send_batch(
-    sender: &mut futures_util::stream::SplitSink<WebSocket, Message>,
+    sender: &mut impl FrameSink,
     batcher: &mut MessageBatcher,
+    encoding: MessageEncoding,
     player_id: &PlayerId,
     server: &Arc<EnhancedGameServer>,
 ) -> Result<(), ()> {
@@ -72,15 +130,11 @@ pub(super) async fn send_batch(
     let batch_size = messages.len();
 
     // Send each message in the batch
-    for message in messages {
-        if send_single_message(sender, message, player_id, server)
-            .await
-            .is_err()
-        {
-            return Err(());
-        }
+    for outbound in messages {
+        send_outbound(sender, outbound, encoding, player_id, server).await?;
     }
 
+    server.metrics.record_websocket_batch(batch_size);
     tracing::trace!(%player_id, batch_size, "Flushed message batch");
     Ok(())
 }
@@ -89,9 +143,16 @@ pub(super) async fn send_batch(
 mod tests {
     use super::*;
 
+    fn room_frame(bytes: &[u8]) -> Outbound {
+        Outbound::RoomFrame {
+            message: Arc::new(ServerMessage::Pong),
+            frame: Arc::from(bytes),
+        }
+    }
+
     #[test]
     fn test_message_batcher_new() {
-        let batcher = MessageBatcher::new(10, 16);
+        let batcher = MessageBatcher::new(10, Duration::from_millis(16));
         assert_eq!(batcher.len(), 0);
         assert!(batcher.is_empty());
         assert!(!batcher.should_flush());
@@ -99,26 +160,26 @@ mod tests {
 
     #[test]
     fn test_message_batcher_queue() {
-        let mut batcher = MessageBatcher::new(10, 16);
+        let mut batcher = MessageBatcher::new(10, Duration::from_millis(16));
         let message = Arc::new(ServerMessage::PlayerLeft {
             player_id: uuid::Uuid::new_v4(),
         });
 
-        batcher.queue(message);
+        batcher.queue(message.into());
         assert_eq!(batcher.len(), 1);
         assert!(!batcher.is_empty());
     }
 
     #[test]
     fn test_message_batcher_flush_on_size() {
-        let mut batcher = MessageBatcher::new(3, 1000); // Large interval, flush on size
+        let mut batcher = MessageBatcher::new(3, Duration::from_millis(1000)); // Large interval, flush on size
 
         // Add messages up to batch size
         for _ in 0..2 {
             let message = Arc::new(ServerMessage::PlayerLeft {
                 player_id: uuid::Uuid::new_v4(),
             });
-            batcher.queue(message);
+            batcher.queue(message.into());
         }
 
         assert_eq!(batcher.len(), 2);
@@ -128,7 +189,7 @@ mod tests {
         let message = Arc::new(ServerMessage::PlayerLeft {
             player_id: uuid::Uuid::new_v4(),
         });
-        batcher.queue(message);
+        batcher.queue(message.into());
 
         assert_eq!(batcher.len(), 3);
         assert!(batcher.should_flush()); // Should flush now
@@ -143,15 +204,14 @@ mod tests {
     #[test]
     fn test_message_batcher_flush_on_time() {
         use std::thread;
-        use std::time::Duration;
 
-        let mut batcher = MessageBatcher::new(100, 50); // Small interval, flush on time
+        let mut batcher = MessageBatcher::new(100, Duration::from_millis(50)); // Small interval, flush on time
 
         // Add a single message
         let message = Arc::new(ServerMessage::PlayerLeft {
             player_id: uuid::Uuid::new_v4(),
         });
-        batcher.queue(message);
+        batcher.queue(message.into());
 
         assert_eq!(batcher.len(), 1);
         assert!(!batcher.should_flush()); // Not enough time passed
@@ -169,14 +229,14 @@ mod tests {
 
     #[test]
     fn test_message_batcher_multiple_flushes() {
-        let mut batcher = MessageBatcher::new(2, 1000);
+        let mut batcher = MessageBatcher::new(2, Duration::from_millis(1000));
 
         // First batch
         for _ in 0..2 {
             let message = Arc::new(ServerMessage::PlayerLeft {
                 player_id: uuid::Uuid::new_v4(),
             });
-            batcher.queue(message);
+            batcher.queue(message.into());
         }
 
         assert!(batcher.should_flush());
@@ -189,7 +249,7 @@ mod tests {
             let message = Arc::new(ServerMessage::PlayerLeft {
                 player_id: uuid::Uuid::new_v4(),
             });
-            batcher.queue(message);
+            batcher.queue(message.into());
         }
 
         assert!(batcher.should_flush());
@@ -198,9 +258,84 @@ mod tests {
         assert_eq!(batcher.len(), 0);
     }
 
+    #[test]
+    fn test_message_batcher_counts_room_frames() {
+        let mut batcher = MessageBatcher::new(2, Duration::from_millis(1000));
+        batcher.queue(ServerMessage::RoomLeft.into());
+        batcher.queue(room_frame(b"{\"type\":\"Pong\"}"));
+        assert!(batcher.should_flush());
+
+        let flushed = batcher.flush();
+        assert!(matches!(flushed[0], Outbound::Message(_)));
+        assert!(
+            matches!(&flushed[1], Outbound::RoomFrame { frame, .. } if frame.starts_with(b"{"))
+        );
+    }
+
+    #[test]
+    fn test_message_batcher_flush_on_bytes() {
+        let frame = room_frame(&[0u8; 40]);
+        let mut batcher = MessageBatcher::new(100, Duration::from_millis(1000)).with_max_bytes(100);
+
+        batcher.queue(frame.clone());
+        batcher.queue(frame.clone());
+        assert!(!batcher.should_flush()); // 80 of 100 bytes
+
+        batcher.queue(frame.clone());
+        assert!(batcher.should_flush());
+        assert_eq!(batcher.flush().len(), 3);
+
+        // The byte count starts over after a flush
+        batcher.queue(frame);
+        assert!(!batcher.should_flush());
+    }
+
+    #[test]
+    fn test_message_batcher_measures_messages_as_json() {
+        let message = Arc::new(ServerMessage::PlayerLeft {
+            player_id: uuid::Uuid::new_v4(),
+        });
+        let json_len = serde_json::to_vec(&*message).unwrap().len();
+        assert_eq!(encoded_len(&message), json_len);
+
+        let mut batcher =
+            MessageBatcher::new(100, Duration::from_millis(1000)).with_max_bytes(json_len * 2);
+        batcher.queue(message.clone().into());
+        assert!(!batcher.should_flush());
+        batcher.queue(message.into());
+        assert!(batcher.should_flush());
+
+        // Without a byte limit only the count and delay apply
+        let mut unlimited = MessageBatcher::new(100, Duration::from_millis(1000)).with_max_bytes(0);
+        unlimited.queue(room_frame(&[0u8; 4096]));
+        assert!(!unlimited.should_flush());
+    }
+
+    #[test]
+    fn test_latency_sensitive_messages_bypass_batching() {
+        let error = ServerMessage::Error(crate::protocol::ErrorDetail::new(
+            crate::protocol::ErrorCode::InvalidInput,
+            "bad input",
+        ));
+        assert!(bypasses_batching(&error));
+        assert!(bypasses_batching(&ServerMessage::AuthorityChanged {
+            authority_player: None,
+            you_are_authority: false,
+        }));
+        assert!(bypasses_batching(&ServerMessage::TimeSyncResult {
+            client_sent_at_ms: 1,
+            server_received_at_ms: 2,
+            server_sent_at_ms: 2,
+        }));
+        assert!(!bypasses_batching(&ServerMessage::PlayerLeft {
+            player_id: uuid::Uuid::new_v4(),
+        }));
+        assert!(!bypasses_batching(&ServerMessage::RoomLeft));
+    }
+
     #[test]
     fn test_message_batcher_empty_flush() {
-        let mut batcher = MessageBatcher::new(10, 16);
+        let mut batcher = MessageBatcher::new(10, Duration::from_millis(16));
 
         // Flush empty batcher
         let messages = batcher.flush();
@@ -213,14 +348,14 @@ mod tests {
         use std::thread;
         use std::time::Duration;
 
-        let mut batcher = MessageBatcher::new(10, 20);
+        let mut batcher = MessageBatcher::new(10, Duration::from_millis(20));
 
         // Add fewer messages than batch size
         for _ in 0..3 {
             let message = Arc::new(ServerMessage::PlayerLeft {
                 player_id: uuid::Uuid::new_v4(),
             });
-            batcher.queue(message);
+            batcher.queue(message.into());
         }
 
         assert_eq!(batcher.len(), 3);
@@ -1,187 +1,197 @@
+use crate::auth::AppInfo;
+use crate::broadcast::Outbound;
+use crate::config::{SdkCompatibilityConfig, SdkCompatibilityError, SdkCompatibilityReport};
+use crate::logging::{log_ws_event, WebSocketEvent};
 use crate::protocol::{
-    ClientMessage, ErrorCode, GameDataEncoding, PlayerNameRulesPayload, ProtocolInfoPayload,
-    RateLimitInfo, ServerMessage,
+    decode_client_frame, ClientMessage, DecodedClientFrame, ErrorCode, ErrorDetail, ErrorDetails,
+    GameDataEncoding, MessageEncoding, PlayerNameRulesPayload, ProtocolInfoPayload, RateLimitInfo,
+    ServerMessage,
 };
-use crate::server::{EnhancedGameServer, RegisterClientError};
+use crate::security::ClientCertificateFingerprint;
+use crate::server::{ConnectionSlot, EnhancedGameServer};
 use axum::extract::ws::{Message, WebSocket};
-use futures_util::{SinkExt, StreamExt};
-use std::net::SocketAddr;
+use futures_util::StreamExt;
 use std::sync::Arc;
-use std::time::Duration;
-use tokio::sync::mpsc;
 use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::{mpsc, Notify};
 use tokio::time::Instant;
 
-use super::batching::{send_batch, MessageBatcher};
-use super::sending::{send_immediate_server_message, send_single_message};
+use super::send_loop::{run_send_task, SendExit};
+use super::sending::ServerClose;
 use super::token_binding::{parse_client_message, TokenBindingHandshake};
 
+/// SDK platform and version a client named in its upgrade headers.
+#[derive(Debug, Clone, Default)]
+pub(super) struct ClientSdk {
+    pub platform: Option<String>,
+    pub version: Option<String>,
+}
+
 pub(super) async fn handle_socket(
     socket: WebSocket,
     server: Arc<EnhancedGameServer>,
-    addr: SocketAddr,
+    slot: ConnectionSlot,
     token_binding: Option<TokenBindingHandshake>,
+    client_fingerprint: Option<ClientCertificateFingerprint>,
+    upgrade_app_info: Option<AppInfo>,
+    client_sdk: Option<ClientSdk>,
+    message_encoding: MessageEncoding,
 ) {
-    let (mut sender, mut receiver) = socket.split();
-    let queue_capacity = server.config().websocket_config.batch_size.max(1) * 4;
-    let (tx, mut rx) = mpsc::channel::<Arc<ServerMessage>>(queue_capacity);
+    let accepted_at = Instant::now();
+    let addr = slot.client_addr();
+    let (sender, mut receiver) = socket.split();
+    let queue_capacity = server.config().client_queue_capacity();
+    let (tx, rx) = mpsc::channel::<Outbound>(queue_capacity);
 
     // Keep a clone of tx for sending auth responses
     let tx_clone = tx.clone();
 
     // Register client with server
-    let player_id = match server.register_client(tx, addr).await {
-        Ok(player_id) => {
-            tracing::info!(%player_id, client_addr = %addr, "WebSocket connection established");
-            player_id
-        }
-        Err(RegisterClientError::IpLimitExceeded { current, limit }) => {
-            let error_message = ServerMessage::Error {
-                message: format!("Too many connections from your IP ({current}/{limit})"),
-                error_code: Some(ErrorCode::TooManyConnections),
-            };
-            if let Err(err) = send_immediate_server_message(&mut sender, &error_message).await {
-                tracing::debug!(
-                    client_addr = %addr,
+    let player_id = server.register_reserved_client(tx, slot).await;
+    log_ws_event(&WebSocketEvent::ConnectionOpened {
+        player_id,
+        ip: addr.ip(),
+    });
+    if let Some(fingerprint) = &client_fingerprint {
+        server.set_client_fingerprint(&player_id, fingerprint);
+    }
+
+    // Recorded on drop, so abnormal disconnects are measured too
+    let _session_timer = SessionTimer {
+        metrics: server.metrics(),
+        accepted_at,
+    };
+
+    // An SDK named on the upgrade is checked before anything is sent
+    let sdk_config = &server.protocol_config().sdk_compatibility;
+    let mut compatibility = sdk_config.default_report();
+    let mut incompatible = None;
+    if let Some(sdk) = &client_sdk {
+        match sdk_config.evaluate(sdk.platform.as_deref(), sdk.version.as_deref()) {
+            Ok(report) => {
+                server.set_client_sdk_report(&player_id, &report);
+                compatibility = report;
+            }
+            Err(err) => {
+                tracing::warn!(
+                    %player_id,
+                    platform = ?sdk.platform,
+                    sdk_version = ?sdk.version,
                     error = %err,
-                    "Failed to send IP limit error frame"
+                    "SDK compatibility check failed on upgrade"
                 );
+                incompatible = Some(err);
             }
-            let _ = sender.close().await;
-            return;
         }
-    };
+    }
 
     // Track authentication state
     let mut authenticated = !server.config().auth_enabled; // Auto-authenticated if auth disabled
 
-    // Track connection time for authentication timeout
-    let connection_start = Instant::now();
-    let auth_timeout = Duration::from_secs(server.config().websocket_config.auth_timeout_secs);
+    // A verified bearer token or API key on the upgrade stands in for the Authenticate handshake
+    if let Some(info) = upgrade_app_info {
+        tracing::info!(%player_id, app_name = %info.name, "Client authenticated on upgrade");
+        server.set_client_app_info(&player_id, info.clone());
+        server.apply_app_bandwidth_policy(&info);
+        authenticated = true;
+    }
 
-    // Spawn task to handle outgoing messages
-    let server_clone = server.clone();
-    let player_id_clone = player_id;
-    let send_task = tokio::spawn(async move {
-        let config = server_clone.config();
-        let batching_enabled = config.websocket_config.enable_batching;
-        let batch_size = config.websocket_config.batch_size;
-        let batch_interval_ms = config.websocket_config.batch_interval_ms;
-
-        if batching_enabled {
-            // Batching mode: collect multiple messages and send together
-            let mut batcher = MessageBatcher::new(batch_size, batch_interval_ms);
-            let mut flush_interval =
-                tokio::time::interval(Duration::from_millis(batch_interval_ms));
-            flush_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
-
-            loop {
-                tokio::select! {
-                    // Receive new message from channel
-                    message_opt = rx.recv() => {
-                        if let Some(message) = message_opt {
-                            batcher.queue(message);
-
-                            // Flush if batch is full or time threshold exceeded
-                            if batcher.should_flush()
-                                && send_batch(
-                                    &mut sender,
-                                    &mut batcher,
-                                    &player_id_clone,
-                                    &server_clone,
-                                )
-                                    .await
-                                    .is_err()
-                            {
-                                break;
-                            }
-                        } else {
-                            // Channel closed, flush remaining messages and exit
-                            if !batcher.is_empty() {
-                                let _ = send_batch(
-                                    &mut sender,
-                                    &mut batcher,
-                                    &player_id_clone,
-                                    &server_clone,
-                                )
-                                .await;
-                            }
-                            break;
-                        }
-                    }
-                    // Periodic flush based on time interval
-                    _ = flush_interval.tick() => {
-                        if !batcher.is_empty()
-                            && batcher.should_flush()
-                            && send_batch(
-                                &mut sender,
-                                &mut batcher,
-                                &player_id_clone,
-                                &server_clone,
-                            )
-                                .await
-                                .is_err()
-                        {
-                            break;
-                        }
-                    }
-                }
-            }
+    // A refused client is held unauthenticated until the queued close goes out
+    if incompatible.is_some() {
+        authenticated = false;
+    }
+
+    // Connections that need no Authenticate learn the limits before their first message;
+    // the rest get them with `Authenticated`
+    if authenticated && server.protocol_config().send_protocol_info {
+        if let Err(err) = tx_clone.try_send(protocol_info(&server, &compatibility).into()) {
+            tracing::warn!(%player_id, error = %err, "Failed to enqueue protocol info");
         } else {
-            // Non-batching mode: send each message immediately (legacy behavior)
-            while let Some(message) = rx.recv().await {
-                if send_single_message(&mut sender, message, &player_id_clone, &server_clone)
-                    .await
-                    .is_err()
-                {
-                    break;
-                }
-            }
+            log_ws_event(&WebSocketEvent::MessageSent {
+                message_type: "ProtocolInfo",
+                player_id,
+            });
         }
+    }
 
-        // Cleanup when send task ends
-        server_clone.unregister_client(&player_id_clone).await;
-    });
+    // Track connection time for authentication timeout
+    let connection_start = Instant::now();
+    let auth_timeout = server
+        .config()
+        .websocket_config
+        .auth_timeout_secs
+        .as_duration();
+
+    // Server-initiated disconnects are closed by the send task, which owns the sink
+    let (close_tx, close_rx) = mpsc::channel::<ServerClose>(1);
+    if let Some(err) = incompatible {
+        reject_incompatible_client(&server, &tx_clone, &close_tx, player_id, &err);
+    } else {
+        // An operator announcement that has not expired reaches late joiners too
+        server.send_active_announcement(&player_id).await;
+    }
+
+    // Room broadcasts are serialized once per encoding and queued as shared frames
+    server
+        .subscribe_room_frames(player_id, message_encoding)
+        .await;
+
+    // A client whose queue overflows is shed rather than silently losing messages
+    let backpressure = Arc::new(Notify::new());
+    server
+        .watch_backpressure(player_id, Arc::clone(&backpressure))
+        .await;
+
+    // Spawn task to handle outgoing messages
+    let mut send_task = tokio::spawn(run_send_task(
+        sender,
+        rx,
+        close_rx,
+        backpressure,
+        server.clone(),
+        player_id,
+        message_encoding,
+    ));
 
     // Handle incoming messages
     let token_binding_for_receive = token_binding.clone();
     let server_clone = server.clone();
-    let auth_timeout_secs = server.config().websocket_config.auth_timeout_secs;
-    let receive_task = tokio::spawn(async move {
+    let auth_timeout_secs = auth_timeout.as_secs();
+    let mut receive_task = tokio::spawn(async move {
         let token_binding = token_binding_for_receive;
+        let request_close = |code: ErrorCode, message: String| {
+            // Capacity 1 and the loop exits right after, so this cannot fill up
+            let _ = close_tx.try_send(ServerClose { code, message });
+        };
         // Create authentication timeout timer
         let auth_deadline = tokio::time::sleep_until(connection_start + auth_timeout);
         tokio::pin!(auth_deadline);
+        let mut shutdown = server_clone.shutdown_watch();
 
-        loop {
-            let msg = if authenticated {
-                // If authenticated, no timeout needed
-                match receiver.next().await {
-                    Some(msg) => msg,
-                    None => break, // Connection closed
-                }
-            } else {
-                // If not authenticated, enforce timeout
-                tokio::select! {
-                    msg_opt = receiver.next() => {
-                        match msg_opt {
-                            Some(msg) => msg,
-                            None => break, // Connection closed
-                        }
-                    }
-                    () = &mut auth_deadline => {
-                        // Authentication timeout
-                        tracing::warn!(%player_id, timeout_secs = auth_timeout_secs, "Authentication timeout, closing connection");
-                        let _ = server_clone
-                            .send_error_to_player(
-                                &player_id,
-                                format!("Authentication timeout - must authenticate within {auth_timeout_secs} seconds"),
-                                Some(ErrorCode::AuthenticationTimeout),
-                            )
-                            .await;
-                        break;
+        let close_reason = loop {
+            let msg = tokio::select! {
+                msg_opt = receiver.next() => {
+                    match msg_opt {
+                        Some(msg) => msg,
+                        None => break CONNECTION_LOST,
                     }
                 }
+                // Only unauthenticated connections have a deadline
+                () = &mut auth_deadline, if !authenticated => {
+                    tracing::warn!(%player_id, timeout_secs = auth_timeout_secs, "Authentication timeout, closing connection");
+                    request_close(
+                        ErrorCode::AuthenticationTimeout,
+                        format!("Authentication timeout - must authenticate within {auth_timeout_secs} seconds"),
+                    );
+                    break "auth_timeout";
+                }
+                Ok(_) = shutdown.wait_for(|shutting_down| *shutting_down) => {
+                    request_close(
+                        ErrorCode::ServerShuttingDown,
+                        "Server is shutting down".to_string(),
+                    );
+                    break SERVER_SHUTDOWN;
+                }
             };
 
             // Process the message
@@ -189,36 +199,24 @@ pub(super) async fn handle_socket(
                 Ok(msg) => msg,
                 Err(e) => {
                     tracing::warn!(%player_id, "WebSocket error: {}", e);
-                    break;
+                    break CONNECTION_LOST;
                 }
             };
 
-            match msg {
+            let client_message = match msg {
                 Message::Text(text) => {
-                    // Check message size limit
-                    let max_size = server_clone.config().max_message_size;
-                    if text.len() > max_size {
-                        tracing::warn!(
-                            %player_id,
-                            size = text.len(),
-                            max = max_size,
-                            "Message exceeds size limit"
-                        );
-                        let _ = server_clone
-                            .send_error_to_player(
-                                &player_id,
-                                format!(
-                                    "Message too large ({} bytes, max {} bytes)",
-                                    text.len(),
-                                    max_size
-                                ),
-                                Some(ErrorCode::MessageTooLarge),
-                            )
-                            .await;
+                    if reject_oversized_frame(&server_clone, &player_id, text.len()).await {
                         continue;
                     }
 
-                    let client_message = match parse_client_message(&text, token_binding.as_ref()) {
+                    // Token-bound frames carry a proof only the general parser checks
+                    let heartbeat = match token_binding {
+                        None => parse_heartbeat_frame(&text).map(Ok),
+                        Some(_) => None,
+                    };
+                    match heartbeat
+                        .unwrap_or_else(|| parse_client_message(&text, token_binding.as_ref()))
+                    {
                         Ok(message) => message,
                         Err(err) => {
                             tracing::warn!(
@@ -226,266 +224,104 @@ pub(super) async fn handle_socket(
                                 error = %err,
                                 "Rejected client WebSocket frame"
                             );
+                            if err.should_disconnect() {
+                                request_close(err.error_code(), err.user_message().to_string());
+                                break "protocol_error";
+                            }
                             let _ = server_clone
                                 .send_error_to_player(
                                     &player_id,
-                                    err.user_message().to_string(),
-                                    Some(err.error_code()),
+                                    ErrorDetail::new(
+                                        err.error_code(),
+                                        err.user_message().to_string(),
+                                    ),
                                 )
                                 .await;
-                            if err.should_disconnect() {
-                                break;
-                            }
                             continue;
                         }
-                    };
-
-                    match client_message {
-                        ClientMessage::Authenticate {
-                            app_id,
-                            sdk_version,
-                            platform,
-                            game_data_format,
-                        } => {
-                            if authenticated {
-                                tracing::warn!(%player_id, "Client already authenticated");
-                                continue;
-                            }
-
-                            // Validate App ID
-                            match server_clone.auth_middleware.validate_app_id(&app_id).await {
-                                Ok(info) => {
-                                    let compatibility = match server_clone
-                                        .protocol_config()
-                                        .sdk_compatibility
-                                        .evaluate(platform.as_deref(), sdk_version.as_deref())
-                                    {
-                                        Ok(report) => report,
-                                        Err(err) => {
-                                            let error_message = err.to_string();
-                                            tracing::warn!(
-                                                %player_id,
-                                                app_id = %app_id,
-                                                ?sdk_version,
-                                                ?platform,
-                                                error = %error_message,
-                                                "SDK compatibility check failed"
-                                            );
-                                            if let Err(err) = tx_clone.try_send(Arc::new(
-                                                ServerMessage::AuthenticationError {
-                                                    error: error_message,
-                                                    error_code: ErrorCode::SdkVersionUnsupported,
-                                                },
-                                            )) {
-                                                if matches!(err, TrySendError::Full(_)) {
-                                                    server_clone
-                                                        .metrics()
-                                                        .increment_websocket_messages_dropped();
-                                                }
-                                                tracing::warn!(
-                                                    %player_id,
-                                                    error = %err,
-                                                    "Failed to enqueue SDK compatibility error"
-                                                );
-                                            }
-                                            continue;
-                                        }
-                                    };
-
-                                    authenticated = true;
-                                    server_clone.set_client_app_info(&player_id, info.clone());
-                                    server_clone.apply_app_bandwidth_policy(&info);
-                                    let supported_formats = server_clone
-                                        .protocol_config()
-                                        .supported_game_data_formats();
-                                    let negotiated_format = match game_data_format {
-                                        Some(format) if supported_formats.contains(&format) => {
-                                            format
-                                        }
-                                        Some(format) => {
-                                            let supported_list: Vec<String> = supported_formats
-                                                .iter()
-                                                .map(|f| format!("{f:?}"))
-                                                .collect();
-                                            let error_message = format!(
-                                                "Requested game data format {:?} is not supported. Server supports: {}. Falling back to JSON.",
-                                                format,
-                                                supported_list.join(", ")
-                                            );
-                                            tracing::warn!(
-                                                %player_id,
-                                                ?format,
-                                                ?supported_formats,
-                                                "Client requested unsupported game_data_format"
-                                            );
-                                            // Send error message to client about capability mismatch
-                                            if let Err(err) =
-                                                tx_clone.try_send(Arc::new(ServerMessage::Error {
-                                                    message: error_message,
-                                                    error_code: Some(
-                                                        ErrorCode::UnsupportedGameDataFormat,
-                                                    ),
-                                                }))
-                                            {
-                                                if matches!(err, TrySendError::Full(_)) {
-                                                    server_clone
-                                                        .metrics()
-                                                        .increment_websocket_messages_dropped();
-                                                }
-                                                tracing::warn!(
-                                                    %player_id,
-                                                    error = %err,
-                                                    "Failed to enqueue game data format error"
-                                                );
-                                            }
-                                            GameDataEncoding::Json
-                                        }
-                                        None => GameDataEncoding::Json,
-                                    };
-                                    server_clone
-                                        .set_client_game_data_format(&player_id, negotiated_format);
-                                    tracing::info!(
-                                        %player_id,
-                                        app_name = %info.name,
-                                        app_id = %app_id,
-                                        ?sdk_version,
-                                        ?platform,
-                                        "Client authenticated"
-                                    );
-
-                                    // Send success response
-                                    let auth_response = ServerMessage::Authenticated {
-                                        app_name: info.name.clone(),
-                                        organization: info.organization.clone(),
-                                        rate_limits: RateLimitInfo {
-                                            per_minute: info.rate_limits.per_minute,
-                                            per_hour: info.rate_limits.per_hour,
-                                            per_day: info.rate_limits.per_day,
-                                        },
-                                    };
-
-                                    let player_name_rules =
-                                        PlayerNameRulesPayload::from_protocol_config(
-                                            server_clone.protocol_config(),
-                                        );
-                                    let protocol_info =
-                                        ServerMessage::ProtocolInfo(ProtocolInfoPayload {
-                                            platform: compatibility.platform.clone(),
-                                            sdk_version: compatibility.sdk_version.clone(),
-                                            minimum_version: compatibility.minimum_version.clone(),
-                                            recommended_version: compatibility
-                                                .recommended_version
-                                                .clone(),
-                                            capabilities: compatibility.capabilities.clone(),
-                                            notes: compatibility.notes.clone(),
-                                            game_data_formats: supported_formats,
-                                            player_name_rules: Some(player_name_rules),
-                                        });
-
-                                    if let Err(err) = tx_clone.try_send(Arc::new(auth_response)) {
-                                        if matches!(err, TrySendError::Full(_)) {
-                                            server_clone
-                                                .metrics()
-                                                .increment_websocket_messages_dropped();
-                                        }
-                                        tracing::warn!(
-                                            %player_id,
-                                            error = %err,
-                                            "Failed to enqueue authentication success response"
-                                        );
-                                    }
-                                    if let Err(err) = tx_clone.try_send(Arc::new(protocol_info)) {
-                                        if matches!(err, TrySendError::Full(_)) {
-                                            server_clone
-                                                .metrics()
-                                                .increment_websocket_messages_dropped();
-                                        }
-                                        tracing::warn!(
-                                            %player_id,
-                                            error = %err,
-                                            "Failed to enqueue protocol info response"
-                                        );
-                                    }
-                                }
-                                Err(e) => {
-                                    tracing::warn!(%player_id, %app_id, "Authentication failed: {:?}", e);
-
-                                    // Send error response.
-                                    // The AppIdExpired, AppIdRevoked, and AppIdSuspended
-                                    // variants are not currently returned by
-                                    // `validate_app_id`, but are retained for future
-                                    // backend implementations (e.g., app status management
-                                    // or admin-controlled app suspension).
-                                    let error_code = match e {
-                                        crate::auth::AuthError::InvalidAppId => {
-                                            ErrorCode::InvalidAppId
-                                        }
-                                        crate::auth::AuthError::AppIdExpired => {
-                                            ErrorCode::AppIdExpired
-                                        }
-                                        crate::auth::AuthError::AppIdRevoked => {
-                                            ErrorCode::AppIdRevoked
-                                        }
-                                        crate::auth::AuthError::AppIdSuspended => {
-                                            ErrorCode::AppIdSuspended
-                                        }
-                                        crate::auth::AuthError::RateLimitExceeded => {
-                                            ErrorCode::RateLimitExceeded
-                                        }
-                                        _ => ErrorCode::InternalError,
-                                    };
-
-                                    let auth_error = Arc::new(ServerMessage::AuthenticationError {
-                                        error: format!("{e:?}"),
-                                        error_code,
-                                    });
-
-                                    if let Err(err) = tx_clone.try_send(auth_error) {
-                                        if matches!(err, TrySendError::Full(_)) {
-                                            server_clone
-                                                .metrics()
-                                                .increment_websocket_messages_dropped();
-                                        }
-                                        tracing::warn!(
-                                            %player_id,
-                                            error = %err,
-                                            "Failed to enqueue authentication failure response"
-                                        );
-                                    }
+                    }
+                }
+                Message::Binary(payload) if message_encoding == MessageEncoding::MsgPack => {
+                    if reject_oversized_frame(&server_clone, &player_id, payload.len()).await {
+                        continue;
+                    }
 
-                                    // Close connection after auth failure
-                                    break;
-                                }
-                            }
+                    match rmp_serde::from_slice::<ClientMessage>(&payload) {
+                        Ok(message) => message,
+                        Err(err) => {
+                            tracing::warn!(
+                                %player_id,
+                                error = %err,
+                                "Rejected malformed MessagePack frame"
+                            );
+                            let _ = server_clone
+                                .send_error_to_player(
+                                    &player_id,
+                                    ErrorDetail::new(
+                                        ErrorCode::InvalidInput,
+                                        format!("Invalid MessagePack message: {err}"),
+                                    ),
+                                )
+                                .await;
+                            continue;
                         }
-                        other => {
+                    }
+                }
+                Message::Binary(payload) if message_encoding == MessageEncoding::Rkyv => {
+                    if reject_oversized_frame(&server_clone, &player_id, payload.len()).await {
+                        continue;
+                    }
+
+                    // Validated before any field is read; relay payloads are not decoded
+                    match decode_client_frame(payload) {
+                        Ok(DecodedClientFrame::Message(message)) => message,
+                        Ok(DecodedClientFrame::GameData { encoding, payload }) => {
                             if !authenticated {
-                                tracing::warn!(%player_id, "Received message before authentication");
-                                let _ = server_clone
-                                    .send_error_to_player(
-                                        &player_id,
-                                        "Authentication required".to_string(),
-                                        Some(ErrorCode::MissingAppId),
-                                    )
-                                    .await;
-                                break;
+                                tracing::warn!(%player_id, "Received binary message before authentication");
+                                request_close(
+                                    ErrorCode::MissingAppId,
+                                    "Authentication required before sending binary data"
+                                        .to_string(),
+                                );
+                                break UNAUTHENTICATED;
                             }
-
-                            server_clone.handle_client_message(&player_id, other).await;
+                            server_clone
+                                .metrics()
+                                .increment_websocket_messages_received();
+                            log_ws_event(&WebSocketEvent::MessageReceived {
+                                message_type: "GameData",
+                                player_id,
+                                room_id: server_clone.get_client_room(&player_id).await,
+                            });
+                            server_clone
+                                .handle_game_data_binary(&player_id, encoding, payload)
+                                .await;
+                            continue;
+                        }
+                        Err(err) => {
+                            tracing::warn!(
+                                %player_id,
+                                error = %err,
+                                "Rejected malformed rkyv frame"
+                            );
+                            let _ = server_clone
+                                .send_error_to_player(
+                                    &player_id,
+                                    ErrorDetail::new(ErrorCode::InvalidInput, err.to_string()),
+                                )
+                                .await;
+                            continue;
                         }
                     }
                 }
                 Message::Binary(payload) => {
                     if !authenticated {
                         tracing::warn!(%player_id, "Received binary message before authentication");
-                        let _ = server_clone
-                            .send_error_to_player(
-                                &player_id,
-                                "Authentication required before sending binary data".to_string(),
-                                Some(ErrorCode::MissingAppId),
-                            )
-                            .await;
-                        break;
+                        request_close(
+                            ErrorCode::MissingAppId,
+                            "Authentication required before sending binary data".to_string(),
+                        );
+                        break UNAUTHENTICATED;
                     }
 
                     let encoding = server_clone.client_game_data_format(&player_id);
@@ -497,50 +333,467 @@ pub(super) async fn handle_socket(
                         let _ = server_clone
                             .send_error_to_player(
                                 &player_id,
-                                "Binary payloads are disabled for this connection".to_string(),
-                                Some(ErrorCode::InvalidInput),
+                                ErrorDetail::new(
+                                    ErrorCode::InvalidInput,
+                                    "Binary payloads are disabled for this connection",
+                                ),
                             )
                             .await;
                         continue;
                     }
 
+                    server_clone
+                        .metrics()
+                        .increment_websocket_messages_received();
+                    server_clone.metrics().record_message_received("GameData");
+                    log_ws_event(&WebSocketEvent::MessageReceived {
+                        message_type: "GameData",
+                        player_id,
+                        room_id: server_clone.get_client_room(&player_id).await,
+                    });
                     // Payload from axum WebSocket is already Bytes - pass directly for zero-copy
                     server_clone
                         .handle_game_data_binary(&player_id, encoding, payload)
                         .await;
+                    continue;
                 }
-                Message::Close(_) => {
-                    tracing::info!(%player_id, "WebSocket connection closed");
-                    break;
-                }
+                Message::Close(_) => break "client_closed",
                 Message::Pong(_) => {
                     // Handle pong as ping response
                     server_clone
                         .handle_client_message(&player_id, ClientMessage::Ping)
                         .await;
+                    continue;
                 }
                 _ => {
                     // Ignore other message types
+                    continue;
+                }
+            };
+
+            server_clone
+                .metrics()
+                .increment_websocket_messages_received();
+            server_clone
+                .metrics()
+                .record_message_received(client_message.message_type());
+            log_ws_event(&WebSocketEvent::MessageReceived {
+                message_type: client_message.message_type(),
+                player_id,
+                room_id: server_clone.get_client_room(&player_id).await,
+            });
+
+            match client_message {
+                ClientMessage::Authenticate {
+                    app_id,
+                    sdk_version,
+                    platform,
+                    game_data_format,
+                    capabilities,
+                } => {
+                    if authenticated {
+                        // Auth is disabled or was settled at the upgrade; still
+                        // honour client capabilities.
+                        server_clone.negotiate_client_capabilities(&player_id, &capabilities);
+                        tracing::warn!(%player_id, "Client already authenticated");
+                        continue;
+                    }
+
+                    // Validate App ID
+                    match server_clone.auth_middleware.validate_app_id(&app_id).await {
+                        Ok(info) => {
+                            // Fields left out of Authenticate fall back to the upgrade headers
+                            let upgrade_sdk = client_sdk.clone().unwrap_or_default();
+                            let platform = platform.or(upgrade_sdk.platform);
+                            let sdk_version = sdk_version.or(upgrade_sdk.version);
+                            let compatibility = match server_clone
+                                .protocol_config()
+                                .sdk_compatibility
+                                .evaluate(platform.as_deref(), sdk_version.as_deref())
+                            {
+                                Ok(report) => report,
+                                Err(err) => {
+                                    tracing::warn!(
+                                        %player_id,
+                                        app_id = %app_id,
+                                        ?sdk_version,
+                                        ?platform,
+                                        error = %err,
+                                        "SDK compatibility check failed"
+                                    );
+                                    reject_incompatible_client(
+                                        &server_clone,
+                                        &tx_clone,
+                                        &close_tx,
+                                        player_id,
+                                        &err,
+                                    );
+                                    break INCOMPATIBLE_CLIENT;
+                                }
+                            };
+
+                            authenticated = true;
+                            server_clone.set_client_sdk_report(&player_id, &compatibility);
+                            server_clone.set_client_app_info(&player_id, info.clone());
+                            server_clone.negotiate_client_capabilities(&player_id, &capabilities);
+                            server_clone.apply_app_bandwidth_policy(&info);
+                            let supported_formats =
+                                server_clone.protocol_config().supported_game_data_formats();
+                            let negotiated_format = match game_data_format {
+                                Some(format) if supported_formats.contains(&format) => format,
+                                Some(format) => {
+                                    let supported_list: Vec<String> = supported_formats
+                                        .iter()
+                                        .map(|f| format!("{f:?}"))
+                                        .collect();
+                                    let error_message = format!(
+                                        "Requested game data format {:?} is not supported. Server supports: {}. Falling back to JSON.",
+                                        format,
+                                        supported_list.join(", ")
+                                    );
+                                    tracing::warn!(
+                                        %player_id,
+                                        ?format,
+                                        ?supported_formats,
+                                        "Client requested unsupported game_data_format"
+                                    );
+                                    // Send error message to client about capability mismatch
+                                    if let Err(err) = tx_clone.try_send(
+                                        ServerMessage::Error(ErrorDetail::new(
+                                            ErrorCode::UnsupportedGameDataFormat,
+                                            error_message,
+                                        ))
+                                        .into(),
+                                    ) {
+                                        if matches!(err, TrySendError::Full(_)) {
+                                            server_clone
+                                                .metrics()
+                                                .increment_websocket_messages_dropped();
+                                        }
+                                        tracing::warn!(
+                                            %player_id,
+                                            error = %err,
+                                            "Failed to enqueue game data format error"
+                                        );
+                                    } else {
+                                        log_ws_event(&WebSocketEvent::MessageSent {
+                                            message_type: "Error",
+                                            player_id,
+                                        });
+                                    }
+                                    GameDataEncoding::Json
+                                }
+                                None => GameDataEncoding::Json,
+                            };
+                            server_clone.set_client_game_data_format(&player_id, negotiated_format);
+                            tracing::info!(
+                                %player_id,
+                                app_name = %info.name,
+                                app_id = %app_id,
+                                ?sdk_version,
+                                ?platform,
+                                "Client authenticated"
+                            );
+
+                            // Send success response
+                            let auth_response = ServerMessage::Authenticated {
+                                app_name: info.name.clone(),
+                                organization: info.organization.clone(),
+                                rate_limits: RateLimitInfo {
+                                    per_minute: info.rate_limits.per_minute,
+                                    per_hour: info.rate_limits.per_hour,
+                                    per_day: info.rate_limits.per_day,
+                                },
+                            };
+
+                            if let Err(err) = tx_clone.try_send(auth_response.into()) {
+                                if matches!(err, TrySendError::Full(_)) {
+                                    server_clone
+                                        .metrics()
+                                        .increment_websocket_messages_dropped();
+                                }
+                                tracing::warn!(
+                                    %player_id,
+                                    error = %err,
+                                    "Failed to enqueue authentication success response"
+                                );
+                            } else {
+                                log_ws_event(&WebSocketEvent::MessageSent {
+                                    message_type: "Authenticated",
+                                    player_id,
+                                });
+                            }
+                            if server_clone.protocol_config().send_protocol_info {
+                                let protocol_info = protocol_info(&server_clone, &compatibility);
+                                if let Err(err) = tx_clone.try_send(protocol_info.into()) {
+                                    if matches!(err, TrySendError::Full(_)) {
+                                        server_clone
+                                            .metrics()
+                                            .increment_websocket_messages_dropped();
+                                    }
+                                    tracing::warn!(
+                                        %player_id,
+                                        error = %err,
+                                        "Failed to enqueue protocol info response"
+                                    );
+                                } else {
+                                    log_ws_event(&WebSocketEvent::MessageSent {
+                                        message_type: "ProtocolInfo",
+                                        player_id,
+                                    });
+                                }
+                            }
+                        }
+                        Err(e) => {
+                            tracing::warn!(%player_id, %app_id, "Authentication failed: {:?}", e);
+
+                            // Send error response.
+                            // The AppIdExpired, AppIdRevoked, and AppIdSuspended
+                            // variants are not currently returned by
+                            // `validate_app_id`, but are retained for future
+                            // backend implementations (e.g., app status management
+                            // or admin-controlled app suspension).
+                            let error_code = match e {
+                                crate::auth::AuthError::InvalidAppId => ErrorCode::InvalidAppId,
+                                crate::auth::AuthError::AppIdExpired => ErrorCode::AppIdExpired,
+                                crate::auth::AuthError::AppIdRevoked => ErrorCode::AppIdRevoked,
+                                crate::auth::AuthError::AppIdSuspended => ErrorCode::AppIdSuspended,
+                                crate::auth::AuthError::RateLimitExceeded => {
+                                    ErrorCode::RateLimitExceeded
+                                }
+                                _ => ErrorCode::InternalError,
+                            };
+
+                            let auth_error = Arc::new(ServerMessage::AuthenticationError {
+                                error: format!("{e:?}"),
+                                error_code: error_code.clone(),
+                            });
+
+                            if let Err(err) = tx_clone.try_send(auth_error.into()) {
+                                if matches!(err, TrySendError::Full(_)) {
+                                    server_clone
+                                        .metrics()
+                                        .increment_websocket_messages_dropped();
+                                }
+                                tracing::warn!(
+                                    %player_id,
+                                    error = %err,
+                                    "Failed to enqueue authentication failure response"
+                                );
+                            } else {
+                                log_ws_event(&WebSocketEvent::MessageSent {
+                                    message_type: "AuthenticationError",
+                                    player_id,
+                                });
+                            }
+
+                            // Close connection after auth failure
+                            request_close(error_code, "Authentication failed".to_string());
+                            break "auth_failed";
+                        }
+                    }
+                }
+                other => {
+                    if !authenticated {
+                        tracing::warn!(%player_id, "Received message before authentication");
+                        request_close(
+                            ErrorCode::MissingAppId,
+                            "Authentication required".to_string(),
+                        );
+                        break UNAUTHENTICATED;
+                    }
+
+                    server_clone.handle_client_message(&player_id, other).await;
                 }
             }
-        }
+        };
 
         // Cleanup when receive task ends
+        if close_reason == CONNECTION_LOST {
+            server_clone
+                .metrics()
+                .increment_disconnects_detected_on_read();
+        }
         server_clone.unregister_client(&player_id).await;
+        close_reason
     });
 
     // Wait for either task to complete
-    tokio::select! {
-        _ = send_task => {
-            tracing::info!(%player_id, "Send task completed");
-        }
-        _ = receive_task => {
-            tracing::info!(%player_id, "Receive task completed");
+    let close_reason = tokio::select! {
+        exit = &mut send_task => {
+            // The client is gone; don't leave the read half waiting on a dead socket
+            receive_task.abort();
+            exit.map_or("send_task_failed", SendExit::close_reason)
         }
-    }
+        reason = &mut receive_task => reason.unwrap_or("receive_task_failed"),
+    };
 
     // Ensure cleanup
     server.unregister_client(&player_id).await;
+    log_ws_event(&WebSocketEvent::ConnectionClosed {
+        player_id,
+        reason: close_reason,
+    });
+}
+
+/// Close reason for a read half that ended without a close handshake.
+const CONNECTION_LOST: &str = "connection_lost";
+/// Close reason for a connection closed because the server is shutting down.
+const SERVER_SHUTDOWN: &str = "server_shutdown";
+/// Close reason for a client that sent messages before authenticating.
+const UNAUTHENTICATED: &str = "unauthenticated";
+/// Close reason for a client whose SDK failed the compatibility check.
+const INCOMPATIBLE_CLIENT: &str = "incompatible_client";
+
+/// The `IncompatibleClient` notice for `err`, with the versions to upgrade to.
+fn incompatible_client(
+    config: &SdkCompatibilityConfig,
+    err: &SdkCompatibilityError,
+) -> ServerMessage {
+    let platform = err.platform();
+    ServerMessage::IncompatibleClient {
+        reason: err.to_string(),
+        min_version: platform
+            .and_then(|p| config.minimum_version(p))
+            .map(ToString::to_string),
+        recommended_version: platform
+            .and_then(|p| config.recommended_version(p))
+            .map(ToString::to_string),
+    }
+}
+
+/// Tell the client why its SDK was refused, then have the send task close the
+/// connection with `SDK_VERSION_UNSUPPORTED` once the notice is written.
+fn reject_incompatible_client(
+    server: &EnhancedGameServer,
+    tx: &mpsc::Sender<Outbound>,
+    close_tx: &mpsc::Sender<ServerClose>,
+    player_id: crate::protocol::PlayerId,
+    err: &SdkCompatibilityError,
+) {
+    let notice = incompatible_client(&server.protocol_config().sdk_compatibility, err);
+    match tx.try_send(notice.into()) {
+        Ok(()) => log_ws_event(&WebSocketEvent::MessageSent {
+            message_type: "IncompatibleClient",
+            player_id,
+        }),
+        Err(err) => {
+            if matches!(err, TrySendError::Full(_)) {
+                server.metrics().increment_websocket_messages_dropped();
+            }
+            tracing::warn!(%player_id, error = %err, "Failed to enqueue incompatible client notice");
+        }
+    }
+    // Capacity 1 and nothing else has closed yet, so this cannot fill up
+    let _ = close_tx.try_send(ServerClose {
+        code: ErrorCode::SdkVersionUnsupported,
+        message: err.to_string(),
+    });
+}
+
+/// The `ProtocolInfo` handshake: SDK compatibility plus the limits clients
+/// would otherwise hardcode.
+fn protocol_info(
+    server: &EnhancedGameServer,
+    compatibility: &SdkCompatibilityReport,
+) -> ServerMessage {
+    let config = server.protocol_config();
+    ServerMessage::ProtocolInfo(ProtocolInfoPayload {
+        platform: compatibility.platform.clone(),
+        sdk_version: compatibility.sdk_version.clone(),
+        minimum_version: compatibility.minimum_version.clone(),
+        recommended_version: compatibility.recommended_version.clone(),
+        capabilities: server.advertised_capabilities(&compatibility.capabilities),
+        notes: compatibility.notes.clone(),
+        game_data_formats: config.supported_game_data_formats(),
+        player_name_rules: Some(PlayerNameRulesPayload::from_protocol_config(config)),
+        room_code_length: config.room_code_length,
+        max_players: config.max_players_limit,
+    })
+}
+
+/// Records the session duration into the metrics when dropped.
+struct SessionTimer {
+    metrics: Arc<crate::metrics::ServerMetrics>,
+    accepted_at: Instant,
+}
+
+impl Drop for SessionTimer {
+    fn drop(&mut self) {
+        self.metrics
+            .record_session_duration(self.accepted_at.elapsed());
+    }
+}
+
+/// Longest text frame tried as a heartbeat before the general parser.
+const HEARTBEAT_FRAME_MAX_LEN: usize = 256;
+
+/// Decode a `Ping` or `Heartbeat` frame without the general `ClientMessage`
+/// parser, which buffers the whole frame to find its tag.
+///
+/// Only frames starting the way clients serialize them are tried. Anything
+/// else, including a heartbeat that does not decode here, returns `None` and
+/// goes through the general parser, which reports the error.
+fn parse_heartbeat_frame(text: &str) -> Option<ClientMessage> {
+    #[derive(serde::Deserialize)]
+    struct HeartbeatFrame {
+        data: HeartbeatData,
+    }
+
+    #[derive(serde::Deserialize)]
+    struct HeartbeatData {
+        client_timestamp: u64,
+        #[serde(default)]
+        last_ack: Option<crate::protocol::HeartbeatEcho>,
+    }
+
+    if text.len() > HEARTBEAT_FRAME_MAX_LEN {
+        return None;
+    }
+    let text = text.trim();
+    if text == r#"{"type":"Ping"}"# {
+        return Some(ClientMessage::Ping);
+    }
+    if !text.starts_with(r#"{"type":"Heartbeat","data":{"#) {
+        return None;
+    }
+    let frame: HeartbeatFrame = serde_json::from_str(text).ok()?;
+    Some(ClientMessage::Heartbeat {
+        client_timestamp: frame.data.client_timestamp,
+        last_ack: frame.data.last_ack,
+    })
+}
+
+/// Reject frames over `max_message_size`, telling the client why. Returns true
+/// when the frame was rejected.
+async fn reject_oversized_frame(
+    server: &EnhancedGameServer,
+    player_id: &crate::protocol::PlayerId,
+    size: usize,
+) -> bool {
+    let max_size = server.config().max_message_size;
+    if size <= max_size {
+        return false;
+    }
+
+    tracing::warn!(
+        %player_id,
+        size,
+        max = max_size,
+        "Message exceeds size limit"
+    );
+    let _ = server
+        .send_error_to_player(
+            player_id,
+            ErrorDetail::new(
+                ErrorCode::MessageTooLarge,
+                format!("Message too large ({size} bytes, max {max_size} bytes)"),
+            )
+            .with_context(serde_json::json!({ "size": size, "max_size": max_size }))
+            .with_details(ErrorDetails::MessageTooLarge { size, max_size }),
+        )
+        .await;
+    true
 }
 
 #[cfg(test)]
@@ -549,9 +802,61 @@ mod tests {
     use crate::database::DatabaseConfig;
     use crate::protocol::{ClientMessage, ServerMessage};
     use crate::server::ServerConfig;
+    use futures_util::SinkExt;
     use std::net::SocketAddr;
     use tokio_tungstenite::{connect_async, tungstenite::Message as TungsteniteMessage};
 
+    #[test]
+    fn heartbeat_frames_are_decoded_without_the_general_parser() {
+        assert!(matches!(
+            parse_heartbeat_frame(r#"{"type":"Ping"}"#),
+            Some(ClientMessage::Ping)
+        ));
+
+        let heartbeat = ClientMessage::Heartbeat {
+            client_timestamp: 1_718_000_000_000,
+            last_ack: Some(crate::protocol::HeartbeatEcho {
+                server_timestamp: 1_718_000_000_012,
+                held_ms: 4_980,
+            }),
+        };
+        let frame = serde_json::to_string(&heartbeat).unwrap();
+        match parse_heartbeat_frame(&frame) {
+            Some(ClientMessage::Heartbeat {
+                client_timestamp,
+                last_ack,
+            }) => {
+                assert_eq!(client_timestamp, 1_718_000_000_000);
+                assert_eq!(
+                    last_ack.map(|ack| (ack.server_timestamp, ack.held_ms)),
+                    Some((1_718_000_000_012, 4_980))
+                );
+            }
+            other => panic!("expected Heartbeat, got {other:?}"),
+        }
+        assert!(matches!(
+            parse_heartbeat_frame(r#"{"type":"Heartbeat","data":{"client_timestamp":7}}"#),
+            Some(ClientMessage::Heartbeat {
+                client_timestamp: 7,
+                last_ack: None
+            })
+        ));
+
+        // Everything else is left to the general parser
+        for frame in [
+            r#"{ "type": "Ping" }"#,
+            r#"{"data":{"client_timestamp":7},"type":"Heartbeat"}"#,
+            r#"{"type":"Heartbeat","data":{"client_timestamp":"soon"}}"#,
+            r#"{"type":"GameData","data":{"data":{"type":"Heartbeat"}}}"#,
+        ] {
+            assert!(parse_heartbeat_frame(frame).is_none(), "{frame}");
+        }
+        assert!(matches!(
+            serde_json::from_str::<ClientMessage>(r#"{ "type": "Ping" }"#),
+            Ok(ClientMessage::Ping)
+        ));
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn test_websocket_connection() {
         // Add overall test timeout to prevent infinite hanging
@@ -646,6 +951,18 @@ mod tests {
             };
         let (mut ws_sender, mut ws_receiver) = ws_stream.split();
 
+        // The ProtocolInfo handshake arrives before any reply
+        match tokio::time::timeout(tokio::time::Duration::from_secs(5), ws_receiver.next()).await {
+            Ok(Some(Ok(TungsteniteMessage::Text(text)))) => assert!(matches!(
+                serde_json::from_str::<ServerMessage>(&text),
+                Ok(ServerMessage::ProtocolInfo(_))
+            )),
+            _ => {
+                tracing::error!("No ProtocolInfo handshake received");
+                return;
+            }
+        }
+
         // Send join room message
         let join_message = ClientMessage::JoinRoom {
             game_name: "test_game".to_string(),
@@ -654,6 +971,10 @@ mod tests {
             max_players: Some(4),
             supports_authority: Some(true),
             relay_transport: None,
+            relay_type: None,
+            spectator_chat_mode: None,
+            idempotency_key: None,
+            allow_overflow_joins: None,
         };
 
         let json_message = match serde_json::to_string(&join_message) {
@@ -0,0 +1,76 @@
+use crate::room_events::RoomEvent;
+use crate::server::EnhancedGameServer;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Query, State};
+use axum::http::HeaderMap;
+use axum::response::Response;
+use serde::Deserialize;
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::broadcast::Receiver;
+
+use super::admin::{admin_error, enforce_admin_auth};
+
+/// Filters for `GET /v2/events`.
+#[derive(Debug, Deserialize)]
+pub struct RoomEventsQuery {
+    /// Only stream events for rooms of this game
+    game_name: Option<String>,
+}
+
+/// `GET /v2/events`: stream room lifecycle events over a WebSocket, one JSON
+/// text frame per event. Requires the admin (or metrics) bearer token.
+///
+/// An observer that cannot keep up loses the oldest events and receives
+/// `{"type":"events_dropped","count":N}` in their place; the count covers
+/// all games, not just `game_name`.
+pub async fn room_events_handler(
+    ws: WebSocketUpgrade,
+    headers: HeaderMap,
+    State(server): State<Arc<EnhancedGameServer>>,
+    Query(query): Query<RoomEventsQuery>,
+) -> Response {
+    if let Err((status, error, message)) = enforce_admin_auth(&headers, server.as_ref()) {
+        return admin_error(status, error, message);
+    }
+
+    let events = server.subscribe_room_events();
+    ws.on_upgrade(move |socket| stream_room_events(socket, events, query.game_name))
+}
+
+async fn stream_room_events(
+    mut socket: WebSocket,
+    mut events: Receiver<Arc<RoomEvent>>,
+    game_name: Option<String>,
+) {
+    loop {
+        let frame = tokio::select! {
+            received = events.recv() => match received {
+                Ok(event) => {
+                    if game_name.as_deref().is_some_and(|game| game != event.game_name) {
+                        continue;
+                    }
+                    match serde_json::to_string(event.as_ref()) {
+                        Ok(json) => json,
+                        Err(e) => {
+                            tracing::error!(error = %e, "Failed to serialize room event");
+                            continue;
+                        }
+                    }
+                }
+                Err(RecvError::Lagged(count)) => {
+                    serde_json::json!({ "type": "events_dropped", "count": count }).to_string()
+                }
+                Err(RecvError::Closed) => break,
+            },
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_)) | Err(_)) | None => break,
+                Some(Ok(_)) => continue,
+            },
+        };
+
+        if socket.send(Message::Text(frame.into())).await.is_err() {
+            break;
+        }
+    }
+}
@@ -1,23 +1,78 @@
+use crate::auth::AuthError;
+use crate::protocol::{ErrorCode, MessageEncoding};
 use crate::security::ClientCertificateFingerprint;
 use crate::server::EnhancedGameServer;
 use axum::extract::ws::WebSocketUpgrade;
-use axum::extract::{ConnectInfo, Extension, State};
-use axum::http::HeaderMap;
-use axum::response::Response;
+use axum::extract::{ConnectInfo, Extension, RawQuery, State};
+use axum::http::header::{AUTHORIZATION, RETRY_AFTER};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Json, Response};
 use std::net::SocketAddr;
 use std::sync::Arc;
 
-use super::connection::handle_socket;
+use super::connection::{handle_socket, ClientSdk};
 use super::token_binding::{client_requested_subprotocol, negotiate_token_binding};
 
 /// WebSocket handler for the game protocol
 pub async fn websocket_handler(
     ws: WebSocketUpgrade,
-    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
     State(server): State<Arc<EnhancedGameServer>>,
+    RawQuery(query): RawQuery,
     headers: HeaderMap,
     fingerprint: Option<Extension<ClientCertificateFingerprint>>,
 ) -> Response {
+    // Behind a trusted proxy the client is identified by the forwarding headers.
+    let addr = server.config().trusted_proxies.client_addr(peer, &headers);
+    if addr != peer {
+        tracing::debug!(client_addr = %addr, %peer, "Resolved client address from forwarding headers");
+    }
+
+    // Churning connections is refused before it costs a slot or an upgrade.
+    if let Err(err) = server.check_connection_rate(addr) {
+        tracing::warn!(client_addr = %addr, error = %err, "Rejected WebSocket upgrade");
+        return (
+            StatusCode::TOO_MANY_REQUESTS,
+            [(RETRY_AFTER, err.retry_after_secs().to_string())],
+            err.to_string(),
+        )
+            .into_response();
+    }
+
+    // Held until the client registers; every early return below gives it back.
+    let slot = match server.reserve_connection_slot(addr) {
+        Ok(slot) => slot,
+        Err(err) => {
+            tracing::warn!(client_addr = %addr, error = %err, "Rejected WebSocket upgrade");
+            return (StatusCode::TOO_MANY_REQUESTS, err.to_string()).into_response();
+        }
+    };
+
+    let upgrade_auth = match api_key(&headers) {
+        Some(key) if server.auth_middleware.api_keys_enabled() => {
+            Some(server.auth_middleware.validate_api_key(key).await)
+        }
+        _ if server.auth_middleware.jwt_enabled() => {
+            match bearer_token(&headers).or_else(|| query_token(query.as_deref())) {
+                Some(token) => Some(server.auth_middleware.validate_bearer_token(token).await),
+                None if server.auth_middleware.jwt_required() => Some(Err(AuthError::MissingToken)),
+                // Mixed rollout: tokenless clients use the Authenticate handshake
+                None => None,
+            }
+        }
+        _ => None,
+    };
+    let upgrade_app_info = match upgrade_auth {
+        Some(Ok(info)) => Some(info),
+        Some(Err(err)) => {
+            tracing::warn!(client_addr = %addr, error = %err, "Rejected WebSocket upgrade");
+            return upgrade_auth_rejection(&err);
+        }
+        None => None,
+    };
+
+    let client_sdk = sdk_headers(&headers);
+
     let token_binding_cfg = server.token_binding_config().clone();
     let client_offered_binding =
         client_requested_subprotocol(&headers, &token_binding_cfg.subprotocol);
@@ -33,11 +88,116 @@ pub async fn websocket_handler(
         Err(response) => return response,
     };
 
-    let upgrade = if token_binding_cfg.enabled && client_offered_binding {
+    // Token binding signs JSON envelopes, so it wins when both are offered.
+    // Between the binary encodings, rkyv is preferred.
+    let binding_selected = token_binding_cfg.enabled && client_offered_binding;
+    let websocket_config = &server.config().websocket_config;
+    // The subprotocol echoed back must be the one the client offered
+    let msgpack_subprotocol = [
+        MessageEncoding::MSGPACK_SUBPROTOCOL,
+        MessageEncoding::MESSAGEPACK_SUBPROTOCOL,
+    ]
+    .into_iter()
+    .find(|protocol| client_requested_subprotocol(&headers, protocol));
+    let encoding = if binding_selected {
+        MessageEncoding::Json
+    } else if websocket_config.allow_rkyv
+        && client_requested_subprotocol(&headers, MessageEncoding::RKYV_SUBPROTOCOL)
+    {
+        MessageEncoding::Rkyv
+    } else if websocket_config.allow_msgpack && msgpack_subprotocol.is_some() {
+        MessageEncoding::MsgPack
+    } else {
+        MessageEncoding::Json
+    };
+
+    let upgrade = if binding_selected {
         ws.protocols([token_binding_cfg.subprotocol])
     } else {
-        ws
+        match encoding {
+            MessageEncoding::MsgPack => ws.protocols(msgpack_subprotocol),
+            MessageEncoding::Rkyv => ws.protocols([MessageEncoding::RKYV_SUBPROTOCOL]),
+            MessageEncoding::Json => ws,
+        }
+    };
+
+    upgrade.on_upgrade(move |socket| {
+        handle_socket(
+            socket,
+            server,
+            slot,
+            binding_session,
+            client_fingerprint,
+            upgrade_app_info,
+            client_sdk,
+            encoding,
+        )
+    })
+}
+
+/// Name of the header carrying a static API key.
+const API_KEY_HEADER: &str = "x-api-key";
+
+/// Extract a non-empty `X-Api-Key` header value.
+fn api_key(headers: &HeaderMap) -> Option<&str> {
+    let key = headers.get(API_KEY_HEADER)?.to_str().ok()?.trim();
+    (!key.is_empty()).then_some(key)
+}
+
+/// Header naming the client's SDK platform, e.g. `Unity`.
+const SDK_PLATFORM_HEADER: &str = "x-sdk-platform";
+/// Header carrying the client's SDK version, e.g. `1.12.0`.
+const SDK_VERSION_HEADER: &str = "x-sdk-version";
+
+/// The SDK named by the `X-SDK-Platform`/`X-SDK-Version` headers, if either is sent.
+fn sdk_headers(headers: &HeaderMap) -> Option<ClientSdk> {
+    let header = |name: &str| {
+        let value = headers.get(name)?.to_str().ok()?.trim();
+        (!value.is_empty()).then(|| value.to_string())
+    };
+    let sdk = ClientSdk {
+        platform: header(SDK_PLATFORM_HEADER),
+        version: header(SDK_VERSION_HEADER),
     };
+    (sdk.platform.is_some() || sdk.version.is_some()).then_some(sdk)
+}
+
+/// Extract a non-empty `token` query parameter, as sent by browser clients
+/// that cannot set headers on the upgrade request.
+fn query_token(query: Option<&str>) -> Option<&str> {
+    query?
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(name, _)| *name == "token")
+        .map(|(_, token)| token.trim())
+        .filter(|token| !token.is_empty())
+}
+
+/// JSON body for an upgrade refused by credential checks: `401`, or `429`
+/// when the application is over its rate limit.
+fn upgrade_auth_rejection(err: &AuthError) -> Response {
+    let (status, code) = match err {
+        AuthError::RateLimitExceeded => {
+            (StatusCode::TOO_MANY_REQUESTS, ErrorCode::RateLimitExceeded)
+        }
+        AuthError::MissingToken => (StatusCode::UNAUTHORIZED, ErrorCode::AuthenticationRequired),
+        AuthError::InvalidToken(_) | AuthError::TokenExpired => {
+            (StatusCode::UNAUTHORIZED, ErrorCode::InvalidToken)
+        }
+        AuthError::InvalidAppId => (StatusCode::UNAUTHORIZED, ErrorCode::InvalidAppId),
+        _ => (StatusCode::UNAUTHORIZED, ErrorCode::Unauthorized),
+    };
+    let body = serde_json::json!({
+        "error": code.close_reason(),
+        "message": err.to_string(),
+    });
+    (status, Json(body)).into_response()
+}
 
-    upgrade.on_upgrade(move |socket| handle_socket(socket, server, addr, binding_session))
+/// Extract the token from an `Authorization: Bearer <token>` header.
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    let value = headers.get(AUTHORIZATION)?.to_str().ok()?;
+    let (scheme, token) = value.trim().split_once(' ')?;
+    let token = token.trim();
+    (scheme.eq_ignore_ascii_case("bearer") && !token.is_empty()).then_some(token)
 }
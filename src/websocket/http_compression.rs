@@ -0,0 +1,123 @@
+//! gzip for the metrics and admin HTTP responses.
+//!
+//! [`build_router`](super::build_router) wraps only the metrics and admin
+//! routes, so WebSocket upgrades (`/v2/ws`, `/v2/events`) and health probes
+//! never pass through it. Bodies are buffered and compressed in one pass; the
+//! dashboard cache stores metrics data rather than rendered bodies, so there
+//! is no cached response to compress ahead of time.
+
+use crate::config::HttpCompressionConfig;
+use axum::body::Body;
+use axum::extract::{Request, State};
+use axum::http::header::{ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_LENGTH, VARY};
+use axum::http::{HeaderMap, HeaderValue, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::Router;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::io::Write;
+
+/// Compress `router`'s responses when `config` enables it.
+pub(super) fn compress_responses<S>(router: Router<S>, config: &HttpCompressionConfig) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    if !config.enabled {
+        return router;
+    }
+    router.layer(middleware::from_fn_with_state(
+        config.min_size.as_usize(),
+        gzip_response,
+    ))
+}
+
+async fn gzip_response(State(min_size): State<usize>, request: Request, next: Next) -> Response {
+    let accepts_gzip = accepts_gzip(request.headers());
+    let mut response = next.run(request).await;
+    // The body depends on Accept-Encoding even when this response is not compressed.
+    response
+        .headers_mut()
+        .append(VARY, HeaderValue::from_static("accept-encoding"));
+    if !accepts_gzip || response.headers().contains_key(CONTENT_ENCODING) {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            tracing::warn!(error = %err, "Failed to buffer response for compression");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    if bytes.len() < min_size {
+        return Response::from_parts(parts, Body::from(bytes));
+    }
+    match gzip(&bytes) {
+        Ok(compressed) => {
+            parts
+                .headers
+                .insert(CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+            parts.headers.remove(CONTENT_LENGTH);
+            Response::from_parts(parts, Body::from(compressed))
+        }
+        Err(err) => {
+            tracing::warn!(error = %err, "Failed to gzip response, sending it uncompressed");
+            Response::from_parts(parts, Body::from(bytes))
+        }
+    }
+}
+
+fn gzip(bytes: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::with_capacity(bytes.len() / 4), Compression::fast());
+    encoder.write_all(bytes)?;
+    encoder.finish()
+}
+
+/// Whether `Accept-Encoding` allows gzip. An explicit `gzip` entry takes
+/// precedence over `*`, and `q=0` refuses the coding.
+fn accepts_gzip(headers: &HeaderMap) -> bool {
+    let mut gzip_quality = None;
+    let mut any_quality = None;
+    let entries = headers
+        .get_all(ACCEPT_ENCODING)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','));
+    for entry in entries {
+        let mut params = entry.split(';');
+        let coding = params.next().unwrap_or_default().trim();
+        let quality = params
+            .find_map(|param| param.trim().strip_prefix("q="))
+            .map_or(1.0, |q| q.trim().parse::<f32>().unwrap_or(0.0));
+        if coding.eq_ignore_ascii_case("gzip") || coding.eq_ignore_ascii_case("x-gzip") {
+            gzip_quality = Some(quality);
+        } else if coding == "*" {
+            any_quality = Some(quality);
+        }
+    }
+    gzip_quality.or(any_quality).is_some_and(|q| q > 0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accept(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(ACCEPT_ENCODING, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_accepts_gzip_negotiation() {
+        assert!(!accepts_gzip(&HeaderMap::new()));
+        for accepted in ["gzip", "br, GZIP", "gzip;q=0.5", "deflate, *", "x-gzip"] {
+            assert!(accepts_gzip(&accept(accepted)), "{accepted} allows gzip");
+        }
+        for refused in ["br", "identity", "gzip;q=0", "gzip; q=0.0, *", "*;q=0"] {
+            assert!(!accepts_gzip(&accept(refused)), "{refused} refuses gzip");
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! TCP keepalive on accepted connections.
+//!
+//! A client whose network vanishes never sends a FIN, and writes to it keep
+//! landing in the kernel buffer until TCP gives up. Keepalive probes let the
+//! kernel notice such half-open connections while the server is idle, which
+//! surfaces as a read or write error on the socket.
+
+use crate::config::{ConfigDuration, WebSocketConfig};
+use socket2::{SockRef, TcpKeepalive};
+use tokio::net::TcpStream;
+
+/// Keepalive parameters from `websocket.tcp_keepalive_*`, or `None` when disabled.
+pub(super) fn tcp_keepalive(config: &WebSocketConfig) -> Option<TcpKeepalive> {
+    if config.tcp_keepalive_secs == ConfigDuration::ZERO {
+        return None;
+    }
+    let keepalive = TcpKeepalive::new().with_time(config.tcp_keepalive_secs.into());
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "windows",
+    ))]
+    let keepalive = keepalive
+        .with_interval(config.tcp_keepalive_interval_secs.into())
+        .with_retries(config.tcp_keepalive_retries);
+    Some(keepalive)
+}
+
+/// Enable keepalive on an accepted stream. Failures are logged, not fatal.
+pub(super) fn apply_tcp_keepalive(stream: &TcpStream, keepalive: &TcpKeepalive) {
+    if let Err(err) = SockRef::from(stream).set_tcp_keepalive(keepalive) {
+        tracing::debug!(error = %err, "Failed to enable TCP keepalive on accepted connection");
+    }
+}
+
+/// Applies TCP keepalive before the TLS handshake.
+#[cfg(feature = "tls")]
+#[derive(Debug, Clone)]
+pub(super) struct KeepaliveAcceptor(pub Option<TcpKeepalive>);
+
+#[cfg(feature = "tls")]
+impl<S> axum_server::accept::Accept<TcpStream, S> for KeepaliveAcceptor {
+    type Stream = TcpStream;
+    type Service = S;
+    type Future = std::future::Ready<std::io::Result<(TcpStream, S)>>;
+
+    fn accept(&self, stream: TcpStream, service: S) -> Self::Future {
+        if let Some(keepalive) = &self.0 {
+            apply_tcp_keepalive(&stream, keepalive);
+        }
+        std::future::ready(Ok((stream, service)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn zero_idle_time_disables_keepalive() {
+        let config = WebSocketConfig {
+            tcp_keepalive_secs: ConfigDuration::ZERO,
+            ..WebSocketConfig::default()
+        };
+        assert!(tcp_keepalive(&config).is_none());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn accepted_streams_get_the_configured_probe_schedule() {
+        let config = WebSocketConfig {
+            tcp_keepalive_secs: ConfigDuration::from_secs(7),
+            tcp_keepalive_interval_secs: ConfigDuration::from_secs(2),
+            tcp_keepalive_retries: 4,
+            ..WebSocketConfig::default()
+        };
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let _client = TcpStream::connect(addr).await.unwrap();
+        let (stream, _) = listener.accept().await.unwrap();
+
+        apply_tcp_keepalive(&stream, &tcp_keepalive(&config).unwrap());
+
+        let socket = SockRef::from(&stream);
+        assert!(socket.keepalive().unwrap());
+        assert_eq!(socket.tcp_keepalive_time().unwrap(), Duration::from_secs(7));
+        assert_eq!(
+            socket.tcp_keepalive_interval().unwrap(),
+            Duration::from_secs(2)
+        );
+        assert_eq!(socket.tcp_keepalive_retries().unwrap(), 4);
+    }
+}
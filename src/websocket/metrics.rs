@@ -1,10 +1,13 @@
-use crate::server::EnhancedGameServer;
+use crate::config::{ConfigDuration, DashboardHistoryField};
+use crate::server::{EnhancedGameServer, ServerStats};
 use axum::extract::State;
 use axum::http::header::AUTHORIZATION;
 use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Json, Response};
 use std::sync::Arc;
 
-use super::prometheus::render_prometheus_metrics;
+use super::admin::admin_error;
+use super::prometheus::render_server_metrics;
 
 async fn enforce_metrics_auth(
     headers: &HeaderMap,
@@ -95,6 +98,7 @@ pub async fn metrics_handler(
         "gamePercentiles": game_percentiles,
         "activeRooms": active_rooms,
         "timestamp": now.to_rfc3339(),
+        "draining": server.is_draining(),
         "dashboardCache": {
             "fetchedAt": cache_fetched_at,
             "ageSeconds": cache_age_seconds,
@@ -118,7 +122,15 @@ pub async fn metrics_handler(
                 "queries": metrics_snapshot.performance.query_count,
                 "room_creation_latency": metrics_snapshot.performance.room_creation_latency,
                 "room_join_latency": metrics_snapshot.performance.room_join_latency,
-                "query_latency": metrics_snapshot.performance.query_latency
+                "query_latency": metrics_snapshot.performance.query_latency,
+                "join_interceptor_latency": metrics_snapshot.performance.join_interceptor_latency,
+                "latency_ping_processing": metrics_snapshot.performance.latency_ping_processing
+            },
+            "reconnection": {
+                "tokensIssued": metrics_snapshot.reconnection.tokens_issued,
+                "sessionsActive": metrics_snapshot.reconnection.sessions_active,
+                "completions": metrics_snapshot.reconnection.completions,
+                "byGame": metrics_snapshot.reconnection_by_game
             },
             "errors": {
                 "internal": metrics_snapshot.errors.internal_errors,
@@ -150,6 +162,23 @@ pub async fn metrics_handler(
         }
     });
 
+    match server.app_quota_usage().await {
+        Ok(usage) => {
+            if let (Some(obj), Ok(value)) = (response.as_object_mut(), serde_json::to_value(usage))
+            {
+                obj.insert("applicationQuotas".to_string(), value);
+            }
+        }
+        Err(e) => tracing::warn!("Failed to collect app quota usage: {}", e),
+    }
+
+    if let Some(build_info) = server.build_info() {
+        if let (Some(obj), Ok(value)) = (response.as_object_mut(), serde_json::to_value(build_info))
+        {
+            obj.insert("buildInfo".to_string(), value);
+        }
+    }
+
     if query.include_snapshot {
         if let Ok(snapshot_value) = serde_json::to_value(&metrics_snapshot) {
             if let Some(obj) = response.as_object_mut() {
@@ -167,14 +196,12 @@ pub async fn prometheus_metrics_handler(
     State(server): State<Arc<EnhancedGameServer>>,
 ) -> axum::response::Result<axum::response::Response> {
     use axum::http::header::{HeaderValue, CONTENT_TYPE};
-    use axum::response::IntoResponse;
 
     if server.config().require_metrics_auth {
         enforce_metrics_auth(&headers, server.as_ref()).await?;
     }
 
-    let snapshot = server.metrics.snapshot().await;
-    let body = render_prometheus_metrics(&snapshot);
+    let body = render_server_metrics(&server).await;
     let headers = [(
         CONTENT_TYPE,
         HeaderValue::from_static("text/plain; version=0.0.4; charset=utf-8"),
@@ -183,6 +210,122 @@ pub async fn prometheus_metrics_handler(
     Ok((headers, body).into_response())
 }
 
+/// Matchmaking queue depth per game and region
+pub async fn matchmaking_stats_handler(
+    headers: axum::http::HeaderMap,
+    State(server): State<Arc<EnhancedGameServer>>,
+) -> axum::response::Result<axum::response::Json<serde_json::Value>> {
+    if server.config().require_metrics_auth {
+        enforce_metrics_auth(&headers, server.as_ref()).await?;
+    }
+
+    let queues = server.matchmaking_stats();
+    let total_queued: usize = queues.iter().map(|queue| queue.queued).sum();
+
+    Ok(axum::response::Json(serde_json::json!({
+        "timestamp": chrono::Utc::now().to_rfc3339(),
+        "totalQueued": total_queued,
+        "queues": queues,
+    })))
+}
+
+/// Server stats endpoint - connection, room and player totals for this instance
+pub async fn server_stats_handler(
+    headers: axum::http::HeaderMap,
+    State(server): State<Arc<EnhancedGameServer>>,
+) -> axum::response::Result<axum::response::Json<ServerStats>> {
+    if server.config().require_metrics_auth {
+        enforce_metrics_auth(&headers, server.as_ref()).await?;
+    }
+
+    let stats = server.get_server_stats().await.map_err(|e| {
+        tracing::error!("Failed to collect server stats: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    Ok(axum::response::Json(stats))
+}
+
+/// Query parameters for `GET /metrics/history`
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct MetricsHistoryQuery {
+    /// Comma-separated series names; every recorded series when omitted
+    series: Option<String>,
+    /// How far back to return, e.g. `15m` or `1h`; the whole buffer when omitted
+    window: Option<String>,
+}
+
+/// Sparkline history: timestamped values of the series in `metrics.history_series`
+pub async fn metrics_history_handler(
+    headers: HeaderMap,
+    State(server): State<Arc<EnhancedGameServer>>,
+    axum::extract::Query(query): axum::extract::Query<MetricsHistoryQuery>,
+) -> Response {
+    if server.config().require_metrics_auth {
+        if let Err(status) = enforce_metrics_auth(&headers, server.as_ref()).await {
+            return status.into_response();
+        }
+    }
+
+    let history = server.metrics_history();
+    let fields = match query.series.as_deref() {
+        None => history.series().to_vec(),
+        Some(names) => {
+            let mut fields = Vec::new();
+            for name in names
+                .split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+            {
+                match DashboardHistoryField::from_series_name(name) {
+                    Some(field) if history.tracks(field) => fields.push(field),
+                    _ => {
+                        return admin_error(
+                            StatusCode::BAD_REQUEST,
+                            "unknown_series",
+                            format!("Series `{name}` is not recorded"),
+                        );
+                    }
+                }
+            }
+            fields
+        }
+    };
+
+    let now = chrono::Utc::now();
+    let since = match query.window.as_deref().map(ConfigDuration::parse) {
+        None => None,
+        Some(Ok(window)) => chrono::Duration::from_std(window.into())
+            .ok()
+            .and_then(|window| now.checked_sub_signed(window)),
+        Some(Err(e)) => {
+            return admin_error(
+                StatusCode::BAD_REQUEST,
+                "invalid_window",
+                format!("Invalid window: {e}"),
+            );
+        }
+    };
+
+    let series: serde_json::Map<String, serde_json::Value> = fields
+        .into_iter()
+        .map(|field| {
+            let points = history.points(field, since).unwrap_or_default();
+            (
+                field.series_name().to_string(),
+                serde_json::to_value(points).unwrap_or_default(),
+            )
+        })
+        .collect();
+
+    Json(serde_json::json!({
+        "timestamp": now.to_rfc3339(),
+        "sampleIntervalSeconds": history.sample_interval().as_secs(),
+        "capacity": history.capacity(),
+        "series": series,
+    }))
+    .into_response()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -191,14 +334,21 @@ mod tests {
     use axum::http::header::AUTHORIZATION;
     use axum::http::HeaderMap;
 
-    async fn build_metrics_test_server(mut config: ServerConfig) -> Arc<EnhancedGameServer> {
+    async fn build_metrics_test_server(config: ServerConfig) -> Arc<EnhancedGameServer> {
+        build_metrics_test_server_with(config, crate::config::MetricsConfig::default()).await
+    }
+
+    async fn build_metrics_test_server_with(
+        mut config: ServerConfig,
+        metrics_config: crate::config::MetricsConfig,
+    ) -> Arc<EnhancedGameServer> {
         config.require_metrics_auth = true;
         EnhancedGameServer::new(
             config,
             crate::config::ProtocolConfig::default(),
             crate::config::RelayTypeConfig::default(),
             DatabaseConfig::InMemory,
-            crate::config::MetricsConfig::default(),
+            metrics_config,
             crate::config::AuthMaintenanceConfig::default(),
             crate::config::CoordinationConfig::default(),
             crate::config::TransportSecurityConfig::default(),
@@ -282,4 +432,199 @@ mod tests {
             StatusCode::UNAUTHORIZED
         );
     }
+
+    fn authorized_headers() -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            AUTHORIZATION,
+            "Bearer metrics-token".parse().expect("header parse failed"),
+        );
+        headers
+    }
+
+    fn token_config() -> ServerConfig {
+        ServerConfig {
+            metrics_auth_token: Some("metrics-token".to_string()),
+            ..ServerConfig::default()
+        }
+    }
+
+    async fn json_metrics(server: &Arc<EnhancedGameServer>) -> serde_json::Value {
+        let query = MetricsQuery {
+            time_range: default_time_range(),
+            include_snapshot: false,
+        };
+        metrics_handler(
+            authorized_headers(),
+            State(server.clone()),
+            axum::extract::Query(query),
+        )
+        .await
+        .expect("metrics handler failed")
+        .0
+    }
+
+    async fn prometheus_metrics(server: &Arc<EnhancedGameServer>) -> String {
+        let response = prometheus_metrics_handler(authorized_headers(), State(server.clone()))
+            .await
+            .expect("prometheus handler failed");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("read prometheus body");
+        String::from_utf8(body.to_vec()).expect("prometheus body is UTF-8")
+    }
+
+    fn prometheus_uptime(rendered: &str) -> f64 {
+        rendered
+            .lines()
+            .find_map(|line| line.strip_prefix("signal_fish_uptime_seconds "))
+            .expect("uptime gauge missing")
+            .parse()
+            .expect("uptime is a number")
+    }
+
+    #[tokio::test]
+    async fn test_metrics_include_build_info_and_increasing_uptime() {
+        let server = build_metrics_test_server(token_config()).await;
+
+        let first = json_metrics(&server).await;
+        let build_info = &first["buildInfo"];
+        assert_eq!(build_info["version"], env!("CARGO_PKG_VERSION"));
+        assert!(!build_info["commit"].as_str().unwrap().is_empty());
+        assert!(build_info["startedAt"].is_string());
+        let first_prometheus = prometheus_metrics(&server).await;
+        assert!(first_prometheus.contains(&format!(
+            "signal_fish_build_info{{version=\"{}\",commit=\"{}\"}} 1",
+            env!("CARGO_PKG_VERSION"),
+            crate::metrics::BUILD_COMMIT
+        )));
+
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+
+        let second = json_metrics(&server).await;
+        assert_eq!(second["buildInfo"]["startedAt"], build_info["startedAt"]);
+        assert!(
+            second["buildInfo"]["uptimeSeconds"].as_f64().unwrap()
+                > build_info["uptimeSeconds"].as_f64().unwrap()
+        );
+        let second_prometheus = prometheus_metrics(&server).await;
+        assert!(prometheus_uptime(&second_prometheus) > prometheus_uptime(&first_prometheus));
+    }
+
+    #[tokio::test]
+    async fn test_metrics_omit_build_info_when_disabled() {
+        let metrics_config = crate::config::MetricsConfig {
+            include_build_info: false,
+            ..crate::config::MetricsConfig::default()
+        };
+        let server = build_metrics_test_server_with(token_config(), metrics_config).await;
+
+        assert!(json_metrics(&server).await.get("buildInfo").is_none());
+        let rendered = prometheus_metrics(&server).await;
+        assert!(!rendered.contains("signal_fish_build_info"));
+        assert!(!rendered.contains("signal_fish_uptime_seconds"));
+    }
+
+    #[tokio::test]
+    async fn test_metrics_report_default_app_quota_usage() {
+        let config = ServerConfig {
+            default_app_quota: crate::config::AppQuotaConfig {
+                max_rooms_per_app: Some(25),
+                ..crate::config::AppQuotaConfig::default()
+            },
+            ..token_config()
+        };
+        let server = build_metrics_test_server(config).await;
+
+        let quotas = &json_metrics(&server).await["applicationQuotas"];
+        assert_eq!(quotas[0]["appName"], crate::server::DEFAULT_APP_NAME);
+        assert_eq!(quotas[0]["rooms"], 0);
+        assert_eq!(quotas[0]["maxRooms"], 25);
+        let rendered = prometheus_metrics(&server).await;
+        assert!(rendered
+            .contains("signal_fish_app_quota_limit{app=\"default\",quota=\"app_rooms\"} 25"));
+    }
+
+    #[tokio::test]
+    async fn test_server_stats_require_metrics_auth() {
+        let server = build_metrics_test_server(token_config()).await;
+
+        let rejected = server_stats_handler(HeaderMap::new(), State(server.clone())).await;
+        assert!(rejected.is_err());
+
+        let stats = server_stats_handler(authorized_headers(), State(server.clone()))
+            .await
+            .expect("stats handler failed")
+            .0;
+        assert_eq!(stats.total_rooms, 0);
+        assert_eq!(stats.cleanup_runs, 0);
+        assert!(stats.last_cleanup_at.is_none());
+    }
+
+    async fn metrics_history(
+        server: &Arc<EnhancedGameServer>,
+        series: Option<&str>,
+        window: Option<&str>,
+    ) -> (StatusCode, serde_json::Value) {
+        let query = MetricsHistoryQuery {
+            series: series.map(str::to_string),
+            window: window.map(str::to_string),
+        };
+        let response = metrics_history_handler(
+            authorized_headers(),
+            State(server.clone()),
+            axum::extract::Query(query),
+        )
+        .await;
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("read history body");
+        let value = serde_json::from_slice(&body).unwrap_or_default();
+        (status, value)
+    }
+
+    #[tokio::test]
+    async fn test_metrics_history_returns_sampled_series() {
+        let server = build_metrics_test_server(token_config()).await;
+        let start = std::time::Instant::now();
+        server.sample_metrics_history(start).await;
+        server.metrics().increment_websocket_messages_received();
+        server.metrics().increment_websocket_messages_received();
+        server
+            .sample_metrics_history(start + std::time::Duration::from_secs(60))
+            .await;
+
+        let (status, body) = metrics_history(&server, Some("active_rooms"), Some("1h")).await;
+        assert_eq!(status, StatusCode::OK);
+        let active_rooms = body["series"]["active_rooms"].as_array().unwrap();
+        assert_eq!(active_rooms.len(), 2);
+        assert_eq!(active_rooms[1]["value"], 0.0);
+        assert!(active_rooms[1]["timestamp"].is_string());
+        assert!(body["series"].get("messages_per_sec").is_none());
+
+        let (_, body) = metrics_history(&server, None, None).await;
+        let messages = body["series"]["messages_per_sec"].as_array().unwrap();
+        assert_eq!(messages.len(), 1);
+        let rate = messages[0]["value"].as_f64().unwrap();
+        assert!((rate - 2.0 / 60.0).abs() < 1e-9);
+        assert_eq!(body["capacity"], 720);
+    }
+
+    #[tokio::test]
+    async fn test_metrics_history_rejects_unknown_series_and_bad_windows() {
+        let metrics_config = crate::config::MetricsConfig {
+            history_series: vec![DashboardHistoryField::ActiveRooms],
+            ..crate::config::MetricsConfig::default()
+        };
+        let server = build_metrics_test_server_with(token_config(), metrics_config).await;
+
+        let (status, body) = metrics_history(&server, Some("reconnect_rate"), None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "unknown_series");
+
+        let (status, body) = metrics_history(&server, Some("active_rooms"), Some("soon")).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "invalid_window");
+    }
 }
@@ -3,25 +3,48 @@
 // This module provides the WebSocket handler and HTTP endpoints for the signaling server.
 // It is organized as follows:
 //
+// - admin: Admin HTTP endpoints (room force-close, recently closed rooms, draining)
+// - app: Full application router and listener (embedding API)
 // - handler: WebSocket upgrade handler (entry point)
+// - http_compression: gzip for metrics and admin responses
+// - keepalive: TCP keepalive on accepted connections
 // - connection: Main WebSocket connection handling logic
+// - events: Room lifecycle event stream for observers (/v2/events)
 // - batching: Message batching for performance optimization
+// - send_loop: Outbound half of a connection (batching, write failure detection)
 // - sending: Message serialization and sending functions
 // - token_binding: Token binding security features
 // - routes: HTTP route setup (health, metrics, etc.)
 // - metrics: Metrics endpoints and authentication
 // - prometheus: Prometheus metrics rendering
 
+mod admin;
+mod app;
 mod batching;
 mod connection;
+mod events;
 mod handler;
+mod http_compression;
+mod keepalive;
 mod metrics;
 mod prometheus;
 mod routes;
+mod send_loop;
 mod sending;
 mod token_binding;
 
 // Re-export public API to maintain backward compatibility
+pub use admin::{
+    admin_close_room_handler, admin_drain_handler, admin_flapping_players_handler,
+    admin_recent_closed_rooms_handler, admin_undrain_handler, FlappingPlayersQuery,
+    RecentClosedQuery,
+};
+pub use app::{build_router, serve, with_middleware};
+pub use events::{room_events_handler, RoomEventsQuery};
 pub use handler::websocket_handler;
-pub use metrics::{metrics_handler, prometheus_metrics_handler, MetricsQuery};
+pub use metrics::{
+    matchmaking_stats_handler, metrics_handler, prometheus_metrics_handler, MetricsQuery,
+};
+#[cfg(feature = "otlp")]
+pub(crate) use prometheus::render_server_metrics;
 pub use routes::{create_router, run_server};
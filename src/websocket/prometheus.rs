@@ -1,8 +1,124 @@
-use crate::metrics::{MetricsSnapshot, OperationLatencyMetrics};
+use crate::auth::AppRateLimitCounts;
+use crate::metrics::{BuildInfo, ConnectionRttMetrics, MetricsSnapshot, OperationLatencyMetrics};
+use crate::protocol::QuotaKind;
+use crate::server::{AppQuotaUsage, EnhancedGameServer};
 use chrono::Utc;
 
+/// Render the server's current metrics as served from `/metrics/prom`.
+pub(crate) async fn render_server_metrics(server: &EnhancedGameServer) -> String {
+    let snapshot = server.metrics().snapshot().await;
+    let mut buf = render_prometheus_metrics(
+        &snapshot,
+        server.build_info().as_ref(),
+        server.is_draining(),
+    );
+    match server.app_quota_usage().await {
+        Ok(usage) => render_app_quota_metrics(&mut buf, &usage),
+        Err(e) => tracing::warn!("Failed to collect app quota usage: {}", e),
+    }
+    render_rate_limit_metrics(&mut buf, &server.auth_middleware.rate_limit_counts());
+    buf
+}
+
+fn label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Append per-application usage and limit gauges, labelled by app name and quota.
+pub(crate) fn render_app_quota_metrics(buf: &mut String, usage: &[AppQuotaUsage]) {
+    use std::fmt::Write;
+
+    let series = |app: &AppQuotaUsage| {
+        [
+            (QuotaKind::AppRooms, app.rooms, app.max_rooms),
+            (QuotaKind::AppPlayers, app.players, app.max_players),
+            (
+                QuotaKind::AppRoomsPerHour,
+                app.rooms_created_last_hour,
+                app.max_rooms_per_hour.map(|limit| limit as usize),
+            ),
+        ]
+    };
+
+    let _ = writeln!(
+        buf,
+        "# HELP signal_fish_app_quota_usage Current usage of each application quota"
+    );
+    let _ = writeln!(buf, "# TYPE signal_fish_app_quota_usage gauge");
+    for app in usage {
+        for (quota, used, _) in series(app) {
+            let _ = writeln!(
+                buf,
+                "signal_fish_app_quota_usage{{app=\"{}\",quota=\"{quota}\"}} {used}",
+                label_value(&app.app_name)
+            );
+        }
+    }
+
+    let _ = writeln!(
+        buf,
+        "# HELP signal_fish_app_quota_limit Configured application quotas; unlimited quotas are omitted"
+    );
+    let _ = writeln!(buf, "# TYPE signal_fish_app_quota_limit gauge");
+    for app in usage {
+        for (quota, _, limit) in series(app) {
+            if let Some(limit) = limit {
+                let _ = writeln!(
+                    buf,
+                    "signal_fish_app_quota_limit{{app=\"{}\",quota=\"{quota}\"}} {limit}",
+                    label_value(&app.app_name)
+                );
+            }
+        }
+    }
+}
+
+/// Append per-application rate-limit check and rejection counters, labelled by app ID.
+pub(crate) fn render_rate_limit_metrics(buf: &mut String, counts: &[AppRateLimitCounts]) {
+    use std::fmt::Write;
+
+    let _ = writeln!(
+        buf,
+        "# HELP signal_fish_rate_limit_checks_total Application rate-limit checks"
+    );
+    let _ = writeln!(buf, "# TYPE signal_fish_rate_limit_checks_total counter");
+    for count in counts {
+        let _ = writeln!(
+            buf,
+            "signal_fish_rate_limit_checks_total{{app_id=\"{}\"}} {}",
+            count.app_id, count.checks
+        );
+    }
+
+    let _ = writeln!(
+        buf,
+        "# HELP signal_fish_rate_limit_rejections_total Application requests rejected by the rate limiter"
+    );
+    let _ = writeln!(
+        buf,
+        "# TYPE signal_fish_rate_limit_rejections_total counter"
+    );
+    for count in counts {
+        let _ = writeln!(
+            buf,
+            "signal_fish_rate_limit_rejections_total{{app_id=\"{}\"}} {}",
+            count.app_id, count.rejections
+        );
+    }
+}
+
 /// Render unified metrics snapshot into Prometheus text exposition format.
-pub(crate) fn render_prometheus_metrics(snapshot: &MetricsSnapshot) -> String {
+///
+/// `build_info` adds the `signal_fish_build_info` and `signal_fish_uptime_seconds` series;
+/// `draining` is reported as the `signal_fish_draining` gauge.
+pub(crate) fn render_prometheus_metrics(
+    snapshot: &MetricsSnapshot,
+    build_info: Option<&BuildInfo>,
+    draining: bool,
+) -> String {
     use std::fmt::Write;
 
     fn write_metric(buf: &mut String, name: &str, help: &str, metric_type: &str, value: f64) {
@@ -23,6 +139,25 @@ pub(crate) fn render_prometheus_metrics(snapshot: &MetricsSnapshot) -> String {
         write_metric(buf, name, help, "gauge", value);
     }
 
+    /// `buckets` holds `(upper bound, cumulative count)` pairs in increasing order.
+    fn histogram(
+        buf: &mut String,
+        name: &str,
+        help: &str,
+        buckets: &[(f64, u64)],
+        sum: f64,
+        count: u64,
+    ) {
+        let _ = writeln!(buf, "# HELP {name} {help}");
+        let _ = writeln!(buf, "# TYPE {name} histogram");
+        for (bound, cumulative) in buckets {
+            let _ = writeln!(buf, "{name}_bucket{{le=\"{bound}\"}} {cumulative}");
+        }
+        let _ = writeln!(buf, "{name}_bucket{{le=\"+Inf\"}} {count}");
+        let _ = writeln!(buf, "{name}_sum {sum}");
+        let _ = writeln!(buf, "{name}_count {count}");
+    }
+
     fn emit_latency_metrics(
         buf: &mut String,
         metric_prefix: &str,
@@ -83,10 +218,47 @@ pub(crate) fn render_prometheus_metrics(snapshot: &MetricsSnapshot) -> String {
             &format!("Total samples recorded for {description} latency calculations"),
             metrics.sample_count,
         );
+        if !metrics.buckets.is_empty() {
+            histogram(
+                buf,
+                &format!("{metric_prefix}_ms"),
+                &format!("Distribution of {description} latency in milliseconds"),
+                &metrics.buckets,
+                metrics.average_ms.unwrap_or(0.0) * metrics.sample_count as f64,
+                metrics.sample_count,
+            );
+        }
     }
 
     let mut buf = String::new();
 
+    if let Some(info) = build_info {
+        let _ = writeln!(
+            buf,
+            "# HELP signal_fish_build_info Build version and commit of the running server"
+        );
+        let _ = writeln!(buf, "# TYPE signal_fish_build_info gauge");
+        let _ = writeln!(
+            buf,
+            "signal_fish_build_info{{version=\"{}\",commit=\"{}\"}} 1",
+            label_value(&info.version),
+            label_value(&info.commit)
+        );
+        gauge_f64(
+            &mut buf,
+            "signal_fish_uptime_seconds",
+            "Seconds since the server process started",
+            info.uptime_seconds,
+        );
+    }
+
+    gauge(
+        &mut buf,
+        "signal_fish_draining",
+        "1 while the instance refuses new rooms and joins ahead of a deploy",
+        u64::from(draining),
+    );
+
     counter(
         &mut buf,
         "signal_fish_connections_total",
@@ -105,12 +277,96 @@ pub(crate) fn render_prometheus_metrics(snapshot: &MetricsSnapshot) -> String {
         "Total connection closures observed since startup",
         snapshot.connections.disconnections,
     );
+    counter(
+        &mut buf,
+        "signal_fish_connections_rejected_ip_limit_total",
+        "WebSocket upgrades refused with 429 because the client IP was at its connection limit",
+        snapshot.connections.connections_rejected_ip_limit,
+    );
+    counter(
+        &mut buf,
+        "signal_fish_connections_rejected_rate_limit_total",
+        "WebSocket upgrades refused with 429 because the client IP opened connections too quickly",
+        snapshot.connections.connections_rejected_rate_limit,
+    );
+    counter(
+        &mut buf,
+        "signal_fish_connections_blocked_allowlist_total",
+        "Requests refused with 403 because the client IP was not in the allowlist",
+        snapshot.connections.connections_blocked_allowlist,
+    );
+    counter(
+        &mut buf,
+        "signal_fish_connections_blocked_blocklist_total",
+        "Requests refused with 403 because the client IP was in the blocklist",
+        snapshot.connections.connections_blocked_blocklist,
+    );
+    counter(
+        &mut buf,
+        "signal_fish_connections_closed_backpressure_total",
+        "Connections closed with BACKPRESSURE_SHED because their outbound queue filled up",
+        snapshot.connections.connections_closed_backpressure,
+    );
+    counter(
+        &mut buf,
+        "signal_fish_server_announcements_sent_total",
+        "ServerAnnouncement messages queued to players, one per recipient",
+        snapshot.connections.server_announcements_sent,
+    );
+    counter(
+        &mut buf,
+        "signal_fish_websocket_batches_flushed_total",
+        "Outbound message batches flushed by connections with batching enabled",
+        snapshot.connections.websocket_batches_flushed,
+    );
+    counter(
+        &mut buf,
+        "signal_fish_websocket_batched_messages_total",
+        "Messages written as part of outbound batches; divide by batches flushed for the average batch size",
+        snapshot.connections.websocket_batched_messages,
+    );
     counter(
         &mut buf,
         "signal_fish_websocket_messages_dropped_total",
         "Server messages dropped because the outbound WebSocket buffer was full",
         snapshot.connections.websocket_messages_dropped,
     );
+    counter(
+        &mut buf,
+        "signal_fish_websocket_messages_received_total",
+        "Client messages received over WebSocket, including binary game data",
+        snapshot.connections.websocket_messages_received,
+    );
+    counter(
+        &mut buf,
+        "signal_fish_websocket_sent_bytes_total",
+        "Payload bytes written to WebSocket frames",
+        snapshot.connections.websocket_bytes_sent,
+    );
+    let _ = writeln!(
+        buf,
+        "# HELP signal_fish_connections_lost_total Connections lost without a close handshake, by the half that noticed"
+    );
+    let _ = writeln!(buf, "# TYPE signal_fish_connections_lost_total counter");
+    for (detected_by, value) in [
+        ("read", snapshot.connections.disconnects_detected_on_read),
+        ("write", snapshot.connections.disconnects_detected_on_write),
+    ] {
+        let _ = writeln!(
+            buf,
+            "signal_fish_connections_lost_total{{detected_by=\"{detected_by}\"}} {value}"
+        );
+    }
+
+    let sessions = &snapshot.connections.session_duration;
+    histogram(
+        &mut buf,
+        "signal_fish_session_duration_seconds",
+        "Time from WebSocket accept to close",
+        &sessions.buckets,
+        sessions.sum_seconds,
+        sessions.count,
+    );
 
     counter(
         &mut buf,
@@ -118,6 +374,21 @@ pub(crate) fn render_prometheus_metrics(snapshot: &MetricsSnapshot) -> String {
         "Total rooms created since startup",
         snapshot.rooms.rooms_created,
     );
+    let _ = writeln!(
+        buf,
+        "# HELP signal_fish_rooms_created_by_relay_type_total Rooms created since startup, by relay type"
+    );
+    let _ = writeln!(
+        buf,
+        "# TYPE signal_fish_rooms_created_by_relay_type_total counter"
+    );
+    for (relay_type, value) in &snapshot.rooms.rooms_created_by_relay_type {
+        let _ = writeln!(
+            buf,
+            "signal_fish_rooms_created_by_relay_type_total{{relay_type=\"{}\"}} {value}",
+            label_value(relay_type)
+        );
+    }
     counter(
         &mut buf,
         "signal_fish_rooms_joined_total",
@@ -130,6 +401,12 @@ pub(crate) fn render_prometheus_metrics(snapshot: &MetricsSnapshot) -> String {
         "Total rooms deleted since startup",
         snapshot.rooms.rooms_deleted,
     );
+    counter(
+        &mut buf,
+        "signal_fish_rooms_closed_by_admin_total",
+        "Rooms force-closed through the admin API",
+        snapshot.rooms.rooms_closed_by_admin,
+    );
     counter(
         &mut buf,
         "signal_fish_room_cap_lock_acquisitions_total",
@@ -148,6 +425,42 @@ pub(crate) fn render_prometheus_metrics(snapshot: &MetricsSnapshot) -> String {
         "Room creation attempts rejected because the per-game room cap was reached",
         snapshot.rooms.room_cap_denials,
     );
+    counter(
+        &mut buf,
+        "signal_fish_join_interceptor_denials_total",
+        "Joins refused by the join interceptor",
+        snapshot.rooms.join_interceptor_denials,
+    );
+    counter(
+        &mut buf,
+        "signal_fish_join_interceptor_failures_total",
+        "Join interceptor calls that errored or timed out",
+        snapshot.rooms.join_interceptor_failures,
+    );
+    counter(
+        &mut buf,
+        "signal_fish_webhook_delivery_failed_total",
+        "Webhook deliveries abandoned after exhausting their retries",
+        snapshot.rooms.webhook_delivery_failed,
+    );
+    counter(
+        &mut buf,
+        "signal_fish_invites_created_total",
+        "Invite tokens issued with CreateInvite",
+        snapshot.rooms.invites_created,
+    );
+    counter(
+        &mut buf,
+        "signal_fish_invites_redeemed_total",
+        "Joins that used an invite token",
+        snapshot.rooms.invites_redeemed,
+    );
+    counter(
+        &mut buf,
+        "signal_fish_invites_expired_total",
+        "Invite tokens dropped after their TTL",
+        snapshot.rooms.invites_expired,
+    );
 
     counter(
         &mut buf,
@@ -222,6 +535,18 @@ pub(crate) fn render_prometheus_metrics(snapshot: &MetricsSnapshot) -> String {
         "query",
         &snapshot.performance.query_latency,
     );
+    emit_latency_metrics(
+        &mut buf,
+        "signal_fish_join_interceptor_latency",
+        "join interceptor",
+        &snapshot.performance.join_interceptor_latency,
+    );
+    emit_latency_metrics(
+        &mut buf,
+        "signal_fish_latency_ping_processing",
+        "ping processing",
+        &snapshot.performance.latency_ping_processing,
+    );
     counter(
         &mut buf,
         "signal_fish_latency_clamped_samples_total",
@@ -281,10 +606,16 @@ pub(crate) fn render_prometheus_metrics(snapshot: &MetricsSnapshot) -> String {
         "Total game data messages forwarded through the relays",
         snapshot.players.game_data_messages,
     );
+    counter(
+        &mut buf,
+        "signal_fish_player_reports_total",
+        "Player reports accepted from ReportPlayer",
+        snapshot.players.player_reports,
+    );
     counter(
         &mut buf,
         "signal_fish_reconnection_tokens_issued_total",
-        "Total reconnection tokens minted for disconnected players",
+        "Total reconnection tokens minted, including rotations",
         snapshot.reconnection.tokens_issued,
     );
     gauge(
@@ -311,6 +642,18 @@ pub(crate) fn render_prometheus_metrics(snapshot: &MetricsSnapshot) -> String {
         "Total lobby events buffered for reconnecting players",
         snapshot.reconnection.events_buffered,
     );
+    counter(
+        &mut buf,
+        "signal_fish_token_replay_detected_total",
+        "Total reconnection attempts using a superseded (rotated) token",
+        snapshot.reconnection.token_replays_detected,
+    );
+    counter(
+        &mut buf,
+        "signal_fish_flaps_suppressed_total",
+        "Total reconnects within the flap suppression window, hidden from other players",
+        snapshot.reconnection.flaps_suppressed,
+    );
     counter(
         &mut buf,
         "signal_fish_distributed_lock_release_failures_total",
@@ -354,6 +697,12 @@ pub(crate) fn render_prometheus_metrics(snapshot: &MetricsSnapshot) -> String {
         "Total players disconnected by the cleanup task after missing heartbeats",
         snapshot.cleanup.expired_players_cleaned,
     );
+    counter(
+        &mut buf,
+        "signal_fish_cleanup_stale_players_removed_total",
+        "Total seated players removed by the cleanup task after going unseen past the stale player timeout",
+        snapshot.cleanup.stale_players_removed,
+    );
 
     counter(
         &mut buf,
@@ -435,6 +784,273 @@ pub(crate) fn render_prometheus_metrics(snapshot: &MetricsSnapshot) -> String {
         snapshot.relay_health.client_id_exhaustion_events,
     );
 
+    fn labeled_series<T: std::fmt::Display>(
+        buf: &mut String,
+        name: &str,
+        help: &str,
+        metric_type: &str,
+        series: impl Iterator<Item = (String, String, T)>,
+    ) {
+        let _ = writeln!(buf, "# HELP {name} {help}");
+        let _ = writeln!(buf, "# TYPE {name} {metric_type}");
+        for (game, region, value) in series {
+            let _ = writeln!(
+                buf,
+                "{name}{{game=\"{}\",region=\"{}\"}} {value}",
+                label_value(&game),
+                label_value(&region)
+            );
+        }
+    }
+
+    let delivery_acks = &snapshot.delivery_acks;
+    labeled_series(
+        &mut buf,
+        "signal_fish_delivery_ack_expected_total",
+        "Acknowledgements requested for sampled broadcasts",
+        "counter",
+        delivery_acks
+            .iter()
+            .map(|g| (g.game_name.clone(), g.region_id.clone(), g.expected)),
+    );
+    labeled_series(
+        &mut buf,
+        "signal_fish_delivery_ack_received_total",
+        "Acknowledgements received for sampled broadcasts",
+        "counter",
+        delivery_acks
+            .iter()
+            .map(|g| (g.game_name.clone(), g.region_id.clone(), g.acked)),
+    );
+    labeled_series(
+        &mut buf,
+        "signal_fish_delivery_ack_failed_total",
+        "Sampled broadcasts that were never acknowledged",
+        "counter",
+        delivery_acks
+            .iter()
+            .map(|g| (g.game_name.clone(), g.region_id.clone(), g.failed)),
+    );
+    labeled_series(
+        &mut buf,
+        "signal_fish_delivery_ack_rate",
+        "Share of resolved delivery samples that were acknowledged",
+        "gauge",
+        delivery_acks
+            .iter()
+            .map(|g| (g.game_name.clone(), g.region_id.clone(), g.ack_rate)),
+    );
+    labeled_series(
+        &mut buf,
+        "signal_fish_delivery_ack_latency_average_ms",
+        "Average broadcast-to-acknowledgement latency in milliseconds",
+        "gauge",
+        delivery_acks.iter().filter_map(|g| {
+            g.average_latency_ms
+                .map(|value| (g.game_name.clone(), g.region_id.clone(), value))
+        }),
+    );
+
+    fn game_series<T: std::fmt::Display>(
+        buf: &mut String,
+        name: &str,
+        help: &str,
+        metric_type: &str,
+        series: impl Iterator<Item = (String, T)>,
+    ) {
+        let _ = writeln!(buf, "# HELP {name} {help}");
+        let _ = writeln!(buf, "# TYPE {name} {metric_type}");
+        for (game, value) in series {
+            let _ = writeln!(buf, "{name}{{game=\"{}\"}} {value}", label_value(&game));
+        }
+    }
+
+    let broadcast_queues = &snapshot.broadcast_queues;
+    game_series(
+        &mut buf,
+        "signal_fish_broadcast_isolated",
+        "Whether a game's broadcasts run on dedicated workers (1) or the shared budget (0)",
+        "gauge",
+        broadcast_queues
+            .iter()
+            .map(|g| (g.game_name.clone(), u8::from(g.isolated))),
+    );
+    game_series(
+        &mut buf,
+        "signal_fish_broadcast_queue_depth",
+        "Game data broadcasts waiting for a worker",
+        "gauge",
+        broadcast_queues
+            .iter()
+            .map(|g| (g.game_name.clone(), g.queue_depth)),
+    );
+    game_series(
+        &mut buf,
+        "signal_fish_broadcasts_dispatched_total",
+        "Game data broadcasts dispatched to a worker",
+        "counter",
+        broadcast_queues
+            .iter()
+            .map(|g| (g.game_name.clone(), g.dispatched)),
+    );
+    game_series(
+        &mut buf,
+        "signal_fish_broadcast_wait_average_ms",
+        "Average time game data broadcasts waited for a worker in milliseconds",
+        "gauge",
+        broadcast_queues
+            .iter()
+            .filter_map(|g| g.average_wait_ms.map(|value| (g.game_name.clone(), value))),
+    );
+    game_series(
+        &mut buf,
+        "signal_fish_broadcast_wait_max_ms",
+        "Longest time a game data broadcast waited for a worker in milliseconds",
+        "gauge",
+        broadcast_queues
+            .iter()
+            .filter_map(|g| g.max_wait_ms.map(|value| (g.game_name.clone(), value))),
+    );
+
+    let _ = writeln!(
+        buf,
+        "# HELP signal_fish_messages_total Protocol messages received from and sent to clients, by message type"
+    );
+    let _ = writeln!(buf, "# TYPE signal_fish_messages_total counter");
+    for m in &snapshot.messages_by_type {
+        let _ = writeln!(
+            buf,
+            "signal_fish_messages_total{{direction=\"{}\",type=\"{}\"}} {}",
+            m.direction.as_str(),
+            label_value(&m.message_type),
+            m.count
+        );
+    }
+
+    let reconnection_by_game = &snapshot.reconnection_by_game;
+    game_series(
+        &mut buf,
+        "signal_fish_reconnection_game_tokens_issued_total",
+        "Reconnection tokens minted, including rotations",
+        "counter",
+        reconnection_by_game
+            .iter()
+            .map(|g| (g.game_name.clone(), g.tokens_issued)),
+    );
+    game_series(
+        &mut buf,
+        "signal_fish_reconnection_attempts_total",
+        "Reconnection attempts received",
+        "counter",
+        reconnection_by_game
+            .iter()
+            .map(|g| (g.game_name.clone(), g.attempted)),
+    );
+    game_series(
+        &mut buf,
+        "signal_fish_reconnection_successes_total",
+        "Reconnections that resumed the player's session",
+        "counter",
+        reconnection_by_game
+            .iter()
+            .map(|g| (g.game_name.clone(), g.succeeded)),
+    );
+    let _ = writeln!(
+        buf,
+        "# HELP signal_fish_reconnection_failures_total Reconnection attempts turned away, by reason"
+    );
+    let _ = writeln!(
+        buf,
+        "# TYPE signal_fish_reconnection_failures_total counter"
+    );
+    for g in reconnection_by_game {
+        for (reason, value) in [
+            ("expired", g.failed_expired),
+            ("invalid", g.failed_invalid),
+            ("room_gone", g.failed_room_gone),
+            ("slot_reclaimed", g.failed_slot_reclaimed),
+        ] {
+            let _ = writeln!(
+                buf,
+                "signal_fish_reconnection_failures_total{{game=\"{}\",reason=\"{reason}\"}} {value}",
+                label_value(&g.game_name)
+            );
+        }
+    }
+    game_series(
+        &mut buf,
+        "signal_fish_reconnection_time_p50_ms",
+        "Median time from disconnect to reconnect in milliseconds",
+        "gauge",
+        reconnection_by_game.iter().filter_map(|g| {
+            g.median_time_to_reconnect_ms
+                .map(|value| (g.game_name.clone(), value))
+        }),
+    );
+    game_series(
+        &mut buf,
+        "signal_fish_reconnection_tokens_outstanding",
+        "Unexpired reconnection tokens as of the last cleanup run",
+        "gauge",
+        reconnection_by_game
+            .iter()
+            .map(|g| (g.game_name.clone(), g.outstanding_tokens)),
+    );
+    game_series(
+        &mut buf,
+        "signal_fish_ghost_slots_reclaimed_total",
+        "Joins into a full room seated by reclaiming a seat held for a player who could no longer reconnect",
+        "counter",
+        reconnection_by_game
+            .iter()
+            .map(|g| (g.game_name.clone(), g.ghost_slots_reclaimed)),
+    );
+
+    fn region_series<T: std::fmt::Display>(
+        buf: &mut String,
+        name: &str,
+        help: &str,
+        metric_type: &str,
+        series: impl Iterator<Item = (String, T)>,
+    ) {
+        let _ = writeln!(buf, "# HELP {name} {help}");
+        let _ = writeln!(buf, "# TYPE {name} {metric_type}");
+        for (region, value) in series {
+            let _ = writeln!(buf, "{name}{{region=\"{}\"}} {value}", label_value(&region));
+        }
+    }
+
+    fn rtt_percentile(
+        buf: &mut String,
+        regions: &[ConnectionRttMetrics],
+        percentile: &str,
+        value: impl Fn(&OperationLatencyMetrics) -> Option<f64>,
+    ) {
+        region_series(
+            buf,
+            &format!("signal_fish_connection_rtt_{percentile}_ms"),
+            &format!("{percentile} heartbeat round-trip time in milliseconds"),
+            "gauge",
+            regions
+                .iter()
+                .filter_map(|r| value(&r.rtt).map(|value| (r.region_id.clone(), value))),
+        );
+    }
+
+    let connection_rtt = &snapshot.connection_rtt;
+    rtt_percentile(&mut buf, connection_rtt, "p50", |rtt| rtt.p50_ms);
+    rtt_percentile(&mut buf, connection_rtt, "p95", |rtt| rtt.p95_ms);
+    rtt_percentile(&mut buf, connection_rtt, "p99", |rtt| rtt.p99_ms);
+    region_series(
+        &mut buf,
+        "signal_fish_connection_rtt_samples_total",
+        "Heartbeat round trips measured",
+        "counter",
+        connection_rtt
+            .iter()
+            .map(|r| (r.region_id.clone(), r.rtt.sample_count)),
+    );
+
     let cache_age_seconds = {
         let last_refresh = snapshot.dashboard_cache.last_refresh_timestamp;
         if last_refresh == 0 {
@@ -463,7 +1079,7 @@ pub(crate) fn render_prometheus_metrics(snapshot: &MetricsSnapshot) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::metrics::{RateLimitWindow, ServerMetrics};
+    use crate::metrics::{RateLimitWindow, ReconnectFailureReason, ServerMetrics};
     use std::io::Write;
     use std::path::PathBuf;
     use std::process::{Command, Stdio};
@@ -482,7 +1098,7 @@ mod tests {
 
         let metrics = ServerMetrics::new();
         let snapshot = metrics.snapshot().await;
-        let rendered = render_prometheus_metrics(&snapshot);
+        let rendered = render_prometheus_metrics(&snapshot, Some(&metrics.build_info()), false);
 
         let mut child = Command::new(promtool_path)
             .arg("check")
@@ -515,6 +1131,9 @@ mod tests {
     #[tokio::test]
     async fn test_render_prometheus_metrics_includes_core_counters() {
         let metrics = ServerMetrics::new();
+        metrics.record_message_received("ProvideConnectionInfo");
+        metrics.record_message_received("ProvideConnectionInfo");
+        metrics.record_messages_sent("PlayerJoined", 3);
         metrics.increment_connections();
         metrics.increment_connections();
         metrics.decrement_active_connections();
@@ -523,14 +1142,29 @@ mod tests {
         metrics.record_rate_limit_check(RateLimitWindow::Minute);
         metrics.record_rate_limit_rejection(RateLimitWindow::Minute);
         metrics.increment_query_count();
+        metrics.record_delivery_ack_expected("chess", "eu-west", 2);
+        metrics.record_delivery_ack("chess", "eu-west", std::time::Duration::from_millis(8));
+        metrics.mark_broadcast_isolated("chess");
+        metrics.increment_rooms_created_for_relay_type("webrtc-mesh");
+        metrics.record_broadcast_dispatched("chess", std::time::Duration::from_millis(4));
+        metrics
+            .record_connection_rtt("eu-west", std::time::Duration::from_millis(40))
+            .await;
+        metrics.record_reconnection_failed("chess", ReconnectFailureReason::RoomGone);
 
         let snapshot = metrics.snapshot().await;
-        let rendered = render_prometheus_metrics(&snapshot);
+        let rendered = render_prometheus_metrics(&snapshot, Some(&metrics.build_info()), false);
 
         assert!(
             rendered.contains("signal_fish_connections_total 2"),
             "expected connections counter line"
         );
+        assert!(
+            rendered.contains(
+                "signal_fish_reconnection_failures_total{game=\"chess\",reason=\"room_gone\"} 1"
+            ),
+            "expected reconnection failures labelled by game and reason"
+        );
         assert!(
             rendered.contains("signal_fish_rate_limit_minute_limit 120"),
             "expected minute limit gauge"
@@ -547,6 +1181,10 @@ mod tests {
             rendered.contains("signal_fish_websocket_messages_dropped_total 0"),
             "expected websocket drop counter line"
         );
+        assert!(
+            rendered.contains("signal_fish_websocket_sent_bytes_total 0"),
+            "expected sent bytes counter line"
+        );
         assert!(
             rendered.contains("signal_fish_dashboard_cache_age_seconds"),
             "expected dashboard cache age gauge"
@@ -567,5 +1205,151 @@ mod tests {
             rendered.contains("signal_fish_query_latency_samples_total 0"),
             "expected query latency sample counter"
         );
+        assert!(
+            rendered.contains(
+                "signal_fish_delivery_ack_expected_total{game=\"chess\",region=\"eu-west\"} 2"
+            ),
+            "expected labeled delivery ack counter"
+        );
+        assert!(
+            rendered.contains("# TYPE signal_fish_delivery_ack_rate gauge"),
+            "expected delivery ack rate gauge"
+        );
+        assert!(
+            rendered.contains(
+                "signal_fish_rooms_created_by_relay_type_total{relay_type=\"webrtc-mesh\"} 1"
+            ),
+            "expected per relay type room creation counter"
+        );
+        assert!(
+            rendered.contains(
+                "signal_fish_messages_total{direction=\"inbound\",type=\"ProvideConnectionInfo\"} 2"
+            ),
+            "expected inbound per message type counter"
+        );
+        assert!(
+            rendered.contains(
+                "signal_fish_messages_total{direction=\"outbound\",type=\"PlayerJoined\"} 3"
+            ),
+            "expected outbound per message type counter"
+        );
+        assert!(
+            rendered.contains("signal_fish_broadcast_isolated{game=\"chess\"} 1"),
+            "expected broadcast isolation gauge"
+        );
+        assert!(
+            rendered.contains("signal_fish_broadcasts_dispatched_total{game=\"chess\"} 1"),
+            "expected broadcast dispatch counter"
+        );
+        assert!(
+            rendered.contains("signal_fish_broadcast_wait_max_ms{game=\"chess\"} 4"),
+            "expected broadcast wait gauge"
+        );
+        assert!(
+            rendered.contains("signal_fish_connection_rtt_p95_ms{region=\"eu-west\"} 40"),
+            "expected regional RTT percentile"
+        );
+        assert!(
+            rendered.contains("signal_fish_connection_rtt_samples_total{region=\"eu-west\"} 1"),
+            "expected regional RTT sample counter"
+        );
+    }
+
+    #[test]
+    fn test_render_app_quota_metrics_labels_usage_and_limits() {
+        let usage = [AppQuotaUsage {
+            app_name: "Space \"Race\"".to_string(),
+            rooms: 2,
+            max_rooms: Some(10),
+            players: 7,
+            max_players: None,
+            rooms_created_last_hour: 3,
+            max_rooms_per_hour: Some(50),
+        }];
+        let mut buf = String::new();
+        render_app_quota_metrics(&mut buf, &usage);
+
+        for line in [
+            r#"signal_fish_app_quota_usage{app="Space \"Race\"",quota="app_rooms"} 2"#,
+            r#"signal_fish_app_quota_usage{app="Space \"Race\"",quota="app_players"} 7"#,
+            r#"signal_fish_app_quota_usage{app="Space \"Race\"",quota="app_rooms_per_hour"} 3"#,
+            r#"signal_fish_app_quota_limit{app="Space \"Race\"",quota="app_rooms"} 10"#,
+            r#"signal_fish_app_quota_limit{app="Space \"Race\"",quota="app_rooms_per_hour"} 50"#,
+        ] {
+            assert!(buf.contains(line), "missing `{line}` in:\n{buf}");
+        }
+        assert!(!buf
+            .contains(r#"signal_fish_app_quota_limit{app="Space \"Race\"",quota="app_players"}"#));
+    }
+
+    #[test]
+    fn test_render_rate_limit_metrics_labels_by_app_id() {
+        let app_id = uuid::Uuid::from_u128(7);
+        let mut buf = String::new();
+        render_rate_limit_metrics(
+            &mut buf,
+            &[AppRateLimitCounts {
+                app_id,
+                checks: 12,
+                rejections: 2,
+            }],
+        );
+        assert!(buf.contains("# TYPE signal_fish_rate_limit_checks_total counter"));
+        assert!(buf.contains(&format!(
+            "signal_fish_rate_limit_checks_total{{app_id=\"{app_id}\"}} 12"
+        )));
+        assert!(buf.contains(&format!(
+            "signal_fish_rate_limit_rejections_total{{app_id=\"{app_id}\"}} 2"
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_render_session_duration_histogram() {
+        let metrics = ServerMetrics::with_session_duration_buckets(&[1.0, 60.0]);
+        metrics.record_session_duration(std::time::Duration::from_millis(250));
+        metrics.record_session_duration(std::time::Duration::from_secs(30));
+        metrics.record_session_duration(std::time::Duration::from_secs(90));
+
+        let snapshot = metrics.snapshot().await;
+        let rendered = render_prometheus_metrics(&snapshot, None, false);
+
+        for line in [
+            "# TYPE signal_fish_session_duration_seconds histogram",
+            "signal_fish_session_duration_seconds_bucket{le=\"1\"} 1",
+            "signal_fish_session_duration_seconds_bucket{le=\"60\"} 2",
+            "signal_fish_session_duration_seconds_bucket{le=\"+Inf\"} 3",
+            "signal_fish_session_duration_seconds_sum 120.25",
+            "signal_fish_session_duration_seconds_count 3",
+        ] {
+            assert!(rendered.contains(line), "missing `{line}` in:\n{rendered}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_render_latency_histogram_with_configured_buckets() {
+        let metrics = ServerMetrics::new().with_latency_buckets(&[10.0, 100.0]);
+        for millis in [3, 40, 60, 700] {
+            metrics
+                .record_response_time("room_join", std::time::Duration::from_millis(millis))
+                .await;
+        }
+
+        let snapshot = metrics.snapshot().await;
+        let rendered = render_prometheus_metrics(&snapshot, None, false);
+
+        for line in [
+            "# TYPE signal_fish_room_join_latency_ms histogram",
+            "signal_fish_room_join_latency_ms_bucket{le=\"10\"} 1",
+            "signal_fish_room_join_latency_ms_bucket{le=\"100\"} 3",
+            "signal_fish_room_join_latency_ms_bucket{le=\"+Inf\"} 4",
+            "signal_fish_room_join_latency_ms_count 4",
+            "signal_fish_room_creation_latency_ms_bucket{le=\"100\"} 0",
+        ] {
+            assert!(rendered.contains(line), "missing `{line}` in:\n{rendered}");
+        }
+        assert!(
+            !rendered.contains("le=\"5000\""),
+            "default buckets were not replaced"
+        );
     }
 }
@@ -1,20 +1,29 @@
 use crate::database::DatabaseConfig;
 use crate::server::{EnhancedGameServer, ServerConfig};
 use axum::extract::State;
-use axum::routing::get;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Json, Response};
+use axum::routing::{delete, get, post};
+use axum::serve::ListenerExt;
 use std::net::SocketAddr;
 use std::sync::Arc;
 
+use super::admin::{
+    admin_announce_handler, admin_close_room_handler, admin_drain_handler,
+    admin_flapping_players_handler, admin_recent_closed_rooms_handler, admin_undrain_handler,
+};
+use super::events::room_events_handler;
 use super::handler::websocket_handler;
-use super::metrics::{metrics_handler, prometheus_metrics_handler};
+use super::metrics::{
+    matchmaking_stats_handler, metrics_handler, metrics_history_handler,
+    prometheus_metrics_handler, server_stats_handler,
+};
 
-/// Create the Axum router with WebSocket support
-pub fn create_router(cors_origins: &str) -> axum::Router<Arc<EnhancedGameServer>> {
+/// Build the CORS layer for a comma-separated origin list, or `*` for any origin.
+pub(super) fn cors_layer(cors_origins: &str) -> tower_http::cors::CorsLayer {
     use tower_http::cors::{Any, CorsLayer};
-    use tower_http::trace::TraceLayer;
 
-    // Parse CORS origins
-    let cors = if cors_origins == "*" {
+    if cors_origins == "*" {
         CorsLayer::permissive()
     } else {
         let origins: Vec<_> = cors_origins
@@ -31,15 +40,140 @@ pub fn create_router(cors_origins: &str) -> axum::Router<Arc<EnhancedGameServer>
                 .allow_methods(Any)
                 .allow_headers(Any)
         }
-    };
+    }
+}
+
+/// Log each entry of a comma-separated origin list that is not a valid origin.
+pub(super) fn warn_invalid_cors_origins(scope: CorsScope, cors_origins: &str) {
+    if cors_origins == "*" {
+        return;
+    }
+    for origin in cors_origins.split(',').map(str::trim) {
+        if origin.parse::<axum::http::HeaderValue>().is_err() {
+            tracing::warn!(?scope, origin, "Ignoring invalid CORS origin");
+        }
+    }
+}
+
+/// The route groups with their own CORS origin list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum CorsScope {
+    /// WebSocket, event stream and health routes, following `cors_origins`
+    Game,
+    /// Metrics, stats and admin routes, following `metrics_cors_origins`
+    Metrics,
+}
+
+impl CorsScope {
+    /// The group of the route at `path`: [`reporting_routes`] and the
+    /// top-level metrics endpoints, with or without a `/v1` or `/v2` prefix,
+    /// are metrics routes and everything else is a game route.
+    pub(super) fn for_path(path: &str) -> Self {
+        let route = path
+            .strip_prefix("/v2")
+            .or_else(|| path.strip_prefix("/v1"))
+            .unwrap_or(path);
+        let is_reporting = REPORTING_ROUTE_PREFIXES.iter().any(|prefix| {
+            route
+                .strip_prefix(prefix)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        });
+        if is_reporting {
+            Self::Metrics
+        } else {
+            Self::Game
+        }
+    }
+
+    fn origins(self, config: &ServerConfig) -> &str {
+        match self {
+            Self::Game => &config.cors_origins,
+            Self::Metrics => &config.metrics_cors_origins,
+        }
+    }
+}
+
+/// Build a CORS layer that checks each request against the server's current
+/// origin list for the request's [`CorsScope`], so a configuration reload
+/// takes effect immediately. One layer covers every route, since a preflight
+/// is answered by the outermost CORS layer it reaches.
+pub(super) fn reloadable_cors_layer(
+    server: Arc<EnhancedGameServer>,
+) -> tower_http::cors::CorsLayer {
+    use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+
+    CorsLayer::new()
+        .allow_origin(AllowOrigin::predicate(move |origin, parts| {
+            let scope = CorsScope::for_path(parts.uri.path());
+            origin_allowed(scope.origins(&server.config()), origin)
+        }))
+        .allow_methods(Any)
+        .allow_headers(Any)
+}
 
+/// Whether `origin` is allowed by a comma-separated origin list. `*`, or a
+/// list without any valid origin, allows every origin.
+fn origin_allowed(cors_origins: &str, origin: &axum::http::HeaderValue) -> bool {
+    if cors_origins == "*" {
+        return true;
+    }
+    let mut origins = cors_origins
+        .split(',')
+        .filter_map(|s| s.trim().parse::<axum::http::HeaderValue>().ok())
+        .peekable();
+    origins.peek().is_none() || origins.any(|allowed| allowed == origin)
+}
+
+/// Create the Axum router with WebSocket support
+pub fn create_router(cors_origins: &str) -> axum::Router<Arc<EnhancedGameServer>> {
+    use tower_http::trace::TraceLayer;
+
+    routes()
+        .layer(cors_layer(cors_origins))
+        .layer(TraceLayer::new_for_http())
+}
+
+/// The enhanced protocol's routes, without middleware.
+pub(super) fn routes() -> axum::Router<Arc<EnhancedGameServer>> {
+    connection_routes().merge(reporting_routes())
+}
+
+/// WebSocket upgrades and health probes.
+pub(super) fn connection_routes() -> axum::Router<Arc<EnhancedGameServer>> {
     axum::Router::new()
         .route("/ws", get(websocket_handler))
+        .route("/events", get(room_events_handler))
         .route("/health", get(health_check))
+        .route("/health/live", get(liveness_probe))
+        .route("/health/ready", get(readiness_probe))
+}
+
+/// Path prefixes of [`reporting_routes`], which follow the metrics CORS origins.
+const REPORTING_ROUTE_PREFIXES: &[&str] = &["/metrics", "/matchmaking/stats", "/stats", "/admin"];
+
+/// Metrics and admin endpoints.
+pub(super) fn reporting_routes() -> axum::Router<Arc<EnhancedGameServer>> {
+    axum::Router::new()
         .route("/metrics", get(metrics_handler))
         .route("/metrics/prom", get(prometheus_metrics_handler))
-        .layer(cors)
-        .layer(TraceLayer::new_for_http())
+        .route("/metrics/history", get(metrics_history_handler))
+        .route("/matchmaking/stats", get(matchmaking_stats_handler))
+        .route("/stats", get(server_stats_handler))
+        .route(
+            "/admin/rooms/recent-closed",
+            get(admin_recent_closed_rooms_handler),
+        )
+        .route(
+            "/admin/rooms/{game_name}/{room_code}",
+            delete(admin_close_room_handler),
+        )
+        .route(
+            "/admin/players/flapping",
+            get(admin_flapping_players_handler),
+        )
+        .route("/admin/announce", post(admin_announce_handler))
+        .route("/admin/drain", post(admin_drain_handler))
+        .route("/admin/undrain", post(admin_undrain_handler))
 }
 
 /// Health check endpoint
@@ -53,6 +187,26 @@ async fn health_check(
     }
 }
 
+/// Liveness probe: answering at all means the process and runtime are up
+async fn liveness_probe() -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "status": "alive" }))
+}
+
+/// Readiness probe: 503 while the server should not receive traffic
+async fn readiness_probe(State(server): State<Arc<EnhancedGameServer>>) -> Response {
+    match server.readiness().await {
+        Ok(()) => Json(serde_json::json!({ "status": "ready" })).into_response(),
+        Err(reason) => {
+            tracing::warn!(reason, "Readiness probe failed");
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(serde_json::json!({ "status": "not_ready", "reason": reason })),
+            )
+                .into_response()
+        }
+    }
+}
+
 /// Start the server with both the new WebSocket protocol and legacy matchbox relay support
 #[allow(dead_code)]
 pub async fn run_server(
@@ -86,7 +240,14 @@ pub async fn run_server(
     let app = create_router(&cors_origins).with_state(game_server);
 
     // Start server
-    let listener = tokio::net::TcpListener::bind(addr).await?;
+    let keepalive = super::keepalive::tcp_keepalive(&server_config.websocket_config);
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await?
+        .tap_io(move |stream| {
+            if let Some(keepalive) = &keepalive {
+                super::keepalive::apply_tcp_keepalive(stream, keepalive);
+            }
+        });
     tracing::info!(%addr, "Starting enhanced Signal Fish server");
 
     axum::serve(
@@ -0,0 +1,464 @@
+//! Outbound half of a WebSocket connection: drains the client's queue into
+//! the socket, batching when enabled, and disconnects the client as soon as a
+//! write fails or stalls, or once it falls so far behind that its queue
+//! overflows.
+
+use crate::broadcast::Outbound;
+use crate::protocol::{ErrorCode, MessageEncoding, PlayerId};
+use crate::server::EnhancedGameServer;
+use std::sync::Arc;
+use tokio::sync::{mpsc, Notify};
+
+use super::batching::{bypasses_batching, send_batch, MessageBatcher};
+use super::sending::{close_with_error, send_outbound, write_timeout, FrameSink, ServerClose};
+
+/// Why the send loop stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum SendExit {
+    /// The client's queue closed because it was unregistered
+    QueueClosed,
+    /// A server-initiated close was delivered
+    ServerClosed,
+    /// A frame could not be written within the write timeout
+    WriteFailed,
+    /// The client's queue overflowed and it was closed with `BACKPRESSURE_SHED`
+    Shed,
+}
+
+impl SendExit {
+    /// Reason logged when the connection closes because the send loop stopped.
+    pub(super) const fn close_reason(self) -> &'static str {
+        match self {
+            Self::QueueClosed => "unregistered",
+            Self::ServerClosed => "server_closed",
+            Self::WriteFailed => "write_failed",
+            Self::Shed => "backpressure",
+        }
+    }
+}
+
+/// Run the send loop, then unregister the client.
+///
+/// A failed write takes the same disconnect path as a closed read half, so the
+/// player's reconnection session is created and the room hears `PlayerLeft`
+/// without waiting for the read half to notice.
+pub(super) async fn run_send_task(
+    sender: impl FrameSink,
+    rx: mpsc::Receiver<Outbound>,
+    close_rx: mpsc::Receiver<ServerClose>,
+    backpressure: Arc<Notify>,
+    server: Arc<EnhancedGameServer>,
+    player_id: PlayerId,
+    encoding: MessageEncoding,
+) -> SendExit {
+    let exit = send_loop(
+        sender,
+        rx,
+        close_rx,
+        &backpressure,
+        &server,
+        &player_id,
+        encoding,
+    )
+    .await;
+    match exit {
+        SendExit::WriteFailed => {
+            tracing::warn!(%player_id, "Write to client failed or stalled, disconnecting");
+            server.metrics().increment_disconnects_detected_on_write();
+        }
+        SendExit::Shed => server.metrics().increment_connections_closed_backpressure(),
+        SendExit::QueueClosed | SendExit::ServerClosed => {}
+    }
+    server.unregister_client(&player_id).await;
+    exit
+}
+
+/// Close a client that fell behind. What is still queued is dropped; the
+/// client only gets `BACKPRESSURE_SHED`.
+async fn shed(
+    sender: &mut impl FrameSink,
+    encoding: MessageEncoding,
+    player_id: &PlayerId,
+    server: &EnhancedGameServer,
+) -> SendExit {
+    tracing::warn!(%player_id, "Client fell too far behind its outbound queue, shedding connection");
+    let _ = close_with_error(
+        sender,
+        ErrorCode::BackpressureShed,
+        "Outbound queue overflowed".to_string(),
+        encoding,
+        write_timeout(server),
+    )
+    .await;
+    SendExit::Shed
+}
+
+async fn send_loop(
+    mut sender: impl FrameSink,
+    mut rx: mpsc::Receiver<Outbound>,
+    mut close_rx: mpsc::Receiver<ServerClose>,
+    backpressure: &Notify,
+    server: &Arc<EnhancedGameServer>,
+    player_id: &PlayerId,
+    encoding: MessageEncoding,
+) -> SendExit {
+    let config = server.config();
+    if !config.websocket_config.enable_batching {
+        // Non-batching mode: send each message immediately (legacy behavior)
+        loop {
+            tokio::select! {
+                biased;
+                _ = backpressure.notified() => {
+                    return shed(&mut sender, encoding, player_id, server).await;
+                }
+                Some(close) = close_rx.recv() => {
+                    while let Ok(outbound) = rx.try_recv() {
+                        if send_outbound(&mut sender, outbound, encoding, player_id, server)
+                            .await
+                            .is_err()
+                        {
+                            return SendExit::WriteFailed;
+                        }
+                    }
+                    let _ = close_with_error(&mut sender, close.code, close.message, encoding, write_timeout(server)).await;
+                    return SendExit::ServerClosed;
+                }
+                message_opt = rx.recv() => {
+                    let Some(outbound) = message_opt else {
+                        return SendExit::QueueClosed;
+                    };
+                    if send_outbound(&mut sender, outbound, encoding, player_id, server)
+                        .await
+                        .is_err()
+                    {
+                        return SendExit::WriteFailed;
+                    }
+                }
+            }
+        }
+    }
+
+    // Batching mode: collect multiple messages and send together
+    let batch_interval = config.websocket_config.batch_interval_ms.as_duration();
+    let mut batcher = MessageBatcher::new(config.websocket_config.batch_size, batch_interval)
+        .with_max_bytes(config.websocket_config.max_batch_bytes.as_usize());
+    let mut flush_interval = tokio::time::interval(batch_interval);
+    flush_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+    loop {
+        tokio::select! {
+            biased;
+            // The client stopped keeping up; nothing queued is worth sending
+            _ = backpressure.notified() => {
+                return shed(&mut sender, encoding, player_id, server).await;
+            }
+            // The receive loop asked to close; deliver what is queued first
+            Some(close) = close_rx.recv() => {
+                while let Ok(outbound) = rx.try_recv() {
+                    batcher.queue(outbound);
+                }
+                if send_batch(&mut sender, &mut batcher, encoding, player_id, server)
+                    .await
+                    .is_err()
+                {
+                    return SendExit::WriteFailed;
+                }
+                let _ = close_with_error(&mut sender, close.code, close.message, encoding, write_timeout(server)).await;
+                return SendExit::ServerClosed;
+            }
+            // Receive new message from channel
+            message_opt = rx.recv() => {
+                let Some(outbound) = message_opt else {
+                    // Channel closed, flush remaining messages and exit
+                    let _ = send_batch(&mut sender, &mut batcher, encoding, player_id, server).await;
+                    return SendExit::QueueClosed;
+                };
+                let urgent = bypasses_batching(&outbound);
+                batcher.queue(outbound);
+
+                // Flush if batch is full or time threshold exceeded, or right
+                // away for errors and authority changes
+                if (urgent || batcher.should_flush())
+                    && send_batch(&mut sender, &mut batcher, encoding, player_id, server)
+                        .await
+                        .is_err()
+                {
+                    return SendExit::WriteFailed;
+                }
+            }
+            // Periodic flush based on time interval
+            _ = flush_interval.tick() => {
+                if !batcher.is_empty()
+                    && batcher.should_flush()
+                    && send_batch(&mut sender, &mut batcher, encoding, player_id, server)
+                        .await
+                        .is_err()
+                {
+                    return SendExit::WriteFailed;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ConfigDuration, WebSocketConfig};
+    use crate::database::DatabaseConfig;
+    use crate::protocol::ServerMessage;
+    use crate::server::ServerConfig;
+    use axum::extract::ws::Message;
+    use futures_util::Sink;
+    use std::net::SocketAddr;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+    use tokio::time::{timeout, Duration, Instant};
+
+    /// A peer that stopped reading: the socket never becomes writable again.
+    struct StalledSink;
+
+    impl Sink<Message> for StalledSink {
+        type Error = axum::Error;
+
+        fn poll_ready(
+            self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            Poll::Pending
+        }
+
+        fn start_send(self: Pin<&mut Self>, _item: Message) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        fn poll_flush(
+            self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            Poll::Pending
+        }
+
+        fn poll_close(
+            self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            Poll::Pending
+        }
+    }
+
+    /// A peer that reads everything, keeping the frames for inspection.
+    #[derive(Clone, Default)]
+    struct RecordingSink(Arc<std::sync::Mutex<Vec<Message>>>);
+
+    impl Sink<Message> for RecordingSink {
+        type Error = axum::Error;
+
+        fn poll_ready(
+            self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn start_send(self: Pin<&mut Self>, item: Message) -> Result<(), Self::Error> {
+            self.0.lock().unwrap().push(item);
+            Ok(())
+        }
+
+        fn poll_flush(
+            self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(
+            self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    async fn join(
+        server: &EnhancedGameServer,
+        port: u16,
+        name: &str,
+    ) -> (PlayerId, mpsc::Receiver<Outbound>) {
+        let (sender, receiver) = mpsc::channel(32);
+        let addr: SocketAddr = format!("127.0.0.1:{port}").parse().unwrap();
+        let player_id = server.register_client(sender, addr).await.unwrap();
+        server
+            .handle_join_room(
+                &player_id,
+                "stall-game".to_string(),
+                Some("STALL1".to_string()),
+                name.to_string(),
+                Some(4),
+                Some(false),
+                None,
+                None,
+                None,
+            )
+            .await;
+        (player_id, receiver)
+    }
+
+    #[tokio::test]
+    async fn stalled_write_trips_the_timeout_and_runs_the_disconnect_flow() {
+        let write_timeout = Duration::from_millis(100);
+        let server = EnhancedGameServer::new(
+            ServerConfig {
+                websocket_config: WebSocketConfig {
+                    enable_batching: false,
+                    write_timeout_ms: ConfigDuration::from_millis(100),
+                    ..WebSocketConfig::default()
+                },
+                flap_suppression: Duration::ZERO,
+                ..ServerConfig::default()
+            },
+            crate::config::ProtocolConfig::default(),
+            crate::config::RelayTypeConfig::default(),
+            DatabaseConfig::InMemory,
+            crate::config::MetricsConfig::default(),
+            crate::config::AuthMaintenanceConfig::default(),
+            crate::config::CoordinationConfig::default(),
+            crate::config::TransportSecurityConfig::default(),
+            Vec::new(),
+        )
+        .await
+        .unwrap();
+
+        let (_host, mut host_rx) = join(&server, 48800, "Host").await;
+        // The guest's RoomJoined is queued and will be the first frame written
+        let (guest, guest_rx) = join(&server, 48801, "Guest").await;
+        while host_rx.try_recv().is_ok() {}
+
+        let (_close_tx, close_rx) = mpsc::channel(1);
+        let started = Instant::now();
+        let exit = timeout(
+            write_timeout * 10,
+            run_send_task(
+                StalledSink,
+                guest_rx,
+                close_rx,
+                Arc::new(Notify::new()),
+                Arc::clone(&server),
+                guest,
+                MessageEncoding::Json,
+            ),
+        )
+        .await
+        .expect("send task gives up on the stalled write");
+        assert_eq!(exit, SendExit::WriteFailed);
+        assert!(started.elapsed() >= write_timeout);
+
+        let left = timeout(Duration::from_secs(1), async {
+            loop {
+                let message = host_rx.recv().await.expect("host channel open");
+                if let ServerMessage::PlayerLeft { player_id } = &*message {
+                    return *player_id;
+                }
+            }
+        })
+        .await
+        .expect("host hears PlayerLeft");
+        assert_eq!(left, guest);
+        assert!(
+            server
+                .reconnection_manager()
+                .expect("reconnection is enabled by default")
+                .has_pending_reconnection(&guest)
+                .await
+        );
+
+        let snapshot = server.metrics().snapshot().await;
+        assert_eq!(snapshot.connections.disconnects_detected_on_write, 1);
+        assert_eq!(snapshot.connections.disconnects_detected_on_read, 0);
+    }
+
+    #[tokio::test]
+    async fn overflowing_queue_sheds_the_connection() {
+        let server = EnhancedGameServer::new(
+            ServerConfig {
+                websocket_config: WebSocketConfig {
+                    enable_batching: false,
+                    max_pending_send_queue_depth: 4,
+                    ..WebSocketConfig::default()
+                },
+                ..ServerConfig::default()
+            },
+            crate::config::ProtocolConfig::default(),
+            crate::config::RelayTypeConfig::default(),
+            DatabaseConfig::InMemory,
+            crate::config::MetricsConfig::default(),
+            crate::config::AuthMaintenanceConfig::default(),
+            crate::config::CoordinationConfig::default(),
+            crate::config::TransportSecurityConfig::default(),
+            Vec::new(),
+        )
+        .await
+        .unwrap();
+
+        let capacity = server.config().client_queue_capacity();
+        assert_eq!(capacity, 4);
+        let (sender, rx) = mpsc::channel(capacity);
+        let addr: SocketAddr = "127.0.0.1:48810".parse().unwrap();
+        let player_id = server.register_client(sender, addr).await.unwrap();
+        let backpressure = Arc::new(Notify::new());
+        assert!(
+            server
+                .watch_backpressure(player_id, Arc::clone(&backpressure))
+                .await
+        );
+
+        // Nothing drains the queue, so the last message overflows it
+        for _ in 0..=capacity {
+            server
+                .send_error_to_player(
+                    &player_id,
+                    crate::protocol::ErrorDetail::new(ErrorCode::InvalidInput, "filler"),
+                )
+                .await
+                .unwrap();
+        }
+
+        let sink = RecordingSink::default();
+        let (_close_tx, close_rx) = mpsc::channel(1);
+        let exit = timeout(
+            Duration::from_secs(1),
+            run_send_task(
+                sink.clone(),
+                rx,
+                close_rx,
+                backpressure,
+                Arc::clone(&server),
+                player_id,
+                MessageEncoding::Json,
+            ),
+        )
+        .await
+        .expect("send task sheds the client");
+        assert_eq!(exit, SendExit::Shed);
+
+        // The queued backlog is dropped; only the error and the close go out
+        let frames = sink.0.lock().unwrap().clone();
+        assert_eq!(frames.len(), 2, "unexpected frames: {frames:?}");
+        let Message::Text(error) = &frames[0] else {
+            panic!("expected an Error frame, got {:?}", frames[0]);
+        };
+        assert!(
+            error.as_str().contains("BACKPRESSURE_SHED"),
+            "{}",
+            error.as_str()
+        );
+        let Message::Close(Some(close)) = &frames[1] else {
+            panic!("expected a Close frame, got {:?}", frames[1]);
+        };
+        assert_eq!(close.code, ErrorCode::BackpressureShed.close_code());
+
+        let snapshot = server.metrics().snapshot().await;
+        assert_eq!(snapshot.connections.connections_closed_backpressure, 1);
+    }
+}
@@ -1,32 +1,117 @@
-use crate::protocol::{GameDataEncoding, PlayerId, ServerMessage};
+use crate::broadcast::Outbound;
+use crate::protocol::{
+    ErrorCode, ErrorDetail, GameDataEncoding, MessageEncoding, PlayerId, ServerMessage,
+};
 use crate::server::EnhancedGameServer;
-use axum::extract::ws::{Message, WebSocket};
-use futures_util::SinkExt;
+use axum::extract::ws::{CloseFrame, Message, Utf8Bytes};
+use bytes::Bytes;
+use futures_util::{Sink, SinkExt};
 use rmp_serde::{from_slice, to_vec_named};
 use serde::Serialize;
 use std::sync::Arc;
+use std::time::Duration;
+
+/// The outbound half of a WebSocket connection.
+pub(super) trait FrameSink: Sink<Message, Error = axum::Error> + Unpin + Send {}
+
+impl<S> FrameSink for S where S: Sink<Message, Error = axum::Error> + Unpin + Send {}
+
+/// Write one frame, failing if it has not been flushed within `write_timeout`.
+///
+/// Writes to a half-open connection succeed until the kernel buffer fills up;
+/// after that a flush stalls until TCP gives up, which can take minutes.
+pub(super) async fn write_frame(
+    sender: &mut impl FrameSink,
+    frame: Message,
+    write_timeout: Duration,
+) -> Result<(), axum::Error> {
+    match tokio::time::timeout(write_timeout, sender.send(frame)).await {
+        Ok(result) => result,
+        Err(_) => Err(axum::Error::new(format!(
+            "frame write stalled for more than {}ms",
+            write_timeout.as_millis()
+        ))),
+    }
+}
+
+/// `websocket.write_timeout_ms` for this server.
+pub(super) fn write_timeout(server: &EnhancedGameServer) -> Duration {
+    server
+        .config()
+        .websocket_config
+        .write_timeout_ms
+        .as_duration()
+}
 
 pub(super) async fn send_immediate_server_message(
-    sender: &mut futures_util::stream::SplitSink<WebSocket, Message>,
+    sender: &mut impl FrameSink,
     message: &ServerMessage,
+    encoding: MessageEncoding,
+    write_timeout: Duration,
 ) -> Result<(), axum::Error> {
-    let payload = match serde_json::to_string(message) {
-        Ok(payload) => payload,
-        Err(err) => {
-            tracing::error!(error = %err, "Failed to serialize server message");
-            "{\"type\":\"error\",\"data\":{\"message\":\"Internal error\"}}".to_string()
-        }
+    let frame = encode_frame(message, encoding).unwrap_or_else(|err| {
+        tracing::error!(error = %err, "Failed to serialize server message");
+        Message::Text("{\"type\":\"error\",\"data\":{\"message\":\"Internal error\"}}".into())
+    });
+
+    write_frame(sender, frame, write_timeout).await
+}
+
+/// A server-initiated disconnect, handed from the receive loop to the send task.
+#[derive(Debug)]
+pub(super) struct ServerClose {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+/// Close the connection because of `code`: send a final `Error` carrying
+/// `message`, then a Close frame with the code's close code and reason.
+pub(super) async fn close_with_error(
+    sender: &mut impl FrameSink,
+    code: ErrorCode,
+    message: String,
+    encoding: MessageEncoding,
+    write_timeout: Duration,
+) -> Result<(), axum::Error> {
+    let close_frame = CloseFrame {
+        code: code.close_code(),
+        reason: code.close_reason().into(),
     };
+    let error = ServerMessage::Error(ErrorDetail::new(code, message));
+    send_immediate_server_message(sender, &error, encoding, write_timeout).await?;
+    write_frame(sender, Message::Close(Some(close_frame)), write_timeout).await
+}
 
-    sender.send(Message::Text(payload.into())).await
+/// Write one entry of the connection's queue.
+pub(super) async fn send_outbound(
+    sender: &mut impl FrameSink,
+    outbound: Outbound,
+    encoding: MessageEncoding,
+    player_id: &PlayerId,
+    server: &Arc<EnhancedGameServer>,
+) -> Result<(), ()> {
+    match outbound {
+        Outbound::Message(message) => {
+            send_single_message(sender, message, encoding, player_id, server).await
+        }
+        Outbound::RoomFrame { message, frame } => {
+            send_room_frame(sender, &message, frame, encoding, player_id, server).await
+        }
+    }
 }
 
-pub(super) async fn send_single_message(
-    sender: &mut futures_util::stream::SplitSink<WebSocket, Message>,
+async fn send_single_message(
+    sender: &mut impl FrameSink,
     message: Arc<ServerMessage>,
+    encoding: MessageEncoding,
     player_id: &PlayerId,
     server: &Arc<EnhancedGameServer>,
 ) -> Result<(), ()> {
+    // MessagePack and rkyv connections carry binary game data inside the envelope.
+    if encoding.is_binary() {
+        return send_message(sender, &message, encoding, player_id, server).await;
+    }
+
     match message.as_ref() {
         ServerMessage::GameDataBinary {
             from_player,
@@ -36,13 +121,18 @@ pub(super) async fn send_single_message(
             if server.prefers_encoding(player_id, *encoding) {
                 match encode_binary_game_data(*from_player, *encoding, payload) {
                     Ok(frame_bytes) => {
-                        if sender
-                            .send(Message::Binary(frame_bytes.into()))
-                            .await
-                            .is_err()
+                        record_frame_sent(server, frame_bytes.len());
+                        server.metrics.record_messages_sent("GameDataBinary", 1);
+                        if let Err(err) = write_frame(
+                            sender,
+                            Message::Binary(frame_bytes.into()),
+                            write_timeout(server),
+                        )
+                        .await
                         {
                             tracing::warn!(
                                 %player_id,
+                                error = %err,
                                 "Failed to send binary game data, connection closed"
                             );
                             return Err(());
@@ -62,6 +152,7 @@ pub(super) async fn send_single_message(
                             *encoding,
                             payload,
                             player_id,
+                            server,
                         )
                         .await
                         {
@@ -76,7 +167,8 @@ pub(super) async fn send_single_message(
                     }
                 }
             } else if let Err(err) =
-                send_binary_fallback(sender, *from_player, *encoding, payload, player_id).await
+                send_binary_fallback(sender, *from_player, *encoding, payload, player_id, server)
+                    .await
             {
                 tracing::warn!(
                     %player_id,
@@ -88,7 +180,7 @@ pub(super) async fn send_single_message(
             }
         }
         other => {
-            send_text_message(sender, other, player_id).await?;
+            send_message(sender, other, encoding, player_id, server).await?;
         }
     }
 
@@ -96,44 +188,108 @@ pub(super) async fn send_single_message(
 }
 
 async fn send_binary_fallback(
-    sender: &mut futures_util::stream::SplitSink<WebSocket, Message>,
+    sender: &mut impl FrameSink,
     from_player: PlayerId,
     encoding: GameDataEncoding,
     payload: &[u8],
     player_id: &PlayerId,
+    server: &EnhancedGameServer,
 ) -> Result<(), String> {
     let data = decode_binary_to_json(encoding, payload)?;
     let fallback = ServerMessage::GameData { from_player, data };
-    send_text_message(sender, &fallback, player_id)
+    send_message(sender, &fallback, MessageEncoding::Json, player_id, server)
         .await
         .map_err(|()| "failed to write JSON fallback frame".to_string())
 }
 
-pub(super) async fn send_text_message(
-    sender: &mut futures_util::stream::SplitSink<WebSocket, Message>,
+/// Serialize `message` in the connection's encoding and write it as one frame.
+pub(super) async fn send_message(
+    sender: &mut impl FrameSink,
     message: &ServerMessage,
+    encoding: MessageEncoding,
     player_id: &PlayerId,
+    server: &EnhancedGameServer,
 ) -> Result<(), ()> {
-    let json_message = match serde_json::to_string(message) {
-        Ok(json) => json,
+    let frame = match encode_frame(message, encoding) {
+        Ok(frame) => frame,
         Err(e) => {
-            tracing::error!(%player_id, "Failed to serialize message: {}", e);
+            tracing::error!(%player_id, ?encoding, "Failed to serialize message: {}", e);
             return Ok(());
         }
     };
+    let frame_len = match &frame {
+        Message::Text(text) => text.len(),
+        Message::Binary(bytes) => bytes.len(),
+        _ => 0,
+    };
+    record_frame_sent(server, frame_len);
+    server
+        .metrics
+        .record_messages_sent(message.message_type(), 1);
 
-    if sender
-        .send(Message::Text(json_message.into()))
-        .await
-        .is_err()
-    {
-        tracing::warn!(%player_id, "Failed to send message, connection closed");
+    if let Err(err) = write_frame(sender, frame, write_timeout(server)).await {
+        tracing::warn!(%player_id, error = %err, "Failed to send message, connection closed");
+        return Err(());
+    }
+
+    Ok(())
+}
+
+/// Write a room broadcast that was serialized once for every connection in
+/// the room using `encoding`.
+async fn send_room_frame(
+    sender: &mut impl FrameSink,
+    message: &ServerMessage,
+    frame: Arc<[u8]>,
+    encoding: MessageEncoding,
+    player_id: &PlayerId,
+    server: &EnhancedGameServer,
+) -> Result<(), ()> {
+    // Shares the room's buffer instead of copying it per connection
+    let bytes = Bytes::from_owner(frame);
+    let frame_len = bytes.len();
+    let ws_message = if encoding.is_binary() {
+        Message::Binary(bytes)
+    } else {
+        match Utf8Bytes::try_from(bytes) {
+            Ok(text) => Message::Text(text),
+            Err(err) => {
+                tracing::error!(%player_id, error = %err, "Room frame is not valid UTF-8");
+                return Ok(());
+            }
+        }
+    };
+    record_frame_sent(server, frame_len);
+    server
+        .metrics
+        .record_messages_sent(message.message_type(), 1);
+
+    if let Err(err) = write_frame(sender, ws_message, write_timeout(server)).await {
+        tracing::warn!(%player_id, error = %err, "Failed to send room frame, connection closed");
         return Err(());
     }
 
     Ok(())
 }
 
+/// JSON goes out as a text frame, MessagePack (with field names) as a binary
+/// frame. rkyv connections receive MessagePack too.
+fn encode_frame(message: &ServerMessage, encoding: MessageEncoding) -> Result<Message, String> {
+    match encoding {
+        MessageEncoding::Json => serde_json::to_string(message)
+            .map(|json| Message::Text(json.into()))
+            .map_err(|err| err.to_string()),
+        MessageEncoding::MsgPack | MessageEncoding::Rkyv => to_vec_named(message)
+            .map(|bytes| Message::Binary(bytes.into()))
+            .map_err(|err| err.to_string()),
+    }
+}
+
+/// Count an outbound frame's payload bytes.
+fn record_frame_sent(server: &EnhancedGameServer, payload_len: usize) {
+    server.metrics.record_websocket_bytes_sent(payload_len);
+}
+
 #[derive(Serialize)]
 struct BinaryGameDataFrame<'a> {
     from_player: PlayerId,
@@ -180,3 +336,112 @@ fn decode_binary_to_json(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::{ClientMessage, LobbyState, PlayerInfo, PlayerRole, RoomJoinedPayload};
+
+    fn full_room_joined() -> ServerMessage {
+        let players: Vec<PlayerInfo> = (0..8)
+            .map(|index| PlayerInfo {
+                id: uuid::Uuid::new_v4(),
+                name: format!("Player{index}"),
+                is_authority: index == 0,
+                role: PlayerRole::default(),
+                is_ready: index % 2 == 0,
+                connected_at: chrono::Utc::now(),
+                connection_info: None,
+                region_id: String::new(),
+            })
+            .collect();
+        ServerMessage::RoomJoined(Box::new(RoomJoinedPayload {
+            room_id: uuid::Uuid::new_v4(),
+            room_code: "ABC123".to_string(),
+            player_id: players[0].id,
+            player_name: players[0].name.clone(),
+            game_name: "encoding-game".to_string(),
+            max_players: 8,
+            supports_authority: true,
+            ready_players: players
+                .iter()
+                .filter(|p| p.is_ready)
+                .map(|p| p.id)
+                .collect(),
+            current_players: players,
+            is_authority: true,
+            role: PlayerRole::default(),
+            lobby_state: LobbyState::Lobby,
+            lobby_version: 1,
+            relay_type: "matchbox".to_string(),
+            current_spectators: Vec::new(),
+            reconnection_token: None,
+            relay_session: None,
+            metadata: Default::default(),
+            peer_connection: None,
+        }))
+    }
+
+    fn frame_bytes(frame: Message) -> Vec<u8> {
+        match frame {
+            Message::Text(text) => text.as_bytes().to_vec(),
+            Message::Binary(bytes) => bytes.to_vec(),
+            other => panic!("unexpected frame {other:?}"),
+        }
+    }
+
+    #[test]
+    fn msgpack_frames_are_binary_and_round_trip() {
+        let message = full_room_joined();
+        let frame = encode_frame(&message, MessageEncoding::MsgPack).unwrap();
+        assert!(matches!(frame, Message::Binary(_)));
+
+        let decoded: ServerMessage = rmp_serde::from_slice(&frame_bytes(frame)).unwrap();
+        let (ServerMessage::RoomJoined(original), ServerMessage::RoomJoined(decoded)) =
+            (&message, &decoded)
+        else {
+            panic!("expected RoomJoined, got {decoded:?}");
+        };
+        assert_eq!(decoded.room_id, original.room_id);
+        assert_eq!(decoded.current_players.len(), 8);
+        assert_eq!(decoded.ready_players, original.ready_players);
+    }
+
+    #[test]
+    fn msgpack_room_joined_is_at_least_30_percent_smaller() {
+        let message = full_room_joined();
+        let json = frame_bytes(encode_frame(&message, MessageEncoding::Json).unwrap());
+        let msgpack = frame_bytes(encode_frame(&message, MessageEncoding::MsgPack).unwrap());
+        assert!(
+            msgpack.len() * 10 <= json.len() * 7,
+            "msgpack {} bytes vs json {} bytes",
+            msgpack.len(),
+            json.len()
+        );
+    }
+
+    #[test]
+    fn client_messages_decode_the_same_from_json_and_msgpack() {
+        let player = uuid::Uuid::new_v4();
+        let samples = [
+            serde_json::json!({"type": "Authenticate", "data": {"app_id": "mb_app_test", "capabilities": ["delivery-ack"]}}),
+            serde_json::json!({"type": "JoinRoom", "data": {"game_name": "chess", "room_code": null, "player_name": "Ada", "max_players": 4, "supports_authority": true}}),
+            serde_json::json!({"type": "GameData", "data": {"data": {"move": "e4", "clock": [300, 2.5], "check": false}}}),
+            serde_json::json!({"type": "KickPlayer", "data": {"player_id": player, "ban": true}}),
+            serde_json::json!({"type": "UpdateRoomSettings", "data": {"max_spectators": 3}}),
+            serde_json::json!({"type": "Heartbeat", "data": {"client_timestamp": 1_700_000_000_000_u64}}),
+            serde_json::json!({"type": "LeaveRoom"}),
+        ];
+
+        for sample in samples {
+            let from_json: ClientMessage = serde_json::from_value(sample.clone()).unwrap();
+            let msgpack = to_vec_named(&from_json).unwrap();
+            let from_msgpack: ClientMessage = from_slice(&msgpack).unwrap();
+            assert_eq!(
+                serde_json::to_value(&from_msgpack).unwrap(),
+                serde_json::to_value(&from_json).unwrap(),
+                "{sample}"
+            );
+        }
+    }
+}
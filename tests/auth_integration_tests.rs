@@ -5,9 +5,16 @@
 
 mod test_helpers;
 
+use futures_util::{SinkExt, StreamExt};
 use signal_fish_server::auth::{AuthError, AuthMiddleware};
 use signal_fish_server::config::AppAuthEntry;
+use signal_fish_server::protocol::{ClientMessage, ErrorCode, ServerMessage};
+use signal_fish_server::server::EnhancedGameServer;
+use signal_fish_server::websocket::create_router;
+use std::sync::Arc;
 use test_helpers::{create_test_server_with_config, test_server_config};
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::Message;
 
 // ---------------------------------------------------------------------------
 // Helper factories
@@ -19,8 +26,12 @@ fn sample_app_entry() -> AppAuthEntry {
         app_secret: "super-secret-1".to_string(),
         app_name: "Test Game".to_string(),
         max_rooms: Some(50),
+        max_rooms_per_app: None,
+        max_players_per_app: None,
+        max_rooms_per_hour: None,
         max_players_per_room: Some(8),
         rate_limit_per_minute: Some(60),
+        api_keys: vec!["test-game-1-api-key".to_string()],
     }
 }
 
@@ -30,8 +41,12 @@ fn secondary_app_entry() -> AppAuthEntry {
         app_secret: "super-secret-2".to_string(),
         app_name: "Secondary Game".to_string(),
         max_rooms: None,
+        max_rooms_per_app: None,
+        max_players_per_app: None,
+        max_rooms_per_hour: None,
         max_players_per_room: None,
         rate_limit_per_minute: None,
+        api_keys: Vec::new(),
     }
 }
 
@@ -41,8 +56,12 @@ fn rate_limited_app_entry(limit: u32) -> AppAuthEntry {
         app_secret: "rate-secret".to_string(),
         app_name: "Rate Limited App".to_string(),
         max_rooms: Some(10),
+        max_rooms_per_app: None,
+        max_players_per_app: None,
+        max_rooms_per_hour: None,
         max_players_per_room: Some(4),
         rate_limit_per_minute: Some(limit),
+        api_keys: Vec::new(),
     }
 }
 
@@ -211,8 +230,12 @@ async fn test_rate_limits_are_per_app() {
             app_secret: "other-secret".to_string(),
             app_name: "Other App".to_string(),
             max_rooms: None,
+            max_rooms_per_app: None,
+            max_players_per_app: None,
+            max_rooms_per_hour: None,
             max_players_per_room: None,
             rate_limit_per_minute: Some(2),
+            api_keys: Vec::new(),
         },
     ];
     let mw = AuthMiddleware::new(entries);
@@ -277,8 +300,12 @@ async fn test_app_info_rate_limits_are_computed_correctly() {
         app_secret: "s".to_string(),
         app_name: "Computed".to_string(),
         max_rooms: None,
+        max_rooms_per_app: None,
+        max_players_per_app: None,
+        max_rooms_per_hour: None,
         max_players_per_room: None,
         rate_limit_per_minute: Some(10),
+        api_keys: Vec::new(),
     };
     let mw = AuthMiddleware::new(vec![entry]);
 
@@ -398,3 +425,304 @@ async fn test_server_auth_middleware_is_accessible() {
     // it by confirming the server starts and passes health checks.
     assert!(server.health_check().await);
 }
+
+// ===========================================================================
+// API key authentication
+// ===========================================================================
+
+#[tokio::test]
+async fn test_api_key_resolves_bound_app() {
+    let mw = AuthMiddleware::new(vec![sample_app_entry(), secondary_app_entry()]);
+    assert!(mw.api_keys_enabled());
+
+    let by_key = mw
+        .validate_api_key("test-game-1-api-key")
+        .await
+        .expect("configured API key should succeed");
+    let by_id = mw.validate_app_id("test-game-1").await.unwrap();
+    assert_eq!(by_key.id, by_id.id);
+    assert_eq!(by_key.name, "Test Game");
+
+    let err = mw
+        .validate_api_key("not-a-key")
+        .await
+        .expect_err("unknown API key should fail");
+    assert!(
+        matches!(err, AuthError::Unauthorized),
+        "expected Unauthorized, got: {err:?}"
+    );
+}
+
+async fn start_auth_server() -> (std::net::SocketAddr, Arc<EnhancedGameServer>) {
+    start_auth_server_with_transport(signal_fish_server::config::TransportSecurityConfig::default())
+        .await
+}
+
+async fn start_auth_server_with_transport(
+    transport: signal_fish_server::config::TransportSecurityConfig,
+) -> (std::net::SocketAddr, Arc<EnhancedGameServer>) {
+    let mut config = test_server_config();
+    config.auth_enabled = true;
+
+    let server = EnhancedGameServer::new(
+        config,
+        signal_fish_server::config::ProtocolConfig::default(),
+        signal_fish_server::config::RelayTypeConfig::default(),
+        signal_fish_server::database::DatabaseConfig::InMemory,
+        signal_fish_server::config::MetricsConfig::default(),
+        signal_fish_server::config::AuthMaintenanceConfig::default(),
+        signal_fish_server::config::CoordinationConfig::default(),
+        transport,
+        vec![sample_app_entry(), secondary_app_entry()],
+    )
+    .await
+    .expect("server should start");
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = axum::Router::new().nest("/v2", create_router("*").with_state(server.clone()));
+    tokio::spawn(async move {
+        axum::serve(
+            listener,
+            router.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+        )
+        .await
+        .unwrap();
+    });
+    (addr, server)
+}
+
+fn api_key_request(
+    addr: std::net::SocketAddr,
+    api_key: &str,
+) -> tokio_tungstenite::tungstenite::handshake::client::Request {
+    let mut request = format!("ws://{addr}/v2/ws").into_client_request().unwrap();
+    request
+        .headers_mut()
+        .insert("X-Api-Key", api_key.parse().unwrap());
+    request
+}
+
+#[tokio::test]
+async fn test_websocket_api_key_binds_rooms_to_app() {
+    let (addr, server) = start_auth_server().await;
+
+    let (ws, _) = tokio_tungstenite::connect_async(api_key_request(addr, "test-game-1-api-key"))
+        .await
+        .expect("upgrade with a valid API key should succeed");
+    let (mut sender, mut receiver) = ws.split();
+
+    // No Authenticate message: the API key already authenticated the socket
+    let join = ClientMessage::JoinRoom {
+        game_name: "api-key-game".to_string(),
+        room_code: None,
+        player_name: "Backend".to_string(),
+        max_players: Some(4),
+        supports_authority: None,
+        relay_transport: None,
+        relay_type: None,
+        spectator_chat_mode: None,
+        idempotency_key: None,
+        allow_overflow_joins: None,
+    };
+    sender
+        .send(Message::Text(serde_json::to_string(&join).unwrap().into()))
+        .await
+        .unwrap();
+
+    let room_id = loop {
+        let frame = tokio::time::timeout(std::time::Duration::from_secs(5), receiver.next())
+            .await
+            .expect("server should respond")
+            .expect("socket should stay open")
+            .unwrap();
+        let Message::Text(text) = frame else {
+            continue;
+        };
+        match serde_json::from_str::<ServerMessage>(&text).unwrap() {
+            ServerMessage::RoomJoined(payload) => break payload.room_id,
+            ServerMessage::ProtocolInfo(_) => continue,
+            other => panic!("unexpected message: {other:?}"),
+        }
+    };
+
+    let expected = AuthMiddleware::new(vec![sample_app_entry()])
+        .validate_app_id("test-game-1")
+        .await
+        .unwrap()
+        .id;
+    assert_eq!(server.room_application_id(&room_id), Some(expected));
+}
+
+#[tokio::test]
+async fn test_websocket_unknown_api_key_is_rejected() {
+    let (addr, _server) = start_auth_server().await;
+
+    let err = tokio_tungstenite::connect_async(api_key_request(addr, "wrong-key"))
+        .await
+        .expect_err("upgrade with an unknown API key should fail");
+    match err {
+        tokio_tungstenite::tungstenite::Error::Http(response) => {
+            assert_eq!(response.status(), axum::http::StatusCode::UNAUTHORIZED);
+        }
+        other => panic!("expected HTTP 401, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_websocket_message_before_auth_closes_with_error_code() {
+    let (addr, _server) = start_auth_server().await;
+
+    let (ws, _) = tokio_tungstenite::connect_async(format!("ws://{addr}/v2/ws"))
+        .await
+        .expect("upgrade without credentials is allowed");
+    let (mut sender, mut receiver) = ws.split();
+    sender
+        .send(Message::Text(
+            serde_json::to_string(&ClientMessage::Ping).unwrap().into(),
+        ))
+        .await
+        .unwrap();
+
+    let mut final_error = None;
+    let close = loop {
+        let frame = tokio::time::timeout(std::time::Duration::from_secs(5), receiver.next())
+            .await
+            .expect("server should respond")
+            .expect("server should send a close frame before ending the stream")
+            .unwrap();
+        match frame {
+            Message::Text(text) => {
+                if let ServerMessage::Error(error) =
+                    serde_json::from_str::<ServerMessage>(&text).unwrap()
+                {
+                    final_error = Some(error.code);
+                }
+            }
+            Message::Close(frame) => break frame.expect("close frame carries a code"),
+            _ => {}
+        }
+    };
+
+    assert_eq!(final_error, Some(ErrorCode::MissingAppId));
+    assert_eq!(u16::from(close.code), ErrorCode::MissingAppId.close_code());
+    assert_eq!(close.reason.as_str(), "MISSING_APP_ID");
+}
+
+const JWT_SECRET: &str = "integration-jwt-secret";
+
+fn hs256_token(claims: serde_json::Value) -> String {
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    use base64::Engine;
+    use hmac::{Hmac, Mac};
+
+    let header = URL_SAFE_NO_PAD.encode(br#"{"alg":"HS256","typ":"JWT"}"#);
+    let payload = URL_SAFE_NO_PAD.encode(serde_json::to_vec(&claims).unwrap());
+    let signing_input = format!("{header}.{payload}");
+    let mut mac = Hmac::<sha2::Sha256>::new_from_slice(JWT_SECRET.as_bytes()).unwrap();
+    mac.update(signing_input.as_bytes());
+    let signature = URL_SAFE_NO_PAD.encode(mac.finalize().into_bytes());
+    format!("{signing_input}.{signature}")
+}
+
+/// Server with optional HS256 bearer auth, as during a mixed rollout.
+async fn start_optional_jwt_server() -> (std::net::SocketAddr, Arc<EnhancedGameServer>) {
+    let mut transport = signal_fish_server::config::TransportSecurityConfig::default();
+    transport.tls.client_auth = signal_fish_server::config::ClientAuthMode::Jwt;
+    transport.jwt.secret = Some(JWT_SECRET.to_string());
+    transport.jwt.issuer = Some("https://auth.example.com".to_string());
+    transport.jwt.required = false;
+    start_auth_server_with_transport(transport).await
+}
+
+#[tokio::test]
+async fn test_websocket_jwt_in_query_param_enforces_room_scope() {
+    let (addr, _server) = start_optional_jwt_server().await;
+    let token = hs256_token(serde_json::json!({
+        "app_id": "test-game-1",
+        "sub": "player-77",
+        "iss": "https://auth.example.com",
+        "room_scope": "scoped-game",
+        "exp": chrono::Utc::now().timestamp() + 300,
+    }));
+
+    let (ws, _) = tokio_tungstenite::connect_async(format!("ws://{addr}/v2/ws?token={token}"))
+        .await
+        .expect("upgrade with a valid token should succeed");
+    let (mut sender, mut receiver) = ws.split();
+
+    let join = |game_name: &str| ClientMessage::JoinRoom {
+        game_name: game_name.to_string(),
+        room_code: None,
+        player_name: "Scoped".to_string(),
+        max_players: Some(4),
+        supports_authority: None,
+        relay_transport: None,
+        relay_type: None,
+        spectator_chat_mode: None,
+        idempotency_key: None,
+        allow_overflow_joins: None,
+    };
+    for (game_name, allowed) in [("other-game", false), ("scoped-game", true)] {
+        sender
+            .send(Message::Text(
+                serde_json::to_string(&join(game_name)).unwrap().into(),
+            ))
+            .await
+            .unwrap();
+        let response = loop {
+            let frame = tokio::time::timeout(std::time::Duration::from_secs(5), receiver.next())
+                .await
+                .expect("server should respond")
+                .expect("socket should stay open")
+                .unwrap();
+            let Message::Text(text) = frame else {
+                continue;
+            };
+            match serde_json::from_str::<ServerMessage>(&text).unwrap() {
+                ServerMessage::ProtocolInfo(_) => continue,
+                other => break other,
+            }
+        };
+        match response {
+            ServerMessage::RoomJoined(_) => assert!(allowed, "{game_name} is out of scope"),
+            ServerMessage::RoomJoinFailed { error_code, .. } => {
+                assert!(!allowed, "{game_name} is in scope");
+                assert_eq!(error_code, Some(ErrorCode::Unauthorized));
+            }
+            other => panic!("unexpected message: {other:?}"),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_websocket_invalid_jwt_is_rejected_with_structured_body() {
+    let (addr, _server) = start_optional_jwt_server().await;
+    let wrong_issuer = hs256_token(serde_json::json!({
+        "app_id": "test-game-1",
+        "iss": "https://elsewhere.example.com",
+        "exp": chrono::Utc::now().timestamp() + 300,
+    }));
+
+    let mut request = format!("ws://{addr}/v2/ws").into_client_request().unwrap();
+    request.headers_mut().insert(
+        "Authorization",
+        format!("Bearer {wrong_issuer}").parse().unwrap(),
+    );
+    let err = tokio_tungstenite::connect_async(request)
+        .await
+        .expect_err("upgrade with an invalid token should fail");
+    let tokio_tungstenite::tungstenite::Error::Http(response) = err else {
+        panic!("expected HTTP 401, got {err:?}");
+    };
+    assert_eq!(response.status(), axum::http::StatusCode::UNAUTHORIZED);
+    let body: serde_json::Value =
+        serde_json::from_slice(response.body().as_deref().expect("rejection has a body")).unwrap();
+    assert_eq!(body["error"], "INVALID_TOKEN");
+    assert!(!body["message"].as_str().unwrap().contains(JWT_SECRET));
+
+    // Not required: a client without a token may still use the Authenticate handshake
+    tokio_tungstenite::connect_async(format!("ws://{addr}/v2/ws"))
+        .await
+        .expect("upgrade without a token is allowed when jwt.required is false");
+}
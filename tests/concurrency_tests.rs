@@ -2,6 +2,7 @@ mod test_helpers;
 
 use chrono::Duration as ChronoDuration;
 use signal_fish_server::config::ProtocolConfig;
+use signal_fish_server::database::RoomExpiryPolicy;
 use signal_fish_server::protocol::{ErrorCode, ServerMessage};
 use signal_fish_server::server::{EnhancedGameServer, ServerConfig};
 use std::sync::Arc;
@@ -41,6 +42,8 @@ async fn test_concurrent_room_creation() {
                     Some(4),
                     Some(true),
                     None,
+                    None,
+                    None,
                 )
                 .await;
 
@@ -119,6 +122,8 @@ async fn test_max_rooms_per_game_cap_is_enforced_under_concurrency() {
                     Some(4),
                     Some(true),
                     None,
+                    None,
+                    None,
                 )
                 .await;
 
@@ -190,6 +195,8 @@ async fn test_concurrent_authority_requests() {
             Some(10),
             Some(true),
             None,
+            None,
+            None,
         )
         .await;
 
@@ -211,6 +218,8 @@ async fn test_concurrent_authority_requests() {
                 Some(10),
                 Some(true),
                 None,
+                None,
+                None,
             )
             .await;
 
@@ -283,6 +292,8 @@ async fn test_concurrent_room_joining_at_capacity() {
             Some(3), // Max 3 players
             Some(true),
             None,
+            None,
+            None,
         )
         .await;
 
@@ -311,6 +322,8 @@ async fn test_concurrent_room_joining_at_capacity() {
                     Some(3),
                     Some(true),
                     None,
+                    None,
+                    None,
                 )
                 .await;
 
@@ -362,6 +375,8 @@ async fn test_concurrent_authority_player_disconnect() {
             Some(5),
             Some(true),
             None,
+            None,
+            None,
         )
         .await;
 
@@ -381,6 +396,8 @@ async fn test_concurrent_authority_player_disconnect() {
                 Some(5),
                 Some(true),
                 None,
+                None,
+                None,
             )
             .await;
 
@@ -505,6 +522,8 @@ async fn test_concurrent_room_cleanup_and_activity() {
             Some(4),
             Some(true),
             None,
+            None,
+            None,
         )
         .await;
 
@@ -525,7 +544,10 @@ async fn test_concurrent_room_cleanup_and_activity() {
         for _ in 0..10 {
             let _ = server_clone
                 .database()
-                .cleanup_empty_rooms(ChronoDuration::zero())
+                .cleanup_empty_rooms(&RoomExpiryPolicy::uniform(
+                    ChronoDuration::zero(),
+                    ChronoDuration::zero(),
+                ))
                 .await;
             tokio::time::sleep(tokio::time::Duration::from_millis(5)).await;
         }
@@ -552,6 +574,8 @@ async fn test_concurrent_room_cleanup_and_activity() {
                 Some(4),
                 Some(true),
                 None,
+                None,
+                None,
             )
             .await;
 
@@ -602,6 +626,8 @@ async fn test_concurrent_room_cleanup_across_instances() {
             Some(4),
             Some(true),
             None,
+            None,
+            None,
         )
         .await;
 
@@ -616,7 +642,10 @@ async fn test_concurrent_room_cleanup_across_instances() {
             barrier_clone.wait().await;
             server
                 .database()
-                .cleanup_empty_rooms(ChronoDuration::zero())
+                .cleanup_empty_rooms(&RoomExpiryPolicy::uniform(
+                    ChronoDuration::zero(),
+                    ChronoDuration::zero(),
+                ))
                 .await
                 .expect("cleanup should succeed")
                 .len()
@@ -5,10 +5,12 @@
 //! - Environment variable overrides (`SIGNAL_FISH__*`)
 //! - Health endpoint (`/health`)
 //! - Metrics endpoint (`/metrics`)
+//! - Embedding custom routes with `build_router`
 
 mod test_helpers;
 
 use signal_fish_server::config::Config;
+use signal_fish_server::server::ServerConfig;
 use signal_fish_server::websocket::create_router;
 use test_helpers::{create_test_server, test_server_config};
 
@@ -22,11 +24,11 @@ fn test_config_default_values() {
 
     assert_eq!(config.port, 3536);
     assert_eq!(config.server.default_max_players, 8);
-    assert_eq!(config.server.ping_timeout, 30);
-    assert_eq!(config.server.room_cleanup_interval, 60);
+    assert_eq!(config.server.ping_timeout.as_secs(), 30);
+    assert_eq!(config.server.room_cleanup_interval.as_secs(), 60);
     assert_eq!(config.server.max_rooms_per_game, 1000);
-    assert_eq!(config.server.empty_room_timeout, 300);
-    assert_eq!(config.server.inactive_room_timeout, 3600);
+    assert_eq!(config.server.empty_room_timeout.as_secs(), 300);
+    assert_eq!(config.server.inactive_room_timeout.as_secs(), 3600);
     assert_eq!(config.protocol.room_code_length, 6);
     assert_eq!(config.protocol.max_game_name_length, 64);
     assert_eq!(config.protocol.max_player_name_length, 32);
@@ -74,7 +76,7 @@ fn test_config_from_json_string() {
     assert_eq!(config.server.region_id, "us-east-1");
     assert_eq!(config.protocol.room_code_length, 8);
     // Non-specified fields should remain at defaults
-    assert_eq!(config.server.ping_timeout, 30);
+    assert_eq!(config.server.ping_timeout.as_secs(), 30);
 }
 
 #[test]
@@ -134,7 +136,7 @@ fn test_config_rate_limit_section() {
     let config: Config = serde_json::from_str(json).expect("parse should succeed");
 
     assert_eq!(config.rate_limit.max_room_creations, 20);
-    assert_eq!(config.rate_limit.time_window, 120);
+    assert_eq!(config.rate_limit.time_window.as_secs(), 120);
     assert_eq!(config.rate_limit.max_join_attempts, 50);
 }
 
@@ -163,7 +165,7 @@ fn test_config_heartbeat_throttle() {
 
     let config: Config = serde_json::from_str(json).expect("parse should succeed");
 
-    assert_eq!(config.server.heartbeat_throttle_secs, 15);
+    assert_eq!(config.server.heartbeat_throttle_secs.as_secs(), 15);
 }
 
 #[test]
@@ -181,8 +183,271 @@ fn test_config_websocket_section() {
 
     assert!(config.websocket.enable_batching);
     assert_eq!(config.websocket.batch_size, 64);
-    assert_eq!(config.websocket.batch_interval_ms, 50);
-    assert_eq!(config.websocket.auth_timeout_secs, 15);
+    assert_eq!(config.websocket.batch_interval_ms.as_millis(), 50);
+    assert_eq!(config.websocket.auth_timeout_secs.as_secs(), 15);
+}
+
+#[test]
+fn test_config_websocket_batch_limits() {
+    let json = r#"{
+        "websocket": {
+            "max_batch_size": 32,
+            "max_batch_bytes": "16kb",
+            "max_batch_delay_ms": 5
+        }
+    }"#;
+
+    let config: Config = serde_json::from_str(json).expect("parse should succeed");
+
+    assert_eq!(config.websocket.batch_size, 32);
+    assert_eq!(config.websocket.max_batch_bytes.as_bytes(), 16 * 1024);
+    assert_eq!(config.websocket.batch_interval_ms.as_millis(), 5);
+    assert_eq!(Config::default().websocket.max_batch_bytes.as_bytes(), 0);
+}
+
+#[test]
+fn test_config_human_readable_units() {
+    let json = r#"{
+        "server": {
+            "ping_timeout": "45s",
+            "empty_room_timeout": "5m",
+            "inactive_room_timeout": "2h",
+            "heartbeat_throttle_secs": "1m30s"
+        },
+        "rate_limit": { "time_window": "2m" },
+        "security": { "max_message_size": "512kb" },
+        "websocket": { "batch_interval_ms": "20ms", "auth_timeout_secs": "15s" }
+    }"#;
+
+    let config: Config = serde_json::from_str(json).expect("parse should succeed");
+
+    assert_eq!(config.server.ping_timeout.as_secs(), 45);
+    assert_eq!(config.server.empty_room_timeout.as_secs(), 300);
+    assert_eq!(config.server.inactive_room_timeout.as_secs(), 7200);
+    assert_eq!(config.server.heartbeat_throttle_secs.as_secs(), 90);
+    assert_eq!(config.rate_limit.time_window.as_secs(), 120);
+    assert_eq!(config.security.max_message_size.as_bytes(), 512 * 1024);
+    assert_eq!(config.websocket.batch_interval_ms.as_millis(), 20);
+    assert_eq!(config.websocket.auth_timeout_secs.as_secs(), 15);
+}
+
+#[test]
+fn test_config_invalid_unit_names_field_and_formats() {
+    let value = serde_json::json!({ "server": { "ping_timeout": "30 seconds-ish" } });
+    let err = signal_fish_server::config::loader::deserialize_config(value)
+        .expect_err("invalid duration should fail")
+        .to_string();
+    assert!(err.starts_with("server.ping_timeout"), "{err}");
+    assert!(err.contains("\"90s\""), "{err}");
+
+    let value = serde_json::json!({ "security": { "max_message_size": "huge" } });
+    let err = signal_fish_server::config::loader::deserialize_config(value)
+        .expect_err("invalid size should fail")
+        .to_string();
+    assert!(err.starts_with("security.max_message_size"), "{err}");
+    assert!(err.contains("\"1mb\""), "{err}");
+}
+
+#[test]
+#[serial_test::serial]
+fn test_try_load_rejects_configs_that_load_falls_back_on() {
+    use signal_fish_server::config::try_load;
+    use std::env;
+
+    env::set_var("SIGNAL_FISH_CONFIG_JSON", "{invalid json content}");
+    let invalid_json = try_load();
+    env::set_var(
+        "SIGNAL_FISH_CONFIG_JSON",
+        r#"{ "server": { "ping_timeout": "soon" } }"#,
+    );
+    let invalid_value = try_load();
+    env::set_var("SIGNAL_FISH_CONFIG_JSON", r#"{ "port": 4000 }"#);
+    let insecure = try_load();
+    env::set_var(
+        "SIGNAL_FISH_CONFIG_JSON",
+        r#"{ "port": 4000, "security": { "require_metrics_auth": false } }"#,
+    );
+    let valid = try_load();
+    env::remove_var("SIGNAL_FISH_CONFIG_JSON");
+
+    let err = invalid_json
+        .expect_err("invalid JSON is rejected")
+        .to_string();
+    assert!(err.contains("SIGNAL_FISH_CONFIG_JSON"), "{err}");
+    let err = invalid_value
+        .expect_err("invalid value is rejected")
+        .to_string();
+    assert!(err.starts_with("server.ping_timeout"), "{err}");
+    assert!(
+        insecure.is_err(),
+        "config failing security validation is rejected"
+    );
+    assert_eq!(valid.expect("valid config loads").port, 4000);
+}
+
+#[test]
+#[serial_test::serial]
+fn test_try_load_from_reads_toml_yaml_and_extensionless_files() {
+    use signal_fish_server::config::try_load_from;
+
+    let dir = tempfile::tempdir().expect("tempdir");
+    let toml = dir.path().join("server.toml");
+    std::fs::write(
+        &toml,
+        "port = 4100\n\n[server]\nping_timeout = \"45s\"\n\n[security]\nrequire_metrics_auth = false\n",
+    )
+    .expect("write toml");
+    let yaml = dir.path().join("server.yml");
+    std::fs::write(
+        &yaml,
+        "port: 4200\nsecurity:\n  require_metrics_auth: false\n",
+    )
+    .expect("write yaml");
+    let bare = dir.path().join("server");
+    std::fs::write(
+        &bare,
+        "port = 4300\n[security]\nrequire_metrics_auth = false\n",
+    )
+    .expect("write extensionless");
+    let broken = dir.path().join("broken.toml");
+    std::fs::write(&broken, "port = ").expect("write broken toml");
+
+    let from_toml = try_load_from(Some(&toml)).expect("TOML config loads");
+    assert_eq!(from_toml.port, 4100);
+    assert_eq!(from_toml.server.ping_timeout.as_secs(), 45);
+    assert_eq!(
+        try_load_from(Some(&yaml)).expect("YAML config loads").port,
+        4200
+    );
+    assert_eq!(
+        try_load_from(Some(&bare))
+            .expect("extensionless TOML config loads")
+            .port,
+        4300
+    );
+    let err = try_load_from(Some(&broken))
+        .expect_err("invalid TOML is rejected")
+        .to_string();
+    assert!(err.contains("broken.toml"), "{err}");
+    let err = try_load_from(Some(&dir.path().join("missing.json")))
+        .expect_err("missing explicit file is rejected")
+        .to_string();
+    assert!(err.contains("does not exist"), "{err}");
+}
+
+#[test]
+#[serial_test::serial]
+fn test_signalfish_env_overrides_win_and_name_invalid_variables() {
+    use signal_fish_server::config::{load_from, try_load_from};
+    use std::env;
+
+    let dir = tempfile::tempdir().expect("tempdir");
+    let file = dir.path().join("config.json");
+    std::fs::write(
+        &file,
+        r#"{ "server": { "default_max_players": 4 }, "security": { "require_metrics_auth": false } }"#,
+    )
+    .expect("write config");
+
+    env::set_var("SIGNALFISH_SERVER__DEFAULT_MAX_PLAYERS", "16");
+    env::set_var("SIGNAL_FISH__SERVER__DEFAULT_MAX_PLAYERS", "12");
+    env::set_var("SIGNAL_FISH__PORT", "4400");
+    let overridden = try_load_from(Some(&file));
+    env::set_var("SIGNALFISH_SERVER__DEFAULT_MAX_PLAYERS", "lots");
+    let invalid_at_startup = load_from(Some(&file));
+    let invalid_on_reload = try_load_from(Some(&file));
+    env::remove_var("SIGNALFISH_SERVER__DEFAULT_MAX_PLAYERS");
+    env::remove_var("SIGNAL_FISH__SERVER__DEFAULT_MAX_PLAYERS");
+    env::remove_var("SIGNAL_FISH__PORT");
+
+    let overridden = overridden.expect("overridden config loads");
+    assert_eq!(overridden.server.default_max_players, 16);
+    assert_eq!(overridden.port, 4400);
+    for result in [invalid_at_startup, invalid_on_reload] {
+        let err = result
+            .expect_err("invalid override is rejected")
+            .to_string();
+        assert!(
+            err.contains("SIGNALFISH_SERVER__DEFAULT_MAX_PLAYERS"),
+            "{err}"
+        );
+        assert!(err.contains("server.default_max_players"), "{err}");
+    }
+}
+
+#[test]
+#[serial_test::serial]
+fn test_load_from_str_and_reader_fill_defaults_and_apply_env_overrides() {
+    use signal_fish_server::config::{load_from_reader, load_from_str};
+    use std::env;
+
+    let json = r#"{ "port": 4100, "server": { "default_max_players": 6 } }"#;
+    let config = load_from_str(json).expect("fragment loads");
+    let defaults = Config::default();
+    assert_eq!(config.port, 4100);
+    assert_eq!(config.server.default_max_players, 6);
+    assert_eq!(
+        config.server.room_cleanup_interval,
+        defaults.server.room_cleanup_interval
+    );
+    assert_eq!(
+        config.security.require_metrics_auth,
+        defaults.security.require_metrics_auth
+    );
+    assert_eq!(config.websocket.batch_size, defaults.websocket.batch_size);
+
+    let from_reader = load_from_reader(json.as_bytes()).expect("reader loads");
+    assert_eq!(from_reader.port, 4100);
+    assert_eq!(load_from_str("{}").unwrap().port, defaults.port);
+
+    env::set_var("SIGNALFISH_SERVER__DEFAULT_MAX_PLAYERS", "10");
+    let overridden = load_from_str(json);
+    env::set_var("SIGNALFISH_SERVER__DEFAULT_MAX_PLAYERS", "lots");
+    let invalid = load_from_reader(json.as_bytes());
+    env::remove_var("SIGNALFISH_SERVER__DEFAULT_MAX_PLAYERS");
+
+    let overridden = overridden.expect("overridden fragment loads");
+    assert_eq!(overridden.server.default_max_players, 10);
+    assert_eq!(overridden.port, 4100);
+    let err = invalid
+        .expect_err("invalid override is rejected")
+        .to_string();
+    assert!(
+        err.contains("SIGNALFISH_SERVER__DEFAULT_MAX_PLAYERS"),
+        "{err}"
+    );
+
+    let err = load_from_str("{ not json").expect_err("malformed JSON is rejected");
+    assert!(err.to_string().contains("parse"), "{err}");
+}
+
+#[test]
+fn test_config_units_round_trip_to_numeric_form() {
+    let json = r#"{
+        "server": { "reconnection_window": "10m" },
+        "security": { "max_message_size": "1mb" },
+        "websocket": { "batch_interval_ms": "1s" }
+    }"#;
+    let config: Config = serde_json::from_str(json).expect("parse should succeed");
+
+    let printed = serde_json::to_value(&config).expect("serialize should succeed");
+    assert_eq!(printed["server"]["reconnection_window"], 600);
+    assert_eq!(printed["security"]["max_message_size"], 1_048_576);
+    assert_eq!(printed["websocket"]["batch_interval_ms"], 1000);
+
+    let reparsed: Config = serde_json::from_value(printed).expect("reparse should succeed");
+    assert_eq!(
+        reparsed.server.reconnection_window,
+        config.server.reconnection_window
+    );
+    assert_eq!(
+        reparsed.security.max_message_size,
+        config.security.max_message_size
+    );
+    assert_eq!(
+        reparsed.websocket.batch_interval_ms,
+        config.websocket.batch_interval_ms
+    );
 }
 
 // ===========================================================================
@@ -201,6 +466,41 @@ async fn test_health_endpoint_returns_ok() {
     response.assert_text("OK");
 }
 
+#[tokio::test]
+async fn test_liveness_probe_returns_ok() {
+    let server = create_test_server().await;
+    let app = create_router("*").with_state(server);
+
+    let test_server = axum_test::TestServer::new(app).expect("test server should start");
+    let response = test_server.get("/health/live").await;
+
+    response.assert_status_ok();
+    response.assert_json(&serde_json::json!({ "status": "alive" }));
+}
+
+#[tokio::test]
+async fn test_readiness_probe_waits_for_cleanup_task() {
+    let server = create_test_server().await;
+    let app = create_router("*").with_state(server.clone());
+    let test_server = axum_test::TestServer::new(app).expect("test server should start");
+
+    let response = test_server.get("/health/ready").await;
+    response.assert_status(axum::http::StatusCode::SERVICE_UNAVAILABLE);
+    response.assert_json(&serde_json::json!({
+        "status": "not_ready",
+        "reason": "cleanup task is not running",
+    }));
+
+    let cleanup_server = server.clone();
+    let cleanup = tokio::spawn(async move { cleanup_server.cleanup_task().await });
+    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+
+    let response = test_server.get("/health/ready").await;
+    response.assert_status_ok();
+    response.assert_json(&serde_json::json!({ "status": "ready" }));
+    cleanup.abort();
+}
+
 // ===========================================================================
 // Metrics endpoint tests
 // ===========================================================================
@@ -339,6 +639,412 @@ async fn test_prometheus_metrics_endpoint_returns_text() {
     assert!(body.contains("# TYPE"), "Should contain TYPE annotations");
 }
 
+// ===========================================================================
+// Matchmaking stats endpoint tests
+// ===========================================================================
+
+#[tokio::test]
+async fn test_matchmaking_stats_endpoint_reports_empty_queue() {
+    let mut config = test_server_config();
+    config.require_metrics_auth = false;
+
+    let server = test_helpers::create_test_server_with_config(
+        config,
+        signal_fish_server::config::ProtocolConfig::default(),
+    )
+    .await;
+
+    let app = create_router("*").with_state(server);
+    let test_server = axum_test::TestServer::new(app).expect("test server should start");
+
+    let response = test_server.get("/matchmaking/stats").await;
+    response.assert_status_ok();
+
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["totalQueued"], 0);
+    assert_eq!(json["queues"], serde_json::json!([]));
+}
+
+#[tokio::test]
+async fn test_matchmaking_stats_endpoint_requires_auth_when_configured() {
+    let mut config = test_server_config();
+    config.require_metrics_auth = true;
+    config.metrics_auth_token = Some("test-metrics-token".to_string());
+
+    let server = test_helpers::create_test_server_with_config(
+        config,
+        signal_fish_server::config::ProtocolConfig::default(),
+    )
+    .await;
+
+    let app = create_router("*").with_state(server);
+    let test_server = axum_test::TestServer::new(app).expect("test server should start");
+
+    let response = test_server.get("/matchmaking/stats").await;
+    response.assert_status(axum::http::StatusCode::UNAUTHORIZED);
+}
+
+async fn admin_test_server(
+    admin_token: Option<&str>,
+) -> (
+    std::sync::Arc<signal_fish_server::server::EnhancedGameServer>,
+    axum_test::TestServer,
+) {
+    let mut config = test_server_config();
+    config.metrics_auth_token = None;
+    config.admin_auth_token = admin_token.map(str::to_string);
+
+    let server = test_helpers::create_test_server_with_config(
+        config,
+        signal_fish_server::config::ProtocolConfig::default(),
+    )
+    .await;
+    let app = create_router("*").with_state(server.clone());
+    let test_server = axum_test::TestServer::new(app).expect("test server should start");
+    (server, test_server)
+}
+
+#[tokio::test]
+async fn test_admin_close_room_rejects_bad_auth() {
+    let (_server, test_server) = admin_test_server(Some("test-admin-token")).await;
+
+    let response = test_server.delete("/admin/rooms/chess/ABC123").await;
+    response.assert_status(axum::http::StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        response.json::<serde_json::Value>()["error"],
+        "unauthorized"
+    );
+
+    let response = test_server
+        .delete("/admin/rooms/chess/ABC123")
+        .authorization_bearer("wrong-token")
+        .await;
+    response.assert_status(axum::http::StatusCode::FORBIDDEN);
+
+    let (_server, unconfigured) = admin_test_server(None).await;
+    let response = unconfigured
+        .delete("/admin/rooms/chess/ABC123")
+        .authorization_bearer("anything")
+        .await;
+    response.assert_status(axum::http::StatusCode::FORBIDDEN);
+    assert_eq!(
+        response.json::<serde_json::Value>()["error"],
+        "admin_disabled"
+    );
+}
+
+#[tokio::test]
+async fn test_admin_close_room_unknown_room_returns_404() {
+    let (_server, test_server) = admin_test_server(Some("test-admin-token")).await;
+
+    let response = test_server
+        .delete("/admin/rooms/chess/NOPE42")
+        .authorization_bearer("test-admin-token")
+        .await;
+    response.assert_status(axum::http::StatusCode::NOT_FOUND);
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["error"], "room_not_found");
+    assert!(json["message"].as_str().unwrap().contains("NOPE42"));
+}
+
+#[tokio::test]
+async fn test_admin_close_room_reports_disconnected_players() {
+    let (server, test_server) = admin_test_server(Some("test-admin-token")).await;
+
+    for (index, addr) in ["127.0.0.1:48500", "127.0.0.2:48501"].iter().enumerate() {
+        let (sender, _receiver) = tokio::sync::mpsc::channel(32);
+        let player_id = server
+            .register_client(sender, addr.parse().unwrap())
+            .await
+            .expect("client registration succeeds");
+        server
+            .handle_join_room(
+                &player_id,
+                "chess".to_string(),
+                Some("ADM001".to_string()),
+                format!("Player{index}"),
+                Some(4),
+                Some(false),
+                None,
+                None,
+                None,
+            )
+            .await;
+    }
+
+    let response = test_server
+        .delete("/admin/rooms/chess/adm001")
+        .authorization_bearer("test-admin-token")
+        .await;
+    response.assert_status_ok();
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["roomCode"], "ADM001");
+    assert_eq!(json["playersDisconnected"], 2);
+
+    let response = test_server
+        .delete("/admin/rooms/chess/ADM001")
+        .authorization_bearer("test-admin-token")
+        .await;
+    response.assert_status(axum::http::StatusCode::NOT_FOUND);
+    assert_eq!(
+        server
+            .metrics()
+            .snapshot()
+            .await
+            .rooms
+            .rooms_closed_by_admin,
+        1
+    );
+}
+
+#[tokio::test]
+async fn test_admin_recent_closed_rooms_lists_closures() {
+    let (server, test_server) = admin_test_server(Some("test-admin-token")).await;
+
+    let response = test_server.get("/admin/rooms/recent-closed").await;
+    response.assert_status(axum::http::StatusCode::UNAUTHORIZED);
+
+    let (sender, _receiver) = tokio::sync::mpsc::channel(32);
+    let player_id = server
+        .register_client(sender, "127.0.0.1:48510".parse().unwrap())
+        .await
+        .expect("client registration succeeds");
+    server
+        .handle_join_room(
+            &player_id,
+            "chess".to_string(),
+            Some("REC001".to_string()),
+            "Player".to_string(),
+            Some(4),
+            Some(false),
+            None,
+            None,
+            None,
+        )
+        .await;
+    server
+        .admin_close_room("chess", "REC001")
+        .await
+        .unwrap()
+        .expect("room exists");
+
+    let response = test_server
+        .get("/admin/rooms/recent-closed")
+        .add_query_param("game", "chess")
+        .add_query_param("limit", 5)
+        .authorization_bearer("test-admin-token")
+        .await;
+    response.assert_status_ok();
+    let json: serde_json::Value = response.json();
+    let rooms = json["rooms"].as_array().expect("rooms array");
+    assert_eq!(rooms.len(), 1);
+    assert_eq!(rooms[0]["room_code"], "REC001");
+    assert_eq!(rooms[0]["reason"], "closed_by_admin");
+    assert_eq!(rooms[0]["peak_player_count"], 1);
+
+    let response = test_server
+        .get("/admin/rooms/recent-closed")
+        .add_query_param("game", "checkers")
+        .authorization_bearer("test-admin-token")
+        .await;
+    response.assert_json(&serde_json::json!({ "rooms": [] }));
+}
+
+#[tokio::test]
+async fn test_admin_announce_reaches_connected_and_late_players() {
+    let (server, test_server) = admin_test_server(Some("test-admin-token")).await;
+    let (early_tx, mut early_rx) = tokio::sync::mpsc::channel(8);
+    server
+        .register_client(early_tx, "127.0.0.1:45001".parse().unwrap())
+        .await
+        .unwrap();
+    let expires_at = chrono::Utc::now().timestamp() + 600;
+    let body = serde_json::json!({
+        "message": "Maintenance at 02:00 UTC",
+        "severity": "warning",
+        "expires_at": expires_at,
+    });
+
+    let response = test_server.post("/admin/announce").json(&body).await;
+    response.assert_status(axum::http::StatusCode::UNAUTHORIZED);
+
+    let response = test_server
+        .post("/admin/announce")
+        .authorization_bearer("test-admin-token")
+        .json(&serde_json::json!({
+            "message": "Too late",
+            "severity": "info",
+            "expires_at": chrono::Utc::now().timestamp() - 1,
+        }))
+        .await;
+    response.assert_status(axum::http::StatusCode::BAD_REQUEST);
+
+    let response = test_server
+        .post("/admin/announce")
+        .authorization_bearer("test-admin-token")
+        .json(&body)
+        .await;
+    response.assert_status_ok();
+    assert_eq!(response.json::<serde_json::Value>()["recipients"], 1);
+
+    let announcement = serde_json::to_value(&*early_rx.try_recv().unwrap()).unwrap();
+    assert_eq!(
+        announcement,
+        serde_json::json!({
+            "type": "ServerAnnouncement",
+            "data": {
+                "message": "Maintenance at 02:00 UTC",
+                "severity": "warning",
+                "expires_at_ms": expires_at * 1000,
+            },
+        })
+    );
+
+    let late = server
+        .active_announcement()
+        .expect("the announcement is kept until it expires");
+    assert_eq!(serde_json::to_value(&*late).unwrap(), announcement);
+    assert!(test_server
+        .get("/metrics/prom")
+        .await
+        .text()
+        .contains("signal_fish_server_announcements_sent_total 1"));
+}
+
+#[tokio::test]
+async fn test_admin_drain_flips_readiness_and_metrics() {
+    let (server, test_server) = admin_test_server(Some("test-admin-token")).await;
+    let cleanup_server = server.clone();
+    let cleanup = tokio::spawn(async move { cleanup_server.cleanup_task().await });
+    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+
+    let response = test_server.post("/admin/drain").await;
+    response.assert_status(axum::http::StatusCode::UNAUTHORIZED);
+    assert!(!server.is_draining());
+
+    let response = test_server
+        .post("/admin/drain")
+        .authorization_bearer("test-admin-token")
+        .await;
+    response.assert_status_ok();
+    response.assert_json(&serde_json::json!({ "draining": true, "changed": true }));
+
+    let response = test_server.get("/health/ready").await;
+    response.assert_status(axum::http::StatusCode::SERVICE_UNAVAILABLE);
+    response.assert_json(&serde_json::json!({
+        "status": "not_ready",
+        "reason": "instance is draining",
+    }));
+    test_server.get("/health/live").await.assert_status_ok();
+    assert_eq!(
+        test_server
+            .get("/metrics")
+            .await
+            .json::<serde_json::Value>()["draining"],
+        true
+    );
+    assert!(test_server
+        .get("/metrics/prom")
+        .await
+        .text()
+        .contains("signal_fish_draining 1"));
+
+    let response = test_server
+        .post("/admin/undrain")
+        .authorization_bearer("test-admin-token")
+        .await;
+    response.assert_json(&serde_json::json!({ "draining": false, "changed": true }));
+    test_server.get("/health/ready").await.assert_status_ok();
+    assert!(test_server
+        .get("/metrics/prom")
+        .await
+        .text()
+        .contains("signal_fish_draining 0"));
+    cleanup.abort();
+}
+
+#[tokio::test]
+async fn test_room_events_stream_requires_admin_token_and_filters_by_game() {
+    use futures_util::StreamExt;
+    use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+
+    let mut config = test_server_config();
+    config.admin_auth_token = Some("test-admin-token".to_string());
+    let server = test_helpers::create_test_server_with_config(
+        config,
+        signal_fish_server::config::ProtocolConfig::default(),
+    )
+    .await;
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = axum::Router::new().nest("/v2", create_router("*").with_state(server.clone()));
+    tokio::spawn(async move {
+        axum::serve(
+            listener,
+            router.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+        )
+        .await
+        .unwrap();
+    });
+
+    let err = tokio_tungstenite::connect_async(format!("ws://{addr}/v2/events"))
+        .await
+        .expect_err("handshake without a token is rejected");
+    match err {
+        tokio_tungstenite::tungstenite::Error::Http(response) => {
+            assert_eq!(response.status(), axum::http::StatusCode::UNAUTHORIZED);
+        }
+        other => panic!("unexpected handshake error: {other:?}"),
+    }
+
+    let mut request = format!("ws://{addr}/v2/events?game_name=chess")
+        .into_client_request()
+        .unwrap();
+    request.headers_mut().insert(
+        axum::http::header::AUTHORIZATION,
+        "Bearer test-admin-token".parse().unwrap(),
+    );
+    let (mut events, _) = tokio_tungstenite::connect_async(request)
+        .await
+        .expect("authorized observer connects");
+    while !server.has_room_event_observers() {
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+    }
+
+    for (port, game) in [(48520, "checkers"), (48521, "chess")] {
+        let (sender, _receiver) = tokio::sync::mpsc::channel(32);
+        let player_id = server
+            .register_client(sender, format!("127.0.0.1:{port}").parse().unwrap())
+            .await
+            .expect("client registration succeeds");
+        server
+            .handle_join_room(
+                &player_id,
+                game.to_string(),
+                Some("EVT001".to_string()),
+                "Player".to_string(),
+                Some(4),
+                Some(false),
+                None,
+                None,
+                None,
+            )
+            .await;
+    }
+
+    let frame = tokio::time::timeout(std::time::Duration::from_secs(2), events.next())
+        .await
+        .expect("event arrives")
+        .expect("stream open")
+        .expect("valid frame");
+    let event: serde_json::Value = serde_json::from_str(frame.to_text().unwrap()).unwrap();
+    assert_eq!(event["type"], "room_created");
+    assert_eq!(event["game_name"], "chess");
+    assert_eq!(event["room_code"], "EVT001");
+    assert!(event["room_id"].is_string());
+    assert!(event["timestamp"].is_string());
+}
+
 // ===========================================================================
 // Router structure tests
 // ===========================================================================
@@ -361,6 +1067,358 @@ async fn test_websocket_route_exists() {
     );
 }
 
+struct HookState {
+    greeting: &'static str,
+}
+
+/// Reports its own state and the client fingerprint captured by the server middleware.
+async fn custom_hook(
+    axum::Extension(state): axum::Extension<std::sync::Arc<HookState>>,
+    fingerprint: Option<
+        axum::Extension<signal_fish_server::security::ClientCertificateFingerprint>,
+    >,
+) -> String {
+    let fingerprint = fingerprint
+        .map(|axum::Extension(f)| f.fingerprint.to_string())
+        .unwrap_or_default();
+    format!("{}:{fingerprint}", state.greeting)
+}
+
+#[tokio::test]
+async fn test_embedded_custom_route_shares_router_and_middleware() {
+    use signal_fish_server::websocket::{build_router, with_middleware};
+
+    let config = Config::default();
+    let server = create_test_server().await;
+    let custom = axum::Router::new()
+        .route("/my/hook", axum::routing::get(custom_hook))
+        .layer(axum::Extension(std::sync::Arc::new(HookState {
+            greeting: "hello",
+        })));
+    let app = with_middleware(build_router(&config, server.clone()).merge(custom), &server);
+    let test_server = axum_test::TestServer::new(app).expect("test server should start");
+
+    let response = test_server
+        .get("/my/hook")
+        .add_header("x-signalfish-client-cert-sha256", "abc123")
+        .add_header("origin", "http://localhost:3000")
+        .await;
+    response.assert_status_ok();
+    response.assert_text("hello:abc123");
+    assert_eq!(
+        response.header("access-control-allow-origin"),
+        "http://localhost:3000",
+        "CORS applies to merged routes"
+    );
+
+    let response = test_server.get("/v2/ws").await;
+    assert_ne!(
+        response.status_code(),
+        axum::http::StatusCode::NOT_FOUND,
+        "/v2/ws route should exist alongside custom routes"
+    );
+    test_server.get("/v2/health").await.assert_status_ok();
+}
+
+fn gunzip(body: &[u8]) -> Vec<u8> {
+    use std::io::Read;
+
+    let mut decoded = Vec::new();
+    flate2::read::GzDecoder::new(body)
+        .read_to_end(&mut decoded)
+        .expect("body is valid gzip");
+    decoded
+}
+
+#[tokio::test]
+async fn test_metrics_responses_are_gzipped_when_accepted() {
+    use signal_fish_server::websocket::{build_router, with_middleware};
+
+    let mut server_config = test_server_config();
+    server_config.require_metrics_auth = true;
+    server_config.metrics_auth_token = Some("metrics-token".to_string());
+    server_config.admin_auth_token = Some("admin-token".to_string());
+    let server = test_helpers::create_test_server_with_config(
+        server_config,
+        signal_fish_server::config::ProtocolConfig::default(),
+    )
+    .await;
+    let config = Config::default();
+    let app = with_middleware(build_router(&config, server.clone()), &server);
+    let test_server = axum_test::TestServer::new(app).expect("test server should start");
+
+    let plain = test_server
+        .get("/v1/metrics")
+        .authorization_bearer("metrics-token")
+        .await;
+    plain.assert_status_ok();
+    assert!(plain.maybe_header("content-encoding").is_none());
+    assert_eq!(plain.header("vary"), "accept-encoding");
+    let plain: serde_json::Value = plain.json();
+
+    let gzipped = test_server
+        .get("/v1/metrics")
+        .authorization_bearer("metrics-token")
+        .add_header("accept-encoding", "br;q=1.0, gzip;q=0.8")
+        .await;
+    gzipped.assert_status_ok();
+    assert_eq!(gzipped.header("content-encoding"), "gzip");
+    assert_eq!(gzipped.header("content-type"), "application/json");
+    let decoded: serde_json::Value = serde_json::from_slice(&gunzip(gzipped.as_bytes())).unwrap();
+    let keys = |value: &serde_json::Value| {
+        value
+            .as_object()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(keys(&decoded), keys(&plain));
+    assert_eq!(decoded["serverMetrics"], plain["serverMetrics"]);
+
+    // Auth still runs first; the small rejection is below the size threshold.
+    let rejected = test_server
+        .get("/v1/metrics")
+        .add_header("accept-encoding", "gzip")
+        .await;
+    rejected.assert_status(axum::http::StatusCode::UNAUTHORIZED);
+    assert!(rejected.maybe_header("content-encoding").is_none());
+
+    let closed = test_server
+        .get("/v2/admin/rooms/recent-closed")
+        .authorization_bearer("admin-token")
+        .add_header("accept-encoding", "gzip")
+        .await;
+    closed.assert_status_ok();
+    assert!(closed.maybe_header("content-encoding").is_none());
+    closed.assert_json(&serde_json::json!({ "rooms": [] }));
+
+    let health = test_server
+        .get("/v2/health")
+        .add_header("accept-encoding", "gzip")
+        .await;
+    assert!(
+        !health
+            .headers()
+            .get_all("vary")
+            .iter()
+            .any(|value| value == "accept-encoding"),
+        "health probes bypass compression"
+    );
+}
+
+#[tokio::test]
+async fn test_http_compression_threshold_and_disable_flag() {
+    use signal_fish_server::config::{ByteSize, HttpCompressionConfig};
+    use signal_fish_server::websocket::build_router;
+
+    let (server, _) = admin_test_server(Some("admin-token")).await;
+    let mut config = Config::default();
+    config.metrics.http_compression.min_size = ByteSize::from_bytes(0);
+    let test_server = axum_test::TestServer::new(build_router(&config, server.clone()))
+        .expect("test server should start");
+
+    let response = test_server
+        .get("/v2/admin/rooms/recent-closed")
+        .authorization_bearer("admin-token")
+        .add_header("accept-encoding", "gzip")
+        .await;
+    response.assert_status_ok();
+    assert_eq!(response.header("content-encoding"), "gzip");
+    let plain = test_server
+        .get("/v2/admin/rooms/recent-closed")
+        .authorization_bearer("admin-token")
+        .await;
+    assert_eq!(gunzip(response.as_bytes()), plain.as_bytes().to_vec());
+
+    config.metrics.http_compression = HttpCompressionConfig {
+        enabled: false,
+        ..HttpCompressionConfig::default()
+    };
+    let test_server = axum_test::TestServer::new(build_router(&config, server))
+        .expect("test server should start");
+    let response = test_server
+        .get("/v1/metrics")
+        .add_header("accept-encoding", "gzip")
+        .await;
+    response.assert_status_ok();
+    assert!(response.maybe_header("content-encoding").is_none());
+    assert!(response.maybe_header("vary").is_none());
+}
+
+#[tokio::test]
+async fn test_websocket_upgrade_is_not_compressed() {
+    use signal_fish_server::websocket::build_router;
+    use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+
+    let server = create_test_server().await;
+    let mut config = Config::default();
+    config.metrics.http_compression.min_size = signal_fish_server::config::ByteSize::from_bytes(0);
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = build_router(&config, server);
+    tokio::spawn(async move {
+        axum::serve(
+            listener,
+            router.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+        )
+        .await
+        .unwrap();
+    });
+
+    let mut request = format!("ws://{addr}/v2/ws").into_client_request().unwrap();
+    request
+        .headers_mut()
+        .insert(axum::http::header::ACCEPT_ENCODING, "gzip".parse().unwrap());
+    let (_socket, response) = tokio_tungstenite::connect_async(request)
+        .await
+        .expect("upgrade succeeds");
+    assert_eq!(
+        response.status(),
+        axum::http::StatusCode::SWITCHING_PROTOCOLS
+    );
+    assert!(response.headers().get("content-encoding").is_none());
+    assert!(response.headers().get("vary").is_none());
+}
+
+#[tokio::test]
+async fn test_reloaded_cors_origins_apply_without_rebuilding_router() {
+    use signal_fish_server::websocket::{build_router, with_middleware};
+
+    let config = Config::default();
+    let server = create_test_server().await;
+    let app = with_middleware(build_router(&config, server.clone()), &server);
+    let test_server = axum_test::TestServer::new(app).expect("test server should start");
+
+    let allowed_origin = |origin: &'static str| {
+        let request = test_server.get("/v2/health").add_header("origin", origin);
+        async move {
+            request
+                .await
+                .maybe_header("access-control-allow-origin")
+                .map(|value| value.to_str().unwrap().to_string())
+        }
+    };
+    assert_eq!(
+        allowed_origin("http://localhost:3000").await.as_deref(),
+        Some("http://localhost:3000")
+    );
+
+    server.reload_config(&ServerConfig {
+        cors_origins: "https://example.com".to_string(),
+        ..ServerConfig::clone(&server.config())
+    });
+    assert_eq!(allowed_origin("http://localhost:3000").await, None);
+    assert_eq!(
+        allowed_origin("https://example.com").await.as_deref(),
+        Some("https://example.com")
+    );
+}
+
+#[tokio::test]
+async fn test_metrics_routes_follow_their_own_cors_origins() {
+    use signal_fish_server::websocket::{build_router, with_middleware};
+
+    let server = create_test_server().await;
+    server.reload_config(&ServerConfig {
+        cors_origins: "*".to_string(),
+        metrics_cors_origins: "https://ops.example.com".to_string(),
+        ..ServerConfig::clone(&server.config())
+    });
+    let app = with_middleware(build_router(&Config::default(), server.clone()), &server);
+    let test_server = axum_test::TestServer::new(app).expect("test server should start");
+
+    let allowed_origin = |path: &'static str, origin: &'static str| {
+        let request = test_server.get(path).add_header("origin", origin);
+        async move {
+            request
+                .await
+                .maybe_header("access-control-allow-origin")
+                .map(|value| value.to_str().unwrap().to_string())
+        }
+    };
+    assert!(allowed_origin("/v2/health", "https://players.example")
+        .await
+        .is_some());
+    for path in ["/metrics", "/v1/metrics", "/v2/metrics", "/v2/stats"] {
+        assert_eq!(
+            allowed_origin(path, "https://players.example").await,
+            None,
+            "{path} refuses game origins"
+        );
+        assert_eq!(
+            allowed_origin(path, "https://ops.example.com")
+                .await
+                .as_deref(),
+            Some("https://ops.example.com"),
+            "{path} allows the metrics origin"
+        );
+    }
+}
+
+#[test]
+fn test_cors_route_groups_fall_back_to_cors_origins() {
+    let mut security: signal_fish_server::config::SecurityConfig =
+        serde_json::from_str(r#"{"cors_origins": "https://game.example"}"#).unwrap();
+    assert_eq!(security.game_cors_origins(), "https://game.example");
+    assert_eq!(security.metrics_cors_origins(), "https://game.example");
+
+    security = serde_json::from_str(
+        r#"{"cors_origins": "*", "cors": {"metrics_origins": "https://ops.example"}}"#,
+    )
+    .unwrap();
+    assert_eq!(security.game_cors_origins(), "*");
+    assert_eq!(security.metrics_cors_origins(), "https://ops.example");
+}
+
+#[tokio::test]
+async fn test_ip_blocklist_and_allowlist_refuse_requests_with_403() {
+    use signal_fish_server::security::IpFilter;
+    use signal_fish_server::websocket::{build_router, with_middleware};
+
+    let mut server_config = test_server_config();
+    server_config.trusted_proxies = serde_json::from_str(r#"["127.0.0.1"]"#).unwrap();
+    server_config.ip_filter = IpFilter::new(
+        Some(vec!["10.0.0.0/8".parse().unwrap()]),
+        vec!["10.0.0.13".parse().unwrap()],
+    );
+    let server = test_helpers::create_test_server_with_config(
+        server_config,
+        signal_fish_server::config::ProtocolConfig::default(),
+    )
+    .await;
+    let app = with_middleware(build_router(&Config::default(), server.clone()), &server);
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+        )
+        .await
+        .unwrap();
+    });
+
+    let client = reqwest::Client::new();
+    let health_from = |forwarded_for: &'static str| {
+        client
+            .get(format!("http://{addr}/v2/health"))
+            .header("x-forwarded-for", forwarded_for)
+            .send()
+    };
+    assert_eq!(health_from("10.1.2.3").await.unwrap().status(), 200);
+
+    let blocked = health_from("10.0.0.13").await.unwrap();
+    assert_eq!(blocked.status(), 403);
+    let body: serde_json::Value = blocked.json().await.unwrap();
+    assert_eq!(body["error"], "ip_forbidden");
+    assert_eq!(health_from("203.0.113.9").await.unwrap().status(), 403);
+
+    let connections = server.metrics().snapshot().await.connections;
+    assert_eq!(connections.connections_blocked_blocklist, 1);
+    assert_eq!(connections.connections_blocked_allowlist, 1);
+}
+
 #[tokio::test]
 async fn test_unknown_route_returns_404() {
     let server = create_test_server().await;
@@ -399,11 +1457,26 @@ async fn test_specific_cors_origins() {
 // Config validation tests
 // ===========================================================================
 
-use signal_fish_server::config::validate_config_security;
+use signal_fish_server::config::{validate_config_security, ConfigDuration};
 
 /// A config-validation test scenario: (name, config_modifier, expected_ok).
 type ValidationScenario = (&'static str, Box<dyn Fn(&mut Config)>, bool);
 
+fn app_with_api_keys(app_id: &str, api_keys: &[&str]) -> signal_fish_server::config::AppAuthEntry {
+    signal_fish_server::config::AppAuthEntry {
+        app_id: app_id.to_string(),
+        app_secret: format!("{app_id}-secret"),
+        app_name: app_id.to_string(),
+        max_rooms: None,
+        max_rooms_per_app: None,
+        max_players_per_app: None,
+        max_rooms_per_hour: None,
+        max_players_per_room: None,
+        rate_limit_per_minute: None,
+        api_keys: api_keys.iter().map(ToString::to_string).collect(),
+    }
+}
+
 /// The default Config has `require_metrics_auth = true` but no
 /// `metrics_auth_token`, so validation MUST fail.  This is the exact
 /// scenario that caused the Docker CI startup failure.
@@ -502,6 +1575,78 @@ fn test_config_validation_scenarios() {
             }),
             true,
         ),
+        (
+            "distinct API keys per app → passes",
+            Box::new(|c: &mut Config| {
+                c.security.require_metrics_auth = false;
+                c.security.authorized_apps = vec![
+                    app_with_api_keys("game-a", &["key-a"]),
+                    app_with_api_keys("game-b", &["key-b"]),
+                ];
+            }),
+            true,
+        ),
+        (
+            "API key shared by two apps → fails",
+            Box::new(|c: &mut Config| {
+                c.security.require_metrics_auth = false;
+                c.security.authorized_apps = vec![
+                    app_with_api_keys("game-a", &["shared"]),
+                    app_with_api_keys("game-b", &["shared"]),
+                ];
+            }),
+            false,
+        ),
+        (
+            "empty API key → fails",
+            Box::new(|c: &mut Config| {
+                c.security.require_metrics_auth = false;
+                c.security.authorized_apps = vec![app_with_api_keys("game-a", &[" "])];
+            }),
+            false,
+        ),
+        (
+            "flap suppression longer than the reconnection window → fails",
+            Box::new(|c: &mut Config| {
+                c.security.require_metrics_auth = false;
+                c.server.reconnection_window = ConfigDuration::from_secs(3);
+                c.server.flap_suppression_secs = ConfigDuration::from_secs(5);
+            }),
+            false,
+        ),
+        (
+            "banned player name word without letters → fails",
+            Box::new(|c: &mut Config| {
+                c.security.require_metrics_auth = false;
+                c.protocol.player_name_validation.banned_words = vec!["--".to_string()];
+            }),
+            false,
+        ),
+        (
+            "IPv6 connection prefix of zero → fails",
+            Box::new(|c: &mut Config| {
+                c.security.require_metrics_auth = false;
+                c.security.ipv6_connection_prefix = 0;
+            }),
+            false,
+        ),
+        (
+            "file storage → passes only with the persistent feature",
+            Box::new(|c: &mut Config| {
+                c.security.require_metrics_auth = false;
+                c.storage.backend = signal_fish_server::config::StorageBackend::File;
+            }),
+            cfg!(feature = "persistent"),
+        ),
+        (
+            "file storage without a path → fails",
+            Box::new(|c: &mut Config| {
+                c.security.require_metrics_auth = false;
+                c.storage.backend = signal_fish_server::config::StorageBackend::File;
+                c.storage.path = String::new();
+            }),
+            false,
+        ),
     ];
 
     for (name, modifier, expected_ok) in &scenarios {
@@ -536,6 +1681,38 @@ fn test_docker_default_config_passes_validation() {
     );
 }
 
+/// Bucket lists that would render a malformed Prometheus histogram are rejected
+/// with a message naming the offending setting.
+#[test]
+fn test_invalid_histogram_buckets_fail_validation() {
+    for (buckets, expected) in [
+        (vec![], "at least one bucket"),
+        (vec![5.0, 0.0, 10.0], "positive numbers (found 0)"),
+        (
+            vec![10.0, 5.0],
+            "strictly increasing order (10 is followed by 5)",
+        ),
+        (vec![5.0, 5.0], "strictly increasing order"),
+    ] {
+        let mut config = Config::default();
+        config.security.require_metrics_auth = false;
+        config.metrics.histogram_buckets = buckets.clone();
+
+        let err = validate_config_security(&config)
+            .expect_err("invalid buckets must fail validation")
+            .to_string();
+        assert!(
+            err.contains("metrics.histogram_buckets") && err.contains(expected),
+            "buckets {buckets:?}: unexpected error: {err}"
+        );
+    }
+
+    let mut config = Config::default();
+    config.security.require_metrics_auth = false;
+    config.metrics.histogram_buckets = vec![0.5, 2.0, 30.0];
+    assert!(validate_config_security(&config).is_ok());
+}
+
 /// Data-driven test for TLS validation edge cases.
 #[test]
 fn test_validate_config_security_tls_validation() {
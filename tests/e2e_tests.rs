@@ -89,7 +89,30 @@ async fn connect_client(
             .expect("Failed to connect");
 
     println!("WebSocket connection established");
-    ws_stream.split()
+    let (sender, mut receiver) = ws_stream.split();
+
+    // Every connection opens with the ProtocolInfo handshake
+    match next_server_message(&mut receiver).await {
+        ServerMessage::ProtocolInfo(_) => {}
+        other => panic!("Expected ProtocolInfo, got {other:?}"),
+    }
+    (sender, receiver)
+}
+
+/// Helper to read the next JSON server message
+async fn next_server_message(
+    receiver: &mut futures_util::stream::SplitStream<
+        tokio_tungstenite::WebSocketStream<
+            tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
+        >,
+    >,
+) -> ServerMessage {
+    let frame = tokio::time::timeout(tokio::time::Duration::from_secs(5), receiver.next())
+        .await
+        .expect("server should send a message")
+        .expect("socket should stay open")
+        .unwrap();
+    serde_json::from_str(&frame.into_text().unwrap()).unwrap()
 }
 
 /// Helper to send a message and return the response
@@ -125,40 +148,133 @@ async fn send_and_receive(
 #[tokio::test]
 async fn test_websocket_connection_limit_enforced() {
     let mut server_config = test_server_config();
-    server_config.max_connections_per_ip = 1;
-
-    let addr = start_test_server_with_config(server_config).await;
+    server_config.max_connections_per_ip = 3;
+    let game_server = create_test_server_with_config(server_config, test_protocol_config()).await;
+    let addr = start_server_with_instance(game_server.clone()).await;
 
-    let (mut sender1, receiver1) = connect_client(addr, "/v2/ws").await;
+    let mut clients = Vec::new();
+    for _ in 0..3 {
+        clients.push(connect_client(addr, "/v2/ws").await);
+    }
 
-    let (_sender2, mut receiver2) = connect_client(addr, "/v2/ws").await;
-    let frame = tokio::time::timeout(tokio::time::Duration::from_secs(2), receiver2.next())
+    let err = connect_async(format!("ws://{addr}/v2/ws"))
         .await
-        .expect("expected response frame")
-        .expect("WebSocket frame result");
-
-    match frame {
-        Ok(Message::Text(text)) => {
-            let server_msg: ServerMessage =
-                serde_json::from_str(&text).expect("valid ServerMessage");
-            match server_msg {
-                ServerMessage::Error { error_code, .. } => {
-                    assert_eq!(
-                        error_code,
-                        Some(ErrorCode::TooManyConnections),
-                        "second connection should be rejected"
-                    );
-                }
-                other => panic!("expected error message, got {other:?}"),
+        .expect_err("connection over the per-IP limit is refused");
+    match err {
+        tokio_tungstenite::tungstenite::Error::Http(response) => {
+            assert_eq!(response.status(), axum::http::StatusCode::TOO_MANY_REQUESTS);
+        }
+        other => panic!("expected HTTP 429, got {other:?}"),
+    }
+    assert_eq!(
+        game_server
+            .metrics()
+            .snapshot()
+            .await
+            .connections
+            .connections_rejected_ip_limit,
+        1
+    );
+
+    // Closing a connection frees its slot for the next client.
+    let (mut sender, receiver) = clients.pop().unwrap();
+    let _ = sender.close().await;
+    drop(receiver);
+    let reconnected = tokio::time::timeout(tokio::time::Duration::from_secs(5), async {
+        loop {
+            if let Ok(client) = connect_async(format!("ws://{addr}/v2/ws")).await {
+                return client;
             }
+            tokio::time::sleep(tokio::time::Duration::from_millis(20)).await;
+        }
+    })
+    .await;
+    assert!(reconnected.is_ok(), "slot is released on disconnect");
+}
+
+#[tokio::test]
+async fn test_websocket_connection_rate_limit_allows_a_burst() {
+    let mut server_config = test_server_config();
+    server_config.rate_limit_config.connection_bucket =
+        Some(signal_fish_server::config::TokenBucketConfig {
+            capacity: 3,
+            refill_per_sec: 0.01,
+        });
+    let game_server = create_test_server_with_config(server_config, test_protocol_config()).await;
+    let addr = start_server_with_instance(game_server.clone()).await;
+
+    // Reconnecting after each close still draws from the same allowance
+    for _ in 0..3 {
+        let (mut sender, _receiver) = connect_client(addr, "/v2/ws").await;
+        let _ = sender.close().await;
+    }
+
+    let err = connect_async(format!("ws://{addr}/v2/ws"))
+        .await
+        .expect_err("connections beyond the burst are refused");
+    match err {
+        tokio_tungstenite::tungstenite::Error::Http(response) => {
+            assert_eq!(response.status(), axum::http::StatusCode::TOO_MANY_REQUESTS);
+            assert!(response.headers().contains_key("retry-after"));
         }
-        Ok(other) => panic!("expected text error frame, got {other:?}"),
-        Err(err) => panic!("WebSocket error received: {err:?}"),
+        other => panic!("expected HTTP 429, got {other:?}"),
+    }
+    assert_eq!(
+        game_server
+            .metrics()
+            .snapshot()
+            .await
+            .connections
+            .connections_rejected_rate_limit,
+        1
+    );
+}
+
+#[tokio::test]
+async fn test_connection_limit_keys_on_forwarded_client_only_behind_trusted_proxy() {
+    use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+
+    async fn connect_forwarded_for(
+        addr: std::net::SocketAddr,
+        forwarded_for: &str,
+    ) -> Result<(), tokio_tungstenite::tungstenite::Error> {
+        let mut request = format!("ws://{addr}/v2/ws").into_client_request().unwrap();
+        request
+            .headers_mut()
+            .insert("X-Forwarded-For", forwarded_for.parse().unwrap());
+        // Leak the socket so the connection keeps its slot for the test
+        connect_async(request).await.map(std::mem::forget)
     }
 
-    // First connection should remain active; close it cleanly
-    let _ = sender1.close().await;
-    drop(receiver1);
+    // Untrusted peer: spoofed headers cannot buy extra slots
+    let mut server_config = test_server_config();
+    server_config.max_connections_per_ip = 2;
+    let addr = start_test_server_with_config(server_config).await;
+    connect_forwarded_for(addr, "198.51.100.1").await.unwrap();
+    connect_forwarded_for(addr, "198.51.100.2").await.unwrap();
+    assert!(
+        connect_forwarded_for(addr, "198.51.100.3").await.is_err(),
+        "spoofed X-Forwarded-For from an untrusted peer is ignored"
+    );
+
+    // Trusted proxy: each forwarded client has its own budget
+    let mut server_config = test_server_config();
+    server_config.max_connections_per_ip = 2;
+    server_config.trusted_proxies = serde_json::from_str(r#"["127.0.0.1"]"#).unwrap();
+    let addr = start_test_server_with_config(server_config).await;
+    for client in [
+        "198.51.100.1",
+        "198.51.100.1",
+        "198.51.100.2",
+        "198.51.100.2",
+    ] {
+        connect_forwarded_for(addr, client).await.unwrap();
+    }
+    assert!(connect_forwarded_for(addr, "198.51.100.2").await.is_err());
+    // A hop prepended by the client does not change who the proxy saw
+    assert!(connect_forwarded_for(addr, "203.0.113.7, 198.51.100.1")
+        .await
+        .is_err());
 }
 
 #[tokio::test]
@@ -214,6 +330,138 @@ async fn test_websocket_connection() {
     .expect("Test timed out after 30 seconds");
 }
 
+#[tokio::test]
+async fn test_e2e_protocol_info_sent_once_on_connect() {
+    let addr = start_test_server_with_config(test_server_config()).await;
+    let (ws_stream, _) = connect_async(format!("ws://{addr}/v2/ws"))
+        .await
+        .expect("Failed to connect");
+    let (mut sender, mut receiver) = ws_stream.split();
+
+    match next_server_message(&mut receiver).await {
+        ServerMessage::ProtocolInfo(info) => {
+            let protocol = test_protocol_config();
+            assert_eq!(info.room_code_length, protocol.room_code_length);
+            assert_eq!(info.max_players, protocol.max_players_limit);
+            let rules = info.player_name_rules.expect("player name rules");
+            assert_eq!(rules.max_length, protocol.max_player_name_length);
+            assert_eq!(
+                rules.allowed_symbols,
+                protocol.player_name_validation.allowed_symbols
+            );
+            assert_eq!(
+                info.game_data_formats,
+                protocol.supported_game_data_formats()
+            );
+        }
+        other => panic!("Expected ProtocolInfo, got {other:?}"),
+    }
+
+    // Authenticating a connection that needs no auth does not repeat it
+    let authenticate = ClientMessage::Authenticate {
+        app_id: "test-app".to_string(),
+        sdk_version: None,
+        platform: None,
+        game_data_format: None,
+        capabilities: Vec::new(),
+    };
+    sender
+        .send(Message::Text(
+            serde_json::to_string(&authenticate).unwrap().into(),
+        ))
+        .await
+        .unwrap();
+    let pong = send_and_receive(&mut sender, &mut receiver, ClientMessage::Ping)
+        .await
+        .unwrap();
+    assert!(matches!(pong, ServerMessage::Pong), "got {pong:?}");
+}
+
+#[tokio::test]
+async fn test_e2e_protocol_info_can_be_disabled() {
+    let addr = start_test_server_with_config_and_protocol(
+        test_server_config(),
+        signal_fish_server::config::ProtocolConfig {
+            send_protocol_info: false,
+            ..test_protocol_config()
+        },
+    )
+    .await;
+    let (ws_stream, _) = connect_async(format!("ws://{addr}/v2/ws"))
+        .await
+        .expect("Failed to connect");
+    let (mut sender, mut receiver) = ws_stream.split();
+
+    let pong = send_and_receive(&mut sender, &mut receiver, ClientMessage::Ping)
+        .await
+        .unwrap();
+    assert!(matches!(pong, ServerMessage::Pong), "got {pong:?}");
+}
+
+fn sdk_upgrade_request(
+    addr: std::net::SocketAddr,
+    platform: &str,
+    version: &str,
+) -> tokio_tungstenite::tungstenite::handshake::client::Request {
+    use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+
+    let mut request = format!("ws://{addr}/v2/ws").into_client_request().unwrap();
+    let headers = request.headers_mut();
+    headers.insert("X-SDK-Platform", platform.parse().unwrap());
+    headers.insert("X-SDK-Version", version.parse().unwrap());
+    request
+}
+
+#[tokio::test]
+async fn test_e2e_outdated_sdk_gets_upgrade_hint_and_is_closed() {
+    let addr = start_test_server_with_config(test_server_config()).await;
+    let (ws_stream, _) = connect_async(sdk_upgrade_request(addr, "Unity", "1.5.0"))
+        .await
+        .expect("Failed to connect");
+    let (_sender, mut receiver) = ws_stream.split();
+
+    match next_server_message(&mut receiver).await {
+        ServerMessage::IncompatibleClient {
+            reason,
+            min_version,
+            recommended_version,
+        } => {
+            assert!(reason.contains("1.10.0"), "reason: {reason}");
+            assert_eq!(min_version.as_deref(), Some("1.10.0"));
+            assert_eq!(recommended_version.as_deref(), Some("1.12.0"));
+        }
+        other => panic!("Expected IncompatibleClient, got {other:?}"),
+    }
+
+    let close = loop {
+        let frame = tokio::time::timeout(tokio::time::Duration::from_secs(5), receiver.next())
+            .await
+            .expect("server should close the connection")
+            .expect("socket should deliver the close frame")
+            .unwrap();
+        if let Message::Close(close) = frame {
+            break close.expect("close frame carries a code");
+        }
+    };
+    assert_eq!(
+        u16::from(close.code),
+        ErrorCode::SdkVersionUnsupported.close_code()
+    );
+
+    // A supported SDK is described in ProtocolInfo instead
+    let (ws_stream, _) = connect_async(sdk_upgrade_request(addr, "Unity", "1.12.0"))
+        .await
+        .expect("Failed to connect");
+    let (_sender, mut receiver) = ws_stream.split();
+    match next_server_message(&mut receiver).await {
+        ServerMessage::ProtocolInfo(info) => {
+            assert_eq!(info.platform.as_deref(), Some("Unity"));
+            assert!(info.capabilities.contains(&"relay-mirror".to_string()));
+        }
+        other => panic!("Expected ProtocolInfo, got {other:?}"),
+    }
+}
+
 #[tokio::test]
 async fn test_room_creation_and_joining() {
     let addr = start_test_server().await;
@@ -230,6 +478,10 @@ async fn test_room_creation_and_joining() {
         max_players: Some(2),
         supports_authority: Some(true),
         relay_transport: None,
+        relay_type: None,
+        spectator_chat_mode: None,
+        idempotency_key: None,
+        allow_overflow_joins: None,
     };
 
     let response1 = send_and_receive(&mut sender1, &mut receiver1, join_msg1)
@@ -254,6 +506,10 @@ async fn test_room_creation_and_joining() {
         max_players: Some(2),
         supports_authority: Some(true),
         relay_transport: None,
+        relay_type: None,
+        spectator_chat_mode: None,
+        idempotency_key: None,
+        allow_overflow_joins: None,
     };
 
     let response2 = send_and_receive(&mut sender2, &mut receiver2, join_msg2)
@@ -302,6 +558,10 @@ async fn test_game_data_broadcasting() {
         max_players: Some(2),
         supports_authority: Some(true),
         relay_transport: None,
+        relay_type: None,
+        spectator_chat_mode: None,
+        idempotency_key: None,
+        allow_overflow_joins: None,
     };
     let _ = send_and_receive(&mut sender1, &mut receiver1, join_msg)
         .await
@@ -314,6 +574,10 @@ async fn test_game_data_broadcasting() {
         max_players: Some(2),
         supports_authority: Some(true),
         relay_transport: None,
+        relay_type: None,
+        spectator_chat_mode: None,
+        idempotency_key: None,
+        allow_overflow_joins: None,
     };
     let _ = send_and_receive(&mut sender2, &mut receiver2, join_msg2)
         .await
@@ -366,6 +630,10 @@ async fn test_room_capacity_limit() {
         max_players: Some(2),
         supports_authority: Some(true),
         relay_transport: None,
+        relay_type: None,
+        spectator_chat_mode: None,
+        idempotency_key: None,
+        allow_overflow_joins: None,
     };
     let _ = send_and_receive(&mut sender1, &mut receiver1, join_msg1)
         .await
@@ -379,6 +647,10 @@ async fn test_room_capacity_limit() {
         max_players: Some(2),
         supports_authority: Some(true),
         relay_transport: None,
+        relay_type: None,
+        spectator_chat_mode: None,
+        idempotency_key: None,
+        allow_overflow_joins: None,
     };
     let _ = send_and_receive(&mut sender2, &mut receiver2, join_msg2)
         .await
@@ -392,6 +664,10 @@ async fn test_room_capacity_limit() {
         max_players: Some(2),
         supports_authority: Some(true),
         relay_transport: None,
+        relay_type: None,
+        spectator_chat_mode: None,
+        idempotency_key: None,
+        allow_overflow_joins: None,
     };
 
     let response3 = send_and_receive(&mut sender3, &mut receiver3, join_msg3)
@@ -418,6 +694,10 @@ async fn test_validation_errors() {
         max_players: Some(4),
         supports_authority: Some(true),
         relay_transport: None,
+        relay_type: None,
+        spectator_chat_mode: None,
+        idempotency_key: None,
+        allow_overflow_joins: None,
     };
 
     let response = send_and_receive(&mut sender, &mut receiver, invalid_join)
@@ -454,6 +734,10 @@ async fn test_e2e_custom_protocol_limits() {
         max_players: Some(4),
         supports_authority: Some(true),
         relay_transport: None,
+        relay_type: None,
+        spectator_chat_mode: None,
+        idempotency_key: None,
+        allow_overflow_joins: None,
     };
 
     let response = send_and_receive(&mut sender, &mut receiver, long_name_msg)
@@ -474,6 +758,10 @@ async fn test_e2e_custom_protocol_limits() {
         max_players: Some(4),
         supports_authority: Some(true),
         relay_transport: None,
+        relay_type: None,
+        spectator_chat_mode: None,
+        idempotency_key: None,
+        allow_overflow_joins: None,
     };
 
     let response = send_and_receive(&mut sender, &mut receiver, wrong_length_msg)
@@ -494,6 +782,10 @@ async fn test_e2e_custom_protocol_limits() {
         max_players: Some(4),
         supports_authority: Some(true),
         relay_transport: None,
+        relay_type: None,
+        spectator_chat_mode: None,
+        idempotency_key: None,
+        allow_overflow_joins: None,
     };
 
     let response = send_and_receive(&mut sender, &mut receiver, long_player_msg)
@@ -514,6 +806,10 @@ async fn test_e2e_custom_protocol_limits() {
         max_players: Some(16), // Exceeds our custom limit of 8
         supports_authority: Some(true),
         relay_transport: None,
+        relay_type: None,
+        spectator_chat_mode: None,
+        idempotency_key: None,
+        allow_overflow_joins: None,
     };
 
     let response = send_and_receive(&mut sender, &mut receiver, too_many_players_msg)
@@ -534,6 +830,10 @@ async fn test_e2e_custom_protocol_limits() {
         max_players: Some(6),                // Within limit
         supports_authority: Some(true),
         relay_transport: None,
+        relay_type: None,
+        spectator_chat_mode: None,
+        idempotency_key: None,
+        allow_overflow_joins: None,
     };
 
     let response = send_and_receive(&mut sender, &mut receiver, valid_msg)
@@ -591,7 +891,7 @@ async fn test_e2e_config_with_file_and_env() {
     assert_eq!(loaded_config.protocol.room_code_length, 3); // From env override
     assert_eq!(loaded_config.protocol.max_game_name_length, 50); // From file
     assert_eq!(loaded_config.server.default_max_players, 16); // From file
-    assert_eq!(loaded_config.server.ping_timeout, 30); // Default value
+    assert_eq!(loaded_config.server.ping_timeout.as_secs(), 30); // Default value
 
     // Test that the loaded config affects server behavior
     let addr = start_test_server_with_config_and_protocol(
@@ -613,6 +913,10 @@ async fn test_e2e_config_with_file_and_env() {
         max_players: Some(4),
         supports_authority: Some(true),
         relay_transport: None,
+        relay_type: None,
+        spectator_chat_mode: None,
+        idempotency_key: None,
+        allow_overflow_joins: None,
     };
 
     let response = send_and_receive(&mut sender, &mut receiver, test_msg)
@@ -633,6 +937,10 @@ async fn test_e2e_config_with_file_and_env() {
         max_players: Some(4),
         supports_authority: Some(true),
         relay_transport: None,
+        relay_type: None,
+        spectator_chat_mode: None,
+        idempotency_key: None,
+        allow_overflow_joins: None,
     };
 
     let response = send_and_receive(&mut sender, &mut receiver, valid_msg)
@@ -665,6 +973,10 @@ async fn test_e2e_authority_protocol_enforcement() {
         max_players: Some(4),
         supports_authority: Some(true),
         relay_transport: None,
+        relay_type: None,
+        spectator_chat_mode: None,
+        idempotency_key: None,
+        allow_overflow_joins: None,
     };
 
     let response1 = send_and_receive(&mut sender1, &mut receiver1, create_msg)
@@ -688,6 +1000,10 @@ async fn test_e2e_authority_protocol_enforcement() {
         max_players: Some(4),
         supports_authority: Some(true),
         relay_transport: None,
+        relay_type: None,
+        spectator_chat_mode: None,
+        idempotency_key: None,
+        allow_overflow_joins: None,
     };
 
     let response2 = send_and_receive(&mut sender2, &mut receiver2, join_msg)
@@ -989,6 +1305,10 @@ async fn test_simple_authority_release() {
         max_players: Some(2),
         supports_authority: Some(true),
         relay_transport: None,
+        relay_type: None,
+        spectator_chat_mode: None,
+        idempotency_key: None,
+        allow_overflow_joins: None,
     };
 
     let response = send_and_receive(&mut sender, &mut receiver, join_msg)
@@ -1064,6 +1384,10 @@ async fn test_two_player_authority_release() {
         max_players: Some(2),
         supports_authority: Some(true),
         relay_transport: None,
+        relay_type: None,
+        spectator_chat_mode: None,
+        idempotency_key: None,
+        allow_overflow_joins: None,
     };
 
     let response1 = send_and_receive(&mut sender1, &mut receiver1, join_msg1)
@@ -1084,6 +1408,10 @@ async fn test_two_player_authority_release() {
         max_players: Some(2),
         supports_authority: Some(true),
         relay_transport: None,
+        relay_type: None,
+        spectator_chat_mode: None,
+        idempotency_key: None,
+        allow_overflow_joins: None,
     };
 
     let response2 = send_and_receive(&mut sender2, &mut receiver2, join_msg2)
@@ -1170,6 +1498,10 @@ async fn test_e2e_authority_disabled_rooms() {
         max_players: Some(4),
         supports_authority: Some(false), // Authority disabled
         relay_transport: None,
+        relay_type: None,
+        spectator_chat_mode: None,
+        idempotency_key: None,
+        allow_overflow_joins: None,
     };
 
     let response = send_and_receive(&mut sender, &mut receiver, create_msg)
@@ -1232,6 +1564,10 @@ async fn test_e2e_room_code_generation_with_custom_length() {
         max_players: Some(4),
         supports_authority: Some(true),
         relay_transport: None,
+        relay_type: None,
+        spectator_chat_mode: None,
+        idempotency_key: None,
+        allow_overflow_joins: None,
     };
 
     let response = send_and_receive(&mut sender, &mut receiver, auto_room_msg)
@@ -1285,6 +1621,10 @@ async fn test_e2e_fallback_to_defaults_on_invalid_config() {
         max_players: Some(4),
         supports_authority: Some(true),
         relay_transport: None,
+        relay_type: None,
+        spectator_chat_mode: None,
+        idempotency_key: None,
+        allow_overflow_joins: None,
     };
 
     let response = send_and_receive(&mut sender, &mut receiver, test_msg)
@@ -1297,3 +1637,325 @@ async fn test_e2e_fallback_to_defaults_on_invalid_config() {
         _ => panic!("Expected room join to work with default config, got {response:?}"),
     }
 }
+
+#[tokio::test]
+async fn test_e2e_msgpack_subprotocol_round_trip() {
+    use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+
+    let mut server_config = test_server_config();
+    server_config.websocket_config.allow_msgpack = true;
+    let addr = start_test_server_with_config(server_config).await;
+
+    let mut request = format!("ws://{addr}/v2/ws").into_client_request().unwrap();
+    request.headers_mut().insert(
+        "Sec-WebSocket-Protocol",
+        MessageEncoding::MSGPACK_SUBPROTOCOL.parse().unwrap(),
+    );
+    let (ws, response) = tokio::time::timeout(
+        tokio::time::Duration::from_secs(10),
+        tokio_tungstenite::connect_async(request),
+    )
+    .await
+    .expect("WebSocket connection timed out")
+    .expect("upgrade should succeed");
+    assert_eq!(
+        response.headers().get("Sec-WebSocket-Protocol").unwrap(),
+        MessageEncoding::MSGPACK_SUBPROTOCOL
+    );
+    let (mut sender, mut receiver) = ws.split();
+    let handshake = tokio::time::timeout(tokio::time::Duration::from_secs(5), receiver.next())
+        .await
+        .expect("server should send ProtocolInfo")
+        .expect("socket should stay open")
+        .unwrap();
+    let Message::Binary(bytes) = handshake else {
+        panic!("expected a binary MessagePack frame, got {handshake:?}");
+    };
+    assert!(matches!(
+        rmp_serde::from_slice::<ServerMessage>(&bytes).unwrap(),
+        ServerMessage::ProtocolInfo(_)
+    ));
+
+    let join = ClientMessage::JoinRoom {
+        game_name: "msgpackgame".to_string(),
+        room_code: None,
+        player_name: "Packer".to_string(),
+        max_players: Some(4),
+        supports_authority: Some(true),
+        relay_transport: None,
+        relay_type: None,
+        spectator_chat_mode: None,
+        idempotency_key: None,
+        allow_overflow_joins: None,
+    };
+    sender
+        .send(Message::Binary(
+            rmp_serde::to_vec_named(&join).unwrap().into(),
+        ))
+        .await
+        .unwrap();
+
+    let frame = tokio::time::timeout(tokio::time::Duration::from_secs(5), receiver.next())
+        .await
+        .expect("server should respond")
+        .expect("socket should stay open")
+        .unwrap();
+    let Message::Binary(bytes) = frame else {
+        panic!("expected a binary MessagePack frame, got {frame:?}");
+    };
+    match rmp_serde::from_slice::<ServerMessage>(&bytes).unwrap() {
+        ServerMessage::RoomJoined(payload) => assert_eq!(payload.game_name, "msgpackgame"),
+        other => panic!("Expected RoomJoined, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_e2e_messagepack_subprotocol_selects_msgpack() {
+    use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+
+    let mut server_config = test_server_config();
+    server_config.websocket_config.allow_msgpack = true;
+    let addr = start_test_server_with_config(server_config).await;
+
+    let mut request = format!("ws://{addr}/v2/ws").into_client_request().unwrap();
+    request.headers_mut().insert(
+        "Sec-WebSocket-Protocol",
+        MessageEncoding::MESSAGEPACK_SUBPROTOCOL.parse().unwrap(),
+    );
+    let (ws, response) = tokio::time::timeout(
+        tokio::time::Duration::from_secs(10),
+        tokio_tungstenite::connect_async(request),
+    )
+    .await
+    .expect("WebSocket connection timed out")
+    .expect("upgrade should succeed");
+    assert_eq!(
+        response.headers().get("Sec-WebSocket-Protocol").unwrap(),
+        MessageEncoding::MESSAGEPACK_SUBPROTOCOL
+    );
+    let (_sender, mut receiver) = ws.split();
+    let handshake = tokio::time::timeout(tokio::time::Duration::from_secs(5), receiver.next())
+        .await
+        .expect("server should send ProtocolInfo")
+        .expect("socket should stay open")
+        .unwrap();
+    let Message::Binary(bytes) = handshake else {
+        panic!("expected a binary MessagePack frame, got {handshake:?}");
+    };
+    assert!(matches!(
+        rmp_serde::from_slice::<ServerMessage>(&bytes).unwrap(),
+        ServerMessage::ProtocolInfo(_)
+    ));
+}
+
+#[tokio::test]
+async fn test_e2e_rkyv_frames_relay_game_data_and_reject_malformed_bytes() {
+    use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+
+    let mut server_config = test_server_config();
+    server_config.websocket_config.allow_rkyv = true;
+    let addr = start_test_server_with_config(server_config).await;
+
+    let connect = || async {
+        let mut request = format!("ws://{addr}/v2/ws").into_client_request().unwrap();
+        request.headers_mut().insert(
+            "Sec-WebSocket-Protocol",
+            MessageEncoding::RKYV_SUBPROTOCOL.parse().unwrap(),
+        );
+        let (ws, response) = tokio::time::timeout(
+            tokio::time::Duration::from_secs(10),
+            tokio_tungstenite::connect_async(request),
+        )
+        .await
+        .expect("WebSocket connection timed out")
+        .expect("upgrade should succeed");
+        assert_eq!(
+            response.headers().get("Sec-WebSocket-Protocol").unwrap(),
+            MessageEncoding::RKYV_SUBPROTOCOL
+        );
+        let (sender, mut receiver) = ws.split();
+        assert!(matches!(
+            next_message(&mut receiver).await,
+            ServerMessage::ProtocolInfo(_)
+        ));
+        (sender, receiver)
+    };
+    let join = |room_code: Option<String>, player_name: &str| {
+        let message = ClientMessage::JoinRoom {
+            game_name: "rkyvgame".to_string(),
+            room_code,
+            player_name: player_name.to_string(),
+            max_players: Some(4),
+            supports_authority: Some(true),
+            relay_transport: None,
+            relay_type: None,
+            spectator_chat_mode: None,
+            idempotency_key: None,
+            allow_overflow_joins: None,
+        };
+        Message::Binary(ClientFrame::message(&message).unwrap().encode().unwrap())
+    };
+    async fn next_message<S>(receiver: &mut S) -> ServerMessage
+    where
+        S: StreamExt<Item = Result<Message, tokio_tungstenite::tungstenite::Error>> + Unpin,
+    {
+        let frame = tokio::time::timeout(tokio::time::Duration::from_secs(5), receiver.next())
+            .await
+            .expect("server should respond")
+            .expect("socket should stay open")
+            .unwrap();
+        let Message::Binary(bytes) = frame else {
+            panic!("expected a binary MessagePack frame, got {frame:?}");
+        };
+        rmp_serde::from_slice(&bytes).unwrap()
+    }
+
+    let (mut host_tx, mut host_rx) = connect().await;
+    host_tx.send(join(None, "Host")).await.unwrap();
+    let ServerMessage::RoomJoined(joined) = next_message(&mut host_rx).await else {
+        panic!("expected RoomJoined");
+    };
+
+    // A crafted buffer is refused without closing the connection
+    host_tx
+        .send(Message::Binary(vec![0xff; 24].into()))
+        .await
+        .unwrap();
+    match next_message(&mut host_rx).await {
+        ServerMessage::Error(error) => assert_eq!(error.code, ErrorCode::InvalidInput),
+        other => panic!("Expected Error, got {other:?}"),
+    }
+
+    let (mut guest_tx, mut guest_rx) = connect().await;
+    guest_tx
+        .send(join(Some(joined.room_code.clone()), "Guest"))
+        .await
+        .unwrap();
+    assert!(matches!(
+        next_message(&mut guest_rx).await,
+        ServerMessage::RoomJoined(_)
+    ));
+
+    let frame = ClientFrame::GameData {
+        encoding: GameDataEncoding::Rkyv,
+        payload: b"tick:42".to_vec(),
+    };
+    host_tx
+        .send(Message::Binary(frame.encode().unwrap()))
+        .await
+        .unwrap();
+    let relayed = tokio::time::timeout(tokio::time::Duration::from_secs(5), async {
+        loop {
+            if let ServerMessage::GameDataBinary {
+                from_player,
+                encoding,
+                payload,
+            } = next_message(&mut guest_rx).await
+            {
+                return (from_player, encoding, payload);
+            }
+        }
+    })
+    .await
+    .expect("guest receives the relayed payload");
+    assert_eq!(relayed.0, joined.player_id);
+    assert_eq!(relayed.1, GameDataEncoding::Rkyv);
+    assert_eq!(relayed.2.as_ref(), b"tick:42");
+}
+
+#[tokio::test]
+async fn test_e2e_msgpack_subprotocol_ignored_when_disabled() {
+    use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+
+    let addr = start_test_server().await;
+
+    let mut request = format!("ws://{addr}/v2/ws").into_client_request().unwrap();
+    request.headers_mut().insert(
+        "Sec-WebSocket-Protocol",
+        MessageEncoding::MSGPACK_SUBPROTOCOL.parse().unwrap(),
+    );
+    // The server does not select the subprotocol, which a compliant client rejects.
+    let result = tokio_tungstenite::connect_async(request).await;
+    assert!(
+        result.is_err(),
+        "client should refuse an unnegotiated subprotocol"
+    );
+}
+
+#[tokio::test]
+async fn test_e2e_session_duration_recorded_on_abnormal_disconnect() {
+    let game_server = create_test_server().await;
+    let metrics = game_server.metrics();
+    let addr = start_server_with_instance(game_server).await;
+
+    let (sender, receiver) = connect_client(addr, "/v2/ws").await;
+    // Drop the TCP stream without a close frame
+    drop(sender.reunite(receiver).unwrap());
+
+    let recorded = tokio::time::timeout(tokio::time::Duration::from_secs(5), async {
+        loop {
+            let sessions = metrics.snapshot().await.connections.session_duration;
+            if sessions.count == 1 {
+                return sessions;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .expect("session duration should be recorded after the socket drops");
+    assert_eq!(recorded.buckets.len(), 6);
+    assert_eq!(recorded.buckets[0], (1.0, 1));
+}
+
+#[tokio::test]
+async fn test_e2e_shutdown_closes_connections_with_server_shutting_down() {
+    let game_server = create_test_server().await;
+    let addr = start_server_with_instance(Arc::clone(&game_server)).await;
+
+    let (_sender, mut receiver) = connect_client(addr, "/v2/ws").await;
+    game_server.begin_shutdown();
+
+    let mut shutdown_error = false;
+    let close = loop {
+        let frame = tokio::time::timeout(tokio::time::Duration::from_secs(5), receiver.next())
+            .await
+            .expect("server should close the connection")
+            .expect("socket should deliver the close frame")
+            .unwrap();
+        match frame {
+            Message::Text(text) => {
+                shutdown_error |= matches!(
+                    serde_json::from_str(&text).unwrap(),
+                    ServerMessage::Error(ErrorDetail {
+                        code: ErrorCode::ServerShuttingDown,
+                        ..
+                    })
+                );
+            }
+            Message::Close(close) => break close.expect("close frame carries a code"),
+            _ => {}
+        }
+    };
+    assert!(shutdown_error, "the close is explained by an Error first");
+    assert_eq!(
+        u16::from(close.code),
+        ErrorCode::ServerShuttingDown.close_code()
+    );
+
+    // Connections that arrive during shutdown are closed as well
+    let (_sender, mut receiver) = connect_client(addr, "/v2/ws").await;
+    let close = loop {
+        let frame = tokio::time::timeout(tokio::time::Duration::from_secs(5), receiver.next())
+            .await
+            .expect("server should close the connection")
+            .expect("socket should deliver the close frame")
+            .unwrap();
+        if let Message::Close(close) = frame {
+            break close.expect("close frame carries a code");
+        }
+    };
+    assert_eq!(
+        u16::from(close.code),
+        ErrorCode::ServerShuttingDown.close_code()
+    );
+}
@@ -2,7 +2,7 @@ mod test_helpers;
 
 use signal_fish_server::protocol::*;
 use signal_fish_server::server::ServerConfig;
-use test_helpers::{create_test_server, create_test_server_with_config};
+use test_helpers::{create_test_server, create_test_server_with_config, test_server_config};
 use tokio::sync::mpsc;
 
 /// Test creating rooms and joining them with multiple players
@@ -34,6 +34,8 @@ async fn test_multi_player_room_scenario() {
             Some(4),
             Some(true),
             None,
+            None,
+            None,
         )
         .await;
 
@@ -61,6 +63,8 @@ async fn test_multi_player_room_scenario() {
             Some(4),
             Some(true),
             None,
+            None,
+            None,
         )
         .await;
 
@@ -119,6 +123,8 @@ async fn test_game_data_exchange() {
             Some(2),
             Some(true),
             None,
+            None,
+            None,
         )
         .await;
 
@@ -134,6 +140,8 @@ async fn test_game_data_exchange() {
             Some(2),
             Some(true),
             None,
+            None,
+            None,
         )
         .await;
 
@@ -222,6 +230,8 @@ async fn test_room_capacity() {
             Some(2),
             Some(true),
             None,
+            None,
+            None,
         )
         .await;
     server
@@ -233,6 +243,8 @@ async fn test_room_capacity() {
             Some(2),
             Some(true),
             None,
+            None,
+            None,
         )
         .await;
 
@@ -246,6 +258,8 @@ async fn test_room_capacity() {
             Some(2),
             Some(true),
             None,
+            None,
+            None,
         )
         .await;
 
@@ -289,6 +303,8 @@ async fn test_authority_transfer() {
             Some(2),
             Some(true),
             None,
+            None,
+            None,
         )
         .await;
 
@@ -304,6 +320,8 @@ async fn test_authority_transfer() {
             Some(2),
             Some(true),
             None,
+            None,
+            None,
         )
         .await;
 
@@ -447,6 +465,85 @@ async fn test_authority_transfer() {
     println!("Authority transfer test completed successfully");
 }
 
+/// Test that an idle room warns its players once before the cleanup task deletes it
+#[tokio::test]
+async fn test_room_expiry_warning_precedes_cleanup() {
+    let config = ServerConfig {
+        room_cleanup_interval: tokio::time::Duration::from_millis(100),
+        inactive_room_timeout: tokio::time::Duration::from_millis(1500),
+        expiry_warning: tokio::time::Duration::from_secs(1),
+        ..test_server_config()
+    };
+    let server = create_test_server_with_config(
+        config,
+        signal_fish_server::config::ProtocolConfig::default(),
+    )
+    .await;
+
+    let (tx, mut rx) = mpsc::channel(64);
+    let player_id = server
+        .register_client(tx, "127.0.0.1:0".parse().unwrap())
+        .await
+        .unwrap();
+    server
+        .handle_join_room(
+            &player_id,
+            "expiry_game".to_string(),
+            Some("EXP123".to_string()),
+            "Idle".to_string(),
+            Some(4),
+            Some(true),
+            None,
+            None,
+            None,
+        )
+        .await;
+    let room_id = match rx.recv().await.unwrap().as_ref() {
+        ServerMessage::RoomJoined(payload) => payload.room_id,
+        other => panic!("Expected RoomJoined, got {other:?}"),
+    };
+
+    let cleanup_server = server.clone();
+    let cleanup = tokio::spawn(async move { cleanup_server.cleanup_task().await });
+
+    let warning = tokio::time::timeout(tokio::time::Duration::from_secs(2), async {
+        loop {
+            let message = rx.recv().await.expect("channel closed before warning");
+            if let ServerMessage::RoomExpirySoon { seconds_remaining } = message.as_ref() {
+                break *seconds_remaining;
+            }
+        }
+    })
+    .await
+    .expect("RoomExpirySoon was not sent");
+    assert_eq!(warning, 1);
+    assert!(
+        server
+            .database()
+            .get_room_by_id(&room_id)
+            .await
+            .unwrap()
+            .is_some(),
+        "room must still exist when the warning arrives"
+    );
+
+    // The room is removed once the timeout passes, without a second warning
+    tokio::time::sleep(tokio::time::Duration::from_millis(1800)).await;
+    assert!(server
+        .database()
+        .get_room_by_id(&room_id)
+        .await
+        .unwrap()
+        .is_none());
+    while let Ok(message) = rx.try_recv() {
+        assert!(
+            !matches!(message.as_ref(), ServerMessage::RoomExpirySoon { .. }),
+            "warning was sent twice"
+        );
+    }
+    cleanup.abort();
+}
+
 /// Test player disconnection and cleanup
 #[tokio::test]
 async fn test_player_disconnection() {
@@ -474,6 +571,8 @@ async fn test_player_disconnection() {
             Some(2),
             Some(true),
             None,
+            None,
+            None,
         )
         .await;
     server
@@ -485,6 +584,8 @@ async fn test_player_disconnection() {
             Some(2),
             Some(true),
             None,
+            None,
+            None,
         )
         .await;
 
@@ -552,6 +653,104 @@ async fn test_player_disconnection() {
     assert!(found_player_left, "Never received PlayerLeft notification");
 }
 
+/// Test that reconnecting rotates the token and that the superseded token is rejected as a replay
+#[tokio::test]
+async fn test_reconnect_rotates_token_and_detects_replay() {
+    let server = create_test_server().await;
+
+    let (tx1, _rx1) = mpsc::channel(64);
+    let (tx2, mut rx2) = mpsc::channel(64);
+    let host_id = server
+        .register_client(tx1, "127.0.0.1:0".parse().unwrap())
+        .await
+        .unwrap();
+    let player_id = server
+        .register_client(tx2, "127.0.0.1:0".parse().unwrap())
+        .await
+        .unwrap();
+
+    for (id, name) in [(&host_id, "Host"), (&player_id, "Player")] {
+        server
+            .handle_join_room(
+                id,
+                "reconnect_game".to_string(),
+                Some("RCN123".to_string()),
+                name.to_string(),
+                Some(4),
+                Some(true),
+                None,
+                None,
+                None,
+            )
+            .await;
+    }
+
+    let (room_id, first_token) = match rx2.try_recv().unwrap().as_ref() {
+        ServerMessage::RoomJoined(payload) => (
+            payload.room_id,
+            payload
+                .reconnection_token
+                .clone()
+                .expect("RoomJoined carries a reconnection token"),
+        ),
+        other => panic!("Expected RoomJoined, got {other:?}"),
+    };
+
+    // Drop and reconnect with the original token: a new token is issued
+    server.unregister_client(&player_id).await;
+    let (tx3, mut rx3) = mpsc::channel(64);
+    let reconnecting_id = server
+        .register_client(tx3, "127.0.0.1:0".parse().unwrap())
+        .await
+        .unwrap();
+    server
+        .handle_reconnect(&reconnecting_id, &player_id, &room_id, &first_token)
+        .await;
+    let rotated_token = match rx3.try_recv().unwrap().as_ref() {
+        ServerMessage::Reconnected(payload) => payload
+            .reconnection_token
+            .clone()
+            .expect("Reconnected carries a rotated token"),
+        other => panic!("Expected Reconnected, got {other:?}"),
+    };
+    assert_ne!(rotated_token, first_token);
+
+    // Drop again; presenting the superseded token is flagged as a replay
+    server.unregister_client(&player_id).await;
+    let (tx4, mut rx4) = mpsc::channel(64);
+    let attacker_id = server
+        .register_client(tx4, "127.0.0.1:0".parse().unwrap())
+        .await
+        .unwrap();
+    server
+        .handle_reconnect(&attacker_id, &player_id, &room_id, &first_token)
+        .await;
+    match rx4.try_recv().unwrap().as_ref() {
+        ServerMessage::Error(error) => {
+            assert_eq!(error.code, ErrorCode::TokenReplayed);
+        }
+        other => panic!("Expected TokenReplayed error, got {other:?}"),
+    }
+    assert_eq!(
+        server
+            .metrics()
+            .snapshot()
+            .await
+            .reconnection
+            .token_replays_detected,
+        1
+    );
+
+    // The latest token still works
+    server
+        .handle_reconnect(&attacker_id, &player_id, &room_id, &rotated_token)
+        .await;
+    assert!(matches!(
+        rx4.try_recv().unwrap().as_ref(),
+        ServerMessage::Reconnected(_)
+    ));
+}
+
 /// Test ping/pong functionality
 #[tokio::test]
 async fn test_ping_pong() {
@@ -599,6 +798,8 @@ async fn test_validation_errors() {
             Some(4),
             Some(true),
             None,
+            None,
+            None,
         )
         .await;
 
@@ -645,7 +846,7 @@ async fn test_config_fallback_integration() {
     // Environment override should take precedence for default_max_players
     assert_eq!(config.server.default_max_players, 12);
     // Other values should be defaults
-    assert_eq!(config.server.ping_timeout, 30);
+    assert_eq!(config.server.ping_timeout.as_secs(), 30);
 
     // Clean up
     env::remove_var("SIGNAL_FISH_CONFIG_PATH");
@@ -682,6 +883,8 @@ async fn test_custom_protocol_config() {
             Some(2),
             Some(true),
             None,
+            None,
+            None,
         )
         .await;
 
@@ -703,6 +906,8 @@ async fn test_custom_protocol_config() {
             Some(2),
             Some(true),
             None,
+            None,
+            None,
         )
         .await;
 
@@ -725,6 +930,8 @@ async fn test_custom_protocol_config() {
             Some(2),
             Some(true),
             None,
+            None,
+            None,
         )
         .await;
 
@@ -746,6 +953,8 @@ async fn test_custom_protocol_config() {
             Some(8), // Exceeds our custom limit of 4
             Some(true),
             None,
+            None,
+            None,
         )
         .await;
 
@@ -767,6 +976,8 @@ async fn test_custom_protocol_config() {
             Some(3),                  // Within limit
             Some(true),
             None,
+            None,
+            None,
         )
         .await;
 
@@ -779,6 +990,117 @@ async fn test_custom_protocol_config() {
     }
 }
 
+/// Test that duplicate names are suffixed when `on_duplicate_name` is `suffix`
+#[tokio::test]
+async fn test_duplicate_player_names_are_suffixed() {
+    let protocol_config = signal_fish_server::config::ProtocolConfig {
+        room_code_length: 4,
+        max_player_name_length: 8,
+        on_duplicate_name: signal_fish_server::config::DuplicateNamePolicy::Suffix,
+        ..signal_fish_server::config::ProtocolConfig::default()
+    };
+    let server = create_test_server_with_config(ServerConfig::default(), protocol_config).await;
+
+    let mut joined_names = Vec::new();
+    for _ in 0..3 {
+        let (tx, mut rx) = mpsc::channel(64);
+        let player_id = server
+            .register_client(tx, "127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+        server
+            .handle_join_room(
+                &player_id,
+                "game".to_string(),
+                Some("DUPE".to_string()),
+                "Alexandr".to_string(),
+                Some(4),
+                Some(true),
+                None,
+                None,
+                None,
+            )
+            .await;
+
+        match rx.try_recv().unwrap().as_ref() {
+            ServerMessage::RoomJoined(payload) => {
+                let me = payload
+                    .current_players
+                    .iter()
+                    .find(|player| player.id == player_id)
+                    .unwrap();
+                assert_eq!(me.name, payload.player_name);
+                joined_names.push(payload.player_name.clone());
+            }
+            other => panic!("Expected RoomJoined, got {other:?}"),
+        }
+    }
+
+    // The base is truncated so the suffixed name fits in 8 characters
+    assert_eq!(joined_names, ["Alexandr", "Alex (2)", "Alex (3)"]);
+}
+
+/// Test that join failures carry typed details alongside the message
+#[tokio::test]
+async fn test_join_failures_carry_typed_details() {
+    let server = create_test_server().await;
+
+    let mut responses = Vec::new();
+    for name in ["Alex", "alex", "Bea", "Cy"] {
+        let (tx, mut rx) = mpsc::channel(64);
+        let player_id = server
+            .register_client(tx, "127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+        server
+            .handle_join_room(
+                &player_id,
+                "details_game".to_string(),
+                Some("DTL123".to_string()),
+                name.to_string(),
+                Some(2),
+                Some(true),
+                None,
+                None,
+                None,
+            )
+            .await;
+        responses.push(rx.try_recv().unwrap());
+    }
+
+    match responses[1].as_ref() {
+        ServerMessage::RoomJoinFailed {
+            error_code,
+            details,
+            ..
+        } => {
+            assert_eq!(*error_code, Some(ErrorCode::InvalidPlayerName));
+            assert_eq!(
+                *details,
+                Some(ErrorDetails::NameTaken {
+                    suggested: Some("alex (2)".to_string())
+                })
+            );
+        }
+        other => panic!("Expected RoomJoinFailed, got {other:?}"),
+    }
+    assert!(matches!(
+        responses[2].as_ref(),
+        ServerMessage::RoomJoined(_)
+    ));
+    match responses[3].as_ref() {
+        ServerMessage::RoomJoinFailed {
+            error_code,
+            details,
+            ..
+        } => {
+            assert_eq!(*error_code, Some(ErrorCode::RoomFull));
+            assert_eq!(*details, Some(ErrorDetails::RoomFull { max_players: 2 }));
+        }
+        other => panic!("Expected RoomJoinFailed, got {other:?}"),
+    }
+}
+
 /// Test server with custom rate limiting configuration
 #[tokio::test]
 async fn test_custom_rate_limiting() {
@@ -794,21 +1116,56 @@ async fn test_custom_rate_limiting() {
             max_room_creations: 1,               // Very restrictive for testing
             time_window: Duration::from_secs(5), // Longer window to ensure test stability
             max_join_attempts: 2,
+            ..Default::default()
         },
         empty_room_timeout: Duration::from_secs(300),
         inactive_room_timeout: Duration::from_secs(3600),
+        expiry_warning: Duration::from_secs(120),
         max_message_size: 65536,
         max_connections_per_ip: 100,
+        trusted_proxies: Default::default(),
+        ipv6_connection_prefix: 64,
+        ip_filter: Default::default(),
         require_metrics_auth: false,
         metrics_auth_token: None,
+        admin_auth_token: None,
         reconnection_window: Duration::from_secs(300), // 5 minutes
         event_buffer_size: 100,                        // Buffer 100 events
         enable_reconnection: true,                     // Enable reconnection
+        flap_suppression: Duration::ZERO,              // Disconnects leave immediately
+        max_token_age: Duration::from_secs(3600),
+        max_reconnection_slots_per_room: 0,
         websocket_config: signal_fish_server::config::WebSocketConfig::default(),
         auth_enabled: false,                // Disable auth for tests
         heartbeat_throttle: Duration::ZERO, // No throttling for tests
+        latency_update_threshold_ms: None,
         region_id: "test".to_string(),
+        allowed_regions: Vec::new(),
         room_code_prefix: None,
+        max_waiting_list_size: 0,
+        max_promotion_queue_size: 0,
+        matchmaking_elo_window: 200.0,
+        matchmaking_tick: Duration::from_millis(1000),
+        readiness_check_timeout: Duration::from_millis(1000),
+        drain_retry_after: Duration::from_secs(30),
+        isolate_games: Vec::new(),
+        isolation_auto_threshold: 500,
+        broadcast_workers: 256,
+        isolated_broadcast_workers: 32,
+        enable_history_replay: false,
+        room_history_size: 50,
+        join_interceptor_timeout: Duration::from_millis(1000),
+        join_interceptor_failure_policy: Default::default(),
+        webhooks: Vec::new(),
+        ice_servers: Vec::new(),
+        closed_room_history_size: 1000,
+        room_idempotency_window: Duration::from_secs(300),
+        join_request_timeout: Duration::from_secs(30),
+        stale_player_timeout: Duration::from_secs(300),
+        cors_origins: "*".to_string(),
+        metrics_cors_origins: "*".to_string(),
+        game_overrides: Default::default(),
+        default_app_quota: Default::default(),
     };
 
     let server = create_test_server_with_config(
@@ -833,6 +1190,8 @@ async fn test_custom_rate_limiting() {
             Some(4),
             Some(true),
             None,
+            None,
+            None,
         )
         .await;
 
@@ -875,6 +1234,8 @@ async fn test_custom_rate_limiting() {
             Some(4),
             Some(true),
             None,
+            None,
+            None,
         )
         .await;
 
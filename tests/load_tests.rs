@@ -12,6 +12,7 @@
 /// - Memory Usage: < 2GB at max load
 mod test_helpers;
 
+use signal_fish_server::protocol::ClientMessage;
 use signal_fish_server::server::ServerConfig;
 use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
@@ -253,6 +254,8 @@ async fn test_load_room_creation_throughput() {
                         Some(4),
                         Some(true),
                         None,
+                        None,
+                        None,
                     ),
                 )
                 .await;
@@ -339,6 +342,8 @@ async fn test_load_sustained_concurrent_connections() {
                     Some(4),
                     Some(true),
                     None,
+                    None,
+                    None,
                 )
                 .await;
 
@@ -447,6 +452,8 @@ async fn test_load_message_latency_distribution() {
                     Some(4),
                     Some(true),
                     None,
+                    None,
+                    None,
                 )
                 .await;
 
@@ -560,6 +567,8 @@ async fn test_load_stress_to_failure() {
                             Some(4),
                             Some(true),
                             None,
+                            None,
+                            None,
                         )
                         .await;
 
@@ -643,6 +652,8 @@ async fn test_load_memory_usage() {
                     Some(4),
                     Some(true),
                     None,
+                    None,
+                    None,
                 )
                 .await;
 
@@ -778,6 +789,8 @@ async fn test_load_rate_limiting() {
                 Some(4),
                 Some(true),
                 None,
+                None,
+                None,
             )
             .await;
 
@@ -819,3 +832,138 @@ async fn test_load_rate_limiting() {
         "Rate limiting should block most rapid requests (blocked: {rate_limited}, allowed: {successes})"
     );
 }
+
+/// Heartbeat handler latency with 500 connections heartbeating at 1Hz
+///
+/// Players sit in rooms of four and the first player of each room relays
+/// 16KB of game data twenty times a second. The run is repeated with every
+/// heartbeat refreshing its room's activity and with the refresh throttled
+/// per room; throttling should lower the p99.
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+#[ignore]
+async fn test_load_heartbeat_latency_under_relay_traffic() {
+    println!("\nStarting load test: Heartbeat latency under relay traffic");
+
+    let unthrottled = heartbeat_latency_p99(Duration::ZERO).await;
+    let throttled = heartbeat_latency_p99(Duration::from_secs(30)).await;
+
+    println!("\n===================================================");
+    println!("Heartbeat Latency Results");
+    println!("===================================================");
+    println!(
+        "p99 with every heartbeat refreshing room activity: {:.2} ms",
+        unthrottled.as_secs_f64() * 1000.0
+    );
+    println!(
+        "p99 with room activity throttled: {:.2} ms",
+        throttled.as_secs_f64() * 1000.0
+    );
+    println!("===================================================\n");
+
+    assert!(
+        throttled <= unthrottled,
+        "throttled p99 ({throttled:?}) should not exceed unthrottled p99 ({unthrottled:?})"
+    );
+}
+
+async fn heartbeat_latency_p99(heartbeat_throttle: Duration) -> Duration {
+    const CONNECTIONS: usize = 500;
+    const PLAYERS_PER_ROOM: usize = 4;
+    const HEARTBEATS: u64 = 10;
+
+    let config = ServerConfig {
+        heartbeat_throttle,
+        max_rooms_per_game: 1000,
+        rate_limit_config: signal_fish_server::rate_limit::RateLimitConfig {
+            max_room_creations: 10_000,
+            max_join_attempts: 10_000,
+            ip_max_room_creations: 10_000,
+            ip_max_join_attempts: 10_000,
+            ..Default::default()
+        },
+        ..test_helpers::test_server_config()
+    };
+    let server = create_test_server_with_config(
+        config,
+        signal_fish_server::config::ProtocolConfig::default(),
+    )
+    .await;
+
+    let mut players = Vec::with_capacity(CONNECTIONS);
+    for index in 0..CONNECTIONS {
+        let player_id = uuid::Uuid::new_v4();
+        let (tx, mut rx) = mpsc::channel(256);
+        server.connect_client(player_id, tx).await;
+        tokio::spawn(async move { while rx.recv().await.is_some() {} });
+        server
+            .handle_join_room(
+                &player_id,
+                "heartbeat_load".to_string(),
+                Some(format!("HB{:04}", index / PLAYERS_PER_ROOM)),
+                format!("Player{index}"),
+                Some(PLAYERS_PER_ROOM as u8),
+                Some(false),
+                None,
+                None,
+                None,
+            )
+            .await;
+        players.push(player_id);
+    }
+
+    let mut relays = Vec::new();
+    let mut heartbeats = Vec::with_capacity(CONNECTIONS);
+    for (index, player_id) in players.into_iter().enumerate() {
+        if index % PLAYERS_PER_ROOM == 0 {
+            let server = server.clone();
+            relays.push(tokio::spawn(async move {
+                let data = serde_json::json!({ "state": "x".repeat(16 * 1024) });
+                let mut tick = tokio::time::interval(Duration::from_millis(50));
+                loop {
+                    tick.tick().await;
+                    server
+                        .handle_client_message(
+                            &player_id,
+                            ClientMessage::GameData { data: data.clone() },
+                        )
+                        .await;
+                }
+            }));
+        }
+
+        let server = server.clone();
+        heartbeats.push(tokio::spawn(async move {
+            // Spread connections across the second like real clients
+            let offset = Duration::from_millis((index * 1000 / CONNECTIONS) as u64);
+            tokio::time::sleep(offset).await;
+            let mut tick = tokio::time::interval(Duration::from_secs(1));
+            let mut latencies = Vec::with_capacity(HEARTBEATS as usize);
+            for client_timestamp in 0..HEARTBEATS {
+                tick.tick().await;
+                let started = Instant::now();
+                server
+                    .handle_client_message(
+                        &player_id,
+                        ClientMessage::Heartbeat {
+                            client_timestamp,
+                            last_ack: None,
+                        },
+                    )
+                    .await;
+                latencies.push(started.elapsed());
+            }
+            latencies
+        }));
+    }
+
+    let mut latencies = Vec::new();
+    for handle in heartbeats {
+        latencies.extend(handle.await.expect("heartbeat task completes"));
+    }
+    for relay in relays {
+        relay.abort();
+    }
+
+    latencies.sort();
+    latencies[latencies.len() * 99 / 100]
+}
@@ -18,6 +18,8 @@ async fn test_lobby_e2e_websocket_flow() {
 
     let (mut write1, mut read1) = ws_stream1.split();
     let (mut write2, mut read2) = ws_stream2.split();
+    let _ = read1.next().await; // ProtocolInfo
+    let _ = read2.next().await; // ProtocolInfo
 
     // Client 1 joins room
     let join_msg1 = json!({
@@ -212,6 +214,8 @@ async fn test_lobby_e2e_ready_toggle() {
 
     let (mut write1, mut read1) = ws_stream1.split();
     let (mut write2, mut read2) = ws_stream2.split();
+    let _ = read1.next().await; // ProtocolInfo
+    let _ = read2.next().await; // ProtocolInfo
 
     // Join room (same as previous test but abbreviated)
     let join_msg1 = json!({
@@ -304,6 +308,7 @@ async fn test_lobby_e2e_error_cases() {
     // Connect client
     let (ws_stream, _) = connect_async(server_url).await.unwrap();
     let (mut write, mut read) = ws_stream.split();
+    let _ = read.next().await; // ProtocolInfo
 
     // Try to signal ready without being in a room
     let ready_msg = json!({"type": "PlayerReady"});
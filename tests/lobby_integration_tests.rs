@@ -31,6 +31,8 @@ async fn test_lobby_integration_full_flow() {
                 Some(3),
                 Some(true),
                 None,
+                None,
+                None,
             )
             .await;
     }
@@ -80,6 +82,7 @@ async fn test_lobby_integration_full_flow() {
                     lobby_state,
                     ready_players,
                     all_ready,
+                    ..
                 } => {
                     assert_eq!(*lobby_state, LobbyState::Lobby);
                     assert_eq!(ready_players.len(), i + 1);
@@ -94,7 +97,9 @@ async fn test_lobby_integration_full_flow() {
             for rx in channels.iter_mut() {
                 let msg = rx.try_recv().unwrap();
                 match msg.as_ref() {
-                    ServerMessage::GameStarting { peer_connections } => {
+                    ServerMessage::GameStarting {
+                        peer_connections, ..
+                    } => {
                         assert_eq!(peer_connections.len(), 3);
                         // Verify authority assignment
                         let auth_count = peer_connections.iter().filter(|p| p.is_authority).count();
@@ -131,6 +136,8 @@ async fn test_lobby_player_leaves_during_ready_phase() {
                 Some(2),
                 Some(true),
                 None,
+                None,
+                None,
             )
             .await;
     }
@@ -161,6 +168,19 @@ async fn test_lobby_player_leaves_during_ready_phase() {
         _ => panic!("Expected PlayerLeft message"),
     }
 
+    // ...and the lobby reverting to waiting
+    match rx1.try_recv().unwrap().as_ref() {
+        ServerMessage::LobbyStateChanged {
+            lobby_state,
+            ready_players,
+            ..
+        } => {
+            assert_eq!(*lobby_state, LobbyState::Waiting);
+            assert!(ready_players.is_empty());
+        }
+        other => panic!("Expected LobbyStateChanged message, got {other:?}"),
+    }
+
     // Player 2 should receive RoomLeft confirmation
     let msg = rx2.try_recv().unwrap();
     match msg.as_ref() {
@@ -175,8 +195,8 @@ async fn test_lobby_player_leaves_during_ready_phase() {
     // Should receive an error message since room is no longer in lobby state
     let msg = rx1.try_recv().unwrap();
     match msg.as_ref() {
-        ServerMessage::Error { message, .. } => {
-            assert!(message.contains("Room must be in lobby state"));
+        ServerMessage::Error(error) => {
+            assert!(error.message.contains("Room must be in lobby state"));
         }
         _ => panic!("Expected error message, but received: {msg:?}"),
     }
@@ -205,6 +225,8 @@ async fn test_lobby_room_authority_preservation() {
             Some(2),
             Some(true),
             None,
+            None,
+            None,
         )
         .await;
 
@@ -217,6 +239,8 @@ async fn test_lobby_room_authority_preservation() {
             Some(2),
             Some(true),
             None,
+            None,
+            None,
         )
         .await;
 
@@ -259,7 +283,9 @@ async fn test_lobby_room_authority_preservation() {
 
     for msg in [game_start_msg1, game_start_msg2] {
         match msg.as_ref() {
-            ServerMessage::GameStarting { peer_connections } => {
+            ServerMessage::GameStarting {
+                peer_connections, ..
+            } => {
                 assert_eq!(peer_connections.len(), 2);
 
                 let auth_peer = peer_connections.iter().find(|p| p.is_authority).unwrap();
@@ -299,6 +325,8 @@ async fn test_spectator_state_updates_include_snapshots_and_reasons() {
             Some(4),
             Some(true),
             None,
+            None,
+            None,
         )
         .await;
 
@@ -424,3 +452,113 @@ async fn test_spectator_state_updates_include_snapshots_and_reasons() {
         other => panic!("unexpected message after spectator disconnect: {other:?}"),
     }
 }
+
+/// Lobby version carried by `message`, if it is a lobby update or snapshot.
+fn lobby_version(message: &ServerMessage) -> Option<u64> {
+    match message {
+        ServerMessage::LobbyStateChanged { lobby_version, .. }
+        | ServerMessage::GameStarting { lobby_version, .. } => Some(*lobby_version),
+        ServerMessage::RoomJoined(payload) => Some(payload.lobby_version),
+        ServerMessage::Reconnected(payload) => Some(payload.lobby_version),
+        _ => None,
+    }
+}
+
+fn drain_lobby_versions(
+    rx: &mut mpsc::Receiver<signal_fish_server::broadcast::Outbound>,
+) -> Vec<u64> {
+    let mut versions = Vec::new();
+    while let Ok(message) = rx.try_recv() {
+        versions.extend(lobby_version(&message));
+    }
+    versions
+}
+
+#[tokio::test]
+async fn test_lobby_versions_strictly_increase_across_fill_leave_cycles_and_reconnect() {
+    let server = create_test_server().await;
+    let join = |player_id: Uuid, name: &'static str| {
+        let server = std::sync::Arc::clone(&server);
+        async move {
+            server
+                .handle_join_room(
+                    &player_id,
+                    "version_game".to_string(),
+                    Some("VER001".to_string()),
+                    name.to_string(),
+                    Some(2),
+                    Some(true),
+                    None,
+                    None,
+                    None,
+                )
+                .await;
+        }
+    };
+
+    let observer_id = Uuid::new_v4();
+    let (observer_tx, mut observer_rx) = mpsc::channel(256);
+    server.connect_client(observer_id, observer_tx).await;
+    join(observer_id, "Observer").await;
+    let joined = observer_rx.try_recv().unwrap();
+    let ServerMessage::RoomJoined(payload) = joined.as_ref() else {
+        panic!("Expected RoomJoined, got {joined:?}");
+    };
+    let (room_id, token) = (payload.room_id, payload.reconnection_token.clone().unwrap());
+    let mut seen = vec![payload.lobby_version];
+
+    // Fill and empty the second slot repeatedly, toggling readiness in between
+    for _ in 0..3 {
+        let guest_id = Uuid::new_v4();
+        let (guest_tx, _guest_rx) = mpsc::channel(256);
+        server.connect_client(guest_id, guest_tx).await;
+        join(guest_id, "Guest").await;
+        server.handle_player_ready(&observer_id).await;
+        server.handle_player_ready(&observer_id).await;
+        server.handle_player_ready(&guest_id).await;
+        server.leave_room(&guest_id).await;
+        seen.extend(drain_lobby_versions(&mut observer_rx));
+    }
+    // Joined, then per cycle: lobby, three ready toggles, back to waiting
+    assert_eq!(seen.len(), 1 + 3 * 5);
+
+    // The observer drops while another player holds the room, which moves
+    // the lobby on without them, and then reconnects
+    let holder_id = Uuid::new_v4();
+    let (holder_tx, _holder_rx) = mpsc::channel(256);
+    server.connect_client(holder_id, holder_tx).await;
+    join(holder_id, "Holder").await;
+    seen.extend(drain_lobby_versions(&mut observer_rx));
+    server.unregister_client(&observer_id).await;
+    let (reconnect_tx, mut reconnect_rx) = mpsc::channel(256);
+    let reconnecting_id = server
+        .register_client(reconnect_tx, "127.0.0.1:0".parse().unwrap())
+        .await
+        .unwrap();
+    server
+        .handle_reconnect(&reconnecting_id, &observer_id, &room_id, &token)
+        .await;
+    let reconnected = drain_lobby_versions(&mut reconnect_rx);
+    assert_eq!(
+        reconnected.len(),
+        1,
+        "Reconnected carries the lobby version"
+    );
+    seen.extend(reconnected);
+
+    // Updates after the reconnect continue from the snapshot
+    let late_id = Uuid::new_v4();
+    let (late_tx, _late_rx) = mpsc::channel(256);
+    server.connect_client(late_id, late_tx).await;
+    join(late_id, "Late").await;
+    server.handle_player_ready(&holder_id).await;
+    server.handle_player_ready(&late_id).await;
+    let after = drain_lobby_versions(&mut reconnect_rx);
+    assert_eq!(after.len(), 4, "lobby, two ready updates and GameStarting");
+    seen.extend(after);
+
+    assert!(
+        seen.windows(2).all(|pair| pair[0] < pair[1]),
+        "lobby versions must strictly increase: {seen:?}"
+    );
+}
@@ -554,7 +554,7 @@ fn test_real_broadcast_message_performance() {
 
 #[test]
 fn test_real_server_message_serialization_roundtrip() {
-    use signal_fish_server::protocol::{PlayerInfo, ServerMessage};
+    use signal_fish_server::protocol::{PlayerInfo, PlayerRole, ServerMessage};
 
     let player_id = Uuid::new_v4();
     let messages = vec![
@@ -572,6 +572,7 @@ fn test_real_server_message_serialization_roundtrip() {
                 id: player_id,
                 name: "TestPlayer".to_string(),
                 is_authority: false,
+                role: PlayerRole::Member,
                 is_ready: false,
                 connected_at: chrono::Utc::now(),
                 connection_info: None,
@@ -60,21 +60,56 @@ pub fn test_server_config() -> ServerConfig {
             max_room_creations: 10,
             time_window: Duration::from_secs(60),
             max_join_attempts: 20,
+            ..Default::default()
         },
         empty_room_timeout: Duration::from_secs(5), // Fast timeout for tests
         inactive_room_timeout: Duration::from_secs(10),
+        expiry_warning: Duration::from_secs(2),
         max_message_size: 65536,     // 64KB default
         max_connections_per_ip: 100, // Generous for tests
+        trusted_proxies: Default::default(),
+        ipv6_connection_prefix: 64,
+        ip_filter: Default::default(),
         require_metrics_auth: false, // No auth for tests
         metrics_auth_token: None,
+        admin_auth_token: None,
         reconnection_window: Duration::from_secs(300), // 5 minutes for tests
         event_buffer_size: 100,                        // Buffer 100 events
         enable_reconnection: true,                     // Enable reconnection in tests
+        flap_suppression: Duration::ZERO,              // Disconnects leave immediately in tests
+        max_token_age: Duration::from_secs(3600),
+        max_reconnection_slots_per_room: 0,
         websocket_config: signal_fish_server::config::WebSocketConfig::default(),
         auth_enabled: false,                // Disable auth for tests
         heartbeat_throttle: Duration::ZERO, // No throttling in tests for predictable behavior
+        latency_update_threshold_ms: None,
         region_id: "test".to_string(),
+        allowed_regions: Vec::new(),
         room_code_prefix: None,
+        max_waiting_list_size: 0,
+        max_promotion_queue_size: 0,
+        matchmaking_elo_window: 200.0,
+        matchmaking_tick: Duration::from_millis(1000),
+        readiness_check_timeout: Duration::from_millis(1000),
+        drain_retry_after: Duration::from_secs(30),
+        isolate_games: Vec::new(),
+        isolation_auto_threshold: 500,
+        broadcast_workers: 256,
+        isolated_broadcast_workers: 32,
+        enable_history_replay: false,
+        room_history_size: 50,
+        join_interceptor_timeout: Duration::from_millis(1000),
+        join_interceptor_failure_policy: Default::default(),
+        webhooks: Vec::new(),
+        ice_servers: Vec::new(),
+        closed_room_history_size: 1000,
+        room_idempotency_window: Duration::from_secs(300),
+        join_request_timeout: Duration::from_secs(30),
+        stale_player_timeout: Duration::from_secs(300),
+        cors_origins: "*".to_string(),
+        metrics_cors_origins: "*".to_string(),
+        game_overrides: Default::default(),
+        default_app_quota: Default::default(),
     }
 }
 
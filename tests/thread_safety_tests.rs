@@ -4,11 +4,13 @@
 //! (database, distributed locks, circuit breakers, message coordinator)
 //! never produces partial state, data corruption, or deadlocks.
 
-use signal_fish_server::database::{GameDatabase, InMemoryDatabase};
+use signal_fish_server::database::{
+    GameDatabase, InMemoryDatabase, PlayerNameTakenError, RoomExpiryPolicy,
+};
 use signal_fish_server::distributed::{
     CircuitBreaker, CircuitState, DistributedLock, InMemoryDistributedLock,
 };
-use signal_fish_server::protocol::PlayerInfo;
+use signal_fish_server::protocol::{PlayerInfo, PlayerRole};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::Barrier;
@@ -24,6 +26,7 @@ fn make_player(player_id: Uuid) -> PlayerInfo {
         id: player_id,
         name: format!("Player-{}", &player_id.to_string()[..8]),
         is_authority: false,
+        role: PlayerRole::Member,
         is_ready: false,
         connected_at: chrono::Utc::now(),
         connection_info: None,
@@ -288,6 +291,53 @@ async fn test_concurrent_add_player_respects_capacity() {
     );
 }
 
+/// A3b: Player names stay unique under concurrent joins.
+///
+/// Two joiners race to add the same name in different cases. Exactly one
+/// should be seated; the other fails with `PlayerNameTakenError`.
+#[tokio::test]
+async fn test_concurrent_add_player_rejects_duplicate_names() {
+    let db = Arc::new(InMemoryDatabase::new());
+    let room = create_room(&db, "name_game", "NAME01", 8).await;
+    let room_id = room.id;
+
+    let barrier = Arc::new(Barrier::new(2));
+    let mut handles = Vec::new();
+    for name in ["Racer", "RACER"] {
+        let db = Arc::clone(&db);
+        let barrier = Arc::clone(&barrier);
+        handles.push(tokio::spawn(async move {
+            let mut player = make_player(Uuid::new_v4());
+            player.name = name.to_string();
+            barrier.wait().await;
+            db.add_player_to_room(&room_id, player).await
+        }));
+    }
+
+    let mut successes = 0usize;
+    let mut name_taken = 0usize;
+    for handle in handles {
+        match handle.await.expect("task should not panic") {
+            Ok(true) => successes += 1,
+            Err(e) if e.downcast_ref::<PlayerNameTakenError>().is_some() => name_taken += 1,
+            other => panic!("unexpected add result: {other:?}"),
+        }
+    }
+    assert_eq!((successes, name_taken), (1, 1));
+
+    let final_room = db
+        .get_room_by_id(&room_id)
+        .await
+        .unwrap()
+        .expect("room should exist");
+    let racers = final_room
+        .players
+        .values()
+        .filter(|p| p.name.eq_ignore_ascii_case("racer"))
+        .count();
+    assert_eq!(racers, 1, "Only one player should hold the name");
+}
+
 /// A4: Only one player gets authority atomically.
 ///
 /// Create a room, release authority from creator.
@@ -393,9 +443,12 @@ async fn test_cleanup_rooms_atomic_with_creation() {
         let barrier = Arc::clone(&barrier);
         handles.push(tokio::spawn(async move {
             barrier.wait().await;
-            db.cleanup_expired_rooms(chrono::Duration::zero(), chrono::Duration::hours(1))
-                .await
-                .expect("cleanup should not error");
+            db.cleanup_expired_rooms(&RoomExpiryPolicy::uniform(
+                chrono::Duration::zero(),
+                chrono::Duration::hours(1),
+            ))
+            .await
+            .expect("cleanup should not error");
         }));
     }
 